@@ -7,14 +7,14 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion, Benchmark
 use chrono::Utc;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use llm_analytics_hub::analytics::aggregation_engine::AggregationEngine;
 use llm_analytics_hub::analytics::{
-    AggregationEngine,
     PredictionEngine,
     AnomalyDetector,
     CorrelationEngine,
     AnalyticsConfig,
 };
-use llm_analytics_hub::database::Database;
+use llm_analytics_hub::database::MemoryBackend;
 use llm_analytics_hub::models::metrics::TimeWindow;
 use llm_analytics_hub::schemas::events::AnalyticsEvent;
 use uuid::Uuid;
@@ -118,15 +118,10 @@ impl BenchTarget for MetricsAggregationBenchAdapter {
     }
 
     fn run_benchmark(&self) -> BenchmarkResult {
-        // Create a mock database (this is a simplified adapter)
-        // In real scenario, you'd use actual DB connection
+        // Flushed windows go to the in-memory backend, so only the
+        // aggregation itself is measured
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let engine = rt.block_on(async {
-            // Note: This requires Database::new() to be available
-            // For this adapter, we'll measure the computational part only
-            let db = Arc::new(Database::new().await.expect("Failed to create database"));
-            AggregationEngine::new(db)
-        });
+        let engine = AggregationEngine::new(Arc::new(MemoryBackend::new()));
 
         let mut timings = Vec::new();
         let mut successes = 0;
@@ -181,10 +176,7 @@ impl BenchTarget for TimeseriesRollupBenchAdapter {
 
     fn run_benchmark(&self) -> BenchmarkResult {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let db = rt.block_on(async {
-            Arc::new(Database::new().await.expect("Failed to create database"))
-        });
-        let engine = AggregationEngine::new(db);
+        let engine = AggregationEngine::new(Arc::new(MemoryBackend::new()));
 
         let mut timings = Vec::new();
         let mut successes = 0;
@@ -475,10 +467,7 @@ impl BenchTarget for QueryLatencyBenchAdapter {
 
     fn run_benchmark(&self) -> BenchmarkResult {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let db = rt.block_on(async {
-            Arc::new(Database::new().await.expect("Failed to create database"))
-        });
-        let engine = AggregationEngine::new(db);
+        let engine = AggregationEngine::new(Arc::new(MemoryBackend::new()));
 
         let mut timings = Vec::new();
         let mut successes = 0;
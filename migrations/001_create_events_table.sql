@@ -0,0 +1,22 @@
+-- Migration: create_events_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS events (
+    event_id UUID PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    source_module JSONB NOT NULL,
+    event_type JSONB NOT NULL,
+    correlation_id UUID,
+    parent_event_id UUID,
+    schema_version TEXT NOT NULL,
+    severity JSONB NOT NULL,
+    environment TEXT NOT NULL,
+    tags JSONB NOT NULL DEFAULT '{}',
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT create_hypertable('events', 'timestamp', if_not_exists => TRUE);
+
+-- +migrate down
+DROP TABLE IF EXISTS events CASCADE;
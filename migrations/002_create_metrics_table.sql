@@ -0,0 +1,26 @@
+-- Migration: create_metrics_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS aggregated_metrics (
+    id BIGSERIAL,
+    metric_name TEXT NOT NULL,
+    time_window TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    tags JSONB NOT NULL DEFAULT '{}',
+    avg DOUBLE PRECISION NOT NULL,
+    min DOUBLE PRECISION NOT NULL,
+    max DOUBLE PRECISION NOT NULL,
+    p50 DOUBLE PRECISION NOT NULL,
+    p95 DOUBLE PRECISION NOT NULL,
+    p99 DOUBLE PRECISION NOT NULL,
+    stddev DOUBLE PRECISION,
+    count BIGINT NOT NULL,
+    sum DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (metric_name, time_window, window_start, tags)
+);
+
+SELECT create_hypertable('aggregated_metrics', 'window_start', if_not_exists => TRUE);
+
+-- +migrate down
+DROP TABLE IF EXISTS aggregated_metrics CASCADE;
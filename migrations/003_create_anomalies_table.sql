@@ -0,0 +1,20 @@
+-- Migration: create_anomalies_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS anomalies (
+    anomaly_id UUID PRIMARY KEY,
+    detected_at TIMESTAMPTZ NOT NULL,
+    metric_name TEXT NOT NULL,
+    anomaly_type TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    expected_value DOUBLE PRECISION,
+    confidence_score DOUBLE PRECISION NOT NULL,
+    context JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT create_hypertable('anomalies', 'detected_at', if_not_exists => TRUE);
+
+-- +migrate down
+DROP TABLE IF EXISTS anomalies CASCADE;
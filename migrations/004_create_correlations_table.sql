@@ -0,0 +1,15 @@
+-- Migration: create_correlations_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS correlations (
+    correlation_id UUID PRIMARY KEY,
+    correlation_type TEXT NOT NULL,
+    source_event_id UUID NOT NULL,
+    target_event_id UUID NOT NULL,
+    strength DOUBLE PRECISION NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS correlations CASCADE;
@@ -0,0 +1,28 @@
+-- Migration: create_indexes
+
+-- +migrate up
+CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events (timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_events_correlation_id ON events (correlation_id) WHERE correlation_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_events_source_module ON events ((source_module->>'type'));
+CREATE INDEX IF NOT EXISTS idx_events_tags ON events USING GIN (tags);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_metric_window ON aggregated_metrics (metric_name, time_window, window_start DESC);
+CREATE INDEX IF NOT EXISTS idx_metrics_tags ON aggregated_metrics USING GIN (tags);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at ON anomalies (detected_at DESC);
+CREATE INDEX IF NOT EXISTS idx_anomalies_metric_name ON anomalies (metric_name);
+
+CREATE INDEX IF NOT EXISTS idx_correlations_source ON correlations (source_event_id);
+CREATE INDEX IF NOT EXISTS idx_correlations_target ON correlations (target_event_id);
+
+-- +migrate down
+DROP INDEX IF EXISTS idx_correlations_target;
+DROP INDEX IF EXISTS idx_correlations_source;
+DROP INDEX IF EXISTS idx_anomalies_metric_name;
+DROP INDEX IF EXISTS idx_anomalies_detected_at;
+DROP INDEX IF EXISTS idx_metrics_tags;
+DROP INDEX IF EXISTS idx_metrics_metric_window;
+DROP INDEX IF EXISTS idx_events_tags;
+DROP INDEX IF EXISTS idx_events_source_module;
+DROP INDEX IF EXISTS idx_events_correlation_id;
+DROP INDEX IF EXISTS idx_events_timestamp;
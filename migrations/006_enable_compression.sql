@@ -0,0 +1,25 @@
+-- Migration: enable_compression
+
+-- +migrate up
+ALTER TABLE events SET (
+    timescaledb.compress,
+    timescaledb.compress_segmentby = 'source_module, event_type',
+    timescaledb.compress_orderby = 'timestamp DESC'
+);
+
+ALTER TABLE aggregated_metrics SET (
+    timescaledb.compress,
+    timescaledb.compress_segmentby = 'metric_name, time_window',
+    timescaledb.compress_orderby = 'window_start DESC'
+);
+
+SELECT add_compression_policy('events', INTERVAL '7 days', if_not_exists => TRUE);
+SELECT add_compression_policy('aggregated_metrics', INTERVAL '30 days', if_not_exists => TRUE);
+
+-- +migrate down
+SELECT remove_compression_policy('aggregated_metrics', if_exists => TRUE);
+SELECT remove_compression_policy('events', if_exists => TRUE);
+
+-- Fails if chunks are still compressed; decompress them first
+ALTER TABLE aggregated_metrics SET (timescaledb.compress = false);
+ALTER TABLE events SET (timescaledb.compress = false);
@@ -0,0 +1,11 @@
+-- Migration: retention_policies
+
+-- +migrate up
+SELECT add_retention_policy('events', INTERVAL '30 days', if_not_exists => TRUE);
+SELECT add_retention_policy('aggregated_metrics', INTERVAL '365 days', if_not_exists => TRUE);
+SELECT add_retention_policy('anomalies', INTERVAL '90 days', if_not_exists => TRUE);
+
+-- +migrate down
+SELECT remove_retention_policy('anomalies', if_exists => TRUE);
+SELECT remove_retention_policy('aggregated_metrics', if_exists => TRUE);
+SELECT remove_retention_policy('events', if_exists => TRUE);
@@ -0,0 +1,21 @@
+-- Migration: partition_events_by_environment
+
+-- +migrate up
+-- Space partitioning can only be added while the hypertable is empty. Deployments
+-- that already hold events keep a single partition per chunk and need the manual
+-- migration in docs/DEPLOYMENT_GUIDE.md ("Partitioning existing events by environment").
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM events LIMIT 1) THEN
+        PERFORM add_dimension('events', 'environment', number_partitions => 4, if_not_exists => TRUE);
+    ELSE
+        RAISE WARNING 'events already holds data, environment partitioning was not added'
+            USING HINT = 'See "Partitioning existing events by environment" in docs/DEPLOYMENT_GUIDE.md';
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_events_environment_timestamp ON events (environment, timestamp DESC);
+
+-- +migrate down
+-- TimescaleDB cannot remove a space dimension; only the index is dropped
+DROP INDEX IF EXISTS idx_events_environment_timestamp;
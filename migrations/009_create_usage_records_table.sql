@@ -0,0 +1,23 @@
+-- Migration: create_usage_records_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS usage_records (
+    tenant_id TEXT NOT NULL,
+    api_key_id TEXT NOT NULL DEFAULT '',
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    api_calls BIGINT NOT NULL DEFAULT 0,
+    queries BIGINT NOT NULL DEFAULT 0,
+    query_compute_ms BIGINT NOT NULL DEFAULT 0,
+    storage_bytes_latest BIGINT NOT NULL DEFAULT 0,
+    storage_bytes_peak BIGINT NOT NULL DEFAULT 0,
+    alert_deliveries BIGINT NOT NULL DEFAULT 0,
+    breakdown JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, api_key_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_records_period ON usage_records (period_start, tenant_id);
+
+-- +migrate down
+DROP TABLE IF EXISTS usage_records;
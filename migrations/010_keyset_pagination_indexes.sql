@@ -0,0 +1,9 @@
+-- Migration: keyset_pagination_indexes
+
+-- +migrate up
+CREATE INDEX IF NOT EXISTS idx_events_timestamp_event_id ON events (timestamp DESC, event_id DESC);
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at_id ON anomalies (detected_at DESC, anomaly_id DESC);
+
+-- +migrate down
+DROP INDEX IF EXISTS idx_anomalies_detected_at_id;
+DROP INDEX IF EXISTS idx_events_timestamp_event_id;
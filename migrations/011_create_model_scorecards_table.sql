@@ -0,0 +1,18 @@
+-- Migration: create_model_scorecards_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS model_scorecards (
+    model_id TEXT NOT NULL,
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    score DOUBLE PRECISION,
+    grade TEXT,
+    scorecard JSONB NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (model_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_model_scorecards_period ON model_scorecards (period_start DESC);
+
+-- +migrate down
+DROP TABLE IF EXISTS model_scorecards;
@@ -0,0 +1,24 @@
+-- Migration: create_archive_manifests_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS archive_manifests (
+    archive_id UUID PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    partition_start TIMESTAMPTZ NOT NULL,
+    partition_end TIMESTAMPTZ NOT NULL,
+    object_key TEXT NOT NULL,
+    object_uri TEXT NOT NULL,
+    format TEXT NOT NULL,
+    compression TEXT NOT NULL,
+    row_count BIGINT NOT NULL,
+    uncompressed_bytes BIGINT NOT NULL,
+    compressed_bytes BIGINT NOT NULL,
+    sha256 TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    verified_at TIMESTAMPTZ,
+    restored_at TIMESTAMPTZ,
+    UNIQUE (table_name, partition_start)
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS archive_manifests;
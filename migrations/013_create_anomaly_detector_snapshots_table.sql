@@ -0,0 +1,15 @@
+-- Migration: create_anomaly_detector_snapshots_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS anomaly_detector_snapshots (
+    snapshot_id BIGSERIAL PRIMARY KEY,
+    format_version INTEGER NOT NULL,
+    taken_at TIMESTAMPTZ NOT NULL,
+    snapshot JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomaly_detector_snapshots_taken_at
+    ON anomaly_detector_snapshots (taken_at DESC);
+
+-- +migrate down
+DROP TABLE IF EXISTS anomaly_detector_snapshots;
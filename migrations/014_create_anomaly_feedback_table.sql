@@ -0,0 +1,21 @@
+-- Migration: create_anomaly_feedback_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS anomaly_feedback (
+    feedback_id UUID PRIMARY KEY,
+    anomaly_id UUID,
+    metric_name TEXT NOT NULL,
+    verdict TEXT NOT NULL,
+    operator TEXT,
+    note TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One verdict per anomaly; a later verdict replaces the earlier one
+CREATE UNIQUE INDEX IF NOT EXISTS idx_anomaly_feedback_anomaly_id
+    ON anomaly_feedback (anomaly_id) WHERE anomaly_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_anomaly_feedback_metric_name
+    ON anomaly_feedback (metric_name);
+
+-- +migrate down
+DROP TABLE IF EXISTS anomaly_feedback;
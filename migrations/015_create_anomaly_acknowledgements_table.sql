@@ -0,0 +1,12 @@
+-- Migration: create_anomaly_acknowledgements_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS anomaly_acknowledgements (
+    anomaly_id UUID PRIMARY KEY,
+    acknowledged_by TEXT,
+    note TEXT,
+    acknowledged_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS anomaly_acknowledgements;
@@ -0,0 +1,18 @@
+-- Migration: create_alert_silences_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS alert_silences (
+    silence_id UUID PRIMARY KEY,
+    alert_type TEXT,
+    tags JSONB NOT NULL DEFAULT '{}',
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_by TEXT,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_silences_ends_at ON alert_silences (ends_at);
+
+-- +migrate down
+DROP TABLE IF EXISTS alert_silences;
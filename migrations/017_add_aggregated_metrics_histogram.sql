@@ -0,0 +1,8 @@
+-- Migration: add_aggregated_metrics_histogram
+
+-- +migrate up
+-- Bucketed distribution per window; NULL where histograms are disabled
+ALTER TABLE aggregated_metrics ADD COLUMN IF NOT EXISTS histogram JSONB;
+
+-- +migrate down
+ALTER TABLE aggregated_metrics DROP COLUMN IF EXISTS histogram;
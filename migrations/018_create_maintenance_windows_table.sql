@@ -0,0 +1,19 @@
+-- Migration: create_maintenance_windows_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    window_id UUID PRIMARY KEY,
+    environment TEXT NOT NULL,
+    service TEXT,
+    action TEXT NOT NULL DEFAULT 'suppress',
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_by TEXT,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_ends_at ON maintenance_windows (ends_at);
+
+-- +migrate down
+DROP TABLE IF EXISTS maintenance_windows;
@@ -0,0 +1,20 @@
+-- Migration: create_incidents_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS incidents (
+    incident_id UUID PRIMARY KEY,
+    title TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    severity TEXT NOT NULL,
+    environment TEXT NOT NULL,
+    opened_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    resolved_at TIMESTAMPTZ,
+    -- Full incident including its timeline
+    incident JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_incidents_status ON incidents (status, updated_at DESC);
+
+-- +migrate down
+DROP TABLE IF EXISTS incidents;
@@ -0,0 +1,13 @@
+-- Migration: create_adapter_sync_checkpoints_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS adapter_sync_checkpoints (
+    source TEXT PRIMARY KEY,
+    -- Newest upstream last_updated seen by the last complete sync
+    updated_since TIMESTAMPTZ NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL,
+    items_synced BIGINT NOT NULL DEFAULT 0
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS adapter_sync_checkpoints;
@@ -0,0 +1,14 @@
+-- Migration: create_registry_mirror_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS registry_mirror (
+    -- models, providers, or pipelines
+    kind TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL,
+    record JSONB NOT NULL,
+    PRIMARY KEY (kind, entity_id)
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS registry_mirror;
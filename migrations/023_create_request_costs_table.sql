@@ -0,0 +1,19 @@
+-- Migration: create_request_costs_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS request_costs (
+    request_id TEXT PRIMARY KEY,
+    model_id TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    cost_usd DOUBLE PRECISION NOT NULL,
+    tags JSONB NOT NULL DEFAULT '{}',
+    -- Full record including token usage and trace context
+    record JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_costs_started ON request_costs (started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_request_costs_cost ON request_costs (cost_usd DESC);
+
+-- +migrate down
+DROP TABLE IF EXISTS request_costs;
@@ -0,0 +1,25 @@
+-- Migration: create_alert_rules_tables
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS alert_rules (
+    rule_id TEXT PRIMARY KEY,
+    version BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    -- Full rule including its expression, window, and severity
+    rule JSONB NOT NULL
+);
+
+-- Every version of every rule, kept after the rule is deleted
+CREATE TABLE IF NOT EXISTS alert_rule_revisions (
+    rule_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    change TEXT NOT NULL,
+    changed_by TEXT,
+    changed_at TIMESTAMPTZ NOT NULL,
+    rule JSONB NOT NULL,
+    PRIMARY KEY (rule_id, version)
+);
+
+-- +migrate down
+DROP TABLE IF EXISTS alert_rule_revisions;
+DROP TABLE IF EXISTS alert_rules;
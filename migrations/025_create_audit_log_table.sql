@@ -0,0 +1,22 @@
+-- Migration: create_audit_log_table
+
+-- +migrate up
+CREATE TABLE IF NOT EXISTS audit_log (
+    event_id UUID PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    environment TEXT NOT NULL,
+    -- Full record including changes and client details
+    record JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_occurred_at ON audit_log (occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log (actor, occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_resource
+    ON audit_log (resource_type, resource_id, occurred_at DESC);
+
+-- +migrate down
+DROP TABLE IF EXISTS audit_log;
@@ -0,0 +1,68 @@
+-- Migration: create_sql_reader_role
+
+-- +migrate up
+-- The SQL endpoint runs statements as this role. It can read only the
+-- analytics tables, and row-level security limits it to the tenant the
+-- executor names in analytics.sql_tenant ('*' for callers that may read
+-- every tenant). Tables that are not partitioned by tenant are only visible
+-- to those callers.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'analytics_sql_reader') THEN
+        CREATE ROLE analytics_sql_reader NOLOGIN;
+    END IF;
+END
+$$;
+
+GRANT analytics_sql_reader TO CURRENT_USER;
+GRANT USAGE ON SCHEMA public TO analytics_sql_reader;
+GRANT SELECT ON events, aggregated_metrics, usage_records, request_costs,
+    anomalies, correlations, model_scorecards TO analytics_sql_reader;
+
+ALTER TABLE events ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_tenant ON events FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) IN ('*', tags->>'tenant_id'));
+
+ALTER TABLE aggregated_metrics ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_tenant ON aggregated_metrics FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) IN ('*', tags->>'tenant_id'));
+
+ALTER TABLE request_costs ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_tenant ON request_costs FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) IN ('*', tags->>'tenant_id'));
+
+ALTER TABLE usage_records ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_tenant ON usage_records FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) IN ('*', tenant_id));
+
+ALTER TABLE anomalies ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_all_tenants ON anomalies FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) = '*');
+
+ALTER TABLE correlations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_all_tenants ON correlations FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) = '*');
+
+ALTER TABLE model_scorecards ENABLE ROW LEVEL SECURITY;
+CREATE POLICY sql_reader_all_tenants ON model_scorecards FOR SELECT TO analytics_sql_reader
+    USING (current_setting('analytics.sql_tenant', true) = '*');
+
+-- +migrate down
+DROP POLICY IF EXISTS sql_reader_all_tenants ON model_scorecards;
+DROP POLICY IF EXISTS sql_reader_all_tenants ON correlations;
+DROP POLICY IF EXISTS sql_reader_all_tenants ON anomalies;
+DROP POLICY IF EXISTS sql_reader_tenant ON usage_records;
+DROP POLICY IF EXISTS sql_reader_tenant ON request_costs;
+DROP POLICY IF EXISTS sql_reader_tenant ON aggregated_metrics;
+DROP POLICY IF EXISTS sql_reader_tenant ON events;
+ALTER TABLE model_scorecards DISABLE ROW LEVEL SECURITY;
+ALTER TABLE correlations DISABLE ROW LEVEL SECURITY;
+ALTER TABLE anomalies DISABLE ROW LEVEL SECURITY;
+ALTER TABLE usage_records DISABLE ROW LEVEL SECURITY;
+ALTER TABLE request_costs DISABLE ROW LEVEL SECURITY;
+ALTER TABLE aggregated_metrics DISABLE ROW LEVEL SECURITY;
+ALTER TABLE events DISABLE ROW LEVEL SECURITY;
+REVOKE ALL ON events, aggregated_metrics, usage_records, request_costs,
+    anomalies, correlations, model_scorecards FROM analytics_sql_reader;
+REVOKE USAGE ON SCHEMA public FROM analytics_sql_reader;
+DROP ROLE IF EXISTS analytics_sql_reader;
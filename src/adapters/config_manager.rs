@@ -7,6 +7,8 @@
 //! purposes without modifying any upstream logic.
 
 use super::{AdapterHealth, EcosystemAdapter};
+use crate::models::histogram::HistogramLayout;
+use crate::resilience::{Fallback, ResilienceConfig, ResilienceGuard};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -23,6 +25,8 @@ pub struct ConfigManagerConfig {
     pub api_key: Option<String>,
     pub timeout_secs: u64,
     pub cache_ttl_secs: u64,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
 }
 
 impl ConfigManagerConfig {
@@ -39,6 +43,7 @@ impl ConfigManagerConfig {
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()
                 .unwrap_or(300),
+            resilience: ResilienceConfig::from_env("CONFIG_MANAGER"),
         })
     }
 }
@@ -54,6 +59,8 @@ pub struct AnalyticsParameters {
     pub forecasting: ForecastingConfig,
     pub alerting: AlertingConfig,
     pub sampling: SamplingConfig,
+    #[serde(default)]
+    pub metric_filters: MetricFilterConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +70,17 @@ pub struct AggregationConfig {
     pub default_percentiles: Vec<f64>,
     pub max_cardinality: u64,
     pub enable_histograms: bool,
+    /// Bucket layout for histograms; the default layout when unset
+    #[serde(default)]
+    pub histogram_layout: Option<HistogramLayout>,
+}
+
+impl AggregationConfig {
+    /// Histogram bucket layout to aggregate with, if histograms are enabled
+    pub fn histograms(&self) -> Option<HistogramLayout> {
+        self.enable_histograms
+            .then(|| self.histogram_layout.clone().unwrap_or_default())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,6 +170,29 @@ pub struct SamplingConfig {
     pub preserve_errors: bool,
 }
 
+/// Allowlist and denylist rules deciding which metric series are stored
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricFilterConfig {
+    /// When non-empty, only series matching one of these rules are kept
+    #[serde(default)]
+    pub allow: Vec<MetricRule>,
+    /// Series matching any of these rules are dropped, even when allowed
+    #[serde(default)]
+    pub deny: Vec<MetricRule>,
+}
+
+/// Glob patterns over a metric name and its tags; `*` matches any run of
+/// characters and `?` a single one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricRule {
+    /// Pattern over the metric name; matches every name when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Patterns over tag values; every listed tag must be present and match
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
 /// Data retention settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetentionSettings {
@@ -270,162 +311,191 @@ pub struct SecurityConfig {
 pub struct ConfigManagerAdapter {
     config: ConfigManagerConfig,
     connected: AtomicBool,
+    resilience: ResilienceGuard,
+    // Last-known-good configuration served while Config-Manager is unavailable
+    parameters_fallback: Fallback<AnalyticsParameters>,
+    retention_fallback: Fallback<RetentionSettings>,
+    flags_fallback: Fallback<FeatureFlags>,
 }
 
 impl ConfigManagerAdapter {
     pub fn new(config: ConfigManagerConfig) -> Self {
         Self {
+            resilience: ResilienceGuard::new("config_manager", &config.resilience),
             config,
             connected: AtomicBool::new(false),
+            parameters_fallback: Fallback::new().with_last_known_good(),
+            retention_fallback: Fallback::new().with_last_known_good(),
+            flags_fallback: Fallback::new().with_last_known_good(),
         }
     }
 
-    /// Fetch analytics parameters
+    /// Fetch analytics parameters, serving the last-known-good value if the upstream fails
     #[instrument(skip(self))]
     pub async fn fetch_analytics_parameters(&self) -> Result<AnalyticsParameters> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Config-Manager adapter not connected");
         }
 
-        debug!("Fetching analytics parameters from Config-Manager");
-
-        // Placeholder implementation with sensible defaults
-        Ok(AnalyticsParameters {
-            config_id: uuid::Uuid::new_v4().to_string(),
-            version: "1.0.0".to_string(),
-            created_at: Utc::now(),
-            aggregation: AggregationConfig {
-                default_window_minutes: 5,
-                rollup_windows: vec![
-                    RollupWindow {
-                        name: "1min".to_string(),
-                        duration_minutes: 1,
-                        aggregations: vec!["avg".to_string(), "count".to_string()],
+        let fetch = self
+            .resilience
+            .call(|| async {
+                debug!("Fetching analytics parameters from Config-Manager");
+
+                // Placeholder implementation with sensible defaults
+                Ok(AnalyticsParameters {
+                    config_id: uuid::Uuid::new_v4().to_string(),
+                    version: "1.0.0".to_string(),
+                    created_at: Utc::now(),
+                    aggregation: AggregationConfig {
+                        default_window_minutes: 5,
+                        rollup_windows: vec![
+                            RollupWindow {
+                                name: "1min".to_string(),
+                                duration_minutes: 1,
+                                aggregations: vec!["avg".to_string(), "count".to_string()],
+                            },
+                            RollupWindow {
+                                name: "5min".to_string(),
+                                duration_minutes: 5,
+                                aggregations: vec!["avg".to_string(), "min".to_string(), "max".to_string(), "count".to_string()],
+                            },
+                            RollupWindow {
+                                name: "1hour".to_string(),
+                                duration_minutes: 60,
+                                aggregations: vec!["avg".to_string(), "min".to_string(), "max".to_string(), "p50".to_string(), "p95".to_string(), "p99".to_string(), "count".to_string()],
+                            },
+                        ],
+                        default_percentiles: vec![0.5, 0.9, 0.95, 0.99],
+                        max_cardinality: 10000,
+                        enable_histograms: true,
+                        histogram_layout: None,
                     },
-                    RollupWindow {
-                        name: "5min".to_string(),
-                        duration_minutes: 5,
-                        aggregations: vec!["avg".to_string(), "min".to_string(), "max".to_string(), "count".to_string()],
+                    anomaly_detection: AnomalyDetectionConfig {
+                        enabled: true,
+                        algorithm: AnomalyAlgorithm::ZScore,
+                        sensitivity: 3.0,
+                        min_data_points: 30,
+                        evaluation_window_minutes: 15,
+                        cooldown_minutes: 60,
                     },
-                    RollupWindow {
-                        name: "1hour".to_string(),
-                        duration_minutes: 60,
-                        aggregations: vec!["avg".to_string(), "min".to_string(), "max".to_string(), "p50".to_string(), "p95".to_string(), "p99".to_string(), "count".to_string()],
+                    forecasting: ForecastingConfig {
+                        enabled: true,
+                        model: ForecastModel::ExponentialSmoothing,
+                        horizon_hours: 24,
+                        training_window_days: 7,
+                        update_frequency_hours: 1,
+                        confidence_level: 0.95,
                     },
-                ],
-                default_percentiles: vec![0.5, 0.9, 0.95, 0.99],
-                max_cardinality: 10000,
-                enable_histograms: true,
-            },
-            anomaly_detection: AnomalyDetectionConfig {
-                enabled: true,
-                algorithm: AnomalyAlgorithm::ZScore,
-                sensitivity: 3.0,
-                min_data_points: 30,
-                evaluation_window_minutes: 15,
-                cooldown_minutes: 60,
-            },
-            forecasting: ForecastingConfig {
-                enabled: true,
-                model: ForecastModel::ExponentialSmoothing,
-                horizon_hours: 24,
-                training_window_days: 7,
-                update_frequency_hours: 1,
-                confidence_level: 0.95,
-            },
-            alerting: AlertingConfig {
-                enabled: true,
-                default_severity: AlertSeverity::Warning,
-                channels: Vec::new(),
-                rate_limit_per_hour: 100,
-                grouping_window_minutes: 5,
-            },
-            sampling: SamplingConfig {
-                enabled: false,
-                default_rate: 1.0,
-                high_volume_rate: 0.1,
-                high_volume_threshold_rps: 10000,
-                preserve_errors: true,
-            },
-        })
+                    alerting: AlertingConfig {
+                        enabled: true,
+                        default_severity: AlertSeverity::Warning,
+                        channels: Vec::new(),
+                        rate_limit_per_hour: 100,
+                        grouping_window_minutes: 5,
+                    },
+                    sampling: SamplingConfig {
+                        enabled: false,
+                        default_rate: 1.0,
+                        high_volume_rate: 0.1,
+                        high_volume_threshold_rps: 10000,
+                        preserve_errors: true,
+                    },
+                    metric_filters: MetricFilterConfig::default(),
+                })
+            });
+
+        self.parameters_fallback.execute(|| fetch).await
     }
 
-    /// Fetch retention settings
+    /// Fetch retention settings, serving the last-known-good value if the upstream fails
     #[instrument(skip(self))]
     pub async fn fetch_retention_settings(&self) -> Result<RetentionSettings> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Config-Manager adapter not connected");
         }
 
-        debug!("Fetching retention settings from Config-Manager");
-
-        // Placeholder implementation with sensible defaults
-        Ok(RetentionSettings {
-            config_id: uuid::Uuid::new_v4().to_string(),
-            version: "1.0.0".to_string(),
-            created_at: Utc::now(),
-            policies: vec![
-                RetentionPolicy {
-                    policy_id: "raw-events".to_string(),
-                    name: "Raw Events".to_string(),
-                    data_type: DataType::RawEvents,
-                    retention_days: 7,
-                    tier: StorageTier::Hot,
-                    compress_after_days: Some(1),
-                    archive_after_days: Some(7),
-                },
-                RetentionPolicy {
-                    policy_id: "aggregated-metrics".to_string(),
-                    name: "Aggregated Metrics".to_string(),
-                    data_type: DataType::AggregatedMetrics,
-                    retention_days: 90,
-                    tier: StorageTier::Warm,
-                    compress_after_days: Some(7),
-                    archive_after_days: Some(30),
-                },
-                RetentionPolicy {
-                    policy_id: "traces".to_string(),
-                    name: "Traces".to_string(),
-                    data_type: DataType::Traces,
-                    retention_days: 14,
-                    tier: StorageTier::Hot,
-                    compress_after_days: Some(3),
-                    archive_after_days: Some(14),
-                },
-            ],
-            archival: ArchivalConfig {
-                enabled: false,
-                destination: ArchivalDestination::S3 {
-                    bucket: "analytics-archive".to_string(),
-                    prefix: "data/".to_string(),
-                },
-                compression: CompressionType::Zstd,
-                encryption_enabled: true,
-            },
-            compaction: CompactionConfig {
-                enabled: true,
-                schedule_cron: "0 2 * * *".to_string(),
-                target_file_size_mb: 256,
-                max_concurrent_jobs: 4,
-            },
-        })
+        let fetch = self
+            .resilience
+            .call(|| async {
+                debug!("Fetching retention settings from Config-Manager");
+
+                // Placeholder implementation with sensible defaults
+                Ok(RetentionSettings {
+                    config_id: uuid::Uuid::new_v4().to_string(),
+                    version: "1.0.0".to_string(),
+                    created_at: Utc::now(),
+                    policies: vec![
+                        RetentionPolicy {
+                            policy_id: "raw-events".to_string(),
+                            name: "Raw Events".to_string(),
+                            data_type: DataType::RawEvents,
+                            retention_days: 7,
+                            tier: StorageTier::Hot,
+                            compress_after_days: Some(1),
+                            archive_after_days: Some(7),
+                        },
+                        RetentionPolicy {
+                            policy_id: "aggregated-metrics".to_string(),
+                            name: "Aggregated Metrics".to_string(),
+                            data_type: DataType::AggregatedMetrics,
+                            retention_days: 90,
+                            tier: StorageTier::Warm,
+                            compress_after_days: Some(7),
+                            archive_after_days: Some(30),
+                        },
+                        RetentionPolicy {
+                            policy_id: "traces".to_string(),
+                            name: "Traces".to_string(),
+                            data_type: DataType::Traces,
+                            retention_days: 14,
+                            tier: StorageTier::Hot,
+                            compress_after_days: Some(3),
+                            archive_after_days: Some(14),
+                        },
+                    ],
+                    archival: ArchivalConfig {
+                        enabled: false,
+                        destination: ArchivalDestination::S3 {
+                            bucket: "analytics-archive".to_string(),
+                            prefix: "data/".to_string(),
+                        },
+                        compression: CompressionType::Zstd,
+                        encryption_enabled: true,
+                    },
+                    compaction: CompactionConfig {
+                        enabled: true,
+                        schedule_cron: "0 2 * * *".to_string(),
+                        target_file_size_mb: 256,
+                        max_concurrent_jobs: 4,
+                    },
+                })
+            });
+
+        self.retention_fallback.execute(|| fetch).await
     }
 
-    /// Fetch feature flags
+    /// Fetch feature flags, serving the last-known-good value if the upstream fails
     #[instrument(skip(self))]
     pub async fn fetch_feature_flags(&self) -> Result<FeatureFlags> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Config-Manager adapter not connected");
         }
 
-        debug!("Fetching feature flags from Config-Manager");
+        let fetch = self
+            .resilience
+            .call(|| async {
+                debug!("Fetching feature flags from Config-Manager");
 
-        // Placeholder implementation
-        Ok(FeatureFlags {
-            config_id: uuid::Uuid::new_v4().to_string(),
-            flags: HashMap::new(),
-            last_updated: Utc::now(),
-        })
+                // Placeholder implementation
+                Ok(FeatureFlags {
+                    config_id: uuid::Uuid::new_v4().to_string(),
+                    flags: HashMap::new(),
+                    last_updated: Utc::now(),
+                })
+            });
+
+        self.flags_fallback.execute(|| fetch).await
     }
 
     /// Fetch environment-specific configuration
@@ -435,25 +505,29 @@ impl ConfigManagerAdapter {
             anyhow::bail!("Config-Manager adapter not connected");
         }
 
-        debug!(environment = %environment, "Fetching environment config from Config-Manager");
-
-        // Placeholder implementation
-        Ok(EnvironmentConfig {
-            environment: environment.to_string(),
-            endpoints: HashMap::new(),
-            limits: ResourceLimits {
-                max_concurrent_queries: 100,
-                max_query_timeout_secs: 300,
-                max_result_rows: 100000,
-                max_memory_mb: 4096,
-            },
-            security: SecurityConfig {
-                require_auth: true,
-                allowed_origins: vec!["*".to_string()],
-                rate_limit_rps: 1000,
-                ip_whitelist: None,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(environment = %environment, "Fetching environment config from Config-Manager");
+
+                // Placeholder implementation
+                Ok(EnvironmentConfig {
+                    environment: environment.to_string(),
+                    endpoints: HashMap::new(),
+                    limits: ResourceLimits {
+                        max_concurrent_queries: 100,
+                        max_query_timeout_secs: 300,
+                        max_result_rows: 100000,
+                        max_memory_mb: 4096,
+                    },
+                    security: SecurityConfig {
+                        require_auth: true,
+                        allowed_origins: vec!["*".to_string()],
+                        rate_limit_rps: 1000,
+                        ip_whitelist: None,
+                    },
+                })
+            })
+            .await
     }
 
     /// Get a specific configuration value by key
@@ -466,10 +540,14 @@ impl ConfigManagerAdapter {
             anyhow::bail!("Config-Manager adapter not connected");
         }
 
-        debug!(key = %key, "Fetching config value from Config-Manager");
+        self.resilience
+            .call(|| async {
+                debug!(key = %key, "Fetching config value from Config-Manager");
 
-        // Placeholder implementation
-        Ok(None)
+                // Placeholder implementation
+                Ok(None)
+            })
+            .await
     }
 }
 
@@ -493,7 +571,8 @@ impl EcosystemAdapter for ConfigManagerAdapter {
         }
 
         let latency_ms = start.elapsed().as_millis() as u64;
-        Ok(AdapterHealth::healthy("config_manager", latency_ms))
+        Ok(AdapterHealth::healthy("config_manager", latency_ms)
+            .with_circuit_state(self.resilience.circuit_state().await))
     }
 
     #[instrument(skip(self))]
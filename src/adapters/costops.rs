@@ -6,7 +6,9 @@
 //! This adapter provides read-only access to cost data for analytics
 //! purposes without modifying any upstream logic.
 
+use super::pagination::{fetch_all, IncrementalSync, Page, PageRequest, SyncBatch, SyncConfig, Updated};
 use super::{AdapterHealth, EcosystemAdapter};
+use crate::resilience::{ResilienceConfig, ResilienceGuard};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -14,7 +16,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
+
+/// Checkpoint name of the incremental consumer team sync
+pub const CONSUMER_TEAMS_SYNC_SOURCE: &str = "costops.consumer_teams";
 
 /// Configuration for CostOps adapter
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,11 @@ pub struct CostOpsConfig {
     pub endpoint: String,
     pub api_key: Option<String>,
     pub timeout_secs: u64,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
+    /// Page size and page limit of listings
+    #[serde(default)]
+    pub paging: SyncConfig,
 }
 
 impl CostOpsConfig {
@@ -34,6 +44,8 @@ impl CostOpsConfig {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            resilience: ResilienceConfig::from_env("COSTOPS"),
+            paging: SyncConfig::from_env(),
         })
     }
 }
@@ -67,6 +79,20 @@ pub struct CostConsumer {
     pub percentage: f64,
 }
 
+/// Team a consumer is billed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerTeam {
+    pub consumer_id: String,
+    pub team_id: String,
+    pub last_updated: DateTime<Utc>,
+}
+
+impl Updated for ConsumerTeam {
+    fn last_updated(&self) -> DateTime<Utc> {
+        self.last_updated
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConsumerType {
     User,
@@ -174,11 +200,13 @@ pub enum Granularity {
 pub struct CostOpsAdapter {
     config: CostOpsConfig,
     connected: AtomicBool,
+    resilience: ResilienceGuard,
 }
 
 impl CostOpsAdapter {
     pub fn new(config: CostOpsConfig) -> Self {
         Self {
+            resilience: ResilienceGuard::new("costops", &config.resilience),
             config,
             connected: AtomicBool::new(false),
         }
@@ -191,23 +219,27 @@ impl CostOpsAdapter {
             anyhow::bail!("CostOps adapter not connected");
         }
 
-        debug!("Fetching cost summary from CostOps");
-
-        // Placeholder implementation
-        Ok(CostSummary {
-            summary_id: uuid::Uuid::new_v4().to_string(),
-            period_start: query.start_time.unwrap_or_else(Utc::now),
-            period_end: query.end_time.unwrap_or_else(Utc::now),
-            total_cost_usd: 0.0,
-            breakdown: CostBreakdown {
-                by_provider: HashMap::new(),
-                by_model: HashMap::new(),
-                by_operation: HashMap::new(),
-                by_team: HashMap::new(),
-            },
-            top_consumers: Vec::new(),
-            currency: "USD".to_string(),
-        })
+        self.resilience
+            .call(|| async {
+                debug!("Fetching cost summary from CostOps");
+
+                // Placeholder implementation
+                Ok(CostSummary {
+                    summary_id: uuid::Uuid::new_v4().to_string(),
+                    period_start: query.start_time.unwrap_or_else(Utc::now),
+                    period_end: query.end_time.unwrap_or_else(Utc::now),
+                    total_cost_usd: 0.0,
+                    breakdown: CostBreakdown {
+                        by_provider: HashMap::new(),
+                        by_model: HashMap::new(),
+                        by_operation: HashMap::new(),
+                        by_team: HashMap::new(),
+                    },
+                    top_consumers: Vec::new(),
+                    currency: "USD".to_string(),
+                })
+            })
+            .await
     }
 
     /// Fetch cost projections
@@ -220,10 +252,14 @@ impl CostOpsAdapter {
             anyhow::bail!("CostOps adapter not connected");
         }
 
-        debug!(?period, "Fetching cost projections from CostOps");
+        self.resilience
+            .call(|| async {
+                debug!(?period, "Fetching cost projections from CostOps");
 
-        // Placeholder implementation
-        Ok(Vec::new())
+                // Placeholder implementation
+                Ok(Vec::new())
+            })
+            .await
     }
 
     /// Fetch token accounting baseline
@@ -237,32 +273,36 @@ impl CostOpsAdapter {
             anyhow::bail!("CostOps adapter not connected");
         }
 
-        debug!("Fetching token accounting baseline from CostOps");
-
-        // Placeholder implementation
-        Ok(TokenAccountingBaseline {
-            baseline_id: uuid::Uuid::new_v4().to_string(),
-            created_at: Utc::now(),
-            period: BaselinePeriod { start, end },
-            token_metrics: TokenMetrics {
-                total_tokens: 0,
-                prompt_tokens: 0,
-                completion_tokens: 0,
-                cached_tokens: 0,
-                by_model: HashMap::new(),
-            },
-            cost_per_token: CostPerToken {
-                average_cost_per_1k_tokens: 0.0,
-                prompt_cost_per_1k: 0.0,
-                completion_cost_per_1k: 0.0,
-                by_model: HashMap::new(),
-            },
-            efficiency_metrics: EfficiencyMetrics {
-                cache_hit_rate: 0.0,
-                tokens_per_request_avg: 0.0,
-                cost_per_request_avg: 0.0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!("Fetching token accounting baseline from CostOps");
+
+                // Placeholder implementation
+                Ok(TokenAccountingBaseline {
+                    baseline_id: uuid::Uuid::new_v4().to_string(),
+                    created_at: Utc::now(),
+                    period: BaselinePeriod { start, end },
+                    token_metrics: TokenMetrics {
+                        total_tokens: 0,
+                        prompt_tokens: 0,
+                        completion_tokens: 0,
+                        cached_tokens: 0,
+                        by_model: HashMap::new(),
+                    },
+                    cost_per_token: CostPerToken {
+                        average_cost_per_1k_tokens: 0.0,
+                        prompt_cost_per_1k: 0.0,
+                        completion_cost_per_1k: 0.0,
+                        by_model: HashMap::new(),
+                    },
+                    efficiency_metrics: EfficiencyMetrics {
+                        cache_hit_rate: 0.0,
+                        tokens_per_request_avg: 0.0,
+                        cost_per_request_avg: 0.0,
+                    },
+                })
+            })
+            .await
     }
 
     /// Get current budget status
@@ -272,18 +312,62 @@ impl CostOpsAdapter {
             anyhow::bail!("CostOps adapter not connected");
         }
 
-        debug!(?team_id, "Fetching budget status from CostOps");
-
-        // Placeholder implementation
-        Ok(BudgetStatus {
-            budget_id: uuid::Uuid::new_v4().to_string(),
-            team_id: team_id.map(String::from),
-            period_budget_usd: 0.0,
-            spent_usd: 0.0,
-            remaining_usd: 0.0,
-            utilization_percentage: 0.0,
-            projected_overage: None,
+        self.resilience
+            .call(|| async {
+                debug!(?team_id, "Fetching budget status from CostOps");
+
+                // Placeholder implementation
+                Ok(BudgetStatus {
+                    budget_id: uuid::Uuid::new_v4().to_string(),
+                    team_id: team_id.map(String::from),
+                    period_budget_usd: 0.0,
+                    spent_usd: 0.0,
+                    remaining_usd: 0.0,
+                    utilization_percentage: 0.0,
+                    projected_overage: None,
+                })
+            })
+            .await
+    }
+
+    /// Fetch one page of consumer team assignments
+    #[instrument(skip(self))]
+    pub async fn list_consumer_teams_page(&self, page: PageRequest) -> Result<Page<ConsumerTeam>> {
+        if !self.connected.load(Ordering::Relaxed) {
+            anyhow::bail!("CostOps adapter not connected");
+        }
+
+        self.resilience
+            .call(|| async {
+                debug!(cursor = ?page.cursor, updated_since = ?page.updated_since, "Fetching consumer team mapping from CostOps");
+
+                // Placeholder implementation
+                Ok(Page::last(Vec::new()))
+            })
+            .await
+    }
+
+    /// Fetch the team each consumer (user, application, or pipeline) is billed to
+    #[instrument(skip(self))]
+    pub async fn fetch_consumer_teams(&self) -> Result<HashMap<String, String>> {
+        let paged = fetch_all(self.config.paging.first_page(), self.config.paging.max_pages, |page| {
+            self.list_consumer_teams_page(page)
         })
+        .await?;
+        if !paged.complete {
+            warn!(pages = paged.pages, "Consumer team listing truncated at the page limit");
+        }
+        Ok(paged
+            .items
+            .into_iter()
+            .map(|assignment| (assignment.consumer_id, assignment.team_id))
+            .collect())
+    }
+
+    /// Consumer team assignments changed since the last complete sync
+    pub async fn sync_consumer_teams(&self, sync: &IncrementalSync) -> Result<SyncBatch<ConsumerTeam>> {
+        sync.sync(CONSUMER_TEAMS_SYNC_SOURCE, |page| self.list_consumer_teams_page(page))
+            .await
     }
 }
 
@@ -318,7 +402,8 @@ impl EcosystemAdapter for CostOpsAdapter {
         }
 
         let latency_ms = start.elapsed().as_millis() as u64;
-        Ok(AdapterHealth::healthy("costops", latency_ms))
+        Ok(AdapterHealth::healthy("costops", latency_ms)
+            .with_circuit_state(self.resilience.circuit_state().await))
     }
 
     #[instrument(skip(self))]
@@ -7,6 +7,7 @@
 //! purposes without modifying any upstream logic.
 
 use super::{AdapterHealth, EcosystemAdapter};
+use crate::resilience::{ResilienceConfig, ResilienceGuard};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -22,6 +23,8 @@ pub struct MemoryGraphConfig {
     pub endpoint: String,
     pub api_key: Option<String>,
     pub timeout_secs: u64,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
 }
 
 impl MemoryGraphConfig {
@@ -34,6 +37,7 @@ impl MemoryGraphConfig {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            resilience: ResilienceConfig::from_env("MEMORY_GRAPH"),
         })
     }
 }
@@ -195,11 +199,13 @@ pub struct LineageQuery {
 pub struct MemoryGraphAdapter {
     config: MemoryGraphConfig,
     connected: AtomicBool,
+    resilience: ResilienceGuard,
 }
 
 impl MemoryGraphAdapter {
     pub fn new(config: MemoryGraphConfig) -> Self {
         Self {
+            resilience: ResilienceGuard::new("memory_graph", &config.resilience),
             config,
             connected: AtomicBool::new(false),
         }
@@ -212,23 +218,27 @@ impl MemoryGraphAdapter {
             anyhow::bail!("Memory-Graph adapter not connected");
         }
 
-        debug!(?query.context_id, "Fetching context lineage from Memory-Graph");
-
-        // Placeholder implementation
-        Ok(ContextLineage {
-            lineage_id: uuid::Uuid::new_v4().to_string(),
-            root_context_id: query.context_id.unwrap_or_else(|| "root".to_string()),
-            created_at: Utc::now(),
-            depth: 0,
-            nodes: Vec::new(),
-            edges: Vec::new(),
-            metadata: LineageMetadata {
-                total_tokens: 0,
-                total_interactions: 0,
-                active_branches: 0,
-                compression_ratio: 1.0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(?query.context_id, "Fetching context lineage from Memory-Graph");
+
+                // Placeholder implementation
+                Ok(ContextLineage {
+                    lineage_id: uuid::Uuid::new_v4().to_string(),
+                    root_context_id: query.context_id.clone().unwrap_or_else(|| "root".to_string()),
+                    created_at: Utc::now(),
+                    depth: 0,
+                    nodes: Vec::new(),
+                    edges: Vec::new(),
+                    metadata: LineageMetadata {
+                        total_tokens: 0,
+                        total_interactions: 0,
+                        active_branches: 0,
+                        compression_ratio: 1.0,
+                    },
+                })
+            })
+            .await
     }
 
     /// Fetch interaction graph for a session
@@ -238,25 +248,50 @@ impl MemoryGraphAdapter {
             anyhow::bail!("Memory-Graph adapter not connected");
         }
 
-        debug!(session_id = %session_id, "Fetching interaction graph from Memory-Graph");
-
-        // Placeholder implementation
-        Ok(InteractionGraph {
-            graph_id: uuid::Uuid::new_v4().to_string(),
-            session_id: session_id.to_string(),
-            created_at: Utc::now(),
-            last_updated: Utc::now(),
-            statistics: GraphStatistics {
-                node_count: 0,
-                edge_count: 0,
-                avg_degree: 0.0,
-                clustering_coefficient: 0.0,
-                diameter: 0,
-                density: 0.0,
-            },
-            topics: Vec::new(),
-            entities: Vec::new(),
-        })
+        self.resilience
+            .call(|| async {
+                debug!(session_id = %session_id, "Fetching interaction graph from Memory-Graph");
+
+                // Placeholder implementation
+                Ok(InteractionGraph {
+                    graph_id: uuid::Uuid::new_v4().to_string(),
+                    session_id: session_id.to_string(),
+                    created_at: Utc::now(),
+                    last_updated: Utc::now(),
+                    statistics: GraphStatistics {
+                        node_count: 0,
+                        edge_count: 0,
+                        avg_degree: 0.0,
+                        clustering_coefficient: 0.0,
+                        diameter: 0,
+                        density: 0.0,
+                    },
+                    topics: Vec::new(),
+                    entities: Vec::new(),
+                })
+            })
+            .await
+    }
+
+    /// IDs of sessions with activity in `[start, end)`
+    #[instrument(skip(self))]
+    pub async fn fetch_active_sessions(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        if !self.connected.load(Ordering::Relaxed) {
+            anyhow::bail!("Memory-Graph adapter not connected");
+        }
+
+        self.resilience
+            .call(|| async {
+                debug!(%start, %end, "Fetching active sessions from Memory-Graph");
+
+                // Placeholder implementation
+                Ok(Vec::new())
+            })
+            .await
     }
 
     /// Fetch memory snapshot for a session
@@ -266,23 +301,27 @@ impl MemoryGraphAdapter {
             anyhow::bail!("Memory-Graph adapter not connected");
         }
 
-        debug!(session_id = %session_id, "Fetching memory snapshot from Memory-Graph");
-
-        // Placeholder implementation
-        Ok(MemorySnapshot {
-            snapshot_id: uuid::Uuid::new_v4().to_string(),
-            session_id: session_id.to_string(),
-            created_at: Utc::now(),
-            context_window_tokens: 0,
-            summarized_tokens: 0,
-            active_memories: Vec::new(),
-            retrieval_stats: RetrievalStats {
-                total_retrievals: 0,
-                avg_latency_ms: 0.0,
-                cache_hit_rate: 0.0,
-                relevance_avg: 0.0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(session_id = %session_id, "Fetching memory snapshot from Memory-Graph");
+
+                // Placeholder implementation
+                Ok(MemorySnapshot {
+                    snapshot_id: uuid::Uuid::new_v4().to_string(),
+                    session_id: session_id.to_string(),
+                    created_at: Utc::now(),
+                    context_window_tokens: 0,
+                    summarized_tokens: 0,
+                    active_memories: Vec::new(),
+                    retrieval_stats: RetrievalStats {
+                        total_retrievals: 0,
+                        avg_latency_ms: 0.0,
+                        cache_hit_rate: 0.0,
+                        relevance_avg: 0.0,
+                    },
+                })
+            })
+            .await
     }
 
     /// Get graph statistics for analytics
@@ -296,25 +335,29 @@ impl MemoryGraphAdapter {
             anyhow::bail!("Memory-Graph adapter not connected");
         }
 
-        debug!("Fetching graph analytics from Memory-Graph");
-
-        // Placeholder implementation
-        Ok(GraphAnalytics {
-            period_start: start,
-            period_end: end,
-            total_sessions: 0,
-            total_nodes_created: 0,
-            total_edges_created: 0,
-            avg_session_depth: 0.0,
-            avg_session_tokens: 0.0,
-            top_topics: Vec::new(),
-            memory_efficiency: MemoryEfficiency {
-                avg_compression_ratio: 0.0,
-                cache_hit_rate: 0.0,
-                retrieval_latency_p50_ms: 0.0,
-                retrieval_latency_p99_ms: 0.0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!("Fetching graph analytics from Memory-Graph");
+
+                // Placeholder implementation
+                Ok(GraphAnalytics {
+                    period_start: start,
+                    period_end: end,
+                    total_sessions: 0,
+                    total_nodes_created: 0,
+                    total_edges_created: 0,
+                    avg_session_depth: 0.0,
+                    avg_session_tokens: 0.0,
+                    top_topics: Vec::new(),
+                    memory_efficiency: MemoryEfficiency {
+                        avg_compression_ratio: 0.0,
+                        cache_hit_rate: 0.0,
+                        retrieval_latency_p50_ms: 0.0,
+                        retrieval_latency_p99_ms: 0.0,
+                    },
+                })
+            })
+            .await
     }
 }
 
@@ -359,7 +402,8 @@ impl EcosystemAdapter for MemoryGraphAdapter {
         }
 
         let latency_ms = start.elapsed().as_millis() as u64;
-        Ok(AdapterHealth::healthy("memory_graph", latency_ms))
+        Ok(AdapterHealth::healthy("memory_graph", latency_ms)
+            .with_circuit_state(self.resilience.circuit_state().await))
     }
 
     #[instrument(skip(self))]
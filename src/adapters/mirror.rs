@@ -0,0 +1,578 @@
+//! Registry Mirror
+//!
+//! Joining events against LLM-Registry per request is too slow, so
+//! `RegistryMirror` keeps a local copy of registry models, providers, and
+//! pipelines. A background job syncs it on a schedule: models and pipelines
+//! incrementally through their checkpoints, providers in full, and everything
+//! in full every `full_resync_every` runs so records deleted upstream drop out.
+//! Synced records are persisted to the `registry_mirror` table and reloaded on
+//! startup, so lookups are served locally from the first request.
+//!
+//! Each record kind tracks when it last synced; a kind that has not synced
+//! within `max_staleness_secs` is reported stale but keeps serving lookups.
+
+use super::pagination::{IncrementalSync, SyncBatch};
+use super::registry::{ModelMetadata, PipelineDescriptor, ProviderInfo, RegistryAdapter};
+use crate::database::Database;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Registry record kinds kept in the mirror
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorKind {
+    Models,
+    Providers,
+    Pipelines,
+}
+
+impl MirrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MirrorKind::Models => "models",
+            MirrorKind::Providers => "providers",
+            MirrorKind::Pipelines => "pipelines",
+        }
+    }
+}
+
+/// Registry mirror configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorConfig {
+    /// Seconds between syncs
+    pub interval_secs: u64,
+    /// Age after which a record kind is reported stale
+    pub max_staleness_secs: i64,
+    /// Every Nth sync lists everything and drops records missing upstream
+    pub full_resync_every: u64,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            max_staleness_secs: 1800,
+            full_resync_every: 12,
+        }
+    }
+}
+
+impl MirrorConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            interval_secs: std::env::var("REGISTRY_MIRROR_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+            max_staleness_secs: std::env::var("REGISTRY_MIRROR_MAX_STALENESS_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_staleness_secs),
+            full_resync_every: std::env::var("REGISTRY_MIRROR_FULL_RESYNC_EVERY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.full_resync_every),
+        }
+    }
+}
+
+/// Freshness of one record kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorKindStatus {
+    pub kind: MirrorKind,
+    pub records: usize,
+    pub last_synced: Option<DateTime<Utc>>,
+    pub age_secs: Option<i64>,
+    pub stale: bool,
+    pub last_error: Option<String>,
+}
+
+/// Local copy of one record kind
+struct MirrorTable<T> {
+    records: HashMap<String, T>,
+    last_synced: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl<T: Clone> MirrorTable<T> {
+    fn new() -> Self {
+        Self {
+            records: HashMap::new(),
+            last_synced: None,
+            last_error: None,
+        }
+    }
+
+    fn upsert(&mut self, records: Vec<(String, T)>, synced_at: DateTime<Utc>) {
+        self.records.extend(records);
+        self.last_synced = Some(synced_at);
+        self.last_error = None;
+    }
+
+    fn replace(&mut self, records: Vec<(String, T)>, synced_at: DateTime<Utc>) {
+        self.records = records.into_iter().collect();
+        self.last_synced = Some(synced_at);
+        self.last_error = None;
+    }
+
+    fn status(
+        &self,
+        kind: MirrorKind,
+        max_staleness: Duration,
+        now: DateTime<Utc>,
+    ) -> MirrorKindStatus {
+        let age = self.last_synced.map(|synced| now - synced);
+        MirrorKindStatus {
+            kind,
+            records: self.records.len(),
+            last_synced: self.last_synced,
+            age_secs: age.map(|age| age.num_seconds()),
+            stale: age.map_or(true, |age| age > max_staleness),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// How one kind was synced
+enum Synced<T> {
+    /// Records changed since the checkpoint
+    Delta(Vec<T>),
+    /// Every record upstream
+    Full(Vec<T>),
+}
+
+impl<T> From<SyncBatch<T>> for Synced<T> {
+    fn from(batch: SyncBatch<T>) -> Self {
+        // A complete sync without a checkpoint listed everything
+        if batch.is_full() && batch.complete {
+            Synced::Full(batch.items)
+        } else {
+            Synced::Delta(batch.items)
+        }
+    }
+}
+
+/// Scheduled local mirror of LLM-Registry
+pub struct RegistryMirror {
+    registry: Arc<RegistryAdapter>,
+    sync: IncrementalSync,
+    database: Option<Arc<Database>>,
+    config: MirrorConfig,
+    models: RwLock<MirrorTable<ModelMetadata>>,
+    providers: RwLock<MirrorTable<ProviderInfo>>,
+    pipelines: RwLock<MirrorTable<PipelineDescriptor>>,
+    runs: AtomicU64,
+    lookup_hits: AtomicU64,
+    lookup_misses: AtomicU64,
+}
+
+impl RegistryMirror {
+    pub fn new(
+        registry: Arc<RegistryAdapter>,
+        sync: IncrementalSync,
+        config: MirrorConfig,
+    ) -> Self {
+        Self {
+            registry,
+            sync,
+            database: None,
+            config,
+            models: RwLock::new(MirrorTable::new()),
+            providers: RwLock::new(MirrorTable::new()),
+            pipelines: RwLock::new(MirrorTable::new()),
+            runs: AtomicU64::new(0),
+            lookup_hits: AtomicU64::new(0),
+            lookup_misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Persist synced records so restarts serve lookups before the first sync
+    pub fn with_database(mut self, database: Arc<Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Load persisted records into memory, returning the number loaded
+    pub async fn load(&self) -> Result<usize> {
+        let Some(db) = &self.database else {
+            return Ok(0);
+        };
+        let models = load_kind(db, MirrorKind::Models, |m: &ModelMetadata| {
+            m.model_id.clone()
+        })
+        .await?;
+        let providers = load_kind(db, MirrorKind::Providers, |p: &ProviderInfo| {
+            p.provider_id.clone()
+        })
+        .await?;
+        let pipelines = load_kind(db, MirrorKind::Pipelines, |p: &PipelineDescriptor| {
+            p.pipeline_id.clone()
+        })
+        .await?;
+
+        let loaded = models.0.len() + providers.0.len() + pipelines.0.len();
+        restore(&self.models, models);
+        restore(&self.providers, providers);
+        restore(&self.pipelines, pipelines);
+        info!(records = loaded, "Loaded registry mirror");
+        Ok(loaded)
+    }
+
+    /// Sync every record kind once. A kind that fails keeps its previous
+    /// records and reports the error in its status.
+    pub async fn sync_once(&self) -> Vec<MirrorKindStatus> {
+        let run = self.runs.fetch_add(1, Ordering::Relaxed);
+        let full = self.config.full_resync_every > 0 && run % self.config.full_resync_every == 0;
+
+        let models = if full {
+            self.registry
+                .list_models(Default::default())
+                .await
+                .map(Synced::Full)
+        } else {
+            self.registry
+                .sync_models(&self.sync)
+                .await
+                .map(Synced::from)
+        };
+        self.apply(MirrorKind::Models, &self.models, models, |m| {
+            m.model_id.clone()
+        })
+        .await;
+
+        let providers = self.registry.list_providers().await.map(Synced::Full);
+        self.apply(MirrorKind::Providers, &self.providers, providers, |p| {
+            p.provider_id.clone()
+        })
+        .await;
+
+        let pipelines = if full {
+            self.registry
+                .list_pipelines(Default::default())
+                .await
+                .map(Synced::Full)
+        } else {
+            self.registry
+                .sync_pipelines(&self.sync)
+                .await
+                .map(Synced::from)
+        };
+        self.apply(MirrorKind::Pipelines, &self.pipelines, pipelines, |p| {
+            p.pipeline_id.clone()
+        })
+        .await;
+
+        self.status(Utc::now())
+    }
+
+    async fn apply<T, K>(
+        &self,
+        kind: MirrorKind,
+        table: &RwLock<MirrorTable<T>>,
+        synced: Result<Synced<T>>,
+        key: K,
+    ) where
+        T: Clone + Serialize,
+        K: Fn(&T) -> String,
+    {
+        let synced_at = Utc::now();
+        let synced = match synced {
+            Ok(synced) => synced,
+            Err(e) => {
+                warn!(kind = kind.as_str(), "Registry mirror sync failed: {}", e);
+                table.write().last_error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let (records, full) = match synced {
+            Synced::Delta(records) => (records, false),
+            Synced::Full(records) => (records, true),
+        };
+        if let Some(db) = &self.database {
+            if let Err(e) = persist(db, kind, &records, &key, synced_at, full).await {
+                warn!(
+                    kind = kind.as_str(),
+                    "Failed to persist registry mirror: {}", e
+                );
+                table.write().last_error = Some(e.to_string());
+                return;
+            }
+        }
+
+        let count = records.len();
+        let keyed: Vec<(String, T)> = records.into_iter().map(|r| (key(&r), r)).collect();
+        let mut table = table.write();
+        if full {
+            table.replace(keyed, synced_at);
+        } else {
+            table.upsert(keyed, synced_at);
+        }
+        debug!(
+            kind = kind.as_str(),
+            records = count,
+            full,
+            "Registry mirror synced"
+        );
+    }
+
+    /// Sync on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let stale: Vec<&str> = self
+                    .sync_once()
+                    .await
+                    .iter()
+                    .filter(|status| status.stale)
+                    .map(|status| status.kind.as_str())
+                    .collect();
+                if !stale.is_empty() {
+                    warn!(kinds = ?stale, "Registry mirror is stale");
+                }
+            }
+        })
+    }
+
+    fn record_lookup<T>(&self, found: Option<T>) -> Option<T> {
+        let counter = if found.is_some() {
+            &self.lookup_hits
+        } else {
+            &self.lookup_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    pub fn model(&self, model_id: &str) -> Option<ModelMetadata> {
+        self.record_lookup(self.models.read().records.get(model_id).cloned())
+    }
+
+    pub fn provider(&self, provider_id: &str) -> Option<ProviderInfo> {
+        self.record_lookup(self.providers.read().records.get(provider_id).cloned())
+    }
+
+    pub fn pipeline(&self, pipeline_id: &str) -> Option<PipelineDescriptor> {
+        self.record_lookup(self.pipelines.read().records.get(pipeline_id).cloned())
+    }
+
+    pub fn models(&self) -> Vec<ModelMetadata> {
+        self.models.read().records.values().cloned().collect()
+    }
+
+    pub fn providers(&self) -> Vec<ProviderInfo> {
+        self.providers.read().records.values().cloned().collect()
+    }
+
+    pub fn pipelines(&self) -> Vec<PipelineDescriptor> {
+        self.pipelines.read().records.values().cloned().collect()
+    }
+
+    /// A mirrored pipeline, falling back to the registry for pipelines not synced yet
+    pub async fn fetch_pipeline(&self, pipeline_id: &str) -> Result<PipelineDescriptor> {
+        match self.pipeline(pipeline_id) {
+            Some(pipeline) => Ok(pipeline),
+            None => self.registry.fetch_pipeline(pipeline_id).await,
+        }
+    }
+
+    /// Freshness of every record kind
+    pub fn status(&self, now: DateTime<Utc>) -> Vec<MirrorKindStatus> {
+        let max_staleness = Duration::seconds(self.config.max_staleness_secs);
+        vec![
+            self.models
+                .read()
+                .status(MirrorKind::Models, max_staleness, now),
+            self.providers
+                .read()
+                .status(MirrorKind::Providers, max_staleness, now),
+            self.pipelines
+                .read()
+                .status(MirrorKind::Pipelines, max_staleness, now),
+        ]
+    }
+
+    /// Whether any record kind is stale
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.status(now).iter().any(|status| status.stale)
+    }
+
+    pub fn get_stats(&self) -> MirrorStats {
+        MirrorStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            lookup_hits: self.lookup_hits.load(Ordering::Relaxed),
+            lookup_misses: self.lookup_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Registry mirror statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorStats {
+    pub runs: u64,
+    pub lookup_hits: u64,
+    pub lookup_misses: u64,
+}
+
+/// Persisted records of one kind with the newest sync time
+async fn load_kind<T, K>(
+    db: &Database,
+    kind: MirrorKind,
+    key: K,
+) -> Result<(Vec<(String, T)>, Option<DateTime<Utc>>)>
+where
+    T: DeserializeOwned,
+    K: Fn(&T) -> String,
+{
+    let rows = db.query_mirror_records(kind.as_str()).await?;
+    let synced = rows.iter().map(|row| row.synced_at).max();
+    let records = rows
+        .into_iter()
+        .filter_map(|row| match serde_json::from_value::<T>(row.record) {
+            Ok(record) => Some((key(&record), record)),
+            Err(e) => {
+                warn!(kind = kind.as_str(), entity_id = %row.entity_id, "Skipping unreadable mirror record: {}", e);
+                None
+            }
+        })
+        .collect();
+    Ok((records, synced))
+}
+
+fn restore<T: Clone>(
+    table: &RwLock<MirrorTable<T>>,
+    (records, synced): (Vec<(String, T)>, Option<DateTime<Utc>>),
+) {
+    let mut table = table.write();
+    table.records = records.into_iter().collect();
+    table.last_synced = synced;
+}
+
+async fn persist<T, K>(
+    db: &Database,
+    kind: MirrorKind,
+    records: &[T],
+    key: &K,
+    synced_at: DateTime<Utc>,
+    full: bool,
+) -> Result<()>
+where
+    T: Serialize,
+    K: Fn(&T) -> String,
+{
+    let rows = records
+        .iter()
+        .map(|record| -> Result<(String, serde_json::Value)> {
+            Ok((key(record), serde_json::to_value(record)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    db.upsert_mirror_records(kind.as_str(), &rows, synced_at)
+        .await?;
+    if full {
+        let removed = db
+            .delete_mirror_records_before(kind.as_str(), synced_at)
+            .await?;
+        if removed > 0 {
+            info!(
+                kind = kind.as_str(),
+                removed, "Dropped records deleted from the registry"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::pagination::{MemoryCheckpointStore, SyncConfig};
+    use crate::adapters::registry::RegistryConfig;
+    use crate::adapters::EcosystemAdapter;
+
+    fn provider(id: &str) -> ProviderInfo {
+        serde_json::from_value(serde_json::json!({
+            "provider_id": id,
+            "name": id,
+            "status": "Operational",
+            "api_version": "v1",
+            "models": [],
+            "rate_limits": {"requests_per_minute": 0, "tokens_per_minute": 0, "tokens_per_day": null},
+            "health": {"availability": 1.0, "avg_latency_ms": 0.0, "error_rate": 0.0, "last_checked": "2024-01-01T00:00:00Z"}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_tracks_staleness_and_serves_lookups() {
+        let registry = Arc::new(RegistryAdapter::new(RegistryConfig::from_env().unwrap()));
+        registry.connect().await.unwrap();
+        let mirror = RegistryMirror::new(
+            registry,
+            IncrementalSync::new(
+                Arc::new(MemoryCheckpointStore::new()),
+                SyncConfig::default(),
+            ),
+            MirrorConfig::default(),
+        );
+
+        let now = Utc::now();
+        assert!(mirror.is_stale(now));
+
+        let status = mirror.sync_once().await;
+        assert!(status.iter().all(|s| !s.stale && s.last_error.is_none()));
+        assert!(!mirror.is_stale(Utc::now()));
+        assert!(mirror.is_stale(Utc::now() + Duration::hours(1)));
+
+        // Records applied by a sync are served locally
+        let synced_at = Utc::now();
+        mirror
+            .apply(
+                MirrorKind::Providers,
+                &mirror.providers,
+                Ok(Synced::Delta(vec![provider("openai")])),
+                |p| p.provider_id.clone(),
+            )
+            .await;
+        assert_eq!(mirror.provider("openai").unwrap().name, "openai");
+        assert!(mirror.provider("anthropic").is_none());
+        assert_eq!(mirror.get_stats().lookup_hits, 1);
+        assert_eq!(mirror.get_stats().lookup_misses, 1);
+
+        // A full listing drops records missing upstream
+        mirror
+            .apply(
+                MirrorKind::Providers,
+                &mirror.providers,
+                Ok(Synced::Full(vec![provider("anthropic")])),
+                |p| p.provider_id.clone(),
+            )
+            .await;
+        assert!(mirror.provider("openai").is_none());
+        assert_eq!(mirror.providers().len(), 1);
+
+        // Failures keep the previous records and surface the error
+        mirror
+            .apply(
+                MirrorKind::Providers,
+                &mirror.providers,
+                Err(anyhow::anyhow!("registry down")),
+                |p: &ProviderInfo| p.provider_id.clone(),
+            )
+            .await;
+        let providers = &mirror.status(synced_at)[1];
+        assert_eq!(providers.records, 1);
+        assert_eq!(providers.last_error.as_deref(), Some("registry down"));
+    }
+}
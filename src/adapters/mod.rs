@@ -11,12 +11,22 @@ pub mod costops;
 pub mod memory_graph;
 pub mod registry;
 pub mod config_manager;
+pub mod pagination;
+pub mod mirror;
 
 use async_trait::async_trait;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use crate::resilience::CircuitState;
+
+pub use pagination::{
+    fetch_all, CheckpointStore, IncrementalSync, Page, PageRequest, SyncBatch, SyncCheckpoint,
+    SyncConfig,
+};
+pub use mirror::{MirrorConfig, MirrorKind, MirrorKindStatus, RegistryMirror};
+
 /// Common trait for all ecosystem adapters
 #[async_trait]
 pub trait EcosystemAdapter: Send + Sync {
@@ -38,6 +48,9 @@ pub struct AdapterHealth {
     pub latency_ms: Option<u64>,
     pub last_successful_fetch: Option<chrono::DateTime<chrono::Utc>>,
     pub error_message: Option<String>,
+    /// State of the circuit breaker guarding this upstream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_state: Option<CircuitState>,
 }
 
 impl AdapterHealth {
@@ -48,6 +61,7 @@ impl AdapterHealth {
             latency_ms: Some(latency_ms),
             last_successful_fetch: Some(chrono::Utc::now()),
             error_message: None,
+            circuit_state: None,
         }
     }
 
@@ -58,8 +72,25 @@ impl AdapterHealth {
             latency_ms: None,
             last_successful_fetch: None,
             error_message: Some(error.to_string()),
+            circuit_state: None,
         }
     }
+
+    /// Attach circuit breaker state; an open circuit marks the adapter unhealthy
+    pub fn with_circuit_state(mut self, state: CircuitState) -> Self {
+        if state == CircuitState::Open {
+            self.is_healthy = false;
+            self.error_message
+                .get_or_insert_with(|| "Circuit breaker open".to_string());
+        }
+        self.circuit_state = Some(state);
+        self
+    }
+
+    /// Whether the upstream is unhealthy or still recovering (half-open circuit)
+    pub fn is_degraded(&self) -> bool {
+        !self.is_healthy || self.circuit_state == Some(CircuitState::HalfOpen)
+    }
 }
 
 /// Unified adapter manager for all ecosystem integrations
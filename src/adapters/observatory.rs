@@ -7,6 +7,7 @@
 //! purposes without modifying any upstream logic.
 
 use super::{AdapterHealth, EcosystemAdapter};
+use crate::resilience::{ResilienceConfig, ResilienceGuard};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -23,6 +24,8 @@ pub struct ObservatoryConfig {
     pub api_key: Option<String>,
     pub timeout_secs: u64,
     pub batch_size: usize,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
 }
 
 impl ObservatoryConfig {
@@ -39,6 +42,7 @@ impl ObservatoryConfig {
                 .unwrap_or_else(|_| "100".to_string())
                 .parse()
                 .unwrap_or(100),
+            resilience: ResilienceConfig::from_env("OBSERVATORY"),
         })
     }
 }
@@ -144,11 +148,13 @@ pub struct TraceQuery {
 pub struct ObservatoryAdapter {
     config: ObservatoryConfig,
     connected: AtomicBool,
+    resilience: ResilienceGuard,
 }
 
 impl ObservatoryAdapter {
     pub fn new(config: ObservatoryConfig) -> Self {
         Self {
+            resilience: ResilienceGuard::new("observatory", &config.resilience),
             config,
             connected: AtomicBool::new(false),
         }
@@ -161,26 +167,30 @@ impl ObservatoryAdapter {
             anyhow::bail!("Observatory adapter not connected");
         }
 
-        debug!("Fetching telemetry from Observatory");
-
-        // Construct query parameters
-        let mut params = HashMap::new();
-        if let Some(names) = &query.metric_names {
-            params.insert("metrics", names.join(","));
-        }
-        if let Some(start) = query.start_time {
-            params.insert("start", start.to_rfc3339());
-        }
-        if let Some(end) = query.end_time {
-            params.insert("end", end.to_rfc3339());
-        }
-        if let Some(limit) = query.limit {
-            params.insert("limit", limit.to_string());
-        }
-
-        // In a real implementation, this would make HTTP calls to Observatory
-        // For now, return empty vec as placeholder
-        Ok(Vec::new())
+        self.resilience
+            .call(|| async {
+                debug!("Fetching telemetry from Observatory");
+
+                // Construct query parameters
+                let mut params = HashMap::new();
+                if let Some(names) = &query.metric_names {
+                    params.insert("metrics", names.join(","));
+                }
+                if let Some(start) = query.start_time {
+                    params.insert("start", start.to_rfc3339());
+                }
+                if let Some(end) = query.end_time {
+                    params.insert("end", end.to_rfc3339());
+                }
+                if let Some(limit) = query.limit {
+                    params.insert("limit", limit.to_string());
+                }
+
+                // In a real implementation, this would make HTTP calls to Observatory
+                // For now, return empty vec as placeholder
+                Ok(Vec::new())
+            })
+            .await
     }
 
     /// Fetch usage traces
@@ -190,10 +200,14 @@ impl ObservatoryAdapter {
             anyhow::bail!("Observatory adapter not connected");
         }
 
-        debug!("Fetching traces from Observatory");
+        self.resilience
+            .call(|| async {
+                debug!("Fetching traces from Observatory");
 
-        // In a real implementation, this would make HTTP calls to Observatory
-        Ok(Vec::new())
+                // In a real implementation, this would make HTTP calls to Observatory
+                Ok(Vec::new())
+            })
+            .await
     }
 
     /// Fetch time-series performance metrics
@@ -207,24 +221,28 @@ impl ObservatoryAdapter {
             anyhow::bail!("Observatory adapter not connected");
         }
 
-        debug!(measurement = %measurement, "Fetching performance metrics from Observatory");
-
-        // Placeholder implementation
-        Ok(PerformanceMetrics {
-            metric_id: uuid::Uuid::new_v4().to_string(),
-            measurement: measurement.to_string(),
-            time_range,
-            data_points: Vec::new(),
-            aggregations: MetricAggregations {
-                min: 0.0,
-                max: 0.0,
-                avg: 0.0,
-                p50: 0.0,
-                p95: 0.0,
-                p99: 0.0,
-                count: 0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(measurement = %measurement, "Fetching performance metrics from Observatory");
+
+                // Placeholder implementation
+                Ok(PerformanceMetrics {
+                    metric_id: uuid::Uuid::new_v4().to_string(),
+                    measurement: measurement.to_string(),
+                    time_range: time_range.clone(),
+                    data_points: Vec::new(),
+                    aggregations: MetricAggregations {
+                        min: 0.0,
+                        max: 0.0,
+                        avg: 0.0,
+                        p50: 0.0,
+                        p95: 0.0,
+                        p99: 0.0,
+                        count: 0,
+                    },
+                })
+            })
+            .await
     }
 
     /// Stream telemetry in real-time (returns channel receiver)
@@ -236,13 +254,17 @@ impl ObservatoryAdapter {
             anyhow::bail!("Observatory adapter not connected");
         }
 
-        let (tx, rx) = tokio::sync::mpsc::channel(self.config.batch_size);
+        self.resilience
+            .call(|| async {
+                let (tx, rx) = tokio::sync::mpsc::channel(self.config.batch_size);
 
-        // In a real implementation, this would establish a WebSocket or SSE connection
-        // and forward telemetry points through the channel
-        info!(metrics = ?metric_names, "Started telemetry stream");
+                // In a real implementation, this would establish a WebSocket or SSE connection
+                // and forward telemetry points through the channel
+                info!(metrics = ?metric_names, "Started telemetry stream");
 
-        Ok(rx)
+                Ok(rx)
+            })
+            .await
     }
 }
 
@@ -269,7 +291,8 @@ impl EcosystemAdapter for ObservatoryAdapter {
         // In a real implementation, ping the Observatory health endpoint
         let latency_ms = start.elapsed().as_millis() as u64;
 
-        Ok(AdapterHealth::healthy("observatory", latency_ms))
+        Ok(AdapterHealth::healthy("observatory", latency_ms)
+            .with_circuit_state(self.resilience.circuit_state().await))
     }
 
     #[instrument(skip(self))]
@@ -6,7 +6,11 @@
 //! This adapter provides read-only access to registry data for analytics
 //! purposes without modifying any upstream logic.
 
+use super::pagination::{
+    fetch_all, IncrementalSync, Page, PageRequest, SyncBatch, SyncConfig, Updated,
+};
 use super::{AdapterHealth, EcosystemAdapter};
+use crate::resilience::{ResilienceConfig, ResilienceGuard};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -14,7 +18,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
+
+/// Checkpoint name of the incremental model sync
+pub const MODELS_SYNC_SOURCE: &str = "registry.models";
+
+/// Checkpoint name of the incremental pipeline sync
+pub const PIPELINES_SYNC_SOURCE: &str = "registry.pipelines";
 
 /// Configuration for Registry adapter
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +32,11 @@ pub struct RegistryConfig {
     pub endpoint: String,
     pub api_key: Option<String>,
     pub timeout_secs: u64,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
+    /// Page size and page limit of listings
+    #[serde(default)]
+    pub paging: SyncConfig,
 }
 
 impl RegistryConfig {
@@ -34,6 +49,8 @@ impl RegistryConfig {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            resilience: ResilienceConfig::from_env("REGISTRY"),
+            paging: SyncConfig::from_env(),
         })
     }
 }
@@ -56,6 +73,12 @@ pub struct ModelMetadata {
     pub tags: HashMap<String, String>,
 }
 
+impl Updated for ModelMetadata {
+    fn last_updated(&self) -> DateTime<Utc> {
+        self.last_updated
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ModelType {
     TextGeneration,
@@ -126,6 +149,12 @@ pub struct PipelineDescriptor {
     pub metrics: PipelineMetrics,
 }
 
+impl Updated for PipelineDescriptor {
+    fn last_updated(&self) -> DateTime<Utc> {
+        self.last_updated
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineStage {
     pub stage_id: String,
@@ -233,11 +262,13 @@ pub struct PipelineQuery {
 pub struct RegistryAdapter {
     config: RegistryConfig,
     connected: AtomicBool,
+    resilience: ResilienceGuard,
 }
 
 impl RegistryAdapter {
     pub fn new(config: RegistryConfig) -> Self {
         Self {
+            resilience: ResilienceGuard::new("registry", &config.resilience),
             config,
             connected: AtomicBool::new(false),
         }
@@ -250,49 +281,89 @@ impl RegistryAdapter {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!(model_id = %model_id, "Fetching model metadata from Registry");
-
-        // Placeholder implementation
-        Ok(ModelMetadata {
-            model_id: model_id.to_string(),
-            name: model_id.to_string(),
-            version: "1.0.0".to_string(),
-            provider: "unknown".to_string(),
-            model_type: ModelType::TextGeneration,
-            capabilities: Vec::new(),
-            context_window: 0,
-            pricing: ModelPricing {
-                currency: "USD".to_string(),
-                input_cost_per_1k_tokens: 0.0,
-                output_cost_per_1k_tokens: 0.0,
-                image_cost_per_unit: None,
-                audio_cost_per_minute: None,
-            },
-            performance: ModelPerformance {
-                avg_latency_ms: 0.0,
-                p95_latency_ms: 0.0,
-                p99_latency_ms: 0.0,
-                tokens_per_second: 0.0,
-                availability: 0.0,
-            },
-            status: ModelStatus::Active,
-            registered_at: Utc::now(),
-            last_updated: Utc::now(),
-            tags: HashMap::new(),
-        })
+        self.resilience
+            .call(|| async {
+                debug!(model_id = %model_id, "Fetching model metadata from Registry");
+
+                // Placeholder implementation
+                Ok(ModelMetadata {
+                    model_id: model_id.to_string(),
+                    name: model_id.to_string(),
+                    version: "1.0.0".to_string(),
+                    provider: "unknown".to_string(),
+                    model_type: ModelType::TextGeneration,
+                    capabilities: Vec::new(),
+                    context_window: 0,
+                    pricing: ModelPricing {
+                        currency: "USD".to_string(),
+                        input_cost_per_1k_tokens: 0.0,
+                        output_cost_per_1k_tokens: 0.0,
+                        image_cost_per_unit: None,
+                        audio_cost_per_minute: None,
+                    },
+                    performance: ModelPerformance {
+                        avg_latency_ms: 0.0,
+                        p95_latency_ms: 0.0,
+                        p99_latency_ms: 0.0,
+                        tokens_per_second: 0.0,
+                        availability: 0.0,
+                    },
+                    status: ModelStatus::Active,
+                    registered_at: Utc::now(),
+                    last_updated: Utc::now(),
+                    tags: HashMap::new(),
+                })
+            })
+            .await
     }
 
-    /// List models matching query
+    /// Fetch one page of the models matching query
     #[instrument(skip(self))]
-    pub async fn list_models(&self, query: ModelQuery) -> Result<Vec<ModelMetadata>> {
+    pub async fn list_models_page(
+        &self,
+        query: &ModelQuery,
+        page: PageRequest,
+    ) -> Result<Page<ModelMetadata>> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!("Listing models from Registry");
+        self.resilience
+            .call(|| async {
+                debug!(cursor = ?page.cursor, updated_since = ?page.updated_since, "Listing models from Registry");
+
+                // Placeholder implementation
+                Ok(Page::last(Vec::new()))
+            })
+            .await
+    }
 
-        // Placeholder implementation
-        Ok(Vec::new())
+    /// List models matching query, across every page
+    #[instrument(skip(self))]
+    pub async fn list_models(&self, query: ModelQuery) -> Result<Vec<ModelMetadata>> {
+        let query = &query;
+        let paged = fetch_all(
+            self.config.paging.first_page(),
+            self.config.paging.max_pages,
+            move |page| self.list_models_page(query, page),
+        )
+        .await?;
+        if !paged.complete {
+            warn!(
+                pages = paged.pages,
+                "Model listing truncated at the page limit"
+            );
+        }
+        Ok(paged.items)
+    }
+
+    /// Models registered or changed since the last complete sync
+    pub async fn sync_models(&self, sync: &IncrementalSync) -> Result<SyncBatch<ModelMetadata>> {
+        let query = &ModelQuery::default();
+        sync.sync(MODELS_SYNC_SOURCE, move |page| {
+            self.list_models_page(query, page)
+        })
+        .await
     }
 
     /// Fetch pipeline descriptor by ID
@@ -302,41 +373,84 @@ impl RegistryAdapter {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!(pipeline_id = %pipeline_id, "Fetching pipeline descriptor from Registry");
-
-        // Placeholder implementation
-        Ok(PipelineDescriptor {
-            pipeline_id: pipeline_id.to_string(),
-            name: pipeline_id.to_string(),
-            version: "1.0.0".to_string(),
-            description: String::new(),
-            stages: Vec::new(),
-            input_schema: serde_json::json!({}),
-            output_schema: serde_json::json!({}),
-            created_at: Utc::now(),
-            last_updated: Utc::now(),
-            owner: "unknown".to_string(),
-            status: PipelineStatus::Active,
-            metrics: PipelineMetrics {
-                total_invocations: 0,
-                success_rate: 0.0,
-                avg_latency_ms: 0.0,
-                avg_cost_per_invocation: 0.0,
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(pipeline_id = %pipeline_id, "Fetching pipeline descriptor from Registry");
+
+                // Placeholder implementation
+                Ok(PipelineDescriptor {
+                    pipeline_id: pipeline_id.to_string(),
+                    name: pipeline_id.to_string(),
+                    version: "1.0.0".to_string(),
+                    description: String::new(),
+                    stages: Vec::new(),
+                    input_schema: serde_json::json!({}),
+                    output_schema: serde_json::json!({}),
+                    created_at: Utc::now(),
+                    last_updated: Utc::now(),
+                    owner: "unknown".to_string(),
+                    status: PipelineStatus::Active,
+                    metrics: PipelineMetrics {
+                        total_invocations: 0,
+                        success_rate: 0.0,
+                        avg_latency_ms: 0.0,
+                        avg_cost_per_invocation: 0.0,
+                    },
+                })
+            })
+            .await
     }
 
-    /// List pipelines matching query
+    /// Fetch one page of the pipelines matching query
     #[instrument(skip(self))]
-    pub async fn list_pipelines(&self, query: PipelineQuery) -> Result<Vec<PipelineDescriptor>> {
+    pub async fn list_pipelines_page(
+        &self,
+        query: &PipelineQuery,
+        page: PageRequest,
+    ) -> Result<Page<PipelineDescriptor>> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!("Listing pipelines from Registry");
+        self.resilience
+            .call(|| async {
+                debug!(cursor = ?page.cursor, updated_since = ?page.updated_since, "Listing pipelines from Registry");
 
-        // Placeholder implementation
-        Ok(Vec::new())
+                // Placeholder implementation
+                Ok(Page::last(Vec::new()))
+            })
+            .await
+    }
+
+    /// List pipelines matching query, across every page
+    #[instrument(skip(self))]
+    pub async fn list_pipelines(&self, query: PipelineQuery) -> Result<Vec<PipelineDescriptor>> {
+        let query = &query;
+        let paged = fetch_all(
+            self.config.paging.first_page(),
+            self.config.paging.max_pages,
+            move |page| self.list_pipelines_page(query, page),
+        )
+        .await?;
+        if !paged.complete {
+            warn!(
+                pages = paged.pages,
+                "Pipeline listing truncated at the page limit"
+            );
+        }
+        Ok(paged.items)
+    }
+
+    /// Pipelines created or changed since the last complete sync
+    pub async fn sync_pipelines(
+        &self,
+        sync: &IncrementalSync,
+    ) -> Result<SyncBatch<PipelineDescriptor>> {
+        let query = &PipelineQuery::default();
+        sync.sync(PIPELINES_SYNC_SOURCE, move |page| {
+            self.list_pipelines_page(query, page)
+        })
+        .await
     }
 
     /// Get provider information
@@ -346,40 +460,66 @@ impl RegistryAdapter {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!(provider_id = %provider_id, "Fetching provider info from Registry");
-
-        // Placeholder implementation
-        Ok(ProviderInfo {
-            provider_id: provider_id.to_string(),
-            name: provider_id.to_string(),
-            status: ProviderStatus::Operational,
-            api_version: "1.0".to_string(),
-            models: Vec::new(),
-            rate_limits: RateLimits {
-                requests_per_minute: 0,
-                tokens_per_minute: 0,
-                tokens_per_day: None,
-            },
-            health: ProviderHealth {
-                availability: 0.0,
-                avg_latency_ms: 0.0,
-                error_rate: 0.0,
-                last_checked: Utc::now(),
-            },
-        })
+        self.resilience
+            .call(|| async {
+                debug!(provider_id = %provider_id, "Fetching provider info from Registry");
+
+                // Placeholder implementation
+                Ok(ProviderInfo {
+                    provider_id: provider_id.to_string(),
+                    name: provider_id.to_string(),
+                    status: ProviderStatus::Operational,
+                    api_version: "1.0".to_string(),
+                    models: Vec::new(),
+                    rate_limits: RateLimits {
+                        requests_per_minute: 0,
+                        tokens_per_minute: 0,
+                        tokens_per_day: None,
+                    },
+                    health: ProviderHealth {
+                        availability: 0.0,
+                        avg_latency_ms: 0.0,
+                        error_rate: 0.0,
+                        last_checked: Utc::now(),
+                    },
+                })
+            })
+            .await
     }
 
-    /// List all providers
+    /// Fetch one page of providers
     #[instrument(skip(self))]
-    pub async fn list_providers(&self) -> Result<Vec<ProviderInfo>> {
+    pub async fn list_providers_page(&self, page: PageRequest) -> Result<Page<ProviderInfo>> {
         if !self.connected.load(Ordering::Relaxed) {
             anyhow::bail!("Registry adapter not connected");
         }
 
-        debug!("Listing providers from Registry");
+        self.resilience
+            .call(|| async {
+                debug!(cursor = ?page.cursor, "Listing providers from Registry");
+
+                // Placeholder implementation
+                Ok(Page::last(Vec::new()))
+            })
+            .await
+    }
 
-        // Placeholder implementation
-        Ok(Vec::new())
+    /// List all providers, across every page
+    #[instrument(skip(self))]
+    pub async fn list_providers(&self) -> Result<Vec<ProviderInfo>> {
+        let paged = fetch_all(
+            self.config.paging.first_page(),
+            self.config.paging.max_pages,
+            |page| self.list_providers_page(page),
+        )
+        .await?;
+        if !paged.complete {
+            warn!(
+                pages = paged.pages,
+                "Provider listing truncated at the page limit"
+            );
+        }
+        Ok(paged.items)
     }
 }
 
@@ -403,7 +543,8 @@ impl EcosystemAdapter for RegistryAdapter {
         }
 
         let latency_ms = start.elapsed().as_millis() as u64;
-        Ok(AdapterHealth::healthy("registry", latency_ms))
+        Ok(AdapterHealth::healthy("registry", latency_ms)
+            .with_circuit_state(self.resilience.circuit_state().await))
     }
 
     #[instrument(skip(self))]
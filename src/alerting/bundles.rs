@@ -0,0 +1,696 @@
+//! Rules as Code
+//!
+//! Loads alert rule and SLO definitions from YAML or TOML bundles kept in git,
+//! either from a local directory (e.g. a mounted ConfigMap) or a config
+//! endpoint. Each sync diffs the bundles against the stored rule set and
+//! applies the difference: alert rule changes are written in a single
+//! transaction, and SLO changes are applied only once that transaction has
+//! committed. A dry run returns the same report without changing anything.
+//!
+//! Definitions that are stored but absent from every bundle are reported as
+//! unmanaged and left alone unless pruning is enabled, so rules created through
+//! the API are not deleted by a bundle that does not know about them.
+
+use crate::alerting::rules::{
+    validate_rule_id, AlertRule, AlertRuleRevision, AlertRuleSpec, AlertRuleStore, RuleWrite,
+};
+use crate::grpc::EventRouter;
+use crate::slo::{SloEngine, SloObjective};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Actor recorded on changes applied by scheduled syncs
+pub const RULE_BUNDLE_ACTOR: &str = "llm-analytics-hub/rules-as-code";
+
+/// Serialization of a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Yaml,
+    Toml,
+}
+
+impl BundleFormat {
+    /// Format implied by a file extension, or `None` for files that are not bundles
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(BundleFormat::Yaml),
+            "toml" => Some(BundleFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// An alert rule as written in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAlertRule {
+    pub rule_id: String,
+    #[serde(flatten)]
+    pub spec: AlertRuleSpec,
+}
+
+/// Alert rules and SLOs declared in one bundle file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleBundle {
+    #[serde(default)]
+    pub alert_rules: Vec<BundleAlertRule>,
+    #[serde(default)]
+    pub slos: Vec<SloObjective>,
+}
+
+impl RuleBundle {
+    pub fn parse(text: &str, format: BundleFormat) -> Result<Self> {
+        match format {
+            BundleFormat::Yaml => serde_yaml::from_str(text).context("Failed to parse YAML bundle"),
+            BundleFormat::Toml => config::Config::builder()
+                .add_source(config::File::from_str(text, config::FileFormat::Toml))
+                .build()
+                .and_then(|c| c.try_deserialize())
+                .context("Failed to parse TOML bundle"),
+        }
+    }
+
+    /// Merge bundles, rejecting ids declared more than once
+    pub fn merge(bundles: Vec<(String, RuleBundle)>) -> Result<Self> {
+        let mut rule_sources: HashMap<String, String> = HashMap::new();
+        let mut slo_sources: HashMap<String, String> = HashMap::new();
+        let mut merged = RuleBundle::default();
+        for (source, bundle) in bundles {
+            for rule in bundle.alert_rules {
+                if let Some(first) = rule_sources.insert(rule.rule_id.clone(), source.clone()) {
+                    bail!(
+                        "Alert rule {} is declared in both {} and {}",
+                        rule.rule_id,
+                        first,
+                        source
+                    );
+                }
+                merged.alert_rules.push(rule);
+            }
+            for slo in bundle.slos {
+                if let Some(first) = slo_sources.insert(slo.id.clone(), source.clone()) {
+                    bail!(
+                        "SLO {} is declared in both {} and {}",
+                        slo.id,
+                        first,
+                        source
+                    );
+                }
+                merged.slos.push(slo);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Check every definition on its own
+    pub fn validate(&self) -> Result<()> {
+        for rule in &self.alert_rules {
+            validate_rule_id(&rule.rule_id)?;
+            rule.spec
+                .validate()
+                .with_context(|| format!("Invalid alert rule {}", rule.rule_id))?;
+        }
+        for slo in &self.slos {
+            slo.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Where bundles are read from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleSource {
+    /// Every `.yaml`, `.yml`, and `.toml` file under a directory
+    Directory(PathBuf),
+    /// A single bundle served over HTTP
+    Url(String),
+}
+
+/// Rules-as-code configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleBundleConfig {
+    pub source: BundleSource,
+    /// Seconds between syncs after the startup sync; 0 syncs only at startup
+    pub reload_secs: u64,
+    /// Delete stored definitions that no bundle declares
+    pub prune: bool,
+    /// Report scheduled syncs without applying them
+    pub dry_run: bool,
+}
+
+impl RuleBundleConfig {
+    /// Configuration from the environment, or `None` when no bundle source is set
+    pub fn from_env() -> Option<Self> {
+        let source = match (
+            std::env::var("RULE_BUNDLE_DIR").ok(),
+            std::env::var("RULE_BUNDLE_URL").ok(),
+        ) {
+            (Some(dir), _) => BundleSource::Directory(PathBuf::from(dir)),
+            (None, Some(url)) => BundleSource::Url(url),
+            (None, None) => return None,
+        };
+        let flag = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false)
+        };
+        Some(Self {
+            source,
+            reload_secs: std::env::var("RULE_BUNDLE_RELOAD_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            prune: flag("RULE_BUNDLE_PRUNE"),
+            dry_run: flag("RULE_BUNDLE_DRY_RUN"),
+        })
+    }
+}
+
+/// Kind of definition a change applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    AlertRule,
+    Slo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// One difference between the bundles and the stored rule set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub kind: DefinitionKind,
+    pub id: String,
+    pub action: PlanAction,
+    /// Top-level fields that differ, for updates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+/// Result of diffing bundles against the stored rule set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulePlan {
+    pub changes: Vec<PlannedChange>,
+    pub unchanged: usize,
+    /// Stored definitions no bundle declares, kept because pruning is off
+    pub unmanaged: Vec<PlannedChange>,
+}
+
+/// Outcome of a sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub dry_run: bool,
+    pub sources: Vec<String>,
+    #[serde(flatten)]
+    pub plan: RulePlan,
+    /// Revisions written for alert rule changes; empty for dry runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<AlertRuleRevision>,
+}
+
+/// Top-level fields whose values differ between two serialized definitions
+fn changed_fields(current: &serde_json::Value, desired: &serde_json::Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let current = current.as_object().unwrap_or(&empty);
+    let desired = desired.as_object().unwrap_or(&empty);
+    let mut fields: Vec<String> = current
+        .keys()
+        .chain(desired.keys())
+        .filter(|key| current.get(*key) != desired.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+fn diff<T: Serialize>(
+    kind: DefinitionKind,
+    current: BTreeMap<&str, &T>,
+    desired: BTreeMap<&str, &T>,
+    prune: bool,
+    plan: &mut RulePlan,
+) {
+    for (id, definition) in &desired {
+        let desired_value = serde_json::to_value(definition).unwrap_or_default();
+        match current.get(id) {
+            None => plan.changes.push(PlannedChange {
+                kind,
+                id: id.to_string(),
+                action: PlanAction::Create,
+                fields: Vec::new(),
+            }),
+            Some(stored) => {
+                let stored_value = serde_json::to_value(stored).unwrap_or_default();
+                let fields = changed_fields(&stored_value, &desired_value);
+                if fields.is_empty() {
+                    plan.unchanged += 1;
+                } else {
+                    plan.changes.push(PlannedChange {
+                        kind,
+                        id: id.to_string(),
+                        action: PlanAction::Update,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    for id in current.keys().filter(|id| !desired.contains_key(*id)) {
+        let change = PlannedChange {
+            kind,
+            id: id.to_string(),
+            action: PlanAction::Delete,
+            fields: Vec::new(),
+        };
+        if prune {
+            plan.changes.push(change);
+        } else {
+            plan.unmanaged.push(change);
+        }
+    }
+}
+
+/// Diff a validated bundle against the stored alert rules and SLOs
+pub fn plan(
+    bundle: &RuleBundle,
+    rules: &[AlertRule],
+    slos: &[SloObjective],
+    prune: bool,
+) -> RulePlan {
+    let mut plan = RulePlan::default();
+    diff(
+        DefinitionKind::AlertRule,
+        rules
+            .iter()
+            .map(|r| (r.rule_id.as_str(), &r.spec))
+            .collect(),
+        bundle
+            .alert_rules
+            .iter()
+            .map(|r| (r.rule_id.as_str(), &r.spec))
+            .collect(),
+        prune,
+        &mut plan,
+    );
+    diff(
+        DefinitionKind::Slo,
+        slos.iter().map(|s| (s.id.as_str(), s)).collect(),
+        bundle.slos.iter().map(|s| (s.id.as_str(), s)).collect(),
+        prune,
+        &mut plan,
+    );
+    plan
+}
+
+/// Rules-as-code statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleBundleStats {
+    pub syncs: u64,
+    pub changes_applied: u64,
+    pub failures: u64,
+}
+
+/// Syncs alert rules and SLOs from bundles
+pub struct RuleBundleLoader {
+    rules: Arc<AlertRuleStore>,
+    slo: Option<Arc<SloEngine>>,
+    config: RuleBundleConfig,
+    http: reqwest::Client,
+    syncs: AtomicU64,
+    changes_applied: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl RuleBundleLoader {
+    pub fn new(rules: Arc<AlertRuleStore>, config: RuleBundleConfig) -> Self {
+        Self {
+            rules,
+            slo: None,
+            config,
+            http: reqwest::Client::new(),
+            syncs: AtomicU64::new(0),
+            changes_applied: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Manage SLO definitions as well as alert rules
+    pub fn with_slo_engine(mut self, slo: Arc<SloEngine>) -> Self {
+        self.slo = Some(slo);
+        self
+    }
+
+    pub fn config(&self) -> &RuleBundleConfig {
+        &self.config
+    }
+
+    /// Read and merge every bundle from the configured source
+    pub async fn load(&self) -> Result<(Vec<String>, RuleBundle)> {
+        let bundles = match &self.config.source {
+            BundleSource::Directory(dir) => read_directory(dir)?,
+            BundleSource::Url(url) => vec![(url.clone(), self.fetch(url).await?)],
+        };
+        let sources = bundles.iter().map(|(source, _)| source.clone()).collect();
+        Ok((sources, RuleBundle::merge(bundles)?))
+    }
+
+    async fn fetch(&self, url: &str) -> Result<RuleBundle> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to fetch rule bundle from {}", url))?;
+        let is_toml = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("toml"));
+        let format = if is_toml {
+            BundleFormat::Toml
+        } else {
+            BundleFormat::from_path(response.url().path()).unwrap_or(BundleFormat::Yaml)
+        };
+        let text = response.text().await?;
+        RuleBundle::parse(&text, format).with_context(|| format!("Invalid bundle at {}", url))
+    }
+
+    /// Diff the bundles against the stored rule set and, unless `dry_run`,
+    /// apply the difference.
+    ///
+    /// Nothing is applied when any definition is invalid or references an
+    /// unknown metric.
+    pub async fn sync(&self, dry_run: bool, actor: Option<&str>) -> Result<SyncReport> {
+        self.syncs.fetch_add(1, Ordering::Relaxed);
+        let result = self.sync_inner(dry_run, actor).await;
+        match &result {
+            Ok(report) => {
+                self.changes_applied
+                    .fetch_add(report.revisions.len() as u64, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+
+    async fn sync_inner(&self, dry_run: bool, actor: Option<&str>) -> Result<SyncReport> {
+        let (sources, bundle) = self.load().await?;
+        bundle.validate()?;
+        if self.slo.is_none() && !bundle.slos.is_empty() {
+            bail!("Bundles declare SLOs but no SLO engine is configured");
+        }
+        for rule in &bundle.alert_rules {
+            self.rules
+                .validate(&rule.spec)
+                .await
+                .with_context(|| format!("Invalid alert rule {}", rule.rule_id))?;
+        }
+
+        let current_rules = self.rules.list().await?;
+        let current_slos = self.slo.as_ref().map(|s| s.list()).unwrap_or_default();
+        let plan = plan(&bundle, &current_rules, &current_slos, self.config.prune);
+        let mut report = SyncReport {
+            dry_run,
+            sources,
+            plan,
+            revisions: Vec::new(),
+        };
+        if dry_run || report.plan.changes.is_empty() {
+            return Ok(report);
+        }
+
+        let now = Utc::now();
+        let current: HashMap<&str, &AlertRule> = current_rules
+            .iter()
+            .map(|r| (r.rule_id.as_str(), r))
+            .collect();
+        let desired: HashMap<&str, &AlertRuleSpec> = bundle
+            .alert_rules
+            .iter()
+            .map(|r| (r.rule_id.as_str(), &r.spec))
+            .collect();
+        let mut writes = Vec::new();
+        for change in &report.plan.changes {
+            if change.kind != DefinitionKind::AlertRule {
+                continue;
+            }
+            let id = change.id.as_str();
+            writes.push(match change.action {
+                PlanAction::Create => {
+                    let previous = self.rules.previous_version(id).await?;
+                    RuleWrite::create(id, desired[id].clone(), previous, actor, now)
+                }
+                PlanAction::Update => {
+                    RuleWrite::update(current[id], desired[id].clone(), actor, now)
+                }
+                PlanAction::Delete => RuleWrite::delete(current[id], actor, now),
+            });
+        }
+        report.revisions = self.rules.apply_all(writes).await?;
+
+        // SLOs were validated above, so applying them cannot fail half way
+        if let Some(engine) = &self.slo {
+            let desired: HashMap<&str, &SloObjective> =
+                bundle.slos.iter().map(|s| (s.id.as_str(), s)).collect();
+            for change in &report.plan.changes {
+                if change.kind != DefinitionKind::Slo {
+                    continue;
+                }
+                match change.action {
+                    PlanAction::Create | PlanAction::Update => {
+                        engine.define(desired[change.id.as_str()].clone())?;
+                    }
+                    PlanAction::Delete => {
+                        engine.remove(&change.id);
+                    }
+                }
+            }
+        }
+        info!(
+            changes = report.plan.changes.len(),
+            unchanged = report.plan.unchanged,
+            unmanaged = report.plan.unmanaged.len(),
+            "Applied rule bundles"
+        );
+
+        Ok(report)
+    }
+
+    /// Sync at startup and then every `reload_secs`, publishing an audit event
+    /// for each alert rule change through `audit`
+    pub fn spawn(self: Arc<Self>, audit: Arc<dyn EventRouter>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let environment = crate::database::environment::default_environment();
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.reload_secs.max(1),
+            ));
+            loop {
+                interval.tick().await;
+                match self
+                    .sync(self.config.dry_run, Some(RULE_BUNDLE_ACTOR))
+                    .await
+                {
+                    Ok(report) => {
+                        if report.dry_run && !report.plan.changes.is_empty() {
+                            info!(
+                                "Rule bundle dry run: {} change(s) pending",
+                                report.plan.changes.len()
+                            );
+                        }
+                        for revision in &report.revisions {
+                            let event = crate::alerting::rules::audit_event(revision, &environment);
+                            if let Err(e) = audit.route(event).await {
+                                warn!("Failed to publish alert rule audit event: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => error!("Rule bundle sync failed: {:#}", e),
+                }
+                if self.config.reload_secs == 0 {
+                    break;
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> RuleBundleStats {
+        RuleBundleStats {
+            syncs: self.syncs.load(Ordering::Relaxed),
+            changes_applied: self.changes_applied.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Parse every bundle file under `dir`, in path order
+fn read_directory(dir: &Path) -> Result<Vec<(String, RuleBundle)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read rule bundle directory {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Some(format) = BundleFormat::from_path(&path.to_string_lossy()) {
+                files.push((path, format));
+            }
+        }
+    }
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|(path, format)| {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let bundle = RuleBundle::parse(&text, format)
+                .with_context(|| format!("Invalid bundle {}", path.display()))?;
+            Ok((path.display().to_string(), bundle))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+alert_rules:
+  - rule_id: gpt4-slow
+    name: GPT-4 latency
+    expression: p95(latency_ms) > 2000
+    window: five_minutes
+    severity: warning
+    tags:
+      model_id: gpt-4
+slos:
+  - id: chat-availability
+    name: Chat availability
+    indicator:
+      type: availability
+    target: 99.9
+"#;
+
+    const TOML: &str = r#"
+[[alert_rules]]
+rule_id = "error-ratio"
+name = "Error ratio"
+expression = "errors / requests >= 0.05"
+window = "one_hour"
+severity = "critical"
+"#;
+
+    #[test]
+    fn test_parse_and_merge_bundles() {
+        let yaml = RuleBundle::parse(YAML, BundleFormat::Yaml).unwrap();
+        assert_eq!(yaml.alert_rules[0].rule_id, "gpt4-slow");
+        assert!(yaml.alert_rules[0].spec.enabled);
+        assert_eq!(yaml.slos[0].id, "chat-availability");
+
+        let toml = RuleBundle::parse(TOML, BundleFormat::Toml).unwrap();
+        assert_eq!(
+            toml.alert_rules[0].spec.severity,
+            crate::schemas::events::Severity::Critical
+        );
+        assert!(toml.slos.is_empty());
+
+        let merged = RuleBundle::merge(vec![
+            ("a.yaml".to_string(), yaml.clone()),
+            ("b.toml".to_string(), toml),
+        ])
+        .unwrap();
+        assert_eq!(merged.alert_rules.len(), 2);
+        merged.validate().unwrap();
+
+        let err = RuleBundle::merge(vec![
+            ("a.yaml".to_string(), yaml.clone()),
+            ("c.yaml".to_string(), yaml),
+        ])
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("declared in both a.yaml and c.yaml"));
+
+        assert_eq!(
+            BundleFormat::from_path("rules/slo.YML"),
+            Some(BundleFormat::Yaml)
+        );
+        assert_eq!(BundleFormat::from_path("README.md"), None);
+    }
+
+    #[test]
+    fn test_plan_diffs_against_stored_rules() {
+        let bundle = RuleBundle::parse(YAML, BundleFormat::Yaml).unwrap();
+        let now = Utc::now();
+        let stored = |rule_id: &str, spec: &AlertRuleSpec| {
+            RuleWrite::create(rule_id, spec.clone(), None, None, now)
+                .revision
+                .rule
+        };
+
+        // Nothing stored: everything is created
+        let created = plan(&bundle, &[], &[], false);
+        assert_eq!(created.changes.len(), 2);
+        assert!(created
+            .changes
+            .iter()
+            .all(|c| c.action == PlanAction::Create));
+
+        // Same rule stored: unchanged; an edited threshold is an update
+        let spec = bundle.alert_rules[0].spec.clone();
+        let same = plan(&bundle, &[stored("gpt4-slow", &spec)], &bundle.slos, false);
+        assert!(same.changes.is_empty());
+        assert_eq!(same.unchanged, 2);
+
+        let mut edited = spec.clone();
+        edited.expression = "p95(latency_ms) > 1500".to_string();
+        let updated = plan(
+            &bundle,
+            &[stored("gpt4-slow", &edited)],
+            &bundle.slos,
+            false,
+        );
+        assert_eq!(
+            updated.changes,
+            vec![PlannedChange {
+                kind: DefinitionKind::AlertRule,
+                id: "gpt4-slow".to_string(),
+                action: PlanAction::Update,
+                fields: vec!["expression".to_string()],
+            }]
+        );
+
+        // Rules no bundle declares are only deleted when pruning
+        let extra = stored("api-created", &spec);
+        let kept = plan(&bundle, &[extra.clone()], &bundle.slos, false);
+        assert_eq!(kept.unmanaged.len(), 1);
+        assert!(kept.changes.iter().all(|c| c.action != PlanAction::Delete));
+        let pruned = plan(&bundle, &[extra], &bundle.slos, true);
+        assert!(pruned.unmanaged.is_empty());
+        assert!(pruned
+            .changes
+            .iter()
+            .any(|c| c.id == "api-created" && c.action == PlanAction::Delete));
+    }
+}
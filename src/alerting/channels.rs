@@ -0,0 +1,439 @@
+//! Notification Channels
+//!
+//! Slack, PagerDuty, and generic webhook delivery built from the alert
+//! channels in the Config-Manager alerting configuration. Channels are
+//! addressed by name: the `name` key of the channel config, or the channel
+//! type in lowercase when unset.
+//!
+//! Notifications carrying a report document are rendered per channel: Slack
+//! receives Block Kit blocks and webhooks an HTML email body alongside the
+//! plain body.
+
+use crate::adapters::config_manager::{AlertChannel, AlertingConfig, ChannelType};
+use crate::reporting::message::{email_html, slack_blocks};
+use crate::reporting::render::ReportDocument;
+use crate::schemas::events::Severity;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// PagerDuty Events API v2 endpoint
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// A message delivered to a notification channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub subject: String,
+    pub body: String,
+    /// MIME type of `body`, e.g. `text/markdown`
+    pub content_type: String,
+    pub severity: Severity,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// Structured report behind `body`, for channels with richer formatting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<ReportDocument>,
+}
+
+impl Notification {
+    pub fn new(subject: impl Into<String>, body: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            subject: subject.into(),
+            body: body.into(),
+            content_type: "text/plain".to_string(),
+            severity,
+            tags: HashMap::new(),
+            document: None,
+        }
+    }
+
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_document(mut self, document: ReportDocument) -> Self {
+        self.document = Some(document);
+        self
+    }
+}
+
+/// A destination notifications can be delivered to
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Posts the notification as JSON to an arbitrary URL, adding an `html`
+/// email body when it carries a report document
+pub struct WebhookChannel {
+    name: String,
+    url: String,
+    http: reqwest::Client,
+}
+
+impl WebhookChannel {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let mut payload =
+            serde_json::to_value(notification).context("Failed to serialize notification")?;
+        if let (Some(document), Some(fields)) = (&notification.document, payload.as_object_mut()) {
+            fields.insert("html".to_string(), email_html(document).into());
+        }
+        self.http
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to post webhook notification")?
+            .error_for_status()
+            .context("Webhook rejected notification")?;
+        Ok(())
+    }
+}
+
+/// Posts to a Slack incoming webhook
+pub struct SlackChannel {
+    name: String,
+    webhook_url: String,
+    http: reqwest::Client,
+}
+
+impl SlackChannel {
+    pub fn new(name: &str, webhook_url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            webhook_url: webhook_url.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        self.http
+            .post(&self.webhook_url)
+            .json(&slack_payload(notification))
+            .send()
+            .await
+            .context("Failed to post Slack notification")?
+            .error_for_status()
+            .context("Slack rejected notification")?;
+        Ok(())
+    }
+}
+
+/// Slack message for a notification: Block Kit blocks when it carries a
+/// report document, with the subject as the fallback text, otherwise
+/// the subject and body as one mrkdwn message
+pub fn slack_payload(notification: &Notification) -> serde_json::Value {
+    match &notification.document {
+        Some(document) => serde_json::json!({
+            "text": notification.subject,
+            "blocks": slack_blocks(document),
+        }),
+        None => serde_json::json!({
+            "text": format!("*{}*\n{}", notification.subject, notification.body),
+        }),
+    }
+}
+
+/// Triggers PagerDuty incidents through the Events API v2
+pub struct PagerDutyChannel {
+    name: String,
+    routing_key: String,
+    http: reqwest::Client,
+}
+
+impl PagerDutyChannel {
+    pub fn new(name: &str, routing_key: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            routing_key: routing_key.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+fn pagerduty_severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Debug | Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+        Severity::Critical => "critical",
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for PagerDutyChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let event = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "payload": {
+                "summary": notification.subject,
+                "source": "llm-analytics-hub",
+                "severity": pagerduty_severity(&notification.severity),
+                "custom_details": {
+                    "body": notification.body,
+                    "tags": notification.tags,
+                },
+            },
+        });
+        self.http
+            .post(PAGERDUTY_EVENTS_URL)
+            .json(&event)
+            .send()
+            .await
+            .context("Failed to send PagerDuty event")?
+            .error_for_status()
+            .context("PagerDuty rejected event")?;
+        Ok(())
+    }
+}
+
+/// Name a configured channel is addressed by
+pub fn channel_name(channel: &AlertChannel) -> String {
+    channel
+        .config
+        .get("name")
+        .cloned()
+        .unwrap_or_else(|| format!("{:?}", channel.channel_type).to_lowercase())
+}
+
+/// Build the channel described by a Config-Manager alert channel
+pub fn channel_from_config(channel: &AlertChannel) -> Result<Arc<dyn NotificationChannel>> {
+    let name = channel_name(channel);
+    let setting = |key: &str| {
+        channel
+            .config
+            .get(key)
+            .with_context(|| format!("Channel '{}' has no '{}' setting", name, key))
+    };
+
+    Ok(match channel.channel_type {
+        ChannelType::Webhook => Arc::new(WebhookChannel::new(&name, setting("url")?)),
+        ChannelType::Slack => Arc::new(SlackChannel::new(&name, setting("webhook_url")?)),
+        ChannelType::PagerDuty => Arc::new(PagerDutyChannel::new(&name, setting("routing_key")?)),
+        ChannelType::Email | ChannelType::SNS => {
+            bail!(
+                "Channel '{}' uses unsupported type {:?}",
+                name,
+                channel.channel_type
+            )
+        }
+    })
+}
+
+/// Delivers notifications to channels by name
+#[derive(Default)]
+pub struct NotificationRouter {
+    channels: HashMap<String, Arc<dyn NotificationChannel>>,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl NotificationRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register every enabled, supported channel of the alerting configuration
+    pub fn from_alerting_config(config: &AlertingConfig) -> Self {
+        let mut router = Self::new();
+        for channel in config.channels.iter().filter(|c| c.enabled) {
+            match channel_from_config(channel) {
+                Ok(channel) => router = router.with_channel(channel),
+                Err(e) => warn!("Skipping notification channel: {}", e),
+            }
+        }
+        router
+    }
+
+    pub fn with_channel(mut self, channel: Arc<dyn NotificationChannel>) -> Self {
+        self.channels.insert(channel.name().to_string(), channel);
+        self
+    }
+
+    pub fn channel_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.channels.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Deliver to one channel
+    pub async fn send(&self, channel: &str, notification: &Notification) -> Result<()> {
+        let Some(target) = self.channels.get(channel) else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            bail!("Unknown notification channel '{}'", channel);
+        };
+
+        match target.send(notification).await {
+            Ok(()) => {
+                debug!(channel, subject = %notification.subject, "Notification delivered");
+                self.delivered.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+
+    /// Deliver to several channels, returning the number that succeeded.
+    /// Failures are logged rather than aborting delivery to the rest.
+    pub async fn broadcast(&self, channels: &[String], notification: &Notification) -> usize {
+        let mut delivered = 0;
+        for channel in channels {
+            match self.send(channel, notification).await {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!(channel = %channel, "Notification delivery failed: {}", e),
+            }
+        }
+        delivered
+    }
+
+    pub fn get_stats(&self) -> NotificationStats {
+        NotificationStats {
+            channels: self.channels.len(),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Notification delivery statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationStats {
+    pub channels: usize,
+    pub delivered: u64,
+    pub failed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    struct RecordingChannel {
+        name: String,
+        sent: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn send(&self, notification: &Notification) -> Result<()> {
+            self.sent.lock().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    fn alert_channel(channel_type: ChannelType, config: &[(&str, &str)]) -> AlertChannel {
+        AlertChannel {
+            channel_type,
+            config: config
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_channel_from_config() {
+        let slack = alert_channel(
+            ChannelType::Slack,
+            &[
+                ("name", "ops"),
+                ("webhook_url", "https://hooks.slack.test/x"),
+            ],
+        );
+        assert_eq!(channel_from_config(&slack).unwrap().name(), "ops");
+
+        let webhook = alert_channel(ChannelType::Webhook, &[("url", "https://example.test")]);
+        assert_eq!(channel_from_config(&webhook).unwrap().name(), "webhook");
+
+        assert!(channel_from_config(&alert_channel(ChannelType::PagerDuty, &[])).is_err());
+        assert!(channel_from_config(&alert_channel(ChannelType::Email, &[])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_router_broadcast() {
+        let channel = Arc::new(RecordingChannel {
+            name: "ops".to_string(),
+            sent: Mutex::new(Vec::new()),
+        });
+        let router = NotificationRouter::new().with_channel(channel.clone());
+
+        let notification = Notification::new("Daily cost", "$12.00", Severity::Info)
+            .with_content_type("text/markdown");
+        let delivered = router
+            .broadcast(&["ops".to_string(), "missing".to_string()], &notification)
+            .await;
+
+        assert_eq!(delivered, 1);
+        assert_eq!(channel.sent.lock().as_slice(), &[notification]);
+        let stats = router.get_stats();
+        assert_eq!((stats.delivered, stats.failed), (1, 1));
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let plain = Notification::new("Daily cost", "$12.00", Severity::Info);
+        assert_eq!(
+            slack_payload(&plain),
+            serde_json::json!({ "text": "*Daily cost*\n$12.00" })
+        );
+
+        let now = chrono::Utc::now();
+        let document = ReportDocument::new("Cost summary", now - chrono::Duration::days(1), now)
+            .with_summary("Total cost", "12.00 USD");
+        let rich = plain.with_document(document);
+        let payload = slack_payload(&rich);
+        assert_eq!(payload["text"], "Daily cost");
+        assert_eq!(payload["blocks"][0]["text"]["text"], "Cost summary");
+        assert_eq!(
+            payload["blocks"][2]["fields"][0]["text"],
+            "*Total cost*\n12.00 USD"
+        );
+    }
+}
@@ -0,0 +1,757 @@
+//! Alert Digests
+//!
+//! Batches lower-severity alerts into hourly or daily summaries so channels
+//! are not flooded, while alerts at or above a rule's paging severity are
+//! still delivered immediately.
+//!
+//! Each alert is handled by the first rule whose alert types match it; a rule
+//! with no alert types matches every alert. Alerts no rule matches, or that an
+//! active silence matches, are not delivered.
+//!
+//! Notifications are rendered per channel with the templates of the alert rule
+//! that raised the alert, or else of the digest rule. A template that fails to
+//! render falls back to the default notification.
+//!
+//! When a usage meter is attached, every delivery is metered per channel
+//! against the tenants whose alerts it carries.
+
+use super::channels::{Notification, NotificationRouter};
+use super::maintenance::{MaintenanceAction, MaintenanceRegistry, MAINTENANCE_TAG};
+use super::rules::{AlertRuleStore, ALERT_RULE_TAG};
+use super::silences::SilenceRegistry;
+use super::templates::{alert_context, digest_context, NotificationTemplates};
+use crate::metering::{Consumer, UsageMeter};
+use crate::reporting::scheduler::{alert_report, AnomalyDigestBuilder};
+use crate::schemas::events::{AnalyticsEvent, EventPayload, Severity};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// How often pending digests are checked for delivery
+const FLUSH_INTERVAL_SECS: u64 = 60;
+
+/// Period a digest summarizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestInterval {
+    #[default]
+    Hourly,
+    Daily,
+}
+
+impl DigestInterval {
+    pub fn duration(&self) -> Duration {
+        match self {
+            DigestInterval::Hourly => Duration::hours(1),
+            DigestInterval::Daily => Duration::days(1),
+        }
+    }
+
+    /// Start of the UTC-aligned period containing `timestamp`
+    pub fn window_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        timestamp
+            .duration_trunc(self.duration())
+            .unwrap_or(timestamp)
+    }
+}
+
+fn default_page_at() -> Severity {
+    Severity::Critical
+}
+
+/// Which alerts a rule handles and how they are delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestRule {
+    pub name: String,
+    /// Custom alert types (or lowercase event types) matched; empty matches all
+    #[serde(default)]
+    pub alert_types: Vec<String>,
+    /// Notification channel names
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub interval: DigestInterval,
+    /// Alerts at or above this severity are sent immediately
+    #[serde(default = "default_page_at")]
+    pub page_at: Severity,
+    /// Per-channel templates for paged alerts and digests
+    #[serde(default, skip_serializing_if = "NotificationTemplates::is_empty")]
+    pub templates: NotificationTemplates,
+}
+
+impl DigestRule {
+    pub fn new(name: &str, channels: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            alert_types: Vec::new(),
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            interval: DigestInterval::default(),
+            page_at: default_page_at(),
+            templates: NotificationTemplates::default(),
+        }
+    }
+
+    pub fn with_alert_type(mut self, alert_type: &str) -> Self {
+        self.alert_types.push(alert_type.to_string());
+        self
+    }
+
+    pub fn with_interval(mut self, interval: DigestInterval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_page_at(mut self, severity: Severity) -> Self {
+        self.page_at = severity;
+        self
+    }
+
+    pub fn with_templates(mut self, templates: NotificationTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    pub fn matches(&self, alert_type: &str) -> bool {
+        self.alert_types.is_empty() || self.alert_types.iter().any(|t| t == alert_type)
+    }
+}
+
+/// Type an alert is matched on: the custom type, or the event type in lowercase
+pub fn alert_type(event: &AnalyticsEvent) -> String {
+    match &event.payload {
+        EventPayload::Custom(custom) => custom.custom_type.clone(),
+        _ => format!("{:?}", event.common.event_type).to_lowercase(),
+    }
+}
+
+/// What happened to an alert handed to the notifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDisposition {
+    /// Sent immediately
+    Paged,
+    /// Held for the rule's next digest
+    Batched,
+    /// Suppressed by an active silence
+    Silenced,
+    /// Suppressed by an active maintenance window
+    Maintenance,
+    /// No rule matched
+    Unrouted,
+}
+
+/// Alerts batched for one rule and period
+struct PendingDigest {
+    window_start: DateTime<Utc>,
+    highest: Severity,
+    builder: AnomalyDigestBuilder,
+    /// Tenants whose alerts are in the digest
+    tenants: BTreeSet<String>,
+}
+
+/// Digest statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestStats {
+    pub rules: usize,
+    pub alerts_paged: u64,
+    pub alerts_batched: u64,
+    pub alerts_unrouted: u64,
+    pub alerts_silenced: u64,
+    /// Suppressed during maintenance windows
+    pub alerts_in_maintenance: u64,
+    pub digests_sent: u64,
+    pub pending_digests: usize,
+    /// Notifications sent untemplated because their template failed to render
+    pub template_fallbacks: u64,
+}
+
+/// Routes alerts to channels, paging or batching them per rule
+pub struct DigestNotifier {
+    router: Arc<NotificationRouter>,
+    rules: Vec<DigestRule>,
+    silences: Option<Arc<SilenceRegistry>>,
+    maintenance: Option<Arc<MaintenanceRegistry>>,
+    alert_rules: Option<Arc<AlertRuleStore>>,
+    usage: Option<UsageMeter>,
+    pending: Mutex<HashMap<String, PendingDigest>>,
+    alerts_paged: AtomicU64,
+    alerts_batched: AtomicU64,
+    alerts_unrouted: AtomicU64,
+    alerts_silenced: AtomicU64,
+    alerts_in_maintenance: AtomicU64,
+    digests_sent: AtomicU64,
+    template_fallbacks: AtomicU64,
+}
+
+impl DigestNotifier {
+    pub fn new(router: Arc<NotificationRouter>) -> Self {
+        Self {
+            router,
+            rules: Vec::new(),
+            silences: None,
+            maintenance: None,
+            alert_rules: None,
+            usage: None,
+            pending: Mutex::new(HashMap::new()),
+            alerts_paged: AtomicU64::new(0),
+            alerts_batched: AtomicU64::new(0),
+            alerts_unrouted: AtomicU64::new(0),
+            alerts_silenced: AtomicU64::new(0),
+            alerts_in_maintenance: AtomicU64::new(0),
+            digests_sent: AtomicU64::new(0),
+            template_fallbacks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: DigestRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Drop alerts matched by an active silence
+    pub fn with_silences(mut self, silences: Arc<SilenceRegistry>) -> Self {
+        self.silences = Some(silences);
+        self
+    }
+
+    /// Suppress or mark alerts raised during maintenance windows
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceRegistry>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Render alerts raised by an alert rule with that rule's templates
+    pub fn with_alert_rules(mut self, alert_rules: Arc<AlertRuleStore>) -> Self {
+        self.alert_rules = Some(alert_rules);
+        self
+    }
+
+    /// Meter deliveries against the tenants of the alerts they carry
+    pub fn with_usage(mut self, usage: UsageMeter) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Add rules from a YAML list, after any existing rules
+    pub fn load_yaml(&mut self, yaml: &str) -> Result<usize> {
+        let rules: Vec<DigestRule> =
+            serde_yaml::from_str(yaml).context("Failed to parse digest rules")?;
+        for rule in &rules {
+            rule.templates
+                .validate()
+                .with_context(|| format!("Invalid templates for digest rule {}", rule.name))?;
+        }
+        let count = rules.len();
+        self.rules.extend(rules);
+        Ok(count)
+    }
+
+    pub fn rules(&self) -> &[DigestRule] {
+        &self.rules
+    }
+
+    fn rule_for(&self, alert_type: &str) -> Option<&DigestRule> {
+        self.rules.iter().find(|rule| rule.matches(alert_type))
+    }
+
+    /// Templates of the alert rule that raised an alert, if it has any
+    async fn alert_rule_templates(&self, event: &AnalyticsEvent) -> Option<NotificationTemplates> {
+        let store = self.alert_rules.as_ref()?;
+        let rule_id = event.common.tags.get(ALERT_RULE_TAG)?;
+        match store.get(rule_id).await {
+            Ok(rule) if !rule.spec.templates.is_empty() => Some(rule.spec.templates),
+            Ok(_) => None,
+            Err(e) => {
+                debug!(rule_id = %rule_id, "No templates for alert rule: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Deliver to each channel, rendering the notification with `templates`
+    /// and falling back to it unrendered when a template fails
+    async fn deliver(
+        &self,
+        channels: &[String],
+        templates: &NotificationTemplates,
+        context: &serde_json::Value,
+        notification: &Notification,
+        tenants: &BTreeSet<String>,
+    ) -> usize {
+        let mut delivered = 0;
+        for channel in channels {
+            let rendered = if templates.is_empty() {
+                None
+            } else {
+                match templates.render(channel, context, notification) {
+                    Ok(rendered) => Some(rendered),
+                    Err(e) => {
+                        warn!(channel = %channel, "Notification template failed, sending default: {:#}", e);
+                        self.template_fallbacks.fetch_add(1, Ordering::Relaxed);
+                        None
+                    }
+                }
+            };
+            match self
+                .router
+                .send(channel, rendered.as_ref().unwrap_or(notification))
+                .await
+            {
+                Ok(()) => {
+                    delivered += 1;
+                    if let Some(usage) = &self.usage {
+                        for tenant_id in tenants {
+                            usage.record_alert_delivery(
+                                &Consumer::tenant(tenant_id.as_str()),
+                                channel,
+                            );
+                        }
+                    }
+                }
+                Err(e) => warn!(channel = %channel, "Notification delivery failed: {}", e),
+            }
+        }
+        delivered
+    }
+
+    /// Page or batch an alert according to the first matching rule
+    pub async fn notify(&self, event: &AnalyticsEvent) -> AlertDisposition {
+        if let Some(silence_id) = self
+            .silences
+            .as_ref()
+            .and_then(|silences| silences.silenced_by(event, Utc::now()))
+        {
+            debug!(%silence_id, "Alert silenced");
+            self.alerts_silenced.fetch_add(1, Ordering::Relaxed);
+            return AlertDisposition::Silenced;
+        }
+
+        let window = self
+            .maintenance
+            .as_ref()
+            .and_then(|maintenance| maintenance.window_for_event(event, Utc::now()));
+        if let Some(window) = &window {
+            if window.action == MaintenanceAction::Suppress {
+                debug!(window_id = %window.window_id, "Alert suppressed during maintenance");
+                self.alerts_in_maintenance.fetch_add(1, Ordering::Relaxed);
+                return AlertDisposition::Maintenance;
+            }
+        }
+
+        let kind = alert_type(event);
+        let Some(rule) = self.rule_for(&kind) else {
+            self.alerts_unrouted.fetch_add(1, Ordering::Relaxed);
+            return AlertDisposition::Unrouted;
+        };
+
+        let severity = &event.common.severity;
+        if *severity >= rule.page_at {
+            let title = match &window {
+                Some(_) => format!("[{:?}] [maintenance] {}", severity, kind),
+                None => format!("[{:?}] {}", severity, kind),
+            };
+            let mut notification = Notification::new(
+                title,
+                serde_json::to_string_pretty(&event.payload).unwrap_or_default(),
+                severity.clone(),
+            )
+            .with_content_type("application/json")
+            .with_tag("rule", rule.name.clone())
+            .with_document(alert_report(event));
+            if let Some(window) = &window {
+                notification = notification.with_tag(MAINTENANCE_TAG, window.window_id.to_string());
+            }
+            let templates = self
+                .alert_rule_templates(event)
+                .await
+                .unwrap_or_else(|| rule.templates.clone());
+            let context = alert_context(event, &rule.name);
+            let tenants = event
+                .common
+                .tenant_id()
+                .map(String::from)
+                .into_iter()
+                .collect();
+            self.deliver(
+                &rule.channels,
+                &templates,
+                &context,
+                &notification,
+                &tenants,
+            )
+            .await;
+            self.alerts_paged.fetch_add(1, Ordering::Relaxed);
+            return AlertDisposition::Paged;
+        }
+
+        let window_start = rule.interval.window_start(event.common.timestamp);
+        let mut pending = self.pending.lock();
+        let digest = pending
+            .entry(rule.name.clone())
+            .or_insert_with(|| PendingDigest {
+                window_start,
+                highest: severity.clone(),
+                builder: AnomalyDigestBuilder::new(),
+                tenants: BTreeSet::new(),
+            });
+        // Late alerts join the open digest rather than reopening a sent period
+        digest.window_start = digest.window_start.min(window_start);
+        digest.highest = digest.highest.clone().max(severity.clone());
+        digest.builder.observe(event);
+        if let Some(tenant_id) = event.common.tenant_id() {
+            digest.tenants.insert(tenant_id.to_string());
+        }
+        self.alerts_batched.fetch_add(1, Ordering::Relaxed);
+        AlertDisposition::Batched
+    }
+
+    /// Send every digest whose period has ended, returning the number sent
+    pub async fn flush_due(&self, now: DateTime<Utc>) -> usize {
+        let due: Vec<(DigestRule, PendingDigest)> = {
+            let mut pending = self.pending.lock();
+            self.rules
+                .iter()
+                .filter_map(|rule| {
+                    let ready = pending.get(&rule.name).map_or(false, |digest| {
+                        digest.window_start + rule.interval.duration() <= now
+                    });
+                    if ready {
+                        pending
+                            .remove(&rule.name)
+                            .map(|digest| (rule.clone(), digest))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        let mut sent = 0;
+        for (rule, digest) in due {
+            let total = digest.builder.total();
+            let end = (digest.window_start + rule.interval.duration()).min(now);
+            let document = digest.builder.build(digest.window_start, end);
+            let summary = document.to_markdown();
+            let context = digest_context(&rule.name, total, digest.window_start, end, &summary);
+            let notification = Notification::new(
+                format!("Alert digest: {} ({} alerts)", rule.name, total),
+                summary,
+                digest.highest,
+            )
+            .with_content_type("text/markdown")
+            .with_tag("rule", rule.name.clone())
+            .with_document(document);
+
+            if self
+                .deliver(
+                    &rule.channels,
+                    &rule.templates,
+                    &context,
+                    &notification,
+                    &digest.tenants,
+                )
+                .await
+                > 0
+            {
+                sent += 1;
+            } else {
+                warn!(rule = %rule.name, alerts = total, "Alert digest reached no channels");
+            }
+        }
+        self.digests_sent.fetch_add(sent as u64, Ordering::Relaxed);
+        sent
+    }
+
+    /// Flush digests as their periods end until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(std::time::Duration::from_secs(FLUSH_INTERVAL_SECS));
+            loop {
+                ticker.tick().await;
+                let sent = self.flush_due(Utc::now()).await;
+                if sent > 0 {
+                    info!("Sent {} alert digests", sent);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> DigestStats {
+        DigestStats {
+            rules: self.rules.len(),
+            alerts_paged: self.alerts_paged.load(Ordering::Relaxed),
+            alerts_batched: self.alerts_batched.load(Ordering::Relaxed),
+            alerts_unrouted: self.alerts_unrouted.load(Ordering::Relaxed),
+            alerts_silenced: self.alerts_silenced.load(Ordering::Relaxed),
+            alerts_in_maintenance: self.alerts_in_maintenance.load(Ordering::Relaxed),
+            digests_sent: self.digests_sent.load(Ordering::Relaxed),
+            pending_digests: self.pending.lock().len(),
+            template_fallbacks: self.template_fallbacks.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::NotificationChannel;
+    use crate::schemas::events::{CommonEventFields, CustomPayload, EventType, SourceModule};
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+
+    struct RecordingChannel {
+        name: String,
+        sent: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for RecordingChannel {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn send(&self, notification: &Notification) -> Result<()> {
+            self.sent.lock().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    fn alert(custom_type: &str, severity: Severity, timestamp: DateTime<Utc>) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: uuid::Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: custom_type.to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_window_start_and_rules() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 14, 37, 12).unwrap();
+        assert_eq!(
+            DigestInterval::Hourly.window_start(at),
+            Utc.with_ymd_and_hms(2024, 3, 1, 14, 0, 0).unwrap()
+        );
+        assert_eq!(
+            DigestInterval::Daily.window_start(at),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+        );
+
+        let mut notifier = DigestNotifier::new(Arc::new(NotificationRouter::new()));
+        let yaml = r#"
+- name: drift
+  alert_types: [changepoint.detected]
+  channels: [ml-team]
+  interval: daily
+  page_at: error
+- name: everything
+  channels: [ops]
+"#;
+        assert_eq!(notifier.load_yaml(yaml).unwrap(), 2);
+        assert_eq!(notifier.rules()[0].page_at, Severity::Error);
+        assert_eq!(notifier.rules()[1].interval, DigestInterval::Hourly);
+        assert_eq!(
+            notifier.rule_for("changepoint.detected").unwrap().name,
+            "drift"
+        );
+        assert_eq!(notifier.rule_for("lag").unwrap().name, "everything");
+    }
+
+    #[tokio::test]
+    async fn test_batches_until_period_ends() {
+        let channel = Arc::new(RecordingChannel {
+            name: "ops".to_string(),
+            sent: Mutex::new(Vec::new()),
+        });
+        let router = Arc::new(NotificationRouter::new().with_channel(channel.clone()));
+        let notifier = DigestNotifier::new(router)
+            .with_rule(DigestRule::new("ops", &["ops"]).with_alert_type("latency.spike"));
+
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+        for minute in [5, 20, 40] {
+            let event = alert("latency.spike", Severity::Warning, at(9, minute));
+            assert_eq!(notifier.notify(&event).await, AlertDisposition::Batched);
+        }
+        let critical = alert("latency.spike", Severity::Critical, at(9, 45));
+        assert_eq!(notifier.notify(&critical).await, AlertDisposition::Paged);
+        let other = alert("cost.budget", Severity::Warning, at(9, 50));
+        assert_eq!(notifier.notify(&other).await, AlertDisposition::Unrouted);
+
+        // Only the page has been delivered so far
+        assert_eq!(channel.sent.lock().len(), 1);
+        assert_eq!(notifier.flush_due(at(9, 59)).await, 0);
+
+        assert_eq!(notifier.flush_due(at(10, 0)).await, 1);
+        let sent = channel.sent.lock();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].subject, "Alert digest: ops (3 alerts)");
+        assert_eq!(sent[1].severity, Severity::Warning);
+        assert!(sent[1].body.contains("| latency.spike | warning | 3 |"));
+
+        let stats = notifier.get_stats();
+        assert_eq!(
+            (
+                stats.alerts_paged,
+                stats.alerts_batched,
+                stats.alerts_unrouted
+            ),
+            (1, 3, 1)
+        );
+        assert_eq!((stats.digests_sent, stats.pending_digests), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_are_metered_per_tenant() {
+        use crate::metering::UsagePeriod;
+
+        let channel = Arc::new(RecordingChannel {
+            name: "ops".to_string(),
+            sent: Mutex::new(Vec::new()),
+        });
+        let router = Arc::new(NotificationRouter::new().with_channel(channel.clone()));
+        let usage = UsageMeter::new();
+        let notifier = DigestNotifier::new(router)
+            .with_rule(DigestRule::new("ops", &["ops"]))
+            .with_usage(usage.clone());
+
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 5, 0).unwrap();
+        let mut critical = alert("latency.spike", Severity::Critical, at);
+        critical.common.set_tenant("team-a");
+        notifier.notify(&critical).await;
+        for tenant_id in ["team-a", "team-b"] {
+            let mut warning = alert("latency.spike", Severity::Warning, at);
+            warning.common.set_tenant(tenant_id);
+            notifier.notify(&warning).await;
+        }
+        assert_eq!(notifier.flush_due(at + Duration::hours(1)).await, 1);
+
+        let period = UsagePeriod::current();
+        let team_a = usage
+            .get_record(&Consumer::tenant("team-a"), period)
+            .unwrap();
+        assert_eq!(team_a.alert_deliveries_by_channel["ops"], 2);
+        let team_b = usage
+            .get_record(&Consumer::tenant("team-b"), period)
+            .unwrap();
+        assert_eq!(team_b.alert_deliveries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_windows_suppress_or_mark_pages() {
+        use crate::alerting::{MaintenanceAction, MaintenanceWindow};
+
+        let channel = Arc::new(RecordingChannel {
+            name: "ops".to_string(),
+            sent: Mutex::new(Vec::new()),
+        });
+        let router = Arc::new(NotificationRouter::new().with_channel(channel.clone()));
+        let maintenance = Arc::new(MaintenanceRegistry::new());
+        let notifier = DigestNotifier::new(router)
+            .with_rule(DigestRule::new("ops", &["ops"]))
+            .with_maintenance(maintenance.clone());
+
+        let now = Utc::now();
+        let deploy = MaintenanceWindow::new(
+            "test",
+            now - chrono::Duration::minutes(5),
+            chrono::Duration::hours(1),
+        );
+        let deploy_id = deploy.window_id;
+        maintenance.add(deploy).unwrap();
+        let critical = alert("latency.spike", Severity::Critical, now);
+        assert_eq!(
+            notifier.notify(&critical).await,
+            AlertDisposition::Maintenance
+        );
+        assert!(channel.sent.lock().is_empty());
+
+        maintenance.end(deploy_id, now);
+        maintenance
+            .add(
+                MaintenanceWindow::new(
+                    "test",
+                    now - chrono::Duration::minutes(5),
+                    chrono::Duration::hours(1),
+                )
+                .with_action(MaintenanceAction::Tag),
+            )
+            .unwrap();
+        assert_eq!(notifier.notify(&critical).await, AlertDisposition::Paged);
+        let sent = channel.sent.lock();
+        assert!(sent[0].subject.contains("[maintenance]"));
+        assert!(sent[0].tags.contains_key(MAINTENANCE_TAG));
+        assert_eq!(notifier.get_stats().alerts_in_maintenance, 1);
+    }
+
+    #[tokio::test]
+    async fn test_templates_render_per_channel_with_fallback() {
+        use crate::alerting::templates::NotificationTemplate;
+
+        let recording = |name: &str| {
+            Arc::new(RecordingChannel {
+                name: name.to_string(),
+                sent: Mutex::new(Vec::new()),
+            })
+        };
+        let (slack, pager) = (recording("slack"), recording("pager"));
+        let router = Arc::new(
+            NotificationRouter::new()
+                .with_channel(slack.clone())
+                .with_channel(pager.clone()),
+        );
+        let templates = NotificationTemplates {
+            default: None,
+            channels: HashMap::from([
+                (
+                    "slack".to_string(),
+                    NotificationTemplate {
+                        subject: Some("{{ alert_type }} in {{ environment }}".to_string()),
+                        body: Some("Rule {{ rule }} paged {{ channel }}".to_string()),
+                        content_type: None,
+                    },
+                ),
+                (
+                    "pager".to_string(),
+                    NotificationTemplate {
+                        subject: Some("{{ missing }}".to_string()),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        };
+        let notifier = DigestNotifier::new(router)
+            .with_rule(DigestRule::new("ops", &["slack", "pager"]).with_templates(templates));
+
+        let event = alert("latency.spike", Severity::Critical, Utc::now());
+        assert_eq!(notifier.notify(&event).await, AlertDisposition::Paged);
+
+        let slack_sent = slack.sent.lock();
+        assert_eq!(slack_sent[0].subject, "latency.spike in test");
+        assert_eq!(slack_sent[0].body, "Rule ops paged slack");
+        assert_eq!(slack_sent[0].content_type, "text/plain");
+        assert!(slack_sent[0].document.is_none());
+
+        // The pager template renders an empty subject, so the default is sent
+        let pager_sent = pager.sent.lock();
+        assert_eq!(pager_sent[0].subject, "[Critical] latency.spike");
+        assert!(pager_sent[0].document.is_some());
+        assert_eq!(notifier.get_stats().template_fallbacks, 1);
+    }
+}
@@ -0,0 +1,731 @@
+//! Incidents
+//!
+//! Groups related anomalies and alerts into incidents so they are reviewed
+//! together rather than one finding at a time. A new signal joins an open
+//! incident in the same environment that shares its correlation ID, or that
+//! had a signal within the grouping window and either touches a related
+//! topology entity (the same one, or one depending on the other) or saw the
+//! same kind of finding. Otherwise it opens a new incident.
+//!
+//! Incidents move from open to acknowledged to resolved, and keep a timeline
+//! of their signals, status changes, and notes.
+
+use super::digest::alert_type;
+use super::ticketing::TicketRef;
+use super::webhooks::{WebhookDispatcher, WebhookEvent};
+use crate::analytics::topology::{EntityKind, EntityRef, TopologyStore};
+use crate::database::environment::default_environment;
+use crate::database::{AnomalyRow, Database};
+use crate::schemas::events::{AnalyticsEvent, Severity};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Entity kinds recognised in signal tags, keyed by the kind's name
+const TAGGED_ENTITY_KINDS: [EntityKind; 4] = [
+    EntityKind::Provider,
+    EntityKind::Model,
+    EntityKind::Pipeline,
+    EntityKind::Team,
+];
+
+/// Anomalies read per poll
+const ANOMALY_POLL_LIMIT: i64 = 1_000;
+
+/// How signals are grouped into incidents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentConfig {
+    /// Longest gap between related signals of one incident
+    pub grouping_window_secs: i64,
+    /// Seconds between polls for newly stored anomalies
+    pub poll_interval_secs: u64,
+    /// Resolved incidents are kept in memory this long
+    pub retention_hours: i64,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        Self {
+            grouping_window_secs: 1800,
+            poll_interval_secs: 30,
+            retention_hours: 24 * 7,
+        }
+    }
+}
+
+impl IncidentConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            grouping_window_secs: std::env::var("INCIDENT_GROUPING_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.grouping_window_secs),
+            poll_interval_secs: std::env::var("INCIDENT_POLL_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.poll_interval_secs),
+            retention_hours: std::env::var("INCIDENT_RETENTION_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.retention_hours),
+        }
+    }
+
+    fn grouping_window(&self) -> Duration {
+        Duration::seconds(self.grouping_window_secs.max(0))
+    }
+}
+
+/// Lifecycle state of an incident
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentStatus {
+    #[default]
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+impl IncidentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentStatus::Open => "open",
+            IncidentStatus::Acknowledged => "acknowledged",
+            IncidentStatus::Resolved => "resolved",
+        }
+    }
+}
+
+impl std::str::FromStr for IncidentStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Invalid incident status: {}", s))
+    }
+}
+
+/// Kind of finding grouped into an incident
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalKind {
+    Anomaly,
+    Alert,
+}
+
+/// An anomaly or alert as seen by incident grouping
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentSignal {
+    pub kind: SignalKind,
+    /// Anomaly ID or alert event ID
+    pub signal_id: Uuid,
+    pub at: DateTime<Utc>,
+    /// Metric name for anomalies, alert type for alerts
+    pub summary: String,
+    pub severity: Severity,
+    pub environment: String,
+    pub correlation_id: Option<Uuid>,
+    /// Topology entities named in the signal's tags
+    #[serde(default)]
+    pub entities: Vec<EntityRef>,
+}
+
+impl IncidentSignal {
+    pub fn from_alert(event: &AnalyticsEvent) -> Self {
+        Self {
+            kind: SignalKind::Alert,
+            signal_id: event.common.event_id,
+            at: event.common.timestamp,
+            summary: alert_type(event),
+            severity: event.common.severity.clone(),
+            environment: event.common.environment.clone(),
+            correlation_id: event.common.correlation_id,
+            entities: TAGGED_ENTITY_KINDS
+                .iter()
+                .filter_map(|kind| {
+                    let id = event.common.tags.get(kind.as_str())?;
+                    Some(EntityRef::new(*kind, id.clone()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Signal for a stored anomaly, reading its environment, correlation ID,
+    /// and tags from the anomaly's context when present
+    pub fn from_anomaly(row: &AnomalyRow, default_environment: &str) -> Self {
+        let context = &row.context;
+        let severity = match row.severity.to_lowercase().as_str() {
+            "critical" => Severity::Critical,
+            "high" => Severity::Error,
+            "medium" => Severity::Warning,
+            "low" => Severity::Info,
+            other => serde_json::from_value(serde_json::Value::String(other.to_string()))
+                .unwrap_or(Severity::Warning),
+        };
+        Self {
+            kind: SignalKind::Anomaly,
+            signal_id: row.anomaly_id,
+            at: row.detected_at,
+            summary: row.metric_name.clone(),
+            severity,
+            environment: context["environment"]
+                .as_str()
+                .unwrap_or(default_environment)
+                .to_string(),
+            correlation_id: context["correlation_id"]
+                .as_str()
+                .and_then(|id| id.parse().ok()),
+            entities: TAGGED_ENTITY_KINDS
+                .iter()
+                .filter_map(|kind| {
+                    let id = context["tags"][kind.as_str()].as_str()?;
+                    Some(EntityRef::new(*kind, id))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Something that happened to an incident
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    Signal {
+        signal: IncidentSignal,
+    },
+    StatusChanged {
+        status: IncidentStatus,
+        by: Option<String>,
+        note: Option<String>,
+    },
+    Note {
+        by: Option<String>,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+/// Related anomalies and alerts reviewed as one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    pub incident_id: Uuid,
+    pub title: String,
+    pub status: IncidentStatus,
+    /// Highest severity of the incident's signals
+    pub severity: Severity,
+    pub environment: String,
+    pub assignee: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Time of the latest signal, or of opening when there are none
+    pub last_signal_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub correlation_ids: BTreeSet<Uuid>,
+    pub entities: BTreeSet<EntityRef>,
+    /// Distinct signal summaries seen
+    pub summaries: BTreeSet<String>,
+    pub signal_count: u64,
+    pub timeline: Vec<TimelineEntry>,
+    /// Jira or ServiceNow ticket raised for the incident
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<TicketRef>,
+}
+
+impl Incident {
+    /// An open incident with no signals, as created by hand
+    pub fn new(
+        title: impl Into<String>,
+        environment: impl Into<String>,
+        severity: Severity,
+        at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            incident_id: Uuid::new_v4(),
+            title: title.into(),
+            status: IncidentStatus::Open,
+            severity,
+            environment: environment.into(),
+            assignee: None,
+            opened_at: at,
+            updated_at: at,
+            last_signal_at: at,
+            resolved_at: None,
+            correlation_ids: BTreeSet::new(),
+            entities: BTreeSet::new(),
+            summaries: BTreeSet::new(),
+            signal_count: 0,
+            timeline: Vec::new(),
+            ticket: None,
+        }
+    }
+
+    /// An incident opened by `signal`
+    pub fn from_signal(signal: IncidentSignal) -> Self {
+        let title = match signal.kind {
+            SignalKind::Anomaly => format!("Anomaly in {}", signal.summary),
+            SignalKind::Alert => signal.summary.clone(),
+        };
+        let mut incident = Self::new(
+            title,
+            signal.environment.clone(),
+            signal.severity.clone(),
+            signal.at,
+        );
+        incident.add_signal(signal);
+        incident
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            bail!("An incident needs a title");
+        }
+        if self.environment.trim().is_empty() {
+            bail!("An incident needs an environment");
+        }
+        Ok(())
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.status == IncidentStatus::Resolved
+    }
+
+    pub fn has_signal(&self, signal_id: Uuid) -> bool {
+        self.signals().any(|s| s.signal_id == signal_id)
+    }
+
+    pub fn signals(&self) -> impl Iterator<Item = &IncidentSignal> {
+        self.timeline.iter().filter_map(|entry| match &entry.event {
+            TimelineEvent::Signal { signal } => Some(signal),
+            _ => None,
+        })
+    }
+
+    pub fn add_signal(&mut self, signal: IncidentSignal) {
+        self.severity = self.severity.clone().max(signal.severity.clone());
+        self.correlation_ids.extend(signal.correlation_id);
+        self.entities.extend(signal.entities.iter().cloned());
+        self.summaries.insert(signal.summary.clone());
+        self.signal_count += 1;
+        self.last_signal_at = self.last_signal_at.max(signal.at);
+        self.updated_at = self.updated_at.max(signal.at);
+        self.push(signal.at, TimelineEvent::Signal { signal });
+    }
+
+    /// Move to `status`, recording who did so. Resolved incidents can be
+    /// reopened; acknowledging one is rejected.
+    pub fn set_status(
+        &mut self,
+        status: IncidentStatus,
+        by: Option<String>,
+        note: Option<String>,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        if status == self.status {
+            bail!("Incident is already {}", status.as_str());
+        }
+        if self.status == IncidentStatus::Resolved && status == IncidentStatus::Acknowledged {
+            bail!("Resolved incidents must be reopened before they are acknowledged");
+        }
+        self.status = status;
+        self.resolved_at = (status == IncidentStatus::Resolved).then_some(at);
+        self.updated_at = at;
+        self.push(at, TimelineEvent::StatusChanged { status, by, note });
+        Ok(())
+    }
+
+    pub fn add_note(&mut self, by: Option<String>, text: String, at: DateTime<Utc>) {
+        self.updated_at = at;
+        self.push(at, TimelineEvent::Note { by, text });
+    }
+
+    // Entries stay ordered by time even when signals arrive late
+    fn push(&mut self, at: DateTime<Utc>, event: TimelineEvent) {
+        let index = self.timeline.partition_point(|entry| entry.at <= at);
+        self.timeline.insert(index, TimelineEntry { at, event });
+    }
+}
+
+/// In-memory incidents, grouping signals as they arrive
+pub struct IncidentTracker {
+    config: IncidentConfig,
+    incidents: RwLock<Vec<Incident>>,
+    topology: Option<Arc<TopologyStore>>,
+}
+
+impl IncidentTracker {
+    pub fn new(config: IncidentConfig) -> Self {
+        Self {
+            config,
+            incidents: RwLock::new(Vec::new()),
+            topology: None,
+        }
+    }
+
+    /// Group signals whose entities depend on one another
+    pub fn with_topology(mut self, topology: Arc<TopologyStore>) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    pub fn config(&self) -> &IncidentConfig {
+        &self.config
+    }
+
+    /// Replace all incidents, e.g. with those loaded from storage
+    pub fn replace(&self, incidents: Vec<Incident>) {
+        *self.incidents.write() = incidents;
+    }
+
+    /// Group `signal` into an incident, returning the incident it joined or
+    /// opened, or `None` when the signal was already recorded
+    pub fn record(&self, signal: IncidentSignal) -> Option<Incident> {
+        let mut incidents = self.incidents.write();
+        if incidents.iter().any(|i| i.has_signal(signal.signal_id)) {
+            return None;
+        }
+
+        let window = self.config.grouping_window();
+        let topology = self.topology.as_ref().map(|store| store.current());
+        let related_entities = |incident: &Incident| {
+            signal.entities.iter().any(|entity| {
+                incident.entities.iter().any(|other| match &topology {
+                    Some(topology) => topology.related(entity, other),
+                    None => entity == other,
+                })
+            })
+        };
+
+        let joined = incidents
+            .iter_mut()
+            .filter(|i| !i.is_resolved() && i.environment == signal.environment)
+            .filter(|i| {
+                let i: &Incident = i;
+                let shares_correlation = signal
+                    .correlation_id
+                    .map_or(false, |id| i.correlation_ids.contains(&id));
+                let recent = (signal.at - i.last_signal_at).abs() <= window;
+                shares_correlation
+                    || (recent && (related_entities(i) || i.summaries.contains(&signal.summary)))
+            })
+            .max_by_key(|i| i.last_signal_at);
+
+        let incident = match joined {
+            Some(incident) => {
+                incident.add_signal(signal);
+                incident.clone()
+            }
+            None => {
+                let incident = Incident::from_signal(signal);
+                debug!(incident_id = %incident.incident_id, title = %incident.title, "Opened incident");
+                incidents.push(incident.clone());
+                incident
+            }
+        };
+        Some(incident)
+    }
+
+    pub fn add(&self, incident: Incident) -> Result<()> {
+        incident.validate()?;
+        self.incidents.write().push(incident);
+        Ok(())
+    }
+
+    pub fn get(&self, incident_id: Uuid) -> Option<Incident> {
+        self.incidents
+            .read()
+            .iter()
+            .find(|i| i.incident_id == incident_id)
+            .cloned()
+    }
+
+    /// Incidents newest first, optionally only those in `status`
+    pub fn list(&self, status: Option<IncidentStatus>) -> Vec<Incident> {
+        let mut incidents: Vec<Incident> = self
+            .incidents
+            .read()
+            .iter()
+            .filter(|i| status.map_or(true, |s| i.status == s))
+            .cloned()
+            .collect();
+        incidents.sort_by(|a, b| b.last_signal_at.cmp(&a.last_signal_at));
+        incidents
+    }
+
+    /// Apply `change` to an incident, returning it updated
+    pub fn update(
+        &self,
+        incident_id: Uuid,
+        change: impl FnOnce(&mut Incident) -> Result<()>,
+    ) -> Result<Incident> {
+        let mut incidents = self.incidents.write();
+        let incident = incidents
+            .iter_mut()
+            .find(|i| i.incident_id == incident_id)
+            .with_context(|| format!("No incident {}", incident_id))?;
+        let mut updated = incident.clone();
+        change(&mut updated)?;
+        updated.validate()?;
+        *incident = updated.clone();
+        Ok(updated)
+    }
+
+    pub fn remove(&self, incident_id: Uuid) -> Option<Incident> {
+        let mut incidents = self.incidents.write();
+        let index = incidents
+            .iter()
+            .position(|i| i.incident_id == incident_id)?;
+        Some(incidents.remove(index))
+    }
+
+    /// Drop incidents resolved longer ago than the retention period,
+    /// returning how many were dropped
+    pub fn prune(&self, now: DateTime<Utc>) -> usize {
+        let cutoff = now - Duration::hours(self.config.retention_hours.max(0));
+        let mut incidents = self.incidents.write();
+        let before = incidents.len();
+        incidents.retain(|i| i.resolved_at.map_or(true, |at| at > cutoff));
+        before - incidents.len()
+    }
+}
+
+/// Feeds newly stored anomalies into the tracker and persists the incidents
+/// they change
+pub struct IncidentSync {
+    database: Arc<Database>,
+    tracker: Arc<IncidentTracker>,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    cursor: Mutex<DateTime<Utc>>,
+    polls: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl IncidentSync {
+    /// Sync starting from anomalies detected at `since`
+    pub fn new(
+        database: Arc<Database>,
+        tracker: Arc<IncidentTracker>,
+        since: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            database,
+            tracker,
+            webhooks: None,
+            cursor: Mutex::new(since),
+            polls: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Push new anomalies and the incidents they change to webhooks
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Group anomalies detected since the last poll, returning the number of
+    /// incidents opened or updated
+    pub async fn poll(&self) -> Result<usize> {
+        let since = *self.cursor.lock();
+        let mut rows = self
+            .database
+            .query_recent_anomalies(since, Some(ANOMALY_POLL_LIMIT))
+            .await?;
+        rows.sort_by_key(|row| row.detected_at);
+
+        let environment = default_environment();
+        let mut changed: Vec<Incident> = Vec::new();
+        for row in &rows {
+            let signal = IncidentSignal::from_anomaly(row, &environment);
+            let event = WebhookEvent::from_anomaly(row, &signal);
+            if let Some(incident) = self.tracker.record(signal) {
+                if let Some(webhooks) = &self.webhooks {
+                    webhooks.publish(event).await;
+                }
+                changed.retain(|i| i.incident_id != incident.incident_id);
+                changed.push(incident);
+            }
+        }
+        for incident in &changed {
+            self.database.upsert_incident(incident).await?;
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.publish(WebhookEvent::from_incident(incident)).await;
+            }
+        }
+
+        // Anomalies at the cursor are read again next time and skipped as duplicates
+        if let Some(last) = rows.last() {
+            *self.cursor.lock() = last.detected_at;
+        }
+        self.tracker.prune(Utc::now());
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        Ok(changed.len())
+    }
+
+    /// Poll on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period =
+            std::time::Duration::from_secs(self.tracker.config().poll_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.poll().await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Incident grouping of new anomalies failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> IncidentSyncStats {
+        IncidentSyncStats {
+            polls: self.polls.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Incident sync statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentSyncStats {
+    pub polls: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(summary: &str, minute: i64) -> IncidentSignal {
+        IncidentSignal {
+            kind: SignalKind::Anomaly,
+            signal_id: Uuid::new_v4(),
+            at: DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minute),
+            summary: summary.to_string(),
+            severity: Severity::Warning,
+            environment: "production".to_string(),
+            correlation_id: None,
+            entities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_signals_group_by_correlation_entity_and_time() {
+        let tracker = IncidentTracker::new(IncidentConfig::default());
+        let correlation_id = Uuid::new_v4();
+
+        let mut first = signal("latency_ms", 0);
+        first.entities = vec![EntityRef::new(EntityKind::Model, "gpt-4")];
+        let opened = tracker.record(first.clone()).unwrap();
+        assert_eq!(opened.title, "Anomaly in latency_ms");
+        assert!(tracker.record(first).is_none());
+
+        // Same entity within the window joins, whatever the metric
+        let mut errors = signal("error_rate", 10);
+        errors.entities = vec![EntityRef::new(EntityKind::Model, "gpt-4")];
+        errors.severity = Severity::Critical;
+        errors.correlation_id = Some(correlation_id);
+        let joined = tracker.record(errors).unwrap();
+        assert_eq!(joined.incident_id, opened.incident_id);
+        assert_eq!(joined.severity, Severity::Critical);
+
+        // Unrelated findings and other environments open their own incidents
+        let cost = tracker.record(signal("cost_usd", 12)).unwrap();
+        assert_ne!(cost.incident_id, opened.incident_id);
+        let mut staging = signal("latency_ms", 12);
+        staging.environment = "staging".to_string();
+        assert_ne!(
+            tracker.record(staging).unwrap().incident_id,
+            opened.incident_id
+        );
+
+        // A shared correlation ID joins long after the window
+        let mut alert = signal("provider.outage", 240);
+        alert.kind = SignalKind::Alert;
+        alert.correlation_id = Some(correlation_id);
+        assert_eq!(
+            tracker.record(alert).unwrap().incident_id,
+            opened.incident_id
+        );
+        // Without one, the same metric hours later starts afresh
+        assert_ne!(
+            tracker
+                .record(signal("latency_ms", 300))
+                .unwrap()
+                .incident_id,
+            opened.incident_id
+        );
+        assert_eq!(tracker.list(None).len(), 4);
+        assert_eq!(tracker.get(opened.incident_id).unwrap().signal_count, 3);
+    }
+
+    #[test]
+    fn test_lifecycle_and_timeline() {
+        let tracker = IncidentTracker::new(IncidentConfig::default());
+        let incident = tracker.record(signal("latency_ms", 5)).unwrap();
+        let at = incident.opened_at;
+        let id = incident.incident_id;
+
+        let acked = tracker
+            .update(id, |i| {
+                i.set_status(
+                    IncidentStatus::Acknowledged,
+                    Some("oncall".into()),
+                    None,
+                    at,
+                )
+            })
+            .unwrap();
+        assert_eq!(acked.status, IncidentStatus::Acknowledged);
+        assert!(tracker
+            .update(id, |i| i.set_status(
+                IncidentStatus::Acknowledged,
+                None,
+                None,
+                at
+            ))
+            .is_err());
+
+        let resolved = tracker
+            .update(id, |i| {
+                i.add_note(Some("oncall".into()), "Rolled back".into(), at);
+                i.set_status(
+                    IncidentStatus::Resolved,
+                    None,
+                    None,
+                    at + Duration::hours(1),
+                )
+            })
+            .unwrap();
+        assert_eq!(resolved.resolved_at, Some(at + Duration::hours(1)));
+        assert_eq!(resolved.timeline.len(), 4);
+        // Resolved incidents take no more signals
+        let late = tracker.record(signal("latency_ms", 0)).unwrap();
+        assert_ne!(late.incident_id, id);
+        assert!(matches!(
+            resolved.timeline[0].event,
+            TimelineEvent::Signal { .. }
+        ));
+
+        assert_eq!(tracker.prune(at + Duration::days(30)), 1);
+        assert!(tracker.get(id).is_none());
+        assert_eq!(
+            "Resolved".parse::<IncidentStatus>().unwrap(),
+            IncidentStatus::Resolved
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Maintenance Windows
+//!
+//! Scheduled periods, such as deploys, during which an environment or one of
+//! its services is expected to misbehave. Findings raised inside a window are
+//! still recorded, tagged with the window's ID for later review, but are
+//! either suppressed or delivered tagged depending on the window's action.
+//! Alerts and metrics name their service with the `service` tag.
+
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tag naming the service an alert or metric belongs to
+pub const SERVICE_TAG: &str = "service";
+
+/// Tag added to findings raised during a maintenance window
+pub const MAINTENANCE_TAG: &str = "maintenance_window";
+
+/// What happens to findings raised during a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceAction {
+    /// Record the finding but do not notify
+    #[default]
+    Suppress,
+    /// Notify as usual, marked as raised during maintenance
+    Tag,
+}
+
+impl MaintenanceAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceAction::Suppress => "suppress",
+            MaintenanceAction::Tag => "tag",
+        }
+    }
+}
+
+impl std::str::FromStr for MaintenanceAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Invalid maintenance action: {}", s))
+    }
+}
+
+/// A scheduled maintenance period for an environment or one of its services
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub window_id: Uuid,
+    pub environment: String,
+    /// Service under maintenance; `None` covers the whole environment
+    pub service: Option<String>,
+    #[serde(default)]
+    pub action: MaintenanceAction,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_by: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl MaintenanceWindow {
+    /// A window over `environment` starting at `starts_at` and lasting `duration`
+    pub fn new(
+        environment: impl Into<String>,
+        starts_at: DateTime<Utc>,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            window_id: Uuid::new_v4(),
+            environment: environment.into(),
+            service: None,
+            action: MaintenanceAction::default(),
+            starts_at,
+            ends_at: starts_at + duration,
+            created_by: None,
+            comment: None,
+        }
+    }
+
+    pub fn with_service(mut self, service: impl Into<String>) -> Self {
+        self.service = Some(service.into());
+        self
+    }
+
+    pub fn with_action(mut self, action: MaintenanceAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn with_created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Reject windows without an environment or that would never be active
+    pub fn validate(&self) -> Result<()> {
+        if self.environment.trim().is_empty() {
+            bail!("A maintenance window needs an environment");
+        }
+        if self
+            .service
+            .as_deref()
+            .map_or(false, |s| s.trim().is_empty())
+        {
+            bail!("A maintenance window's service cannot be empty");
+        }
+        if self.ends_at <= self.starts_at {
+            bail!("A maintenance window must end after it starts");
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self, at: DateTime<Utc>) -> bool {
+        self.starts_at <= at && at < self.ends_at
+    }
+
+    /// Whether the window covers `service` in `environment`
+    pub fn covers(&self, environment: &str, service: Option<&str>) -> bool {
+        self.environment == environment
+            && self.service.as_deref().map_or(true, |s| service == Some(s))
+    }
+}
+
+/// In-memory set of maintenance windows consulted by detectors and notifiers
+#[derive(Debug, Default)]
+pub struct MaintenanceRegistry {
+    windows: RwLock<Vec<MaintenanceWindow>>,
+}
+
+impl MaintenanceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace all windows, e.g. with those loaded from storage
+    pub fn replace(&self, windows: Vec<MaintenanceWindow>) {
+        *self.windows.write() = windows;
+    }
+
+    pub fn add(&self, window: MaintenanceWindow) -> Result<()> {
+        window.validate()?;
+        self.windows.write().push(window);
+        Ok(())
+    }
+
+    /// End a window now, or cancel it if it has not started, returning
+    /// whether it had not yet ended
+    pub fn end(&self, window_id: Uuid, now: DateTime<Utc>) -> bool {
+        let mut windows = self.windows.write();
+        match windows
+            .iter_mut()
+            .find(|w| w.window_id == window_id && w.ends_at > now)
+        {
+            Some(window) => {
+                window.starts_at = window.starts_at.min(now);
+                window.ends_at = now;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Windows in effect or scheduled, dropping those that have ended
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<MaintenanceWindow> {
+        let mut windows = self.windows.write();
+        windows.retain(|w| w.ends_at > now);
+        windows.clone()
+    }
+
+    /// The window covering `service` in `environment` at `at`, preferring
+    /// one that suppresses when several overlap
+    pub fn window_for(
+        &self,
+        environment: &str,
+        service: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Option<MaintenanceWindow> {
+        self.windows
+            .read()
+            .iter()
+            .filter(|w| w.is_active(at) && w.covers(environment, service))
+            .min_by_key(|w| w.action != MaintenanceAction::Suppress)
+            .cloned()
+    }
+
+    /// The window covering an alert's environment and service at `at`
+    pub fn window_for_event(
+        &self,
+        event: &AnalyticsEvent,
+        at: DateTime<Utc>,
+    ) -> Option<MaintenanceWindow> {
+        let service = event.common.tags.get(SERVICE_TAG).map(String::as_str);
+        self.window_for(&event.common.environment, service, at)
+    }
+
+    /// Tag an alert raised during a window with the window's ID so it can be
+    /// reviewed later, returning the window
+    pub fn annotate(
+        &self,
+        event: &mut AnalyticsEvent,
+        at: DateTime<Utc>,
+    ) -> Option<MaintenanceWindow> {
+        let window = self.window_for_event(event, at)?;
+        event
+            .common
+            .tags
+            .insert(MAINTENANCE_TAG.to_string(), window.window_id.to_string());
+        Some(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+    };
+
+    fn alert(environment: &str, service: Option<&str>) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Warning,
+                environment: environment.to_string(),
+                tags: service
+                    .map(|s| (SERVICE_TAG.to_string(), s.to_string()))
+                    .into_iter()
+                    .collect(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "changepoint.detected".to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_windows_cover_environment_and_service() {
+        let registry = MaintenanceRegistry::new();
+        let now = Utc::now();
+        let deploy = MaintenanceWindow::new("production", now, Duration::minutes(30))
+            .with_service("gateway")
+            .with_action(MaintenanceAction::Tag);
+        let migration =
+            MaintenanceWindow::new("staging", now + Duration::hours(1), Duration::hours(1));
+        let (deploy_id, migration_id) = (deploy.window_id, migration.window_id);
+        registry.add(deploy).unwrap();
+        registry.add(migration).unwrap();
+        assert!(registry
+            .add(MaintenanceWindow::new("", now, Duration::hours(1)))
+            .is_err());
+
+        let mut gateway = alert("production", Some("gateway"));
+        let window = registry.annotate(&mut gateway, now).unwrap();
+        assert_eq!(window.action, MaintenanceAction::Tag);
+        assert_eq!(
+            gateway.common.tags.get(MAINTENANCE_TAG),
+            Some(&deploy_id.to_string())
+        );
+        assert!(registry
+            .window_for_event(&alert("production", Some("router")), now)
+            .is_none());
+        assert!(registry
+            .window_for_event(&alert("production", None), now)
+            .is_none());
+
+        // Environment-wide windows cover every service once they start
+        let staging = alert("staging", Some("gateway"));
+        assert!(registry.window_for_event(&staging, now).is_none());
+        assert_eq!(
+            registry
+                .window_for_event(&staging, now + Duration::minutes(90))
+                .map(|w| w.window_id),
+            Some(migration_id)
+        );
+
+        // Scheduled windows can be cancelled before they start
+        assert!(registry.end(migration_id, now));
+        assert!(!registry.end(migration_id, now));
+        assert!(registry
+            .window_for_event(&staging, now + Duration::minutes(90))
+            .is_none());
+        assert_eq!(registry.active(now + Duration::minutes(1)).len(), 1);
+        assert_eq!(
+            "TAG".parse::<MaintenanceAction>().unwrap(),
+            MaintenanceAction::Tag
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Alerting
+//!
+//! Delivery of alerts and reports to the notification channels configured in
+//! LLM-Config-Manager.
+
+pub mod bundles;
+pub mod channels;
+pub mod digest;
+pub mod incidents;
+pub mod maintenance;
+pub mod rules;
+pub mod silences;
+pub mod templates;
+pub mod ticketing;
+pub mod webhooks;
+
+pub use bundles::{
+    BundleSource, RuleBundle, RuleBundleConfig, RuleBundleLoader, RuleBundleStats, SyncReport,
+};
+pub use channels::{
+    channel_from_config, Notification, NotificationChannel, NotificationRouter, NotificationStats,
+};
+pub use digest::{AlertDisposition, DigestInterval, DigestNotifier, DigestRule, DigestStats};
+pub use incidents::{
+    Incident, IncidentConfig, IncidentSignal, IncidentStatus, IncidentSync, IncidentTracker,
+    TimelineEntry,
+};
+pub use maintenance::{MaintenanceAction, MaintenanceRegistry, MaintenanceWindow};
+pub use rules::{
+    AlertCondition, AlertRule, AlertRuleConfig, AlertRuleRevision, AlertRuleSpec, AlertRuleStore,
+    Comparator, RuleChange, RuleError, RuleWrite,
+};
+pub use silences::{Silence, SilenceRegistry};
+pub use templates::{NotificationTemplate, NotificationTemplates};
+pub use ticketing::{
+    ticket_client_from_config, TicketClient, TicketProvider, TicketRef, TicketSync,
+    TicketSyncStats, TicketingConfig,
+};
+pub use webhooks::{
+    DeliveryStatus, WebhookConfig, WebhookDelivery, WebhookDispatcher, WebhookEvent,
+    WebhookEventType, WebhookStats, WebhookSubscription,
+};
@@ -0,0 +1,675 @@
+//! Alert Rules
+//!
+//! Threshold rules over aggregated metrics, managed at runtime through the
+//! API. A rule's expression is a derived-metric expression compared against a
+//! constant, e.g. `p95(latency_ms) > 2000` or `errors / requests >= 0.05`.
+//!
+//! Rules are versioned: every create, update, and delete writes a revision
+//! alongside the rule in one transaction, and updates and deletes must name
+//! the version they were based on so concurrent edits cannot silently
+//! overwrite each other. Each change is also published as an audit trail event.
+
+use crate::alerting::templates::NotificationTemplates;
+use crate::analytics::derived::DerivedMetric;
+use crate::audit::{self, UNKNOWN_ACTOR};
+use crate::database::Database;
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::schemas::events::{AnalyticsEvent, AuditTrailEvent, Severity};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::info;
+
+/// Tag alerts raised by a rule carry, holding the rule id
+pub const ALERT_RULE_TAG: &str = "alert_rule";
+
+/// Longest accepted rule id
+const MAX_RULE_ID_LEN: usize = 64;
+
+/// Comparison between a rule's expression and its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparator {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Comparator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparator::Gt => ">",
+            Comparator::Ge => ">=",
+            Comparator::Lt => "<",
+            Comparator::Le => "<=",
+        }
+    }
+
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Gt => value > threshold,
+            Comparator::Ge => value >= threshold,
+            Comparator::Lt => value < threshold,
+            Comparator::Le => value <= threshold,
+        }
+    }
+}
+
+/// Compiled rule expression
+#[derive(Debug, Clone)]
+pub struct AlertCondition {
+    metric: DerivedMetric,
+    pub comparator: Comparator,
+    pub threshold: f64,
+}
+
+impl AlertCondition {
+    /// Parse `<expression> <comparator> <number>`
+    pub fn parse(expression: &str) -> Result<Self> {
+        let Some(pos) = expression.find(['<', '>']) else {
+            bail!("Expression must compare against a threshold with >, >=, < or <=");
+        };
+        let (lhs, rest) = expression.split_at(pos);
+        let (comparator, rhs) = match rest.as_bytes() {
+            [b'>', b'=', ..] => (Comparator::Ge, &rest[2..]),
+            [b'<', b'=', ..] => (Comparator::Le, &rest[2..]),
+            [b'>', ..] => (Comparator::Gt, &rest[1..]),
+            _ => (Comparator::Lt, &rest[1..]),
+        };
+        let threshold: f64 = rhs
+            .trim()
+            .parse()
+            .with_context(|| format!("Threshold must be a number, found '{}'", rhs.trim()))?;
+        if !threshold.is_finite() {
+            bail!("Threshold must be finite");
+        }
+        // Named so no input metric can collide with it
+        let metric = DerivedMetric::new("alert_rule.condition", lhs.trim())?;
+
+        Ok(Self {
+            metric,
+            comparator,
+            threshold,
+        })
+    }
+
+    /// Metrics the condition reads
+    pub fn inputs(&self) -> Vec<&str> {
+        self.metric.inputs()
+    }
+
+    /// Whether the condition holds for one window, or `None` when an input is
+    /// missing or the expression is not finite
+    pub fn evaluate(&self, inputs: &HashMap<String, StatisticalMeasures>) -> Option<bool> {
+        self.metric
+            .evaluate(inputs)
+            .map(|value| self.comparator.holds(value, self.threshold))
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// The user-editable part of an alert rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRuleSpec {
+    pub name: String,
+    pub expression: String,
+    /// Aggregation window the expression is evaluated on
+    pub window: TimeWindow,
+    /// Tags the input series must carry
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    pub severity: Severity,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Per-channel notification templates for the rule's alerts
+    #[serde(default, skip_serializing_if = "NotificationTemplates::is_empty")]
+    pub templates: NotificationTemplates,
+}
+
+impl AlertRuleSpec {
+    /// Check the rule on its own, returning the compiled condition
+    pub fn validate(&self) -> Result<AlertCondition> {
+        if self.name.trim().is_empty() {
+            bail!("Rule name must not be empty");
+        }
+        let condition = AlertCondition::parse(&self.expression)
+            .with_context(|| format!("Invalid expression '{}'", self.expression))?;
+        self.templates.validate()?;
+        Ok(condition)
+    }
+}
+
+/// A stored alert rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub rule_id: String,
+    #[serde(flatten)]
+    pub spec: AlertRuleSpec,
+    /// Incremented on every change; updates must name the version they replace
+    pub version: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<String>,
+}
+
+/// Rule ids are lowercase slugs so they can appear in URLs and tags
+pub fn validate_rule_id(rule_id: &str) -> Result<()> {
+    if rule_id.is_empty() || rule_id.len() > MAX_RULE_ID_LEN {
+        bail!("Rule id must be 1 to {} characters", MAX_RULE_ID_LEN);
+    }
+    if !rule_id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        bail!("Rule id may only contain lowercase letters, digits, '-' and '_'");
+    }
+    Ok(())
+}
+
+/// Kind of change a revision records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl RuleChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleChange::Created => "created",
+            RuleChange::Updated => "updated",
+            RuleChange::Deleted => "deleted",
+        }
+    }
+}
+
+/// One entry in a rule's version history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRuleRevision {
+    pub rule_id: String,
+    pub version: u64,
+    pub change: RuleChange,
+    /// The rule as of this revision; for deletions, as it was when deleted
+    pub rule: AlertRule,
+    pub changed_by: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A revision to write, guarded by the version it replaces
+#[derive(Debug, Clone)]
+pub struct RuleWrite {
+    pub revision: AlertRuleRevision,
+    /// Version the stored rule must have; `None` when creating
+    pub expected_version: Option<u64>,
+}
+
+impl RuleWrite {
+    /// Revision creating a rule, continuing the history of a deleted rule with the same id
+    pub fn create(
+        rule_id: &str,
+        spec: AlertRuleSpec,
+        previous_version: Option<u64>,
+        actor: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let version = previous_version.unwrap_or(0) + 1;
+        Self {
+            revision: AlertRuleRevision {
+                rule_id: rule_id.to_string(),
+                version,
+                change: RuleChange::Created,
+                rule: AlertRule {
+                    rule_id: rule_id.to_string(),
+                    spec,
+                    version,
+                    created_at: now,
+                    updated_at: now,
+                    updated_by: actor.map(str::to_string),
+                },
+                changed_by: actor.map(str::to_string),
+                changed_at: now,
+            },
+            expected_version: None,
+        }
+    }
+
+    /// Revision replacing the spec of `current`
+    pub fn update(
+        current: &AlertRule,
+        spec: AlertRuleSpec,
+        actor: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let version = current.version + 1;
+        Self {
+            revision: AlertRuleRevision {
+                rule_id: current.rule_id.clone(),
+                version,
+                change: RuleChange::Updated,
+                rule: AlertRule {
+                    spec,
+                    version,
+                    updated_at: now,
+                    updated_by: actor.map(str::to_string),
+                    ..current.clone()
+                },
+                changed_by: actor.map(str::to_string),
+                changed_at: now,
+            },
+            expected_version: Some(current.version),
+        }
+    }
+
+    /// Revision deleting `current`
+    pub fn delete(current: &AlertRule, actor: Option<&str>, now: DateTime<Utc>) -> Self {
+        Self {
+            revision: AlertRuleRevision {
+                rule_id: current.rule_id.clone(),
+                version: current.version + 1,
+                change: RuleChange::Deleted,
+                rule: current.clone(),
+                changed_by: actor.map(str::to_string),
+                changed_at: now,
+            },
+            expected_version: Some(current.version),
+        }
+    }
+}
+
+/// Reason a rule change was refused
+#[derive(Debug)]
+pub enum RuleError {
+    /// The rule or its expression is invalid
+    Invalid(String),
+    NotFound(String),
+    /// A rule with the id already exists
+    Exists(String),
+    /// The rule changed since the version the caller based its change on
+    Conflict {
+        rule_id: String,
+        expected: u64,
+        current: u64,
+    },
+    Storage(anyhow::Error),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Invalid(reason) => write!(f, "{}", reason),
+            RuleError::NotFound(rule_id) => write!(f, "Alert rule {} not found", rule_id),
+            RuleError::Exists(rule_id) => write!(f, "Alert rule {} already exists", rule_id),
+            RuleError::Conflict {
+                rule_id,
+                expected,
+                current,
+            } => write!(
+                f,
+                "Alert rule {} is at version {}, not {}; reload it and retry",
+                rule_id, current, expected
+            ),
+            RuleError::Storage(e) => write!(f, "Failed to store alert rule: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<anyhow::Error> for RuleError {
+    fn from(e: anyhow::Error) -> Self {
+        RuleError::Storage(e)
+    }
+}
+
+/// Alert rule management configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRuleConfig {
+    /// How far back a metric must have been seen to be referenced by a rule
+    pub metric_lookback_days: i64,
+}
+
+impl Default for AlertRuleConfig {
+    fn default() -> Self {
+        Self {
+            metric_lookback_days: 7,
+        }
+    }
+}
+
+impl AlertRuleConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            metric_lookback_days: std::env::var("ALERT_RULE_METRIC_LOOKBACK_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.metric_lookback_days),
+        }
+    }
+}
+
+/// Build the audit trail event recording a rule change
+pub fn audit_event(revision: &AlertRuleRevision, environment: &str) -> AnalyticsEvent {
+    let mut changes = HashMap::new();
+    changes.insert("version".to_string(), serde_json::json!(revision.version));
+    changes.insert(
+        "expression".to_string(),
+        serde_json::json!(revision.rule.spec.expression),
+    );
+    changes.insert("rule".to_string(), serde_json::json!(revision.rule));
+
+    audit::audit_event(
+        AuditTrailEvent {
+            action: format!("alert_rule.{}", revision.change.as_str()),
+            actor: revision
+                .changed_by
+                .clone()
+                .unwrap_or_else(|| UNKNOWN_ACTOR.to_string()),
+            resource_type: "alert_rule".to_string(),
+            resource_id: revision.rule_id.clone(),
+            changes,
+            ip_address: None,
+            user_agent: None,
+        },
+        revision.changed_at,
+        environment,
+    )
+}
+
+/// Validates and stores alert rules
+pub struct AlertRuleStore {
+    database: Arc<Database>,
+    config: AlertRuleConfig,
+}
+
+impl AlertRuleStore {
+    pub fn new(database: Arc<Database>, config: AlertRuleConfig) -> Self {
+        Self { database, config }
+    }
+
+    /// Check a rule's expression and that every metric it reads has been seen recently
+    pub async fn validate(&self, spec: &AlertRuleSpec) -> Result<AlertCondition, RuleError> {
+        let condition = spec
+            .validate()
+            .map_err(|e| RuleError::Invalid(format!("{:#}", e)))?;
+        let inputs: Vec<String> = condition.inputs().iter().map(|s| s.to_string()).collect();
+        let since = Utc::now() - Duration::days(self.config.metric_lookback_days);
+        let known = self.database.query_known_metrics(&inputs, since).await?;
+        let unknown: Vec<&str> = inputs
+            .iter()
+            .filter(|name| !known.contains(name))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(RuleError::Invalid(format!(
+                "Unknown metric(s) in the last {} days: {}",
+                self.config.metric_lookback_days,
+                unknown.join(", ")
+            )));
+        }
+        Ok(condition)
+    }
+
+    pub async fn list(&self) -> Result<Vec<AlertRule>, RuleError> {
+        Ok(self.database.query_alert_rules().await?)
+    }
+
+    pub async fn get(&self, rule_id: &str) -> Result<AlertRule, RuleError> {
+        self.database
+            .get_alert_rule(rule_id)
+            .await?
+            .ok_or_else(|| RuleError::NotFound(rule_id.to_string()))
+    }
+
+    /// Version history of a rule, newest first, including deletions
+    pub async fn history(&self, rule_id: &str) -> Result<Vec<AlertRuleRevision>, RuleError> {
+        let revisions = self.database.query_alert_rule_revisions(rule_id).await?;
+        if revisions.is_empty() {
+            return Err(RuleError::NotFound(rule_id.to_string()));
+        }
+        Ok(revisions)
+    }
+
+    pub async fn create(
+        &self,
+        rule_id: &str,
+        spec: AlertRuleSpec,
+        actor: Option<&str>,
+    ) -> Result<AlertRuleRevision, RuleError> {
+        validate_rule_id(rule_id).map_err(|e| RuleError::Invalid(e.to_string()))?;
+        self.validate(&spec).await?;
+        if self.database.get_alert_rule(rule_id).await?.is_some() {
+            return Err(RuleError::Exists(rule_id.to_string()));
+        }
+        let previous = self.previous_version(rule_id).await?;
+        let write = RuleWrite::create(rule_id, spec, previous, actor, Utc::now());
+        self.apply(write).await
+    }
+
+    pub async fn update(
+        &self,
+        rule_id: &str,
+        expected_version: u64,
+        spec: AlertRuleSpec,
+        actor: Option<&str>,
+    ) -> Result<AlertRuleRevision, RuleError> {
+        let current = self.get(rule_id).await?;
+        check_version(&current, expected_version)?;
+        self.validate(&spec).await?;
+        self.apply(RuleWrite::update(&current, spec, actor, Utc::now()))
+            .await
+    }
+
+    pub async fn delete(
+        &self,
+        rule_id: &str,
+        expected_version: u64,
+        actor: Option<&str>,
+    ) -> Result<AlertRuleRevision, RuleError> {
+        let current = self.get(rule_id).await?;
+        check_version(&current, expected_version)?;
+        self.apply(RuleWrite::delete(&current, actor, Utc::now()))
+            .await
+    }
+
+    /// Highest version recorded for a rule id, so a recreated rule continues its history
+    pub async fn previous_version(&self, rule_id: &str) -> Result<Option<u64>, RuleError> {
+        Ok(self.database.latest_alert_rule_version(rule_id).await?)
+    }
+
+    /// Apply writes atomically: either every write lands or none does
+    pub async fn apply_all(
+        &self,
+        writes: Vec<RuleWrite>,
+    ) -> Result<Vec<AlertRuleRevision>, RuleError> {
+        if writes.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.database.apply_alert_rule_writes(&writes).await? {
+            // Lost a race with another writer between the read and the write
+            for write in &writes {
+                if let Some(err) = self.stale(write).await? {
+                    return Err(err);
+                }
+            }
+            return Err(RuleError::Storage(anyhow!(
+                "Alert rules changed while being written"
+            )));
+        }
+
+        let revisions: Vec<AlertRuleRevision> = writes.into_iter().map(|w| w.revision).collect();
+        for revision in &revisions {
+            info!(
+                rule_id = %revision.rule_id,
+                version = revision.version,
+                change = revision.change.as_str(),
+                "Alert rule changed"
+            );
+        }
+        Ok(revisions)
+    }
+
+    async fn apply(&self, write: RuleWrite) -> Result<AlertRuleRevision, RuleError> {
+        let mut revisions = self.apply_all(vec![write]).await?;
+        Ok(revisions.remove(0))
+    }
+
+    /// Why a write no longer matches the stored rule, if it does not
+    async fn stale(&self, write: &RuleWrite) -> Result<Option<RuleError>, RuleError> {
+        let rule_id = write.revision.rule_id.clone();
+        let current = self.database.get_alert_rule(&rule_id).await?;
+        Ok(match (current, write.expected_version) {
+            (Some(_), None) => Some(RuleError::Exists(rule_id)),
+            (Some(current), Some(expected)) if current.version != expected => {
+                Some(RuleError::Conflict {
+                    rule_id,
+                    expected,
+                    current: current.version,
+                })
+            }
+            (None, Some(_)) => Some(RuleError::NotFound(rule_id)),
+            _ => None,
+        })
+    }
+}
+
+fn check_version(current: &AlertRule, expected: u64) -> Result<(), RuleError> {
+    if current.version != expected {
+        return Err(RuleError::Conflict {
+            rule_id: current.rule_id.clone(),
+            expected,
+            current: current.version,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::templates::NotificationTemplate;
+    use crate::schemas::events::{EventPayload, GovernancePayload};
+
+    fn spec(expression: &str) -> AlertRuleSpec {
+        AlertRuleSpec {
+            name: "Slow GPT-4".to_string(),
+            expression: expression.to_string(),
+            window: TimeWindow::FiveMinutes,
+            tags: HashMap::new(),
+            severity: Severity::Warning,
+            enabled: true,
+            description: None,
+            templates: NotificationTemplates::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_evaluate_conditions() {
+        let condition = AlertCondition::parse("p95(latency_ms) > 2000").unwrap();
+        assert_eq!(condition.comparator, Comparator::Gt);
+        assert_eq!(condition.threshold, 2000.0);
+        assert_eq!(condition.inputs(), vec!["latency_ms"]);
+
+        let mut inputs = HashMap::new();
+        assert_eq!(condition.evaluate(&inputs), None);
+        inputs.insert(
+            "latency_ms".to_string(),
+            StatisticalMeasures::from_values(&[100.0, 2500.0]),
+        );
+        assert_eq!(condition.evaluate(&inputs), Some(true));
+
+        let ratio = AlertCondition::parse("errors / requests >= 0.05").unwrap();
+        assert_eq!(ratio.comparator, Comparator::Ge);
+        assert_eq!(ratio.inputs(), vec!["errors", "requests"]);
+        assert_eq!(
+            AlertCondition::parse("avg(score)<=-1").unwrap().comparator,
+            Comparator::Le
+        );
+
+        assert!(AlertCondition::parse("latency_ms").is_err());
+        assert!(AlertCondition::parse("latency_ms > slow").is_err());
+        assert!(AlertCondition::parse("latency_ms > 1 > 2").is_err());
+        assert!(AlertCondition::parse("1 > 2").is_err());
+        assert!(AlertCondition::parse("(latency_ms > 2").is_err());
+    }
+
+    #[test]
+    fn test_rule_ids_and_specs_are_validated() {
+        assert!(validate_rule_id("gpt4-latency_p95").is_ok());
+        assert!(validate_rule_id("").is_err());
+        assert!(validate_rule_id("GPT4").is_err());
+        assert!(validate_rule_id("a/b").is_err());
+
+        assert!(spec("p95(latency_ms) > 2000").validate().is_ok());
+        assert!(spec("p95(latency_ms) >").validate().is_err());
+        let mut unnamed = spec("latency_ms > 1");
+        unnamed.name = " ".to_string();
+        assert!(unnamed.validate().is_err());
+        let mut templated = spec("latency_ms > 1");
+        templated.templates.default = Some(NotificationTemplate {
+            subject: Some("{{ rule".to_string()),
+            ..Default::default()
+        });
+        assert!(templated.validate().is_err());
+    }
+
+    #[test]
+    fn test_revisions_bump_versions() {
+        let now = Utc::now();
+        let created = RuleWrite::create("slow", spec("latency_ms > 1"), None, Some("alice"), now);
+        assert_eq!(created.revision.version, 1);
+        assert_eq!(created.expected_version, None);
+        let rule = created.revision.rule;
+        assert_eq!(rule.updated_by.as_deref(), Some("alice"));
+
+        let later = now + Duration::minutes(5);
+        let updated = RuleWrite::update(&rule, spec("latency_ms > 2"), Some("bob"), later);
+        assert_eq!(updated.expected_version, Some(1));
+        assert_eq!(updated.revision.rule.version, 2);
+        assert_eq!(updated.revision.rule.created_at, now);
+        assert_eq!(updated.revision.rule.updated_at, later);
+
+        let deleted = RuleWrite::delete(&updated.revision.rule, None, later);
+        assert_eq!(deleted.revision.version, 3);
+        assert_eq!(deleted.revision.rule.spec.expression, "latency_ms > 2");
+
+        // A recreated rule continues the deleted rule's history
+        let recreated = RuleWrite::create("slow", spec("latency_ms > 3"), Some(3), None, later);
+        assert_eq!(recreated.revision.version, 4);
+
+        let event = audit_event(&deleted.revision, "production");
+        match event.payload {
+            EventPayload::Governance(GovernancePayload::AuditTrail(audit)) => {
+                assert_eq!(audit.action, "alert_rule.deleted");
+                assert_eq!(audit.actor, UNKNOWN_ACTOR);
+                assert_eq!(audit.resource_id, "slow");
+                assert_eq!(audit.changes["version"], serde_json::json!(3));
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_conflict_message() {
+        let err = RuleError::Conflict {
+            rule_id: "slow".to_string(),
+            expected: 1,
+            current: 3,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Alert rule slow is at version 3, not 1; reload it and retry"
+        );
+    }
+}
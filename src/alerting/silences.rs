@@ -0,0 +1,211 @@
+//! Alert Silences
+//!
+//! Time-boxed suppression of alert notifications. A silence matches alerts by
+//! alert type and/or tag values; an alert matched by any active silence is
+//! neither paged nor added to a digest.
+
+use super::digest::alert_type;
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A period during which matching alerts are not delivered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Silence {
+    pub silence_id: Uuid,
+    /// Alert type matched; `None` matches every type
+    pub alert_type: Option<String>,
+    /// Tags an alert must carry, all of which must match
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_by: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl Silence {
+    /// A silence starting now and lasting `duration`
+    pub fn new(duration: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            silence_id: Uuid::new_v4(),
+            alert_type: None,
+            tags: HashMap::new(),
+            starts_at: now,
+            ends_at: now + duration,
+            created_by: None,
+            comment: None,
+        }
+    }
+
+    pub fn with_alert_type(mut self, alert_type: impl Into<String>) -> Self {
+        self.alert_type = Some(alert_type.into());
+        self
+    }
+
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Reject silences that would match every alert or never be active
+    pub fn validate(&self) -> Result<()> {
+        if self.alert_type.is_none() && self.tags.is_empty() {
+            bail!("A silence needs an alert type or at least one tag");
+        }
+        if self.ends_at <= self.starts_at {
+            bail!("A silence must end after it starts");
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    pub fn matches(&self, event: &AnalyticsEvent) -> bool {
+        self.alert_type
+            .as_ref()
+            .map_or(true, |t| *t == alert_type(event))
+            && self
+                .tags
+                .iter()
+                .all(|(k, v)| event.common.tags.get(k) == Some(v))
+    }
+}
+
+/// In-memory set of silences consulted on every alert
+#[derive(Debug, Default)]
+pub struct SilenceRegistry {
+    silences: RwLock<Vec<Silence>>,
+}
+
+impl SilenceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace all silences, e.g. with those loaded from storage
+    pub fn replace(&self, silences: Vec<Silence>) {
+        *self.silences.write() = silences;
+    }
+
+    pub fn add(&self, silence: Silence) -> Result<()> {
+        silence.validate()?;
+        self.silences.write().push(silence);
+        Ok(())
+    }
+
+    /// End a silence now, returning whether it was active
+    pub fn expire(&self, silence_id: Uuid, now: DateTime<Utc>) -> bool {
+        let mut silences = self.silences.write();
+        match silences
+            .iter_mut()
+            .find(|s| s.silence_id == silence_id && s.is_active(now))
+        {
+            Some(silence) => {
+                silence.ends_at = now;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Silences in effect or scheduled, dropping those that have ended
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<Silence> {
+        let mut silences = self.silences.write();
+        silences.retain(|s| s.ends_at > now);
+        silences.clone()
+    }
+
+    /// The active silence matching an alert, if any
+    pub fn silenced_by(&self, event: &AnalyticsEvent, now: DateTime<Utc>) -> Option<Uuid> {
+        self.silences
+            .read()
+            .iter()
+            .find(|s| s.is_active(now) && s.matches(event))
+            .map(|s| s.silence_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+    };
+
+    fn alert(custom_type: &str, tags: &[(&str, &str)]) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Warning,
+                environment: "test".to_string(),
+                tags: tags
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: custom_type.to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_silence_matching_and_expiry() {
+        let registry = SilenceRegistry::new();
+        let silence = Silence::new(Duration::hours(2))
+            .with_alert_type("provider.failover_recommendation")
+            .with_tag("provider", "openai");
+        let id = silence.silence_id;
+        registry.add(silence).unwrap();
+        assert!(registry.add(Silence::new(Duration::hours(1))).is_err());
+
+        let now = Utc::now();
+        let matched = alert(
+            "provider.failover_recommendation",
+            &[("provider", "openai")],
+        );
+        let other_provider = alert(
+            "provider.failover_recommendation",
+            &[("provider", "anthropic")],
+        );
+        let other_type = alert("changepoint.detected", &[("provider", "openai")]);
+
+        assert_eq!(registry.silenced_by(&matched, now), Some(id));
+        assert_eq!(registry.silenced_by(&other_provider, now), None);
+        assert_eq!(registry.silenced_by(&other_type, now), None);
+        assert_eq!(
+            registry.silenced_by(&matched, now + Duration::hours(3)),
+            None
+        );
+
+        assert!(registry.expire(id, now));
+        assert!(!registry.expire(id, now));
+        assert_eq!(registry.silenced_by(&matched, now), None);
+        assert!(registry.active(now).is_empty());
+    }
+}
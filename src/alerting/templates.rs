@@ -0,0 +1,266 @@
+//! Notification Templates
+//!
+//! Jinja-style templates (rendered with minijinja) that let teams customize
+//! the subject and body of alert notifications per rule and per channel, e.g.
+//! a terse subject for PagerDuty and a detailed markdown body for Slack.
+//!
+//! Templates are compiled when a rule is saved so syntax errors are rejected
+//! up front. A template that still fails at delivery time, e.g. because a
+//! filter errors on an unexpected value, never blocks the alert: the caller
+//! falls back to the default notification.
+
+use super::channels::Notification;
+use super::digest::alert_type;
+use super::incidents::Incident;
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Template for one notification
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationTemplate {
+    /// Replaces the default subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Replaces the default body, and any report document behind it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// MIME type of the rendered body; plain text when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+impl NotificationTemplate {
+    /// Compile every part, rejecting syntax errors
+    pub fn validate(&self) -> Result<()> {
+        let env = Environment::new();
+        for (part, source) in [("subject", &self.subject), ("body", &self.body)] {
+            if let Some(source) = source {
+                env.template_from_str(source)
+                    .with_context(|| format!("Invalid {} template", part))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render over `base`, keeping its fields for any part without a template
+    pub fn render(&self, context: &serde_json::Value, base: &Notification) -> Result<Notification> {
+        let env = Environment::new();
+        let mut notification = base.clone();
+        if let Some(source) = &self.subject {
+            let subject = env
+                .render_str(source, context)
+                .context("Failed to render subject template")?;
+            let subject = subject.trim();
+            if subject.is_empty() {
+                bail!("Subject template rendered an empty subject");
+            }
+            notification.subject = subject.to_string();
+        }
+        if let Some(source) = &self.body {
+            notification.body = env
+                .render_str(source, context)
+                .context("Failed to render body template")?;
+            notification.content_type = self
+                .content_type
+                .clone()
+                .unwrap_or_else(|| "text/plain".to_string());
+            // Channels prefer the document over the body, which would hide the template
+            notification.document = None;
+        }
+        Ok(notification)
+    }
+}
+
+/// Templates of one rule: a default plus per-channel overrides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationTemplates {
+    /// Used for channels without their own template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<NotificationTemplate>,
+    /// Templates by channel name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub channels: HashMap<String, NotificationTemplate>,
+}
+
+impl NotificationTemplates {
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.channels.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(template) = &self.default {
+            template.validate().context("Invalid default template")?;
+        }
+        for (channel, template) in &self.channels {
+            template
+                .validate()
+                .with_context(|| format!("Invalid template for channel '{}'", channel))?;
+        }
+        Ok(())
+    }
+
+    /// Template used for a channel, if any
+    pub fn for_channel(&self, channel: &str) -> Option<&NotificationTemplate> {
+        self.channels.get(channel).or(self.default.as_ref())
+    }
+
+    /// Render the notification for one channel, with the channel name
+    /// available to templates as `channel`. Returns `base` unchanged when no
+    /// template applies.
+    pub fn render(
+        &self,
+        channel: &str,
+        context: &serde_json::Value,
+        base: &Notification,
+    ) -> Result<Notification> {
+        let Some(template) = self.for_channel(channel) else {
+            return Ok(base.clone());
+        };
+        let mut context = context.clone();
+        if let Some(fields) = context.as_object_mut() {
+            fields.insert("channel".to_string(), channel.into());
+        }
+        template.render(&context, base)
+    }
+}
+
+/// Template context for an alert handled by `rule`; `payload` holds the
+/// fields of the event payload, without its type tag
+pub fn alert_context(event: &AnalyticsEvent, rule: &str) -> serde_json::Value {
+    let payload = serde_json::to_value(&event.payload)
+        .ok()
+        .and_then(|mut value| value.get_mut("data").map(serde_json::Value::take))
+        .unwrap_or_default();
+    serde_json::json!({
+        "kind": "alert",
+        "rule": rule,
+        "alert_type": alert_type(event),
+        "event_id": event.common.event_id,
+        "severity": event.common.severity,
+        "timestamp": event.common.timestamp,
+        "environment": event.common.environment,
+        "tags": event.common.tags,
+        "payload": payload,
+    })
+}
+
+/// Template context for a digest of `alerts` alerts between `start` and `end`
+pub fn digest_context(
+    rule: &str,
+    alerts: u64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    summary: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "kind": "digest",
+        "rule": rule,
+        "alerts": alerts,
+        "start": start,
+        "end": end,
+        "summary": summary,
+    })
+}
+
+/// Template context for an incident
+pub fn incident_context(incident: &Incident) -> serde_json::Value {
+    serde_json::json!({
+        "kind": "incident",
+        "incident": incident,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+    };
+
+    fn alert() -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: uuid::Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Critical,
+                environment: "production".to_string(),
+                tags: HashMap::from([("model_id".to_string(), "gpt-4".to_string())]),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "latency_spike".to_string(),
+                data: serde_json::json!({"p95_ms": 4200}),
+            }),
+        }
+    }
+
+    fn template(subject: &str, body: Option<&str>) -> NotificationTemplate {
+        NotificationTemplate {
+            subject: Some(subject.to_string()),
+            body: body.map(str::to_string),
+            content_type: body.map(|_| "text/markdown".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_per_channel() {
+        let templates = NotificationTemplates {
+            default: Some(template("{{ alert_type }} on {{ tags.model_id }}", None)),
+            channels: HashMap::from([(
+                "slack".to_string(),
+                template(
+                    "[{{ severity }}] {{ rule }}",
+                    Some("*{{ alert_type }}* via {{ channel }}: p95 {{ payload.data.p95_ms }}ms"),
+                ),
+            )]),
+        };
+        templates.validate().unwrap();
+
+        let base = Notification::new("default", "{}", Severity::Critical)
+            .with_content_type("application/json");
+        let context = alert_context(&alert(), "latency");
+
+        let pager = templates.render("pagerduty", &context, &base).unwrap();
+        assert_eq!(pager.subject, "latency_spike on gpt-4");
+        assert_eq!(pager.body, "{}");
+        assert_eq!(pager.content_type, "application/json");
+
+        let slack = templates.render("slack", &context, &base).unwrap();
+        assert_eq!(slack.subject, "[critical] latency");
+        assert_eq!(slack.body, "*latency_spike* via slack: p95 4200ms");
+        assert_eq!(slack.content_type, "text/markdown");
+
+        let untemplated = NotificationTemplates::default();
+        assert!(untemplated.is_empty());
+        assert_eq!(untemplated.render("slack", &context, &base).unwrap(), base);
+    }
+
+    #[test]
+    fn test_invalid_and_failing_templates() {
+        assert!(template("{{ rule", None).validate().is_err());
+        assert!(template("ok", Some("{% if %}")).validate().is_err());
+        let invalid = NotificationTemplates {
+            default: None,
+            channels: HashMap::from([("slack".to_string(), template("{% for %}", None))]),
+        };
+        let err = invalid.validate().unwrap_err();
+        assert!(format!("{:#}", err).contains("channel 'slack'"));
+
+        // Valid syntax can still fail, or render nothing, at delivery time
+        let base = Notification::new("default", "body", Severity::Warning);
+        let context = alert_context(&alert(), "latency");
+        let failing = template("{{ rule | nonexistent_filter }}", None);
+        assert!(failing.validate().is_err() || failing.render(&context, &base).is_err());
+        assert!(template("{{ missing }}", None)
+            .render(&context, &base)
+            .is_err());
+    }
+}
@@ -0,0 +1,865 @@
+//! Incident Ticketing
+//!
+//! Opens a ticket in Jira or ServiceNow for every unresolved incident at or
+//! above the configured severity (critical by default) and records the ticket
+//! on the incident. Resolution is kept in step both ways on a polling
+//! interval: resolving or reopening the incident in the hub resolves or
+//! reopens its ticket, and resolving or reopening the ticket does the same to
+//! the incident. Each side's change is told apart from the other's by the
+//! last state both sides agreed on, kept on the ticket reference.
+//!
+//! Incidents opened by alerts or through the API are ticketed as they are
+//! created; those opened by anomalies are picked up on the next poll.
+
+use super::incidents::{Incident, IncidentStatus, IncidentTracker};
+use crate::database::Database;
+use crate::schemas::events::Severity;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// ServiceNow incident states treated as resolved: Resolved and Closed
+const SERVICENOW_RESOLVED_STATES: [&str; 2] = ["6", "7"];
+
+/// ServiceNow incident state set when a ticket is reopened: In Progress
+const SERVICENOW_REOPENED_STATE: &str = "2";
+
+/// Ticketing system incidents are raised in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketProvider {
+    #[default]
+    Disabled,
+    Jira,
+    #[serde(rename = "servicenow")]
+    ServiceNow,
+}
+
+impl TicketProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketProvider::Disabled => "disabled",
+            TicketProvider::Jira => "jira",
+            TicketProvider::ServiceNow => "servicenow",
+        }
+    }
+}
+
+impl FromStr for TicketProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown ticket provider: {}", s))
+    }
+}
+
+/// Ticket linked to an incident
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketRef {
+    pub provider: TicketProvider,
+    /// Jira issue key or ServiceNow `sys_id`
+    pub ticket_id: String,
+    /// Human-facing number, e.g. `OPS-42` or `INC0010001`
+    pub number: String,
+    pub url: String,
+    /// Whether both sides were resolved when last in step
+    pub synced_resolved: bool,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Resolution state of a ticket as reported by its system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketState {
+    Open,
+    Resolved,
+}
+
+/// A ticketing system incidents can be raised in
+#[async_trait]
+pub trait TicketClient: Send + Sync {
+    fn provider(&self) -> TicketProvider;
+
+    async fn create(&self, incident: &Incident) -> Result<TicketRef>;
+
+    async fn state(&self, ticket: &TicketRef) -> Result<TicketState>;
+
+    async fn resolve(&self, ticket: &TicketRef, note: &str) -> Result<()>;
+
+    async fn reopen(&self, ticket: &TicketRef, note: &str) -> Result<()>;
+}
+
+/// Ticketing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketingConfig {
+    pub provider: TicketProvider,
+    /// e.g. `https://acme.atlassian.net` or `https://acme.service-now.com`
+    pub base_url: String,
+    pub username: String,
+    /// Jira API token or ServiceNow password
+    pub token: String,
+    /// Jira project issues are created in
+    pub jira_project: String,
+    pub jira_issue_type: String,
+    /// Lowest incident severity that gets a ticket
+    pub min_severity: Severity,
+    pub poll_interval_secs: u64,
+    /// Per-request timeout
+    pub timeout_secs: u64,
+}
+
+impl Default for TicketingConfig {
+    fn default() -> Self {
+        Self {
+            provider: TicketProvider::Disabled,
+            base_url: String::new(),
+            username: String::new(),
+            token: String::new(),
+            jira_project: "OPS".to_string(),
+            jira_issue_type: "Bug".to_string(),
+            min_severity: Severity::Critical,
+            poll_interval_secs: 60,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl TicketingConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            provider: std::env::var("TICKETING_PROVIDER")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?
+                .unwrap_or(defaults.provider),
+            base_url: std::env::var("TICKETING_BASE_URL").unwrap_or(defaults.base_url),
+            username: std::env::var("TICKETING_USERNAME").unwrap_or(defaults.username),
+            token: std::env::var("TICKETING_TOKEN").unwrap_or(defaults.token),
+            jira_project: std::env::var("JIRA_PROJECT_KEY").unwrap_or(defaults.jira_project),
+            jira_issue_type: std::env::var("JIRA_ISSUE_TYPE").unwrap_or(defaults.jira_issue_type),
+            min_severity: std::env::var("TICKETING_MIN_SEVERITY")
+                .ok()
+                .map(|v| {
+                    serde_json::from_value(serde_json::Value::String(v.to_lowercase()))
+                        .with_context(|| format!("Invalid ticketing severity: {}", v))
+                })
+                .transpose()?
+                .unwrap_or(defaults.min_severity),
+            poll_interval_secs: std::env::var("TICKETING_POLL_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.poll_interval_secs),
+            timeout_secs: std::env::var("TICKETING_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_secs),
+        })
+    }
+
+    fn http(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(self.timeout_secs.max(1)))
+            .build()
+            .context("Failed to build ticketing HTTP client")
+    }
+
+    fn base_url(&self) -> &str {
+        self.base_url.trim_end_matches('/')
+    }
+}
+
+/// Build the client for the configured provider, or `None` when disabled
+pub fn ticket_client_from_config(
+    config: &TicketingConfig,
+) -> Result<Option<Arc<dyn TicketClient>>> {
+    if config.provider != TicketProvider::Disabled && config.base_url.is_empty() {
+        bail!(
+            "Ticketing with {} needs TICKETING_BASE_URL",
+            config.provider.as_str()
+        );
+    }
+    Ok(match config.provider {
+        TicketProvider::Disabled => None,
+        TicketProvider::Jira => Some(Arc::new(JiraClient::new(config)?)),
+        TicketProvider::ServiceNow => Some(Arc::new(ServiceNowClient::new(config)?)),
+    })
+}
+
+/// Summary and description of the ticket raised for an incident
+fn ticket_text(incident: &Incident) -> (String, String) {
+    let summary = format!("[{:?}] {}", incident.severity, incident.title);
+    let mut description = format!(
+        "Incident {} opened {} in {}.\n",
+        incident.incident_id,
+        incident.opened_at.to_rfc3339(),
+        incident.environment
+    );
+    if !incident.summaries.is_empty() {
+        let findings: Vec<&str> = incident.summaries.iter().map(String::as_str).collect();
+        description.push_str(&format!("Findings: {}\n", findings.join(", ")));
+    }
+    if !incident.entities.is_empty() {
+        let entities: Vec<String> = incident
+            .entities
+            .iter()
+            .map(|e| format!("{}:{}", e.kind.as_str(), e.id))
+            .collect();
+        description.push_str(&format!("Affected: {}\n", entities.join(", ")));
+    }
+    (summary, description)
+}
+
+/// Raises Jira issues through the REST API v2
+pub struct JiraClient {
+    config: TicketingConfig,
+    http: reqwest::Client,
+}
+
+impl JiraClient {
+    pub fn new(config: &TicketingConfig) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            http: config.http()?,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/rest/api/2/{}", self.config.base_url(), path)
+    }
+
+    async fn get(&self, path: &str) -> Result<serde_json::Value> {
+        self.http
+            .get(self.url(path))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .send()
+            .await
+            .context("Failed to reach Jira")?
+            .error_for_status()
+            .context("Jira rejected request")?
+            .json()
+            .await
+            .context("Failed to parse Jira response")
+    }
+
+    /// Move the issue through the first transition into (or out of) the done category
+    async fn transition(&self, ticket: &TicketRef, to_done: bool, note: &str) -> Result<()> {
+        let path = format!("issue/{}/transitions", ticket.ticket_id);
+        let transitions = self.get(&path).await?;
+        let transition = transitions["transitions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|t| (t["to"]["statusCategory"]["key"] == "done") == to_done)
+            .and_then(|t| t["id"].as_str())
+            .with_context(|| {
+                format!(
+                    "Jira issue {} has no transition {} done",
+                    ticket.number,
+                    if to_done { "to" } else { "out of" }
+                )
+            })?;
+        self.http
+            .post(self.url(&path))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .json(&serde_json::json!({
+                "transition": { "id": transition },
+                "update": { "comment": [{ "add": { "body": note } }] },
+            }))
+            .send()
+            .await
+            .context("Failed to reach Jira")?
+            .error_for_status()
+            .context("Jira rejected transition")?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TicketClient for JiraClient {
+    fn provider(&self) -> TicketProvider {
+        TicketProvider::Jira
+    }
+
+    async fn create(&self, incident: &Incident) -> Result<TicketRef> {
+        let (summary, description) = ticket_text(incident);
+        let created: serde_json::Value = self
+            .http
+            .post(self.url("issue"))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .json(&serde_json::json!({
+                "fields": {
+                    "project": { "key": self.config.jira_project },
+                    "issuetype": { "name": self.config.jira_issue_type },
+                    "summary": summary,
+                    "description": description,
+                    "labels": ["llm-analytics-hub"],
+                },
+            }))
+            .send()
+            .await
+            .context("Failed to reach Jira")?
+            .error_for_status()
+            .context("Jira rejected issue")?
+            .json()
+            .await
+            .context("Failed to parse Jira response")?;
+        let key = created["key"]
+            .as_str()
+            .context("Jira response has no issue key")?;
+        Ok(TicketRef {
+            provider: TicketProvider::Jira,
+            ticket_id: key.to_string(),
+            number: key.to_string(),
+            url: format!("{}/browse/{}", self.config.base_url(), key),
+            synced_resolved: false,
+            synced_at: Utc::now(),
+        })
+    }
+
+    async fn state(&self, ticket: &TicketRef) -> Result<TicketState> {
+        let issue = self
+            .get(&format!("issue/{}?fields=status", ticket.ticket_id))
+            .await?;
+        Ok(
+            if issue["fields"]["status"]["statusCategory"]["key"] == "done" {
+                TicketState::Resolved
+            } else {
+                TicketState::Open
+            },
+        )
+    }
+
+    async fn resolve(&self, ticket: &TicketRef, note: &str) -> Result<()> {
+        self.transition(ticket, true, note).await
+    }
+
+    async fn reopen(&self, ticket: &TicketRef, note: &str) -> Result<()> {
+        self.transition(ticket, false, note).await
+    }
+}
+
+/// Raises ServiceNow incidents through the Table API
+pub struct ServiceNowClient {
+    config: TicketingConfig,
+    http: reqwest::Client,
+}
+
+impl ServiceNowClient {
+    pub fn new(config: &TicketingConfig) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            http: config.http()?,
+        })
+    }
+
+    fn url(&self, sys_id: Option<&str>) -> String {
+        match sys_id {
+            Some(sys_id) => format!(
+                "{}/api/now/table/incident/{}",
+                self.config.base_url(),
+                sys_id
+            ),
+            None => format!("{}/api/now/table/incident", self.config.base_url()),
+        }
+    }
+
+    async fn patch(&self, ticket: &TicketRef, fields: serde_json::Value) -> Result<()> {
+        self.http
+            .patch(self.url(Some(&ticket.ticket_id)))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .json(&fields)
+            .send()
+            .await
+            .context("Failed to reach ServiceNow")?
+            .error_for_status()
+            .context("ServiceNow rejected update")?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TicketClient for ServiceNowClient {
+    fn provider(&self) -> TicketProvider {
+        TicketProvider::ServiceNow
+    }
+
+    async fn create(&self, incident: &Incident) -> Result<TicketRef> {
+        let (summary, description) = ticket_text(incident);
+        let created: serde_json::Value = self
+            .http
+            .post(self.url(None))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .json(&serde_json::json!({
+                "short_description": summary,
+                "description": description,
+                "impact": "1",
+                "urgency": "1",
+                "correlation_id": incident.incident_id.to_string(),
+                "correlation_display": "llm-analytics-hub",
+            }))
+            .send()
+            .await
+            .context("Failed to reach ServiceNow")?
+            .error_for_status()
+            .context("ServiceNow rejected incident")?
+            .json()
+            .await
+            .context("Failed to parse ServiceNow response")?;
+        let record = &created["result"];
+        let sys_id = record["sys_id"]
+            .as_str()
+            .context("ServiceNow response has no sys_id")?;
+        Ok(TicketRef {
+            provider: TicketProvider::ServiceNow,
+            ticket_id: sys_id.to_string(),
+            number: record["number"].as_str().unwrap_or(sys_id).to_string(),
+            url: format!(
+                "{}/nav_to.do?uri=incident.do?sys_id={}",
+                self.config.base_url(),
+                sys_id
+            ),
+            synced_resolved: false,
+            synced_at: Utc::now(),
+        })
+    }
+
+    async fn state(&self, ticket: &TicketRef) -> Result<TicketState> {
+        let record: serde_json::Value = self
+            .http
+            .get(format!(
+                "{}?sysparm_fields=state",
+                self.url(Some(&ticket.ticket_id))
+            ))
+            .basic_auth(&self.config.username, Some(&self.config.token))
+            .send()
+            .await
+            .context("Failed to reach ServiceNow")?
+            .error_for_status()
+            .context("ServiceNow rejected request")?
+            .json()
+            .await
+            .context("Failed to parse ServiceNow response")?;
+        let state = record["result"]["state"].as_str().unwrap_or_default();
+        Ok(if SERVICENOW_RESOLVED_STATES.contains(&state) {
+            TicketState::Resolved
+        } else {
+            TicketState::Open
+        })
+    }
+
+    async fn resolve(&self, ticket: &TicketRef, note: &str) -> Result<()> {
+        self.patch(
+            ticket,
+            serde_json::json!({
+                "state": SERVICENOW_RESOLVED_STATES[0],
+                "close_code": "Solved (Permanently)",
+                "close_notes": note,
+            }),
+        )
+        .await
+    }
+
+    async fn reopen(&self, ticket: &TicketRef, note: &str) -> Result<()> {
+        self.patch(
+            ticket,
+            serde_json::json!({
+                "state": SERVICENOW_REOPENED_STATE,
+                "work_notes": note,
+            }),
+        )
+        .await
+    }
+}
+
+/// What one sync did to an incident and its ticket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncAction {
+    None,
+    ResolveTicket,
+    ReopenTicket,
+    ResolveIncident,
+    ReopenIncident,
+}
+
+/// Decide which side follows the other. The side that differs from the last
+/// agreed state changed; when both changed, the hub wins.
+fn sync_action(ticket: &TicketRef, incident_resolved: bool, ticket_resolved: bool) -> SyncAction {
+    if incident_resolved == ticket_resolved {
+        return SyncAction::None;
+    }
+    match (
+        incident_resolved != ticket.synced_resolved,
+        incident_resolved,
+    ) {
+        (true, true) => SyncAction::ResolveTicket,
+        (true, false) => SyncAction::ReopenTicket,
+        (false, _) if ticket_resolved => SyncAction::ResolveIncident,
+        (false, _) => SyncAction::ReopenIncident,
+    }
+}
+
+/// Raises tickets for severe incidents and keeps their resolution in step
+pub struct TicketSync {
+    client: Arc<dyn TicketClient>,
+    tracker: Arc<IncidentTracker>,
+    database: Option<Arc<Database>>,
+    config: TicketingConfig,
+    // Serializes ticket creation so an incident is never ticketed twice
+    lock: tokio::sync::Mutex<()>,
+    created: AtomicU64,
+    synced: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl TicketSync {
+    pub fn new(
+        client: Arc<dyn TicketClient>,
+        tracker: Arc<IncidentTracker>,
+        config: TicketingConfig,
+    ) -> Self {
+        Self {
+            client,
+            tracker,
+            database: None,
+            config,
+            lock: tokio::sync::Mutex::new(()),
+            created: AtomicU64::new(0),
+            synced: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Persist incidents as tickets are linked and states synced
+    pub fn with_database(mut self, database: Arc<Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    fn needs_ticket(&self, incident: &Incident) -> bool {
+        incident.ticket.is_none()
+            && !incident.is_resolved()
+            && incident.severity >= self.config.min_severity
+    }
+
+    /// Raise a ticket for an incident that qualifies and has none yet,
+    /// returning the incident with the ticket linked
+    pub async fn open_ticket(&self, incident_id: Uuid) -> Result<Option<Incident>> {
+        let _guard = self.lock.lock().await;
+        self.open_ticket_locked(incident_id).await
+    }
+
+    async fn open_ticket_locked(&self, incident_id: Uuid) -> Result<Option<Incident>> {
+        let Some(incident) = self.tracker.get(incident_id) else {
+            return Ok(None);
+        };
+        if !self.needs_ticket(&incident) {
+            return Ok(None);
+        }
+
+        let ticket = self.client.create(&incident).await?;
+        info!(
+            incident_id = %incident_id,
+            ticket = %ticket.number,
+            provider = self.client.provider().as_str(),
+            "Opened ticket for incident"
+        );
+        self.created.fetch_add(1, Ordering::Relaxed);
+        let note = format!("Ticket {} opened: {}", ticket.number, ticket.url);
+        let incident = self.tracker.update(incident_id, |incident| {
+            incident.ticket = Some(ticket);
+            incident.add_note(
+                Some(self.client.provider().as_str().to_string()),
+                note,
+                Utc::now(),
+            );
+            Ok(())
+        })?;
+        self.persist(&incident).await?;
+        Ok(Some(incident))
+    }
+
+    /// Raise missing tickets, then bring every ticketed incident and its
+    /// ticket into the same resolution state, returning the number of
+    /// incidents changed. Failures on one incident do not stop the rest.
+    pub async fn sync_once(&self) -> usize {
+        let _guard = self.lock.lock().await;
+        let mut changed = 0;
+        for incident in self.tracker.list(None) {
+            let result = if self.needs_ticket(&incident) {
+                self.open_ticket_locked(incident.incident_id)
+                    .await
+                    .map(|opened| opened.is_some())
+            } else {
+                self.sync_incident(&incident).await
+            };
+            match result {
+                Ok(true) => changed += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!(incident_id = %incident.incident_id, "Ticket sync failed: {}", e);
+                }
+            }
+        }
+        changed
+    }
+
+    async fn sync_incident(&self, incident: &Incident) -> Result<bool> {
+        let Some(ticket) = &incident.ticket else {
+            return Ok(false);
+        };
+        if ticket.provider != self.client.provider() {
+            return Ok(false);
+        }
+        let ticket_resolved = self.client.state(ticket).await? == TicketState::Resolved;
+        let incident_resolved = incident.is_resolved();
+        let action = sync_action(ticket, incident_resolved, ticket_resolved);
+        let provider = self.client.provider().as_str();
+        let now = Utc::now();
+
+        match action {
+            SyncAction::None => {}
+            SyncAction::ResolveTicket => {
+                let note = format!(
+                    "Incident {} resolved in the analytics hub",
+                    incident.incident_id
+                );
+                self.client.resolve(ticket, &note).await?;
+            }
+            SyncAction::ReopenTicket => {
+                let note = format!(
+                    "Incident {} reopened in the analytics hub",
+                    incident.incident_id
+                );
+                self.client.reopen(ticket, &note).await?;
+            }
+            SyncAction::ResolveIncident | SyncAction::ReopenIncident => {}
+        }
+
+        if action == SyncAction::None && ticket.synced_resolved == incident_resolved {
+            return Ok(false);
+        }
+        let updated = self.tracker.update(incident.incident_id, |incident| {
+            match action {
+                SyncAction::ResolveIncident => incident.set_status(
+                    IncidentStatus::Resolved,
+                    Some(provider.to_string()),
+                    Some(format!("Ticket {} resolved", ticket.number)),
+                    now,
+                )?,
+                SyncAction::ReopenIncident => incident.set_status(
+                    IncidentStatus::Open,
+                    Some(provider.to_string()),
+                    Some(format!("Ticket {} reopened", ticket.number)),
+                    now,
+                )?,
+                _ => {}
+            }
+            let resolved = incident.is_resolved();
+            if let Some(ticket) = incident.ticket.as_mut() {
+                ticket.synced_resolved = resolved;
+                ticket.synced_at = now;
+            }
+            Ok(())
+        })?;
+        self.persist(&updated).await?;
+        if action != SyncAction::None {
+            debug!(incident_id = %incident.incident_id, ?action, "Synced ticket resolution");
+            self.synced.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(action != SyncAction::None)
+    }
+
+    async fn persist(&self, incident: &Incident) -> Result<()> {
+        if let Some(db) = &self.database {
+            db.upsert_incident(incident).await?;
+        }
+        Ok(())
+    }
+
+    /// Sync on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.poll_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                self.sync_once().await;
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> TicketSyncStats {
+        TicketSyncStats {
+            provider: self.client.provider(),
+            created: self.created.load(Ordering::Relaxed),
+            synced: self.synced.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Ticket sync statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketSyncStats {
+    pub provider: TicketProvider,
+    pub created: u64,
+    pub synced: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::incidents::IncidentConfig;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+
+    /// Tickets held in memory, resolved or reopened by hand in tests
+    #[derive(Default)]
+    struct MemoryTickets {
+        states: Mutex<HashMap<String, TicketState>>,
+    }
+
+    impl MemoryTickets {
+        fn set(&self, ticket_id: &str, state: TicketState) {
+            self.states.lock().insert(ticket_id.to_string(), state);
+        }
+
+        fn get(&self, ticket_id: &str) -> TicketState {
+            self.states.lock()[ticket_id]
+        }
+    }
+
+    #[async_trait]
+    impl TicketClient for MemoryTickets {
+        fn provider(&self) -> TicketProvider {
+            TicketProvider::Jira
+        }
+
+        async fn create(&self, incident: &Incident) -> Result<TicketRef> {
+            let id = format!("OPS-{}", self.states.lock().len() + 1);
+            self.set(&id, TicketState::Open);
+            Ok(TicketRef {
+                provider: TicketProvider::Jira,
+                ticket_id: id.clone(),
+                number: id.clone(),
+                url: format!("https://jira.test/browse/{}", id),
+                synced_resolved: false,
+                synced_at: incident.opened_at,
+            })
+        }
+
+        async fn state(&self, ticket: &TicketRef) -> Result<TicketState> {
+            Ok(self.get(&ticket.ticket_id))
+        }
+
+        async fn resolve(&self, ticket: &TicketRef, _note: &str) -> Result<()> {
+            self.set(&ticket.ticket_id, TicketState::Resolved);
+            Ok(())
+        }
+
+        async fn reopen(&self, ticket: &TicketRef, _note: &str) -> Result<()> {
+            self.set(&ticket.ticket_id, TicketState::Open);
+            Ok(())
+        }
+    }
+
+    fn setup() -> (Arc<MemoryTickets>, Arc<IncidentTracker>, TicketSync) {
+        let tickets = Arc::new(MemoryTickets::default());
+        let tracker = Arc::new(IncidentTracker::new(IncidentConfig::default()));
+        let sync = TicketSync::new(tickets.clone(), tracker.clone(), TicketingConfig::default());
+        (tickets, tracker, sync)
+    }
+
+    #[tokio::test]
+    async fn test_only_critical_incidents_are_ticketed_once() {
+        let (_, tracker, sync) = setup();
+        let critical = Incident::new(
+            "Provider outage",
+            "production",
+            Severity::Critical,
+            Utc::now(),
+        );
+        let warning = Incident::new("Latency creep", "production", Severity::Warning, Utc::now());
+        tracker.add(critical.clone()).unwrap();
+        tracker.add(warning.clone()).unwrap();
+
+        let opened = sync
+            .open_ticket(critical.incident_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(opened.ticket.as_ref().unwrap().number, "OPS-1");
+        assert!(sync
+            .open_ticket(critical.incident_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(sync
+            .open_ticket(warning.incident_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(sync.sync_once().await, 0);
+        assert!(tracker.get(warning.incident_id).unwrap().ticket.is_none());
+        assert_eq!(sync.get_stats().created, 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolution_syncs_both_ways() {
+        let (tickets, tracker, sync) = setup();
+        let incident = Incident::new(
+            "Provider outage",
+            "production",
+            Severity::Critical,
+            Utc::now(),
+        );
+        let id = incident.incident_id;
+        tracker.add(incident).unwrap();
+        assert_eq!(sync.sync_once().await, 1);
+
+        // Resolved in the hub, so the ticket follows
+        tracker
+            .update(id, |i| {
+                i.set_status(IncidentStatus::Resolved, None, None, Utc::now())
+            })
+            .unwrap();
+        assert_eq!(sync.sync_once().await, 1);
+        assert_eq!(tickets.get("OPS-1"), TicketState::Resolved);
+        assert!(tracker.get(id).unwrap().ticket.unwrap().synced_resolved);
+
+        // Reopened in Jira, so the incident follows
+        tickets.set("OPS-1", TicketState::Open);
+        assert_eq!(sync.sync_once().await, 1);
+        let incident = tracker.get(id).unwrap();
+        assert_eq!(incident.status, IncidentStatus::Open);
+        assert!(!incident.ticket.unwrap().synced_resolved);
+
+        // Already in step
+        assert_eq!(sync.sync_once().await, 0);
+    }
+
+    #[test]
+    fn test_provider_parsing() {
+        assert_eq!(
+            "jira".parse::<TicketProvider>().unwrap(),
+            TicketProvider::Jira
+        );
+        assert_eq!(
+            "ServiceNow".parse::<TicketProvider>().unwrap(),
+            TicketProvider::ServiceNow
+        );
+        assert!("zendesk".parse::<TicketProvider>().is_err());
+    }
+}
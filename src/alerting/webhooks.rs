@@ -0,0 +1,1162 @@
+//! Webhooks
+//!
+//! Pushes hub findings (alerts, anomalies, and incident changes) to partner
+//! endpoints registered through the API. A registration names a URL, a shared
+//! secret, and optional filters on event type, minimum severity, and
+//! environment.
+//!
+//! Every matching finding becomes a delivery. Deliveries are signed with
+//! HMAC-SHA256 over `<timestamp>.<body>`, sent in the `X-Hub-Signature-256`
+//! header as `sha256=<hex>`, and retried with exponential backoff until they
+//! succeed or run out of attempts. Deliveries are kept in the
+//! `webhook_deliveries` log, and any of them can be replayed as a new
+//! delivery of the same event.
+//!
+//! Endpoints must be public. Registrations naming loopback, private, or
+//! link-local hosts (including cloud metadata at 169.254.169.254) are refused,
+//! and every delivery resolves its host again, connects only to the address it
+//! checked, and does not follow redirects. Findings inside a suppressing
+//! maintenance window are not delivered, as with paging and digests; their
+//! deliveries are logged as `suppressed` so they can be reviewed or replayed.
+
+use super::incidents::{Incident, IncidentSignal, IncidentStatus};
+use super::maintenance::{MaintenanceAction, MaintenanceRegistry, SERVICE_TAG};
+use crate::database::{AnomalyRow, Database};
+use crate::schemas::events::{AnalyticsEvent, Severity};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Header carrying the HMAC-SHA256 signature of the request
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+/// Header carrying the Unix timestamp included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-Hub-Timestamp";
+
+/// Header naming the event type, e.g. `incident.opened`
+pub const EVENT_HEADER: &str = "X-Hub-Event";
+
+/// Header carrying the delivery ID; replays get a new one
+pub const DELIVERY_HEADER: &str = "X-Hub-Delivery";
+
+/// Shortest secret accepted at registration
+const MIN_SECRET_LEN: usize = 16;
+
+/// Deliveries kept in memory when no database is configured
+const RECENT_DELIVERIES: usize = 1_000;
+
+/// Kind of finding pushed to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "alert.raised")]
+    AlertRaised,
+    #[serde(rename = "anomaly.detected")]
+    AnomalyDetected,
+    #[serde(rename = "incident.opened")]
+    IncidentOpened,
+    #[serde(rename = "incident.updated")]
+    IncidentUpdated,
+    #[serde(rename = "incident.resolved")]
+    IncidentResolved,
+}
+
+impl WebhookEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::AlertRaised => "alert.raised",
+            WebhookEventType::AnomalyDetected => "anomaly.detected",
+            WebhookEventType::IncidentOpened => "incident.opened",
+            WebhookEventType::IncidentUpdated => "incident.updated",
+            WebhookEventType::IncidentResolved => "incident.resolved",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEventType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Invalid webhook event type: {}", s))
+    }
+}
+
+/// A finding as delivered to webhooks; replays resend the same event ID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub severity: Severity,
+    pub environment: String,
+    /// Service the finding concerns, when known, for matching maintenance windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// The alert event, anomaly, or incident itself
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    pub fn from_alert(event: &AnalyticsEvent) -> Self {
+        Self {
+            event_id: Uuid::new_v4(),
+            event_type: WebhookEventType::AlertRaised,
+            occurred_at: event.common.timestamp,
+            severity: event.common.severity.clone(),
+            environment: event.common.environment.clone(),
+            service: event.common.tags.get(SERVICE_TAG).cloned(),
+            data: serde_json::to_value(event).unwrap_or_default(),
+        }
+    }
+
+    /// Event for a stored anomaly, with the severity and environment its
+    /// incident signal resolved
+    pub fn from_anomaly(row: &AnomalyRow, signal: &IncidentSignal) -> Self {
+        Self {
+            event_id: Uuid::new_v4(),
+            event_type: WebhookEventType::AnomalyDetected,
+            occurred_at: row.detected_at,
+            severity: signal.severity.clone(),
+            environment: signal.environment.clone(),
+            service: None,
+            data: serde_json::to_value(row).unwrap_or_default(),
+        }
+    }
+
+    /// Event for an incident that just changed: resolved incidents are
+    /// `incident.resolved`, ones with no history before their first entry
+    /// `incident.opened`, and anything else `incident.updated`
+    pub fn from_incident(incident: &Incident) -> Self {
+        let event_type = if incident.status == IncidentStatus::Resolved {
+            WebhookEventType::IncidentResolved
+        } else if incident.timeline.len() <= 1 {
+            WebhookEventType::IncidentOpened
+        } else {
+            WebhookEventType::IncidentUpdated
+        };
+        Self {
+            event_id: Uuid::new_v4(),
+            event_type,
+            occurred_at: incident.updated_at,
+            severity: incident.severity.clone(),
+            environment: incident.environment.clone(),
+            service: None,
+            data: serde_json::to_value(incident).unwrap_or_default(),
+        }
+    }
+}
+
+/// A registered webhook endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub webhook_id: Uuid,
+    pub url: String,
+    /// Shared HMAC secret; never returned by the API
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    /// Event types delivered; empty delivers every type
+    #[serde(default)]
+    pub event_types: Vec<WebhookEventType>,
+    /// Lowest severity delivered; `None` delivers every severity
+    pub min_severity: Option<Severity>,
+    /// Environment delivered; `None` delivers every environment
+    pub environment: Option<String>,
+    pub description: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    pub fn new(url: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            webhook_id: Uuid::new_v4(),
+            url: url.into(),
+            secret: secret.into(),
+            event_types: Vec::new(),
+            min_severity: None,
+            environment: None,
+            description: None,
+            created_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_event_types(mut self, event_types: Vec<WebhookEventType>) -> Self {
+        self.event_types = event_types;
+        self
+    }
+
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    pub fn with_created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    /// Reject registrations without an HTTP(S) URL to a public host, or with
+    /// a guessable secret. Host names are resolved by the transport.
+    pub fn validate(&self) -> Result<()> {
+        let url = reqwest::Url::parse(&self.url)
+            .with_context(|| format!("Invalid webhook URL: {}", self.url))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("Webhook URLs must use http or https");
+        }
+        let host = url
+            .host_str()
+            .context("Webhook URLs must name a host")?
+            .to_ascii_lowercase();
+        let literal = host.trim_start_matches('[').trim_end_matches(']');
+        if host == "localhost"
+            || host.ends_with(".localhost")
+            || literal.parse().is_ok_and(|ip| !is_public_ip(ip))
+        {
+            bail!("Webhook URLs must not point at loopback, private, or link-local hosts");
+        }
+        if self.secret.len() < MIN_SECRET_LEN {
+            bail!(
+                "Webhook secrets must be at least {} characters",
+                MIN_SECRET_LEN
+            );
+        }
+        if self
+            .environment
+            .as_deref()
+            .is_some_and(|e| e.trim().is_empty())
+        {
+            bail!("A webhook's environment filter cannot be empty");
+        }
+        Ok(())
+    }
+
+    /// Whether the webhook's filters accept `event`
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self
+                .min_severity
+                .as_ref()
+                .map_or(true, |min| &event.severity >= min)
+            && self
+                .environment
+                .as_deref()
+                .map_or(true, |env| env == event.environment)
+    }
+}
+
+/// Where a delivery stands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its first or next attempt
+    #[default]
+    Pending,
+    Delivered,
+    /// Ran out of attempts
+    Failed,
+    /// Held back by a maintenance window and never attempted
+    Suppressed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+            DeliveryStatus::Suppressed => "suppressed",
+        }
+    }
+}
+
+impl std::str::FromStr for DeliveryStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Invalid delivery status: {}", s))
+    }
+}
+
+/// One event sent, or to be sent, to one webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub delivery_id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the latest attempt, when a response was received
+    pub response_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// Delivery this one replays
+    pub replay_of: Option<Uuid>,
+}
+
+impl WebhookDelivery {
+    pub fn new(webhook_id: Uuid, event: WebhookEvent, now: DateTime<Utc>) -> Self {
+        Self {
+            delivery_id: Uuid::new_v4(),
+            webhook_id,
+            event,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            last_error: None,
+            created_at: now,
+            next_attempt_at: now,
+            delivered_at: None,
+            replay_of: None,
+        }
+    }
+
+    /// A fresh delivery of the same event to the same webhook
+    pub fn replay(&self, now: DateTime<Utc>) -> Self {
+        Self {
+            replay_of: Some(self.delivery_id),
+            ..Self::new(self.webhook_id, self.event.clone(), now)
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == DeliveryStatus::Pending && self.next_attempt_at <= now
+    }
+}
+
+/// Hex HMAC-SHA256 of `data` keyed by `key`
+fn hmac_sha256_hex(key: &[u8], data: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let mut out = String::with_capacity(64);
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+/// `X-Hub-Signature-256` value for a body sent at `timestamp`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(body);
+    format!("sha256={}", hmac_sha256_hex(secret.as_bytes(), &signed))
+}
+
+/// Whether `ip` is a public unicast address, rather than loopback, private,
+/// link-local (including cloud metadata), shared, or otherwise reserved
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+                // Benchmarking, 198.18.0.0/15
+                || (a == 198 && (b == 18 || b == 19))
+                // Reserved and broadcast, 240.0.0.0/4
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Resolve the host of a webhook URL, failing unless every address it
+/// resolves to is public; returns the host and the address to connect to
+pub async fn resolve_public(url: &str) -> Result<(String, SocketAddr)> {
+    let parsed =
+        reqwest::Url::parse(url).with_context(|| format!("Invalid webhook URL: {}", url))?;
+    let host = parsed
+        .host_str()
+        .context("Webhook URLs must name a host")?
+        .to_string();
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((literal, port))
+        .await
+        .with_context(|| format!("Failed to resolve webhook host {}", host))?
+        .collect();
+    let Some(first) = addrs.first().copied() else {
+        bail!("Webhook host {} did not resolve", host);
+    };
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        bail!(
+            "Webhook host {} resolves to non-public address {}",
+            host,
+            addr.ip()
+        );
+    }
+    Ok((host, first))
+}
+
+/// A signed request ready to send
+#[derive(Debug, Clone)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl WebhookRequest {
+    pub fn signed(
+        webhook: &WebhookSubscription,
+        delivery: &WebhookDelivery,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let body =
+            serde_json::to_vec(&delivery.event).context("Failed to serialize webhook event")?;
+        let timestamp = now.timestamp();
+        Ok(Self {
+            url: webhook.url.clone(),
+            headers: vec![
+                ("Content-Type", "application/json".to_string()),
+                (EVENT_HEADER, delivery.event.event_type.as_str().to_string()),
+                (DELIVERY_HEADER, delivery.delivery_id.to_string()),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+                (SIGNATURE_HEADER, sign(&webhook.secret, timestamp, &body)),
+            ],
+            body,
+        })
+    }
+}
+
+/// Sends signed requests, returning the HTTP status received
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    /// Refuse endpoints the transport must not reach; checked at registration
+    async fn check_destination(&self, _url: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn post(&self, request: &WebhookRequest) -> Result<u16>;
+}
+
+/// Sends webhooks over HTTP to public addresses only
+pub struct HttpTransport {
+    timeout: std::time::Duration,
+}
+
+impl HttpTransport {
+    pub fn new(timeout: std::time::Duration) -> Result<Self> {
+        Ok(Self { timeout })
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for HttpTransport {
+    async fn check_destination(&self, url: &str) -> Result<()> {
+        resolve_public(url).await.map(|_| ())
+    }
+
+    async fn post(&self, request: &WebhookRequest) -> Result<u16> {
+        // The client is pinned to the checked address so a second DNS answer
+        // cannot redirect the request to an internal host
+        let (host, addr) = resolve_public(&request.url).await?;
+        let http = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .build()
+            .context("Failed to build webhook HTTP client")?;
+        let mut builder = http.post(&request.url).body(request.body.clone());
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let response = builder.send().await.context("Failed to post webhook")?;
+        Ok(response.status().as_u16())
+    }
+}
+
+/// Webhook delivery configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Attempts before a delivery is marked failed
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each later one
+    pub initial_backoff_secs: i64,
+    pub max_backoff_secs: i64,
+    /// Per-request timeout
+    pub timeout_secs: u64,
+    /// Seconds between checks for due deliveries
+    pub poll_interval_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 3600,
+            timeout_secs: 10,
+            poll_interval_secs: 5,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_attempts),
+            initial_backoff_secs: std::env::var("WEBHOOK_INITIAL_BACKOFF_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.initial_backoff_secs),
+            max_backoff_secs: std::env::var("WEBHOOK_MAX_BACKOFF_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_backoff_secs),
+            timeout_secs: std::env::var("WEBHOOK_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_secs),
+            poll_interval_secs: std::env::var("WEBHOOK_POLL_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.poll_interval_secs),
+        }
+    }
+
+    /// Wait after the `attempts`th failed attempt
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(30);
+        let secs = self
+            .initial_backoff_secs
+            .max(1)
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff_secs.max(1));
+        Duration::seconds(secs)
+    }
+}
+
+/// Registered webhooks and the worker delivering findings to them
+pub struct WebhookDispatcher {
+    config: WebhookConfig,
+    transport: Arc<dyn WebhookTransport>,
+    database: Option<Arc<Database>>,
+    maintenance: Option<Arc<MaintenanceRegistry>>,
+    webhooks: RwLock<Vec<WebhookSubscription>>,
+    pending: Mutex<Vec<WebhookDelivery>>,
+    // Delivery log when there is no database
+    recent: Mutex<VecDeque<WebhookDelivery>>,
+    published: AtomicU64,
+    suppressed: AtomicU64,
+    delivered: AtomicU64,
+    retried: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let transport =
+            HttpTransport::new(std::time::Duration::from_secs(config.timeout_secs.max(1)))?;
+        Ok(Self::with_transport(config, Arc::new(transport)))
+    }
+
+    pub fn with_transport(config: WebhookConfig, transport: Arc<dyn WebhookTransport>) -> Self {
+        Self {
+            config,
+            transport,
+            database: None,
+            maintenance: None,
+            webhooks: RwLock::new(Vec::new()),
+            pending: Mutex::new(Vec::new()),
+            recent: Mutex::new(VecDeque::new()),
+            published: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+            delivered: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    /// Persist registrations and the delivery log
+    pub fn with_database(mut self, database: Arc<Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Hold back findings raised during suppressing maintenance windows
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceRegistry>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Load registrations and undelivered deliveries from storage, returning
+    /// the number of webhooks loaded
+    pub async fn load(&self) -> Result<usize> {
+        let Some(db) = &self.database else {
+            return Ok(0);
+        };
+        let webhooks = db.query_webhooks().await?;
+        let pending = db.query_pending_webhook_deliveries().await?;
+        let count = webhooks.len();
+        info!(webhooks = count, pending = pending.len(), "Loaded webhooks");
+        *self.webhooks.write() = webhooks;
+        *self.pending.lock() = pending;
+        Ok(count)
+    }
+
+    pub async fn register(&self, webhook: WebhookSubscription) -> Result<()> {
+        webhook.validate()?;
+        self.transport.check_destination(&webhook.url).await?;
+        if let Some(db) = &self.database {
+            db.store_webhook(&webhook).await?;
+        }
+        self.webhooks.write().push(webhook);
+        Ok(())
+    }
+
+    /// Remove a webhook and drop its undelivered deliveries, returning
+    /// whether it existed
+    pub async fn unregister(&self, webhook_id: Uuid) -> Result<bool> {
+        let mut removed = false;
+        if let Some(db) = &self.database {
+            removed = db.delete_webhook(webhook_id).await?;
+        }
+        let mut webhooks = self.webhooks.write();
+        let before = webhooks.len();
+        webhooks.retain(|w| w.webhook_id != webhook_id);
+        removed |= webhooks.len() < before;
+        self.pending.lock().retain(|d| d.webhook_id != webhook_id);
+        Ok(removed)
+    }
+
+    pub fn get(&self, webhook_id: Uuid) -> Option<WebhookSubscription> {
+        self.webhooks
+            .read()
+            .iter()
+            .find(|w| w.webhook_id == webhook_id)
+            .cloned()
+    }
+
+    /// Registered webhooks, oldest first
+    pub fn list(&self) -> Vec<WebhookSubscription> {
+        self.webhooks.read().clone()
+    }
+
+    /// Queue `event` for every webhook whose filters accept it, returning the
+    /// number of deliveries queued
+    pub async fn publish(&self, event: WebhookEvent) -> usize {
+        let now = Utc::now();
+        let deliveries: Vec<WebhookDelivery> = self
+            .webhooks
+            .read()
+            .iter()
+            .filter(|w| w.matches(&event))
+            .map(|w| WebhookDelivery::new(w.webhook_id, event.clone(), now))
+            .collect();
+        let count = deliveries.len();
+
+        // Suppressed deliveries are logged for review and can be replayed later
+        let window = self.maintenance.as_ref().and_then(|maintenance| {
+            maintenance.window_for(&event.environment, event.service.as_deref(), now)
+        });
+        if let Some(window) = window.filter(|w| w.action == MaintenanceAction::Suppress) {
+            for mut delivery in deliveries {
+                delivery.status = DeliveryStatus::Suppressed;
+                delivery.last_error = Some(format!(
+                    "Suppressed by maintenance window {}",
+                    window.window_id
+                ));
+                self.log(&delivery).await;
+            }
+            self.suppressed.fetch_add(count as u64, Ordering::Relaxed);
+            debug!(
+                window_id = %window.window_id,
+                event_type = event.event_type.as_str(),
+                deliveries = count,
+                "Webhook deliveries suppressed during maintenance"
+            );
+            return 0;
+        }
+        for delivery in deliveries {
+            self.enqueue(delivery).await;
+        }
+        if count > 0 {
+            self.published.fetch_add(count as u64, Ordering::Relaxed);
+            debug!(
+                event_type = event.event_type.as_str(),
+                deliveries = count,
+                "Queued webhook deliveries"
+            );
+        }
+        count
+    }
+
+    /// Queue the event of an earlier delivery to `webhook_id` again as a new
+    /// delivery, returning `None` when the webhook has no such delivery
+    pub async fn replay(
+        &self,
+        webhook_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<Option<WebhookDelivery>> {
+        if self.get(webhook_id).is_none() {
+            return Ok(None);
+        }
+        let Some(original) = self
+            .find_delivery(delivery_id)
+            .await?
+            .filter(|d| d.webhook_id == webhook_id)
+        else {
+            return Ok(None);
+        };
+        let replay = original.replay(Utc::now());
+        self.enqueue(replay.clone()).await;
+        Ok(Some(replay))
+    }
+
+    async fn find_delivery(&self, delivery_id: Uuid) -> Result<Option<WebhookDelivery>> {
+        if let Some(db) = &self.database {
+            return db.query_webhook_delivery(delivery_id).await;
+        }
+        Ok(self
+            .recent
+            .lock()
+            .iter()
+            .find(|d| d.delivery_id == delivery_id)
+            .cloned())
+    }
+
+    /// Deliveries to a webhook, newest first
+    pub async fn deliveries(&self, webhook_id: Uuid, limit: usize) -> Result<Vec<WebhookDelivery>> {
+        if let Some(db) = &self.database {
+            return db.query_webhook_deliveries(webhook_id, limit as i64).await;
+        }
+        Ok(self
+            .recent
+            .lock()
+            .iter()
+            .rev()
+            .filter(|d| d.webhook_id == webhook_id)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn enqueue(&self, delivery: WebhookDelivery) {
+        self.log(&delivery).await;
+        self.pending.lock().push(delivery);
+    }
+
+    async fn log(&self, delivery: &WebhookDelivery) {
+        match &self.database {
+            Some(db) => {
+                if let Err(e) = db.upsert_webhook_delivery(delivery).await {
+                    warn!(delivery_id = %delivery.delivery_id, "Failed to log webhook delivery: {}", e);
+                }
+            }
+            None => {
+                let mut recent = self.recent.lock();
+                match recent
+                    .iter_mut()
+                    .find(|d| d.delivery_id == delivery.delivery_id)
+                {
+                    Some(logged) => *logged = delivery.clone(),
+                    None => {
+                        recent.push_back(delivery.clone());
+                        if recent.len() > RECENT_DELIVERIES {
+                            recent.pop_front();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Attempt every due delivery, returning the number delivered
+    pub async fn deliver_due(&self, now: DateTime<Utc>) -> usize {
+        let due: Vec<WebhookDelivery> = {
+            let mut pending = self.pending.lock();
+            let (due, waiting) = pending.drain(..).partition(|d| d.is_due(now));
+            *pending = waiting;
+            due
+        };
+
+        let mut delivered = 0;
+        for mut delivery in due {
+            self.attempt(&mut delivery, now).await;
+            self.log(&delivery).await;
+            match delivery.status {
+                DeliveryStatus::Delivered => delivered += 1,
+                DeliveryStatus::Pending => self.pending.lock().push(delivery),
+                DeliveryStatus::Failed | DeliveryStatus::Suppressed => {}
+            }
+        }
+        delivered
+    }
+
+    async fn attempt(&self, delivery: &mut WebhookDelivery, now: DateTime<Utc>) {
+        let Some(webhook) = self.get(delivery.webhook_id) else {
+            delivery.status = DeliveryStatus::Failed;
+            delivery.last_error = Some("Webhook no longer exists".to_string());
+            return;
+        };
+
+        delivery.attempts += 1;
+        let outcome = match WebhookRequest::signed(&webhook, delivery, now) {
+            Ok(request) => self.transport.post(&request).await,
+            Err(e) => Err(e),
+        };
+        let error = match outcome {
+            Ok(status) if (200..300).contains(&status) => {
+                delivery.status = DeliveryStatus::Delivered;
+                delivery.response_status = Some(status);
+                delivery.last_error = None;
+                delivery.delivered_at = Some(now);
+                self.delivered.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            Ok(status) => {
+                delivery.response_status = Some(status);
+                format!("Endpoint responded with HTTP {}", status)
+            }
+            Err(e) => {
+                delivery.response_status = None;
+                e.to_string()
+            }
+        };
+
+        if delivery.attempts >= self.config.max_attempts {
+            warn!(
+                webhook_id = %delivery.webhook_id,
+                delivery_id = %delivery.delivery_id,
+                attempts = delivery.attempts,
+                "Webhook delivery failed: {}", error
+            );
+            delivery.status = DeliveryStatus::Failed;
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            delivery.next_attempt_at = now + self.config.backoff(delivery.attempts);
+            self.retried.fetch_add(1, Ordering::Relaxed);
+        }
+        delivery.last_error = Some(error);
+    }
+
+    /// Deliver due deliveries on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.poll_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                self.deliver_due(Utc::now()).await;
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> WebhookStats {
+        WebhookStats {
+            webhooks: self.webhooks.read().len(),
+            pending: self.pending.lock().len(),
+            published: self.published.load(Ordering::Relaxed),
+            suppressed: self.suppressed.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Webhook delivery statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookStats {
+    pub webhooks: usize,
+    pub pending: usize,
+    pub published: u64,
+    /// Deliveries held back by a maintenance window
+    pub suppressed: u64,
+    pub delivered: u64,
+    pub retried: u64,
+    pub failed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records requests and answers with queued statuses, then 200
+    struct ScriptedTransport {
+        statuses: Mutex<VecDeque<u16>>,
+        requests: Mutex<Vec<WebhookRequest>>,
+    }
+
+    impl ScriptedTransport {
+        fn new(statuses: &[u16]) -> Arc<Self> {
+            Arc::new(Self {
+                statuses: Mutex::new(statuses.iter().copied().collect()),
+                requests: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl WebhookTransport for ScriptedTransport {
+        async fn post(&self, request: &WebhookRequest) -> Result<u16> {
+            self.requests.lock().push(request.clone());
+            Ok(self.statuses.lock().pop_front().unwrap_or(200))
+        }
+    }
+
+    fn event(event_type: WebhookEventType, severity: Severity) -> WebhookEvent {
+        WebhookEvent {
+            event_id: Uuid::new_v4(),
+            event_type,
+            occurred_at: Utc::now(),
+            severity,
+            environment: "production".to_string(),
+            service: None,
+            data: serde_json::json!({}),
+        }
+    }
+
+    fn header<'a>(request: &'a WebhookRequest, name: &str) -> &'a str {
+        request
+            .headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            sign("Jefe", 1_700_000_000, b"{}"),
+            format!("sha256={}", hmac_sha256_hex(b"Jefe", b"1700000000.{}"))
+        );
+    }
+
+    #[test]
+    fn test_subscription_filters() {
+        let webhook = WebhookSubscription::new("https://partner.test/hook", "0123456789abcdef")
+            .with_event_types(vec![WebhookEventType::IncidentOpened])
+            .with_min_severity(Severity::Error)
+            .with_environment("production");
+        assert!(webhook.validate().is_ok());
+
+        assert!(webhook.matches(&event(WebhookEventType::IncidentOpened, Severity::Critical)));
+        assert!(!webhook.matches(&event(WebhookEventType::IncidentOpened, Severity::Warning)));
+        assert!(!webhook.matches(&event(WebhookEventType::AlertRaised, Severity::Critical)));
+
+        let mut staging = event(WebhookEventType::IncidentOpened, Severity::Critical);
+        staging.environment = "staging".to_string();
+        assert!(!webhook.matches(&staging));
+
+        assert!(
+            WebhookSubscription::new("https://partner.test/hook", "short")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            WebhookSubscription::new("ftp://partner.test", "0123456789abcdef")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_public_destinations_are_refused() {
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://127.0.0.1:8080/hook",
+            "http://10.0.0.7/hook",
+            "http://[::1]/hook",
+            "http://[::ffff:192.168.1.1]/hook",
+            "http://localhost:9090/hook",
+        ] {
+            let webhook = WebhookSubscription::new(url, "0123456789abcdef");
+            assert!(webhook.validate().is_err(), "{} was accepted", url);
+        }
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(!is_public_ip("100.64.0.1".parse().unwrap()));
+        assert!(!is_public_ip("fd00::1".parse().unwrap()));
+
+        // Addresses are checked again when the host is resolved
+        assert!(resolve_public("http://127.0.0.1/hook").await.is_err());
+        let (host, addr) = resolve_public("https://93.184.216.34/hook").await.unwrap();
+        assert_eq!(host, "93.184.216.34");
+        assert_eq!(addr.port(), 443);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = WebhookConfig::default();
+        assert_eq!(config.backoff(1), Duration::seconds(10));
+        assert_eq!(config.backoff(3), Duration::seconds(40));
+        assert_eq!(config.backoff(20), Duration::seconds(3600));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_and_replays() {
+        let transport = ScriptedTransport::new(&[500, 503]);
+        let config = WebhookConfig {
+            max_attempts: 3,
+            ..WebhookConfig::default()
+        };
+        let dispatcher = WebhookDispatcher::with_transport(config.clone(), transport.clone());
+        let webhook = WebhookSubscription::new("https://partner.test/hook", "0123456789abcdef");
+        dispatcher.register(webhook.clone()).await.unwrap();
+
+        let queued = dispatcher
+            .publish(event(WebhookEventType::AlertRaised, Severity::Error))
+            .await;
+        assert_eq!(queued, 1);
+
+        // Failed attempts wait out the backoff before the next one
+        let now = Utc::now();
+        assert_eq!(dispatcher.deliver_due(now).await, 0);
+        assert_eq!(dispatcher.deliver_due(now).await, 0);
+        assert_eq!(transport.requests.lock().len(), 1);
+        let later = now + config.backoff(1);
+        assert_eq!(dispatcher.deliver_due(later).await, 0);
+        let later = later + config.backoff(2);
+        assert_eq!(dispatcher.deliver_due(later).await, 1);
+
+        let log = dispatcher.deliveries(webhook.webhook_id, 10).await.unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].status, DeliveryStatus::Delivered);
+        assert_eq!(log[0].attempts, 3);
+
+        // Requests are signed over the timestamp and body
+        let request = transport.requests.lock().last().cloned().unwrap();
+        let timestamp: i64 = header(&request, TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(
+            header(&request, SIGNATURE_HEADER),
+            sign(&webhook.secret, timestamp, &request.body)
+        );
+        assert_eq!(header(&request, EVENT_HEADER), "alert.raised");
+
+        // A replay is a new delivery of the same event
+        assert!(dispatcher
+            .replay(Uuid::new_v4(), log[0].delivery_id)
+            .await
+            .unwrap()
+            .is_none());
+        let replay = dispatcher
+            .replay(webhook.webhook_id, log[0].delivery_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replay.replay_of, Some(log[0].delivery_id));
+        assert_eq!(replay.event.event_id, log[0].event.event_id);
+        assert_eq!(dispatcher.deliver_due(Utc::now()).await, 1);
+        assert_eq!(
+            dispatcher
+                .deliveries(webhook.webhook_id, 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let stats = dispatcher.get_stats();
+        assert_eq!((stats.delivered, stats.retried, stats.failed), (2, 2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_windows_suppress_deliveries() {
+        use crate::alerting::MaintenanceWindow;
+
+        let maintenance = Arc::new(MaintenanceRegistry::new());
+        let dispatcher = WebhookDispatcher::with_transport(
+            WebhookConfig::default(),
+            ScriptedTransport::new(&[]),
+        )
+        .with_maintenance(maintenance.clone());
+        let webhook = WebhookSubscription::new("https://partner.test/hook", "0123456789abcdef");
+        dispatcher.register(webhook).await.unwrap();
+
+        let now = Utc::now();
+        let deploy = MaintenanceWindow::new("production", now, Duration::minutes(30));
+        let deploy_id = deploy.window_id;
+        maintenance.add(deploy).unwrap();
+        let alert = event(WebhookEventType::AlertRaised, Severity::Critical);
+        assert_eq!(dispatcher.publish(alert.clone()).await, 0);
+        assert_eq!(dispatcher.get_stats().suppressed, 1);
+        let webhook_id = dispatcher.list()[0].webhook_id;
+        let log = dispatcher.deliveries(webhook_id, 10).await.unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].status, DeliveryStatus::Suppressed);
+        assert_eq!(dispatcher.deliver_due(Utc::now()).await, 0);
+
+        // Tagging windows deliver as usual
+        maintenance.end(deploy_id, now);
+        maintenance
+            .add(
+                MaintenanceWindow::new("production", now, Duration::minutes(30))
+                    .with_action(MaintenanceAction::Tag),
+            )
+            .unwrap();
+        assert_eq!(dispatcher.publish(alert).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_gives_up_after_max_attempts() {
+        let transport = ScriptedTransport::new(&[500, 500]);
+        let config = WebhookConfig {
+            max_attempts: 2,
+            ..WebhookConfig::default()
+        };
+        let dispatcher = WebhookDispatcher::with_transport(config, transport);
+        let webhook = WebhookSubscription::new("https://partner.test/hook", "0123456789abcdef");
+        dispatcher.register(webhook.clone()).await.unwrap();
+        dispatcher
+            .publish(event(WebhookEventType::IncidentResolved, Severity::Info))
+            .await;
+
+        let far_future = Utc::now() + Duration::days(1);
+        dispatcher.deliver_due(far_future).await;
+        dispatcher.deliver_due(far_future + Duration::days(1)).await;
+
+        let log = dispatcher.deliveries(webhook.webhook_id, 10).await.unwrap();
+        assert_eq!(log[0].status, DeliveryStatus::Failed);
+        assert_eq!(log[0].response_status, Some(500));
+        assert_eq!(dispatcher.get_stats().pending, 0);
+    }
+}
@@ -5,19 +5,22 @@
 use crate::models::metrics::{
     AggregatedMetric, MetricValues, StatisticalMeasures, TimeWindow,
 };
+use crate::pipeline::sampling::{sampling_rate_from_tags, SAMPLING_RATE_TAG};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tracing::debug;
 
-use super::AnalyticsConfig;
+use super::{AnalyticsConfig, SharedConfig};
 
 /// Real-time aggregation engine
 pub struct AggregationEngine {
-    #[allow(dead_code)]
-    config: Arc<AnalyticsConfig>,
+    config: SharedConfig,
+    // Configuration generation the windows were last synced to
+    synced_generation: AtomicU64,
     // Window -> Metric Name -> Aggregation State
     aggregations: Arc<DashMap<TimeWindow, DashMap<String, AggregationState>>>,
 }
@@ -25,18 +28,38 @@ pub struct AggregationEngine {
 impl AggregationEngine {
     /// Create a new aggregation engine
     pub async fn new(config: Arc<AnalyticsConfig>) -> Result<Self> {
-        let aggregations = Arc::new(DashMap::new());
+        Ok(Self::with_shared_config(SharedConfig::new(config)))
+    }
+
+    /// Create an engine following a reloadable configuration
+    pub fn with_shared_config(config: SharedConfig) -> Self {
+        let engine = Self {
+            synced_generation: AtomicU64::new(config.generation()),
+            config,
+            aggregations: Arc::new(DashMap::new()),
+        };
 
         // Initialize aggregation windows
-        for &window_secs in &config.aggregation_windows {
-            let window = Self::seconds_to_window(window_secs);
-            aggregations.insert(window, DashMap::new());
-        }
+        engine.sync_windows();
+        engine
+    }
 
-        Ok(Self {
-            config,
-            aggregations,
-        })
+    /// Add windows introduced by the active configuration and drop removed ones
+    pub fn sync_windows(&self) {
+        let generation = self.config.generation();
+        let windows: HashSet<TimeWindow> = self
+            .config
+            .load()
+            .aggregation_windows
+            .iter()
+            .map(|&secs| Self::seconds_to_window(secs))
+            .collect();
+
+        self.aggregations.retain(|window, _| windows.contains(window));
+        for window in windows {
+            self.aggregations.entry(window).or_default();
+        }
+        self.synced_generation.store(generation, Ordering::Release);
     }
 
     /// Convert seconds to TimeWindow enum
@@ -54,14 +77,21 @@ impl AggregationEngine {
         }
     }
 
-    /// Add a data point to aggregation
+    /// Add a data point to aggregation.
+    ///
+    /// Points from sampled events carry their rate in the `sampling_rate` tag.
     pub fn add_point(
         &self,
         metric_name: &str,
         value: f64,
         timestamp: DateTime<Utc>,
-        _tags: HashMap<String, String>,
+        tags: HashMap<String, String>,
     ) -> Result<()> {
+        if self.synced_generation.load(Ordering::Acquire) != self.config.generation() {
+            self.sync_windows();
+        }
+        let sampling_rate = sampling_rate_from_tags(&tags);
+
         for window_map in self.aggregations.iter() {
             let _window = *window_map.key();
             let metrics = window_map.value();
@@ -69,7 +99,7 @@ impl AggregationEngine {
             metrics
                 .entry(metric_name.to_string())
                 .or_insert_with(AggregationState::new)
-                .add_value(value, timestamp);
+                .add_value(value, timestamp, sampling_rate);
         }
 
         debug!(
@@ -91,13 +121,18 @@ impl AggregationEngine {
         let stats = state.calculate_statistics();
         let (window_start, window_end) = state.get_time_bounds();
 
+        let mut tags = HashMap::new();
+        if let Some(rate) = state.effective_sampling_rate() {
+            tags.insert(SAMPLING_RATE_TAG.to_string(), format!("{:.4}", rate));
+        }
+
         Some(AggregatedMetric {
             name: metric_name.to_string(),
             window,
             window_start,
             window_end,
             values: MetricValues::Stats(stats),
-            tags: HashMap::new(),
+            tags,
         })
     }
 
@@ -157,6 +192,8 @@ struct AggregationState {
     timestamps: Vec<DateTime<Utc>>,
     min_timestamp: Option<DateTime<Utc>>,
     max_timestamp: Option<DateTime<Utc>>,
+    /// Estimated number of source events the sampled values stand for
+    represented: f64,
 }
 
 impl AggregationState {
@@ -166,12 +203,14 @@ impl AggregationState {
             timestamps: Vec::new(),
             min_timestamp: None,
             max_timestamp: None,
+            represented: 0.0,
         }
     }
 
-    fn add_value(&mut self, value: f64, timestamp: DateTime<Utc>) {
+    fn add_value(&mut self, value: f64, timestamp: DateTime<Utc>, sampling_rate: f64) {
         self.values.push(value);
         self.timestamps.push(timestamp);
+        self.represented += 1.0 / sampling_rate;
 
         if self.min_timestamp.is_none() || timestamp < self.min_timestamp.unwrap() {
             self.min_timestamp = Some(timestamp);
@@ -226,7 +265,17 @@ impl AggregationState {
             stddev: Some(stddev),
             count,
             sum,
+            histogram: None,
+        }
+    }
+
+    /// Fraction of source events retained, if any values were sampled
+    fn effective_sampling_rate(&self) -> Option<f64> {
+        let kept = self.values.len() as f64;
+        if kept == 0.0 || self.represented <= kept {
+            return None;
         }
+        Some(kept / self.represented)
     }
 
     fn get_time_bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
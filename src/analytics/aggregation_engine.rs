@@ -1,76 +1,336 @@
 //! Time-Series Aggregation Engine
 //!
 //! High-performance aggregation of events into statistical measures across
-//! multiple time windows (1m, 5m, 15m, 1h, 6h, 1d, 1w, 1M).
+//! multiple time windows (1m, 5m, 15m, 1h, 6h, 1d, 1w, 1M). Windows are keyed
+//! by event time and closed by a watermark (see `windowing`), so out-of-order
+//! events land in the window they belong to and late events re-emit corrected
+//! rollups. In exactly-once mode a window is instead written a single time,
+//! when its allowed lateness has passed, and its in-flight values can be read
+//! as preliminary rows until then. Closed windows are written to any
+//! `StorageBackend`, including the in-memory one used by benchmarks. Derived
+//! metrics (see `derived`) are
+//! re-evaluated whenever one of their input windows is written, and windows
+//! can carry a bucketed histogram so percentiles merge across windows.
+//! Events processed with their Kafka offset are tracked until every window
+//! they touched is in storage, so consumers only commit what a restart would
+//! not lose (see `flushed_offsets`).
+//! Values from sampled events are weighted by the inverse of their
+//! `sampling_rate` tag instead of starting a series per rate, and a window
+//! built from sampled events is stored with its effective rate in a companion
+//! `<metric>.sampling_rate` series.
+//! Every series carries the environment of its events as the `environment`
+//! tag, so queries can be scoped to one environment.
 
-use crate::database::Database;
+use super::derived::DerivedMetric;
+use super::windowing::{EmissionMode, LateArrivalStats, Watermark, WatermarkConfig, WindowState};
+use crate::database::environment::ENVIRONMENT_TAG;
+use crate::database::{AggregatedMetricRow, StorageBackend};
+use crate::export::prometheus::HubMetrics;
+use crate::models::histogram::{Histogram, HistogramLayout};
 use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::pipeline::cardinality::CardinalityGuard;
+use crate::pipeline::hot_cache::tags_contain;
+use crate::pipeline::metric_filter::MetricFilter;
+use crate::pipeline::sampling::{sampling_rate_of, SAMPLING_RATE_TAG};
 use crate::schemas::events::AnalyticsEvent;
-use anyhow::{Context, Result};
+use crate::telemetry::record_event_context;
+use crate::tenancy::TenantScope;
+use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
-use std::collections::HashMap;
+use sqlx::types::Json;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, instrument};
 
+/// Name of the series holding the effective sampling rate of `metric_name`'s windows
+pub fn sampling_rate_metric(metric_name: &str) -> String {
+    format!("{}.{}", metric_name, SAMPLING_RATE_TAG)
+}
+
 /// Aggregation engine for time-series data
 pub struct AggregationEngine {
-    database: Arc<Database>,
-    // Metric name + window -> Aggregated data
+    database: Arc<dyn StorageBackend>,
+    // Metric name + window + window start -> Aggregated data
     aggregates: Arc<DashMap<AggregateKey, WindowedAggregates>>,
-    // Active time buckets
-    active_buckets: Arc<RwLock<HashMap<TimeWindow, DateTime<Utc>>>>,
+    // Event-time watermark and the last minute windows were closed at
+    clock: Arc<RwLock<WindowClock>>,
+    late_arrivals: Arc<LateArrivalStats>,
+    derived: Vec<DerivedMetric>,
+    // Histogram bucket bounds; no histograms are stored when unset
+    histogram_bounds: Option<Vec<f64>>,
+    emission: EmissionMode,
+    windows_emitted: AtomicU64,
+    // Allowlist/denylist applied to extracted metrics; everything is kept when unset
+    metric_filter: Option<Arc<MetricFilter>>,
+    // Per-metric series limit; tags are aggregated as they arrive when unset
+    cardinality: Option<Arc<CardinalityGuard>>,
+    // Partition -> highest offset processed through `process_record`
+    consumed_offsets: DashMap<i32, i64>,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 struct AggregateKey {
     metric_name: String,
     window: TimeWindow,
+    window_start: DateTime<Utc>,
     tags_hash: u64,
 }
 
+struct WindowClock {
+    watermark: Watermark,
+    last_sweep: Option<DateTime<Utc>>,
+}
+
+/// What happened to a value added to one window
+enum Placement {
+    Merged,
+    /// Merged into a finalized window, which was re-emitted
+    Corrected,
+    /// The window was past its allowed lateness
+    Dropped,
+}
+
 impl AggregationEngine {
     /// Create a new aggregation engine
-    pub fn new(database: Arc<Database>) -> Self {
+    pub fn new(database: Arc<dyn StorageBackend>) -> Self {
         Self {
             database,
             aggregates: Arc::new(DashMap::new()),
-            active_buckets: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(RwLock::new(WindowClock {
+                watermark: Watermark::new(WatermarkConfig::default()),
+                last_sweep: None,
+            })),
+            late_arrivals: Arc::new(LateArrivalStats::new()),
+            derived: Vec::new(),
+            histogram_bounds: None,
+            emission: EmissionMode::default(),
+            windows_emitted: AtomicU64::new(0),
+            metric_filter: None,
+            cardinality: None,
+            consumed_offsets: DashMap::new(),
+        }
+    }
+
+    /// Store a histogram bucketed by `layout` with every window
+    pub fn with_histograms(mut self, layout: &HistogramLayout) -> Result<Self> {
+        self.histogram_bounds = Some(layout.bounds()?);
+        Ok(self)
+    }
+
+    /// Evaluate these derived metrics alongside the rollups they read
+    pub fn with_derived_metrics(mut self, derived: Vec<DerivedMetric>) -> Self {
+        self.derived = derived;
+        self
+    }
+
+    /// Close windows with the given watermark settings instead of the defaults
+    pub fn with_watermark_config(self, config: WatermarkConfig) -> Self {
+        Self {
+            clock: Arc::new(RwLock::new(WindowClock {
+                watermark: Watermark::new(config),
+                last_sweep: None,
+            })),
+            ..self
         }
     }
 
+    /// Write each window once its allowed lateness has passed, or on every correction
+    pub fn with_emission_mode(mut self, emission: EmissionMode) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    /// Drop metric series rejected by allowlist/denylist rules before aggregating them
+    pub fn with_metric_filter(mut self, filter: Arc<MetricFilter>) -> Self {
+        self.metric_filter = Some(filter);
+        self
+    }
+
+    /// Drop runaway tags, or samples of new series, once a metric exceeds its series limit
+    pub fn with_cardinality_guard(mut self, guard: Arc<CardinalityGuard>) -> Self {
+        self.cardinality = Some(guard);
+        self
+    }
+
     /// Process an event and update aggregations
-    #[instrument(skip(self, event))]
     pub async fn process_event(&self, event: &AnalyticsEvent) -> Result<()> {
+        self.aggregate(event, None).await
+    }
+
+    /// Process an event consumed from `partition` at `offset`, tracking the
+    /// offset until every window the event touched is in storage
+    pub async fn process_record(
+        &self,
+        event: &AnalyticsEvent,
+        partition: i32,
+        offset: i64,
+    ) -> Result<()> {
+        self.aggregate(event, Some((partition, offset))).await?;
+        self.consumed_offsets
+            .entry(partition)
+            .and_modify(|highest| *highest = (*highest).max(offset))
+            .or_insert(offset);
+        Ok(())
+    }
+
+    /// Per partition, the offset of the first record whose windows are not
+    /// all in storage yet, or the one after the last record processed. This
+    /// is the offset a consumer can commit without losing aggregated data.
+    pub fn flushed_offsets(&self) -> HashMap<i32, i64> {
+        let mut offsets: HashMap<i32, i64> = self
+            .consumed_offsets
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value() + 1))
+            .collect();
+        for agg in self.aggregates.iter() {
+            if agg.emitted {
+                continue;
+            }
+            for (partition, first) in &agg.source_offsets {
+                if let Some(next) = offsets.get_mut(partition) {
+                    *next = (*next).min(*first);
+                }
+            }
+        }
+        offsets
+    }
+
+    /// Aggregate an event, noting its source offset on every window it joins
+    #[instrument(
+        name = "pipeline.aggregate",
+        skip(self, event),
+        fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+    )]
+    async fn aggregate(&self, event: &AnalyticsEvent, source: Option<(i32, i64)>) -> Result<()> {
+        record_event_context(&event.common);
+
+        // The sampling rate weights the event's values rather than naming a series
+        let sampling_rate = sampling_rate_of(event);
+        let mut event_tags = Cow::Borrowed(&event.common.tags);
+        if event_tags.contains_key(SAMPLING_RATE_TAG) {
+            event_tags.to_mut().remove(SAMPLING_RATE_TAG);
+        }
+
         // Extract numeric metrics from the event
-        let metrics = self.extract_metrics(event)?;
+        let mut metrics = self.extract_metrics(event)?;
+        if let Some(filter) = &self.metric_filter {
+            filter.retain(&mut metrics, &event_tags);
+        }
+        let timestamp = event.common.timestamp;
+
+        // Place the event against the watermark as it stood before it arrived
+        let watermark = self.clock.read().await.watermark;
+        let late = matches!(watermark.current(), Some(w) if timestamp < w);
+        let mut corrected = 0;
+        let mut dropped = false;
 
         for (metric_name, value) in metrics {
+            let mut tags = match &self.cardinality {
+                Some(guard) => match guard.apply(&metric_name, &event_tags) {
+                    Some(tags) => tags,
+                    None => continue,
+                },
+                None => Cow::Borrowed(event_tags.as_ref()),
+            };
+            // Series are split by environment after the cardinality guard, which
+            // may never drop it
+            let environment = &event.common.environment;
+            if tags.get(ENVIRONMENT_TAG) != Some(environment) {
+                tags.to_mut()
+                    .insert(ENVIRONMENT_TAG.to_string(), environment.clone());
+            }
+            let tags_hash = self.hash_tags(&tags);
             // Aggregate across all time windows
             for window in Self::all_windows() {
-                self.update_aggregation(
-                    &metric_name,
-                    value,
-                    window,
-                    event.common.timestamp,
-                    &event.common.tags,
-                )
-                .await?;
+                let placement = self
+                    .update_aggregation(
+                        &metric_name,
+                        value,
+                        sampling_rate,
+                        *window,
+                        timestamp,
+                        &tags,
+                        tags_hash,
+                        &watermark,
+                        source,
+                    )
+                    .await?;
+                match placement {
+                    Placement::Merged => {}
+                    Placement::Corrected => corrected += 1,
+                    Placement::Dropped => dropped = true,
+                }
             }
         }
 
+        if late {
+            self.late_arrivals
+                .record(event.common.source_module.as_str(), corrected, dropped);
+        }
+
+        // Windows can only close when the watermark enters a new minute
+        let sweep = {
+            let mut clock = self.clock.write().await;
+            clock.watermark.observe(timestamp);
+            match clock.watermark.current() {
+                Some(current) => {
+                    let minute = self.align_to_window(current, TimeWindow::OneMinute);
+                    if matches!(clock.last_sweep, Some(last) if last >= minute) {
+                        None
+                    } else {
+                        clock.last_sweep = Some(minute);
+                        Some(clock.watermark)
+                    }
+                }
+                None => None,
+            }
+        };
+        if let Some(watermark) = sweep {
+            self.close_windows(&watermark).await?;
+        }
+
         Ok(())
     }
 
+    /// Delay between now and the newest event aggregated so far
+    pub async fn aggregation_lag(&self) -> Option<Duration> {
+        self.clock
+            .read()
+            .await
+            .watermark
+            .max_event_time()
+            .map(|w| (Utc::now() - w).max(Duration::zero()))
+    }
+
+    /// Event time up to which windows have been closed
+    pub async fn watermark(&self) -> Option<DateTime<Utc>> {
+        self.clock.read().await.watermark.current()
+    }
+
+    /// Windows written to storage so far, counting corrections
+    pub fn windows_emitted(&self) -> u64 {
+        self.windows_emitted.load(Ordering::Relaxed)
+    }
+
+    /// Events that arrived behind the watermark, per source module
+    pub fn late_arrivals(&self) -> &LateArrivalStats {
+        &self.late_arrivals
+    }
+
     /// Extract numeric metrics from an event
-    fn extract_metrics(&self, event: &AnalyticsEvent) -> Result<Vec<(String, f64)>> {
+    pub(crate) fn extract_metrics(&self, event: &AnalyticsEvent) -> Result<Vec<(String, f64)>> {
         let mut metrics = Vec::new();
 
         // This is a simplified extraction - in reality, you'd parse the payload
-        // based on event type and extract relevant metrics
+        // based on event type and extract relevant metrics. Payloads serialize
+        // as `{"payload_type": .., "data": {..}}`, so the variant's fields sit
+        // under `data`.
         if let Ok(value) = serde_json::to_value(&event.payload) {
-            if let Some(obj) = value.as_object() {
+            if let Some(obj) = value.get("data").and_then(|data| data.as_object()) {
                 for (key, val) in obj {
                     if let Some(num) = val.as_f64() {
                         metrics.push((key.clone(), num));
@@ -83,60 +343,243 @@ impl AggregationEngine {
     }
 
     /// Update aggregation for a specific metric and time window
+    #[allow(clippy::too_many_arguments)]
     async fn update_aggregation(
         &self,
         metric_name: &str,
         value: f64,
+        sampling_rate: f64,
         window: TimeWindow,
         timestamp: DateTime<Utc>,
         tags: &HashMap<String, String>,
-    ) -> Result<()> {
+        tags_hash: u64,
+        watermark: &Watermark,
+        source: Option<(i32, i64)>,
+    ) -> Result<Placement> {
         let window_start = self.align_to_window(timestamp, window);
-        let tags_hash = self.hash_tags(tags);
+        let window_end = window_start + Duration::seconds(window.to_seconds() as i64);
+        if watermark.classify(window_end) == WindowState::Expired {
+            return Ok(Placement::Dropped);
+        }
 
         let key = AggregateKey {
             metric_name: metric_name.to_string(),
             window,
+            window_start,
             tags_hash,
         };
 
-        // Get or create windowed aggregates
-        let mut agg = self.aggregates
-            .entry(key.clone())
-            .or_insert_with(|| WindowedAggregates::new(window_start));
+        // Get or create the window and add the value; a window that was
+        // already emitted is re-emitted with the late value merged in
+        let correction = {
+            let mut agg = match self.aggregates.get_mut(&key) {
+                Some(agg) => agg,
+                None => self
+                    .aggregates
+                    .entry(key.clone())
+                    .or_insert(WindowedAggregates::new(
+                        window_start,
+                        window,
+                        serde_json::to_value(tags)?,
+                    )),
+            };
+            // An exactly-once window is final once written
+            if agg.emitted && self.emission == EmissionMode::ExactlyOnce {
+                return Ok(Placement::Dropped);
+            }
+            agg.add_value(value, sampling_rate);
+            if let Some((partition, offset)) = source {
+                agg.track_source(partition, offset);
+            }
+            if agg.emitted {
+                Some((
+                    self.window_measures(&agg),
+                    agg.tags.clone(),
+                    agg.sampling_rate(),
+                ))
+            } else {
+                None
+            }
+        };
 
-        // Add value to current window
-        agg.add_value(value);
+        let Some((measures, tags_json, rate)) = correction else {
+            return Ok(Placement::Merged);
+        };
+        self.store_window(&key, &measures, &tags_json, rate).await?;
+        self.windows_emitted.fetch_add(1, Ordering::Relaxed);
+        if self.is_derived_input(metric_name) {
+            self.emit_derived([(window, window_start, tags_hash)].into())
+                .await?;
+        }
+        debug!(
+            "Corrected {} window for {} at {}: avg={:.2}, count={}",
+            window.as_str(),
+            metric_name,
+            window_start,
+            measures.avg,
+            measures.count
+        );
+        Ok(Placement::Corrected)
+    }
 
-        // Check if we need to flush this window
-        if timestamp >= agg.window_end() {
-            // Compute statistics and store
-            let measures = agg.compute_statistics();
+    /// Emit every window the watermark has finalized and evict the expired ones
+    async fn close_windows(&self, watermark: &Watermark) -> Result<()> {
+        let start = std::time::Instant::now();
+        let mut ready = Vec::new();
+        let mut expired = Vec::new();
+        for mut entry in self.aggregates.iter_mut() {
+            let state = watermark.classify(entry.window_end());
+            let due = match self.emission {
+                EmissionMode::Corrections => state != WindowState::Open,
+                EmissionMode::ExactlyOnce => state == WindowState::Expired,
+            };
+            if !due {
+                continue;
+            }
+            if state == WindowState::Expired {
+                expired.push(entry.key().clone());
+            }
+            if !entry.emitted {
+                // Marked before storing so concurrent late values re-emit
+                entry.emitted = true;
+                ready.push((
+                    entry.key().clone(),
+                    self.window_measures(&entry),
+                    entry.tags.clone(),
+                    entry.sampling_rate(),
+                ));
+            }
+        }
 
-            // Store to database
-            let tags_json = serde_json::to_value(tags)?;
-            self.database
-                .store_aggregated_metric(
-                    metric_name,
-                    window,
-                    agg.window_start,
-                    &tags_json,
-                    &measures,
-                )
-                .await?;
+        let mut derived_windows = HashSet::new();
+        for (i, (key, measures, tags_json, rate)) in ready.iter().enumerate() {
+            if let Err(e) = self.store_window(key, measures, tags_json, *rate).await {
+                // Windows not yet written are retried on the next sweep
+                for (key, _, _, _) in &ready[i..] {
+                    if let Some(mut agg) = self.aggregates.get_mut(key) {
+                        agg.emitted = false;
+                    }
+                }
+                return Err(e);
+            }
+            self.windows_emitted.fetch_add(1, Ordering::Relaxed);
 
             debug!(
-                "Flushed {} window for {}: avg={:.2}, count={}",
-                window.as_str(),
-                metric_name,
+                "Closed {} window for {}: avg={:.2}, count={}",
+                key.window.as_str(),
+                key.metric_name,
                 measures.avg,
                 measures.count
             );
+            if self.is_derived_input(&key.metric_name) {
+                derived_windows.insert((key.window, key.window_start, key.tags_hash));
+            }
+        }
+        self.emit_derived(derived_windows).await?;
+        if !ready.is_empty() {
+            HubMetrics::global().observe_processing("close_windows", start.elapsed());
+        }
 
-            // Create new window
-            *agg = WindowedAggregates::new(window_start);
+        for key in expired {
+            self.aggregates.remove(&key);
         }
+        Ok(())
+    }
 
+    /// Store a window, and its effective sampling rate when it holds sampled values
+    async fn store_window(
+        &self,
+        key: &AggregateKey,
+        measures: &StatisticalMeasures,
+        tags_json: &serde_json::Value,
+        sampling_rate: Option<f64>,
+    ) -> Result<()> {
+        self.database
+            .store_aggregated_metric(
+                &key.metric_name,
+                key.window,
+                key.window_start,
+                tags_json,
+                measures,
+            )
+            .await?;
+        if let Some(rate) = sampling_rate {
+            self.database
+                .store_aggregated_metric(
+                    &sampling_rate_metric(&key.metric_name),
+                    key.window,
+                    key.window_start,
+                    tags_json,
+                    &StatisticalMeasures::from_values(&[rate]),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Statistics for a window to store, with its histogram when enabled
+    fn window_measures(&self, agg: &WindowedAggregates) -> StatisticalMeasures {
+        let mut measures = agg.compute_statistics();
+        if let Some(bounds) = &self.histogram_bounds {
+            measures.histogram = Some(Histogram::from_values(bounds.clone(), &agg.values));
+        }
+        measures
+    }
+
+    /// Whether any derived metric reads `metric_name`
+    fn is_derived_input(&self, metric_name: &str) -> bool {
+        self.derived
+            .iter()
+            .any(|derived| derived.inputs().contains(&metric_name))
+    }
+
+    /// Evaluate derived metrics for windows (by window, start, and tag set)
+    /// whose inputs were just written. Windows missing an input are skipped.
+    async fn emit_derived(&self, windows: HashSet<(TimeWindow, DateTime<Utc>, u64)>) -> Result<()> {
+        for (window, window_start, tags_hash) in windows {
+            let mut inputs = HashMap::new();
+            let mut tags_json = None;
+            for name in self.derived.iter().flat_map(|derived| derived.inputs()) {
+                if inputs.contains_key(name) {
+                    continue;
+                }
+                let key = AggregateKey {
+                    metric_name: name.to_string(),
+                    window,
+                    window_start,
+                    tags_hash,
+                };
+                if let Some(agg) = self.aggregates.get(&key) {
+                    inputs.insert(name.to_string(), agg.compute_statistics());
+                    tags_json.get_or_insert_with(|| agg.tags.clone());
+                }
+            }
+            let Some(tags_json) = tags_json else {
+                continue;
+            };
+
+            for derived in &self.derived {
+                let Some(value) = derived.evaluate(&inputs) else {
+                    continue;
+                };
+                self.database
+                    .store_aggregated_metric(
+                        derived.name(),
+                        window,
+                        window_start,
+                        &tags_json,
+                        &StatisticalMeasures::from_values(&[value]),
+                    )
+                    .await?;
+                debug!(
+                    "Derived {} window for {} at {}: {:.4}",
+                    window.as_str(),
+                    derived.name(),
+                    window_start,
+                    value
+                );
+            }
+        }
         Ok(())
     }
 
@@ -162,16 +605,7 @@ impl AggregationEngine {
 
     /// All supported time windows
     fn all_windows() -> &'static [TimeWindow] {
-        &[
-            TimeWindow::OneMinute,
-            TimeWindow::FiveMinutes,
-            TimeWindow::FifteenMinutes,
-            TimeWindow::OneHour,
-            TimeWindow::SixHours,
-            TimeWindow::OneDay,
-            TimeWindow::OneWeek,
-            TimeWindow::OneMonth,
-        ]
+        &TimeWindow::ALL
     }
 
     /// Get aggregated statistics for a metric
@@ -183,9 +617,32 @@ impl AggregationEngine {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Vec<StatisticalMeasures>> {
-        let rows = self.database
-            .query_aggregated_metrics(metric_name, window, start, end)
-            .await?;
+        self.get_aggregated_stats_scoped(metric_name, window, start, end, &TenantScope::AllTenants)
+            .await
+    }
+
+    /// Get aggregated statistics for a metric within a tenant scope
+    #[instrument(skip(self))]
+    pub async fn get_aggregated_stats_scoped(
+        &self,
+        metric_name: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        scope: &TenantScope,
+    ) -> Result<Vec<StatisticalMeasures>> {
+        let rows = match scope.aggregate_tags() {
+            Some(tags) => {
+                self.database
+                    .query_aggregated_metrics_tagged(metric_name, window, start, end, &tags)
+                    .await?
+            }
+            None => {
+                self.database
+                    .query_aggregated_metrics(metric_name, window, start, end)
+                    .await?
+            }
+        };
 
         let stats: Vec<StatisticalMeasures> = rows
             .into_iter()
@@ -199,39 +656,92 @@ impl AggregationEngine {
                 stddev: row.stddev,
                 count: row.count as u64,
                 sum: row.sum,
+                histogram: row.histogram.map(|histogram| histogram.0),
             })
             .collect();
 
         Ok(stats)
     }
 
-    /// Force flush all pending aggregates
-    pub async fn flush_all(&self) -> Result<usize> {
-        let mut flushed = 0;
-
-        for entry in self.aggregates.iter() {
-            let key = entry.key();
-            let agg = entry.value();
+    /// Windows of a metric still held in memory and not yet written, as
+    /// preliminary rows ordered by window start. Their values change until the
+    /// window is emitted.
+    pub fn preliminary_aggregates(
+        &self,
+        metric_name: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        scope: &TenantScope,
+    ) -> Vec<AggregatedMetricRow> {
+        let tags = scope.aggregate_tags();
+        let mut rows: Vec<AggregatedMetricRow> = self
+            .aggregates
+            .iter()
+            .filter(|entry| {
+                let key = entry.key();
+                key.metric_name == metric_name
+                    && key.window == window
+                    && key.window_start >= start
+                    && key.window_start < end
+                    && !entry.emitted
+                    && !entry.values.is_empty()
+                    && tags
+                        .as_ref()
+                        .map_or(true, |filter| tags_contain(&entry.tags, filter))
+            })
+            .map(|entry| {
+                let measures = self.window_measures(&entry);
+                AggregatedMetricRow {
+                    metric_name: metric_name.to_string(),
+                    time_window: window.as_str().to_string(),
+                    window_start: entry.window_start,
+                    tags: entry.tags.clone(),
+                    avg: measures.avg,
+                    min: measures.min,
+                    max: measures.max,
+                    p50: measures.p50,
+                    p95: measures.p95,
+                    p99: measures.p99,
+                    stddev: measures.stddev,
+                    count: measures.count as i64,
+                    sum: measures.sum,
+                    histogram: measures.histogram.map(Json),
+                }
+            })
+            .collect();
+        rows.sort_by_key(|row| row.window_start);
+        rows
+    }
 
-            if agg.values.is_empty() {
+    /// Force flush all pending aggregates, including windows still open
+    pub async fn flush_all(&self) -> Result<usize> {
+        let start = std::time::Instant::now();
+        let mut pending = Vec::new();
+        for mut entry in self.aggregates.iter_mut() {
+            if entry.emitted || entry.values.is_empty() {
                 continue;
             }
+            entry.emitted = true;
+            pending.push((
+                entry.key().clone(),
+                self.window_measures(&entry),
+                entry.tags.clone(),
+                entry.sampling_rate(),
+            ));
+        }
 
-            let measures = agg.compute_statistics();
-            let tags_json = serde_json::json!({});
-
-            self.database
-                .store_aggregated_metric(
-                    &key.metric_name,
-                    key.window,
-                    agg.window_start,
-                    &tags_json,
-                    &measures,
-                )
-                .await?;
-
-            flushed += 1;
+        let flushed = pending.len();
+        let mut derived_windows = HashSet::new();
+        for (key, measures, tags_json, rate) in pending {
+            self.store_window(&key, &measures, &tags_json, rate).await?;
+            self.windows_emitted.fetch_add(1, Ordering::Relaxed);
+            if self.is_derived_input(&key.metric_name) {
+                derived_windows.insert((key.window, key.window_start, key.tags_hash));
+            }
         }
+        self.emit_derived(derived_windows).await?;
+        HubMetrics::global().observe_processing("flush_windows", start.elapsed());
 
         info!("Flushed {} pending aggregates", flushed);
         Ok(flushed)
@@ -243,19 +753,51 @@ struct WindowedAggregates {
     window_start: DateTime<Utc>,
     window_duration: Duration,
     values: Vec<f64>,
+    /// Source events the values stand for, counting each sampled one as 1/rate
+    represented: f64,
+    /// Sum of the values, each weighted by 1/rate
+    weighted_sum: f64,
+    tags: serde_json::Value,
+    /// Whether the window has been written since it was finalized
+    emitted: bool,
+    /// Lowest offset per source partition merged into the window
+    source_offsets: HashMap<i32, i64>,
 }
 
 impl WindowedAggregates {
-    fn new(window_start: DateTime<Utc>) -> Self {
+    fn new(window_start: DateTime<Utc>, window: TimeWindow, tags: serde_json::Value) -> Self {
         Self {
             window_start,
-            window_duration: Duration::minutes(1),
+            window_duration: Duration::seconds(window.to_seconds() as i64),
             values: Vec::new(),
+            represented: 0.0,
+            weighted_sum: 0.0,
+            tags,
+            emitted: false,
+            source_offsets: HashMap::new(),
         }
     }
 
-    fn add_value(&mut self, value: f64) {
+    fn add_value(&mut self, value: f64, sampling_rate: f64) {
         self.values.push(value);
+        self.represented += 1.0 / sampling_rate;
+        self.weighted_sum += value / sampling_rate;
+    }
+
+    /// Fraction of source events retained, if any values were sampled
+    fn sampling_rate(&self) -> Option<f64> {
+        let kept = self.values.len() as f64;
+        if kept == 0.0 || self.represented <= kept {
+            return None;
+        }
+        Some(kept / self.represented)
+    }
+
+    fn track_source(&mut self, partition: i32, offset: i64) {
+        self.source_offsets
+            .entry(partition)
+            .and_modify(|first| *first = (*first).min(offset))
+            .or_insert(offset);
     }
 
     fn window_end(&self) -> DateTime<Utc> {
@@ -289,10 +831,11 @@ impl WindowedAggregates {
                 let diff = v - avg;
                 diff * diff
             })
-            .sum::<f64>() / count as f64;
+            .sum::<f64>()
+            / count as f64;
         let stddev = Some(variance.sqrt());
 
-        StatisticalMeasures {
+        let mut measures = StatisticalMeasures {
             avg,
             min,
             max,
@@ -302,7 +845,15 @@ impl WindowedAggregates {
             stddev,
             count,
             sum,
+            histogram: None,
+        };
+        // Counts and sums estimate the source events, not just the kept ones
+        if self.sampling_rate().is_some() {
+            measures.count = self.represented.round() as u64;
+            measures.sum = self.weighted_sum;
+            measures.avg = self.weighted_sum / self.represented;
         }
+        measures
     }
 
     fn percentile(sorted_values: &[f64], percentile: f64) -> f64 {
@@ -318,6 +869,318 @@ impl WindowedAggregates {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::environment::default_environment;
+    use crate::database::{AggregatedMetricRow, MemoryBackend};
+    use crate::schemas::events::{
+        CommonEventFields, EventPayload, EventType, LatencyMetrics, Severity, SourceModule,
+        TelemetryPayload, SCHEMA_VERSION,
+    };
+    use uuid::Uuid;
+
+    fn latency_event(timestamp: DateTime<Utc>, total_latency_ms: f64) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: default_environment(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Telemetry(TelemetryPayload::Latency(LatencyMetrics {
+                model_id: "gpt-4".to_string(),
+                request_id: "req".to_string(),
+                total_latency_ms,
+                ttft_ms: None,
+                tokens_per_second: None,
+                breakdown: None,
+            })),
+        }
+    }
+
+    async fn minute_rows(
+        backend: &MemoryBackend,
+        start: DateTime<Utc>,
+    ) -> Vec<AggregatedMetricRow> {
+        backend
+            .query_aggregated_metrics(
+                "total_latency_ms",
+                TimeWindow::OneMinute,
+                start,
+                start + Duration::minutes(1),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_watermark_closes_windows_and_corrects_late_events() {
+        let backend = Arc::new(MemoryBackend::new());
+        let engine =
+            AggregationEngine::new(backend.clone()).with_watermark_config(WatermarkConfig {
+                max_out_of_orderness_secs: 10,
+                allowed_lateness_secs: 120,
+            });
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+        let at = |secs: i64| t0 + Duration::seconds(secs);
+        let first_minute = || minute_rows(&backend, t0);
+
+        engine
+            .process_event(&latency_event(at(5), 10.0))
+            .await
+            .unwrap();
+        assert!(first_minute().await.is_empty());
+
+        // Watermark reaches t0 + 60s and closes the first minute
+        engine
+            .process_event(&latency_event(at(70), 20.0))
+            .await
+            .unwrap();
+        assert_eq!(engine.watermark().await, Some(at(60)));
+        let rows = first_minute().await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].count, 1);
+
+        // Late but within the allowed lateness: the rollup is corrected
+        engine
+            .process_event(&latency_event(at(20), 30.0))
+            .await
+            .unwrap();
+        let rows = first_minute().await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].count, 2);
+        assert_eq!(rows[0].avg, 20.0);
+
+        // Past the allowed lateness of the minute window: dropped there
+        engine
+            .process_event(&latency_event(at(400), 40.0))
+            .await
+            .unwrap();
+        engine
+            .process_event(&latency_event(at(30), 50.0))
+            .await
+            .unwrap();
+        assert_eq!(first_minute().await[0].count, 2);
+
+        let totals = engine.late_arrivals().totals();
+        assert_eq!(totals.late_events, 2);
+        assert_eq!(totals.dropped_events, 1);
+        assert_eq!(totals.corrected_windows, 1);
+        assert_eq!(
+            engine.late_arrivals().snapshot()[0].0,
+            SourceModule::LlmObservatory.as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exactly_once_emission_and_preliminary_windows() {
+        let backend = Arc::new(MemoryBackend::new());
+        let engine = AggregationEngine::new(backend.clone())
+            .with_watermark_config(WatermarkConfig {
+                max_out_of_orderness_secs: 10,
+                allowed_lateness_secs: 120,
+            })
+            .with_emission_mode(EmissionMode::ExactlyOnce);
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+        let at = |secs: i64| t0 + Duration::seconds(secs);
+        let preliminary = || {
+            engine.preliminary_aggregates(
+                "total_latency_ms",
+                TimeWindow::OneMinute,
+                t0,
+                at(60),
+                &TenantScope::AllTenants,
+            )
+        };
+
+        // Finalized and late-merged, but held until the allowed lateness passes
+        for (secs, value) in [(5, 10.0), (70, 20.0), (20, 30.0)] {
+            engine
+                .process_event(&latency_event(at(secs), value))
+                .await
+                .unwrap();
+        }
+        assert!(minute_rows(&backend, t0).await.is_empty());
+        assert_eq!(preliminary().len(), 1);
+        assert_eq!(preliminary()[0].count, 2);
+
+        // Watermark passes 180s: the first minute is written once, with both values
+        engine
+            .process_event(&latency_event(at(200), 40.0))
+            .await
+            .unwrap();
+        let rows = minute_rows(&backend, t0).await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].count, 2);
+        assert_eq!(rows[0].avg, 20.0);
+        assert!(preliminary().is_empty());
+        assert_eq!(engine.windows_emitted(), 1);
+
+        // Anything later is dropped rather than re-emitted
+        engine
+            .process_event(&latency_event(at(30), 50.0))
+            .await
+            .unwrap();
+        assert_eq!(minute_rows(&backend, t0).await[0].count, 2);
+        assert_eq!(engine.windows_emitted(), 1);
+        assert_eq!(engine.late_arrivals().totals().dropped_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_flushed_offsets_wait_for_windows_in_storage() {
+        let backend = Arc::new(MemoryBackend::new());
+        let engine =
+            AggregationEngine::new(backend.clone()).with_watermark_config(WatermarkConfig {
+                max_out_of_orderness_secs: 10,
+                allowed_lateness_secs: 120,
+            });
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+        let at = |secs: i64| t0 + Duration::seconds(secs);
+
+        engine
+            .process_record(&latency_event(at(5), 10.0), 0, 40)
+            .await
+            .unwrap();
+        engine
+            .process_record(&latency_event(at(70), 20.0), 0, 41)
+            .await
+            .unwrap();
+        engine
+            .process_record(&latency_event(at(75), 30.0), 1, 7)
+            .await
+            .unwrap();
+
+        // The first minute is stored, but its longer windows are still open
+        assert_eq!(minute_rows(&backend, t0).await.len(), 1);
+        let offsets = engine.flushed_offsets();
+        assert_eq!(offsets[&0], 40);
+        assert_eq!(offsets[&1], 7);
+
+        engine.flush_all().await.unwrap();
+        let offsets = engine.flushed_offsets();
+        assert_eq!(offsets[&0], 42);
+        assert_eq!(offsets[&1], 8);
+    }
+
+    #[tokio::test]
+    async fn test_sampled_events_are_reweighted_in_one_series() {
+        let backend = Arc::new(MemoryBackend::new());
+        let engine = AggregationEngine::new(backend.clone());
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+
+        for (value, rate) in [(10.0, Some("0.5")), (30.0, Some("0.5")), (20.0, None)] {
+            let mut event = latency_event(t0 + Duration::seconds(5), value);
+            event
+                .common
+                .tags
+                .insert("model".to_string(), "gpt-4".to_string());
+            if let Some(rate) = rate {
+                event
+                    .common
+                    .tags
+                    .insert(SAMPLING_RATE_TAG.to_string(), rate.to_string());
+            }
+            engine.process_event(&event).await.unwrap();
+        }
+        engine.flush_all().await.unwrap();
+
+        // Two sampled events at 0.5 stand for four source events
+        let rows = minute_rows(&backend, t0).await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].tags,
+            serde_json::json!({"model": "gpt-4", "environment": default_environment()})
+        );
+        assert_eq!(rows[0].count, 5);
+        assert_eq!(rows[0].sum, 100.0);
+        assert_eq!(rows[0].avg, 20.0);
+
+        let rates = backend
+            .query_aggregated_metrics(
+                &sampling_rate_metric("total_latency_ms"),
+                TimeWindow::OneMinute,
+                t0,
+                t0 + Duration::minutes(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rates.len(), 1);
+        assert!((rates[0].avg - 0.6).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_series_are_split_by_environment() {
+        let backend = Arc::new(MemoryBackend::new());
+        let engine = AggregationEngine::new(backend.clone());
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+
+        for (environment, value) in [("production", 10.0), ("staging", 30.0)] {
+            let mut event = latency_event(t0 + Duration::seconds(5), value);
+            event.common.environment = environment.to_string();
+            // A tag claiming another environment does not move the event
+            event
+                .common
+                .tags
+                .insert(ENVIRONMENT_TAG.to_string(), "production".to_string());
+            engine.process_event(&event).await.unwrap();
+        }
+        engine.flush_all().await.unwrap();
+
+        let staging = backend
+            .query_aggregated_metrics_tagged(
+                "total_latency_ms",
+                TimeWindow::OneMinute,
+                t0,
+                t0 + Duration::minutes(1),
+                &serde_json::json!({ ENVIRONMENT_TAG: "staging" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(staging.len(), 1);
+        assert_eq!(staging[0].avg, 30.0);
+        assert_eq!(minute_rows(&backend, t0).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_derived_metrics_and_histograms_are_stored_with_rollups() {
+        let backend = Arc::new(MemoryBackend::new());
+        let derived = DerivedMetric::new("ttft_share", "ttft_ms / total_latency_ms").unwrap();
+        let engine = AggregationEngine::new(backend.clone())
+            .with_derived_metrics(vec![derived])
+            .with_histograms(&HistogramLayout::default())
+            .unwrap();
+        let t0 = DateTime::from_timestamp(1_699_999_980, 0).unwrap();
+        for (total, ttft) in [(100.0, 20.0), (300.0, 40.0)] {
+            let mut event = latency_event(t0, total);
+            if let EventPayload::Telemetry(TelemetryPayload::Latency(latency)) = &mut event.payload
+            {
+                latency.ttft_ms = Some(ttft);
+            }
+            engine.process_event(&event).await.unwrap();
+        }
+        engine.flush_all().await.unwrap();
+
+        let rows = backend
+            .query_aggregated_metrics(
+                "ttft_share",
+                TimeWindow::OneMinute,
+                t0,
+                t0 + Duration::minutes(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!((rows[0].avg - 0.15).abs() < 1e-9);
+
+        let rows = minute_rows(&backend, t0).await;
+        let histogram = &rows[0].histogram.as_ref().unwrap().0;
+        assert_eq!(histogram.total(), 2);
+        assert_eq!(histogram.bounds.len(), 20);
+    }
 
     #[test]
     fn test_percentile_calculation() {
@@ -328,7 +1191,8 @@ mod tests {
 
     #[test]
     fn test_statistical_measures() {
-        let mut agg = WindowedAggregates::new(Utc::now());
+        let mut agg =
+            WindowedAggregates::new(Utc::now(), TimeWindow::OneMinute, serde_json::json!({}));
         for i in 1..=10 {
             agg.add_value(i as f64);
         }
@@ -1,33 +1,70 @@
 //! Anomaly Detection Module
 //!
 //! Statistical and machine learning-based anomaly detection.
+//!
+//! Anomalies found while a maintenance window covers the metric's environment
+//! and service are still stored, marked with the window, but are only
+//! returned to callers when the window tags rather than suppresses findings.
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::Arc;
 use tracing::debug;
+use uuid::Uuid;
 
-use super::AnalyticsConfig;
+use super::feedback::{FeedbackConfig, FeedbackTally, FeedbackVerdict};
+use super::{AnalyticsConfig, SharedConfig};
+use crate::alerting::maintenance::{MaintenanceAction, MaintenanceRegistry};
 
 /// Anomaly detector
 pub struct AnomalyDetector {
-    config: Arc<AnalyticsConfig>,
+    config: SharedConfig,
     // Metric name -> Historical data
     baselines: Arc<DashMap<String, MetricBaseline>>,
     // Detected anomalies
     anomalies: Arc<DashMap<String, Vec<Anomaly>>>,
+    // Metric name -> Operator verdicts
+    feedback: Arc<DashMap<String, FeedbackTally>>,
+    feedback_config: FeedbackConfig,
+    // Windows consulted for the environment this detector runs in
+    maintenance: Option<(Arc<MaintenanceRegistry>, String)>,
 }
 
 impl AnomalyDetector {
     /// Create a new anomaly detector
     pub async fn new(config: Arc<AnalyticsConfig>) -> Result<Self> {
-        Ok(Self {
+        Ok(Self::with_shared_config(SharedConfig::new(config)))
+    }
+
+    /// Create a detector following a reloadable configuration
+    pub fn with_shared_config(config: SharedConfig) -> Self {
+        Self {
             config,
             baselines: Arc::new(DashMap::new()),
             anomalies: Arc::new(DashMap::new()),
-        })
+            feedback: Arc::new(DashMap::new()),
+            feedback_config: FeedbackConfig::default(),
+            maintenance: None,
+        }
+    }
+
+    /// Set how operator feedback adjusts per-metric thresholds
+    pub fn with_feedback_config(mut self, config: FeedbackConfig) -> Self {
+        self.feedback_config = config;
+        self
+    }
+
+    /// Mark or suppress anomalies found during `environment`'s maintenance windows
+    pub fn with_maintenance(
+        mut self,
+        registry: Arc<MaintenanceRegistry>,
+        environment: impl Into<String>,
+    ) -> Self {
+        self.maintenance = Some((registry, environment.into()));
+        self
     }
 
     /// Add a data point and check for anomalies
@@ -36,6 +73,18 @@ impl AnomalyDetector {
         metric_name: &str,
         value: f64,
         timestamp: DateTime<Utc>,
+    ) -> Result<Option<Anomaly>> {
+        self.check_anomaly_for(metric_name, value, timestamp, None)
+    }
+
+    /// `check_anomaly` for a metric belonging to `service`, so windows
+    /// scheduled for that service apply
+    pub fn check_anomaly_for(
+        &self,
+        metric_name: &str,
+        value: f64,
+        timestamp: DateTime<Utc>,
+        service: Option<&str>,
     ) -> Result<Option<Anomaly>> {
         // Get or create baseline
         let mut baseline = self
@@ -57,9 +106,15 @@ impl AnomalyDetector {
 
         // Z-score method for anomaly detection
         let z_score = (value - mean).abs() / stddev;
-        let threshold = self.get_threshold_for_sensitivity();
+        let threshold = self.threshold_for(metric_name);
 
         if z_score > threshold {
+            let window = self
+                .maintenance
+                .as_ref()
+                .and_then(|(registry, environment)| {
+                    registry.window_for(environment, service, timestamp)
+                });
             let anomaly = Anomaly {
                 metric_name: metric_name.to_string(),
                 timestamp,
@@ -68,6 +123,7 @@ impl AnomalyDetector {
                 deviation: z_score,
                 anomaly_type: self.classify_anomaly(value, mean, &baseline),
                 severity: self.calculate_severity(z_score),
+                maintenance_window: window.as_ref().map(|w| w.window_id),
             };
 
             debug!(
@@ -81,6 +137,10 @@ impl AnomalyDetector {
                 .or_insert_with(Vec::new)
                 .push(anomaly.clone());
 
+            if window.map_or(false, |w| w.action == MaintenanceAction::Suppress) {
+                debug!("Suppressed anomaly in {} during maintenance", metric_name);
+                return Ok(None);
+            }
             return Ok(Some(anomaly));
         }
 
@@ -91,10 +151,45 @@ impl AnomalyDetector {
     fn get_threshold_for_sensitivity(&self) -> f64 {
         // Convert sensitivity (0.0-1.0) to z-score threshold
         // Higher sensitivity = lower threshold
-        let sensitivity = self.config.anomaly_sensitivity;
+        let sensitivity = self.config.load().anomaly_sensitivity;
         3.0 - (sensitivity * 2.0) // Range: 1.0 to 3.0
     }
 
+    /// Sensitivity threshold widened by the metric's false-positive feedback
+    pub fn threshold_for(&self, metric_name: &str) -> f64 {
+        let increase = self
+            .feedback
+            .get(metric_name)
+            .map_or(0.0, |tally| self.feedback_config.threshold_increase(&tally));
+        self.get_threshold_for_sensitivity() + increase
+    }
+
+    /// Record an operator verdict for a metric
+    pub fn record_feedback(&self, metric_name: &str, verdict: FeedbackVerdict) {
+        self.feedback
+            .entry(metric_name.to_string())
+            .or_default()
+            .record(verdict);
+    }
+
+    /// Replace all feedback tallies, e.g. with those loaded from storage.
+    /// Returns the number of metrics with feedback.
+    pub fn restore_feedback(&self, tallies: Vec<(String, FeedbackTally)>) -> usize {
+        self.feedback.clear();
+        for (metric_name, tally) in tallies {
+            self.feedback.insert(metric_name, tally);
+        }
+        self.feedback.len()
+    }
+
+    /// Feedback tally for a metric
+    pub fn feedback_for(&self, metric_name: &str) -> FeedbackTally {
+        self.feedback
+            .get(metric_name)
+            .map(|tally| *tally)
+            .unwrap_or_default()
+    }
+
     /// Classify type of anomaly
     fn classify_anomaly(&self, value: f64, mean: f64, baseline: &MetricBaseline) -> AnomalyType {
         if value > mean {
@@ -161,6 +256,51 @@ impl AnomalyDetector {
         self.baselines.remove(metric_name);
     }
 
+    /// Copy of every metric baseline, for persisting detector state
+    pub fn export_baselines(&self) -> Vec<BaselineState> {
+        self.baselines
+            .iter()
+            .map(|entry| BaselineState {
+                metric_name: entry.key().clone(),
+                values: entry.values.iter().copied().collect(),
+                timestamps: entry.timestamps.iter().copied().collect(),
+                max_size: entry.max_size,
+            })
+            .collect()
+    }
+
+    /// Load persisted baselines, replacing any in memory for the same metric.
+    /// Returns the number of baselines restored.
+    pub fn restore_baselines(&self, states: Vec<BaselineState>) -> usize {
+        let mut restored = 0;
+        for state in states {
+            if state.values.len() != state.timestamps.len() || state.max_size == 0 {
+                debug!("Skipping inconsistent baseline for {}", state.metric_name);
+                continue;
+            }
+
+            let mut baseline = MetricBaseline::new(state.max_size);
+            for (value, timestamp) in state.values.into_iter().zip(state.timestamps) {
+                baseline.add_value(value, timestamp);
+            }
+            self.baselines.insert(state.metric_name, baseline);
+            restored += 1;
+        }
+        restored
+    }
+
+    /// Anomalies recorded during a maintenance window, for review once it ends
+    pub fn get_maintenance_anomalies(&self, window_id: Uuid) -> Vec<Anomaly> {
+        let mut anomalies: Vec<Anomaly> = self
+            .anomalies
+            .iter()
+            .flat_map(|entry| entry.value().clone())
+            .filter(|anomaly| anomaly.maintenance_window == Some(window_id))
+            .collect();
+        anomalies.sort_by_key(|anomaly| anomaly.timestamp);
+        anomalies
+    }
+
     /// Get detector statistics
     pub fn get_stats(&self) -> DetectorStats {
         let total_anomalies = self
@@ -168,15 +308,50 @@ impl AnomalyDetector {
             .iter()
             .map(|entry| entry.value().len())
             .sum();
+        let maintenance_anomalies = self
+            .anomalies
+            .iter()
+            .map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|anomaly| anomaly.maintenance_window.is_some())
+                    .count()
+            })
+            .sum();
+
+        let mut feedback = FeedbackTally::default();
+        let mut adjusted_metrics = 0;
+        for entry in self.feedback.iter() {
+            feedback.merge(entry.value());
+            if self.feedback_config.threshold_increase(entry.value()) > 0.0 {
+                adjusted_metrics += 1;
+            }
+        }
 
         DetectorStats {
             total_metrics: self.baselines.len(),
             total_anomalies,
+            maintenance_anomalies,
             active_baselines: self.baselines.len(),
+            feedback_received: feedback.total(),
+            precision: feedback.precision(),
+            recall: feedback.recall(),
+            adjusted_metrics,
         }
     }
 }
 
+/// Serializable form of a metric baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineState {
+    pub metric_name: String,
+    /// Rolling window, oldest first
+    pub values: Vec<f64>,
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub max_size: usize,
+}
+
 /// Metric baseline for anomaly detection
 struct MetricBaseline {
     values: VecDeque<f64>,
@@ -238,6 +413,8 @@ pub struct Anomaly {
     pub deviation: f64,
     pub anomaly_type: AnomalyType,
     pub severity: AnomalySeverity,
+    /// Maintenance window active when the anomaly was found
+    pub maintenance_window: Option<Uuid>,
 }
 
 /// Type of anomaly
@@ -251,7 +428,7 @@ pub enum AnomalyType {
 }
 
 /// Anomaly severity
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AnomalySeverity {
     Low,
     Medium,
@@ -264,5 +441,119 @@ pub enum AnomalySeverity {
 pub struct DetectorStats {
     pub total_metrics: usize,
     pub total_anomalies: usize,
+    /// Anomalies found during maintenance windows
+    pub maintenance_anomalies: usize,
     pub active_baselines: usize,
+    /// Operator verdicts received across all metrics
+    pub feedback_received: u64,
+    /// Estimated from operator feedback; `None` until feedback arrives
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    /// Metrics whose threshold feedback has widened
+    pub adjusted_metrics: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> AnomalyDetector {
+        AnomalyDetector::with_shared_config(SharedConfig::new(Arc::new(AnalyticsConfig::default())))
+    }
+
+    #[test]
+    fn test_baseline_export_and_restore() {
+        let original = detector();
+        let now = Utc::now();
+        for i in 0..20 {
+            original
+                .check_anomaly("latency", 100.0 + i as f64, now)
+                .unwrap();
+        }
+
+        let exported = original.export_baselines();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].values.len(), 20);
+
+        let restored = detector();
+        assert_eq!(restored.restore_baselines(exported.clone()), 1);
+        assert_eq!(restored.export_baselines(), exported);
+
+        // A warm baseline flags outliers immediately after restore
+        assert!(restored
+            .check_anomaly("latency", 10_000.0, now)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_false_positive_feedback_widens_threshold() {
+        let detector = detector();
+        let now = Utc::now();
+        for i in 0..50 {
+            let value = if i % 2 == 0 { 99.0 } else { 101.0 };
+            detector.check_anomaly("latency", value, now).unwrap();
+        }
+        let base = detector.threshold_for("latency");
+
+        // Well past the default threshold
+        assert!(detector
+            .check_anomaly("latency", 103.0, now)
+            .unwrap()
+            .is_some());
+
+        for _ in 0..10 {
+            detector.record_feedback("latency", FeedbackVerdict::FalsePositive);
+        }
+        detector.record_feedback("errors", FeedbackVerdict::TruePositive);
+        detector.record_feedback("errors", FeedbackVerdict::FalseNegative);
+
+        assert!(detector.threshold_for("latency") > base);
+        assert_eq!(detector.threshold_for("errors"), base);
+        assert!(detector
+            .check_anomaly("latency", 103.0, now)
+            .unwrap()
+            .is_none());
+
+        let stats = detector.get_stats();
+        assert_eq!(stats.feedback_received, 12);
+        assert_eq!(stats.precision, Some(1.0 / 11.0));
+        assert_eq!(stats.recall, Some(0.5));
+        assert_eq!(stats.adjusted_metrics, 1);
+    }
+
+    #[test]
+    fn test_maintenance_windows_record_but_suppress_anomalies() {
+        use crate::alerting::maintenance::MaintenanceWindow;
+
+        let registry = Arc::new(MaintenanceRegistry::new());
+        let detector = detector().with_maintenance(registry.clone(), "production");
+        let now = Utc::now();
+        for i in 0..20 {
+            detector
+                .check_anomaly("latency", 100.0 + (i % 2) as f64, now)
+                .unwrap();
+        }
+
+        let deploy = MaintenanceWindow::new("production", now, chrono::Duration::minutes(30))
+            .with_service("gateway");
+        let deploy_id = deploy.window_id;
+        registry.add(deploy).unwrap();
+
+        // Other services are unaffected by the gateway's window
+        let other = detector
+            .check_anomaly_for("latency", 10_000.0, now, Some("router"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.maintenance_window, None);
+        assert!(detector
+            .check_anomaly_for("latency", 20_000.0, now, Some("gateway"))
+            .unwrap()
+            .is_none());
+
+        let recorded = detector.get_maintenance_anomalies(deploy_id);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].value, 20_000.0);
+        assert_eq!(detector.get_stats().maintenance_anomalies, 1);
+    }
 }
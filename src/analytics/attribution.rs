@@ -0,0 +1,459 @@
+//! Per-Request Cost Attribution
+//!
+//! Joins Observatory usage traces with the `TokenCostEvent`s recorded for the
+//! same request, so each request's cost sits next to its latency and outcome.
+//! The job rescans a trailing window on every run (cost events may land after
+//! their trace) and upserts on request id, so reruns refine rather than
+//! duplicate records. Stored records back "most expensive slow requests"
+//! queries through the API.
+
+use crate::adapters::observatory::{ObservatoryAdapter, TraceQuery, TraceStatus, UsageTrace};
+use crate::analytics::cost::trace_model;
+use crate::database::{Database, EnvironmentScope, EventFilter};
+use crate::schemas::events::{
+    AnalyticsEvent, CostPayload, EventPayload, EventType, TokenCostEvent,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+/// Trace attributes that may carry the request identifier, in priority order
+const REQUEST_ID_ATTRIBUTES: [&str; 3] = ["request_id", "gen_ai.request.id", "llm.request_id"];
+
+/// Cost attribution job configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAttributionConfig {
+    /// How often the join runs
+    pub interval_secs: u64,
+    /// Trailing window rescanned on each run
+    pub lookback_secs: i64,
+    /// Upper bound on traces fetched per run
+    pub max_traces: usize,
+}
+
+impl Default for CostAttributionConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            lookback_secs: 3600,
+            max_traces: 10_000,
+        }
+    }
+}
+
+impl CostAttributionConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            interval_secs: std::env::var("COST_ATTRIBUTION_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+            lookback_secs: std::env::var("COST_ATTRIBUTION_LOOKBACK_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.lookback_secs),
+            max_traces: std::env::var("COST_ATTRIBUTION_MAX_TRACES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_traces),
+        }
+    }
+}
+
+/// Cost of one request with the latency and outcome of its trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestCost {
+    pub request_id: String,
+    pub trace_id: String,
+    pub model_id: String,
+    pub operation_name: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub status: String,
+    /// Token usage observed on the trace
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Tokens billed by the cost events
+    pub billed_tokens: u64,
+    pub cost_usd: f64,
+    pub currency: String,
+    /// Tags of the cost events (e.g. the owning tenant)
+    pub tags: HashMap<String, String>,
+}
+
+impl RequestCost {
+    /// Cost per second of request latency
+    pub fn cost_per_second(&self) -> f64 {
+        if self.duration_ms == 0 {
+            0.0
+        } else {
+            self.cost_usd / (self.duration_ms as f64 / 1000.0)
+        }
+    }
+}
+
+/// Ordering of a request cost listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestCostOrder {
+    #[default]
+    Cost,
+    Duration,
+}
+
+impl RequestCostOrder {
+    /// Column the listing is sorted on, descending
+    pub fn column(&self) -> &'static str {
+        match self {
+            RequestCostOrder::Cost => "cost_usd",
+            RequestCostOrder::Duration => "duration_ms",
+        }
+    }
+}
+
+/// Filter for listing attributed request costs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestCostQuery {
+    pub since: DateTime<Utc>,
+    /// Only requests at least this slow
+    pub min_duration_ms: u64,
+    pub model_id: Option<String>,
+    pub tenant_id: Option<String>,
+    pub order: RequestCostOrder,
+    pub limit: i64,
+}
+
+/// Request id recorded on a trace
+pub fn trace_request_id(trace: &UsageTrace) -> Option<&str> {
+    REQUEST_ID_ATTRIBUTES
+        .iter()
+        .find_map(|key| trace.attributes.get(*key).and_then(|v| v.as_str()))
+}
+
+fn status_label(status: &TraceStatus) -> &'static str {
+    match status {
+        TraceStatus::Ok => "ok",
+        TraceStatus::Error => "error",
+        TraceStatus::Timeout => "timeout",
+    }
+}
+
+/// Token cost carried by an event, if it is one
+pub fn token_cost(event: &AnalyticsEvent) -> Option<&TokenCostEvent> {
+    match &event.payload {
+        EventPayload::Cost(CostPayload::TokenCost(cost)) => Some(cost),
+        _ => None,
+    }
+}
+
+/// Outcome of joining traces with token cost events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttributionOutcome {
+    pub records: Vec<RequestCost>,
+    /// Traces with token usage but no cost event
+    pub unmatched_traces: usize,
+    /// Requests with cost events but no trace
+    pub unmatched_costs: usize,
+}
+
+/// Join traces with token cost events on request id
+///
+/// Traces without token usage or a request id are skipped. When a request has
+/// several spans the longest one stands for it; when it has several cost
+/// events (e.g. retried calls) their costs are summed.
+pub fn attribute_costs(traces: &[UsageTrace], costs: &[AnalyticsEvent]) -> AttributionOutcome {
+    let mut spans: HashMap<&str, &UsageTrace> = HashMap::new();
+    for trace in traces {
+        let (Some(_), Some(request_id)) = (&trace.token_usage, trace_request_id(trace)) else {
+            continue;
+        };
+        let span = spans.entry(request_id).or_insert(trace);
+        if trace.duration_ms > span.duration_ms {
+            *span = trace;
+        }
+    }
+
+    let mut billed: HashMap<&str, Vec<(&TokenCostEvent, &AnalyticsEvent)>> = HashMap::new();
+    for event in costs {
+        if let Some(cost) = token_cost(event) {
+            billed
+                .entry(cost.request_id.as_str())
+                .or_default()
+                .push((cost, event));
+        }
+    }
+
+    let mut outcome = AttributionOutcome::default();
+    for (request_id, trace) in &spans {
+        let Some(entries) = billed.remove(request_id) else {
+            outcome.unmatched_traces += 1;
+            continue;
+        };
+        let Some(usage) = &trace.token_usage else {
+            continue;
+        };
+        let (first, _) = entries[0];
+        let mut tags = HashMap::new();
+        for (_, event) in &entries {
+            tags.extend(event.common.tags.clone());
+        }
+
+        outcome.records.push(RequestCost {
+            request_id: request_id.to_string(),
+            trace_id: trace.trace_id.clone(),
+            model_id: trace_model(trace)
+                .map(str::to_string)
+                .unwrap_or_else(|| first.model_id.clone()),
+            operation_name: trace.operation_name.clone(),
+            started_at: trace.start_time,
+            duration_ms: trace.duration_ms,
+            status: status_label(&trace.status).to_string(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+            billed_tokens: entries.iter().map(|(c, _)| c.total_tokens as u64).sum(),
+            cost_usd: entries.iter().map(|(c, _)| c.total_cost_usd).sum(),
+            currency: first.currency.clone(),
+            tags,
+        });
+    }
+    outcome.unmatched_costs = billed.len();
+    outcome
+        .records
+        .sort_by(|a, b| a.request_id.cmp(&b.request_id));
+    outcome
+}
+
+/// Cost attribution statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAttributionStats {
+    pub runs: u64,
+    pub requests_attributed: u64,
+    pub unmatched_traces: u64,
+    pub unmatched_costs: u64,
+    pub failures: u64,
+}
+
+/// Periodically joins recent traces with token cost events into request costs
+pub struct CostAttributionJob {
+    observatory: Arc<ObservatoryAdapter>,
+    database: Arc<Database>,
+    config: CostAttributionConfig,
+    runs: AtomicU64,
+    requests_attributed: AtomicU64,
+    unmatched_traces: AtomicU64,
+    unmatched_costs: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl CostAttributionJob {
+    pub fn new(
+        observatory: Arc<ObservatoryAdapter>,
+        database: Arc<Database>,
+        config: CostAttributionConfig,
+    ) -> Self {
+        Self {
+            observatory,
+            database,
+            config,
+            runs: AtomicU64::new(0),
+            requests_attributed: AtomicU64::new(0),
+            unmatched_traces: AtomicU64::new(0),
+            unmatched_costs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Join the trailing window before `now` and store the request costs
+    #[instrument(skip(self))]
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<AttributionOutcome> {
+        let start = now - Duration::seconds(self.config.lookback_secs);
+        let query = TraceQuery {
+            start_time: Some(start),
+            end_time: Some(now),
+            limit: Some(self.config.max_traces),
+            ..Default::default()
+        };
+        let traces = self.observatory.fetch_traces(query).await?;
+
+        let mut costs = Vec::new();
+        self.database
+            .scan_events(
+                start,
+                now,
+                Some(&EventFilter::event_type(EventType::Cost)),
+                &EnvironmentScope::Default,
+                |event| {
+                    if token_cost(event).is_some() {
+                        costs.push(event.clone());
+                    }
+                },
+            )
+            .await?;
+
+        let outcome = attribute_costs(&traces, &costs);
+        self.database.upsert_request_costs(&outcome.records).await?;
+
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.requests_attributed
+            .fetch_add(outcome.records.len() as u64, Ordering::Relaxed);
+        self.unmatched_traces
+            .fetch_add(outcome.unmatched_traces as u64, Ordering::Relaxed);
+        self.unmatched_costs
+            .fetch_add(outcome.unmatched_costs as u64, Ordering::Relaxed);
+        info!(
+            attributed = outcome.records.len(),
+            unmatched_traces = outcome.unmatched_traces,
+            unmatched_costs = outcome.unmatched_costs,
+            "Cost attribution pass complete"
+        );
+        Ok(outcome)
+    }
+
+    /// Run every interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run_once(Utc::now()).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Cost attribution pass failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> CostAttributionStats {
+        CostAttributionStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            requests_attributed: self.requests_attributed.load(Ordering::Relaxed),
+            unmatched_traces: self.unmatched_traces.load(Ordering::Relaxed),
+            unmatched_costs: self.unmatched_costs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::observatory::TokenUsage;
+    use crate::schemas::events::{CommonEventFields, Severity, SourceModule, SCHEMA_VERSION};
+    use uuid::Uuid;
+
+    fn trace(request_id: Option<&str>, duration_ms: u64, tokens: Option<u64>) -> UsageTrace {
+        let mut attributes = HashMap::new();
+        attributes.insert("model_id".to_string(), serde_json::json!("gpt-4"));
+        if let Some(request_id) = request_id {
+            attributes.insert("request_id".to_string(), serde_json::json!(request_id));
+        }
+        UsageTrace {
+            trace_id: Uuid::new_v4().to_string(),
+            span_id: Uuid::new_v4().to_string(),
+            parent_span_id: None,
+            operation_name: "chat.completion".to_string(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            duration_ms,
+            status: TraceStatus::Ok,
+            attributes,
+            token_usage: tokens.map(|total_tokens| TokenUsage {
+                prompt_tokens: total_tokens / 2,
+                completion_tokens: total_tokens / 2,
+                total_tokens,
+            }),
+        }
+    }
+
+    fn cost_event(request_id: &str, cost: f64, tenant: Option<&str>) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        if let Some(tenant) = tenant {
+            tags.insert("tenant_id".to_string(), tenant.to_string());
+        }
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmCostOps,
+                event_type: EventType::Cost,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: "production".to_string(),
+                tags,
+            },
+            payload: EventPayload::Cost(CostPayload::TokenCost(TokenCostEvent {
+                model_id: "gpt-4-0613".to_string(),
+                request_id: request_id.to_string(),
+                prompt_tokens: 500,
+                completion_tokens: 500,
+                total_tokens: 1000,
+                cost_per_prompt_token: 0.00003,
+                cost_per_completion_token: 0.00006,
+                total_cost_usd: cost,
+                currency: "USD".to_string(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_joins_traces_and_costs_on_request_id() {
+        let traces = vec![
+            trace(Some("req-1"), 1200, Some(1000)),
+            trace(Some("req-2"), 300, Some(800)),
+            trace(Some("req-3"), 900, Some(400)),
+            // Skipped: no usage or no request id
+            trace(Some("req-4"), 100, None),
+            trace(None, 100, Some(100)),
+        ];
+        let costs = vec![
+            cost_event("req-1", 0.09, Some("team-a")),
+            cost_event("req-2", 0.02, None),
+            cost_event("req-9", 0.05, None),
+        ];
+
+        let outcome = attribute_costs(&traces, &costs);
+        assert_eq!(outcome.records.len(), 2);
+        assert_eq!(outcome.unmatched_traces, 1);
+        assert_eq!(outcome.unmatched_costs, 1);
+
+        let first = &outcome.records[0];
+        assert_eq!(first.request_id, "req-1");
+        assert_eq!(first.model_id, "gpt-4");
+        assert_eq!(first.duration_ms, 1200);
+        assert_eq!(first.total_tokens, 1000);
+        assert_eq!(first.billed_tokens, 1000);
+        assert_eq!(first.cost_usd, 0.09);
+        assert_eq!(first.status, "ok");
+        assert_eq!(first.tags["tenant_id"], "team-a");
+        assert!((first.cost_per_second() - 0.075).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_longest_span_and_summed_costs() {
+        let traces = vec![
+            trace(Some("req-1"), 200, Some(1000)),
+            trace(Some("req-1"), 1500, Some(1000)),
+        ];
+        let costs = vec![
+            cost_event("req-1", 0.03, None),
+            cost_event("req-1", 0.04, None),
+        ];
+
+        let outcome = attribute_costs(&traces, &costs);
+        assert_eq!(outcome.records.len(), 1);
+        assert_eq!(outcome.records[0].duration_ms, 1500);
+        assert_eq!(outcome.records[0].billed_tokens, 2000);
+        assert!((outcome.records[0].cost_usd - 0.07).abs() < 1e-9);
+    }
+}
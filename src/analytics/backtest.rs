@@ -0,0 +1,552 @@
+//! Anomaly Detection Backtesting
+//!
+//! Replays resolved incidents against the current detector configuration to
+//! show how sensitivity changes would have played out. An incident is labeled
+//! with the metrics of its anomaly signals; each metric's rollup history from
+//! a warm-up period before the incident until its resolution is fed through a
+//! fresh detector carrying the current sensitivity and operator feedback.
+//!
+//! An incident is a hit when any of its metrics is flagged between the early
+//! tolerance before the incident started and its resolution. Detection latency
+//! is measured from the incident's start and is negative when the detector
+//! fired early. Anomalies during the warm-up, before the early tolerance, are
+//! counted as false alarms.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::anomaly::AnomalyDetector;
+use super::feedback::{FeedbackConfig, FeedbackTally};
+use super::{AnalyticsConfig, SharedConfig};
+use crate::alerting::incidents::{Incident, SignalKind};
+use crate::database::timescale::parse_window;
+use crate::database::Database;
+use crate::models::metrics::TimeWindow;
+
+/// Backtest configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Seconds between scheduled runs
+    pub interval_secs: u64,
+    /// Incidents resolved within this many days are replayed
+    pub lookback_days: i64,
+    /// History replayed before an incident to build the detector's baseline
+    pub warmup_minutes: i64,
+    /// Detections this long before an incident started still count as hits
+    pub early_tolerance_minutes: i64,
+    /// Rollup window whose averages are replayed
+    pub window: TimeWindow,
+    /// Most recent incidents replayed per run
+    pub max_incidents: usize,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 86_400,
+            lookback_days: 30,
+            warmup_minutes: 120,
+            early_tolerance_minutes: 15,
+            window: TimeWindow::OneMinute,
+            max_incidents: 200,
+        }
+    }
+}
+
+impl BacktestConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            interval_secs: std::env::var("BACKTEST_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+            lookback_days: std::env::var("BACKTEST_LOOKBACK_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.lookback_days),
+            warmup_minutes: std::env::var("BACKTEST_WARMUP_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.warmup_minutes),
+            early_tolerance_minutes: std::env::var("BACKTEST_EARLY_TOLERANCE_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.early_tolerance_minutes),
+            window: match std::env::var("BACKTEST_WINDOW") {
+                Ok(window) => parse_window(&window)?,
+                Err(_) => defaults.window,
+            },
+            max_incidents: std::env::var("BACKTEST_MAX_INCIDENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_incidents),
+        })
+    }
+}
+
+/// A resolved incident and the metrics it was raised on
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledIncident {
+    pub incident_id: Uuid,
+    pub title: String,
+    pub metrics: Vec<String>,
+    /// Opening or first signal, whichever came first
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl LabeledIncident {
+    /// Label a resolved incident, `None` when it is open or has no anomaly
+    /// signals to name its metrics
+    pub fn from_incident(incident: &Incident) -> Option<Self> {
+        let end = incident.resolved_at?;
+        let mut metrics: Vec<String> = incident
+            .signals()
+            .filter(|s| s.kind == SignalKind::Anomaly)
+            .map(|s| s.summary.clone())
+            .collect();
+        metrics.sort();
+        metrics.dedup();
+        if metrics.is_empty() {
+            return None;
+        }
+        let start = incident
+            .signals()
+            .map(|s| s.at)
+            .fold(incident.opened_at, |start, at| start.min(at));
+        Some(Self {
+            incident_id: incident.incident_id,
+            title: incident.title.clone(),
+            metrics,
+            start,
+            end: end.max(start),
+        })
+    }
+}
+
+/// Replay of one metric of an incident
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricOutcome {
+    pub metric_name: String,
+    /// Points replayed
+    pub points: usize,
+    /// First detection within the incident's window
+    pub detected_at: Option<DateTime<Utc>>,
+    /// Anomalies flagged before the incident's window
+    pub false_alarms: usize,
+}
+
+/// Replay of one incident
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentOutcome {
+    pub incident_id: Uuid,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub detected: bool,
+    /// Seconds from the incident's start to its first detection
+    pub latency_secs: Option<i64>,
+    pub metrics: Vec<MetricOutcome>,
+}
+
+impl IncidentOutcome {
+    pub fn new(incident: &LabeledIncident, metrics: Vec<MetricOutcome>) -> Self {
+        let detected_at = metrics.iter().filter_map(|m| m.detected_at).min();
+        Self {
+            incident_id: incident.incident_id,
+            title: incident.title.clone(),
+            start: incident.start,
+            end: incident.end,
+            detected: detected_at.is_some(),
+            latency_secs: detected_at.map(|at| (at - incident.start).num_seconds()),
+            metrics,
+        }
+    }
+}
+
+/// Hit/miss rates and detection latency over the replayed incidents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub run_at: DateTime<Utc>,
+    /// Sensitivity the detectors ran with
+    pub sensitivity: f64,
+    pub incidents: usize,
+    pub hits: usize,
+    pub misses: usize,
+    /// Resolved incidents without anomaly signals to replay
+    pub skipped: usize,
+    pub hit_rate: Option<f64>,
+    pub miss_rate: Option<f64>,
+    pub mean_latency_secs: Option<f64>,
+    pub median_latency_secs: Option<i64>,
+    pub max_latency_secs: Option<i64>,
+    pub false_alarms: usize,
+    pub outcomes: Vec<IncidentOutcome>,
+}
+
+impl BacktestReport {
+    pub fn new(
+        run_at: DateTime<Utc>,
+        sensitivity: f64,
+        skipped: usize,
+        outcomes: Vec<IncidentOutcome>,
+    ) -> Self {
+        let incidents = outcomes.len();
+        let hits = outcomes.iter().filter(|o| o.detected).count();
+        let mut latencies: Vec<i64> = outcomes.iter().filter_map(|o| o.latency_secs).collect();
+        latencies.sort_unstable();
+        let rate = |n: usize| (incidents > 0).then(|| n as f64 / incidents as f64);
+        Self {
+            run_at,
+            sensitivity,
+            incidents,
+            hits,
+            misses: incidents - hits,
+            skipped,
+            hit_rate: rate(hits),
+            miss_rate: rate(incidents - hits),
+            mean_latency_secs: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<i64>() as f64 / latencies.len() as f64),
+            median_latency_secs: latencies.get(latencies.len() / 2).copied(),
+            max_latency_secs: latencies.last().copied(),
+            false_alarms: outcomes
+                .iter()
+                .flat_map(|o| &o.metrics)
+                .map(|m| m.false_alarms)
+                .sum(),
+            outcomes,
+        }
+    }
+}
+
+/// Feed `points` through `detector`, noting the first detection within the
+/// incident's window and any anomalies before it
+pub fn replay_metric(
+    detector: &AnomalyDetector,
+    metric_name: &str,
+    points: &[(DateTime<Utc>, f64)],
+    incident: &LabeledIncident,
+    early_tolerance: Duration,
+) -> MetricOutcome {
+    let window_start = incident.start - early_tolerance;
+    let mut detected_at = None;
+    let mut false_alarms = 0;
+    for &(at, value) in points {
+        if at > incident.end {
+            break;
+        }
+        let Ok(Some(anomaly)) = detector.check_anomaly(metric_name, value, at) else {
+            continue;
+        };
+        if anomaly.timestamp < window_start {
+            false_alarms += 1;
+        } else {
+            detected_at = Some(anomaly.timestamp);
+            break;
+        }
+    }
+    MetricOutcome {
+        metric_name: metric_name.to_string(),
+        points: points.len(),
+        detected_at,
+        false_alarms,
+    }
+}
+
+/// Replays labeled incidents on a schedule and keeps the latest report
+pub struct Backtester {
+    database: Arc<Database>,
+    analytics: AnalyticsConfig,
+    feedback: FeedbackConfig,
+    config: BacktestConfig,
+    latest: RwLock<Option<BacktestReport>>,
+    runs: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl Backtester {
+    /// Backtest detectors configured like `analytics`
+    pub fn new(
+        database: Arc<Database>,
+        analytics: AnalyticsConfig,
+        config: BacktestConfig,
+    ) -> Self {
+        Self {
+            database,
+            analytics,
+            feedback: FeedbackConfig::default(),
+            config,
+            latest: RwLock::new(None),
+            runs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Set how stored operator feedback adjusts the replayed detectors
+    pub fn with_feedback_config(mut self, config: FeedbackConfig) -> Self {
+        self.feedback = config;
+        self
+    }
+
+    /// Replay recently resolved incidents, at `sensitivity` instead of the
+    /// configured one when given. Only runs at the configured sensitivity
+    /// become the latest report.
+    pub async fn run(&self, sensitivity: Option<f64>) -> Result<BacktestReport> {
+        let now = Utc::now();
+        let since = now - Duration::days(self.config.lookback_days);
+        let mut resolved: Vec<Incident> = self
+            .database
+            .query_incidents(since)
+            .await?
+            .into_iter()
+            .filter(|i| i.resolved_at.is_some_and(|at| at >= since))
+            .collect();
+        resolved.sort_by(|a, b| b.resolved_at.cmp(&a.resolved_at));
+        resolved.truncate(self.config.max_incidents);
+
+        let labeled: Vec<LabeledIncident> = resolved
+            .iter()
+            .filter_map(LabeledIncident::from_incident)
+            .collect();
+        let skipped = resolved.len() - labeled.len();
+
+        let tallies: Vec<(String, FeedbackTally)> = self
+            .database
+            .query_anomaly_feedback_tallies()
+            .await?
+            .into_iter()
+            .map(|row| (row.metric_name.clone(), row.tally()))
+            .collect();
+        let mut analytics = self.analytics.clone();
+        if let Some(sensitivity) = sensitivity {
+            analytics.anomaly_sensitivity = sensitivity.clamp(0.0, 1.0);
+        }
+        let shared = SharedConfig::new(Arc::new(analytics.clone()));
+
+        let warmup = Duration::minutes(self.config.warmup_minutes);
+        let tolerance = Duration::minutes(self.config.early_tolerance_minutes);
+        let mut outcomes = Vec::with_capacity(labeled.len());
+        for incident in &labeled {
+            let mut metrics = Vec::with_capacity(incident.metrics.len());
+            for metric_name in &incident.metrics {
+                let points: Vec<(DateTime<Utc>, f64)> = self
+                    .database
+                    .query_aggregated_metrics(
+                        metric_name,
+                        self.config.window,
+                        incident.start - warmup,
+                        incident.end,
+                    )
+                    .await?
+                    .iter()
+                    .map(|row| (row.window_start, row.avg))
+                    .collect();
+                // A fresh detector per incident so baselines never span incidents
+                let detector = AnomalyDetector::with_shared_config(shared.clone())
+                    .with_feedback_config(self.feedback.clone());
+                detector.restore_feedback(tallies.clone());
+                metrics.push(replay_metric(
+                    &detector,
+                    metric_name,
+                    &points,
+                    incident,
+                    tolerance,
+                ));
+            }
+            debug!(incident_id = %incident.incident_id, "Replayed incident");
+            outcomes.push(IncidentOutcome::new(incident, metrics));
+        }
+
+        let report = BacktestReport::new(now, analytics.anomaly_sensitivity, skipped, outcomes);
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        if sensitivity.is_none() {
+            *self.latest.write() = Some(report.clone());
+        }
+        Ok(report)
+    }
+
+    /// Report of the latest scheduled run
+    pub fn latest(&self) -> Option<BacktestReport> {
+        self.latest.read().clone()
+    }
+
+    /// Run on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.interval_secs.max(60));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match self.run(None).await {
+                    Ok(report) => info!(
+                        incidents = report.incidents,
+                        hits = report.hits,
+                        false_alarms = report.false_alarms,
+                        "Anomaly detection backtest finished"
+                    ),
+                    Err(e) => {
+                        self.failures.fetch_add(1, Ordering::Relaxed);
+                        warn!("Anomaly detection backtest failed: {}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> BacktestStats {
+        BacktestStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Backtester statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestStats {
+    pub runs: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::incidents::IncidentSignal;
+    use crate::schemas::events::Severity;
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minute)
+    }
+
+    fn detector(sensitivity: f64) -> AnomalyDetector {
+        AnomalyDetector::with_shared_config(SharedConfig::new(Arc::new(AnalyticsConfig {
+            anomaly_sensitivity: sensitivity,
+            ..AnalyticsConfig::default()
+        })))
+    }
+
+    fn signal(kind: SignalKind, summary: &str, minute: i64) -> IncidentSignal {
+        IncidentSignal {
+            kind,
+            signal_id: Uuid::new_v4(),
+            at: at(minute),
+            summary: summary.to_string(),
+            severity: Severity::Error,
+            environment: "production".to_string(),
+            correlation_id: None,
+            entities: Vec::new(),
+        }
+    }
+
+    fn labeled(start: i64, end: i64) -> LabeledIncident {
+        LabeledIncident {
+            incident_id: Uuid::new_v4(),
+            title: "latency".to_string(),
+            metrics: vec!["latency_ms".to_string()],
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    /// A flat series with a sustained step from `spike_at`
+    fn series(spike_at: i64, len: i64) -> Vec<(DateTime<Utc>, f64)> {
+        (0..len)
+            .map(|m| (at(m), if m >= spike_at { 500.0 } else { 100.0 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_labels_resolved_incidents_by_anomaly_metrics() {
+        let mut incident = Incident::from_signal(signal(SignalKind::Anomaly, "latency_ms", 10));
+        incident.add_signal(signal(SignalKind::Alert, "threshold_breach", 5));
+        incident.add_signal(signal(SignalKind::Anomaly, "error_rate", 12));
+        incident.add_signal(signal(SignalKind::Anomaly, "latency_ms", 14));
+        assert!(LabeledIncident::from_incident(&incident).is_none());
+
+        incident.resolved_at = Some(at(30));
+        let labeled = LabeledIncident::from_incident(&incident).unwrap();
+        assert_eq!(labeled.metrics, vec!["error_rate", "latency_ms"]);
+        assert_eq!(labeled.start, at(5));
+        assert_eq!(labeled.end, at(30));
+
+        let mut manual = Incident::new("manual", "production", Severity::Error, at(0));
+        manual.resolved_at = Some(at(10));
+        assert!(LabeledIncident::from_incident(&manual).is_none());
+    }
+
+    #[test]
+    fn test_replay_detects_spike_within_window() {
+        let incident = labeled(60, 90);
+        let outcome = replay_metric(
+            &detector(0.95),
+            "latency_ms",
+            &series(62, 100),
+            &incident,
+            Duration::minutes(15),
+        );
+        assert_eq!(outcome.detected_at, Some(at(62)));
+        assert_eq!(outcome.false_alarms, 0);
+        assert_eq!(outcome.points, 100);
+    }
+
+    #[test]
+    fn test_replay_counts_early_anomalies_as_false_alarms() {
+        // The spike lands before the early tolerance and the baseline absorbs it
+        let incident = labeled(60, 90);
+        let outcome = replay_metric(
+            &detector(0.95),
+            "latency_ms",
+            &series(20, 100),
+            &incident,
+            Duration::minutes(15),
+        );
+        assert!(outcome.false_alarms >= 1);
+        assert_eq!(outcome.detected_at, None);
+    }
+
+    #[test]
+    fn test_report_rates_and_latency() {
+        let hit = labeled(60, 90);
+        let miss = labeled(200, 230);
+        let outcomes = vec![
+            IncidentOutcome::new(
+                &hit,
+                vec![MetricOutcome {
+                    metric_name: "latency_ms".to_string(),
+                    points: 90,
+                    detected_at: Some(at(62)),
+                    false_alarms: 1,
+                }],
+            ),
+            IncidentOutcome::new(
+                &miss,
+                vec![MetricOutcome {
+                    metric_name: "latency_ms".to_string(),
+                    points: 90,
+                    detected_at: None,
+                    false_alarms: 0,
+                }],
+            ),
+        ];
+        assert_eq!(outcomes[0].latency_secs, Some(120));
+
+        let report = BacktestReport::new(at(300), 0.95, 3, outcomes);
+        assert_eq!(report.incidents, 2);
+        assert_eq!((report.hits, report.misses, report.skipped), (1, 1, 3));
+        assert_eq!(report.hit_rate, Some(0.5));
+        assert_eq!(report.mean_latency_secs, Some(120.0));
+        assert_eq!(report.false_alarms, 1);
+
+        let empty = BacktestReport::new(at(300), 0.95, 0, Vec::new());
+        assert_eq!(empty.hit_rate, None);
+        assert_eq!(empty.median_latency_secs, None);
+    }
+}
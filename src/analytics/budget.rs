@@ -0,0 +1,466 @@
+//! Budget Burn-Rate Forecasting
+//!
+//! Projects end-of-period spend per team from CostOps budget status and the
+//! prediction engine, and raises budget alerts when an overage is likely.
+
+use super::cost::budget_alert_event;
+use super::prediction::{PredictionEngine, PredictionPoint};
+use crate::adapters::costops::{BudgetStatus, CostOpsAdapter};
+use crate::metering::UsagePeriod;
+use crate::schemas::events::{AnalyticsEvent, BudgetAlertEvent, BudgetAlertType};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Uncertainty floor, as a fraction of projected additional spend
+///
+/// Keeps tight model bounds from turning the overage probability into a step function.
+const MIN_RELATIVE_UNCERTAINTY: f64 = 0.1;
+
+/// Budget forecasting configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetForecastConfig {
+    /// Interval between spend samples
+    pub sample_interval_secs: i64,
+    /// Overage probability at which a warning alert is raised
+    pub alert_probability: f64,
+    /// Overage probability at which a critical alert is raised
+    pub critical_probability: f64,
+    /// Environment stamped on emitted events
+    pub environment: String,
+}
+
+impl Default for BudgetForecastConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_secs: 3600,
+            alert_probability: 0.5,
+            critical_probability: 0.9,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl BudgetForecastConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            sample_interval_secs: std::env::var("BUDGET_FORECAST_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.sample_interval_secs),
+            alert_probability: std::env::var("BUDGET_OVERAGE_ALERT_PROBABILITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.alert_probability),
+            ..defaults
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::seconds(self.sample_interval_secs.max(1))
+    }
+}
+
+/// Model used to project remaining spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastMethod {
+    Arima,
+    ExponentialSmoothing,
+    /// Average burn since the start of the period; used until enough samples exist
+    Linear,
+}
+
+/// Projected end-of-period spend for one budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetForecast {
+    pub budget_id: String,
+    pub team_id: Option<String>,
+    pub period_end: DateTime<Utc>,
+    pub budget_limit_usd: f64,
+    pub spent_usd: f64,
+    pub burn_rate_usd_per_hour: f64,
+    pub projected_spend_usd: f64,
+    pub projected_overage_usd: f64,
+    /// Probability (0.0 - 1.0) that spend exceeds the limit by period end
+    pub overage_probability: f64,
+    /// When cumulative spend is projected to reach the limit
+    pub projected_exhaustion: Option<DateTime<Utc>>,
+    pub method: ForecastMethod,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl BudgetForecast {
+    /// Budget alert to raise for this forecast, if any
+    pub fn alert(&self, config: &BudgetForecastConfig) -> Option<BudgetAlertEvent> {
+        if self.projected_overage_usd <= 0.0 || self.overage_probability < config.alert_probability
+        {
+            return None;
+        }
+
+        let alert_type = if self.overage_probability >= config.critical_probability {
+            BudgetAlertType::Critical
+        } else {
+            BudgetAlertType::Warning
+        };
+
+        Some(BudgetAlertEvent {
+            budget_id: self.budget_id.clone(),
+            budget_name: self
+                .team_id
+                .clone()
+                .unwrap_or_else(|| "organization".to_string()),
+            budget_limit_usd: self.budget_limit_usd,
+            current_spend_usd: self.spent_usd,
+            threshold_percent: self.projected_spend_usd / self.budget_limit_usd * 100.0,
+            alert_type,
+        })
+    }
+}
+
+/// Project remaining spend from per-interval burn predictions
+///
+/// Returns `(projected_spend, overage_probability, projected_exhaustion)`.
+pub fn project_spend(
+    spent: f64,
+    limit: f64,
+    burn: &[PredictionPoint],
+    interval: Duration,
+    now: DateTime<Utc>,
+) -> (f64, f64, Option<DateTime<Utc>>) {
+    let mut cumulative = spent;
+    let mut variance = 0.0;
+    let mut exhaustion = (spent >= limit).then_some(now);
+
+    for (step, point) in burn.iter().enumerate() {
+        cumulative += point.value.max(0.0);
+        // Bounds are treated as a 95% interval around the step's burn
+        let sigma = (point.upper_bound - point.lower_bound).abs() / (2.0 * 1.96);
+        variance += sigma * sigma;
+        if exhaustion.is_none() && cumulative >= limit {
+            exhaustion = Some(now + interval * (step as i32 + 1));
+        }
+    }
+
+    let sigma = variance
+        .sqrt()
+        .max((cumulative - spent) * MIN_RELATIVE_UNCERTAINTY);
+    let probability = if spent >= limit {
+        1.0
+    } else if sigma <= 0.0 {
+        if cumulative > limit {
+            1.0
+        } else {
+            0.0
+        }
+    } else {
+        Normal::new(cumulative, sigma)
+            .map(|dist| 1.0 - dist.cdf(limit))
+            .unwrap_or(0.0)
+    };
+
+    (cumulative, probability, exhaustion)
+}
+
+/// Forecaster statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetForecasterStats {
+    pub forecasts: u64,
+    pub alerts_raised: u64,
+    pub events_dropped: u64,
+}
+
+/// Samples budget spend and forecasts end-of-period overage per team
+pub struct BudgetForecaster {
+    costops: Arc<CostOpsAdapter>,
+    prediction: Arc<PredictionEngine>,
+    config: BudgetForecastConfig,
+    sink: mpsc::Sender<AnalyticsEvent>,
+    // Budget key -> last cumulative spend sample
+    last_spend: DashMap<String, (DateTime<Utc>, f64)>,
+    forecasts: AtomicU64,
+    alerts_raised: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl BudgetForecaster {
+    /// Create a forecaster publishing budget alerts into the given pipeline channel
+    pub fn new(
+        costops: Arc<CostOpsAdapter>,
+        prediction: Arc<PredictionEngine>,
+        config: BudgetForecastConfig,
+        sink: mpsc::Sender<AnalyticsEvent>,
+    ) -> Self {
+        Self {
+            costops,
+            prediction,
+            config,
+            sink,
+            last_spend: DashMap::new(),
+            forecasts: AtomicU64::new(0),
+            alerts_raised: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn metric_name(team_id: Option<&str>) -> String {
+        format!("budget.{}.burn_usd", team_id.unwrap_or("organization"))
+    }
+
+    /// Record a spend sample, feeding the burn since the previous sample to the prediction engine
+    pub fn record_status(&self, status: &BudgetStatus, at: DateTime<Utc>) -> Result<()> {
+        let metric = Self::metric_name(status.team_id.as_deref());
+        let previous = self
+            .last_spend
+            .insert(metric.clone(), (at, status.spent_usd));
+
+        match previous {
+            // Spend dropping means a new budget period started
+            Some((_, spent)) if status.spent_usd >= spent => {
+                self.prediction
+                    .add_data_point(&metric, status.spent_usd - spent, at)?;
+            }
+            _ => debug!(%metric, "Starting new budget burn series"),
+        }
+        Ok(())
+    }
+
+    /// Forecast end-of-period spend for a budget
+    pub fn forecast(&self, status: &BudgetStatus, now: DateTime<Utc>) -> BudgetForecast {
+        let period = UsagePeriod::containing(now);
+        let interval = self.config.interval();
+        let remaining = period.end() - now;
+        let steps = (remaining.num_seconds() as f64 / interval.num_seconds() as f64)
+            .ceil()
+            .max(0.0) as usize;
+
+        let metric = Self::metric_name(status.team_id.as_deref());
+        let (burn, method) = match self.prediction.predict_arima(&metric, steps) {
+            Ok(points) => (points, ForecastMethod::Arima),
+            Err(_) => match self
+                .prediction
+                .predict_exponential_smoothing(&metric, steps, 0.3)
+            {
+                Ok(points) => (points, ForecastMethod::ExponentialSmoothing),
+                Err(_) => (
+                    linear_burn(status.spent_usd, period.start(), now, interval, steps),
+                    ForecastMethod::Linear,
+                ),
+            },
+        };
+
+        let (projected, probability, exhaustion) = project_spend(
+            status.spent_usd,
+            status.period_budget_usd,
+            &burn,
+            interval,
+            now,
+        );
+        let burn_per_interval = burn.first().map(|p| p.value.max(0.0)).unwrap_or(0.0);
+
+        BudgetForecast {
+            budget_id: status.budget_id.clone(),
+            team_id: status.team_id.clone(),
+            period_end: period.end(),
+            budget_limit_usd: status.period_budget_usd,
+            spent_usd: status.spent_usd,
+            burn_rate_usd_per_hour: burn_per_interval * 3600.0 / interval.num_seconds() as f64,
+            projected_spend_usd: projected,
+            projected_overage_usd: (projected - status.period_budget_usd).max(0.0),
+            overage_probability: probability,
+            projected_exhaustion: exhaustion,
+            method,
+            generated_at: now,
+        }
+    }
+
+    /// Sample, forecast, and alert for each team, or the organization budget when empty
+    pub async fn run_once(&self, team_ids: &[String]) -> Result<Vec<BudgetForecast>> {
+        let now = Utc::now();
+        let teams: Vec<Option<&str>> = if team_ids.is_empty() {
+            vec![None]
+        } else {
+            team_ids.iter().map(|t| Some(t.as_str())).collect()
+        };
+
+        let mut forecasts = Vec::with_capacity(teams.len());
+        for team_id in teams {
+            let status = match self.costops.fetch_budget_status(team_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    warn!(?team_id, error = %e, "Failed to fetch budget status");
+                    continue;
+                }
+            };
+
+            self.record_status(&status, now)?;
+            let forecast = self.forecast(&status, now);
+            self.forecasts.fetch_add(1, Ordering::Relaxed);
+
+            if let Some(alert) = forecast.alert(&self.config) {
+                info!(
+                    ?team_id,
+                    projected_spend = forecast.projected_spend_usd,
+                    probability = forecast.overage_probability,
+                    "Budget forecast to exceed limit"
+                );
+                self.raise(alert, &forecast);
+            }
+            forecasts.push(forecast);
+        }
+
+        Ok(forecasts)
+    }
+
+    fn raise(&self, alert: BudgetAlertEvent, forecast: &BudgetForecast) {
+        let mut event = budget_alert_event(alert, &self.config.environment);
+        event
+            .common
+            .tags
+            .insert("forecast".to_string(), "true".to_string());
+        event.common.tags.insert(
+            "overage_probability".to_string(),
+            format!("{:.2}", forecast.overage_probability),
+        );
+
+        match self.sink.try_send(event) {
+            Ok(()) => {
+                self.alerts_raised.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!(error = %e, "Dropped budget forecast alert");
+            }
+        }
+    }
+
+    /// Get forecaster statistics
+    pub fn get_stats(&self) -> BudgetForecasterStats {
+        BudgetForecasterStats {
+            forecasts: self.forecasts.load(Ordering::Relaxed),
+            alerts_raised: self.alerts_raised.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Extrapolate the period's average burn when there is no sample history
+fn linear_burn(
+    spent: f64,
+    period_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+    interval: Duration,
+    steps: usize,
+) -> Vec<PredictionPoint> {
+    let elapsed = (now - period_start).num_seconds().max(1) as f64;
+    let per_interval = spent / elapsed * interval.num_seconds() as f64;
+
+    (1..=steps)
+        .map(|i| PredictionPoint {
+            timestamp: now + interval * i as i32,
+            value: per_interval,
+            confidence: 0.5,
+            lower_bound: per_interval * 0.5,
+            upper_bound: per_interval * 1.5,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::AnalyticsConfig;
+    use chrono::TimeZone;
+
+    fn points(values: &[f64]) -> Vec<PredictionPoint> {
+        values
+            .iter()
+            .map(|&v| PredictionPoint {
+                timestamp: Utc::now(),
+                value: v,
+                confidence: 0.9,
+                lower_bound: v * 0.9,
+                upper_bound: v * 1.1,
+            })
+            .collect()
+    }
+
+    fn status(spent: f64, limit: f64) -> BudgetStatus {
+        BudgetStatus {
+            budget_id: "budget-1".to_string(),
+            team_id: Some("ml-platform".to_string()),
+            period_budget_usd: limit,
+            spent_usd: spent,
+            remaining_usd: limit - spent,
+            utilization_percentage: spent / limit * 100.0,
+            projected_overage: None,
+        }
+    }
+
+    #[test]
+    fn test_project_spend_overage() {
+        let now = Utc::now();
+        let (projected, probability, exhaustion) =
+            project_spend(900.0, 1000.0, &points(&[50.0; 6]), Duration::hours(1), now);
+
+        assert_eq!(projected, 1200.0);
+        assert!(probability > 0.9);
+        assert_eq!(exhaustion, Some(now + Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_project_spend_within_budget() {
+        let (projected, probability, exhaustion) = project_spend(
+            100.0,
+            1000.0,
+            &points(&[10.0; 10]),
+            Duration::hours(1),
+            Utc::now(),
+        );
+
+        assert_eq!(projected, 200.0);
+        assert!(probability < 0.01);
+        assert!(exhaustion.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forecast_from_sampled_burn() {
+        let prediction = Arc::new(
+            PredictionEngine::new(Arc::new(AnalyticsConfig::default()))
+                .await
+                .unwrap(),
+        );
+        let costops = Arc::new(CostOpsAdapter::new(
+            crate::adapters::costops::CostOpsConfig::from_env().unwrap(),
+        ));
+        let (tx, _rx) = mpsc::channel(8);
+        let forecaster =
+            BudgetForecaster::new(costops, prediction, BudgetForecastConfig::default(), tx);
+
+        // $20/hour for the first 20 hours of the month against a $1000 budget
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        for hour in 0..=20 {
+            let at = start + Duration::hours(hour);
+            forecaster
+                .record_status(&status(20.0 * hour as f64, 1000.0), at)
+                .unwrap();
+        }
+
+        let now = start + Duration::hours(20);
+        let forecast = forecaster.forecast(&status(400.0, 1000.0), now);
+        assert_eq!(forecast.method, ForecastMethod::Arima);
+        assert!((forecast.burn_rate_usd_per_hour - 20.0).abs() < 1.0);
+        assert!(forecast.projected_spend_usd > 1000.0);
+        assert!(forecast.overage_probability > 0.9);
+
+        let alert = forecast.alert(&BudgetForecastConfig::default()).unwrap();
+        assert_eq!(alert.alert_type, BudgetAlertType::Critical);
+    }
+}
@@ -0,0 +1,668 @@
+//! Changepoint Detection
+//!
+//! Detects sustained level shifts in metric series, such as an error rate that
+//! steps up after a deploy, as opposed to the single-point outliers flagged by
+//! the anomaly detector. Two methods are available:
+//!
+//! - **CUSUM**: two-sided cumulative sums of standardized deviations from a
+//!   reference mean; cheap and responsive to shifts of a known minimum size.
+//! - **Bayesian online** (Adams & MacKay): maintains a posterior over the
+//!   length of the current run under a Normal-Gamma model and reports a
+//!   changepoint once the most probable run starts well after the segment did.
+//!
+//! Each series first collects a warm-up segment that becomes the reference.
+//! When a changepoint is confirmed a `ChangepointEvent` is emitted with
+//! statistics for the segments before and after it, and the series re-warms
+//! on the new level.
+
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use statrs::function::gamma::ln_gamma;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+use uuid::Uuid;
+
+/// Custom payload type for changepoint events
+pub const CHANGEPOINT_EVENT_TYPE: &str = "analytics.changepoint";
+
+/// Floor for segment standard deviations, so flat series can still be standardized
+const MIN_STDDEV: f64 = 0.0001;
+
+/// Changepoint detection method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangepointMethod {
+    #[default]
+    Cusum,
+    Bayesian,
+}
+
+impl ChangepointMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cusum => "cusum",
+            Self::Bayesian => "bayesian",
+        }
+    }
+}
+
+impl FromStr for ChangepointMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "cusum" => Ok(Self::Cusum),
+            "bayesian" | "bocpd" => Ok(Self::Bayesian),
+            other => anyhow::bail!("Unknown changepoint method '{}'", other),
+        }
+    }
+}
+
+/// Changepoint detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangepointConfig {
+    pub method: ChangepointMethod,
+    /// Samples collected as the reference segment before detection starts
+    pub warmup_samples: usize,
+    /// CUSUM slack, in reference standard deviations; shifts smaller than
+    /// this are absorbed
+    pub cusum_drift: f64,
+    /// CUSUM decision threshold, in reference standard deviations
+    pub cusum_threshold: f64,
+    /// Prior probability of a changepoint at any sample (Bayesian)
+    pub hazard_rate: f64,
+    /// Longest run length tracked by the Bayesian posterior
+    pub max_run_length: usize,
+    /// Samples needed after a changepoint before it is reported (Bayesian)
+    pub min_samples_after: usize,
+    /// Samples needed before a changepoint for it to be reported (Bayesian)
+    pub min_samples_before: usize,
+}
+
+impl Default for ChangepointConfig {
+    fn default() -> Self {
+        Self {
+            method: ChangepointMethod::default(),
+            warmup_samples: 30,
+            cusum_drift: 0.5,
+            cusum_threshold: 5.0,
+            hazard_rate: 1.0 / 250.0,
+            max_run_length: 500,
+            min_samples_after: 5,
+            min_samples_before: 10,
+        }
+    }
+}
+
+impl ChangepointConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            method: match std::env::var("CHANGEPOINT_METHOD") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.method,
+            },
+            warmup_samples: std::env::var("CHANGEPOINT_WARMUP_SAMPLES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.warmup_samples),
+            cusum_threshold: std::env::var("CHANGEPOINT_CUSUM_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.cusum_threshold),
+            hazard_rate: std::env::var("CHANGEPOINT_HAZARD_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.hazard_rate),
+            ..defaults
+        })
+    }
+}
+
+// ========== Segment Statistics ==========
+
+/// Summary of a segment of a series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentStats {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub count: usize,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl SegmentStats {
+    /// Statistics of a non-empty run of `(timestamp, value)` samples
+    pub fn from_samples<'a>(
+        samples: impl IntoIterator<Item = &'a (DateTime<Utc>, f64)>,
+    ) -> Option<Self> {
+        let samples: Vec<&(DateTime<Utc>, f64)> = samples.into_iter().collect();
+        let (first, last) = (samples.first()?, samples.last()?);
+
+        let count = samples.len();
+        let mean = samples.iter().map(|(_, v)| v).sum::<f64>() / count as f64;
+        let variance = if count > 1 {
+            samples.iter().map(|(_, v)| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            start: first.0,
+            end: last.0,
+            count,
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+/// Direction of a level shift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShiftDirection {
+    Increase,
+    Decrease,
+}
+
+/// A detected sustained level shift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangepointEvent {
+    pub metric_name: String,
+    /// Timestamp of the first sample of the new segment
+    pub changepoint_at: DateTime<Utc>,
+    /// Timestamp of the sample that confirmed the changepoint
+    pub detected_at: DateTime<Utc>,
+    pub method: ChangepointMethod,
+    pub direction: ShiftDirection,
+    pub before: SegmentStats,
+    pub after: SegmentStats,
+    /// Difference between segment means
+    pub shift: f64,
+    /// Ratio of segment means, when the earlier mean is non-zero
+    pub ratio: Option<f64>,
+    /// CUSUM statistic at detection, or posterior probability of the new run
+    pub score: f64,
+}
+
+impl ChangepointEvent {
+    /// Shift expressed in standard deviations of the earlier segment
+    pub fn shift_in_stddevs(&self) -> f64 {
+        self.shift / self.before.stddev.max(MIN_STDDEV)
+    }
+
+    /// Wrap the changepoint as an analytics event for publishing
+    pub fn to_event(&self, environment: &str) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        tags.insert("metric".to_string(), self.metric_name.clone());
+        tags.insert("method".to_string(), self.method.as_str().to_string());
+
+        let severity = if self.shift_in_stddevs().abs() >= 6.0 {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: self.detected_at,
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity,
+                environment: environment.to_string(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: CHANGEPOINT_EVENT_TYPE.to_string(),
+                data: serde_json::to_value(self).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+// ========== CUSUM ==========
+
+/// Two-sided CUSUM against a fixed reference mean and standard deviation
+#[derive(Debug, Clone)]
+pub struct CusumDetector {
+    mean: f64,
+    stddev: f64,
+    drift: f64,
+    threshold: f64,
+    upper: f64,
+    lower: f64,
+    upper_start: usize,
+    lower_start: usize,
+    samples: usize,
+}
+
+impl CusumDetector {
+    pub fn new(mean: f64, stddev: f64, drift: f64, threshold: f64) -> Self {
+        Self {
+            mean,
+            stddev: stddev.max(MIN_STDDEV),
+            drift,
+            threshold,
+            upper: 0.0,
+            lower: 0.0,
+            upper_start: 0,
+            lower_start: 0,
+            samples: 0,
+        }
+    }
+
+    /// Feed a sample. When a shift is confirmed, returns how many of the
+    /// samples seen so far (including this one) belong to the new segment,
+    /// the direction, and the statistic that crossed the threshold.
+    pub fn update(&mut self, value: f64) -> Option<(usize, ShiftDirection, f64)> {
+        let z = (value - self.mean) / self.stddev;
+        let index = self.samples;
+        self.samples += 1;
+
+        // Each excursion starts at the sample that lifted its sum off zero
+        if self.upper == 0.0 {
+            self.upper_start = index;
+        }
+        if self.lower == 0.0 {
+            self.lower_start = index;
+        }
+        self.upper = (self.upper + z - self.drift).max(0.0);
+        self.lower = (self.lower - z - self.drift).max(0.0);
+
+        if self.upper > self.threshold {
+            Some((
+                self.samples - self.upper_start,
+                ShiftDirection::Increase,
+                self.upper,
+            ))
+        } else if self.lower > self.threshold {
+            Some((
+                self.samples - self.lower_start,
+                ShiftDirection::Decrease,
+                self.lower,
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+// ========== Bayesian Online ==========
+
+/// Normal-Gamma posterior parameters for one candidate run
+#[derive(Debug, Clone, Copy)]
+struct NormalGamma {
+    mean: f64,
+    kappa: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl NormalGamma {
+    /// Log density of the Student-t posterior predictive at `x`
+    fn log_predictive(&self, x: f64) -> f64 {
+        let nu = 2.0 * self.alpha;
+        let scale2 = self.beta * (self.kappa + 1.0) / (self.alpha * self.kappa);
+        ln_gamma((nu + 1.0) / 2.0)
+            - ln_gamma(nu / 2.0)
+            - 0.5 * (nu * std::f64::consts::PI * scale2).ln()
+            - (nu + 1.0) / 2.0 * (1.0 + (x - self.mean).powi(2) / (nu * scale2)).ln()
+    }
+
+    fn observe(&self, x: f64) -> Self {
+        Self {
+            mean: (self.kappa * self.mean + x) / (self.kappa + 1.0),
+            kappa: self.kappa + 1.0,
+            alpha: self.alpha + 0.5,
+            beta: self.beta + self.kappa * (x - self.mean).powi(2) / (2.0 * (self.kappa + 1.0)),
+        }
+    }
+}
+
+/// Bayesian online changepoint detection with a Normal-Gamma model
+#[derive(Debug, Clone)]
+pub struct BayesianOnlineDetector {
+    prior: NormalGamma,
+    hazard: f64,
+    max_run_length: usize,
+    min_after: usize,
+    min_before: usize,
+    /// Posterior probability of each run length, indexed by run length
+    run_probs: Vec<f64>,
+    params: Vec<NormalGamma>,
+    samples: usize,
+}
+
+impl BayesianOnlineDetector {
+    /// Build a detector whose prior is centred on a reference segment
+    pub fn new(
+        prior_mean: f64,
+        prior_stddev: f64,
+        hazard: f64,
+        max_run_length: usize,
+        min_after: usize,
+        min_before: usize,
+    ) -> Self {
+        let prior = NormalGamma {
+            mean: prior_mean,
+            kappa: 1.0,
+            alpha: 1.0,
+            beta: prior_stddev.max(MIN_STDDEV).powi(2),
+        };
+        Self {
+            prior,
+            hazard: hazard.clamp(f64::EPSILON, 1.0),
+            max_run_length: max_run_length.max(2),
+            min_after: min_after.max(1),
+            min_before: min_before.max(1),
+            run_probs: vec![1.0],
+            params: vec![prior],
+            samples: 0,
+        }
+    }
+
+    /// Most probable current run length and its posterior probability
+    pub fn most_probable_run(&self) -> (usize, f64) {
+        self.run_probs
+            .iter()
+            .copied()
+            .enumerate()
+            .fold(
+                (0, 0.0),
+                |best, (r, p)| if p > best.1 { (r, p) } else { best },
+            )
+    }
+
+    /// Feed a sample. When a changepoint is confirmed, returns the length of
+    /// the new run and its posterior probability.
+    pub fn update(&mut self, value: f64) -> Option<(usize, f64)> {
+        self.samples += 1;
+
+        let log_joint: Vec<f64> = self
+            .run_probs
+            .iter()
+            .zip(&self.params)
+            .map(|(p, params)| p.ln() + params.log_predictive(value))
+            .collect();
+        let max = log_joint.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = log_joint.iter().map(|l| (l - max).exp()).collect();
+
+        let mut run_probs = Vec::with_capacity(weights.len() + 1);
+        run_probs.push(weights.iter().sum::<f64>() * self.hazard);
+        run_probs.extend(weights.iter().map(|w| w * (1.0 - self.hazard)));
+
+        let mut params = Vec::with_capacity(self.params.len() + 1);
+        params.push(self.prior);
+        params.extend(self.params.iter().map(|p| p.observe(value)));
+
+        // Fold the tail into the longest tracked run
+        if run_probs.len() > self.max_run_length {
+            let tail: f64 = run_probs.drain(self.max_run_length..).sum();
+            params.truncate(self.max_run_length);
+            run_probs[self.max_run_length - 1] += tail;
+        }
+
+        let total: f64 = run_probs.iter().sum();
+        if total > 0.0 && total.is_finite() {
+            run_probs.iter_mut().for_each(|p| *p /= total);
+        } else {
+            // Numerical collapse; restart from the prior
+            run_probs = vec![1.0];
+            params = vec![self.prior];
+        }
+
+        self.run_probs = run_probs;
+        self.params = params;
+
+        let (run, probability) = self.most_probable_run();
+        let horizon = self.samples.min(self.max_run_length - 1);
+        if run >= self.min_after && run + self.min_before <= horizon {
+            Some((run, probability))
+        } else {
+            None
+        }
+    }
+}
+
+// ========== Engine ==========
+
+enum SeriesDetector {
+    Cusum(CusumDetector),
+    Bayesian(BayesianOnlineDetector),
+}
+
+/// Per-metric detection state
+struct SeriesState {
+    /// Samples since the current segment began, oldest first
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+    /// Set once the warm-up segment is complete
+    detector: Option<SeriesDetector>,
+}
+
+/// Changepoint detection over many metric series
+pub struct ChangepointDetector {
+    config: ChangepointConfig,
+    series: DashMap<String, SeriesState>,
+    samples_processed: AtomicU64,
+    changepoints_detected: AtomicU64,
+}
+
+impl ChangepointDetector {
+    pub fn new(config: ChangepointConfig) -> Self {
+        Self {
+            config,
+            series: DashMap::new(),
+            samples_processed: AtomicU64::new(0),
+            changepoints_detected: AtomicU64::new(0),
+        }
+    }
+
+    /// Feed a sample for a metric, returning a changepoint if one is confirmed
+    pub fn observe(
+        &self,
+        metric_name: &str,
+        value: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Option<ChangepointEvent> {
+        self.samples_processed.fetch_add(1, Ordering::Relaxed);
+
+        let mut state = self
+            .series
+            .entry(metric_name.to_string())
+            .or_insert_with(|| SeriesState {
+                samples: VecDeque::new(),
+                detector: None,
+            });
+
+        state.samples.push_back((timestamp, value));
+        if state.samples.len() > self.config.max_run_length.max(self.config.warmup_samples) {
+            state.samples.pop_front();
+        }
+
+        let Some(detector) = state.detector.as_mut() else {
+            if state.samples.len() >= self.config.warmup_samples.max(2) {
+                let reference = SegmentStats::from_samples(&state.samples)?;
+                state.detector = Some(self.new_detector(&reference));
+            }
+            return None;
+        };
+
+        let (run, score) = match detector {
+            SeriesDetector::Cusum(cusum) => {
+                let (run, _, statistic) = cusum.update(value)?;
+                (run, statistic)
+            }
+            SeriesDetector::Bayesian(bocpd) => bocpd.update(value)?,
+        };
+
+        let split = state.samples.len().saturating_sub(run);
+        if split == 0 {
+            return None;
+        }
+        let before = SegmentStats::from_samples(state.samples.range(..split))?;
+        let after = SegmentStats::from_samples(state.samples.range(split..))?;
+
+        // The new level becomes the next reference once it has warmed up
+        state.samples.drain(..split);
+        state.detector = None;
+        drop(state);
+
+        let shift = after.mean - before.mean;
+        let event = ChangepointEvent {
+            metric_name: metric_name.to_string(),
+            changepoint_at: after.start,
+            detected_at: timestamp,
+            method: self.config.method,
+            direction: if shift >= 0.0 {
+                ShiftDirection::Increase
+            } else {
+                ShiftDirection::Decrease
+            },
+            ratio: (before.mean != 0.0).then(|| after.mean / before.mean),
+            shift,
+            before,
+            after,
+            score,
+        };
+
+        self.changepoints_detected.fetch_add(1, Ordering::Relaxed);
+        info!(
+            metric = %metric_name,
+            method = self.config.method.as_str(),
+            before = event.before.mean,
+            after = event.after.mean,
+            "Changepoint detected"
+        );
+
+        Some(event)
+    }
+
+    fn new_detector(&self, reference: &SegmentStats) -> SeriesDetector {
+        match self.config.method {
+            ChangepointMethod::Cusum => SeriesDetector::Cusum(CusumDetector::new(
+                reference.mean,
+                reference.stddev,
+                self.config.cusum_drift,
+                self.config.cusum_threshold,
+            )),
+            ChangepointMethod::Bayesian => SeriesDetector::Bayesian(BayesianOnlineDetector::new(
+                reference.mean,
+                reference.stddev,
+                self.config.hazard_rate,
+                self.config.max_run_length,
+                self.config.min_samples_after,
+                self.config.min_samples_before,
+            )),
+        }
+    }
+
+    /// Forget a metric's history
+    pub fn reset(&self, metric_name: &str) {
+        self.series.remove(metric_name);
+    }
+
+    pub fn get_stats(&self) -> ChangepointStats {
+        ChangepointStats {
+            tracked_series: self.series.len(),
+            samples_processed: self.samples_processed.load(Ordering::Relaxed),
+            changepoints_detected: self.changepoints_detected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Changepoint detector statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangepointStats {
+    pub tracked_series: usize,
+    pub samples_processed: u64,
+    pub changepoints_detected: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// Deterministic noise in [-1, 1]
+    fn noise(i: usize) -> f64 {
+        ((i as f64) * 1.7).sin()
+    }
+
+    fn run(method: ChangepointMethod, values: &[f64]) -> Vec<ChangepointEvent> {
+        let detector = ChangepointDetector::new(ChangepointConfig {
+            method,
+            ..Default::default()
+        });
+        let start = Utc::now();
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| {
+                detector.observe("error_rate", *v, start + Duration::seconds(i as i64))
+            })
+            .collect()
+    }
+
+    fn step_series() -> Vec<f64> {
+        (0..120)
+            .map(|i| if i < 60 { 10.0 } else { 20.0 } + noise(i))
+            .collect()
+    }
+
+    #[test]
+    fn test_cusum_detects_level_shift() {
+        let events = run(ChangepointMethod::Cusum, &step_series());
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.direction, ShiftDirection::Increase);
+        assert!((event.before.mean - 10.0).abs() < 0.5);
+        assert!((event.after.mean - 20.0).abs() < 1.0);
+        assert!((event.ratio.unwrap() - 2.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_bayesian_detects_level_shift() {
+        let events = run(ChangepointMethod::Bayesian, &step_series());
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.direction, ShiftDirection::Increase);
+        assert!((event.before.mean - 10.0).abs() < 0.5);
+        assert!((event.after.mean - 20.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_stationary_series_has_no_changepoints() {
+        let values: Vec<f64> = (0..300).map(|i| 10.0 + noise(i)).collect();
+
+        assert!(run(ChangepointMethod::Cusum, &values).is_empty());
+        assert!(run(ChangepointMethod::Bayesian, &values).is_empty());
+    }
+
+    #[test]
+    fn test_changepoint_event_payload() {
+        let event = &run(ChangepointMethod::Cusum, &step_series())[0];
+        let analytics_event = event.to_event("production");
+
+        match analytics_event.payload {
+            EventPayload::Custom(payload) => {
+                assert_eq!(payload.custom_type, CHANGEPOINT_EVENT_TYPE);
+                assert_eq!(payload.data["direction"], "increase");
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,543 @@
+//! Behavioral Clustering
+//!
+//! Segments models or sessions into cohorts by their behaviour. Feature vectors
+//! are built per model from aggregated metrics (grouped by a tag such as
+//! `model_id`) or per session from Memory-Graph interaction graphs and memory
+//! snapshots, standardized, and clustered with k-means or DBSCAN.
+//!
+//! Cluster centroids are reported in the original feature units so cohorts can
+//! be read directly ("high latency, high cost").
+
+use crate::adapters::memory_graph::{InteractionGraph, MemoryGraphAdapter, MemorySnapshot};
+use crate::database::Database;
+use crate::models::metrics::TimeWindow;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{debug, instrument, warn};
+
+/// Default metrics forming a model's feature vector
+pub const DEFAULT_MODEL_FEATURES: [&str; 3] = ["latency_ms", "error_rate", "cost_usd"];
+
+/// Tag identifying the model an aggregate belongs to
+pub const DEFAULT_MODEL_TAG: &str = "model_id";
+
+/// Features derived for each session
+pub const SESSION_FEATURES: [&str; 8] = [
+    "node_count",
+    "avg_degree",
+    "clustering_coefficient",
+    "topic_count",
+    "entity_count",
+    "context_window_tokens",
+    "retrieval_cache_hit_rate",
+    "retrieval_latency_ms",
+];
+
+/// Most iterations k-means runs before stopping
+const MAX_KMEANS_ITERATIONS: usize = 100;
+
+/// Clustering algorithm and its parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum ClusteringAlgorithm {
+    KMeans {
+        k: usize,
+        /// Seed for centroid initialization, for reproducible cohorts
+        seed: u64,
+    },
+    Dbscan {
+        /// Neighbourhood radius in standardized units
+        eps: f64,
+        min_points: usize,
+    },
+}
+
+impl Default for ClusteringAlgorithm {
+    fn default() -> Self {
+        Self::KMeans { k: 3, seed: 42 }
+    }
+}
+
+/// What is being clustered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Model,
+    Session,
+}
+
+/// A labeled feature vector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureVector {
+    pub id: String,
+    pub values: Vec<f64>,
+}
+
+// ========== Algorithms ==========
+
+/// Scale each feature to zero mean and unit variance.
+/// Constant features are centred but left unscaled.
+pub fn standardize(points: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let Some(dims) = points.first().map(Vec::len) else {
+        return Vec::new();
+    };
+    let n = points.len() as f64;
+
+    let (means, stddevs): (Vec<f64>, Vec<f64>) = (0..dims)
+        .map(|d| {
+            let mean = points.iter().map(|p| p[d]).sum::<f64>() / n;
+            let var = points.iter().map(|p| (p[d] - mean).powi(2)).sum::<f64>() / n;
+            (mean, if var > 0.0 { var.sqrt() } else { 1.0 })
+        })
+        .unzip();
+
+    points
+        .iter()
+        .map(|p| {
+            p.iter()
+                .zip(means.iter().zip(&stddevs))
+                .map(|(v, (m, s))| (v - m) / s)
+                .collect()
+        })
+        .collect()
+}
+
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+/// k-means with k-means++ initialization. Returns a cluster index per point.
+pub fn kmeans(points: &[Vec<f64>], k: usize, seed: u64) -> Vec<usize> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let k = k.clamp(1, points.len());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // k-means++: each new centroid is drawn proportionally to its squared
+    // distance from the nearest existing one
+    let mut centroids = vec![points[rng.gen_range(0..points.len())].clone()];
+    while centroids.len() < k {
+        let weights: Vec<f64> = points
+            .iter()
+            .map(|p| {
+                centroids
+                    .iter()
+                    .map(|c| squared_distance(p, c))
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            // Fewer distinct points than clusters
+            break;
+        }
+        let mut target = rng.gen_range(0.0..total);
+        let next = weights
+            .iter()
+            .position(|w| {
+                target -= w;
+                target <= 0.0
+            })
+            .unwrap_or(points.len() - 1);
+        centroids.push(points[next].clone());
+    }
+
+    let nearest = |p: &[f64], centroids: &[Vec<f64>]| {
+        centroids
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, squared_distance(p, c)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    };
+
+    let mut assignments: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+    for _ in 0..MAX_KMEANS_ITERATIONS {
+        let dims = points[0].len();
+        let mut sums = vec![vec![0.0; dims]; centroids.len()];
+        let mut counts = vec![0usize; centroids.len()];
+        for (p, &c) in points.iter().zip(&assignments) {
+            counts[c] += 1;
+            for (s, v) in sums[c].iter_mut().zip(p) {
+                *s += v;
+            }
+        }
+        for (c, (sum, count)) in centroids.iter_mut().zip(sums.into_iter().zip(counts)) {
+            // Empty clusters keep their previous centroid
+            if count > 0 {
+                *c = sum.into_iter().map(|s| s / count as f64).collect();
+            }
+        }
+
+        let next: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+    }
+
+    relabel(assignments.into_iter().map(Some).collect())
+        .into_iter()
+        .map(|c| c.unwrap_or(0))
+        .collect()
+}
+
+/// DBSCAN. Returns a cluster index per point, `None` for noise.
+pub fn dbscan(points: &[Vec<f64>], eps: f64, min_points: usize) -> Vec<Option<usize>> {
+    let eps2 = eps * eps;
+    let neighbours = |i: usize| -> Vec<usize> {
+        (0..points.len())
+            .filter(|&j| squared_distance(&points[i], &points[j]) <= eps2)
+            .collect()
+    };
+
+    let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+    let mut visited = vec![false; points.len()];
+    let mut cluster = 0;
+
+    for i in 0..points.len() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+
+        let seeds = neighbours(i);
+        if seeds.len() < min_points {
+            continue;
+        }
+
+        labels[i] = Some(cluster);
+        let mut queue = seeds;
+        while let Some(j) = queue.pop() {
+            if labels[j].is_none() {
+                labels[j] = Some(cluster);
+            }
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+
+            let reachable = neighbours(j);
+            if reachable.len() >= min_points {
+                queue.extend(reachable);
+            }
+        }
+        cluster += 1;
+    }
+
+    labels
+}
+
+/// Renumber clusters by descending size so label 0 is the largest cohort
+fn relabel(labels: Vec<Option<usize>>) -> Vec<Option<usize>> {
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    for label in labels.iter().flatten() {
+        *sizes.entry(*label).or_default() += 1;
+    }
+    let mut order: Vec<(usize, usize)> = sizes.into_iter().collect();
+    order.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mapping: BTreeMap<usize, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(new, (old, _))| (*old, new))
+        .collect();
+
+    labels.into_iter().map(|l| l.map(|l| mapping[&l])).collect()
+}
+
+// ========== Report ==========
+
+/// One cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    pub label: usize,
+    pub size: usize,
+    /// Mean feature values of the members, in original units
+    pub centroid: BTreeMap<String, f64>,
+    pub members: Vec<String>,
+}
+
+/// Result of clustering a set of entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusteringReport {
+    pub entity: EntityKind,
+    pub algorithm: ClusteringAlgorithm,
+    pub features: Vec<String>,
+    pub clusters: Vec<Cluster>,
+    /// Entities DBSCAN left unassigned
+    pub noise: Vec<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Cluster feature vectors into a labeled report
+pub fn cluster(
+    entity: EntityKind,
+    features: &[String],
+    vectors: &[FeatureVector],
+    algorithm: ClusteringAlgorithm,
+) -> Result<ClusteringReport> {
+    if let Some(v) = vectors.iter().find(|v| v.values.len() != features.len()) {
+        bail!(
+            "Feature vector for {} has {} values, expected {}",
+            v.id,
+            v.values.len(),
+            features.len()
+        );
+    }
+
+    let raw: Vec<Vec<f64>> = vectors.iter().map(|v| v.values.clone()).collect();
+    let scaled = standardize(&raw);
+    let labels = match algorithm {
+        ClusteringAlgorithm::KMeans { k, seed } => {
+            kmeans(&scaled, k, seed).into_iter().map(Some).collect()
+        }
+        ClusteringAlgorithm::Dbscan { eps, min_points } => {
+            relabel(dbscan(&scaled, eps, min_points.max(1)))
+        }
+    };
+
+    let mut clusters: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    let mut noise = Vec::new();
+    for (i, label) in labels.iter().enumerate() {
+        match label {
+            Some(label) => clusters.entry(*label).or_default().push(i),
+            None => noise.push(vectors[i].id.clone()),
+        }
+    }
+
+    let clusters = clusters
+        .into_iter()
+        .map(|(label, members)| {
+            let centroid = features
+                .iter()
+                .enumerate()
+                .map(|(d, name)| {
+                    let mean =
+                        members.iter().map(|&i| raw[i][d]).sum::<f64>() / members.len() as f64;
+                    (name.clone(), mean)
+                })
+                .collect();
+            Cluster {
+                label,
+                size: members.len(),
+                centroid,
+                members: members.iter().map(|&i| vectors[i].id.clone()).collect(),
+            }
+        })
+        .collect();
+
+    Ok(ClusteringReport {
+        entity,
+        algorithm,
+        features: features.to_vec(),
+        clusters,
+        noise,
+        generated_at: Utc::now(),
+    })
+}
+
+/// Feature values for one session, in `SESSION_FEATURES` order
+pub fn session_features(graph: &InteractionGraph, snapshot: &MemorySnapshot) -> Vec<f64> {
+    vec![
+        graph.statistics.node_count as f64,
+        graph.statistics.avg_degree,
+        graph.statistics.clustering_coefficient,
+        graph.topics.len() as f64,
+        graph.entities.len() as f64,
+        snapshot.context_window_tokens as f64,
+        snapshot.retrieval_stats.cache_hit_rate,
+        snapshot.retrieval_stats.avg_latency_ms,
+    ]
+}
+
+// ========== Analyzer ==========
+
+/// Builds feature vectors from hub data and clusters them
+pub struct ClusterAnalyzer {
+    database: Arc<Database>,
+    memory_graph: Option<Arc<MemoryGraphAdapter>>,
+}
+
+impl ClusterAnalyzer {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            memory_graph: None,
+        }
+    }
+
+    /// Enable session clustering from Memory-Graph data
+    pub fn with_memory_graph(mut self, adapter: Arc<MemoryGraphAdapter>) -> Self {
+        self.memory_graph = Some(adapter);
+        self
+    }
+
+    /// Cluster models by the mean of each feature metric over `[start, end)`.
+    /// Models missing any feature are left out.
+    #[instrument(skip(self))]
+    pub async fn cluster_models(
+        &self,
+        features: &[String],
+        group_tag: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        algorithm: ClusteringAlgorithm,
+    ) -> Result<ClusteringReport> {
+        let means = self
+            .database
+            .query_metric_means_by_tag(features, group_tag, window, start, end)
+            .await?;
+
+        let mut by_model: BTreeMap<String, Vec<Option<f64>>> = BTreeMap::new();
+        for row in means {
+            let Some(d) = features.iter().position(|f| *f == row.metric_name) else {
+                continue;
+            };
+            by_model
+                .entry(row.tag_value)
+                .or_insert_with(|| vec![None; features.len()])[d] = Some(row.mean);
+        }
+
+        let vectors: Vec<FeatureVector> = by_model
+            .into_iter()
+            .filter_map(|(id, values)| {
+                Some(FeatureVector {
+                    values: values.into_iter().collect::<Option<Vec<f64>>>()?,
+                    id,
+                })
+            })
+            .collect();
+
+        debug!(models = vectors.len(), "Clustering models");
+        cluster(EntityKind::Model, features, &vectors, algorithm)
+    }
+
+    /// Cluster sessions by their Memory-Graph interaction and memory statistics.
+    /// Sessions that cannot be fetched are skipped.
+    #[instrument(skip(self, session_ids))]
+    pub async fn cluster_sessions(
+        &self,
+        session_ids: &[String],
+        algorithm: ClusteringAlgorithm,
+    ) -> Result<ClusteringReport> {
+        let Some(adapter) = &self.memory_graph else {
+            bail!("Session clustering requires the Memory-Graph adapter");
+        };
+
+        let mut vectors = Vec::with_capacity(session_ids.len());
+        for session_id in session_ids {
+            let fetched = tokio::try_join!(
+                adapter.fetch_interaction_graph(session_id),
+                adapter.fetch_memory_snapshot(session_id),
+            );
+            match fetched {
+                Ok((graph, snapshot)) => vectors.push(FeatureVector {
+                    id: session_id.clone(),
+                    values: session_features(&graph, &snapshot),
+                }),
+                Err(e) => warn!(session_id = %session_id, "Skipping session: {}", e),
+            }
+        }
+
+        let features: Vec<String> = SESSION_FEATURES.iter().map(|f| f.to_string()).collect();
+        cluster(EntityKind::Session, &features, &vectors, algorithm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two tight groups around (0, 0) and (10, 10) plus one outlier
+    fn points() -> Vec<FeatureVector> {
+        let mut vectors = Vec::new();
+        for i in 0..6 {
+            let jitter = i as f64 * 0.1;
+            vectors.push(FeatureVector {
+                id: format!("low-{}", i),
+                values: vec![jitter, 0.2 - jitter / 2.0],
+            });
+            vectors.push(FeatureVector {
+                id: format!("high-{}", i),
+                values: vec![10.0 + jitter, 10.0 - jitter],
+            });
+        }
+        vectors.push(FeatureVector {
+            id: "outlier".to_string(),
+            values: vec![30.0, -20.0],
+        });
+        vectors
+    }
+
+    fn features() -> Vec<String> {
+        vec!["latency_ms".to_string(), "cost_usd".to_string()]
+    }
+
+    fn members_of(report: &ClusteringReport, id: &str) -> usize {
+        report
+            .clusters
+            .iter()
+            .find(|c| c.members.iter().any(|m| m == id))
+            .map(|c| c.label)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_kmeans_separates_groups() {
+        let report = cluster(
+            EntityKind::Model,
+            &features(),
+            &points()[..12],
+            ClusteringAlgorithm::KMeans { k: 2, seed: 7 },
+        )
+        .unwrap();
+
+        assert_eq!(report.clusters.len(), 2);
+        assert_eq!(members_of(&report, "low-0"), members_of(&report, "low-5"));
+        assert_ne!(members_of(&report, "low-0"), members_of(&report, "high-0"));
+
+        let high = &report.clusters[members_of(&report, "high-0")];
+        assert!((high.centroid["latency_ms"] - 10.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dbscan_marks_noise() {
+        let report = cluster(
+            EntityKind::Model,
+            &features(),
+            &points(),
+            ClusteringAlgorithm::Dbscan {
+                eps: 0.5,
+                min_points: 3,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(report.clusters.len(), 2);
+        assert_eq!(report.noise, vec!["outlier".to_string()]);
+        assert!(report.clusters.iter().all(|c| c.size == 6));
+    }
+
+    #[test]
+    fn test_rejects_mismatched_vectors() {
+        let vectors = vec![FeatureVector {
+            id: "m".to_string(),
+            values: vec![1.0],
+        }];
+        assert!(cluster(
+            EntityKind::Model,
+            &features(),
+            &vectors,
+            ClusteringAlgorithm::default()
+        )
+        .is_err());
+    }
+}
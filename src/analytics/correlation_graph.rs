@@ -0,0 +1,488 @@
+//! Correlation Graph Analytics
+//!
+//! Graph algorithms over stored event correlations. Events are grouped by
+//! signature (`<source_module>/<custom type or event type>`) so that kinds
+//! of event recurring across many correlations become single nodes, and each
+//! correlation becomes an edge from the earlier event's signature to the
+//! later one's.
+//!
+//! Importance is a personalized PageRank run over the reversed edges, with
+//! teleports landing on signatures in proportion to the incidents (events at
+//! or above the incident severity) they were. Rank therefore flows from
+//! incidents back to the events that precede them, and the highest ranked
+//! signatures are the "hubs" that repeatedly lead up to incidents.
+
+use crate::database::CorrelationEdgeRow;
+use crate::schemas::events::Severity;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// Longest chain reported
+pub const MAX_CHAIN_LENGTH: usize = 6;
+
+/// Paths explored while searching for chains, bounding work on dense graphs
+const MAX_EXPLORED_PATHS: usize = 10_000;
+
+/// Graph analysis parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationGraphConfig {
+    /// Probability of following an edge rather than teleporting
+    pub damping: f64,
+    pub iterations: usize,
+    /// Events at or above this severity count as incidents
+    pub incident_severity: Severity,
+    /// Signatures in a chain, at most [`MAX_CHAIN_LENGTH`]
+    pub max_chain_length: usize,
+    /// Hubs and chains reported
+    pub top_n: usize,
+}
+
+impl Default for CorrelationGraphConfig {
+    fn default() -> Self {
+        Self {
+            damping: 0.85,
+            iterations: 50,
+            incident_severity: Severity::Error,
+            max_chain_length: 4,
+            top_n: 10,
+        }
+    }
+}
+
+/// Correlations between two signatures, aggregated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureEdge {
+    pub from: String,
+    pub to: String,
+    pub correlations: u64,
+    pub mean_strength: f64,
+}
+
+/// A signature ranked by how strongly it leads up to incidents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HubEvent {
+    pub signature: String,
+    pub score: f64,
+    /// Distinct events with this signature
+    pub occurrences: u64,
+    /// Distinct incidents an event with this signature directly preceded
+    pub incidents_preceded: u64,
+    /// Events with this signature that were incidents themselves
+    pub incidents: u64,
+    /// Index into the report's components
+    pub component: usize,
+}
+
+/// A path of signatures ending in an incident signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelationChain {
+    pub signatures: Vec<String>,
+    /// Correlations along the weakest link
+    pub support: u64,
+    /// Product of the links' mean strengths
+    pub strength: f64,
+}
+
+impl CorrelationChain {
+    fn score(&self) -> f64 {
+        self.support as f64 * self.strength
+    }
+}
+
+/// Graph analysis of correlations over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationGraphReport {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub correlations: usize,
+    pub signatures: usize,
+    /// Signatures of each connected component, largest first
+    pub components: Vec<Vec<String>>,
+    pub hubs: Vec<HubEvent>,
+    pub chains: Vec<CorrelationChain>,
+}
+
+#[derive(Debug, Default)]
+struct NodeStats {
+    events: HashSet<Uuid>,
+    incidents: HashSet<Uuid>,
+    incidents_preceded: HashSet<Uuid>,
+}
+
+/// Directed, weighted graph of signatures
+#[derive(Debug, Default)]
+pub struct CorrelationGraph {
+    nodes: BTreeMap<String, NodeStats>,
+    // (from, to) -> (correlations, total strength)
+    edges: BTreeMap<(String, String), (u64, f64)>,
+    correlations: usize,
+}
+
+impl CorrelationGraph {
+    /// Build the graph from correlation rows, treating events at or above
+    /// `incident_severity` as incidents
+    pub fn from_rows(rows: &[CorrelationEdgeRow], incident_severity: &Severity) -> Self {
+        let is_incident = |severity: &str| {
+            serde_json::from_value::<Severity>(serde_json::Value::String(severity.to_string()))
+                .map_or(false, |s| s >= *incident_severity)
+        };
+
+        let mut graph = Self::default();
+        for row in rows {
+            let source = (
+                &row.source_signature,
+                row.source_event_id,
+                row.source_timestamp,
+                is_incident(&row.source_severity),
+            );
+            let target = (
+                &row.target_signature,
+                row.target_event_id,
+                row.target_timestamp,
+                is_incident(&row.target_severity),
+            );
+            // Edges run from the earlier event to the later one
+            let (earlier, later) = if target.2 < source.2 {
+                (target, source)
+            } else {
+                (source, target)
+            };
+
+            for (signature, event_id, _, incident) in [earlier, later] {
+                let node = graph.nodes.entry(signature.clone()).or_default();
+                node.events.insert(event_id);
+                if incident {
+                    node.incidents.insert(event_id);
+                }
+            }
+            if earlier.0 == later.0 {
+                continue;
+            }
+            if later.3 {
+                graph
+                    .nodes
+                    .entry(earlier.0.clone())
+                    .or_default()
+                    .incidents_preceded
+                    .insert(later.1);
+            }
+            let edge = graph
+                .edges
+                .entry((earlier.0.clone(), later.0.clone()))
+                .or_insert((0, 0.0));
+            edge.0 += 1;
+            edge.1 += row.strength;
+            graph.correlations += 1;
+        }
+        graph
+    }
+
+    pub fn edges(&self) -> Vec<SignatureEdge> {
+        self.edges
+            .iter()
+            .map(|((from, to), (count, total))| SignatureEdge {
+                from: from.clone(),
+                to: to.clone(),
+                correlations: *count,
+                mean_strength: total / *count as f64,
+            })
+            .collect()
+    }
+
+    /// Weakly connected components, largest first
+    pub fn connected_components(&self) -> Vec<Vec<String>> {
+        let names: Vec<&String> = self.nodes.keys().collect();
+        let index: HashMap<&String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+        let mut parent: Vec<usize> = (0..names.len()).collect();
+
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for (from, to) in self.edges.keys() {
+            let (a, b) = (find(&mut parent, index[from]), find(&mut parent, index[to]));
+            if a != b {
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (i, name) in names.iter().enumerate() {
+            let root = find(&mut parent, i);
+            components.entry(root).or_default().push((*name).clone());
+        }
+        let mut components: Vec<Vec<String>> = components.into_values().collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    /// Personalized PageRank over the reversed edges, teleporting to incident
+    /// signatures (or uniformly when there are none). Scores sum to one.
+    pub fn importance(&self, damping: f64, iterations: usize) -> HashMap<String, f64> {
+        let names: Vec<&String> = self.nodes.keys().collect();
+        let n = names.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+        let index: HashMap<&String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+
+        let incidents: Vec<f64> = names
+            .iter()
+            .map(|name| self.nodes[*name].incidents.len() as f64)
+            .collect();
+        let total_incidents: f64 = incidents.iter().sum();
+        let teleport: Vec<f64> = if total_incidents > 0.0 {
+            incidents.iter().map(|i| i / total_incidents).collect()
+        } else {
+            vec![1.0 / n as f64; n]
+        };
+
+        // Reversed adjacency: rank moves from the later signature to the earlier
+        let mut out_weight = vec![0.0; n];
+        let mut links: Vec<(usize, usize, f64)> = Vec::with_capacity(self.edges.len());
+        for ((from, to), (_, strength)) in &self.edges {
+            let (later, earlier) = (index[to], index[from]);
+            out_weight[later] += strength;
+            links.push((later, earlier, *strength));
+        }
+
+        let damping = damping.clamp(0.0, 1.0);
+        let mut rank = teleport.clone();
+        for _ in 0..iterations {
+            // Rank of signatures nothing precedes returns through the teleport
+            let dangling: f64 = (0..n)
+                .filter(|i| out_weight[*i] == 0.0)
+                .map(|i| rank[i])
+                .sum();
+            let mut next: Vec<f64> = teleport
+                .iter()
+                .map(|t| (1.0 - damping + damping * dangling) * t)
+                .collect();
+            for (later, earlier, weight) in &links {
+                next[*earlier] += damping * rank[*later] * weight / out_weight[*later];
+            }
+            rank = next;
+        }
+
+        names
+            .into_iter()
+            .zip(rank)
+            .map(|(name, score)| (name.clone(), score))
+            .collect()
+    }
+
+    /// Highest-ranked signatures that preceded at least one incident
+    pub fn hubs(&self, config: &CorrelationGraphConfig) -> Vec<HubEvent> {
+        let scores = self.importance(config.damping, config.iterations);
+        let components = self.connected_components();
+        let component_of: HashMap<&String, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, names)| names.iter().map(move |name| (name, i)))
+            .collect();
+
+        let mut hubs: Vec<HubEvent> = self
+            .nodes
+            .iter()
+            .filter(|(_, stats)| !stats.incidents_preceded.is_empty())
+            .map(|(signature, stats)| HubEvent {
+                signature: signature.clone(),
+                score: scores.get(signature).copied().unwrap_or(0.0),
+                occurrences: stats.events.len() as u64,
+                incidents_preceded: stats.incidents_preceded.len() as u64,
+                incidents: stats.incidents.len() as u64,
+                component: component_of[signature],
+            })
+            .collect();
+        hubs.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.signature.cmp(&b.signature))
+        });
+        hubs.truncate(config.top_n);
+        hubs
+    }
+
+    /// Strongest chains of signatures ending in one that was an incident
+    pub fn top_chains(&self, config: &CorrelationGraphConfig) -> Vec<CorrelationChain> {
+        let max_length = config.max_chain_length.clamp(2, MAX_CHAIN_LENGTH);
+        let mut predecessors: HashMap<&String, Vec<(&String, u64, f64)>> = HashMap::new();
+        for ((from, to), (count, total)) in &self.edges {
+            predecessors
+                .entry(to)
+                .or_default()
+                .push((from, *count, total / *count as f64));
+        }
+
+        let mut chains = Vec::new();
+        let mut explored = 0;
+        for (incident, _) in self.nodes.iter().filter(|(_, s)| !s.incidents.is_empty()) {
+            // Depth-first back from the incident over simple paths
+            let mut stack = vec![(vec![incident], u64::MAX, 1.0)];
+            while let Some((path, support, strength)) = stack.pop() {
+                explored += 1;
+                if explored > MAX_EXPLORED_PATHS {
+                    break;
+                }
+                if path.len() > 1 {
+                    chains.push(CorrelationChain {
+                        signatures: path.iter().rev().map(|s| (*s).clone()).collect(),
+                        support,
+                        strength,
+                    });
+                }
+                if path.len() == max_length {
+                    continue;
+                }
+                let head = path[path.len() - 1];
+                for (from, count, mean) in predecessors.get(head).into_iter().flatten() {
+                    if path.contains(from) {
+                        continue;
+                    }
+                    let mut next = path.clone();
+                    next.push(*from);
+                    stack.push((next, support.min(*count), strength * mean));
+                }
+            }
+        }
+
+        chains.sort_by(|a, b| {
+            b.score()
+                .total_cmp(&a.score())
+                .then_with(|| b.signatures.len().cmp(&a.signatures.len()))
+                .then_with(|| a.signatures.cmp(&b.signatures))
+        });
+        chains.truncate(config.top_n);
+        chains
+    }
+
+    /// Full report for `[start, end)`
+    pub fn report(
+        &self,
+        config: &CorrelationGraphConfig,
+        window: (DateTime<Utc>, DateTime<Utc>),
+    ) -> CorrelationGraphReport {
+        CorrelationGraphReport {
+            window_start: window.0,
+            window_end: window.1,
+            correlations: self.correlations,
+            signatures: self.nodes.len(),
+            components: self.connected_components(),
+            hubs: self.hubs(config),
+            chains: self.top_chains(config),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    struct Event {
+        id: Uuid,
+        signature: &'static str,
+        severity: &'static str,
+        at: DateTime<Utc>,
+    }
+
+    fn event(signature: &'static str, severity: &'static str, minute: i64) -> Event {
+        Event {
+            id: Uuid::new_v4(),
+            signature,
+            severity,
+            at: DateTime::from_timestamp(0, 0).unwrap() + Duration::minutes(minute),
+        }
+    }
+
+    // Stored in either direction; the graph orients by time
+    fn row(a: &Event, b: &Event, strength: f64) -> CorrelationEdgeRow {
+        CorrelationEdgeRow {
+            correlation_type: "temporal".to_string(),
+            strength,
+            source_event_id: b.id,
+            source_timestamp: b.at,
+            source_signature: b.signature.to_string(),
+            source_severity: b.severity.to_string(),
+            target_event_id: a.id,
+            target_timestamp: a.at,
+            target_signature: a.signature.to_string(),
+            target_severity: a.severity.to_string(),
+        }
+    }
+
+    fn graph() -> CorrelationGraph {
+        let mut rows = Vec::new();
+        // A config push twice precedes latency spikes that become outages
+        for i in 0..2 {
+            let base = i * 60;
+            let push = event("llm-config-manager/config.pushed", "info", base);
+            let spike = event("llm-observatory/telemetry", "warning", base + 2);
+            let outage = event("llm-analytics-hub/provider.outage", "critical", base + 5);
+            rows.push(row(&push, &spike, 0.9));
+            rows.push(row(&spike, &outage, 0.8));
+            rows.push(row(&push, &outage, 0.6));
+        }
+        // Unrelated noise in its own component
+        let cost = event("llm-costops/cost", "info", 10);
+        let governance = event("llm-governance-dashboard/governance", "info", 11);
+        rows.push(row(&cost, &governance, 0.75));
+        CorrelationGraph::from_rows(&rows, &Severity::Error)
+    }
+
+    #[test]
+    fn test_components_and_hub_ranking() {
+        let graph = graph();
+        let components = graph.connected_components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].len(), 3);
+
+        let scores = graph.importance(0.85, 50);
+        let total: f64 = scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let hubs = graph.hubs(&CorrelationGraphConfig::default());
+        assert_eq!(hubs.len(), 2);
+        assert_eq!(hubs[0].signature, "llm-config-manager/config.pushed");
+        assert_eq!(hubs[0].occurrences, 2);
+        assert_eq!(hubs[0].incidents_preceded, 2);
+        assert_eq!(hubs[1].signature, "llm-observatory/telemetry");
+        assert!(scores["llm-costops/cost"] < 1e-9);
+    }
+
+    #[test]
+    fn test_chains_end_in_incidents() {
+        let graph = graph();
+        let chains = graph.top_chains(&CorrelationGraphConfig::default());
+        assert_eq!(chains.len(), 3);
+        // Every link in the two-step chain was seen twice
+        let full = chains.iter().find(|c| c.signatures.len() == 3).unwrap();
+        assert_eq!(
+            full.signatures,
+            vec![
+                "llm-config-manager/config.pushed",
+                "llm-observatory/telemetry",
+                "llm-analytics-hub/provider.outage"
+            ]
+        );
+        assert_eq!(full.support, 2);
+        assert!((full.strength - 0.72).abs() < 1e-9);
+        assert!(chains
+            .iter()
+            .all(|c| c.signatures.last().unwrap() == "llm-analytics-hub/provider.outage"));
+    }
+}
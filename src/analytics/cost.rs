@@ -0,0 +1,716 @@
+//! Cost Anomaly Detection
+//!
+//! Fuses token usage traces from LLM-Observatory with pricing baselines from
+//! LLM-CostOps to detect per-model cost regressions and budget threshold crossings.
+
+use crate::adapters::costops::{BudgetStatus, CostOpsAdapter, TokenAccountingBaseline};
+use crate::adapters::observatory::{ObservatoryAdapter, TraceQuery, UsageTrace};
+use crate::models::correlation::{AnomalyEvent, AnomalyType};
+use crate::schemas::events::{
+    AnalyticsEvent, BudgetAlertEvent, BudgetAlertType, CommonEventFields, CostPayload,
+    CustomPayload, EventPayload, EventType, Severity, SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+/// Custom payload type for cost regression anomalies
+pub const COST_ANOMALY_EVENT_TYPE: &str = "cost.anomaly";
+
+/// Trace attributes that may carry the model identifier, in priority order
+const MODEL_ATTRIBUTES: [&str; 3] = ["model_id", "gen_ai.request.model", "model"];
+
+/// Unit cost being compared against the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostMetric {
+    CostPerToken,
+    CostPerRequest,
+}
+
+impl CostMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostMetric::CostPerToken => "cost_per_token",
+            CostMetric::CostPerRequest => "cost_per_request",
+        }
+    }
+}
+
+/// Cost anomaly detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAnalysisConfig {
+    /// Length of the window being evaluated
+    pub window_secs: i64,
+    /// Length of the reference period preceding the window
+    pub baseline_days: i64,
+    /// Increase over baseline (percent) that counts as a regression
+    pub regression_threshold_pct: f64,
+    /// Increase over baseline (percent) that makes a regression critical
+    pub critical_threshold_pct: f64,
+    /// Minimum requests in both periods before a model is compared
+    pub min_requests: u64,
+    /// Budget utilization (percent) that raises a warning alert
+    pub budget_warning_pct: f64,
+    /// Budget utilization (percent) that raises a critical alert
+    pub budget_critical_pct: f64,
+    /// Environment stamped on emitted events
+    pub environment: String,
+}
+
+impl Default for CostAnalysisConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            baseline_days: 7,
+            regression_threshold_pct: 25.0,
+            critical_threshold_pct: 100.0,
+            min_requests: 50,
+            budget_warning_pct: 80.0,
+            budget_critical_pct: 95.0,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl CostAnalysisConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            window_secs: std::env::var("COST_ANOMALY_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.window_secs),
+            regression_threshold_pct: std::env::var("COST_ANOMALY_THRESHOLD_PCT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.regression_threshold_pct),
+            min_requests: std::env::var("COST_ANOMALY_MIN_REQUESTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_requests),
+            ..defaults
+        }
+    }
+}
+
+// ========== Cost Profiles ==========
+
+/// Usage and unit cost of one model over a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCostProfile {
+    pub model_id: String,
+    pub requests: u64,
+    pub total_tokens: u64,
+    pub cost_per_1k_tokens: f64,
+}
+
+impl ModelCostProfile {
+    pub fn tokens_per_request(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.total_tokens as f64 / self.requests as f64
+        }
+    }
+
+    pub fn cost_per_token(&self) -> f64 {
+        self.cost_per_1k_tokens / 1000.0
+    }
+
+    pub fn cost_per_request(&self) -> f64 {
+        self.tokens_per_request() * self.cost_per_token()
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.total_tokens as f64 * self.cost_per_token()
+    }
+
+    pub fn metric(&self, metric: CostMetric) -> f64 {
+        match metric {
+            CostMetric::CostPerToken => self.cost_per_token(),
+            CostMetric::CostPerRequest => self.cost_per_request(),
+        }
+    }
+
+    /// Join Observatory traces with a CostOps baseline, grouped by model
+    ///
+    /// Traces without token usage or a model attribute are skipped. Models without
+    /// their own price in the baseline fall back to the average price.
+    pub fn fuse(
+        traces: &[UsageTrace],
+        baseline: &TokenAccountingBaseline,
+    ) -> HashMap<String, ModelCostProfile> {
+        let mut profiles: HashMap<String, ModelCostProfile> = HashMap::new();
+
+        for trace in traces {
+            let (Some(usage), Some(model_id)) = (&trace.token_usage, trace_model(trace)) else {
+                continue;
+            };
+
+            let prices = &baseline.cost_per_token;
+            let profile = profiles
+                .entry(model_id.to_string())
+                .or_insert_with(|| ModelCostProfile {
+                    model_id: model_id.to_string(),
+                    requests: 0,
+                    total_tokens: 0,
+                    cost_per_1k_tokens: prices
+                        .by_model
+                        .get(model_id)
+                        .copied()
+                        .unwrap_or(prices.average_cost_per_1k_tokens),
+                });
+            profile.requests += 1;
+            profile.total_tokens += usage.total_tokens;
+        }
+
+        profiles
+    }
+}
+
+pub(crate) fn trace_model(trace: &UsageTrace) -> Option<&str> {
+    MODEL_ATTRIBUTES
+        .iter()
+        .find_map(|key| trace.attributes.get(*key).and_then(|v| v.as_str()))
+}
+
+// ========== Regression Detection ==========
+
+/// A model whose unit cost rose beyond the configured threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostRegression {
+    pub model_id: String,
+    pub metric: CostMetric,
+    pub baseline_value: f64,
+    pub observed_value: f64,
+    pub change_pct: f64,
+    pub severity: Severity,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+}
+
+impl CostRegression {
+    /// Express the regression as an anomaly record
+    pub fn to_anomaly(&self) -> AnomalyEvent {
+        AnomalyEvent {
+            event_id: Uuid::new_v4(),
+            source_module: SourceModule::LlmCostOps,
+            anomaly_type: AnomalyType::Spike,
+            // Saturates once the cost has tripled relative to baseline
+            anomaly_score: (self.change_pct / 200.0).clamp(0.0, 1.0),
+            baseline: self.baseline_value,
+            observed: self.observed_value,
+            deviation: self.observed_value - self.baseline_value,
+            timestamp: self.window_end,
+            metric: format!("{}.{}", self.model_id, self.metric.as_str()),
+        }
+    }
+}
+
+/// Compare current model profiles against the reference period
+pub fn detect_regressions(
+    baseline: &HashMap<String, ModelCostProfile>,
+    current: &HashMap<String, ModelCostProfile>,
+    window: (DateTime<Utc>, DateTime<Utc>),
+    config: &CostAnalysisConfig,
+) -> Vec<CostRegression> {
+    let mut regressions = Vec::new();
+
+    for (model_id, observed) in current {
+        let Some(reference) = baseline.get(model_id) else {
+            continue;
+        };
+        if observed.requests < config.min_requests || reference.requests < config.min_requests {
+            continue;
+        }
+
+        for metric in [CostMetric::CostPerToken, CostMetric::CostPerRequest] {
+            let baseline_value = reference.metric(metric);
+            let observed_value = observed.metric(metric);
+            if baseline_value <= 0.0 {
+                continue;
+            }
+
+            let change_pct = (observed_value - baseline_value) / baseline_value * 100.0;
+            if change_pct < config.regression_threshold_pct {
+                continue;
+            }
+
+            regressions.push(CostRegression {
+                model_id: model_id.clone(),
+                metric,
+                baseline_value,
+                observed_value,
+                change_pct,
+                severity: if change_pct >= config.critical_threshold_pct {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                window_start: window.0,
+                window_end: window.1,
+            });
+        }
+    }
+
+    regressions.sort_by(|a, b| {
+        a.model_id
+            .cmp(&b.model_id)
+            .then_with(|| a.metric.as_str().cmp(b.metric.as_str()))
+    });
+    regressions
+}
+
+/// Build a budget alert when utilization crosses a configured threshold
+pub fn budget_alert(
+    status: &BudgetStatus,
+    config: &CostAnalysisConfig,
+) -> Option<BudgetAlertEvent> {
+    let utilization = status.utilization_percentage;
+    let (alert_type, threshold_percent) = if utilization >= 100.0 {
+        (BudgetAlertType::Exceeded, 100.0)
+    } else if utilization >= config.budget_critical_pct {
+        (BudgetAlertType::Critical, config.budget_critical_pct)
+    } else if utilization >= config.budget_warning_pct {
+        (BudgetAlertType::Warning, config.budget_warning_pct)
+    } else {
+        return None;
+    };
+
+    Some(BudgetAlertEvent {
+        budget_id: status.budget_id.clone(),
+        budget_name: status
+            .team_id
+            .clone()
+            .unwrap_or_else(|| "organization".to_string()),
+        budget_limit_usd: status.period_budget_usd,
+        current_spend_usd: status.spent_usd,
+        threshold_percent,
+        alert_type,
+    })
+}
+
+// ========== Event Builders ==========
+
+fn build_event(
+    environment: &str,
+    event_type: EventType,
+    severity: Severity,
+    tags: HashMap<String, String>,
+    payload: EventPayload,
+) -> AnalyticsEvent {
+    AnalyticsEvent {
+        common: CommonEventFields {
+            event_id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            source_module: SourceModule::LlmAnalyticsHub,
+            event_type,
+            correlation_id: None,
+            parent_event_id: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity,
+            environment: environment.to_string(),
+            tags,
+        },
+        payload,
+    }
+}
+
+/// Build the anomaly event published for a cost regression
+pub fn regression_event(regression: &CostRegression, environment: &str) -> AnalyticsEvent {
+    let mut tags = HashMap::new();
+    tags.insert("model_id".to_string(), regression.model_id.clone());
+    tags.insert("metric".to_string(), regression.metric.as_str().to_string());
+
+    build_event(
+        environment,
+        EventType::Alert,
+        regression.severity.clone(),
+        tags,
+        EventPayload::Custom(CustomPayload {
+            custom_type: COST_ANOMALY_EVENT_TYPE.to_string(),
+            data: serde_json::json!({
+                "anomaly": regression.to_anomaly(),
+                "change_pct": regression.change_pct,
+                "window_start": regression.window_start,
+                "window_end": regression.window_end,
+            }),
+        }),
+    )
+}
+
+/// Build the budget alert event published for a team
+pub fn budget_alert_event(alert: BudgetAlertEvent, environment: &str) -> AnalyticsEvent {
+    let severity = match alert.alert_type {
+        BudgetAlertType::Warning => Severity::Warning,
+        BudgetAlertType::Critical => Severity::Error,
+        BudgetAlertType::Exceeded => Severity::Critical,
+    };
+    let mut tags = HashMap::new();
+    tags.insert("budget_id".to_string(), alert.budget_id.clone());
+
+    build_event(
+        environment,
+        EventType::Cost,
+        severity,
+        tags,
+        EventPayload::Cost(CostPayload::BudgetAlert(alert)),
+    )
+}
+
+// ========== Analyzer ==========
+
+/// Result of one cost analysis pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAnalysisReport {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub profiles: Vec<ModelCostProfile>,
+    pub regressions: Vec<CostRegression>,
+    pub budget_alerts: Vec<BudgetAlertEvent>,
+}
+
+/// Analyzer statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAnalyzerStats {
+    pub runs: u64,
+    pub regressions_detected: u64,
+    pub budget_alerts: u64,
+    pub events_emitted: u64,
+    pub events_dropped: u64,
+}
+
+/// Periodically compares current per-model unit costs with a trailing baseline
+pub struct CostAnalyzer {
+    observatory: Arc<ObservatoryAdapter>,
+    costops: Arc<CostOpsAdapter>,
+    config: CostAnalysisConfig,
+    sink: mpsc::Sender<AnalyticsEvent>,
+    runs: AtomicU64,
+    regressions_detected: AtomicU64,
+    budget_alerts: AtomicU64,
+    events_emitted: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl CostAnalyzer {
+    /// Create an analyzer publishing anomalies into the given pipeline channel
+    pub fn new(
+        observatory: Arc<ObservatoryAdapter>,
+        costops: Arc<CostOpsAdapter>,
+        config: CostAnalysisConfig,
+        sink: mpsc::Sender<AnalyticsEvent>,
+    ) -> Self {
+        Self {
+            observatory,
+            costops,
+            config,
+            sink,
+            runs: AtomicU64::new(0),
+            regressions_detected: AtomicU64::new(0),
+            budget_alerts: AtomicU64::new(0),
+            events_emitted: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    async fn profiles(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<HashMap<String, ModelCostProfile>> {
+        let query = TraceQuery {
+            start_time: Some(start),
+            end_time: Some(end),
+            ..Default::default()
+        };
+        let (traces, baseline) = tokio::try_join!(
+            self.observatory.fetch_traces(query),
+            self.costops.fetch_token_baseline(start, end),
+        )?;
+        Ok(ModelCostProfile::fuse(&traces, &baseline))
+    }
+
+    /// Compare the window ending at `now` against the preceding reference period
+    #[instrument(skip(self))]
+    pub async fn analyze(&self, now: DateTime<Utc>) -> Result<CostAnalysisReport> {
+        let window_start = now - Duration::seconds(self.config.window_secs);
+        let reference_start = window_start - Duration::days(self.config.baseline_days);
+
+        let (reference, current) = tokio::try_join!(
+            self.profiles(reference_start, window_start),
+            self.profiles(window_start, now),
+        )?;
+        let regressions =
+            detect_regressions(&reference, &current, (window_start, now), &self.config);
+
+        let mut profiles: Vec<ModelCostProfile> = current.into_values().collect();
+        profiles.sort_by(|a, b| a.model_id.cmp(&b.model_id));
+
+        debug!(
+            models = profiles.len(),
+            regressions = regressions.len(),
+            "Cost analysis complete"
+        );
+
+        Ok(CostAnalysisReport {
+            window_start,
+            window_end: now,
+            profiles,
+            regressions,
+            budget_alerts: Vec::new(),
+        })
+    }
+
+    /// Run one analysis pass and publish anomalies and budget alerts
+    ///
+    /// Budgets are checked for each team in `team_ids`, or the organization-wide
+    /// budget when the list is empty.
+    pub async fn run_once(&self, team_ids: &[String]) -> Result<CostAnalysisReport> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let mut report = self.analyze(Utc::now()).await?;
+
+        for regression in &report.regressions {
+            info!(
+                model_id = %regression.model_id,
+                metric = regression.metric.as_str(),
+                change_pct = regression.change_pct,
+                "Cost regression detected"
+            );
+            self.emit(regression_event(regression, &self.config.environment));
+        }
+        self.regressions_detected
+            .fetch_add(report.regressions.len() as u64, Ordering::Relaxed);
+
+        let teams: Vec<Option<&str>> = if team_ids.is_empty() {
+            vec![None]
+        } else {
+            team_ids.iter().map(|t| Some(t.as_str())).collect()
+        };
+        for team_id in teams {
+            match self.costops.fetch_budget_status(team_id).await {
+                Ok(status) => {
+                    if let Some(alert) = budget_alert(&status, &self.config) {
+                        self.budget_alerts.fetch_add(1, Ordering::Relaxed);
+                        report.budget_alerts.push(alert.clone());
+                        self.emit(budget_alert_event(alert, &self.config.environment));
+                    }
+                }
+                Err(e) => warn!(?team_id, error = %e, "Failed to fetch budget status"),
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn emit(&self, event: AnalyticsEvent) {
+        match self.sink.try_send(event) {
+            Ok(()) => {
+                self.events_emitted.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!(error = %e, "Dropped cost anomaly event");
+            }
+        }
+    }
+
+    /// Get analyzer statistics
+    pub fn get_stats(&self) -> CostAnalyzerStats {
+        CostAnalyzerStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            regressions_detected: self.regressions_detected.load(Ordering::Relaxed),
+            budget_alerts: self.budget_alerts.load(Ordering::Relaxed),
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::costops::{BaselinePeriod, CostPerToken, EfficiencyMetrics, TokenMetrics};
+    use crate::adapters::observatory::{TokenUsage, TraceStatus};
+
+    fn trace(model: &str, tokens: u64) -> UsageTrace {
+        let mut attributes = HashMap::new();
+        attributes.insert("model_id".to_string(), serde_json::json!(model));
+        UsageTrace {
+            trace_id: Uuid::new_v4().to_string(),
+            span_id: Uuid::new_v4().to_string(),
+            parent_span_id: None,
+            operation_name: "chat.completion".to_string(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            duration_ms: 100,
+            status: TraceStatus::Ok,
+            attributes,
+            token_usage: Some(TokenUsage {
+                prompt_tokens: tokens / 2,
+                completion_tokens: tokens / 2,
+                total_tokens: tokens,
+            }),
+        }
+    }
+
+    fn baseline(prices: &[(&str, f64)]) -> TokenAccountingBaseline {
+        TokenAccountingBaseline {
+            baseline_id: "b".to_string(),
+            created_at: Utc::now(),
+            period: BaselinePeriod {
+                start: Utc::now(),
+                end: Utc::now(),
+            },
+            token_metrics: TokenMetrics {
+                total_tokens: 0,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                cached_tokens: 0,
+                by_model: HashMap::new(),
+            },
+            cost_per_token: CostPerToken {
+                average_cost_per_1k_tokens: 0.5,
+                prompt_cost_per_1k: 0.5,
+                completion_cost_per_1k: 0.5,
+                by_model: prices.iter().map(|(m, p)| (m.to_string(), *p)).collect(),
+            },
+            efficiency_metrics: EfficiencyMetrics {
+                cache_hit_rate: 0.0,
+                tokens_per_request_avg: 0.0,
+                cost_per_request_avg: 0.0,
+            },
+        }
+    }
+
+    fn profile(model: &str, requests: u64, tokens: u64, price: f64) -> ModelCostProfile {
+        ModelCostProfile {
+            model_id: model.to_string(),
+            requests,
+            total_tokens: tokens,
+            cost_per_1k_tokens: price,
+        }
+    }
+
+    fn by_model(profiles: Vec<ModelCostProfile>) -> HashMap<String, ModelCostProfile> {
+        profiles
+            .into_iter()
+            .map(|p| (p.model_id.clone(), p))
+            .collect()
+    }
+
+    #[test]
+    fn test_fuse_groups_by_model_with_price_fallback() {
+        let mut unattributed = trace("gpt-4", 100);
+        unattributed.attributes.clear();
+        let traces = vec![
+            trace("gpt-4", 1000),
+            trace("gpt-4", 3000),
+            trace("claude-3", 500),
+            unattributed,
+        ];
+
+        let profiles = ModelCostProfile::fuse(&traces, &baseline(&[("gpt-4", 30.0)]));
+        assert_eq!(profiles.len(), 2);
+
+        let gpt4 = &profiles["gpt-4"];
+        assert_eq!(gpt4.requests, 2);
+        assert_eq!(gpt4.tokens_per_request(), 2000.0);
+        assert!((gpt4.cost_per_request() - 0.06).abs() < 1e-9);
+        assert_eq!(profiles["claude-3"].cost_per_1k_tokens, 0.5);
+    }
+
+    #[test]
+    fn test_detect_regressions_by_metric() {
+        let config = CostAnalysisConfig::default();
+        let window = (Utc::now() - Duration::hours(1), Utc::now());
+        let reference = by_model(vec![
+            profile("gpt-4", 100, 100_000, 30.0),
+            profile("claude-3", 100, 100_000, 3.0),
+        ]);
+        // gpt-4: same price, 3x tokens per request; claude-3: unchanged
+        let current = by_model(vec![
+            profile("gpt-4", 100, 300_000, 30.0),
+            profile("claude-3", 100, 100_000, 3.0),
+        ]);
+
+        let regressions = detect_regressions(&reference, &current, window, &config);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].model_id, "gpt-4");
+        assert_eq!(regressions[0].metric, CostMetric::CostPerRequest);
+        assert_eq!(regressions[0].severity, Severity::Critical);
+        assert!((regressions[0].change_pct - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_detect_regressions_requires_min_requests() {
+        let config = CostAnalysisConfig::default();
+        let window = (Utc::now() - Duration::hours(1), Utc::now());
+        let reference = by_model(vec![profile("gpt-4", 100, 100_000, 30.0)]);
+        let current = by_model(vec![profile("gpt-4", 10, 10_000, 60.0)]);
+
+        assert!(detect_regressions(&reference, &current, window, &config).is_empty());
+    }
+
+    #[test]
+    fn test_budget_alert_thresholds() {
+        let config = CostAnalysisConfig::default();
+        let mut status = BudgetStatus {
+            budget_id: "budget-1".to_string(),
+            team_id: Some("ml-platform".to_string()),
+            period_budget_usd: 1000.0,
+            spent_usd: 500.0,
+            remaining_usd: 500.0,
+            utilization_percentage: 50.0,
+            projected_overage: None,
+        };
+        assert!(budget_alert(&status, &config).is_none());
+
+        status.utilization_percentage = 85.0;
+        let alert = budget_alert(&status, &config).unwrap();
+        assert_eq!(alert.alert_type, BudgetAlertType::Warning);
+        assert_eq!(alert.budget_name, "ml-platform");
+
+        status.utilization_percentage = 104.0;
+        let event = budget_alert_event(budget_alert(&status, &config).unwrap(), "test");
+        assert_eq!(event.common.severity, Severity::Critical);
+        assert!(matches!(
+            event.payload,
+            EventPayload::Cost(CostPayload::BudgetAlert(BudgetAlertEvent {
+                alert_type: BudgetAlertType::Exceeded,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_regression_event_carries_anomaly() {
+        let regression = CostRegression {
+            model_id: "gpt-4".to_string(),
+            metric: CostMetric::CostPerToken,
+            baseline_value: 0.03,
+            observed_value: 0.045,
+            change_pct: 50.0,
+            severity: Severity::Warning,
+            window_start: Utc::now() - Duration::hours(1),
+            window_end: Utc::now(),
+        };
+
+        let event = regression_event(&regression, "production");
+        assert_eq!(event.common.event_type, EventType::Alert);
+        assert_eq!(event.common.tags["model_id"], "gpt-4");
+        let EventPayload::Custom(custom) = event.payload else {
+            panic!("expected custom payload");
+        };
+        assert_eq!(custom.custom_type, COST_ANOMALY_EVENT_TYPE);
+        assert_eq!(custom.data["anomaly"]["metric"], "gpt-4.cost_per_token");
+        assert_eq!(custom.data["anomaly"]["source_module"], "llm-cost-ops");
+    }
+}
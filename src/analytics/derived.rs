@@ -0,0 +1,344 @@
+//! Derived Metrics
+//!
+//! Derived metrics are arithmetic over other metrics' rollups, such as
+//! `cost_per_1k_tokens = total_cost / (total_tokens / 1000)`. The aggregation
+//! engine evaluates them whenever one of their input windows is written and
+//! stores the result as an aggregated window of its own, so they are queried
+//! like any other metric.
+//!
+//! Expressions support `+ - * /`, parentheses, numbers, and metric references.
+//! A bare reference reads the window's sum; `avg(latency_ms)`, `p95(..)`,
+//! `count(..)` and the other series statistics read that statistic instead.
+//! Inputs are matched within one window and tag set, so they should come from
+//! the same events.
+
+use super::downsampling::SeriesStatistic;
+use crate::models::metrics::StatisticalMeasures;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Derived metric as written in configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedMetricDefinition {
+    pub name: String,
+    pub expression: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Metric {
+        name: String,
+        statistic: SeriesStatistic,
+    },
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Op(Op::Add),
+            '-' => Token::Op(Op::Sub),
+            '*' => Token::Op(Op::Mul),
+            '/' => Token::Op(Op::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = pos + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    end = i + c.len_utf8();
+                }
+                let literal = &expression[pos..end];
+                Token::Number(
+                    literal
+                        .parse()
+                        .with_context(|| format!("Invalid number: {}", literal))?,
+                )
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut end = pos + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    end = i + c.len_utf8();
+                }
+                Token::Ident(expression[pos..end].to_string())
+            }
+            c => bail!("Unexpected character '{}' at position {}", c, pos),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token stream
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect_close(&mut self) -> Result<()> {
+        match self.advance() {
+            Some(Token::Close) => Ok(()),
+            other => bail!("Expected ')', found {:?}", other),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ (Op::Add | Op::Sub))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ (Op::Mul | Op::Div))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | primary
+    fn unary(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Op(Op::Sub)) {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    // primary := number | metric | statistic '(' metric ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<Expr> {
+        match self.advance() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Open) => {
+                let inner = self.expr()?;
+                self.expect_close()?;
+                Ok(inner)
+            }
+            Some(Token::Ident(ident)) if self.peek() == Some(&Token::Open) => {
+                self.pos += 1;
+                let statistic: SeriesStatistic = ident.parse()?;
+                let Some(Token::Ident(name)) = self.advance() else {
+                    bail!("Expected a metric name inside {}(..)", ident);
+                };
+                self.expect_close()?;
+                Ok(Expr::Metric { name, statistic })
+            }
+            Some(Token::Ident(name)) => Ok(Expr::Metric {
+                name,
+                statistic: SeriesStatistic::Sum,
+            }),
+            other => bail!("Expected a number, metric, or '(', found {:?}", other),
+        }
+    }
+}
+
+impl Expr {
+    fn parse(expression: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            pos: 0,
+        };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {:?} after end of expression", token);
+        }
+        Ok(expr)
+    }
+
+    fn collect_inputs<'a>(&'a self, inputs: &mut Vec<&'a str>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Metric { name, .. } => {
+                if !inputs.contains(&name.as_str()) {
+                    inputs.push(name);
+                }
+            }
+            Expr::Neg(inner) => inner.collect_inputs(inputs),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_inputs(inputs);
+                rhs.collect_inputs(inputs);
+            }
+        }
+    }
+
+    fn evaluate(&self, inputs: &HashMap<String, StatisticalMeasures>) -> Option<f64> {
+        Some(match self {
+            Expr::Number(value) => *value,
+            Expr::Metric { name, statistic } => statistic.measure(inputs.get(name)?),
+            Expr::Neg(inner) => -inner.evaluate(inputs)?,
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(inputs)?, rhs.evaluate(inputs)?);
+                match op {
+                    Op::Add => lhs + rhs,
+                    Op::Sub => lhs - rhs,
+                    Op::Mul => lhs * rhs,
+                    Op::Div => lhs / rhs,
+                }
+            }
+        })
+    }
+}
+
+/// Compiled derived metric
+#[derive(Debug, Clone)]
+pub struct DerivedMetric {
+    definition: DerivedMetricDefinition,
+    expr: Expr,
+}
+
+impl DerivedMetric {
+    /// Compile a definition, rejecting malformed and self-referencing expressions
+    pub fn compile(definition: DerivedMetricDefinition) -> Result<Self> {
+        let expr = Expr::parse(&definition.expression).with_context(|| {
+            format!("Invalid expression for derived metric {}", definition.name)
+        })?;
+        let metric = Self { definition, expr };
+        if metric.inputs().is_empty() {
+            bail!(
+                "Derived metric {} does not reference any metric",
+                metric.definition.name
+            );
+        }
+        if metric.inputs().contains(&metric.definition.name.as_str()) {
+            bail!(
+                "Derived metric {} references itself",
+                metric.definition.name
+            );
+        }
+        Ok(metric)
+    }
+
+    /// Compile `expression` as the derived metric `name`
+    pub fn new(name: impl Into<String>, expression: impl Into<String>) -> Result<Self> {
+        Self::compile(DerivedMetricDefinition {
+            name: name.into(),
+            expression: expression.into(),
+            description: None,
+        })
+    }
+
+    /// Compile derived metrics from a YAML list of definitions
+    pub fn load_yaml(yaml: &str) -> Result<Vec<Self>> {
+        let definitions: Vec<DerivedMetricDefinition> =
+            serde_yaml::from_str(yaml).context("Failed to parse derived metric definitions")?;
+        definitions.into_iter().map(Self::compile).collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub fn definition(&self) -> &DerivedMetricDefinition {
+        &self.definition
+    }
+
+    /// Metrics the expression reads
+    pub fn inputs(&self) -> Vec<&str> {
+        let mut inputs = Vec::new();
+        self.expr.collect_inputs(&mut inputs);
+        inputs
+    }
+
+    /// Value for one window, or `None` when an input is missing or the
+    /// result is not finite (e.g. division by zero)
+    pub fn evaluate(&self, inputs: &HashMap<String, StatisticalMeasures>) -> Option<f64> {
+        self.expr.evaluate(inputs).filter(|value| value.is_finite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_evaluate_with_precedence_and_statistics() {
+        let metric =
+            DerivedMetric::new("cost_per_1k_tokens", "total_cost / (total_tokens / 1000)").unwrap();
+        assert_eq!(metric.inputs(), vec!["total_cost", "total_tokens"]);
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "total_cost".to_string(),
+            StatisticalMeasures::from_values(&[0.5, 1.5]),
+        );
+        assert_eq!(metric.evaluate(&inputs), None);
+        inputs.insert(
+            "total_tokens".to_string(),
+            StatisticalMeasures::from_values(&[1_000.0, 3_000.0]),
+        );
+        assert_eq!(metric.evaluate(&inputs), Some(0.5));
+
+        let mixed =
+            DerivedMetric::new("mixed", "-avg(total_cost) + 2 * count(total_tokens) - 1").unwrap();
+        assert_eq!(mixed.evaluate(&inputs), Some(2.0));
+
+        inputs.insert(
+            "total_tokens".to_string(),
+            StatisticalMeasures::from_values(&[0.0]),
+        );
+        assert_eq!(metric.evaluate(&inputs), None);
+    }
+
+    #[test]
+    fn test_invalid_definitions_are_rejected() {
+        assert!(DerivedMetric::new("a", "total_cost /").is_err());
+        assert!(DerivedMetric::new("a", "(total_cost").is_err());
+        assert!(DerivedMetric::new("a", "median(total_cost)").is_err());
+        assert!(DerivedMetric::new("a", "1 + 2").is_err());
+        assert!(DerivedMetric::new("a", "a * 2").is_err());
+
+        let yaml = r#"
+- name: cost_per_1k_tokens
+  expression: total_cost / (total_tokens / 1000)
+  description: Spend per thousand tokens
+- name: error_share
+  expression: errors / requests
+"#;
+        let metrics = DerivedMetric::load_yaml(yaml).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[1].inputs(), vec!["errors", "requests"]);
+    }
+}
@@ -0,0 +1,422 @@
+//! Series Downsampling
+//!
+//! Dashboards ask for a time range and a point budget rather than a rollup
+//! window. The finest stored window that yields at most a few times the budget
+//! is chosen, rows sharing a window start (one per tag set) are merged into a
+//! single series, and Largest-Triangle-Three-Buckets reduces that series to the
+//! budget while keeping the peaks and dips a plain average would flatten.
+
+use crate::database::{AggregatedMetricRow, StorageBackend};
+use crate::models::histogram::Histogram;
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Point budget used when a request does not give one
+pub const DEFAULT_POINT_BUDGET: usize = 500;
+
+/// Largest point budget a request may ask for
+pub const MAX_POINT_BUDGET: usize = 10_000;
+
+/// Rows fetched per budgeted point, so LTTB has detail to choose from
+const OVERSAMPLE_FACTOR: u64 = 4;
+
+/// Statistic of an aggregated window to plot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesStatistic {
+    #[default]
+    Avg,
+    Min,
+    Max,
+    P50,
+    P95,
+    P99,
+    Sum,
+    Count,
+}
+
+impl SeriesStatistic {
+    fn value(&self, row: &AggregatedMetricRow) -> f64 {
+        match self {
+            SeriesStatistic::Avg => row.avg,
+            SeriesStatistic::Min => row.min,
+            SeriesStatistic::Max => row.max,
+            SeriesStatistic::P50 => row.p50,
+            SeriesStatistic::P95 => row.p95,
+            SeriesStatistic::P99 => row.p99,
+            SeriesStatistic::Sum => row.sum,
+            SeriesStatistic::Count => row.count as f64,
+        }
+    }
+
+    /// Quantile a percentile statistic reads
+    fn quantile(&self) -> Option<f64> {
+        match self {
+            SeriesStatistic::P50 => Some(0.5),
+            SeriesStatistic::P95 => Some(0.95),
+            SeriesStatistic::P99 => Some(0.99),
+            _ => None,
+        }
+    }
+
+    /// This statistic over several rows of the same window
+    fn merge(&self, rows: &[&AggregatedMetricRow]) -> f64 {
+        let values = rows.iter().map(|row| self.value(row));
+        match self {
+            SeriesStatistic::Sum | SeriesStatistic::Count => values.sum(),
+            SeriesStatistic::Min => values.fold(f64::INFINITY, f64::min),
+            SeriesStatistic::Max => values.fold(f64::NEG_INFINITY, f64::max),
+            _ => {
+                if let Some(q) = self.quantile() {
+                    let merged = rows
+                        .iter()
+                        .map(|row| row.histogram.as_ref().map(|histogram| &histogram.0))
+                        .collect::<Option<Vec<_>>>()
+                        .and_then(Histogram::merged)
+                        .and_then(|histogram| histogram.quantile(q));
+                    if let Some(value) = merged {
+                        return value;
+                    }
+                }
+                let weights: Vec<f64> = rows.iter().map(|row| row.count.max(1) as f64).collect();
+                let weighted: f64 = values.zip(&weights).map(|(v, w)| v * w).sum();
+                weighted / weights.iter().sum::<f64>()
+            }
+        }
+    }
+
+    /// This statistic of a window's measures
+    pub fn measure(&self, measures: &StatisticalMeasures) -> f64 {
+        match self {
+            SeriesStatistic::Avg => measures.avg,
+            SeriesStatistic::Min => measures.min,
+            SeriesStatistic::Max => measures.max,
+            SeriesStatistic::P50 => measures.p50,
+            SeriesStatistic::P95 => measures.p95,
+            SeriesStatistic::P99 => measures.p99,
+            SeriesStatistic::Sum => measures.sum,
+            SeriesStatistic::Count => measures.count as f64,
+        }
+    }
+}
+
+impl std::str::FromStr for SeriesStatistic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown series statistic: {}", s))
+    }
+}
+
+/// One plotted point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SeriesPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Downsampled series request
+#[derive(Debug, Clone)]
+pub struct DownsampleRequest {
+    pub metric_name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Maximum points to return
+    pub max_points: usize,
+    pub statistic: SeriesStatistic,
+    /// Only windows whose tags contain these
+    pub tags: Option<serde_json::Value>,
+}
+
+impl DownsampleRequest {
+    /// Rollup window to read for this request's range and budget
+    pub fn resolution(&self) -> TimeWindow {
+        select_resolution(self.start, self.end, self.max_points)
+    }
+}
+
+/// Downsampled series ready for charting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownsampledSeries {
+    pub metric_name: String,
+    pub statistic: SeriesStatistic,
+    /// Rollup window the points were read from
+    pub window: TimeWindow,
+    /// Points in the series before downsampling
+    pub source_points: usize,
+    pub points: Vec<SeriesPoint>,
+}
+
+/// Finest rollup window that covers `[start, end)` in at most
+/// `max_points` times the oversample factor, else the coarsest window
+pub fn select_resolution(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    max_points: usize,
+) -> TimeWindow {
+    let range_secs = (end - start).num_seconds().max(1) as u64;
+    let limit = max_points.max(1) as u64 * OVERSAMPLE_FACTOR;
+    TimeWindow::ALL
+        .iter()
+        .copied()
+        .find(|window| {
+            let window_secs = window.to_seconds();
+            (range_secs + window_secs - 1) / window_secs <= limit
+        })
+        .unwrap_or(TimeWindow::ALL[TimeWindow::ALL.len() - 1])
+}
+
+/// Merge rows sharing a window start into one point each, oldest first.
+///
+/// Sums and counts add up, minimums and maximums take the extreme, and
+/// averages are weighted by count. Percentiles come from the merged
+/// histograms when every row has one with the same buckets, and are
+/// otherwise approximated by their count-weighted mean.
+pub fn merge_rows(rows: &[AggregatedMetricRow], statistic: SeriesStatistic) -> Vec<SeriesPoint> {
+    let mut groups: BTreeMap<DateTime<Utc>, Vec<&AggregatedMetricRow>> = BTreeMap::new();
+    for row in rows {
+        groups.entry(row.window_start).or_default().push(row);
+    }
+    groups
+        .into_iter()
+        .map(|(timestamp, rows)| SeriesPoint {
+            timestamp,
+            value: statistic.merge(&rows),
+        })
+        .collect()
+}
+
+/// Largest-Triangle-Three-Buckets downsampling to at most `threshold` points.
+///
+/// The first and last points are always kept. Series already within the
+/// threshold, and thresholds below three, return the input unchanged.
+pub fn lttb(points: &[SeriesPoint], threshold: usize) -> Vec<SeriesPoint> {
+    if threshold >= points.len() || threshold < 3 {
+        return points.to_vec();
+    }
+
+    // Seconds from the first point keep the x axis within f64 precision
+    let origin = points[0].timestamp;
+    let x = |i: usize| (points[i].timestamp - origin).num_milliseconds() as f64 / 1000.0;
+    let y = |i: usize| points[i].value;
+
+    let bucket_size = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(points[0]);
+    let mut selected = 0;
+
+    for bucket in 0..threshold - 2 {
+        // Average of the next bucket is the third vertex
+        let next_start = ((bucket + 1) as f64 * bucket_size) as usize + 1;
+        let next_end = (((bucket + 2) as f64 * bucket_size) as usize + 1).min(points.len());
+        let next_len = (next_end - next_start) as f64;
+        let (avg_x, avg_y) =
+            (next_start..next_end).fold((0.0, 0.0), |(sx, sy), i| (sx + x(i), sy + y(i)));
+        let (avg_x, avg_y) = (avg_x / next_len, avg_y / next_len);
+
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = ((bucket + 1) as f64 * bucket_size) as usize + 1;
+        let (ax, ay) = (x(selected), y(selected));
+        let mut max_area = -1.0;
+        for i in start..end {
+            let area = ((ax - avg_x) * (y(i) - ay) - (ax - x(i)) * (avg_y - ay)).abs();
+            if area > max_area {
+                max_area = area;
+                selected = i;
+            }
+        }
+        sampled.push(points[selected]);
+    }
+
+    sampled.push(points[points.len() - 1]);
+    sampled
+}
+
+/// Merge and downsample rows read at `window` for `request`
+pub fn downsample_rows(
+    request: &DownsampleRequest,
+    window: TimeWindow,
+    rows: &[AggregatedMetricRow],
+) -> DownsampledSeries {
+    let series = merge_rows(rows, request.statistic);
+    DownsampledSeries {
+        metric_name: request.metric_name.clone(),
+        statistic: request.statistic,
+        window,
+        source_points: series.len(),
+        points: lttb(&series, request.max_points),
+    }
+}
+
+/// Read `request`'s range at an automatically selected resolution and
+/// downsample it to the point budget
+pub async fn get_downsampled_series(
+    backend: &dyn StorageBackend,
+    request: &DownsampleRequest,
+) -> Result<DownsampledSeries> {
+    let window = request.resolution();
+    let rows = match &request.tags {
+        Some(tags) => {
+            backend
+                .query_aggregated_metrics_tagged(
+                    &request.metric_name,
+                    window,
+                    request.start,
+                    request.end,
+                    tags,
+                )
+                .await?
+        }
+        None => {
+            backend
+                .query_aggregated_metrics(&request.metric_name, window, request.start, request.end)
+                .await?
+        }
+    };
+    Ok(downsample_rows(request, window, &rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MemoryBackend;
+    use chrono::Duration;
+
+    fn measures(value: f64) -> StatisticalMeasures {
+        StatisticalMeasures {
+            avg: value,
+            min: value,
+            max: value,
+            p50: value,
+            p95: value,
+            p99: value,
+            stddev: None,
+            count: 1,
+            sum: value,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_lttb_keeps_endpoints_and_spikes() {
+        let origin = Utc::now();
+        let points: Vec<SeriesPoint> = (0..1_000)
+            .map(|i| SeriesPoint {
+                timestamp: origin + Duration::seconds(i),
+                value: if i == 437 { 100.0 } else { (i % 7) as f64 },
+            })
+            .collect();
+
+        let sampled = lttb(&points, 50);
+        assert_eq!(sampled.len(), 50);
+        assert_eq!(sampled[0], points[0]);
+        assert_eq!(sampled[49], points[999]);
+        assert!(sampled.iter().any(|p| p.value == 100.0));
+        assert!(sampled.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        assert_eq!(lttb(&points[..10], 50).len(), 10);
+    }
+
+    #[test]
+    fn test_select_resolution_scales_with_range_and_budget() {
+        let end = Utc::now();
+        assert_eq!(
+            select_resolution(end - Duration::hours(1), end, 500),
+            TimeWindow::OneMinute
+        );
+        // 30 days at 500 points: 1h gives 720 rows, within 2000
+        assert_eq!(
+            select_resolution(end - Duration::days(30), end, 500),
+            TimeWindow::OneHour
+        );
+        assert_eq!(
+            select_resolution(end - Duration::days(30), end, 20),
+            TimeWindow::OneDay
+        );
+        assert_eq!(
+            select_resolution(end - Duration::days(3650), end, 3),
+            TimeWindow::OneMonth
+        );
+    }
+
+    #[test]
+    fn test_percentiles_merge_through_histograms() {
+        let window_start = Utc::now();
+        let bounds = vec![10.0, 20.0, 30.0, 40.0];
+        let row = |values: &[f64], p50: f64| AggregatedMetricRow {
+            metric_name: "latency_ms".to_string(),
+            time_window: "1m".to_string(),
+            window_start,
+            tags: serde_json::json!({}),
+            avg: 0.0,
+            min: 0.0,
+            max: 0.0,
+            p50,
+            p95: 0.0,
+            p99: 0.0,
+            stddev: None,
+            count: values.len() as i64,
+            sum: 0.0,
+            histogram: Some(sqlx::types::Json(Histogram::from_values(
+                bounds.clone(),
+                values,
+            ))),
+        };
+        let mut rows = vec![row(&[5.0, 15.0], 10.0), row(&[35.0, 35.0], 35.0)];
+
+        let merged = merge_rows(&rows, SeriesStatistic::P50);
+        assert_eq!(merged[0].value, 20.0);
+
+        // Without histograms on every row, fall back to the weighted mean
+        rows[1].histogram = None;
+        let merged = merge_rows(&rows, SeriesStatistic::P50);
+        assert_eq!(merged[0].value, 22.5);
+    }
+
+    #[tokio::test]
+    async fn test_get_downsampled_series_merges_tag_sets() {
+        let backend = MemoryBackend::new();
+        let end = DateTime::from_timestamp(1_700_006_400, 0).unwrap();
+        let start = end - Duration::hours(6);
+        for minute in 0..360 {
+            let window_start = start + Duration::minutes(minute);
+            for model in ["gpt-4", "claude-3"] {
+                backend
+                    .store_aggregated_metric(
+                        "latency_ms",
+                        TimeWindow::OneMinute,
+                        window_start,
+                        &serde_json::json!({ "model": model }),
+                        &measures(minute as f64),
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let request = DownsampleRequest {
+            metric_name: "latency_ms".to_string(),
+            start,
+            end,
+            max_points: 100,
+            statistic: SeriesStatistic::Sum,
+            tags: None,
+        };
+        let series = get_downsampled_series(&backend, &request).await.unwrap();
+        assert_eq!(series.window, TimeWindow::OneMinute);
+        assert_eq!(series.source_points, 360);
+        assert_eq!(series.points.len(), 100);
+        assert_eq!(series.points[99].value, 2.0 * 359.0);
+
+        let tagged = DownsampleRequest {
+            tags: Some(serde_json::json!({ "model": "gpt-4" })),
+            ..request
+        };
+        let series = get_downsampled_series(&backend, &tagged).await.unwrap();
+        assert_eq!(series.points[99].value, 359.0);
+    }
+}
@@ -0,0 +1,337 @@
+//! Anomaly Feedback
+//!
+//! Operator verdicts on detected anomalies (true positive, false positive) and
+//! on anomalies the detector missed (false negative). Per-metric tallies of
+//! those verdicts drive precision/recall estimates and widen the z-score
+//! threshold of metrics whose false-positive rate exceeds the target.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::anomaly::AnomalyDetector;
+use super::AnalyticsEngine;
+use crate::database::Database;
+
+/// Operator verdict on an anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackVerdict {
+    /// A flagged anomaly that was real
+    TruePositive,
+    /// A flagged anomaly that was noise
+    FalsePositive,
+    /// A real anomaly the detector did not flag
+    FalseNegative,
+}
+
+impl FeedbackVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackVerdict::TruePositive => "true_positive",
+            FeedbackVerdict::FalsePositive => "false_positive",
+            FeedbackVerdict::FalseNegative => "false_negative",
+        }
+    }
+}
+
+impl FromStr for FeedbackVerdict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown feedback verdict '{}'", s))
+    }
+}
+
+/// One operator verdict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyFeedback {
+    pub feedback_id: Uuid,
+    /// The anomaly judged; `None` for false negatives
+    pub anomaly_id: Option<Uuid>,
+    pub metric_name: String,
+    pub verdict: FeedbackVerdict,
+    pub operator: Option<String>,
+    pub note: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl AnomalyFeedback {
+    pub fn new(metric_name: &str, verdict: FeedbackVerdict) -> Self {
+        Self {
+            feedback_id: Uuid::new_v4(),
+            anomaly_id: None,
+            metric_name: metric_name.to_string(),
+            verdict,
+            operator: None,
+            note: None,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    pub fn with_anomaly(mut self, anomaly_id: Uuid) -> Self {
+        self.anomaly_id = Some(anomaly_id);
+        self
+    }
+
+    pub fn with_operator(mut self, operator: impl Into<String>) -> Self {
+        self.operator = Some(operator.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// Verdict counts for one metric (or across all metrics)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedbackTally {
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub false_negatives: u64,
+}
+
+impl FeedbackTally {
+    pub fn record(&mut self, verdict: FeedbackVerdict) {
+        match verdict {
+            FeedbackVerdict::TruePositive => self.true_positives += 1,
+            FeedbackVerdict::FalsePositive => self.false_positives += 1,
+            FeedbackVerdict::FalseNegative => self.false_negatives += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &FeedbackTally) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.true_positives + self.false_positives + self.false_negatives
+    }
+
+    /// Verdicts on anomalies the detector flagged
+    pub fn flagged(&self) -> u64 {
+        self.true_positives + self.false_positives
+    }
+
+    /// Share of flagged anomalies that were real
+    pub fn precision(&self) -> Option<f64> {
+        let flagged = self.flagged();
+        (flagged > 0).then(|| self.true_positives as f64 / flagged as f64)
+    }
+
+    /// Share of real anomalies that were flagged
+    pub fn recall(&self) -> Option<f64> {
+        let real = self.true_positives + self.false_negatives;
+        (real > 0).then(|| self.true_positives as f64 / real as f64)
+    }
+
+    pub fn false_positive_rate(&self) -> Option<f64> {
+        self.precision().map(|p| 1.0 - p)
+    }
+}
+
+/// How feedback adjusts detector sensitivity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackConfig {
+    /// Verdicts on flagged anomalies needed before a metric's threshold moves
+    pub min_feedback: u64,
+    /// False-positive rate tolerated without widening the threshold
+    pub target_false_positive_rate: f64,
+    /// Largest increase to a metric's z-score threshold
+    pub max_threshold_increase: f64,
+    /// Seconds between reloads of stored feedback
+    pub sync_interval_secs: u64,
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        Self {
+            min_feedback: 5,
+            target_false_positive_rate: 0.2,
+            max_threshold_increase: 2.0,
+            sync_interval_secs: 300,
+        }
+    }
+}
+
+impl FeedbackConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            min_feedback: std::env::var("ANOMALY_FEEDBACK_MIN_SAMPLES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_feedback),
+            target_false_positive_rate: std::env::var("ANOMALY_FEEDBACK_TARGET_FP_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.target_false_positive_rate),
+            max_threshold_increase: std::env::var("ANOMALY_FEEDBACK_MAX_THRESHOLD_INCREASE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_threshold_increase),
+            sync_interval_secs: std::env::var("ANOMALY_FEEDBACK_SYNC_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.sync_interval_secs),
+        }
+    }
+
+    /// Amount added to a metric's z-score threshold, scaling linearly from zero
+    /// at the target false-positive rate to the maximum at a rate of 1.0
+    pub fn threshold_increase(&self, tally: &FeedbackTally) -> f64 {
+        if tally.flagged() < self.min_feedback {
+            return 0.0;
+        }
+        let Some(fp_rate) = tally.false_positive_rate() else {
+            return 0.0;
+        };
+        let target = self.target_false_positive_rate.clamp(0.0, 0.99);
+        let excess = ((fp_rate - target) / (1.0 - target)).clamp(0.0, 1.0);
+        excess * self.max_threshold_increase
+    }
+}
+
+/// Feedback and its effect on one metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackSummary {
+    pub metric_name: String,
+    #[serde(flatten)]
+    pub tally: FeedbackTally,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    /// Amount the metric's z-score threshold is widened by
+    pub threshold_increase: f64,
+}
+
+impl FeedbackSummary {
+    pub fn new(metric_name: &str, tally: FeedbackTally, config: &FeedbackConfig) -> Self {
+        Self {
+            metric_name: metric_name.to_string(),
+            tally,
+            precision: tally.precision(),
+            recall: tally.recall(),
+            threshold_increase: config.threshold_increase(&tally),
+        }
+    }
+}
+
+/// Reloads stored feedback into a detector so verdicts recorded by any
+/// instance of the API adjust every detector
+pub struct FeedbackSync {
+    database: Arc<Database>,
+    config: FeedbackConfig,
+    syncs: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl FeedbackSync {
+    pub fn new(database: Arc<Database>, config: FeedbackConfig) -> Self {
+        Self {
+            database,
+            config,
+            syncs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the detector's tallies with the stored ones, returning the
+    /// number of metrics with feedback
+    pub async fn sync(&self, detector: &AnomalyDetector) -> Result<usize> {
+        let tallies = self.database.query_anomaly_feedback_tallies().await?;
+        let metrics = detector.restore_feedback(
+            tallies
+                .into_iter()
+                .map(|row| (row.metric_name.clone(), row.tally()))
+                .collect(),
+        );
+        self.syncs.fetch_add(1, Ordering::Relaxed);
+        debug!(metrics, "Synced anomaly feedback");
+        Ok(metrics)
+    }
+
+    /// Sync the engine's detector on the configured interval until the task is
+    /// aborted
+    pub fn spawn(self: Arc<Self>, engine: Arc<AnalyticsEngine>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.sync_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.sync(engine.anomaly()).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Anomaly feedback sync failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> FeedbackSyncStats {
+        FeedbackSyncStats {
+            syncs: self.syncs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Feedback sync statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackSyncStats {
+    pub syncs: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tally(tp: u64, fp: u64, fn_: u64) -> FeedbackTally {
+        FeedbackTally {
+            true_positives: tp,
+            false_positives: fp,
+            false_negatives: fn_,
+        }
+    }
+
+    #[test]
+    fn test_precision_and_recall() {
+        let t = tally(6, 2, 3);
+        assert_eq!(t.precision(), Some(0.75));
+        assert_eq!(t.recall(), Some(6.0 / 9.0));
+        assert_eq!(FeedbackTally::default().precision(), None);
+        assert_eq!(tally(0, 0, 2).recall(), Some(0.0));
+
+        assert_eq!(
+            "False_Positive".parse::<FeedbackVerdict>().unwrap(),
+            FeedbackVerdict::FalsePositive
+        );
+        assert!("maybe".parse::<FeedbackVerdict>().is_err());
+    }
+
+    #[test]
+    fn test_threshold_increase() {
+        let config = FeedbackConfig::default();
+
+        // Too little feedback to act on
+        assert_eq!(config.threshold_increase(&tally(0, 4, 0)), 0.0);
+        // At or under the target rate
+        assert_eq!(config.threshold_increase(&tally(8, 2, 0)), 0.0);
+        // Every flag was noise
+        assert_eq!(config.threshold_increase(&tally(0, 10, 0)), 2.0);
+        // 60% false positives is halfway from the 20% target to 100%
+        assert!((config.threshold_increase(&tally(4, 6, 0)) - 1.0).abs() < 1e-9);
+        // Missed anomalies never widen the threshold
+        assert_eq!(config.threshold_increase(&tally(10, 0, 50)), 0.0);
+    }
+}
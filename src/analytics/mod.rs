@@ -3,20 +3,80 @@
 //! Core analytics capabilities including aggregation, correlation, and prediction.
 
 pub mod aggregation;
+pub mod aggregation_engine;
+pub mod changepoint;
+pub mod clustering;
 pub mod correlation;
+pub mod correlation_graph;
+pub mod derived;
+pub mod downsampling;
 pub mod anomaly;
+pub mod attribution;
+pub mod backtest;
+pub mod budget;
+pub mod cost;
+pub mod feedback;
+pub mod multivariate;
+pub mod pipelines;
 pub mod prediction;
+pub mod providers;
+pub mod scaling;
+pub mod scorecard;
+pub mod sessions;
+pub mod shadow;
+pub mod snapshot;
+pub mod threats;
+pub mod topology;
+pub mod token_efficiency;
+pub mod unit_economics;
+pub mod windowing;
 
 pub use aggregation::AggregationEngine;
+pub use changepoint::{ChangepointConfig, ChangepointDetector, ChangepointEvent};
+pub use clustering::{ClusterAnalyzer, ClusteringAlgorithm, ClusteringReport};
 pub use correlation::CorrelationEngine;
+pub use correlation_graph::{CorrelationGraph, CorrelationGraphConfig, CorrelationGraphReport};
+pub use derived::{DerivedMetric, DerivedMetricDefinition};
+pub use downsampling::{DownsampleRequest, DownsampledSeries, SeriesPoint, SeriesStatistic};
 pub use anomaly::AnomalyDetector;
+pub use attribution::{
+    CostAttributionConfig, CostAttributionJob, CostAttributionStats, RequestCost, RequestCostOrder,
+    RequestCostQuery,
+};
+pub use backtest::{BacktestConfig, BacktestReport, BacktestStats, Backtester};
+pub use budget::{BudgetForecastConfig, BudgetForecaster};
+pub use cost::{CostAnalysisConfig, CostAnalyzer};
+pub use feedback::{
+    AnomalyFeedback, FeedbackConfig, FeedbackSummary, FeedbackSync, FeedbackTally, FeedbackVerdict,
+};
+pub use multivariate::{MultivariateConfig, MultivariateDetector};
+pub use pipelines::{PipelineAnalyzer, PipelineBreakdown};
 pub use prediction::PredictionEngine;
-
+pub use providers::{ProviderHealthConfig, ProviderHealthMonitor, ProviderHealthReport};
+pub use scaling::{ScalingConfig, ScalingRecommendation, ScalingRecommender};
+pub use scorecard::{ModelScorecard, ScorecardGenerator};
+pub use sessions::{SessionAnalyticsConfig, SessionAnalyticsJob, SessionEngagement};
+pub use shadow::{
+    AnomalyModel, ForecastMethod, ForecastModel, PredictionModel, ShadowDetector,
+    ShadowForecaster, ShadowReport,
+};
+pub use snapshot::{DetectorSnapshot, DetectorSnapshotter, SnapshotConfig, SnapshotStore};
+pub use threats::{ThreatTrendAnalyzer, ThreatTrendReport};
+pub use topology::{EntityRef, Topology, TopologyStore};
+pub use token_efficiency::{TokenEfficiencyAnalyzer, TokenEfficiencyConfig, TokenEfficiencyReport};
+pub use unit_economics::{
+    UnitCost, UnitDefinition, UnitEconomicsConfig, UnitEconomicsJob, UnitEconomicsStats,
+};
+pub use windowing::{EmissionMode, LateArrivalCounts, LateArrivalStats, WatermarkConfig};
+
+use crate::adapters::config_manager::AnalyticsParameters;
 use anyhow::Result;
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// Analytics configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AnalyticsConfig {
     /// Enable real-time aggregation
     pub enable_realtime_aggregation: bool,
@@ -50,10 +110,72 @@ impl Default for AnalyticsConfig {
     }
 }
 
+impl AnalyticsConfig {
+    /// Apply Config-Manager analytics parameters on top of this configuration.
+    ///
+    /// The Config-Manager anomaly sensitivity is a z-score threshold; it is
+    /// mapped back onto the 0.0 - 1.0 scale used by the anomaly detector.
+    pub fn with_parameters(&self, params: &AnalyticsParameters) -> Self {
+        let mut windows: Vec<u64> = params
+            .aggregation
+            .rollup_windows
+            .iter()
+            .map(|w| w.duration_minutes as u64 * 60)
+            .collect();
+        windows.sort_unstable();
+        windows.dedup();
+
+        Self {
+            enable_anomaly_detection: params.anomaly_detection.enabled,
+            enable_prediction: params.forecasting.enabled,
+            aggregation_windows: if windows.is_empty() {
+                self.aggregation_windows.clone()
+            } else {
+                windows
+            },
+            anomaly_sensitivity: ((3.0 - params.anomaly_detection.sensitivity) / 2.0)
+                .clamp(0.0, 1.0),
+            ..self.clone()
+        }
+    }
+}
+
+/// Analytics configuration shared by the engines and swapped in place on reload
+#[derive(Debug, Clone)]
+pub struct SharedConfig {
+    current: Arc<RwLock<Arc<AnalyticsConfig>>>,
+    generation: Arc<AtomicU64>,
+}
+
+impl SharedConfig {
+    pub fn new(config: Arc<AnalyticsConfig>) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(config)),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Active configuration
+    pub fn load(&self) -> Arc<AnalyticsConfig> {
+        self.current.read().clone()
+    }
+
+    /// Replace the active configuration, returning the previous one
+    pub fn store(&self, config: AnalyticsConfig) -> Arc<AnalyticsConfig> {
+        let previous = std::mem::replace(&mut *self.current.write(), Arc::new(config));
+        self.generation.fetch_add(1, Ordering::Release);
+        previous
+    }
+
+    /// Number of times the configuration has been replaced
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+}
+
 /// Main analytics engine orchestrator
 pub struct AnalyticsEngine {
-    #[allow(dead_code)]
-    config: Arc<AnalyticsConfig>,
+    config: SharedConfig,
     aggregation: AggregationEngine,
     correlation: CorrelationEngine,
     anomaly: AnomalyDetector,
@@ -63,12 +185,12 @@ pub struct AnalyticsEngine {
 impl AnalyticsEngine {
     /// Create a new analytics engine
     pub async fn new(config: AnalyticsConfig) -> Result<Self> {
-        let config = Arc::new(config);
+        let config = SharedConfig::new(Arc::new(config));
 
-        let aggregation = AggregationEngine::new(config.clone()).await?;
+        let aggregation = AggregationEngine::with_shared_config(config.clone());
         let correlation = CorrelationEngine::new();
-        let anomaly = AnomalyDetector::new(config.clone()).await?;
-        let prediction = PredictionEngine::new(config.clone()).await?;
+        let anomaly = AnomalyDetector::with_shared_config(config.clone());
+        let prediction = PredictionEngine::with_shared_config(config.clone());
 
         Ok(Self {
             config,
@@ -79,6 +201,11 @@ impl AnalyticsEngine {
         })
     }
 
+    /// Configuration shared by the engines, for hot reload
+    pub fn config(&self) -> SharedConfig {
+        self.config.clone()
+    }
+
     /// Get aggregation engine
     pub fn aggregation(&self) -> &AggregationEngine {
         &self.aggregation
@@ -0,0 +1,448 @@
+//! Multivariate Anomaly Detection
+//!
+//! Learns the joint behaviour of a configurable vector of metrics per model
+//! (latency, error rate, and cost by default) and flags observations whose
+//! Mahalanobis distance from that baseline is improbable, which catches
+//! correlated shifts that look normal metric-by-metric, such as latency rising
+//! while cost per request falls.
+//!
+//! The baseline is an exponentially weighted mean and covariance that behaves
+//! like a plain running estimate until `window_size` samples have been seen.
+//! Anomalous observations are not learned from, so an incident does not become
+//! the new normal. Under a Gaussian baseline the squared distance follows a
+//! chi-squared distribution, which sets the alerting threshold.
+
+use super::anomaly::AnomalySeverity;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ChiSquared, ContinuousCDF};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
+use uuid::Uuid;
+
+/// Custom payload type for multivariate anomaly events
+pub const MULTIVARIATE_ANOMALY_EVENT_TYPE: &str = "analytics.multivariate_anomaly";
+
+/// Multivariate detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultivariateConfig {
+    /// Metrics forming the vector, in order
+    pub metrics: Vec<String>,
+    /// Observations learned before a model is scored
+    pub warmup_samples: usize,
+    /// Effective number of observations the baseline remembers
+    pub window_size: usize,
+    /// Chi-squared quantile of the squared distance above which an
+    /// observation is anomalous
+    pub confidence: f64,
+    /// Added to the covariance diagonal, relative to each variance, so
+    /// near-constant metrics do not make the covariance singular
+    pub regularization: f64,
+}
+
+impl Default for MultivariateConfig {
+    fn default() -> Self {
+        Self {
+            metrics: vec![
+                "latency_ms".to_string(),
+                "error_rate".to_string(),
+                "cost_usd".to_string(),
+            ],
+            warmup_samples: 50,
+            window_size: 1000,
+            confidence: 0.999,
+            regularization: 1e-6,
+        }
+    }
+}
+
+impl MultivariateConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            metrics: std::env::var("MULTIVARIATE_METRICS")
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .map(|m| m.trim().to_string())
+                        .filter(|m| !m.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|m| !m.is_empty())
+                .unwrap_or(defaults.metrics),
+            warmup_samples: std::env::var("MULTIVARIATE_WARMUP_SAMPLES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.warmup_samples),
+            confidence: std::env::var("MULTIVARIATE_CONFIDENCE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.confidence),
+            ..defaults
+        }
+    }
+
+    /// Squared-distance threshold for the configured vector size
+    pub fn threshold(&self) -> f64 {
+        let dims = self.metrics.len().max(1) as f64;
+        ChiSquared::new(dims)
+            .map(|chi2| chi2.inverse_cdf(self.confidence.clamp(0.5, 0.999_999)))
+            .unwrap_or(f64::INFINITY)
+    }
+}
+
+// ========== Baseline ==========
+
+/// Exponentially weighted mean and covariance of a metric vector
+#[derive(Debug, Clone)]
+pub struct VectorBaseline {
+    mean: Vec<f64>,
+    /// Row-major covariance
+    covariance: Vec<f64>,
+    count: usize,
+}
+
+impl VectorBaseline {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            mean: vec![0.0; dims],
+            covariance: vec![0.0; dims * dims],
+            count: 0,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.mean.len()
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    pub fn variance(&self, i: usize) -> f64 {
+        self.covariance[i * self.dims() + i]
+    }
+
+    /// Learn from an observation
+    pub fn update(&mut self, x: &[f64], window_size: usize) {
+        let n = self.dims();
+        self.count += 1;
+        let alpha = 1.0 / (self.count.min(window_size.max(1)) as f64);
+
+        let delta: Vec<f64> = x.iter().zip(&self.mean).map(|(v, m)| v - m).collect();
+        for (m, d) in self.mean.iter_mut().zip(&delta) {
+            *m += alpha * d;
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let c = &mut self.covariance[i * n + j];
+                *c = (1.0 - alpha) * (*c + alpha * delta[i] * delta[j]);
+            }
+        }
+    }
+
+    /// Squared Mahalanobis distance of `x`, or `None` if the covariance
+    /// cannot be factored
+    pub fn squared_distance(&self, x: &[f64], regularization: f64) -> Option<f64> {
+        let n = self.dims();
+        let mut cov = self.covariance.clone();
+        for i in 0..n {
+            let var = cov[i * n + i];
+            cov[i * n + i] = var + regularization * var.max(1.0);
+        }
+
+        let chol = cholesky(&cov, n)?;
+        let delta: Vec<f64> = x.iter().zip(&self.mean).map(|(v, m)| v - m).collect();
+
+        // Solve L y = delta; the distance is |y|^2
+        let mut y = vec![0.0; n];
+        for i in 0..n {
+            let sum: f64 = (0..i).map(|k| chol[i * n + k] * y[k]).sum();
+            y[i] = (delta[i] - sum) / chol[i * n + i];
+        }
+        Some(y.iter().map(|v| v * v).sum())
+    }
+}
+
+/// Lower-triangular Cholesky factor of a symmetric positive-definite matrix
+fn cholesky(matrix: &[f64], n: usize) -> Option<Vec<f64>> {
+    let mut l = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum();
+            if i == j {
+                let d = matrix[i * n + i] - sum;
+                if d <= 0.0 || !d.is_finite() {
+                    return None;
+                }
+                l[i * n + j] = d.sqrt();
+            } else {
+                l[i * n + j] = (matrix[i * n + j] - sum) / l[j * n + j];
+            }
+        }
+    }
+    Some(l)
+}
+
+// ========== Detection ==========
+
+/// How much one metric deviates from its baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricContribution {
+    pub metric: String,
+    pub value: f64,
+    pub expected: f64,
+    /// Deviation in the metric's own standard deviations
+    pub z_score: f64,
+}
+
+/// An observation whose joint behaviour departs from the learned baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultivariateAnomaly {
+    pub model_id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Mahalanobis distance from the baseline
+    pub distance: f64,
+    /// Distance at which observations become anomalous
+    pub threshold: f64,
+    /// Per-metric deviations, largest first
+    pub contributions: Vec<MetricContribution>,
+    pub severity: AnomalySeverity,
+}
+
+impl MultivariateAnomaly {
+    /// Wrap the anomaly as an analytics event for publishing
+    pub fn to_event(&self, environment: &str) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        tags.insert("model_id".to_string(), self.model_id.clone());
+
+        let severity = match self.severity {
+            AnomalySeverity::Low | AnomalySeverity::Medium => Severity::Warning,
+            AnomalySeverity::High => Severity::Error,
+            AnomalySeverity::Critical => Severity::Critical,
+        };
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: self.timestamp,
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity,
+                environment: environment.to_string(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: MULTIVARIATE_ANOMALY_EVENT_TYPE.to_string(),
+                data: serde_json::to_value(self).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Multivariate anomaly detector keyed by model
+pub struct MultivariateDetector {
+    config: MultivariateConfig,
+    /// Squared-distance threshold
+    threshold: f64,
+    baselines: DashMap<String, VectorBaseline>,
+    observations: AtomicU64,
+    incomplete: AtomicU64,
+    anomalies: AtomicU64,
+}
+
+impl MultivariateDetector {
+    pub fn new(config: MultivariateConfig) -> Self {
+        let threshold = config.threshold();
+        Self {
+            config,
+            threshold,
+            baselines: DashMap::new(),
+            observations: AtomicU64::new(0),
+            incomplete: AtomicU64::new(0),
+            anomalies: AtomicU64::new(0),
+        }
+    }
+
+    /// Score one observation of a model's metrics and learn from it if normal.
+    /// Observations missing any configured metric are ignored.
+    pub fn observe(
+        &self,
+        model_id: &str,
+        values: &HashMap<String, f64>,
+        timestamp: DateTime<Utc>,
+    ) -> Option<MultivariateAnomaly> {
+        self.observations.fetch_add(1, Ordering::Relaxed);
+
+        let vector: Option<Vec<f64>> = self
+            .config
+            .metrics
+            .iter()
+            .map(|m| values.get(m).copied().filter(|v| v.is_finite()))
+            .collect();
+        let Some(vector) = vector else {
+            self.incomplete.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        let mut baseline = self
+            .baselines
+            .entry(model_id.to_string())
+            .or_insert_with(|| VectorBaseline::new(self.config.metrics.len()));
+
+        if baseline.count() < self.config.warmup_samples {
+            baseline.update(&vector, self.config.window_size);
+            return None;
+        }
+
+        let Some(squared) = baseline.squared_distance(&vector, self.config.regularization) else {
+            debug!(model_id, "Covariance not positive definite, learning only");
+            baseline.update(&vector, self.config.window_size);
+            return None;
+        };
+
+        if squared <= self.threshold {
+            baseline.update(&vector, self.config.window_size);
+            return None;
+        }
+
+        let mut contributions: Vec<MetricContribution> = self
+            .config
+            .metrics
+            .iter()
+            .enumerate()
+            .map(|(i, metric)| {
+                let expected = baseline.mean()[i];
+                let stddev = baseline.variance(i).sqrt().max(f64::EPSILON);
+                MetricContribution {
+                    metric: metric.clone(),
+                    value: vector[i],
+                    expected,
+                    z_score: (vector[i] - expected) / stddev,
+                }
+            })
+            .collect();
+        contributions.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+        drop(baseline);
+
+        self.anomalies.fetch_add(1, Ordering::Relaxed);
+        Some(MultivariateAnomaly {
+            model_id: model_id.to_string(),
+            timestamp,
+            distance: squared.sqrt(),
+            threshold: self.threshold.sqrt(),
+            contributions,
+            severity: Self::severity(squared / self.threshold),
+        })
+    }
+
+    fn severity(excess: f64) -> AnomalySeverity {
+        if excess >= 4.0 {
+            AnomalySeverity::Critical
+        } else if excess >= 2.5 {
+            AnomalySeverity::High
+        } else if excess >= 1.5 {
+            AnomalySeverity::Medium
+        } else {
+            AnomalySeverity::Low
+        }
+    }
+
+    /// Forget a model's baseline
+    pub fn reset(&self, model_id: &str) {
+        self.baselines.remove(model_id);
+    }
+
+    pub fn get_stats(&self) -> MultivariateStats {
+        MultivariateStats {
+            tracked_models: self.baselines.len(),
+            observations: self.observations.load(Ordering::Relaxed),
+            incomplete_observations: self.incomplete.load(Ordering::Relaxed),
+            anomalies_detected: self.anomalies.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Multivariate detector statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultivariateStats {
+    pub tracked_models: usize,
+    pub observations: u64,
+    pub incomplete_observations: u64,
+    pub anomalies_detected: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(latency: f64, error_rate: f64, cost: f64) -> HashMap<String, f64> {
+        HashMap::from([
+            ("latency_ms".to_string(), latency),
+            ("error_rate".to_string(), error_rate),
+            ("cost_usd".to_string(), cost),
+        ])
+    }
+
+    /// Cost tracks latency closely; error rate varies independently
+    fn trained_detector() -> MultivariateDetector {
+        let detector = MultivariateDetector::new(MultivariateConfig::default());
+        let now = Utc::now();
+        for i in 0..200 {
+            let latency = 100.0 + 20.0 * (i as f64 * 0.7).sin();
+            let error_rate = 0.02 + 0.005 * (i as f64 * 1.3).cos();
+            let cost = 0.001 * latency + 0.002 * (i as f64 * 2.9).sin();
+            assert!(detector
+                .observe("gpt-4", &observation(latency, error_rate, cost), now)
+                .is_none());
+        }
+        detector
+    }
+
+    #[test]
+    fn test_correlated_shift_is_flagged() {
+        let detector = trained_detector();
+
+        // Each metric is within its usual range, but high latency with low cost
+        // breaks the learned relationship
+        let anomaly = detector
+            .observe("gpt-4", &observation(118.0, 0.02, 0.083), Utc::now())
+            .expect("joint departure should be anomalous");
+
+        assert!(anomaly.distance > anomaly.threshold);
+        assert!(anomaly.contributions.iter().all(|c| c.z_score.abs() < 3.0));
+    }
+
+    #[test]
+    fn test_consistent_observation_is_normal() {
+        let detector = trained_detector();
+
+        assert!(detector
+            .observe("gpt-4", &observation(115.0, 0.022, 0.115), Utc::now())
+            .is_none());
+        assert!(detector
+            .observe("gpt-4", &HashMap::new(), Utc::now())
+            .is_none());
+        assert_eq!(detector.get_stats().incomplete_observations, 1);
+    }
+
+    #[test]
+    fn test_cholesky_factor() {
+        let l = cholesky(&[4.0, 2.0, 2.0, 3.0], 2).unwrap();
+        assert_eq!(l, vec![2.0, 0.0, 1.0, 2.0_f64.sqrt()]);
+        assert!(cholesky(&[1.0, 2.0, 2.0, 1.0], 2).is_none());
+    }
+}
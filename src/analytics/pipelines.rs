@@ -0,0 +1,433 @@
+//! Pipeline Analytics
+//!
+//! Attributes Observatory traces and CostOps pricing to the stages of a
+//! pipeline registered in LLM-Registry, producing per-stage latency, cost,
+//! and error rates.
+//!
+//! A trace belongs to a pipeline when it carries the pipeline's ID attribute
+//! or an explicit stage ID from it. Within the pipeline it is matched to a
+//! stage by, in order: a stage ID attribute, the stage name or configured
+//! `operation_name` equal to the trace's operation name, and finally the only
+//! model inference stage running the trace's model.
+
+use super::cost::trace_model;
+use super::token_efficiency::trace_pipeline;
+use crate::adapters::costops::{CostOpsAdapter, TokenAccountingBaseline};
+use crate::adapters::mirror::RegistryMirror;
+use crate::adapters::observatory::{ObservatoryAdapter, TraceQuery, TraceStatus, UsageTrace};
+use crate::adapters::registry::{PipelineDescriptor, PipelineStage, RegistryAdapter, StageType};
+use crate::models::metrics::StatisticalMeasures;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+/// Trace attributes that may carry the pipeline stage identifier, in priority order
+const STAGE_ATTRIBUTES: [&str; 2] = ["stage_id", "llm.pipeline.stage_id"];
+
+/// Stage config key naming the trace operation the stage emits
+const STAGE_OPERATION_KEY: &str = "operation_name";
+
+/// Latency, cost, and errors of one pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageBreakdown {
+    pub stage_id: String,
+    pub stage_name: String,
+    pub stage_type: StageType,
+    pub model_id: Option<String>,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub latency_ms: StatisticalMeasures,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    /// Share of the pipeline's attributed cost
+    pub cost_share: f64,
+    /// Requests that ran past the stage's configured timeout
+    pub timeouts: u64,
+}
+
+/// Per-stage breakdown of a pipeline over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineBreakdown {
+    pub pipeline_id: String,
+    pub name: String,
+    pub version: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub stages: Vec<StageBreakdown>,
+    pub total_cost_usd: f64,
+    /// Pipeline traces that matched no stage
+    pub unattributed_traces: u64,
+}
+
+/// Index of the stage `trace` is attributed to
+pub fn match_stage(pipeline: &PipelineDescriptor, trace: &UsageTrace) -> Option<usize> {
+    let stages = &pipeline.stages;
+
+    if let Some(stage_id) = trace_stage(trace) {
+        return stages.iter().position(|s| s.stage_id == stage_id);
+    }
+
+    let by_operation = stages.iter().position(|s| {
+        s.stage_name == trace.operation_name
+            || s.config
+                .get(STAGE_OPERATION_KEY)
+                .and_then(|v| v.as_str())
+                .map_or(false, |op| op == trace.operation_name)
+    });
+    if by_operation.is_some() {
+        return by_operation;
+    }
+
+    let model_id = trace_model(trace)?;
+    let mut inference = stages.iter().enumerate().filter(|(_, s)| {
+        matches!(s.stage_type, StageType::ModelInference) && s.model_id.as_deref() == Some(model_id)
+    });
+    match (inference.next(), inference.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+fn trace_stage(trace: &UsageTrace) -> Option<&str> {
+    STAGE_ATTRIBUTES
+        .iter()
+        .find_map(|key| trace.attributes.get(*key).and_then(|v| v.as_str()))
+}
+
+/// Whether `trace` was emitted by `pipeline`
+fn in_pipeline(pipeline: &PipelineDescriptor, trace: &UsageTrace) -> bool {
+    match trace_pipeline(trace) {
+        Some(pipeline_id) => pipeline_id == pipeline.pipeline_id,
+        None => {
+            trace_stage(trace).map_or(false, |id| pipeline.stages.iter().any(|s| s.stage_id == id))
+        }
+    }
+}
+
+/// Build the per-stage breakdown of `pipeline` from traces in the window
+///
+/// Trace cost is priced at the model's baseline rate, or the baseline average
+/// when the model has no price of its own.
+pub fn breakdown(
+    pipeline: &PipelineDescriptor,
+    traces: &[UsageTrace],
+    baseline: &TokenAccountingBaseline,
+    window: (DateTime<Utc>, DateTime<Utc>),
+) -> PipelineBreakdown {
+    let mut latencies: Vec<Vec<f64>> = vec![Vec::new(); pipeline.stages.len()];
+    let mut stages: Vec<StageBreakdown> = pipeline.stages.iter().map(empty_stage).collect();
+    let mut unattributed_traces = 0;
+
+    for trace in traces.iter().filter(|t| in_pipeline(pipeline, t)) {
+        let Some(index) = match_stage(pipeline, trace) else {
+            unattributed_traces += 1;
+            continue;
+        };
+        let stage = &mut stages[index];
+        let definition = &pipeline.stages[index];
+
+        stage.requests += 1;
+        if !matches!(trace.status, TraceStatus::Ok) {
+            stage.errors += 1;
+        }
+        if definition.timeout_ms > 0 && trace.duration_ms > definition.timeout_ms {
+            stage.timeouts += 1;
+        }
+        latencies[index].push(trace.duration_ms as f64);
+
+        if let Some(usage) = &trace.token_usage {
+            let prices = &baseline.cost_per_token;
+            let price_per_1k = trace_model(trace)
+                .or(definition.model_id.as_deref())
+                .and_then(|model| prices.by_model.get(model))
+                .copied()
+                .unwrap_or(prices.average_cost_per_1k_tokens);
+            stage.total_tokens += usage.total_tokens;
+            stage.cost_usd += usage.total_tokens as f64 / 1000.0 * price_per_1k;
+        }
+    }
+
+    let total_cost_usd: f64 = stages.iter().map(|s| s.cost_usd).sum();
+    for (stage, samples) in stages.iter_mut().zip(&latencies) {
+        stage.latency_ms = StatisticalMeasures::from_values(samples);
+        if stage.requests > 0 {
+            stage.error_rate = stage.errors as f64 / stage.requests as f64;
+        }
+        if total_cost_usd > 0.0 {
+            stage.cost_share = stage.cost_usd / total_cost_usd;
+        }
+    }
+
+    PipelineBreakdown {
+        pipeline_id: pipeline.pipeline_id.clone(),
+        name: pipeline.name.clone(),
+        version: pipeline.version.clone(),
+        window_start: window.0,
+        window_end: window.1,
+        stages,
+        total_cost_usd,
+        unattributed_traces,
+    }
+}
+
+fn empty_stage(stage: &PipelineStage) -> StageBreakdown {
+    StageBreakdown {
+        stage_id: stage.stage_id.clone(),
+        stage_name: stage.stage_name.clone(),
+        stage_type: stage.stage_type.clone(),
+        model_id: stage.model_id.clone(),
+        requests: 0,
+        errors: 0,
+        error_rate: 0.0,
+        latency_ms: StatisticalMeasures::default(),
+        total_tokens: 0,
+        cost_usd: 0.0,
+        cost_share: 0.0,
+        timeouts: 0,
+    }
+}
+
+/// Joins registry pipeline definitions with traces and pricing
+pub struct PipelineAnalyzer {
+    registry: Arc<RegistryAdapter>,
+    observatory: Arc<ObservatoryAdapter>,
+    costops: Arc<CostOpsAdapter>,
+    mirror: Option<Arc<RegistryMirror>>,
+}
+
+impl PipelineAnalyzer {
+    pub fn new(
+        registry: Arc<RegistryAdapter>,
+        observatory: Arc<ObservatoryAdapter>,
+        costops: Arc<CostOpsAdapter>,
+    ) -> Self {
+        Self {
+            registry,
+            observatory,
+            costops,
+            mirror: None,
+        }
+    }
+
+    /// Read pipeline definitions from the local registry mirror
+    pub fn with_mirror(mut self, mirror: Arc<RegistryMirror>) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    async fn fetch_pipeline(&self, pipeline_id: &str) -> Result<PipelineDescriptor> {
+        match &self.mirror {
+            Some(mirror) => mirror.fetch_pipeline(pipeline_id).await,
+            None => self.registry.fetch_pipeline(pipeline_id).await,
+        }
+    }
+
+    /// Per-stage breakdown of a registered pipeline over `[start, end)`
+    #[instrument(skip(self))]
+    pub async fn breakdown(
+        &self,
+        pipeline_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<PipelineBreakdown> {
+        let query = TraceQuery {
+            start_time: Some(start),
+            end_time: Some(end),
+            ..Default::default()
+        };
+        let (pipeline, traces, baseline) = tokio::try_join!(
+            self.fetch_pipeline(pipeline_id),
+            self.observatory.fetch_traces(query),
+            self.costops.fetch_token_baseline(start, end),
+        )?;
+
+        let breakdown = breakdown(&pipeline, &traces, &baseline, (start, end));
+        debug!(
+            pipeline_id = %pipeline_id,
+            stages = breakdown.stages.len(),
+            unattributed = breakdown.unattributed_traces,
+            "Pipeline breakdown complete"
+        );
+        Ok(breakdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::costops::{BaselinePeriod, CostPerToken, EfficiencyMetrics, TokenMetrics};
+    use crate::adapters::observatory::TokenUsage;
+    use crate::adapters::registry::{PipelineMetrics, PipelineStatus, RetryPolicy};
+    use std::collections::HashMap;
+
+    fn stage(id: &str, stage_type: StageType, model_id: Option<&str>) -> PipelineStage {
+        PipelineStage {
+            stage_id: id.to_string(),
+            stage_name: id.to_string(),
+            stage_type,
+            model_id: model_id.map(String::from),
+            config: HashMap::new(),
+            timeout_ms: 1_000,
+            retry_policy: RetryPolicy {
+                max_attempts: 1,
+                initial_delay_ms: 0,
+                max_delay_ms: 0,
+                backoff_multiplier: 1.0,
+            },
+        }
+    }
+
+    fn pipeline() -> PipelineDescriptor {
+        let mut retrieve = stage("retrieve", StageType::Retrieval, None);
+        retrieve.config.insert(
+            "operation_name".to_string(),
+            serde_json::json!("vector.search"),
+        );
+        PipelineDescriptor {
+            pipeline_id: "rag".to_string(),
+            name: "RAG".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            stages: vec![
+                retrieve,
+                stage("generate", StageType::ModelInference, Some("gpt-4")),
+            ],
+            input_schema: serde_json::json!({}),
+            output_schema: serde_json::json!({}),
+            created_at: Utc::now(),
+            last_updated: Utc::now(),
+            owner: "search".to_string(),
+            status: PipelineStatus::Active,
+            metrics: PipelineMetrics {
+                total_invocations: 0,
+                success_rate: 0.0,
+                avg_latency_ms: 0.0,
+                avg_cost_per_invocation: 0.0,
+            },
+        }
+    }
+
+    fn trace(
+        operation: &str,
+        attributes: &[(&str, &str)],
+        duration_ms: u64,
+        status: TraceStatus,
+        tokens: Option<u64>,
+    ) -> UsageTrace {
+        UsageTrace {
+            trace_id: "t".to_string(),
+            span_id: "s".to_string(),
+            parent_span_id: None,
+            operation_name: operation.to_string(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            duration_ms,
+            status,
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), serde_json::json!(v)))
+                .collect(),
+            token_usage: tokens.map(|total_tokens| TokenUsage {
+                prompt_tokens: total_tokens,
+                completion_tokens: 0,
+                total_tokens,
+            }),
+        }
+    }
+
+    fn baseline() -> TokenAccountingBaseline {
+        TokenAccountingBaseline {
+            baseline_id: "b".to_string(),
+            created_at: Utc::now(),
+            period: BaselinePeriod {
+                start: Utc::now(),
+                end: Utc::now(),
+            },
+            token_metrics: TokenMetrics {
+                total_tokens: 0,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                cached_tokens: 0,
+                by_model: HashMap::new(),
+            },
+            cost_per_token: CostPerToken {
+                average_cost_per_1k_tokens: 1.0,
+                prompt_cost_per_1k: 1.0,
+                completion_cost_per_1k: 1.0,
+                by_model: [("gpt-4".to_string(), 30.0)].into_iter().collect(),
+            },
+            efficiency_metrics: EfficiencyMetrics {
+                cache_hit_rate: 0.0,
+                tokens_per_request_avg: 0.0,
+                cost_per_request_avg: 0.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_match_stage() {
+        let pipeline = pipeline();
+        let by_id = trace(
+            "anything",
+            &[("stage_id", "generate")],
+            10,
+            TraceStatus::Ok,
+            None,
+        );
+        let by_operation = trace("vector.search", &[], 10, TraceStatus::Ok, None);
+        let by_model = trace("chat", &[("model_id", "gpt-4")], 10, TraceStatus::Ok, None);
+        let unknown = trace(
+            "chat",
+            &[("model_id", "claude-3")],
+            10,
+            TraceStatus::Ok,
+            None,
+        );
+
+        assert_eq!(match_stage(&pipeline, &by_id), Some(1));
+        assert_eq!(match_stage(&pipeline, &by_operation), Some(0));
+        assert_eq!(match_stage(&pipeline, &by_model), Some(1));
+        assert_eq!(match_stage(&pipeline, &unknown), None);
+    }
+
+    #[test]
+    fn test_breakdown_attributes_latency_cost_and_errors() {
+        let rag = [("pipeline_id", "rag")];
+        let generate = [("pipeline_id", "rag"), ("model_id", "gpt-4")];
+        let traces = vec![
+            trace("vector.search", &rag, 20, TraceStatus::Ok, None),
+            trace("vector.search", &rag, 40, TraceStatus::Error, None),
+            trace("chat", &generate, 900, TraceStatus::Ok, Some(1_000)),
+            trace("chat", &generate, 1_500, TraceStatus::Timeout, Some(1_000)),
+            trace("rerank", &rag, 5, TraceStatus::Ok, None),
+            trace(
+                "vector.search",
+                &[("pipeline_id", "other")],
+                10,
+                TraceStatus::Ok,
+                None,
+            ),
+        ];
+
+        let now = Utc::now();
+        let report = breakdown(&pipeline(), &traces, &baseline(), (now, now));
+        assert_eq!(report.unattributed_traces, 1);
+
+        let retrieve = &report.stages[0];
+        assert_eq!(retrieve.requests, 2);
+        assert_eq!(retrieve.error_rate, 0.5);
+        assert_eq!(retrieve.latency_ms.avg, 30.0);
+        assert_eq!(retrieve.cost_usd, 0.0);
+
+        let generate = &report.stages[1];
+        assert_eq!(generate.requests, 2);
+        assert_eq!(generate.errors, 1);
+        assert_eq!(generate.timeouts, 1);
+        assert!((generate.cost_usd - 60.0).abs() < 1e-9);
+        assert_eq!(generate.cost_share, 1.0);
+        assert!((report.total_cost_usd - 60.0).abs() < 1e-9);
+    }
+}
@@ -5,14 +5,15 @@
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::Arc;
 
-use super::AnalyticsConfig;
+use super::{AnalyticsConfig, SharedConfig};
 
 /// Prediction engine for time-series forecasting
 pub struct PredictionEngine {
-    config: Arc<AnalyticsConfig>,
+    config: SharedConfig,
     // Metric name -> Historical data for training
     time_series: Arc<DashMap<String, TimeSeriesData>>,
     // Cached predictions
@@ -22,11 +23,16 @@ pub struct PredictionEngine {
 impl PredictionEngine {
     /// Create a new prediction engine
     pub async fn new(config: Arc<AnalyticsConfig>) -> Result<Self> {
-        Ok(Self {
+        Ok(Self::with_shared_config(SharedConfig::new(config)))
+    }
+
+    /// Create an engine following a reloadable configuration
+    pub fn with_shared_config(config: SharedConfig) -> Self {
+        Self {
             config,
             time_series: Arc::new(DashMap::new()),
             predictions: Arc::new(DashMap::new()),
-        })
+        }
     }
 
     /// Add a data point to time series
@@ -36,9 +42,10 @@ impl PredictionEngine {
         value: f64,
         timestamp: DateTime<Utc>,
     ) -> Result<()> {
+        let history_size = self.config.load().prediction_history_size;
         self.time_series
             .entry(metric_name.to_string())
-            .or_insert_with(|| TimeSeriesData::new(self.config.prediction_history_size))
+            .or_insert_with(|| TimeSeriesData::new(history_size))
             .add_point(value, timestamp);
 
         // Invalidate cached prediction
@@ -278,7 +285,7 @@ impl TimeSeriesData {
 }
 
 /// Prediction point
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PredictionPoint {
     pub timestamp: DateTime<Utc>,
     pub value: f64,
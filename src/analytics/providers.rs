@@ -0,0 +1,620 @@
+//! Provider Health Scoring
+//!
+//! Blends the health LLM-Registry reports for each provider with error rate
+//! and latency aggregates observed by the hub into a rolling health score.
+//! Observed traffic usually degrades before the registry's periodic checks
+//! notice, so the score flags providers ahead of the registry status and
+//! emits failover recommendations as alert events naming healthier providers
+//! that serve the same models.
+
+use crate::adapters::registry::{ProviderInfo, ProviderStatus, RegistryAdapter};
+use crate::database::Database;
+use crate::models::metrics::TimeWindow;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+/// Custom payload type for provider failover recommendations
+pub const PROVIDER_FAILOVER_EVENT_TYPE: &str = "provider.failover_recommendation";
+
+/// Aggregated metric carrying the request error rate
+pub const ERROR_RATE_METRIC: &str = "error_rate";
+
+/// Aggregated metric carrying request latency
+pub const LATENCY_METRIC: &str = "latency_ms";
+
+/// Provider health configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealthConfig {
+    /// Tag identifying the provider on aggregated metrics
+    pub provider_tag: String,
+    /// Length of the recent window scored on each run
+    pub window_secs: i64,
+    /// Length of the history observed latency is compared against
+    pub baseline_hours: i64,
+    /// Error rate at which the error component of the score reaches zero
+    pub max_error_rate: f64,
+    /// Weight of the registry-reported health in the score
+    pub registry_weight: f64,
+    /// Smoothing factor of the rolling score; higher reacts faster
+    pub smoothing: f64,
+    /// Rolling score below which a provider is degraded
+    pub degraded_score: f64,
+    /// Rolling score below which a provider is critical
+    pub critical_score: f64,
+    /// Environment stamped on emitted events
+    pub environment: String,
+}
+
+impl Default for ProviderHealthConfig {
+    fn default() -> Self {
+        Self {
+            provider_tag: "provider".to_string(),
+            window_secs: 900,
+            baseline_hours: 24,
+            max_error_rate: 0.2,
+            registry_weight: 0.3,
+            smoothing: 0.5,
+            degraded_score: 0.7,
+            critical_score: 0.4,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl ProviderHealthConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider_tag: std::env::var("PROVIDER_HEALTH_TAG").unwrap_or(defaults.provider_tag),
+            window_secs: std::env::var("PROVIDER_HEALTH_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.window_secs),
+            degraded_score: std::env::var("PROVIDER_HEALTH_DEGRADED_SCORE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.degraded_score),
+            critical_score: std::env::var("PROVIDER_HEALTH_CRITICAL_SCORE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.critical_score),
+            ..defaults
+        }
+    }
+}
+
+// ========== Scoring ==========
+
+/// Health classification derived from the rolling score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Critical,
+}
+
+/// Error rate and latency observed for a provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObservedHealth {
+    pub error_rate: Option<f64>,
+    pub latency_ms: Option<f64>,
+    /// Mean latency over the baseline history
+    pub baseline_latency_ms: Option<f64>,
+}
+
+/// Score in `[0, 1]` from the registry's view of the provider
+pub fn registry_score(provider: &ProviderInfo) -> f64 {
+    let health = &provider.health;
+    let score = health.availability.clamp(0.0, 1.0) * (1.0 - health.error_rate.clamp(0.0, 1.0));
+    match provider.status {
+        ProviderStatus::Operational => score,
+        ProviderStatus::Degraded => score.min(0.5),
+        ProviderStatus::Outage => 0.0,
+    }
+}
+
+/// Score in `[0, 1]` from observed traffic, `None` without observations
+///
+/// Error rate scales linearly to zero at `max_error_rate`. Latency scores the
+/// ratio of baseline to observed latency, falling back to the registry's
+/// latency when there is no history.
+pub fn observed_score(
+    observed: &ObservedHealth,
+    provider: &ProviderInfo,
+    config: &ProviderHealthConfig,
+) -> Option<f64> {
+    let error_score = observed
+        .error_rate
+        .map(|rate| (1.0 - rate / config.max_error_rate).clamp(0.0, 1.0));
+
+    let reference = observed
+        .baseline_latency_ms
+        .or(Some(provider.health.avg_latency_ms))
+        .filter(|l| *l > 0.0);
+    let latency_score = match (observed.latency_ms, reference) {
+        (Some(latency), Some(reference)) if latency > 0.0 => {
+            Some((reference / latency).clamp(0.0, 1.0))
+        }
+        _ => None,
+    };
+
+    match (error_score, latency_score) {
+        (Some(e), Some(l)) => Some((e + l) / 2.0),
+        (Some(score), None) | (None, Some(score)) => Some(score),
+        (None, None) => None,
+    }
+}
+
+/// Current health of one provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealthScore {
+    pub provider_id: String,
+    pub name: String,
+    /// Smoothed score in `[0, 1]`
+    pub score: f64,
+    /// Score from this run alone
+    pub instant_score: f64,
+    pub registry_score: f64,
+    pub observed: ObservedHealth,
+    pub status: HealthStatus,
+    pub registry_status: ProviderStatus,
+    /// Degraded by observed traffic while the registry still reports it operational
+    pub ahead_of_registry: bool,
+    pub models: Vec<String>,
+}
+
+fn classify(score: f64, config: &ProviderHealthConfig) -> HealthStatus {
+    if score < config.critical_score {
+        HealthStatus::Critical
+    } else if score < config.degraded_score {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
+    }
+}
+
+// ========== Recommendations ==========
+
+/// Suggestion to move traffic away from an unhealthy provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverRecommendation {
+    pub from_provider: String,
+    pub status: HealthStatus,
+    pub score: f64,
+    /// Share of the provider's traffic to shift
+    pub shift_fraction: f64,
+    /// Healthy providers serving at least one of the same models, best first
+    pub to_providers: Vec<String>,
+    pub message: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl FailoverRecommendation {
+    /// Wrap the recommendation as an alert event for publishing
+    pub fn to_event(&self, environment: &str) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        tags.insert("provider".to_string(), self.from_provider.clone());
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: self.generated_at,
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: match self.status {
+                    HealthStatus::Critical => Severity::Critical,
+                    _ => Severity::Warning,
+                },
+                environment: environment.to_string(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: PROVIDER_FAILOVER_EVENT_TYPE.to_string(),
+                data: serde_json::to_value(self).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Recommend failover for every unhealthy provider in `scores`
+pub fn recommend_failover(
+    scores: &[ProviderHealthScore],
+    now: DateTime<Utc>,
+) -> Vec<FailoverRecommendation> {
+    scores
+        .iter()
+        .filter(|s| s.status != HealthStatus::Healthy)
+        .map(|unhealthy| {
+            let mut alternatives: Vec<&ProviderHealthScore> = scores
+                .iter()
+                .filter(|s| {
+                    s.status == HealthStatus::Healthy
+                        && s.models.iter().any(|m| unhealthy.models.contains(m))
+                })
+                .collect();
+            alternatives.sort_by(|a, b| b.score.total_cmp(&a.score));
+            let to_providers: Vec<String> =
+                alternatives.iter().map(|s| s.provider_id.clone()).collect();
+
+            let shift_fraction = match unhealthy.status {
+                HealthStatus::Critical => 1.0,
+                _ => 0.5,
+            };
+            let message = match to_providers.first() {
+                Some(target) => format!(
+                    "Shift {:.0}% of traffic from provider {} (health {:.2}) to {}",
+                    shift_fraction * 100.0,
+                    unhealthy.provider_id,
+                    unhealthy.score,
+                    target
+                ),
+                None => format!(
+                    "Provider {} is {:?} (health {:.2}) and no healthy provider serves its models",
+                    unhealthy.provider_id, unhealthy.status, unhealthy.score
+                ),
+            };
+
+            FailoverRecommendation {
+                from_provider: unhealthy.provider_id.clone(),
+                status: unhealthy.status,
+                score: unhealthy.score,
+                shift_fraction,
+                to_providers,
+                message,
+                generated_at: now,
+            }
+        })
+        .collect()
+}
+
+// ========== Monitor ==========
+
+/// Result of one provider health pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealthReport {
+    pub evaluated_at: DateTime<Utc>,
+    pub providers: Vec<ProviderHealthScore>,
+    pub recommendations: Vec<FailoverRecommendation>,
+}
+
+/// Monitor statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealthStats {
+    pub runs: u64,
+    pub early_detections: u64,
+    pub recommendations: u64,
+    pub events_emitted: u64,
+    pub events_dropped: u64,
+}
+
+/// Scores providers on each run and publishes failover recommendations
+pub struct ProviderHealthMonitor {
+    registry: Arc<RegistryAdapter>,
+    database: Arc<Database>,
+    config: ProviderHealthConfig,
+    sink: mpsc::Sender<AnalyticsEvent>,
+    /// Rolling score and status per provider
+    state: DashMap<String, (f64, HealthStatus)>,
+    runs: AtomicU64,
+    early_detections: AtomicU64,
+    recommendations: AtomicU64,
+    events_emitted: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl ProviderHealthMonitor {
+    /// Create a monitor publishing recommendations into the given pipeline channel
+    pub fn new(
+        registry: Arc<RegistryAdapter>,
+        database: Arc<Database>,
+        config: ProviderHealthConfig,
+        sink: mpsc::Sender<AnalyticsEvent>,
+    ) -> Self {
+        Self {
+            registry,
+            database,
+            config,
+            sink,
+            state: DashMap::new(),
+            runs: AtomicU64::new(0),
+            early_detections: AtomicU64::new(0),
+            recommendations: AtomicU64::new(0),
+            events_emitted: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Observed error rate and latency per provider
+    async fn observed(&self, now: DateTime<Utc>) -> Result<HashMap<String, ObservedHealth>> {
+        let metrics = [ERROR_RATE_METRIC.to_string(), LATENCY_METRIC.to_string()];
+        let tag = &self.config.provider_tag;
+        let window_start = now - Duration::seconds(self.config.window_secs);
+        let baseline_start = window_start - Duration::hours(self.config.baseline_hours);
+
+        let (recent, baseline) = tokio::try_join!(
+            self.database.query_metric_means_by_tag(
+                &metrics,
+                tag,
+                TimeWindow::OneMinute,
+                window_start,
+                now
+            ),
+            self.database.query_metric_means_by_tag(
+                &metrics[1..],
+                tag,
+                TimeWindow::OneHour,
+                baseline_start,
+                window_start
+            ),
+        )?;
+
+        let mut observed: HashMap<String, ObservedHealth> = HashMap::new();
+        for row in recent {
+            let entry = observed.entry(row.tag_value).or_default();
+            match row.metric_name.as_str() {
+                ERROR_RATE_METRIC => entry.error_rate = Some(row.mean),
+                _ => entry.latency_ms = Some(row.mean),
+            }
+        }
+        for row in baseline {
+            observed
+                .entry(row.tag_value)
+                .or_default()
+                .baseline_latency_ms = Some(row.mean);
+        }
+        Ok(observed)
+    }
+
+    /// Score every registered provider, smoothing against the scores of earlier runs
+    #[instrument(skip(self))]
+    pub async fn evaluate(&self, now: DateTime<Utc>) -> Result<ProviderHealthReport> {
+        let (providers, observed) =
+            tokio::try_join!(self.registry.list_providers(), self.observed(now))?;
+
+        let mut scores = Vec::with_capacity(providers.len());
+        for provider in providers {
+            let observed = observed
+                .get(&provider.provider_id)
+                .copied()
+                .unwrap_or_default();
+            let registry = registry_score(&provider);
+            let instant_score = match observed_score(&observed, &provider, &self.config) {
+                Some(score) => {
+                    let w = self.config.registry_weight.clamp(0.0, 1.0);
+                    (1.0 - w) * score + w * registry
+                }
+                None => registry,
+            };
+
+            let score = match self.state.get(&provider.provider_id) {
+                Some(previous) => {
+                    let alpha = self.config.smoothing.clamp(0.0, 1.0);
+                    alpha * instant_score + (1.0 - alpha) * previous.0
+                }
+                None => instant_score,
+            };
+            let status = classify(score, &self.config);
+            let ahead_of_registry = status != HealthStatus::Healthy
+                && matches!(provider.status, ProviderStatus::Operational);
+
+            scores.push(ProviderHealthScore {
+                provider_id: provider.provider_id,
+                name: provider.name,
+                score,
+                instant_score,
+                registry_score: registry,
+                observed,
+                status,
+                registry_status: provider.status,
+                ahead_of_registry,
+                models: provider.models,
+            });
+        }
+        scores.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+
+        let recommendations = recommend_failover(&scores, now);
+        debug!(
+            providers = scores.len(),
+            unhealthy = recommendations.len(),
+            "Provider health evaluated"
+        );
+
+        Ok(ProviderHealthReport {
+            evaluated_at: now,
+            providers: scores,
+            recommendations,
+        })
+    }
+
+    /// Run one pass and publish recommendations for providers whose status worsened
+    pub async fn run_once(&self) -> Result<ProviderHealthReport> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let report = self.evaluate(Utc::now()).await?;
+
+        let mut worsened = Vec::new();
+        for provider in &report.providers {
+            let previous = self
+                .state
+                .insert(
+                    provider.provider_id.clone(),
+                    (provider.score, provider.status),
+                )
+                .map_or(HealthStatus::Healthy, |(_, status)| status);
+            if provider.status > previous {
+                worsened.push(provider.provider_id.as_str());
+                if provider.ahead_of_registry {
+                    self.early_detections.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        for recommendation in &report.recommendations {
+            if !worsened.contains(&recommendation.from_provider.as_str()) {
+                continue;
+            }
+            info!(
+                provider = %recommendation.from_provider,
+                status = ?recommendation.status,
+                score = recommendation.score,
+                "Provider failover recommended"
+            );
+            self.recommendations.fetch_add(1, Ordering::Relaxed);
+            self.emit(recommendation.to_event(&self.config.environment));
+        }
+
+        Ok(report)
+    }
+
+    fn emit(&self, event: AnalyticsEvent) {
+        match self.sink.try_send(event) {
+            Ok(()) => {
+                self.events_emitted.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!(error = %e, "Dropped provider failover event");
+            }
+        }
+    }
+
+    /// Get monitor statistics
+    pub fn get_stats(&self) -> ProviderHealthStats {
+        ProviderHealthStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            early_detections: self.early_detections.load(Ordering::Relaxed),
+            recommendations: self.recommendations.load(Ordering::Relaxed),
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::registry::{ProviderHealth, RateLimits};
+
+    fn provider(id: &str, status: ProviderStatus, models: &[&str]) -> ProviderInfo {
+        ProviderInfo {
+            provider_id: id.to_string(),
+            name: id.to_string(),
+            status,
+            api_version: "1".to_string(),
+            models: models.iter().map(|m| m.to_string()).collect(),
+            rate_limits: RateLimits {
+                requests_per_minute: 0,
+                tokens_per_minute: 0,
+                tokens_per_day: None,
+            },
+            health: ProviderHealth {
+                availability: 1.0,
+                avg_latency_ms: 200.0,
+                error_rate: 0.0,
+                last_checked: Utc::now(),
+            },
+        }
+    }
+
+    fn score(id: &str, value: f64, models: &[&str]) -> ProviderHealthScore {
+        let config = ProviderHealthConfig::default();
+        ProviderHealthScore {
+            provider_id: id.to_string(),
+            name: id.to_string(),
+            score: value,
+            instant_score: value,
+            registry_score: 1.0,
+            observed: ObservedHealth::default(),
+            status: classify(value, &config),
+            registry_status: ProviderStatus::Operational,
+            ahead_of_registry: false,
+            models: models.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_scores() {
+        let config = ProviderHealthConfig::default();
+        let healthy = provider("openai", ProviderStatus::Operational, &[]);
+        assert_eq!(registry_score(&healthy), 1.0);
+        assert_eq!(
+            registry_score(&provider("x", ProviderStatus::Degraded, &[])),
+            0.5
+        );
+        assert_eq!(
+            registry_score(&provider("x", ProviderStatus::Outage, &[])),
+            0.0
+        );
+
+        assert_eq!(
+            observed_score(&ObservedHealth::default(), &healthy, &config),
+            None
+        );
+
+        // 10% errors halves the error score; latency doubled over its history
+        let observed = ObservedHealth {
+            error_rate: Some(0.1),
+            latency_ms: Some(800.0),
+            baseline_latency_ms: Some(400.0),
+        };
+        let score = observed_score(&observed, &healthy, &config).unwrap();
+        assert!((score - 0.5).abs() < 1e-9);
+
+        // Without history the registry's latency is the reference
+        let no_history = ObservedHealth {
+            baseline_latency_ms: None,
+            error_rate: None,
+            ..observed
+        };
+        let score = observed_score(&no_history, &healthy, &config).unwrap();
+        assert!((score - 0.25).abs() < 1e-9);
+
+        assert_eq!(classify(0.9, &config), HealthStatus::Healthy);
+        assert_eq!(classify(0.5, &config), HealthStatus::Degraded);
+        assert_eq!(classify(0.1, &config), HealthStatus::Critical);
+    }
+
+    #[test]
+    fn test_recommend_failover() {
+        let scores = vec![
+            score("anthropic", 0.95, &["claude-3"]),
+            score("azure", 0.8, &["gpt-4"]),
+            score("openai", 0.3, &["gpt-4", "gpt-3.5"]),
+            score("other", 0.9, &["gpt-4"]),
+            score("mistral", 0.6, &["mistral-large"]),
+        ];
+
+        let recommendations = recommend_failover(&scores, Utc::now());
+        assert_eq!(recommendations.len(), 2);
+
+        let openai = &recommendations[0];
+        assert_eq!(openai.from_provider, "openai");
+        assert_eq!(openai.status, HealthStatus::Critical);
+        assert_eq!(openai.shift_fraction, 1.0);
+        assert_eq!(openai.to_providers, vec!["other", "azure"]);
+        assert!(openai.message.contains("from provider openai"));
+
+        let mistral = &recommendations[1];
+        assert_eq!(mistral.shift_fraction, 0.5);
+        assert!(mistral.to_providers.is_empty());
+
+        let event = openai.to_event("test");
+        assert_eq!(event.common.event_type, EventType::Alert);
+        assert_eq!(event.common.tags["provider"], "openai");
+    }
+}
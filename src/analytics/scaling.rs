@@ -0,0 +1,421 @@
+//! Forecast-Driven Scaling Recommendations
+//!
+//! Forecasts each service's request throughput for the coming hours and turns
+//! it into replica counts. A service's hourly throughput is the sum of the
+//! hourly averages of its throughput series (one per model), forecast with the
+//! prediction engine: ARIMA once there are enough hours of history, otherwise
+//! exponential smoothing. Each forecast hour's upper bound is divided by what
+//! one replica serves at the target utilization, so recommendations carry
+//! headroom for forecast error as well as for load.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::debug;
+
+use super::prediction::{PredictionEngine, PredictionPoint};
+use super::shadow::ForecastMethod;
+use super::{AnalyticsConfig, SharedConfig};
+use crate::alerting::maintenance::SERVICE_TAG;
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::metrics::TimeWindow;
+
+/// Smoothing factor used when there is too little history for ARIMA
+const SMOOTHING_ALPHA: f64 = 0.3;
+
+/// Scaling recommendation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingConfig {
+    /// Aggregated metric holding request throughput, tagged by service
+    pub throughput_metric: String,
+    /// Requests per second one replica serves at full utilization
+    pub capacity_per_replica: f64,
+    /// Per-service overrides of `capacity_per_replica`
+    pub service_capacity: HashMap<String, f64>,
+    /// Share of a replica's capacity to plan for (0.0 - 1.0)
+    pub target_utilization: f64,
+    pub min_replicas: u32,
+    pub max_replicas: u32,
+    /// Hours of history the forecast is fitted to
+    pub history_hours: i64,
+    pub default_horizon_hours: i64,
+    pub max_horizon_hours: i64,
+}
+
+impl Default for ScalingConfig {
+    fn default() -> Self {
+        Self {
+            throughput_metric: "requests_per_second".to_string(),
+            capacity_per_replica: 50.0,
+            service_capacity: HashMap::new(),
+            target_utilization: 0.75,
+            min_replicas: 1,
+            max_replicas: 50,
+            history_hours: 7 * 24,
+            default_horizon_hours: 6,
+            max_horizon_hours: 48,
+        }
+    }
+}
+
+impl ScalingConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let service_capacity = match std::env::var("SCALING_SERVICE_CAPACITY") {
+            Ok(spec) => Self::parse_capacities(&spec)?,
+            Err(_) => defaults.service_capacity,
+        };
+        Ok(Self {
+            throughput_metric: std::env::var("SCALING_THROUGHPUT_METRIC")
+                .unwrap_or(defaults.throughput_metric),
+            capacity_per_replica: std::env::var("SCALING_CAPACITY_PER_REPLICA")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.capacity_per_replica),
+            service_capacity,
+            target_utilization: std::env::var("SCALING_TARGET_UTILIZATION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.target_utilization),
+            min_replicas: std::env::var("SCALING_MIN_REPLICAS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_replicas),
+            max_replicas: std::env::var("SCALING_MAX_REPLICAS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_replicas),
+            history_hours: std::env::var("SCALING_HISTORY_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.history_hours),
+            ..defaults
+        })
+    }
+
+    /// Parse `service:requests_per_second` entries separated by commas
+    pub fn parse_capacities(spec: &str) -> Result<HashMap<String, f64>> {
+        let mut capacities = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((service, capacity)) = entry.split_once(':') else {
+                anyhow::bail!("Service capacities must be service:requests_per_second");
+            };
+            let capacity: f64 = capacity
+                .parse()
+                .with_context(|| format!("Invalid capacity for service {}", service))?;
+            if capacity <= 0.0 {
+                anyhow::bail!("Capacity for service {} must be positive", service);
+            }
+            capacities.insert(service.to_string(), capacity);
+        }
+        Ok(capacities)
+    }
+
+    /// Requests per second one replica of `service` serves
+    pub fn capacity_for(&self, service: &str) -> f64 {
+        self.service_capacity
+            .get(service)
+            .copied()
+            .unwrap_or(self.capacity_per_replica)
+    }
+
+    /// Replicas of `service` needed to serve `rps` at the target utilization
+    pub fn replicas_for(&self, service: &str, rps: f64) -> u32 {
+        let usable = self.capacity_for(service) * self.target_utilization.clamp(0.05, 1.0);
+        let needed = (rps.max(0.0) / usable).ceil() as u32;
+        needed.clamp(self.min_replicas, self.max_replicas.max(self.min_replicas))
+    }
+}
+
+/// Forecast demand and replicas for one hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HourlyScaling {
+    pub hour: DateTime<Utc>,
+    pub forecast_rps: f64,
+    /// Upper bound of the forecast, which replicas are sized for
+    pub upper_rps: f64,
+    pub replicas: u32,
+}
+
+/// Replicas recommended for a service over the forecast horizon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScalingRecommendation {
+    pub service: String,
+    /// Throughput in the latest complete hour
+    pub current_rps: f64,
+    /// Replicas needed for the latest hour
+    pub current_replicas: u32,
+    /// Replicas covering the busiest forecast hour
+    pub recommended_replicas: u32,
+    pub peak_rps: f64,
+    pub peak_at: Option<DateTime<Utc>>,
+    pub capacity_per_replica: f64,
+    pub method: ForecastMethod,
+    pub hours: Vec<HourlyScaling>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Hourly throughput per service: each hour's averages summed across the
+/// service's series. Rows without a service tag are skipped.
+pub fn service_throughput(
+    rows: &[AggregatedMetricRow],
+) -> BTreeMap<String, Vec<(DateTime<Utc>, f64)>> {
+    let mut hourly: BTreeMap<String, BTreeMap<DateTime<Utc>, f64>> = BTreeMap::new();
+    for row in rows {
+        let Some(service) = row.tags[SERVICE_TAG].as_str() else {
+            continue;
+        };
+        *hourly
+            .entry(service.to_string())
+            .or_default()
+            .entry(row.window_start)
+            .or_default() += row.avg;
+    }
+    hourly
+        .into_iter()
+        .map(|(service, hours)| (service, hours.into_iter().collect()))
+        .collect()
+}
+
+/// Recommend replicas for `service` from its hourly throughput history
+pub fn recommend(
+    service: &str,
+    history: &[(DateTime<Utc>, f64)],
+    horizon_hours: usize,
+    config: &ScalingConfig,
+    now: DateTime<Utc>,
+) -> Result<ScalingRecommendation> {
+    let &(last_hour, current_rps) = history
+        .last()
+        .with_context(|| format!("No throughput history for {}", service))?;
+
+    let engine =
+        PredictionEngine::with_shared_config(SharedConfig::new(Arc::new(AnalyticsConfig {
+            prediction_history_size: history.len(),
+            ..AnalyticsConfig::default()
+        })));
+    for &(hour, rps) in history {
+        engine.add_data_point(service, rps, hour)?;
+    }
+    let (points, method): (Vec<PredictionPoint>, ForecastMethod) =
+        match engine.predict_arima(service, horizon_hours) {
+            Ok(points) => (points, ForecastMethod::Arima),
+            Err(_) => (
+                engine.predict_exponential_smoothing(service, horizon_hours, SMOOTHING_ALPHA)?,
+                ForecastMethod::ExponentialSmoothing {
+                    alpha: SMOOTHING_ALPHA,
+                },
+            ),
+        };
+
+    // Prediction points are stepped by the engine; they stand for the hours
+    // after the last observed one
+    let hours: Vec<HourlyScaling> = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let upper_rps = point.upper_bound.max(point.value).max(0.0);
+            HourlyScaling {
+                hour: last_hour + Duration::hours(i as i64 + 1),
+                forecast_rps: point.value.max(0.0),
+                upper_rps,
+                replicas: config.replicas_for(service, upper_rps),
+            }
+        })
+        .collect();
+    let peak = hours
+        .iter()
+        .max_by(|a, b| a.upper_rps.total_cmp(&b.upper_rps));
+    let current_replicas = config.replicas_for(service, current_rps);
+
+    Ok(ScalingRecommendation {
+        service: service.to_string(),
+        current_rps,
+        current_replicas,
+        recommended_replicas: peak.map_or(current_replicas, |p| p.replicas),
+        peak_rps: peak.map_or(current_rps, |p| p.upper_rps),
+        peak_at: peak.map(|p| p.hour),
+        capacity_per_replica: config.capacity_for(service),
+        method,
+        hours,
+        generated_at: now,
+    })
+}
+
+/// Builds scaling recommendations from stored throughput
+pub struct ScalingRecommender {
+    database: Arc<Database>,
+    config: ScalingConfig,
+}
+
+impl ScalingRecommender {
+    pub fn new(database: Arc<Database>, config: ScalingConfig) -> Self {
+        Self { database, config }
+    }
+
+    pub fn config(&self) -> &ScalingConfig {
+        &self.config
+    }
+
+    /// Recommendations for the next `horizon_hours` (the configured default
+    /// when `None`), for one service or all, over series matching `tags`
+    pub async fn recommend(
+        &self,
+        horizon_hours: Option<i64>,
+        service: Option<&str>,
+        tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<ScalingRecommendation>> {
+        let now = Utc::now();
+        let horizon = horizon_hours
+            .unwrap_or(self.config.default_horizon_hours)
+            .clamp(1, self.config.max_horizon_hours.max(1)) as usize;
+        // The current hour is still filling, so history ends at the last complete one
+        let end = now.duration_trunc(Duration::hours(1))?;
+        let start = end - Duration::hours(self.config.history_hours.max(1));
+
+        let rows = self
+            .database
+            .query_aggregates_cached(
+                &self.config.throughput_metric,
+                TimeWindow::OneHour,
+                start,
+                end,
+                tags,
+            )
+            .await?
+            .rows;
+
+        let mut recommendations = Vec::new();
+        for (name, history) in service_throughput(&rows) {
+            if service.is_some_and(|s| s != name) {
+                continue;
+            }
+            let recommendation = recommend(&name, &history, horizon, &self.config, now)?;
+            debug!(
+                service = %name,
+                replicas = recommendation.recommended_replicas,
+                "Built scaling recommendation"
+            );
+            recommendations.push(recommendation);
+        }
+        Ok(recommendations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hour(h: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 / 3600 * 3600, 0).unwrap() + Duration::hours(h)
+    }
+
+    fn row(service: Option<&str>, model: &str, h: i64, avg: f64) -> AggregatedMetricRow {
+        let mut tags = json!({"model": model});
+        if let Some(service) = service {
+            tags["service"] = json!(service);
+        }
+        AggregatedMetricRow {
+            metric_name: "requests_per_second".to_string(),
+            time_window: "1h".to_string(),
+            window_start: hour(h),
+            tags,
+            avg,
+            min: avg,
+            max: avg,
+            p50: avg,
+            p95: avg,
+            p99: avg,
+            stddev: None,
+            count: 1,
+            sum: avg,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_sums_series_per_service_and_hour() {
+        let rows = vec![
+            row(Some("gateway"), "gpt-4", 0, 30.0),
+            row(Some("gateway"), "claude", 0, 20.0),
+            row(Some("gateway"), "gpt-4", 1, 40.0),
+            row(Some("batch"), "gpt-4", 0, 5.0),
+            row(None, "gpt-4", 0, 99.0),
+        ];
+        let throughput = service_throughput(&rows);
+        assert_eq!(throughput.len(), 2);
+        assert_eq!(
+            throughput["gateway"],
+            vec![(hour(0), 50.0), (hour(1), 40.0)]
+        );
+        assert_eq!(throughput["batch"], vec![(hour(0), 5.0)]);
+    }
+
+    #[test]
+    fn test_replicas_for_capacity() {
+        let config = ScalingConfig {
+            capacity_per_replica: 100.0,
+            target_utilization: 0.5,
+            service_capacity: ScalingConfig::parse_capacities("gateway:200").unwrap(),
+            min_replicas: 2,
+            max_replicas: 10,
+            ..ScalingConfig::default()
+        };
+        // 50 usable rps per replica by default, 100 for the gateway
+        assert_eq!(config.replicas_for("batch", 260.0), 6);
+        assert_eq!(config.replicas_for("gateway", 260.0), 3);
+        assert_eq!(config.replicas_for("batch", 0.0), 2);
+        assert_eq!(config.replicas_for("batch", 10_000.0), 10);
+
+        assert!(ScalingConfig::parse_capacities("gateway").is_err());
+        assert!(ScalingConfig::parse_capacities("gateway:-1").is_err());
+    }
+
+    #[test]
+    fn test_recommends_for_growing_throughput() {
+        let config = ScalingConfig {
+            capacity_per_replica: 10.0,
+            target_utilization: 1.0,
+            ..ScalingConfig::default()
+        };
+        let history: Vec<(DateTime<Utc>, f64)> =
+            (0..24).map(|h| (hour(h), 20.0 + 2.0 * h as f64)).collect();
+
+        let recommendation = recommend("gateway", &history, 6, &config, hour(24)).unwrap();
+        assert_eq!(recommendation.method, ForecastMethod::Arima);
+        assert_eq!(recommendation.hours.len(), 6);
+        assert_eq!(recommendation.hours[0].hour, hour(24));
+        assert_eq!(recommendation.current_rps, 66.0);
+        assert_eq!(recommendation.current_replicas, 7);
+        assert!(recommendation.peak_rps > recommendation.current_rps);
+        assert!(recommendation.recommended_replicas >= recommendation.current_replicas);
+        assert_eq!(
+            recommendation.recommended_replicas,
+            recommendation
+                .hours
+                .iter()
+                .map(|h| h.replicas)
+                .max()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_short_history_uses_smoothing() {
+        let config = ScalingConfig::default();
+        let history = vec![(hour(0), 100.0), (hour(1), 100.0)];
+        let recommendation = recommend("gateway", &history, 3, &config, hour(2)).unwrap();
+        assert_eq!(
+            recommendation.method,
+            ForecastMethod::ExponentialSmoothing { alpha: 0.3 }
+        );
+        // 100 rps at 37.5 usable rps per replica, plus the forecast's upper bound
+        assert_eq!(recommendation.current_replicas, 3);
+        assert!(recommendation.recommended_replicas >= 3);
+
+        assert!(recommend("gateway", &[], 3, &config, hour(0)).is_err());
+    }
+}
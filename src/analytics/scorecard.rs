@@ -0,0 +1,429 @@
+//! Model Performance Scorecards
+//!
+//! Joins observed latency, error rate, and throughput from aggregated metrics with
+//! registry-declared performance and pricing into periodic per-model scorecards.
+
+use crate::adapters::registry::{
+    ModelMetadata, ModelPerformance, ModelPricing, ModelQuery, RegistryAdapter,
+};
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::metrics::TimeWindow;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Aggregated metric holding request latency per model
+pub const LATENCY_METRIC: &str = "total_latency_ms";
+
+/// Aggregated metric holding error rate per model
+pub const ERROR_RATE_METRIC: &str = "error_rate_percent";
+
+/// Aggregated metric holding token throughput per model
+pub const THROUGHPUT_METRIC: &str = "tokens_per_second";
+
+/// Weights of the latency, throughput, and reliability components
+const SCORE_WEIGHTS: [f64; 3] = [0.4, 0.3, 0.3];
+
+/// Letter grade derived from the overall score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScorecardGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl ScorecardGrade {
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 90.0 => ScorecardGrade::A,
+            s if s >= 80.0 => ScorecardGrade::B,
+            s if s >= 70.0 => ScorecardGrade::C,
+            s if s >= 60.0 => ScorecardGrade::D,
+            _ => ScorecardGrade::F,
+        }
+    }
+}
+
+/// Performance observed from aggregated telemetry over the scorecard period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObservedPerformance {
+    pub avg_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+    pub p99_latency_ms: Option<f64>,
+    pub error_rate_percent: Option<f64>,
+    pub tokens_per_second: Option<f64>,
+    /// Latency samples behind the observation
+    pub sample_count: u64,
+}
+
+impl ObservedPerformance {
+    /// Summarize aggregated metric windows
+    ///
+    /// Averages and percentiles are weighted by each window's sample count, which
+    /// approximates the true percentile without access to raw samples.
+    pub fn from_rows(
+        latency: &[AggregatedMetricRow],
+        error_rate: &[AggregatedMetricRow],
+        throughput: &[AggregatedMetricRow],
+    ) -> Self {
+        Self {
+            avg_latency_ms: weighted(latency, |r| r.avg),
+            p95_latency_ms: weighted(latency, |r| r.p95),
+            p99_latency_ms: weighted(latency, |r| r.p99),
+            error_rate_percent: weighted(error_rate, |r| r.avg),
+            tokens_per_second: weighted(throughput, |r| r.avg),
+            sample_count: latency.iter().map(|r| r.count.max(0) as u64).sum(),
+        }
+    }
+
+    /// Observed availability in percent
+    pub fn availability(&self) -> Option<f64> {
+        self.error_rate_percent
+            .map(|e| (100.0 - e).clamp(0.0, 100.0))
+    }
+}
+
+fn weighted(
+    rows: &[AggregatedMetricRow],
+    value: impl Fn(&AggregatedMetricRow) -> f64,
+) -> Option<f64> {
+    let total: i64 = rows.iter().map(|r| r.count.max(0)).sum();
+    if total == 0 {
+        return None;
+    }
+    Some(
+        rows.iter()
+            .map(|r| value(r) * r.count.max(0) as f64)
+            .sum::<f64>()
+            / total as f64,
+    )
+}
+
+/// Periodic scorecard for one model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelScorecard {
+    pub model_id: String,
+    pub name: String,
+    pub provider: String,
+    pub version: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub declared: ModelPerformance,
+    pub observed: ObservedPerformance,
+    pub pricing: ModelPricing,
+    /// Observed p95 latency relative to the declared p95 (positive is slower)
+    pub latency_vs_declared_pct: Option<f64>,
+    /// Observed throughput relative to the declared throughput (negative is slower)
+    pub throughput_vs_declared_pct: Option<f64>,
+    /// Overall score (0 - 100); `None` without enough observations to compare
+    pub score: Option<f64>,
+    pub grade: Option<ScorecardGrade>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl ModelScorecard {
+    /// Build a scorecard from registry metadata and observed performance
+    pub fn build(
+        model: &ModelMetadata,
+        observed: ObservedPerformance,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Self {
+        let declared = &model.performance;
+        let relative = |observed: Option<f64>, declared: f64| {
+            observed
+                .filter(|_| declared > 0.0)
+                .map(|o| (o - declared) / declared * 100.0)
+        };
+
+        // Each component is 1.0 when the model meets or beats its declared figure
+        let components = [
+            observed
+                .p95_latency_ms
+                .filter(|o| *o > 0.0 && declared.p95_latency_ms > 0.0)
+                .map(|o| (declared.p95_latency_ms / o).min(1.0)),
+            observed
+                .tokens_per_second
+                .filter(|_| declared.tokens_per_second > 0.0)
+                .map(|o| (o / declared.tokens_per_second).min(1.0)),
+            observed
+                .availability()
+                .filter(|_| declared.availability > 0.0)
+                .map(|o| (o / declared_availability_pct(declared.availability)).min(1.0)),
+        ];
+
+        let (weighted_sum, weight) = components
+            .iter()
+            .zip(SCORE_WEIGHTS)
+            .filter_map(|(c, w)| c.map(|c| (c * w, w)))
+            .fold((0.0, 0.0), |(s, t), (c, w)| (s + c, t + w));
+        let score = (weight > 0.0).then(|| weighted_sum / weight * 100.0);
+
+        Self {
+            model_id: model.model_id.clone(),
+            name: model.name.clone(),
+            provider: model.provider.clone(),
+            version: model.version.clone(),
+            period_start,
+            period_end,
+            latency_vs_declared_pct: relative(observed.p95_latency_ms, declared.p95_latency_ms),
+            throughput_vs_declared_pct: relative(
+                observed.tokens_per_second,
+                declared.tokens_per_second,
+            ),
+            declared: declared.clone(),
+            observed,
+            pricing: model.pricing.clone(),
+            score,
+            grade: score.map(ScorecardGrade::from_score),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Average of input and output price per 1K tokens
+    pub fn blended_cost_per_1k_tokens(&self) -> f64 {
+        (self.pricing.input_cost_per_1k_tokens + self.pricing.output_cost_per_1k_tokens) / 2.0
+    }
+}
+
+/// Registry availability may be declared as a fraction or a percentage
+fn declared_availability_pct(availability: f64) -> f64 {
+    if availability <= 1.0 {
+        availability * 100.0
+    } else {
+        availability
+    }
+}
+
+/// Scorecard generation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScorecardConfig {
+    /// Length of each scorecard period
+    pub period_secs: i64,
+    /// Aggregation window read from the metrics store
+    pub time_window: TimeWindow,
+}
+
+impl Default for ScorecardConfig {
+    fn default() -> Self {
+        Self {
+            period_secs: 86400,
+            time_window: TimeWindow::OneHour,
+        }
+    }
+}
+
+/// Generates and persists scorecards for every registered model
+pub struct ScorecardGenerator {
+    registry: Arc<RegistryAdapter>,
+    database: Arc<Database>,
+    config: ScorecardConfig,
+}
+
+impl ScorecardGenerator {
+    pub fn new(
+        registry: Arc<RegistryAdapter>,
+        database: Arc<Database>,
+        config: ScorecardConfig,
+    ) -> Self {
+        Self {
+            registry,
+            database,
+            config,
+        }
+    }
+
+    /// Observed performance of one model over a period
+    async fn observe(
+        &self,
+        model_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<ObservedPerformance> {
+        let tags = serde_json::json!({ "model_id": model_id });
+        let window = self.config.time_window;
+        let (latency, error_rate, throughput) = tokio::try_join!(
+            self.database.query_aggregated_metrics_tagged(
+                LATENCY_METRIC,
+                window,
+                start,
+                end,
+                &tags
+            ),
+            self.database.query_aggregated_metrics_tagged(
+                ERROR_RATE_METRIC,
+                window,
+                start,
+                end,
+                &tags
+            ),
+            self.database.query_aggregated_metrics_tagged(
+                THROUGHPUT_METRIC,
+                window,
+                start,
+                end,
+                &tags
+            ),
+        )?;
+        Ok(ObservedPerformance::from_rows(
+            &latency,
+            &error_rate,
+            &throughput,
+        ))
+    }
+
+    /// Generate scorecards for the period ending at `end`
+    #[instrument(skip(self))]
+    pub async fn generate(&self, end: DateTime<Utc>) -> Result<Vec<ModelScorecard>> {
+        let start = end - Duration::seconds(self.config.period_secs);
+        let models = self.registry.list_models(ModelQuery::default()).await?;
+
+        let mut scorecards = Vec::with_capacity(models.len());
+        for model in &models {
+            match self.observe(&model.model_id, start, end).await {
+                Ok(observed) => scorecards.push(ModelScorecard::build(model, observed, start, end)),
+                Err(e) => warn!(model_id = %model.model_id, error = %e, "Failed to observe model"),
+            }
+        }
+
+        Ok(scorecards)
+    }
+
+    /// Generate scorecards for the period ending now and persist them for trending
+    pub async fn generate_and_store(&self) -> Result<Vec<ModelScorecard>> {
+        let scorecards = self.generate(Utc::now()).await?;
+        for scorecard in &scorecards {
+            self.database.store_model_scorecard(scorecard).await?;
+        }
+        info!(count = scorecards.len(), "Stored model scorecards");
+        Ok(scorecards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::registry::{ModelStatus, ModelType};
+    use std::collections::HashMap;
+
+    fn row(metric: &str, avg: f64, p95: f64, count: i64) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: metric.to_string(),
+            time_window: "1h".to_string(),
+            window_start: Utc::now(),
+            tags: serde_json::json!({}),
+            avg,
+            min: avg,
+            max: p95,
+            p50: avg,
+            p95,
+            p99: p95,
+            stddev: None,
+            count,
+            sum: avg * count as f64,
+            histogram: None,
+        }
+    }
+
+    fn model(p95_latency_ms: f64, tokens_per_second: f64, availability: f64) -> ModelMetadata {
+        ModelMetadata {
+            model_id: "gpt-4".to_string(),
+            name: "GPT-4".to_string(),
+            version: "0613".to_string(),
+            provider: "openai".to_string(),
+            model_type: ModelType::TextGeneration,
+            capabilities: Vec::new(),
+            context_window: 8192,
+            pricing: ModelPricing {
+                currency: "USD".to_string(),
+                input_cost_per_1k_tokens: 0.03,
+                output_cost_per_1k_tokens: 0.06,
+                image_cost_per_unit: None,
+                audio_cost_per_minute: None,
+            },
+            performance: ModelPerformance {
+                avg_latency_ms: 0.0,
+                p95_latency_ms,
+                p99_latency_ms: 0.0,
+                tokens_per_second,
+                availability,
+            },
+            status: ModelStatus::Active,
+            registered_at: Utc::now(),
+            last_updated: Utc::now(),
+            tags: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_observed_performance_is_count_weighted() {
+        let observed = ObservedPerformance::from_rows(
+            &[
+                row(LATENCY_METRIC, 100.0, 200.0, 30),
+                row(LATENCY_METRIC, 200.0, 400.0, 10),
+            ],
+            &[row(ERROR_RATE_METRIC, 1.0, 1.0, 4)],
+            &[],
+        );
+
+        assert_eq!(observed.avg_latency_ms, Some(125.0));
+        assert_eq!(observed.p95_latency_ms, Some(250.0));
+        assert_eq!(observed.availability(), Some(99.0));
+        assert_eq!(observed.tokens_per_second, None);
+        assert_eq!(observed.sample_count, 40);
+    }
+
+    #[test]
+    fn test_scorecard_meeting_declared_performance() {
+        let observed = ObservedPerformance {
+            p95_latency_ms: Some(800.0),
+            tokens_per_second: Some(60.0),
+            error_rate_percent: Some(0.05),
+            ..Default::default()
+        };
+        let scorecard = ModelScorecard::build(
+            &model(1000.0, 50.0, 0.999),
+            observed,
+            Utc::now(),
+            Utc::now(),
+        );
+
+        assert_eq!(scorecard.score, Some(100.0));
+        assert_eq!(scorecard.grade, Some(ScorecardGrade::A));
+        assert_eq!(scorecard.latency_vs_declared_pct, Some(-20.0));
+        assert!((scorecard.blended_cost_per_1k_tokens() - 0.045).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scorecard_penalizes_slow_model() {
+        let observed = ObservedPerformance {
+            p95_latency_ms: Some(2000.0),
+            tokens_per_second: Some(25.0),
+            ..Default::default()
+        };
+        let scorecard =
+            ModelScorecard::build(&model(1000.0, 50.0, 99.9), observed, Utc::now(), Utc::now());
+
+        // Latency and throughput both at half the declared figure; no error-rate data
+        assert_eq!(scorecard.score, Some(50.0));
+        assert_eq!(scorecard.grade, Some(ScorecardGrade::F));
+        assert_eq!(scorecard.latency_vs_declared_pct, Some(100.0));
+        assert_eq!(scorecard.throughput_vs_declared_pct, Some(-50.0));
+    }
+
+    #[test]
+    fn test_scorecard_without_observations() {
+        let scorecard = ModelScorecard::build(
+            &model(1000.0, 50.0, 99.9),
+            ObservedPerformance::default(),
+            Utc::now(),
+            Utc::now(),
+        );
+        assert!(scorecard.score.is_none());
+        assert!(scorecard.grade.is_none());
+    }
+}
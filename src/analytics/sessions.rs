@@ -0,0 +1,338 @@
+//! Session Analytics
+//!
+//! Periodically pulls the interaction graph and memory snapshot of every
+//! recently active session from LLM-Memory-Graph, derives engagement metrics
+//! (conversation depth, topic diversity, retrieval cache hit rate, ...), and
+//! stores their distribution across sessions as aggregated metrics so trends
+//! can be read through the metrics API like any other series.
+
+use crate::adapters::memory_graph::{InteractionGraph, MemoryGraphAdapter, MemorySnapshot};
+use crate::database::Database;
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Prefix of the aggregated metrics written by the job
+pub const SESSION_METRIC_PREFIX: &str = "session";
+
+/// Engagement metrics derived for one session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEngagement {
+    pub session_id: String,
+    /// Longest chain of interactions in the session graph
+    pub depth: f64,
+    pub interactions: f64,
+    pub topic_count: f64,
+    /// Normalized entropy of interactions across topics (0 = one topic, 1 = even spread)
+    pub topic_diversity: f64,
+    pub retrieval_cache_hit_rate: f64,
+    pub retrieval_latency_ms: f64,
+    pub context_window_tokens: f64,
+}
+
+impl SessionEngagement {
+    pub fn from_memory_graph(graph: &InteractionGraph, snapshot: &MemorySnapshot) -> Self {
+        let topic_weights: Vec<f64> = graph
+            .topics
+            .iter()
+            .map(|t| t.node_ids.len().max(1) as f64)
+            .collect();
+
+        Self {
+            session_id: graph.session_id.clone(),
+            depth: graph.statistics.diameter as f64,
+            interactions: graph.statistics.node_count as f64,
+            topic_count: graph.topics.len() as f64,
+            topic_diversity: normalized_entropy(&topic_weights),
+            retrieval_cache_hit_rate: snapshot.retrieval_stats.cache_hit_rate,
+            retrieval_latency_ms: snapshot.retrieval_stats.avg_latency_ms,
+            context_window_tokens: snapshot.context_window_tokens as f64,
+        }
+    }
+
+    /// `(metric suffix, value)` pairs stored for the session
+    pub fn metrics(&self) -> [(&'static str, f64); 7] {
+        [
+            ("depth", self.depth),
+            ("interactions", self.interactions),
+            ("topic_count", self.topic_count),
+            ("topic_diversity", self.topic_diversity),
+            ("retrieval_cache_hit_rate", self.retrieval_cache_hit_rate),
+            ("retrieval_latency_ms", self.retrieval_latency_ms),
+            ("context_window_tokens", self.context_window_tokens),
+        ]
+    }
+}
+
+/// Shannon entropy of the weights divided by its maximum, in `[0, 1]`
+pub fn normalized_entropy(weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+    if weights.len() < 2 || total <= 0.0 {
+        return 0.0;
+    }
+
+    let entropy: f64 = weights
+        .iter()
+        .filter(|w| **w > 0.0)
+        .map(|w| {
+            let p = w / total;
+            -p * p.ln()
+        })
+        .sum();
+    entropy / (weights.len() as f64).ln()
+}
+
+/// Name of the aggregated metric for an engagement measure, e.g. `session.depth`
+pub fn session_metric_name(suffix: &str) -> String {
+    format!("{}.{}", SESSION_METRIC_PREFIX, suffix)
+}
+
+/// Session analytics configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAnalyticsConfig {
+    /// Aggregation window, and how often the job runs
+    pub window: TimeWindow,
+    /// Upper bound on sessions fetched per run
+    pub max_sessions: usize,
+}
+
+impl Default for SessionAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            window: TimeWindow::FiveMinutes,
+            max_sessions: 1000,
+        }
+    }
+}
+
+impl SessionAnalyticsConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            window: std::env::var("SESSION_ANALYTICS_WINDOW")
+                .ok()
+                .and_then(|v| crate::database::timescale::parse_window(&v).ok())
+                .unwrap_or(defaults.window),
+            max_sessions: std::env::var("SESSION_ANALYTICS_MAX_SESSIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_sessions),
+        }
+    }
+}
+
+/// Result of one session analytics pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAnalyticsReport {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub sessions: Vec<SessionEngagement>,
+    /// Sessions whose graph or snapshot could not be fetched
+    pub failed_sessions: usize,
+}
+
+/// Periodic session engagement job
+pub struct SessionAnalyticsJob {
+    memory_graph: Arc<MemoryGraphAdapter>,
+    database: Arc<Database>,
+    config: SessionAnalyticsConfig,
+    runs: AtomicU64,
+    sessions_analyzed: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl SessionAnalyticsJob {
+    pub fn new(
+        memory_graph: Arc<MemoryGraphAdapter>,
+        database: Arc<Database>,
+        config: SessionAnalyticsConfig,
+    ) -> Self {
+        Self {
+            memory_graph,
+            database,
+            config,
+            runs: AtomicU64::new(0),
+            sessions_analyzed: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Analyze sessions active in the last complete window before `now` and
+    /// store the cross-session distribution of each engagement metric
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<SessionAnalyticsReport> {
+        let seconds = self.config.window.to_seconds() as i64;
+        let window_end =
+            DateTime::from_timestamp((now.timestamp() / seconds) * seconds, 0).unwrap_or(now);
+        let window_start = window_end - Duration::seconds(seconds);
+
+        let mut session_ids = self
+            .memory_graph
+            .fetch_active_sessions(window_start, window_end)
+            .await?;
+        if session_ids.len() > self.config.max_sessions {
+            warn!(
+                active = session_ids.len(),
+                max = self.config.max_sessions,
+                "Too many active sessions, analyzing a subset"
+            );
+            session_ids.truncate(self.config.max_sessions);
+        }
+
+        let mut sessions = Vec::with_capacity(session_ids.len());
+        let mut failed_sessions = 0;
+        for session_id in &session_ids {
+            let fetched = tokio::try_join!(
+                self.memory_graph.fetch_interaction_graph(session_id),
+                self.memory_graph.fetch_memory_snapshot(session_id),
+            );
+            match fetched {
+                Ok((graph, snapshot)) => {
+                    sessions.push(SessionEngagement::from_memory_graph(&graph, &snapshot))
+                }
+                Err(e) => {
+                    debug!(session_id = %session_id, "Skipping session: {}", e);
+                    failed_sessions += 1;
+                }
+            }
+        }
+
+        if let Some(first) = sessions.first() {
+            let tags = serde_json::json!({});
+            for (i, (suffix, _)) in first.metrics().iter().enumerate() {
+                let values: Vec<f64> = sessions.iter().map(|s| s.metrics()[i].1).collect();
+                self.database
+                    .store_aggregated_metric(
+                        &session_metric_name(suffix),
+                        self.config.window,
+                        window_start,
+                        &tags,
+                        &StatisticalMeasures::from_values(&values),
+                    )
+                    .await?;
+            }
+        }
+
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.sessions_analyzed
+            .fetch_add(sessions.len() as u64, Ordering::Relaxed);
+        info!(
+            sessions = sessions.len(),
+            failed = failed_sessions,
+            "Session analytics pass complete"
+        );
+
+        Ok(SessionAnalyticsReport {
+            window_start,
+            window_end,
+            sessions,
+            failed_sessions,
+        })
+    }
+
+    /// Run once per window until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.window.to_seconds());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick fires immediately; wait for a full window instead
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run_once(Utc::now()).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Session analytics pass failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> SessionAnalyticsStats {
+        SessionAnalyticsStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            sessions_analyzed: self.sessions_analyzed.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Session analytics statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAnalyticsStats {
+    pub runs: u64,
+    pub sessions_analyzed: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory_graph::{GraphStatistics, RetrievalStats, TopicCluster};
+
+    fn topic(name: &str, nodes: usize) -> TopicCluster {
+        TopicCluster {
+            cluster_id: name.to_string(),
+            topic: name.to_string(),
+            relevance_score: 1.0,
+            node_ids: (0..nodes).map(|i| format!("{}-{}", name, i)).collect(),
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_normalized_entropy() {
+        assert_eq!(normalized_entropy(&[]), 0.0);
+        assert_eq!(normalized_entropy(&[5.0]), 0.0);
+        assert!((normalized_entropy(&[2.0, 2.0, 2.0]) - 1.0).abs() < 1e-12);
+        assert!(normalized_entropy(&[10.0, 1.0]) < 0.5);
+    }
+
+    #[test]
+    fn test_engagement_from_memory_graph() {
+        let now = Utc::now();
+        let graph = InteractionGraph {
+            graph_id: "g".to_string(),
+            session_id: "s-1".to_string(),
+            created_at: now,
+            last_updated: now,
+            statistics: GraphStatistics {
+                node_count: 12,
+                edge_count: 11,
+                avg_degree: 1.8,
+                clustering_coefficient: 0.1,
+                diameter: 6,
+                density: 0.2,
+            },
+            topics: vec![topic("billing", 6), topic("refunds", 6)],
+            entities: Vec::new(),
+        };
+        let snapshot = MemorySnapshot {
+            snapshot_id: "m".to_string(),
+            session_id: "s-1".to_string(),
+            created_at: now,
+            context_window_tokens: 4096,
+            summarized_tokens: 1024,
+            active_memories: Vec::new(),
+            retrieval_stats: RetrievalStats {
+                total_retrievals: 10,
+                avg_latency_ms: 12.5,
+                cache_hit_rate: 0.8,
+                relevance_avg: 0.7,
+            },
+        };
+
+        let engagement = SessionEngagement::from_memory_graph(&graph, &snapshot);
+        assert_eq!(engagement.depth, 6.0);
+        assert_eq!(engagement.topic_count, 2.0);
+        assert!((engagement.topic_diversity - 1.0).abs() < 1e-12);
+        assert_eq!(engagement.retrieval_cache_hit_rate, 0.8);
+        assert_eq!(
+            session_metric_name(engagement.metrics()[0].0),
+            "session.depth"
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! Shadow Evaluation
+//!
+//! Runs a candidate anomaly algorithm or forecast model next to the primary one
+//! on the same data points. Only the primary's decisions are acted on; the
+//! candidate's would-have-fired decisions are recorded and tallied against the
+//! primary so a rollout can be judged on agreement rate and on the detections
+//! the candidate adds or misses before it replaces anything.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use super::anomaly::AnomalyDetector;
+use super::changepoint::ChangepointDetector;
+use super::prediction::PredictionEngine;
+
+/// Fired decisions kept for inspection
+const DEFAULT_DECISION_LOG_SIZE: usize = 1000;
+
+/// Anomaly algorithm that can run as primary or candidate
+pub trait AnomalyModel: Send + Sync {
+    /// Name shown in comparison reports
+    fn name(&self) -> &str;
+
+    /// Learn from a data point and report whether it is anomalous
+    fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<bool>;
+}
+
+impl AnomalyModel for AnomalyDetector {
+    fn name(&self) -> &str {
+        "zscore"
+    }
+
+    fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<bool> {
+        Ok(self.check_anomaly(metric_name, value, timestamp)?.is_some())
+    }
+}
+
+impl AnomalyModel for ChangepointDetector {
+    fn name(&self) -> &str {
+        "changepoint"
+    }
+
+    fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<bool> {
+        Ok(ChangepointDetector::observe(self, metric_name, value, timestamp).is_some())
+    }
+}
+
+/// Forecast model that can run as primary or candidate
+pub trait ForecastModel: Send + Sync {
+    /// Name shown in comparison reports
+    fn name(&self) -> &str;
+
+    /// Learn from a data point
+    fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<()>;
+
+    /// Forecast of the next data point; `None` while the model is warming up
+    fn forecast_next(&self, metric_name: &str) -> Option<f64>;
+}
+
+/// Forecasting method of a `PredictionEngine`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastMethod {
+    Arima,
+    ExponentialSmoothing { alpha: f64 },
+}
+
+/// `PredictionEngine` pinned to one forecasting method
+pub struct PredictionModel {
+    engine: PredictionEngine,
+    method: ForecastMethod,
+    name: String,
+}
+
+impl PredictionModel {
+    pub fn new(engine: PredictionEngine, method: ForecastMethod) -> Self {
+        let name = match method {
+            ForecastMethod::Arima => "arima".to_string(),
+            ForecastMethod::ExponentialSmoothing { alpha } => {
+                format!("exponential_smoothing(alpha={})", alpha)
+            }
+        };
+        Self {
+            engine,
+            method,
+            name,
+        }
+    }
+}
+
+impl ForecastModel for PredictionModel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<()> {
+        self.engine.add_data_point(metric_name, value, timestamp)
+    }
+
+    fn forecast_next(&self, metric_name: &str) -> Option<f64> {
+        let points = match self.method {
+            ForecastMethod::Arima => self.engine.predict_arima(metric_name, 1),
+            ForecastMethod::ExponentialSmoothing { alpha } => self
+                .engine
+                .predict_exponential_smoothing(metric_name, 1, alpha),
+        };
+        points.ok()?.first().map(|point| point.value)
+    }
+}
+
+/// Data point on which at least one of the two anomaly models fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowDecision {
+    pub metric_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    pub primary_fired: bool,
+    /// What the candidate would have done; never alerted on
+    pub candidate_fired: bool,
+}
+
+impl ShadowDecision {
+    pub fn agrees(&self) -> bool {
+        self.primary_fired == self.candidate_fired
+    }
+}
+
+/// Decision counts for one metric, or for all of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgreementTally {
+    pub observations: u64,
+    pub both_fired: u64,
+    /// Fired by the candidate only
+    pub extra_detections: u64,
+    /// Fired by the primary only
+    pub missed_detections: u64,
+}
+
+impl AgreementTally {
+    fn record(&mut self, primary_fired: bool, candidate_fired: bool) {
+        self.observations += 1;
+        match (primary_fired, candidate_fired) {
+            (true, true) => self.both_fired += 1,
+            (false, true) => self.extra_detections += 1,
+            (true, false) => self.missed_detections += 1,
+            (false, false) => {}
+        }
+    }
+
+    fn merge(&mut self, other: &AgreementTally) {
+        self.observations += other.observations;
+        self.both_fired += other.both_fired;
+        self.extra_detections += other.extra_detections;
+        self.missed_detections += other.missed_detections;
+    }
+
+    /// Share of observations on which both models made the same call
+    pub fn agreement_rate(&self) -> Option<f64> {
+        (self.observations > 0).then(|| {
+            let disagreements = self.extra_detections + self.missed_detections;
+            1.0 - disagreements as f64 / self.observations as f64
+        })
+    }
+
+    /// Share of the primary's detections the candidate also made
+    pub fn detection_overlap(&self) -> Option<f64> {
+        let primary = self.both_fired + self.missed_detections;
+        (primary > 0).then(|| self.both_fired as f64 / primary as f64)
+    }
+}
+
+/// Per-metric line of a comparison report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricAgreement {
+    pub metric_name: String,
+    pub tally: AgreementTally,
+    pub agreement_rate: Option<f64>,
+}
+
+/// Candidate versus primary anomaly model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowReport {
+    pub primary: String,
+    pub candidate: String,
+    pub generated_at: DateTime<Utc>,
+    pub totals: AgreementTally,
+    pub agreement_rate: Option<f64>,
+    pub detection_overlap: Option<f64>,
+    /// Data points the candidate failed on; they count as not fired
+    pub candidate_errors: u64,
+    /// Metrics with the most disagreements first
+    pub metrics: Vec<MetricAgreement>,
+}
+
+/// Runs a candidate anomaly model in the shadow of the primary
+pub struct ShadowDetector {
+    primary: Arc<dyn AnomalyModel>,
+    candidate: Arc<dyn AnomalyModel>,
+    tallies: DashMap<String, AgreementTally>,
+    decisions: Mutex<VecDeque<ShadowDecision>>,
+    decision_log_size: usize,
+    candidate_errors: AtomicU64,
+}
+
+impl ShadowDetector {
+    pub fn new(primary: Arc<dyn AnomalyModel>, candidate: Arc<dyn AnomalyModel>) -> Self {
+        Self {
+            primary,
+            candidate,
+            tallies: DashMap::new(),
+            decisions: Mutex::new(VecDeque::new()),
+            decision_log_size: DEFAULT_DECISION_LOG_SIZE,
+            candidate_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Keep at most `size` fired decisions
+    pub fn with_decision_log_size(mut self, size: usize) -> Self {
+        self.decision_log_size = size;
+        self
+    }
+
+    /// Feed a data point to both models and return the primary's decision.
+    ///
+    /// Candidate failures are logged and counted but never surface to the
+    /// caller, so a broken candidate cannot disturb production detection.
+    pub fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<bool> {
+        let primary_fired = self.primary.observe(metric_name, value, timestamp)?;
+        let candidate_fired = match self.candidate.observe(metric_name, value, timestamp) {
+            Ok(fired) => fired,
+            Err(e) => {
+                self.candidate_errors.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    candidate = self.candidate.name(),
+                    metric_name, "Shadow candidate failed: {:#}", e
+                );
+                false
+            }
+        };
+
+        self.tallies
+            .entry(metric_name.to_string())
+            .or_default()
+            .record(primary_fired, candidate_fired);
+
+        if primary_fired || candidate_fired {
+            let decision = ShadowDecision {
+                metric_name: metric_name.to_string(),
+                timestamp,
+                value,
+                primary_fired,
+                candidate_fired,
+            };
+            if !decision.agrees() {
+                debug!(
+                    metric_name,
+                    primary_fired, candidate_fired, "Shadow candidate disagrees with primary"
+                );
+            }
+            let mut decisions = self.decisions.lock();
+            decisions.push_back(decision);
+            while decisions.len() > self.decision_log_size {
+                decisions.pop_front();
+            }
+        }
+
+        Ok(primary_fired)
+    }
+
+    /// Most recent fired decisions, newest first
+    pub fn recent_decisions(&self, limit: usize) -> Vec<ShadowDecision> {
+        self.decisions
+            .lock()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Most recent decisions on which the models disagreed, newest first
+    pub fn disagreements(&self, limit: usize) -> Vec<ShadowDecision> {
+        self.decisions
+            .lock()
+            .iter()
+            .rev()
+            .filter(|decision| !decision.agrees())
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Agreement so far
+    pub fn report(&self) -> ShadowReport {
+        let mut totals = AgreementTally::default();
+        let mut metrics: Vec<MetricAgreement> = self
+            .tallies
+            .iter()
+            .map(|entry| {
+                totals.merge(entry.value());
+                MetricAgreement {
+                    metric_name: entry.key().clone(),
+                    tally: *entry.value(),
+                    agreement_rate: entry.value().agreement_rate(),
+                }
+            })
+            .collect();
+        metrics.sort_by(|a, b| {
+            let disagreements =
+                |m: &MetricAgreement| m.tally.extra_detections + m.tally.missed_detections;
+            disagreements(b)
+                .cmp(&disagreements(a))
+                .then_with(|| a.metric_name.cmp(&b.metric_name))
+        });
+
+        ShadowReport {
+            primary: self.primary.name().to_string(),
+            candidate: self.candidate.name().to_string(),
+            generated_at: Utc::now(),
+            agreement_rate: totals.agreement_rate(),
+            detection_overlap: totals.detection_overlap(),
+            totals,
+            candidate_errors: self.candidate_errors.load(Ordering::Relaxed),
+            metrics,
+        }
+    }
+
+    /// Forget all tallies and decisions, e.g. after retuning the candidate
+    pub fn reset(&self) {
+        self.tallies.clear();
+        self.decisions.lock().clear();
+        self.candidate_errors.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Forecast error totals for one model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ForecastErrors {
+    pub samples: u64,
+    pub absolute_error: f64,
+    pub squared_error: f64,
+}
+
+impl ForecastErrors {
+    fn record(&mut self, forecast: f64, actual: f64) {
+        let error = forecast - actual;
+        self.samples += 1;
+        self.absolute_error += error.abs();
+        self.squared_error += error * error;
+    }
+
+    pub fn mae(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.absolute_error / self.samples as f64)
+    }
+
+    pub fn rmse(&self) -> Option<f64> {
+        (self.samples > 0).then(|| (self.squared_error / self.samples as f64).sqrt())
+    }
+}
+
+/// Candidate versus primary forecast model, scored on one-step-ahead forecasts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastShadowReport {
+    pub primary: String,
+    pub candidate: String,
+    pub generated_at: DateTime<Utc>,
+    /// Only data points both models forecast are scored
+    pub primary_errors: ForecastErrors,
+    pub candidate_errors: ForecastErrors,
+    /// Scored data points the candidate forecast more closely
+    pub candidate_wins: u64,
+}
+
+impl ForecastShadowReport {
+    /// Candidate MAE relative to the primary's; below 1.0 means the candidate is better
+    pub fn mae_ratio(&self) -> Option<f64> {
+        match (self.candidate_errors.mae(), self.primary_errors.mae()) {
+            (Some(candidate), Some(primary)) if primary > 0.0 => Some(candidate / primary),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ForecastScore {
+    primary: ForecastErrors,
+    candidate: ForecastErrors,
+    candidate_wins: u64,
+}
+
+/// Runs a candidate forecast model in the shadow of the primary
+pub struct ShadowForecaster {
+    primary: Arc<dyn ForecastModel>,
+    candidate: Arc<dyn ForecastModel>,
+    score: Mutex<ForecastScore>,
+}
+
+impl ShadowForecaster {
+    pub fn new(primary: Arc<dyn ForecastModel>, candidate: Arc<dyn ForecastModel>) -> Self {
+        Self {
+            primary,
+            candidate,
+            score: Mutex::new(ForecastScore::default()),
+        }
+    }
+
+    /// Score both models' forecasts against `value`, then teach it to both.
+    ///
+    /// Candidate failures are logged and skipped.
+    pub fn observe(&self, metric_name: &str, value: f64, timestamp: DateTime<Utc>) -> Result<()> {
+        let forecasts = (
+            self.primary.forecast_next(metric_name),
+            self.candidate.forecast_next(metric_name),
+        );
+        if let (Some(primary), Some(candidate)) = forecasts {
+            let mut score = self.score.lock();
+            score.primary.record(primary, value);
+            score.candidate.record(candidate, value);
+            if (candidate - value).abs() < (primary - value).abs() {
+                score.candidate_wins += 1;
+            }
+        }
+
+        self.primary.observe(metric_name, value, timestamp)?;
+        if let Err(e) = self.candidate.observe(metric_name, value, timestamp) {
+            warn!(
+                candidate = self.candidate.name(),
+                metric_name, "Shadow candidate failed: {:#}", e
+            );
+        }
+        Ok(())
+    }
+
+    /// Forecast accuracy so far
+    pub fn report(&self) -> ForecastShadowReport {
+        let score = self.score.lock();
+        ForecastShadowReport {
+            primary: self.primary.name().to_string(),
+            candidate: self.candidate.name().to_string(),
+            generated_at: Utc::now(),
+            primary_errors: score.primary,
+            candidate_errors: score.candidate,
+            candidate_wins: score.candidate_wins,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::{AnalyticsConfig, SharedConfig};
+    use chrono::Duration;
+
+    /// Fires when the value exceeds a fixed threshold
+    struct Threshold(f64);
+
+    impl AnomalyModel for Threshold {
+        fn name(&self) -> &str {
+            "threshold"
+        }
+
+        fn observe(&self, _: &str, value: f64, _: DateTime<Utc>) -> Result<bool> {
+            Ok(value > self.0)
+        }
+    }
+
+    struct Failing;
+
+    impl AnomalyModel for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn observe(&self, _: &str, _: f64, _: DateTime<Utc>) -> Result<bool> {
+            anyhow::bail!("model not loaded")
+        }
+    }
+
+    #[test]
+    fn test_agreement_report() {
+        let shadow = ShadowDetector::new(Arc::new(Threshold(10.0)), Arc::new(Threshold(5.0)));
+        let now = Utc::now();
+        for value in [1.0, 7.0, 8.0, 12.0, 2.0] {
+            assert_eq!(shadow.observe("latency", value, now).unwrap(), value > 10.0);
+        }
+
+        let report = shadow.report();
+        assert_eq!(report.totals.observations, 5);
+        assert_eq!(report.totals.both_fired, 1);
+        assert_eq!(report.totals.extra_detections, 2);
+        assert_eq!(report.totals.missed_detections, 0);
+        assert!((report.agreement_rate.unwrap() - 0.6).abs() < 1e-9);
+        assert_eq!(report.detection_overlap, Some(1.0));
+        assert_eq!(shadow.recent_decisions(10).len(), 3);
+        assert_eq!(shadow.disagreements(10).len(), 2);
+        assert_eq!(shadow.disagreements(10)[0].value, 8.0);
+    }
+
+    #[test]
+    fn test_candidate_failure_does_not_affect_primary() {
+        let shadow = ShadowDetector::new(Arc::new(Threshold(10.0)), Arc::new(Failing));
+        assert!(shadow.observe("latency", 50.0, Utc::now()).unwrap());
+
+        let report = shadow.report();
+        assert_eq!(report.candidate_errors, 1);
+        assert_eq!(report.totals.missed_detections, 1);
+    }
+
+    #[test]
+    fn test_forecast_comparison() {
+        let config = SharedConfig::new(Arc::new(AnalyticsConfig::default()));
+        let shadow = ShadowForecaster::new(
+            Arc::new(PredictionModel::new(
+                PredictionEngine::with_shared_config(config.clone()),
+                ForecastMethod::ExponentialSmoothing { alpha: 0.1 },
+            )),
+            Arc::new(PredictionModel::new(
+                PredictionEngine::with_shared_config(config),
+                ForecastMethod::ExponentialSmoothing { alpha: 0.9 },
+            )),
+        );
+
+        // A level shift: the faster-adapting candidate should track it better
+        let start = Utc::now();
+        for i in 0..40 {
+            let value = if i < 20 { 100.0 } else { 200.0 };
+            shadow
+                .observe("cost", value, start + Duration::minutes(i))
+                .unwrap();
+        }
+
+        let report = shadow.report();
+        assert_eq!(report.primary_errors.samples, 39);
+        assert!(report.candidate_wins > 0);
+        assert!(report.mae_ratio().unwrap() < 1.0);
+    }
+}
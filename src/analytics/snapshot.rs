@@ -0,0 +1,342 @@
+//! Anomaly Detector Snapshots
+//!
+//! Periodically persists the detector's rolling baselines so a restarted
+//! process resumes with warm statistics instead of a cold start that misses
+//! anomalies until the windows refill.
+//!
+//! Snapshots carry a format version; a snapshot written by an unknown format is
+//! rejected rather than misread. Snapshots older than the configured maximum
+//! age are expired, and individual baselines whose newest sample is older than
+//! that age are dropped on restore.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::anomaly::{AnomalyDetector, BaselineState};
+use super::AnalyticsEngine;
+use crate::database::Database;
+use crate::pipeline::hot_cache::{HotCache, HotCacheConfig};
+
+/// Current snapshot format version
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// A point-in-time copy of detector state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectorSnapshot {
+    pub format_version: u32,
+    pub taken_at: DateTime<Utc>,
+    pub baselines: Vec<BaselineState>,
+}
+
+impl DetectorSnapshot {
+    /// Capture the detector's current baselines
+    pub fn capture(detector: &AnomalyDetector) -> Self {
+        Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            taken_at: Utc::now(),
+            baselines: detector.export_baselines(),
+        }
+    }
+
+    /// Decode a stored snapshot, rejecting formats this build cannot read
+    pub fn from_json(value: serde_json::Value) -> Result<Self> {
+        let version = value
+            .get("format_version")
+            .and_then(|v| v.as_u64())
+            .context("Snapshot has no format_version")?;
+
+        if version != SNAPSHOT_FORMAT_VERSION as u64 {
+            bail!(
+                "Unsupported detector snapshot format {} (expected {})",
+                version,
+                SNAPSHOT_FORMAT_VERSION
+            );
+        }
+
+        serde_json::from_value(value).context("Failed to decode detector snapshot")
+    }
+
+    /// Whether the snapshot is older than `max_age`
+    pub fn is_expired(&self, max_age: Duration, now: DateTime<Utc>) -> bool {
+        self.taken_at < now - max_age
+    }
+
+    /// Drop baselines whose newest sample is older than `max_age`.
+    /// Returns the number of baselines removed.
+    pub fn prune_stale(&mut self, max_age: Duration, now: DateTime<Utc>) -> usize {
+        let cutoff = now - max_age;
+        let before = self.baselines.len();
+        self.baselines
+            .retain(|b| b.timestamps.last().map_or(false, |ts| *ts >= cutoff));
+        before - self.baselines.len()
+    }
+}
+
+/// Where snapshots are persisted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotStoreKind {
+    #[default]
+    Postgres,
+    Redis,
+}
+
+impl FromStr for SnapshotStoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown snapshot store '{}'", s))
+    }
+}
+
+/// Snapshot configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    pub store: SnapshotStoreKind,
+    /// Seconds between snapshots
+    pub interval_secs: u64,
+    /// Snapshots and baselines older than this are expired
+    pub max_age_secs: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            store: SnapshotStoreKind::default(),
+            interval_secs: 60,
+            max_age_secs: 86_400,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            store: match std::env::var("ANOMALY_SNAPSHOT_STORE") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.store,
+            },
+            interval_secs: std::env::var("ANOMALY_SNAPSHOT_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+            max_age_secs: std::env::var("ANOMALY_SNAPSHOT_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_age_secs),
+        })
+    }
+
+    pub fn max_age(&self) -> Duration {
+        Duration::seconds(self.max_age_secs as i64)
+    }
+}
+
+/// Durable storage for detector snapshots
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    /// Persist a snapshot, expiring anything older than `retention`
+    async fn save(&self, snapshot: &DetectorSnapshot, retention: Duration) -> Result<()>;
+
+    /// Most recent snapshot, undecoded
+    async fn load_latest(&self) -> Result<Option<serde_json::Value>>;
+}
+
+#[async_trait]
+impl SnapshotStore for Database {
+    async fn save(&self, snapshot: &DetectorSnapshot, retention: Duration) -> Result<()> {
+        self.store_detector_snapshot(
+            snapshot.format_version as i32,
+            snapshot.taken_at,
+            &serde_json::to_value(snapshot)?,
+        )
+        .await?;
+
+        let purged = self
+            .delete_detector_snapshots_before(snapshot.taken_at - retention)
+            .await?;
+        if purged > 0 {
+            debug!(purged, "Expired detector snapshots");
+        }
+        Ok(())
+    }
+
+    async fn load_latest(&self) -> Result<Option<serde_json::Value>> {
+        self.latest_detector_snapshot().await
+    }
+}
+
+/// Build the configured snapshot store. Postgres reuses the existing `database`.
+pub async fn connect_snapshot_store(
+    config: &SnapshotConfig,
+    database: Arc<Database>,
+) -> Result<Arc<dyn SnapshotStore>> {
+    match config.store {
+        SnapshotStoreKind::Postgres => Ok(database),
+        SnapshotStoreKind::Redis => Ok(Arc::new(
+            HotCache::connect(HotCacheConfig::from_env()).await?,
+        )),
+    }
+}
+
+/// Takes periodic snapshots of a detector and restores them on startup
+pub struct DetectorSnapshotter {
+    store: Arc<dyn SnapshotStore>,
+    config: SnapshotConfig,
+    snapshots_saved: AtomicU64,
+    baselines_restored: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl DetectorSnapshotter {
+    pub fn new(store: Arc<dyn SnapshotStore>, config: SnapshotConfig) -> Self {
+        Self {
+            store,
+            config,
+            snapshots_saved: AtomicU64::new(0),
+            baselines_restored: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Load the latest unexpired snapshot into `detector`, returning the number
+    /// of baselines restored
+    pub async fn restore(&self, detector: &AnomalyDetector) -> Result<usize> {
+        let Some(value) = self.store.load_latest().await? else {
+            info!("No detector snapshot found, starting with empty baselines");
+            return Ok(0);
+        };
+
+        let mut snapshot = DetectorSnapshot::from_json(value)?;
+        let now = Utc::now();
+        if snapshot.is_expired(self.config.max_age(), now) {
+            info!(taken_at = %snapshot.taken_at, "Detector snapshot expired, ignoring it");
+            return Ok(0);
+        }
+
+        let stale = snapshot.prune_stale(self.config.max_age(), now);
+        let restored = detector.restore_baselines(snapshot.baselines);
+        self.baselines_restored
+            .fetch_add(restored as u64, Ordering::Relaxed);
+
+        info!(
+            restored,
+            stale,
+            taken_at = %snapshot.taken_at,
+            "Restored anomaly detector baselines"
+        );
+        Ok(restored)
+    }
+
+    /// Snapshot `detector` now, returning the number of baselines saved
+    pub async fn snapshot(&self, detector: &AnomalyDetector) -> Result<usize> {
+        let snapshot = DetectorSnapshot::capture(detector);
+        self.store.save(&snapshot, self.config.max_age()).await?;
+        self.snapshots_saved.fetch_add(1, Ordering::Relaxed);
+        Ok(snapshot.baselines.len())
+    }
+
+    /// Snapshot the engine's detector on the configured interval until the
+    /// task is aborted
+    pub fn spawn(self: Arc<Self>, engine: Arc<AnalyticsEngine>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick fires immediately; skip it so a restore can run first
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.snapshot(engine.anomaly()).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Anomaly detector snapshot failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> SnapshotStats {
+        SnapshotStats {
+            snapshots_saved: self.snapshots_saved.load(Ordering::Relaxed),
+            baselines_restored: self.baselines_restored.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Snapshotter statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStats {
+    pub snapshots_saved: u64,
+    pub baselines_restored: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(metric_name: &str, last_seen: DateTime<Utc>) -> BaselineState {
+        BaselineState {
+            metric_name: metric_name.to_string(),
+            values: vec![1.0, 2.0],
+            timestamps: vec![last_seen - Duration::seconds(60), last_seen],
+            max_size: 100,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_format_version() {
+        let snapshot = DetectorSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            taken_at: Utc::now(),
+            baselines: vec![baseline("latency", Utc::now())],
+        };
+
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(
+            DetectorSnapshot::from_json(value.clone()).unwrap(),
+            snapshot
+        );
+
+        let mut future = value.clone();
+        future["format_version"] = serde_json::json!(SNAPSHOT_FORMAT_VERSION + 1);
+        assert!(DetectorSnapshot::from_json(future).is_err());
+
+        let mut unversioned = value;
+        unversioned
+            .as_object_mut()
+            .unwrap()
+            .remove("format_version");
+        assert!(DetectorSnapshot::from_json(unversioned).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_expiry() {
+        let now = Utc::now();
+        let max_age = Duration::hours(1);
+        let mut snapshot = DetectorSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            taken_at: now - Duration::minutes(5),
+            baselines: vec![
+                baseline("fresh", now - Duration::minutes(10)),
+                baseline("stale", now - Duration::hours(3)),
+            ],
+        };
+
+        assert!(!snapshot.is_expired(max_age, now));
+        assert!(snapshot.is_expired(max_age, now + Duration::hours(2)));
+
+        assert_eq!(snapshot.prune_stale(max_age, now), 1);
+        assert_eq!(snapshot.baselines.len(), 1);
+        assert_eq!(snapshot.baselines[0].metric_name, "fresh");
+    }
+}
@@ -0,0 +1,551 @@
+//! Security Posture Analytics
+//!
+//! Aggregates Sentinel threat events into trend metrics for the governance
+//! dashboard: threats per type and level per hour, mitigation resolution time
+//! distributions, and the most frequently attacked resources.
+
+use crate::database::{Database, EnvironmentScope, EventFilter};
+use crate::schemas::events::{
+    AnalyticsEvent, EventPayload, EventType, MitigationStatus, SecurityPayload, ThreatEvent,
+    ThreatLevel, ThreatType,
+};
+use crate::tenancy::TenantScope;
+use anyhow::Result;
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+/// Default number of resources reported in the top attacked list
+pub const DEFAULT_TOP_RESOURCES: usize = 10;
+
+/// Stable label for a threat type, matching its serialized form
+pub fn threat_type_label(threat_type: &ThreatType) -> String {
+    match threat_type {
+        ThreatType::Other(name) => name.clone(),
+        other => serde_json::to_value(other)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+/// Whether a mitigation status closes out the threat
+fn is_resolved(status: &MitigationStatus) -> bool {
+    matches!(
+        status,
+        MitigationStatus::Blocked | MitigationStatus::Mitigated | MitigationStatus::Resolved
+    )
+}
+
+/// Lifecycle of a single threat reconstructed from its events
+#[derive(Debug, Clone)]
+struct ThreatRecord {
+    threat_type: String,
+    threat_level: ThreatLevel,
+    target_resource: String,
+    first_seen: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Threat count for one hour, type, and level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreatHourBucket {
+    pub hour: DateTime<Utc>,
+    pub threat_type: String,
+    pub threat_level: ThreatLevel,
+    pub count: u64,
+}
+
+/// Distribution of time from detection to mitigation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionStats {
+    pub resolved: u64,
+    pub unresolved: u64,
+    pub mean_secs: Option<f64>,
+    pub p50_secs: Option<f64>,
+    pub p90_secs: Option<f64>,
+    pub p99_secs: Option<f64>,
+    pub max_secs: Option<f64>,
+}
+
+impl ResolutionStats {
+    fn from_durations(mut durations: Vec<f64>, unresolved: u64) -> Self {
+        durations.sort_by(|a, b| a.total_cmp(b));
+        let resolved = durations.len() as u64;
+        let mean_secs = if durations.is_empty() {
+            None
+        } else {
+            Some(durations.iter().sum::<f64>() / durations.len() as f64)
+        };
+
+        Self {
+            resolved,
+            unresolved,
+            mean_secs,
+            p50_secs: percentile(&durations, 50.0),
+            p90_secs: percentile(&durations, 90.0),
+            p99_secs: percentile(&durations, 99.0),
+            max_secs: durations.last().copied(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Resource ranked by the number of threats targeting it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttackedResource {
+    pub resource: String,
+    pub threat_count: u64,
+    pub highest_level: ThreatLevel,
+}
+
+/// Threat trend metrics over a reporting window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatTrendReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub total_threats: u64,
+    pub threats_by_level: BTreeMap<ThreatLevel, u64>,
+    pub hourly: Vec<ThreatHourBucket>,
+    pub resolution: ResolutionStats,
+    pub resolution_by_level: BTreeMap<ThreatLevel, ResolutionStats>,
+    pub top_resources: Vec<AttackedResource>,
+}
+
+/// Folds threat events into per-threat lifecycles.
+///
+/// Events may arrive in any order; a threat is attributed to the hour it was
+/// first seen and resolved at the earliest blocking, mitigating, or resolving event.
+#[derive(Debug, Default)]
+pub struct ThreatTrendAggregator {
+    threats: HashMap<String, ThreatRecord>,
+}
+
+impl ThreatTrendAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an aggregator from a batch of events
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a AnalyticsEvent>) -> Self {
+        let mut aggregator = Self::new();
+        for event in events {
+            aggregator.observe(event);
+        }
+        aggregator
+    }
+
+    /// Record an event, ignoring anything that is not a threat event
+    pub fn observe(&mut self, event: &AnalyticsEvent) {
+        if let EventPayload::Security(SecurityPayload::Threat(threat)) = &event.payload {
+            self.record_threat(threat, event.common.timestamp);
+        }
+    }
+
+    pub fn record_threat(&mut self, threat: &ThreatEvent, timestamp: DateTime<Utc>) {
+        let resolved_at = is_resolved(&threat.mitigation_status).then_some(timestamp);
+        let record = self
+            .threats
+            .entry(threat.threat_id.clone())
+            .or_insert_with(|| ThreatRecord {
+                threat_type: threat_type_label(&threat.threat_type),
+                threat_level: threat.threat_level.clone(),
+                target_resource: threat.target_resource.clone(),
+                first_seen: timestamp,
+                resolved_at,
+            });
+
+        if timestamp < record.first_seen {
+            record.first_seen = timestamp;
+        }
+        if threat.threat_level > record.threat_level {
+            record.threat_level = threat.threat_level.clone();
+        }
+        if let Some(at) = resolved_at {
+            record.resolved_at = Some(record.resolved_at.map_or(at, |existing| existing.min(at)));
+        }
+    }
+
+    /// Number of distinct threats observed
+    pub fn threat_count(&self) -> usize {
+        self.threats.len()
+    }
+
+    pub fn report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        top_n: usize,
+    ) -> ThreatTrendReport {
+        let mut hourly: BTreeMap<(DateTime<Utc>, String, ThreatLevel), u64> = BTreeMap::new();
+        let mut threats_by_level: BTreeMap<ThreatLevel, u64> = BTreeMap::new();
+        let mut resources: HashMap<&str, AttackedResource> = HashMap::new();
+        let mut durations: Vec<f64> = Vec::new();
+        let mut durations_by_level: BTreeMap<ThreatLevel, (Vec<f64>, u64)> = BTreeMap::new();
+        let mut unresolved = 0u64;
+
+        for record in self.threats.values() {
+            let hour = record
+                .first_seen
+                .duration_trunc(chrono::Duration::hours(1))
+                .unwrap_or(record.first_seen);
+            *hourly
+                .entry((
+                    hour,
+                    record.threat_type.clone(),
+                    record.threat_level.clone(),
+                ))
+                .or_insert(0) += 1;
+            *threats_by_level
+                .entry(record.threat_level.clone())
+                .or_insert(0) += 1;
+
+            let resource = resources
+                .entry(record.target_resource.as_str())
+                .or_insert_with(|| AttackedResource {
+                    resource: record.target_resource.clone(),
+                    threat_count: 0,
+                    highest_level: record.threat_level.clone(),
+                });
+            resource.threat_count += 1;
+            if record.threat_level > resource.highest_level {
+                resource.highest_level = record.threat_level.clone();
+            }
+
+            let level_entry = durations_by_level
+                .entry(record.threat_level.clone())
+                .or_default();
+            match record.resolved_at {
+                Some(resolved_at) => {
+                    let secs = (resolved_at - record.first_seen).num_milliseconds() as f64 / 1000.0;
+                    durations.push(secs);
+                    level_entry.0.push(secs);
+                }
+                None => {
+                    unresolved += 1;
+                    level_entry.1 += 1;
+                }
+            }
+        }
+
+        let mut top_resources: Vec<AttackedResource> = resources.into_values().collect();
+        top_resources.sort_by(|a, b| {
+            b.threat_count
+                .cmp(&a.threat_count)
+                .then_with(|| b.highest_level.cmp(&a.highest_level))
+                .then_with(|| a.resource.cmp(&b.resource))
+        });
+        top_resources.truncate(top_n);
+
+        ThreatTrendReport {
+            start,
+            end,
+            total_threats: self.threats.len() as u64,
+            threats_by_level,
+            hourly: hourly
+                .into_iter()
+                .map(
+                    |((hour, threat_type, threat_level), count)| ThreatHourBucket {
+                        hour,
+                        threat_type,
+                        threat_level,
+                        count,
+                    },
+                )
+                .collect(),
+            resolution: ResolutionStats::from_durations(durations, unresolved),
+            resolution_by_level: durations_by_level
+                .into_iter()
+                .map(|(level, (durations, unresolved))| {
+                    (
+                        level,
+                        ResolutionStats::from_durations(durations, unresolved),
+                    )
+                })
+                .collect(),
+            top_resources,
+        }
+    }
+}
+
+/// Builds threat trend reports from stored security events
+pub struct ThreatTrendAnalyzer {
+    database: Arc<Database>,
+    tenant: TenantScope,
+}
+
+impl ThreatTrendAnalyzer {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            tenant: TenantScope::AllTenants,
+        }
+    }
+
+    /// Only report on security events owned by the scoped tenant
+    pub fn with_tenant_scope(mut self, tenant: TenantScope) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Aggregate every security event in `[start, end)` into a trend report
+    #[instrument(skip(self))]
+    pub async fn report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        top_n: usize,
+    ) -> Result<ThreatTrendReport> {
+        let filter = self
+            .tenant
+            .scope_filter(Some(EventFilter::event_type(EventType::Security)));
+        let mut aggregator = ThreatTrendAggregator::new();
+        let scanned = self
+            .database
+            .scan_events(
+                start,
+                end,
+                filter.as_ref(),
+                &EnvironmentScope::Default,
+                |event| aggregator.observe(event),
+            )
+            .await?;
+
+        debug!(
+            scanned,
+            threats = aggregator.threat_count(),
+            "Aggregated security events"
+        );
+        Ok(aggregator.report(start, end, top_n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{CommonEventFields, Severity, SourceModule, SCHEMA_VERSION};
+    use chrono::{Duration, TimeZone};
+    use uuid::Uuid;
+
+    fn threat_event(
+        id: &str,
+        threat_type: ThreatType,
+        level: ThreatLevel,
+        resource: &str,
+        status: MitigationStatus,
+        timestamp: DateTime<Utc>,
+    ) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmSentinel,
+                event_type: EventType::Security,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Warning,
+                environment: "production".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Security(SecurityPayload::Threat(ThreatEvent {
+                threat_id: id.to_string(),
+                threat_type,
+                threat_level: level,
+                source_ip: None,
+                target_resource: resource.to_string(),
+                attack_vector: "prompt".to_string(),
+                mitigation_status: status,
+                indicators_of_compromise: vec![],
+            })),
+        }
+    }
+
+    fn base() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 1, 10, 15, 0).unwrap()
+    }
+
+    #[test]
+    fn test_threat_type_label() {
+        assert_eq!(
+            threat_type_label(&ThreatType::PromptInjection),
+            "prompt_injection"
+        );
+        assert_eq!(
+            threat_type_label(&ThreatType::Other("jailbreak".to_string())),
+            "jailbreak"
+        );
+    }
+
+    #[test]
+    fn test_hourly_buckets_count_distinct_threats() {
+        let t = base();
+        let events = vec![
+            threat_event(
+                "a",
+                ThreatType::PromptInjection,
+                ThreatLevel::High,
+                "chat",
+                MitigationStatus::Detected,
+                t,
+            ),
+            threat_event(
+                "a",
+                ThreatType::PromptInjection,
+                ThreatLevel::High,
+                "chat",
+                MitigationStatus::Blocked,
+                t + Duration::minutes(2),
+            ),
+            threat_event(
+                "b",
+                ThreatType::PromptInjection,
+                ThreatLevel::High,
+                "chat",
+                MitigationStatus::Detected,
+                t + Duration::minutes(20),
+            ),
+            threat_event(
+                "c",
+                ThreatType::DataExfiltration,
+                ThreatLevel::Critical,
+                "rag",
+                MitigationStatus::Detected,
+                t + Duration::hours(1),
+            ),
+        ];
+
+        let report = ThreatTrendAggregator::from_events(&events).report(
+            t,
+            t + Duration::hours(2),
+            DEFAULT_TOP_RESOURCES,
+        );
+
+        assert_eq!(report.total_threats, 3);
+        assert_eq!(report.hourly.len(), 2);
+        assert_eq!(
+            report.hourly[0].hour,
+            Utc.with_ymd_and_hms(2025, 3, 1, 10, 0, 0).unwrap()
+        );
+        assert_eq!(report.hourly[0].threat_type, "prompt_injection");
+        assert_eq!(report.hourly[0].count, 2);
+        assert_eq!(report.hourly[1].threat_level, ThreatLevel::Critical);
+        assert_eq!(report.threats_by_level[&ThreatLevel::High], 2);
+    }
+
+    #[test]
+    fn test_resolution_times_are_order_independent() {
+        let t = base();
+        // Resolution observed before detection (newest-first paging)
+        let events = vec![
+            threat_event(
+                "a",
+                ThreatType::MaliciousInput,
+                ThreatLevel::Medium,
+                "chat",
+                MitigationStatus::Resolved,
+                t + Duration::minutes(10),
+            ),
+            threat_event(
+                "a",
+                ThreatType::MaliciousInput,
+                ThreatLevel::Medium,
+                "chat",
+                MitigationStatus::Investigating,
+                t + Duration::minutes(5),
+            ),
+            threat_event(
+                "a",
+                ThreatType::MaliciousInput,
+                ThreatLevel::Medium,
+                "chat",
+                MitigationStatus::Detected,
+                t,
+            ),
+            threat_event(
+                "b",
+                ThreatType::MaliciousInput,
+                ThreatLevel::Medium,
+                "chat",
+                MitigationStatus::Detected,
+                t,
+            ),
+        ];
+
+        let report =
+            ThreatTrendAggregator::from_events(&events).report(t, t + Duration::hours(1), 5);
+
+        assert_eq!(report.resolution.resolved, 1);
+        assert_eq!(report.resolution.unresolved, 1);
+        assert_eq!(report.resolution.p50_secs, Some(600.0));
+        assert_eq!(
+            report.resolution_by_level[&ThreatLevel::Medium].unresolved,
+            1
+        );
+    }
+
+    #[test]
+    fn test_top_resources_ranked_and_truncated() {
+        let t = base();
+        let events = vec![
+            threat_event(
+                "a",
+                ThreatType::DenialOfService,
+                ThreatLevel::Low,
+                "gateway",
+                MitigationStatus::Detected,
+                t,
+            ),
+            threat_event(
+                "b",
+                ThreatType::DenialOfService,
+                ThreatLevel::Low,
+                "gateway",
+                MitigationStatus::Detected,
+                t,
+            ),
+            threat_event(
+                "c",
+                ThreatType::UnauthorizedAccess,
+                ThreatLevel::Critical,
+                "admin",
+                MitigationStatus::Detected,
+                t,
+            ),
+            threat_event(
+                "d",
+                ThreatType::ModelPoisoning,
+                ThreatLevel::Medium,
+                "trainer",
+                MitigationStatus::Detected,
+                t,
+            ),
+        ];
+
+        let report =
+            ThreatTrendAggregator::from_events(&events).report(t, t + Duration::hours(1), 2);
+
+        assert_eq!(report.top_resources.len(), 2);
+        assert_eq!(report.top_resources[0].resource, "gateway");
+        assert_eq!(report.top_resources[0].threat_count, 2);
+        assert_eq!(report.top_resources[1].resource, "admin");
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&values, 50.0), Some(2.0));
+        assert_eq!(percentile(&values, 99.0), Some(4.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+}
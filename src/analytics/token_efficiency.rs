@@ -0,0 +1,751 @@
+//! Token Efficiency Analysis
+//!
+//! Compares cached and uncached token usage per model and per pipeline by
+//! fusing Observatory traces, the CostOps token baseline (`cached_tokens` and
+//! prompt prices), and Memory-Graph retrieval statistics. Reports cache savings,
+//! prompt tokens that repeated reusable context without hitting the provider
+//! cache, and memory compression ratios, and publishes recommendations as
+//! custom events.
+
+use super::cost::trace_model;
+use crate::adapters::costops::{CostOpsAdapter, TokenAccountingBaseline};
+use crate::adapters::memory_graph::{MemoryGraphAdapter, MemorySnapshot};
+use crate::adapters::observatory::{ObservatoryAdapter, TraceQuery, UsageTrace};
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+/// Custom payload type for token efficiency recommendations
+pub const TOKEN_EFFICIENCY_EVENT_TYPE: &str = "token_efficiency.recommendation";
+
+/// Trace attributes that may carry the pipeline identifier, in priority order
+const PIPELINE_ATTRIBUTES: [&str; 2] = ["pipeline_id", "llm.pipeline.id"];
+
+/// Trace attributes that may carry provider-cached prompt tokens, in priority order
+const CACHED_TOKEN_ATTRIBUTES: [&str; 2] = ["gen_ai.usage.cached_tokens", "cached_tokens"];
+
+/// Token efficiency analysis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEfficiencyConfig {
+    /// Length of the window being evaluated
+    pub window_secs: i64,
+    /// Price of a cached prompt token relative to an uncached one
+    pub cached_price_ratio: f64,
+    /// Minimum prompt tokens before a model or pipeline gets recommendations
+    pub min_prompt_tokens: u64,
+    /// Share of prompt tokens wasted on uncached reusable context that triggers
+    /// a caching recommendation
+    pub max_wasted_share: f64,
+    /// Prompt-to-completion token ratio that triggers a prompt trimming recommendation
+    pub max_prompt_ratio: f64,
+    /// Memory compression ratio below which long contexts should be summarized
+    pub min_compression_ratio: f64,
+    /// Average context window size (tokens) at which summarization is considered
+    pub summarize_context_tokens: u64,
+    /// Upper bound on Memory-Graph sessions sampled per run
+    pub max_sessions: usize,
+    /// Environment stamped on emitted events
+    pub environment: String,
+}
+
+impl Default for TokenEfficiencyConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 3600,
+            cached_price_ratio: 0.5,
+            min_prompt_tokens: 10_000,
+            max_wasted_share: 0.25,
+            max_prompt_ratio: 20.0,
+            min_compression_ratio: 1.2,
+            summarize_context_tokens: 8_000,
+            max_sessions: 500,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl TokenEfficiencyConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            window_secs: std::env::var("TOKEN_EFFICIENCY_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.window_secs),
+            cached_price_ratio: std::env::var("TOKEN_EFFICIENCY_CACHED_PRICE_RATIO")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.cached_price_ratio),
+            min_prompt_tokens: std::env::var("TOKEN_EFFICIENCY_MIN_PROMPT_TOKENS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.min_prompt_tokens),
+            ..defaults
+        }
+    }
+}
+
+// ========== Efficiency Profiles ==========
+
+/// Dimension token usage is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EfficiencyGroup {
+    Model,
+    Pipeline,
+}
+
+impl EfficiencyGroup {
+    /// Tag key identifying the group on emitted events
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EfficiencyGroup::Model => "model_id",
+            EfficiencyGroup::Pipeline => "pipeline_id",
+        }
+    }
+
+    fn key<'a>(&self, trace: &'a UsageTrace) -> Option<&'a str> {
+        match self {
+            EfficiencyGroup::Model => trace_model(trace),
+            EfficiencyGroup::Pipeline => trace_pipeline(trace),
+        }
+    }
+}
+
+pub(crate) fn trace_pipeline(trace: &UsageTrace) -> Option<&str> {
+    PIPELINE_ATTRIBUTES
+        .iter()
+        .find_map(|key| trace.attributes.get(*key).and_then(|v| v.as_str()))
+}
+
+/// Cached vs uncached token usage of one model or pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenEfficiency {
+    pub group: EfficiencyGroup,
+    pub key: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cached_tokens: u64,
+    /// Cost avoided by serving prompt tokens from the provider cache
+    pub cache_savings_usd: f64,
+    /// Uncached prompt tokens that repeated reusable context
+    pub wasted_prompt_tokens: u64,
+    /// Additional savings if the wasted prompt tokens had been cached
+    pub potential_savings_usd: f64,
+}
+
+impl TokenEfficiency {
+    pub fn cache_hit_rate(&self) -> f64 {
+        ratio(self.cached_tokens as f64, self.prompt_tokens as f64)
+    }
+
+    pub fn wasted_share(&self) -> f64 {
+        ratio(self.wasted_prompt_tokens as f64, self.prompt_tokens as f64)
+    }
+
+    pub fn prompt_completion_ratio(&self) -> f64 {
+        ratio(self.prompt_tokens as f64, self.completion_tokens as f64)
+    }
+
+    /// Group traces by model or pipeline and price their cached tokens
+    ///
+    /// Traces without a cached-token attribute are assumed to hit the cache at
+    /// the baseline's organization-wide rate. `reuse_rate` is the share of
+    /// prompt tokens expected to repeat earlier context; anything beyond the
+    /// cached tokens up to that share counts as wasted.
+    pub fn fuse(
+        traces: &[UsageTrace],
+        baseline: &TokenAccountingBaseline,
+        group: EfficiencyGroup,
+        reuse_rate: f64,
+        config: &TokenEfficiencyConfig,
+    ) -> HashMap<String, TokenEfficiency> {
+        let tokens = &baseline.token_metrics;
+        let baseline_hit_rate = ratio(tokens.cached_tokens as f64, tokens.prompt_tokens as f64);
+        let discount = (1.0 - config.cached_price_ratio).clamp(0.0, 1.0);
+        let mut profiles: HashMap<String, TokenEfficiency> = HashMap::new();
+
+        for trace in traces {
+            let (Some(usage), Some(key)) = (&trace.token_usage, group.key(trace)) else {
+                continue;
+            };
+
+            let cached = CACHED_TOKEN_ATTRIBUTES
+                .iter()
+                .find_map(|key| trace.attributes.get(*key).and_then(|v| v.as_u64()))
+                .unwrap_or_else(|| (usage.prompt_tokens as f64 * baseline_hit_rate) as u64)
+                .min(usage.prompt_tokens);
+            let reusable = (usage.prompt_tokens as f64 * reuse_rate.clamp(0.0, 1.0)) as u64;
+            let wasted = reusable.saturating_sub(cached);
+
+            let prices = &baseline.cost_per_token;
+            let price_per_token = trace_model(trace)
+                .and_then(|model| prices.by_model.get(model))
+                .copied()
+                .unwrap_or(prices.prompt_cost_per_1k)
+                / 1000.0;
+
+            let profile = profiles
+                .entry(key.to_string())
+                .or_insert_with(|| TokenEfficiency {
+                    group,
+                    key: key.to_string(),
+                    requests: 0,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                    cached_tokens: 0,
+                    cache_savings_usd: 0.0,
+                    wasted_prompt_tokens: 0,
+                    potential_savings_usd: 0.0,
+                });
+            profile.requests += 1;
+            profile.prompt_tokens += usage.prompt_tokens;
+            profile.completion_tokens += usage.completion_tokens;
+            profile.cached_tokens += cached;
+            profile.cache_savings_usd += cached as f64 * price_per_token * discount;
+            profile.wasted_prompt_tokens += wasted;
+            profile.potential_savings_usd += wasted as f64 * price_per_token * discount;
+        }
+
+        profiles
+    }
+}
+
+/// Context reuse and compression across sampled Memory-Graph sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEfficiency {
+    pub sessions: u64,
+    pub context_window_tokens: u64,
+    pub summarized_tokens: u64,
+    /// Retrieval cache hit rate weighted by retrieval count
+    pub retrieval_cache_hit_rate: f64,
+}
+
+impl MemoryEfficiency {
+    pub fn from_snapshots(snapshots: &[MemorySnapshot]) -> Self {
+        let mut hits = 0.0;
+        let mut retrievals = 0u64;
+        let mut efficiency = Self::default();
+
+        for snapshot in snapshots {
+            let stats = &snapshot.retrieval_stats;
+            efficiency.sessions += 1;
+            efficiency.context_window_tokens += snapshot.context_window_tokens;
+            efficiency.summarized_tokens += snapshot.summarized_tokens;
+            hits += stats.cache_hit_rate * stats.total_retrievals as f64;
+            retrievals += stats.total_retrievals;
+        }
+
+        efficiency.retrieval_cache_hit_rate = ratio(hits, retrievals as f64);
+        efficiency
+    }
+
+    /// Conversation history tokens represented per token held in context
+    pub fn compression_ratio(&self) -> f64 {
+        if self.context_window_tokens == 0 {
+            return 1.0;
+        }
+        (self.context_window_tokens + self.summarized_tokens) as f64
+            / self.context_window_tokens as f64
+    }
+
+    pub fn avg_context_tokens(&self) -> f64 {
+        ratio(self.context_window_tokens as f64, self.sessions as f64)
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+// ========== Recommendations ==========
+
+/// Kind of token efficiency improvement being suggested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationKind {
+    /// Reused context is resent without hitting the provider prompt cache
+    EnablePromptCaching,
+    /// Prompts are far larger than the completions they produce
+    TrimPrompts,
+    /// Long contexts are carried with little summarization
+    SummarizeContext,
+}
+
+/// A suggested change with its estimated effect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EfficiencyRecommendation {
+    pub kind: RecommendationKind,
+    /// Model or pipeline the recommendation targets, `None` for memory-wide advice
+    pub group: Option<EfficiencyGroup>,
+    pub key: Option<String>,
+    pub message: String,
+    pub estimated_savings_usd: f64,
+}
+
+impl EfficiencyRecommendation {
+    /// Build the custom event published for the recommendation
+    pub fn to_event(&self, environment: &str) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        if let (Some(group), Some(key)) = (self.group, &self.key) {
+            tags.insert(group.tag().to_string(), key.clone());
+        }
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Cost,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: environment.to_string(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: TOKEN_EFFICIENCY_EVENT_TYPE.to_string(),
+                data: serde_json::to_value(self).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Derive recommendations from per-group profiles and memory statistics
+pub fn recommend(
+    profiles: &[TokenEfficiency],
+    memory: &MemoryEfficiency,
+    config: &TokenEfficiencyConfig,
+) -> Vec<EfficiencyRecommendation> {
+    let mut recommendations = Vec::new();
+
+    for profile in profiles {
+        if profile.prompt_tokens < config.min_prompt_tokens {
+            continue;
+        }
+
+        if profile.wasted_share() >= config.max_wasted_share {
+            recommendations.push(EfficiencyRecommendation {
+                kind: RecommendationKind::EnablePromptCaching,
+                group: Some(profile.group),
+                key: Some(profile.key.clone()),
+                message: format!(
+                    "{:.0}% of prompt tokens repeat reusable context without a cache hit \
+                     (cache hit rate {:.0}%); move shared context into a stable cacheable prefix",
+                    profile.wasted_share() * 100.0,
+                    profile.cache_hit_rate() * 100.0
+                ),
+                estimated_savings_usd: profile.potential_savings_usd,
+            });
+        }
+
+        if profile.completion_tokens > 0
+            && profile.prompt_completion_ratio() >= config.max_prompt_ratio
+        {
+            recommendations.push(EfficiencyRecommendation {
+                kind: RecommendationKind::TrimPrompts,
+                group: Some(profile.group),
+                key: Some(profile.key.clone()),
+                message: format!(
+                    "Prompts average {:.0} tokens per completion token; trim instructions or \
+                     retrieved context",
+                    profile.prompt_completion_ratio()
+                ),
+                estimated_savings_usd: 0.0,
+            });
+        }
+    }
+
+    if memory.sessions > 0
+        && memory.avg_context_tokens() >= config.summarize_context_tokens as f64
+        && memory.compression_ratio() < config.min_compression_ratio
+    {
+        recommendations.push(EfficiencyRecommendation {
+            kind: RecommendationKind::SummarizeContext,
+            group: None,
+            key: None,
+            message: format!(
+                "Sessions carry {:.0} context tokens on average with a compression ratio of \
+                 {:.2}; summarize older turns in Memory-Graph",
+                memory.avg_context_tokens(),
+                memory.compression_ratio()
+            ),
+            estimated_savings_usd: 0.0,
+        });
+    }
+
+    recommendations
+}
+
+// ========== Analyzer ==========
+
+/// Result of one token efficiency pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEfficiencyReport {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub by_model: Vec<TokenEfficiency>,
+    pub by_pipeline: Vec<TokenEfficiency>,
+    pub memory: MemoryEfficiency,
+    pub recommendations: Vec<EfficiencyRecommendation>,
+}
+
+/// Analyzer statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEfficiencyStats {
+    pub runs: u64,
+    pub recommendations: u64,
+    pub events_emitted: u64,
+    pub events_dropped: u64,
+}
+
+/// Periodically measures token efficiency and publishes recommendations
+pub struct TokenEfficiencyAnalyzer {
+    observatory: Arc<ObservatoryAdapter>,
+    costops: Arc<CostOpsAdapter>,
+    memory_graph: Arc<MemoryGraphAdapter>,
+    config: TokenEfficiencyConfig,
+    sink: mpsc::Sender<AnalyticsEvent>,
+    runs: AtomicU64,
+    recommendations: AtomicU64,
+    events_emitted: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl TokenEfficiencyAnalyzer {
+    /// Create an analyzer publishing recommendations into the given pipeline channel
+    pub fn new(
+        observatory: Arc<ObservatoryAdapter>,
+        costops: Arc<CostOpsAdapter>,
+        memory_graph: Arc<MemoryGraphAdapter>,
+        config: TokenEfficiencyConfig,
+        sink: mpsc::Sender<AnalyticsEvent>,
+    ) -> Self {
+        Self {
+            observatory,
+            costops,
+            memory_graph,
+            config,
+            sink,
+            runs: AtomicU64::new(0),
+            recommendations: AtomicU64::new(0),
+            events_emitted: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Memory statistics of sessions active in the window. Memory-Graph being
+    /// unavailable degrades the report rather than failing it.
+    async fn memory_efficiency(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> MemoryEfficiency {
+        let mut session_ids = match self.memory_graph.fetch_active_sessions(start, end).await {
+            Ok(session_ids) => session_ids,
+            Err(e) => {
+                warn!(
+                    "Memory-Graph sessions unavailable, skipping memory efficiency: {}",
+                    e
+                );
+                return MemoryEfficiency::default();
+            }
+        };
+        session_ids.truncate(self.config.max_sessions);
+
+        let mut snapshots = Vec::with_capacity(session_ids.len());
+        for session_id in &session_ids {
+            match self.memory_graph.fetch_memory_snapshot(session_id).await {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => debug!(session_id = %session_id, "Skipping memory snapshot: {}", e),
+            }
+        }
+        MemoryEfficiency::from_snapshots(&snapshots)
+    }
+
+    /// Measure token efficiency over the window ending at `now`
+    #[instrument(skip(self))]
+    pub async fn analyze(&self, now: DateTime<Utc>) -> Result<TokenEfficiencyReport> {
+        let window_start = now - Duration::seconds(self.config.window_secs);
+        let query = TraceQuery {
+            start_time: Some(window_start),
+            end_time: Some(now),
+            ..Default::default()
+        };
+        let (traces, baseline) = tokio::try_join!(
+            self.observatory.fetch_traces(query),
+            self.costops.fetch_token_baseline(window_start, now),
+        )?;
+        let memory = self.memory_efficiency(window_start, now).await;
+
+        let profiles = |group| {
+            let mut profiles: Vec<TokenEfficiency> = TokenEfficiency::fuse(
+                &traces,
+                &baseline,
+                group,
+                memory.retrieval_cache_hit_rate,
+                &self.config,
+            )
+            .into_values()
+            .collect();
+            profiles.sort_by(|a, b| a.key.cmp(&b.key));
+            profiles
+        };
+        let by_model = profiles(EfficiencyGroup::Model);
+        let by_pipeline = profiles(EfficiencyGroup::Pipeline);
+
+        let mut recommendations = recommend(&by_model, &memory, &self.config);
+        recommendations.extend(recommend(
+            &by_pipeline,
+            &MemoryEfficiency::default(),
+            &self.config,
+        ));
+
+        debug!(
+            models = by_model.len(),
+            pipelines = by_pipeline.len(),
+            recommendations = recommendations.len(),
+            "Token efficiency analysis complete"
+        );
+
+        Ok(TokenEfficiencyReport {
+            window_start,
+            window_end: now,
+            by_model,
+            by_pipeline,
+            memory,
+            recommendations,
+        })
+    }
+
+    /// Run one analysis pass and publish its recommendations
+    pub async fn run_once(&self) -> Result<TokenEfficiencyReport> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let report = self.analyze(Utc::now()).await?;
+
+        for recommendation in &report.recommendations {
+            info!(
+                kind = ?recommendation.kind,
+                key = ?recommendation.key,
+                savings_usd = recommendation.estimated_savings_usd,
+                "Token efficiency recommendation"
+            );
+            self.emit(recommendation.to_event(&self.config.environment));
+        }
+        self.recommendations
+            .fetch_add(report.recommendations.len() as u64, Ordering::Relaxed);
+
+        Ok(report)
+    }
+
+    fn emit(&self, event: AnalyticsEvent) {
+        match self.sink.try_send(event) {
+            Ok(()) => {
+                self.events_emitted.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!(error = %e, "Dropped token efficiency event");
+            }
+        }
+    }
+
+    /// Get analyzer statistics
+    pub fn get_stats(&self) -> TokenEfficiencyStats {
+        TokenEfficiencyStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            recommendations: self.recommendations.load(Ordering::Relaxed),
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::costops::{BaselinePeriod, CostPerToken, EfficiencyMetrics, TokenMetrics};
+    use crate::adapters::memory_graph::RetrievalStats;
+    use crate::adapters::observatory::{TokenUsage, TraceStatus};
+
+    fn trace(model: &str, pipeline: &str, prompt: u64, completion: u64) -> UsageTrace {
+        let mut attributes = HashMap::new();
+        attributes.insert("model_id".to_string(), serde_json::json!(model));
+        attributes.insert("pipeline_id".to_string(), serde_json::json!(pipeline));
+        UsageTrace {
+            trace_id: Uuid::new_v4().to_string(),
+            span_id: Uuid::new_v4().to_string(),
+            parent_span_id: None,
+            operation_name: "chat.completion".to_string(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            duration_ms: 100,
+            status: TraceStatus::Ok,
+            attributes,
+            token_usage: Some(TokenUsage {
+                prompt_tokens: prompt,
+                completion_tokens: completion,
+                total_tokens: prompt + completion,
+            }),
+        }
+    }
+
+    fn baseline(prompt_tokens: u64, cached_tokens: u64) -> TokenAccountingBaseline {
+        TokenAccountingBaseline {
+            baseline_id: "b".to_string(),
+            created_at: Utc::now(),
+            period: BaselinePeriod {
+                start: Utc::now(),
+                end: Utc::now(),
+            },
+            token_metrics: TokenMetrics {
+                total_tokens: prompt_tokens,
+                prompt_tokens,
+                completion_tokens: 0,
+                cached_tokens,
+                by_model: HashMap::new(),
+            },
+            cost_per_token: CostPerToken {
+                average_cost_per_1k_tokens: 1.0,
+                prompt_cost_per_1k: 1.0,
+                completion_cost_per_1k: 2.0,
+                by_model: HashMap::new(),
+            },
+            efficiency_metrics: EfficiencyMetrics {
+                cache_hit_rate: 0.0,
+                tokens_per_request_avg: 0.0,
+                cost_per_request_avg: 0.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_fuse_prices_cached_and_wasted_tokens() {
+        let mut cached = trace("gpt-4", "rag", 10_000, 500);
+        cached
+            .attributes
+            .insert("cached_tokens".to_string(), serde_json::json!(6_000));
+        let traces = vec![cached, trace("gpt-4", "rag", 10_000, 500)];
+        let config = TokenEfficiencyConfig::default();
+
+        // Uncached traces fall back to the baseline hit rate of 10%
+        let profiles = TokenEfficiency::fuse(
+            &traces,
+            &baseline(100_000, 10_000),
+            EfficiencyGroup::Model,
+            0.5,
+            &config,
+        );
+        let gpt4 = &profiles["gpt-4"];
+        assert_eq!(gpt4.requests, 2);
+        assert_eq!(gpt4.cached_tokens, 7_000);
+        assert!((gpt4.cache_hit_rate() - 0.35).abs() < 1e-9);
+        // 7k cached tokens at $1/1k with a 50% cache discount
+        assert!((gpt4.cache_savings_usd - 3.5).abs() < 1e-9);
+        // Only the second trace falls short of the 5k reusable tokens
+        assert_eq!(gpt4.wasted_prompt_tokens, 4_000);
+        assert!((gpt4.potential_savings_usd - 2.0).abs() < 1e-9);
+
+        let by_pipeline = TokenEfficiency::fuse(
+            &traces,
+            &baseline(100_000, 10_000),
+            EfficiencyGroup::Pipeline,
+            0.5,
+            &config,
+        );
+        assert_eq!(by_pipeline["rag"].prompt_tokens, 20_000);
+    }
+
+    #[test]
+    fn test_memory_compression_ratio() {
+        let snapshot = |context, summarized, retrievals, hit_rate| MemorySnapshot {
+            snapshot_id: "m".to_string(),
+            session_id: "s".to_string(),
+            created_at: Utc::now(),
+            context_window_tokens: context,
+            summarized_tokens: summarized,
+            active_memories: Vec::new(),
+            retrieval_stats: RetrievalStats {
+                total_retrievals: retrievals,
+                avg_latency_ms: 5.0,
+                cache_hit_rate: hit_rate,
+                relevance_avg: 0.8,
+            },
+        };
+
+        let memory = MemoryEfficiency::from_snapshots(&[
+            snapshot(4_000, 4_000, 10, 1.0),
+            snapshot(6_000, 1_000, 30, 0.2),
+        ]);
+        assert_eq!(memory.sessions, 2);
+        assert!((memory.compression_ratio() - 1.5).abs() < 1e-9);
+        assert!((memory.retrieval_cache_hit_rate - 0.4).abs() < 1e-9);
+        assert_eq!(MemoryEfficiency::default().compression_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_recommendations() {
+        let config = TokenEfficiencyConfig::default();
+        let profile = TokenEfficiency {
+            group: EfficiencyGroup::Pipeline,
+            key: "rag".to_string(),
+            requests: 10,
+            prompt_tokens: 100_000,
+            completion_tokens: 1_000,
+            cached_tokens: 0,
+            cache_savings_usd: 0.0,
+            wasted_prompt_tokens: 40_000,
+            potential_savings_usd: 20.0,
+        };
+        let memory = MemoryEfficiency {
+            sessions: 2,
+            context_window_tokens: 20_000,
+            summarized_tokens: 0,
+            retrieval_cache_hit_rate: 0.4,
+        };
+
+        let recommendations = recommend(&[profile.clone()], &memory, &config);
+        let kinds: Vec<_> = recommendations.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RecommendationKind::EnablePromptCaching,
+                RecommendationKind::TrimPrompts,
+                RecommendationKind::SummarizeContext,
+            ]
+        );
+        assert_eq!(recommendations[0].estimated_savings_usd, 20.0);
+
+        let event = recommendations[0].to_event("test");
+        assert_eq!(event.common.tags["pipeline_id"], "rag");
+        match event.payload {
+            EventPayload::Custom(custom) => {
+                assert_eq!(custom.custom_type, TOKEN_EFFICIENCY_EVENT_TYPE);
+                assert_eq!(custom.data["kind"], "enable_prompt_caching");
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        let small = TokenEfficiency {
+            prompt_tokens: 100,
+            ..profile
+        };
+        assert!(recommend(&[small], &MemoryEfficiency::default(), &config).is_empty());
+    }
+}
@@ -0,0 +1,708 @@
+//! Entity Topology
+//!
+//! A graph of the entities analytics reports on, built from LLM-Registry and
+//! LLM-CostOps: models and the providers serving them, pipelines and the
+//! models their stages run, and the teams that own pipelines and budgets.
+//!
+//! Relations point from an entity to what it depends on (team → pipeline →
+//! stage → model → provider, and team → budget). Following them forward gives
+//! everything an entity uses; following them backward gives its blast radius,
+//! the entities and owners affected when it fails.
+
+use crate::adapters::costops::{BudgetStatus, CostOpsAdapter};
+use crate::adapters::mirror::RegistryMirror;
+use crate::adapters::registry::{
+    ModelMetadata, ModelQuery, PipelineDescriptor, PipelineQuery, ProviderInfo, RegistryAdapter,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Owner key for values whose entity no team owns
+pub const UNOWNED: &str = "unowned";
+
+/// Kind of entity in the topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Provider,
+    Model,
+    Pipeline,
+    /// Pipeline stage, identified as `<pipeline_id>:<stage_id>`
+    Stage,
+    Team,
+    Budget,
+}
+
+impl EntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Provider => "provider",
+            EntityKind::Model => "model",
+            EntityKind::Pipeline => "pipeline",
+            EntityKind::Stage => "stage",
+            EntityKind::Team => "team",
+            EntityKind::Budget => "budget",
+        }
+    }
+}
+
+impl std::str::FromStr for EntityKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Invalid entity kind: {}", s))
+    }
+}
+
+/// Reference to one entity
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EntityRef {
+    pub kind: EntityKind,
+    pub id: String,
+}
+
+impl EntityRef {
+    pub fn new(kind: EntityKind, id: impl Into<String>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for EntityRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind.as_str(), self.id)
+    }
+}
+
+/// An entity and the attributes analytics reports alongside it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    #[serde(flatten)]
+    pub entity: EntityRef,
+    pub name: String,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, serde_json::Value>,
+}
+
+/// How one entity depends on another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    /// Model → provider serving it
+    ServedBy,
+    /// Pipeline → its stage
+    HasStage,
+    /// Stage → model it runs
+    Runs,
+    /// Team → pipeline it owns
+    Owns,
+    /// Team → budget it spends against
+    HasBudget,
+}
+
+/// `from` depends on `to`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Relation {
+    pub from: EntityRef,
+    pub to: EntityRef,
+    pub kind: RelationKind,
+}
+
+/// Entities and owners affected when an entity fails
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlastRadius {
+    pub entity: EntityRef,
+    /// Affected entities by kind, excluding `entity` itself
+    pub affected: BTreeMap<EntityKind, Vec<String>>,
+    /// Teams owning an affected pipeline
+    pub teams: Vec<String>,
+}
+
+impl BlastRadius {
+    /// Number of affected entities
+    pub fn size(&self) -> usize {
+        self.affected.values().map(Vec::len).sum()
+    }
+}
+
+/// An entity with what it depends on and what depends on it, for attaching
+/// to root-cause analyses and alerts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityContext {
+    pub entity: Entity,
+    /// Entities this one depends on, directly or transitively
+    pub dependencies: Vec<EntityRef>,
+    pub blast_radius: BlastRadius,
+}
+
+impl EntityContext {
+    /// Human-readable facts about the entity's place in the topology, in the
+    /// form of root-cause contributing factors
+    pub fn contributing_factors(&self) -> Vec<String> {
+        let mut factors = Vec::new();
+        let upstream: Vec<String> = self
+            .dependencies
+            .iter()
+            .filter(|d| matches!(d.kind, EntityKind::Model | EntityKind::Provider))
+            .map(ToString::to_string)
+            .collect();
+        if !upstream.is_empty() {
+            factors.push(format!(
+                "{} depends on {}",
+                self.entity.entity,
+                upstream.join(", ")
+            ));
+        }
+        let radius = &self.blast_radius;
+        if radius.size() > 0 {
+            let counts: Vec<String> = radius
+                .affected
+                .iter()
+                .map(|(kind, ids)| format!("{} {}(s)", ids.len(), kind.as_str()))
+                .collect();
+            factors.push(format!(
+                "{} affects {}",
+                self.entity.entity,
+                counts.join(", ")
+            ));
+        }
+        if !radius.teams.is_empty() {
+            factors.push(format!("Owned by {}", radius.teams.join(", ")));
+        }
+        factors
+    }
+}
+
+/// Entities a team owns, by kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnerRollup {
+    pub team: String,
+    pub entities: BTreeMap<EntityKind, Vec<String>>,
+}
+
+/// Graph of entities and their dependencies
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Topology {
+    pub built_at: Option<DateTime<Utc>>,
+    entities: Vec<Entity>,
+    relations: Vec<Relation>,
+    #[serde(skip)]
+    index: HashMap<EntityRef, usize>,
+}
+
+impl Topology {
+    /// Build the topology from registry and CostOps data. Pipelines are owned
+    /// by the team CostOps bills them to, falling back to the registry owner.
+    pub fn build(
+        models: &[ModelMetadata],
+        providers: &[ProviderInfo],
+        pipelines: &[PipelineDescriptor],
+        consumer_teams: &HashMap<String, String>,
+        budgets: &[BudgetStatus],
+    ) -> Self {
+        let mut topology = Self {
+            built_at: Some(Utc::now()),
+            ..Default::default()
+        };
+
+        for provider in providers {
+            let entity =
+                topology.upsert(EntityKind::Provider, &provider.provider_id, &provider.name);
+            entity.attributes.insert(
+                "status".to_string(),
+                serde_json::to_value(&provider.status).unwrap_or_default(),
+            );
+            for model_id in &provider.models {
+                topology.relate(
+                    EntityRef::new(EntityKind::Model, model_id),
+                    EntityRef::new(EntityKind::Provider, &provider.provider_id),
+                    RelationKind::ServedBy,
+                );
+            }
+        }
+
+        for model in models {
+            let entity = topology.upsert(EntityKind::Model, &model.model_id, &model.name);
+            entity
+                .attributes
+                .insert("version".to_string(), model.version.clone().into());
+            entity.attributes.insert(
+                "status".to_string(),
+                serde_json::to_value(&model.status).unwrap_or_default(),
+            );
+            topology.relate(
+                EntityRef::new(EntityKind::Model, &model.model_id),
+                EntityRef::new(EntityKind::Provider, &model.provider),
+                RelationKind::ServedBy,
+            );
+        }
+
+        for pipeline in pipelines {
+            let pipeline_ref = EntityRef::new(EntityKind::Pipeline, &pipeline.pipeline_id);
+            let entity =
+                topology.upsert(EntityKind::Pipeline, &pipeline.pipeline_id, &pipeline.name);
+            entity
+                .attributes
+                .insert("version".to_string(), pipeline.version.clone().into());
+
+            for stage in &pipeline.stages {
+                let stage_id = format!("{}:{}", pipeline.pipeline_id, stage.stage_id);
+                let entity = topology.upsert(EntityKind::Stage, &stage_id, &stage.stage_name);
+                entity.attributes.insert(
+                    "stage_type".to_string(),
+                    serde_json::to_value(&stage.stage_type).unwrap_or_default(),
+                );
+                let stage_ref = EntityRef::new(EntityKind::Stage, &stage_id);
+                topology.relate(
+                    pipeline_ref.clone(),
+                    stage_ref.clone(),
+                    RelationKind::HasStage,
+                );
+                if let Some(model_id) = &stage.model_id {
+                    topology.relate(
+                        stage_ref,
+                        EntityRef::new(EntityKind::Model, model_id),
+                        RelationKind::Runs,
+                    );
+                }
+            }
+
+            let owner = consumer_teams
+                .get(&pipeline.pipeline_id)
+                .or(Some(&pipeline.owner))
+                .filter(|team| !team.is_empty());
+            if let Some(team) = owner {
+                topology.relate(
+                    EntityRef::new(EntityKind::Team, team),
+                    pipeline_ref,
+                    RelationKind::Owns,
+                );
+            }
+        }
+
+        for budget in budgets {
+            let Some(team) = &budget.team_id else {
+                continue;
+            };
+            let entity = topology.upsert(EntityKind::Budget, &budget.budget_id, &budget.budget_id);
+            for (key, value) in [
+                ("period_budget_usd", budget.period_budget_usd),
+                ("spent_usd", budget.spent_usd),
+                ("utilization_percentage", budget.utilization_percentage),
+            ] {
+                entity.attributes.insert(key.to_string(), value.into());
+            }
+            topology.relate(
+                EntityRef::new(EntityKind::Team, team),
+                EntityRef::new(EntityKind::Budget, &budget.budget_id),
+                RelationKind::HasBudget,
+            );
+        }
+
+        topology
+    }
+
+    /// Entity by reference, creating it named `name` if missing
+    fn upsert(&mut self, kind: EntityKind, id: &str, name: &str) -> &mut Entity {
+        let entity = EntityRef::new(kind, id);
+        let index = match self.index.get(&entity) {
+            Some(index) => *index,
+            None => {
+                self.entities.push(Entity {
+                    entity: entity.clone(),
+                    name: id.to_string(),
+                    attributes: BTreeMap::new(),
+                });
+                self.index.insert(entity, self.entities.len() - 1);
+                self.entities.len() - 1
+            }
+        };
+        let entity = &mut self.entities[index];
+        if !name.is_empty() {
+            entity.name = name.to_string();
+        }
+        entity
+    }
+
+    /// Add a relation, creating entities it references that are not yet known
+    fn relate(&mut self, from: EntityRef, to: EntityRef, kind: RelationKind) {
+        for entity in [&from, &to] {
+            if !self.index.contains_key(entity) {
+                self.upsert(entity.kind, &entity.id, "");
+            }
+        }
+        let relation = Relation { from, to, kind };
+        if !self.relations.contains(&relation) {
+            self.relations.push(relation);
+        }
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn relations(&self) -> &[Relation] {
+        &self.relations
+    }
+
+    pub fn get(&self, entity: &EntityRef) -> Option<&Entity> {
+        self.index.get(entity).map(|index| &self.entities[*index])
+    }
+
+    /// Entities reachable from `entity` following relations forward
+    /// (`downstream`) or backward, excluding `entity`
+    fn reachable(&self, entity: &EntityRef, downstream: bool) -> BTreeSet<EntityRef> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([entity]);
+        while let Some(current) = queue.pop_front() {
+            for relation in &self.relations {
+                let (from, to) = if downstream {
+                    (&relation.from, &relation.to)
+                } else {
+                    (&relation.to, &relation.from)
+                };
+                if from == current && to != entity && seen.insert(to.clone()) {
+                    queue.push_back(to);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Everything `entity` depends on, directly or transitively
+    pub fn dependencies(&self, entity: &EntityRef) -> Vec<EntityRef> {
+        self.reachable(entity, true).into_iter().collect()
+    }
+
+    /// Whether the entities are the same or one depends on the other
+    pub fn related(&self, a: &EntityRef, b: &EntityRef) -> bool {
+        a == b || self.reachable(a, true).contains(b) || self.reachable(a, false).contains(b)
+    }
+
+    /// Entities and teams affected when `entity` fails
+    pub fn blast_radius(&self, entity: &EntityRef) -> BlastRadius {
+        let mut affected: BTreeMap<EntityKind, Vec<String>> = BTreeMap::new();
+        for dependent in self.reachable(entity, false) {
+            affected
+                .entry(dependent.kind)
+                .or_default()
+                .push(dependent.id);
+        }
+        let teams = affected.get(&EntityKind::Team).cloned().unwrap_or_default();
+        BlastRadius {
+            entity: entity.clone(),
+            affected,
+            teams,
+        }
+    }
+
+    /// Teams owning `entity`, or using it through a pipeline they own
+    pub fn owners(&self, entity: &EntityRef) -> Vec<String> {
+        if entity.kind == EntityKind::Team {
+            return vec![entity.id.clone()];
+        }
+        self.blast_radius(entity).teams
+    }
+
+    pub fn context(&self, entity: &EntityRef) -> Option<EntityContext> {
+        Some(EntityContext {
+            entity: self.get(entity)?.clone(),
+            dependencies: self.dependencies(entity),
+            blast_radius: self.blast_radius(entity),
+        })
+    }
+
+    /// Entities each team owns or uses through its pipelines
+    pub fn owner_rollups(&self) -> Vec<OwnerRollup> {
+        let mut teams: Vec<&Entity> = self
+            .entities
+            .iter()
+            .filter(|e| e.entity.kind == EntityKind::Team)
+            .collect();
+        teams.sort_by(|a, b| a.entity.id.cmp(&b.entity.id));
+        teams
+            .into_iter()
+            .map(|team| {
+                let mut entities: BTreeMap<EntityKind, Vec<String>> = BTreeMap::new();
+                for entity in self.dependencies(&team.entity) {
+                    entities.entry(entity.kind).or_default().push(entity.id);
+                }
+                OwnerRollup {
+                    team: team.entity.id.clone(),
+                    entities,
+                }
+            })
+            .collect()
+    }
+
+    /// Sum per-entity values, such as cost by model, per owning team. Values of
+    /// entities shared by several teams are split evenly between them so the
+    /// totals match; values of entities no team owns go to [`UNOWNED`].
+    pub fn rollup_by_owner(
+        &self,
+        values: impl IntoIterator<Item = (EntityRef, f64)>,
+    ) -> BTreeMap<String, f64> {
+        let mut totals = BTreeMap::new();
+        for (entity, value) in values {
+            let owners = self.owners(&entity);
+            if owners.is_empty() {
+                *totals.entry(UNOWNED.to_string()).or_insert(0.0) += value;
+                continue;
+            }
+            let share = value / owners.len() as f64;
+            for owner in owners {
+                *totals.entry(owner).or_insert(0.0) += share;
+            }
+        }
+        totals
+    }
+}
+
+/// Keeps a topology built from the registry and CostOps up to date
+pub struct TopologyStore {
+    registry: Arc<RegistryAdapter>,
+    costops: Arc<CostOpsAdapter>,
+    // Local registry copy read instead of listing the registry on every refresh
+    mirror: Option<Arc<RegistryMirror>>,
+    topology: RwLock<Arc<Topology>>,
+}
+
+impl TopologyStore {
+    pub fn new(registry: Arc<RegistryAdapter>, costops: Arc<CostOpsAdapter>) -> Self {
+        Self {
+            registry,
+            costops,
+            mirror: None,
+            topology: RwLock::new(Arc::new(Topology::default())),
+        }
+    }
+
+    /// Build from the local registry mirror rather than the registry itself
+    pub fn with_mirror(mut self, mirror: Arc<RegistryMirror>) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    /// Most recently built topology; empty until the first refresh
+    pub fn current(&self) -> Arc<Topology> {
+        self.topology.read().clone()
+    }
+
+    /// Rebuild the topology from the registry and CostOps
+    pub async fn refresh(&self) -> Result<Arc<Topology>> {
+        let (models, providers, pipelines, consumer_teams) = match &self.mirror {
+            Some(mirror) => (
+                mirror.models(),
+                mirror.providers(),
+                mirror.pipelines(),
+                self.costops.fetch_consumer_teams().await?,
+            ),
+            None => tokio::try_join!(
+                self.registry.list_models(ModelQuery::default()),
+                self.registry.list_providers(),
+                self.registry.list_pipelines(PipelineQuery::default()),
+                self.costops.fetch_consumer_teams(),
+            )?,
+        };
+
+        // Budgets are fetched per team; a team whose budget cannot be read
+        // is still part of the topology
+        let teams: BTreeSet<&String> = pipelines
+            .iter()
+            .map(|p| consumer_teams.get(&p.pipeline_id).unwrap_or(&p.owner))
+            .filter(|team| !team.is_empty())
+            .collect();
+        let mut budgets = Vec::new();
+        for team in teams {
+            match self.costops.fetch_budget_status(Some(team)).await {
+                Ok(budget) => budgets.push(budget),
+                Err(e) => warn!(team = %team, "Failed to fetch team budget: {}", e),
+            }
+        }
+
+        let topology = Arc::new(Topology::build(
+            &models,
+            &providers,
+            &pipelines,
+            &consumer_teams,
+            &budgets,
+        ));
+        debug!(
+            entities = topology.entities().len(),
+            relations = topology.relations().len(),
+            "Rebuilt entity topology"
+        );
+        *self.topology.write() = topology.clone();
+        Ok(topology)
+    }
+
+    /// Refresh every `period` until the task is aborted
+    pub fn spawn(self: Arc<Self>, period: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match self.refresh().await {
+                    Ok(topology) => info!(
+                        "Entity topology refreshed with {} entities",
+                        topology.entities().len()
+                    ),
+                    Err(e) => warn!("Entity topology refresh failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology() -> Topology {
+        let provider = |id: &str, models: &[&str]| -> ProviderInfo {
+            serde_json::from_value(serde_json::json!({
+                "provider_id": id,
+                "name": id,
+                "status": "Operational",
+                "api_version": "v1",
+                "models": models,
+                "rate_limits": {"requests_per_minute": 0, "tokens_per_minute": 0, "tokens_per_day": null},
+                "health": {"availability": 1.0, "avg_latency_ms": 0.0, "error_rate": 0.0, "last_checked": Utc::now()},
+            }))
+            .unwrap()
+        };
+        let pipeline = |id: &str,
+                        owner: &str,
+                        stages: &[(&str, Option<&str>)]|
+         -> PipelineDescriptor {
+            let stages: Vec<serde_json::Value> = stages
+                .iter()
+                .map(|(stage_id, model_id)| {
+                    serde_json::json!({
+                        "stage_id": stage_id,
+                        "stage_name": stage_id,
+                        "stage_type": "ModelInference",
+                        "model_id": model_id,
+                        "config": {},
+                        "timeout_ms": 0,
+                        "retry_policy": {"max_attempts": 1, "initial_delay_ms": 0, "max_delay_ms": 0, "backoff_multiplier": 1.0},
+                    })
+                })
+                .collect();
+            serde_json::from_value(serde_json::json!({
+                "pipeline_id": id,
+                "name": id,
+                "version": "1.0.0",
+                "description": "",
+                "stages": stages,
+                "input_schema": {},
+                "output_schema": {},
+                "created_at": Utc::now(),
+                "last_updated": Utc::now(),
+                "owner": owner,
+                "status": "Active",
+                "metrics": {"total_invocations": 0, "success_rate": 1.0, "avg_latency_ms": 0.0, "avg_cost_per_invocation": 0.0},
+            }))
+            .unwrap()
+        };
+
+        let mut consumer_teams = HashMap::new();
+        consumer_teams.insert("support-bot".to_string(), "support".to_string());
+        Topology::build(
+            &[],
+            &[
+                provider("openai", &["gpt-4", "text-embedding-3"]),
+                provider("anthropic", &["claude-3"]),
+            ],
+            &[
+                pipeline(
+                    "rag",
+                    "search",
+                    &[
+                        ("embed", Some("text-embedding-3")),
+                        ("generate", Some("gpt-4")),
+                    ],
+                ),
+                pipeline("support-bot", "platform", &[("reply", Some("gpt-4"))]),
+                pipeline("summarizer", "", &[("summarize", Some("claude-3"))]),
+            ],
+            &consumer_teams,
+            &[BudgetStatus {
+                budget_id: "search-q3".to_string(),
+                team_id: Some("search".to_string()),
+                period_budget_usd: 1_000.0,
+                spent_usd: 250.0,
+                remaining_usd: 750.0,
+                utilization_percentage: 25.0,
+                projected_overage: None,
+            }],
+        )
+    }
+
+    #[test]
+    fn test_blast_radius_follows_dependents_to_owners() {
+        let topology = topology();
+        let openai = EntityRef::new(EntityKind::Provider, "openai");
+
+        let radius = topology.blast_radius(&openai);
+        assert_eq!(
+            radius.affected[&EntityKind::Model],
+            vec!["gpt-4", "text-embedding-3"]
+        );
+        assert_eq!(
+            radius.affected[&EntityKind::Pipeline],
+            vec!["rag", "support-bot"]
+        );
+        // CostOps billing overrides the registry owner
+        assert_eq!(radius.teams, vec!["search", "support"]);
+        assert_eq!(radius.size(), 9);
+
+        let claude = EntityRef::new(EntityKind::Model, "claude-3");
+        assert!(topology.owners(&claude).is_empty());
+
+        let context = topology
+            .context(&EntityRef::new(EntityKind::Stage, "rag:generate"))
+            .unwrap();
+        assert_eq!(
+            context.dependencies,
+            vec![
+                EntityRef::new(EntityKind::Provider, "openai"),
+                EntityRef::new(EntityKind::Model, "gpt-4"),
+            ]
+        );
+        let factors = context.contributing_factors();
+        assert_eq!(
+            factors[0],
+            "stage:rag:generate depends on provider:openai, model:gpt-4"
+        );
+        assert_eq!(factors[2], "Owned by search");
+    }
+
+    #[test]
+    fn test_rollups_by_owner_split_shared_entities() {
+        let topology = topology();
+        let search = &topology.owner_rollups()[0];
+        assert_eq!(search.team, "search");
+        assert_eq!(search.entities[&EntityKind::Budget], vec!["search-q3"]);
+        assert_eq!(search.entities[&EntityKind::Provider], vec!["openai"]);
+
+        let totals = topology.rollup_by_owner([
+            (EntityRef::new(EntityKind::Model, "gpt-4"), 100.0),
+            (EntityRef::new(EntityKind::Model, "text-embedding-3"), 10.0),
+            (EntityRef::new(EntityKind::Model, "claude-3"), 5.0),
+        ]);
+        assert_eq!(totals["search"], 60.0);
+        assert_eq!(totals["support"], 50.0);
+        assert_eq!(totals[UNOWNED], 5.0);
+        assert_eq!("Stage".parse::<EntityKind>().unwrap(), EntityKind::Stage);
+    }
+}
@@ -0,0 +1,462 @@
+//! Unit Economics
+//!
+//! Divides cost aggregates by activity to answer product questions such as
+//! "what does a session cost" or "what does a user on the free tier cost".
+//! Each unit is a tag that identifies one unit of activity (`session_id`,
+//! `user_id`, ...), optionally grouped by another tag (`user_tier`, `feature`).
+//! For every complete window the job sums the cost metric per unit, then
+//! stores the distribution of per-unit cost as an aggregated metric named
+//! `unit_cost.per_<unit>`: `avg` is the cost per unit, `count` the number of
+//! active units and `sum` the total cost. Units that share a tag write the
+//! same metric, told apart by their group tag. The results are read through
+//! the metrics API like any other series.
+
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::schemas::events::TENANT_TAG;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Prefix of the aggregated metrics written by the job
+pub const UNIT_COST_METRIC_PREFIX: &str = "unit_cost";
+
+/// One unit of activity that cost is divided by
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitDefinition {
+    /// Tag identifying one unit, e.g. `session_id`
+    pub unit_tag: String,
+    /// Tag the units are grouped by, e.g. `user_tier`
+    pub group_by: Option<String>,
+}
+
+impl UnitDefinition {
+    pub fn new(unit_tag: impl Into<String>, group_by: Option<&str>) -> Self {
+        Self {
+            unit_tag: unit_tag.into(),
+            group_by: group_by.map(str::to_string),
+        }
+    }
+
+    /// Name of the stored metric, e.g. `unit_cost.per_session` for `session_id`
+    pub fn metric_name(&self) -> String {
+        let unit = self.unit_tag.strip_suffix("_id").unwrap_or(&self.unit_tag);
+        format!("{}.per_{}", UNIT_COST_METRIC_PREFIX, unit)
+    }
+}
+
+/// Unit economics job configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitEconomicsConfig {
+    /// Aggregation window, and how often the job runs
+    pub window: TimeWindow,
+    /// Aggregated metric holding cost
+    pub cost_metric: String,
+    pub units: Vec<UnitDefinition>,
+}
+
+impl Default for UnitEconomicsConfig {
+    fn default() -> Self {
+        Self {
+            window: TimeWindow::OneHour,
+            cost_metric: "total_cost_usd".to_string(),
+            units: vec![
+                UnitDefinition::new("session_id", None),
+                UnitDefinition::new("user_id", Some("user_tier")),
+                UnitDefinition::new("session_id", Some("feature")),
+            ],
+        }
+    }
+}
+
+impl UnitEconomicsConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let units = match std::env::var("UNIT_ECONOMICS_UNITS") {
+            Ok(spec) => Self::parse_units(&spec)?,
+            Err(_) => defaults.units,
+        };
+        Ok(Self {
+            window: std::env::var("UNIT_ECONOMICS_WINDOW")
+                .ok()
+                .and_then(|v| crate::database::timescale::parse_window(&v).ok())
+                .unwrap_or(defaults.window),
+            cost_metric: std::env::var("UNIT_ECONOMICS_COST_METRIC")
+                .unwrap_or(defaults.cost_metric),
+            units,
+        })
+    }
+
+    /// Parse `unit_tag[:group_by]` entries separated by commas
+    pub fn parse_units(spec: &str) -> Result<Vec<UnitDefinition>> {
+        let mut units = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').map(str::trim).collect();
+            let unit = match parts.as_slice() {
+                &[unit_tag] if !unit_tag.is_empty() => UnitDefinition::new(unit_tag, None),
+                &[unit_tag, group_by] if !unit_tag.is_empty() && !group_by.is_empty() => {
+                    UnitDefinition::new(unit_tag, Some(group_by))
+                }
+                _ => bail!("Unit definitions must be unit_tag or unit_tag:group_by"),
+            };
+            units.push(unit);
+        }
+        Ok(units)
+    }
+}
+
+/// Cost per unit for one group in one window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitCost {
+    pub metric_name: String,
+    /// Group tag and owning tenant, when present
+    pub tags: serde_json::Value,
+    /// Distribution of cost across the group's units
+    pub measures: StatisticalMeasures,
+}
+
+impl UnitCost {
+    pub fn units(&self) -> u64 {
+        self.measures.count
+    }
+
+    pub fn cost_per_unit(&self) -> f64 {
+        self.measures.avg
+    }
+}
+
+/// Divide the cost rows of one window into per-unit costs for each group
+///
+/// Rows without the unit tag (or the group tag, when grouping) carry cost that
+/// cannot be attributed to a unit and are left out. Groups never span tenants.
+pub fn unit_costs(rows: &[AggregatedMetricRow], unit: &UnitDefinition) -> Vec<UnitCost> {
+    // (tenant, group) -> unit -> cost
+    type GroupKey = (Option<String>, Option<String>);
+    let mut groups: BTreeMap<GroupKey, BTreeMap<String, f64>> = BTreeMap::new();
+    for row in rows {
+        let Some(unit_id) = row.tags[unit.unit_tag.as_str()].as_str() else {
+            continue;
+        };
+        let group = match &unit.group_by {
+            Some(tag) => match row.tags[tag.as_str()].as_str() {
+                Some(value) => Some(value.to_string()),
+                None => continue,
+            },
+            None => None,
+        };
+        let tenant = row.tags[TENANT_TAG].as_str().map(str::to_string);
+        *groups
+            .entry((tenant, group))
+            .or_default()
+            .entry(unit_id.to_string())
+            .or_default() += row.sum;
+    }
+
+    let metric_name = unit.metric_name();
+    groups
+        .into_iter()
+        .map(|((tenant, group), costs)| {
+            let mut tags = serde_json::Map::new();
+            if let (Some(tag), Some(value)) = (&unit.group_by, group) {
+                tags.insert(tag.clone(), value.into());
+            }
+            if let Some(tenant) = tenant {
+                tags.insert(TENANT_TAG.to_string(), tenant.into());
+            }
+            let costs: Vec<f64> = costs.into_values().collect();
+            UnitCost {
+                metric_name: metric_name.clone(),
+                tags: serde_json::Value::Object(tags),
+                measures: StatisticalMeasures::from_values(&costs),
+            }
+        })
+        .collect()
+}
+
+/// Result of one unit economics pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitEconomicsReport {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub unit_costs: Vec<UnitCost>,
+}
+
+/// Unit economics statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitEconomicsStats {
+    pub runs: u64,
+    pub series_written: u64,
+    pub failures: u64,
+}
+
+/// Periodically rolls cost aggregates up into per-unit costs
+pub struct UnitEconomicsJob {
+    database: Arc<Database>,
+    config: UnitEconomicsConfig,
+    runs: AtomicU64,
+    series_written: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl UnitEconomicsJob {
+    pub fn new(database: Arc<Database>, config: UnitEconomicsConfig) -> Self {
+        Self {
+            database,
+            config,
+            runs: AtomicU64::new(0),
+            series_written: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Compute unit costs for the last complete window before `now`
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<UnitEconomicsReport> {
+        let seconds = self.config.window.to_seconds() as i64;
+        let window_end =
+            DateTime::from_timestamp((now.timestamp() / seconds) * seconds, 0).unwrap_or(now);
+        let window_start = window_end - Duration::seconds(seconds);
+
+        let rows = self
+            .database
+            .query_aggregated_metrics(
+                &self.config.cost_metric,
+                self.config.window,
+                window_start,
+                window_end,
+            )
+            .await?;
+
+        let mut all_costs = Vec::new();
+        for unit in &self.config.units {
+            let costs = unit_costs(&rows, unit);
+            debug!(
+                metric = %unit.metric_name(),
+                groups = costs.len(),
+                "Computed unit costs"
+            );
+            for cost in &costs {
+                self.database
+                    .store_aggregated_metric(
+                        &cost.metric_name,
+                        self.config.window,
+                        window_start,
+                        &cost.tags,
+                        &cost.measures,
+                    )
+                    .await?;
+            }
+            all_costs.extend(costs);
+        }
+
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.series_written
+            .fetch_add(all_costs.len() as u64, Ordering::Relaxed);
+        info!(
+            cost_rows = rows.len(),
+            series = all_costs.len(),
+            "Unit economics pass complete"
+        );
+
+        Ok(UnitEconomicsReport {
+            window_start,
+            window_end,
+            unit_costs: all_costs,
+        })
+    }
+
+    /// Run once per window until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.window.to_seconds());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick fires immediately; wait for a full window instead
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run_once(Utc::now()).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Unit economics pass failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> UnitEconomicsStats {
+        UnitEconomicsStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            series_written: self.series_written.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::enrichment::{TagEnricher, TagEnrichmentConfig};
+    use crate::schemas::events::{
+        AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+        SourceModule,
+    };
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn row(tags: serde_json::Value, sum: f64) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: "total_cost_usd".to_string(),
+            time_window: "1h".to_string(),
+            window_start: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            tags,
+            avg: sum,
+            min: sum,
+            max: sum,
+            p50: sum,
+            p95: sum,
+            p99: sum,
+            stddev: None,
+            count: 1,
+            sum,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_metric_names_and_unit_parsing() {
+        assert_eq!(
+            UnitDefinition::new("session_id", None).metric_name(),
+            "unit_cost.per_session"
+        );
+        assert_eq!(
+            UnitDefinition::new("workspace", None).metric_name(),
+            "unit_cost.per_workspace"
+        );
+
+        let units = UnitEconomicsConfig::parse_units("session_id, user_id:user_tier").unwrap();
+        assert_eq!(
+            units,
+            vec![
+                UnitDefinition::new("session_id", None),
+                UnitDefinition::new("user_id", Some("user_tier")),
+            ]
+        );
+        assert!(UnitEconomicsConfig::parse_units("a:b:c").is_err());
+        assert!(UnitEconomicsConfig::parse_units("user_id:").is_err());
+    }
+
+    #[test]
+    fn test_cost_per_session() {
+        let rows = vec![
+            // Two models billed in session s1
+            row(json!({"session_id": "s1", "model": "gpt-4"}), 0.30),
+            row(json!({"session_id": "s1", "model": "claude"}), 0.10),
+            row(json!({"session_id": "s2", "model": "gpt-4"}), 0.20),
+            // Not attributable to a session
+            row(json!({"model": "gpt-4"}), 5.0),
+        ];
+        let costs = unit_costs(&rows, &UnitDefinition::new("session_id", None));
+        assert_eq!(costs.len(), 1);
+        assert_eq!(costs[0].metric_name, "unit_cost.per_session");
+        assert_eq!(costs[0].tags, json!({}));
+        assert_eq!(costs[0].units(), 2);
+        assert!((costs[0].measures.sum - 0.6).abs() < 1e-9);
+        assert!((costs[0].cost_per_unit() - 0.3).abs() < 1e-9);
+        assert!((costs[0].measures.max - 0.4).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_default_units_survive_enrichment() {
+        let event = |tags: &[(&str, &str)]| AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmCostOps,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Info,
+                environment: "production".to_string(),
+                tags: tags
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "cost".to_string(),
+                data: json!({}),
+            }),
+        };
+        let mut events = vec![
+            event(&[
+                ("session_id", "s1"),
+                ("user", "u1"),
+                ("user_tier", "free"),
+                ("feature", "search"),
+                ("request_id", "r1"),
+            ]),
+            event(&[
+                ("session_id", "s2"),
+                ("user", "u2"),
+                ("user_tier", "pro"),
+                ("feature", "chat"),
+                ("request_id", "r2"),
+            ]),
+        ];
+        TagEnricher::new(TagEnrichmentConfig::default())
+            .enrich(&mut events)
+            .await;
+
+        // Cost rows are aggregated under the enriched tags
+        let rows: Vec<AggregatedMetricRow> = events
+            .iter()
+            .map(|e| row(serde_json::to_value(&e.common.tags).unwrap(), 0.5))
+            .collect();
+        for unit in UnitEconomicsConfig::default().units {
+            let costs = unit_costs(&rows, &unit);
+            assert!(!costs.is_empty(), "{} matched no rows", unit.metric_name());
+            let units: u64 = costs.iter().map(UnitCost::units).sum();
+            assert_eq!(units, 2);
+        }
+    }
+
+    #[test]
+    fn test_cost_per_user_grouped_by_tier_and_tenant() {
+        let rows = vec![
+            row(json!({"user_id": "u1", "user_tier": "free"}), 0.01),
+            row(json!({"user_id": "u2", "user_tier": "free"}), 0.03),
+            row(json!({"user_id": "u3", "user_tier": "pro"}), 1.00),
+            row(
+                json!({"user_id": "u4", "user_tier": "pro", "tenant_id": "team-a"}),
+                2.00,
+            ),
+            // Missing the group tag
+            row(json!({"user_id": "u5"}), 9.0),
+        ];
+        let costs = unit_costs(&rows, &UnitDefinition::new("user_id", Some("user_tier")));
+        assert_eq!(costs.len(), 3);
+
+        let free = costs
+            .iter()
+            .find(|c| c.tags == json!({"user_tier": "free"}))
+            .unwrap();
+        assert_eq!(free.units(), 2);
+        assert!((free.cost_per_unit() - 0.02).abs() < 1e-9);
+
+        let pro = costs
+            .iter()
+            .find(|c| c.tags == json!({"user_tier": "pro"}))
+            .unwrap();
+        assert_eq!(pro.units(), 1);
+
+        let tenant_pro = costs
+            .iter()
+            .find(|c| c.tags == json!({"user_tier": "pro", "tenant_id": "team-a"}))
+            .unwrap();
+        assert_eq!(tenant_pro.cost_per_unit(), 2.0);
+    }
+}
@@ -0,0 +1,241 @@
+//! Event-Time Windowing
+//!
+//! Events reach the aggregation engine out of order, from several modules with
+//! different delivery delays. Windows are therefore closed by a watermark
+//! derived from event time rather than by the wall clock: the watermark trails
+//! the newest event seen by `max_out_of_orderness`, and a window is finalized
+//! once the watermark passes its end. Finalized windows stay mergeable for
+//! `allowed_lateness`, so a late event re-emits a corrected rollup; anything
+//! later than that is discarded and counted as a late arrival. With
+//! `EmissionMode::ExactlyOnce` a window is instead held until its lateness has
+//! passed and written a single time.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Watermark and lateness settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatermarkConfig {
+    /// How far the watermark trails the newest event time
+    pub max_out_of_orderness_secs: u64,
+    /// How long a finalized window still accepts late events
+    pub allowed_lateness_secs: u64,
+}
+
+impl Default for WatermarkConfig {
+    fn default() -> Self {
+        Self {
+            max_out_of_orderness_secs: 30,
+            allowed_lateness_secs: 300,
+        }
+    }
+}
+
+impl WatermarkConfig {
+    /// Read `AGGREGATION_MAX_OUT_OF_ORDERNESS_SECS` and
+    /// `AGGREGATION_ALLOWED_LATENESS_SECS`, falling back to the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_out_of_orderness_secs: std::env::var("AGGREGATION_MAX_OUT_OF_ORDERNESS_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_out_of_orderness_secs),
+            allowed_lateness_secs: std::env::var("AGGREGATION_ALLOWED_LATENESS_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.allowed_lateness_secs),
+        }
+    }
+
+    pub fn max_out_of_orderness(&self) -> Duration {
+        Duration::seconds(self.max_out_of_orderness_secs as i64)
+    }
+
+    pub fn allowed_lateness(&self) -> Duration {
+        Duration::seconds(self.allowed_lateness_secs as i64)
+    }
+}
+
+/// When finalized windows are written to storage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmissionMode {
+    /// Write when the watermark passes the window end, and again for each late event
+    #[default]
+    Corrections,
+    /// Write once, when the window's allowed lateness has passed
+    ExactlyOnce,
+}
+
+impl FromStr for EmissionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(
+            s.to_lowercase().replace('-', "_"),
+        ))
+        .with_context(|| format!("Unknown emission mode: {}", s))
+    }
+}
+
+impl EmissionMode {
+    /// Read `AGGREGATION_EMISSION_MODE`, falling back to corrections
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("AGGREGATION_EMISSION_MODE") {
+            Ok(mode) => mode.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+/// Where an event falls relative to a window and the watermark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowState {
+    /// The window has not been finalized yet
+    Open,
+    /// The window was finalized but is within its allowed lateness
+    Late,
+    /// The window is past its allowed lateness
+    Expired,
+}
+
+/// Event-time watermark
+#[derive(Debug, Clone, Copy)]
+pub struct Watermark {
+    config: WatermarkConfig,
+    max_event_time: Option<DateTime<Utc>>,
+}
+
+impl Watermark {
+    pub fn new(config: WatermarkConfig) -> Self {
+        Self {
+            config,
+            max_event_time: None,
+        }
+    }
+
+    pub fn config(&self) -> &WatermarkConfig {
+        &self.config
+    }
+
+    /// Advance with an event time; returns whether the watermark moved
+    pub fn observe(&mut self, event_time: DateTime<Utc>) -> bool {
+        if matches!(self.max_event_time, Some(max) if max >= event_time) {
+            return false;
+        }
+        self.max_event_time = Some(event_time);
+        true
+    }
+
+    /// Newest event time seen
+    pub fn max_event_time(&self) -> Option<DateTime<Utc>> {
+        self.max_event_time
+    }
+
+    /// Event time up to which all events are assumed to have arrived
+    pub fn current(&self) -> Option<DateTime<Utc>> {
+        self.max_event_time
+            .map(|max| max - self.config.max_out_of_orderness())
+    }
+
+    /// Whether the window ending at `window_end` is still open, late, or expired
+    pub fn classify(&self, window_end: DateTime<Utc>) -> WindowState {
+        match self.current() {
+            Some(watermark) if window_end + self.config.allowed_lateness() <= watermark => {
+                WindowState::Expired
+            }
+            Some(watermark) if window_end <= watermark => WindowState::Late,
+            _ => WindowState::Open,
+        }
+    }
+}
+
+/// Late-arrival counts for one source module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LateArrivalCounts {
+    /// Events behind the watermark when they arrived
+    pub late_events: u64,
+    /// Finalized windows re-emitted with a late event merged in
+    pub corrected_windows: u64,
+    /// Events discarded from at least one window past its allowed lateness
+    pub dropped_events: u64,
+}
+
+/// Late-arrival counts keyed by source module
+#[derive(Debug, Default)]
+pub struct LateArrivalStats {
+    by_module: DashMap<String, LateArrivalCounts>,
+}
+
+impl LateArrivalStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one late event
+    pub fn record(&self, source_module: &str, corrected_windows: u64, dropped: bool) {
+        let mut counts = self.by_module.entry(source_module.to_string()).or_default();
+        counts.late_events += 1;
+        counts.corrected_windows += corrected_windows;
+        if dropped {
+            counts.dropped_events += 1;
+        }
+    }
+
+    /// Counts per source module, sorted by module
+    pub fn snapshot(&self) -> Vec<(String, LateArrivalCounts)> {
+        let mut snapshot: Vec<_> = self
+            .by_module
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        snapshot.sort_by(|a, b| a.0.cmp(&b.0));
+        snapshot
+    }
+
+    /// Counts summed over all modules
+    pub fn totals(&self) -> LateArrivalCounts {
+        self.by_module
+            .iter()
+            .fold(LateArrivalCounts::default(), |mut totals, entry| {
+                totals.late_events += entry.late_events;
+                totals.corrected_windows += entry.corrected_windows;
+                totals.dropped_events += entry.dropped_events;
+                totals
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_trails_newest_event() {
+        let mut watermark = Watermark::new(WatermarkConfig {
+            max_out_of_orderness_secs: 10,
+            allowed_lateness_secs: 60,
+        });
+        let t0 = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(watermark.current(), None);
+        assert_eq!(watermark.classify(t0), WindowState::Open);
+
+        assert!(watermark.observe(t0 + Duration::seconds(70)));
+        assert!(!watermark.observe(t0));
+        assert_eq!(watermark.current(), Some(t0 + Duration::seconds(60)));
+
+        assert_eq!(
+            watermark.classify(t0 + Duration::seconds(120)),
+            WindowState::Open
+        );
+        assert_eq!(
+            watermark.classify(t0 + Duration::seconds(60)),
+            WindowState::Late
+        );
+        assert_eq!(watermark.classify(t0), WindowState::Expired);
+    }
+}
@@ -0,0 +1,511 @@
+//! Cold Data Archival
+//!
+//! Exports aged hypertable partitions to object storage as compressed JSON
+//! Lines, verifies each upload by checksum, and records an archive manifest so
+//! partitions can be restored on demand. Archival jobs are produced by the
+//! retention enforcer; the archiver only copies data, dropping it remains the
+//! job of the TimescaleDB retention policy. Queries reaching past hot
+//! retention read archived partitions through `tiered`.
+
+pub mod store;
+pub mod tiered;
+
+pub use store::{object_store_for, AzureBlobStore, ObjectStore, S3ObjectStore};
+pub use tiered::{DataSource, Sourced, TieredQuery, TieredQueryConfig, TieredResult};
+
+use crate::adapters::config_manager::CompressionType;
+use crate::audit::{self, AuditRecord};
+use crate::database::environment::default_environment;
+use crate::database::Database;
+use crate::retention::ArchivalJob;
+use crate::schemas::events::{AnalyticsEvent, AuditTrailEvent};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+/// Serialization format of archive objects
+pub const ARCHIVE_FORMAT: &str = "jsonl";
+
+/// Zstd level used for archive objects
+const ZSTD_LEVEL: i32 = 9;
+
+/// Actor recorded on audit trail events for archive exports and restores
+pub const ARCHIVAL_AUDIT_ACTOR: &str = "llm-analytics-hub/archival";
+
+/// Hypertable that can be archived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveTable {
+    Events,
+    AggregatedMetrics,
+    Anomalies,
+}
+
+impl ArchiveTable {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "events" => Ok(ArchiveTable::Events),
+            "aggregated_metrics" => Ok(ArchiveTable::AggregatedMetrics),
+            "anomalies" => Ok(ArchiveTable::Anomalies),
+            other => anyhow::bail!("Table {} cannot be archived", other),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArchiveTable::Events => "events",
+            ArchiveTable::AggregatedMetrics => "aggregated_metrics",
+            ArchiveTable::Anomalies => "anomalies",
+        }
+    }
+
+    /// Time column the hypertable is partitioned on
+    pub fn time_column(&self) -> &'static str {
+        match self {
+            ArchiveTable::Events => "timestamp",
+            ArchiveTable::AggregatedMetrics => "window_start",
+            ArchiveTable::Anomalies => "detected_at",
+        }
+    }
+}
+
+/// Record of an archived partition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchiveManifest {
+    pub archive_id: Uuid,
+    pub table_name: String,
+    pub partition_start: DateTime<Utc>,
+    pub partition_end: DateTime<Utc>,
+    pub object_key: String,
+    pub object_uri: String,
+    pub format: String,
+    pub compression: String,
+    pub row_count: i64,
+    pub uncompressed_bytes: i64,
+    pub compressed_bytes: i64,
+    /// Hex SHA-256 of the stored object
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub restored_at: Option<DateTime<Utc>>,
+}
+
+/// Encoded archive object ready for upload
+#[derive(Debug, Clone)]
+pub struct EncodedArchive {
+    pub body: Vec<u8>,
+    pub sha256: String,
+    pub row_count: usize,
+    pub uncompressed_bytes: usize,
+}
+
+fn compression_label(compression: &CompressionType) -> Result<&'static str> {
+    match compression {
+        CompressionType::None => Ok("none"),
+        CompressionType::Zstd => Ok("zstd"),
+        other => anyhow::bail!("Archive compression {:?} is not supported", other),
+    }
+}
+
+/// Hex SHA-256 digest
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let mut out = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+/// Serialize rows as JSON Lines and compress them
+pub fn encode_archive(
+    rows: &[serde_json::Value],
+    compression: &CompressionType,
+) -> Result<EncodedArchive> {
+    let mut jsonl = Vec::new();
+    for row in rows {
+        serde_json::to_writer(&mut jsonl, row)?;
+        jsonl.write_all(b"\n")?;
+    }
+    let uncompressed_bytes = jsonl.len();
+
+    let body = match compression_label(compression)? {
+        "zstd" => {
+            zstd::encode_all(jsonl.as_slice(), ZSTD_LEVEL).context("Failed to compress archive")?
+        }
+        _ => jsonl,
+    };
+
+    Ok(EncodedArchive {
+        sha256: sha256_hex(&body),
+        body,
+        row_count: rows.len(),
+        uncompressed_bytes,
+    })
+}
+
+/// Verify and decode an archive object back into rows
+pub fn decode_archive(
+    body: &[u8],
+    compression: &str,
+    expected_sha256: &str,
+) -> Result<Vec<serde_json::Value>> {
+    let actual = sha256_hex(body);
+    if actual != expected_sha256 {
+        anyhow::bail!(
+            "Archive checksum mismatch: expected {}, got {}",
+            expected_sha256,
+            actual
+        );
+    }
+
+    let jsonl = match compression {
+        "zstd" => zstd::decode_all(body).context("Failed to decompress archive")?,
+        "none" => body.to_vec(),
+        other => anyhow::bail!("Unknown archive compression {}", other),
+    };
+
+    jsonl
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).context("Malformed archive row"))
+        .collect()
+}
+
+/// Object key of a daily partition, e.g. `events/2025/01/31/events-20250131.jsonl.zst`
+pub fn partition_key(
+    table: ArchiveTable,
+    partition_start: DateTime<Utc>,
+    compression: &str,
+) -> String {
+    let extension = match compression {
+        "zstd" => ".zst",
+        _ => "",
+    };
+    format!(
+        "{}/{}/{}-{}.{}{}",
+        table.name(),
+        partition_start.format("%Y/%m/%d"),
+        table.name(),
+        partition_start.format("%Y%m%d"),
+        ARCHIVE_FORMAT,
+        extension
+    )
+}
+
+/// Build the audit trail event recording an archive export or restore
+pub fn audit_event(action: &str, manifest: &ArchiveManifest, environment: &str) -> AnalyticsEvent {
+    let mut changes = HashMap::new();
+    changes.insert("table".to_string(), serde_json::json!(manifest.table_name));
+    changes.insert(
+        "partition_start".to_string(),
+        serde_json::json!(manifest.partition_start),
+    );
+    changes.insert(
+        "object_uri".to_string(),
+        serde_json::json!(manifest.object_uri),
+    );
+    changes.insert(
+        "row_count".to_string(),
+        serde_json::json!(manifest.row_count),
+    );
+
+    audit::audit_event(
+        AuditTrailEvent {
+            action: format!("archive.{}", action),
+            actor: ARCHIVAL_AUDIT_ACTOR.to_string(),
+            resource_type: "archive".to_string(),
+            resource_id: manifest.archive_id.to_string(),
+            changes,
+            ip_address: None,
+            user_agent: None,
+        },
+        Utc::now(),
+        environment,
+    )
+}
+
+/// Exports partitions to object storage and restores them on demand
+pub struct Archiver {
+    database: Arc<Database>,
+    store: Arc<dyn ObjectStore>,
+    partitions_archived: AtomicU64,
+    rows_archived: AtomicU64,
+    partitions_restored: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl Archiver {
+    pub fn new(database: Arc<Database>, store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            database,
+            store,
+            partitions_archived: AtomicU64::new(0),
+            rows_archived: AtomicU64::new(0),
+            partitions_restored: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Archive every whole day older than the job cutoff that has no manifest yet
+    #[instrument(skip(self, job), fields(table = %job.table, policy = %job.policy_id))]
+    pub async fn archive_job(&self, job: &ArchivalJob) -> Result<Vec<ArchiveManifest>> {
+        let table = ArchiveTable::from_name(&job.table)?;
+        let cutoff = job
+            .archive_before
+            .duration_trunc(Duration::days(1))
+            .unwrap_or(job.archive_before);
+        let partitions = self
+            .database
+            .query_unarchived_partitions(table, cutoff)
+            .await?;
+
+        let mut manifests = Vec::with_capacity(partitions.len());
+        for partition_start in partitions {
+            match self
+                .archive_partition(table, partition_start, &job.compression)
+                .await
+            {
+                Ok(manifest) => manifests.push(manifest),
+                Err(e) => {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    error!(%partition_start, "Failed to archive partition: {:#}", e);
+                }
+            }
+        }
+
+        Ok(manifests)
+    }
+
+    /// Export, upload, verify, and record a single daily partition
+    pub async fn archive_partition(
+        &self,
+        table: ArchiveTable,
+        partition_start: DateTime<Utc>,
+        compression: &CompressionType,
+    ) -> Result<ArchiveManifest> {
+        let partition_end = partition_start + Duration::days(1);
+        let rows = self
+            .database
+            .export_partition_rows(table, partition_start, partition_end)
+            .await?;
+        let encoded = encode_archive(&rows, compression)?;
+        let compression = compression_label(compression)?;
+        let key = partition_key(table, partition_start, compression);
+        let compressed_bytes = encoded.body.len();
+
+        let uri = self.store.put(&key, encoded.body).await?;
+
+        // Read the object back so a manifest is only written for intact uploads
+        let stored = self.store.get(&key).await?;
+        let verified = decode_archive(&stored, compression, &encoded.sha256)?;
+        if verified.len() != encoded.row_count {
+            anyhow::bail!(
+                "Archive {} holds {} rows, expected {}",
+                uri,
+                verified.len(),
+                encoded.row_count
+            );
+        }
+
+        let now = Utc::now();
+        let manifest = ArchiveManifest {
+            archive_id: Uuid::new_v4(),
+            table_name: table.name().to_string(),
+            partition_start,
+            partition_end,
+            object_key: key,
+            object_uri: uri,
+            format: ARCHIVE_FORMAT.to_string(),
+            compression: compression.to_string(),
+            row_count: encoded.row_count as i64,
+            uncompressed_bytes: encoded.uncompressed_bytes as i64,
+            compressed_bytes: compressed_bytes as i64,
+            sha256: encoded.sha256,
+            created_at: now,
+            verified_at: Some(now),
+            restored_at: None,
+        };
+        self.database.store_archive_manifest(&manifest).await?;
+
+        self.record_audit("export", &manifest).await;
+
+        self.partitions_archived.fetch_add(1, Ordering::Relaxed);
+        self.rows_archived
+            .fetch_add(encoded.row_count as u64, Ordering::Relaxed);
+        info!(
+            uri = %manifest.object_uri,
+            rows = manifest.row_count,
+            bytes = manifest.compressed_bytes,
+            "Archived partition"
+        );
+        Ok(manifest)
+    }
+
+    /// Load an archived partition back into its hypertable.
+    ///
+    /// Restored rows older than the retention window are dropped again by the
+    /// next retention run, so restores are meant for short-lived investigation.
+    #[instrument(skip(self))]
+    pub async fn restore(&self, archive_id: Uuid) -> Result<u64> {
+        let manifest = self
+            .database
+            .get_archive_manifest(archive_id)
+            .await?
+            .with_context(|| format!("Archive {} not found", archive_id))?;
+        let table = ArchiveTable::from_name(&manifest.table_name)?;
+
+        let body = self.store.get(&manifest.object_key).await?;
+        let rows = decode_archive(&body, &manifest.compression, &manifest.sha256)?;
+        let inserted = self.database.restore_archive_rows(table, &rows).await?;
+        self.database.mark_archive_restored(archive_id).await?;
+        self.record_audit("restore", &manifest).await;
+
+        self.partitions_restored.fetch_add(1, Ordering::Relaxed);
+        info!(
+            uri = %manifest.object_uri,
+            rows = rows.len(),
+            inserted,
+            "Restored archived partition"
+        );
+        Ok(inserted)
+    }
+
+    /// Restore every archived partition of a table overlapping `[start, end)`
+    pub async fn restore_range(
+        &self,
+        table: ArchiveTable,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<u64> {
+        let manifests = self
+            .database
+            .query_archive_manifests(table, start, end)
+            .await?;
+        let mut inserted = 0;
+        for manifest in manifests {
+            inserted += self.restore(manifest.archive_id).await?;
+        }
+        Ok(inserted)
+    }
+
+    /// Store the audit record of an export or restore; the data operation has
+    /// already succeeded, so a failure here is logged rather than returned
+    async fn record_audit(&self, action: &str, manifest: &ArchiveManifest) {
+        let event = audit_event(action, manifest, &default_environment());
+        let Some(record) = AuditRecord::from_event(&event) else {
+            return;
+        };
+        if let Err(e) = self.database.store_audit_record(&record).await {
+            warn!(archive_id = %manifest.archive_id, "Failed to store archive audit record: {}", e);
+        }
+    }
+
+    /// Process archival jobs from the retention enforcer until the channel closes
+    pub async fn run(&self, mut jobs: mpsc::Receiver<ArchivalJob>) {
+        while let Some(job) = jobs.recv().await {
+            if let Err(e) = self.archive_job(&job).await {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                warn!(policy = %job.policy_id, "Archival job failed: {:#}", e);
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> ArchiverStats {
+        ArchiverStats {
+            partitions_archived: self.partitions_archived.load(Ordering::Relaxed),
+            rows_archived: self.rows_archived.load(Ordering::Relaxed),
+            partitions_restored: self.partitions_restored.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Archiver statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiverStats {
+    pub partitions_archived: u64,
+    pub rows_archived: u64,
+    pub partitions_restored: u64,
+    pub failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn rows() -> Vec<serde_json::Value> {
+        vec![
+            json!({"event_id": "a", "payload": {"k": 1}}),
+            json!({"event_id": "b", "payload": {"k": "line\nbreak"}}),
+        ]
+    }
+
+    #[test]
+    fn test_zstd_round_trip() {
+        let encoded = encode_archive(&rows(), &CompressionType::Zstd).unwrap();
+
+        assert_eq!(encoded.row_count, 2);
+        assert_eq!(encoded.sha256.len(), 64);
+
+        let decoded = decode_archive(&encoded.body, "zstd", &encoded.sha256).unwrap();
+        assert_eq!(decoded, rows());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_is_rejected() {
+        let mut encoded = encode_archive(&rows(), &CompressionType::None).unwrap();
+        encoded.body[0] = b' ';
+
+        let err = decode_archive(&encoded.body, "none", &encoded.sha256).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn test_unsupported_compression() {
+        assert!(encode_archive(&rows(), &CompressionType::Snappy).is_err());
+    }
+
+    #[test]
+    fn test_partition_key_layout() {
+        let day = Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap();
+        assert_eq!(
+            partition_key(ArchiveTable::Events, day, "zstd"),
+            "events/2025/01/31/events-20250131.jsonl.zst"
+        );
+        assert_eq!(
+            partition_key(ArchiveTable::Anomalies, day, "none"),
+            "anomalies/2025/01/31/anomalies-20250131.jsonl"
+        );
+    }
+
+    #[test]
+    fn test_sha256_hex_known_vector() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_archive_table_names() {
+        assert_eq!(
+            ArchiveTable::from_name("aggregated_metrics")
+                .unwrap()
+                .time_column(),
+            "window_start"
+        );
+        assert!(ArchiveTable::from_name("correlations").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Object Storage Backends
+//!
+//! Minimal put/get interface over the archival destinations declared in
+//! `ArchivalConfig`. GCS is reached through its S3-compatible XML API using
+//! HMAC credentials; Azure uses the Blob REST API with a SAS token.
+
+use crate::adapters::config_manager::ArchivalDestination;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
+use std::sync::Arc;
+use tracing::debug;
+
+/// GCS endpoint for S3-compatible access
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+
+/// Object storage holding archive files
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Upload an object and return its URI
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<String>;
+
+    /// Download an object
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+}
+
+/// Build the store for a configured destination
+pub async fn object_store_for(
+    destination: &ArchivalDestination,
+    encryption_enabled: bool,
+) -> Result<Arc<dyn ObjectStore>> {
+    Ok(match destination {
+        ArchivalDestination::S3 { bucket, prefix } => {
+            Arc::new(S3ObjectStore::new(bucket, prefix, encryption_enabled, None).await)
+        }
+        ArchivalDestination::GCS { bucket, prefix } => {
+            Arc::new(S3ObjectStore::new(bucket, prefix, false, Some(GCS_ENDPOINT)).await)
+        }
+        ArchivalDestination::Azure { container, prefix } => {
+            Arc::new(AzureBlobStore::from_env(container, prefix)?)
+        }
+    })
+}
+
+/// S3 (or S3-compatible) bucket
+pub struct S3ObjectStore {
+    client: S3Client,
+    bucket: String,
+    prefix: String,
+    encryption_enabled: bool,
+    scheme: &'static str,
+}
+
+impl S3ObjectStore {
+    /// Create a store using the default AWS credential chain.
+    ///
+    /// `endpoint` points the client at an S3-compatible service such as GCS.
+    pub async fn new(
+        bucket: &str,
+        prefix: &str,
+        encryption_enabled: bool,
+        endpoint: Option<&str>,
+    ) -> Self {
+        let aws_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .load()
+            .await;
+        let mut builder = aws_sdk_s3::config::Builder::from(&aws_config);
+        if let Some(endpoint) = endpoint {
+            builder = builder
+                .endpoint_url(endpoint)
+                .region(aws_sdk_s3::config::Region::new("auto"))
+                .force_path_style(true);
+        }
+
+        Self {
+            client: S3Client::from_conf(builder.build()),
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            encryption_enabled,
+            scheme: if endpoint == Some(GCS_ENDPOINT) {
+                "gs"
+            } else {
+                "s3"
+            },
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3ObjectStore {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<String> {
+        let object_key = self.object_key(key);
+        debug!(bucket = %self.bucket, key = %object_key, "Uploading archive object");
+
+        let mut request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&object_key)
+            .body(ByteStream::from(body));
+        if self.encryption_enabled {
+            request =
+                request.server_side_encryption(aws_sdk_s3::types::ServerSideEncryption::Aes256);
+        }
+        request
+            .send()
+            .await
+            .context("Failed to upload archive object")?;
+
+        Ok(format!("{}://{}/{}", self.scheme, self.bucket, object_key))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key))
+            .send()
+            .await
+            .context("Failed to download archive object")?;
+        let body = response
+            .body
+            .collect()
+            .await
+            .context("Failed to read archive object")?;
+
+        Ok(body.into_bytes().to_vec())
+    }
+}
+
+/// Azure Blob Storage container
+pub struct AzureBlobStore {
+    http: reqwest::Client,
+    account: String,
+    container: String,
+    prefix: String,
+    sas_token: String,
+}
+
+impl AzureBlobStore {
+    pub fn new(account: &str, container: &str, prefix: &str, sas_token: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            account: account.to_string(),
+            container: container.to_string(),
+            prefix: prefix.to_string(),
+            sas_token: sas_token.trim_start_matches('?').to_string(),
+        }
+    }
+
+    /// Read the account and SAS token from AZURE_STORAGE_ACCOUNT and AZURE_STORAGE_SAS_TOKEN
+    pub fn from_env(container: &str, prefix: &str) -> Result<Self> {
+        let account =
+            std::env::var("AZURE_STORAGE_ACCOUNT").context("AZURE_STORAGE_ACCOUNT not set")?;
+        let sas_token =
+            std::env::var("AZURE_STORAGE_SAS_TOKEN").context("AZURE_STORAGE_SAS_TOKEN not set")?;
+        Ok(Self::new(&account, container, prefix, &sas_token))
+    }
+
+    fn blob_url(&self, key: &str) -> String {
+        format!(
+            "https://{}.blob.core.windows.net/{}/{}{}",
+            self.account, self.container, self.prefix, key
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for AzureBlobStore {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<String> {
+        let url = self.blob_url(key);
+        debug!(url = %url, "Uploading archive blob");
+
+        self.http
+            .put(format!("{}?{}", url, self.sas_token))
+            .header("x-ms-blob-type", "BlockBlob")
+            .body(body)
+            .send()
+            .await
+            .context("Failed to upload archive blob")?
+            .error_for_status()
+            .context("Azure rejected archive upload")?;
+
+        Ok(url)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let response = self
+            .http
+            .get(format!("{}?{}", self.blob_url(key), self.sas_token))
+            .send()
+            .await
+            .context("Failed to download archive blob")?
+            .error_for_status()
+            .context("Azure rejected archive download")?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
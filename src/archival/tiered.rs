@@ -0,0 +1,546 @@
+//! Tiered Storage Queries
+//!
+//! Answers range queries that reach past hot retention. The requested range is
+//! split at the table's retention boundary: the recent part is read from
+//! TimescaleDB and the older part from the archived partitions recorded in the
+//! archive manifests. Every row carries the tier it was read from, and spans
+//! of the archived part with no manifest are reported as gaps so callers can
+//! tell an incomplete answer from an empty one.
+
+use super::{decode_archive, ArchiveManifest, ArchiveTable, ObjectStore};
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::metrics::TimeWindow;
+use crate::pipeline::hot_cache::tags_contain;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+/// Storage tier a row was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    Hot,
+    Archive,
+}
+
+/// Row tagged with the tier it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sourced<T> {
+    pub source: DataSource,
+    #[serde(flatten)]
+    pub row: T,
+}
+
+impl<T> Sourced<T> {
+    pub fn hot(row: T) -> Self {
+        Self {
+            source: DataSource::Hot,
+            row,
+        }
+    }
+
+    pub fn archive(row: T) -> Self {
+        Self {
+            source: DataSource::Archive,
+            row,
+        }
+    }
+}
+
+/// Part of a query answered by one tier or archived partition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub source: DataSource,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub rows: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_uri: Option<String>,
+}
+
+/// Range past hot retention that no archived partition covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveGap {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Rows merged from both tiers, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieredResult<T> {
+    pub rows: Vec<Sourced<T>>,
+    pub sources: Vec<SourceSpan>,
+    pub gaps: Vec<ArchiveGap>,
+    /// Start of hot retention when the query was answered, if the table has a retention policy
+    pub hot_from: Option<DateTime<Utc>>,
+    /// Whether the hot portion was served from the result cache
+    pub from_cache: bool,
+    /// Seconds until the cached hot portion expires
+    pub cache_ttl: Option<u32>,
+}
+
+impl<T> TieredResult<T> {
+    /// Whether every part of the requested range was readable
+    pub fn complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+
+    /// Human-readable notes on gaps and archive reads, for query warnings
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .gaps
+            .iter()
+            .map(|gap| {
+                format!(
+                    "No hot or archived data for {} to {}",
+                    gap.start.to_rfc3339(),
+                    gap.end.to_rfc3339()
+                )
+            })
+            .collect();
+        let partitions = self
+            .sources
+            .iter()
+            .filter(|span| span.source == DataSource::Archive)
+            .count();
+        if partitions > 0 {
+            warnings.push(format!(
+                "Read {} archived partitions for data older than hot retention",
+                partitions
+            ));
+        }
+        warnings
+    }
+}
+
+/// Tiered query configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieredQueryConfig {
+    /// Decoded partitions kept in memory between queries
+    pub max_cached_partitions: usize,
+    /// Archived partitions a single query may read; older ones are reported as gaps
+    pub max_archive_partitions: usize,
+}
+
+impl Default for TieredQueryConfig {
+    fn default() -> Self {
+        Self {
+            max_cached_partitions: 32,
+            max_archive_partitions: 90,
+        }
+    }
+}
+
+impl TieredQueryConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_cached_partitions: std::env::var("TIERED_MAX_CACHED_PARTITIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_cached_partitions),
+            max_archive_partitions: std::env::var("TIERED_MAX_ARCHIVE_PARTITIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_archive_partitions),
+        }
+    }
+}
+
+type Range = (DateTime<Utc>, DateTime<Utc>);
+
+/// Split `[start, end)` at `hot_from` into the archived part and the hot part.
+///
+/// Without a retention boundary the whole range is hot.
+pub fn split_range(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    hot_from: Option<DateTime<Utc>>,
+) -> (Option<Range>, Option<Range>) {
+    match hot_from {
+        None => (None, Some((start, end))),
+        Some(boundary) if boundary <= start => (None, Some((start, end))),
+        Some(boundary) if boundary >= end => (Some((start, end)), None),
+        Some(boundary) => (Some((start, boundary)), Some((boundary, end))),
+    }
+}
+
+/// Latest manifest of each partition, ordered by partition start.
+///
+/// A partition archived twice keeps both manifests; the newer upload wins.
+pub fn latest_manifests(mut manifests: Vec<ArchiveManifest>) -> Vec<ArchiveManifest> {
+    manifests.sort_by_key(|m| (m.partition_start, m.created_at));
+    let mut latest: Vec<ArchiveManifest> = Vec::with_capacity(manifests.len());
+    for manifest in manifests {
+        match latest.last_mut() {
+            Some(last) if last.partition_start == manifest.partition_start => *last = manifest,
+            _ => latest.push(manifest),
+        }
+    }
+    latest
+}
+
+/// Parts of `[start, end)` not covered by any manifest, which must be sorted by start
+pub fn archive_gaps(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    manifests: &[ArchiveManifest],
+) -> Vec<ArchiveGap> {
+    let mut gaps = Vec::new();
+    let mut covered_to = start;
+    for manifest in manifests {
+        if covered_to >= end {
+            break;
+        }
+        if manifest.partition_start > covered_to {
+            gaps.push(ArchiveGap {
+                start: covered_to,
+                end: manifest.partition_start.min(end),
+            });
+        }
+        covered_to = covered_to.max(manifest.partition_end);
+    }
+    if covered_to < end {
+        gaps.push(ArchiveGap {
+            start: covered_to,
+            end,
+        });
+    }
+    gaps
+}
+
+/// Archived aggregate rows of one series inside `[start, end)`
+fn archived_aggregates(
+    rows: &[serde_json::Value],
+    metric_name: &str,
+    time_window: TimeWindow,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tags: Option<&serde_json::Value>,
+) -> Vec<AggregatedMetricRow> {
+    rows.iter()
+        .filter(|row| row.get("metric_name").and_then(|v| v.as_str()) == Some(metric_name))
+        .filter(|row| row.get("time_window").and_then(|v| v.as_str()) == Some(time_window.as_str()))
+        .filter_map(|row| serde_json::from_value::<AggregatedMetricRow>(row.clone()).ok())
+        .filter(|row| row.window_start >= start && row.window_start < end)
+        .filter(|row| tags.map_or(true, |filter| tags_contain(&row.tags, filter)))
+        .collect()
+}
+
+/// Query layer reading hot data from TimescaleDB and older data from archives
+pub struct TieredQuery {
+    database: Arc<Database>,
+    store: Arc<dyn ObjectStore>,
+    config: TieredQueryConfig,
+    retention: RwLock<HashMap<String, Duration>>,
+    partitions: Mutex<VecDeque<(Uuid, Arc<Vec<serde_json::Value>>)>>,
+    archive_queries: AtomicU64,
+    partitions_read: AtomicU64,
+    partition_cache_hits: AtomicU64,
+}
+
+impl TieredQuery {
+    pub fn new(
+        database: Arc<Database>,
+        store: Arc<dyn ObjectStore>,
+        config: TieredQueryConfig,
+    ) -> Self {
+        Self {
+            database,
+            store,
+            config,
+            retention: RwLock::new(HashMap::new()),
+            partitions: Mutex::new(VecDeque::new()),
+            archive_queries: AtomicU64::new(0),
+            partitions_read: AtomicU64::new(0),
+            partition_cache_hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Load retention windows from the policy jobs registered in the database,
+    /// returning how many tables have one
+    pub async fn refresh_retention(&self) -> Result<usize> {
+        let policies = self.database.query_hypertable_policies().await?;
+        let retention: HashMap<String, Duration> = policies
+            .into_iter()
+            .filter(|policy| policy.proc_name == "policy_retention")
+            .filter_map(|policy| {
+                let secs = policy.after_secs?;
+                Some((policy.hypertable_name, Duration::seconds(secs as i64)))
+            })
+            .collect();
+        let count = retention.len();
+        *self.retention.write() = retention;
+        debug!(tables = count, "Refreshed tiered query retention windows");
+        Ok(count)
+    }
+
+    /// Oldest time still held in the hot tier of `table` as of `now`
+    pub fn hot_from(&self, table: ArchiveTable, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.retention
+            .read()
+            .get(table.name())
+            .map(|retention| now - *retention)
+    }
+
+    /// Decoded rows of an archived partition, from memory when recently read
+    async fn partition_rows(
+        &self,
+        manifest: &ArchiveManifest,
+    ) -> Result<Arc<Vec<serde_json::Value>>> {
+        if let Some((_, rows)) = self
+            .partitions
+            .lock()
+            .iter()
+            .find(|(archive_id, _)| *archive_id == manifest.archive_id)
+        {
+            self.partition_cache_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(rows.clone());
+        }
+
+        let body = self.store.get(&manifest.object_key).await?;
+        let rows = Arc::new(
+            decode_archive(&body, &manifest.compression, &manifest.sha256)
+                .with_context(|| format!("Failed to read archive {}", manifest.object_uri))?,
+        );
+        self.partitions_read.fetch_add(1, Ordering::Relaxed);
+
+        let mut partitions = self.partitions.lock();
+        partitions.push_back((manifest.archive_id, rows.clone()));
+        while partitions.len() > self.config.max_cached_partitions {
+            partitions.pop_front();
+        }
+        Ok(rows)
+    }
+
+    /// Aggregates of one series over `[start, end)`, reading archived partitions
+    /// for the part older than hot retention
+    #[instrument(skip(self, tags))]
+    pub async fn aggregates(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: Option<&serde_json::Value>,
+        now: DateTime<Utc>,
+    ) -> Result<TieredResult<AggregatedMetricRow>> {
+        let hot_from = self.hot_from(ArchiveTable::AggregatedMetrics, now);
+        let (archived, hot) = split_range(start, end, hot_from);
+        let mut result = TieredResult {
+            rows: Vec::new(),
+            sources: Vec::new(),
+            gaps: Vec::new(),
+            hot_from,
+            from_cache: false,
+            cache_ttl: None,
+        };
+
+        if let Some((archive_start, archive_end)) = archived {
+            self.archive_queries.fetch_add(1, Ordering::Relaxed);
+            let mut manifests = latest_manifests(
+                self.database
+                    .query_archive_manifests(
+                        ArchiveTable::AggregatedMetrics,
+                        archive_start,
+                        archive_end,
+                    )
+                    .await?,
+            );
+            // Prefer the partitions nearest the hot tier; older ones surface as gaps
+            if manifests.len() > self.config.max_archive_partitions {
+                manifests.drain(..manifests.len() - self.config.max_archive_partitions);
+            }
+            result.gaps = archive_gaps(archive_start, archive_end, &manifests);
+
+            for manifest in &manifests {
+                let rows = self.partition_rows(manifest).await?;
+                let span_start = manifest.partition_start.max(archive_start);
+                let span_end = manifest.partition_end.min(archive_end);
+                let matched = archived_aggregates(
+                    &rows,
+                    metric_name,
+                    time_window,
+                    span_start,
+                    span_end,
+                    tags,
+                );
+                result.sources.push(SourceSpan {
+                    source: DataSource::Archive,
+                    start: span_start,
+                    end: span_end,
+                    rows: matched.len() as u64,
+                    archive_id: Some(manifest.archive_id),
+                    object_uri: Some(manifest.object_uri.clone()),
+                });
+                result
+                    .rows
+                    .extend(matched.into_iter().map(Sourced::archive));
+            }
+        }
+
+        if let Some((hot_start, hot_end)) = hot {
+            let cached = self
+                .database
+                .query_aggregates_cached(metric_name, time_window, hot_start, hot_end, tags)
+                .await?;
+            result.sources.push(SourceSpan {
+                source: DataSource::Hot,
+                start: hot_start,
+                end: hot_end,
+                rows: cached.rows.len() as u64,
+                archive_id: None,
+                object_uri: None,
+            });
+            result.from_cache = cached.from_cache;
+            result.cache_ttl = cached.cache_ttl;
+            result
+                .rows
+                .extend(cached.rows.into_iter().map(Sourced::hot));
+        }
+
+        result.rows.sort_by_key(|row| row.row.window_start);
+        Ok(result)
+    }
+
+    pub fn get_stats(&self) -> TieredQueryStats {
+        TieredQueryStats {
+            archive_queries: self.archive_queries.load(Ordering::Relaxed),
+            partitions_read: self.partitions_read.load(Ordering::Relaxed),
+            partition_cache_hits: self.partition_cache_hits.load(Ordering::Relaxed),
+            cached_partitions: self.partitions.lock().len(),
+        }
+    }
+}
+
+/// Tiered query statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieredQueryStats {
+    pub archive_queries: u64,
+    pub partitions_read: u64,
+    pub partition_cache_hits: u64,
+    pub cached_partitions: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, d, 0, 0, 0).unwrap()
+    }
+
+    fn manifest(d: u32, created_at: DateTime<Utc>) -> ArchiveManifest {
+        ArchiveManifest {
+            archive_id: Uuid::new_v4(),
+            table_name: "aggregated_metrics".to_string(),
+            partition_start: day(d),
+            partition_end: day(d + 1),
+            object_key: format!("aggregated_metrics/2025/01/{:02}", d),
+            object_uri: format!("s3://archive/aggregated_metrics/2025/01/{:02}", d),
+            format: "jsonl".to_string(),
+            compression: "zstd".to_string(),
+            row_count: 1,
+            uncompressed_bytes: 1,
+            compressed_bytes: 1,
+            sha256: String::new(),
+            created_at,
+            verified_at: None,
+            restored_at: None,
+        }
+    }
+
+    #[test]
+    fn test_split_range_and_gaps() {
+        assert_eq!(
+            split_range(day(1), day(5), None),
+            (None, Some((day(1), day(5))))
+        );
+        assert_eq!(
+            split_range(day(1), day(5), Some(day(3))),
+            (Some((day(1), day(3))), Some((day(3), day(5))))
+        );
+        assert_eq!(
+            split_range(day(1), day(5), Some(day(6))),
+            (Some((day(1), day(5))), None)
+        );
+
+        let reupload = manifest(2, day(20));
+        let manifests = latest_manifests(vec![
+            manifest(4, day(10)),
+            reupload.clone(),
+            manifest(2, day(10)),
+        ]);
+        assert_eq!(manifests.len(), 2);
+        assert_eq!(manifests[0].archive_id, reupload.archive_id);
+
+        // Day 1, day 3, and the afternoon of day 5 have no archive
+        let end = day(5) + Duration::hours(12);
+        assert_eq!(
+            archive_gaps(day(1), end, &manifests),
+            vec![
+                ArchiveGap {
+                    start: day(1),
+                    end: day(2)
+                },
+                ArchiveGap {
+                    start: day(3),
+                    end: day(4)
+                },
+                ArchiveGap { start: day(5), end },
+            ]
+        );
+        assert!(archive_gaps(day(2), day(3), &manifests).is_empty());
+    }
+
+    #[test]
+    fn test_archived_aggregates_filter_and_provenance() {
+        let row = |metric: &str, window: &str, start: DateTime<Utc>, tenant: &str| {
+            json!({
+                "id": 1,
+                "metric_name": metric,
+                "time_window": window,
+                "window_start": start,
+                "tags": { "tenant": tenant },
+                "avg": 1.0, "min": 1.0, "max": 1.0,
+                "p50": 1.0, "p95": 1.0, "p99": 1.0,
+                "stddev": null, "count": 1, "sum": 1.0,
+                "histogram": null,
+                "created_at": start,
+            })
+        };
+        let rows = vec![
+            row("latency", "1h", day(2), "acme"),
+            row("latency", "1h", day(2) + Duration::hours(1), "other"),
+            row("latency", "5m", day(2), "acme"),
+            row("cost", "1h", day(2), "acme"),
+            row("latency", "1h", day(3), "acme"),
+        ];
+
+        let tags = json!({ "tenant": "acme" });
+        let matched = archived_aggregates(
+            &rows,
+            "latency",
+            TimeWindow::OneHour,
+            day(2),
+            day(3),
+            Some(&tags),
+        );
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].window_start, day(2));
+
+        let sourced = serde_json::to_value(Sourced::archive(matched[0].clone())).unwrap();
+        assert_eq!(sourced["source"], "archive");
+        assert_eq!(sourced["metric_name"], "latency");
+    }
+}
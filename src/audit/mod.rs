@@ -0,0 +1,310 @@
+//! Audit Log
+//!
+//! Records who changed what in the hub. Alert rule changes, silences,
+//! retention policy changes, archive exports and restores, and every
+//! state-changing API request produce an `AuditTrailEvent`. Events are stored
+//! in the `audit_log` table, which backs the audit query endpoint, and the
+//! API service also publishes them to the audit Kafka topic.
+//!
+//! API requests are audited by route: mutating methods on every route except
+//! ingestion and read-only query routes, plus reads of routes that export data.
+
+use crate::federation::ROLLUPS_PATH;
+use crate::schemas::events::{
+    AnalyticsEvent, AuditTrailEvent, CommonEventFields, EventPayload, EventType, GovernancePayload,
+    Severity, SourceModule, SCHEMA_VERSION,
+};
+use axum::http::{HeaderMap, Method, StatusCode};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Actor recorded when a change has no authenticated caller
+pub const UNKNOWN_ACTOR: &str = "unknown";
+
+/// Resource type of audited API requests
+pub const API_REQUEST_RESOURCE: &str = "api_request";
+
+/// Most records one audit query returns
+pub const MAX_AUDIT_QUERY_LIMIT: i64 = 1000;
+
+/// Routes whose mutating requests ingest data or run read-only queries
+const DEFAULT_EXCLUDED_ROUTES: &[&str] = &[
+    "/api/v1/events",
+    "/v1/traces",
+    "/v1/metrics",
+    "/api/v1/sql",
+    "/api/grafana",
+    "/api/v1/metric-filters/preview",
+    ROLLUPS_PATH,
+];
+
+/// Read routes that export data and are audited like mutations
+const DEFAULT_AUDITED_READS: &[&str] = &["/api/v1/incidents/:incident_id/postmortem"];
+
+fn list_from_env(name: &str, defaults: &[&str]) -> Vec<String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => defaults.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+/// Audit log configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Kafka topic audit events are published to
+    pub topic: String,
+    /// Route prefixes whose mutating requests are not audited
+    pub excluded_routes: Vec<String>,
+    /// Routes audited on every request, including reads
+    pub audited_reads: Vec<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            topic: "llm-audit".to_string(),
+            excluded_routes: DEFAULT_EXCLUDED_ROUTES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            audited_reads: DEFAULT_AUDITED_READS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl AuditConfig {
+    pub fn from_env() -> Self {
+        Self {
+            topic: std::env::var("AUDIT_TOPIC").unwrap_or_else(|_| Self::default().topic),
+            excluded_routes: list_from_env("AUDIT_EXCLUDED_ROUTES", DEFAULT_EXCLUDED_ROUTES),
+            audited_reads: list_from_env("AUDIT_READ_ROUTES", DEFAULT_AUDITED_READS),
+        }
+    }
+
+    /// Whether a request to the matched `route` is audited
+    pub fn audits(&self, method: &Method, route: &str) -> bool {
+        if self.audited_reads.iter().any(|r| r == route) {
+            return true;
+        }
+        let mutating = matches!(
+            *method,
+            Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+        );
+        mutating
+            && !self
+                .excluded_routes
+                .iter()
+                .any(|prefix| route.starts_with(prefix.as_str()))
+    }
+}
+
+/// A stored audit trail entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub event_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub environment: String,
+    pub action: String,
+    pub actor: String,
+    pub resource_type: String,
+    pub resource_id: String,
+    #[serde(default)]
+    pub changes: HashMap<String, serde_json::Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditRecord {
+    /// The record behind an audit trail event, or `None` for other events
+    pub fn from_event(event: &AnalyticsEvent) -> Option<Self> {
+        let EventPayload::Governance(GovernancePayload::AuditTrail(trail)) = &event.payload else {
+            return None;
+        };
+        Some(Self {
+            event_id: event.common.event_id,
+            occurred_at: event.common.timestamp,
+            environment: event.common.environment.clone(),
+            action: trail.action.clone(),
+            actor: trail.actor.clone(),
+            resource_type: trail.resource_type.clone(),
+            resource_id: trail.resource_id.clone(),
+            changes: trail.changes.clone(),
+            ip_address: trail.ip_address.clone(),
+            user_agent: trail.user_agent.clone(),
+        })
+    }
+}
+
+/// Wrap an audit trail entry in a governance event
+pub fn audit_event(
+    trail: AuditTrailEvent,
+    timestamp: DateTime<Utc>,
+    environment: &str,
+) -> AnalyticsEvent {
+    AnalyticsEvent {
+        common: CommonEventFields {
+            event_id: Uuid::new_v4(),
+            timestamp,
+            source_module: SourceModule::LlmAnalyticsHub,
+            event_type: EventType::Governance,
+            correlation_id: None,
+            parent_event_id: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity: Severity::Info,
+            environment: environment.to_string(),
+            tags: HashMap::new(),
+        },
+        payload: EventPayload::Governance(GovernancePayload::AuditTrail(trail)),
+    }
+}
+
+/// Audit event recording a change `actor` made to a resource
+pub fn change_event(
+    action: &str,
+    actor: Option<&str>,
+    resource_type: &str,
+    resource_id: &str,
+    changes: HashMap<String, serde_json::Value>,
+    environment: &str,
+) -> AnalyticsEvent {
+    audit_event(
+        AuditTrailEvent {
+            action: action.to_string(),
+            actor: actor.unwrap_or(UNKNOWN_ACTOR).to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id: resource_id.to_string(),
+            changes,
+            ip_address: None,
+            user_agent: None,
+        },
+        Utc::now(),
+        environment,
+    )
+}
+
+/// Client address of a request, as reported by the proxy in front of the hub
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    header("x-forwarded-for")
+        .and_then(|v| v.split(',').next())
+        .or_else(|| header("x-real-ip"))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// A state-changing API request as seen by the audit middleware
+#[derive(Debug, Clone)]
+pub struct AuditedRequest {
+    pub method: Method,
+    /// Route template the request matched, e.g. `/api/v1/alerts/rules/:rule_id`
+    pub route: String,
+    pub path: String,
+    pub actor: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditedRequest {
+    /// Audit event recording the request and how it was answered
+    pub fn event(&self, status: StatusCode, environment: &str) -> AnalyticsEvent {
+        let mut changes = HashMap::new();
+        changes.insert(
+            "method".to_string(),
+            serde_json::json!(self.method.as_str()),
+        );
+        changes.insert("route".to_string(), serde_json::json!(self.route));
+        changes.insert("status".to_string(), serde_json::json!(status.as_u16()));
+
+        audit_event(
+            AuditTrailEvent {
+                action: format!("api.{}", self.method.as_str().to_lowercase()),
+                actor: self
+                    .actor
+                    .clone()
+                    .unwrap_or_else(|| UNKNOWN_ACTOR.to_string()),
+                resource_type: API_REQUEST_RESOURCE.to_string(),
+                resource_id: self.path.clone(),
+                changes,
+                ip_address: self.ip_address.clone(),
+                user_agent: self.user_agent.clone(),
+            },
+            Utc::now(),
+            environment,
+        )
+    }
+}
+
+/// Filters for querying the audit log; unset filters match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_audits_mutations_and_exports() {
+        let config = AuditConfig::default();
+        assert!(config.audits(&Method::POST, "/api/v1/alerts/silences"));
+        assert!(config.audits(&Method::DELETE, "/api/v1/alerts/rules/:rule_id"));
+        assert!(!config.audits(&Method::GET, "/api/v1/alerts/rules"));
+        assert!(!config.audits(&Method::POST, "/api/v1/events/batch"));
+        assert!(!config.audits(&Method::POST, "/api/grafana/query"));
+        assert!(!config.audits(&Method::POST, ROLLUPS_PATH));
+        assert!(config.audits(&Method::GET, "/api/v1/incidents/:incident_id/postmortem"));
+    }
+
+    #[test]
+    fn test_request_events_round_trip_to_records() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("203.0.113.7, 10.0.0.1"),
+        );
+        let request = AuditedRequest {
+            method: Method::PUT,
+            route: "/api/v1/alerts/rules/:rule_id".to_string(),
+            path: "/api/v1/alerts/rules/gpt4-slow".to_string(),
+            actor: Some("ops".to_string()),
+            ip_address: client_ip(&headers),
+            user_agent: None,
+        };
+        let event = request.event(StatusCode::CONFLICT, "production");
+        let record = AuditRecord::from_event(&event).unwrap();
+        assert_eq!(record.event_id, event.common.event_id);
+        assert_eq!(record.action, "api.put");
+        assert_eq!(record.actor, "ops");
+        assert_eq!(record.resource_type, API_REQUEST_RESOURCE);
+        assert_eq!(record.resource_id, "/api/v1/alerts/rules/gpt4-slow");
+        assert_eq!(record.changes["status"], serde_json::json!(409));
+        assert_eq!(record.ip_address.as_deref(), Some("203.0.113.7"));
+        assert_eq!(record.environment, "production");
+
+        let anonymous = AuditedRequest {
+            actor: None,
+            ..request
+        };
+        let record = AuditRecord::from_event(&anonymous.event(StatusCode::OK, "dev")).unwrap();
+        assert_eq!(record.actor, UNKNOWN_ACTOR);
+        assert!(client_ip(&HeaderMap::new()).is_none());
+    }
+}
@@ -0,0 +1,808 @@
+//! API Authentication & Authorization
+//!
+//! Enforces the `SecurityConfig` published by Config-Manager on the HTTP API.
+//! Callers authenticate with an API key in the `x-api-key` header or an HS256
+//! JWT bearer token, and are granted a scope: `read` for queries, `write` for
+//! ingestion, and `admin` for configuration such as alert rules, silences,
+//! webhooks, and SLO definitions. Each scope includes the ones below it. Keys
+//! and tokens may also be bound to a tenant, which then scopes every request
+//! they make.
+//!
+//! `require_auth` authenticates every request. Routes declare the scope they
+//! need by layering `require_scope` over them with a `ScopeGuard`, so the
+//! scope lives next to the route rather than in a list of paths. When
+//! authentication is disabled, anonymous callers get the `write` scope.
+//!
+//! Denied requests are answered with 401/403 and published as `AuthEvent`
+//! security events so that failed access attempts show up in the audit trail.
+//! CORS is derived from `allowed_origins`.
+
+use crate::adapters::config_manager::SecurityConfig;
+use crate::grpc::EventRouter;
+use crate::schemas::events::{
+    AnalyticsEvent, AuthAction, AuthEvent, CommonEventFields, EventPayload, EventType,
+    SecurityPayload, Severity, SourceModule, SCHEMA_VERSION,
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// Request header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Scope of anonymous callers when authentication is disabled
+const ANONYMOUS_SCOPE: Scope = Scope::Write;
+
+// ========== Scopes ==========
+
+/// Access level granted to a caller; each scope includes the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Scope {
+    pub fn grants(self, required: Scope) -> bool {
+        self >= required
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "read" | "read_only" | "readonly" => Ok(Scope::Read),
+            "write" | "ingest" => Ok(Scope::Write),
+            "admin" => Ok(Scope::Admin),
+            other => anyhow::bail!("Unknown scope: {}", other),
+        }
+    }
+}
+
+// ========== Configuration ==========
+
+/// Authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Reject requests without valid credentials
+    pub require_auth: bool,
+    /// Origins allowed by CORS; `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    /// HMAC secret for JWT bearer tokens; bearer auth is off when unset
+    #[serde(skip_serializing)]
+    pub jwt_secret: Option<String>,
+    /// Required `iss` claim
+    pub jwt_issuer: Option<String>,
+    /// Required `aud` claim
+    pub jwt_audience: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            require_auth: true,
+            allowed_origins: vec!["*".to_string()],
+            jwt_secret: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            require_auth: std::env::var("REQUIRE_AUTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.require_auth),
+            allowed_origins: std::env::var("CORS_ALLOWED_ORIGINS")
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .map(|o| o.trim().to_string())
+                        .filter(|o| !o.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.allowed_origins),
+            jwt_secret: std::env::var("JWT_SECRET").ok(),
+            jwt_issuer: std::env::var("JWT_ISSUER").ok(),
+            jwt_audience: std::env::var("JWT_AUDIENCE").ok(),
+        }
+    }
+
+    /// Apply the environment's security settings from Config-Manager
+    pub fn with_security(mut self, security: &SecurityConfig) -> Self {
+        self.require_auth = security.require_auth;
+        self.allowed_origins = security.allowed_origins.clone();
+        self
+    }
+}
+
+// ========== Credentials ==========
+
+/// Client, scope, and tenant an API key grants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyGrant {
+    pub client_id: String,
+    pub scope: Scope,
+    /// Tenant the key is bound to; unbound keys may act for any tenant
+    pub tenant_id: Option<String>,
+}
+
+/// API keys stored as SHA-256 digests with the grant they carry
+#[derive(Debug, Default)]
+pub struct ScopedApiKeys {
+    keys: HashMap<Vec<u8>, KeyGrant>,
+}
+
+impl ScopedApiKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `client_id:key[:scope[:tenant_id]]` entries separated by commas.
+    ///
+    /// Keys without a scope are read-only; keys without a tenant are unbound.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut keys = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = entry.splitn(4, ':');
+            let client_id = parts.next().unwrap_or_default();
+            let key = parts.next().unwrap_or_default();
+            if client_id.is_empty() || key.is_empty() {
+                anyhow::bail!("API key entries must be client_id:key[:scope[:tenant_id]]");
+            }
+            let scope = match parts.next().filter(|s| !s.is_empty()) {
+                Some(scope) => scope
+                    .parse()
+                    .with_context(|| format!("Invalid scope for API key of {}", client_id))?,
+                None => Scope::Read,
+            };
+            let tenant_id = parts.next().filter(|t| !t.is_empty());
+            keys.insert(
+                key,
+                KeyGrant {
+                    client_id: client_id.to_string(),
+                    scope,
+                    tenant_id: tenant_id.map(String::from),
+                },
+            );
+        }
+        Ok(keys)
+    }
+
+    /// Load keys from API_KEYS
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("API_KEYS") {
+            Ok(spec) => Self::parse(&spec),
+            Err(_) => Ok(Self::new()),
+        }
+    }
+
+    pub fn insert(&mut self, key: &str, grant: KeyGrant) {
+        self.keys.insert(digest(key), grant);
+    }
+
+    /// Grant carried by a key
+    pub fn authenticate(&self, key: &str) -> Option<&KeyGrant> {
+        self.keys.get(&digest(key))
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+fn digest(key: &str) -> Vec<u8> {
+    Sha256::digest(key.as_bytes()).to_vec()
+}
+
+/// JWT claims read by the hub
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    /// Space-separated scopes; the highest recognized one is granted
+    #[serde(default)]
+    pub scope: String,
+    /// Tenant the token is bound to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl Claims {
+    fn granted_scope(&self) -> Option<Scope> {
+        self.scope
+            .split_whitespace()
+            .filter_map(|s| s.parse().ok())
+            .max()
+    }
+}
+
+/// How a caller authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    ApiKey,
+    Bearer,
+    /// Authentication is disabled
+    Anonymous,
+}
+
+/// Authenticated caller, stored in request extensions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    pub subject: String,
+    pub scope: Scope,
+    pub method: AuthMethod,
+    /// Tenant the caller is bound to
+    pub tenant_id: Option<String>,
+}
+
+/// Reason a request was denied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    MissingCredentials,
+    InvalidApiKey,
+    InvalidToken(String),
+    Forbidden { required: Scope, granted: Scope },
+}
+
+impl AuthError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AuthError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => write!(f, "Missing credentials"),
+            AuthError::InvalidApiKey => write!(f, "Invalid API key"),
+            AuthError::InvalidToken(reason) => write!(f, "Invalid bearer token: {}", reason),
+            AuthError::Forbidden { required, granted } => {
+                write!(f, "Requires {} scope, caller has {}", required, granted)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "success": false,
+            "error": self.to_string(),
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+// ========== Authenticator ==========
+
+/// Authenticates and authorizes API requests
+pub struct Authenticator {
+    config: AuthConfig,
+    api_keys: ScopedApiKeys,
+    audit: Option<Arc<dyn EventRouter>>,
+    allowed: AtomicU64,
+    denied: AtomicU64,
+}
+
+impl Authenticator {
+    pub fn new(config: AuthConfig, api_keys: ScopedApiKeys) -> Self {
+        if config.require_auth && api_keys.is_empty() && config.jwt_secret.is_none() {
+            warn!("API requires auth but neither API keys nor a JWT secret are configured");
+        }
+        Self {
+            config,
+            api_keys,
+            audit: None,
+            allowed: AtomicU64::new(0),
+            denied: AtomicU64::new(0),
+        }
+    }
+
+    /// Publish denied requests as security events
+    pub fn with_audit(mut self, router: Arc<dyn EventRouter>) -> Self {
+        self.audit = Some(router);
+        self
+    }
+
+    /// Identify the caller from request headers
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Principal, AuthError> {
+        if let Some(key) = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+            return match self.api_keys.authenticate(key) {
+                Some(grant) => Ok(Principal {
+                    subject: grant.client_id.clone(),
+                    scope: grant.scope,
+                    method: AuthMethod::ApiKey,
+                    tenant_id: grant.tenant_id.clone(),
+                }),
+                None => Err(AuthError::InvalidApiKey),
+            };
+        }
+
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if let Some(token) = bearer {
+            return self.verify_token(token.trim());
+        }
+
+        if self.config.require_auth {
+            Err(AuthError::MissingCredentials)
+        } else {
+            Ok(Principal {
+                subject: "anonymous".to_string(),
+                scope: ANONYMOUS_SCOPE,
+                method: AuthMethod::Anonymous,
+                tenant_id: None,
+            })
+        }
+    }
+
+    fn verify_token(&self, token: &str) -> Result<Principal, AuthError> {
+        let Some(secret) = &self.config.jwt_secret else {
+            return Err(AuthError::InvalidToken(
+                "bearer tokens are not accepted".to_string(),
+            ));
+        };
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = &self.config.jwt_issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.config.jwt_audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &validation,
+        )
+        .map_err(|e| AuthError::InvalidToken(e.to_string()))?
+        .claims;
+        let scope = claims
+            .granted_scope()
+            .ok_or_else(|| AuthError::InvalidToken("no recognized scope".to_string()))?;
+
+        Ok(Principal {
+            subject: claims.sub,
+            scope,
+            method: AuthMethod::Bearer,
+            tenant_id: claims.tenant_id,
+        })
+    }
+
+    /// Check an authenticated caller against the scope a route needs
+    pub fn authorize(&self, principal: &Principal, required: Scope) -> Result<(), AuthError> {
+        if principal.scope.grants(required) {
+            Ok(())
+        } else {
+            Err(AuthError::Forbidden {
+                required,
+                granted: principal.scope,
+            })
+        }
+    }
+
+    /// Guard for routes that need `required`, for use with `require_scope`
+    pub fn guard(self: &Arc<Self>, required: Scope) -> ScopeGuard {
+        ScopeGuard {
+            auth: self.clone(),
+            required,
+        }
+    }
+
+    /// CORS policy for `allowed_origins`
+    pub fn cors_layer(&self) -> CorsLayer {
+        let origins = &self.config.allowed_origins;
+        let allow_origin = if origins.iter().any(|o| o == "*") {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(origins.iter().filter_map(|o| HeaderValue::from_str(o).ok()))
+        };
+
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                header::HeaderName::from_static(API_KEY_HEADER),
+                header::HeaderName::from_static(crate::metering::TENANT_HEADER),
+                header::HeaderName::from_static(crate::database::environment::ENVIRONMENT_HEADER),
+            ])
+    }
+
+    /// Security event recording a denied request
+    pub fn denial_event(
+        &self,
+        headers: &HeaderMap,
+        method: &Method,
+        path: &str,
+        error: &AuthError,
+    ) -> AnalyticsEvent {
+        let (user_id, action) = match error {
+            AuthError::Forbidden { .. } => (
+                self.authenticate(headers)
+                    .map(|p| p.subject)
+                    .unwrap_or_else(|_| "unknown".to_string()),
+                AuthAction::PermissionDenied,
+            ),
+            _ => ("unknown".to_string(), AuthAction::AccessAttempt),
+        };
+
+        let mut tags = HashMap::new();
+        tags.insert("http_method".to_string(), method.to_string());
+        tags.insert("status".to_string(), error.status().as_u16().to_string());
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Security,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Warning,
+                environment: crate::database::environment::default_environment(),
+                tags,
+            },
+            payload: EventPayload::Security(SecurityPayload::Auth(AuthEvent {
+                user_id,
+                action,
+                resource: path.to_string(),
+                success: false,
+                failure_reason: Some(error.to_string()),
+            })),
+        }
+    }
+
+    /// Count, log, and publish a denied request, returning its response
+    fn deny(&self, request: &Request, error: AuthError) -> Response {
+        let method = request.method();
+        let path = request.uri().path();
+        self.denied.fetch_add(1, Ordering::Relaxed);
+        warn!(%method, path = %path, "Denied API request: {}", error);
+        self.audit_denial(self.denial_event(request.headers(), method, path, &error));
+        error.into_response()
+    }
+
+    /// Publish a denial without holding up the response
+    fn audit_denial(&self, event: AnalyticsEvent) {
+        if let Some(router) = &self.audit {
+            let router = router.clone();
+            tokio::spawn(async move {
+                if let Err(e) = router.route(event).await {
+                    error!("Failed to publish auth audit event: {}", e);
+                }
+            });
+        }
+    }
+
+    pub fn get_stats(&self) -> AuthStats {
+        AuthStats {
+            allowed: self.allowed.load(Ordering::Relaxed),
+            denied: self.denied.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Authentication statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthStats {
+    pub allowed: u64,
+    pub denied: u64,
+}
+
+/// Middleware rejecting requests that fail authentication, and storing the
+/// caller's `Principal` in the request extensions
+pub async fn require_auth(
+    State(auth): State<Arc<Authenticator>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match auth.authenticate(request.headers()) {
+        Ok(principal) => {
+            auth.allowed.fetch_add(1, Ordering::Relaxed);
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(e) => auth.deny(&request, e),
+    }
+}
+
+/// Scope required by the routes a `require_scope` layer wraps
+#[derive(Clone)]
+pub struct ScopeGuard {
+    auth: Arc<Authenticator>,
+    required: Scope,
+}
+
+/// Route middleware rejecting callers without the guard's scope; runs inside
+/// `require_auth`, which provides the caller's `Principal`
+pub async fn require_scope(
+    State(guard): State<ScopeGuard>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let result = match request.extensions().get::<Principal>() {
+        Some(principal) => guard.auth.authorize(principal, guard.required),
+        None => Err(AuthError::MissingCredentials),
+    };
+    match result {
+        Ok(()) => next.run(request).await,
+        Err(e) => guard.auth.deny(&request, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::{get, post};
+    use axum::{middleware, Router};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use tower::ServiceExt;
+
+    fn authenticator(require_auth: bool) -> Authenticator {
+        let keys = ScopedApiKeys::parse(
+            "dashboard:dash-key,ingester:ingest-key:write:team-a,ops:ops-key:admin",
+        )
+        .unwrap();
+        Authenticator::new(
+            AuthConfig {
+                require_auth,
+                jwt_secret: Some("secret".to_string()),
+                ..AuthConfig::default()
+            },
+            keys,
+        )
+    }
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn token(scope: &str, secret: &str) -> String {
+        let claims = Claims {
+            sub: "alice".to_string(),
+            exp: (Utc::now().timestamp() + 3600) as usize,
+            scope: scope.to_string(),
+            tenant_id: Some("team-a".to_string()),
+        };
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    /// GET is open to readers and POST needs `scope`, as the API router declares them
+    fn guarded_app(auth: Arc<Authenticator>, path: &str, scope: Scope) -> Router {
+        let read = Router::new().route(path, get(|| async { "ok" }));
+        let guarded = Router::new()
+            .route(path, post(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                auth.guard(scope),
+                require_scope,
+            ));
+        read.merge(guarded)
+            .layer(middleware::from_fn_with_state(auth, require_auth))
+    }
+
+    async fn status(app: &Router, method: Method, path: &str, key: Option<&str>) -> StatusCode {
+        let mut request = axum::http::Request::builder().method(method).uri(path);
+        if let Some(key) = key {
+            request = request.header(API_KEY_HEADER, key);
+        }
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn test_api_key_scopes() {
+        let auth = authenticator(true);
+        let api_key = header::HeaderName::from_static(API_KEY_HEADER);
+
+        let dash = auth
+            .authenticate(&headers(api_key.clone(), "dash-key"))
+            .unwrap();
+        assert!(auth.authorize(&dash, Scope::Read).is_ok());
+        assert_eq!(
+            auth.authorize(&dash, Scope::Write),
+            Err(AuthError::Forbidden {
+                required: Scope::Write,
+                granted: Scope::Read
+            })
+        );
+
+        let ingest = auth
+            .authenticate(&headers(api_key.clone(), "ingest-key"))
+            .unwrap();
+        assert_eq!(ingest.tenant_id.as_deref(), Some("team-a"));
+        assert!(auth.authorize(&ingest, Scope::Write).is_ok());
+        assert!(auth.authorize(&ingest, Scope::Admin).is_err());
+
+        let ops = auth
+            .authenticate(&headers(api_key.clone(), "ops-key"))
+            .unwrap();
+        assert_eq!(ops.subject, "ops");
+        assert!(auth.authorize(&ops, Scope::Admin).is_ok());
+
+        let bad = headers(api_key, "nope");
+        assert_eq!(auth.authenticate(&bad), Err(AuthError::InvalidApiKey));
+    }
+
+    #[tokio::test]
+    async fn test_routes_require_their_declared_scope() {
+        let auth = Arc::new(authenticator(true));
+        let app = guarded_app(auth.clone(), "/api/v1/alerts/rules", Scope::Admin);
+
+        let rules = "/api/v1/alerts/rules";
+        assert_eq!(
+            status(&app, Method::GET, rules, Some("dash-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&app, Method::POST, rules, Some("ingest-key")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&app, Method::POST, rules, Some("ops-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&app, Method::POST, rules, None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(auth.get_stats().denied, 2);
+
+        // Anonymous callers can ingest but not administer
+        let open = Arc::new(authenticator(false));
+        let app = guarded_app(open.clone(), rules, Scope::Admin);
+        assert_eq!(
+            status(&app, Method::POST, rules, None).await,
+            StatusCode::FORBIDDEN
+        );
+        let app = guarded_app(open, "/api/v1/events", Scope::Write);
+        assert_eq!(
+            status(&app, Method::POST, "/api/v1/events", None).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_silences_need_admin() {
+        let auth = Arc::new(authenticator(true));
+        let silences = "/api/v1/alerts/silences";
+        let app = guarded_app(auth, silences, Scope::Admin);
+
+        // Ingestion keys must not be able to mute paging
+        assert_eq!(
+            status(&app, Method::POST, silences, Some("ingest-key")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&app, Method::POST, silences, Some("ops-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&app, Method::GET, silences, Some("dash-key")).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_bearer_tokens() {
+        let auth = authenticator(true);
+
+        let admin = headers(
+            header::AUTHORIZATION,
+            &format!("Bearer {}", token("read admin", "secret")),
+        );
+        let principal = auth.authenticate(&admin).unwrap();
+        assert_eq!(principal.scope, Scope::Admin);
+        assert_eq!(principal.method, AuthMethod::Bearer);
+        assert_eq!(principal.tenant_id.as_deref(), Some("team-a"));
+
+        let forged = headers(
+            header::AUTHORIZATION,
+            &format!("Bearer {}", token("admin", "other")),
+        );
+        assert!(matches!(
+            auth.authenticate(&forged),
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_credentials() {
+        let empty = HeaderMap::new();
+        assert_eq!(
+            authenticator(true).authenticate(&empty),
+            Err(AuthError::MissingCredentials)
+        );
+        let anonymous = authenticator(false).authenticate(&empty).unwrap();
+        assert_eq!(anonymous.method, AuthMethod::Anonymous);
+        assert_eq!(anonymous.scope, Scope::Write);
+    }
+
+    #[test]
+    fn test_denial_event() {
+        let auth = authenticator(true);
+        let dash = headers(header::HeaderName::from_static(API_KEY_HEADER), "dash-key");
+        let principal = auth.authenticate(&dash).unwrap();
+        let error = auth.authorize(&principal, Scope::Admin).unwrap_err();
+        let event = auth.denial_event(&dash, &Method::POST, "/api/v1/webhooks", &error);
+        match event.payload {
+            EventPayload::Security(SecurityPayload::Auth(auth_event)) => {
+                assert_eq!(auth_event.user_id, "dashboard");
+                assert_eq!(auth_event.action, AuthAction::PermissionDenied);
+                assert!(!auth_event.success);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+}
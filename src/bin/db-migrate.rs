@@ -1,12 +1,33 @@
 //! Database Migration Tool
 //!
 //! Rust-based database migration tool for TimescaleDB.
-//! Replaces SQL init scripts with type-safe, version-controlled migrations.
+//! Applies ordered, checksummed SQL migrations from the `migrations/` directory.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use llm_analytics_hub::adapters::config_manager::{
+    ConfigManagerAdapter, ConfigManagerConfig, RetentionSettings,
+};
+use llm_analytics_hub::adapters::EcosystemAdapter;
+use llm_analytics_hub::audit::AuditRecord;
+use llm_analytics_hub::database::environment::default_environment;
+use llm_analytics_hub::database::migrations::{
+    current_version, load_migrations, next_version, pending_migrations, verify_applied,
+    AppliedMigration, ChecksumStatus, Migration, DOWN_MARKER, UP_MARKER,
+};
+use llm_analytics_hub::database::timescale::{
+    create_rollup_sql, parse_window, rollup_policy_sql, rollup_view_name, validate_hypertable,
+    STANDARD_ROLLUP_WINDOWS,
+};
+use llm_analytics_hub::database::Database;
+use llm_analytics_hub::models::metrics::TimeWindow;
+use llm_analytics_hub::retention::{
+    actual_policies, audit_event, desired_policies, plan_changes, PolicyKind,
+};
 use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Executor;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::info;
 
@@ -18,6 +39,10 @@ struct Cli {
     #[arg(short, long, env = "DATABASE_URL")]
     database_url: String,
 
+    /// Directory containing NNN_name.sql migration files
+    #[arg(long, env = "MIGRATIONS_DIR", default_value = "migrations")]
+    migrations_dir: PathBuf,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -25,7 +50,11 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Run pending migrations
-    Migrate,
+    Migrate {
+        /// Print the SQL of pending migrations without executing it
+        #[arg(long, visible_alias = "plan")]
+        dry_run: bool,
+    },
 
     /// Create a new migration
     Create {
@@ -39,6 +68,9 @@ enum Commands {
     /// Show migration status
     Status,
 
+    /// Verify applied migrations against their files (checksum drift)
+    Verify,
+
     /// Reset database (dangerous!)
     Reset {
         /// Confirm reset
@@ -48,6 +80,57 @@ enum Commands {
 
     /// Initialize fresh database
     Init,
+
+    /// Manage continuous aggregates over the standard rollup windows
+    Aggregates {
+        #[command(subcommand)]
+        action: AggregateCommand,
+    },
+
+    /// Change the chunk_time_interval of a hypertable (applies to new chunks)
+    ChunkInterval {
+        /// Hypertable name
+        table: String,
+
+        /// New chunk interval, e.g. "12 hours" or "7 days"
+        #[arg(long)]
+        interval: String,
+    },
+
+    /// Sync retention and compression policies with RetentionSettings
+    Policies {
+        /// Read RetentionSettings from a YAML/JSON file instead of Config-Manager
+        #[arg(long)]
+        settings: Option<PathBuf>,
+
+        /// Show the policy changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AggregateCommand {
+    /// Create continuous aggregates and their refresh policies
+    Create {
+        /// Windows to create (default: all standard windows)
+        #[arg(long, value_delimiter = ',')]
+        windows: Vec<String>,
+    },
+
+    /// Refresh continuous aggregates over a recent range
+    Refresh {
+        /// Windows to refresh (default: all standard windows)
+        #[arg(long, value_delimiter = ',')]
+        windows: Vec<String>,
+
+        /// Hours back from now to re-materialize
+        #[arg(long, default_value = "24")]
+        since_hours: u32,
+    },
+
+    /// List continuous aggregates and their refresh jobs
+    List,
 }
 
 #[tokio::main]
@@ -61,6 +144,11 @@ async fn main() -> Result<()> {
     println!("{}", "🗄️  LLM Analytics Hub - Database Migration Tool".bold().cyan());
     println!();
 
+    // Creating a migration file needs no database connection
+    if let Commands::Create { name } = &cli.command {
+        return create_migration(&cli.migrations_dir, name).await;
+    }
+
     // Connect to database
     let pool = PgPoolOptions::new()
         .max_connections(5)
@@ -69,13 +157,32 @@ async fn main() -> Result<()> {
         .await
         .context("Failed to connect to database")?;
 
+    let dir = cli.migrations_dir.as_path();
     match cli.command {
-        Commands::Migrate => migrate(&pool).await?,
-        Commands::Create { name } => create_migration(&name).await?,
-        Commands::Rollback => rollback(&pool).await?,
-        Commands::Status => show_status(&pool).await?,
+        Commands::Migrate { dry_run: true } => plan(&pool, dir).await?,
+        Commands::Migrate { dry_run: false } => migrate(&pool, dir).await?,
+        Commands::Create { .. } => unreachable!("handled before connecting"),
+        Commands::Rollback => rollback(&pool, dir).await?,
+        Commands::Status => show_status(&pool, dir).await?,
+        Commands::Verify => verify(&pool, dir).await?,
         Commands::Reset { confirm } => reset(&pool, confirm).await?,
-        Commands::Init => init_database(&pool).await?,
+        Commands::Init => init_database(&pool, dir).await?,
+        Commands::Aggregates { action } => match action {
+            AggregateCommand::Create { windows } => {
+                create_aggregates(&pool, &resolve_windows(&windows)?).await?
+            }
+            AggregateCommand::Refresh {
+                windows,
+                since_hours,
+            } => refresh_aggregates(&pool, &resolve_windows(&windows)?, since_hours).await?,
+            AggregateCommand::List => list_aggregates(&pool).await?,
+        },
+        Commands::ChunkInterval { table, interval } => {
+            set_chunk_interval(&pool, &table, &interval).await?
+        }
+        Commands::Policies { settings, dry_run } => {
+            sync_policies(&pool, settings.as_deref(), dry_run).await?
+        }
     }
 
     pool.close().await;
@@ -83,10 +190,8 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn migrate(pool: &PgPool) -> Result<()> {
-    println!("{}", "🚀 Running migrations...".bold());
-
-    // Create migrations table if not exists
+/// Create the bookkeeping table, adding the checksum column to legacy installs
+async fn ensure_migrations_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
         CREATE TABLE IF NOT EXISTS _migrations (
@@ -99,125 +204,361 @@ async fn migrate(pool: &PgPool) -> Result<()> {
     .execute(pool)
     .await?;
 
-    // Run all migrations
-    apply_migration(pool, "001_create_events_table", CREATE_EVENTS_TABLE).await?;
-    apply_migration(pool, "002_create_metrics_table", CREATE_METRICS_TABLE).await?;
-    apply_migration(pool, "003_create_anomalies_table", CREATE_ANOMALIES_TABLE).await?;
-    apply_migration(pool, "004_create_correlations_table", CREATE_CORRELATIONS_TABLE).await?;
-    apply_migration(pool, "005_create_indexes", CREATE_INDEXES).await?;
-    apply_migration(pool, "006_enable_compression", ENABLE_COMPRESSION).await?;
-    apply_migration(pool, "007_retention_policies", RETENTION_POLICIES).await?;
-
-    println!("{}", "✅ All migrations applied successfully!".bold().green());
+    sqlx::query("ALTER TABLE _migrations ADD COLUMN IF NOT EXISTS checksum VARCHAR(64)")
+        .execute(pool)
+        .await?;
 
     Ok(())
 }
 
-async fn apply_migration(pool: &PgPool, name: &str, sql: &str) -> Result<()> {
-    // Check if migration already applied
-    let exists: bool = sqlx::query_scalar(
-        "SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = $1)"
+/// Rows of `_migrations` in apply order, without modifying the database
+async fn applied_migrations(pool: &PgPool) -> Result<Vec<AppliedMigration>> {
+    let has_table: bool = sqlx::query_scalar("SELECT to_regclass('_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    // Installs that predate checksum tracking have no checksum column yet
+    let has_checksum: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM information_schema.columns \
+         WHERE table_name = '_migrations' AND column_name = 'checksum')",
     )
-    .bind(name)
     .fetch_one(pool)
     .await?;
 
-    if exists {
-        println!("  {} {}", "⏭️".yellow(), name.dimmed());
+    let sql = if has_checksum {
+        "SELECT name, checksum FROM _migrations ORDER BY id"
+    } else {
+        "SELECT name, NULL::VARCHAR FROM _migrations ORDER BY id"
+    };
+
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(sql).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(name, checksum)| AppliedMigration { name, checksum })
+        .collect())
+}
+
+async fn migrate(pool: &PgPool, dir: &Path) -> Result<()> {
+    println!("{}", "🚀 Running migrations...".bold());
+
+    ensure_migrations_table(pool).await?;
+
+    let migrations = load_migrations(dir)?;
+    let applied = applied_migrations(pool).await?;
+
+    // Refuse to run anything if an applied migration was edited afterwards
+    let mut drifted = 0;
+    for (row, status) in verify_applied(&migrations, &applied) {
+        match status {
+            ChecksumStatus::Modified => {
+                println!("  {} {} (checksum mismatch)", "❌".red(), row.name.red());
+                drifted += 1;
+            }
+            ChecksumStatus::Untracked => {
+                // Applied before checksums were tracked; adopt the current file
+                if let Some(migration) = migrations.iter().find(|m| m.name == row.name) {
+                    sqlx::query("UPDATE _migrations SET checksum = $1 WHERE name = $2")
+                        .bind(&migration.checksum)
+                        .bind(&migration.name)
+                        .execute(pool)
+                        .await?;
+                }
+            }
+            ChecksumStatus::Verified | ChecksumStatus::Missing => {}
+        }
+    }
+
+    if drifted > 0 {
+        bail!(
+            "{} applied migration(s) were modified on disk; restore the original files before migrating",
+            drifted
+        );
+    }
+
+    let pending = pending_migrations(&migrations, &applied);
+    for migration in &migrations {
+        if !pending.iter().any(|m| m.name == migration.name) {
+            println!("  {} {}", "⏭️".yellow(), migration.name.dimmed());
+        }
+    }
+
+    for migration in pending {
+        apply_migration(pool, migration).await?;
+    }
+
+    println!("{}", "✅ All migrations applied successfully!".bold().green());
+
+    Ok(())
+}
+
+/// Print the SQL that `migrate` would execute, without touching the database
+async fn plan(pool: &PgPool, dir: &Path) -> Result<()> {
+    println!("{}", "📋 Migration plan (dry run)".bold());
+    println!();
+
+    let migrations = load_migrations(dir)?;
+    let applied = applied_migrations(pool).await?;
+    let pending = pending_migrations(&migrations, &applied);
+
+    let current = current_version(&applied);
+    let target = pending.last().map(|m| m.version).unwrap_or(current).max(current);
+
+    println!("  Current version: {}", format!("{:03}", current).cyan());
+    println!("  Target version:  {}", format!("{:03}", target).cyan());
+    println!("  Pending:         {}", pending.len());
+    println!();
+
+    let drifted: Vec<&str> = verify_applied(&migrations, &applied)
+        .into_iter()
+        .filter(|(_, status)| *status == ChecksumStatus::Modified)
+        .map(|(row, _)| row.name.as_str())
+        .collect();
+    if !drifted.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "⚠️  migrate will refuse to run: modified migrations {}",
+                drifted.join(", ")
+            )
+            .yellow()
+        );
+        println!();
+    }
+
+    if pending.is_empty() {
+        println!("{}", "Database is up to date".green());
+        return Ok(());
+    }
+
+    for migration in pending {
+        println!("-- ========== {} ==========", migration.name);
+        println!("BEGIN;");
+        println!("{}", migration.up);
+        println!(
+            "INSERT INTO _migrations (name, checksum) VALUES ('{}', '{}');",
+            migration.name, migration.checksum
+        );
+        println!("COMMIT;");
+        println!();
+    }
+
+    println!("{}", "No changes were made (dry run)".dimmed());
+
+    Ok(())
+}
+
+/// Check applied migrations against their files; fails on drift
+async fn verify(pool: &PgPool, dir: &Path) -> Result<()> {
+    println!("{}", "🔍 Verifying migration checksums...".bold());
+    println!();
+
+    let migrations = load_migrations(dir)?;
+    let applied = applied_migrations(pool).await?;
+
+    if applied.is_empty() {
+        println!("{}", "No migrations applied yet".yellow());
         return Ok(());
     }
 
-    info!("Applying migration: {}", name);
+    let results = verify_applied(&migrations, &applied);
+    for (row, status) in &results {
+        match status {
+            ChecksumStatus::Verified => println!("  {} {}", "✅".green(), row.name),
+            ChecksumStatus::Modified => println!(
+                "  {} {} {}",
+                "❌".red(),
+                row.name,
+                "file modified after apply".red()
+            ),
+            ChecksumStatus::Missing => {
+                println!("  {} {} {}", "❌".red(), row.name, "file missing".red())
+            }
+            ChecksumStatus::Untracked => println!(
+                "  {} {} {}",
+                "➖".yellow(),
+                row.name,
+                "no recorded checksum (run migrate to record it)".yellow()
+            ),
+        }
+    }
+
+    let drifted = results.iter().filter(|(_, status)| status.is_drift()).count();
+    println!();
+
+    if drifted > 0 {
+        bail!("{} of {} applied migration(s) failed verification", drifted, results.len());
+    }
+
+    println!("{}", format!("✅ {} applied migration(s) verified", results.len()).green());
+
+    Ok(())
+}
+
+async fn apply_migration(pool: &PgPool, migration: &Migration) -> Result<()> {
+    info!("Applying migration: {}", migration.name);
+
+    let mut tx = pool.begin().await?;
 
-    // Execute migration
-    sqlx::query(sql).execute(pool).await
-        .context(format!("Failed to apply migration: {}", name))?;
+    // A bare &str runs unprepared, so a file may contain multiple statements
+    tx.execute(migration.up.as_str())
+        .await
+        .context(format!("Failed to apply migration: {}", migration.name))?;
 
     // Record migration
-    sqlx::query("INSERT INTO _migrations (name) VALUES ($1)")
-        .bind(name)
-        .execute(pool)
+    sqlx::query("INSERT INTO _migrations (name, checksum) VALUES ($1, $2)")
+        .bind(&migration.name)
+        .bind(&migration.checksum)
+        .execute(&mut *tx)
         .await?;
 
-    println!("  {} {}", "✅".green(), name.green());
+    tx.commit().await?;
+
+    println!("  {} {}", "✅".green(), migration.name.green());
 
     Ok(())
 }
 
-async fn create_migration(name: &str) -> Result<()> {
-    let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
-    let filename = format!("migrations/{}_{}.sql", timestamp, name);
+async fn create_migration(dir: &Path, name: &str) -> Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let version = next_version(&load_migrations(dir)?);
+    let filename = dir.join(format!("{:03}_{}.sql", version, name));
 
     let template = format!(
         r#"-- Migration: {}
 -- Created: {}
 
--- Up Migration
-BEGIN;
-
+{}
 -- Add your migration SQL here
 
-COMMIT;
-
--- Down Migration (for rollback)
-BEGIN;
-
+{}
 -- Add rollback SQL here
-
-COMMIT;
 "#,
         name,
-        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S")
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
+        UP_MARKER,
+        DOWN_MARKER
     );
 
     tokio::fs::write(&filename, template).await?;
 
-    println!("{}", format!("✅ Created migration: {}", filename).green());
+    println!("{}", format!("✅ Created migration: {}", filename.display()).green());
 
     Ok(())
 }
 
-async fn rollback(pool: &PgPool) -> Result<()> {
+async fn rollback(pool: &PgPool, dir: &Path) -> Result<()> {
     println!("{}", "⏪ Rolling back last migration...".bold().yellow());
 
-    let last_migration: Option<(i32, String)> = sqlx::query_as(
-        "SELECT id, name FROM _migrations ORDER BY id DESC LIMIT 1"
+    ensure_migrations_table(pool).await?;
+
+    let last_migration: Option<(i32, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, name, checksum FROM _migrations ORDER BY id DESC LIMIT 1"
     )
     .fetch_optional(pool)
     .await?;
 
-    if let Some((id, name)) = last_migration {
-        sqlx::query("DELETE FROM _migrations WHERE id = $1")
-            .bind(id)
-            .execute(pool)
-            .await?;
-
-        println!("{}", format!("✅ Rolled back: {}", name).green());
-    } else {
+    let Some((id, name, checksum)) = last_migration else {
         println!("{}", "No migrations to rollback".yellow());
+        return Ok(());
+    };
+
+    let migration = load_migrations(dir)?
+        .into_iter()
+        .find(|m| m.name == name)
+        .with_context(|| format!("Migration file for {} not found in {}", name, dir.display()))?;
+
+    if !migration.is_reversible() {
+        bail!("Migration {} has no '{}' section", name, DOWN_MARKER);
+    }
+
+    if checksum.as_deref().is_some_and(|c| c != migration.checksum) {
+        println!(
+            "  {} {} was modified since it was applied; rolling back with the current file",
+            "⚠️".yellow(),
+            name
+        );
     }
 
+    info!("Rolling back migration: {}", name);
+
+    let mut tx = pool.begin().await?;
+
+    tx.execute(migration.down.as_str())
+        .await
+        .context(format!("Failed to roll back migration: {}", name))?;
+
+    sqlx::query("DELETE FROM _migrations WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    println!("{}", format!("✅ Rolled back: {}", name).green());
+
     Ok(())
 }
 
-async fn show_status(pool: &PgPool) -> Result<()> {
+async fn show_status(pool: &PgPool, dir: &Path) -> Result<()> {
     println!("{}", "📊 Migration Status".bold());
     println!();
 
-    let migrations: Vec<(String, String)> = sqlx::query_as(
-        "SELECT name, applied_at::TEXT FROM _migrations ORDER BY id"
-    )
-    .fetch_all(pool)
-    .await?;
+    ensure_migrations_table(pool).await?;
+
+    let migrations = load_migrations(dir)?;
+    let applied = applied_migrations(pool).await?;
+    let applied_at: Vec<String> =
+        sqlx::query_scalar("SELECT applied_at::TEXT FROM _migrations ORDER BY id")
+            .fetch_all(pool)
+            .await?;
 
-    if migrations.is_empty() {
+    if applied.is_empty() {
         println!("{}", "No migrations applied yet".yellow());
-    } else {
-        for (name, applied_at) in migrations {
-            println!("  {} {} ({})", "✅".green(), name, applied_at.dimmed());
+    }
+
+    for ((row, status), applied_at) in verify_applied(&migrations, &applied)
+        .into_iter()
+        .zip(&applied_at)
+    {
+        match status {
+            ChecksumStatus::Missing => println!(
+                "  {} {} ({}) {}",
+                "❓".red(),
+                row.name,
+                applied_at.dimmed(),
+                "file missing".red()
+            ),
+            ChecksumStatus::Modified => println!(
+                "  {} {} ({}) {}",
+                "⚠️".yellow(),
+                row.name,
+                applied_at.dimmed(),
+                "modified".yellow()
+            ),
+            ChecksumStatus::Verified | ChecksumStatus::Untracked => {
+                println!("  {} {} ({})", "✅".green(), row.name, applied_at.dimmed())
+            }
         }
     }
 
+    let pending = pending_migrations(&migrations, &applied);
+    for migration in &pending {
+        println!("  {} {} {}", "⏳".cyan(), migration.name, "pending".cyan());
+    }
+
+    println!();
+    println!(
+        "Version {:03}, {} applied, {} pending",
+        current_version(&applied),
+        applied.len(),
+        pending.len()
+    );
+
     Ok(())
 }
 
@@ -242,7 +583,7 @@ async fn reset(pool: &PgPool, confirm: bool) -> Result<()> {
     Ok(())
 }
 
-async fn init_database(pool: &PgPool) -> Result<()> {
+async fn init_database(pool: &PgPool, dir: &Path) -> Result<()> {
     println!("{}", "🔧 Initializing database...".bold());
 
     // Install TimescaleDB extension
@@ -253,121 +594,219 @@ async fn init_database(pool: &PgPool) -> Result<()> {
     println!("{}", "  ✅ TimescaleDB extension installed".green());
 
     // Run migrations
-    migrate(pool).await?;
+    migrate(pool, dir).await?;
 
     println!("{}", "✅ Database initialized!".bold().green());
 
     Ok(())
 }
 
-// ========== Migration SQL ==========
-
-const CREATE_EVENTS_TABLE: &str = r#"
-CREATE TABLE IF NOT EXISTS events (
-    event_id UUID PRIMARY KEY,
-    timestamp TIMESTAMPTZ NOT NULL,
-    source_module JSONB NOT NULL,
-    event_type JSONB NOT NULL,
-    correlation_id UUID,
-    parent_event_id UUID,
-    schema_version TEXT NOT NULL,
-    severity JSONB NOT NULL,
-    environment TEXT NOT NULL,
-    tags JSONB NOT NULL DEFAULT '{}',
-    payload JSONB NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-
-SELECT create_hypertable('events', 'timestamp', if_not_exists => TRUE);
-"#;
-
-const CREATE_METRICS_TABLE: &str = r#"
-CREATE TABLE IF NOT EXISTS aggregated_metrics (
-    id BIGSERIAL,
-    metric_name TEXT NOT NULL,
-    time_window TEXT NOT NULL,
-    window_start TIMESTAMPTZ NOT NULL,
-    tags JSONB NOT NULL DEFAULT '{}',
-    avg DOUBLE PRECISION NOT NULL,
-    min DOUBLE PRECISION NOT NULL,
-    max DOUBLE PRECISION NOT NULL,
-    p50 DOUBLE PRECISION NOT NULL,
-    p95 DOUBLE PRECISION NOT NULL,
-    p99 DOUBLE PRECISION NOT NULL,
-    stddev DOUBLE PRECISION,
-    count BIGINT NOT NULL,
-    sum DOUBLE PRECISION NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    PRIMARY KEY (metric_name, time_window, window_start, tags)
-);
-
-SELECT create_hypertable('aggregated_metrics', 'window_start', if_not_exists => TRUE);
-"#;
-
-const CREATE_ANOMALIES_TABLE: &str = r#"
-CREATE TABLE IF NOT EXISTS anomalies (
-    anomaly_id UUID PRIMARY KEY,
-    detected_at TIMESTAMPTZ NOT NULL,
-    metric_name TEXT NOT NULL,
-    anomaly_type TEXT NOT NULL,
-    severity TEXT NOT NULL,
-    value DOUBLE PRECISION NOT NULL,
-    expected_value DOUBLE PRECISION,
-    confidence_score DOUBLE PRECISION NOT NULL,
-    context JSONB NOT NULL DEFAULT '{}',
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-
-SELECT create_hypertable('anomalies', 'detected_at', if_not_exists => TRUE);
-"#;
-
-const CREATE_CORRELATIONS_TABLE: &str = r#"
-CREATE TABLE IF NOT EXISTS correlations (
-    correlation_id UUID PRIMARY KEY,
-    correlation_type TEXT NOT NULL,
-    source_event_id UUID NOT NULL,
-    target_event_id UUID NOT NULL,
-    strength DOUBLE PRECISION NOT NULL,
-    metadata JSONB NOT NULL DEFAULT '{}',
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-"#;
-
-const CREATE_INDEXES: &str = r#"
-CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events (timestamp DESC);
-CREATE INDEX IF NOT EXISTS idx_events_correlation_id ON events (correlation_id) WHERE correlation_id IS NOT NULL;
-CREATE INDEX IF NOT EXISTS idx_events_source_module ON events ((source_module->>'type'));
-CREATE INDEX IF NOT EXISTS idx_events_tags ON events USING GIN (tags);
-
-CREATE INDEX IF NOT EXISTS idx_metrics_metric_window ON aggregated_metrics (metric_name, time_window, window_start DESC);
-CREATE INDEX IF NOT EXISTS idx_metrics_tags ON aggregated_metrics USING GIN (tags);
-
-CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at ON anomalies (detected_at DESC);
-CREATE INDEX IF NOT EXISTS idx_anomalies_metric_name ON anomalies (metric_name);
-
-CREATE INDEX IF NOT EXISTS idx_correlations_source ON correlations (source_event_id);
-CREATE INDEX IF NOT EXISTS idx_correlations_target ON correlations (target_event_id);
-"#;
-
-const ENABLE_COMPRESSION: &str = r#"
-ALTER TABLE events SET (
-    timescaledb.compress,
-    timescaledb.compress_segmentby = 'source_module, event_type',
-    timescaledb.compress_orderby = 'timestamp DESC'
-);
-
-ALTER TABLE aggregated_metrics SET (
-    timescaledb.compress,
-    timescaledb.compress_segmentby = 'metric_name, time_window',
-    timescaledb.compress_orderby = 'window_start DESC'
-);
-
-SELECT add_compression_policy('events', INTERVAL '7 days', if_not_exists => TRUE);
-SELECT add_compression_policy('aggregated_metrics', INTERVAL '30 days', if_not_exists => TRUE);
-"#;
-
-const RETENTION_POLICIES: &str = r#"
-SELECT add_retention_policy('events', INTERVAL '30 days', if_not_exists => TRUE);
-SELECT add_retention_policy('aggregated_metrics', INTERVAL '365 days', if_not_exists => TRUE);
-SELECT add_retention_policy('anomalies', INTERVAL '90 days', if_not_exists => TRUE);
-"#;
+// ========== Hypertable Management ==========
+
+/// Windows named on the command line, or all standard windows
+fn resolve_windows(names: &[String]) -> Result<Vec<TimeWindow>> {
+    if names.is_empty() {
+        return Ok(STANDARD_ROLLUP_WINDOWS.to_vec());
+    }
+    names.iter().map(|name| parse_window(name)).collect()
+}
+
+async fn create_aggregates(pool: &PgPool, windows: &[TimeWindow]) -> Result<()> {
+    println!("{}", "📈 Creating continuous aggregates...".bold());
+
+    for window in windows {
+        let view = rollup_view_name(*window);
+
+        // Continuous aggregates cannot be created inside a transaction
+        pool.execute(create_rollup_sql(*window).as_str())
+            .await
+            .with_context(|| format!("Failed to create continuous aggregate {}", view))?;
+        pool.execute(rollup_policy_sql(*window).as_str())
+            .await
+            .with_context(|| format!("Failed to add refresh policy for {}", view))?;
+
+        println!("  {} {} ({})", "✅".green(), view.green(), window.as_str());
+    }
+
+    println!(
+        "{}",
+        "Run `db-migrate aggregates refresh` to backfill existing data".dimmed()
+    );
+
+    Ok(())
+}
+
+async fn refresh_aggregates(pool: &PgPool, windows: &[TimeWindow], since_hours: u32) -> Result<()> {
+    println!(
+        "{}",
+        format!("🔄 Refreshing continuous aggregates (last {}h)...", since_hours).bold()
+    );
+
+    for window in windows {
+        let view = rollup_view_name(*window);
+        sqlx::query(
+            "CALL refresh_continuous_aggregate($1::REGCLASS, NOW() - make_interval(hours => $2), NOW())",
+        )
+        .bind(&view)
+        .bind(since_hours as i32)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to refresh {}", view))?;
+
+        println!("  {} {}", "✅".green(), view.green());
+    }
+
+    Ok(())
+}
+
+async fn list_aggregates(pool: &PgPool) -> Result<()> {
+    println!("{}", "📈 Continuous Aggregates".bold());
+    println!();
+
+    let rows: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT
+            ca.view_name::TEXT,
+            j.schedule_interval::TEXT,
+            js.last_successful_finish::TEXT
+        FROM timescaledb_information.continuous_aggregates ca
+        LEFT JOIN timescaledb_information.jobs j
+            ON j.hypertable_schema = ca.materialization_hypertable_schema
+           AND j.hypertable_name = ca.materialization_hypertable_name
+           AND j.proc_name = 'policy_refresh_continuous_aggregate'
+        LEFT JOIN timescaledb_information.job_stats js ON js.job_id = j.job_id
+        ORDER BY ca.view_name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for window in STANDARD_ROLLUP_WINDOWS {
+        let view = rollup_view_name(window);
+        match rows.iter().find(|(name, _, _)| *name == view) {
+            Some((_, schedule, last_run)) => println!(
+                "  {} {} every {} (last refresh: {})",
+                "✅".green(),
+                view,
+                schedule.as_deref().unwrap_or("-"),
+                last_run.as_deref().unwrap_or("never").dimmed()
+            ),
+            None => println!("  {} {} {}", "⏳".cyan(), view, "not created".cyan()),
+        }
+    }
+
+    Ok(())
+}
+
+async fn set_chunk_interval(pool: &PgPool, table: &str, interval: &str) -> Result<()> {
+    let table = validate_hypertable(table)?;
+
+    let current: Option<String> = sqlx::query_scalar(
+        "SELECT time_interval::TEXT FROM timescaledb_information.dimensions \
+         WHERE hypertable_name = $1 AND dimension_type = 'Time'",
+    )
+    .bind(table)
+    .fetch_optional(pool)
+    .await?;
+
+    sqlx::query("SELECT set_chunk_time_interval($1::REGCLASS, $2::INTERVAL)")
+        .bind(table)
+        .bind(interval)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to set chunk interval on {}", table))?;
+
+    println!(
+        "{}",
+        format!(
+            "✅ {} chunk_time_interval: {} -> {}",
+            table,
+            current.as_deref().unwrap_or("unknown"),
+            interval
+        )
+        .green()
+    );
+    println!("{}", "Existing chunks keep their current size".dimmed());
+
+    Ok(())
+}
+
+/// RetentionSettings from a file, or from Config-Manager when no file is given
+async fn load_retention_settings(path: Option<&Path>) -> Result<RetentionSettings> {
+    if let Some(path) = path {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid RetentionSettings in {}", path.display()));
+    }
+
+    let adapter = ConfigManagerAdapter::new(ConfigManagerConfig::from_env()?);
+    adapter.connect().await?;
+    adapter.fetch_retention_settings().await
+}
+
+async fn sync_policies(pool: &PgPool, settings: Option<&Path>, dry_run: bool) -> Result<()> {
+    println!("{}", "🗂️  Syncing retention and compression policies...".bold());
+
+    let settings = load_retention_settings(settings).await?;
+    let database = Database::from_pool(pool.clone());
+
+    let desired = desired_policies(&settings);
+    let actual = actual_policies(&database.query_hypertable_policies().await?);
+    let changes = plan_changes(&desired, &actual);
+
+    println!("  RetentionSettings version: {}", settings.version.cyan());
+    println!();
+
+    if changes.is_empty() {
+        println!("{}", "✅ Policies already match RetentionSettings".green());
+        return Ok(());
+    }
+
+    let days = |d: Option<u32>| d.map(|d| format!("{}d", d)).unwrap_or_else(|| "none".to_string());
+    for change in &changes {
+        let kind = match change.kind {
+            PolicyKind::Retention => "retention",
+            PolicyKind::Compression => "compression",
+        };
+        println!(
+            "  {} {} {}: {} -> {}",
+            "~".yellow(),
+            change.table,
+            kind,
+            days(change.current_days),
+            days(change.desired_days)
+        );
+    }
+    println!();
+
+    if dry_run {
+        println!("{}", "No changes were made (dry run)".dimmed());
+        return Ok(());
+    }
+
+    for change in &changes {
+        match change.kind {
+            PolicyKind::Retention => {
+                database
+                    .set_retention_policy(&change.table, change.desired_days)
+                    .await?
+            }
+            PolicyKind::Compression => {
+                database
+                    .set_compression_policy(&change.table, change.desired_days)
+                    .await?
+            }
+        }
+
+        let event = audit_event(change, &settings.version, &default_environment());
+        if let Some(record) = AuditRecord::from_event(&event) {
+            database.store_audit_record(&record).await?;
+        }
+    }
+
+    println!("{}", format!("✅ Applied {} policy change(s)", changes.len()).green());
+
+    Ok(())
+}
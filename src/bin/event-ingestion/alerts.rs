@@ -0,0 +1,447 @@
+//! Alerts, silences, alert rules and maintenance windows
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use axum::http::StatusCode;
+use llm_analytics_hub::alerting::{
+    AlertRule, AlertRuleRevision, AlertRuleSpec, AlertRuleStore, MaintenanceAction,
+    MaintenanceWindow, RuleError, Silence, SyncReport,
+};
+use llm_analytics_hub::audit::change_event;
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::environment::default_environment;
+use llm_analytics_hub::database::{EnvironmentScope, EventFilter};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::{AnalyticsEvent, ApiResponse, EventType, Severity};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::audit::record_audit;
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AlertListParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    limit: Option<i64>,
+    /// Minimum severity
+    severity: Option<Severity>,
+}
+
+/// Recent alert events, newest first
+pub(crate) async fn list_alerts(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    Query(params): Query<AlertListParams>,
+) -> Result<Json<ApiResponse<Vec<AnalyticsEvent>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+    let mut filter = EventFilter::event_type(EventType::Alert);
+    if let Some(severity) = params.severity {
+        filter = filter.and(EventFilter::severity_at_least(severity));
+    }
+
+    let alerts = database
+        .search_events(
+            &filter,
+            start,
+            end,
+            Some(params.limit.unwrap_or(100).clamp(1, 1000)),
+            &environment,
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(alerts)))
+}
+
+/// Active and scheduled alert silences
+pub(crate) async fn list_silences(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<Silence>>>, AppError> {
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(
+        state.silences.active(chrono::Utc::now()),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SilenceRequest {
+    alert_type: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    duration_minutes: i64,
+    comment: Option<String>,
+}
+
+/// Silence matching alerts for a period
+pub(crate) async fn create_silence(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<SilenceRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Silence>>), AppError> {
+    tenant.require_all_tenants()?;
+    if request.duration_minutes <= 0 {
+        return Err(AppError::ValidationError(
+            "duration_minutes must be positive".to_string(),
+        ));
+    }
+
+    let mut silence = Silence::new(chrono::Duration::minutes(request.duration_minutes));
+    silence.alert_type = request.alert_type;
+    silence.tags = request.tags;
+    silence.comment = request.comment;
+    if let Some(Extension(principal)) = principal {
+        silence = silence.with_created_by(principal.subject);
+    }
+    silence
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    if let Some(database) = &state.database {
+        database
+            .store_alert_silence(&silence)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+    state
+        .silences
+        .add(silence.clone())
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let changes = HashMap::from([("silence".to_string(), serde_json::json!(silence))]);
+    audit_silence_change(
+        &state,
+        "silence.created",
+        silence.created_by.as_deref(),
+        silence.silence_id,
+        changes,
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(silence))))
+}
+
+/// End a silence early
+pub(crate) async fn expire_silence(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Path(silence_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    tenant.require_all_tenants()?;
+    let now = chrono::Utc::now();
+    let mut expired = state.silences.expire(silence_id, now);
+    if let Some(database) = &state.database {
+        expired |= database
+            .expire_alert_silence(silence_id, now)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+
+    if expired {
+        let actor = principal.map(|Extension(p)| p.subject);
+        let changes = HashMap::from([("expired_at".to_string(), serde_json::json!(now))]);
+        audit_silence_change(
+            &state,
+            "silence.expired",
+            actor.as_deref(),
+            silence_id,
+            changes,
+        )
+        .await;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::ValidationError(format!(
+            "No active silence {}",
+            silence_id
+        )))
+    }
+}
+
+/// Record the audit trail event for a silence change
+async fn audit_silence_change(
+    state: &AppState,
+    action: &str,
+    actor: Option<&str>,
+    silence_id: uuid::Uuid,
+    changes: HashMap<String, serde_json::Value>,
+) {
+    let event = change_event(
+        action,
+        actor,
+        "silence",
+        &silence_id.to_string(),
+        changes,
+        &default_environment(),
+    );
+    if let Err(e) = record_audit(state, event).await {
+        warn!(%silence_id, "Failed to record silence audit event: {}", e);
+    }
+}
+
+fn alert_rule_store(state: &AppState) -> Result<&Arc<AlertRuleStore>, AppError> {
+    state
+        .alert_rules
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))
+}
+
+/// Record the audit trail event for a rule change
+async fn audit_alert_rule_change(state: &AppState, revision: &AlertRuleRevision) {
+    let event = llm_analytics_hub::alerting::rules::audit_event(revision, &default_environment());
+    if let Err(e) = record_audit(state, event).await {
+        warn!(rule_id = %revision.rule_id, "Failed to record alert rule audit event: {}", e);
+    }
+}
+
+/// All alert rules
+pub(crate) async fn list_alert_rules(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<AlertRule>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let rules = alert_rule_store(&state)?.list().await?;
+    Ok(Json(ApiResponse::success(rules)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateAlertRuleRequest {
+    rule_id: String,
+    #[serde(flatten)]
+    spec: AlertRuleSpec,
+}
+
+/// Create an alert rule after checking its expression and metrics
+pub(crate) async fn create_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<CreateAlertRuleRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<AlertRule>>), AppError> {
+    tenant.require_all_tenants()?;
+    let actor = principal.map(|Extension(p)| p.subject);
+    let revision = alert_rule_store(&state)?
+        .create(&request.rule_id, request.spec, actor.as_deref())
+        .await?;
+    audit_alert_rule_change(&state, &revision).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(revision.rule)),
+    ))
+}
+
+/// Current version of an alert rule
+pub(crate) async fn get_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(rule_id): Path<String>,
+) -> Result<Json<ApiResponse<AlertRule>>, AppError> {
+    tenant.require_all_tenants()?;
+    let rule = alert_rule_store(&state)?.get(&rule_id).await?;
+    Ok(Json(ApiResponse::success(rule)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UpdateAlertRuleRequest {
+    /// Version the change is based on; rejected with 409 if the rule has moved on
+    version: u64,
+    #[serde(flatten)]
+    spec: AlertRuleSpec,
+}
+
+/// Replace an alert rule, guarded by the version the caller last read
+pub(crate) async fn update_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Path(rule_id): Path<String>,
+    Json(request): Json<UpdateAlertRuleRequest>,
+) -> Result<Json<ApiResponse<AlertRule>>, AppError> {
+    tenant.require_all_tenants()?;
+    let actor = principal.map(|Extension(p)| p.subject);
+    let revision = alert_rule_store(&state)?
+        .update(&rule_id, request.version, request.spec, actor.as_deref())
+        .await?;
+    audit_alert_rule_change(&state, &revision).await;
+
+    Ok(Json(ApiResponse::success(revision.rule)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeleteAlertRuleParams {
+    version: u64,
+}
+
+/// Delete an alert rule, keeping its history
+pub(crate) async fn delete_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Path(rule_id): Path<String>,
+    Query(params): Query<DeleteAlertRuleParams>,
+) -> Result<StatusCode, AppError> {
+    tenant.require_all_tenants()?;
+    let actor = principal.map(|Extension(p)| p.subject);
+    let revision = alert_rule_store(&state)?
+        .delete(&rule_id, params.version, actor.as_deref())
+        .await?;
+    audit_alert_rule_change(&state, &revision).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Every version of an alert rule, newest first
+pub(crate) async fn alert_rule_history(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(rule_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<AlertRuleRevision>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let history = alert_rule_store(&state)?.history(&rule_id).await?;
+    Ok(Json(ApiResponse::success(history)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReloadRulesParams {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Sync alert rules and SLOs from the configured bundles, or report what a sync would change
+pub(crate) async fn reload_rule_bundles(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Query(params): Query<ReloadRulesParams>,
+) -> Result<Json<ApiResponse<SyncReport>>, AppError> {
+    tenant.require_all_tenants()?;
+    let loader = state
+        .rule_bundles
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Rule bundles not configured".to_string()))?;
+    let actor = principal.map(|Extension(p)| p.subject);
+    let report = loader
+        .sync(params.dry_run, actor.as_deref())
+        .await
+        .map_err(|e| match e.downcast_ref::<RuleError>() {
+            Some(RuleError::Conflict { .. } | RuleError::Exists(_)) => {
+                AppError::Conflict(format!("{:#}", e))
+            }
+            Some(RuleError::Storage(_)) => AppError::InternalError(format!("{:#}", e)),
+            _ => AppError::ValidationError(format!("{:#}", e)),
+        })?;
+    for revision in &report.revisions {
+        audit_alert_rule_change(&state, revision).await;
+    }
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+/// Active and scheduled maintenance windows
+pub(crate) async fn list_maintenance_windows(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<MaintenanceWindow>>>, AppError> {
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(
+        state.maintenance.active(chrono::Utc::now()),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MaintenanceWindowRequest {
+    environment: String,
+    service: Option<String>,
+    #[serde(default)]
+    action: MaintenanceAction,
+    /// Defaults to now
+    starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    duration_minutes: i64,
+    comment: Option<String>,
+}
+
+/// Schedule a maintenance window for an environment or service
+pub(crate) async fn create_maintenance_window(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<MaintenanceWindowRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<MaintenanceWindow>>), AppError> {
+    tenant.require_all_tenants()?;
+    if request.duration_minutes <= 0 {
+        return Err(AppError::ValidationError(
+            "duration_minutes must be positive".to_string(),
+        ));
+    }
+
+    let mut window = MaintenanceWindow::new(
+        request.environment,
+        request.starts_at.unwrap_or_else(chrono::Utc::now),
+        chrono::Duration::minutes(request.duration_minutes),
+    )
+    .with_action(request.action);
+    window.service = request.service;
+    window.comment = request.comment;
+    if let Some(Extension(principal)) = principal {
+        window = window.with_created_by(principal.subject);
+    }
+    window
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    if window.ends_at <= chrono::Utc::now() {
+        return Err(AppError::ValidationError(
+            "Maintenance window has already ended".to_string(),
+        ));
+    }
+
+    if let Some(database) = &state.database {
+        database
+            .store_maintenance_window(&window)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+    state
+        .maintenance
+        .add(window.clone())
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(window))))
+}
+
+/// End a maintenance window early, or cancel a scheduled one
+pub(crate) async fn end_maintenance_window(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(window_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    tenant.require_all_tenants()?;
+    let now = chrono::Utc::now();
+    let mut ended = state.maintenance.end(window_id, now);
+    if let Some(database) = &state.database {
+        ended |= database
+            .end_maintenance_window(window_id, now)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+
+    if ended {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::ValidationError(format!(
+            "No active or scheduled maintenance window {}",
+            window_id
+        )))
+    }
+}
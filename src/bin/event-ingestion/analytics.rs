@@ -0,0 +1,421 @@
+//! Analytics reports: scorecards, threats, heavy hitters, clusters, costs and scaling
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use llm_analytics_hub::analytics::clustering::{
+    ClusterAnalyzer, ClusteringAlgorithm, ClusteringReport, EntityKind, DEFAULT_MODEL_FEATURES,
+    DEFAULT_MODEL_TAG,
+};
+use llm_analytics_hub::analytics::correlation_graph::{
+    CorrelationGraph, CorrelationGraphConfig, CorrelationGraphReport, MAX_CHAIN_LENGTH,
+};
+use llm_analytics_hub::analytics::threats::DEFAULT_TOP_RESOURCES;
+use llm_analytics_hub::analytics::{
+    ModelScorecard, PipelineBreakdown, RequestCost, RequestCostOrder, RequestCostQuery,
+    ScalingRecommendation, ThreatTrendAnalyzer, ThreatTrendReport,
+};
+use llm_analytics_hub::flags::HEAVY_HITTER_ENDPOINTS;
+use llm_analytics_hub::models::metrics::TimeWindow;
+use llm_analytics_hub::pipeline::heavy_hitters::{DistinctDimension, HeavyHitter, TopKDimension};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ScorecardParams {
+    model_id: Option<String>,
+    /// Trend lookback in days
+    days: Option<i64>,
+}
+
+/// Model scorecard trend endpoint
+pub(crate) async fn scorecards(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<ScorecardParams>,
+) -> Result<Json<ApiResponse<Vec<ModelScorecard>>>, AppError> {
+    // Scorecards are computed across every tenant's traffic
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let days = params.days.unwrap_or(30).clamp(1, 365);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    let scorecards = database
+        .query_model_scorecards(params.model_id.as_deref(), since, None)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(scorecards)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ThreatTrendParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    /// Number of top attacked resources to return
+    top: Option<usize>,
+}
+
+/// Threat trend metrics for the governance dashboard
+pub(crate) async fn threat_trends(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<ThreatTrendParams>,
+) -> Result<Json<ApiResponse<ThreatTrendReport>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let hours = params.hours.unwrap_or(24).clamp(1, 24 * 90);
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(hours);
+    let top = params.top.unwrap_or(DEFAULT_TOP_RESOURCES).clamp(1, 100);
+
+    let report = ThreatTrendAnalyzer::new(database.clone())
+        .with_tenant_scope(tenant)
+        .report(start, end, top)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TopKParams {
+    dimension: TopKDimension,
+    /// Window in minutes
+    minutes: Option<i64>,
+    /// Number of keys to return
+    k: Option<usize>,
+}
+
+/// Heaviest keys over a recent window, answered from the in-memory sketches
+pub(crate) async fn top_k(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<TopKParams>,
+) -> Result<Json<ApiResponse<Vec<HeavyHitter>>>, AppError> {
+    heavy_hitters_enabled(&state)?;
+    tenant.require_all_tenants()?;
+    let window = chrono::Duration::minutes(params.minutes.unwrap_or(15).clamp(1, 24 * 60));
+    let k = params.k.unwrap_or(10).clamp(1, 100);
+    let top = state
+        .heavy_hitters
+        .top_k(params.dimension, window, k, chrono::Utc::now());
+
+    Ok(Json(ApiResponse::success(top)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DistinctParams {
+    dimension: DistinctDimension,
+    /// Window in minutes
+    minutes: Option<i64>,
+}
+
+/// Estimated distinct count over a recent window
+pub(crate) async fn distinct_count(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<DistinctParams>,
+) -> Result<Json<ApiResponse<u64>>, AppError> {
+    heavy_hitters_enabled(&state)?;
+    tenant.require_all_tenants()?;
+    let window = chrono::Duration::minutes(params.minutes.unwrap_or(60).clamp(1, 24 * 60));
+    let count = state
+        .heavy_hitters
+        .distinct(params.dimension, window, chrono::Utc::now());
+
+    Ok(Json(ApiResponse::success(count)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClusterParams {
+    /// `model` (default) or `session`
+    entity: Option<EntityKind>,
+    /// `kmeans` (default) or `dbscan`
+    algorithm: Option<String>,
+    k: Option<usize>,
+    seed: Option<u64>,
+    eps: Option<f64>,
+    min_points: Option<usize>,
+    /// Comma-separated feature metrics for model clustering
+    features: Option<String>,
+    /// Tag identifying the model on aggregated metrics
+    group_tag: Option<String>,
+    /// Lookback in hours for model clustering
+    hours: Option<i64>,
+    /// Comma-separated session IDs for session clustering
+    session_ids: Option<String>,
+}
+
+fn comma_separated(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Behavioral cohorts of models or sessions
+pub(crate) async fn clusters(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<ClusterParams>,
+) -> Result<Json<ApiResponse<ClusteringReport>>, AppError> {
+    // Cohorts are computed across every tenant's traffic
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let algorithm = match params.algorithm.as_deref().unwrap_or("kmeans") {
+        "kmeans" => ClusteringAlgorithm::KMeans {
+            k: params.k.unwrap_or(3).clamp(1, 50),
+            seed: params.seed.unwrap_or(42),
+        },
+        "dbscan" => ClusteringAlgorithm::Dbscan {
+            eps: params.eps.unwrap_or(0.5).max(f64::EPSILON),
+            min_points: params.min_points.unwrap_or(3).max(1),
+        },
+        other => {
+            return Err(AppError::ValidationError(format!(
+                "Unknown clustering algorithm '{}'",
+                other
+            )))
+        }
+    };
+
+    let analyzer =
+        ClusterAnalyzer::new(database.clone()).with_memory_graph(state.memory_graph.clone());
+
+    let report = match params.entity.unwrap_or(EntityKind::Model) {
+        EntityKind::Model => {
+            let features = params
+                .features
+                .as_deref()
+                .map(comma_separated)
+                .filter(|f| !f.is_empty())
+                .unwrap_or_else(|| {
+                    DEFAULT_MODEL_FEATURES
+                        .iter()
+                        .map(|f| f.to_string())
+                        .collect()
+                });
+            let end = chrono::Utc::now();
+            let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+            analyzer
+                .cluster_models(
+                    &features,
+                    params.group_tag.as_deref().unwrap_or(DEFAULT_MODEL_TAG),
+                    TimeWindow::OneHour,
+                    start,
+                    end,
+                    algorithm,
+                )
+                .await
+        }
+        EntityKind::Session => {
+            let session_ids = params
+                .session_ids
+                .as_deref()
+                .map(comma_separated)
+                .unwrap_or_default();
+            if session_ids.is_empty() || session_ids.len() > 1000 {
+                return Err(AppError::ValidationError(
+                    "session_ids must list between 1 and 1000 sessions".to_string(),
+                ));
+            }
+            analyzer.cluster_sessions(&session_ids, algorithm).await
+        }
+    }
+    .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PipelineBreakdownParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+}
+
+/// Per-stage latency, cost, and error rates of a registered pipeline
+pub(crate) async fn pipeline_breakdown(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(pipeline_id): Path<String>,
+    Query(params): Query<PipelineBreakdownParams>,
+) -> Result<Json<ApiResponse<PipelineBreakdown>>, AppError> {
+    // Traces and pipeline definitions are not partitioned by tenant
+    tenant.require_all_tenants()?;
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+
+    let breakdown = state
+        .pipelines
+        .breakdown(&pipeline_id, start, end)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(breakdown)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RequestCostParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    /// Only requests at least this slow
+    min_duration_ms: Option<u64>,
+    model_id: Option<String>,
+    /// Sort by `cost` (default) or `duration`
+    sort: Option<RequestCostOrder>,
+    limit: Option<i64>,
+}
+
+/// Attributed per-request costs, e.g. the most expensive slow requests
+pub(crate) async fn request_costs(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<RequestCostParams>,
+) -> Result<Json<ApiResponse<Vec<RequestCost>>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let query = RequestCostQuery {
+        since: chrono::Utc::now()
+            - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90)),
+        min_duration_ms: params.min_duration_ms.unwrap_or(0),
+        model_id: params.model_id,
+        tenant_id: tenant.tenant_id().map(str::to_string),
+        order: params.sort.unwrap_or_default(),
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+    };
+    let records = database
+        .query_request_costs(&query)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(records)))
+}
+
+/// Correlations loaded for one graph analysis
+const MAX_GRAPH_CORRELATIONS: i64 = 50_000;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CorrelationGraphParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    /// Weakest correlation included
+    min_strength: Option<f64>,
+    /// Hubs and chains returned
+    top: Option<usize>,
+    /// Longest chain returned, in signatures
+    max_chain: Option<usize>,
+}
+
+/// Hub events that repeatedly precede incidents and the strongest chains
+/// leading up to them, for incident reviews
+pub(crate) async fn correlation_graph(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<CorrelationGraphParams>,
+) -> Result<Json<ApiResponse<CorrelationGraphReport>>, AppError> {
+    // Correlations span events from every tenant
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 30));
+    let defaults = CorrelationGraphConfig::default();
+    let config = CorrelationGraphConfig {
+        top_n: params.top.unwrap_or(defaults.top_n).clamp(1, 100),
+        max_chain_length: params
+            .max_chain
+            .unwrap_or(defaults.max_chain_length)
+            .clamp(2, MAX_CHAIN_LENGTH),
+        ..defaults
+    };
+
+    let rows = database
+        .query_correlation_edges(
+            start,
+            end,
+            params.min_strength.unwrap_or(0.0).clamp(0.0, 1.0),
+            MAX_GRAPH_CORRELATIONS,
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    let graph = CorrelationGraph::from_rows(&rows, &config.incident_severity);
+
+    Ok(Json(ApiResponse::success(
+        graph.report(&config, (start, end)),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ScalingParams {
+    /// Forecast horizon in hours
+    hours: Option<i64>,
+    service: Option<String>,
+}
+
+/// Recommended replica counts per service from forecast throughput
+pub(crate) async fn scaling_recommendations(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<ScalingParams>,
+) -> Result<Json<ApiResponse<Vec<ScalingRecommendation>>>, AppError> {
+    let scaling = state
+        .scaling
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+    if let Some(hours) = params.hours {
+        let max = scaling.config().max_horizon_hours;
+        if !(1..=max).contains(&hours) {
+            return Err(AppError::ValidationError(format!(
+                "hours must be between 1 and {}",
+                max
+            )));
+        }
+    }
+    let recommendations = scaling
+        .recommend(
+            params.hours,
+            params.service.as_deref(),
+            tenant.aggregate_tags().as_ref(),
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    if recommendations.is_empty() {
+        if let Some(service) = params.service {
+            return Err(AppError::NotFound(format!(
+                "No throughput history for service {}",
+                service
+            )));
+        }
+    }
+    Ok(Json(ApiResponse::success(recommendations)))
+}
+
+fn heavy_hitters_enabled(state: &AppState) -> Result<(), AppError> {
+    if state.flags.is_enabled(HEAVY_HITTER_ENDPOINTS) {
+        Ok(())
+    } else {
+        Err(AppError::Unavailable(
+            "Heavy hitter endpoints are disabled".to_string(),
+        ))
+    }
+}
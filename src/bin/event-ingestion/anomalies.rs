@@ -0,0 +1,240 @@
+//! Anomaly listing, acknowledgement, feedback and backtests
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use axum::http::StatusCode;
+use llm_analytics_hub::analytics::{
+    AnomalyFeedback, BacktestReport, Backtester, FeedbackSummary, FeedbackVerdict,
+};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::AnomalyStatusRow;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AnomalyListParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    limit: Option<i64>,
+    /// Only anomalies nobody has acknowledged
+    #[serde(default)]
+    unacknowledged: bool,
+}
+
+/// Recent anomalies with their acknowledgement status
+pub(crate) async fn list_anomalies(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<AnomalyListParams>,
+) -> Result<Json<ApiResponse<Vec<AnomalyStatusRow>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let since =
+        chrono::Utc::now() - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+    let anomalies = database
+        .query_anomaly_statuses(
+            since,
+            params.unacknowledged,
+            params.limit.unwrap_or(100).clamp(1, 1000),
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(anomalies)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AcknowledgeRequest {
+    note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Acknowledgement {
+    anomaly_id: uuid::Uuid,
+    acknowledged_by: Option<String>,
+    acknowledged_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Acknowledge an anomaly so it drops out of the on-call queue
+pub(crate) async fn acknowledge_anomaly(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Path(anomaly_id): Path<uuid::Uuid>,
+    request: Option<Json<AcknowledgeRequest>>,
+) -> Result<Json<ApiResponse<Acknowledgement>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    database
+        .get_anomaly(anomaly_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?
+        .ok_or_else(|| AppError::ValidationError(format!("Unknown anomaly {}", anomaly_id)))?;
+
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let acknowledged_by = principal.map(|Extension(p)| p.subject);
+    let acknowledged_at = database
+        .acknowledge_anomaly(
+            anomaly_id,
+            acknowledged_by.as_deref(),
+            request.note.as_deref(),
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(Acknowledgement {
+        anomaly_id,
+        acknowledged_by,
+        acknowledged_at,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AnomalyFeedbackRequest {
+    /// Anomaly being judged; omit when reporting a missed anomaly
+    anomaly_id: Option<uuid::Uuid>,
+    /// Required for missed anomalies, otherwise taken from the anomaly
+    metric_name: Option<String>,
+    verdict: FeedbackVerdict,
+    note: Option<String>,
+}
+
+/// Mark an anomaly as a true or false positive, or report a missed one
+pub(crate) async fn record_anomaly_feedback(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<AnomalyFeedbackRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<AnomalyFeedback>>), AppError> {
+    // Detector sensitivity is shared by every tenant
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let mut feedback = match (request.anomaly_id, request.verdict) {
+        (Some(_), FeedbackVerdict::FalseNegative) => {
+            return Err(AppError::ValidationError(
+                "A detected anomaly cannot be a false negative".to_string(),
+            ))
+        }
+        (Some(anomaly_id), verdict) => {
+            let anomaly = database
+                .get_anomaly(anomaly_id)
+                .await
+                .map_err(|e| AppError::InternalError(e.to_string()))?
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!("Unknown anomaly {}", anomaly_id))
+                })?;
+            AnomalyFeedback::new(&anomaly.metric_name, verdict).with_anomaly(anomaly_id)
+        }
+        (None, FeedbackVerdict::FalseNegative) => {
+            let metric_name = request.metric_name.ok_or_else(|| {
+                AppError::ValidationError(
+                    "metric_name is required for missed anomalies".to_string(),
+                )
+            })?;
+            AnomalyFeedback::new(&metric_name, FeedbackVerdict::FalseNegative)
+        }
+        (None, _) => {
+            return Err(AppError::ValidationError(
+                "anomaly_id is required for true and false positives".to_string(),
+            ))
+        }
+    };
+    if let Some(Extension(principal)) = principal {
+        feedback = feedback.with_operator(principal.subject);
+    }
+    if let Some(note) = request.note {
+        feedback = feedback.with_note(note);
+    }
+
+    database
+        .store_anomaly_feedback(&feedback)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(feedback))))
+}
+
+/// Per-metric feedback with precision/recall estimates and threshold adjustments
+pub(crate) async fn anomaly_feedback_summary(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<FeedbackSummary>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let summaries = database
+        .query_anomaly_feedback_tallies()
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?
+        .iter()
+        .map(|row| FeedbackSummary::new(&row.metric_name, row.tally(), &state.feedback))
+        .collect();
+
+    Ok(Json(ApiResponse::success(summaries)))
+}
+
+fn backtester(state: &AppState) -> Result<&Arc<Backtester>, AppError> {
+    state
+        .backtest
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))
+}
+
+/// Latest scheduled replay of resolved incidents against the detectors
+pub(crate) async fn latest_backtest(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<BacktestReport>>, AppError> {
+    tenant.require_all_tenants()?;
+    let report = backtester(&state)?
+        .latest()
+        .ok_or_else(|| AppError::NotFound("No backtest has finished yet".to_string()))?;
+    Ok(Json(ApiResponse::success(report)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct BacktestRequest {
+    /// Sensitivity to try instead of the configured one
+    sensitivity: Option<f64>,
+}
+
+/// Replay resolved incidents now, optionally at another sensitivity
+pub(crate) async fn run_backtest(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    body: Option<Json<BacktestRequest>>,
+) -> Result<Json<ApiResponse<BacktestReport>>, AppError> {
+    tenant.require_all_tenants()?;
+    let request = body.map(|Json(request)| request).unwrap_or_default();
+    if let Some(sensitivity) = request.sensitivity {
+        if !(0.0..=1.0).contains(&sensitivity) {
+            return Err(AppError::ValidationError(
+                "sensitivity must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+    }
+    let report = backtester(&state)?
+        .run(request.sensitivity)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(report)))
+}
@@ -0,0 +1,141 @@
+//! Audit trail recording and the audit log
+
+use axum::extract::{Extension, Json, MatchedPath, Query, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use llm_analytics_hub::audit::{
+    client_ip, AuditQuery, AuditRecord, AuditedRequest, MAX_AUDIT_QUERY_LIMIT,
+};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::environment::default_environment;
+use llm_analytics_hub::grpc::EventRouter;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::{AnalyticsEvent, ApiResponse};
+use rdkafka::producer::FutureRecord;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Routes audit trail events from background tasks into the audit log
+#[derive(Clone)]
+pub(crate) struct AuditRouter(pub(crate) AppState);
+
+#[async_trait::async_trait]
+impl EventRouter for AuditRouter {
+    async fn route(&self, event: AnalyticsEvent) -> anyhow::Result<bool> {
+        record_audit(&self.0, event).await?;
+        Ok(true)
+    }
+}
+
+/// Store an audit trail event in the audit log and publish it to the audit topic
+pub(crate) async fn record_audit(state: &AppState, event: AnalyticsEvent) -> anyhow::Result<()> {
+    if let (Some(database), Some(record)) = (&state.database, AuditRecord::from_event(&event)) {
+        database.store_audit_record(&record).await?;
+    }
+
+    let payload = serde_json::to_vec(&event)?;
+    let record = FutureRecord::to(&state.audit.topic)
+        .key(&event.common.event_id.to_string())
+        .payload(&payload);
+    state
+        .kafka_producer
+        .send(record, Duration::from_secs(5))
+        .await
+        .map_err(|(e, _)| anyhow::anyhow!("Kafka error: {}", e))?;
+    Ok(())
+}
+
+/// Record audited requests, with the caller and response status, in the audit log
+pub(crate) async fn audit_requests(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+    if !state.audit.audits(request.method(), &route) {
+        return next.run(request).await;
+    }
+
+    let audited = AuditedRequest {
+        method: request.method().clone(),
+        route,
+        path: request.uri().path().to_string(),
+        actor: request
+            .extensions()
+            .get::<Principal>()
+            .map(|p| p.subject.clone()),
+        ip_address: client_ip(request.headers()),
+        user_agent: request
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+    };
+    let response = next.run(request).await;
+
+    let event = audited.event(response.status(), &default_environment());
+    tokio::spawn(async move {
+        if let Err(e) = record_audit(&state, event).await {
+            warn!(route = %audited.route, "Failed to record request audit event: {}", e);
+        }
+    });
+    response
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AuditLogParams {
+    actor: Option<String>,
+    action: Option<String>,
+    resource_type: Option<String>,
+    resource_id: Option<String>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<i64>,
+}
+
+/// Audit log entries matching the filters, newest first
+pub(crate) async fn audit_log(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<AuditLogParams>,
+) -> Result<Json<ApiResponse<Vec<AuditRecord>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+    if let (Some(start), Some(end)) = (params.start, params.end) {
+        if start >= end {
+            return Err(AppError::ValidationError(
+                "start must be before end".to_string(),
+            ));
+        }
+    }
+
+    let query = AuditQuery {
+        actor: params.actor,
+        action: params.action,
+        resource_type: params.resource_type,
+        resource_id: params.resource_id,
+        since: params.start,
+        until: params.end,
+        limit: params.limit.unwrap_or(100).clamp(1, MAX_AUDIT_QUERY_LIMIT),
+    };
+    let records = database
+        .query_audit_log(&query)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(records)))
+}
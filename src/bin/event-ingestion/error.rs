@@ -0,0 +1,79 @@
+//! Error type returned by the HTTP handlers
+
+use axum::extract::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use llm_analytics_hub::alerting::RuleError;
+use llm_analytics_hub::tenancy::{QuotaExceeded, TenantError};
+
+/// Application error types
+#[derive(Debug)]
+pub(crate) enum AppError {
+    ValidationError(String),
+    NotFound(String),
+    InternalError(String),
+    Unavailable(String),
+    Forbidden(String),
+    QuotaExceeded(String),
+    Conflict(String),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::QuotaExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+        };
+
+        let body = serde_json::json!({
+            "success": false,
+            "error": error_message
+        });
+
+        (status, Json(body)).into_response()
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::InternalError(msg) => write!(f, "Internal error: {}", msg),
+            AppError::Unavailable(msg) => write!(f, "Unavailable: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            AppError::QuotaExceeded(msg) => write!(f, "Quota exceeded: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<TenantError> for AppError {
+    fn from(e: TenantError) -> Self {
+        AppError::Forbidden(e.to_string())
+    }
+}
+
+impl From<QuotaExceeded> for AppError {
+    fn from(e: QuotaExceeded) -> Self {
+        AppError::QuotaExceeded(e.to_string())
+    }
+}
+
+impl From<RuleError> for AppError {
+    fn from(e: RuleError) -> Self {
+        match e {
+            RuleError::Invalid(_) => AppError::ValidationError(e.to_string()),
+            RuleError::NotFound(_) => AppError::NotFound(e.to_string()),
+            RuleError::Exists(_) | RuleError::Conflict { .. } => AppError::Conflict(e.to_string()),
+            RuleError::Storage(_) => AppError::InternalError(e.to_string()),
+        }
+    }
+}
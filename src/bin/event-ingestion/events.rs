@@ -0,0 +1,355 @@
+//! Event ingestion, publishing and listing
+
+use axum::extract::{Extension, Json, Query, State};
+use llm_analytics_hub::alerting::{IncidentSignal, WebhookEvent};
+use llm_analytics_hub::database::{EnvironmentScope, MAX_PAGE_SIZE};
+use llm_analytics_hub::export::prometheus::HubMetrics;
+use llm_analytics_hub::flags::INGESTION_SAMPLING;
+use llm_analytics_hub::grpc::EventRouter;
+use llm_analytics_hub::pipeline::LifecyclePhase;
+use llm_analytics_hub::schemas::events::validate_event;
+use llm_analytics_hub::telemetry::record_event_context;
+use llm_analytics_hub::tenancy::{QuotaExceeded, TenantScope};
+use llm_analytics_hub::{AnalyticsEvent, ApiResponse, EventType, PageCursor, PaginatedResponse};
+use rdkafka::producer::FutureRecord;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::error::AppError;
+use crate::incidents::open_ticket;
+use crate::AppState;
+
+pub(crate) async fn ingest_event(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(mut event): Json<AnalyticsEvent>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let started = std::time::Instant::now();
+    tenant.stamp(&mut event);
+    record_event_context(&event.common);
+
+    let event_type = format!("{:?}", event.common.event_type);
+    let source = format!("{:?}", event.common.source_module);
+
+    state
+        .metrics
+        .events_received
+        .with_label_values(&[&event_type, &source])
+        .inc();
+
+    // Validate event
+    if let Err(e) = validate_event(&event) {
+        warn!("Invalid event: {}", e);
+        state
+            .metrics
+            .events_failed
+            .with_label_values(&["validation"])
+            .inc();
+        return Err(AppError::ValidationError(e.to_string()));
+    }
+
+    // A retry of an event that was already accepted is acknowledged again
+    if state.dedup.is_duplicate(&event).await {
+        return Ok(Json(ApiResponse::success(())));
+    }
+    state
+        .enricher
+        .enrich(std::slice::from_mut(&mut event))
+        .await;
+
+    match admit(&state, &mut event) {
+        Ok(true) => {}
+        Ok(false) => {
+            state.dedup.release_ids(&[event.common.event_id]).await;
+            state
+                .metrics
+                .events_failed
+                .with_label_values(&["module_quota"])
+                .inc();
+            return Err(AppError::QuotaExceeded(format!(
+                "Source module {} exceeded its ingestion quota",
+                event.common.source_module.as_str()
+            )));
+        }
+        Err(e) => {
+            state.dedup.release_ids(&[event.common.event_id]).await;
+            state
+                .metrics
+                .events_failed
+                .with_label_values(&["tenant_quota"])
+                .inc();
+            return Err(e.into());
+        }
+    }
+
+    state.heavy_hitters.observe(&event);
+    HubMetrics::global().record_ingested(event.common.source_module.as_str(), 1);
+    if !keep_sampled(&state, &mut event) {
+        return Ok(Json(ApiResponse::success(())));
+    }
+
+    // Publish to Kafka, with the same alert handling as batch and gRPC ingestion
+    let timer = state
+        .metrics
+        .publish_duration
+        .with_label_values(&["llm-events"])
+        .start_timer();
+
+    let event_id = event.common.event_id;
+    if let Err(e) = publish_event(&state, event).await {
+        error!("Kafka publish error: {}", e);
+        state
+            .metrics
+            .events_failed
+            .with_label_values(&["kafka_publish"])
+            .inc();
+        state.dedup.release_ids(&[event_id]).await;
+        return Err(AppError::InternalError(format!(
+            "Failed to publish event: {}",
+            e
+        )));
+    }
+
+    timer.observe_duration();
+    state
+        .metrics
+        .events_published
+        .with_label_values(&["llm-events"])
+        .inc();
+    HubMetrics::global().observe_processing("ingest_event", started.elapsed());
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// Ingest batch of events
+pub(crate) async fn ingest_batch(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(mut events): Json<Vec<AnalyticsEvent>>,
+) -> Result<Json<ApiResponse<BatchResponse>>, AppError> {
+    let started = std::time::Instant::now();
+    let mut successful = 0;
+    let mut failed = 0;
+    let mut sampled = 0;
+    let mut throttled = 0;
+    let received = events.len();
+    events.retain(|event| match validate_event(event) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(event_id = %event.common.event_id, "Invalid event in batch: {}", e);
+            false
+        }
+    });
+    let invalid = received - events.len();
+    let duplicates = state.dedup.dedup(&mut events).await;
+    state.enricher.enrich(&mut events).await;
+
+    for mut event in events {
+        tenant.stamp(&mut event);
+        if !matches!(admit(&state, &mut event), Ok(true)) {
+            state.dedup.release_ids(&[event.common.event_id]).await;
+            throttled += 1;
+            continue;
+        }
+        state.heavy_hitters.observe(&event);
+        HubMetrics::global().record_ingested(event.common.source_module.as_str(), 1);
+        if !keep_sampled(&state, &mut event) {
+            sampled += 1;
+            continue;
+        }
+        let event_id = event.common.event_id;
+        match publish_event(&state, event).await {
+            Ok(_) => successful += 1,
+            Err(e) => {
+                warn!("Failed to publish event in batch: {}", e);
+                state.dedup.release_ids(&[event_id]).await;
+                failed += 1;
+            }
+        }
+    }
+    HubMetrics::global().observe_processing("ingest_batch", started.elapsed());
+
+    Ok(Json(ApiResponse::success(BatchResponse {
+        successful,
+        failed,
+        sampled,
+        throttled,
+        duplicates,
+        invalid,
+        total: successful + failed + sampled + throttled + duplicates + invalid,
+    })))
+}
+
+#[async_trait::async_trait]
+impl EventRouter for AppState {
+    async fn route(&self, mut event: AnalyticsEvent) -> anyhow::Result<bool> {
+        if self.dedup.is_duplicate(&event).await {
+            return Ok(false);
+        }
+        self.enricher.enrich(std::slice::from_mut(&mut event)).await;
+        match admit(self, &mut event) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.dedup.release_ids(&[event.common.event_id]).await;
+                anyhow::bail!(
+                    "Source module {} exceeded its ingestion quota",
+                    event.common.source_module.as_str()
+                );
+            }
+            Err(e) => {
+                self.dedup.release_ids(&[event.common.event_id]).await;
+                return Err(e.into());
+            }
+        }
+        self.heavy_hitters.observe(&event);
+        HubMetrics::global().record_ingested(event.common.source_module.as_str(), 1);
+        if !keep_sampled(self, &mut event) {
+            return Ok(false);
+        }
+        let event_id = event.common.event_id;
+        if let Err(e) = publish_event(self, event).await {
+            self.dedup.release_ids(&[event_id]).await;
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
+
+/// Check an event against its tenant's ingestion quota, then its source
+/// module's quota, returning whether the module quota kept it
+fn admit(state: &AppState, event: &mut AnalyticsEvent) -> Result<bool, QuotaExceeded> {
+    if let Some(tenant_id) = event.common.tenant_id() {
+        state.tenants.check_ingest(tenant_id, 1)?;
+    }
+    Ok(state.module_quotas.admit(event))
+}
+
+/// Apply ingestion sampling when the flag is on
+fn keep_sampled(state: &AppState, event: &mut AnalyticsEvent) -> bool {
+    !state.flags.is_enabled(INGESTION_SAMPLING) || state.sampler.sample(event)
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct BatchResponse {
+    successful: usize,
+    failed: usize,
+    /// Accepted but dropped by sampling
+    sampled: usize,
+    /// Rejected by the tenant's or source module's ingestion quota
+    throttled: usize,
+    /// Already ingested, dropped as retries
+    duplicates: usize,
+    /// Rejected by schema validation
+    invalid: usize,
+    total: usize,
+}
+
+pub(crate) async fn publish_event(
+    state: &AppState,
+    mut event: AnalyticsEvent,
+) -> anyhow::Result<()> {
+    // Alerts raised during maintenance are stored tagged with the window for later review
+    if event.common.event_type == EventType::Alert {
+        state.maintenance.annotate(&mut event, chrono::Utc::now());
+        if let Some(incident) = state.incidents.record(IncidentSignal::from_alert(&event)) {
+            let database = state.database.clone();
+            let webhooks = state.webhooks.clone();
+            let tickets = state.tickets.clone();
+            tokio::spawn(async move {
+                if let Some(database) = database {
+                    if let Err(e) = database.upsert_incident(&incident).await {
+                        warn!("Failed to store incident {}: {}", incident.incident_id, e);
+                    }
+                }
+                webhooks
+                    .publish(WebhookEvent::from_incident(&incident))
+                    .await;
+                if let Some(tickets) = tickets {
+                    open_ticket(&tickets, incident.incident_id).await;
+                }
+            });
+        }
+    }
+
+    let payload = serde_json::to_vec(&event)?;
+    let record = FutureRecord::to("llm-events")
+        .key(&event.common.event_id.to_string())
+        .payload(&payload);
+
+    state
+        .kafka_producer
+        .send(record, Duration::from_secs(5))
+        .await
+        .map_err(|(e, _)| anyhow::anyhow!("Kafka error: {}", e))?;
+
+    // Alerts are paged or batched into digests, and pushed to webhooks, off the request path
+    if event.common.event_type == EventType::Alert {
+        let webhooks = state.webhooks.clone();
+        let alert = WebhookEvent::from_alert(&event);
+        tokio::spawn(async move {
+            webhooks.publish(alert).await;
+        });
+        if let Some(alerts) = state.alerts.clone() {
+            tokio::spawn(async move {
+                alerts.notify(&event).await;
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Publish a lifecycle event directly rather than through the self-monitor's
+/// channel, so the shutdown event is sent before the service exits
+pub(crate) async fn publish_lifecycle(state: &AppState, phase: LifecyclePhase) {
+    if !state.self_monitor.enabled() {
+        return;
+    }
+    let event = state
+        .self_monitor
+        .lifecycle_event(phase, serde_json::json!({ "service": "event-ingestion" }));
+    if let Err(e) = publish_event(state, event).await {
+        warn!(?phase, "Failed to publish lifecycle event: {}", e);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EventListParams {
+    /// Lookback in hours
+    hours: Option<i64>,
+    /// Token from the previous page's `next_cursor`; omit for the first page
+    cursor: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Recent events, newest first, one cursor page at a time
+pub(crate) async fn list_events(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    Query(params): Query<EventListParams>,
+) -> Result<Json<PaginatedResponse<AnalyticsEvent>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(PageCursor::decode)
+        .transpose()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_PAGE_SIZE);
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+    let filter = tenant.scope_filter(None);
+
+    let page = database
+        .query_events_page(start, end, filter.as_ref(), cursor, limit, &environment)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(Json(page.into_response(limit, cursor.is_some())))
+}
@@ -0,0 +1,132 @@
+//! Cross-region rollup federation
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use llm_analytics_hub::database::timescale::parse_window;
+use llm_analytics_hub::database::AggregatedMetricRow;
+use llm_analytics_hub::federation::{
+    merge_by_window, FederationReceiver, RegionStatus, RollupBatch, REGION_TAG,
+};
+use llm_analytics_hub::models::metrics::TimeWindow;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::AppState;
+
+fn federation_receiver(state: &AppState) -> Result<&Arc<FederationReceiver>, AppError> {
+    state.federation.as_ref().ok_or_else(|| {
+        AppError::Unavailable("This hub does not receive federated rollups".to_string())
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RollupReceipt {
+    batch_id: uuid::Uuid,
+    rows_stored: u64,
+}
+
+/// Store a batch of rollups shipped by a region-local hub
+pub(crate) async fn receive_rollups(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(batch): Json<RollupBatch>,
+) -> Result<Json<ApiResponse<RollupReceipt>>, AppError> {
+    // Batches carry every tenant's rollups for the region
+    tenant.require_all_tenants()?;
+    let receiver = federation_receiver(&state)?;
+    batch
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    let batch_id = batch.batch_id;
+    let rows_stored = receiver
+        .receive(batch)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(RollupReceipt {
+        batch_id,
+        rows_stored,
+    })))
+}
+
+/// Regions and clusters that have shipped rollups since startup
+pub(crate) async fn federation_regions(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<RegionStatus>>>, AppError> {
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(
+        federation_receiver(&state)?.regions(),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FederatedSeriesParams {
+    /// Aggregation window, e.g. `5m` or `1h`
+    window: Option<String>,
+    /// Lookback in hours, ignored when `start` is given
+    hours: Option<i64>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only rollups shipped by this region
+    region: Option<String>,
+    /// Tag to keep series apart by, e.g. `region`; merged into one series when unset
+    group_by: Option<String>,
+}
+
+/// One metric merged across federated regions, or per region with `group_by=region`
+pub(crate) async fn federated_series(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(metric_name): Path<String>,
+    Query(params): Query<FederatedSeriesParams>,
+) -> Result<Json<ApiResponse<Vec<AggregatedMetricRow>>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let window = match params.window.as_deref() {
+        Some(w) => parse_window(w).map_err(|e| AppError::ValidationError(e.to_string()))?,
+        None => TimeWindow::OneHour,
+    };
+    let end = params.end.unwrap_or_else(chrono::Utc::now);
+    let start = params.start.unwrap_or_else(|| {
+        end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90))
+    });
+    if start >= end {
+        return Err(AppError::ValidationError(
+            "start must be before end".to_string(),
+        ));
+    }
+
+    // Rows without a region tag were not shipped by a federated hub
+    let mut tags = match tenant.aggregate_tags() {
+        Some(serde_json::Value::Object(tags)) => tags,
+        _ => serde_json::Map::new(),
+    };
+    if let Some(region) = params.region {
+        tags.insert(REGION_TAG.to_string(), region.into());
+    }
+    let rows = database
+        .query_aggregates_cached(
+            &metric_name,
+            window,
+            start,
+            end,
+            Some(&serde_json::Value::Object(tags)),
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?
+        .rows
+        .into_iter()
+        .filter(|row| row.tags.get(REGION_TAG).is_some())
+        .collect::<Vec<_>>();
+
+    Ok(Json(ApiResponse::success(merge_by_window(
+        &rows,
+        params.group_by.as_deref(),
+    ))))
+}
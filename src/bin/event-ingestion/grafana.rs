@@ -0,0 +1,116 @@
+//! Grafana JSON datasource
+
+use axum::extract::{Extension, Json, State};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::environment::default_environment;
+use llm_analytics_hub::database::EnvironmentScope;
+use llm_analytics_hub::export::{
+    GrafanaDatasource, GrafanaQueryRequest, GrafanaSearchRequest, GrafanaTagKey, GrafanaTagValue,
+    GrafanaTagValuesRequest, GrafanaTimeSeries,
+};
+use llm_analytics_hub::tenancy::TenantScope;
+
+use crate::error::AppError;
+use crate::metering::meter_query;
+use crate::AppState;
+
+/// Grafana JSON datasource over the aggregated metrics; responses are bare
+/// JSON in the plugin's protocol rather than `ApiResponse` envelopes
+fn grafana_datasource(state: &AppState) -> Result<GrafanaDatasource, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+    Ok(GrafanaDatasource::new(
+        database.clone(),
+        state.planner.clone(),
+    ))
+}
+
+/// Tags selecting the caller's tenant and environment in the aggregated metrics
+fn aggregate_scope_tags(
+    state: &AppState,
+    tenant: &TenantScope,
+    environment: &EnvironmentScope,
+) -> Option<serde_json::Value> {
+    let default_environment = state
+        .database
+        .as_ref()
+        .map_or_else(default_environment, |db| {
+            db.default_environment().to_string()
+        });
+    environment.scope_tags(&default_environment, tenant.aggregate_tags())
+}
+
+/// Datasource connection test
+pub(crate) async fn grafana_health(
+    State(state): State<AppState>,
+) -> Result<&'static str, AppError> {
+    grafana_datasource(&state)?;
+    Ok("OK")
+}
+
+/// Metric names for the query editor
+pub(crate) async fn grafana_search(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    body: Option<Json<GrafanaSearchRequest>>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let request = body.map(|Json(request)| request).unwrap_or_default();
+    let scope_tags = aggregate_scope_tags(&state, &tenant, &environment);
+    let names = grafana_datasource(&state)?
+        .search(request.target.as_deref(), scope_tags.as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(names))
+}
+
+/// Series for a panel's targets
+pub(crate) async fn grafana_query(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<GrafanaQueryRequest>,
+) -> Result<Json<Vec<GrafanaTimeSeries>>, AppError> {
+    let datasource = grafana_datasource(&state)?;
+    request
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let scope_tags = aggregate_scope_tags(&state, &tenant, &environment);
+    let started = std::time::Instant::now();
+    let series = datasource.query(&request, scope_tags.as_ref()).await;
+    meter_query(&state, &tenant, principal.as_deref(), started);
+    let series = series.map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(series))
+}
+
+/// Tag keys for ad-hoc filters
+pub(crate) async fn grafana_tag_keys(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+) -> Result<Json<Vec<GrafanaTagKey>>, AppError> {
+    let scope_tags = aggregate_scope_tags(&state, &tenant, &environment);
+    let keys = grafana_datasource(&state)?
+        .tag_keys(scope_tags.as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(keys))
+}
+
+/// Values of one tag key for ad-hoc filters
+pub(crate) async fn grafana_tag_values(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    Json(request): Json<GrafanaTagValuesRequest>,
+) -> Result<Json<Vec<GrafanaTagValue>>, AppError> {
+    let scope_tags = aggregate_scope_tags(&state, &tenant, &environment);
+    let values = grafana_datasource(&state)?
+        .tag_values(&request.key, scope_tags.as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(values))
+}
@@ -0,0 +1,251 @@
+//! Incident tracking, ticket sync and postmortems
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use llm_analytics_hub::alerting::{
+    Incident, IncidentStatus, TicketSync, TimelineEntry, WebhookEvent,
+};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::reporting::{PostmortemConfig, PostmortemExporter, ReportFormat};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::{ApiResponse, Severity};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct IncidentListParams {
+    status: Option<String>,
+}
+
+/// Incidents newest first, optionally filtered by status
+pub(crate) async fn list_incidents(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<IncidentListParams>,
+) -> Result<Json<ApiResponse<Vec<Incident>>>, AppError> {
+    // Incidents group findings across every tenant
+    tenant.require_all_tenants()?;
+    let status = params
+        .status
+        .as_deref()
+        .map(str::parse::<IncidentStatus>)
+        .transpose()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(state.incidents.list(status))))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateIncidentRequest {
+    title: String,
+    environment: Option<String>,
+    severity: Option<Severity>,
+    assignee: Option<String>,
+}
+
+/// Open an incident by hand, e.g. for a customer report no detector caught
+pub(crate) async fn create_incident(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(request): Json<CreateIncidentRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Incident>>), AppError> {
+    tenant.require_all_tenants()?;
+    let mut incident = Incident::new(
+        request.title,
+        request
+            .environment
+            .unwrap_or_else(llm_analytics_hub::database::environment::default_environment),
+        request.severity.unwrap_or(Severity::Warning),
+        chrono::Utc::now(),
+    );
+    incident.assignee = request.assignee;
+    incident
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    if let Some(database) = &state.database {
+        database
+            .upsert_incident(&incident)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+    state
+        .incidents
+        .add(incident.clone())
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    state
+        .webhooks
+        .publish(WebhookEvent::from_incident(&incident))
+        .await;
+    if let Some(tickets) = state.tickets.clone() {
+        let incident_id = incident.incident_id;
+        tokio::spawn(async move {
+            open_ticket(&tickets, incident_id).await;
+        });
+    }
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(incident))))
+}
+
+/// Raise a ticket for a newly opened incident; failures are retried by the ticket sync
+pub(crate) async fn open_ticket(tickets: &TicketSync, incident_id: uuid::Uuid) {
+    if let Err(e) = tickets.open_ticket(incident_id).await {
+        warn!("Failed to open ticket for incident {}: {}", incident_id, e);
+    }
+}
+
+pub(crate) async fn get_incident(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(incident_id): Path<uuid::Uuid>,
+) -> Result<Json<ApiResponse<Incident>>, AppError> {
+    tenant.require_all_tenants()?;
+    state
+        .incidents
+        .get(incident_id)
+        .map(|incident| Json(ApiResponse::success(incident)))
+        .ok_or_else(|| AppError::NotFound(format!("No incident {}", incident_id)))
+}
+
+/// Signals, status changes, and notes of an incident in time order
+pub(crate) async fn incident_timeline(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(incident_id): Path<uuid::Uuid>,
+) -> Result<Json<ApiResponse<Vec<TimelineEntry>>>, AppError> {
+    tenant.require_all_tenants()?;
+    state
+        .incidents
+        .get(incident_id)
+        .map(|incident| Json(ApiResponse::success(incident.timeline)))
+        .ok_or_else(|| AppError::NotFound(format!("No incident {}", incident_id)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PostmortemParams {
+    /// json (default), markdown, or html
+    format: Option<String>,
+}
+
+/// Related events, metric windows, forecasts vs actuals, and changes around
+/// an incident, bundled for its postmortem
+pub(crate) async fn incident_postmortem(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(incident_id): Path<uuid::Uuid>,
+    Query(params): Query<PostmortemParams>,
+) -> Result<Response, AppError> {
+    tenant.require_all_tenants()?;
+    let format = match params.format.as_deref() {
+        Some(format) => format
+            .parse::<ReportFormat>()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?,
+        None => ReportFormat::Json,
+    };
+    let incident = state
+        .incidents
+        .get(incident_id)
+        .ok_or_else(|| AppError::NotFound(format!("No incident {}", incident_id)))?;
+    let database = state
+        .database
+        .clone()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let bundle = PostmortemExporter::new(database, PostmortemConfig::default())
+        .with_topology(state.topology.clone())
+        .export(&incident)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok(match format {
+        ReportFormat::Json => Json(ApiResponse::success(bundle)).into_response(),
+        format => (
+            [(header::CONTENT_TYPE, format.content_type())],
+            bundle.to_document().render(format),
+        )
+            .into_response(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UpdateIncidentRequest {
+    title: Option<String>,
+    status: Option<IncidentStatus>,
+    assignee: Option<String>,
+    /// Recorded with the status change, or on its own
+    note: Option<String>,
+}
+
+/// Acknowledge, resolve, reopen, retitle, or annotate an incident
+pub(crate) async fn update_incident(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Path(incident_id): Path<uuid::Uuid>,
+    Json(request): Json<UpdateIncidentRequest>,
+) -> Result<Json<ApiResponse<Incident>>, AppError> {
+    tenant.require_all_tenants()?;
+    if state.incidents.get(incident_id).is_none() {
+        return Err(AppError::NotFound(format!("No incident {}", incident_id)));
+    }
+    let by = principal.map(|Extension(principal)| principal.subject);
+    let now = chrono::Utc::now();
+
+    let incident = state
+        .incidents
+        .update(incident_id, |incident| {
+            if let Some(title) = request.title {
+                incident.title = title;
+                incident.updated_at = now;
+            }
+            if let Some(assignee) = request.assignee {
+                incident.assignee = Some(assignee);
+                incident.updated_at = now;
+            }
+            match (request.status, request.note) {
+                (Some(status), note) => incident.set_status(status, by, note, now)?,
+                (None, Some(note)) => incident.add_note(by, note, now),
+                (None, None) => {}
+            }
+            Ok(())
+        })
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    if let Some(database) = &state.database {
+        database
+            .upsert_incident(&incident)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+    state
+        .webhooks
+        .publish(WebhookEvent::from_incident(&incident))
+        .await;
+
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+pub(crate) async fn delete_incident(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(incident_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    tenant.require_all_tenants()?;
+    let mut deleted = state.incidents.remove(incident_id).is_some();
+    if let Some(database) = &state.database {
+        deleted |= database
+            .delete_incident(incident_id)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+    }
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("No incident {}", incident_id)))
+    }
+}
@@ -0,0 +1,952 @@
+//! Event Ingestion Service
+//!
+//! High-performance HTTP service for ingesting LLM analytics events.
+//! Features:
+//! - HTTP/2 support with Axum framework
+//! - Request validation and sanitization
+//! - API key and JWT authentication with scoped authorization
+//! - Tenant-scoped queries and per-tenant ingestion and query quotas
+//! - Kafka producer for event streaming
+//! - OTLP/HTTP receiver for OpenTelemetry traces and metrics
+//! - Prometheus metrics export
+//! - Structured logging
+//! - Graceful shutdown
+//! - Health checks
+
+mod alerts;
+mod analytics;
+mod anomalies;
+mod audit;
+mod error;
+mod events;
+mod federation;
+mod grafana;
+mod incidents;
+mod metering;
+mod metrics;
+mod otlp;
+mod scope;
+mod slo;
+mod sql;
+mod topology;
+mod webhooks;
+
+use axum::routing::{delete, get, patch, post, put};
+use axum::{middleware, Router};
+use llm_analytics_hub::adapters::memory_graph::MemoryGraphAdapter;
+use llm_analytics_hub::adapters::{
+    AdapterManager, IncrementalSync, MirrorConfig, RegistryMirror, SyncConfig,
+};
+use llm_analytics_hub::alerting::{
+    ticket_client_from_config, AlertRuleConfig, AlertRuleStore, DigestNotifier, IncidentConfig,
+    IncidentSync, IncidentTracker, MaintenanceRegistry, NotificationRouter, RuleBundleConfig,
+    RuleBundleLoader, SilenceRegistry, TicketSync, TicketingConfig, WebhookConfig,
+    WebhookDispatcher,
+};
+use llm_analytics_hub::analytics::topology::TopologyStore;
+use llm_analytics_hub::analytics::{
+    AnalyticsConfig, BacktestConfig, Backtester, CostAttributionConfig, CostAttributionJob,
+    FeedbackConfig, PipelineAnalyzer, ScalingConfig, ScalingRecommender, SessionAnalyticsConfig,
+    SessionAnalyticsJob, UnitEconomicsConfig, UnitEconomicsJob,
+};
+use llm_analytics_hub::archival::{object_store_for, TieredQuery, TieredQueryConfig};
+use llm_analytics_hub::audit::AuditConfig;
+use llm_analytics_hub::auth::{
+    require_auth, require_scope, AuthConfig, Authenticator, Scope, ScopedApiKeys,
+};
+use llm_analytics_hub::database::{
+    event_search_router, QueryPlanner, QueryPlannerConfig, QueryResultCache, ResultCacheConfig,
+    SqlExecutor, SqlLimits,
+};
+use llm_analytics_hub::export::prometheus::metrics_router;
+use llm_analytics_hub::federation::{
+    FederationConfig, FederationReceiver, FederationRole, FederationShipper, FederationTransport,
+    ROLLUPS_PATH,
+};
+use llm_analytics_hub::flags::{
+    FlagService, FlagServiceConfig, HEAVY_HITTER_ENDPOINTS, INGESTION_SAMPLING,
+};
+use llm_analytics_hub::grpc::{ApiKeyStore, GrpcIngestionConfig, IngestionService};
+use llm_analytics_hub::health::{
+    health_router, lag_router, live_handler, ready_handler, KafkaLagCheck, LagMonitor,
+    LagMonitorConfig, ReadinessProbe,
+};
+use llm_analytics_hub::metering::{UsageFlushConfig, UsageFlushJob, UsageMeter};
+use llm_analytics_hub::otlp::OtlpConverter;
+use llm_analytics_hub::pipeline::heavy_hitters::{HeavyHitterConfig, HeavyHitterTracker};
+use llm_analytics_hub::pipeline::{
+    DedupConfig, Deduplicator, HotCache, HotCacheConfig, LifecyclePhase, ModuleQuotaConfig,
+    ModuleQuotas, Sampler, SelfMonitor, SelfMonitorConfig, TagEnricher, TagEnrichmentConfig,
+};
+use llm_analytics_hub::reporting::ReportScheduler;
+use llm_analytics_hub::slo::SloEngine;
+use llm_analytics_hub::telemetry::{init_tracing, TracingConfig};
+use llm_analytics_hub::tenancy::{TenantQuotaConfig, TenantQuotas};
+use llm_analytics_hub::Database;
+use prometheus::{
+    register_counter_vec, register_histogram_vec, register_int_gauge, CounterVec, HistogramVec,
+    IntGauge,
+};
+use rdkafka::producer::FutureProducer;
+use rdkafka::ClientConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal;
+use tokio::sync::mpsc;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info, warn};
+
+use crate::alerts::{
+    alert_rule_history, create_alert_rule, create_maintenance_window, create_silence,
+    delete_alert_rule, end_maintenance_window, expire_silence, get_alert_rule, list_alert_rules,
+    list_alerts, list_maintenance_windows, list_silences, reload_rule_bundles, update_alert_rule,
+};
+use crate::analytics::{
+    clusters, correlation_graph, distinct_count, pipeline_breakdown, request_costs,
+    scaling_recommendations, scorecards, threat_trends, top_k,
+};
+use crate::anomalies::{
+    acknowledge_anomaly, anomaly_feedback_summary, latest_backtest, list_anomalies,
+    record_anomaly_feedback, run_backtest,
+};
+use crate::audit::{audit_log, audit_requests, AuditRouter};
+use crate::events::{ingest_batch, ingest_event, list_events, publish_event, publish_lifecycle};
+use crate::federation::{federated_series, federation_regions, receive_rollups};
+use crate::grafana::{
+    grafana_health, grafana_query, grafana_search, grafana_tag_keys, grafana_tag_values,
+};
+use crate::incidents::{
+    create_incident, delete_incident, get_incident, incident_postmortem, incident_timeline,
+    list_incidents, update_incident,
+};
+use crate::metering::{meter_usage, usage_report};
+use crate::metrics::{downsampled_series, metric_series, preview_metric_filter};
+use crate::otlp::{otlp_metrics, otlp_traces};
+use crate::scope::{scope_environment, scope_tenant};
+use crate::slo::{define_slo, slo_status};
+use crate::sql::{run_cross_environment_sql, run_sql};
+use crate::topology::{
+    get_topology, mirrored_model, registry_mirror_status, topology_entity, topology_owners,
+};
+use crate::webhooks::{
+    delete_webhook, get_webhook, list_webhooks, register_webhook, replay_webhook_delivery,
+    webhook_deliveries,
+};
+
+/// Application state shared across handlers
+#[derive(Clone)]
+struct AppState {
+    kafka_producer: Arc<FutureProducer>,
+    metrics: Arc<Metrics>,
+    usage: UsageMeter,
+    database: Option<Arc<Database>>,
+    planner: Arc<QueryPlanner>,
+    sql: Option<Arc<SqlExecutor>>,
+    slo: Option<Arc<SloEngine>>,
+    dedup: Arc<Deduplicator>,
+    enricher: Arc<TagEnricher>,
+    heavy_hitters: Arc<HeavyHitterTracker>,
+    sampler: Arc<Sampler>,
+    flags: Arc<FlagService>,
+    otlp: OtlpConverter,
+    tenants: Arc<TenantQuotas>,
+    module_quotas: Arc<ModuleQuotas>,
+    memory_graph: Arc<MemoryGraphAdapter>,
+    pipelines: Arc<PipelineAnalyzer>,
+    topology: Arc<TopologyStore>,
+    alerts: Option<Arc<DigestNotifier>>,
+    alert_rules: Option<Arc<AlertRuleStore>>,
+    rule_bundles: Option<Arc<RuleBundleLoader>>,
+    silences: Arc<SilenceRegistry>,
+    maintenance: Arc<MaintenanceRegistry>,
+    incidents: Arc<IncidentTracker>,
+    tickets: Option<Arc<TicketSync>>,
+    webhooks: Arc<WebhookDispatcher>,
+    federation: Option<Arc<FederationReceiver>>,
+    tiered: Option<Arc<TieredQuery>>,
+    registry_mirror: Option<Arc<RegistryMirror>>,
+    backtest: Option<Arc<Backtester>>,
+    scaling: Option<Arc<ScalingRecommender>>,
+    feedback: FeedbackConfig,
+    audit: AuditConfig,
+    self_monitor: Arc<SelfMonitor>,
+}
+
+/// Prometheus metrics
+struct Metrics {
+    events_received: CounterVec,
+    events_published: CounterVec,
+    events_failed: CounterVec,
+    publish_duration: HistogramVec,
+    active_connections: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            events_received: register_counter_vec!(
+                "llm_events_received_total",
+                "Total number of events received",
+                &["event_type", "source_module"]
+            )?,
+            events_published: register_counter_vec!(
+                "llm_events_published_total",
+                "Total number of events published to Kafka",
+                &["topic"]
+            )?,
+            events_failed: register_counter_vec!(
+                "llm_events_failed_total",
+                "Total number of failed event ingestions",
+                &["error_type"]
+            )?,
+            publish_duration: register_histogram_vec!(
+                "llm_event_publish_duration_seconds",
+                "Duration of event publishing to Kafka",
+                &["topic"]
+            )?,
+            active_connections: register_int_gauge!(
+                "llm_active_connections",
+                "Number of active HTTP connections"
+            )?,
+        })
+    }
+}
+
+/// Configuration from environment variables
+#[derive(Debug, Clone)]
+struct Config {
+    kafka_brokers: String,
+    kafka_topic: String,
+    http_port: u16,
+    max_payload_size: usize,
+    database_url: Option<String>,
+    lag_group_id: String,
+    max_consumer_lag: u64,
+    slo_definitions: Option<String>,
+    report_schedules: Option<String>,
+    alert_digest_rules: Option<String>,
+    topology_refresh_secs: u64,
+}
+
+impl Config {
+    fn from_env() -> Self {
+        Self {
+            kafka_brokers: std::env::var("KAFKA_BROKERS")
+                .unwrap_or_else(|_| "kafka.llm-analytics.svc.cluster.local:9092".to_string()),
+            kafka_topic: std::env::var("KAFKA_TOPIC").unwrap_or_else(|_| "llm-events".to_string()),
+            http_port: std::env::var("HTTP_PORT")
+                .unwrap_or_else(|_| "8080".to_string())
+                .parse()
+                .expect("Invalid HTTP_PORT"),
+            max_payload_size: std::env::var("MAX_PAYLOAD_SIZE")
+                .unwrap_or_else(|_| "10485760".to_string()) // 10MB default
+                .parse()
+                .expect("Invalid MAX_PAYLOAD_SIZE"),
+            database_url: std::env::var("DATABASE_URL").ok(),
+            lag_group_id: std::env::var("KAFKA_LAG_GROUP_ID")
+                .unwrap_or_else(|_| "metrics-aggregation".to_string()),
+            max_consumer_lag: std::env::var("MAX_CONSUMER_LAG")
+                .unwrap_or_else(|_| "100000".to_string())
+                .parse()
+                .expect("Invalid MAX_CONSUMER_LAG"),
+            slo_definitions: std::env::var("SLO_DEFINITIONS_FILE").ok(),
+            report_schedules: std::env::var("REPORT_SCHEDULE_FILE").ok(),
+            alert_digest_rules: std::env::var("ALERT_DIGEST_FILE").ok(),
+            topology_refresh_secs: std::env::var("TOPOLOGY_REFRESH_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .expect("Invalid TOPOLOGY_REFRESH_SECS"),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Initialize tracing (spans are exported when OTEL_EXPORTER_OTLP_ENDPOINT is set)
+    let _tracing = init_tracing(
+        &TracingConfig::from_env("event-ingestion").with_default_filter("event_ingestion=info,tower_http=debug"),
+    )?;
+
+    info!("Starting Event Ingestion Service v{}", env!("CARGO_PKG_VERSION"));
+
+    // Load configuration
+    let config = Config::from_env();
+    info!(?config, "Configuration loaded");
+
+    // Initialize metrics
+    let metrics = Arc::new(Metrics::new()?);
+
+    // Create Kafka producer
+    let kafka_producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .set("compression.type", "lz4")
+        .set("batch.size", "16384")
+        .set("linger.ms", "10")
+        .set("acks", "1")
+        .set("client.id", "event-ingestion-service")
+        .create()?;
+
+    info!("Kafka producer initialized");
+
+    // Readiness combines downstream consumer lag, database, and adapter health
+    let mut probe = ReadinessProbe::new();
+
+    let mut database = None;
+    if let Some(url) = &config.database_url {
+        match Database::from_url(url).await {
+            Ok(mut db) => {
+                db = db.with_result_cache(Arc::new(QueryResultCache::new(
+                    ResultCacheConfig::from_env(),
+                )));
+                // Recent aggregate windows are served from Redis when it is configured
+                if std::env::var("REDIS_URL").is_ok() {
+                    match HotCache::connect(HotCacheConfig::from_env()).await {
+                        Ok(cache) => db = db.with_hot_cache(Arc::new(cache)),
+                        Err(e) => warn!("Hot cache unavailable, reading aggregates from the database: {}", e),
+                    }
+                }
+                let db = Arc::new(db);
+                probe = probe.with_check(db.clone());
+                database = Some(db);
+            }
+            Err(e) => warn!("Database unavailable, readiness will not include it: {}", e),
+        }
+    }
+
+    // Long-range metric queries are costed, and downgraded to coarser rollups, before they run
+    let planner = Arc::new(QueryPlanner::new(QueryPlannerConfig::from_env()));
+    if let Some(db) = &database {
+        if let Err(e) = planner.refresh_policies(db).await {
+            warn!("Using default compression thresholds for query planning: {}", e);
+        }
+    }
+
+    let slo = match &database {
+        Some(db) => {
+            let engine = SloEngine::new(db.clone());
+            if let Some(path) = &config.slo_definitions {
+                let yaml = std::fs::read_to_string(path)?;
+                info!("Loaded {} SLO definitions", engine.load_yaml(&yaml)?);
+            }
+            Some(Arc::new(engine))
+        }
+        None => None,
+    };
+
+    let adapters = AdapterManager::new()?;
+    adapters.connect_all().await?;
+
+    let params = adapters.config_manager.fetch_analytics_parameters().await?;
+    let analytics_config = AnalyticsConfig::default().with_parameters(&params);
+    let sampler = Arc::new(Sampler::new(params.sampling));
+
+    let flags = Arc::new(
+        FlagService::new(adapters.config_manager.clone(), FlagServiceConfig::from_env())
+            .with_default(HEAVY_HITTER_ENDPOINTS, true)
+            .with_default(INGESTION_SAMPLING, true),
+    );
+    if let Err(e) = flags.refresh().await {
+        warn!("Initial feature flag refresh failed: {}", e);
+    }
+    flags.clone().spawn();
+
+    // Session engagement trends are rolled up from Memory-Graph into aggregated metrics
+    if let Some(db) = &database {
+        Arc::new(SessionAnalyticsJob::new(
+            adapters.memory_graph.clone(),
+            db.clone(),
+            SessionAnalyticsConfig::from_env(),
+        ))
+        .spawn();
+    }
+
+    // Cost aggregates are divided into cost per session, user, and feature
+    if let Some(db) = &database {
+        Arc::new(UnitEconomicsJob::new(
+            db.clone(),
+            UnitEconomicsConfig::from_env()?,
+        ))
+        .spawn();
+    }
+
+    // Trace latency is joined with token cost events into per-request cost records
+    if let Some(db) = &database {
+        Arc::new(CostAttributionJob::new(
+            adapters.observatory.clone(),
+            db.clone(),
+            CostAttributionConfig::from_env(),
+        ))
+        .spawn();
+    }
+
+    // Resolved incidents are replayed against the current detector configuration
+    let mut backtest = None;
+    if let Some(db) = &database {
+        let backtester = Arc::new(
+            Backtester::new(db.clone(), analytics_config, BacktestConfig::from_env()?)
+                .with_feedback_config(FeedbackConfig::from_env()),
+        );
+        backtester.clone().spawn();
+        backtest = Some(backtester);
+    }
+
+    // Throughput forecasts are turned into per-service replica recommendations
+    let scaling = match &database {
+        Some(db) => Some(Arc::new(ScalingRecommender::new(
+            db.clone(),
+            ScalingConfig::from_env()?,
+        ))),
+        None => None,
+    };
+
+    // Registry models, providers, and pipelines are mirrored locally so joins never wait on the registry
+    let mut registry_mirror = None;
+    if let Some(db) = &database {
+        let sync = IncrementalSync::new(db.clone(), SyncConfig::from_env());
+        let mirror = Arc::new(
+            RegistryMirror::new(adapters.registry.clone(), sync, MirrorConfig::from_env())
+                .with_database(db.clone()),
+        );
+        if let Err(e) = mirror.load().await {
+            warn!("Failed to load registry mirror: {}", e);
+        }
+        mirror.clone().spawn();
+        registry_mirror = Some(mirror);
+    }
+
+    // Models, pipelines, and owning teams are rebuilt from Registry and CostOps in the background
+    let mut topology = TopologyStore::new(adapters.registry.clone(), adapters.costops.clone());
+    if let Some(mirror) = &registry_mirror {
+        topology = topology.with_mirror(mirror.clone());
+    }
+    let topology = Arc::new(topology);
+    topology
+        .clone()
+        .spawn(Duration::from_secs(config.topology_refresh_secs.max(1)));
+
+    // Scheduled reports and alerts are delivered through the Config-Manager alert channels
+    let notifications = Arc::new(NotificationRouter::from_alerting_config(&params.alerting));
+    if let Some(path) = &config.report_schedules {
+        let mut scheduler =
+            ReportScheduler::new(notifications.clone()).with_costops(adapters.costops.clone());
+        if let Some(db) = &database {
+            scheduler = scheduler.with_database(db.clone());
+        }
+        if let Some(engine) = &slo {
+            scheduler = scheduler.with_slo_engine(engine.clone());
+        }
+        let yaml = std::fs::read_to_string(path)?;
+        info!("Loaded {} report schedules", scheduler.load_yaml(&yaml)?);
+        Arc::new(scheduler).spawn();
+    }
+    // Alert rules are edited through the API and stored with their version history
+    let alert_rules = database
+        .as_ref()
+        .map(|db| Arc::new(AlertRuleStore::new(db.clone(), AlertRuleConfig::from_env())));
+
+    // Alert rules and SLOs kept in git are synced from bundles at startup and on reload
+    let rule_bundles = match (RuleBundleConfig::from_env(), &alert_rules) {
+        (Some(bundle_config), Some(store)) => {
+            let mut loader = RuleBundleLoader::new(store.clone(), bundle_config);
+            if let Some(engine) = &slo {
+                loader = loader.with_slo_engine(engine.clone());
+            }
+            Some(Arc::new(loader))
+        }
+        (Some(_), None) => {
+            warn!("Rule bundles need a database; RULE_BUNDLE_* settings are ignored");
+            None
+        }
+        (None, _) => None,
+    };
+
+    // Silences are persisted so they survive restarts and apply to every replica's notifier
+    let silences = Arc::new(SilenceRegistry::new());
+    if let Some(db) = &database {
+        match db.query_active_silences(chrono::Utc::now()).await {
+            Ok(active) => silences.replace(active),
+            Err(e) => warn!("Failed to load alert silences: {}", e),
+        }
+    }
+    let maintenance = Arc::new(MaintenanceRegistry::new());
+    if let Some(db) = &database {
+        match db.query_maintenance_windows(chrono::Utc::now()).await {
+            Ok(windows) => maintenance.replace(windows),
+            Err(e) => warn!("Failed to load maintenance windows: {}", e),
+        }
+    }
+    // Alerts, anomalies, and incident changes are pushed to registered webhooks
+    let mut webhooks =
+        WebhookDispatcher::new(WebhookConfig::from_env())?.with_maintenance(maintenance.clone());
+    if let Some(db) = &database {
+        webhooks = webhooks.with_database(db.clone());
+    }
+    let webhooks = Arc::new(webhooks);
+    if let Err(e) = webhooks.load().await {
+        warn!("Failed to load webhooks: {}", e);
+    }
+    webhooks.clone().spawn();
+    // Anomalies and alerts are grouped into incidents; stored anomalies are picked up by polling
+    let incident_config = IncidentConfig::from_env();
+    let retention = chrono::Duration::hours(incident_config.retention_hours);
+    let incidents = Arc::new(IncidentTracker::new(incident_config).with_topology(topology.clone()));
+    if let Some(db) = &database {
+        let now = chrono::Utc::now();
+        match db.query_incidents(now - retention).await {
+            Ok(stored) => incidents.replace(stored),
+            Err(e) => warn!("Failed to load incidents: {}", e),
+        }
+        Arc::new(
+            IncidentSync::new(db.clone(), incidents.clone(), now).with_webhooks(webhooks.clone()),
+        )
+        .spawn();
+    }
+    // Critical incidents are ticketed in Jira or ServiceNow, with resolution synced both ways
+    let ticketing_config = TicketingConfig::from_env()?;
+    let mut tickets = None;
+    if let Some(client) = ticket_client_from_config(&ticketing_config)? {
+        let mut sync = TicketSync::new(client, incidents.clone(), ticketing_config);
+        if let Some(db) = &database {
+            sync = sync.with_database(db.clone());
+        }
+        let sync = Arc::new(sync);
+        sync.clone().spawn();
+        tickets = Some(sync);
+    }
+    // Region-local hubs ship rollups to the global hub, which stores them tagged by region
+    let federation_config = FederationConfig::from_env()?;
+    let mut federation = None;
+    match (federation_config.role, &database) {
+        (FederationRole::Disabled, _) => {}
+        (_, None) => warn!("Federation needs a database, not shipping or receiving rollups"),
+        (FederationRole::Local, Some(db)) => {
+            Arc::new(FederationShipper::new(federation_config.clone(), db.clone())?).spawn();
+        }
+        (FederationRole::Global, Some(db)) => {
+            let receiver = Arc::new(FederationReceiver::new(db.clone()));
+            if federation_config.transport == FederationTransport::Kafka {
+                receiver.clone().spawn_kafka(&federation_config)?;
+            }
+            federation = Some(receiver);
+        }
+    }
+    // Metric queries reaching past hot retention read archived partitions when archival is enabled
+    let mut tiered = None;
+    if let Some(db) = &database {
+        match adapters.config_manager.fetch_retention_settings().await {
+            Ok(settings) if settings.archival.enabled => {
+                match object_store_for(
+                    &settings.archival.destination,
+                    settings.archival.encryption_enabled,
+                )
+                .await
+                {
+                    Ok(store) => {
+                        let query = TieredQuery::new(db.clone(), store, TieredQueryConfig::from_env());
+                        if let Err(e) = query.refresh_retention().await {
+                            warn!("Serving metric queries from hot storage only: {}", e);
+                        }
+                        tiered = Some(Arc::new(query));
+                    }
+                    Err(e) => warn!("Archive store unavailable, archived data is not queryable: {}", e),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Retention settings unavailable, archived data is not queryable: {}", e),
+        }
+    }
+    // Usage is persisted in the background and the current month reloaded so restarts keep it
+    let usage = UsageMeter::new();
+    if let Some(db) = &database {
+        let flush = Arc::new(UsageFlushJob::new(
+            usage.clone(),
+            db.clone(),
+            UsageFlushConfig::from_env(),
+        ));
+        if let Err(e) = flush.load(chrono::Utc::now()).await {
+            warn!("Failed to load usage records: {}", e);
+        }
+        flush.spawn();
+    }
+    let alerts = match &config.alert_digest_rules {
+        Some(path) => {
+            let mut notifier =
+                DigestNotifier::new(notifications.clone())
+                    .with_silences(silences.clone())
+                    .with_maintenance(maintenance.clone())
+                    .with_usage(usage.clone());
+            if let Some(store) = &alert_rules {
+                notifier = notifier.with_alert_rules(store.clone());
+            }
+            let yaml = std::fs::read_to_string(path)?;
+            info!("Loaded {} alert digest rules", notifier.load_yaml(&yaml)?);
+            let notifier = Arc::new(notifier);
+            notifier.clone().spawn();
+            Some(notifier)
+        }
+        None => None,
+    };
+
+    // Enforce the environment's security settings on the HTTP API
+    let environment = llm_analytics_hub::database::environment::default_environment();
+    let mut auth_config = AuthConfig::from_env();
+    let mut sql_limits = SqlLimits::from_env();
+    match adapters.config_manager.fetch_environment_config(&environment).await {
+        Ok(env_config) => {
+            auth_config = auth_config.with_security(&env_config.security);
+            sql_limits = SqlLimits::from(&env_config.limits);
+        }
+        Err(e) => warn!("Using local security settings, Config-Manager unavailable: {}", e),
+    }
+    let sql = database.as_ref().map(|db| {
+        Arc::new(
+            SqlExecutor::new(db.pool().clone(), sql_limits)
+                .with_default_environment(db.default_environment()),
+        )
+    });
+    // Create application state
+    let mut pipelines = PipelineAnalyzer::new(
+        adapters.registry.clone(),
+        adapters.observatory.clone(),
+        adapters.costops.clone(),
+    );
+    if let Some(mirror) = &registry_mirror {
+        pipelines = pipelines.with_mirror(mirror.clone());
+    }
+    // Upstream retries are dropped by event ID before quotas, sampling, and publishing
+    let dedup = Arc::new(Deduplicator::from_config(&DedupConfig::from_env()?).await?);
+
+    // Tags are normalized, and provider and team derived, before quotas and analytics read them
+    let enricher = Arc::new(
+        TagEnricher::new(TagEnrichmentConfig::from_env()?)
+            .with_registry(adapters.registry.clone())
+            .with_costops(adapters.costops.clone()),
+    );
+    enricher.clone().spawn();
+
+    // The service's lifecycle and adapter health are published alongside every other event
+    let (self_events_tx, mut self_events) = mpsc::channel(256);
+    let self_monitor = Arc::new(SelfMonitor::new(
+        SelfMonitorConfig::from_env(),
+        self_events_tx,
+    ));
+    // A runaway source module is shed or sampled down, reported through the self-monitor
+    let module_quotas = Arc::new(
+        ModuleQuotas::new(ModuleQuotaConfig::from_env()?).with_self_monitor(self_monitor.clone()),
+    );
+    let state = AppState {
+        kafka_producer: Arc::new(kafka_producer),
+        metrics,
+        usage,
+        database,
+        planner,
+        sql,
+        slo,
+        dedup,
+        enricher,
+        heavy_hitters: Arc::new(HeavyHitterTracker::new(HeavyHitterConfig::from_env())),
+        sampler,
+        flags,
+        otlp: OtlpConverter::default(),
+        tenants: Arc::new(TenantQuotas::new(TenantQuotaConfig::from_env()?)),
+        module_quotas,
+        memory_graph: adapters.memory_graph.clone(),
+        pipelines: Arc::new(pipelines),
+        registry_mirror,
+        backtest,
+        scaling,
+        topology,
+        alerts,
+        alert_rules,
+        rule_bundles,
+        silences,
+        maintenance,
+        incidents,
+        tickets,
+        webhooks,
+        federation,
+        tiered,
+        feedback: FeedbackConfig::from_env(),
+        audit: AuditConfig::from_env(),
+        self_monitor,
+    };
+    tokio::spawn({
+        let state = state.clone();
+        async move {
+            while let Some(event) = self_events.recv().await {
+                if let Err(e) = publish_event(&state, event).await {
+                    warn!("Failed to publish self-monitoring event: {}", e);
+                }
+            }
+        }
+    });
+
+    // Denied requests are recorded in the audit log with every other audited action
+    let auth = Arc::new(
+        Authenticator::new(auth_config, ScopedApiKeys::from_env()?)
+            .with_audit(Arc::new(AuditRouter(state.clone()))),
+    );
+    let cors = auth.cors_layer();
+
+    // Consumer lag is sampled in the background; readiness and /health/lag read the latest sample
+    let lag_monitor = Arc::new(
+        LagMonitor::new(
+            KafkaLagCheck::new(
+                &config.kafka_brokers,
+                &config.lag_group_id,
+                &config.kafka_topic,
+                config.max_consumer_lag,
+            )?,
+            LagMonitorConfig::from_env(),
+        )
+        .with_alerts(Arc::new(state.clone())),
+    );
+    lag_monitor.clone().spawn();
+    if let Some(loader) = &state.rule_bundles {
+        loader.clone().spawn(Arc::new(AuditRouter(state.clone())));
+    }
+    let adapters = Arc::new(adapters);
+    state.self_monitor.clone().spawn(adapters.clone());
+    let probe = Arc::new(probe.with_check(lag_monitor.clone()).with_check(adapters));
+
+    // gRPC push ingestion shares the HTTP path's sampling and Kafka routing
+    let grpc_config = GrpcIngestionConfig::from_env();
+    let grpc_addr = format!("0.0.0.0:{}", grpc_config.port).parse()?;
+    let grpc_service = IngestionService::new(
+        grpc_config,
+        Arc::new(state.clone()),
+        Arc::new(ApiKeyStore::from_env()?),
+    );
+    info!("gRPC ingestion listening on {}", grpc_addr);
+    let grpc_server = tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(grpc_service.into_server())
+            .serve_with_shutdown(grpc_addr, shutdown_signal()),
+    );
+
+    // Build router; reads need any authenticated caller, and writes and
+    // configuration changes declare their scope on their own router
+    let lifecycle_state = state.clone();
+    let read = Router::new()
+        .route("/api/v1/usage", get(usage_report))
+        .route("/api/v1/scorecards", get(scorecards))
+        .route("/api/v1/slos", get(slo_status))
+        .route("/api/v1/security/threat-trends", get(threat_trends))
+        .route("/api/v1/analytics/top", get(top_k))
+        .route("/api/v1/analytics/distinct", get(distinct_count))
+        .route("/api/v1/analytics/clusters", get(clusters))
+        .route(
+            "/api/v1/analytics/pipelines/:pipeline_id",
+            get(pipeline_breakdown),
+        )
+        .route("/api/v1/analytics/request-costs", get(request_costs))
+        .route(
+            "/api/v1/analytics/correlations/graph",
+            get(correlation_graph),
+        )
+        .route("/api/v1/topology", get(get_topology))
+        .route("/api/v1/topology/owners", get(topology_owners))
+        .route("/api/v1/topology/:kind/:entity_id", get(topology_entity))
+        .route("/api/v1/registry/mirror", get(registry_mirror_status))
+        .route("/api/v1/registry/models/:model_id", get(mirrored_model))
+        .route("/api/v1/metrics/:metric_name", get(metric_series))
+        .route(
+            "/api/v1/metrics/:metric_name/downsampled",
+            get(downsampled_series),
+        )
+        // The Grafana datasource protocol queries over POST
+        .route("/api/grafana", get(grafana_health))
+        .route("/api/grafana/search", post(grafana_search))
+        .route("/api/grafana/metrics", post(grafana_search))
+        .route("/api/grafana/query", post(grafana_query))
+        .route("/api/grafana/tag-keys", post(grafana_tag_keys))
+        .route("/api/grafana/tag-values", post(grafana_tag_values))
+        .route("/api/v1/events", get(list_events))
+        .route("/api/v1/anomalies", get(list_anomalies))
+        .route("/api/v1/anomalies/feedback", get(anomaly_feedback_summary))
+        .route("/api/v1/anomalies/backtest", get(latest_backtest))
+        .route(
+            "/api/v1/recommendations/scaling",
+            get(scaling_recommendations),
+        )
+        .route("/api/v1/alerts", get(list_alerts))
+        .route("/api/v1/alerts/silences", get(list_silences))
+        .route("/api/v1/alerts/rules", get(list_alert_rules))
+        .route("/api/v1/alerts/rules/:rule_id", get(get_alert_rule))
+        .route(
+            "/api/v1/alerts/rules/:rule_id/history",
+            get(alert_rule_history),
+        )
+        // Ad-hoc SQL runs read-only as a SELECT-only role under row-level security
+        .route("/api/v1/sql", post(run_sql))
+        .route("/api/v1/maintenance-windows", get(list_maintenance_windows))
+        .route("/api/v1/incidents", get(list_incidents))
+        .route("/api/v1/incidents/:incident_id", get(get_incident))
+        .route(
+            "/api/v1/incidents/:incident_id/timeline",
+            get(incident_timeline),
+        )
+        .route(
+            "/api/v1/incidents/:incident_id/postmortem",
+            get(incident_postmortem),
+        )
+        .route("/api/v1/federation/regions", get(federation_regions))
+        .route(
+            "/api/v1/federation/metrics/:metric_name",
+            get(federated_series),
+        )
+        .route(
+            "/api/v1/metric-filters/preview",
+            post(preview_metric_filter),
+        );
+    // Event search is served by the library router with the database as its state
+    let read = match state.database.clone() {
+        Some(database) => read.merge(event_search_router(database)),
+        None => read,
+    };
+    let write = Router::new()
+        .route("/api/v1/events", post(ingest_event))
+        .route("/api/v1/events/batch", post(ingest_batch))
+        .route(
+            "/api/v1/anomalies/:anomaly_id/ack",
+            post(acknowledge_anomaly),
+        )
+        .route("/api/v1/anomalies/feedback", post(record_anomaly_feedback))
+        .route("/api/v1/anomalies/backtest", post(run_backtest))
+        .route("/api/v1/incidents", post(create_incident))
+        .route("/api/v1/incidents/:incident_id", patch(update_incident))
+        .route(ROLLUPS_PATH, post(receive_rollups))
+        // OTLP/HTTP with JSON encoding; protobuf bodies are rejected with 415
+        .route("/v1/traces", post(otlp_traces))
+        .route("/v1/metrics", post(otlp_metrics))
+        .route_layer(middleware::from_fn_with_state(
+            auth.guard(Scope::Write),
+            require_scope,
+        ));
+    let admin = Router::new()
+        // The audit log records who did what, so only operators may read it
+        .route("/api/v1/audit", get(audit_log))
+        // Reading across environments is an explicit operator opt-in
+        .route(
+            "/api/v1/sql/cross-environment",
+            post(run_cross_environment_sql),
+        )
+        .route("/api/v1/alerts/silences", post(create_silence))
+        .route(
+            "/api/v1/alerts/silences/:silence_id",
+            delete(expire_silence),
+        )
+        .route("/api/v1/slos", post(define_slo))
+        .route("/api/v1/alerts/rules", post(create_alert_rule))
+        .route(
+            "/api/v1/alerts/rules/:rule_id",
+            put(update_alert_rule).delete(delete_alert_rule),
+        )
+        .route("/api/v1/rules/reload", post(reload_rule_bundles))
+        .route(
+            "/api/v1/maintenance-windows",
+            post(create_maintenance_window),
+        )
+        .route(
+            "/api/v1/maintenance-windows/:window_id",
+            delete(end_maintenance_window),
+        )
+        .route("/api/v1/incidents/:incident_id", delete(delete_incident))
+        .route(
+            "/api/v1/webhooks",
+            get(list_webhooks).post(register_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:webhook_id",
+            get(get_webhook).delete(delete_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:webhook_id/deliveries",
+            get(webhook_deliveries),
+        )
+        .route(
+            "/api/v1/webhooks/:webhook_id/deliveries/:delivery_id/replay",
+            post(replay_webhook_delivery),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            auth.guard(Scope::Admin),
+            require_scope,
+        ));
+    let app = read
+        .merge(write)
+        .merge(admin)
+        .layer(middleware::from_fn_with_state(state.clone(), meter_usage))
+        .layer(middleware::from_fn_with_state(state.clone(), scope_tenant))
+        .layer(middleware::from_fn(scope_environment))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            audit_requests,
+        ))
+        .layer(middleware::from_fn_with_state(auth, require_auth))
+        .merge(metrics_router())
+        .merge(health_router(probe.clone()))
+        .merge(lag_router(lag_monitor))
+        .merge(
+            // Legacy probe paths
+            Router::new()
+                .route("/health", get(live_handler))
+                .route("/ready", get(ready_handler))
+                .with_state(probe),
+        )
+        .layer(cors)
+        .layer(TraceLayer::new_for_http())
+        .with_state(state);
+
+    // Start server
+    let addr = format!("0.0.0.0:{}", config.http_port);
+    info!("Listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    publish_lifecycle(&lifecycle_state, LifecyclePhase::Startup).await;
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    if let Err(e) = grpc_server.await? {
+        error!("gRPC server error: {}", e);
+    }
+
+    publish_lifecycle(&lifecycle_state, LifecyclePhase::Shutdown).await;
+    info!("Service shutdown complete");
+    Ok(())
+}
+
+/// Ingest single event
+#[tracing::instrument(
+    name = "ingestion.event",
+    skip(state, event),
+    fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+)]
+/// Graceful shutdown signal handler
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received, starting graceful shutdown");
+}
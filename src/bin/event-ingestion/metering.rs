@@ -0,0 +1,99 @@
+//! Usage metering and usage reports
+
+use axum::extract::{Extension, Json, Query, Request, State};
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::Response;
+use llm_analytics_hub::auth::{AuthMethod, Principal};
+use llm_analytics_hub::metering::{Consumer, UsagePeriod};
+use llm_analytics_hub::reporting::UsageReport;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Consumer a request is metered against: its tenant and, unless
+/// authentication is disabled, the authenticated caller
+fn usage_consumer(tenant: &TenantScope, principal: Option<&Principal>) -> Option<Consumer> {
+    Some(Consumer {
+        tenant_id: tenant.tenant_id()?.to_string(),
+        api_key_id: principal
+            .filter(|p| p.method != AuthMethod::Anonymous)
+            .map(|p| p.subject.clone()),
+    })
+}
+
+/// Record query compute time for a request that identifies a tenant
+pub(crate) fn meter_query(
+    state: &AppState,
+    tenant: &TenantScope,
+    principal: Option<&Principal>,
+    started: std::time::Instant,
+) {
+    if let Some(consumer) = usage_consumer(tenant, principal) {
+        state
+            .usage
+            .record_query(&consumer, started.elapsed().as_millis() as u64);
+    }
+}
+
+/// Record API usage, and compute time of reads, for requests that identify a tenant
+pub(crate) async fn meter_usage(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let consumer = request
+        .extensions()
+        .get::<TenantScope>()
+        .and_then(|tenant| usage_consumer(tenant, request.extensions().get::<Principal>()));
+    let Some(consumer) = consumer else {
+        return next.run(request).await;
+    };
+    state.usage.record_api_call(&consumer, request.uri().path());
+
+    // Reads over GET are queries, as for query slots in scope_tenant
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let started = std::time::Instant::now();
+    let response = next.run(request).await;
+    state
+        .usage
+        .record_query(&consumer, started.elapsed().as_millis() as u64);
+    response
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UsageReportParams {
+    period: Option<String>,
+}
+
+/// Monthly usage report endpoint
+pub(crate) async fn usage_report(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<UsageReportParams>,
+) -> Result<Json<ApiResponse<UsageReport>>, AppError> {
+    let period = match params.period {
+        Some(p) => p
+            .parse::<UsagePeriod>()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?,
+        None => UsagePeriod::current(),
+    };
+
+    // Closed periods are drained from the meter once they are stored
+    let mut records = match &state.database {
+        Some(database) if period < UsagePeriod::current() => database
+            .query_usage_records(period)
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?,
+        _ => state.usage.records_for_period(period),
+    };
+    records.retain(|record| tenant.allows(Some(&record.consumer.tenant_id)));
+    Ok(Json(ApiResponse::success(UsageReport::from_records(
+        period, records,
+    ))))
+}
@@ -0,0 +1,219 @@
+//! Aggregated metric series, downsampling and metric filter previews
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use llm_analytics_hub::adapters::config_manager::MetricFilterConfig;
+use llm_analytics_hub::analytics::downsampling::{
+    downsample_rows, DownsampleRequest, DownsampledSeries, SeriesStatistic, DEFAULT_POINT_BUDGET,
+    MAX_POINT_BUDGET,
+};
+use llm_analytics_hub::archival::Sourced;
+use llm_analytics_hub::database::timescale::parse_window;
+use llm_analytics_hub::database::AggregatedMetricRow;
+use llm_analytics_hub::models::api::QueryStatus;
+use llm_analytics_hub::models::metrics::TimeWindow;
+use llm_analytics_hub::pipeline::metric_filter::preview as preview_metric_rules;
+use llm_analytics_hub::pipeline::MetricFilterPreview;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::{ApiResponse, QueryResult};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MetricSeriesParams {
+    /// Aggregation window, e.g. `5m` or `1h`
+    window: Option<String>,
+    /// Lookback in hours, ignored when `start` is given
+    hours: Option<i64>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Aggregated time series for one metric, e.g. `session.topic_diversity`
+pub(crate) async fn metric_series(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(metric_name): Path<String>,
+    Query(params): Query<MetricSeriesParams>,
+) -> Result<Json<ApiResponse<QueryResult<Vec<Sourced<AggregatedMetricRow>>>>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let window = match params.window.as_deref() {
+        Some(w) => parse_window(w).map_err(|e| AppError::ValidationError(e.to_string()))?,
+        None => TimeWindow::OneHour,
+    };
+    let end = params.end.unwrap_or_else(chrono::Utc::now);
+    let start = params.start.unwrap_or_else(|| {
+        end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90))
+    });
+    if start >= end {
+        return Err(AppError::ValidationError(
+            "start must be before end".to_string(),
+        ));
+    }
+
+    let plan = state.planner.plan("aggregated_metrics", window, start, end);
+    if plan.downgraded() {
+        debug!(
+            metric = %metric_name,
+            requested = plan.requested_window.as_str(),
+            window = plan.window.as_str(),
+            "Downgraded metric query to a coarser rollup"
+        );
+    }
+
+    let started = std::time::Instant::now();
+    let tags = tenant.aggregate_tags();
+    // Each row is marked with the tier it came from; archive gaps make the result partial
+    if let Some(tiered) = &state.tiered {
+        let tiered_result = tiered
+            .aggregates(
+                &metric_name,
+                plan.window,
+                start,
+                end,
+                tags.as_ref(),
+                chrono::Utc::now(),
+            )
+            .await
+            .map_err(|e| AppError::InternalError(e.to_string()))?;
+        let warnings = tiered_result.warnings();
+        let complete = tiered_result.complete();
+        let (from_cache, cache_ttl) = (tiered_result.from_cache, tiered_result.cache_ttl);
+
+        let returned = tiered_result.rows.len() as u64;
+        let mut result = plan.into_result(tiered_result.rows, returned, started.elapsed());
+        result.warnings.extend(warnings);
+        if !complete {
+            result.status = QueryStatus::PartialSuccess;
+        }
+        result.metrics.from_cache = from_cache;
+        result.metrics.cache_ttl = cache_ttl;
+        return Ok(Json(ApiResponse::success(result)));
+    }
+
+    let cached = database
+        .query_aggregates_cached(&metric_name, plan.window, start, end, tags.as_ref())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let returned = cached.rows.len() as u64;
+    let rows = cached.rows.into_iter().map(Sourced::hot).collect();
+    let mut result = plan.into_result(rows, returned, started.elapsed());
+    result.metrics.from_cache = cached.from_cache;
+    result.metrics.cache_ttl = cached.cache_ttl;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DownsampledSeriesParams {
+    /// Maximum points to return
+    points: Option<usize>,
+    /// Statistic to plot, e.g. `avg` or `p95`
+    stat: Option<String>,
+    /// Lookback in hours, ignored when `start` is given
+    hours: Option<i64>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One metric downsampled to a point budget, reading the rollup window that
+/// suits the requested range
+pub(crate) async fn downsampled_series(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(metric_name): Path<String>,
+    Query(params): Query<DownsampledSeriesParams>,
+) -> Result<Json<ApiResponse<QueryResult<DownsampledSeries>>>, AppError> {
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let statistic = match params.stat.as_deref() {
+        Some(stat) => stat
+            .parse::<SeriesStatistic>()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?,
+        None => SeriesStatistic::default(),
+    };
+    let end = params.end.unwrap_or_else(chrono::Utc::now);
+    let start = params.start.unwrap_or_else(|| {
+        end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90))
+    });
+    if start >= end {
+        return Err(AppError::ValidationError(
+            "start must be before end".to_string(),
+        ));
+    }
+
+    let request = DownsampleRequest {
+        metric_name,
+        start,
+        end,
+        max_points: params
+            .points
+            .unwrap_or(DEFAULT_POINT_BUDGET)
+            .clamp(3, MAX_POINT_BUDGET),
+        statistic,
+        tags: tenant.aggregate_tags(),
+    };
+    let window = request.resolution();
+    let plan = state.planner.plan("aggregated_metrics", window, start, end);
+
+    let started = std::time::Instant::now();
+    let cached = database
+        .query_aggregates_cached(
+            &request.metric_name,
+            plan.window,
+            start,
+            end,
+            request.tags.as_ref(),
+        )
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let series = downsample_rows(&request, plan.window, &cached.rows);
+    let returned = series.points.len() as u64;
+    let mut result = plan.into_result(series, returned, started.elapsed());
+    result.metrics.from_cache = cached.from_cache;
+    result.metrics.cache_ttl = cached.cache_ttl;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Stored series checked by one metric filter preview
+const METRIC_FILTER_PREVIEW_SERIES: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MetricFilterPreviewParams {
+    /// How far back to look for stored series, in hours
+    hours: Option<i64>,
+}
+
+/// Stored series that a candidate allowlist/denylist would drop, without applying it
+pub(crate) async fn preview_metric_filter(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Query(params): Query<MetricFilterPreviewParams>,
+    Json(rules): Json<MetricFilterConfig>,
+) -> Result<Json<ApiResponse<MetricFilterPreview>>, AppError> {
+    tenant.require_all_tenants()?;
+    let database = state
+        .database
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    let since =
+        chrono::Utc::now() - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 30));
+    let series = database
+        .query_metric_series(since, METRIC_FILTER_PREVIEW_SERIES)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(preview_metric_rules(
+        &rules, &series,
+    ))))
+}
@@ -0,0 +1,72 @@
+//! OTLP/HTTP receivers for OpenTelemetry traces and metrics
+
+use axum::extract::{Extension, Json, State};
+use llm_analytics_hub::otlp::{
+    ExportMetricsServiceRequest, ExportResponse, ExportTraceServiceRequest, PartialSuccess,
+};
+use llm_analytics_hub::schemas::events::validate_event;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::AnalyticsEvent;
+use tracing::{debug, warn};
+
+use crate::AppState;
+
+/// Receive OTLP spans as latency and token usage telemetry
+pub(crate) async fn otlp_traces(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(request): Json<ExportTraceServiceRequest>,
+) -> Json<ExportResponse> {
+    let conversion = state.otlp.convert_traces(&request);
+    let rejected = route_otlp(&state, &tenant, conversion.events, conversion.skipped).await;
+    Json(ExportResponse {
+        partial_success: (rejected > 0).then(|| PartialSuccess {
+            rejected_spans: Some(rejected),
+            rejected_data_points: None,
+            error_message: "failed to publish converted events".to_string(),
+        }),
+    })
+}
+
+/// Receive OTLP metrics as latency, throughput, and token usage telemetry
+pub(crate) async fn otlp_metrics(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Json(request): Json<ExportMetricsServiceRequest>,
+) -> Json<ExportResponse> {
+    let conversion = state.otlp.convert_metrics(&request);
+    let rejected = route_otlp(&state, &tenant, conversion.events, conversion.skipped).await;
+    Json(ExportResponse {
+        partial_success: (rejected > 0).then(|| PartialSuccess {
+            rejected_spans: None,
+            rejected_data_points: Some(rejected),
+            error_message: "failed to publish converted events".to_string(),
+        }),
+    })
+}
+
+/// Route converted OTLP events, returning how many failed to publish
+async fn route_otlp(
+    state: &AppState,
+    tenant: &TenantScope,
+    events: Vec<AnalyticsEvent>,
+    skipped: u64,
+) -> u64 {
+    let mut rejected = 0;
+    for mut event in events {
+        tenant.stamp(&mut event);
+        if let Err(e) = validate_event(&event) {
+            warn!("Invalid OTLP event: {}", e);
+            rejected += 1;
+            continue;
+        }
+        if let Err(e) = state.route(event).await {
+            warn!("Failed to publish OTLP event: {}", e);
+            rejected += 1;
+        }
+    }
+    if skipped > 0 {
+        debug!(skipped, "Skipped OTLP items without a telemetry mapping");
+    }
+    rejected
+}
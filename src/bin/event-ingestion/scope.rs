@@ -0,0 +1,45 @@
+//! Request scoping to a tenant and an environment
+
+use axum::extract::{Request, State};
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::Response;
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::environment::ENVIRONMENT_HEADER;
+use llm_analytics_hub::database::EnvironmentScope;
+use llm_analytics_hub::metering::TENANT_HEADER;
+use llm_analytics_hub::tenancy::TenantScope;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Resolve the tenant scope of a request and hold a query slot for reads
+pub(crate) async fn scope_tenant(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let requested = request
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let tenant = TenantScope::resolve(request.extensions().get::<Principal>(), requested)?;
+
+    let _permit = match (request.method() == Method::GET, tenant.tenant_id()) {
+        (true, Some(tenant_id)) => state.tenants.acquire_query(tenant_id).await?,
+        _ => None,
+    };
+    request.extensions_mut().insert(tenant);
+    Ok(next.run(request).await)
+}
+
+/// Scope each request to the environment it names, the default one otherwise
+pub(crate) async fn scope_environment(mut request: Request, next: Next) -> Response {
+    let requested = request
+        .headers()
+        .get(ENVIRONMENT_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let environment = EnvironmentScope::requested(requested);
+    request.extensions_mut().insert(environment);
+    next.run(request).await
+}
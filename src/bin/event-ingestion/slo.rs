@@ -0,0 +1,40 @@
+//! Service level objectives
+
+use axum::extract::{Extension, Json, State};
+use axum::http::StatusCode;
+use llm_analytics_hub::slo::{SloEngine, SloObjective, SloStatus};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::AppState;
+
+fn slo_engine(state: &AppState) -> Result<&Arc<SloEngine>, AppError> {
+    state
+        .slo
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))
+}
+
+/// Compliance, error budget, and burn rates for every SLO
+pub(crate) async fn slo_status(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<SloStatus>>>, AppError> {
+    tenant.require_all_tenants()?;
+    let engine = slo_engine(&state)?;
+    Ok(Json(ApiResponse::success(engine.evaluate_all().await)))
+}
+
+/// Define or replace an SLO
+pub(crate) async fn define_slo(
+    State(state): State<AppState>,
+    Json(objective): Json<SloObjective>,
+) -> Result<(StatusCode, Json<ApiResponse<SloObjective>>), AppError> {
+    let engine = slo_engine(&state)?;
+    engine
+        .define(objective.clone())
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(objective))))
+}
@@ -0,0 +1,94 @@
+//! Ad-hoc SQL over the analytics tables
+
+use axum::extract::{Extension, Json, State};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::database::{EnvironmentScope, SqlError, SqlQueryResult};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::metering::meter_query;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SqlRequest {
+    query: String,
+}
+
+/// Ad-hoc read-only SQL over the analytics tables, restricted to the caller's
+/// tenant and environment
+pub(crate) async fn run_sql(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<SqlRequest>,
+) -> Result<Json<ApiResponse<SqlQueryResult>>, AppError> {
+    execute_sql(
+        &state,
+        &tenant,
+        &environment,
+        principal.as_deref(),
+        &request.query,
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrossEnvironmentSqlRequest {
+    query: String,
+    /// Environments to read; omit to read every environment
+    environments: Option<Vec<String>>,
+}
+
+/// Ad-hoc SQL across environments, which only operators may opt into
+pub(crate) async fn run_cross_environment_sql(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<CrossEnvironmentSqlRequest>,
+) -> Result<Json<ApiResponse<SqlQueryResult>>, AppError> {
+    let principal = principal
+        .map(|Extension(p)| p)
+        .ok_or_else(|| AppError::Forbidden("Caller is not authenticated".to_string()))?;
+    let environment = EnvironmentScope::cross_environment(&principal, request.environments)
+        .map_err(|e| AppError::Forbidden(e.to_string()))?;
+    execute_sql(
+        &state,
+        &tenant,
+        &environment,
+        Some(&principal),
+        &request.query,
+    )
+    .await
+}
+
+async fn execute_sql(
+    state: &AppState,
+    tenant: &TenantScope,
+    environment: &EnvironmentScope,
+    principal: Option<&Principal>,
+    query: &str,
+) -> Result<Json<ApiResponse<SqlQueryResult>>, AppError> {
+    let sql = state
+        .sql
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Database not configured".to_string()))?;
+
+    // Reads over POST don't get a query slot from scope_tenant
+    let _permit = match tenant.tenant_id() {
+        Some(tenant_id) => state.tenants.acquire_query(tenant_id).await?,
+        None => None,
+    };
+    let started = std::time::Instant::now();
+    let result = sql.execute(query, tenant.tenant_id(), environment).await;
+    meter_query(state, tenant, principal, started);
+    let result = result.map_err(|e| match e {
+        SqlError::Rejected(_) => AppError::ValidationError(e.to_string()),
+        SqlError::Busy => AppError::QuotaExceeded(e.to_string()),
+        SqlError::Timeout { .. } => AppError::Unavailable(e.to_string()),
+        SqlError::Failed(_) => AppError::ValidationError(e.to_string()),
+    })?;
+    Ok(Json(ApiResponse::success(result)))
+}
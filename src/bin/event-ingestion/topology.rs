@@ -0,0 +1,83 @@
+//! Service topology and the model registry mirror
+
+use axum::extract::{Extension, Json, Path, State};
+use llm_analytics_hub::adapters::registry::ModelMetadata;
+use llm_analytics_hub::adapters::MirrorKindStatus;
+use llm_analytics_hub::analytics::topology::{
+    EntityContext, EntityKind as TopologyEntityKind, EntityRef, OwnerRollup, Topology,
+};
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::ApiResponse;
+
+use crate::error::AppError;
+use crate::AppState;
+
+/// Entities and relations built from Registry and CostOps
+pub(crate) async fn get_topology(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Topology>>, AppError> {
+    // Registry and CostOps data are not partitioned by tenant
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(
+        state.topology.current().as_ref().clone(),
+    )))
+}
+
+/// Entities each team owns or uses through its pipelines
+pub(crate) async fn topology_owners(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<OwnerRollup>>>, AppError> {
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(
+        state.topology.current().owner_rollups(),
+    )))
+}
+
+/// Dependencies, blast radius, and owners of one entity
+pub(crate) async fn topology_entity(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path((kind, entity_id)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<EntityContext>>, AppError> {
+    tenant.require_all_tenants()?;
+    let kind: TopologyEntityKind = kind
+        .parse()
+        .map_err(|e: anyhow::Error| AppError::ValidationError(e.to_string()))?;
+    let entity = EntityRef::new(kind, entity_id);
+    let context = state
+        .topology
+        .current()
+        .context(&entity)
+        .ok_or_else(|| AppError::ValidationError(format!("Unknown entity {}", entity)))?;
+    Ok(Json(ApiResponse::success(context)))
+}
+
+/// Freshness of each mirrored registry record kind
+pub(crate) async fn registry_mirror_status(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<MirrorKindStatus>>>, AppError> {
+    let mirror = state
+        .registry_mirror
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Registry mirror not configured".to_string()))?;
+    Ok(Json(ApiResponse::success(
+        mirror.status(chrono::Utc::now()),
+    )))
+}
+
+/// Registry metadata of one model, served from the local mirror
+pub(crate) async fn mirrored_model(
+    State(state): State<AppState>,
+    Path(model_id): Path<String>,
+) -> Result<Json<ApiResponse<ModelMetadata>>, AppError> {
+    let mirror = state
+        .registry_mirror
+        .as_ref()
+        .ok_or_else(|| AppError::Unavailable("Registry mirror not configured".to_string()))?;
+    let model = mirror
+        .model(&model_id)
+        .ok_or_else(|| AppError::NotFound(format!("Model {} not found", model_id)))?;
+    Ok(Json(ApiResponse::success(model)))
+}
@@ -0,0 +1,140 @@
+//! Webhook subscriptions and deliveries
+
+use axum::extract::{Extension, Json, Path, Query, State};
+use axum::http::StatusCode;
+use llm_analytics_hub::alerting::{WebhookDelivery, WebhookEventType, WebhookSubscription};
+use llm_analytics_hub::auth::Principal;
+use llm_analytics_hub::tenancy::TenantScope;
+use llm_analytics_hub::{ApiResponse, Severity};
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RegisterWebhookRequest {
+    url: String,
+    /// Shared secret requests are signed with
+    secret: String,
+    #[serde(default)]
+    event_types: Vec<WebhookEventType>,
+    min_severity: Option<Severity>,
+    environment: Option<String>,
+    description: Option<String>,
+}
+
+/// Registered webhooks; secrets are never returned
+pub(crate) async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+) -> Result<Json<ApiResponse<Vec<WebhookSubscription>>>, AppError> {
+    tenant.require_all_tenants()?;
+    Ok(Json(ApiResponse::success(state.webhooks.list())))
+}
+
+/// Register an endpoint to receive signed findings
+pub(crate) async fn register_webhook(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<RegisterWebhookRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<WebhookSubscription>>), AppError> {
+    tenant.require_all_tenants()?;
+    let mut webhook =
+        WebhookSubscription::new(request.url, request.secret).with_event_types(request.event_types);
+    webhook.min_severity = request.min_severity;
+    webhook.environment = request.environment;
+    webhook.description = request.description;
+    if let Some(Extension(principal)) = principal {
+        webhook = webhook.with_created_by(principal.subject);
+    }
+    webhook
+        .validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+
+    state
+        .webhooks
+        .register(webhook.clone())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(webhook))))
+}
+
+pub(crate) async fn get_webhook(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(webhook_id): Path<uuid::Uuid>,
+) -> Result<Json<ApiResponse<WebhookSubscription>>, AppError> {
+    tenant.require_all_tenants()?;
+    state
+        .webhooks
+        .get(webhook_id)
+        .map(|webhook| Json(ApiResponse::success(webhook)))
+        .ok_or_else(|| AppError::NotFound(format!("No webhook {}", webhook_id)))
+}
+
+/// Remove a webhook along with its delivery log
+pub(crate) async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(webhook_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    tenant.require_all_tenants()?;
+    let deleted = state
+        .webhooks
+        .unregister(webhook_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("No webhook {}", webhook_id)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WebhookDeliveryParams {
+    limit: Option<usize>,
+}
+
+/// Delivery log of a webhook, newest first
+pub(crate) async fn webhook_deliveries(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path(webhook_id): Path<uuid::Uuid>,
+    Query(params): Query<WebhookDeliveryParams>,
+) -> Result<Json<ApiResponse<Vec<WebhookDelivery>>>, AppError> {
+    tenant.require_all_tenants()?;
+    if state.webhooks.get(webhook_id).is_none() {
+        return Err(AppError::NotFound(format!("No webhook {}", webhook_id)));
+    }
+    let deliveries = state
+        .webhooks
+        .deliveries(webhook_id, params.limit.unwrap_or(100).min(1000))
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(deliveries)))
+}
+
+/// Send the event of an earlier delivery again
+pub(crate) async fn replay_webhook_delivery(
+    State(state): State<AppState>,
+    Extension(tenant): Extension<TenantScope>,
+    Path((webhook_id, delivery_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<(StatusCode, Json<ApiResponse<WebhookDelivery>>), AppError> {
+    tenant.require_all_tenants()?;
+    let replay = state
+        .webhooks
+        .replay(webhook_id, delivery_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No delivery {} for webhook {}",
+                delivery_id, webhook_id
+            ))
+        })?;
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::success(replay))))
+}
@@ -5,20 +5,34 @@
 //!
 //! Features:
 //! - Create and manage 14 LLM Analytics topics
-//! - Configure ACLs for security
+//! - Configure ACLs for security from a declarative spec, with drift detection
 //! - Verify cluster health
+//! - Reconcile live topics with the declared topic configuration
 //! - Performance testing
 
 use anyhow::{anyhow, Context, Result};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+use indicatif::{ProgressBar, ProgressStyle};
+use llm_analytics_hub::infra::kafka::acls::{
+    acl_add_args, operation_name, parse_acl_listing, AclDrift, AclSpec,
+};
+use llm_analytics_hub::infra::kafka::AclConfig;
+use rdkafka::admin::{
+    AdminClient, AdminOptions, AlterConfig, NewPartitions, NewTopic, ResourceSpecifier,
+    TopicReplication,
+};
 use rdkafka::client::DefaultClientContext;
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::consumer::{BaseConsumer, Consumer, StreamConsumer};
+use rdkafka::message::Message;
 use rdkafka::metadata::Metadata;
-use std::collections::HashMap;
-use std::time::Duration;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
 use tracing::{info, warn};
 
 #[derive(Parser)]
@@ -63,13 +77,42 @@ enum Commands {
         /// Force delete without confirmation
         #[arg(short, long)]
         force: bool,
+
+        /// Write the topics' configs to this JSON file before deleting
+        #[arg(short, long)]
+        backup: Option<String>,
+
+        /// Allow deleting topics outside the llm-* namespace
+        #[arg(long)]
+        allow_non_llm: bool,
     },
 
     /// Verify cluster health
     Verify,
 
+    /// Converge live topics on the declared configuration (never decreases partitions)
+    Reconcile,
+
+    /// Manage produce/consume ACLs for the ecosystem service principals
+    Acl {
+        /// kafka-acls.sh executable
+        #[arg(long, env = "KAFKA_ACLS_BIN", default_value = "kafka-acls.sh")]
+        acls_bin: String,
+
+        /// Client properties for authenticating to the cluster
+        #[arg(long, env = "KAFKA_COMMAND_CONFIG")]
+        command_config: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: AclCommand,
+    },
+
     /// Performance test
     PerfTest {
+        /// Topic to produce to and consume from (created if missing)
+        #[arg(short, long, default_value = "kafka-admin-perf-test")]
+        topic: String,
+
         /// Number of messages to produce
         #[arg(short, long, default_value = "100000")]
         messages: usize,
@@ -77,6 +120,54 @@ enum Commands {
         /// Message size in bytes
         #[arg(short, long, default_value = "1024")]
         size: usize,
+
+        /// Number of concurrent producer tasks
+        #[arg(short, long, default_value = "4")]
+        concurrency: usize,
+
+        /// Producer acknowledgements (0, 1, all)
+        #[arg(long, default_value = "all")]
+        acks: String,
+
+        /// Compression codec (none, gzip, snappy, lz4, zstd)
+        #[arg(long, default_value = "lz4")]
+        compression: String,
+
+        /// Partitions for the perf test topic when it is created
+        #[arg(long, default_value = "6")]
+        partitions: i32,
+
+        /// Replication factor for the perf test topic when it is created
+        #[arg(long, default_value = "1")]
+        replication_factor: i32,
+
+        /// Only measure production
+        #[arg(long)]
+        skip_consume: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AclCommand {
+    /// Add the ACLs from the spec that are missing on the cluster
+    Set {
+        /// ACL spec file (YAML); defaults to the standard LLM Analytics ACLs
+        #[arg(short, long)]
+        spec: Option<PathBuf>,
+    },
+
+    /// List ACLs on the cluster
+    List {
+        /// Only show ACLs for this principal
+        #[arg(short, long)]
+        principal: Option<String>,
+    },
+
+    /// Report drift between the spec and the cluster; fails if any is found
+    Verify {
+        /// ACL spec file (YAML); defaults to the standard LLM Analytics ACLs
+        #[arg(short, long)]
+        spec: Option<PathBuf>,
     },
 }
 
@@ -528,6 +619,910 @@ async fn verify_cluster(bootstrap_servers: &str) -> Result<()> {
     Ok(())
 }
 
+// ========== Reconciliation ==========
+
+/// Live state of a topic as seen by reconciliation
+#[derive(Debug, Default)]
+struct LiveTopic {
+    partitions: usize,
+    replication_factor: usize,
+    /// Effective value of every config entry
+    configs: HashMap<String, String>,
+    /// Entries explicitly set on the topic
+    overrides: HashMap<String, String>,
+}
+
+/// Changes needed to converge one topic on its declared configuration
+#[derive(Debug, Default)]
+struct TopicDrift {
+    name: &'static str,
+    missing: bool,
+    /// (live, desired) when the topic needs more partitions
+    add_partitions: Option<(usize, usize)>,
+    /// (live, desired) when the topic has more partitions than declared
+    excess_partitions: Option<(usize, usize)>,
+    /// (live, desired) replication factors; changing them needs a reassignment
+    replication_mismatch: Option<(usize, usize)>,
+    /// (key, live, desired)
+    config_changes: Vec<(&'static str, Option<String>, &'static str)>,
+}
+
+impl TopicDrift {
+    fn is_clean(&self) -> bool {
+        !self.missing
+            && self.add_partitions.is_none()
+            && self.excess_partitions.is_none()
+            && self.replication_mismatch.is_none()
+            && self.config_changes.is_empty()
+    }
+}
+
+fn diff_topic(config: &TopicConfig, live: Option<&LiveTopic>) -> TopicDrift {
+    let mut drift = TopicDrift {
+        name: config.name,
+        ..Default::default()
+    };
+    let Some(live) = live else {
+        drift.missing = true;
+        return drift;
+    };
+
+    let desired_partitions = config.partitions.max(0) as usize;
+    if live.partitions < desired_partitions {
+        drift.add_partitions = Some((live.partitions, desired_partitions));
+    } else if live.partitions > desired_partitions {
+        // Partitions are never removed; keyed data would be re-hashed
+        drift.excess_partitions = Some((live.partitions, desired_partitions));
+    }
+
+    let desired_replication = config.replication_factor.max(0) as usize;
+    if live.replication_factor != desired_replication {
+        drift.replication_mismatch = Some((live.replication_factor, desired_replication));
+    }
+
+    for (key, value) in &config.config {
+        let current = live.configs.get(*key);
+        if current.map(String::as_str) != Some(*value) {
+            drift.config_changes.push((*key, current.cloned(), *value));
+        }
+    }
+
+    drift
+}
+
+fn print_topic_drift(drift: &TopicDrift) {
+    if drift.is_clean() {
+        println!("  {} {}", "✓".green(), drift.name);
+        return;
+    }
+
+    println!("  {} {}", "~".yellow(), drift.name.yellow().bold());
+    if drift.missing {
+        println!("      {} topic does not exist, will be created", "+".green());
+    }
+    if let Some((live, desired)) = drift.add_partitions {
+        println!("      {} partitions: {} -> {}", "~".yellow(), live, desired);
+    }
+    if let Some((live, desired)) = drift.excess_partitions {
+        println!(
+            "      {} partitions: {} live > {} declared (never decreased)",
+            "!".yellow(),
+            live,
+            desired
+        );
+    }
+    if let Some((live, desired)) = drift.replication_mismatch {
+        println!(
+            "      {} replication factor: {} live, {} declared (use kafka-reassign-partitions)",
+            "!".yellow(),
+            live,
+            desired
+        );
+    }
+    for (key, live, desired) in &drift.config_changes {
+        println!(
+            "      {} {}: {} -> {}",
+            "~".yellow(),
+            key,
+            live.as_deref().unwrap_or("<unset>"),
+            desired
+        );
+    }
+}
+
+async fn fetch_live_topics(
+    bootstrap_servers: &str,
+    admin_client: &AdminClient<DefaultClientContext>,
+    configs: &[TopicConfig],
+) -> Result<HashMap<String, LiveTopic>> {
+    let consumer = create_consumer(bootstrap_servers)?;
+    let metadata = consumer
+        .fetch_metadata(None, Duration::from_secs(10))
+        .context("Failed to fetch metadata")?;
+
+    let mut live: HashMap<String, LiveTopic> = metadata
+        .topics()
+        .iter()
+        .filter(|t| configs.iter().any(|c| c.name == t.name()))
+        .map(|t| {
+            let topic = LiveTopic {
+                partitions: t.partitions().len(),
+                replication_factor: t.partitions().first().map(|p| p.replicas().len()).unwrap_or(0),
+                ..Default::default()
+            };
+            (t.name().to_string(), topic)
+        })
+        .collect();
+
+    let names: Vec<String> = live.keys().cloned().collect();
+    if names.is_empty() {
+        return Ok(live);
+    }
+    let resources: Vec<ResourceSpecifier> = names
+        .iter()
+        .map(|name| ResourceSpecifier::Topic(name.as_str()))
+        .collect();
+    let described = admin_client
+        .describe_configs(&resources, &AdminOptions::default())
+        .await
+        .context("Failed to describe topic configs")?;
+
+    for (name, result) in names.iter().zip(described) {
+        let resource = result.map_err(|e| anyhow!("Failed to describe {}: {}", name, e))?;
+        let topic = live.get_mut(name).expect("described topic is live");
+        for entry in resource.entries {
+            if let Some(value) = entry.value {
+                if !entry.is_default && !entry.is_sensitive {
+                    topic.overrides.insert(entry.name.clone(), value.clone());
+                }
+                topic.configs.insert(entry.name, value);
+            }
+        }
+    }
+
+    Ok(live)
+}
+
+async fn reconcile(bootstrap_servers: &str, dry_run: bool) -> Result<()> {
+    log_info("===========================================");
+    log_info("      Kafka Topic Reconciliation");
+    log_info("===========================================");
+    println!();
+
+    let admin_client = create_admin_client(bootstrap_servers)?;
+    let configs = get_topic_configs();
+    let live = fetch_live_topics(bootstrap_servers, &admin_client, &configs).await?;
+
+    let drifts: Vec<TopicDrift> = configs
+        .iter()
+        .map(|config| diff_topic(config, live.get(config.name)))
+        .collect();
+
+    log_info("Diff against declared topic configuration:");
+    for drift in &drifts {
+        print_topic_drift(drift);
+    }
+    println!();
+
+    let pending: Vec<(&TopicConfig, &TopicDrift)> = configs
+        .iter()
+        .zip(&drifts)
+        .filter(|(_, d)| d.missing || d.add_partitions.is_some() || !d.config_changes.is_empty())
+        .collect();
+
+    if pending.is_empty() {
+        log_success("Cluster matches the declared topic configuration");
+        return Ok(());
+    }
+
+    if dry_run {
+        log_warn(&format!("[DRY RUN] Would converge {} topic(s)", pending.len()));
+        return Ok(());
+    }
+
+    let options = AdminOptions::default();
+    for (config, drift) in pending {
+        if drift.missing {
+            let mut new_topic = NewTopic::new(
+                config.name,
+                config.partitions,
+                TopicReplication::Fixed(config.replication_factor),
+            );
+            for (key, value) in &config.config {
+                new_topic = new_topic.set(key, value);
+            }
+            for result in admin_client.create_topics(&[new_topic], &options).await? {
+                result.map_err(|(t, e)| anyhow!("Failed to create {}: {}", t, e))?;
+            }
+            log_success(&format!("  ✓ Created {}", config.name));
+            continue;
+        }
+
+        if let Some((_, desired)) = drift.add_partitions {
+            let partitions = NewPartitions::new(config.name, desired);
+            for result in admin_client.create_partitions(&[partitions], &options).await? {
+                result.map_err(|(t, e)| anyhow!("Failed to add partitions to {}: {}", t, e))?;
+            }
+            log_success(&format!("  ✓ {} now has {} partitions", config.name, desired));
+        }
+
+        if !drift.config_changes.is_empty() {
+            // AlterConfigs replaces the full override set, so carry existing overrides along
+            let existing = live.get(config.name).map(|t| &t.overrides);
+            let mut alter = AlterConfig::new(ResourceSpecifier::Topic(config.name));
+            for (key, value) in existing.into_iter().flatten() {
+                if !config.config.iter().any(|(k, _)| *k == key.as_str()) {
+                    alter = alter.set(key, value);
+                }
+            }
+            for (key, value) in &config.config {
+                alter = alter.set(key, value);
+            }
+            for result in admin_client.alter_configs(&[alter], &options).await? {
+                result.map_err(|(r, e)| anyhow!("Failed to alter {:?}: {}", r, e))?;
+            }
+            log_success(&format!(
+                "  ✓ Applied {} config change(s) to {}",
+                drift.config_changes.len(),
+                config.name
+            ));
+        }
+    }
+
+    println!();
+    log_success("===========================================");
+    log_success("   Reconciliation complete!");
+    log_success("===========================================");
+
+    Ok(())
+}
+
+// ========== Topic Deletion ==========
+
+/// Topic snapshot written before deletion so topics can be recreated
+#[derive(Debug, Serialize)]
+struct TopicBackup {
+    name: String,
+    partitions: usize,
+    replication_factor: usize,
+    configs: BTreeMap<String, String>,
+}
+
+/// Parse, deduplicate, and guard the topics requested for deletion
+fn deletion_targets(topics: &str, allow_non_llm: bool) -> Result<Vec<String>> {
+    let mut names: Vec<String> = topics
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    if names.is_empty() {
+        return Err(anyhow!("No topics given"));
+    }
+
+    if !allow_non_llm {
+        let foreign: Vec<&str> = names
+            .iter()
+            .filter(|name| !name.starts_with("llm-"))
+            .map(|name| name.as_str())
+            .collect();
+        if !foreign.is_empty() {
+            return Err(anyhow!(
+                "Refusing to delete non llm-* topics without --allow-non-llm: {}",
+                foreign.join(", ")
+            ));
+        }
+    }
+
+    Ok(names)
+}
+
+async fn backup_topics(
+    admin_client: &AdminClient<DefaultClientContext>,
+    metadata: &Metadata,
+    names: &[String],
+    path: &str,
+) -> Result<()> {
+    let resources: Vec<ResourceSpecifier> = names
+        .iter()
+        .map(|name| ResourceSpecifier::Topic(name.as_str()))
+        .collect();
+    let described = admin_client
+        .describe_configs(&resources, &AdminOptions::default())
+        .await
+        .context("Failed to describe topic configs")?;
+
+    let mut backups = Vec::with_capacity(names.len());
+    for (name, result) in names.iter().zip(described) {
+        let resource = result.map_err(|e| anyhow!("Failed to describe {}: {}", name, e))?;
+        let topic = metadata.topics().iter().find(|t| t.name() == name);
+
+        // Only overrides are needed to recreate the topic; defaults come from the broker
+        let configs = resource
+            .entries
+            .into_iter()
+            .filter(|entry| !entry.is_default && !entry.is_sensitive)
+            .filter_map(|entry| entry.value.map(|value| (entry.name, value)))
+            .collect();
+
+        backups.push(TopicBackup {
+            name: name.clone(),
+            partitions: topic.map(|t| t.partitions().len()).unwrap_or(0),
+            replication_factor: topic
+                .and_then(|t| t.partitions().first())
+                .map(|p| p.replicas().len())
+                .unwrap_or(0),
+            configs,
+        });
+    }
+
+    let document = serde_json::json!({
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "topics": backups,
+    });
+    std::fs::write(path, serde_json::to_string_pretty(&document)?)
+        .with_context(|| format!("Failed to write backup to {}", path))?;
+
+    log_success(&format!("  ✓ Topic configs backed up to {}", path));
+    Ok(())
+}
+
+async fn delete_topics(
+    bootstrap_servers: &str,
+    topics: &str,
+    force: bool,
+    backup: Option<&str>,
+    allow_non_llm: bool,
+    dry_run: bool,
+) -> Result<()> {
+    log_info("===========================================");
+    log_info("      Kafka Topic Deletion");
+    log_info("===========================================");
+    println!();
+
+    let names = deletion_targets(topics, allow_non_llm)?;
+
+    // Every requested topic must exist before anything is deleted
+    let consumer = create_consumer(bootstrap_servers)?;
+    let metadata = consumer
+        .fetch_metadata(None, Duration::from_secs(10))
+        .context("Failed to fetch metadata")?;
+    let missing: Vec<&str> = names
+        .iter()
+        .filter(|name| !metadata.topics().iter().any(|t| t.name() == name.as_str()))
+        .map(|name| name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!("Topics not found: {}", missing.join(", ")));
+    }
+
+    log_info(&format!("Topics to delete ({}):", names.len()));
+    for name in &names {
+        let partitions = metadata
+            .topics()
+            .iter()
+            .find(|t| t.name() == name.as_str())
+            .map(|t| t.partitions().len())
+            .unwrap_or(0);
+        println!("  {} {} ({} partitions)", "●".red(), name.red(), partitions);
+    }
+    println!();
+
+    if dry_run {
+        if let Some(path) = backup {
+            log_warn(&format!("  [DRY RUN] Would back up topic configs to {}", path));
+        }
+        log_warn("  [DRY RUN] Would delete the topics above");
+        return Ok(());
+    }
+
+    let admin_client = create_admin_client(bootstrap_servers)?;
+
+    if let Some(path) = backup {
+        backup_topics(&admin_client, &metadata, &names, path).await?;
+        println!();
+    }
+
+    if !force {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Permanently delete {} topic(s) and all their data?",
+                names.len()
+            ))
+            .default(false)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !confirmed {
+            log_warn("Aborted, no topics were deleted");
+            return Ok(());
+        }
+    }
+
+    let topic_refs: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    let results = admin_client
+        .delete_topics(&topic_refs, &AdminOptions::default())
+        .await
+        .context("Failed to delete topics")?;
+
+    let mut failures = 0;
+    for result in results {
+        match result {
+            Ok(topic_name) => log_success(&format!("  ✓ Topic deleted: {}", topic_name)),
+            Err((topic_name, error)) => {
+                failures += 1;
+                log_error(&format!("  ✗ Failed to delete topic {}: {}", topic_name, error));
+            }
+        }
+    }
+    println!();
+
+    if failures > 0 {
+        return Err(anyhow!("{} topic(s) could not be deleted", failures));
+    }
+
+    log_success("===========================================");
+    log_success("   Topic deletion completed!");
+    log_success("===========================================");
+
+    Ok(())
+}
+
+// ========== ACL Management ==========
+
+/// Runs `kafka-acls.sh` against the cluster from the local machine
+struct AclTool {
+    bin: String,
+    bootstrap_servers: String,
+    command_config: Option<PathBuf>,
+}
+
+impl AclTool {
+    async fn run(&self, mut args: Vec<String>) -> Result<String> {
+        if let Some(config) = &self.command_config {
+            args.push("--command-config".to_string());
+            args.push(config.display().to_string());
+        }
+
+        let output = tokio::process::Command::new(&self.bin)
+            .args(&args)
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {}", self.bin))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} failed: {}",
+                self.bin,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    async fn list(&self) -> Result<Vec<AclConfig>> {
+        let listing = self
+            .run(vec![
+                "--bootstrap-server".to_string(),
+                self.bootstrap_servers.clone(),
+                "--list".to_string(),
+            ])
+            .await?;
+        Ok(parse_acl_listing(&listing))
+    }
+
+    async fn add(&self, acl: &AclConfig) -> Result<()> {
+        self.run(acl_add_args(acl, &self.bootstrap_servers)).await?;
+        Ok(())
+    }
+}
+
+fn load_acl_spec(spec: Option<&PathBuf>) -> Result<AclSpec> {
+    match spec {
+        Some(path) => AclSpec::from_file(path),
+        None => Ok(AclSpec::standard()),
+    }
+}
+
+fn describe_acl(acl: &AclConfig) -> String {
+    format!(
+        "{:<28} {:?} {:<8} {:<10} {}",
+        acl.principal,
+        acl.permission,
+        operation_name(&acl.operation),
+        format!("{:?}", acl.resource_type),
+        acl.resource_name
+    )
+}
+
+fn print_drift(drift: &AclDrift) {
+    for acl in &drift.missing {
+        println!("  {} {}", "+".green(), describe_acl(acl).green());
+    }
+    for acl in &drift.unexpected {
+        println!("  {} {}", "-".red(), describe_acl(acl).red());
+    }
+}
+
+async fn acl_set(tool: &AclTool, spec: Option<&PathBuf>, dry_run: bool) -> Result<()> {
+    log_info("===========================================");
+    log_info("      Kafka ACL Configuration");
+    log_info("===========================================");
+    println!();
+
+    let spec = load_acl_spec(spec)?;
+    let drift = AclDrift::detect(&spec, &tool.list().await?);
+
+    if drift.missing.is_empty() {
+        log_success(&format!("All {} ACLs from the spec are in place", spec.acls.len()));
+    } else {
+        log_info(&format!("Adding {} missing ACLs:", drift.missing.len()));
+    }
+    print_drift(&AclDrift {
+        missing: drift.missing.clone(),
+        unexpected: Vec::new(),
+    });
+
+    if !drift.unexpected.is_empty() {
+        println!();
+        log_warn(&format!(
+            "{} ACLs for managed principals are not in the spec (left untouched):",
+            drift.unexpected.len()
+        ));
+        print_drift(&AclDrift {
+            missing: Vec::new(),
+            unexpected: drift.unexpected.clone(),
+        });
+    }
+    println!();
+
+    if dry_run {
+        log_warn("[DRY RUN] Would add the ACLs above");
+        return Ok(());
+    }
+
+    for acl in &drift.missing {
+        tool.add(acl).await?;
+        log_success(&format!("  ✓ {}", describe_acl(acl)));
+    }
+
+    log_success("ACL configuration complete");
+    Ok(())
+}
+
+async fn acl_list(tool: &AclTool, principal: Option<&str>) -> Result<()> {
+    let mut acls = tool.list().await?;
+    if let Some(principal) = principal {
+        acls.retain(|acl| acl.principal == principal);
+    }
+    acls.sort();
+
+    log_success(&format!("Found {} ACLs:", acls.len()));
+    println!();
+    for acl in &acls {
+        println!("  {} {}", "●".blue(), describe_acl(acl));
+    }
+    Ok(())
+}
+
+async fn acl_verify(tool: &AclTool, spec: Option<&PathBuf>) -> Result<()> {
+    log_info("Verifying ACLs against spec...");
+    println!();
+
+    let spec = load_acl_spec(spec)?;
+    let drift = AclDrift::detect(&spec, &tool.list().await?);
+
+    if drift.is_clean() {
+        log_success(&format!("No drift: {} ACLs match the spec", spec.acls.len()));
+        return Ok(());
+    }
+
+    print_drift(&drift);
+    println!();
+    log_error(&format!(
+        "ACL drift detected: {} missing, {} unexpected",
+        drift.missing.len(),
+        drift.unexpected.len()
+    ));
+    Err(anyhow!("ACLs do not match the spec"))
+}
+
+// ========== Performance Test ==========
+
+/// Options for the produce/consume performance test
+#[derive(Debug, Clone)]
+struct PerfTestOptions {
+    topic: String,
+    messages: usize,
+    size: usize,
+    concurrency: usize,
+    acks: String,
+    compression: String,
+    partitions: i32,
+    replication_factor: i32,
+    skip_consume: bool,
+}
+
+/// Results reported in the same layout as the bench-* tools
+#[derive(Debug)]
+struct BenchmarkResults {
+    total_operations: usize,
+    total_time: Duration,
+    ops_per_sec: f64,
+    mb_per_sec: f64,
+    avg_time_ms: f64,
+    p50_time_ms: f64,
+    p95_time_ms: f64,
+    p99_time_ms: f64,
+}
+
+impl BenchmarkResults {
+    fn from_timings(ops: usize, message_size: usize, total_time: Duration, timings: &[Duration]) -> Self {
+        let secs = total_time.as_secs_f64().max(f64::EPSILON);
+        let ops_per_sec = ops as f64 / secs;
+        let mb_per_sec = (ops * message_size) as f64 / (1024.0 * 1024.0) / secs;
+
+        // Calculate percentiles
+        let mut sorted_ms: Vec<f64> = timings.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        sorted_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let avg_time_ms = if sorted_ms.is_empty() {
+            0.0
+        } else {
+            sorted_ms.iter().sum::<f64>() / sorted_ms.len() as f64
+        };
+
+        Self {
+            total_operations: ops,
+            total_time,
+            ops_per_sec,
+            mb_per_sec,
+            avg_time_ms,
+            p50_time_ms: percentile(&sorted_ms, 50.0),
+            p95_time_ms: percentile(&sorted_ms, 95.0),
+            p99_time_ms: percentile(&sorted_ms, 99.0),
+        }
+    }
+}
+
+fn percentile(sorted_data: &[f64], p: f64) -> f64 {
+    if sorted_data.is_empty() {
+        return 0.0;
+    }
+    let index = ((p / 100.0) * (sorted_data.len() - 1) as f64).round() as usize;
+    sorted_data[index.min(sorted_data.len() - 1)]
+}
+
+fn print_results(name: &str, results: &BenchmarkResults) {
+    log_success(&format!("{} Results:", name));
+    println!("  Total Operations:   {}", format!("{:>12}", results.total_operations).cyan());
+    println!("  Total Time:         {}", format!("{:>10.2}s", results.total_time.as_secs_f64()).cyan());
+    println!("  Operations/sec:     {}", format!("{:>12.0}", results.ops_per_sec).green().bold());
+    println!("  Throughput:         {}", format!("{:>8.2}MB/s", results.mb_per_sec).green());
+    println!("  Avg Time:           {}", format!("{:>10.2}ms", results.avg_time_ms).cyan());
+    println!("  P50 Time:           {}", format!("{:>10.2}ms", results.p50_time_ms).cyan());
+    println!("  P95 Time:           {}", format!("{:>10.2}ms", results.p95_time_ms).yellow());
+    println!("  P99 Time:           {}", format!("{:>10.2}ms", results.p99_time_ms).yellow().bold());
+    println!();
+}
+
+/// Build a payload of `size` bytes whose first 8 bytes carry the send time in microseconds
+fn perf_payload(size: usize) -> Vec<u8> {
+    let mut payload = vec![b'x'; size.max(8)];
+    let sent_at = chrono::Utc::now().timestamp_micros();
+    payload[..8].copy_from_slice(&sent_at.to_be_bytes());
+    payload
+}
+
+/// End-to-end latency of a perf payload, if it carries a send timestamp
+fn payload_latency(payload: &[u8]) -> Option<Duration> {
+    let sent_at = i64::from_be_bytes(payload.get(..8)?.try_into().ok()?);
+    let elapsed = chrono::Utc::now().timestamp_micros() - sent_at;
+    Some(Duration::from_micros(elapsed.max(0) as u64))
+}
+
+async fn ensure_perf_topic(bootstrap_servers: &str, options: &PerfTestOptions) -> Result<()> {
+    let admin_client = create_admin_client(bootstrap_servers)?;
+    let new_topic = NewTopic::new(
+        &options.topic,
+        options.partitions,
+        TopicReplication::Fixed(options.replication_factor),
+    )
+    .set("retention.ms", "3600000"); // 1 hour
+
+    let results = admin_client
+        .create_topics(&[new_topic], &AdminOptions::default())
+        .await
+        .context("Failed to create perf test topic")?;
+
+    for result in results {
+        if let Err((topic_name, error)) = result {
+            if !error.to_string().contains("already exists") {
+                return Err(anyhow!("Failed to create topic {}: {}", topic_name, error));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_produce_test(
+    bootstrap_servers: &str,
+    options: &PerfTestOptions,
+    run_id: &str,
+) -> Result<BenchmarkResults> {
+    log_info(&format!(
+        "Producing {} messages of {} bytes with {} producers (acks={}, compression={})...",
+        options.messages, options.size, options.concurrency, options.acks, options.compression
+    ));
+
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", bootstrap_servers)
+        .set("client.id", "kafka-admin-perf-test")
+        .set("acks", &options.acks)
+        .set("compression.type", &options.compression)
+        .set("linger.ms", "5")
+        .set("message.timeout.ms", "30000")
+        .create()
+        .context("Failed to create perf test producer")?;
+
+    let concurrency = options.concurrency.max(1);
+    let per_task = options.messages / concurrency;
+    let remainder = options.messages % concurrency;
+
+    let progress = ProgressBar::new(options.messages as u64);
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template("  [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")?
+            .progress_chars("=>-"),
+    );
+
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for task in 0..concurrency {
+        let count = per_task + usize::from(task < remainder);
+        let producer = producer.clone();
+        let topic = options.topic.clone();
+        let key = run_id.to_string();
+        let size = options.size;
+        let progress = progress.clone();
+
+        tasks.spawn(async move {
+            let mut timings = Vec::with_capacity(count);
+            let mut failures = 0usize;
+            for _ in 0..count {
+                let payload = perf_payload(size);
+                let record = FutureRecord::to(&topic).payload(&payload).key(&key);
+                let sent = Instant::now();
+                match producer.send(record, Duration::from_secs(30)).await {
+                    Ok(_) => timings.push(sent.elapsed()),
+                    Err((e, _)) => {
+                        failures += 1;
+                        warn!("Perf test delivery failed: {}", e);
+                    }
+                }
+                progress.inc(1);
+            }
+            (timings, failures)
+        });
+    }
+
+    let mut timings = Vec::with_capacity(options.messages);
+    let mut failures = 0;
+    while let Some(result) = tasks.join_next().await {
+        let (task_timings, task_failures) = result.context("Producer task panicked")?;
+        timings.extend(task_timings);
+        failures += task_failures;
+    }
+    let total_time = start.elapsed();
+    progress.finish_and_clear();
+
+    if failures > 0 {
+        log_warn(&format!("  {} messages failed to deliver", failures));
+    }
+
+    Ok(BenchmarkResults::from_timings(timings.len(), options.size, total_time, &timings))
+}
+
+async fn run_consume_test(
+    bootstrap_servers: &str,
+    options: &PerfTestOptions,
+    run_id: &str,
+    expected: usize,
+) -> Result<BenchmarkResults> {
+    log_info(&format!("Consuming {} messages...", expected));
+
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", bootstrap_servers)
+        .set("group.id", format!("kafka-admin-perf-{}", run_id))
+        .set("auto.offset.reset", "earliest")
+        .set("enable.auto.commit", "false")
+        .create()
+        .context("Failed to create perf test consumer")?;
+    consumer
+        .subscribe(&[options.topic.as_str()])
+        .context("Failed to subscribe to perf test topic")?;
+
+    let idle_timeout = Duration::from_secs(30);
+    let mut timings = Vec::with_capacity(expected);
+    let mut start = None;
+
+    while timings.len() < expected {
+        let message = match tokio::time::timeout(idle_timeout, consumer.recv()).await {
+            Ok(Ok(message)) => message,
+            Ok(Err(e)) => {
+                warn!("Perf test consume error: {}", e);
+                continue;
+            }
+            Err(_) => {
+                log_warn(&format!(
+                    "  Timed out after {}/{} messages",
+                    timings.len(),
+                    expected
+                ));
+                break;
+            }
+        };
+
+        // Skip messages left behind by earlier runs on the same topic
+        if message.key() != Some(run_id.as_bytes()) {
+            continue;
+        }
+        start.get_or_insert_with(Instant::now);
+        if let Some(latency) = message.payload().and_then(payload_latency) {
+            timings.push(latency);
+        }
+    }
+
+    let total_time = start.map(|s| s.elapsed()).unwrap_or_default();
+    Ok(BenchmarkResults::from_timings(timings.len(), options.size, total_time, &timings))
+}
+
+async fn perf_test(bootstrap_servers: &str, options: PerfTestOptions, dry_run: bool) -> Result<()> {
+    log_info("===========================================");
+    log_info("      Kafka Performance Test");
+    log_info("===========================================");
+    println!();
+    println!("  Topic:        {}", options.topic);
+    println!("  Messages:     {}", options.messages);
+    println!("  Size:         {} bytes", options.size);
+    println!("  Concurrency:  {}", options.concurrency);
+    println!("  Acks:         {}", options.acks);
+    println!("  Compression:  {}", options.compression);
+    println!();
+
+    if dry_run {
+        log_warn("[DRY RUN] Would run produce/consume performance test");
+        return Ok(());
+    }
+
+    wait_for_kafka(bootstrap_servers).await?;
+    ensure_perf_topic(bootstrap_servers, &options).await?;
+    println!();
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+
+    let produce_results = run_produce_test(bootstrap_servers, &options, &run_id).await?;
+    print_results("Produce", &produce_results);
+
+    if !options.skip_consume {
+        let consume_results = run_consume_test(
+            bootstrap_servers,
+            &options,
+            &run_id,
+            produce_results.total_operations,
+        )
+        .await?;
+        print_results("Consume (end-to-end latency)", &consume_results);
+    }
+
+    log_success("===========================================");
+    log_success("   Performance test completed!");
+    log_success("===========================================");
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt()
@@ -554,16 +1549,67 @@ async fn main() -> Result<()> {
         Commands::Describe { topic } => {
             describe_topic(&cli.bootstrap_servers, &topic).await?;
         }
-        Commands::DeleteTopics { .. } => {
-            log_error("Topic deletion not yet implemented");
-            log_warn("Use Kafka CLI tools for topic deletion for safety");
+        Commands::DeleteTopics {
+            topics,
+            force,
+            backup,
+            allow_non_llm,
+        } => {
+            delete_topics(
+                &cli.bootstrap_servers,
+                &topics,
+                force,
+                backup.as_deref(),
+                allow_non_llm,
+                cli.dry_run,
+            )
+            .await?;
         }
         Commands::Verify => {
             verify_cluster(&cli.bootstrap_servers).await?;
         }
-        Commands::PerfTest { .. } => {
-            log_error("Performance testing not yet implemented");
-            log_warn("Use kafka-producer-perf-test and kafka-consumer-perf-test");
+        Commands::Reconcile => {
+            reconcile(&cli.bootstrap_servers, cli.dry_run).await?;
+        }
+        Commands::Acl {
+            acls_bin,
+            command_config,
+            action,
+        } => {
+            let tool = AclTool {
+                bin: acls_bin,
+                bootstrap_servers: cli.bootstrap_servers.clone(),
+                command_config,
+            };
+            match action {
+                AclCommand::Set { spec } => acl_set(&tool, spec.as_ref(), cli.dry_run).await?,
+                AclCommand::List { principal } => acl_list(&tool, principal.as_deref()).await?,
+                AclCommand::Verify { spec } => acl_verify(&tool, spec.as_ref()).await?,
+            }
+        }
+        Commands::PerfTest {
+            topic,
+            messages,
+            size,
+            concurrency,
+            acks,
+            compression,
+            partitions,
+            replication_factor,
+            skip_consume,
+        } => {
+            let options = PerfTestOptions {
+                topic,
+                messages,
+                size,
+                concurrency,
+                acks,
+                compression,
+                partitions,
+                replication_factor,
+                skip_consume,
+            };
+            perf_test(&cli.bootstrap_servers, options, cli.dry_run).await?;
         }
     }
 
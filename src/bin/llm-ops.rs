@@ -4,10 +4,53 @@
 //! Replaces shell scripts with type-safe, testable Rust code.
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
+use llm_analytics_hub::adapters::config_manager::ConfigManagerConfig;
+use llm_analytics_hub::adapters::costops::CostOpsConfig;
+use llm_analytics_hub::adapters::memory_graph::MemoryGraphConfig;
+use llm_analytics_hub::adapters::observatory::ObservatoryConfig;
+use llm_analytics_hub::adapters::registry::RegistryConfig;
+use llm_analytics_hub::adapters::AdapterHealth;
+use llm_analytics_hub::auth::API_KEY_HEADER;
+use llm_analytics_hub::database::timescale::parse_window;
+use llm_analytics_hub::alerting::{MaintenanceAction, MaintenanceWindow, Silence};
+use llm_analytics_hub::analytics::ScalingRecommendation;
+use llm_analytics_hub::database::migrations::{
+    load_migrations, pending_migrations, verify_applied, AppliedMigration, ChecksumStatus,
+};
+use llm_analytics_hub::database::{AggregatedMetricRow, AnomalyStatusRow};
+use llm_analytics_hub::health::doctor::{
+    adapter_check, clock_skew_check, disk_check, lag_check, migrations_check, parse_df_usage,
+    topics_check,
+};
+use llm_analytics_hub::health::{
+    CheckCategory, ComponentStatus, DiagnosticCheck, DoctorReport, DoctorThresholds, LagReport,
+    ReadinessReport,
+};
+use llm_analytics_hub::infra::backup::{BackupDestination, BackupManifest, BackupStore, ScratchRestore};
+use llm_analytics_hub::infra::kafka::{get_llm_topic_configs, TopicManager};
+use llm_analytics_hub::infra::k8s::{
+    Cluster, ClusterOptions, K8sClient, K8sError, Kubectl, RolloutConfig, RolloutController,
+    RolloutOutcome, RolloutSlo, RolloutStrategy, WorkloadKind,
+};
+use llm_analytics_hub::reporting::{ComplianceReportConfig, ComplianceReporter};
+use llm_analytics_hub::{
+    AnalyticsEvent, ApiResponse, Database, EventPayload, EventType, Severity, SourceModule,
+};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use tracing::{info, warn, error};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info, warn};
 
 #[derive(Parser)]
 #[command(name = "llm-ops")]
@@ -23,6 +66,19 @@ struct Cli {
     /// Dry run (don't execute, just show what would happen)
     #[arg(short, long)]
     dry_run: bool,
+
+    /// Kubeconfig file (defaults to $KUBECONFIG or ~/.kube/config)
+    #[arg(long, global = true)]
+    kubeconfig: Option<PathBuf>,
+
+    /// Kubeconfig context to use instead of the current one
+    #[arg(long = "context", global = true)]
+    kube_context: Option<String>,
+
+    /// Output format (text, json); json prints one result document per
+    /// command, except `events tail`, which streams one event per line
+    #[arg(long, global = true, env = "LLM_OPS_OUTPUT", default_value = "text")]
+    output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -40,6 +96,9 @@ enum Commands {
         /// Region
         #[arg(short, long)]
         region: Option<String>,
+
+        #[command(flatten)]
+        rollout: RolloutArgs,
     },
 
     /// Validate deployment and infrastructure
@@ -63,6 +122,29 @@ enum Commands {
         service: String,
     },
 
+    /// Diagnose common problems and print a prioritized remediation list
+    Doctor {
+        /// Postgres URL for the migration and clock checks
+        #[arg(long, env = "DATABASE_URL")]
+        database_url: Option<String>,
+
+        /// Kafka bootstrap servers
+        #[arg(long, env = "KAFKA_BROKERS", default_value = "localhost:9092")]
+        brokers: String,
+
+        /// Directory containing the migration files
+        #[arg(long, env = "MIGRATIONS_DIR", default_value = "migrations")]
+        migrations_dir: PathBuf,
+
+        /// Fail on degraded checks too, not just unhealthy ones
+        #[arg(long)]
+        strict: bool,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Build Docker images
     Build {
         /// Service to build (all, rust, api, frontend)
@@ -82,21 +164,33 @@ enum Commands {
     },
 
     /// Backup databases
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Backup {
+        #[command(subcommand)]
+        action: Option<BackupAction>,
+
         /// Database to backup (timescaledb, redis, kafka, all)
         #[arg(short, long, default_value = "all")]
         database: String,
 
-        /// Backup destination
-        #[arg(short, long)]
-        destination: String,
+        /// Backup destination: a local file, or an s3://, gs://, az:// or file:// URL
+        #[arg(long, required = true)]
+        destination: Option<String>,
+
+        /// Backups to keep at a URL destination; older ones are rotated out
+        #[arg(long, default_value = "7", env = "BACKUP_RETAIN")]
+        keep: usize,
     },
 
     /// Restore from backup
     Restore {
-        /// Backup file path
+        /// Backup file path, or the URL backups were uploaded to
         #[arg(short, long)]
         backup_file: String,
+
+        /// Backup to restore from a URL (defaults to the newest)
+        #[arg(long)]
+        backup_id: Option<String>,
     },
 
     /// Scale services
@@ -104,8 +198,24 @@ enum Commands {
         /// Service name
         service: String,
 
-        /// Number of replicas
-        replicas: u32,
+        /// Number of replicas (omit with --recommended or --auto to use a hint)
+        #[arg(required_unless_present_any = ["recommended", "auto"])]
+        replicas: Option<u32>,
+
+        /// Use the replica count recommended by the hub's consumer lag monitor
+        #[arg(long, conflicts_with = "replicas")]
+        recommended: bool,
+
+        /// Use the hub's forecast-driven recommendation for this service
+        #[arg(long, conflicts_with_all = ["replicas", "recommended"])]
+        auto: bool,
+
+        /// Forecast horizon in hours for --auto
+        #[arg(long, default_value = "6", requires = "auto")]
+        hours: i64,
+
+        #[command(flatten)]
+        hub: HubArgs,
     },
 
     /// Connect to a service (interactive shell)
@@ -117,6 +227,378 @@ enum Commands {
         #[arg(short, long, default_value = "llm-analytics-hub")]
         namespace: String,
     },
+
+    /// Generate a report from stored analytics events
+    Report {
+        /// Report type (compliance)
+        #[arg(default_value = "compliance")]
+        report_type: String,
+
+        /// Lookback period in days
+        #[arg(long, default_value = "30")]
+        days: i64,
+
+        /// Restrict to a compliance framework (e.g. SOC2, GDPR)
+        #[arg(long)]
+        framework: Option<String>,
+
+        /// Days allowed to complete a data deletion request
+        #[arg(long, default_value = "30")]
+        deletion_sla_days: i64,
+
+        /// Output format (json, csv)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short = 'o', long)]
+        output_file: Option<String>,
+
+        /// Database connection URL
+        #[arg(long, env = "DATABASE_URL")]
+        database_url: String,
+    },
+
+    /// Query an aggregated metric series
+    Query {
+        /// Metric name, e.g. llm.latency or session.depth
+        metric: String,
+
+        /// Aggregation window (1m, 5m, 15m, 1h, 1d)
+        #[arg(short, long, default_value = "1h")]
+        window: String,
+
+        /// Lookback in hours, ending at --until
+        #[arg(long, default_value = "24", conflicts_with = "since")]
+        hours: i64,
+
+        /// Start of the range (RFC 3339)
+        #[arg(long)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// End of the range (RFC 3339), defaults to now
+        #[arg(long)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Output format (table, json, csv)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+
+        #[command(flatten)]
+        hub: HubArgs,
+
+        /// Query this database directly instead of the hub API
+        #[arg(long)]
+        database_url: Option<String>,
+    },
+
+    /// Inspect the analytics event stream
+    Events {
+        #[command(subcommand)]
+        command: EventsCommand,
+    },
+
+    /// Inspect and acknowledge detected anomalies
+    Anomalies {
+        #[command(subcommand)]
+        command: AnomaliesCommand,
+
+        #[command(flatten)]
+        hub: HubArgs,
+    },
+
+    /// Inspect alerts and manage silences
+    Alerts {
+        #[command(subcommand)]
+        command: AlertsCommand,
+
+        #[command(flatten)]
+        hub: HubArgs,
+    },
+
+    /// Schedule maintenance windows that suppress or tag findings
+    Maintenance {
+        #[command(subcommand)]
+        command: MaintenanceCommand,
+
+        #[command(flatten)]
+        hub: HubArgs,
+    },
+}
+
+/// How `deploy` rolls new images out to the hub's Deployments
+#[derive(Args)]
+struct RolloutArgs {
+    /// Rollout strategy (rolling, blue-green, canary)
+    #[arg(long, default_value = "rolling")]
+    strategy: RolloutStrategy,
+
+    /// Image tag to roll out; required for blue-green and canary
+    #[arg(long)]
+    image_tag: Option<String>,
+
+    /// Deployments to roll out, in order
+    #[arg(long, value_delimiter = ',', default_value = "event-ingestion,api,frontend")]
+    deployments: Vec<String>,
+
+    /// Canary traffic percentages, in order
+    #[arg(long, value_delimiter = ',', default_value = "10,50")]
+    canary_steps: Vec<u32>,
+
+    /// How long each rollout phase is observed (e.g. 5m)
+    #[arg(long, default_value = "5m", value_parser = parse_duration)]
+    analysis_window: chrono::Duration,
+
+    /// Roll back when more than this share of health probes fail
+    #[arg(long, default_value = "0.01")]
+    max_error_rate: f64,
+
+    /// Roll back when p95 health probe latency exceeds this (milliseconds)
+    #[arg(long, default_value = "500")]
+    max_latency_ms: u64,
+
+    /// Health endpoint probed during rollout analysis [default: /health/ready on the hub API]
+    #[arg(long, env = "API_HEALTH_URL")]
+    health_url: Option<String>,
+}
+
+/// Hub API base URL when none is configured; event-ingestion serves HTTP on port 8080
+const DEFAULT_HUB_API_URL: &str = "http://localhost:8080";
+
+/// Hub API base URL for commands that take no `HubArgs`
+fn hub_api_url() -> String {
+    std::env::var("HUB_API_URL").unwrap_or_else(|_| DEFAULT_HUB_API_URL.to_string())
+}
+
+/// `path` on the hub API at `api_url`
+fn hub_endpoint(api_url: &str, path: &str) -> String {
+    format!("{}{}", api_url.trim_end_matches('/'), path)
+}
+
+/// Connection to the hub API
+#[derive(Args)]
+struct HubArgs {
+    /// Hub API base URL
+    #[arg(long, global = true, env = "HUB_API_URL", default_value = DEFAULT_HUB_API_URL)]
+    api_url: String,
+
+    /// API key for the hub API
+    #[arg(long, global = true, env = "HUB_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum EventsCommand {
+    /// Print events from the event topic as they arrive
+    Tail {
+        /// Only show events from this source module (repeatable, e.g. llm-sentinel)
+        #[arg(short, long)]
+        module: Vec<String>,
+
+        /// Minimum severity (debug, info, warning, error, critical)
+        #[arg(short, long)]
+        severity: Option<String>,
+
+        /// Only show events of this type (repeatable, e.g. alert, telemetry)
+        #[arg(short = 't', long)]
+        event_type: Vec<String>,
+
+        /// Print each event as one line of JSON, for piping
+        #[arg(long)]
+        json: bool,
+
+        /// Start from the earliest retained event instead of new ones
+        #[arg(long)]
+        from_beginning: bool,
+
+        /// Exit after this many matching events
+        #[arg(short = 'n', long)]
+        limit: Option<u64>,
+
+        /// Kafka bootstrap servers
+        #[arg(long, env = "KAFKA_BROKERS", default_value = "localhost:9092")]
+        brokers: String,
+
+        /// Event topic
+        #[arg(long, env = "KAFKA_TOPIC", default_value = "llm-events")]
+        topic: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+    /// List the backups at a destination
+    List {
+        /// Destination URL
+        #[arg(long)]
+        destination: String,
+
+        /// Print the manifests as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Test-restore a backup into a scratch database
+    Verify {
+        /// Destination URL
+        #[arg(long)]
+        destination: String,
+
+        /// Backup to verify (defaults to the newest)
+        #[arg(long)]
+        backup_id: Option<String>,
+
+        /// Postgres server to create the scratch database on
+        #[arg(long, env = "DATABASE_URL")]
+        database_url: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AnomaliesCommand {
+    /// List recent anomalies with their acknowledgement status
+    List {
+        /// Lookback in hours
+        #[arg(long, default_value = "24")]
+        hours: i64,
+
+        /// Maximum anomalies to show
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: i64,
+
+        /// Only show anomalies nobody has acknowledged
+        #[arg(long)]
+        unacked: bool,
+
+        /// Print the anomalies as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Acknowledge an anomaly
+    Ack {
+        /// Anomaly ID
+        anomaly_id: uuid::Uuid,
+
+        /// Note recorded with the acknowledgement
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AlertsCommand {
+    /// List recent alerts
+    List {
+        /// Lookback in hours
+        #[arg(long, default_value = "24")]
+        hours: i64,
+
+        /// Maximum alerts to show
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: i64,
+
+        /// Minimum severity (debug, info, warning, error, critical)
+        #[arg(short, long)]
+        severity: Option<String>,
+
+        /// Print the alerts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Silence matching alerts for a period
+    Silence {
+        /// Alert type to silence, e.g. provider.failover_recommendation
+        #[arg(short = 't', long, required_unless_present = "tag")]
+        alert_type: Option<String>,
+
+        /// Tag the alert must carry, as key=value (repeatable)
+        #[arg(long, value_parser = parse_tag)]
+        tag: Vec<(String, String)>,
+
+        /// How long the silence lasts (e.g. 30m, 2h, 1d)
+        #[arg(short, long, default_value = "1h", value_parser = parse_duration)]
+        duration: chrono::Duration,
+
+        /// Why the alerts are silenced
+        #[arg(short, long)]
+        comment: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MaintenanceCommand {
+    /// Schedule a maintenance window for an environment or one of its services
+    Schedule {
+        /// Environment under maintenance
+        #[arg(short, long)]
+        environment: String,
+
+        /// Service under maintenance; omit to cover the whole environment
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// Start of the window (RFC 3339), defaults to now
+        #[arg(long)]
+        start: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// How long the window lasts (e.g. 30m, 2h, 1d)
+        #[arg(short, long, default_value = "1h", value_parser = parse_duration)]
+        duration: chrono::Duration,
+
+        /// What happens to findings during the window (suppress, tag)
+        #[arg(short, long, default_value = "suppress")]
+        action: MaintenanceAction,
+
+        /// Why the window is scheduled, e.g. the deploy it covers
+        #[arg(short, long)]
+        comment: Option<String>,
+    },
+
+    /// List active and scheduled maintenance windows
+    List {
+        /// Print the windows as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// End a maintenance window early, or cancel a scheduled one
+    End {
+        /// Maintenance window ID
+        window_id: uuid::Uuid,
+    },
+}
+
+impl Commands {
+    /// Command name reported in the JSON result document
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Deploy { .. } => "deploy",
+            Commands::Validate { .. } => "validate",
+            Commands::DbInit { .. } => "db-init",
+            Commands::Health { .. } => "health",
+            Commands::Doctor { .. } => "doctor",
+            Commands::Build { .. } => "build",
+            Commands::Test { .. } => "test",
+            Commands::Backup { action: None, .. } => "backup",
+            Commands::Backup { action: Some(BackupAction::List { .. }), .. } => "backup list",
+            Commands::Backup { action: Some(BackupAction::Verify { .. }), .. } => "backup verify",
+            Commands::Restore { .. } => "restore",
+            Commands::Scale { .. } => "scale",
+            Commands::Connect { .. } => "connect",
+            Commands::Report { .. } => "report",
+            Commands::Query { .. } => "query",
+            Commands::Events { .. } => "events tail",
+            Commands::Anomalies { command: AnomaliesCommand::List { .. }, .. } => "anomalies list",
+            Commands::Anomalies { command: AnomaliesCommand::Ack { .. }, .. } => "anomalies ack",
+            Commands::Alerts { command: AlertsCommand::List { .. }, .. } => "alerts list",
+            Commands::Alerts { command: AlertsCommand::Silence { .. }, .. } => "alerts silence",
+            Commands::Maintenance { command: MaintenanceCommand::Schedule { .. }, .. } => "maintenance schedule",
+            Commands::Maintenance { command: MaintenanceCommand::List { .. }, .. } => "maintenance list",
+            Commands::Maintenance { command: MaintenanceCommand::End { .. }, .. } => "maintenance end",
+        }
+    }
 }
 
 #[tokio::main]
@@ -127,40 +609,179 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
+    let out = Output::new(cli.output);
+
+    // Keep stdout clean when output is meant for piping
+    let machine_output = out.is_json() || match &cli.command {
+        Commands::Events { command: EventsCommand::Tail { json, .. } } => *json,
+        Commands::Query { format, .. } => format != "table",
+        Commands::Anomalies { command: AnomaliesCommand::List { json, .. }, .. } => *json,
+        Commands::Alerts { command: AlertsCommand::List { json, .. }, .. } => *json,
+        Commands::Maintenance { command: MaintenanceCommand::List { json }, .. } => *json,
+        Commands::Backup { action: Some(BackupAction::List { json, .. }), .. } => *json,
+        Commands::Doctor { json, .. } => *json,
+        _ => false,
+    };
+    if !machine_output {
+        println!("{}", "🚀 LLM Analytics Hub Operations CLI".bold().cyan());
+        println!();
+    }
 
-    println!("{}", "🚀 LLM Analytics Hub Operations CLI".bold().cyan());
-    println!();
+    let command = cli.command.name();
+    // Tailing streams events instead of producing a single result
+    let streaming = matches!(cli.command, Commands::Events { .. });
+    let result = run(cli, &out).await;
+    if !streaming {
+        out.finish(command, &result)?;
+    }
+    result
+}
+
+async fn run(cli: Cli, out: &Output) -> Result<()> {
+    let cluster_options = ClusterOptions {
+        kubeconfig: cli.kubeconfig.clone(),
+        context: cli.kube_context.clone(),
+        ..ClusterOptions::default()
+    };
 
     match cli.command {
-        Commands::Deploy { provider, environment, region } => {
-            deploy(&provider, &environment, region.as_deref(), cli.dry_run).await?;
+        Commands::Deploy { provider, environment, region, rollout } => {
+            deploy(&provider, &environment, region.as_deref(), &rollout, cli.dry_run, &cluster_options, out).await?;
         }
         Commands::Validate { target } => {
-            validate(&target, cli.verbose).await?;
+            validate(&target, cli.verbose, &cluster_options, out).await?;
         }
         Commands::DbInit { database } => {
-            db_init(&database, cli.dry_run).await?;
+            db_init(&database, cli.dry_run, out).await?;
         }
         Commands::Health { service } => {
-            health_check(&service).await?;
+            health_check(&service, &cluster_options, out).await?;
+        }
+        Commands::Doctor { database_url, brokers, migrations_dir, strict, json } => {
+            doctor(database_url.as_deref(), &brokers, &migrations_dir, strict, json, &cluster_options, out).await?;
         }
         Commands::Build { service, push } => {
-            build(&service, push, cli.dry_run).await?;
+            build(&service, push, cli.dry_run, out).await?;
         }
         Commands::Test { test_type } => {
-            run_tests(&test_type, cli.verbose).await?;
+            run_tests(&test_type, cli.verbose, out).await?;
         }
-        Commands::Backup { database, destination } => {
-            backup(&database, &destination, cli.dry_run).await?;
+        Commands::Backup { action: Some(action), .. } => match action {
+            BackupAction::List { destination, json } => {
+                list_backups(&destination, json, out).await?;
+            }
+            BackupAction::Verify { destination, backup_id, database_url } => {
+                verify_backup(&destination, backup_id.as_deref(), &database_url, cli.dry_run, out).await?;
+            }
+        },
+        Commands::Backup { action: None, database, destination, keep } => {
+            let destination = destination.context("--destination is required")?;
+            backup(&database, &destination, keep, cli.dry_run, &cluster_options, out).await?;
         }
-        Commands::Restore { backup_file } => {
-            restore(&backup_file, cli.dry_run).await?;
+        Commands::Restore { backup_file, backup_id } => {
+            restore(&backup_file, backup_id.as_deref(), cli.dry_run, &cluster_options, out).await?;
         }
-        Commands::Scale { service, replicas } => {
-            scale(&service, replicas, cli.dry_run).await?;
+        Commands::Scale { service, replicas, recommended, auto, hours, hub } => {
+            let replicas = match replicas {
+                Some(replicas) => replicas,
+                None if recommended => recommended_replicas(&hub.api_url, out).await?,
+                None if auto => forecast_replicas(&HubClient::new(hub), &service, hours, out).await?,
+                None => anyhow::bail!("Either a replica count, --recommended or --auto is required"),
+            };
+            scale(&service, replicas, cli.dry_run, &cluster_options, out).await?;
         }
         Commands::Connect { service, namespace } => {
-            connect(&service, &namespace).await?;
+            connect(&service, cluster_options.with_namespace(namespace), out).await?;
+        }
+        Commands::Report {
+            report_type,
+            days,
+            framework,
+            deletion_sla_days,
+            format,
+            output_file,
+            database_url,
+        } => {
+            let config = ComplianceReportConfig {
+                framework,
+                deletion_sla_days,
+            };
+            report(&report_type, days, config, &format, output_file.as_deref(), &database_url, out).await?;
+        }
+        Commands::Query {
+            metric,
+            window,
+            hours,
+            since,
+            until,
+            format,
+            hub,
+            database_url,
+        } => {
+            let end = until.unwrap_or_else(chrono::Utc::now);
+            let start = since.unwrap_or_else(|| end - chrono::Duration::hours(hours));
+            let source = match database_url {
+                Some(url) => MetricSource::Database(url),
+                None => MetricSource::Api(HubClient::new(hub)),
+            };
+            query_metric(&source, &metric, &window, start, end, &format, out).await?;
+        }
+        Commands::Events { command } => match command {
+            EventsCommand::Tail {
+                module,
+                severity,
+                event_type,
+                json,
+                from_beginning,
+                limit,
+                brokers,
+                topic,
+            } => {
+                let filter = TailFilter::parse(&module, severity.as_deref(), &event_type)?;
+                let options = TailOptions { json: json || out.is_json(), from_beginning, limit };
+                tail_events(&brokers, &topic, filter, options).await?;
+            }
+        },
+        Commands::Anomalies { command, hub } => {
+            let client = HubClient::new(hub);
+            match command {
+                AnomaliesCommand::List { hours, limit, unacked, json } => {
+                    list_anomalies(&client, hours, limit, unacked, json, out).await?;
+                }
+                AnomaliesCommand::Ack { anomaly_id, note } => {
+                    acknowledge_anomaly(&client, anomaly_id, note, out).await?;
+                }
+            }
+        }
+        Commands::Alerts { command, hub } => {
+            let client = HubClient::new(hub);
+            match command {
+                AlertsCommand::List { hours, limit, severity, json } => {
+                    let severity = severity.map(|s| parse_enum::<Severity>("severity", &s)).transpose()?;
+                    list_alerts(&client, hours, limit, severity, json, out).await?;
+                }
+                AlertsCommand::Silence { alert_type, tag, duration, comment } => {
+                    let mut silence = Silence::new(duration);
+                    silence.alert_type = alert_type;
+                    silence.tags = tag.into_iter().collect();
+                    silence.comment = comment;
+                    create_silence(&client, &silence, out).await?;
+                }
+            }
+        }
+        Commands::Maintenance { command, hub } => {
+            let client = HubClient::new(hub);
+            match command {
+                MaintenanceCommand::Schedule { environment, service, start, duration, action, comment } => {
+                    let mut window = MaintenanceWindow::new(environment, start.unwrap_or_else(chrono::Utc::now), duration)
+                        .with_action(action);
+                    window.service = service;
+                    window.comment = comment;
+                    schedule_maintenance(&client, &window, out).await?;
+                }
+                MaintenanceCommand::List { json } => list_maintenance(&client, json, out).await?,
+                MaintenanceCommand::End { window_id } => end_maintenance(&client, window_id, out).await?,
+            }
         }
     }
 
@@ -169,185 +790,259 @@ async fn main() -> Result<()> {
 
 // ========== Deployment ==========
 
-async fn deploy(provider: &str, environment: &str, region: Option<&str>, dry_run: bool) -> Result<()> {
-    println!("{}", format!("📦 Deploying to {}", provider).bold());
-    println!("Environment: {}", environment.green());
+async fn deploy(
+    provider: &str,
+    environment: &str,
+    region: Option<&str>,
+    rollout: &RolloutArgs,
+    dry_run: bool,
+    options: &ClusterOptions,
+    out: &Output,
+) -> Result<()> {
+    out.line(format!("📦 Deploying to {}", provider).bold());
+    out.line(format!("Environment: {}", environment.green()));
     if let Some(r) = region {
-        println!("Region: {}", r.green());
+        out.line(format!("Region: {}", r.green()));
+    }
+    out.line(format!("Strategy: {}", rollout.strategy.as_str().green()));
+
+    if rollout.strategy != RolloutStrategy::Rolling && rollout.image_tag.is_none() {
+        anyhow::bail!("--image-tag is required for {} deployments", rollout.strategy.as_str());
     }
 
     if dry_run {
-        println!("{}", "[DRY RUN] Would deploy but not executing".yellow());
+        out.line("[DRY RUN] Would deploy but not executing".yellow());
         return Ok(());
     }
 
     // Pre-deployment checks
     info!("Running pre-deployment checks...");
-    pre_deploy_check().await?;
+    pre_deploy_check(out).await?;
+
+    // Progressive strategies change images themselves; re-applying the
+    // manifests would roll every Deployment at once
+    let apply_manifests = rollout.strategy == RolloutStrategy::Rolling;
 
     // Deploy based on provider
+    out.section("infrastructure", format!("🏗️  Provisioning {} infrastructure", provider).bold());
     match provider {
-        "aws" => deploy_aws(environment, region).await?,
-        "gcp" => deploy_gcp(environment, region).await?,
-        "azure" => deploy_azure(environment, region).await?,
+        "aws" => deploy_aws(environment, region, apply_manifests, out).await?,
+        "gcp" => deploy_gcp(environment, region, out).await?,
+        "azure" => deploy_azure(environment, region, out).await?,
         _ => anyhow::bail!("Unknown provider: {}", provider),
     }
+    out.pass("Terraform", format!("{} applied", environment));
+
+    if let Some(image_tag) = &rollout.image_tag {
+        roll_out(rollout, image_tag, options, out).await?;
+    }
 
     // Post-deployment validation
     info!("Running post-deployment validation...");
-    validate("all", false).await?;
+    validate("all", false, options, out).await?;
 
-    println!("{}", "✅ Deployment complete!".bold().green());
+    out.line("✅ Deployment complete!".bold().green());
     Ok(())
 }
 
-async fn deploy_aws(environment: &str, region: Option<&str>) -> Result<()> {
+async fn deploy_aws(environment: &str, region: Option<&str>, apply_manifests: bool, out: &Output) -> Result<()> {
     let region = region.unwrap_or("us-east-1");
 
     info!("Deploying to AWS region: {}", region);
 
     // Initialize Terraform
-    run_command("terraform", &["init"], "infrastructure/terraform/aws").await?;
+    run_command("terraform", &["init"], "infrastructure/terraform/aws", out).await?;
 
     // Apply Terraform
     run_command(
         "terraform",
         &["apply", "-auto-approve", &format!("-var=environment={}", environment)],
         "infrastructure/terraform/aws",
+        out,
     ).await?;
 
     // Deploy Kubernetes resources
-    run_command("kubectl", &["apply", "-f", "k8s/"], ".").await?;
+    if apply_manifests {
+        run_command("kubectl", &["apply", "-f", "k8s/"], ".", out).await?;
+    }
 
     Ok(())
 }
 
-async fn deploy_gcp(environment: &str, region: Option<&str>) -> Result<()> {
+async fn deploy_gcp(environment: &str, region: Option<&str>, out: &Output) -> Result<()> {
     let region = region.unwrap_or("us-central1");
     info!("Deploying to GCP region: {}", region);
 
-    run_command("terraform", &["init"], "infrastructure/terraform/gcp").await?;
+    run_command("terraform", &["init"], "infrastructure/terraform/gcp", out).await?;
     run_command(
         "terraform",
         &["apply", "-auto-approve", &format!("-var=environment={}", environment)],
         "infrastructure/terraform/gcp",
+        out,
     ).await?;
 
     Ok(())
 }
 
-async fn deploy_azure(environment: &str, region: Option<&str>) -> Result<()> {
+async fn deploy_azure(environment: &str, region: Option<&str>, out: &Output) -> Result<()> {
     let region = region.unwrap_or("eastus");
     info!("Deploying to Azure region: {}", region);
 
-    run_command("terraform", &["init"], "infrastructure/terraform/azure").await?;
+    run_command("terraform", &["init"], "infrastructure/terraform/azure", out).await?;
     run_command(
         "terraform",
         &["apply", "-auto-approve", &format!("-var=environment={}", environment)],
         "infrastructure/terraform/azure",
+        out,
     ).await?;
 
     Ok(())
 }
 
-async fn pre_deploy_check() -> Result<()> {
-    println!("{}", "🔍 Pre-deployment checks".bold());
+/// Roll `image_tag` out to each Deployment in turn, stopping at the first rollback
+async fn roll_out(rollout: &RolloutArgs, image_tag: &str, options: &ClusterOptions, out: &Output) -> Result<()> {
+    let config = RolloutConfig::default()
+        .with_strategy(rollout.strategy)
+        .with_image_tag(image_tag)
+        .with_canary_steps(rollout.canary_steps.clone())
+        .with_analysis_window(rollout.analysis_window.to_std()?)
+        .with_health_url(
+            &rollout
+                .health_url
+                .clone()
+                .unwrap_or_else(|| hub_endpoint(&hub_api_url(), "/health/ready")),
+        )
+        .with_slo(RolloutSlo {
+            max_error_rate: rollout.max_error_rate,
+            max_p95_latency: std::time::Duration::from_millis(rollout.max_latency_ms),
+        });
+    let client = K8sClient::connect(
+        options.namespace.clone(),
+        options.kubeconfig.clone(),
+        options.context.clone(),
+    )
+    .await?;
+    let controller = RolloutController::new(client, config)?;
+
+    for name in &rollout.deployments {
+        out.section(name, format!("🚦 Rolling out {} ({})", name, rollout.strategy.as_str()).bold());
+        match controller.rollout(name).await? {
+            RolloutOutcome::Promoted => {
+                out.pass(name, format!("promoted to {}", image_tag));
+            }
+            RolloutOutcome::RolledBack { phase, reason } => {
+                out.fail(name, format!("rolled back during {}: {}", phase, reason));
+                anyhow::bail!("Rollout of {} stopped after {} was rolled back", image_tag, name);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn pre_deploy_check(out: &Output) -> Result<()> {
+    out.section("pre-deploy", "🔍 Pre-deployment checks".bold());
 
     // Check required tools
-    check_command("kubectl", &["version", "--client"]).await?;
-    check_command("terraform", &["version"]).await?;
-    check_command("docker", &["version"]).await?;
+    check_command("kubectl", &["version", "--client"], out).await?;
+    check_command("terraform", &["version"], out).await?;
+    check_command("docker", &["version"], out).await?;
 
-    println!("{}", "✅ All required tools available".green());
+    out.line("✅ All required tools available".green());
     Ok(())
 }
 
 // ========== Validation ==========
 
-async fn validate(target: &str, verbose: bool) -> Result<()> {
-    println!("{}", format!("🔍 Validating: {}", target).bold());
+async fn validate(target: &str, verbose: bool, options: &ClusterOptions, out: &Output) -> Result<()> {
+    out.line(format!("🔍 Validating: {}", target).bold());
 
     match target {
         "all" => {
-            validate_k8s(verbose).await?;
-            validate_databases(verbose).await?;
-            validate_services(verbose).await?;
+            let cluster = Cluster::connect(options.clone()).await?;
+            validate_k8s(&cluster, verbose, out).await?;
+            validate_databases(&cluster, verbose, out).await?;
+            validate_services(verbose, out).await?;
         }
-        "k8s" => validate_k8s(verbose).await?,
-        "databases" => validate_databases(verbose).await?,
-        "api" | "frontend" => validate_services(verbose).await?,
+        "k8s" => validate_k8s(&Cluster::connect(options.clone()).await?, verbose, out).await?,
+        "databases" => validate_databases(&Cluster::connect(options.clone()).await?, verbose, out).await?,
+        "api" | "frontend" => validate_services(verbose, out).await?,
         _ => anyhow::bail!("Unknown validation target: {}", target),
     }
 
-    println!("{}", "✅ Validation complete!".bold().green());
+    out.line("✅ Validation complete!".bold().green());
     Ok(())
 }
 
-async fn validate_k8s(verbose: bool) -> Result<()> {
+async fn validate_k8s(cluster: &Cluster, verbose: bool, out: &Output) -> Result<()> {
     info!("Validating Kubernetes cluster...");
+    out.section("k8s", "=== Kubernetes ===".bold());
 
     // Check cluster connectivity
-    run_command("kubectl", &["cluster-info"], ".").await?;
+    let version = cluster.server_version().await?;
+    out.pass("Cluster", format!("{} (via {})", version, cluster.backend()));
 
     // Check all pods are running
-    let output = run_command_output("kubectl", &["get", "pods", "-A"]).await?;
+    let pods = cluster.pods(None, true).await?;
 
     if verbose {
-        println!("{}", output);
+        for pod in &pods {
+            out.line(format!(
+                "  {:<24} {:<48} {}",
+                pod.metadata.namespace.as_deref().unwrap_or_default(),
+                pod.metadata.name.as_deref().unwrap_or_default(),
+                K8sClient::get_pod_phase(pod)
+            ));
+        }
     }
 
-    // Count non-running pods
-    let non_running = output.lines()
-        .filter(|line| !line.contains("Running") && !line.contains("NAMESPACE"))
+    // Count pods that are neither running nor completed
+    let non_running = pods
+        .iter()
+        .filter(|pod| !matches!(K8sClient::get_pod_phase(pod).as_str(), "Running" | "Succeeded"))
         .count();
 
     if non_running > 0 {
-        warn!("{} pods are not in Running state", non_running);
+        out.warn("Pods", format!("{} of {} not in Running state", non_running, pods.len()));
+    } else {
+        out.pass("Pods", format!("{} running", pods.len()));
     }
-
-    println!("{}", "✅ Kubernetes cluster validated".green());
     Ok(())
 }
 
-async fn validate_databases(verbose: bool) -> Result<()> {
+async fn validate_databases(cluster: &Cluster, verbose: bool, out: &Output) -> Result<()> {
     info!("Validating databases...");
-
-    // Check TimescaleDB
-    if let Ok(_) = run_command("kubectl", &["exec", "-it", "timescaledb-0", "--", "psql", "-c", "SELECT 1"], ".").await {
-        println!("{}", "✅ TimescaleDB: OK".green());
-    } else {
-        error!("❌ TimescaleDB: FAILED");
-    }
-
-    // Check Redis
-    if let Ok(_) = run_command("kubectl", &["exec", "-it", "redis-0", "--", "redis-cli", "ping"], ".").await {
-        println!("{}", "✅ Redis: OK".green());
-    } else {
-        error!("❌ Redis: FAILED");
-    }
-
-    // Check Kafka
-    if let Ok(_) = run_command("kubectl", &["exec", "-it", "kafka-0", "--", "kafka-topics.sh", "--list", "--bootstrap-server", "localhost:9092"], ".").await {
-        println!("{}", "✅ Kafka: OK".green());
-    } else {
-        error!("❌ Kafka: FAILED");
+    out.section("databases", "=== Databases ===".bold());
+
+    let checks: [(&str, &str, &[&str]); 3] = [
+        ("TimescaleDB", "timescaledb-0", &["psql", "-U", "postgres", "-c", "SELECT 1"]),
+        ("Redis", "redis-0", &["redis-cli", "ping"]),
+        ("Kafka", "kafka-0", &["kafka-topics.sh", "--list", "--bootstrap-server", "localhost:9092"]),
+    ];
+
+    for (name, pod, command) in checks {
+        match cluster.exec(pod, command).await {
+            Ok(_) => out.pass(name, "OK"),
+            Err(e) if verbose => out.fail(name, format!("FAILED ({:#})", e)),
+            Err(_) => out.fail(name, "FAILED"),
+        }
     }
 
     Ok(())
 }
 
-async fn validate_services(_verbose: bool) -> Result<()> {
+async fn validate_services(_verbose: bool, out: &Output) -> Result<()> {
     info!("Validating services...");
+    out.section("services", "=== Services ===".bold());
 
     // Check API health endpoint
-    if let Ok(response) = reqwest::get("http://localhost:3000/health").await {
+    if let Ok(response) = reqwest::get(hub_endpoint(&hub_api_url(), "/health")).await {
         if response.status().is_success() {
-            println!("{}", "✅ API: OK".green());
+            out.pass("API", "OK");
         } else {
-            error!("❌ API: FAILED (status: {})", response.status());
+            out.fail("API", format!("FAILED (status: {})", response.status()));
         }
     } else {
-        error!("❌ API: UNREACHABLE");
+        out.fail("API", "UNREACHABLE");
     }
 
     Ok(())
@@ -355,54 +1050,55 @@ async fn validate_services(_verbose: bool) -> Result<()> {
 
 // ========== Database Operations ==========
 
-async fn db_init(database: &str, dry_run: bool) -> Result<()> {
-    println!("{}", format!("🗄️  Initializing database: {}", database).bold());
+async fn db_init(database: &str, dry_run: bool, out: &Output) -> Result<()> {
+    out.line(format!("🗄️  Initializing database: {}", database).bold());
 
     if dry_run {
-        println!("{}", "[DRY RUN] Would initialize database".yellow());
+        out.line("[DRY RUN] Would initialize database".yellow());
         return Ok(());
     }
 
     match database {
-        "timescaledb" => init_timescaledb().await?,
-        "redis" => init_redis().await?,
-        "kafka" => init_kafka().await?,
+        "timescaledb" => init_timescaledb(out).await?,
+        "redis" => init_redis(out).await?,
+        "kafka" => init_kafka(out).await?,
         "all" => {
-            init_timescaledb().await?;
-            init_redis().await?;
-            init_kafka().await?;
+            init_timescaledb(out).await?;
+            init_redis(out).await?;
+            init_kafka(out).await?;
         }
         _ => anyhow::bail!("Unknown database: {}", database),
     }
 
-    println!("{}", "✅ Database initialized!".green());
+    out.line("✅ Database initialized!".green());
     Ok(())
 }
 
-async fn init_timescaledb() -> Result<()> {
+async fn init_timescaledb(out: &Output) -> Result<()> {
     info!("Initializing TimescaleDB...");
 
     // Run schema initialization using sqlx migrations
-    run_command("sqlx", &["migrate", "run"], ".").await?;
+    run_command("sqlx", &["migrate", "run"], ".", out).await?;
 
-    println!("{}", "✅ TimescaleDB initialized".green());
+    out.pass("TimescaleDB", "initialized");
     Ok(())
 }
 
-async fn init_redis() -> Result<()> {
+async fn init_redis(out: &Output) -> Result<()> {
     info!("Initializing Redis cluster...");
 
     run_command(
         "kubectl",
         &["exec", "-it", "redis-0", "--", "redis-cli", "--cluster", "create", "..."],
         ".",
+        out,
     ).await?;
 
-    println!("{}", "✅ Redis initialized".green());
+    out.pass("Redis", "initialized");
     Ok(())
 }
 
-async fn init_kafka() -> Result<()> {
+async fn init_kafka(out: &Output) -> Result<()> {
     info!("Initializing Kafka...");
 
     // Create topics
@@ -420,200 +1116,198 @@ async fn init_kafka() -> Result<()> {
                 "--replication-factor", "3",
             ],
             ".",
+            out,
         ).await?;
     }
 
-    println!("{}", "✅ Kafka initialized".green());
+    out.pass("Kafka", "initialized");
     Ok(())
 }
 
 // ========== Health Checks ==========
 
-async fn health_check(service: &str) -> Result<()> {
-    println!("{}", format!("🏥 Health check: {}", service).bold());
+async fn health_check(service: &str, options: &ClusterOptions, out: &Output) -> Result<()> {
+    out.line(format!("🏥 Health check: {}", service).bold());
 
     match service {
         "all" => {
-            check_api_health().await?;
-            check_database_health().await?;
-            check_kafka_health().await?;
-        }
-        "api" => check_api_health().await?,
-        "databases" => check_database_health().await?,
-        "kafka" => check_kafka_health().await?,
-        "redis" => check_redis_health().await?,
+            check_api_health(out).await?;
+            let cluster = Cluster::connect(options.clone()).await?;
+            check_database_health(&cluster, out).await?;
+            check_kafka_health(&cluster, out).await?;
+        }
+        "api" => check_api_health(out).await?,
+        "databases" => check_database_health(&Cluster::connect(options.clone()).await?, out).await?,
+        "kafka" => check_kafka_health(&Cluster::connect(options.clone()).await?, out).await?,
+        "redis" => check_redis_health(&Cluster::connect(options.clone()).await?, out).await?,
         _ => anyhow::bail!("Unknown service: {}", service),
     }
 
-    println!("{}", "✅ Health check complete!".green());
+    out.line("✅ Health check complete!".green());
     Ok(())
 }
 
-async fn check_api_health() -> Result<()> {
-    match reqwest::get("http://localhost:3000/health").await {
-        Ok(response) => {
-            if response.status().is_success() {
-                println!("{}", "✅ API: Healthy".green());
-            } else {
-                println!("{}", format!("⚠️  API: Degraded ({})", response.status()).yellow());
-            }
+async fn check_api_health(out: &Output) -> Result<()> {
+    let url = std::env::var("API_HEALTH_URL")
+        .unwrap_or_else(|_| hub_endpoint(&hub_api_url(), "/health/ready"));
+
+    out.section("api", "=== API Health Check ===".bold());
+    let response = match reqwest::get(&url).await {
+        Ok(response) => response,
+        Err(_) => {
+            out.fail("API", "Unreachable");
+            return Ok(());
+        }
+    };
+
+    let status = response.status();
+    let report = match response.json::<ReadinessReport>().await {
+        Ok(report) => report,
+        Err(_) if status.is_success() => {
+            out.pass("API", "Healthy");
+            return Ok(());
         }
         Err(_) => {
-            println!("{}", "❌ API: Unreachable".red());
+            out.warn("API", format!("Degraded ({})", status));
+            return Ok(());
+        }
+    };
+
+    match report.status {
+        ComponentStatus::Healthy => out.pass("API", "Healthy"),
+        ComponentStatus::Degraded => out.warn("API", "Degraded"),
+        ComponentStatus::Unhealthy => out.fail("API", "Not ready"),
+    }
+
+    for component in &report.components {
+        let message = component.message.clone().unwrap_or_default();
+        match component.status {
+            ComponentStatus::Healthy => out.pass(&component.name, message),
+            ComponentStatus::Unhealthy if component.critical => out.fail(&component.name, message),
+            ComponentStatus::Degraded | ComponentStatus::Unhealthy => out.warn(&component.name, message),
         }
     }
     Ok(())
 }
 
-async fn check_database_health() -> Result<()> {
-    println!("{}", "=== TimescaleDB Health Check ===".bold());
+/// Number of pods matching `selector` in the Running phase, recording a
+/// failed check when there are none
+async fn running_pods(cluster: &Cluster, selector: &str, out: &Output) -> Result<usize> {
+    let pods = cluster.pods(Some(selector), false).await?;
+    let phases: Vec<String> = pods.iter().map(K8sClient::get_pod_phase).collect();
+    let running = phases.iter().filter(|phase| *phase == "Running").count();
+
+    if running == 0 {
+        let phases = if phases.is_empty() { "none found".to_string() } else { phases.join(" ") };
+        out.fail("Pods", phases);
+    }
+    Ok(running)
+}
+
+async fn check_database_health(cluster: &Cluster, out: &Output) -> Result<()> {
+    out.section("timescaledb", "=== TimescaleDB Health Check ===".bold());
 
     // Check pods are running
-    let output = run_command_output("kubectl", &[
-        "get", "pods", "-l", "app=timescaledb",
-        "-n", "llm-analytics-hub",
-        "-o", "jsonpath={.items[*].status.phase}"
-    ]).await?;
-
-    if output.contains("Running") {
-        println!("{}", "  ✅ Pods: Running".green());
-    } else {
-        println!("{}", format!("  ❌ Pods: {}", output).red());
+    if running_pods(cluster, "app=timescaledb", out).await? == 0 {
         return Ok(());
     }
+    out.pass("Pods", "Running");
 
     // Check database connectivity
-    let pg_ready = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "timescaledb-0",
-        "--", "pg_isready", "-U", "postgres"
-    ]).await;
+    let pg_ready = cluster.exec("timescaledb-0", &["pg_isready", "-U", "postgres"]).await;
 
     match pg_ready {
-        Ok(_) => println!("{}", "  ✅ Database: Accepting connections".green()),
-        Err(_) => println!("{}", "  ❌ Database: Not accepting connections".red()),
+        Ok(_) => out.pass("Database", "Accepting connections"),
+        Err(_) => out.fail("Database", "Not accepting connections"),
     }
 
     // Check active connections
-    let conn_output = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "timescaledb-0", "--",
+    let conn_output = cluster.exec("timescaledb-0", &[
         "psql", "-U", "postgres", "-t", "-c",
         "SELECT count(*) FROM pg_stat_activity WHERE state='active';"
     ]).await;
 
     if let Ok(count) = conn_output {
-        println!("{}", format!("  📊 Active connections: {}", count.trim()).cyan());
+        out.info("Active connections", count.trim());
     }
 
     // Check disk usage
-    let disk_output = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "timescaledb-0", "--",
-        "df", "-h", "/var/lib/postgresql/data"
-    ]).await;
+    let disk_output = cluster.exec("timescaledb-0", &["df", "-h", "/var/lib/postgresql/data"]).await;
 
     if let Ok(disk) = disk_output {
         let lines: Vec<&str> = disk.lines().collect();
         if lines.len() > 1 {
-            println!("{}", format!("  💾 Disk usage: {}", lines[1]).cyan());
+            out.info("Disk usage", lines[1]);
         }
     }
 
     Ok(())
 }
 
-async fn check_kafka_health() -> Result<()> {
-    println!("{}", "=== Kafka Health Check ===".bold());
+async fn check_kafka_health(cluster: &Cluster, out: &Output) -> Result<()> {
+    out.section("kafka", "=== Kafka Health Check ===".bold());
 
     // Check pods are running
-    let output = run_command_output("kubectl", &[
-        "get", "pods", "-l", "app=kafka",
-        "-n", "llm-analytics-hub",
-        "-o", "jsonpath={.items[*].status.phase}"
-    ]).await?;
-
-    let running_count = output.matches("Running").count();
-    if running_count > 0 {
-        println!("{}", format!("  ✅ Pods: {} Running", running_count).green());
-    } else {
-        println!("{}", format!("  ❌ Pods: {}", output).red());
+    let running_count = running_pods(cluster, "app=kafka", out).await?;
+    if running_count == 0 {
         return Ok(());
     }
+    out.pass("Pods", format!("{} Running", running_count));
 
     // Check broker connectivity (using kafka-admin tool would be better)
-    let broker_check = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "kafka-0", "--",
+    let broker_check = cluster.exec("kafka-0", &[
         "kafka-broker-api-versions.sh", "--bootstrap-server", "localhost:9092"
     ]).await;
 
     match broker_check {
-        Ok(_) => println!("{}", "  ✅ Brokers: Responding".green()),
-        Err(_) => println!("{}", "  ❌ Brokers: Not responding".red()),
+        Ok(_) => out.pass("Brokers", "Responding"),
+        Err(_) => out.fail("Brokers", "Not responding"),
     }
 
     // List topics count
-    let topics = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "kafka-0", "--",
+    let topics = cluster.exec("kafka-0", &[
         "kafka-topics.sh", "--list", "--bootstrap-server", "localhost:9092"
     ]).await;
 
     if let Ok(topic_list) = topics {
         let llm_topics = topic_list.lines().filter(|t| t.starts_with("llm-")).count();
-        println!("{}", format!("  📊 LLM Analytics topics: {}/14", llm_topics).cyan());
+        out.info("LLM Analytics topics", format!("{}/14", llm_topics));
     }
 
     Ok(())
 }
 
-async fn check_redis_health() -> Result<()> {
-    println!("{}", "=== Redis Health Check ===".bold());
+async fn check_redis_health(cluster: &Cluster, out: &Output) -> Result<()> {
+    out.section("redis", "=== Redis Health Check ===".bold());
 
     // Check pods are running
-    let output = run_command_output("kubectl", &[
-        "get", "pods", "-l", "app=redis-cluster",
-        "-n", "llm-analytics-hub",
-        "-o", "jsonpath={.items[*].status.phase}"
-    ]).await?;
-
-    let running_count = output.matches("Running").count();
-    if running_count > 0 {
-        println!("{}", format!("  ✅ Pods: {} Running", running_count).green());
-    } else {
-        println!("{}", format!("  ❌ Pods: {}", output).red());
+    let running_count = running_pods(cluster, "app=redis-cluster", out).await?;
+    if running_count == 0 {
         return Ok(());
     }
+    out.pass("Pods", format!("{} Running", running_count));
 
     // Check Redis connectivity
-    let ping = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "redis-cluster-0", "--",
-        "redis-cli", "ping"
-    ]).await;
+    let ping = cluster.exec("redis-cluster-0", &["redis-cli", "ping"]).await;
 
     match ping {
-        Ok(response) if response.contains("PONG") => {
-            println!("{}", "  ✅ Redis: Responding to PING".green());
-        }
-        _ => {
-            println!("{}", "  ❌ Redis: Not responding".red());
-        }
+        Ok(response) if response.contains("PONG") => out.pass("Redis", "Responding to PING"),
+        _ => out.fail("Redis", "Not responding"),
     }
 
     // Check cluster info
-    let cluster_info = run_command_output("kubectl", &[
-        "exec", "-n", "llm-analytics-hub", "redis-cluster-0", "--",
-        "redis-cli", "cluster", "info"
-    ]).await;
+    let cluster_info = cluster.exec("redis-cluster-0", &["redis-cli", "cluster", "info"]).await;
 
     if let Ok(info) = cluster_info {
         if info.contains("cluster_state:ok") {
-            println!("{}", "  ✅ Cluster: State OK".green());
+            out.pass("Cluster", "State OK");
         } else {
-            println!("{}", "  ⚠️  Cluster: Check state".yellow());
+            out.warn("Cluster", "Check state");
         }
 
         // Extract cluster size
-        for line in info.lines() {
-            if line.starts_with("cluster_size:") {
-                println!("{}", format!("  📊 {}", line).cyan());
-            }
+        if let Some(size) = info.lines().find_map(|line| line.strip_prefix("cluster_size:")) {
+            out.info("cluster_size", size.trim());
         }
     }
 
@@ -622,186 +1316,660 @@ async fn check_redis_health() -> Result<()> {
 
 // ========== Build ==========
 
-async fn build(service: &str, push: bool, dry_run: bool) -> Result<()> {
-    println!("{}", format!("🔨 Building: {}", service).bold());
+async fn build(service: &str, push: bool, dry_run: bool, out: &Output) -> Result<()> {
+    out.line(format!("🔨 Building: {}", service).bold());
 
     if dry_run {
-        println!("{}", "[DRY RUN] Would build but not executing".yellow());
+        out.line("[DRY RUN] Would build but not executing".yellow());
         return Ok(());
     }
 
     match service {
         "all" => {
-            build_rust(push).await?;
-            build_api(push).await?;
-            build_frontend(push).await?;
+            build_rust(push, out).await?;
+            build_api(push, out).await?;
+            build_frontend(push, out).await?;
         }
-        "rust" => build_rust(push).await?,
-        "api" => build_api(push).await?,
-        "frontend" => build_frontend(push).await?,
+        "rust" => build_rust(push, out).await?,
+        "api" => build_api(push, out).await?,
+        "frontend" => build_frontend(push, out).await?,
         _ => anyhow::bail!("Unknown service: {}", service),
     }
 
-    println!("{}", "✅ Build complete!".green());
+    out.line("✅ Build complete!".green());
     Ok(())
 }
 
-async fn build_rust(push: bool) -> Result<()> {
+async fn build_rust(push: bool, out: &Output) -> Result<()> {
     info!("Building Rust services...");
 
-    run_command("docker", &["build", "-f", "docker/Dockerfile.rust", "-t", "llm-analytics-hub-rust", "."], ".").await?;
+    run_command("docker", &["build", "-f", "docker/Dockerfile.rust", "-t", "llm-analytics-hub-rust", "."], ".", out).await?;
 
     if push {
-        run_command("docker", &["push", "llm-analytics-hub-rust"], ".").await?;
+        run_command("docker", &["push", "llm-analytics-hub-rust"], ".", out).await?;
     }
 
-    println!("{}", "✅ Rust services built".green());
+    out.pass("Rust services", "built");
     Ok(())
 }
 
-async fn build_api(push: bool) -> Result<()> {
+async fn build_api(push: bool, out: &Output) -> Result<()> {
     info!("Building API...");
 
-    run_command("docker", &["build", "-f", "docker/Dockerfile.api", "-t", "llm-analytics-hub-api", "."], ".").await?;
+    run_command("docker", &["build", "-f", "docker/Dockerfile.api", "-t", "llm-analytics-hub-api", "."], ".", out).await?;
 
     if push {
-        run_command("docker", &["push", "llm-analytics-hub-api"], ".").await?;
+        run_command("docker", &["push", "llm-analytics-hub-api"], ".", out).await?;
     }
 
-    println!("{}", "✅ API built".green());
+    out.pass("API", "built");
     Ok(())
 }
 
-async fn build_frontend(push: bool) -> Result<()> {
+async fn build_frontend(push: bool, out: &Output) -> Result<()> {
     info!("Building Frontend...");
 
-    run_command("docker", &["build", "-f", "docker/Dockerfile.frontend", "-t", "llm-analytics-hub-frontend", "."], ".").await?;
+    run_command("docker", &["build", "-f", "docker/Dockerfile.frontend", "-t", "llm-analytics-hub-frontend", "."], ".", out).await?;
 
     if push {
-        run_command("docker", &["push", "llm-analytics-hub-frontend"], ".").await?;
+        run_command("docker", &["push", "llm-analytics-hub-frontend"], ".", out).await?;
     }
 
-    println!("{}", "✅ Frontend built".green());
+    out.pass("Frontend", "built");
     Ok(())
 }
 
 // ========== Testing ==========
 
-async fn run_tests(test_type: &str, verbose: bool) -> Result<()> {
-    println!("{}", format!("🧪 Running tests: {}", test_type).bold());
+async fn run_tests(test_type: &str, verbose: bool, out: &Output) -> Result<()> {
+    out.line(format!("🧪 Running tests: {}", test_type).bold());
 
     match test_type {
         "all" => {
-            run_command("cargo", &["test"], ".").await?;
-            run_command("npm", &["test"], "api").await?;
-            run_command("npm", &["test"], "frontend").await?;
+            run_command("cargo", &["test"], ".", out).await?;
+            out.pass("cargo test", "");
+            run_command("npm", &["test"], "api", out).await?;
+            out.pass("api", "");
+            run_command("npm", &["test"], "frontend", out).await?;
+            out.pass("frontend", "");
         }
         "unit" => {
-            run_command("cargo", &["test", "--lib"], ".").await?;
+            run_command("cargo", &["test", "--lib"], ".", out).await?;
+            out.pass("cargo test --lib", "");
         }
         "integration" => {
-            run_command("cargo", &["test", "--test", "*"], ".").await?;
+            run_command("cargo", &["test", "--test", "*"], ".", out).await?;
+            out.pass("cargo test --test *", "");
         }
         "e2e" => {
-            run_command("npm", &["run", "test:e2e"], "frontend").await?;
+            run_command("npm", &["run", "test:e2e"], "frontend", out).await?;
+            out.pass("frontend e2e", "");
         }
         _ => anyhow::bail!("Unknown test type: {}", test_type),
     }
 
-    println!("{}", "✅ Tests passed!".green());
+    out.line("✅ Tests passed!".green());
     Ok(())
 }
 
 // ========== Backup & Restore ==========
 
-async fn backup(database: &str, destination: &str, dry_run: bool) -> Result<()> {
-    println!("{}", format!("💾 Backing up: {} to {}", database, destination).bold());
+async fn backup(
+    database: &str,
+    destination: &str,
+    keep: usize,
+    dry_run: bool,
+    options: &ClusterOptions,
+    out: &Output,
+) -> Result<()> {
+    out.line(format!("💾 Backing up: {} to {}", database, destination).bold());
 
     if dry_run {
-        println!("{}", "[DRY RUN] Would backup but not executing".yellow());
+        out.line("[DRY RUN] Would backup but not executing".yellow());
         return Ok(());
     }
 
+    let cluster = Cluster::connect(options.clone()).await?;
     match database {
-        "timescaledb" => backup_timescaledb(destination).await?,
+        "timescaledb" => backup_timescaledb(&cluster, destination, keep, out).await?,
         "all" => {
-            backup_timescaledb(destination).await?;
+            backup_timescaledb(&cluster, destination, keep, out).await?;
         }
         _ => anyhow::bail!("Unknown database: {}", database),
     }
 
-    println!("{}", "✅ Backup complete!".green());
+    out.line("✅ Backup complete!".green());
     Ok(())
 }
 
-async fn backup_timescaledb(destination: &str) -> Result<()> {
+async fn backup_timescaledb(cluster: &Cluster, destination: &str, keep: usize, out: &Output) -> Result<()> {
     info!("Backing up TimescaleDB to {}", destination);
 
-    run_command(
-        "kubectl",
-        &["exec", "-it", "timescaledb-0", "--", "pg_dump", "-Fc", "-f", "/tmp/backup.dump"],
-        ".",
-    ).await?;
+    let dump = ["pg_dump", "-Fc", "-U", "postgres", "llm_analytics"];
+    if BackupDestination::is_url(destination) {
+        return upload_timescaledb(cluster, &dump, destination, keep, out).await;
+    }
 
-    run_command(
-        "kubectl",
-        &["cp", "timescaledb-0:/tmp/backup.dump", destination],
-        ".",
-    ).await?;
+    // Stream the dump straight to the destination rather than staging it in the pod
+    let bytes = cluster
+        .exec_to_file("timescaledb-0", &dump, Path::new(destination))
+        .await?;
 
+    out.pass("TimescaleDB", format!("Wrote {:.1} MiB", bytes as f64 / (1024.0 * 1024.0)));
     Ok(())
 }
 
-async fn restore(backup_file: &str, dry_run: bool) -> Result<()> {
-    println!("{}", format!("🔄 Restoring from: {}", backup_file).bold());
+/// Stream a dump into a multipart upload, record its manifest, and rotate old backups
+async fn upload_timescaledb(cluster: &Cluster, dump: &[&str], destination: &str, keep: usize, out: &Output) -> Result<()> {
+    let store = BackupStore::open(destination)?;
+    let mut upload = store.begin("timescaledb").await?;
+    out.line(format!("Uploading backup {} to {}", upload.backup_id(), store.url()));
 
-    if dry_run {
-        println!("{}", "[DRY RUN] Would restore but not executing".yellow());
-        return Ok(());
+    if let Err(e) = cluster.exec_to_writer("timescaledb-0", dump, upload.writer()).await {
+        store.abort(upload).await;
+        return Err(e);
+    }
+    let manifest = store.finish(upload).await?;
+    out.pass(
+        "TimescaleDB",
+        format!(
+            "Wrote {:.1} MiB in {:.1}s (SHA-256 {})",
+            manifest.size_bytes as f64 / (1024.0 * 1024.0),
+            manifest.duration_secs,
+            manifest.sha256
+        ),
+    );
+
+    let rotated = store.rotate(keep).await?;
+    for backup_id in &rotated {
+        out.line(format!("Rotated out {}", backup_id).dimmed());
     }
+    out.data(&serde_json::json!({ "manifest": manifest, "rotated": rotated }))
+}
 
-    run_command(
-        "kubectl",
-        &["cp", backup_file, "timescaledb-0:/tmp/backup.dump"],
-        ".",
-    ).await?;
+async fn list_backups(destination: &str, json: bool, out: &Output) -> Result<()> {
+    let store = BackupStore::open(destination)?;
+    let manifests: Vec<BackupManifest> = store.list().await?;
 
-    run_command(
-        "kubectl",
-        &["exec", "-it", "timescaledb-0", "--", "pg_restore", "-d", "llm_analytics", "/tmp/backup.dump"],
-        ".",
-    ).await?;
+    if out.is_json() {
+        return out.data(&manifests);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&manifests)?);
+        return Ok(());
+    }
 
-    println!("{}", "✅ Restore complete!".green());
+    println!("{}", format!("💾 Backups at {}", store.url()).bold());
+    if manifests.is_empty() {
+        println!("{}", "No backups".yellow());
+        return Ok(());
+    }
+    println!(
+        "{}",
+        format!(
+            "{:<38} {:<16} {:>10} {:>9}  {}",
+            "ID", "STARTED", "SIZE", "DURATION", "VERIFIED"
+        )
+        .bold()
+    );
+    for manifest in &manifests {
+        let verified = match manifest.verified_at {
+            Some(at) => format!("✓ {}", at.format("%Y-%m-%d %H:%M")).green(),
+            None => "—".dimmed(),
+        };
+        println!(
+            "{:<38} {:<16} {:>6.1} MiB {:>8.1}s  {}",
+            manifest.backup_id,
+            manifest.started_at.format("%Y-%m-%d %H:%M"),
+            manifest.size_bytes as f64 / (1024.0 * 1024.0),
+            manifest.duration_secs,
+            verified
+        );
+    }
     Ok(())
 }
 
-// ========== Scaling ==========
+async fn verify_backup(destination: &str, backup_id: Option<&str>, database_url: &str, dry_run: bool, out: &Output) -> Result<()> {
+    let store = BackupStore::open(destination)?;
+    let mut manifest = store.find(backup_id).await?;
+    out.section(&manifest.backup_id, format!("🧪 Verifying backup {}", manifest.backup_id).bold());
+
+    if dry_run {
+        out.line("[DRY RUN] Would test-restore into a scratch database but not executing".yellow());
+        return Ok(());
+    }
+
+    let result = ScratchRestore::new(database_url).verify(&store, &manifest).await?;
+    for check in &result.checks {
+        if check.passed {
+            out.pass(&check.name, check.message.as_str());
+        } else {
+            out.fail(&check.name, check.message.as_str());
+        }
+    }
+    if !result.valid {
+        anyhow::bail!("Backup {} failed verification", manifest.backup_id);
+    }
+
+    store.mark_verified(&mut manifest).await?;
+    out.line("✅ Backup verified!".green());
+    out.data(&manifest)
+}
 
-async fn scale(service: &str, replicas: u32, dry_run: bool) -> Result<()> {
-    println!("{}", format!("📊 Scaling {} to {} replicas", service, replicas).bold());
+async fn restore(backup_file: &str, backup_id: Option<&str>, dry_run: bool, options: &ClusterOptions, out: &Output) -> Result<()> {
+    out.line(format!("🔄 Restoring from: {}", backup_file).bold());
 
+    if !BackupDestination::is_url(backup_file) && backup_id.is_some() {
+        anyhow::bail!("--backup-id only applies when restoring from a URL");
+    }
     if dry_run {
-        println!("{}", "[DRY RUN] Would scale but not executing".yellow());
+        out.line("[DRY RUN] Would restore but not executing".yellow());
         return Ok(());
     }
 
-    run_command(
-        "kubectl",
-        &["scale", "deployment", service, "--replicas", &replicas.to_string()],
-        ".",
-    ).await?;
+    if BackupDestination::is_url(backup_file) {
+        let store = BackupStore::open(backup_file)?;
+        let manifest = store.find(backup_id).await?;
+        let local = std::env::temp_dir().join(format!("{}.dump", manifest.backup_id));
+
+        // The download fails rather than restoring a dump whose checksum doesn't match
+        out.line(format!("Downloading backup {}", manifest.backup_id));
+        store.download(&manifest, &local).await?;
+        out.pass("Download", format!("{} checksum verified", manifest.backup_id));
+        let result = restore_dump(&local.to_string_lossy(), options, out).await;
+        let _ = std::fs::remove_file(&local);
+        result?;
+    } else {
+        restore_dump(backup_file, options, out).await?;
+    }
 
-    println!("{}", "✅ Scaled successfully!".green());
+    out.line("✅ Restore complete!".green());
     Ok(())
 }
 
-// ========== Connect ==========
+async fn restore_dump(dump_file: &str, options: &ClusterOptions, out: &Output) -> Result<()> {
+    // Uploading the dump needs a stdin stream the exec API cannot close, so
+    // restores still go through kubectl
+    let kubectl = Kubectl::new(options.clone());
+    let target = format!("{}/timescaledb-0:/tmp/backup.dump", options.namespace);
+    let mut copy = kubectl.base_args(false);
+    copy.extend(["cp".to_string(), dump_file.to_string(), target]);
+    run_command("kubectl", &copy.iter().map(String::as_str).collect::<Vec<_>>(), ".", out).await?;
+    out.pass("Upload", "copied dump to timescaledb-0");
+
+    let mut args = kubectl.base_args(true);
+    args.extend(
+        ["exec", "timescaledb-0", "--", "pg_restore", "-d", "llm_analytics", "/tmp/backup.dump"]
+            .map(String::from),
+    );
+    run_command("kubectl", &args.iter().map(String::as_str).collect::<Vec<_>>(), ".", out).await?;
+    out.pass("pg_restore", "restored llm_analytics");
+    Ok(())
+}
 
-async fn connect(service: &str, namespace: &str) -> Result<()> {
-    let (pod, container) = match service.to_lowercase().as_str() {
-        "kafka" => ("kafka-0", None),
-        "redis" => ("redis-master-0", None),
+// ========== Scaling ==========
+
+async fn scale(service: &str, replicas: u32, dry_run: bool, options: &ClusterOptions, out: &Output) -> Result<()> {
+    out.line(format!("📊 Scaling {} to {} replicas", service, replicas).bold());
+
+    if dry_run {
+        out.line("[DRY RUN] Would scale but not executing".yellow());
+        return Ok(());
+    }
+
+    let count = i32::try_from(replicas).context("Replica count out of range")?;
+    let cluster = Cluster::connect(options.clone()).await?;
+
+    // Stateful services (databases, brokers) run as statefulsets
+    match cluster.scale(WorkloadKind::Deployment, service, count).await {
+        Err(e) if matches!(e.downcast_ref::<K8sError>(), Some(K8sError::NotFound { .. })) => {
+            cluster.scale(WorkloadKind::StatefulSet, service, count).await?;
+        }
+        result => result?,
+    }
+
+    out.pass(service, format!("Scaled to {} replicas", replicas));
+    Ok(())
+}
+
+/// Latest sample from the hub's `/health/lag` endpoint
+async fn fetch_lag_report(api_url: &str) -> Result<LagReport> {
+    let url = std::env::var("API_LAG_URL").unwrap_or_else(|_| hub_endpoint(api_url, "/health/lag"));
+
+    reqwest::get(&url)
+        .await
+        .with_context(|| format!("Failed to reach {}", url))?
+        .error_for_status()
+        .context("No consumer lag sample available")?
+        .json()
+        .await
+        .context("Invalid consumer lag report")
+}
+
+/// Fetch the replica recommendation from the hub's `/health/lag` endpoint
+async fn recommended_replicas(api_url: &str, out: &Output) -> Result<u32> {
+    let report = fetch_lag_report(api_url).await?;
+
+    out.info(
+        "Consumer lag",
+        format!(
+            "{} on {}: {} ({:?}), recommending {} replicas",
+            report.group_id, report.topic, report.total_lag, report.level, report.recommended_replicas
+        ),
+    );
+    Ok(report.recommended_replicas)
+}
+
+async fn forecast_replicas(client: &HubClient, service: &str, hours: i64, out: &Output) -> Result<u32> {
+    let request = client
+        .request(reqwest::Method::GET, "/api/v1/recommendations/scaling")
+        .query(&[("service", service.to_string()), ("hours", hours.to_string())]);
+    let recommendations: Vec<ScalingRecommendation> = client.send(request).await?;
+    let recommendation = recommendations
+        .into_iter()
+        .find(|r| r.service == service)
+        .with_context(|| format!("The hub has no scaling recommendation for {}", service))?;
+
+    out.info(
+        "Forecast",
+        format!(
+            "{} peaks at {:.1} rps within {}h ({:.1} rps now), recommending {} replicas",
+            service, recommendation.peak_rps, hours, recommendation.current_rps, recommendation.recommended_replicas
+        ),
+    );
+    Ok(recommendation.recommended_replicas)
+}
+
+// ========== Diagnostics ==========
+
+async fn doctor(
+    database_url: Option<&str>,
+    brokers: &str,
+    migrations_dir: &Path,
+    strict: bool,
+    json: bool,
+    options: &ClusterOptions,
+    out: &Output,
+) -> Result<()> {
+    let thresholds = DoctorThresholds::default();
+
+    let mut checks = doctor_adapters().await;
+    checks.push(doctor_topics(brokers).await);
+    checks.push(match fetch_lag_report(&hub_api_url()).await {
+        Ok(report) => lag_check(&report),
+        Err(e) => DiagnosticCheck::degraded(
+            "kafka:lag",
+            CheckCategory::Kafka,
+            format!("{:#}", e),
+            "Check that event-ingestion is running and API_LAG_URL points at it",
+        ),
+    });
+    match database_url {
+        Some(url) => checks.extend(doctor_database(url, migrations_dir, &thresholds).await),
+        None => checks.push(DiagnosticCheck::degraded(
+            "database",
+            CheckCategory::Database,
+            "No database URL configured",
+            "Set DATABASE_URL or pass --database-url",
+        )),
+    }
+    checks.extend(doctor_disks(options, &thresholds).await);
+    checks.extend(doctor_hub_clock(&thresholds).await);
+
+    let report = DoctorReport::from_checks(checks);
+    if out.is_json() {
+        for check in &report.checks {
+            let message = check.message.as_str();
+            match check.status {
+                ComponentStatus::Healthy => out.pass(&check.name, message),
+                ComponentStatus::Degraded => out.warn(&check.name, message),
+                ComponentStatus::Unhealthy => out.fail(&check.name, message),
+            }
+        }
+        out.data(&report)?;
+    } else if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_doctor_report(&report);
+    }
+
+    if !report.passes(strict) {
+        anyhow::bail!("Doctor found {} problem(s)", report.remediations.len());
+    }
+    Ok(())
+}
+
+/// Whether each upstream adapter endpoint answers HTTP at all
+async fn doctor_adapters() -> Vec<DiagnosticCheck> {
+    let endpoints = match adapter_endpoints() {
+        Ok(endpoints) => endpoints,
+        Err(e) => {
+            return vec![DiagnosticCheck::unhealthy(
+                "adapters",
+                CheckCategory::Adapters,
+                format!("{:#}", e),
+                "Fix the adapter *_ENDPOINT environment variables",
+            )]
+        }
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap_or_default();
+    let probes = endpoints.into_iter().map(|(name, endpoint)| {
+        let client = client.clone();
+        async move {
+            let start = std::time::Instant::now();
+            // Any response at all means the endpoint is reachable
+            let health = match client.get(&endpoint).send().await {
+                Ok(_) => AdapterHealth::healthy(name, start.elapsed().as_millis() as u64),
+                Err(e) => AdapterHealth::unhealthy(name, &format!("{} unreachable: {}", endpoint, e)),
+            };
+            adapter_check(&health)
+        }
+    });
+    futures::future::join_all(probes).await
+}
+
+fn adapter_endpoints() -> Result<Vec<(&'static str, String)>> {
+    Ok(vec![
+        ("observatory", ObservatoryConfig::from_env()?.endpoint),
+        ("costops", CostOpsConfig::from_env()?.endpoint),
+        ("memory_graph", MemoryGraphConfig::from_env()?.endpoint),
+        ("registry", RegistryConfig::from_env()?.endpoint),
+        ("config_manager", ConfigManagerConfig::from_env()?.endpoint),
+    ])
+}
+
+async fn doctor_topics(brokers: &str) -> DiagnosticCheck {
+    let expected: Vec<String> = get_llm_topic_configs().into_iter().map(|t| t.name).collect();
+    let present = match TopicManager::new(brokers) {
+        Ok(manager) => manager.list_topics().await,
+        Err(e) => Err(e),
+    };
+
+    match present {
+        Ok(present) => topics_check(&expected, &present),
+        Err(e) => DiagnosticCheck::unhealthy(
+            "kafka:topics",
+            CheckCategory::Kafka,
+            format!("Cannot reach Kafka at {}: {:#}", brokers, e),
+            "Check KAFKA_BROKERS and that the brokers are running (`llm-ops health --service kafka`)",
+        ),
+    }
+}
+
+/// Migration status and database clock skew
+async fn doctor_database(url: &str, migrations_dir: &Path, thresholds: &DoctorThresholds) -> Vec<DiagnosticCheck> {
+    let pool = match sqlx::postgres::PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(std::time::Duration::from_secs(5))
+        .connect(url)
+        .await
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            return vec![DiagnosticCheck::unhealthy(
+                "database",
+                CheckCategory::Database,
+                format!("Cannot connect: {}", e),
+                "Check DATABASE_URL and that TimescaleDB is running (`llm-ops health --service databases`)",
+            )]
+        }
+    };
+
+    let mut checks = vec![doctor_migrations(&pool, migrations_dir).await.unwrap_or_else(|e| {
+        DiagnosticCheck::unhealthy(
+            "database:migrations",
+            CheckCategory::Database,
+            format!("{:#}", e),
+            "Run `db-migrate status` for details",
+        )
+    })];
+
+    let before = chrono::Utc::now();
+    match sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>("SELECT now()")
+        .fetch_one(&pool)
+        .await
+    {
+        Ok(server) => {
+            let local = before + (chrono::Utc::now() - before) / 2;
+            checks.push(clock_skew_check("database", server - local, thresholds));
+        }
+        Err(e) => warn!("Failed to read database clock: {}", e),
+    }
+
+    pool.close().await;
+    checks
+}
+
+async fn doctor_migrations(pool: &sqlx::PgPool, migrations_dir: &Path) -> Result<DiagnosticCheck> {
+    let migrations = load_migrations(migrations_dir)?;
+
+    let has_table: bool = sqlx::query_scalar("SELECT to_regclass('_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    let rows: Vec<(String, Option<String>)> = if !has_table {
+        Vec::new()
+    } else {
+        // Installs that predate checksum tracking have no checksum column
+        match sqlx::query_as("SELECT name, checksum FROM _migrations ORDER BY id")
+            .fetch_all(pool)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(_) => {
+                sqlx::query_as("SELECT name, NULL::VARCHAR FROM _migrations ORDER BY id")
+                    .fetch_all(pool)
+                    .await?
+            }
+        }
+    };
+    let applied: Vec<AppliedMigration> = rows
+        .into_iter()
+        .map(|(name, checksum)| AppliedMigration { name, checksum })
+        .collect();
+
+    let pending: Vec<&str> = pending_migrations(&migrations, &applied)
+        .into_iter()
+        .map(|m| m.name.as_str())
+        .collect();
+    let drifted: Vec<&str> = verify_applied(&migrations, &applied)
+        .into_iter()
+        .filter(|(_, status)| *status == ChecksumStatus::Modified)
+        .map(|(row, _)| row.name.as_str())
+        .collect();
+    Ok(migrations_check(&pending, &drifted))
+}
+
+/// Usage of the TimescaleDB and Kafka data volumes
+async fn doctor_disks(options: &ClusterOptions, thresholds: &DoctorThresholds) -> Vec<DiagnosticCheck> {
+    let cluster = match Cluster::connect(options.clone()).await {
+        Ok(cluster) => cluster,
+        Err(e) => {
+            return vec![DiagnosticCheck::degraded(
+                "disk",
+                CheckCategory::Capacity,
+                format!("Cannot inspect volumes: {:#}", e),
+                "Pass --kubeconfig/--context for the hub's cluster",
+            )]
+        }
+    };
+
+    let mut checks = Vec::new();
+    for (pod, path) in [("timescaledb-0", "/var/lib/postgresql/data"), ("kafka-0", "/var/lib/kafka/data")] {
+        let check = match cluster.exec(pod, &["df", "-P", path]).await {
+            Ok(output) => match parse_df_usage(&output) {
+                Some(used) => disk_check(pod, used, thresholds),
+                None => DiagnosticCheck::degraded(
+                    format!("disk:{}", pod),
+                    CheckCategory::Capacity,
+                    "Unrecognized df output",
+                    format!("Run `df -P {}` in {} by hand", path, pod),
+                ),
+            },
+            Err(e) => DiagnosticCheck::degraded(
+                format!("disk:{}", pod),
+                CheckCategory::Capacity,
+                format!("{:#}", e),
+                format!("Check that pod {} is running", pod),
+            ),
+        };
+        checks.push(check);
+    }
+    checks
+}
+
+/// Clock skew against the hub, from its HTTP `Date` header
+async fn doctor_hub_clock(thresholds: &DoctorThresholds) -> Option<DiagnosticCheck> {
+    let url = std::env::var("API_HEALTH_URL")
+        .unwrap_or_else(|_| hub_endpoint(&hub_api_url(), "/health/ready"));
+
+    let before = chrono::Utc::now();
+    let response = reqwest::get(&url).await.ok()?;
+    let local = before + (chrono::Utc::now() - before) / 2;
+
+    let date = response.headers().get(reqwest::header::DATE)?.to_str().ok()?;
+    let server = chrono::DateTime::parse_from_rfc2822(date).ok()?;
+    // The header is truncated to whole seconds
+    let server = server.with_timezone(&chrono::Utc) + chrono::Duration::milliseconds(500);
+    Some(clock_skew_check("hub", server - local, thresholds))
+}
+
+fn print_doctor_report(report: &DoctorReport) {
+    println!("{}", "🩺 Diagnostics".bold());
+    for check in &report.checks {
+        let line = format!("{:<28} {}", check.name, check.message);
+        match check.status {
+            ComponentStatus::Healthy => println!("  ✅ {}", line),
+            ComponentStatus::Degraded => println!("  ⚠️  {}", line.yellow()),
+            ComponentStatus::Unhealthy => println!("  ❌ {}", line.red()),
+        }
+    }
+    println!();
+
+    if report.remediations.is_empty() {
+        println!("{}", "✅ No problems found".green());
+        return;
+    }
+    println!("{}", "Remediation, most urgent first:".bold());
+    for remediation in &report.remediations {
+        let rank = format!("{:>2}.", remediation.priority);
+        let rank = match remediation.status {
+            ComponentStatus::Unhealthy => rank.red(),
+            _ => rank.yellow(),
+        };
+        println!("  {} [{}] {}", rank, remediation.check, remediation.action);
+    }
+}
+
+// ========== Connect ==========
+
+async fn connect(service: &str, options: ClusterOptions, out: &Output) -> Result<()> {
+    let (pod, container) = match service.to_lowercase().as_str() {
+        "kafka" => ("kafka-0", None),
+        "redis" => ("redis-master-0", None),
         "timescaledb" | "postgres" => ("timescaledb-0", None),
         _ => {
             return Err(anyhow::anyhow!(
@@ -811,17 +1979,19 @@ async fn connect(service: &str, namespace: &str) -> Result<()> {
         }
     };
 
-    println!("{}", format!("🔌 Connecting to {} (pod: {})...", service, pod).bold().cyan());
-    println!("{}", format!("   Namespace: {}", namespace).dimmed());
-    println!();
+    out.line(format!("🔌 Connecting to {} (pod: {})...", service, pod).bold().cyan());
+    out.line(format!("   Namespace: {}", options.namespace).dimmed());
+    out.line("");
 
-    let mut args = vec!["exec", "-it", "-n", namespace, pod, "--"];
+    // Interactive sessions need a TTY, which only kubectl provides
+    let mut args = Kubectl::new(options).base_args(true);
+    args.extend(["exec".to_string(), "-it".to_string(), pod.to_string()]);
 
     if let Some(c) = container {
-        args.extend(&["-c", c]);
+        args.extend(["-c".to_string(), c.to_string()]);
     }
 
-    args.push("/bin/bash");
+    args.extend(["--".to_string(), "/bin/bash".to_string()]);
 
     let status = Command::new("kubectl")
         .args(&args)
@@ -835,48 +2005,840 @@ async fn connect(service: &str, namespace: &str) -> Result<()> {
         return Err(anyhow::anyhow!("Connection failed with exit code: {:?}", status.code()));
     }
 
+    out.pass(pod, "session closed");
     Ok(())
 }
 
-// ========== Utility Functions ==========
+// ========== Reports ==========
+
+async fn report(
+    report_type: &str,
+    days: i64,
+    config: ComplianceReportConfig,
+    format: &str,
+    output: Option<&str>,
+    database_url: &str,
+    out: &Output,
+) -> Result<()> {
+    if !matches!(format, "json" | "csv") {
+        anyhow::bail!("Unknown report format: {}", format);
+    }
+    if report_type != "compliance" {
+        anyhow::bail!("Unknown report type: {}", report_type);
+    }
 
-async fn run_command(cmd: &str, args: &[&str], dir: &str) -> Result<()> {
-    let output = Command::new(cmd)
-        .args(args)
-        .current_dir(dir)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()
-        .context(format!("Failed to execute: {} {:?}", cmd, args))?;
+    out.line(format!("📋 Generating {} report ({} days)", report_type, days).bold());
 
-    if !output.status.success() {
-        anyhow::bail!("Command failed: {} {:?}", cmd, args);
+    let database = Database::from_url(database_url)
+        .await
+        .context("Failed to connect to database")?;
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::days(days);
+    let report = ComplianceReporter::new(Arc::new(database), config)
+        .generate(start, end)
+        .await?;
+
+    let rendered = match format {
+        "csv" => format!("{}\n{}", report.to_csv(), report.findings_to_csv()),
+        _ => serde_json::to_string_pretty(&report)?,
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, rendered).with_context(|| format!("Failed to write {}", path))?;
+            out.pass("Report", format!("written to {}", path));
+        }
+        None if out.is_json() => out.data(&report)?,
+        None => println!("{}", rendered),
+    }
+
+    if !report.open_findings.is_empty() {
+        warn!("{} open compliance findings", report.open_findings.len());
+        out.warn("Findings", format!("{} open", report.open_findings.len()));
+    }
+    Ok(())
+}
+
+// ========== Hub API ==========
+
+/// Minimal client for the hub's REST API
+struct HubClient {
+    url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+impl HubClient {
+    fn new(args: HubArgs) -> Self {
+        Self {
+            url: args.api_url.trim_end_matches('/').to_string(),
+            api_key: args.api_key,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, format!("{}{}", self.url, path));
+        match &self.api_key {
+            Some(key) => request.header(API_KEY_HEADER, key),
+            None => request,
+        }
+    }
+
+    /// Send a request and unwrap the `data` of the API response
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", self.url))?;
+        let status = response.status();
+        let url = response.url().to_string();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("{} returned {}: {}", url, status, body);
+        }
+        let body: ApiResponse<T> = response
+            .json()
+            .await
+            .with_context(|| format!("Invalid response from {}", url))?;
+        body.data.with_context(|| format!("{} returned no data", url))
+    }
+
+    /// Send a request whose successful response has no body
+    async fn send_empty(&self, request: reqwest::RequestBuilder) -> Result<()> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", self.url))?;
+        let status = response.status();
+        if !status.is_success() {
+            let url = response.url().to_string();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("{} returned {}: {}", url, status, body);
+        }
+        Ok(())
+    }
+}
+
+// ========== Metric Queries ==========
+
+/// Where `query` reads aggregated metrics from
+enum MetricSource {
+    Api(HubClient),
+    Database(String),
+}
+
+async fn query_metric(
+    source: &MetricSource,
+    metric: &str,
+    window: &str,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    format: &str,
+    out: &Output,
+) -> Result<()> {
+    if !matches!(format, "table" | "json" | "csv") {
+        anyhow::bail!("Unknown output format: {}", format);
+    }
+    if start >= end {
+        anyhow::bail!("The start of the range must be before its end");
+    }
+    let time_window = parse_window(window)?;
+
+    let rows = match source {
+        MetricSource::Database(url) => {
+            let database = Database::from_url(url)
+                .await
+                .context("Failed to connect to database")?;
+            database.query_aggregated_metrics(metric, time_window, start, end).await?
+        }
+        MetricSource::Api(client) => {
+            let request = client
+                .request(reqwest::Method::GET, &format!("/api/v1/metrics/{}", metric))
+                .query(&[
+                    ("window", window.to_string()),
+                    ("start", start.to_rfc3339()),
+                    ("end", end.to_rfc3339()),
+                ]);
+            client.send::<Vec<AggregatedMetricRow>>(request).await?
+        }
+    };
+
+    if out.is_json() {
+        return out.data(&rows);
+    }
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&rows)?),
+        "csv" => print!("{}", metric_rows_to_csv(&rows)),
+        _ => print_metric_table(metric, window, &rows),
+    }
+    Ok(())
+}
+
+fn metric_rows_to_csv(rows: &[AggregatedMetricRow]) -> String {
+    let mut csv = String::from("window_start,avg,min,max,p50,p95,p99,count\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            row.window_start.to_rfc3339(),
+            row.avg, row.min, row.max, row.p50, row.p95, row.p99, row.count
+        ));
+    }
+    csv
+}
+
+fn print_metric_table(metric: &str, window: &str, rows: &[AggregatedMetricRow]) {
+    println!("{}", format!("📊 {} ({} windows)", metric, window).bold());
+    if rows.is_empty() {
+        println!("{}", "No data in range".yellow());
+        return;
+    }
+
+    println!(
+        "{}",
+        format!(
+            "{:<20} {:>12} {:>12} {:>12} {:>12} {:>10}",
+            "WINDOW START", "AVG", "MIN", "MAX", "P95", "COUNT"
+        )
+        .bold()
+    );
+    for row in rows {
+        println!(
+            "{:<20} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>10}",
+            row.window_start.format("%Y-%m-%d %H:%M"),
+            row.avg, row.min, row.max, row.p95, row.count
+        );
+    }
+
+    let averages: Vec<f64> = rows.iter().map(|row| row.avg).collect();
+    println!();
+    println!("Trend: {}", sparkline(&averages).cyan());
+}
+
+/// Unicode block sparkline scaled between the series min and max
+fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|v| {
+            if range <= 0.0 || !range.is_finite() {
+                BARS[BARS.len() / 2]
+            } else {
+                BARS[(((v - min) / range) * (BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+// ========== Anomalies & Alerts ==========
+
+async fn list_anomalies(client: &HubClient, hours: i64, limit: i64, unacked: bool, json: bool, out: &Output) -> Result<()> {
+    let request = client.request(reqwest::Method::GET, "/api/v1/anomalies").query(&[
+        ("hours", hours.to_string()),
+        ("limit", limit.to_string()),
+        ("unacknowledged", unacked.to_string()),
+    ]);
+    let anomalies: Vec<AnomalyStatusRow> = client.send(request).await?;
+
+    if out.is_json() {
+        return out.data(&anomalies);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&anomalies)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("🔎 Anomalies in the last {}h", hours).bold());
+    if anomalies.is_empty() {
+        println!("{}", "No anomalies".green());
+        return Ok(());
+    }
+    println!(
+        "{}",
+        format!(
+            "{:<36} {:<16} {:<9} {:<28} {:>12} {:>12}  {}",
+            "ID", "DETECTED", "SEVERITY", "METRIC", "VALUE", "EXPECTED", "ACK"
+        )
+        .bold()
+    );
+    for row in &anomalies {
+        let anomaly = &row.anomaly;
+        let severity = format!("{:<9}", anomaly.severity.to_uppercase());
+        let severity = match anomaly.severity.to_lowercase().as_str() {
+            "critical" => severity.red().bold(),
+            "high" => severity.red(),
+            "medium" => severity.yellow(),
+            _ => severity.normal(),
+        };
+        let ack = match (&row.acknowledged_at, &row.acknowledged_by) {
+            (Some(_), Some(by)) => format!("✓ {}", by).green(),
+            (Some(_), None) => "✓".green(),
+            (None, _) => "—".dimmed(),
+        };
+        println!(
+            "{:<36} {:<16} {} {:<28} {:>12.3} {:>12}  {}",
+            anomaly.anomaly_id,
+            anomaly.detected_at.format("%Y-%m-%d %H:%M"),
+            severity,
+            anomaly.metric_name,
+            anomaly.value,
+            anomaly.expected_value.map(|v| format!("{:.3}", v)).unwrap_or_default(),
+            ack,
+        );
+    }
+
+    let unacknowledged = anomalies.iter().filter(|row| row.acknowledged_at.is_none()).count();
+    println!();
+    println!("{} shown, {} unacknowledged", anomalies.len(), unacknowledged);
+    Ok(())
+}
+
+async fn acknowledge_anomaly(client: &HubClient, anomaly_id: uuid::Uuid, note: Option<String>, out: &Output) -> Result<()> {
+    let request = client
+        .request(reqwest::Method::POST, &format!("/api/v1/anomalies/{}/ack", anomaly_id))
+        .json(&serde_json::json!({ "note": note }));
+    let acknowledged: serde_json::Value = client.send(request).await?;
+    out.pass("Acknowledged", anomaly_id.to_string());
+    out.data(&acknowledged)
+}
+
+async fn list_alerts(
+    client: &HubClient,
+    hours: i64,
+    limit: i64,
+    severity: Option<Severity>,
+    json: bool,
+    out: &Output,
+) -> Result<()> {
+    let mut query = vec![("hours", hours.to_string()), ("limit", limit.to_string())];
+    if let Some(severity) = severity {
+        query.push(("severity", format!("{:?}", severity).to_lowercase()));
+    }
+    let request = client.request(reqwest::Method::GET, "/api/v1/alerts").query(&query);
+    let alerts: Vec<AnalyticsEvent> = client.send(request).await?;
+
+    if out.is_json() {
+        return out.data(&alerts);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&alerts)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("🚨 Alerts in the last {}h", hours).bold());
+    if alerts.is_empty() {
+        println!("{}", "No alerts".green());
+        return Ok(());
+    }
+    for alert in &alerts {
+        println!("{}", format_event(alert));
+    }
+    Ok(())
+}
+
+async fn create_silence(client: &HubClient, silence: &Silence, out: &Output) -> Result<()> {
+    silence.validate()?;
+    let request = client
+        .request(reqwest::Method::POST, "/api/v1/alerts/silences")
+        .json(&serde_json::json!({
+            "alert_type": silence.alert_type,
+            "tags": silence.tags,
+            "duration_minutes": (silence.ends_at - silence.starts_at).num_minutes().max(1),
+            "comment": silence.comment,
+        }));
+    let created: Silence = client.send(request).await?;
+
+    out.line(format!("🔕 Silence {} created", created.silence_id).green());
+    if let Some(alert_type) = &created.alert_type {
+        out.line(format!("Alert type: {}", alert_type.cyan()));
+    }
+    let mut tags: Vec<String> = created.tags.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    tags.sort();
+    if !tags.is_empty() {
+        out.line(format!("Tags: {}", tags.join(" ").cyan()));
+    }
+    out.line(format!("Until: {}", created.ends_at.format("%Y-%m-%d %H:%M UTC")));
+    out.data(&created)
+}
+
+async fn schedule_maintenance(client: &HubClient, window: &MaintenanceWindow, out: &Output) -> Result<()> {
+    window.validate()?;
+    let request = client
+        .request(reqwest::Method::POST, "/api/v1/maintenance-windows")
+        .json(&serde_json::json!({
+            "environment": window.environment,
+            "service": window.service,
+            "action": window.action,
+            "starts_at": window.starts_at,
+            "duration_minutes": (window.ends_at - window.starts_at).num_minutes().max(1),
+            "comment": window.comment,
+        }));
+    let created: MaintenanceWindow = client.send(request).await?;
+
+    out.line(format!("🛠  Maintenance window {} scheduled", created.window_id).green());
+    out.line(format!("Scope: {}", maintenance_scope(&created).cyan()));
+    out.line(format!("Findings: {}", created.action.as_str()));
+    out.line(format!(
+        "From {} until {}",
+        created.starts_at.format("%Y-%m-%d %H:%M UTC"),
+        created.ends_at.format("%Y-%m-%d %H:%M UTC")
+    ));
+    out.data(&created)
+}
+
+async fn list_maintenance(client: &HubClient, json: bool, out: &Output) -> Result<()> {
+    let request = client.request(reqwest::Method::GET, "/api/v1/maintenance-windows");
+    let windows: Vec<MaintenanceWindow> = client.send(request).await?;
+
+    if out.is_json() {
+        return out.data(&windows);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&windows)?);
+        return Ok(());
     }
 
+    println!("{}", "🛠  Maintenance windows".bold());
+    if windows.is_empty() {
+        println!("{}", "No active or scheduled windows".green());
+        return Ok(());
+    }
+    let now = chrono::Utc::now();
+    for window in &windows {
+        let state = if window.is_active(now) { "active".yellow() } else { "scheduled".normal() };
+        println!(
+            "{} {} [{}] {} → {} ({}){}",
+            window.window_id,
+            maintenance_scope(window).cyan(),
+            state,
+            window.starts_at.format("%Y-%m-%d %H:%M"),
+            window.ends_at.format("%Y-%m-%d %H:%M UTC"),
+            window.action.as_str(),
+            window.comment.as_ref().map(|c| format!(" {}", c)).unwrap_or_default()
+        );
+    }
     Ok(())
 }
 
-async fn run_command_output(cmd: &str, args: &[&str]) -> Result<String> {
+async fn end_maintenance(client: &HubClient, window_id: uuid::Uuid, out: &Output) -> Result<()> {
+    let request = client.request(
+        reqwest::Method::DELETE,
+        &format!("/api/v1/maintenance-windows/{}", window_id),
+    );
+    client.send_empty(request).await?;
+    out.pass("Ended maintenance window", window_id.to_string());
+    out.data(&serde_json::json!({ "window_id": window_id }))
+}
+
+/// `environment` or `environment/service`
+fn maintenance_scope(window: &MaintenanceWindow) -> String {
+    match &window.service {
+        Some(service) => format!("{}/{}", window.environment, service),
+        None => window.environment.clone(),
+    }
+}
+
+/// Parse a `key=value` tag argument
+fn parse_tag(value: &str) -> Result<(String, String)> {
+    match value.split_once('=') {
+        Some((key, val)) if !key.is_empty() => Ok((key.to_string(), val.to_string())),
+        _ => anyhow::bail!("Expected key=value, got '{}'", value),
+    }
+}
+
+/// Parse a duration such as `30m`, `2h`, or `1d`
+fn parse_duration(value: &str) -> Result<chrono::Duration> {
+    let split = value.len().saturating_sub(1);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("Invalid duration '{}'", value))?;
+    let duration = match unit {
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        _ => anyhow::bail!("Invalid duration '{}', expected e.g. 30m, 2h, or 1d", value),
+    };
+    if duration <= chrono::Duration::zero() {
+        anyhow::bail!("Duration must be positive");
+    }
+    Ok(duration)
+}
+
+// ========== Event Streaming ==========
+
+/// Which events `events tail` prints
+struct TailFilter {
+    modules: Vec<SourceModule>,
+    min_severity: Option<Severity>,
+    event_types: Vec<EventType>,
+}
+
+impl TailFilter {
+    fn parse(modules: &[String], severity: Option<&str>, event_types: &[String]) -> Result<Self> {
+        Ok(Self {
+            modules: modules
+                .iter()
+                .map(|m| parse_enum("module", m))
+                .collect::<Result<_>>()?,
+            min_severity: severity.map(|s| parse_enum("severity", s)).transpose()?,
+            event_types: event_types
+                .iter()
+                .map(|t| parse_enum("event type", t))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn matches(&self, event: &AnalyticsEvent) -> bool {
+        let common = &event.common;
+        (self.modules.is_empty() || self.modules.contains(&common.source_module))
+            && self.min_severity.as_ref().map_or(true, |min| common.severity >= *min)
+            && (self.event_types.is_empty() || self.event_types.contains(&common.event_type))
+    }
+}
+
+/// Parse a CLI value using the enum's serialized name
+fn parse_enum<T: DeserializeOwned>(kind: &str, value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_lowercase()))
+        .with_context(|| format!("Unknown {}: {}", kind, value))
+}
+
+struct TailOptions {
+    json: bool,
+    from_beginning: bool,
+    limit: Option<u64>,
+}
+
+async fn tail_events(brokers: &str, topic: &str, filter: TailFilter, options: TailOptions) -> Result<()> {
+    // A throwaway group so tailing never moves a real consumer group's offsets
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("group.id", format!("llm-ops-tail-{}", uuid::Uuid::new_v4()))
+        .set("bootstrap.servers", brokers)
+        .set("enable.auto.commit", "false")
+        .set("enable.partition.eof", "false")
+        .set("auto.offset.reset", if options.from_beginning { "earliest" } else { "latest" })
+        .create()
+        .context("Failed to create Kafka consumer")?;
+    consumer
+        .subscribe(&[topic])
+        .with_context(|| format!("Failed to subscribe to {}", topic))?;
+
+    if !options.json {
+        println!("{}", format!("📡 Tailing {} on {} (Ctrl+C to stop)", topic, brokers).bold());
+    }
+
+    let mut shown = 0u64;
+    loop {
+        let message = tokio::select! {
+            message = consumer.recv() => message.context("Kafka receive failed")?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let Some(payload) = message.payload() else {
+            continue;
+        };
+
+        let event: AnalyticsEvent = match serde_json::from_slice(payload) {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("{}", format!("Skipping undecodable message at offset {}: {}", message.offset(), e).yellow());
+                continue;
+            }
+        };
+        if !filter.matches(&event) {
+            continue;
+        }
+
+        if options.json {
+            println!("{}", serde_json::to_string(&event)?);
+        } else {
+            println!("{}", format_event(&event));
+        }
+
+        shown += 1;
+        if options.limit.map_or(false, |limit| shown >= limit) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// One-line, colored rendering of an event
+fn format_event(event: &AnalyticsEvent) -> String {
+    let common = &event.common;
+    let severity = format!("{:<8}", format!("{:?}", common.severity).to_uppercase());
+    let severity = match common.severity {
+        Severity::Critical => severity.red().bold(),
+        Severity::Error => severity.red(),
+        Severity::Warning => severity.yellow(),
+        Severity::Info => severity.green(),
+        Severity::Debug => severity.dimmed(),
+    };
+
+    let summary = match &event.payload {
+        EventPayload::Custom(custom) => custom.custom_type.clone(),
+        payload => {
+            let mut summary = serde_json::to_value(payload)
+                .ok()
+                .and_then(|v| v.get("data").map(|d| d.to_string()))
+                .unwrap_or_default();
+            if summary.chars().count() > 120 {
+                summary = summary.chars().take(117).collect::<String>() + "...";
+            }
+            summary
+        }
+    };
+
+    let mut tags: Vec<String> = common.tags.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    tags.sort();
+
+    format!(
+        "{} {} {} {} {}{}",
+        common.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string().dimmed(),
+        severity,
+        common.source_module.as_str().cyan(),
+        format!("{:?}", common.event_type).to_lowercase().bold(),
+        summary,
+        if tags.is_empty() { String::new() } else { format!(" [{}]", tags.join(" ")) },
+    )
+}
+
+// ========== Output ==========
+
+/// How command results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Colored progress for humans
+    Text,
+    /// One result document on stdout when the command finishes
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => anyhow::bail!("Unknown output format '{}', expected text or json", other),
+        }
+    }
+}
+
+/// Outcome of one step a command performed, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum StepStatus {
+    Info,
+    Ok,
+    Warning,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+struct StepRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    section: Option<String>,
+    name: String,
+    status: StepStatus,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    message: String,
+    /// Time since the previous step, i.e. how long this one took
+    duration_ms: u64,
+}
+
+/// Document printed by `--output json`
+#[derive(Debug, Serialize)]
+struct CommandResult<'a> {
+    command: &'a str,
+    status: StepStatus,
+    started_at: chrono::DateTime<chrono::Utc>,
+    duration_ms: u64,
+    checks: &'a [StepRecord],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+struct OutputState {
+    section: Option<String>,
+    last_step: Instant,
+    steps: Vec<StepRecord>,
+    data: Option<serde_json::Value>,
+}
+
+/// Prints command progress as colored text, or records it for the JSON
+/// result document printed by `finish`
+struct Output {
+    format: OutputFormat,
+    started: Instant,
+    started_at: chrono::DateTime<chrono::Utc>,
+    state: RefCell<OutputState>,
+}
+
+impl Output {
+    fn new(format: OutputFormat) -> Self {
+        let now = Instant::now();
+        Self {
+            format,
+            started: now,
+            started_at: chrono::Utc::now(),
+            state: RefCell::new(OutputState {
+                section: None,
+                last_step: now,
+                steps: Vec::new(),
+                data: None,
+            }),
+        }
+    }
+
+    fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Progress text that has no place in the result document
+    fn line(&self, line: impl std::fmt::Display) {
+        if !self.is_json() {
+            println!("{}", line);
+        }
+    }
+
+    /// Start a group of related checks, e.g. one service's health
+    fn section(&self, name: &str, heading: impl std::fmt::Display) {
+        let mut state = self.state.borrow_mut();
+        state.section = Some(name.to_string());
+        state.last_step = Instant::now();
+        drop(state);
+        self.line(heading);
+    }
+
+    fn pass(&self, name: &str, message: impl Into<String>) {
+        self.step(StepStatus::Ok, name, message.into());
+    }
+
+    fn warn(&self, name: &str, message: impl Into<String>) {
+        self.step(StepStatus::Warning, name, message.into());
+    }
+
+    fn fail(&self, name: &str, message: impl Into<String>) {
+        self.step(StepStatus::Failed, name, message.into());
+    }
+
+    /// A measurement rather than a pass/fail result
+    fn info(&self, name: &str, message: impl Into<String>) {
+        self.step(StepStatus::Info, name, message.into());
+    }
+
+    fn step(&self, status: StepStatus, name: &str, message: String) {
+        let mut state = self.state.borrow_mut();
+        let now = Instant::now();
+        let duration_ms = now.duration_since(state.last_step).as_millis() as u64;
+        state.last_step = now;
+
+        if !self.is_json() {
+            let indent = if state.section.is_some() { "  " } else { "" };
+            let text = if message.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}: {}", name, message)
+            };
+            match status {
+                StepStatus::Info => println!("{}{}", indent, format!("📊 {}", text).cyan()),
+                StepStatus::Ok => println!("{}{}", indent, format!("✅ {}", text).green()),
+                StepStatus::Warning => println!("{}{}", indent, format!("⚠️  {}", text).yellow()),
+                StepStatus::Failed => println!("{}{}", indent, format!("❌ {}", text).red()),
+            }
+        }
+
+        let section = state.section.clone();
+        state.steps.push(StepRecord {
+            section,
+            name: name.to_string(),
+            status,
+            message,
+            duration_ms,
+        });
+    }
+
+    /// Attach the command's payload (listings, reports, query rows) to the
+    /// result document
+    fn data<T: Serialize>(&self, data: &T) -> Result<()> {
+        self.state.borrow_mut().data = Some(serde_json::to_value(data)?);
+        Ok(())
+    }
+
+    /// Where child processes write their stdout; piped in JSON mode so it
+    /// can be moved to stderr
+    fn child_stdout(&self) -> Stdio {
+        if self.is_json() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        }
+    }
+
+    /// Print the result document for `command` in JSON mode
+    fn finish(&self, command: &str, result: &Result<()>) -> Result<()> {
+        if !self.is_json() {
+            return Ok(());
+        }
+
+        let state = self.state.borrow();
+        let worst = state.steps.iter().map(|s| s.status).max();
+        let status = match (result, worst) {
+            (Err(_), _) | (_, Some(StepStatus::Failed)) => StepStatus::Failed,
+            (_, Some(StepStatus::Warning)) => StepStatus::Warning,
+            _ => StepStatus::Ok,
+        };
+        let document = CommandResult {
+            command,
+            status,
+            started_at: self.started_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            checks: &state.steps,
+            data: state.data.as_ref(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        };
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        Ok(())
+    }
+}
+
+// ========== Utility Functions ==========
+
+async fn run_command(cmd: &str, args: &[&str], dir: &str, out: &Output) -> Result<()> {
     let output = Command::new(cmd)
         .args(args)
+        .current_dir(dir)
+        .stdout(out.child_stdout())
+        .stderr(Stdio::inherit())
         .output()
         .context(format!("Failed to execute: {} {:?}", cmd, args))?;
 
+    // Keep stdout for the result document
+    std::io::stderr().write_all(&output.stdout)?;
+
     if !output.status.success() {
         anyhow::bail!("Command failed: {} {:?}", cmd, args);
     }
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    Ok(())
 }
 
-async fn check_command(cmd: &str, args: &[&str]) -> Result<()> {
+async fn check_command(cmd: &str, args: &[&str], out: &Output) -> Result<()> {
     match Command::new(cmd).args(args).output() {
         Ok(output) if output.status.success() => {
-            println!("{} {} {}", "✅".green(), cmd.green(), "available");
+            out.pass(cmd, "available");
             Ok(())
         }
         _ => {
-            println!("{} {} {}", "❌".red(), cmd.red(), "not found");
+            out.fail(cmd, "not found");
             anyhow::bail!("{} is required but not installed", cmd)
         }
     }
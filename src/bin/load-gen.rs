@@ -0,0 +1,544 @@
+//! Load Generator
+//!
+//! Produces synthetic `AnalyticsEvent`s at a configurable rate to Kafka or the
+//! gRPC ingestion endpoint for end-to-end throughput testing.
+//!
+//! Features:
+//! - Realistic event mixes from the `synthetic` scenarios
+//! - Constant, linear, step and spike ramp profiles
+//! - Live throughput/latency report and a final summary in the bench-* layout
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use llm_analytics_hub::grpc::proto::event_ingestion_client::EventIngestionClient;
+use llm_analytics_hub::grpc::proto::EventBatch;
+use llm_analytics_hub::grpc::API_KEY_METADATA;
+use llm_analytics_hub::schemas::events::AnalyticsEvent;
+use llm_analytics_hub::synthetic::{Scenario, SyntheticGenerator};
+use parking_lot::Mutex;
+use rand::Rng;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tonic::transport::Channel;
+use tracing::warn;
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(name = "load-gen")]
+#[command(about = "Synthetic event load generator for LLM Analytics Hub")]
+struct Cli {
+    /// Where to send events
+    #[arg(long, value_enum, default_value = "kafka")]
+    target: Target,
+
+    /// Kafka bootstrap servers
+    #[arg(
+        long,
+        env = "KAFKA_BROKERS",
+        default_value = "kafka.llm-analytics.svc.cluster.local:9092"
+    )]
+    bootstrap_servers: String,
+
+    /// Kafka topic consumed by the processing pipeline
+    #[arg(long, env = "KAFKA_TOPIC", default_value = "llm-analytics-events")]
+    topic: String,
+
+    /// gRPC ingestion endpoint
+    #[arg(long, env = "GRPC_ENDPOINT", default_value = "http://localhost:50051")]
+    grpc_endpoint: String,
+
+    /// API key sent in the x-api-key metadata entry
+    #[arg(long, env = "LOAD_GEN_API_KEY")]
+    api_key: Option<String>,
+
+    /// Peak events per second
+    #[arg(long, default_value = "10000")]
+    rate: u64,
+
+    /// Test duration in seconds
+    #[arg(long, default_value = "60")]
+    duration: u64,
+
+    /// How the rate evolves over the run
+    #[arg(long, value_enum, default_value = "constant")]
+    profile: RampProfile,
+
+    /// Seconds to reach the peak rate with the linear profile
+    #[arg(long, default_value = "30")]
+    ramp_secs: u64,
+
+    /// Number of equal rate steps with the step profile
+    #[arg(long, default_value = "5")]
+    steps: u32,
+
+    /// Events per Kafka flush or gRPC call
+    #[arg(long, default_value = "500")]
+    batch_size: usize,
+
+    /// Batches in flight at once
+    #[arg(long, default_value = "32")]
+    concurrency: usize,
+
+    /// Traffic preset: steady, latency-degradation, cost-spike or security-incident
+    #[arg(long, default_value = "steady")]
+    scenario: Scenario,
+
+    /// Fraction of requests that fail, overriding the scenario
+    #[arg(long)]
+    error_rate: Option<f64>,
+
+    /// Fraction of security events that are threats, overriding the scenario
+    #[arg(long)]
+    threat_rate: Option<f64>,
+
+    /// Tenants events are spread across
+    #[arg(long, default_value = "10")]
+    tenants: u32,
+
+    /// Seconds between live reports
+    #[arg(long, default_value = "5")]
+    report_interval: u64,
+
+    /// RNG seed for reproducible value distributions
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Target {
+    Kafka,
+    Grpc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RampProfile {
+    /// Peak rate for the whole run
+    Constant,
+    /// Ramp from zero to the peak over --ramp-secs, then hold
+    Linear,
+    /// Climb to the peak in --steps equal steps
+    Step,
+    /// Half the peak, with a burst at the peak through the middle tenth of the run
+    Spike,
+}
+
+impl RampProfile {
+    /// Target events/sec `elapsed` into the run
+    fn rate_at(self, cli: &Cli, elapsed: Duration) -> f64 {
+        let peak = cli.rate as f64;
+        let progress = elapsed.as_secs_f64() / cli.duration.max(1) as f64;
+        match self {
+            RampProfile::Constant => peak,
+            RampProfile::Linear => {
+                let ramp = cli.ramp_secs.max(1) as f64;
+                peak * (elapsed.as_secs_f64() / ramp).min(1.0)
+            }
+            RampProfile::Step => {
+                let steps = cli.steps.max(1) as f64;
+                let step = (progress * steps).floor().min(steps - 1.0);
+                peak * (step + 1.0) / steps
+            }
+            RampProfile::Spike => {
+                if (0.45..0.55).contains(&progress) {
+                    peak
+                } else {
+                    peak / 2.0
+                }
+            }
+        }
+    }
+}
+
+// ========== Sinks ==========
+
+enum Sink {
+    Kafka {
+        producer: FutureProducer,
+        topic: String,
+    },
+    Grpc {
+        client: EventIngestionClient<Channel>,
+        api_key: Option<String>,
+    },
+}
+
+impl Sink {
+    async fn connect(cli: &Cli) -> Result<Self> {
+        match cli.target {
+            Target::Kafka => {
+                let producer: FutureProducer = ClientConfig::new()
+                    .set("bootstrap.servers", &cli.bootstrap_servers)
+                    .set("client.id", "llm-load-gen")
+                    .set("acks", "1")
+                    .set("compression.type", "zstd")
+                    .set("linger.ms", "5")
+                    .set("queue.buffering.max.messages", "1000000")
+                    .set("message.timeout.ms", "30000")
+                    .create()
+                    .context("Failed to create Kafka producer")?;
+                Ok(Sink::Kafka {
+                    producer,
+                    topic: cli.topic.clone(),
+                })
+            }
+            Target::Grpc => {
+                let client = EventIngestionClient::connect(cli.grpc_endpoint.clone())
+                    .await
+                    .with_context(|| format!("Failed to connect to {}", cli.grpc_endpoint))?;
+                Ok(Sink::Grpc {
+                    client,
+                    api_key: cli.api_key.clone(),
+                })
+            }
+        }
+    }
+
+    fn clone_handle(&self) -> Self {
+        match self {
+            Sink::Kafka { producer, topic } => Sink::Kafka {
+                producer: producer.clone(),
+                topic: topic.clone(),
+            },
+            Sink::Grpc { client, api_key } => Sink::Grpc {
+                client: client.clone(),
+                api_key: api_key.clone(),
+            },
+        }
+    }
+
+    /// Send a batch, returning how many events were accepted
+    async fn send(&self, events: &[AnalyticsEvent]) -> Result<usize> {
+        match self {
+            Sink::Kafka { producer, topic } => {
+                let payloads = events
+                    .iter()
+                    .map(|event| {
+                        Ok((
+                            event.common.event_id.to_string(),
+                            serde_json::to_vec(event)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let deliveries = payloads.iter().map(|(key, payload)| {
+                    producer.send(
+                        FutureRecord::to(topic).key(key).payload(payload),
+                        Duration::from_secs(30),
+                    )
+                });
+
+                let mut accepted = 0;
+                for result in futures::future::join_all(deliveries).await {
+                    match result {
+                        Ok(_) => accepted += 1,
+                        Err((e, _)) => warn!("Delivery failed: {}", e),
+                    }
+                }
+                Ok(accepted)
+            }
+            Sink::Grpc { client, api_key } => {
+                let batch = EventBatch {
+                    batch_id: Uuid::new_v4().to_string(),
+                    events: events
+                        .iter()
+                        .map(serde_json::to_vec)
+                        .collect::<std::result::Result<_, _>>()?,
+                };
+                let mut request = tonic::Request::new(futures::stream::iter(vec![batch]));
+                if let Some(key) = api_key {
+                    request
+                        .metadata_mut()
+                        .insert(API_KEY_METADATA, key.parse().context("Invalid API key")?);
+                }
+
+                let summary = client.clone().ingest_events(request).await?.into_inner();
+                if let Some(rejection) = summary.rejections.first() {
+                    warn!(
+                        "{} events rejected, first: {}",
+                        summary.rejected, rejection.reason
+                    );
+                }
+                Ok(summary.accepted as usize)
+            }
+        }
+    }
+}
+
+// ========== Reporting ==========
+
+/// Counters shared by the batch tasks
+#[derive(Default)]
+struct Stats {
+    sent: u64,
+    failed: u64,
+    /// Batch acknowledgement latencies since the last live report
+    interval_ms: Vec<f64>,
+    /// All batch acknowledgement latencies
+    total_ms: Vec<f64>,
+}
+
+impl Stats {
+    fn record(&mut self, sent: usize, failed: usize, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        self.sent += sent as u64;
+        self.failed += failed as u64;
+        self.interval_ms.push(ms);
+        self.total_ms.push(ms);
+    }
+}
+
+fn percentile(sorted_data: &[f64], p: f64) -> f64 {
+    if sorted_data.is_empty() {
+        return 0.0;
+    }
+    let index = ((p / 100.0) * (sorted_data.len() - 1) as f64).round() as usize;
+    sorted_data[index.min(sorted_data.len() - 1)]
+}
+
+fn sorted(mut values: Vec<f64>) -> Vec<f64> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values
+}
+
+fn log_info(msg: &str) {
+    println!("{}", format!("[LOAD-GEN] {}", msg).blue().bold());
+}
+
+fn log_success(msg: &str) {
+    println!("{}", format!("[LOAD-GEN] {}", msg).green().bold());
+}
+
+fn log_warn(msg: &str) {
+    println!("{}", format!("[LOAD-GEN] {}", msg).yellow().bold());
+}
+
+fn print_live(
+    elapsed: Duration,
+    target: f64,
+    sent_in_interval: u64,
+    interval: Duration,
+    latencies: Vec<f64>,
+    failed: u64,
+) {
+    let latencies = sorted(latencies);
+    let achieved = sent_in_interval as f64 / interval.as_secs_f64().max(f64::EPSILON);
+    let rate = format!("{:>10.0}/s", achieved);
+    let rate = if achieved < target * 0.95 {
+        rate.yellow()
+    } else {
+        rate.green()
+    };
+    println!(
+        "  [{:>5.0}s] target {:>10.0}/s  sent {}  batch p50 {:>8.2}ms  p99 {:>8.2}ms  failed {}",
+        elapsed.as_secs_f64(),
+        target,
+        rate,
+        percentile(&latencies, 50.0),
+        percentile(&latencies, 99.0),
+        failed
+    );
+}
+
+fn print_summary(stats: &Stats, total_time: Duration, batch_size: usize) {
+    let latencies = sorted(stats.total_ms.clone());
+    let avg = if latencies.is_empty() {
+        0.0
+    } else {
+        latencies.iter().sum::<f64>() / latencies.len() as f64
+    };
+
+    log_success("Load Test Results:");
+    println!(
+        "  Events Sent:        {}",
+        format!("{:>12}", stats.sent).cyan()
+    );
+    println!(
+        "  Events Failed:      {}",
+        format!("{:>12}", stats.failed).cyan()
+    );
+    println!(
+        "  Total Time:         {}",
+        format!("{:>10.2}s", total_time.as_secs_f64()).cyan()
+    );
+    println!(
+        "  Events/sec:         {}",
+        format!(
+            "{:>12.0}",
+            stats.sent as f64 / total_time.as_secs_f64().max(f64::EPSILON)
+        )
+        .green()
+        .bold()
+    );
+    println!(
+        "  Batch Size:         {}",
+        format!("{:>12}", batch_size).cyan()
+    );
+    println!(
+        "  Avg Batch Time:     {}",
+        format!("{:>10.2}ms", avg).cyan()
+    );
+    println!(
+        "  P50 Batch Time:     {}",
+        format!("{:>10.2}ms", percentile(&latencies, 50.0)).cyan()
+    );
+    println!(
+        "  P95 Batch Time:     {}",
+        format!("{:>10.2}ms", percentile(&latencies, 95.0)).yellow()
+    );
+    println!(
+        "  P99 Batch Time:     {}",
+        format!("{:>10.2}ms", percentile(&latencies, 99.0))
+            .yellow()
+            .bold()
+    );
+    println!();
+}
+
+// ========== Driver ==========
+
+async fn run(cli: Cli) -> Result<()> {
+    let sink = Sink::connect(&cli).await?;
+    let batch_size = cli.batch_size.max(1);
+    let duration = Duration::from_secs(cli.duration.max(1));
+    let report_interval = Duration::from_secs(cli.report_interval.max(1));
+    let seed = cli.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut config = cli.scenario.config().with_seed(seed);
+    config.tenants = cli.tenants;
+    if let Some(error_rate) = cli.error_rate {
+        config.error_rate = error_rate;
+    }
+    if let Some(threat_rate) = cli.threat_rate {
+        config.threat_rate = threat_rate;
+    }
+    let mut generator = SyntheticGenerator::new(config);
+    let permits = Arc::new(Semaphore::new(cli.concurrency.max(1)));
+    let stats = Arc::new(Mutex::new(Stats::default()));
+
+    log_info(&format!(
+        "Seed {}; sending for {}s",
+        seed,
+        duration.as_secs()
+    ));
+    println!();
+
+    let start = Instant::now();
+    let mut ticker = tokio::time::interval(Duration::from_millis(10));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last_tick = start;
+    let mut owed = 0.0_f64;
+    let mut last_report = start;
+    let mut sent_at_last_report = 0;
+
+    while start.elapsed() < duration {
+        ticker.tick().await;
+        let now = Instant::now();
+        let elapsed = now - start;
+        let target = cli.profile.rate_at(&cli, elapsed);
+        owed += target * (now - last_tick).as_secs_f64();
+        last_tick = now;
+
+        // Dispatch whole batches; a saturated sink shows up as a rate shortfall
+        while owed >= batch_size as f64 {
+            let Ok(permit) = permits.clone().try_acquire_owned() else {
+                break;
+            };
+            owed -= batch_size as f64;
+
+            let events = generator.batch(batch_size, Utc::now());
+            let sink = sink.clone_handle();
+            let stats = stats.clone();
+            tokio::spawn(async move {
+                let sent = Instant::now();
+                let accepted = match sink.send(&events).await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Batch failed: {}", e);
+                        0
+                    }
+                };
+                stats
+                    .lock()
+                    .record(accepted, events.len() - accepted, sent.elapsed());
+                drop(permit);
+            });
+        }
+        // Don't let a backlog build up while the sink is saturated
+        owed = owed.min(target.max(batch_size as f64));
+
+        if now - last_report >= report_interval {
+            let (sent, failed, latencies) = {
+                let mut stats = stats.lock();
+                (
+                    stats.sent,
+                    stats.failed,
+                    std::mem::take(&mut stats.interval_ms),
+                )
+            };
+            print_live(
+                elapsed,
+                target,
+                sent - sent_at_last_report,
+                now - last_report,
+                latencies,
+                failed,
+            );
+            sent_at_last_report = sent;
+            last_report = now;
+        }
+    }
+
+    // Wait for in-flight batches
+    let _drain = permits
+        .acquire_many(cli.concurrency.max(1) as u32)
+        .await
+        .context("Batch semaphore closed")?;
+    let total_time = start.elapsed();
+
+    println!();
+    let stats = stats.lock();
+    print_summary(&stats, total_time, batch_size);
+    if stats.failed > 0 {
+        log_warn(&format!("{} events were not accepted", stats.failed));
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+
+    log_info("===========================================");
+    log_info("      LLM Analytics Hub Load Generator");
+    log_info("===========================================");
+    println!();
+    match cli.target {
+        Target::Kafka => println!(
+            "  Target:       kafka {} ({})",
+            cli.topic, cli.bootstrap_servers
+        ),
+        Target::Grpc => println!("  Target:       grpc {}", cli.grpc_endpoint),
+    }
+    println!("  Peak rate:    {} events/s", cli.rate);
+    println!("  Profile:      {:?}", cli.profile);
+    println!("  Duration:     {}s", cli.duration);
+    println!("  Batch size:   {}", cli.batch_size);
+    println!("  Concurrency:  {}", cli.concurrency);
+    println!("  Scenario:     {:?}", cli.scenario);
+    println!();
+
+    run(cli).await?;
+
+    log_success("===========================================");
+    log_success("   Load test completed!");
+    log_success("===========================================");
+
+    Ok(())
+}
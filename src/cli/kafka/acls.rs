@@ -10,7 +10,7 @@ use crate::common::{
     ExecutionContext,
 };
 use crate::infra::k8s::K8sClient;
-use crate::infra::kafka::{AclManager, get_standard_acls};
+use crate::infra::kafka::{AclManager, AclSpec, get_standard_acls};
 
 /// Kafka ACL management arguments
 #[derive(Debug, Args)]
@@ -70,11 +70,9 @@ impl KafkaAclsArgs {
         }
 
         // Get ACL configurations
-        let acl_configs = if let Some(_config_file) = config_file {
-            // TODO: Load from YAML file
-            anyhow::bail!("Custom config file not yet implemented");
-        } else {
-            get_standard_acls()
+        let acl_configs = match config_file {
+            Some(path) => AclSpec::from_file(path)?.acls,
+            None => get_standard_acls(),
         };
 
         println!("Creating {} standard ACLs...\n", acl_configs.len());
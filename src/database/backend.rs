@@ -0,0 +1,295 @@
+//! Storage Backends
+//!
+//! `StorageBackend` is the write and aggregate-read surface the pipeline needs
+//! from an event store. TimescaleDB (`Database`) is the default implementation;
+//! ClickHouse can be selected with `STORAGE_BACKEND=clickhouse` for deployments
+//! whose event volume outgrows Postgres, and `STORAGE_BACKEND=memory` keeps
+//! everything in process for benchmarks and local runs.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::clickhouse::{ClickHouseBackend, ClickHouseConfig};
+use super::memory::{tags_contain, MemoryBackend};
+use super::{AggregatedMetricRow, Database, EnvironmentScope, EventFilter};
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::retention::TablePolicy;
+use crate::schemas::events::AnalyticsEvent;
+
+/// Which event store the hub writes to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackendKind {
+    #[default]
+    Timescale,
+    Clickhouse,
+    /// Process memory; nothing survives a restart
+    Memory,
+}
+
+impl FromStr for StorageBackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown storage backend '{}'", s))
+    }
+}
+
+/// Storage backend selection
+#[derive(Debug, Clone, Default)]
+pub struct StorageConfig {
+    pub backend: StorageBackendKind,
+    /// Used when `backend` is ClickHouse
+    pub clickhouse: ClickHouseConfig,
+}
+
+impl StorageConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            backend: match std::env::var("STORAGE_BACKEND") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.backend,
+            },
+            clickhouse: ClickHouseConfig::from_env(),
+        })
+    }
+}
+
+/// Event count per bucket, source module, and event type
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EventCountRow {
+    pub bucket: DateTime<Utc>,
+    pub source_module: String,
+    pub event_type: String,
+    pub count: i64,
+}
+
+/// Event store used by the ingestion pipeline
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Backend name for logs and health output
+    fn name(&self) -> &'static str;
+
+    /// Store a batch of events, skipping already-stored event IDs.
+    /// Returns the number of rows written.
+    async fn insert_events_batch(&self, events: &[AnalyticsEvent]) -> Result<u64>;
+
+    /// Insert or replace one aggregated metric window
+    async fn store_aggregated_metric(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+        tags: &serde_json::Value,
+        measures: &StatisticalMeasures,
+    ) -> Result<()>;
+
+    /// Aggregated metric windows in `[start, end)`, oldest first
+    async fn query_aggregated_metrics(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>>;
+
+    /// Aggregated metric windows in `[start, end)` whose tags contain `tags`,
+    /// oldest first
+    async fn query_aggregated_metrics_tagged(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: &serde_json::Value,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        let rows = self
+            .query_aggregated_metrics(metric_name, time_window, start, end)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter(|row| tags_contain(&row.tags, tags))
+            .collect())
+    }
+
+    /// Events in `[start, end)` for the default environment, newest first
+    async fn query_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>>;
+
+    /// Events in `[start, end)` matching `filter` within an environment scope,
+    /// newest first
+    async fn search_events(
+        &self,
+        _filter: &EventFilter,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+        _limit: Option<i64>,
+        _scope: &EnvironmentScope,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        anyhow::bail!("The {} backend does not support event search", self.name())
+    }
+
+    /// Event counts bucketed by `window` for the default environment
+    async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>>;
+
+    /// Apply per-table retention (and compression, where the backend supports it)
+    async fn apply_retention(&self, policies: &[TablePolicy]) -> Result<()>;
+
+    /// Verify the backend is reachable
+    async fn health_check(&self) -> Result<()>;
+}
+
+#[async_trait]
+impl StorageBackend for Database {
+    fn name(&self) -> &'static str {
+        "timescale"
+    }
+
+    async fn insert_events_batch(&self, events: &[AnalyticsEvent]) -> Result<u64> {
+        Database::insert_events_batch(self, events).await
+    }
+
+    async fn store_aggregated_metric(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+        tags: &serde_json::Value,
+        measures: &StatisticalMeasures,
+    ) -> Result<()> {
+        Database::store_aggregated_metric(
+            self,
+            metric_name,
+            time_window,
+            window_start,
+            tags,
+            measures,
+        )
+        .await
+    }
+
+    async fn query_aggregated_metrics(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        Database::query_aggregated_metrics(self, metric_name, time_window, start, end).await
+    }
+
+    async fn query_aggregated_metrics_tagged(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: &serde_json::Value,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        Database::query_aggregated_metrics_tagged(self, metric_name, time_window, start, end, tags)
+            .await
+    }
+
+    async fn query_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        Database::query_events(self, start, end, limit).await
+    }
+
+    async fn search_events(
+        &self,
+        filter: &EventFilter,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+        scope: &EnvironmentScope,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        Database::search_events(self, filter, start, end, limit, scope).await
+    }
+
+    async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        Database::query_event_counts(self, window, start, end).await
+    }
+
+    async fn apply_retention(&self, policies: &[TablePolicy]) -> Result<()> {
+        for policy in policies {
+            self.set_retention_policy(&policy.table, policy.retention_days)
+                .await?;
+            self.set_compression_policy(&policy.table, policy.compress_after_days)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        Database::health_check(self).await.map(|_| ())
+    }
+}
+
+/// Build the configured backend. TimescaleDB reuses the existing `database`.
+pub async fn connect_backend(
+    config: &StorageConfig,
+    database: Arc<Database>,
+) -> Result<Arc<dyn StorageBackend>> {
+    match config.backend {
+        StorageBackendKind::Timescale => Ok(database),
+        StorageBackendKind::Clickhouse => {
+            let backend = ClickHouseBackend::new(config.clickhouse.clone())?;
+            backend.ensure_schema().await?;
+            info!(url = %config.clickhouse.url, "Using ClickHouse storage backend");
+            Ok(Arc::new(backend))
+        }
+        StorageBackendKind::Memory => {
+            warn!("Using in-memory storage backend; events are not persisted");
+            Ok(Arc::new(MemoryBackend::new()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_kind_parsing() {
+        assert_eq!(
+            "clickhouse".parse::<StorageBackendKind>().unwrap(),
+            StorageBackendKind::Clickhouse
+        );
+        assert_eq!(
+            "Timescale".parse::<StorageBackendKind>().unwrap(),
+            StorageBackendKind::Timescale
+        );
+        assert_eq!(
+            "memory".parse::<StorageBackendKind>().unwrap(),
+            StorageBackendKind::Memory
+        );
+        assert!("cassandra".parse::<StorageBackendKind>().is_err());
+        assert_eq!(StorageBackendKind::default(), StorageBackendKind::Timescale);
+    }
+}
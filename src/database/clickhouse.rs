@@ -0,0 +1,613 @@
+//! ClickHouse Storage Backend
+//!
+//! Stores events and aggregated metrics in ClickHouse over its HTTP interface.
+//! Rows are written as `JSONEachRow` batches into `ReplacingMergeTree` tables so
+//! redelivered events and re-aggregated windows collapse on merge, and
+//! retention is enforced with table TTLs instead of scheduled drop jobs.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use super::backend::{EventCountRow, StorageBackend};
+use super::environment::default_environment;
+use super::AggregatedMetricRow;
+use crate::export::prometheus::HubMetrics;
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::retention::TablePolicy;
+use crate::schemas::events::AnalyticsEvent;
+
+/// ClickHouse connection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickHouseConfig {
+    /// HTTP interface URL
+    pub url: String,
+    pub database: String,
+    pub username: String,
+    pub password: Option<String>,
+    pub timeout_secs: u64,
+    /// Environment that queries are scoped to
+    pub environment: String,
+}
+
+impl Default for ClickHouseConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:8123".to_string(),
+            database: "llm_analytics".to_string(),
+            username: "default".to_string(),
+            password: None,
+            timeout_secs: 30,
+            environment: default_environment(),
+        }
+    }
+}
+
+impl ClickHouseConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            url: std::env::var("CLICKHOUSE_URL").unwrap_or(defaults.url),
+            database: std::env::var("CLICKHOUSE_DATABASE").unwrap_or(defaults.database),
+            username: std::env::var("CLICKHOUSE_USER").unwrap_or(defaults.username),
+            password: std::env::var("CLICKHOUSE_PASSWORD").ok(),
+            timeout_secs: std::env::var("CLICKHOUSE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_secs),
+            ..defaults
+        }
+    }
+}
+
+/// Tables created by `ensure_schema`, with the column their TTL is based on
+const TTL_COLUMNS: [(&str, &str); 2] = [
+    ("events", "timestamp"),
+    ("aggregated_metrics", "window_start"),
+];
+
+/// Text format for DateTime64(3) parameters and inserted values
+fn ch_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+/// Plain string form of a serde enum (e.g. `"llm-cost-ops"`)
+fn enum_str<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Event row as written to `events`
+#[derive(Debug, Serialize)]
+struct EventInsertRow<'a> {
+    event_id: Uuid,
+    timestamp: String,
+    source_module: &'a str,
+    event_type: String,
+    correlation_id: Option<Uuid>,
+    parent_event_id: Option<Uuid>,
+    schema_version: &'a str,
+    severity: String,
+    environment: &'a str,
+    tags: &'a HashMap<String, String>,
+    payload: String,
+}
+
+impl<'a> EventInsertRow<'a> {
+    fn from_event(event: &'a AnalyticsEvent) -> Result<Self> {
+        let common = &event.common;
+        Ok(Self {
+            event_id: common.event_id,
+            timestamp: ch_timestamp(common.timestamp),
+            source_module: common.source_module.as_str(),
+            event_type: enum_str(&common.event_type),
+            correlation_id: common.correlation_id,
+            parent_event_id: common.parent_event_id,
+            schema_version: &common.schema_version,
+            severity: enum_str(&common.severity),
+            environment: &common.environment,
+            tags: &common.tags,
+            payload: serde_json::to_string(event).context("Failed to serialize event")?,
+        })
+    }
+}
+
+/// Aggregated metric row as stored; tags are kept as canonical JSON text
+#[derive(Debug, Serialize, Deserialize)]
+struct MetricRow {
+    metric_name: String,
+    time_window: String,
+    window_start: String,
+    tags: String,
+    avg: f64,
+    min: f64,
+    max: f64,
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    stddev: Option<f64>,
+    count: i64,
+    sum: f64,
+    /// Histogram as JSON text
+    #[serde(default)]
+    histogram: Option<String>,
+}
+
+impl MetricRow {
+    fn into_row(self) -> Result<AggregatedMetricRow> {
+        Ok(AggregatedMetricRow {
+            metric_name: self.metric_name,
+            time_window: self.time_window,
+            window_start: DateTime::parse_from_rfc3339(&self.window_start)?.with_timezone(&Utc),
+            tags: serde_json::from_str(&self.tags).unwrap_or(serde_json::Value::Null),
+            avg: self.avg,
+            min: self.min,
+            max: self.max,
+            p50: self.p50,
+            p95: self.p95,
+            p99: self.p99,
+            stddev: self.stddev,
+            count: self.count,
+            sum: self.sum,
+            histogram: self
+                .histogram
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .context("Invalid histogram in aggregated metric row")?
+                .map(Json),
+        })
+    }
+}
+
+/// ClickHouse implementation of `StorageBackend`
+pub struct ClickHouseBackend {
+    client: reqwest::Client,
+    config: ClickHouseConfig,
+}
+
+impl ClickHouseBackend {
+    pub fn new(config: ClickHouseConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build ClickHouse HTTP client")?;
+
+        Ok(Self { client, config })
+    }
+
+    /// Send a statement with an optional body and named `{name:Type}` parameters
+    async fn send(
+        &self,
+        sql: &str,
+        body: Option<String>,
+        params: &[(&str, String)],
+    ) -> Result<String> {
+        self.post(Some(&self.config.database), sql, body, params)
+            .await
+    }
+
+    async fn post(
+        &self,
+        database: Option<&str>,
+        sql: &str,
+        body: Option<String>,
+        params: &[(&str, String)],
+    ) -> Result<String> {
+        let mut query: Vec<(String, String)> = vec![
+            ("date_time_output_format".to_string(), "iso".to_string()),
+            (
+                "output_format_json_quote_64bit_integers".to_string(),
+                "0".to_string(),
+            ),
+        ];
+        if let Some(database) = database {
+            query.push(("database".to_string(), database.to_string()));
+        }
+        query.extend(
+            params
+                .iter()
+                .map(|(k, v)| (format!("param_{}", k), v.clone())),
+        );
+
+        // Statements with a row body carry the SQL in the query string
+        let request = match body {
+            Some(rows) => {
+                query.push(("query".to_string(), sql.to_string()));
+                self.client.post(&self.config.url).query(&query).body(rows)
+            }
+            None => self
+                .client
+                .post(&self.config.url)
+                .query(&query)
+                .body(sql.to_string()),
+        };
+        let request = request.basic_auth(&self.config.username, self.config.password.as_ref());
+
+        let response = request.send().await.context("ClickHouse request failed")?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            bail!("ClickHouse returned {}: {}", status, text.trim());
+        }
+
+        Ok(text)
+    }
+
+    /// Execute a statement that returns no rows
+    async fn execute(&self, sql: &str) -> Result<()> {
+        self.send(sql, None, &[]).await.map(|_| ())
+    }
+
+    /// Run a query and decode its `JSONEachRow` output
+    async fn query_rows<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: &[(&str, String)],
+    ) -> Result<Vec<T>> {
+        let text = self
+            .send(&format!("{} FORMAT JSONEachRow", sql), None, params)
+            .await?;
+
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Failed to decode ClickHouse row"))
+            .collect()
+    }
+
+    /// Insert rows into a table as one `JSONEachRow` batch
+    async fn insert_rows<T: Serialize>(&self, table: &str, rows: &[T]) -> Result<()> {
+        let mut body = String::new();
+        for row in rows {
+            body.push_str(&serde_json::to_string(row)?);
+            body.push('\n');
+        }
+
+        self.send(
+            &format!("INSERT INTO {} FORMAT JSONEachRow", table),
+            Some(body),
+            &[],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Create the database and tables if they do not exist
+    #[instrument(skip(self))]
+    pub async fn ensure_schema(&self) -> Result<()> {
+        // The target database may not exist yet, so this runs without one selected
+        self.post(
+            None,
+            &format!("CREATE DATABASE IF NOT EXISTS {}", self.config.database),
+            None,
+            &[],
+        )
+        .await?;
+
+        self.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS events (
+                event_id UUID,
+                timestamp DateTime64(3, 'UTC'),
+                source_module LowCardinality(String),
+                event_type LowCardinality(String),
+                correlation_id Nullable(UUID),
+                parent_event_id Nullable(UUID),
+                schema_version LowCardinality(String),
+                severity LowCardinality(String),
+                environment LowCardinality(String),
+                tags Map(String, String),
+                payload String CODEC(ZSTD(3))
+            )
+            ENGINE = ReplacingMergeTree
+            PARTITION BY toYYYYMMDD(timestamp)
+            ORDER BY (environment, source_module, timestamp, event_id)
+            "#,
+        )
+        .await
+        .context("Failed to create events table")?;
+
+        self.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS aggregated_metrics (
+                metric_name LowCardinality(String),
+                time_window LowCardinality(String),
+                window_start DateTime64(3, 'UTC'),
+                tags String,
+                avg Float64,
+                min Float64,
+                max Float64,
+                p50 Float64,
+                p95 Float64,
+                p99 Float64,
+                stddev Nullable(Float64),
+                count Int64,
+                sum Float64,
+                histogram Nullable(String) CODEC(ZSTD(3)),
+                updated_at DateTime64(3, 'UTC') DEFAULT now64(3)
+            )
+            ENGINE = ReplacingMergeTree(updated_at)
+            PARTITION BY toYYYYMM(window_start)
+            ORDER BY (metric_name, time_window, window_start, tags)
+            "#,
+        )
+        .await
+        .context("Failed to create aggregated_metrics table")?;
+
+        // Tables created before histograms were stored
+        self.execute(
+            "ALTER TABLE aggregated_metrics ADD COLUMN IF NOT EXISTS histogram Nullable(String) CODEC(ZSTD(3))",
+        )
+        .await
+        .context("Failed to add histogram column to aggregated_metrics")?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for ClickHouseBackend {
+    fn name(&self) -> &'static str {
+        "clickhouse"
+    }
+
+    #[instrument(skip(self, events))]
+    async fn insert_events_batch(&self, events: &[AnalyticsEvent]) -> Result<u64> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        HubMetrics::global().observe_db_batch("events", events.len());
+
+        let rows = events
+            .iter()
+            .map(EventInsertRow::from_event)
+            .collect::<Result<Vec<_>>>()?;
+        self.insert_rows("events", &rows)
+            .await
+            .context("Failed to insert events into ClickHouse")?;
+
+        // Duplicates are collapsed at merge time, so every row counts as written
+        Ok(rows.len() as u64)
+    }
+
+    #[instrument(skip(self))]
+    async fn store_aggregated_metric(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+        tags: &serde_json::Value,
+        measures: &StatisticalMeasures,
+    ) -> Result<()> {
+        let row = MetricRow {
+            metric_name: metric_name.to_string(),
+            time_window: time_window.as_str().to_string(),
+            window_start: ch_timestamp(window_start),
+            tags: tags.to_string(),
+            avg: measures.avg,
+            min: measures.min,
+            max: measures.max,
+            p50: measures.p50,
+            p95: measures.p95,
+            p99: measures.p99,
+            stddev: measures.stddev,
+            count: measures.count as i64,
+            sum: measures.sum,
+            histogram: measures
+                .histogram
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        };
+
+        self.insert_rows("aggregated_metrics", &[row])
+            .await
+            .context("Failed to store aggregated metric in ClickHouse")
+    }
+
+    #[instrument(skip(self))]
+    async fn query_aggregated_metrics(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        let rows: Vec<MetricRow> = self
+            .query_rows(
+                r#"
+                SELECT
+                    metric_name, time_window, window_start, tags,
+                    avg, min, max, p50, p95, p99, stddev, count, sum, histogram
+                FROM aggregated_metrics FINAL
+                WHERE metric_name = {metric:String}
+                  AND time_window = {window:String}
+                  AND window_start >= {start:DateTime64(3, 'UTC')}
+                  AND window_start < {end:DateTime64(3, 'UTC')}
+                ORDER BY window_start ASC
+                "#,
+                &[
+                    ("metric", metric_name.to_string()),
+                    ("window", time_window.as_str().to_string()),
+                    ("start", ch_timestamp(start)),
+                    ("end", ch_timestamp(end)),
+                ],
+            )
+            .await
+            .context("Failed to query aggregated metrics from ClickHouse")?;
+
+        rows.into_iter().map(MetricRow::into_row).collect()
+    }
+
+    #[instrument(skip(self))]
+    async fn query_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        #[derive(Deserialize)]
+        struct PayloadRow {
+            payload: String,
+        }
+
+        let rows: Vec<PayloadRow> = self
+            .query_rows(
+                r#"
+                SELECT payload
+                FROM events FINAL
+                WHERE timestamp >= {start:DateTime64(3, 'UTC')}
+                  AND timestamp < {end:DateTime64(3, 'UTC')}
+                  AND environment = {environment:String}
+                ORDER BY timestamp DESC
+                LIMIT {limit:UInt64}
+                "#,
+                &[
+                    ("start", ch_timestamp(start)),
+                    ("end", ch_timestamp(end)),
+                    ("environment", self.config.environment.clone()),
+                    ("limit", limit.unwrap_or(1000).max(0).to_string()),
+                ],
+            )
+            .await
+            .context("Failed to query events from ClickHouse")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| serde_json::from_str(&row.payload).ok())
+            .collect())
+    }
+
+    #[instrument(skip(self))]
+    async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        #[derive(Deserialize)]
+        struct CountRow {
+            bucket: String,
+            source_module: String,
+            event_type: String,
+            count: i64,
+        }
+
+        let rows: Vec<CountRow> = self
+            .query_rows(
+                r#"
+                SELECT
+                    toStartOfInterval(timestamp, toIntervalSecond({bucket_secs:UInt32})) AS bucket,
+                    source_module,
+                    event_type,
+                    toInt64(uniqExact(event_id)) AS count
+                FROM events
+                WHERE timestamp >= {start:DateTime64(3, 'UTC')}
+                  AND timestamp < {end:DateTime64(3, 'UTC')}
+                  AND environment = {environment:String}
+                GROUP BY bucket, source_module, event_type
+                ORDER BY bucket ASC
+                "#,
+                &[
+                    ("bucket_secs", window.to_seconds().to_string()),
+                    ("start", ch_timestamp(start)),
+                    ("end", ch_timestamp(end)),
+                    ("environment", self.config.environment.clone()),
+                ],
+            )
+            .await
+            .context("Failed to query event counts from ClickHouse")?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(EventCountRow {
+                    bucket: DateTime::parse_from_rfc3339(&row.bucket)?.with_timezone(&Utc),
+                    source_module: row.source_module,
+                    event_type: row.event_type,
+                    count: row.count,
+                })
+            })
+            .collect()
+    }
+
+    /// Translate retention days into table TTLs. ClickHouse compresses every
+    /// part on write, so compression settings are ignored.
+    #[instrument(skip(self, policies))]
+    async fn apply_retention(&self, policies: &[TablePolicy]) -> Result<()> {
+        for policy in policies {
+            let Some((table, column)) = TTL_COLUMNS.iter().find(|(t, _)| *t == policy.table) else {
+                debug!(table = %policy.table, "No ClickHouse table for policy, skipping");
+                continue;
+            };
+
+            let sql = match policy.retention_days {
+                Some(days) => format!(
+                    "ALTER TABLE {} MODIFY TTL toDateTime({}) + INTERVAL {} DAY DELETE",
+                    table, column, days
+                ),
+                None => format!("ALTER TABLE {} REMOVE TTL", table),
+            };
+
+            match self.execute(&sql).await {
+                Ok(()) => {}
+                // Removing a TTL that was never set is not an error
+                Err(e) if policy.retention_days.is_none() && e.to_string().contains("TTL") => {
+                    debug!(table = %table, "No TTL to remove");
+                }
+                Err(e) => return Err(e.context(format!("Failed to set TTL on {}", table))),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.execute("SELECT 1").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_and_enum_formatting() {
+        let ts = DateTime::parse_from_rfc3339("2025-03-01T12:30:45.123Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(ch_timestamp(ts), "2025-03-01 12:30:45.123");
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Kind {
+            TokenUsage,
+        }
+        assert_eq!(enum_str(&Kind::TokenUsage), "token_usage");
+    }
+
+    #[test]
+    fn test_metric_row_decoding() {
+        let row: MetricRow = serde_json::from_str(
+            r#"{"metric_name":"latency","time_window":"5m","window_start":"2025-03-01T12:30:00.000Z",
+                "tags":"{\"model\":\"gpt-4\"}","avg":1.0,"min":0.5,"max":2.0,"p50":1.0,
+                "p95":1.8,"p99":1.9,"stddev":null,"count":10,"sum":10.0}"#,
+        )
+        .unwrap();
+
+        let row = row.into_row().unwrap();
+        assert_eq!(row.tags["model"], "gpt-4");
+        assert_eq!(row.count, 10);
+        assert_eq!(row.window_start.timestamp() % 300, 0);
+    }
+}
@@ -0,0 +1,210 @@
+//! Environment Scoping
+//!
+//! Restricts event queries to a single environment unless cross-environment access is granted.
+//! API callers pick an environment with the `x-analytics-environment` header; only the
+//! admin routes can grant a cross-environment scope. Aggregated series carry their
+//! environment as the `environment` tag, so metric queries are scoped by tag.
+
+use crate::auth::{Principal, Scope};
+use anyhow::Result;
+use serde::Serialize;
+
+/// Environment used when none is configured
+pub const DEFAULT_ENVIRONMENT: &str = "production";
+
+/// Header naming the environment an API request reads
+pub const ENVIRONMENT_HEADER: &str = "x-analytics-environment";
+
+/// Tag carrying the environment of an aggregated series
+pub const ENVIRONMENT_TAG: &str = "environment";
+
+/// Default environment, read from `ANALYTICS_ENVIRONMENT`
+pub fn default_environment() -> String {
+    std::env::var("ANALYTICS_ENVIRONMENT").unwrap_or_else(|_| DEFAULT_ENVIRONMENT.to_string())
+}
+
+/// Environment scope applied to a query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum EnvironmentScope {
+    /// The database's default environment
+    #[default]
+    Default,
+    /// A single named environment
+    Environment { name: String },
+    /// Several or all environments (admin opt-in only)
+    CrossEnvironment(CrossEnvironmentGrant),
+}
+
+/// Proof that a caller explicitly opted into a cross-environment query
+///
+/// Can only be obtained through [`EnvironmentScope::cross_environment`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrossEnvironmentGrant {
+    environments: Option<Vec<String>>,
+    granted_to: String,
+}
+
+impl CrossEnvironmentGrant {
+    /// Environments covered by the grant (`None` means all)
+    pub fn environments(&self) -> Option<&[String]> {
+        self.environments.as_deref()
+    }
+
+    /// Principal the grant was issued to
+    pub fn granted_to(&self) -> &str {
+        &self.granted_to
+    }
+}
+
+impl EnvironmentScope {
+    /// Scope to a single environment
+    pub fn environment(name: impl Into<String>) -> Self {
+        Self::Environment { name: name.into() }
+    }
+
+    /// Scope of an API request naming `requested`, the default when it names none
+    pub fn requested(requested: Option<&str>) -> Self {
+        match requested.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) => Self::environment(name),
+            None => Self::Default,
+        }
+    }
+
+    /// Opt into a cross-environment query
+    ///
+    /// `environments` of `None` spans every environment. Callers without the admin
+    /// scope are rejected.
+    pub fn cross_environment(
+        principal: &Principal,
+        environments: Option<Vec<String>>,
+    ) -> Result<Self> {
+        if !principal.scope.grants(Scope::Admin) {
+            anyhow::bail!(
+                "Cross-environment queries require admin privileges (requested by {})",
+                principal.subject
+            );
+        }
+
+        tracing::info!(
+            principal = %principal.subject,
+            ?environments,
+            "Cross-environment query granted"
+        );
+
+        Ok(Self::CrossEnvironment(CrossEnvironmentGrant {
+            environments,
+            granted_to: principal.subject.clone(),
+        }))
+    }
+
+    /// Whether this scope spans more than one environment
+    pub fn is_cross_environment(&self) -> bool {
+        matches!(self, Self::CrossEnvironment(_))
+    }
+
+    /// Environments to bind as a `text[]` filter; `None` means no filter
+    pub fn filter(&self, default_environment: &str) -> Option<Vec<String>> {
+        match self {
+            Self::Default => Some(vec![default_environment.to_string()]),
+            Self::Environment { name } => Some(vec![name.clone()]),
+            Self::CrossEnvironment(grant) => grant.environments.clone(),
+        }
+    }
+
+    /// Restrict the tags selecting aggregated metrics to this scope
+    ///
+    /// A scope covering several environments adds no tag; those series are told
+    /// apart by their `environment` tag.
+    pub fn scope_tags(
+        &self,
+        default_environment: &str,
+        tags: Option<serde_json::Value>,
+    ) -> Option<serde_json::Value> {
+        let environment = match self.filter(default_environment).as_deref() {
+            Some([environment]) => environment.clone(),
+            _ => return tags,
+        };
+        let mut tags = match tags {
+            Some(serde_json::Value::Object(tags)) => tags,
+            _ => serde_json::Map::new(),
+        };
+        tags.insert(ENVIRONMENT_TAG.to_string(), environment.into());
+        Some(serde_json::Value::Object(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthMethod;
+
+    fn principal(subject: &str, scope: Scope) -> Principal {
+        Principal {
+            subject: subject.to_string(),
+            scope,
+            method: AuthMethod::ApiKey,
+            tenant_id: None,
+        }
+    }
+
+    #[test]
+    fn test_default_scope_uses_default_environment() {
+        let scope = EnvironmentScope::default();
+        assert_eq!(scope.filter("staging"), Some(vec!["staging".to_string()]));
+        assert!(!scope.is_cross_environment());
+    }
+
+    #[test]
+    fn test_named_environment_scope() {
+        let scope = EnvironmentScope::environment("dev");
+        assert_eq!(scope.filter("production"), Some(vec!["dev".to_string()]));
+        assert_eq!(EnvironmentScope::requested(Some("dev")), scope);
+        assert_eq!(
+            EnvironmentScope::requested(Some(" ")),
+            EnvironmentScope::Default
+        );
+        assert_eq!(EnvironmentScope::requested(None), EnvironmentScope::Default);
+    }
+
+    #[test]
+    fn test_scope_tags_pin_one_environment() {
+        let tenant = serde_json::json!({"tenant_id": "acme"});
+        assert_eq!(
+            EnvironmentScope::environment("dev").scope_tags("production", Some(tenant.clone())),
+            Some(serde_json::json!({"tenant_id": "acme", "environment": "dev"}))
+        );
+        assert_eq!(
+            EnvironmentScope::Default.scope_tags("production", None),
+            Some(serde_json::json!({"environment": "production"}))
+        );
+
+        let admin = principal("ops", Scope::Admin);
+        let all = EnvironmentScope::cross_environment(&admin, None).unwrap();
+        assert_eq!(
+            all.scope_tags("production", Some(tenant.clone())),
+            Some(tenant)
+        );
+    }
+
+    #[test]
+    fn test_cross_environment_requires_admin() {
+        let alice = principal("alice", Scope::Write);
+        assert!(EnvironmentScope::cross_environment(&alice, None).is_err());
+
+        let admin = principal("ops", Scope::Admin);
+        let scope = EnvironmentScope::cross_environment(&admin, None).unwrap();
+        assert!(scope.is_cross_environment());
+        assert_eq!(scope.filter("production"), None);
+
+        let scope = EnvironmentScope::cross_environment(
+            &admin,
+            Some(vec!["dev".to_string(), "staging".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(
+            scope.filter("production"),
+            Some(vec!["dev".to_string(), "staging".to_string()])
+        );
+    }
+}
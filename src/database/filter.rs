@@ -0,0 +1,511 @@
+//! Event Search Filters
+//!
+//! Typed filter expressions for event search, compiled to parameterized SQL
+//! against the events hypertable.
+
+use crate::schemas::events::{AnalyticsEvent, EventType, Severity, SourceModule};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+/// Maximum nesting depth accepted for boolean combinations
+pub const MAX_FILTER_DEPTH: usize = 16;
+
+/// All severities in ascending order
+const SEVERITIES: [Severity; 5] = [
+    Severity::Debug,
+    Severity::Info,
+    Severity::Warning,
+    Severity::Error,
+    Severity::Critical,
+];
+
+/// Boolean filter expression over events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EventFilter {
+    /// All sub-filters must match
+    And { filters: Vec<EventFilter> },
+    /// At least one sub-filter must match
+    Or { filters: Vec<EventFilter> },
+    /// Sub-filter must not match
+    Not { filter: Box<EventFilter> },
+    /// Event comes from the given module
+    SourceModule { module: SourceModule },
+    /// Event has the given type
+    EventType { event_type: EventType },
+    /// Event severity lies within an inclusive range
+    Severity {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<Severity>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<Severity>,
+    },
+    /// Tag matches
+    Tag { key: String, matcher: TagMatcher },
+    /// JSONPath condition against the stored event document
+    Payload(PayloadCondition),
+}
+
+/// Matcher applied to a single tag value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "match", content = "value", rename_all = "snake_case")]
+pub enum TagMatcher {
+    Exists,
+    Equals(String),
+    Prefix(String),
+    In(Vec<String>),
+}
+
+/// Comparison applied to the value selected by a JSONPath
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Exists,
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Comparison {
+    fn as_jsonpath_op(&self) -> Option<&'static str> {
+        match self {
+            Comparison::Exists => None,
+            Comparison::Eq => Some("=="),
+            Comparison::Ne => Some("!="),
+            Comparison::Gt => Some(">"),
+            Comparison::Gte => Some(">="),
+            Comparison::Lt => Some("<"),
+            Comparison::Lte => Some("<="),
+        }
+    }
+}
+
+/// JSONPath condition, e.g. `$.payload.data.total_latency_ms > 1000`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadCondition {
+    /// JSONPath into the stored event, rooted at `$`
+    pub path: String,
+    pub comparison: Comparison,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub value: serde_json::Value,
+}
+
+impl EventFilter {
+    // ========== Builders ==========
+
+    pub fn source_module(module: SourceModule) -> Self {
+        EventFilter::SourceModule { module }
+    }
+
+    pub fn event_type(event_type: EventType) -> Self {
+        EventFilter::EventType { event_type }
+    }
+
+    /// Severity at or above `min`
+    pub fn severity_at_least(min: Severity) -> Self {
+        EventFilter::Severity {
+            min: Some(min),
+            max: None,
+        }
+    }
+
+    /// Severity within an inclusive range
+    pub fn severity_between(min: Severity, max: Severity) -> Self {
+        EventFilter::Severity {
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+
+    pub fn tag(key: impl Into<String>, matcher: TagMatcher) -> Self {
+        EventFilter::Tag {
+            key: key.into(),
+            matcher,
+        }
+    }
+
+    pub fn tag_equals(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::tag(key, TagMatcher::Equals(value.into()))
+    }
+
+    pub fn payload(
+        path: impl Into<String>,
+        comparison: Comparison,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        EventFilter::Payload(PayloadCondition {
+            path: path.into(),
+            comparison,
+            value: value.into(),
+        })
+    }
+
+    /// Combine with another filter, flattening nested conjunctions
+    pub fn and(self, other: EventFilter) -> Self {
+        match self {
+            EventFilter::And { mut filters } => {
+                filters.push(other);
+                EventFilter::And { filters }
+            }
+            first => EventFilter::And {
+                filters: vec![first, other],
+            },
+        }
+    }
+
+    /// Combine with another filter, flattening nested disjunctions
+    pub fn or(self, other: EventFilter) -> Self {
+        match self {
+            EventFilter::Or { mut filters } => {
+                filters.push(other);
+                EventFilter::Or { filters }
+            }
+            first => EventFilter::Or {
+                filters: vec![first, other],
+            },
+        }
+    }
+
+    /// Negate this filter
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        EventFilter::Not {
+            filter: Box::new(self),
+        }
+    }
+
+    // ========== Validation ==========
+
+    /// Reject filters that are too deep or contain malformed paths
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at(1)
+    }
+
+    fn validate_at(&self, depth: usize) -> Result<()> {
+        if depth > MAX_FILTER_DEPTH {
+            anyhow::bail!("Filter nesting exceeds maximum depth of {}", MAX_FILTER_DEPTH);
+        }
+
+        match self {
+            EventFilter::And { filters } | EventFilter::Or { filters } => {
+                for f in filters {
+                    f.validate_at(depth + 1)?;
+                }
+                Ok(())
+            }
+            EventFilter::Not { filter } => filter.validate_at(depth + 1),
+            EventFilter::Severity { min, max } => {
+                if let (Some(min), Some(max)) = (min, max) {
+                    if min > max {
+                        anyhow::bail!("Severity range is empty: {:?} > {:?}", min, max);
+                    }
+                }
+                Ok(())
+            }
+            EventFilter::Tag { key, .. } if key.is_empty() => {
+                anyhow::bail!("Tag key must not be empty")
+            }
+            EventFilter::Payload(condition) => condition.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    // ========== SQL Compilation ==========
+
+    /// Append this filter as a parenthesized boolean SQL expression
+    pub fn push_sql(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        match self {
+            EventFilter::And { filters } => push_joined(qb, filters, " AND ", "TRUE"),
+            EventFilter::Or { filters } => push_joined(qb, filters, " OR ", "FALSE"),
+            EventFilter::Not { filter } => {
+                qb.push("(NOT ");
+                filter.push_sql(qb);
+                qb.push(")");
+            }
+            EventFilter::SourceModule { module } => {
+                qb.push("(source_module #>> '{}' = ");
+                qb.push_bind(module.as_str().to_string());
+                qb.push(")");
+            }
+            EventFilter::EventType { event_type } => {
+                qb.push("(event_type #>> '{}' = ");
+                qb.push_bind(json_text(event_type));
+                qb.push(")");
+            }
+            EventFilter::Severity { min, max } => {
+                let levels: Vec<String> = SEVERITIES
+                    .iter()
+                    .filter(|s| !matches!(min, Some(m) if *s < m))
+                    .filter(|s| !matches!(max, Some(m) if *s > m))
+                    .map(json_text)
+                    .collect();
+                qb.push("(severity #>> '{}' = ANY(");
+                qb.push_bind(levels);
+                qb.push("))");
+            }
+            EventFilter::Tag { key, matcher } => push_tag(qb, key, matcher),
+            EventFilter::Payload(condition) => condition.push_sql(qb),
+        }
+    }
+    // ========== In-Memory Evaluation ==========
+
+    /// Evaluate this filter against an event held in memory
+    ///
+    /// Payload conditions are JSONPath expressions evaluated by PostgreSQL and are
+    /// rejected here.
+    pub fn matches(&self, event: &AnalyticsEvent) -> Result<bool> {
+        let common = &event.common;
+        Ok(match self {
+            EventFilter::And { filters } => {
+                for filter in filters {
+                    if !filter.matches(event)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            EventFilter::Or { filters } => {
+                for filter in filters {
+                    if filter.matches(event)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            EventFilter::Not { filter } => !filter.matches(event)?,
+            EventFilter::SourceModule { module } => common.source_module == *module,
+            EventFilter::EventType { event_type } => common.event_type == *event_type,
+            EventFilter::Severity { min, max } => {
+                !matches!(min, Some(m) if common.severity < *m)
+                    && !matches!(max, Some(m) if common.severity > *m)
+            }
+            EventFilter::Tag { key, matcher } => {
+                let value = common.tags.get(key);
+                match matcher {
+                    TagMatcher::Exists => value.is_some(),
+                    TagMatcher::Equals(expected) => value == Some(expected),
+                    TagMatcher::Prefix(prefix) => value.is_some_and(|v| v.starts_with(prefix)),
+                    TagMatcher::In(values) => value.is_some_and(|v| values.contains(v)),
+                }
+            }
+            EventFilter::Payload(condition) => {
+                anyhow::bail!("Payload condition on {} needs TimescaleDB", condition.path)
+            }
+        })
+    }
+}
+
+impl PayloadCondition {
+    fn validate(&self) -> Result<()> {
+        let path = self.path.as_str();
+        if !path.starts_with('$') {
+            anyhow::bail!("JSONPath must start with '$': {}", path);
+        }
+
+        let allowed = |c: char| c.is_ascii_alphanumeric() || "$._[]*-\"".contains(c);
+        if let Some(bad) = path.chars().find(|c| !allowed(*c)) {
+            anyhow::bail!("Unsupported character '{}' in JSONPath {}", bad, path);
+        }
+        if path.matches('"').count() % 2 != 0 {
+            anyhow::bail!("Unbalanced quotes in JSONPath {}", path);
+        }
+
+        if self.comparison != Comparison::Exists && self.value.is_null() {
+            anyhow::bail!("Comparison {:?} requires a value", self.comparison);
+        }
+        Ok(())
+    }
+
+    /// The comparison is expressed as a JSONPath filter with the value passed as a variable
+    fn jsonpath(&self) -> String {
+        match self.comparison.as_jsonpath_op() {
+            Some(op) => format!("{} ? (@ {} $v)", self.path, op),
+            None => self.path.clone(),
+        }
+    }
+
+    fn push_sql(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push("jsonb_path_exists(payload, ");
+        qb.push_bind(self.jsonpath());
+        qb.push("::jsonpath, jsonb_build_object('v', ");
+        qb.push_bind(self.value.clone());
+        qb.push("::jsonb))");
+    }
+}
+
+fn push_joined(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    filters: &[EventFilter],
+    separator: &str,
+    empty: &str,
+) {
+    if filters.is_empty() {
+        qb.push(empty);
+        return;
+    }
+
+    qb.push("(");
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
+            qb.push(separator);
+        }
+        filter.push_sql(qb);
+    }
+    qb.push(")");
+}
+
+fn push_tag(qb: &mut QueryBuilder<'_, Postgres>, key: &str, matcher: &TagMatcher) {
+    match matcher {
+        TagMatcher::Exists => {
+            qb.push("jsonb_exists(tags, ");
+            qb.push_bind(key.to_string());
+            qb.push(")");
+        }
+        TagMatcher::Equals(value) => {
+            // Containment keeps the GIN index on tags usable
+            qb.push("(tags @> jsonb_build_object(");
+            qb.push_bind(key.to_string());
+            qb.push("::TEXT, ");
+            qb.push_bind(value.clone());
+            qb.push("::TEXT))");
+        }
+        TagMatcher::Prefix(prefix) => {
+            qb.push("starts_with(tags ->> ");
+            qb.push_bind(key.to_string());
+            qb.push(", ");
+            qb.push_bind(prefix.clone());
+            qb.push(")");
+        }
+        TagMatcher::In(values) => {
+            qb.push("(tags ->> ");
+            qb.push_bind(key.to_string());
+            qb.push(" = ANY(");
+            qb.push_bind(values.clone());
+            qb.push("))");
+        }
+    }
+}
+
+/// Serialized string form of a unit enum variant
+fn json_text<T: Serialize>(value: T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{CommonEventFields, CustomPayload, EventPayload, SCHEMA_VERSION};
+
+    fn compile(filter: &EventFilter) -> String {
+        let mut qb = QueryBuilder::<Postgres>::new("");
+        filter.push_sql(&mut qb);
+        qb.sql().to_string()
+    }
+
+    #[test]
+    fn test_boolean_combination_compiles_to_parameters() {
+        let filter = EventFilter::source_module(SourceModule::LlmSentinel)
+            .and(EventFilter::severity_at_least(Severity::Error))
+            .and(
+                EventFilter::tag_equals("region", "us-east-1")
+                    .or(EventFilter::tag("team", TagMatcher::Prefix("ml-".into()))),
+            );
+
+        let sql = compile(&filter);
+        assert_eq!(
+            sql,
+            "((source_module #>> '{}' = $1) AND (severity #>> '{}' = ANY($2)) AND \
+             ((tags @> jsonb_build_object($3::TEXT, $4::TEXT)) OR starts_with(tags ->> $5, $6)))"
+        );
+    }
+
+    #[test]
+    fn test_payload_condition_uses_jsonpath_variable() {
+        let condition = PayloadCondition {
+            path: "$.payload.data.total_latency_ms".to_string(),
+            comparison: Comparison::Gt,
+            value: serde_json::json!(1000),
+        };
+        assert_eq!(condition.jsonpath(), "$.payload.data.total_latency_ms ? (@ > $v)");
+
+        let sql = compile(&EventFilter::Payload(condition).not());
+        assert_eq!(
+            sql,
+            "(NOT jsonb_path_exists(payload, $1::jsonpath, jsonb_build_object('v', $2::jsonb)))"
+        );
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_input() {
+        assert!(EventFilter::payload("payload.x", Comparison::Eq, 1).validate().is_err());
+        assert!(EventFilter::payload("$.x') OR true --", Comparison::Eq, 1)
+            .validate()
+            .is_err());
+        assert!(EventFilter::severity_between(Severity::Error, Severity::Info)
+            .validate()
+            .is_err());
+
+        let mut deep = EventFilter::event_type(EventType::Alert);
+        for _ in 0..MAX_FILTER_DEPTH {
+            deep = deep.not();
+        }
+        assert!(deep.validate().is_err());
+    }
+
+    #[test]
+    fn test_matches_events_in_memory() {
+        let mut event = AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: uuid::Uuid::new_v4(),
+                timestamp: chrono::Utc::now(),
+                source_module: SourceModule::LlmSentinel,
+                event_type: EventType::Security,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Error,
+                environment: "production".to_string(),
+                tags: [("team".to_string(), "ml-platform".to_string())].into(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        };
+
+        let filter = EventFilter::source_module(SourceModule::LlmSentinel)
+            .and(EventFilter::severity_at_least(Severity::Warning))
+            .and(EventFilter::tag("team", TagMatcher::Prefix("ml-".into())));
+        assert!(filter.matches(&event).unwrap());
+
+        event.common.tags.clear();
+        assert!(!filter.matches(&event).unwrap());
+        assert!(filter.clone().not().matches(&event).unwrap());
+        let payload = EventFilter::payload("$.x", Comparison::Exists, serde_json::Value::Null);
+        assert!(payload.matches(&event).is_err());
+    }
+
+    #[test]
+    fn test_filter_json_round_trip() {
+        let json = serde_json::json!({
+            "op": "or",
+            "filters": [
+                {"op": "event_type", "event_type": "security"},
+                {"op": "tag", "key": "model", "matcher": {"match": "in", "value": ["gpt-4", "claude-3"]}},
+                {"op": "payload", "path": "$.payload.data.threat_level", "comparison": "eq", "value": "high"}
+            ]
+        });
+
+        let filter: EventFilter = serde_json::from_value(json.clone()).unwrap();
+        assert!(filter.validate().is_ok());
+        assert_eq!(serde_json::to_value(&filter).unwrap(), json);
+    }
+}
@@ -0,0 +1,426 @@
+//! In-Memory Storage Backend
+//!
+//! Keeps events and aggregated metrics in process memory behind the same
+//! `StorageBackend` interface as TimescaleDB, so benchmarks and unit tests can
+//! drive the pipeline and aggregation engine without external services.
+//! Queries follow the TimescaleDB semantics: events are deduplicated by ID,
+//! metric windows are upserted, and reads are scoped to one environment.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use sqlx::types::Json;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::backend::{EventCountRow, StorageBackend};
+use super::environment::{default_environment, EnvironmentScope};
+use super::filter::EventFilter;
+use super::AggregatedMetricRow;
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::retention::TablePolicy;
+use crate::schemas::events::AnalyticsEvent;
+
+/// Identity of an aggregated metric window, matching the TimescaleDB upsert key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MetricKey {
+    metric_name: String,
+    time_window: String,
+    window_start: DateTime<Utc>,
+    /// Canonical JSON text of the tags
+    tags: String,
+}
+
+/// In-memory implementation of `StorageBackend`
+pub struct MemoryBackend {
+    environment: String,
+    events: RwLock<HashMap<Uuid, AnalyticsEvent>>,
+    metrics: RwLock<HashMap<MetricKey, AggregatedMetricRow>>,
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryBackend {
+    /// Empty store scoped to the default environment
+    pub fn new() -> Self {
+        Self {
+            environment: default_environment(),
+            events: RwLock::new(HashMap::new()),
+            metrics: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Scope queries to a different environment
+    pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = environment.into();
+        self
+    }
+
+    /// Number of stored events across all environments
+    pub fn event_count(&self) -> usize {
+        self.events.read().len()
+    }
+
+    /// Number of stored aggregated metric windows
+    pub fn metric_count(&self) -> usize {
+        self.metrics.read().len()
+    }
+
+    /// Drop everything stored so far
+    pub fn clear(&self) {
+        self.events.write().clear();
+        self.metrics.write().clear();
+    }
+}
+
+/// Serialized name of an enum variant, as stored in the `events` table
+fn enum_str<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Start of the `window`-sized bucket containing `timestamp`, aligned to the
+/// Unix epoch like `time_bucket`
+fn bucket_start(timestamp: DateTime<Utc>, window: TimeWindow) -> DateTime<Utc> {
+    let seconds = window.to_seconds() as i64;
+    let aligned = timestamp.timestamp().div_euclid(seconds) * seconds;
+    DateTime::from_timestamp(aligned, 0).unwrap_or(timestamp)
+}
+
+/// Whether `tags` contains every key/value in `subset`, like Postgres `@>`
+pub(crate) fn tags_contain(tags: &serde_json::Value, subset: &serde_json::Value) -> bool {
+    match (tags, subset) {
+        (serde_json::Value::Object(tags), serde_json::Value::Object(subset)) => subset
+            .iter()
+            .all(|(key, value)| tags.get(key).is_some_and(|v| tags_contain(v, value))),
+        (tags, subset) => tags == subset,
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn insert_events_batch(&self, events: &[AnalyticsEvent]) -> Result<u64> {
+        let mut stored = self.events.write();
+        let mut inserted = 0;
+        for event in events {
+            if !stored.contains_key(&event.common.event_id) {
+                stored.insert(event.common.event_id, event.clone());
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    async fn store_aggregated_metric(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+        tags: &serde_json::Value,
+        measures: &StatisticalMeasures,
+    ) -> Result<()> {
+        let key = MetricKey {
+            metric_name: metric_name.to_string(),
+            time_window: time_window.as_str().to_string(),
+            window_start,
+            tags: tags.to_string(),
+        };
+        let row = AggregatedMetricRow {
+            metric_name: key.metric_name.clone(),
+            time_window: key.time_window.clone(),
+            window_start,
+            tags: tags.clone(),
+            avg: measures.avg,
+            min: measures.min,
+            max: measures.max,
+            p50: measures.p50,
+            p95: measures.p95,
+            p99: measures.p99,
+            stddev: measures.stddev,
+            count: measures.count as i64,
+            sum: measures.sum,
+            histogram: measures.histogram.clone().map(Json),
+        };
+
+        self.metrics.write().insert(key, row);
+        Ok(())
+    }
+
+    async fn query_aggregated_metrics(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        let mut rows: Vec<AggregatedMetricRow> = self
+            .metrics
+            .read()
+            .values()
+            .filter(|row| {
+                row.metric_name == metric_name
+                    && row.time_window == time_window.as_str()
+                    && row.window_start >= start
+                    && row.window_start < end
+            })
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| row.window_start);
+        Ok(rows)
+    }
+
+    async fn query_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        let limit = limit.unwrap_or(1000).max(0) as usize;
+
+        let mut events: Vec<AnalyticsEvent> = self
+            .events
+            .read()
+            .values()
+            .filter(|event| {
+                let common = &event.common;
+                common.environment == self.environment
+                    && common.timestamp >= start
+                    && common.timestamp < end
+            })
+            .cloned()
+            .collect();
+        events.sort_by(|a, b| b.common.timestamp.cmp(&a.common.timestamp));
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    async fn search_events(
+        &self,
+        filter: &EventFilter,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+        scope: &EnvironmentScope,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        filter.validate()?;
+        let limit = limit.unwrap_or(1000).max(0) as usize;
+        let environments = scope.filter(&self.environment);
+
+        let mut events = Vec::new();
+        for event in self.events.read().values() {
+            let common = &event.common;
+            let in_scope = environments
+                .as_ref()
+                .map_or(true, |envs| envs.contains(&common.environment));
+            if in_scope
+                && common.timestamp >= start
+                && common.timestamp < end
+                && filter.matches(event)?
+            {
+                events.push(event.clone());
+            }
+        }
+        events.sort_by(|a, b| b.common.timestamp.cmp(&a.common.timestamp));
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        let mut counts: BTreeMap<(DateTime<Utc>, String, String), i64> = BTreeMap::new();
+        for event in self.events.read().values() {
+            let common = &event.common;
+            if common.environment != self.environment
+                || common.timestamp < start
+                || common.timestamp >= end
+            {
+                continue;
+            }
+            let key = (
+                bucket_start(common.timestamp, window),
+                enum_str(&common.source_module),
+                enum_str(&common.event_type),
+            );
+            *counts.entry(key).or_default() += 1;
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(
+                |((bucket, source_module, event_type), count)| EventCountRow {
+                    bucket,
+                    source_module,
+                    event_type,
+                    count,
+                },
+            )
+            .collect())
+    }
+
+    /// Delete rows past their retention immediately; compression does not
+    /// apply to memory
+    async fn apply_retention(&self, policies: &[TablePolicy]) -> Result<()> {
+        for policy in policies {
+            let Some(days) = policy.retention_days else {
+                continue;
+            };
+            let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+
+            match policy.table.as_str() {
+                "events" => self
+                    .events
+                    .write()
+                    .retain(|_, event| event.common.timestamp >= cutoff),
+                "aggregated_metrics" => self
+                    .metrics
+                    .write()
+                    .retain(|_, row| row.window_start >= cutoff),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+        SCHEMA_VERSION,
+    };
+    use serde_json::json;
+
+    fn event(timestamp: DateTime<Utc>) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: default_environment(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: json!({}),
+            }),
+        }
+    }
+
+    fn measures(avg: f64) -> StatisticalMeasures {
+        StatisticalMeasures {
+            avg,
+            count: 1,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_are_deduplicated_and_scoped() {
+        let backend = MemoryBackend::new();
+        let now = Utc::now();
+        let first = event(now - chrono::Duration::seconds(10));
+        let second = event(now);
+        let mut other_env = event(now);
+        other_env.common.environment = "staging".to_string();
+
+        let batch = vec![first.clone(), second.clone(), other_env];
+        assert_eq!(backend.insert_events_batch(&batch).await.unwrap(), 3);
+        assert_eq!(
+            backend.insert_events_batch(&[first.clone()]).await.unwrap(),
+            0
+        );
+        assert_eq!(backend.event_count(), 3);
+
+        let events = backend
+            .query_events(
+                now - chrono::Duration::minutes(1),
+                now + chrono::Duration::seconds(1),
+                None,
+            )
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = events.iter().map(|e| e.common.event_id).collect();
+        assert_eq!(ids, vec![second.common.event_id, first.common.event_id]);
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_metrics_upsert_and_tag_filter() {
+        let backend = MemoryBackend::new();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let tenant = json!({ "tenant_id": "acme" });
+
+        for (offset, avg) in [(60, 2.0), (0, 1.0), (0, 1.5)] {
+            backend
+                .store_aggregated_metric(
+                    "llm.latency",
+                    TimeWindow::OneMinute,
+                    start + chrono::Duration::seconds(offset),
+                    &tenant,
+                    &measures(avg),
+                )
+                .await
+                .unwrap();
+        }
+        backend
+            .store_aggregated_metric(
+                "llm.latency",
+                TimeWindow::OneMinute,
+                start,
+                &json!({}),
+                &measures(9.0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(backend.metric_count(), 3);
+
+        let end = start + chrono::Duration::minutes(5);
+        let rows = backend
+            .query_aggregated_metrics_tagged(
+                "llm.latency",
+                TimeWindow::OneMinute,
+                start,
+                end,
+                &tenant,
+            )
+            .await
+            .unwrap();
+        let averages: Vec<f64> = rows.iter().map(|r| r.avg).collect();
+        assert_eq!(averages, vec![1.5, 2.0]);
+    }
+
+    #[test]
+    fn test_tags_contain() {
+        let tags = json!({ "tenant_id": "acme", "model": "gpt-4" });
+        assert!(tags_contain(&tags, &json!({ "tenant_id": "acme" })));
+        assert!(tags_contain(&tags, &json!({})));
+        assert!(!tags_contain(&tags, &json!({ "tenant_id": "globex" })));
+        assert!(!tags_contain(&json!({}), &json!({ "tenant_id": "acme" })));
+    }
+}
@@ -0,0 +1,299 @@
+//! File-Based Migrations
+//!
+//! Loads ordered SQL migrations from a directory. Each file is named
+//! `NNN_description.sql` and carries `-- +migrate up` / `-- +migrate down`
+//! sections; the SHA-256 of the whole file is recorded when applied so that
+//! edits to an already-applied migration can be detected as drift.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+
+use crate::archival::sha256_hex;
+
+/// Marker that starts the forward section of a migration file
+pub const UP_MARKER: &str = "-- +migrate up";
+
+/// Marker that starts the rollback section of a migration file
+pub const DOWN_MARKER: &str = "-- +migrate down";
+
+/// A single migration parsed from disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Numeric prefix used for ordering
+    pub version: u32,
+    /// File stem, e.g. `001_create_events_table`; recorded in `_migrations`
+    pub name: String,
+    pub up: String,
+    /// Empty when the file has no down section
+    pub down: String,
+    /// SHA-256 of the full file contents
+    pub checksum: String,
+}
+
+impl Migration {
+    /// Whether the migration can be rolled back
+    pub fn is_reversible(&self) -> bool {
+        !self.down.trim().is_empty()
+    }
+}
+
+/// Parse a migration from its file name and contents
+pub fn parse_migration(filename: &str, contents: &str) -> Result<Migration> {
+    let name = filename
+        .strip_suffix(".sql")
+        .ok_or_else(|| anyhow!("Migration file must end in .sql: {}", filename))?;
+
+    let version = version_of(name)
+        .ok_or_else(|| anyhow!("Migration name must start with a numeric version: {}", name))?;
+
+    let mut up = String::new();
+    let mut down = String::new();
+    let mut section: Option<bool> = None;
+
+    for line in contents.lines() {
+        let marker = line.trim().to_lowercase();
+        if marker == UP_MARKER {
+            if section.is_some() {
+                bail!("Migration {} has an up section after another section", name);
+            }
+            section = Some(true);
+            continue;
+        }
+        if marker == DOWN_MARKER {
+            if section != Some(true) {
+                bail!(
+                    "Migration {} has a down section without a preceding up section",
+                    name
+                );
+            }
+            section = Some(false);
+            continue;
+        }
+
+        match section {
+            Some(true) => {
+                up.push_str(line);
+                up.push('\n');
+            }
+            Some(false) => {
+                down.push_str(line);
+                down.push('\n');
+            }
+            // Header comments before the first marker are ignored
+            None => {}
+        }
+    }
+
+    if up.trim().is_empty() {
+        bail!("Migration {} has no '{}' section", name, UP_MARKER);
+    }
+
+    Ok(Migration {
+        version,
+        name: name.to_string(),
+        up: up.trim().to_string(),
+        down: down.trim().to_string(),
+        checksum: sha256_hex(contents.as_bytes()),
+    })
+}
+
+/// Load all `*.sql` migrations from a directory, ordered by version
+pub fn load_migrations(dir: &Path) -> Result<Vec<Migration>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read migrations directory {}", dir.display()))?;
+
+    let mut migrations = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("sql") {
+            continue;
+        }
+
+        let filename = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| anyhow!("Invalid migration file name: {}", path.display()))?;
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read migration {}", path.display()))?;
+
+        migrations.push(parse_migration(filename, &contents)?);
+    }
+
+    migrations.sort_by_key(|m| m.version);
+
+    for pair in migrations.windows(2) {
+        if pair[0].version == pair[1].version {
+            bail!(
+                "Duplicate migration version {}: {} and {}",
+                pair[0].version,
+                pair[0].name,
+                pair[1].name
+            );
+        }
+    }
+
+    Ok(migrations)
+}
+
+/// Next free version number for a new migration
+pub fn next_version(migrations: &[Migration]) -> u32 {
+    migrations.last().map(|m| m.version + 1).unwrap_or(1)
+}
+
+/// Version prefix of a migration name, e.g. `7` for `007_create_widgets`
+pub fn version_of(name: &str) -> Option<u32> {
+    name.split('_').next()?.parse().ok()
+}
+
+/// A row from the `_migrations` bookkeeping table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub name: String,
+    /// `None` for rows recorded before checksums were tracked
+    pub checksum: Option<String>,
+}
+
+/// Result of comparing an applied migration against its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// File matches the checksum recorded at apply time
+    Verified,
+    /// File was edited after being applied
+    Modified,
+    /// Applied migration has no file on disk
+    Missing,
+    /// Applied before checksums were tracked; nothing to compare against
+    Untracked,
+}
+
+impl ChecksumStatus {
+    /// Whether this status indicates tampering or drift
+    pub fn is_drift(&self) -> bool {
+        matches!(self, Self::Modified | Self::Missing)
+    }
+}
+
+/// Compare every applied migration with the file on disk
+pub fn verify_applied<'a>(
+    migrations: &[Migration],
+    applied: &'a [AppliedMigration],
+) -> Vec<(&'a AppliedMigration, ChecksumStatus)> {
+    applied
+        .iter()
+        .map(|row| {
+            let status = match (
+                migrations.iter().find(|m| m.name == row.name),
+                &row.checksum,
+            ) {
+                (None, _) => ChecksumStatus::Missing,
+                (Some(_), None) => ChecksumStatus::Untracked,
+                (Some(m), Some(checksum)) if *checksum == m.checksum => ChecksumStatus::Verified,
+                (Some(_), Some(_)) => ChecksumStatus::Modified,
+            };
+            (row, status)
+        })
+        .collect()
+}
+
+/// Migrations on disk that have not been applied, in version order
+pub fn pending_migrations<'a>(
+    migrations: &'a [Migration],
+    applied: &[AppliedMigration],
+) -> Vec<&'a Migration> {
+    migrations
+        .iter()
+        .filter(|m| !applied.iter().any(|row| row.name == m.name))
+        .collect()
+}
+
+/// Highest applied version, or 0 for an empty database
+pub fn current_version(applied: &[AppliedMigration]) -> u32 {
+    applied
+        .iter()
+        .filter_map(|row| version_of(&row.name))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "-- Migration: create_widgets\n\n\
+        -- +migrate up\nCREATE TABLE widgets (id INT);\n\n\
+        -- +migrate down\nDROP TABLE widgets;\n";
+
+    #[test]
+    fn test_parse_up_and_down_sections() {
+        let migration = parse_migration("007_create_widgets.sql", SAMPLE).unwrap();
+
+        assert_eq!(migration.version, 7);
+        assert_eq!(migration.name, "007_create_widgets");
+        assert_eq!(migration.up, "CREATE TABLE widgets (id INT);");
+        assert_eq!(migration.down, "DROP TABLE widgets;");
+        assert!(migration.is_reversible());
+        assert_eq!(migration.checksum, sha256_hex(SAMPLE.as_bytes()));
+    }
+
+    #[test]
+    fn test_checksum_changes_with_contents() {
+        let original = parse_migration("001_a.sql", SAMPLE).unwrap();
+        let edited = parse_migration("001_a.sql", &SAMPLE.replace("INT", "BIGINT")).unwrap();
+
+        assert_ne!(original.checksum, edited.checksum);
+    }
+
+    #[test]
+    fn test_rejects_invalid_migrations() {
+        assert!(parse_migration("create_widgets.sql", SAMPLE).is_err());
+        assert!(parse_migration("001_widgets.txt", SAMPLE).is_err());
+        assert!(parse_migration("001_widgets.sql", "DROP TABLE widgets;").is_err());
+        assert!(
+            parse_migration("001_widgets.sql", "-- +migrate down\nDROP TABLE widgets;").is_err()
+        );
+
+        let irreversible = parse_migration("001_widgets.sql", "-- +migrate up\nSELECT 1;").unwrap();
+        assert!(!irreversible.is_reversible());
+    }
+
+    #[test]
+    fn test_verify_and_pending() {
+        let first = parse_migration("001_a.sql", SAMPLE).unwrap();
+        let second = parse_migration("002_b.sql", SAMPLE).unwrap();
+        let third = parse_migration("003_c.sql", SAMPLE).unwrap();
+        let migrations = vec![first.clone(), second.clone(), third];
+
+        let applied = vec![
+            AppliedMigration {
+                name: first.name.clone(),
+                checksum: Some(first.checksum.clone()),
+            },
+            AppliedMigration {
+                name: second.name.clone(),
+                checksum: Some("stale".to_string()),
+            },
+            AppliedMigration {
+                name: "000_legacy".to_string(),
+                checksum: None,
+            },
+        ];
+
+        let statuses: Vec<ChecksumStatus> = verify_applied(&migrations, &applied)
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ChecksumStatus::Verified,
+                ChecksumStatus::Modified,
+                ChecksumStatus::Missing
+            ]
+        );
+
+        let pending = pending_migrations(&migrations, &applied);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].name, "003_c");
+        assert_eq!(current_version(&applied), 2);
+    }
+}
@@ -7,16 +7,55 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::types::Json;
 use sqlx::{FromRow, Row};
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 use uuid::Uuid;
 
+pub mod backend;
+pub mod clickhouse;
+pub mod environment;
+pub mod filter;
+pub mod memory;
+pub mod migrations;
+pub mod planner;
 pub mod queries;
+pub mod result_cache;
 pub mod schema;
+pub mod search;
+pub mod sql;
+pub mod timescale;
+
+pub use backend::{connect_backend, EventCountRow, StorageBackend, StorageBackendKind, StorageConfig};
+pub use clickhouse::{ClickHouseBackend, ClickHouseConfig};
+pub use environment::{CrossEnvironmentGrant, EnvironmentScope};
+pub use filter::{Comparison, EventFilter, PayloadCondition, TagMatcher};
+pub use memory::MemoryBackend;
+pub use planner::{QueryPlan, QueryPlanner, QueryPlannerConfig};
+pub use result_cache::{CachedQuery, QueryResultCache, ResultCacheConfig};
+pub use search::{event_search_router, EventSearchParams, EVENT_SEARCH_PATH};
+pub use sql::{SqlError, SqlExecutor, SqlLimits, SqlQueryResult, SqlStats, SqlStatement};
 
 use crate::schemas::events::AnalyticsEvent;
 use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::models::histogram::Histogram;
+use crate::models::api::{CursorPage, PageCursor};
+use crate::metering::{Consumer, UsagePeriod, UsageRecord};
+use crate::alerting::{Incident, MaintenanceWindow, Silence, WebhookDelivery, WebhookSubscription};
+use crate::alerting::rules::{AlertRule, AlertRuleRevision, RuleChange, RuleWrite};
+use crate::analytics::attribution::{RequestCost, RequestCostQuery};
+use crate::analytics::feedback::{AnomalyFeedback, FeedbackTally};
+use crate::analytics::scorecard::ModelScorecard;
+use crate::archival::{ArchiveManifest, ArchiveTable};
+use crate::audit::{AuditQuery, AuditRecord};
+use crate::adapters::pagination::SyncCheckpoint;
+use crate::export::prometheus::HubMetrics;
+use crate::pipeline::hot_cache::HotCache;
+
+/// Largest page a cursor scan will return
+pub const MAX_PAGE_SIZE: u32 = 1000;
 
 /// Database configuration
 #[derive(Debug, Clone, Deserialize)]
@@ -31,6 +70,9 @@ pub struct DatabaseConfig {
     pub connection_timeout: u64,
     pub idle_timeout: u64,
     pub max_lifetime: u64,
+    /// Environment that queries are scoped to by default
+    #[serde(default = "environment::default_environment")]
+    pub environment: String,
 }
 
 impl Default for DatabaseConfig {
@@ -46,6 +88,7 @@ impl Default for DatabaseConfig {
             connection_timeout: 30,
             idle_timeout: 600,
             max_lifetime: 1800,
+            environment: environment::default_environment(),
         }
     }
 }
@@ -63,6 +106,11 @@ impl DatabaseConfig {
 /// Database client with connection pooling
 pub struct Database {
     pool: PgPool,
+    default_environment: String,
+    /// Serves recent aggregate windows without hitting TimescaleDB
+    hot_cache: Option<Arc<HotCache>>,
+    /// Caches aggregate query results, invalidated when a covered window is stored
+    result_cache: Option<Arc<QueryResultCache>>,
 }
 
 impl Database {
@@ -89,7 +137,12 @@ impl Database {
 
         info!("Database connection pool initialized successfully");
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            default_environment: config.environment,
+            hot_cache: None,
+            result_cache: None,
+        })
     }
 
     /// Create a new database client from a connection URL
@@ -115,7 +168,45 @@ impl Database {
 
         info!("Database connection pool initialized successfully");
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            default_environment: environment::default_environment(),
+            hot_cache: None,
+            result_cache: None,
+        })
+    }
+
+    /// Wrap an existing connection pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self {
+            pool,
+            default_environment: environment::default_environment(),
+            hot_cache: None,
+            result_cache: None,
+        }
+    }
+
+    /// Override the environment that queries are scoped to by default
+    pub fn with_default_environment(mut self, environment: impl Into<String>) -> Self {
+        self.default_environment = environment.into();
+        self
+    }
+
+    /// Write aggregates through to, and serve recent ranges from, a hot cache
+    pub fn with_hot_cache(mut self, cache: Arc<HotCache>) -> Self {
+        self.hot_cache = Some(cache);
+        self
+    }
+
+    /// Cache aggregate query results served through `query_aggregates_cached`
+    pub fn with_result_cache(mut self, cache: Arc<QueryResultCache>) -> Self {
+        self.result_cache = Some(cache);
+        self
+    }
+
+    /// Environment that queries are scoped to by default
+    pub fn default_environment(&self) -> &str {
+        &self.default_environment
     }
 
     /// Get a reference to the connection pool
@@ -131,12 +222,17 @@ impl Database {
     // ========== Event Operations ==========
 
     /// Insert a single analytics event
-    #[instrument(skip(self, event))]
+    #[instrument(
+        skip(self, event),
+        fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+    )]
     pub async fn insert_event(&self, event: &AnalyticsEvent) -> Result<Uuid> {
+        crate::telemetry::record_event_context(&event.common);
+
         let event_json = serde_json::to_value(event)
             .context("Failed to serialize event")?;
 
-        let row = sqlx::query(
+        sqlx::query(
             r#"
             INSERT INTO events (
                 event_id, timestamp, source_module, event_type,
@@ -144,7 +240,7 @@ impl Database {
                 severity, environment, tags, payload
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            RETURNING event_id
+            ON CONFLICT (event_id) DO NOTHING
             "#
         )
         .bind(event.common.event_id)
@@ -158,68 +254,78 @@ impl Database {
         .bind(&event.common.environment)
         .bind(serde_json::to_value(&event.common.tags)?)
         .bind(event_json)
-        .fetch_one(&self.pool)
+        .execute(&self.pool)
         .await
         .context("Failed to insert event")?;
 
-        Ok(row.try_get("event_id")?)
+        // A redelivered event is already stored; the insert is a no-op
+        Ok(event.common.event_id)
     }
 
     /// Batch insert analytics events for high throughput
+    ///
+    /// The batch is written in a single transaction and is idempotent on
+    /// `event_id`: events that were already stored (e.g. redelivered after a
+    /// consumer restart) are skipped. Returns the number of newly stored rows.
     #[instrument(skip(self, events))]
     pub async fn insert_events_batch(&self, events: &[AnalyticsEvent]) -> Result<u64> {
         if events.is_empty() {
             return Ok(0);
         }
 
+        HubMetrics::global().observe_db_batch("events", events.len());
+
         let mut tx = self.pool.begin().await?;
-        let mut inserted = 0u64;
 
-        // Use COPY for maximum performance with large batches
-        if events.len() > 100 {
-            // Build bulk insert query
-            let mut query_builder = sqlx::QueryBuilder::new(
-                "INSERT INTO events (event_id, timestamp, source_module, event_type, \
-                 correlation_id, parent_event_id, schema_version, severity, environment, \
-                 tags, payload) "
-            );
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT INTO events (event_id, timestamp, source_module, event_type, \
+             correlation_id, parent_event_id, schema_version, severity, environment, \
+             tags, payload) "
+        );
 
-            query_builder.push_values(events, |mut b, event| {
-                let event_json = serde_json::to_value(event).unwrap();
-                b.push_bind(event.common.event_id)
-                    .push_bind(event.common.timestamp)
-                    .push_bind(serde_json::to_value(&event.common.source_module).unwrap())
-                    .push_bind(serde_json::to_value(&event.common.event_type).unwrap())
-                    .push_bind(event.common.correlation_id)
-                    .push_bind(event.common.parent_event_id)
-                    .push_bind(&event.common.schema_version)
-                    .push_bind(serde_json::to_value(&event.common.severity).unwrap())
-                    .push_bind(&event.common.environment)
-                    .push_bind(serde_json::to_value(&event.common.tags).unwrap())
-                    .push_bind(event_json);
-            });
+        query_builder.push_values(events, |mut b, event| {
+            let event_json = serde_json::to_value(event).unwrap();
+            b.push_bind(event.common.event_id)
+                .push_bind(event.common.timestamp)
+                .push_bind(serde_json::to_value(&event.common.source_module).unwrap())
+                .push_bind(serde_json::to_value(&event.common.event_type).unwrap())
+                .push_bind(event.common.correlation_id)
+                .push_bind(event.common.parent_event_id)
+                .push_bind(&event.common.schema_version)
+                .push_bind(serde_json::to_value(&event.common.severity).unwrap())
+                .push_bind(&event.common.environment)
+                .push_bind(serde_json::to_value(&event.common.tags).unwrap())
+                .push_bind(event_json);
+        });
+        query_builder.push(" ON CONFLICT (event_id) DO NOTHING");
 
-            let result = query_builder.build().execute(&mut *tx).await?;
-            inserted = result.rows_affected();
-        } else {
-            // Use individual inserts for smaller batches
-            for event in events {
-                self.insert_event(event).await?;
-                inserted += 1;
-            }
-        }
+        let result = query_builder.build().execute(&mut *tx).await?;
+        let inserted = result.rows_affected();
 
         tx.commit().await?;
         Ok(inserted)
     }
 
-    /// Query events by time range
+    /// Query events by time range in the default environment
     #[instrument(skip(self))]
     pub async fn query_events(
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
         limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        self.query_events_scoped(start, end, limit, &EnvironmentScope::Default)
+            .await
+    }
+
+    /// Query events by time range within an environment scope
+    #[instrument(skip(self))]
+    pub async fn query_events_scoped(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+        scope: &EnvironmentScope,
     ) -> Result<Vec<AnalyticsEvent>> {
         let limit = limit.unwrap_or(1000);
 
@@ -228,6 +334,7 @@ impl Database {
             SELECT payload
             FROM events
             WHERE timestamp >= $1 AND timestamp < $2
+              AND ($4::TEXT[] IS NULL OR environment = ANY($4))
             ORDER BY timestamp DESC
             LIMIT $3
             "#
@@ -235,6 +342,7 @@ impl Database {
         .bind(start)
         .bind(end)
         .bind(limit)
+        .bind(scope.filter(&self.default_environment))
         .fetch_all(&self.pool)
         .await
         .context("Failed to query events")?;
@@ -250,21 +358,180 @@ impl Database {
         Ok(events)
     }
 
-    /// Query events by correlation ID
+    /// Search events matching a filter expression within an environment scope
+    #[instrument(skip(self, filter))]
+    pub async fn search_events(
+        &self,
+        filter: &EventFilter,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+        scope: &EnvironmentScope,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        filter.validate()?;
+
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT payload FROM events WHERE timestamp >= ",
+        );
+        qb.push_bind(start);
+        qb.push(" AND timestamp < ");
+        qb.push_bind(end);
+        if let Some(environments) = scope.filter(&self.default_environment) {
+            qb.push(" AND environment = ANY(");
+            qb.push_bind(environments);
+            qb.push(")");
+        }
+        qb.push(" AND ");
+        filter.push_sql(&mut qb);
+        qb.push(" ORDER BY timestamp DESC LIMIT ");
+        qb.push_bind(limit.unwrap_or(1000));
+
+        let rows = qb
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to search events")?;
+
+        let events: Vec<AnalyticsEvent> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let payload: serde_json::Value = row.try_get("payload").ok()?;
+                serde_json::from_value(payload).ok()
+            })
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Scan events newest-first using keyset pagination
+    ///
+    /// Pass the previous page's `next_cursor` to continue the scan. Unlike offset
+    /// pagination, each page costs the same regardless of how deep the scan is.
+    #[instrument(skip(self, filter))]
+    pub async fn query_events_page(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        filter: Option<&EventFilter>,
+        cursor: Option<PageCursor>,
+        limit: u32,
+        scope: &EnvironmentScope,
+    ) -> Result<CursorPage<AnalyticsEvent>> {
+        if let Some(filter) = filter {
+            filter.validate()?;
+        }
+        let limit = limit.clamp(1, MAX_PAGE_SIZE) as usize;
+
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT timestamp, event_id, payload FROM events WHERE timestamp >= ",
+        );
+        qb.push_bind(start);
+        qb.push(" AND timestamp < ");
+        qb.push_bind(end);
+        if let Some(cursor) = cursor {
+            qb.push(" AND (timestamp, event_id) < (");
+            qb.push_bind(cursor.timestamp);
+            qb.push(", ");
+            qb.push_bind(cursor.id);
+            qb.push(")");
+        }
+        if let Some(environments) = scope.filter(&self.default_environment) {
+            qb.push(" AND environment = ANY(");
+            qb.push_bind(environments);
+            qb.push(")");
+        }
+        if let Some(filter) = filter {
+            qb.push(" AND ");
+            filter.push_sql(&mut qb);
+        }
+        qb.push(" ORDER BY timestamp DESC, event_id DESC LIMIT ");
+        qb.push_bind(limit as i64 + 1);
+
+        let rows = qb
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to page events")?;
+
+        // The cursor comes from the raw rows so a payload that fails to decode
+        // cannot end the scan early
+        let page = CursorPage::from_rows(rows, limit, |row| {
+            PageCursor::new(row.get("timestamp"), row.get("event_id"))
+        });
+        let events: Vec<AnalyticsEvent> = page
+            .items
+            .into_iter()
+            .filter_map(|row| {
+                let payload: serde_json::Value = row.try_get("payload").ok()?;
+                serde_json::from_value(payload).ok()
+            })
+            .collect();
+
+        Ok(CursorPage {
+            items: events,
+            next_cursor: page.next_cursor,
+        })
+    }
+
+    /// Walk every page of events matching a filter and invoke `visit` on each.
+    ///
+    /// Returns the number of events visited. Used by analytics jobs that fold a
+    /// whole window without holding it in memory.
+    pub async fn scan_events<F>(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        filter: Option<&EventFilter>,
+        scope: &EnvironmentScope,
+        mut visit: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&AnalyticsEvent),
+    {
+        let mut cursor = None;
+        let mut scanned = 0usize;
+
+        loop {
+            let page = self
+                .query_events_page(start, end, filter, cursor, MAX_PAGE_SIZE, scope)
+                .await?;
+            scanned += page.items.len();
+            page.items.iter().for_each(&mut visit);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(scanned),
+            }
+        }
+    }
+
+    /// Query events by correlation ID in the default environment
     #[instrument(skip(self))]
     pub async fn query_events_by_correlation(
         &self,
         correlation_id: Uuid,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        self.query_events_by_correlation_scoped(correlation_id, &EnvironmentScope::Default)
+            .await
+    }
+
+    /// Query events by correlation ID within an environment scope
+    #[instrument(skip(self))]
+    pub async fn query_events_by_correlation_scoped(
+        &self,
+        correlation_id: Uuid,
+        scope: &EnvironmentScope,
     ) -> Result<Vec<AnalyticsEvent>> {
         let rows = sqlx::query(
             r#"
             SELECT payload
             FROM events
             WHERE correlation_id = $1
+              AND ($2::TEXT[] IS NULL OR environment = ANY($2))
             ORDER BY timestamp ASC
             "#
         )
         .bind(correlation_id)
+        .bind(scope.filter(&self.default_environment))
         .fetch_all(&self.pool)
         .await
         .context("Failed to query events by correlation")?;
@@ -296,9 +563,9 @@ impl Database {
             r#"
             INSERT INTO aggregated_metrics (
                 metric_name, time_window, window_start, tags,
-                avg, min, max, p50, p95, p99, stddev, count, sum
+                avg, min, max, p50, p95, p99, stddev, count, sum, histogram
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             ON CONFLICT (metric_name, time_window, window_start, tags)
             DO UPDATE SET
                 avg = EXCLUDED.avg,
@@ -309,7 +576,8 @@ impl Database {
                 p99 = EXCLUDED.p99,
                 stddev = EXCLUDED.stddev,
                 count = EXCLUDED.count,
-                sum = EXCLUDED.sum
+                sum = EXCLUDED.sum,
+                histogram = EXCLUDED.histogram
             "#
         )
         .bind(metric_name)
@@ -325,13 +593,61 @@ impl Database {
         .bind(measures.stddev)
         .bind(measures.count as i64)
         .bind(measures.sum)
+        .bind(measures.histogram.as_ref().map(Json))
         .execute(&self.pool)
         .await
         .context("Failed to store aggregated metric")?;
 
+        if let Some(cache) = &self.hot_cache {
+            let row = AggregatedMetricRow {
+                metric_name: metric_name.to_string(),
+                time_window: time_window.as_str().to_string(),
+                window_start,
+                tags: tags.clone(),
+                avg: measures.avg,
+                min: measures.min,
+                max: measures.max,
+                p50: measures.p50,
+                p95: measures.p95,
+                p99: measures.p99,
+                stddev: measures.stddev,
+                count: measures.count as i64,
+                sum: measures.sum,
+                histogram: measures.histogram.clone().map(Json),
+            };
+            if let Err(e) = cache.put_aggregate(&row).await {
+                warn!("Failed to write aggregate to hot cache: {}", e);
+            }
+        }
+        if let Some(cache) = &self.result_cache {
+            cache.invalidate(metric_name, time_window, window_start);
+        }
+
         Ok(())
     }
 
+    /// Aggregates from the hot cache when it covers `[start, end)`
+    async fn cached_aggregates(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: Option<&serde_json::Value>,
+    ) -> Option<Vec<AggregatedMetricRow>> {
+        let cache = self.hot_cache.as_ref()?;
+        match cache
+            .recent_aggregates(metric_name, time_window, start, end, tags)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("Hot cache read failed, falling back to database: {}", e);
+                None
+            }
+        }
+    }
+
     /// Query aggregated metrics
     #[instrument(skip(self))]
     pub async fn query_aggregated_metrics(
@@ -341,11 +657,18 @@ impl Database {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Vec<AggregatedMetricRow>> {
+        if let Some(rows) = self
+            .cached_aggregates(metric_name, time_window, start, end, None)
+            .await
+        {
+            return Ok(rows);
+        }
+
         let rows = sqlx::query_as::<_, AggregatedMetricRow>(
             r#"
             SELECT
                 metric_name, time_window, window_start, tags,
-                avg, min, max, p50, p95, p99, stddev, count, sum
+                avg, min, max, p50, p95, p99, stddev, count, sum, histogram
             FROM aggregated_metrics
             WHERE metric_name = $1
               AND time_window = $2
@@ -365,127 +688,1903 @@ impl Database {
         Ok(rows)
     }
 
-    // ========== Anomaly Operations ==========
+    /// Query aggregated metrics, optionally filtered by tags, through the result cache
+    #[instrument(skip(self))]
+    pub async fn query_aggregates_cached(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: Option<&serde_json::Value>,
+    ) -> Result<CachedQuery> {
+        let cache = self.result_cache.as_ref();
+        let key = cache.map(|cache| cache.key(metric_name, time_window, start, end, tags));
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Some((rows, ttl)) = cache.get_at(key, Utc::now()) {
+                return Ok(CachedQuery {
+                    rows: rows.as_ref().clone(),
+                    from_cache: true,
+                    cache_ttl: Some(ttl),
+                });
+            }
+        }
 
-    /// Store detected anomaly
+        let rows = match tags {
+            Some(tags) => {
+                self.query_aggregated_metrics_tagged(metric_name, time_window, start, end, tags)
+                    .await?
+            }
+            None => {
+                self.query_aggregated_metrics(metric_name, time_window, start, end)
+                    .await?
+            }
+        };
+        if let (Some(cache), Some(key)) = (cache, key) {
+            cache.insert_at(key, rows.clone(), end, Utc::now());
+        }
+
+        Ok(CachedQuery {
+            rows,
+            from_cache: false,
+            cache_ttl: None,
+        })
+    }
+
+    /// Query aggregated metrics whose tags contain all of `tags`
     #[instrument(skip(self))]
-    pub async fn store_anomaly(
+    pub async fn query_aggregated_metrics_tagged(
         &self,
-        anomaly_id: Uuid,
-        detected_at: DateTime<Utc>,
         metric_name: &str,
-        anomaly_type: &str,
-        severity: &str,
-        value: f64,
-        expected_value: Option<f64>,
-        confidence_score: f64,
-        context: &serde_json::Value,
-    ) -> Result<Uuid> {
-        let result = sqlx::query(
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: &serde_json::Value,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        if let Some(rows) = self
+            .cached_aggregates(metric_name, time_window, start, end, Some(tags))
+            .await
+        {
+            return Ok(rows);
+        }
+
+        let rows = sqlx::query_as::<_, AggregatedMetricRow>(
             r#"
-            INSERT INTO anomalies (
-                anomaly_id, detected_at, metric_name, anomaly_type,
-                severity, value, expected_value, confidence_score, context
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING anomaly_id
+            SELECT
+                metric_name, time_window, window_start, tags,
+                avg, min, max, p50, p95, p99, stddev, count, sum, histogram
+            FROM aggregated_metrics
+            WHERE metric_name = $1
+              AND time_window = $2
+              AND window_start >= $3
+              AND window_start < $4
+              AND tags @> $5
+            ORDER BY window_start ASC
             "#
         )
-        .bind(anomaly_id)
-        .bind(detected_at)
         .bind(metric_name)
-        .bind(anomaly_type)
-        .bind(severity)
-        .bind(value)
-        .bind(expected_value)
-        .bind(confidence_score)
-        .bind(context)
-        .fetch_one(&self.pool)
+        .bind(time_window.as_str())
+        .bind(start)
+        .bind(end)
+        .bind(tags)
+        .fetch_all(&self.pool)
         .await
-        .context("Failed to store anomaly")?;
+        .context("Failed to query tagged aggregated metrics")?;
 
-        Ok(result.try_get("anomaly_id")?)
+        Ok(rows)
     }
 
-    /// Query recent anomalies
+    /// Count-weighted mean of each metric per value of a tag, e.g. per `model_id`
     #[instrument(skip(self))]
-    pub async fn query_recent_anomalies(
+    pub async fn query_metric_means_by_tag(
         &self,
-        since: DateTime<Utc>,
-        limit: Option<i64>,
-    ) -> Result<Vec<AnomalyRow>> {
-        let limit = limit.unwrap_or(100);
-
-        let rows = sqlx::query_as::<_, AnomalyRow>(
+        metric_names: &[String],
+        tag: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MetricMeanRow>> {
+        let rows = sqlx::query_as::<_, MetricMeanRow>(
             r#"
             SELECT
-                anomaly_id, detected_at, metric_name, anomaly_type,
-                severity, value, expected_value, confidence_score, context
-            FROM anomalies
-            WHERE detected_at >= $1
-            ORDER BY detected_at DESC
-            LIMIT $2
+                tags ->> $2 AS tag_value,
+                metric_name,
+                SUM(avg * count) / NULLIF(SUM(count), 0) AS mean
+            FROM aggregated_metrics
+            WHERE metric_name = ANY($1)
+              AND tags ? $2
+              AND time_window = $3
+              AND window_start >= $4
+              AND window_start < $5
+            GROUP BY 1, 2
+            HAVING SUM(count) > 0
             "#
         )
-        .bind(since)
-        .bind(limit)
+        .bind(metric_names)
+        .bind(tag)
+        .bind(time_window.as_str())
+        .bind(start)
+        .bind(end)
         .fetch_all(&self.pool)
         .await
-        .context("Failed to query anomalies")?;
+        .context("Failed to query metric means by tag")?;
 
         Ok(rows)
     }
 
-    // ========== Correlation Operations ==========
-
-    /// Store event correlation
+    /// Aggregated windows of every metric starting at or after `since`, for federation
     #[instrument(skip(self))]
-    pub async fn store_correlation(
+    pub async fn query_aggregates_since(
         &self,
-        correlation_id: Uuid,
-        correlation_type: &str,
-        source_event_id: Uuid,
-        target_event_id: Uuid,
-        strength: f64,
-        metadata: &serde_json::Value,
-    ) -> Result<Uuid> {
-        let result = sqlx::query(
+        time_window: TimeWindow,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        let rows = sqlx::query_as::<_, AggregatedMetricRow>(
             r#"
-            INSERT INTO correlations (
-                correlation_id, correlation_type, source_event_id,
-                target_event_id, strength, metadata, created_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, NOW())
-            RETURNING correlation_id
+            SELECT
+                metric_name, time_window, window_start, tags,
+                avg, min, max, p50, p95, p99, stddev, count, sum, histogram
+            FROM aggregated_metrics
+            WHERE time_window = $1
+              AND window_start >= $2
+            ORDER BY window_start ASC, metric_name ASC
             "#
         )
-        .bind(correlation_id)
-        .bind(correlation_type)
-        .bind(source_event_id)
-        .bind(target_event_id)
-        .bind(strength)
-        .bind(metadata)
-        .fetch_one(&self.pool)
+        .bind(time_window.as_str())
+        .bind(since)
+        .fetch_all(&self.pool)
         .await
-        .context("Failed to store correlation")?;
+        .context("Failed to query aggregates for federation")?;
 
-        Ok(result.try_get("correlation_id")?)
+        Ok(rows)
     }
 
-    // ========== Health Check ==========
-
-    /// Check database health
-    pub async fn health_check(&self) -> Result<DatabaseHealth> {
-        let result = sqlx::query(
+    /// Distinct metric series (name and tag set) with windows starting at or
+    /// after `since`, most recently seen first
+    #[instrument(skip(self))]
+    pub async fn query_metric_series(
+        &self,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<MetricSeriesRow>> {
+        let rows = sqlx::query_as::<_, MetricSeriesRow>(
             r#"
-            SELECT
-                (SELECT COUNT(*) FROM pg_stat_activity WHERE state = 'active') as active_connections,
-                (SELECT COUNT(*) FROM events WHERE timestamp > NOW() - INTERVAL '1 minute') as recent_events,
-                (SELECT pg_database_size(current_database())) as database_size
+            SELECT metric_name, tags, MAX(window_start) AS last_seen
+            FROM aggregated_metrics
+            WHERE window_start >= $1
+            GROUP BY metric_name, tags
+            ORDER BY last_seen DESC, metric_name ASC
+            LIMIT $2
             "#
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query metric series")?;
+
+        Ok(rows)
+    }
+
+    /// Which of `names` have aggregates since `since`
+    #[instrument(skip(self, names))]
+    pub async fn query_known_metrics(
+        &self,
+        names: &[String],
+        since: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        let known = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT metric_name
+            FROM aggregated_metrics
+            WHERE metric_name = ANY($1) AND window_start >= $2
+            "#
+        )
+        .bind(names)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query known metrics")?;
+
+        Ok(known)
+    }
+
+    /// Upsert complete aggregate rows, e.g. rollups shipped by a federated region.
+    ///
+    /// Rows replace any stored row with the same metric, window, start, and
+    /// tags, so a window shipped again as it fills in is not counted twice.
+    #[instrument(skip(self, rows))]
+    pub async fn upsert_aggregate_rows(&self, rows: &[AggregatedMetricRow]) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        HubMetrics::global().observe_db_batch("aggregated_metrics", rows.len());
+
+        let mut tx = self.pool.begin().await?;
+        let mut upserted = 0;
+        // 14 binds per row keeps each statement under the Postgres bind limit
+        for chunk in rows.chunks(1000) {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO aggregated_metrics (metric_name, time_window, window_start, tags, \
+                 avg, min, max, p50, p95, p99, stddev, count, sum, histogram) "
+            );
+            query_builder.push_values(chunk, |mut b, row| {
+                b.push_bind(&row.metric_name)
+                    .push_bind(&row.time_window)
+                    .push_bind(row.window_start)
+                    .push_bind(&row.tags)
+                    .push_bind(row.avg)
+                    .push_bind(row.min)
+                    .push_bind(row.max)
+                    .push_bind(row.p50)
+                    .push_bind(row.p95)
+                    .push_bind(row.p99)
+                    .push_bind(row.stddev)
+                    .push_bind(row.count)
+                    .push_bind(row.sum)
+                    .push_bind(row.histogram.clone());
+            });
+            query_builder.push(
+                " ON CONFLICT (metric_name, time_window, window_start, tags) DO UPDATE SET \
+                 avg = EXCLUDED.avg, min = EXCLUDED.min, max = EXCLUDED.max, \
+                 p50 = EXCLUDED.p50, p95 = EXCLUDED.p95, p99 = EXCLUDED.p99, \
+                 stddev = EXCLUDED.stddev, count = EXCLUDED.count, sum = EXCLUDED.sum, \
+                 histogram = EXCLUDED.histogram"
+            );
+            upserted += query_builder
+                .build()
+                .execute(&mut *tx)
+                .await
+                .context("Failed to upsert aggregate rows")?
+                .rows_affected();
+        }
+        tx.commit().await?;
+
+        if let Some(cache) = &self.hot_cache {
+            for row in rows {
+                if let Err(e) = cache.put_aggregate(row).await {
+                    warn!("Failed to write aggregate to hot cache: {}", e);
+                    break;
+                }
+            }
+        }
+        if let Some(cache) = &self.result_cache {
+            for row in rows {
+                if let Ok(window) = timescale::parse_window(&row.time_window) {
+                    cache.invalidate(&row.metric_name, window, row.window_start);
+                }
+            }
+        }
+
+        Ok(upserted)
+    }
+
+    /// Event counts per bucket, source module, and event type in the default environment
+    #[instrument(skip(self))]
+    pub async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        let rows = sqlx::query_as::<_, EventCountRow>(
+            r#"
+            SELECT
+                time_bucket(make_interval(secs => $1), timestamp) AS bucket,
+                source_module #>> '{}' AS source_module,
+                event_type #>> '{}' AS event_type,
+                COUNT(*) AS count
+            FROM events
+            WHERE timestamp >= $2 AND timestamp < $3
+              AND environment = $4
+            GROUP BY 1, 2, 3
+            ORDER BY bucket ASC
+            "#
+        )
+        .bind(window.to_seconds() as f64)
+        .bind(start)
+        .bind(end)
+        .bind(&self.default_environment)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query event counts")?;
+
+        Ok(rows)
+    }
+
+    // ========== Anomaly Operations ==========
+
+    /// Store detected anomaly
+    #[instrument(skip(self))]
+    pub async fn store_anomaly(
+        &self,
+        anomaly_id: Uuid,
+        detected_at: DateTime<Utc>,
+        metric_name: &str,
+        anomaly_type: &str,
+        severity: &str,
+        value: f64,
+        expected_value: Option<f64>,
+        confidence_score: f64,
+        context: &serde_json::Value,
+    ) -> Result<Uuid> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO anomalies (
+                anomaly_id, detected_at, metric_name, anomaly_type,
+                severity, value, expected_value, confidence_score, context
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING anomaly_id
+            "#
+        )
+        .bind(anomaly_id)
+        .bind(detected_at)
+        .bind(metric_name)
+        .bind(anomaly_type)
+        .bind(severity)
+        .bind(value)
+        .bind(expected_value)
+        .bind(confidence_score)
+        .bind(context)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to store anomaly")?;
+
+        Ok(result.try_get("anomaly_id")?)
+    }
+
+    /// Query recent anomalies
+    #[instrument(skip(self))]
+    pub async fn query_recent_anomalies(
+        &self,
+        since: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnomalyRow>> {
+        let limit = limit.unwrap_or(100);
+
+        let rows = sqlx::query_as::<_, AnomalyRow>(
+            r#"
+            SELECT
+                anomaly_id, detected_at, metric_name, anomaly_type,
+                severity, value, expected_value, confidence_score, context
+            FROM anomalies
+            WHERE detected_at >= $1
+            ORDER BY detected_at DESC
+            LIMIT $2
+            "#
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query anomalies")?;
+
+        Ok(rows)
+    }
+
+    /// Look up a stored anomaly
+    #[instrument(skip(self))]
+    pub async fn get_anomaly(&self, anomaly_id: Uuid) -> Result<Option<AnomalyRow>> {
+        let row = sqlx::query_as::<_, AnomalyRow>(
+            r#"
+            SELECT
+                anomaly_id, detected_at, metric_name, anomaly_type,
+                severity, value, expected_value, confidence_score, context
+            FROM anomalies
+            WHERE anomaly_id = $1
+            "#,
+        )
+        .bind(anomaly_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load anomaly")?;
+
+        Ok(row)
+    }
+
+    /// Recent anomalies with their acknowledgement, newest first
+    #[instrument(skip(self))]
+    pub async fn query_anomaly_statuses(
+        &self,
+        since: DateTime<Utc>,
+        unacknowledged_only: bool,
+        limit: i64,
+    ) -> Result<Vec<AnomalyStatusRow>> {
+        let rows = sqlx::query_as::<_, AnomalyStatusRow>(
+            r#"
+            SELECT
+                a.anomaly_id, a.detected_at, a.metric_name, a.anomaly_type,
+                a.severity, a.value, a.expected_value, a.confidence_score, a.context,
+                k.acknowledged_at, k.acknowledged_by
+            FROM anomalies a
+            LEFT JOIN anomaly_acknowledgements k ON k.anomaly_id = a.anomaly_id
+            WHERE a.detected_at >= $1
+              AND (NOT $2 OR k.anomaly_id IS NULL)
+            ORDER BY a.detected_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(since)
+        .bind(unacknowledged_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query anomaly statuses")?;
+
+        Ok(rows)
+    }
+
+    /// Acknowledge an anomaly, replacing any earlier acknowledgement
+    #[instrument(skip(self))]
+    pub async fn acknowledge_anomaly(
+        &self,
+        anomaly_id: Uuid,
+        acknowledged_by: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<DateTime<Utc>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO anomaly_acknowledgements (anomaly_id, acknowledged_by, note, acknowledged_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (anomaly_id) DO UPDATE SET
+                acknowledged_by = EXCLUDED.acknowledged_by,
+                note = EXCLUDED.note,
+                acknowledged_at = EXCLUDED.acknowledged_at
+            RETURNING acknowledged_at
+            "#,
+        )
+        .bind(anomaly_id)
+        .bind(acknowledged_by)
+        .bind(note)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to acknowledge anomaly")?;
+
+        Ok(row.try_get("acknowledged_at")?)
+    }
+
+    // ========== Anomaly Feedback ==========
+
+    /// Store an operator verdict, replacing any earlier verdict on the same anomaly
+    #[instrument(skip(self, feedback), fields(metric_name = %feedback.metric_name))]
+    pub async fn store_anomaly_feedback(&self, feedback: &AnomalyFeedback) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO anomaly_feedback (
+                feedback_id, anomaly_id, metric_name, verdict, operator, note, recorded_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (anomaly_id) WHERE anomaly_id IS NOT NULL DO UPDATE SET
+                verdict = EXCLUDED.verdict,
+                operator = EXCLUDED.operator,
+                note = EXCLUDED.note,
+                recorded_at = EXCLUDED.recorded_at
+            "#,
+        )
+        .bind(feedback.feedback_id)
+        .bind(feedback.anomaly_id)
+        .bind(&feedback.metric_name)
+        .bind(feedback.verdict.as_str())
+        .bind(&feedback.operator)
+        .bind(&feedback.note)
+        .bind(feedback.recorded_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store anomaly feedback")?;
+
+        Ok(())
+    }
+
+    /// Verdict counts per metric
+    #[instrument(skip(self))]
+    pub async fn query_anomaly_feedback_tallies(&self) -> Result<Vec<FeedbackTallyRow>> {
+        let rows = sqlx::query_as::<_, FeedbackTallyRow>(
+            r#"
+            SELECT
+                metric_name,
+                COUNT(*) FILTER (WHERE verdict = 'true_positive') AS true_positives,
+                COUNT(*) FILTER (WHERE verdict = 'false_positive') AS false_positives,
+                COUNT(*) FILTER (WHERE verdict = 'false_negative') AS false_negatives
+            FROM anomaly_feedback
+            GROUP BY metric_name
+            ORDER BY metric_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query anomaly feedback")?;
+
+        Ok(rows)
+    }
+
+    // ========== Alert Silences ==========
+
+    /// Store an alert silence
+    #[instrument(skip(self, silence), fields(silence_id = %silence.silence_id))]
+    pub async fn store_alert_silence(&self, silence: &Silence) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO alert_silences (
+                silence_id, alert_type, tags, starts_at, ends_at, created_by, comment
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(silence.silence_id)
+        .bind(&silence.alert_type)
+        .bind(serde_json::to_value(&silence.tags)?)
+        .bind(silence.starts_at)
+        .bind(silence.ends_at)
+        .bind(&silence.created_by)
+        .bind(&silence.comment)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store alert silence")?;
+
+        Ok(())
+    }
+
+    /// Silences that have not yet ended
+    #[instrument(skip(self))]
+    pub async fn query_active_silences(&self, now: DateTime<Utc>) -> Result<Vec<Silence>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT silence_id, alert_type, tags, starts_at, ends_at, created_by, comment
+            FROM alert_silences
+            WHERE ends_at > $1
+            ORDER BY starts_at
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query alert silences")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<Silence> {
+                let tags: serde_json::Value = row.try_get("tags")?;
+                Ok(Silence {
+                    silence_id: row.try_get("silence_id")?,
+                    alert_type: row.try_get("alert_type")?,
+                    tags: serde_json::from_value(tags).unwrap_or_default(),
+                    starts_at: row.try_get("starts_at")?,
+                    ends_at: row.try_get("ends_at")?,
+                    created_by: row.try_get("created_by")?,
+                    comment: row.try_get("comment")?,
+                })
+            })
+            .collect()
+    }
+
+    /// End a silence at `now`, returning whether it was still active
+    #[instrument(skip(self))]
+    pub async fn expire_alert_silence(&self, silence_id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE alert_silences SET ends_at = $2 WHERE silence_id = $1 AND ends_at > $2",
+        )
+        .bind(silence_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to expire alert silence")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ========== Alert Rules ==========
+
+    /// All current alert rules
+    #[instrument(skip(self))]
+    pub async fn query_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        let rows = sqlx::query("SELECT rule FROM alert_rules ORDER BY rule_id")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query alert rules")?;
+
+        rows.into_iter()
+            .map(|row| Ok(row.try_get::<Json<AlertRule>, _>("rule")?.0))
+            .collect()
+    }
+
+    /// Current version of an alert rule, if it exists
+    #[instrument(skip(self))]
+    pub async fn get_alert_rule(&self, rule_id: &str) -> Result<Option<AlertRule>> {
+        let row = sqlx::query("SELECT rule FROM alert_rules WHERE rule_id = $1")
+            .bind(rule_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to get alert rule")?;
+
+        row.map(|row| Ok(row.try_get::<Json<AlertRule>, _>("rule")?.0))
+            .transpose()
+    }
+
+    /// Revisions of an alert rule, newest first
+    #[instrument(skip(self))]
+    pub async fn query_alert_rule_revisions(
+        &self,
+        rule_id: &str,
+    ) -> Result<Vec<AlertRuleRevision>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT version, change, changed_by, changed_at, rule
+            FROM alert_rule_revisions
+            WHERE rule_id = $1
+            ORDER BY version DESC
+            "#,
+        )
+        .bind(rule_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query alert rule revisions")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<AlertRuleRevision> {
+                let change: String = row.try_get("change")?;
+                Ok(AlertRuleRevision {
+                    rule_id: rule_id.to_string(),
+                    version: row.try_get::<i64, _>("version")? as u64,
+                    change: serde_json::from_value(serde_json::Value::String(change))?,
+                    rule: row.try_get::<Json<AlertRule>, _>("rule")?.0,
+                    changed_by: row.try_get("changed_by")?,
+                    changed_at: row.try_get("changed_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Highest revision recorded for a rule id, including deleted rules
+    #[instrument(skip(self))]
+    pub async fn latest_alert_rule_version(&self, rule_id: &str) -> Result<Option<u64>> {
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM alert_rule_revisions WHERE rule_id = $1")
+                .bind(rule_id)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to query alert rule version")?;
+
+        Ok(version.map(|v| v as u64))
+    }
+
+    /// Apply rule writes and record their revisions in one transaction.
+    ///
+    /// Returns `false` and applies nothing when any write finds the rule
+    /// already existing (creates) or at a version other than the expected one.
+    #[instrument(skip(self, writes), fields(writes = writes.len()))]
+    pub async fn apply_alert_rule_writes(&self, writes: &[RuleWrite]) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        for write in writes {
+            let revision = &write.revision;
+            let result = match (revision.change, write.expected_version) {
+                (RuleChange::Created, _) => sqlx::query(
+                    r#"
+                    INSERT INTO alert_rules (rule_id, version, updated_at, rule)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (rule_id) DO NOTHING
+                    "#,
+                )
+                .bind(&revision.rule_id)
+                .bind(revision.version as i64)
+                .bind(revision.changed_at)
+                .bind(Json(&revision.rule))
+                .execute(&mut *tx)
+                .await
+                .context("Failed to insert alert rule")?,
+                (RuleChange::Updated, Some(expected)) => sqlx::query(
+                    r#"
+                    UPDATE alert_rules SET version = $2, updated_at = $3, rule = $4
+                    WHERE rule_id = $1 AND version = $5
+                    "#,
+                )
+                .bind(&revision.rule_id)
+                .bind(revision.version as i64)
+                .bind(revision.changed_at)
+                .bind(Json(&revision.rule))
+                .bind(expected as i64)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to update alert rule")?,
+                (RuleChange::Deleted, Some(expected)) => {
+                    sqlx::query("DELETE FROM alert_rules WHERE rule_id = $1 AND version = $2")
+                        .bind(&revision.rule_id)
+                        .bind(expected as i64)
+                        .execute(&mut *tx)
+                        .await
+                        .context("Failed to delete alert rule")?
+                }
+                (change, None) => {
+                    anyhow::bail!(
+                        "{:?} of alert rule {} needs an expected version",
+                        change,
+                        revision.rule_id
+                    )
+                }
+            };
+            if result.rows_affected() == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO alert_rule_revisions (rule_id, version, change, changed_by, changed_at, rule)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(&revision.rule_id)
+            .bind(revision.version as i64)
+            .bind(revision.change.as_str())
+            .bind(&revision.changed_by)
+            .bind(revision.changed_at)
+            .bind(Json(&revision.rule))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record alert rule revision")?;
+        }
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    // ========== Audit Log ==========
+
+    /// Store an audit record; storing the same event twice is a no-op
+    #[instrument(skip(self, record), fields(action = %record.action))]
+    pub async fn store_audit_record(&self, record: &AuditRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (
+                event_id, occurred_at, actor, action, resource_type, resource_id,
+                environment, record
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (event_id) DO NOTHING
+            "#,
+        )
+        .bind(record.event_id)
+        .bind(record.occurred_at)
+        .bind(&record.actor)
+        .bind(&record.action)
+        .bind(&record.resource_type)
+        .bind(&record.resource_id)
+        .bind(&record.environment)
+        .bind(Json(record))
+        .execute(&self.pool)
+        .await
+        .context("Failed to store audit record")?;
+
+        Ok(())
+    }
+
+    /// Audit records matching a query, newest first
+    #[instrument(skip(self))]
+    pub async fn query_audit_log(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT record
+            FROM audit_log
+            WHERE ($1::TEXT IS NULL OR actor = $1)
+              AND ($2::TEXT IS NULL OR action = $2)
+              AND ($3::TEXT IS NULL OR resource_type = $3)
+              AND ($4::TEXT IS NULL OR resource_id = $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR occurred_at >= $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR occurred_at < $6)
+            ORDER BY occurred_at DESC
+            LIMIT $7
+            "#,
+        )
+        .bind(&query.actor)
+        .bind(&query.action)
+        .bind(&query.resource_type)
+        .bind(&query.resource_id)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(query.limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query audit log")?;
+
+        rows.into_iter()
+            .map(|row| Ok(row.try_get::<Json<AuditRecord>, _>("record")?.0))
+            .collect()
+    }
+
+    // ========== Maintenance Windows ==========
+
+    /// Store a maintenance window
+    #[instrument(skip(self, window), fields(window_id = %window.window_id))]
+    pub async fn store_maintenance_window(&self, window: &MaintenanceWindow) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO maintenance_windows (
+                window_id, environment, service, action, starts_at, ends_at, created_by, comment
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(window.window_id)
+        .bind(&window.environment)
+        .bind(&window.service)
+        .bind(window.action.as_str())
+        .bind(window.starts_at)
+        .bind(window.ends_at)
+        .bind(&window.created_by)
+        .bind(&window.comment)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store maintenance window")?;
+
+        Ok(())
+    }
+
+    /// Maintenance windows that have not yet ended
+    #[instrument(skip(self))]
+    pub async fn query_maintenance_windows(&self, now: DateTime<Utc>) -> Result<Vec<MaintenanceWindow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT window_id, environment, service, action, starts_at, ends_at, created_by, comment
+            FROM maintenance_windows
+            WHERE ends_at > $1
+            ORDER BY starts_at
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query maintenance windows")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<MaintenanceWindow> {
+                let action: String = row.try_get("action")?;
+                Ok(MaintenanceWindow {
+                    window_id: row.try_get("window_id")?,
+                    environment: row.try_get("environment")?,
+                    service: row.try_get("service")?,
+                    action: action.parse().unwrap_or_default(),
+                    starts_at: row.try_get("starts_at")?,
+                    ends_at: row.try_get("ends_at")?,
+                    created_by: row.try_get("created_by")?,
+                    comment: row.try_get("comment")?,
+                })
+            })
+            .collect()
+    }
+
+    /// End a maintenance window at `now`, or cancel it if it has not started,
+    /// returning whether it had not yet ended
+    #[instrument(skip(self))]
+    pub async fn end_maintenance_window(&self, window_id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE maintenance_windows
+            SET starts_at = LEAST(starts_at, $2), ends_at = $2
+            WHERE window_id = $1 AND ends_at > $2
+            "#,
+        )
+        .bind(window_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to end maintenance window")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ========== Incidents ==========
+
+    /// Store an incident, replacing any earlier version of it
+    #[instrument(skip(self, incident), fields(incident_id = %incident.incident_id))]
+    pub async fn upsert_incident(&self, incident: &Incident) -> Result<()> {
+        let severity = serde_json::to_value(&incident.severity)?;
+        sqlx::query(
+            r#"
+            INSERT INTO incidents (
+                incident_id, title, status, severity, environment,
+                opened_at, updated_at, resolved_at, incident
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (incident_id) DO UPDATE SET
+                title = EXCLUDED.title,
+                status = EXCLUDED.status,
+                severity = EXCLUDED.severity,
+                updated_at = EXCLUDED.updated_at,
+                resolved_at = EXCLUDED.resolved_at,
+                incident = EXCLUDED.incident
+            "#,
+        )
+        .bind(incident.incident_id)
+        .bind(&incident.title)
+        .bind(incident.status.as_str())
+        .bind(severity.as_str())
+        .bind(&incident.environment)
+        .bind(incident.opened_at)
+        .bind(incident.updated_at)
+        .bind(incident.resolved_at)
+        .bind(Json(incident))
+        .execute(&self.pool)
+        .await
+        .context("Failed to store incident")?;
+
+        Ok(())
+    }
+
+    /// Unresolved incidents, and those resolved since `resolved_since`
+    #[instrument(skip(self))]
+    pub async fn query_incidents(&self, resolved_since: DateTime<Utc>) -> Result<Vec<Incident>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT incident
+            FROM incidents
+            WHERE resolved_at IS NULL OR resolved_at >= $1
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .bind(resolved_since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query incidents")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<Incident> {
+                let Json(incident) = row.try_get::<Json<Incident>, _>("incident")?;
+                Ok(incident)
+            })
+            .collect()
+    }
+
+    /// Delete an incident, returning whether it existed
+    #[instrument(skip(self))]
+    pub async fn delete_incident(&self, incident_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM incidents WHERE incident_id = $1")
+            .bind(incident_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete incident")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ========== Webhooks ==========
+
+    /// Store a webhook registration
+    #[instrument(skip(self, webhook), fields(webhook_id = %webhook.webhook_id))]
+    pub async fn store_webhook(&self, webhook: &WebhookSubscription) -> Result<()> {
+        let min_severity = webhook
+            .min_severity
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()?;
+        sqlx::query(
+            r#"
+            INSERT INTO webhooks (
+                webhook_id, url, secret, event_types, min_severity, environment,
+                description, created_by, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(webhook.webhook_id)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(serde_json::to_value(&webhook.event_types)?)
+        .bind(min_severity.as_ref().and_then(|s| s.as_str()))
+        .bind(&webhook.environment)
+        .bind(&webhook.description)
+        .bind(&webhook.created_by)
+        .bind(webhook.created_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store webhook")?;
+
+        Ok(())
+    }
+
+    /// Every webhook registration, oldest first
+    #[instrument(skip(self))]
+    pub async fn query_webhooks(&self) -> Result<Vec<WebhookSubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                webhook_id, url, secret, event_types, min_severity, environment,
+                description, created_by, created_at
+            FROM webhooks
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query webhooks")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<WebhookSubscription> {
+                let min_severity: Option<String> = row.try_get("min_severity")?;
+                Ok(WebhookSubscription {
+                    webhook_id: row.try_get("webhook_id")?,
+                    url: row.try_get("url")?,
+                    secret: row.try_get("secret")?,
+                    event_types: serde_json::from_value(row.try_get("event_types")?)?,
+                    min_severity: min_severity
+                        .map(|s| serde_json::from_value(serde_json::Value::String(s)))
+                        .transpose()?,
+                    environment: row.try_get("environment")?,
+                    description: row.try_get("description")?,
+                    created_by: row.try_get("created_by")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete a webhook and its delivery log, returning whether it existed
+    #[instrument(skip(self))]
+    pub async fn delete_webhook(&self, webhook_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE webhook_id = $1")
+            .bind(webhook_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete webhook")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store a webhook delivery, replacing any earlier version of it
+    #[instrument(skip(self, delivery), fields(delivery_id = %delivery.delivery_id))]
+    pub async fn upsert_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (
+                delivery_id, webhook_id, event_type, status, attempts,
+                created_at, next_attempt_at, delivery
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (delivery_id) DO UPDATE SET
+                status = EXCLUDED.status,
+                attempts = EXCLUDED.attempts,
+                next_attempt_at = EXCLUDED.next_attempt_at,
+                delivery = EXCLUDED.delivery
+            "#,
+        )
+        .bind(delivery.delivery_id)
+        .bind(delivery.webhook_id)
+        .bind(delivery.event.event_type.as_str())
+        .bind(delivery.status.as_str())
+        .bind(delivery.attempts as i32)
+        .bind(delivery.created_at)
+        .bind(delivery.next_attempt_at)
+        .bind(Json(delivery))
+        .execute(&self.pool)
+        .await
+        .context("Failed to store webhook delivery")?;
+
+        Ok(())
+    }
+
+    /// Deliveries to a webhook, newest first
+    #[instrument(skip(self))]
+    pub async fn query_webhook_deliveries(
+        &self,
+        webhook_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT delivery
+            FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query webhook deliveries")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<WebhookDelivery> {
+                let Json(delivery) = row.try_get::<Json<WebhookDelivery>, _>("delivery")?;
+                Ok(delivery)
+            })
+            .collect()
+    }
+
+    /// A logged webhook delivery
+    #[instrument(skip(self))]
+    pub async fn query_webhook_delivery(&self, delivery_id: Uuid) -> Result<Option<WebhookDelivery>> {
+        let row = sqlx::query("SELECT delivery FROM webhook_deliveries WHERE delivery_id = $1")
+            .bind(delivery_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to query webhook delivery")?;
+
+        row.map(|row| -> Result<WebhookDelivery> {
+            let Json(delivery) = row.try_get::<Json<WebhookDelivery>, _>("delivery")?;
+            Ok(delivery)
+        })
+        .transpose()
+    }
+
+    /// Deliveries still waiting for an attempt, due soonest first
+    #[instrument(skip(self))]
+    pub async fn query_pending_webhook_deliveries(&self) -> Result<Vec<WebhookDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT delivery
+            FROM webhook_deliveries
+            WHERE status = 'pending'
+            ORDER BY next_attempt_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query pending webhook deliveries")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<WebhookDelivery> {
+                let Json(delivery) = row.try_get::<Json<WebhookDelivery>, _>("delivery")?;
+                Ok(delivery)
+            })
+            .collect()
+    }
+
+    // ========== Request Costs ==========
+
+    /// Insert or refresh attributed request costs
+    #[instrument(skip(self, records), fields(records = records.len()))]
+    pub async fn upsert_request_costs(&self, records: &[RequestCost]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<&str> = records.iter().map(|r| r.request_id.as_str()).collect();
+        let values = records
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        sqlx::query(
+            r#"
+            INSERT INTO request_costs (
+                request_id, model_id, started_at, duration_ms, cost_usd, tags, record
+            )
+            SELECT
+                request_id,
+                record->>'model_id',
+                (record->>'started_at')::timestamptz,
+                (record->>'duration_ms')::bigint,
+                (record->>'cost_usd')::double precision,
+                record->'tags',
+                record
+            FROM UNNEST($1::text[], $2::jsonb[]) AS r(request_id, record)
+            ON CONFLICT (request_id) DO UPDATE SET
+                model_id = EXCLUDED.model_id,
+                started_at = EXCLUDED.started_at,
+                duration_ms = EXCLUDED.duration_ms,
+                cost_usd = EXCLUDED.cost_usd,
+                tags = EXCLUDED.tags,
+                record = EXCLUDED.record
+            "#,
+        )
+        .bind(ids)
+        .bind(values)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store request costs")?;
+
+        Ok(())
+    }
+
+    /// Attributed request costs matching a query, most expensive (or slowest) first
+    #[instrument(skip(self))]
+    pub async fn query_request_costs(&self, query: &RequestCostQuery) -> Result<Vec<RequestCost>> {
+        let sql = format!(
+            r#"
+            SELECT record
+            FROM request_costs
+            WHERE started_at >= $1
+              AND duration_ms >= $2
+              AND ($3::TEXT IS NULL OR model_id = $3)
+              AND ($4::TEXT IS NULL OR tags->>'tenant_id' = $4)
+            ORDER BY {} DESC, started_at DESC
+            LIMIT $5
+            "#,
+            query.order.column()
+        );
+        let rows = sqlx::query(&sql)
+            .bind(query.since)
+            .bind(query.min_duration_ms as i64)
+            .bind(&query.model_id)
+            .bind(&query.tenant_id)
+            .bind(query.limit)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query request costs")?;
+
+        rows.into_iter()
+            .map(|row| -> Result<RequestCost> {
+                let Json(record) = row.try_get::<Json<RequestCost>, _>("record")?;
+                Ok(record)
+            })
+            .collect()
+    }
+
+    // ========== Detector Snapshots ==========
+
+    /// Store an anomaly detector snapshot
+    #[instrument(skip(self, snapshot))]
+    pub async fn store_detector_snapshot(
+        &self,
+        format_version: i32,
+        taken_at: DateTime<Utc>,
+        snapshot: &serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO anomaly_detector_snapshots (format_version, taken_at, snapshot)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(format_version)
+        .bind(taken_at)
+        .bind(snapshot)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store detector snapshot")?;
+
+        Ok(())
+    }
+
+    /// Most recent anomaly detector snapshot
+    #[instrument(skip(self))]
+    pub async fn latest_detector_snapshot(&self) -> Result<Option<serde_json::Value>> {
+        let row = sqlx::query(
+            "SELECT snapshot FROM anomaly_detector_snapshots ORDER BY taken_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load detector snapshot")?;
+
+        row.map(|r| r.try_get("snapshot"))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Delete detector snapshots taken before `cutoff`, returning the number removed
+    #[instrument(skip(self))]
+    pub async fn delete_detector_snapshots_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM anomaly_detector_snapshots WHERE taken_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await
+            .context("Failed to expire detector snapshots")?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ========== Correlation Operations ==========
+
+    /// Store event correlation
+    #[instrument(skip(self))]
+    pub async fn store_correlation(
+        &self,
+        correlation_id: Uuid,
+        correlation_type: &str,
+        source_event_id: Uuid,
+        target_event_id: Uuid,
+        strength: f64,
+        metadata: &serde_json::Value,
+    ) -> Result<Uuid> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO correlations (
+                correlation_id, correlation_type, source_event_id,
+                target_event_id, strength, metadata, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            RETURNING correlation_id
+            "#
+        )
+        .bind(correlation_id)
+        .bind(correlation_type)
+        .bind(source_event_id)
+        .bind(target_event_id)
+        .bind(strength)
+        .bind(metadata)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to store correlation")?;
+
+        Ok(result.try_get("correlation_id")?)
+    }
+
+    /// Correlations detected in `[start, end)` with at least `min_strength`,
+    /// joined with both events, strongest first
+    #[instrument(skip(self))]
+    pub async fn query_correlation_edges(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        min_strength: f64,
+        limit: i64,
+    ) -> Result<Vec<CorrelationEdgeRow>> {
+        // Events are bounded by time as well so only the relevant chunks are joined
+        let rows = sqlx::query_as::<_, CorrelationEdgeRow>(
+            r#"
+            SELECT
+                c.correlation_type, c.strength,
+                s.event_id AS source_event_id, s.timestamp AS source_timestamp,
+                concat_ws('/', s.source_module #>> '{}',
+                    COALESCE(s.payload->'data'->>'custom_type', s.event_type #>> '{}')) AS source_signature,
+                s.severity #>> '{}' AS source_severity,
+                t.event_id AS target_event_id, t.timestamp AS target_timestamp,
+                concat_ws('/', t.source_module #>> '{}',
+                    COALESCE(t.payload->'data'->>'custom_type', t.event_type #>> '{}')) AS target_signature,
+                t.severity #>> '{}' AS target_severity
+            FROM correlations c
+            JOIN events s ON s.event_id = c.source_event_id
+                AND s.timestamp >= $1 - INTERVAL '1 day' AND s.timestamp < $2
+            JOIN events t ON t.event_id = c.target_event_id
+                AND t.timestamp >= $1 - INTERVAL '1 day' AND t.timestamp < $2
+            WHERE c.created_at >= $1 AND c.created_at < $2 AND c.strength >= $3
+            ORDER BY c.strength DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(min_strength)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query correlation edges")?;
+
+        Ok(rows)
+    }
+
+    // ========== Model Scorecards ==========
+
+    /// Store a model scorecard, replacing any scorecard for the same period
+    #[instrument(skip(self, scorecard), fields(model_id = %scorecard.model_id))]
+    pub async fn store_model_scorecard(&self, scorecard: &ModelScorecard) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO model_scorecards (
+                model_id, period_start, period_end, score, grade, scorecard, generated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (model_id, period_start)
+            DO UPDATE SET
+                period_end = EXCLUDED.period_end,
+                score = EXCLUDED.score,
+                grade = EXCLUDED.grade,
+                scorecard = EXCLUDED.scorecard,
+                generated_at = EXCLUDED.generated_at
+            "#
+        )
+        .bind(&scorecard.model_id)
+        .bind(scorecard.period_start)
+        .bind(scorecard.period_end)
+        .bind(scorecard.score)
+        .bind(scorecard.grade.map(|g| format!("{:?}", g)))
+        .bind(serde_json::to_value(scorecard)?)
+        .bind(scorecard.generated_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store model scorecard")?;
+
+        Ok(())
+    }
+
+    /// Query scorecards since a point in time, newest first
+    #[instrument(skip(self))]
+    pub async fn query_model_scorecards(
+        &self,
+        model_id: Option<&str>,
+        since: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<ModelScorecard>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT scorecard
+            FROM model_scorecards
+            WHERE period_start >= $1
+              AND ($2::TEXT IS NULL OR model_id = $2)
+            ORDER BY period_start DESC, model_id ASC
+            LIMIT $3
+            "#
+        )
+        .bind(since)
+        .bind(model_id)
+        .bind(limit.unwrap_or(500))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query model scorecards")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let scorecard: serde_json::Value = row.try_get("scorecard").ok()?;
+                serde_json::from_value(scorecard).ok()
+            })
+            .collect())
+    }
+
+    // ========== Usage Metering ==========
+
+    /// Upsert a monthly usage record
+    #[instrument(
+        skip(self, record),
+        fields(tenant = %record.consumer.tenant_id, period = %record.period)
+    )]
+    pub async fn store_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        let breakdown = serde_json::json!({
+            "api_calls_by_endpoint": record.api_calls_by_endpoint,
+            "alert_deliveries_by_channel": record.alert_deliveries_by_channel,
+        });
+
+        sqlx::query(
+            r#"
+            INSERT INTO usage_records (
+                tenant_id, api_key_id, period_start, period_end, api_calls,
+                queries, query_compute_ms, storage_bytes_latest, storage_bytes_peak,
+                alert_deliveries, breakdown, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (tenant_id, api_key_id, period_start)
+            DO UPDATE SET
+                api_calls = EXCLUDED.api_calls,
+                queries = EXCLUDED.queries,
+                query_compute_ms = EXCLUDED.query_compute_ms,
+                storage_bytes_latest = EXCLUDED.storage_bytes_latest,
+                storage_bytes_peak = GREATEST(usage_records.storage_bytes_peak, EXCLUDED.storage_bytes_peak),
+                alert_deliveries = EXCLUDED.alert_deliveries,
+                breakdown = EXCLUDED.breakdown,
+                updated_at = EXCLUDED.updated_at
+            "#
+        )
+        .bind(&record.consumer.tenant_id)
+        .bind(record.consumer.api_key_id.as_deref().unwrap_or(""))
+        .bind(record.period.start())
+        .bind(record.period.end())
+        .bind(record.api_calls as i64)
+        .bind(record.queries as i64)
+        .bind(record.query_compute_ms as i64)
+        .bind(record.storage_bytes_latest as i64)
+        .bind(record.storage_bytes_peak as i64)
+        .bind(record.alert_deliveries as i64)
+        .bind(&breakdown)
+        .bind(record.updated_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store usage record")?;
+
+        Ok(())
+    }
+
+    /// Query usage records for a monthly period
+    #[instrument(skip(self))]
+    pub async fn query_usage_records(&self, period: UsagePeriod) -> Result<Vec<UsageRecord>> {
+        let rows = sqlx::query_as::<_, UsageRecordRow>(
+            r#"
+            SELECT
+                tenant_id, api_key_id, api_calls, queries, query_compute_ms,
+                storage_bytes_latest, storage_bytes_peak, alert_deliveries,
+                breakdown, updated_at
+            FROM usage_records
+            WHERE period_start = $1
+            ORDER BY tenant_id, api_key_id
+            "#
+        )
+        .bind(period.start())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query usage records")?;
+
+        Ok(rows.into_iter().map(|row| row.into_record(period)).collect())
+    }
+
+    /// Bytes of stored events per tenant
+    #[instrument(skip(self))]
+    pub async fn query_tenant_storage(&self) -> Result<Vec<(String, u64)>> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT tags->>'tenant_id' AS tenant_id, SUM(pg_column_size(events.*))::BIGINT AS bytes
+            FROM events
+            WHERE tags->>'tenant_id' <> ''
+            GROUP BY tags->>'tenant_id'
+            ORDER BY tenant_id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query tenant storage")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(tenant_id, bytes)| (tenant_id, bytes.max(0) as u64))
+            .collect())
+    }
+
+    // ========== Retention Policies ==========
+
+    /// Query the retention and compression policy jobs registered on hypertables
+    #[instrument(skip(self))]
+    pub async fn query_hypertable_policies(&self) -> Result<Vec<HypertablePolicyRow>> {
+        let rows = sqlx::query_as::<_, HypertablePolicyRow>(
+            r#"
+            SELECT
+                hypertable_name,
+                proc_name,
+                EXTRACT(EPOCH FROM COALESCE(
+                    config->>'drop_after',
+                    config->>'compress_after'
+                )::INTERVAL)::DOUBLE PRECISION AS after_secs
+            FROM timescaledb_information.jobs
+            WHERE proc_name IN ('policy_retention', 'policy_compression')
+              AND hypertable_schema = current_schema()
+            ORDER BY hypertable_name, proc_name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query hypertable policies")?;
+
+        Ok(rows)
+    }
+
+    /// Replace the retention policy of a hypertable, or drop it when `days` is `None`
+    #[instrument(skip(self))]
+    pub async fn set_retention_policy(&self, table: &str, days: Option<u32>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT remove_retention_policy($1::REGCLASS, if_exists => TRUE)")
+            .bind(table)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to remove retention policy")?;
+        if let Some(days) = days {
+            sqlx::query(
+                "SELECT add_retention_policy($1::REGCLASS, make_interval(days => $2), if_not_exists => TRUE)",
+            )
+            .bind(table)
+            .bind(days as i32)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to add retention policy")?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Replace the compression policy of a hypertable, or drop it when `days` is `None`
+    #[instrument(skip(self))]
+    pub async fn set_compression_policy(&self, table: &str, days: Option<u32>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT remove_compression_policy($1::REGCLASS, if_exists => TRUE)")
+            .bind(table)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to remove compression policy")?;
+        if let Some(days) = days {
+            sqlx::query(
+                "SELECT add_compression_policy($1::REGCLASS, make_interval(days => $2), if_not_exists => TRUE)",
+            )
+            .bind(table)
+            .bind(days as i32)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to add compression policy")?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // ========== Archival ==========
+
+    /// Start of each whole day before `before` that holds rows but has no archive manifest
+    #[instrument(skip(self))]
+    pub async fn query_unarchived_partitions(
+        &self,
+        table: ArchiveTable,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        // Identifiers come from the closed ArchiveTable set
+        let sql = format!(
+            r#"
+            SELECT DISTINCT date_trunc('day', t.{col}) AS partition_start
+            FROM {table} t
+            WHERE t.{col} < date_trunc('day', $1::TIMESTAMPTZ)
+              AND NOT EXISTS (
+                  SELECT 1 FROM archive_manifests m
+                  WHERE m.table_name = $2
+                    AND m.partition_start = date_trunc('day', t.{col})
+              )
+            ORDER BY partition_start
+            "#,
+            table = table.name(),
+            col = table.time_column(),
+        );
+
+        let partitions = sqlx::query_scalar::<_, DateTime<Utc>>(&sql)
+            .bind(before)
+            .bind(table.name())
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query unarchived partitions")?;
+
+        Ok(partitions)
+    }
+
+    /// Export the rows of a partition as JSON objects
+    #[instrument(skip(self))]
+    pub async fn export_partition_rows(
+        &self,
+        table: ArchiveTable,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let sql = format!(
+            "SELECT to_jsonb(t) FROM {table} t WHERE t.{col} >= $1 AND t.{col} < $2 ORDER BY t.{col}",
+            table = table.name(),
+            col = table.time_column(),
+        );
+
+        let rows = sqlx::query_scalar::<_, serde_json::Value>(&sql)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to export partition rows")?;
+
+        Ok(rows)
+    }
+
+    /// Insert archived rows back into their hypertable, skipping rows already present
+    #[instrument(skip(self, rows), fields(rows = rows.len()))]
+    pub async fn restore_archive_rows(
+        &self,
+        table: ArchiveTable,
+        rows: &[serde_json::Value],
+    ) -> Result<u64> {
+        let sql = format!(
+            "INSERT INTO {table} SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1) ON CONFLICT DO NOTHING",
+            table = table.name(),
+        );
+
+        let mut inserted = 0;
+        for chunk in rows.chunks(MAX_PAGE_SIZE as usize) {
+            inserted += sqlx::query(&sql)
+                .bind(serde_json::Value::Array(chunk.to_vec()))
+                .execute(&self.pool)
+                .await
+                .context("Failed to restore archive rows")?
+                .rows_affected();
+        }
+
+        Ok(inserted)
+    }
+
+    /// Record an archive manifest
+    #[instrument(skip(self, manifest), fields(uri = %manifest.object_uri))]
+    pub async fn store_archive_manifest(&self, manifest: &ArchiveManifest) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO archive_manifests (
+                archive_id, table_name, partition_start, partition_end, object_key,
+                object_uri, format, compression, row_count, uncompressed_bytes,
+                compressed_bytes, sha256, created_at, verified_at, restored_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#
+        )
+        .bind(manifest.archive_id)
+        .bind(&manifest.table_name)
+        .bind(manifest.partition_start)
+        .bind(manifest.partition_end)
+        .bind(&manifest.object_key)
+        .bind(&manifest.object_uri)
+        .bind(&manifest.format)
+        .bind(&manifest.compression)
+        .bind(manifest.row_count)
+        .bind(manifest.uncompressed_bytes)
+        .bind(manifest.compressed_bytes)
+        .bind(&manifest.sha256)
+        .bind(manifest.created_at)
+        .bind(manifest.verified_at)
+        .bind(manifest.restored_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store archive manifest")?;
+
+        Ok(())
+    }
+
+    /// Get an archive manifest by ID
+    #[instrument(skip(self))]
+    pub async fn get_archive_manifest(&self, archive_id: Uuid) -> Result<Option<ArchiveManifest>> {
+        let manifest = sqlx::query_as::<_, ArchiveManifest>(
+            "SELECT * FROM archive_manifests WHERE archive_id = $1",
+        )
+        .bind(archive_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to get archive manifest")?;
+
+        Ok(manifest)
+    }
+
+    /// Archive manifests of a table whose partitions overlap `[start, end)`
+    #[instrument(skip(self))]
+    pub async fn query_archive_manifests(
+        &self,
+        table: ArchiveTable,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ArchiveManifest>> {
+        let manifests = sqlx::query_as::<_, ArchiveManifest>(
+            r#"
+            SELECT * FROM archive_manifests
+            WHERE table_name = $1 AND partition_end > $2 AND partition_start < $3
+            ORDER BY partition_start
+            "#
+        )
+        .bind(table.name())
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query archive manifests")?;
+
+        Ok(manifests)
+    }
+
+    /// Stamp the time an archive was last restored
+    #[instrument(skip(self))]
+    pub async fn mark_archive_restored(&self, archive_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE archive_manifests SET restored_at = NOW() WHERE archive_id = $1")
+            .bind(archive_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark archive restored")?;
+
+        Ok(())
+    }
+
+    // ========== Adapter Sync Checkpoints ==========
+
+    /// Checkpoint of an incrementally synced adapter listing
+    #[instrument(skip(self))]
+    pub async fn query_sync_checkpoint(&self, source: &str) -> Result<Option<SyncCheckpoint>> {
+        let row = sqlx::query(
+            r#"
+            SELECT source, updated_since, synced_at, items_synced
+            FROM adapter_sync_checkpoints
+            WHERE source = $1
+            "#,
+        )
+        .bind(source)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query sync checkpoint")?;
+
+        row.map(|row| -> Result<SyncCheckpoint> {
+            let items_synced: i64 = row.try_get("items_synced")?;
+            Ok(SyncCheckpoint {
+                source: row.try_get("source")?,
+                updated_since: row.try_get("updated_since")?,
+                synced_at: row.try_get("synced_at")?,
+                items_synced: items_synced as u64,
+            })
+        })
+        .transpose()
+    }
+
+    /// Store or advance the checkpoint of an adapter listing
+    #[instrument(skip(self, checkpoint), fields(source = %checkpoint.source))]
+    pub async fn upsert_sync_checkpoint(&self, checkpoint: &SyncCheckpoint) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO adapter_sync_checkpoints (source, updated_since, synced_at, items_synced)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (source) DO UPDATE SET
+                updated_since = EXCLUDED.updated_since,
+                synced_at = EXCLUDED.synced_at,
+                items_synced = EXCLUDED.items_synced
+            "#,
+        )
+        .bind(&checkpoint.source)
+        .bind(checkpoint.updated_since)
+        .bind(checkpoint.synced_at)
+        .bind(checkpoint.items_synced as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store sync checkpoint")?;
+
+        Ok(())
+    }
+
+    // ========== Registry Mirror ==========
+
+    /// Mirrored registry records of one kind
+    #[instrument(skip(self))]
+    pub async fn query_mirror_records(&self, kind: &str) -> Result<Vec<MirrorRecordRow>> {
+        let rows = sqlx::query_as::<_, MirrorRecordRow>(
+            "SELECT entity_id, synced_at, record FROM registry_mirror WHERE kind = $1",
+        )
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query registry mirror")?;
+
+        Ok(rows)
+    }
+
+    /// Insert or refresh mirrored registry records of one kind
+    #[instrument(skip(self, records), fields(records = records.len()))]
+    pub async fn upsert_mirror_records(
+        &self,
+        kind: &str,
+        records: &[(String, serde_json::Value)],
+        synced_at: DateTime<Utc>,
+    ) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let (ids, values): (Vec<String>, Vec<serde_json::Value>) = records.iter().cloned().unzip();
+        sqlx::query(
+            r#"
+            INSERT INTO registry_mirror (kind, entity_id, synced_at, record)
+            SELECT $1, entity_id, $2, record
+            FROM UNNEST($3::text[], $4::jsonb[]) AS r(entity_id, record)
+            ON CONFLICT (kind, entity_id) DO UPDATE SET
+                synced_at = EXCLUDED.synced_at,
+                record = EXCLUDED.record
+            "#,
+        )
+        .bind(kind)
+        .bind(synced_at)
+        .bind(ids)
+        .bind(values)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store registry mirror records")?;
+
+        Ok(())
+    }
+
+    /// Delete mirrored records of one kind not refreshed since `cutoff`, returning the number removed
+    #[instrument(skip(self))]
+    pub async fn delete_mirror_records_before(
+        &self,
+        kind: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM registry_mirror WHERE kind = $1 AND synced_at < $2")
+            .bind(kind)
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await
+            .context("Failed to expire registry mirror records")?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ========== Health Check ==========
+
+    /// Check database health
+    pub async fn health_check(&self) -> Result<DatabaseHealth> {
+        let result = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM pg_stat_activity WHERE state = 'active') as active_connections,
+                (SELECT COUNT(*) FROM events WHERE timestamp > NOW() - INTERVAL '1 minute') as recent_events,
+                (SELECT pg_database_size(current_database())) as database_size
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok(DatabaseHealth {
             active_connections: result.try_get::<Option<i64>, _>("active_connections")?.unwrap_or(0),
@@ -512,6 +2611,33 @@ pub struct AggregatedMetricRow {
     pub stddev: Option<f64>,
     pub count: i64,
     pub sum: f64,
+    /// Bucketed distribution, when histograms are enabled
+    pub histogram: Option<Json<Histogram>>,
+}
+
+/// Mean of a metric for one value of a grouping tag
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MetricMeanRow {
+    pub tag_value: String,
+    pub metric_name: String,
+    pub mean: f64,
+}
+
+/// Metric series and when it last had a window
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MetricSeriesRow {
+    pub metric_name: String,
+    pub tags: serde_json::Value,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Retention or compression job registered on a hypertable
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HypertablePolicyRow {
+    pub hypertable_name: String,
+    /// `policy_retention` or `policy_compression`
+    pub proc_name: String,
+    pub after_secs: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -527,6 +2653,102 @@ pub struct AnomalyRow {
     pub context: serde_json::Value,
 }
 
+/// An anomaly and its acknowledgement, if any
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AnomalyStatusRow {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub anomaly: AnomalyRow,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeedbackTallyRow {
+    pub metric_name: String,
+    pub true_positives: i64,
+    pub false_positives: i64,
+    pub false_negatives: i64,
+}
+
+impl FeedbackTallyRow {
+    pub fn tally(&self) -> FeedbackTally {
+        FeedbackTally {
+            true_positives: self.true_positives.max(0) as u64,
+            false_positives: self.false_positives.max(0) as u64,
+            false_negatives: self.false_negatives.max(0) as u64,
+        }
+    }
+}
+
+/// A stored correlation with the events at either end. Signatures identify
+/// the kind of event as `<source_module>/<custom type or event type>`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CorrelationEdgeRow {
+    pub correlation_type: String,
+    pub strength: f64,
+    pub source_event_id: Uuid,
+    pub source_timestamp: DateTime<Utc>,
+    pub source_signature: String,
+    pub source_severity: String,
+    pub target_event_id: Uuid,
+    pub target_timestamp: DateTime<Utc>,
+    pub target_signature: String,
+    pub target_severity: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UsageRecordRow {
+    pub tenant_id: String,
+    pub api_key_id: String,
+    pub api_calls: i64,
+    pub queries: i64,
+    pub query_compute_ms: i64,
+    pub storage_bytes_latest: i64,
+    pub storage_bytes_peak: i64,
+    pub alert_deliveries: i64,
+    pub breakdown: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UsageRecordRow {
+    /// Convert the row back into a usage record for the given period
+    pub fn into_record(self, period: UsagePeriod) -> UsageRecord {
+        let breakdown = |key: &str| {
+            self.breakdown
+                .get(key)
+                .cloned()
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default()
+        };
+
+        UsageRecord {
+            consumer: Consumer {
+                tenant_id: self.tenant_id.clone(),
+                api_key_id: (!self.api_key_id.is_empty()).then(|| self.api_key_id.clone()),
+            },
+            period,
+            api_calls: self.api_calls.max(0) as u64,
+            api_calls_by_endpoint: breakdown("api_calls_by_endpoint"),
+            queries: self.queries.max(0) as u64,
+            query_compute_ms: self.query_compute_ms.max(0) as u64,
+            storage_bytes_latest: self.storage_bytes_latest.max(0) as u64,
+            storage_bytes_peak: self.storage_bytes_peak.max(0) as u64,
+            alert_deliveries: self.alert_deliveries.max(0) as u64,
+            alert_deliveries_by_channel: breakdown("alert_deliveries_by_channel"),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Mirrored registry record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MirrorRecordRow {
+    pub entity_id: String,
+    pub synced_at: DateTime<Utc>,
+    pub record: serde_json::Value,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseHealth {
     pub active_connections: i64,
@@ -0,0 +1,348 @@
+//! Query Planning
+//!
+//! Queries over long ranges touch many chunks, and chunks past a hypertable's
+//! compression threshold are much slower to read. Before a time-series query
+//! runs, the planner estimates how many rows and chunks it will scan and how
+//! many of those chunks are compressed, attaches warnings when the estimate
+//! exceeds the configured thresholds, and can serve a coarser rollup window
+//! instead when the requested one would return too many rows.
+//!
+//! Chunk boundaries follow TimescaleDB's alignment to multiples of the chunk
+//! interval since the epoch. Compression thresholds start from the schema
+//! defaults and are refreshed from the policy jobs registered in the database.
+
+use super::Database;
+use crate::models::api::{QueryMetrics, QueryResult, QueryStatus};
+use crate::models::metrics::TimeWindow;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Query planner configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPlannerConfig {
+    /// Rows per series above which a query is downgraded or warned about
+    pub max_rows: u64,
+    /// Compressed chunks a query may read before it is warned about
+    pub warn_compressed_chunks: u64,
+    /// Serve a coarser window instead of exceeding `max_rows`
+    pub auto_downgrade: bool,
+    /// Hypertable chunk interval
+    pub chunk_interval_hours: u64,
+}
+
+impl Default for QueryPlannerConfig {
+    fn default() -> Self {
+        Self {
+            max_rows: 10_000,
+            warn_compressed_chunks: 4,
+            auto_downgrade: true,
+            // TimescaleDB's default chunk interval
+            chunk_interval_hours: 24 * 7,
+        }
+    }
+}
+
+impl QueryPlannerConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_rows: std::env::var("QUERY_PLANNER_MAX_ROWS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_rows),
+            warn_compressed_chunks: std::env::var("QUERY_PLANNER_WARN_COMPRESSED_CHUNKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.warn_compressed_chunks),
+            auto_downgrade: std::env::var("QUERY_PLANNER_AUTO_DOWNGRADE")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(defaults.auto_downgrade),
+            chunk_interval_hours: std::env::var("QUERY_PLANNER_CHUNK_INTERVAL_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.chunk_interval_hours),
+        }
+    }
+
+    fn chunk_interval_secs(&self) -> i64 {
+        self.chunk_interval_hours.max(1) as i64 * 3600
+    }
+}
+
+/// Estimated cost of scanning a range
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanEstimate {
+    /// Rows per series
+    pub rows: u64,
+    /// Chunks overlapping the range
+    pub chunks: u64,
+    /// Overlapping chunks past the compression threshold
+    pub compressed_chunks: u64,
+}
+
+/// Plan for one time-series query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub table: String,
+    pub requested_window: TimeWindow,
+    /// Window the query should run against
+    pub window: TimeWindow,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub estimate: ScanEstimate,
+    pub warnings: Vec<String>,
+}
+
+impl QueryPlan {
+    /// Whether the planner chose a coarser window than requested
+    pub fn downgraded(&self) -> bool {
+        self.window != self.requested_window
+    }
+
+    /// Wrap query output with this plan's estimate and warnings
+    pub fn into_result<T>(
+        self,
+        data: T,
+        records_returned: u64,
+        execution_time: std::time::Duration,
+    ) -> QueryResult<T> {
+        QueryResult {
+            query_id: Uuid::new_v4(),
+            status: QueryStatus::Success,
+            data: Some(data),
+            metrics: QueryMetrics {
+                execution_time_ms: execution_time.as_millis() as u64,
+                records_scanned: self.estimate.rows,
+                records_returned,
+                bytes_processed: 0,
+                from_cache: false,
+                cache_ttl: None,
+            },
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// Compression thresholds from the schema, used until policies are refreshed
+const DEFAULT_COMPRESS_AFTER_DAYS: [(&str, i64); 2] = [("events", 7), ("aggregated_metrics", 30)];
+
+/// Retention-aware query planner
+pub struct QueryPlanner {
+    config: QueryPlannerConfig,
+    compress_after: RwLock<HashMap<String, Duration>>,
+}
+
+impl QueryPlanner {
+    pub fn new(config: QueryPlannerConfig) -> Self {
+        Self {
+            config,
+            compress_after: RwLock::new(
+                DEFAULT_COMPRESS_AFTER_DAYS
+                    .iter()
+                    .map(|(table, days)| (table.to_string(), Duration::days(*days)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Load compression thresholds from the policy jobs registered in the database,
+    /// returning how many tables have one
+    pub async fn refresh_policies(&self, database: &Database) -> Result<usize> {
+        let policies = database.query_hypertable_policies().await?;
+        let thresholds: HashMap<String, Duration> = policies
+            .into_iter()
+            .filter(|policy| policy.proc_name == "policy_compression")
+            .filter_map(|policy| {
+                let secs = policy.after_secs?;
+                Some((policy.hypertable_name, Duration::seconds(secs as i64)))
+            })
+            .collect();
+        let count = thresholds.len();
+        *self.compress_after.write() = thresholds;
+        debug!(
+            tables = count,
+            "Refreshed query planner compression thresholds"
+        );
+        Ok(count)
+    }
+
+    /// Estimate the cost of scanning `[start, end)` of `table` at `window` as of `now`
+    pub fn estimate_at(
+        &self,
+        table: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> ScanEstimate {
+        if end <= start {
+            return ScanEstimate::default();
+        }
+        let window_secs = window.to_seconds() as i64;
+        let range_secs = (end - start).num_seconds().max(1);
+        let rows = ((range_secs + window_secs - 1) / window_secs) as u64;
+
+        let interval = self.config.chunk_interval_secs();
+        let first = start.timestamp().div_euclid(interval);
+        let last = (end.timestamp() - 1).div_euclid(interval);
+        let chunks = (last - first + 1) as u64;
+
+        // A chunk is compressed once all of it is older than the threshold
+        let compressed_chunks = match self.compress_after.read().get(table) {
+            Some(after) => {
+                let last_compressed = (now - *after).timestamp().div_euclid(interval) - 1;
+                (last.min(last_compressed) - first + 1).max(0) as u64
+            }
+            None => 0,
+        };
+
+        ScanEstimate {
+            rows,
+            chunks,
+            compressed_chunks,
+        }
+    }
+
+    /// Plan a query over `[start, end)` of `table` at `window`
+    pub fn plan(
+        &self,
+        table: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> QueryPlan {
+        self.plan_at(table, window, start, end, Utc::now())
+    }
+
+    /// `plan` as of `now`
+    pub fn plan_at(
+        &self,
+        table: &str,
+        requested_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> QueryPlan {
+        let requested = self.estimate_at(table, requested_window, start, end, now);
+        let mut window = requested_window;
+        let mut estimate = requested;
+        let mut warnings = Vec::new();
+
+        if requested.rows > self.config.max_rows {
+            if self.config.auto_downgrade {
+                // Finest coarser window within the row limit, else the coarsest
+                let coarser = TimeWindow::ALL
+                    .iter()
+                    .filter(|w| w.to_seconds() > requested_window.to_seconds())
+                    .map(|w| (*w, self.estimate_at(table, *w, start, end, now)));
+                for (candidate, candidate_estimate) in coarser {
+                    window = candidate;
+                    estimate = candidate_estimate;
+                    if candidate_estimate.rows <= self.config.max_rows {
+                        break;
+                    }
+                }
+            }
+            if window != requested_window {
+                warnings.push(format!(
+                    "Range spans {} {} windows, above the limit of {}; serving {} rollups instead",
+                    requested.rows,
+                    requested_window.as_str(),
+                    self.config.max_rows,
+                    window.as_str()
+                ));
+            }
+            if estimate.rows > self.config.max_rows {
+                warnings.push(format!(
+                    "Query returns about {} rows per series, above the limit of {}; narrow the time range",
+                    estimate.rows, self.config.max_rows
+                ));
+            }
+        }
+
+        if estimate.compressed_chunks >= self.config.warn_compressed_chunks.max(1) {
+            warnings.push(format!(
+                "Query reads {} of {} chunks from compressed storage; expect slower responses",
+                estimate.compressed_chunks, estimate.chunks
+            ));
+        }
+
+        QueryPlan {
+            table: table.to_string(),
+            requested_window,
+            window,
+            start,
+            end,
+            estimate,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_counts_rows_chunks_and_compressed_chunks() {
+        let planner = QueryPlanner::new(QueryPlannerConfig {
+            chunk_interval_hours: 24,
+            ..Default::default()
+        });
+        let now = DateTime::from_timestamp(100 * 86_400, 0).unwrap();
+        let start = now - Duration::days(40);
+
+        let estimate =
+            planner.estimate_at("aggregated_metrics", TimeWindow::OneHour, start, now, now);
+        assert_eq!(estimate.rows, 40 * 24);
+        assert_eq!(estimate.chunks, 40);
+        // Chunks entirely older than 30 days
+        assert_eq!(estimate.compressed_chunks, 10);
+
+        let unknown = planner.estimate_at("sessions", TimeWindow::OneHour, start, now, now);
+        assert_eq!(unknown.compressed_chunks, 0);
+    }
+
+    #[test]
+    fn test_plan_downgrades_long_ranges_and_warns() {
+        let planner = QueryPlanner::new(QueryPlannerConfig {
+            max_rows: 1_000,
+            ..Default::default()
+        });
+        let now = Utc::now();
+
+        let short = planner.plan_at(
+            "aggregated_metrics",
+            TimeWindow::FiveMinutes,
+            now - Duration::hours(24),
+            now,
+            now,
+        );
+        assert!(!short.downgraded());
+        assert!(short.warnings.is_empty());
+
+        let long = planner.plan_at(
+            "aggregated_metrics",
+            TimeWindow::FiveMinutes,
+            now - Duration::days(90),
+            now,
+            now,
+        );
+        assert_eq!(long.window, TimeWindow::SixHours);
+        assert_eq!(long.estimate.rows, 360);
+        assert!(long.warnings[0].contains("serving 6h rollups"));
+        assert!(long
+            .warnings
+            .iter()
+            .any(|w| w.contains("compressed storage")));
+
+        let result = long.into_result(Vec::<f64>::new(), 0, std::time::Duration::ZERO);
+        assert_eq!(result.metrics.records_scanned, 360);
+        assert_eq!(result.warnings.len(), 2);
+    }
+}
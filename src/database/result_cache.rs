@@ -0,0 +1,447 @@
+//! Aggregate Query Result Cache
+//!
+//! Dashboards re-issue the same aggregate queries over sliding ranges. Results
+//! are cached in process, keyed by the normalized query (metric, window, tag
+//! filter) and the range bucketed to `bucket_secs`, so requests whose bounds
+//! differ by less than a bucket share one result.
+//!
+//! Ranges that reach into recent windows are still being materialized and
+//! expire after `ttl_secs`; older ranges are stable and keep for
+//! `historical_ttl_secs`. Storing an aggregate row invalidates every cached
+//! result for that metric and window whose range covers the row, so corrected
+//! late windows are never served stale by the process that wrote them. The
+//! cache is bounded by entry count and by total rows; the oldest entries are
+//! evicted first.
+
+use super::AggregatedMetricRow;
+use crate::export::prometheus::HubMetrics;
+use crate::models::metrics::TimeWindow;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Query result cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCacheConfig {
+    /// Granularity query bounds are bucketed to
+    pub bucket_secs: u64,
+    /// TTL for ranges that reach into recent windows
+    pub ttl_secs: u64,
+    /// TTL for ranges that end before `recent_horizon_secs`
+    pub historical_ttl_secs: u64,
+    /// How far back windows may still be re-materialized
+    pub recent_horizon_secs: u64,
+    pub max_entries: usize,
+    /// Rows held across all entries
+    pub max_rows: usize,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 60,
+            ttl_secs: 30,
+            historical_ttl_secs: 3600,
+            recent_horizon_secs: 900,
+            max_entries: 1000,
+            max_rows: 500_000,
+        }
+    }
+}
+
+impl ResultCacheConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            bucket_secs: std::env::var("QUERY_CACHE_BUCKET_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.bucket_secs),
+            ttl_secs: std::env::var("QUERY_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.ttl_secs),
+            historical_ttl_secs: std::env::var("QUERY_CACHE_HISTORICAL_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.historical_ttl_secs),
+            recent_horizon_secs: std::env::var("QUERY_CACHE_RECENT_HORIZON_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.recent_horizon_secs),
+            max_entries: std::env::var("QUERY_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_entries),
+            max_rows: std::env::var("QUERY_CACHE_MAX_ROWS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_rows),
+        }
+    }
+
+    fn bucket(&self) -> i64 {
+        self.bucket_secs.max(1) as i64
+    }
+}
+
+/// Normalized aggregate query
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryKey {
+    pub metric_name: String,
+    pub time_window: TimeWindow,
+    /// Tag filter with keys sorted, empty when unfiltered
+    pub tags: String,
+    pub start_bucket: i64,
+    pub end_bucket: i64,
+}
+
+/// Aggregate rows with where they came from
+#[derive(Debug, Clone)]
+pub struct CachedQuery {
+    pub rows: Vec<AggregatedMetricRow>,
+    pub from_cache: bool,
+    /// Seconds until a cached result expires
+    pub cache_ttl: Option<u32>,
+}
+
+struct Entry {
+    rows: Arc<Vec<AggregatedMetricRow>>,
+    expires_at: DateTime<Utc>,
+    generation: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<QueryKey, Entry>,
+    /// Keys per metric and window, for invalidation
+    by_series: HashMap<(String, TimeWindow), HashSet<QueryKey>>,
+    /// Insertion order, for eviction
+    order: VecDeque<(u64, QueryKey)>,
+    generation: u64,
+    total_rows: usize,
+}
+
+impl CacheState {
+    fn remove(&mut self, key: &QueryKey) -> bool {
+        let Some(entry) = self.entries.remove(key) else {
+            return false;
+        };
+        self.total_rows -= entry.rows.len();
+        let series = (key.metric_name.clone(), key.time_window);
+        if let Some(keys) = self.by_series.get_mut(&series) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_series.remove(&series);
+            }
+        }
+        true
+    }
+}
+
+/// In-process cache of aggregate query results
+pub struct QueryResultCache {
+    config: ResultCacheConfig,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl QueryResultCache {
+    pub fn new(config: ResultCacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Normalize a query into its cache key
+    pub fn key(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: Option<&serde_json::Value>,
+    ) -> QueryKey {
+        let bucket = self.config.bucket();
+        QueryKey {
+            metric_name: metric_name.to_string(),
+            time_window,
+            tags: tags.map(canonical_tags).unwrap_or_default(),
+            start_bucket: start.timestamp().div_euclid(bucket),
+            end_bucket: end.timestamp().div_euclid(bucket),
+        }
+    }
+
+    /// Cached rows for a key as of `now`, with the seconds left until they expire
+    pub fn get_at(
+        &self,
+        key: &QueryKey,
+        now: DateTime<Utc>,
+    ) -> Option<(Arc<Vec<AggregatedMetricRow>>, u32)> {
+        let mut state = self.state.lock();
+        let cached = state
+            .entries
+            .get(key)
+            .map(|entry| (entry.rows.clone(), entry.expires_at));
+        let hit = match cached {
+            Some((rows, expires_at)) if expires_at > now => {
+                Some((rows, (expires_at - now).num_seconds().max(0) as u32))
+            }
+            Some(_) => {
+                state.remove(key);
+                None
+            }
+            None => None,
+        };
+        drop(state);
+
+        let counter = if hit.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        HubMetrics::global().record_cache_lookup("query_results", hit.is_some());
+        hit
+    }
+
+    /// Cache the rows for a query over a range ending at `end`, as of `now`
+    pub fn insert_at(
+        &self,
+        key: QueryKey,
+        rows: Vec<AggregatedMetricRow>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) {
+        if rows.len() > self.config.max_rows {
+            return;
+        }
+        let recent = end > now - Duration::seconds(self.config.recent_horizon_secs as i64);
+        let ttl = if recent {
+            self.config.ttl_secs
+        } else {
+            self.config.historical_ttl_secs
+        };
+
+        let mut state = self.state.lock();
+        state.remove(&key);
+        state.generation += 1;
+        let generation = state.generation;
+        state.total_rows += rows.len();
+        state
+            .by_series
+            .entry((key.metric_name.clone(), key.time_window))
+            .or_default()
+            .insert(key.clone());
+        state.order.push_back((generation, key.clone()));
+        state.entries.insert(
+            key,
+            Entry {
+                rows: Arc::new(rows),
+                expires_at: now + Duration::seconds(ttl as i64),
+                generation,
+            },
+        );
+
+        while state.entries.len() > self.config.max_entries
+            || state.total_rows > self.config.max_rows
+        {
+            let Some((generation, key)) = state.order.pop_front() else {
+                break;
+            };
+            // Skip keys that were re-inserted or already removed
+            let current = state.entries.get(&key).map(|entry| entry.generation);
+            if current == Some(generation) && state.remove(&key) {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drop cached results for `metric_name` at `time_window` whose range covers
+    /// `window_start`, returning how many were dropped
+    pub fn invalidate(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+    ) -> usize {
+        let bucket = window_start.timestamp().div_euclid(self.config.bucket());
+        let mut state = self.state.lock();
+        let Some(keys) = state.by_series.get(&(metric_name.to_string(), time_window)) else {
+            return 0;
+        };
+        let stale: Vec<QueryKey> = keys
+            .iter()
+            .filter(|key| key.start_bucket <= bucket && bucket <= key.end_bucket)
+            .cloned()
+            .collect();
+        for key in &stale {
+            state.remove(key);
+        }
+        self.invalidations
+            .fetch_add(stale.len() as u64, Ordering::Relaxed);
+        stale.len()
+    }
+
+    pub fn get_stats(&self) -> ResultCacheStats {
+        let state = self.state.lock();
+        ResultCacheStats {
+            entries: state.entries.len(),
+            rows: state.total_rows,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tag filter rendered with its keys sorted
+fn canonical_tags(tags: &serde_json::Value) -> String {
+    match tags.as_object() {
+        Some(object) => {
+            let mut pairs: Vec<_> = object.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+            pairs
+                .into_iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+        None => tags.to_string(),
+    }
+}
+
+/// Query result cache statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCacheStats {
+    pub entries: usize,
+    pub rows: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub evictions: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(window_start: DateTime<Utc>) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: "latency".to_string(),
+            time_window: "1m".to_string(),
+            window_start,
+            tags: serde_json::json!({}),
+            avg: 1.0,
+            min: 1.0,
+            max: 1.0,
+            p50: 1.0,
+            p95: 1.0,
+            p99: 1.0,
+            stddev: None,
+            count: 1,
+            sum: 1.0,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_bucketed_keys_ttls_and_invalidation() {
+        let cache = QueryResultCache::new(ResultCacheConfig::default());
+        let now = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let start = now - Duration::hours(1);
+        let tags = serde_json::json!({"tenant_id": "acme", "env": "prod"});
+
+        let key = cache.key("latency", TimeWindow::OneMinute, start, now, Some(&tags));
+        cache.insert_at(key.clone(), vec![row(start)], now, now);
+
+        // Bounds within the same bucket share the entry
+        let shifted = cache.key(
+            "latency",
+            TimeWindow::OneMinute,
+            start + Duration::seconds(5),
+            now + Duration::seconds(5),
+            Some(&serde_json::json!({"env": "prod", "tenant_id": "acme"})),
+        );
+        assert_eq!(shifted, key);
+        let (rows, ttl) = cache.get_at(&key, now + Duration::seconds(10)).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(ttl, 20);
+        assert!(cache.get_at(&key, now + Duration::seconds(30)).is_none());
+
+        // Historical ranges keep longer and are dropped when a window is re-stored
+        let old = cache.key(
+            "latency",
+            TimeWindow::OneMinute,
+            start - Duration::days(1),
+            now - Duration::days(1),
+            None,
+        );
+        cache.insert_at(old.clone(), vec![row(start)], now - Duration::days(1), now);
+        assert_eq!(
+            cache.get_at(&old, now + Duration::minutes(30)).unwrap().1,
+            1800
+        );
+        assert_eq!(
+            cache.invalidate("latency", TimeWindow::OneHour, now - Duration::days(1)),
+            0
+        );
+        assert_eq!(
+            cache.invalidate(
+                "latency",
+                TimeWindow::OneMinute,
+                now - Duration::days(1) - Duration::minutes(5)
+            ),
+            1
+        );
+        assert!(cache.get_at(&old, now).is_none());
+    }
+
+    #[test]
+    fn test_size_bounds_evict_oldest() {
+        let cache = QueryResultCache::new(ResultCacheConfig {
+            max_entries: 2,
+            max_rows: 3,
+            ..Default::default()
+        });
+        let now = Utc::now();
+        let key = |minutes: i64| {
+            cache.key(
+                "latency",
+                TimeWindow::OneMinute,
+                now - Duration::minutes(minutes),
+                now,
+                None,
+            )
+        };
+
+        cache.insert_at(key(10), vec![row(now)], now, now);
+        cache.insert_at(key(20), vec![row(now)], now, now);
+        cache.insert_at(key(30), vec![row(now)], now, now);
+        assert!(cache.get_at(&key(10), now).is_none());
+        assert!(cache.get_at(&key(30), now).is_some());
+
+        // Row bound: two rows more than fits alongside one existing row
+        cache.insert_at(key(40), vec![row(now), row(now), row(now)], now, now);
+        let stats = cache.get_stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.rows, 3);
+        assert_eq!(stats.evictions, 3);
+
+        // Results larger than the row bound are not cached
+        cache.insert_at(key(50), vec![row(now); 4], now, now);
+        assert!(cache.get_at(&key(50), now).is_none());
+    }
+}
@@ -22,6 +22,9 @@ CREATE TABLE IF NOT EXISTS events (
 -- Convert to hypertable for time-series optimization
 SELECT create_hypertable('events', 'timestamp', if_not_exists => TRUE);
 
+-- Partition by environment so dev/staging/production data lives in separate chunks
+SELECT add_dimension('events', 'environment', number_partitions => 4, if_not_exists => TRUE);
+
 -- Create indexes for common queries
 CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events (timestamp DESC);
 CREATE INDEX IF NOT EXISTS idx_events_correlation_id ON events (correlation_id) WHERE correlation_id IS NOT NULL;
@@ -29,6 +32,8 @@ CREATE INDEX IF NOT EXISTS idx_events_source_module ON events ((source_module->>
 CREATE INDEX IF NOT EXISTS idx_events_event_type ON events ((event_type->>'type'));
 CREATE INDEX IF NOT EXISTS idx_events_severity ON events ((severity->>'level'));
 CREATE INDEX IF NOT EXISTS idx_events_tags ON events USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_events_environment_timestamp ON events (environment, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_events_timestamp_event_id ON events (timestamp DESC, event_id DESC);
 
 -- Enable compression (4:1 ratio typical)
 ALTER TABLE events SET (
@@ -58,6 +63,7 @@ CREATE TABLE IF NOT EXISTS aggregated_metrics (
     stddev DOUBLE PRECISION,
     count BIGINT NOT NULL,
     sum DOUBLE PRECISION NOT NULL,
+    histogram JSONB,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     PRIMARY KEY (metric_name, time_window, window_start, tags)
 );
@@ -123,6 +129,7 @@ SELECT create_hypertable('anomalies', 'detected_at', if_not_exists => TRUE);
 
 -- Create indexes
 CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at ON anomalies (detected_at DESC);
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at_id ON anomalies (detected_at DESC, anomaly_id DESC);
 CREATE INDEX IF NOT EXISTS idx_anomalies_metric_name ON anomalies (metric_name);
 CREATE INDEX IF NOT EXISTS idx_anomalies_severity ON anomalies (severity);
 CREATE INDEX IF NOT EXISTS idx_anomalies_type ON anomalies (anomaly_type);
@@ -149,6 +156,65 @@ CREATE INDEX IF NOT EXISTS idx_correlations_type ON correlations (correlation_ty
 CREATE INDEX IF NOT EXISTS idx_correlations_strength ON correlations (strength DESC);
 "#;
 
+/// SQL to create usage records table
+pub const CREATE_USAGE_RECORDS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS usage_records (
+    tenant_id TEXT NOT NULL,
+    api_key_id TEXT NOT NULL DEFAULT '',
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    api_calls BIGINT NOT NULL DEFAULT 0,
+    queries BIGINT NOT NULL DEFAULT 0,
+    query_compute_ms BIGINT NOT NULL DEFAULT 0,
+    storage_bytes_latest BIGINT NOT NULL DEFAULT 0,
+    storage_bytes_peak BIGINT NOT NULL DEFAULT 0,
+    alert_deliveries BIGINT NOT NULL DEFAULT 0,
+    breakdown JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, api_key_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_records_period ON usage_records (period_start, tenant_id);
+"#;
+
+/// SQL to create model scorecards table
+pub const CREATE_MODEL_SCORECARDS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS model_scorecards (
+    model_id TEXT NOT NULL,
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    score DOUBLE PRECISION,
+    grade TEXT,
+    scorecard JSONB NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (model_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_model_scorecards_period ON model_scorecards (period_start DESC);
+"#;
+
+/// SQL to create the archive manifest table
+pub const CREATE_ARCHIVE_MANIFESTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS archive_manifests (
+    archive_id UUID PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    partition_start TIMESTAMPTZ NOT NULL,
+    partition_end TIMESTAMPTZ NOT NULL,
+    object_key TEXT NOT NULL,
+    object_uri TEXT NOT NULL,
+    format TEXT NOT NULL,
+    compression TEXT NOT NULL,
+    row_count BIGINT NOT NULL,
+    uncompressed_bytes BIGINT NOT NULL,
+    compressed_bytes BIGINT NOT NULL,
+    sha256 TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    verified_at TIMESTAMPTZ,
+    restored_at TIMESTAMPTZ,
+    UNIQUE (table_name, partition_start)
+);
+"#;
+
 /// SQL to create retention policies
 pub const CREATE_RETENTION_POLICIES: &str = r#"
 -- Retention policy for events: keep raw events for 30 days
@@ -175,6 +241,9 @@ pub async fn initialize_schema(pool: &sqlx::PgPool) -> anyhow::Result<()> {
     sqlx::query(CREATE_AGGREGATED_METRICS_TABLE).execute(pool).await?;
     sqlx::query(CREATE_ANOMALIES_TABLE).execute(pool).await?;
     sqlx::query(CREATE_CORRELATIONS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_USAGE_RECORDS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_MODEL_SCORECARDS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_ARCHIVE_MANIFESTS_TABLE).execute(pool).await?;
 
     // Create retention policies
     sqlx::query(CREATE_RETENTION_POLICIES).execute(pool).await?;
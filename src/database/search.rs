@@ -0,0 +1,236 @@
+//! Event Search API
+//!
+//! Serves `GET /api/v1/events/search`, which runs an [`EventFilter`] over recent
+//! events in any `StorageBackend`. The filter travels as JSON in the `filter`
+//! query parameter. Tenant-bound callers are pinned to their own tenant whatever
+//! the filter says, and every caller to its environment scope.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::backend::StorageBackend;
+use super::environment::EnvironmentScope;
+use super::filter::EventFilter;
+use crate::models::api::ApiResponse;
+use crate::tenancy::TenantScope;
+
+/// Path of the event search route
+pub const EVENT_SEARCH_PATH: &str = "/api/v1/events/search";
+
+/// Query parameters of an event search
+#[derive(Debug, Default, Deserialize)]
+pub struct EventSearchParams {
+    /// `EventFilter` as JSON; omit to match every event
+    pub filter: Option<String>,
+    /// Lookback in hours
+    pub hours: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Router serving event search; expects `TenantScope` and `EnvironmentScope`
+/// extensions on each request
+pub fn event_search_router<S>(backend: Arc<dyn StorageBackend>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route(EVENT_SEARCH_PATH, get(search_handler))
+        .with_state(backend)
+}
+
+/// Event search handler; responds 400 for malformed filters
+pub async fn search_handler(
+    State(backend): State<Arc<dyn StorageBackend>>,
+    Extension(tenant): Extension<TenantScope>,
+    Extension(environment): Extension<EnvironmentScope>,
+    Query(params): Query<EventSearchParams>,
+) -> Response {
+    let filter = match params
+        .filter
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+    {
+        Ok(filter) => filter,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, format!("Invalid filter: {}", e)),
+    };
+    let filter = tenant.scope_filter(filter).unwrap_or(EventFilter::And {
+        filters: Vec::new(),
+    });
+    if let Err(e) = filter.validate() {
+        return error_response(StatusCode::BAD_REQUEST, e.to_string());
+    }
+
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::hours(params.hours.unwrap_or(24).clamp(1, 24 * 90));
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+
+    match backend
+        .search_events(&filter, start, end, Some(limit), &environment)
+        .await
+    {
+        Ok(events) => Json(ApiResponse::success(events)).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    let body = serde_json::json!({
+        "success": false,
+        "error": message,
+    });
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::environment::default_environment;
+    use crate::database::MemoryBackend;
+    use crate::schemas::events::{
+        AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+        SourceModule, SCHEMA_VERSION,
+    };
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn event(tenant_id: &str, severity: Severity) -> AnalyticsEvent {
+        let mut common = CommonEventFields {
+            event_id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now() - chrono::Duration::minutes(5),
+            source_module: SourceModule::LlmSentinel,
+            event_type: EventType::Security,
+            correlation_id: None,
+            parent_event_id: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity,
+            environment: default_environment(),
+            tags: Default::default(),
+        };
+        common.set_tenant(tenant_id);
+        AnalyticsEvent {
+            common,
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    async fn search(tenant: TenantScope, query: &str) -> (StatusCode, serde_json::Value) {
+        search_in(tenant, EnvironmentScope::Default, query).await
+    }
+
+    async fn search_in(
+        tenant: TenantScope,
+        environment: EnvironmentScope,
+        query: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut staging = event("team-c", Severity::Error);
+        staging.common.environment = "staging".to_string();
+        let backend = Arc::new(MemoryBackend::new());
+        backend
+            .insert_events_batch(&[
+                event("team-a", Severity::Error),
+                event("team-a", Severity::Info),
+                event("team-b", Severity::Error),
+                staging,
+            ])
+            .await
+            .unwrap();
+
+        let app: Router = event_search_router(backend)
+            .layer(Extension(tenant))
+            .layer(Extension(environment));
+        let response = app
+            .oneshot(
+                Request::get(format!("{}?{}", EVENT_SEARCH_PATH, query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn tenants(body: &serde_json::Value) -> Vec<String> {
+        body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["tags"]["tenant_id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn filter_query(filter: &EventFilter) -> String {
+        let encoded: String = serde_json::to_string(filter)
+            .unwrap()
+            .bytes()
+            .map(|b| match b.is_ascii_alphanumeric() {
+                true => (b as char).to_string(),
+                false => format!("%{:02X}", b),
+            })
+            .collect();
+        format!("filter={}", encoded)
+    }
+
+    #[tokio::test]
+    async fn test_search_applies_filter() {
+        let errors = filter_query(&EventFilter::severity_at_least(Severity::Error));
+        let (status, body) = search(TenantScope::AllTenants, &errors).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut found = tenants(&body);
+        found.sort();
+        assert_eq!(found, vec!["team-a", "team-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_is_pinned_to_the_callers_tenant() {
+        let (_, body) = search(TenantScope::tenant("team-a"), "").await;
+        assert_eq!(tenants(&body), vec!["team-a", "team-a"]);
+
+        // Asking for another tenant's events only narrows the result
+        let other = filter_query(&EventFilter::tag_equals("tenant_id", "team-b"));
+        let (status, body) = search(TenantScope::tenant("team-a"), &other).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(tenants(&body).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_stays_in_the_callers_environment() {
+        let staging = EnvironmentScope::environment("staging");
+        let (_, body) = search_in(TenantScope::AllTenants, staging, "").await;
+        assert_eq!(tenants(&body), vec!["team-c"]);
+
+        // Naming another environment's tenant does not reach into it
+        let other = filter_query(&EventFilter::tag_equals("tenant_id", "team-c"));
+        let (_, body) = search(TenantScope::AllTenants, &other).await;
+        assert!(tenants(&body).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_malformed_filters() {
+        let (status, _) = search(TenantScope::AllTenants, "filter=%7Bnot-json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let bad_path = filter_query(&EventFilter::payload(
+            "payload.x",
+            crate::database::Comparison::Eq,
+            1,
+        ));
+        let (status, _) = search(TenantScope::AllTenants, &bad_path).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
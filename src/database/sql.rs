@@ -0,0 +1,1057 @@
+//! Read-Only SQL
+//!
+//! Runs ad-hoc SQL from power users against the analytics tables. A statement
+//! is accepted only if it is a single `SELECT` (optionally with `WITH`), reads
+//! only the analytics tables, and avoids system catalogs and functions with
+//! side effects. The checks give clear errors; the database enforces the same
+//! rules on its own, so the checks are only a first line of defense:
+//!
+//! - the statement runs in a read-only transaction that is always rolled back
+//! - it runs as `analytics_sql_reader`, a role that can only `SELECT` from the
+//!   analytics tables, with row-level security limiting it to the caller's
+//!   tenant (see `migrations/026_create_sql_reader_role.sql`)
+//! - `search_path` is reduced to `pg_catalog`, so unqualified table names only
+//!   resolve to the analytics tables the executor shadows with CTEs
+//! - tenant-bound callers see those CTEs filtered to their tenant, and tables
+//!   that are not partitioned by tenant are not shadowed for them at all
+//! - events and aggregated metrics are filtered to the caller's environment
+//!   scope, like every other event query
+//! - `statement_timeout` cancels the query server-side at the time limit
+//!
+//! Row and time limits come from the environment's `ResourceLimits`.
+
+use super::environment::{default_environment, EnvironmentScope};
+use crate::adapters::config_manager::ResourceLimits;
+use crate::resilience::bulkhead::Bulkhead;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A table the SQL endpoint may read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqlTable {
+    pub name: &'static str,
+    /// Expression selecting a row's tenant, `None` when not partitioned by tenant
+    pub tenant_expr: Option<&'static str>,
+    /// Expression selecting a row's environment, `None` when not partitioned by
+    /// environment
+    pub environment_expr: Option<&'static str>,
+}
+
+/// Tables of the analytics schema
+pub const SQL_TABLES: &[SqlTable] = &[
+    SqlTable {
+        name: "events",
+        tenant_expr: Some("tags->>'tenant_id'"),
+        environment_expr: Some("environment"),
+    },
+    SqlTable {
+        name: "aggregated_metrics",
+        tenant_expr: Some("tags->>'tenant_id'"),
+        environment_expr: Some("tags->>'environment'"),
+    },
+    SqlTable {
+        name: "usage_records",
+        tenant_expr: Some("tenant_id"),
+        environment_expr: None,
+    },
+    SqlTable {
+        name: "anomalies",
+        tenant_expr: None,
+        environment_expr: None,
+    },
+    SqlTable {
+        name: "correlations",
+        tenant_expr: None,
+        environment_expr: None,
+    },
+    SqlTable {
+        name: "model_scorecards",
+        tenant_expr: None,
+        environment_expr: None,
+    },
+    SqlTable {
+        name: "request_costs",
+        tenant_expr: Some("tags->>'tenant_id'"),
+        environment_expr: None,
+    },
+];
+
+/// TimescaleDB functions callable from queries; they live outside `pg_catalog`
+const TIMESCALE_FUNCTIONS: &[&str] = &[
+    "time_bucket",
+    "time_bucket_gapfill",
+    "locf",
+    "interpolate",
+    "first",
+    "last",
+];
+
+/// Keywords that write, lock, or change session state
+const FORBIDDEN_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "merge", "into", "drop", "alter", "create", "truncate", "grant",
+    "revoke", "copy", "call", "do", "execute", "prepare", "lock", "vacuum", "set", "reset",
+    "notify", "listen",
+];
+
+/// Functions that reach outside the analytics schema or change settings
+const FORBIDDEN_FUNCTIONS: &[&str] = &[
+    "current_setting",
+    "set_config",
+    "dblink",
+    "dblink_exec",
+    "lo_import",
+    "lo_export",
+    "lo_get",
+    "query_to_xml",
+    "query_to_xml_and_xmlschema",
+    "query_to_xmlschema",
+    "table_to_xml",
+    "table_to_xml_and_xmlschema",
+    "table_to_xmlschema",
+    "cursor_to_xml",
+    "cursor_to_xmlschema",
+    "schema_to_xml",
+    "schema_to_xml_and_xmlschema",
+    "schema_to_xmlschema",
+    "database_to_xml",
+    "database_to_xml_and_xmlschema",
+    "database_to_xmlschema",
+    "ts_stat",
+    "ts_rewrite",
+    "nextval",
+    "setval",
+];
+
+/// Role statements run as; it can only read the analytics tables
+pub const SQL_READER_ROLE: &str = "analytics_sql_reader";
+
+/// Setting the row-level security policies read the caller's tenant from
+const SQL_TENANT_SETTING: &str = "analytics.sql_tenant";
+
+/// `SQL_TENANT_SETTING` value for callers that may read every tenant
+const ALL_TENANTS: &str = "*";
+
+/// Keywords that end a FROM list
+const FROM_LIST_END: &[&str] = &[
+    "where",
+    "group",
+    "having",
+    "window",
+    "order",
+    "limit",
+    "offset",
+    "fetch",
+    "for",
+    "union",
+    "intersect",
+    "except",
+];
+
+/// Whether a name belongs to a system schema or a forbidden function
+fn is_restricted_name(name: &str) -> bool {
+    name.starts_with("pg_")
+        || name.starts_with("_timescaledb")
+        || name.starts_with("timescaledb_")
+        || matches!(name, "public" | "information_schema")
+        || FORBIDDEN_FUNCTIONS.contains(&name)
+}
+
+/// SQL endpoint limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlLimits {
+    /// Rows returned before a result is truncated
+    pub max_result_rows: u64,
+    /// Time after which a query is cancelled
+    pub max_query_timeout_secs: u32,
+    /// SQL queries running at once across all callers
+    pub max_concurrent_queries: u32,
+}
+
+impl Default for SqlLimits {
+    fn default() -> Self {
+        Self {
+            max_result_rows: 10_000,
+            max_query_timeout_secs: 30,
+            max_concurrent_queries: 8,
+        }
+    }
+}
+
+impl SqlLimits {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_result_rows: std::env::var("SQL_MAX_RESULT_ROWS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_result_rows),
+            max_query_timeout_secs: std::env::var("SQL_MAX_QUERY_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_query_timeout_secs),
+            max_concurrent_queries: std::env::var("SQL_MAX_CONCURRENT_QUERIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_concurrent_queries),
+        }
+    }
+}
+
+impl From<&ResourceLimits> for SqlLimits {
+    fn from(limits: &ResourceLimits) -> Self {
+        Self {
+            max_result_rows: limits.max_result_rows,
+            max_query_timeout_secs: limits.max_query_timeout_secs,
+            max_concurrent_queries: limits.max_concurrent_queries,
+        }
+    }
+}
+
+/// Reason a SQL query was not answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlError {
+    /// The statement is not an allowed read-only query
+    Rejected(String),
+    /// Every query slot is taken
+    Busy,
+    /// The query ran past the time limit and was cancelled
+    Timeout { secs: u32 },
+    /// The database reported an error
+    Failed(String),
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlError::Rejected(reason) => write!(f, "Query rejected: {}", reason),
+            SqlError::Busy => write!(f, "Too many SQL queries running; try again shortly"),
+            SqlError::Timeout { secs } => {
+                write!(
+                    f,
+                    "Query cancelled after exceeding the {}s time limit",
+                    secs
+                )
+            }
+            SqlError::Failed(message) => write!(f, "Query failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+fn reject(reason: impl Into<String>) -> SqlError {
+    SqlError::Rejected(reason.into())
+}
+
+// ========== Validation ==========
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Bare word, lowercased
+    Word(String),
+    /// Quoted identifier, lowercased
+    Quoted(String),
+    /// String or numeric literal
+    Literal,
+    Symbol(char),
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+impl Spanned {
+    fn word(&self) -> Option<&str> {
+        match &self.token {
+            Token::Word(w) => Some(w),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        match &self.token {
+            Token::Word(w) | Token::Quoted(w) => Some(w),
+            _ => None,
+        }
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.token == Token::Symbol(c)
+    }
+}
+
+/// Split a statement into tokens, dropping comments
+fn tokenize(sql: &str) -> Result<Vec<Spanned>, SqlError> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(sql.len(), |(o, _)| *o);
+    let mut tokens: Vec<Spanned> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i].1;
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            // Block comments nest in PostgreSQL
+            let mut depth = 0;
+            loop {
+                match (chars.get(i).map(|c| c.1), chars.get(i + 1).map(|c| c.1)) {
+                    (Some('/'), Some('*')) => {
+                        depth += 1;
+                        i += 2;
+                    }
+                    (Some('*'), Some('/')) => {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    (Some(_), _) => i += 1,
+                    (None, _) => return Err(reject("Unterminated comment")),
+                }
+            }
+            continue;
+        }
+
+        let token = match c {
+            '\'' | '"' => {
+                let escaped = c == '\''
+                    && tokens
+                        .last()
+                        .is_some_and(|t| t.end == offset(start) && t.word() == Some("e"));
+                if escaped {
+                    return Err(reject("Escape string literals are not supported"));
+                }
+                // U&"..." spells names with escapes that would hide them from the checks
+                if let [.., u, amp] = tokens.as_slice() {
+                    if u.word() == Some("u")
+                        && amp.is_symbol('&')
+                        && u.end == amp.start
+                        && amp.end == offset(start)
+                    {
+                        return Err(reject("Unicode escapes are not supported"));
+                    }
+                }
+                i += 1;
+                let mut text = String::new();
+                loop {
+                    match (chars.get(i).map(|c| c.1), chars.get(i + 1).map(|c| c.1)) {
+                        (Some(q), Some(n)) if q == c && n == c => {
+                            text.push(c);
+                            i += 2;
+                        }
+                        (Some(q), _) if q == c => {
+                            i += 1;
+                            break;
+                        }
+                        (Some(other), _) => {
+                            text.push(other);
+                            i += 1;
+                        }
+                        (None, _) => return Err(reject("Unterminated quoted text")),
+                    }
+                }
+                if c == '"' {
+                    Token::Quoted(text.to_lowercase())
+                } else {
+                    Token::Literal
+                }
+            }
+            '$' => {
+                return Err(reject(
+                    "Dollar-quoted strings and parameters are not supported",
+                ))
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len()
+                    && (chars[i].1.is_alphanumeric() || chars[i].1 == '_' || chars[i].1 == '$')
+                {
+                    i += 1;
+                }
+                Token::Word(sql[offset(start)..offset(i)].to_lowercase())
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '.') {
+                    i += 1;
+                }
+                Token::Literal
+            }
+            c => {
+                i += 1;
+                Token::Symbol(c)
+            }
+        };
+        tokens.push(Spanned {
+            token,
+            start: offset(start),
+            end: offset(i),
+        });
+    }
+    Ok(tokens)
+}
+
+/// Names defined by `name [(columns)] AS [NOT] [MATERIALIZED] (` in a `WITH`
+fn cte_names(tokens: &[Spanned]) -> Vec<String> {
+    let mut names = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.word() != Some("as") {
+            continue;
+        }
+        let mut j = i + 1;
+        while tokens
+            .get(j)
+            .is_some_and(|t| matches!(t.word(), Some("not" | "materialized")))
+        {
+            j += 1;
+        }
+        if !tokens.get(j).is_some_and(|t| t.is_symbol('(')) || i == 0 {
+            continue;
+        }
+
+        let mut k = i - 1;
+        if tokens[k].is_symbol(')') {
+            let mut depth = 0;
+            loop {
+                if tokens[k].is_symbol(')') {
+                    depth += 1;
+                } else if tokens[k].is_symbol('(') {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                if k == 0 {
+                    break;
+                }
+                k -= 1;
+            }
+            if k == 0 {
+                continue;
+            }
+            k -= 1;
+        }
+        if let Some(name) = tokens[k].name() {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Index of the first token of each comma-separated item in the FROM list
+/// starting at `start`
+fn from_items(tokens: &[Spanned], start: usize) -> Vec<usize> {
+    let mut items = vec![start];
+    let mut depth = 0usize;
+    for (j, token) in tokens.iter().enumerate().skip(start) {
+        match &token.token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') if depth == 0 => break,
+            Token::Symbol(')') => depth -= 1,
+            Token::Symbol(',') if depth == 0 => items.push(j + 1),
+            Token::Word(w) if depth == 0 && FROM_LIST_END.contains(&w.as_str()) => break,
+            _ => {}
+        }
+    }
+    items
+}
+
+/// The analytics table read by the FROM item starting at `start`, or `None`
+/// for subqueries, function calls, and CTEs
+fn table_ref(
+    tokens: &[Spanned],
+    mut start: usize,
+    ctes: &[String],
+) -> Result<Option<SqlTable>, SqlError> {
+    while tokens
+        .get(start)
+        .is_some_and(|t| matches!(t.word(), Some("only" | "lateral")))
+    {
+        start += 1;
+    }
+    let Some(name) = tokens.get(start).and_then(|t| t.name()) else {
+        return Ok(None);
+    };
+    match tokens.get(start + 1) {
+        Some(t) if t.is_symbol('.') => {
+            return Err(reject(format!(
+                "Schema-qualified table {} is not allowed",
+                name
+            )))
+        }
+        // Set-returning function call
+        Some(t) if t.is_symbol('(') => return Ok(None),
+        _ => {}
+    }
+    if ctes.iter().any(|c| c == name) {
+        return Ok(None);
+    }
+    match SQL_TABLES.iter().find(|t| t.name == name) {
+        Some(table) => Ok(Some(*table)),
+        None => {
+            let known: Vec<&str> = SQL_TABLES.iter().map(|t| t.name).collect();
+            Err(reject(format!(
+                "Unknown table {}; queryable tables are {}",
+                name,
+                known.join(", ")
+            )))
+        }
+    }
+}
+
+/// A validated read-only statement
+#[derive(Debug, Clone)]
+pub struct SqlStatement {
+    /// Statement text with TimescaleDB functions qualified
+    sql: String,
+    /// Analytics tables the statement reads
+    tables: Vec<SqlTable>,
+}
+
+impl SqlStatement {
+    /// Validate a statement
+    pub fn parse(sql: &str) -> Result<Self, SqlError> {
+        let mut tokens = tokenize(sql)?;
+        while tokens.last().is_some_and(|t| t.is_symbol(';')) {
+            tokens.pop();
+        }
+        if tokens.is_empty() {
+            return Err(reject("Query is empty"));
+        }
+        if tokens.iter().any(|t| t.is_symbol(';')) {
+            return Err(reject("Only a single statement is allowed"));
+        }
+        if !matches!(tokens[0].word(), Some("select" | "with")) {
+            return Err(reject("Only SELECT statements are allowed"));
+        }
+
+        for (i, token) in tokens.iter().enumerate() {
+            if let Some(word) = token.word() {
+                if FORBIDDEN_KEYWORDS.contains(&word) {
+                    return Err(reject(format!(
+                        "{} is not allowed in a read-only query",
+                        word.to_uppercase()
+                    )));
+                }
+            }
+            if let Some(name) = token.name() {
+                if is_restricted_name(name) {
+                    return Err(reject(format!("{} is outside the analytics schema", name)));
+                }
+                let qualified_call = tokens.get(i + 1).is_some_and(|t| t.is_symbol('.'))
+                    && tokens.get(i + 2).is_some_and(|t| t.name().is_some())
+                    && tokens.get(i + 3).is_some_and(|t| t.is_symbol('('));
+                if qualified_call {
+                    return Err(reject(format!(
+                        "Schema-qualified function in {} is not allowed",
+                        name
+                    )));
+                }
+            }
+        }
+
+        let ctes = cte_names(&tokens);
+        let mut tables: Vec<SqlTable> = Vec::new();
+        // Whether each open parenthesis holds a SELECT, so FROM inside calls
+        // like EXTRACT(epoch FROM ts) is not taken for a table reference
+        let mut selects = vec![false];
+        for (i, token) in tokens.iter().enumerate() {
+            match &token.token {
+                Token::Symbol('(') => selects.push(false),
+                Token::Symbol(')') if selects.len() > 1 => {
+                    selects.pop();
+                }
+                Token::Word(w) if w == "select" => {
+                    if let Some(top) = selects.last_mut() {
+                        *top = true;
+                    }
+                }
+                Token::Word(w) if w == "from" || w == "join" => {
+                    let in_select = selects.last().copied().unwrap_or(false);
+                    let distinct = i > 0 && tokens[i - 1].word() == Some("distinct");
+                    if !in_select || distinct {
+                        continue;
+                    }
+                    // Every item of a comma-separated FROM list is a table reference
+                    let items = if w == "from" {
+                        from_items(&tokens, i + 1)
+                    } else {
+                        vec![i + 1]
+                    };
+                    for item in items {
+                        if let Some(table) = table_ref(&tokens, item, &ctes)? {
+                            if !tables.contains(&table) {
+                                tables.push(table);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let end = tokens.last().map_or(0, |t| t.end);
+        let mut statement = String::with_capacity(end + 16);
+        let mut copied = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            let qualify = token
+                .word()
+                .is_some_and(|w| TIMESCALE_FUNCTIONS.contains(&w))
+                && tokens.get(i + 1).is_some_and(|t| t.is_symbol('('))
+                && !(i > 0 && tokens[i - 1].is_symbol('.'));
+            if qualify {
+                statement.push_str(&sql[copied..token.start]);
+                statement.push_str("public.");
+                copied = token.start;
+            }
+        }
+        statement.push_str(&sql[copied..end]);
+
+        Ok(Self {
+            sql: statement,
+            tables,
+        })
+    }
+
+    /// Analytics tables the statement reads
+    pub fn tables(&self) -> &[SqlTable] {
+        &self.tables
+    }
+
+    /// The statement wrapped for execution: analytics tables shadowed by
+    /// CTEs (filtered by tenant `$1` when `tenant_scoped`, and by the
+    /// environments in the next parameter when `environment_scoped`), each row
+    /// returned as its column names and values, and at most `limit` rows
+    pub fn scoped_sql(&self, tenant_scoped: bool, environment_scoped: bool, limit: u64) -> String {
+        let environments = if tenant_scoped { "$2" } else { "$1" };
+        let shadows: Vec<String> = SQL_TABLES
+            .iter()
+            .filter_map(|table| {
+                let mut conditions = Vec::new();
+                if tenant_scoped {
+                    conditions.push(format!("{} = $1", table.tenant_expr?));
+                }
+                if let (true, Some(expr)) = (environment_scoped, table.environment_expr) {
+                    conditions.push(format!("{} = ANY({}::TEXT[])", expr, environments));
+                }
+                Some(match conditions.is_empty() {
+                    true => format!(
+                        "{0} AS NOT MATERIALIZED (SELECT * FROM public.{0})",
+                        table.name
+                    ),
+                    false => format!(
+                        "{0} AS NOT MATERIALIZED (SELECT * FROM public.{0} WHERE {1})",
+                        table.name,
+                        conditions.join(" AND ")
+                    ),
+                })
+            })
+            .collect();
+        format!(
+            "WITH {} SELECT r.columns, r.vals FROM ({}) AS q, LATERAL (\
+             SELECT json_agg(e.key ORDER BY e.n) AS columns, json_agg(e.value ORDER BY e.n) AS vals \
+             FROM json_each(row_to_json(q)) WITH ORDINALITY AS e(key, value, n)) AS r LIMIT {}",
+            shadows.join(", "),
+            self.sql,
+            limit
+        )
+    }
+}
+
+// ========== Execution ==========
+
+/// Rows returned by a SQL query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlQueryResult {
+    pub columns: Vec<String>,
+    /// Values of each row, in column order
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    /// Whether rows past `max_result_rows` were dropped
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// SQL endpoint statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlStats {
+    pub queries: u64,
+    pub rejected: u64,
+    pub timed_out: u64,
+    pub failed: u64,
+    pub rows_returned: u64,
+}
+
+type SqlRow = (
+    Option<Json<Vec<String>>>,
+    Option<Json<Vec<serde_json::Value>>>,
+);
+
+/// Executes validated read-only statements
+pub struct SqlExecutor {
+    pool: PgPool,
+    limits: SqlLimits,
+    default_environment: String,
+    slots: Bulkhead,
+    queries: AtomicU64,
+    rejected: AtomicU64,
+    timed_out: AtomicU64,
+    failed: AtomicU64,
+    rows_returned: AtomicU64,
+}
+
+impl SqlExecutor {
+    pub fn new(pool: PgPool, limits: SqlLimits) -> Self {
+        Self {
+            pool,
+            slots: Bulkhead::new("sql", limits.max_concurrent_queries as usize, 0),
+            limits,
+            default_environment: default_environment(),
+            queries: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            timed_out: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            rows_returned: AtomicU64::new(0),
+        }
+    }
+
+    /// Environment read by default-scoped queries
+    pub fn with_default_environment(mut self, environment: impl Into<String>) -> Self {
+        self.default_environment = environment.into();
+        self
+    }
+
+    pub fn limits(&self) -> &SqlLimits {
+        &self.limits
+    }
+
+    /// Run a statement, restricted to `tenant_id`'s rows when given and to the
+    /// environments of `scope`
+    pub async fn execute(
+        &self,
+        sql: &str,
+        tenant_id: Option<&str>,
+        scope: &EnvironmentScope,
+    ) -> Result<SqlQueryResult, SqlError> {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        let statement = SqlStatement::parse(sql).and_then(|statement| {
+            match (
+                tenant_id,
+                statement.tables().iter().find(|t| t.tenant_expr.is_none()),
+            ) {
+                (Some(ALL_TENANTS), _) => Err(reject("Invalid tenant")),
+                (Some(tenant_id), Some(table)) => Err(reject(format!(
+                    "{} spans tenants and is not available to tenant {}",
+                    table.name, tenant_id
+                ))),
+                _ => Ok(statement),
+            }
+        });
+        let statement = match statement {
+            Ok(statement) => statement,
+            Err(e) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        let _permit = self.slots.acquire().await.map_err(|_| SqlError::Busy)?;
+
+        let secs = self.limits.max_query_timeout_secs.max(1);
+        let limit = self.limits.max_result_rows;
+        let environments = scope.filter(&self.default_environment);
+        let query = statement.scoped_sql(
+            tenant_id.is_some(),
+            environments.is_some(),
+            limit.saturating_add(1),
+        );
+        let started = Instant::now();
+
+        // statement_timeout cancels the query server-side; the client-side
+        // timeout also covers waiting for a connection
+        let fetched = tokio::time::timeout(
+            Duration::from_secs(secs as u64),
+            self.fetch(&query, tenant_id, environments, secs),
+        )
+        .await;
+        let mut rows = match fetched {
+            Ok(Ok(rows)) => rows,
+            Ok(Err(e)) if is_cancelled(&e) => {
+                self.timed_out.fetch_add(1, Ordering::Relaxed);
+                return Err(SqlError::Timeout { secs });
+            }
+            Ok(Err(e)) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                debug!(error = %e, "SQL query failed");
+                return Err(SqlError::Failed(e.to_string()));
+            }
+            Err(_) => {
+                self.timed_out.fetch_add(1, Ordering::Relaxed);
+                warn!(timeout_secs = secs, "Cancelled SQL query at the time limit");
+                return Err(SqlError::Timeout { secs });
+            }
+        };
+
+        let truncated = rows.len() as u64 > limit;
+        rows.truncate(limit as usize);
+        let columns = rows
+            .first()
+            .and_then(|(columns, _)| columns.clone())
+            .map(|Json(columns)| columns)
+            .unwrap_or_default();
+        let rows: Vec<Vec<serde_json::Value>> = rows
+            .into_iter()
+            .map(|(_, values)| values.map(|Json(values)| values).unwrap_or_default())
+            .collect();
+        self.rows_returned
+            .fetch_add(rows.len() as u64, Ordering::Relaxed);
+
+        Ok(SqlQueryResult {
+            columns,
+            row_count: rows.len(),
+            rows,
+            truncated,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn fetch(
+        &self,
+        query: &str,
+        tenant_id: Option<&str>,
+        environments: Option<Vec<String>>,
+        timeout_secs: u32,
+    ) -> Result<Vec<SqlRow>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "SET LOCAL statement_timeout = {}",
+            timeout_secs as u64 * 1000
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("SET LOCAL search_path TO pg_catalog")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("SELECT set_config($1, $2, true)")
+            .bind(SQL_TENANT_SETTING)
+            .bind(tenant_id.unwrap_or(ALL_TENANTS))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("SET LOCAL ROLE {}", SQL_READER_ROLE))
+            .execute(&mut *tx)
+            .await?;
+
+        let mut select = sqlx::query_as::<_, SqlRow>(query).persistent(false);
+        if let Some(tenant_id) = tenant_id {
+            select = select.bind(tenant_id);
+        }
+        if let Some(environments) = environments {
+            select = select.bind(environments);
+        }
+        let rows = select.fetch_all(&mut *tx).await?;
+        tx.rollback().await?;
+        Ok(rows)
+    }
+
+    pub fn get_stats(&self) -> SqlStats {
+        SqlStats {
+            queries: self.queries.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            timed_out: self.timed_out.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            rows_returned: self.rows_returned.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Whether PostgreSQL cancelled the statement (`query_canceled`)
+fn is_cancelled(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "57014")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected(sql: &str) -> String {
+        match SqlStatement::parse(sql) {
+            Err(SqlError::Rejected(reason)) => reason,
+            other => panic!("expected {:?} to be rejected, got {:?}", sql, other),
+        }
+    }
+
+    #[test]
+    fn test_accepts_selects() {
+        let statement = SqlStatement::parse(
+            "SELECT metric_name, avg(avg) FROM aggregated_metrics \
+             WHERE tags->>'model' = 'gpt-4' GROUP BY 1;",
+        )
+        .unwrap();
+        assert_eq!(statement.tables(), &[SQL_TABLES[1]]);
+        assert!(!statement.sql.ends_with(';'));
+
+        let statement = SqlStatement::parse(
+            "WITH recent (id) AS NOT MATERIALIZED (SELECT event_id FROM events) \
+             SELECT r.id, EXTRACT(epoch FROM a.detected_at) FROM recent r \
+             JOIN anomalies a ON a.anomaly_id = r.id",
+        )
+        .unwrap();
+        assert_eq!(statement.tables(), &[SQL_TABLES[0], SQL_TABLES[3]]);
+    }
+
+    #[test]
+    fn test_rejects_writes_and_multiple_statements() {
+        assert!(rejected("DELETE FROM events").contains("Only SELECT"));
+        assert!(rejected("SELECT 1; DROP TABLE events").contains("single statement"));
+        assert!(
+            rejected("WITH d AS (DELETE FROM events RETURNING *) SELECT * FROM d")
+                .contains("DELETE")
+        );
+        assert!(rejected("SELECT * INTO copy FROM events").contains("INTO"));
+        assert!(rejected("SELECT * FROM events FOR UPDATE").contains("UPDATE"));
+        assert!(rejected("   ").contains("empty"));
+    }
+
+    #[test]
+    fn test_literals_and_comments_are_not_keywords() {
+        assert!(SqlStatement::parse("SELECT 'delete; drop' FROM events -- ; insert").is_ok());
+        assert!(SqlStatement::parse("SELECT /* update /* nested */ */ 1").is_ok());
+        assert!(SqlStatement::parse("SELECT 'it''s' AS \"Set\"").is_ok());
+        assert!(rejected("SELECT E'\\' ; DROP TABLE events --'").contains("Escape"));
+        assert!(rejected("SELECT $$;$$").contains("Dollar"));
+        assert!(rejected("SELECT 'unterminated").contains("Unterminated"));
+    }
+
+    #[test]
+    fn test_rejects_names_outside_analytics_schema() {
+        assert!(rejected("SELECT * FROM pg_catalog.pg_user").contains("pg_catalog"));
+        assert!(rejected("SELECT * FROM public.events").contains("public"));
+        assert!(
+            rejected("SELECT * FROM \"information_schema\".tables").contains("information_schema")
+        );
+        assert!(rejected("SELECT current_setting('is_superuser')").contains("current_setting"));
+        for function in [
+            "table_to_xml_and_xmlschema('webhooks', true, false, '')",
+            "schema_to_xml_and_xmlschema('public', true, false, '')",
+            "database_to_xml_and_xmlschema(true, false, '')",
+            "ts_stat('SELECT 1')",
+            "ts_rewrite('a', 'SELECT 1')",
+        ] {
+            let name = function.split('(').next().unwrap();
+            assert!(rejected(&format!("SELECT {}", function)).contains(name));
+        }
+        assert!(rejected("SELECT * FROM incidents").contains("Unknown table incidents"));
+        assert!(rejected("SELECT * FROM other.events").contains("Schema-qualified"));
+        assert!(rejected("SELECT other.now()").contains("Schema-qualified"));
+    }
+
+    #[test]
+    fn test_checks_every_from_item() {
+        assert!(
+            rejected("SELECT * FROM events, U&\"\\0070ublic\".events").contains("Unicode escapes")
+        );
+        assert!(rejected("SELECT * FROM events, other.events").contains("Schema-qualified"));
+        assert!(rejected("SELECT * FROM events e, incidents i").contains("Unknown table incidents"));
+        assert!(rejected(
+            "SELECT * FROM events e JOIN anomalies a ON a.event_id = e.event_id, webhooks w"
+        )
+        .contains("Unknown table webhooks"));
+
+        let statement = SqlStatement::parse(
+            "SELECT e.event_id FROM events AS e, generate_series(1, 3) g, ONLY usage_records u \
+             WHERE e.event_id IN (SELECT event_id FROM request_costs) ORDER BY 1",
+        )
+        .unwrap();
+        assert_eq!(
+            statement.tables(),
+            &[SQL_TABLES[0], SQL_TABLES[2], SQL_TABLES[6]]
+        );
+        assert!(SqlStatement::parse("SELECT * FROM generate_series(1, 3) AS g").is_ok());
+    }
+
+    #[test]
+    fn test_qualifies_timescale_functions() {
+        let statement = SqlStatement::parse(
+            "SELECT time_bucket('1 hour', timestamp) AS hour, last(event_id, timestamp) \
+             FROM events GROUP BY 1 ORDER BY hour NULLS LAST",
+        )
+        .unwrap();
+        assert!(statement
+            .sql
+            .starts_with("SELECT public.time_bucket('1 hour'"));
+        assert!(statement.sql.contains("public.last(event_id"));
+        assert!(statement.sql.ends_with("NULLS LAST"));
+    }
+
+    #[test]
+    fn test_scoped_sql_filters_tenant_tables() {
+        let statement = SqlStatement::parse("SELECT count(*) FROM events").unwrap();
+
+        let scoped = statement.scoped_sql(true, false, 101);
+        assert!(scoped.contains(
+            "events AS NOT MATERIALIZED (SELECT * FROM public.events WHERE tags->>'tenant_id' = $1)"
+        ));
+        assert!(scoped.contains("WHERE tenant_id = $1"));
+        assert!(!scoped.contains("public.anomalies"));
+        assert!(scoped.contains("FROM (SELECT count(*) FROM events) AS q"));
+        assert!(scoped.ends_with("LIMIT 101"));
+
+        let unscoped = statement.scoped_sql(false, false, 11);
+        assert!(unscoped.contains("anomalies AS NOT MATERIALIZED (SELECT * FROM public.anomalies)"));
+        assert!(!unscoped.contains("$1"));
+    }
+
+    #[test]
+    fn test_scoped_sql_filters_environment_tables() {
+        let statement = SqlStatement::parse("SELECT count(*) FROM events").unwrap();
+
+        let scoped = statement.scoped_sql(false, true, 11);
+        assert!(scoped.contains("public.events WHERE environment = ANY($1::TEXT[])"));
+        assert!(scoped
+            .contains("public.aggregated_metrics WHERE tags->>'environment' = ANY($1::TEXT[])"));
+        assert!(scoped.contains("anomalies AS NOT MATERIALIZED (SELECT * FROM public.anomalies)"));
+
+        let both = statement.scoped_sql(true, true, 11);
+        assert!(both.contains(
+            "public.events WHERE tags->>'tenant_id' = $1 AND environment = ANY($2::TEXT[])"
+        ));
+        assert!(both.contains("public.usage_records WHERE tenant_id = $1)"));
+    }
+
+    #[test]
+    fn test_limits_from_resource_limits() {
+        let limits = SqlLimits::from(&ResourceLimits {
+            max_concurrent_queries: 4,
+            max_query_timeout_secs: 120,
+            max_result_rows: 50_000,
+            max_memory_mb: 1024,
+        });
+        assert_eq!(limits.max_result_rows, 50_000);
+        assert_eq!(limits.max_query_timeout_secs, 120);
+        assert_eq!(limits.max_concurrent_queries, 4);
+    }
+}
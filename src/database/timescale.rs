@@ -0,0 +1,147 @@
+//! TimescaleDB Hypertable Management
+//!
+//! SQL for the continuous aggregates that roll raw events up over the standard
+//! windows, plus chunk interval changes on the managed hypertables. Used by the
+//! `db-migrate` maintenance commands.
+
+use anyhow::{bail, Result};
+
+use crate::models::metrics::TimeWindow;
+
+/// Windows that get a continuous aggregate over `events`
+pub const STANDARD_ROLLUP_WINDOWS: [TimeWindow; 5] = [
+    TimeWindow::OneMinute,
+    TimeWindow::FiveMinutes,
+    TimeWindow::FifteenMinutes,
+    TimeWindow::OneHour,
+    TimeWindow::OneDay,
+];
+
+/// Hypertables created by the migrations
+pub const MANAGED_HYPERTABLES: [&str; 3] = ["events", "aggregated_metrics", "anomalies"];
+
+/// Parse a window from its short form (`5m`, `1h`, ...)
+pub fn parse_window(s: &str) -> Result<TimeWindow> {
+    match STANDARD_ROLLUP_WINDOWS.iter().find(|w| w.as_str() == s) {
+        Some(window) => Ok(*window),
+        None => bail!(
+            "Unknown rollup window '{}', expected one of: {}",
+            s,
+            STANDARD_ROLLUP_WINDOWS
+                .iter()
+                .map(|w| w.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Name of the continuous aggregate for a window, e.g. `events_rollup_5m`
+pub fn rollup_view_name(window: TimeWindow) -> String {
+    format!("events_rollup_{}", window.as_str().to_lowercase())
+}
+
+/// Postgres interval literal for a window
+pub fn window_interval(window: TimeWindow) -> String {
+    format!("{} seconds", window.to_seconds())
+}
+
+/// Refresh policy offsets for a window's continuous aggregate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    pub start_offset_secs: u64,
+    pub end_offset_secs: u64,
+    pub schedule_interval_secs: u64,
+}
+
+impl RefreshPolicy {
+    /// Re-materialize the last three buckets (at least an hour), leaving the
+    /// current bucket to real-time aggregation
+    pub fn for_window(window: TimeWindow) -> Self {
+        let bucket = window.to_seconds();
+        Self {
+            start_offset_secs: (bucket * 3).max(3600),
+            end_offset_secs: bucket,
+            schedule_interval_secs: bucket,
+        }
+    }
+}
+
+/// `CREATE MATERIALIZED VIEW` statement for a window's continuous aggregate
+pub fn create_rollup_sql(window: TimeWindow) -> String {
+    format!(
+        r#"
+CREATE MATERIALIZED VIEW IF NOT EXISTS {view}
+WITH (timescaledb.continuous) AS
+SELECT
+    time_bucket(INTERVAL '{interval}', timestamp) AS bucket,
+    environment,
+    source_module #>> '{{}}' AS source_module,
+    event_type #>> '{{}}' AS event_type,
+    COUNT(*) AS event_count
+FROM events
+GROUP BY bucket, environment, source_module #>> '{{}}', event_type #>> '{{}}'
+WITH NO DATA
+"#,
+        view = rollup_view_name(window),
+        interval = window_interval(window),
+    )
+}
+
+/// Statement registering the refresh policy of a window's continuous aggregate
+pub fn rollup_policy_sql(window: TimeWindow) -> String {
+    let policy = RefreshPolicy::for_window(window);
+    format!(
+        "SELECT add_continuous_aggregate_policy('{view}', \
+         start_offset => INTERVAL '{start} seconds', \
+         end_offset => INTERVAL '{end} seconds', \
+         schedule_interval => INTERVAL '{schedule} seconds', \
+         if_not_exists => TRUE)",
+        view = rollup_view_name(window),
+        start = policy.start_offset_secs,
+        end = policy.end_offset_secs,
+        schedule = policy.schedule_interval_secs,
+    )
+}
+
+/// Reject hypertables outside the managed set before they reach SQL
+pub fn validate_hypertable(table: &str) -> Result<&'static str> {
+    match MANAGED_HYPERTABLES.iter().find(|t| **t == table) {
+        Some(table) => Ok(table),
+        None => bail!(
+            "Unknown hypertable '{}', expected one of: {}",
+            table,
+            MANAGED_HYPERTABLES.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window_and_view_names() {
+        assert_eq!(parse_window("5m").unwrap(), TimeWindow::FiveMinutes);
+        assert!(parse_window("2h").is_err());
+        assert_eq!(rollup_view_name(TimeWindow::OneHour), "events_rollup_1h");
+        assert!(create_rollup_sql(TimeWindow::OneDay).contains("INTERVAL '86400 seconds'"));
+    }
+
+    #[test]
+    fn test_refresh_policy_offsets() {
+        let minute = RefreshPolicy::for_window(TimeWindow::OneMinute);
+        assert_eq!(minute.start_offset_secs, 3600);
+        assert_eq!(minute.end_offset_secs, 60);
+
+        let day = RefreshPolicy::for_window(TimeWindow::OneDay);
+        assert_eq!(day.start_offset_secs, 3 * 86_400);
+        assert_eq!(day.schedule_interval_secs, 86_400);
+    }
+
+    #[test]
+    fn test_validate_hypertable() {
+        assert_eq!(validate_hypertable("events").unwrap(), "events");
+        assert!(validate_hypertable("events; DROP TABLE events").is_err());
+    }
+}
@@ -79,6 +79,7 @@ fn metrics_examples() {
             stddev: Some(350.2),
             count: 7000,
             sum: 3153500.0,
+            histogram: None,
         },
         buckets,
         tags: histogram_tags,
@@ -107,6 +108,7 @@ fn metrics_examples() {
             stddev: Some(450.0),
             count: 5000,
             sum: 4252500.0,
+            histogram: None,
         }),
         tags: agg_tags,
     };
@@ -0,0 +1,483 @@
+//! Grafana JSON Datasource
+//!
+//! Serves aggregated metrics in the protocol of Grafana's JSON datasource
+//! plugin, so panels can query the hub without a custom plugin. Point the
+//! datasource at `/api/grafana`; the `search`/`metrics`, `query`, `tag-keys`,
+//! and `tag-values` calls are answered from the aggregated metrics tables.
+//!
+//! A target names a metric. Its optional payload picks the statistic to plot
+//! (`avg` by default), filters by tags, and may split the series by a tag with
+//! `group_by`. Ad-hoc filters are applied as tag filters on every target, and
+//! the caller's scope tags (tenant and environment) override both. Each series
+//! is read at the rollup window matching the panel's point budget and
+//! downsampled to it.
+
+use crate::analytics::downsampling::{
+    downsample_rows, DownsampleRequest, SeriesStatistic, DEFAULT_POINT_BUDGET, MAX_POINT_BUDGET,
+};
+use crate::database::{AggregatedMetricRow, Database, MetricSeriesRow, QueryPlanner};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::debug;
+
+/// How far back metric and tag listings look for series
+const LISTING_LOOKBACK_DAYS: i64 = 7;
+
+/// Series read for metric and tag listings
+const LISTING_SERIES_LIMIT: i64 = 10_000;
+
+/// Time range of a panel
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrafanaRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Tag filter set on the dashboard
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdhocFilter {
+    pub key: String,
+    #[serde(default = "default_operator")]
+    pub operator: String,
+    pub value: String,
+}
+
+fn default_operator() -> String {
+    "=".to_string()
+}
+
+/// Per-target options
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TargetPayload {
+    /// Statistic to plot, e.g. `avg` or `p95`
+    pub stat: Option<String>,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// Tag whose values each get their own series
+    pub group_by: Option<String>,
+}
+
+/// One query of a panel
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaTarget {
+    /// Metric name
+    pub target: String,
+    #[serde(default)]
+    pub ref_id: Option<String>,
+    #[serde(default)]
+    pub hide: bool,
+    #[serde(default)]
+    pub payload: Option<TargetPayload>,
+}
+
+/// Body of a `/query` call
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaQueryRequest {
+    pub range: GrafanaRange,
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+    pub targets: Vec<GrafanaTarget>,
+    #[serde(default)]
+    pub adhoc_filters: Vec<AdhocFilter>,
+}
+
+impl GrafanaQueryRequest {
+    /// Reject a range, statistic, or ad-hoc filter the datasource can't serve
+    pub fn validate(&self) -> Result<()> {
+        if self.range.from >= self.range.to {
+            bail!("Range must start before it ends");
+        }
+        for target in &self.targets {
+            if let Some(stat) = target.payload.as_ref().and_then(|p| p.stat.as_deref()) {
+                stat.parse::<SeriesStatistic>()?;
+            }
+        }
+        if let Some(filter) = self.adhoc_filters.iter().find(|f| f.operator != "=") {
+            bail!(
+                "Unsupported ad-hoc filter operator '{}'; only '=' is supported",
+                filter.operator
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Body of a `/search` or `/metrics` call
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GrafanaSearchRequest {
+    /// Substring metric names must contain
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// Body of a `/tag-values` call
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrafanaTagValuesRequest {
+    pub key: String,
+}
+
+/// A series as Grafana plots it: `[value, unix milliseconds]` pairs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrafanaTimeSeries {
+    pub target: String,
+    #[serde(rename = "refId", skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// A tag key offered for ad-hoc filtering
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrafanaTagKey {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub text: String,
+}
+
+/// A tag value offered for ad-hoc filtering
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrafanaTagValue {
+    pub text: String,
+}
+
+/// Tag filter of a target: its payload tags and the dashboard's ad-hoc
+/// filters, with the caller's scope tags taking precedence
+pub fn target_tags(
+    target: &GrafanaTarget,
+    adhoc_filters: &[AdhocFilter],
+    scope_tags: Option<&serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let mut tags = serde_json::Map::new();
+    if let Some(payload) = &target.payload {
+        for (key, value) in &payload.tags {
+            tags.insert(key.clone(), value.clone().into());
+        }
+    }
+    for filter in adhoc_filters.iter().filter(|f| f.operator == "=") {
+        tags.insert(filter.key.clone(), filter.value.clone().into());
+    }
+    if let Some(serde_json::Value::Object(scope)) = scope_tags {
+        tags.extend(scope.clone());
+    }
+    (!tags.is_empty()).then_some(serde_json::Value::Object(tags))
+}
+
+/// Split rows by the value of `tag`; rows without it share the empty key
+pub fn group_rows(
+    rows: &[AggregatedMetricRow],
+    tag: &str,
+) -> BTreeMap<String, Vec<AggregatedMetricRow>> {
+    let mut groups: BTreeMap<String, Vec<AggregatedMetricRow>> = BTreeMap::new();
+    for row in rows {
+        let value = row.tags[tag].as_str().unwrap_or_default().to_string();
+        groups.entry(value).or_default().push(row.clone());
+    }
+    groups
+}
+
+/// Whether a series carries every tag in `scope_tags`
+fn in_scope(series: &MetricSeriesRow, scope_tags: Option<&serde_json::Value>) -> bool {
+    match scope_tags {
+        Some(serde_json::Value::Object(scope)) => scope
+            .iter()
+            .all(|(key, value)| series.tags.get(key) == Some(value)),
+        _ => true,
+    }
+}
+
+/// Answers Grafana JSON datasource calls from the aggregated metrics
+pub struct GrafanaDatasource {
+    database: Arc<Database>,
+    planner: Arc<QueryPlanner>,
+}
+
+impl GrafanaDatasource {
+    pub fn new(database: Arc<Database>, planner: Arc<QueryPlanner>) -> Self {
+        Self { database, planner }
+    }
+
+    /// Series of every visible target of a validated request
+    pub async fn query(
+        &self,
+        request: &GrafanaQueryRequest,
+        scope_tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<GrafanaTimeSeries>> {
+        let (start, end) = (request.range.from, request.range.to);
+        let max_points = request
+            .max_data_points
+            .unwrap_or(DEFAULT_POINT_BUDGET)
+            .clamp(3, MAX_POINT_BUDGET);
+
+        let mut series = Vec::new();
+        for target in request.targets.iter().filter(|t| !t.hide) {
+            let payload = target.payload.clone().unwrap_or_default();
+            let statistic = match payload.stat.as_deref() {
+                Some(stat) => stat.parse::<SeriesStatistic>()?,
+                None => SeriesStatistic::default(),
+            };
+            let downsample = DownsampleRequest {
+                metric_name: target.target.clone(),
+                start,
+                end,
+                max_points,
+                statistic,
+                tags: target_tags(target, &request.adhoc_filters, scope_tags),
+            };
+            let plan = self
+                .planner
+                .plan("aggregated_metrics", downsample.resolution(), start, end);
+            let rows = self
+                .database
+                .query_aggregates_cached(
+                    &downsample.metric_name,
+                    plan.window,
+                    start,
+                    end,
+                    downsample.tags.as_ref(),
+                )
+                .await
+                .with_context(|| format!("Failed to query {}", target.target))?
+                .rows;
+            debug!(
+                metric = %target.target,
+                window = plan.window.as_str(),
+                rows = rows.len(),
+                "Answered Grafana target"
+            );
+
+            let groups = match &payload.group_by {
+                Some(tag) => group_rows(&rows, tag)
+                    .into_iter()
+                    .map(|(value, rows)| (format!("{} {}={}", target.target, tag, value), rows))
+                    .collect(),
+                None => vec![(target.target.clone(), rows)],
+            };
+            for (label, rows) in groups {
+                let points = downsample_rows(&downsample, plan.window, &rows).points;
+                series.push(GrafanaTimeSeries {
+                    target: label,
+                    ref_id: target.ref_id.clone(),
+                    datapoints: points
+                        .iter()
+                        .map(|p| (p.value, p.timestamp.timestamp_millis()))
+                        .collect(),
+                });
+            }
+        }
+        Ok(series)
+    }
+
+    async fn recent_series(
+        &self,
+        scope_tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<MetricSeriesRow>> {
+        let since = Utc::now() - Duration::days(LISTING_LOOKBACK_DAYS);
+        let mut series = self
+            .database
+            .query_metric_series(since, LISTING_SERIES_LIMIT)
+            .await?;
+        series.retain(|s| in_scope(s, scope_tags));
+        Ok(series)
+    }
+
+    /// Metric names containing `filter`, sorted
+    pub async fn search(
+        &self,
+        filter: Option<&str>,
+        scope_tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<String>> {
+        let names: BTreeSet<String> = self
+            .recent_series(scope_tags)
+            .await?
+            .into_iter()
+            .map(|s| s.metric_name)
+            .filter(|name| filter.map_or(true, |f| name.contains(f)))
+            .collect();
+        Ok(names.into_iter().collect())
+    }
+
+    /// Tag keys seen on recent series, sorted
+    pub async fn tag_keys(
+        &self,
+        scope_tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<GrafanaTagKey>> {
+        let keys: BTreeSet<String> = self
+            .recent_series(scope_tags)
+            .await?
+            .iter()
+            .filter_map(|s| s.tags.as_object())
+            .flat_map(|tags| tags.keys().cloned())
+            .collect();
+        Ok(keys
+            .into_iter()
+            .map(|text| GrafanaTagKey {
+                kind: "string",
+                text,
+            })
+            .collect())
+    }
+
+    /// Values of `key` seen on recent series, sorted
+    pub async fn tag_values(
+        &self,
+        key: &str,
+        scope_tags: Option<&serde_json::Value>,
+    ) -> Result<Vec<GrafanaTagValue>> {
+        let values: BTreeSet<String> = self
+            .recent_series(scope_tags)
+            .await?
+            .iter()
+            .filter_map(|s| s.tags[key].as_str().map(str::to_string))
+            .collect();
+        Ok(values
+            .into_iter()
+            .map(|text| GrafanaTagValue { text })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(tags: serde_json::Value) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: "latency_ms".to_string(),
+            time_window: "5m".to_string(),
+            window_start: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            tags,
+            avg: 1.0,
+            min: 1.0,
+            max: 1.0,
+            p50: 1.0,
+            p95: 1.0,
+            p99: 1.0,
+            stddev: None,
+            count: 1,
+            sum: 1.0,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_parses_query_request() {
+        let request: GrafanaQueryRequest = serde_json::from_value(json!({
+            "range": {"from": "2024-01-01T00:00:00Z", "to": "2024-01-02T00:00:00Z"},
+            "intervalMs": 60000,
+            "maxDataPoints": 720,
+            "targets": [
+                {"target": "latency_ms", "refId": "A", "payload": {"stat": "p95", "group_by": "model"}},
+                {"target": "cost_usd", "refId": "B", "hide": true}
+            ],
+            "adhocFilters": [{"key": "environment", "operator": "=", "value": "production"}]
+        }))
+        .unwrap();
+        assert_eq!(request.max_data_points, Some(720));
+        assert_eq!(request.targets[0].ref_id.as_deref(), Some("A"));
+        assert!(request.targets[1].hide);
+        assert_eq!(
+            request.targets[0]
+                .payload
+                .as_ref()
+                .unwrap()
+                .group_by
+                .as_deref(),
+            Some("model")
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_unservable_requests() {
+        let request = |body: serde_json::Value| -> GrafanaQueryRequest {
+            serde_json::from_value(body).unwrap()
+        };
+        let range = json!({"from": "2024-01-01T00:00:00Z", "to": "2024-01-02T00:00:00Z"});
+
+        assert!(
+            request(json!({"range": range, "targets": [{"target": "latency_ms"}]}))
+                .validate()
+                .is_ok()
+        );
+        assert!(request(json!({
+            "range": {"from": "2024-01-02T00:00:00Z", "to": "2024-01-01T00:00:00Z"},
+            "targets": []
+        }))
+        .validate()
+        .is_err());
+        assert!(request(json!({
+            "range": range,
+            "targets": [{"target": "latency_ms", "payload": {"stat": "p42"}}]
+        }))
+        .validate()
+        .is_err());
+        assert!(request(json!({
+            "range": range,
+            "targets": [],
+            "adhocFilters": [{"key": "model", "operator": "=~", "value": "gpt.*"}]
+        }))
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_target_tags_apply_filters_and_scope() {
+        let target = GrafanaTarget {
+            target: "latency_ms".to_string(),
+            ref_id: None,
+            hide: false,
+            payload: Some(TargetPayload {
+                tags: HashMap::from([("tenant_id".to_string(), "other".to_string())]),
+                ..Default::default()
+            }),
+        };
+        let filters = vec![AdhocFilter {
+            key: "model".to_string(),
+            operator: "=".to_string(),
+            value: "gpt-4".to_string(),
+        }];
+        let scope = json!({"tenant_id": "acme"});
+
+        let tags = target_tags(&target, &filters, Some(&scope)).unwrap();
+        assert_eq!(tags, json!({"tenant_id": "acme", "model": "gpt-4"}));
+
+        let unscoped = GrafanaTarget {
+            payload: None,
+            ..target
+        };
+        assert!(target_tags(&unscoped, &[], None).is_none());
+    }
+
+    #[test]
+    fn test_group_rows_by_tag() {
+        let rows = vec![
+            row(json!({"model": "gpt-4"})),
+            row(json!({"model": "claude"})),
+            row(json!({"model": "gpt-4"})),
+            row(json!({})),
+        ];
+        let groups = group_rows(&rows, "model");
+        assert_eq!(
+            groups.keys().collect::<Vec<_>>(),
+            vec!["", "claude", "gpt-4"]
+        );
+        assert_eq!(groups["gpt-4"].len(), 2);
+    }
+
+    #[test]
+    fn test_series_serializes_as_datapoints() {
+        let series = GrafanaTimeSeries {
+            target: "latency_ms".to_string(),
+            ref_id: Some("A".to_string()),
+            datapoints: vec![(12.5, 1_700_000_000_000)],
+        };
+        assert_eq!(
+            serde_json::to_value(&series).unwrap(),
+            json!({"target": "latency_ms", "refId": "A", "datapoints": [[12.5, 1_700_000_000_000i64]]})
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Data Export
+//!
+//! Shared building blocks for exporting analytics data to external sinks.
+
+pub mod grafana;
+pub mod prometheus;
+pub mod tags;
+
+pub use grafana::{
+    GrafanaDatasource, GrafanaQueryRequest, GrafanaSearchRequest, GrafanaTagKey, GrafanaTagValue,
+    GrafanaTagValuesRequest, GrafanaTimeSeries,
+};
+pub use self::prometheus::{metrics_router, HubMetrics};
+pub use tags::{ExportSink, NormalizationConfig, NormalizedTags, TagMapping, TagNormalizer};
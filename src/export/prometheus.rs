@@ -0,0 +1,371 @@
+//! Prometheus Metrics Exporter
+//!
+//! Hub-wide Prometheus collectors for ingestion, processing, storage, caching,
+//! and upstream adapter calls, plus a `/metrics` route for scraping.
+
+use crate::resilience::CircuitState;
+use anyhow::Result;
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+use prometheus::{
+    exponential_buckets, CounterVec, Encoder, Gauge, GaugeVec, HistogramOpts, HistogramVec,
+    IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// All circuit states, used to reset the state gauge on transitions
+const CIRCUIT_STATES: [CircuitState; 3] = [
+    CircuitState::Closed,
+    CircuitState::Open,
+    CircuitState::HalfOpen,
+];
+
+static GLOBAL: OnceLock<HubMetrics> = OnceLock::new();
+
+/// Prometheus collectors for the hub
+pub struct HubMetrics {
+    registry: Registry,
+    events_ingested: IntCounterVec,
+    events_duplicate: IntCounterVec,
+    events_throttled: IntCounterVec,
+    metric_samples_dropped: IntCounterVec,
+    ingestion_rate: Gauge,
+    processing_duration: HistogramVec,
+    db_write_batch_size: HistogramVec,
+    circuit_state: GaugeVec,
+    cache_requests: IntCounterVec,
+    cache_hit_ratio: GaugeVec,
+    adapter_request_duration: HistogramVec,
+    adapter_requests: CounterVec,
+    consumer_lag: IntGaugeVec,
+    recommended_replicas: IntGaugeVec,
+}
+
+impl HubMetrics {
+    /// Create collectors and register them with the given registry
+    pub fn new(registry: Registry) -> Result<Self> {
+        let events_ingested = IntCounterVec::new(
+            Opts::new("llm_hub_events_ingested_total", "Total events ingested"),
+            &["source_module"],
+        )?;
+        let events_duplicate = IntCounterVec::new(
+            Opts::new(
+                "llm_hub_events_duplicate_total",
+                "Events dropped at ingestion as redeliveries of an event_id already seen",
+            ),
+            &["source_module"],
+        )?;
+        let events_throttled = IntCounterVec::new(
+            Opts::new(
+                "llm_hub_events_throttled_total",
+                "Events shed or sampled out because their source module exceeded its ingestion quota",
+            ),
+            &["source_module"],
+        )?;
+        let metric_samples_dropped = IntCounterVec::new(
+            Opts::new(
+                "llm_hub_metric_samples_dropped_total",
+                "Metric samples dropped before aggregation by allowlist/denylist rules or cardinality limits",
+            ),
+            &["reason"],
+        )?;
+        let ingestion_rate = Gauge::new(
+            "llm_hub_ingestion_rate_events_per_second",
+            "Average ingestion throughput since startup",
+        )?;
+        let processing_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "llm_hub_processing_duration_seconds",
+                "Event processing latency by pipeline stage",
+            )
+            .buckets(exponential_buckets(0.0005, 2.0, 16)?),
+            &["stage"],
+        )?;
+        let db_write_batch_size = HistogramVec::new(
+            HistogramOpts::new(
+                "llm_hub_db_write_batch_size",
+                "Number of rows written per database batch",
+            )
+            .buckets(exponential_buckets(1.0, 2.0, 14)?),
+            &["table"],
+        )?;
+        let circuit_state = GaugeVec::new(
+            Opts::new(
+                "llm_hub_circuit_breaker_state",
+                "Circuit breaker state per adapter (1 for the current state)",
+            ),
+            &["adapter", "state"],
+        )?;
+        let cache_requests = IntCounterVec::new(
+            Opts::new("llm_hub_cache_requests_total", "Cache lookups by result"),
+            &["cache", "result"],
+        )?;
+        let cache_hit_ratio = GaugeVec::new(
+            Opts::new("llm_hub_cache_hit_ratio", "Cache hit ratio since startup"),
+            &["cache"],
+        )?;
+        let adapter_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "llm_hub_adapter_request_duration_seconds",
+                "Latency of upstream adapter requests",
+            )
+            .buckets(exponential_buckets(0.001, 2.0, 15)?),
+            &["adapter", "outcome"],
+        )?;
+        let adapter_requests = CounterVec::new(
+            Opts::new("llm_hub_adapter_requests_total", "Upstream adapter requests"),
+            &["adapter", "outcome"],
+        )?;
+        let consumer_lag = IntGaugeVec::new(
+            Opts::new(
+                "llm_hub_kafka_consumer_lag",
+                "Kafka consumer group lag per partition",
+            ),
+            &["group", "topic", "partition"],
+        )?;
+        let recommended_replicas = IntGaugeVec::new(
+            Opts::new(
+                "llm_hub_recommended_replicas",
+                "Consumer replicas recommended from the current lag",
+            ),
+            &["group", "topic"],
+        )?;
+
+        registry.register(Box::new(events_ingested.clone()))?;
+        registry.register(Box::new(events_duplicate.clone()))?;
+        registry.register(Box::new(events_throttled.clone()))?;
+        registry.register(Box::new(metric_samples_dropped.clone()))?;
+        registry.register(Box::new(ingestion_rate.clone()))?;
+        registry.register(Box::new(processing_duration.clone()))?;
+        registry.register(Box::new(db_write_batch_size.clone()))?;
+        registry.register(Box::new(circuit_state.clone()))?;
+        registry.register(Box::new(cache_requests.clone()))?;
+        registry.register(Box::new(cache_hit_ratio.clone()))?;
+        registry.register(Box::new(adapter_request_duration.clone()))?;
+        registry.register(Box::new(adapter_requests.clone()))?;
+        registry.register(Box::new(consumer_lag.clone()))?;
+        registry.register(Box::new(recommended_replicas.clone()))?;
+
+        Ok(Self {
+            registry,
+            events_ingested,
+            events_duplicate,
+            events_throttled,
+            metric_samples_dropped,
+            ingestion_rate,
+            processing_duration,
+            db_write_batch_size,
+            circuit_state,
+            cache_requests,
+            cache_hit_ratio,
+            adapter_request_duration,
+            adapter_requests,
+            consumer_lag,
+            recommended_replicas,
+        })
+    }
+
+    /// Process-wide collectors registered with the default Prometheus registry
+    pub fn global() -> &'static HubMetrics {
+        GLOBAL.get_or_init(|| {
+            HubMetrics::new(prometheus::default_registry().clone())
+                .expect("hub metrics registered more than once")
+        })
+    }
+
+    /// Registry the collectors are registered with
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    // ========== Recording ==========
+
+    /// Record ingested events for a source module
+    pub fn record_ingested(&self, source_module: &str, count: u64) {
+        self.events_ingested
+            .with_label_values(&[source_module])
+            .inc_by(count);
+    }
+
+    /// Record duplicate events dropped for a source module
+    pub fn record_duplicates(&self, source_module: &str, count: u64) {
+        self.events_duplicate
+            .with_label_values(&[source_module])
+            .inc_by(count);
+    }
+
+    /// Record events dropped by a source module's ingestion quota
+    pub fn record_throttled(&self, source_module: &str, count: u64) {
+        self.events_throttled
+            .with_label_values(&[source_module])
+            .inc_by(count);
+    }
+
+    /// Record metric samples dropped by allowlist/denylist rules
+    pub fn record_metric_dropped(&self, reason: &str, count: u64) {
+        self.metric_samples_dropped
+            .with_label_values(&[reason])
+            .inc_by(count);
+    }
+
+    /// Set the current ingestion rate
+    pub fn set_ingestion_rate(&self, events_per_second: f64) {
+        self.ingestion_rate.set(events_per_second);
+    }
+
+    /// Record the latency of a pipeline stage
+    pub fn observe_processing(&self, stage: &str, duration: Duration) {
+        self.processing_duration
+            .with_label_values(&[stage])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Record the size of a database write batch
+    pub fn observe_db_batch(&self, table: &str, rows: usize) {
+        self.db_write_batch_size
+            .with_label_values(&[table])
+            .observe(rows as f64);
+    }
+
+    /// Set the circuit breaker state of an adapter
+    pub fn set_circuit_state(&self, adapter: &str, state: CircuitState) {
+        for candidate in CIRCUIT_STATES {
+            let value = if candidate == state { 1.0 } else { 0.0 };
+            self.circuit_state
+                .with_label_values(&[adapter, candidate.as_str()])
+                .set(value);
+        }
+    }
+
+    /// Record a cache lookup and refresh the hit ratio
+    pub fn record_cache_lookup(&self, cache: &str, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.cache_requests.with_label_values(&[cache, result]).inc();
+
+        let hits = self.cache_requests.with_label_values(&[cache, "hit"]).get();
+        let misses = self.cache_requests.with_label_values(&[cache, "miss"]).get();
+        let total = hits + misses;
+        if total > 0 {
+            self.cache_hit_ratio
+                .with_label_values(&[cache])
+                .set(hits as f64 / total as f64);
+        }
+    }
+
+    /// Record an upstream adapter request
+    pub fn observe_adapter_request(&self, adapter: &str, success: bool, duration: Duration) {
+        let outcome = if success { "success" } else { "error" };
+        self.adapter_request_duration
+            .with_label_values(&[adapter, outcome])
+            .observe(duration.as_secs_f64());
+        self.adapter_requests
+            .with_label_values(&[adapter, outcome])
+            .inc();
+    }
+
+    /// Set the lag of every partition of a consumer group on a topic
+    pub fn set_consumer_lag(&self, group: &str, topic: &str, partitions: &BTreeMap<i32, u64>) {
+        for (partition, lag) in partitions {
+            self.consumer_lag
+                .with_label_values(&[group, topic, &partition.to_string()])
+                .set(*lag as i64);
+        }
+    }
+
+    /// Set the replica count recommended for a consumer group
+    pub fn set_recommended_replicas(&self, group: &str, topic: &str, replicas: u32) {
+        self.recommended_replicas
+            .with_label_values(&[group, topic])
+            .set(replicas as i64);
+    }
+
+    // ========== Exposition ==========
+
+    /// Render all collectors in the Prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// Router serving the default registry at `/metrics`
+pub fn metrics_router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    // Make sure hub collectors exist even before the first observation
+    let metrics = HubMetrics::global();
+
+    match metrics.render() {
+        Ok(body) => (
+            axum::http::StatusCode::OK,
+            [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+            body,
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain")],
+            format!("Failed to encode metrics: {}", e),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics() -> HubMetrics {
+        HubMetrics::new(Registry::new()).unwrap()
+    }
+
+    #[test]
+    fn test_circuit_state_gauge_is_one_hot() {
+        let metrics = metrics();
+        metrics.set_circuit_state("costops", CircuitState::Open);
+        metrics.set_circuit_state("costops", CircuitState::HalfOpen);
+
+        let output = metrics.render().unwrap();
+        assert!(output.contains(
+            "llm_hub_circuit_breaker_state{adapter=\"costops\",state=\"half_open\"} 1"
+        ));
+        assert!(output.contains(
+            "llm_hub_circuit_breaker_state{adapter=\"costops\",state=\"open\"} 0"
+        ));
+    }
+
+    #[test]
+    fn test_cache_hit_ratio() {
+        let metrics = metrics();
+        metrics.record_cache_lookup("metrics", true);
+        metrics.record_cache_lookup("metrics", true);
+        metrics.record_cache_lookup("metrics", true);
+        metrics.record_cache_lookup("metrics", false);
+
+        let output = metrics.render().unwrap();
+        assert!(output.contains("llm_hub_cache_hit_ratio{cache=\"metrics\"} 0.75"));
+    }
+
+    #[test]
+    fn test_render_includes_histograms() {
+        let metrics = metrics();
+        metrics.record_ingested("llm-observatory", 10);
+        metrics.observe_db_batch("events", 10);
+        metrics.observe_processing("process", Duration::from_millis(3));
+        metrics.observe_adapter_request("registry", true, Duration::from_millis(12));
+
+        let output = metrics.render().unwrap();
+        assert!(output.contains("llm_hub_events_ingested_total{source_module=\"llm-observatory\"} 10"));
+        assert!(output.contains("llm_hub_db_write_batch_size_count{table=\"events\"} 1"));
+        assert!(output.contains("llm_hub_processing_duration_seconds_bucket"));
+        assert!(output.contains("llm_hub_adapter_request_duration_seconds_count{adapter=\"registry\",outcome=\"success\"} 1"));
+    }
+}
@@ -0,0 +1,371 @@
+//! Export Tag Normalization
+//!
+//! Maps tag keys and values to export-safe forms per sink, keeping reversible mapping metadata.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Export sink whose naming and quoting rules apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSink {
+    /// Prometheus label names (`[a-zA-Z_][a-zA-Z0-9_]*`) with escaped label values
+    Prometheus,
+    /// RFC 4180 CSV fields
+    Csv,
+    /// Percent-encoded URL query parameters (e.g. Grafana links)
+    Url,
+}
+
+/// Tag normalization configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizationConfig {
+    /// Lowercase tag keys before applying sink rules
+    pub lowercase_keys: bool,
+
+    /// Replacement character for invalid key characters
+    pub replacement: char,
+
+    /// Maximum key length after normalization
+    pub max_key_length: usize,
+
+    /// Maximum value length (in characters) before truncation
+    pub max_value_length: Option<usize>,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        Self {
+            lowercase_keys: false,
+            replacement: '_',
+            max_key_length: 128,
+            max_value_length: None,
+        }
+    }
+}
+
+/// Reversible mapping from normalized tags back to the originals
+///
+/// Only entries that changed during normalization are recorded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagMapping {
+    /// Normalized key -> original key
+    pub keys: BTreeMap<String, String>,
+
+    /// Original key -> (normalized value -> original value)
+    pub values: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl TagMapping {
+    /// Whether normalization changed anything
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.values.is_empty()
+    }
+
+    /// Look up the original key for a normalized key
+    pub fn original_key<'a>(&'a self, normalized: &'a str) -> &'a str {
+        self.keys.get(normalized).map(String::as_str).unwrap_or(normalized)
+    }
+
+    /// Restore original tags from normalized ones
+    pub fn restore(&self, normalized: &HashMap<String, String>) -> HashMap<String, String> {
+        normalized
+            .iter()
+            .map(|(key, value)| {
+                let original_key = self.original_key(key).to_string();
+                let original_value = self
+                    .values
+                    .get(&original_key)
+                    .and_then(|values| values.get(value))
+                    .cloned()
+                    .unwrap_or_else(|| value.clone());
+                (original_key, original_value)
+            })
+            .collect()
+    }
+
+    /// Merge another mapping into this one
+    pub fn merge(&mut self, other: TagMapping) {
+        self.keys.extend(other.keys);
+        for (key, values) in other.values {
+            self.values.entry(key).or_default().extend(values);
+        }
+    }
+}
+
+/// Normalized tag set with mapping metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedTags {
+    pub sink: ExportSink,
+    pub tags: BTreeMap<String, String>,
+    pub mapping: TagMapping,
+}
+
+impl NormalizedTags {
+    /// Restore the original tags
+    pub fn restore(&self) -> HashMap<String, String> {
+        let tags: HashMap<String, String> = self.tags.clone().into_iter().collect();
+        self.mapping.restore(&tags)
+    }
+}
+
+/// Normalizes tag keys and values for a specific export sink
+#[derive(Debug, Clone)]
+pub struct TagNormalizer {
+    sink: ExportSink,
+    config: NormalizationConfig,
+}
+
+impl TagNormalizer {
+    /// Create a normalizer with default configuration
+    pub fn new(sink: ExportSink) -> Self {
+        Self::with_config(sink, NormalizationConfig::default())
+    }
+
+    /// Create a normalizer with custom configuration
+    pub fn with_config(sink: ExportSink, config: NormalizationConfig) -> Self {
+        Self { sink, config }
+    }
+
+    /// Target sink
+    pub fn sink(&self) -> ExportSink {
+        self.sink
+    }
+
+    /// Normalize a full tag set, resolving key collisions deterministically
+    pub fn normalize(&self, tags: &HashMap<String, String>) -> NormalizedTags {
+        let mut keys: Vec<&String> = tags.keys().collect();
+        keys.sort();
+
+        let mut normalized = BTreeMap::new();
+        let mut mapping = TagMapping::default();
+
+        for key in keys {
+            let value = &tags[key];
+
+            let base = self.normalize_key(key);
+            let mut candidate = base.clone();
+            let mut suffix = 2;
+            while normalized.contains_key(&candidate) {
+                candidate = format!("{}{}{}", base, self.config.replacement, suffix);
+                suffix += 1;
+            }
+
+            let normalized_value = self.normalize_value(value);
+
+            if &candidate != key {
+                mapping.keys.insert(candidate.clone(), key.clone());
+            }
+            if &normalized_value != value {
+                mapping
+                    .values
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(normalized_value.clone(), value.clone());
+            }
+
+            normalized.insert(candidate, normalized_value);
+        }
+
+        NormalizedTags {
+            sink: self.sink,
+            tags: normalized,
+            mapping,
+        }
+    }
+
+    /// Normalize a single tag key
+    pub fn normalize_key(&self, key: &str) -> String {
+        let key = if self.config.lowercase_keys {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        };
+
+        let normalized = match self.sink {
+            ExportSink::Prometheus => self.prometheus_label_name(&key),
+            ExportSink::Csv => csv_field(&key),
+            ExportSink::Url => percent_encode(&key),
+        };
+
+        truncate_chars(&normalized, self.config.max_key_length)
+    }
+
+    /// Normalize a single tag value
+    pub fn normalize_value(&self, value: &str) -> String {
+        let value = match self.config.max_value_length {
+            Some(max) => truncate_chars(value, max),
+            None => value.to_string(),
+        };
+
+        match self.sink {
+            ExportSink::Prometheus => escape_prometheus_value(&value),
+            ExportSink::Csv => csv_field(&value),
+            ExportSink::Url => percent_encode(&value),
+        }
+    }
+
+    fn prometheus_label_name(&self, key: &str) -> String {
+        let replacement = if self.config.replacement.is_ascii_alphanumeric()
+            || self.config.replacement == '_'
+        {
+            self.config.replacement
+        } else {
+            '_'
+        };
+
+        let mut name: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    replacement
+                }
+            })
+            .collect();
+
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert(0, '_');
+        }
+        // Names starting with "__" are reserved for Prometheus internals
+        if name.starts_with("__") {
+            name.insert_str(0, "tag");
+        }
+
+        name
+    }
+}
+
+/// Escape a Prometheus label value for the text exposition format
+fn escape_prometheus_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quote a CSV field when it contains delimiters, quotes, or surrounding whitespace
+pub(crate) fn csv_field(value: &str) -> String {
+    let needs_quoting = value.contains([',', '"', '\n', '\r'])
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace);
+
+    if needs_quoting {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn truncate_chars(value: &str, max: usize) -> String {
+    match value.char_indices().nth(max) {
+        Some((idx, _)) => value[..idx].to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_prometheus_label_names() {
+        let normalizer = TagNormalizer::new(ExportSink::Prometheus);
+
+        assert_eq!(normalizer.normalize_key("model_id"), "model_id");
+        assert_eq!(normalizer.normalize_key("model id"), "model_id");
+        assert_eq!(normalizer.normalize_key("région"), "r_gion");
+        assert_eq!(normalizer.normalize_key("9lives"), "_9lives");
+        assert_eq!(normalizer.normalize_key("__name__"), "tag__name__");
+        assert_eq!(normalizer.normalize_key(""), "_");
+    }
+
+    #[test]
+    fn test_prometheus_value_escaping() {
+        let normalizer = TagNormalizer::new(ExportSink::Prometheus);
+        assert_eq!(
+            normalizer.normalize_value("say \"hi\"\nback\\slash"),
+            "say \\\"hi\\\"\\nback\\\\slash"
+        );
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let normalizer = TagNormalizer::new(ExportSink::Csv);
+        assert_eq!(normalizer.normalize_value("plain"), "plain");
+        assert_eq!(normalizer.normalize_value("a,b"), "\"a,b\"");
+        assert_eq!(normalizer.normalize_value("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(normalizer.normalize_value(" padded"), "\" padded\"");
+    }
+
+    #[test]
+    fn test_percent_encoding() {
+        let normalizer = TagNormalizer::new(ExportSink::Url);
+        assert_eq!(normalizer.normalize_key("team name"), "team%20name");
+        assert_eq!(normalizer.normalize_value("é"), "%C3%A9");
+    }
+
+    #[test]
+    fn test_collisions_are_disambiguated_and_reversible() {
+        let normalizer = TagNormalizer::new(ExportSink::Prometheus);
+        let original = tags(&[("model id", "gpt-4"), ("model_id", "claude"), ("zone", "a\"b")]);
+
+        let normalized = normalizer.normalize(&original);
+
+        assert_eq!(normalized.tags.len(), 3);
+        assert_eq!(normalized.tags["model_id"], "gpt-4");
+        assert_eq!(normalized.tags["model_id_2"], "claude");
+        assert_eq!(normalized.restore(), original);
+    }
+
+    #[test]
+    fn test_unchanged_tags_have_empty_mapping() {
+        let normalizer = TagNormalizer::new(ExportSink::Prometheus);
+        let normalized = normalizer.normalize(&tags(&[("env", "prod")]));
+        assert!(normalized.mapping.is_empty());
+    }
+
+    #[test]
+    fn test_value_truncation_is_reversible() {
+        let config = NormalizationConfig {
+            max_value_length: Some(3),
+            lowercase_keys: true,
+            ..Default::default()
+        };
+        let normalizer = TagNormalizer::with_config(ExportSink::Prometheus, config);
+        let original = tags(&[("Service", "analytics")]);
+
+        let normalized = normalizer.normalize(&original);
+        assert_eq!(normalized.tags["service"], "ana");
+        assert_eq!(normalized.restore(), original);
+    }
+}
@@ -0,0 +1,748 @@
+//! Multi-Region Federation
+//!
+//! Region-local hubs periodically ship rollup aggregates — never raw events —
+//! to a global hub over HTTP or Kafka. Every shipped row is tagged with its
+//! `region` (and `cluster`, when set), so the global hub stores one row per
+//! region for each window and tag set, and region-level queries are plain tag
+//! filters.
+//!
+//! Local hubs reship each window for a lookback period while it may still be
+//! filling in. The global hub upserts rows by window and tags, so a reshipped
+//! window replaces its earlier copy rather than being counted twice; totals
+//! across regions are merged at query time: counts and sums add, extremes
+//! take the extreme, averages and deviations are pooled by count, and
+//! percentiles come from the merged histograms.
+
+use crate::auth::API_KEY_HEADER;
+use crate::database::timescale::parse_window;
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::histogram::Histogram;
+use crate::models::metrics::TimeWindow;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::Message;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Tag naming the region a federated row came from
+pub const REGION_TAG: &str = "region";
+
+/// Tag naming the cluster within a region a federated row came from
+pub const CLUSTER_TAG: &str = "cluster";
+
+/// Path on the global hub that accepts rollup batches over HTTP
+pub const ROLLUPS_PATH: &str = "/api/v1/federation/rollups";
+
+/// Role of this hub in a federation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationRole {
+    #[default]
+    Disabled,
+    /// Ships its rollups to the global hub
+    Local,
+    /// Receives rollups from local hubs
+    Global,
+}
+
+impl FromStr for FederationRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown federation role: {}", s))
+    }
+}
+
+/// How rollups travel from local hubs to the global hub
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationTransport {
+    #[default]
+    Http,
+    Kafka,
+}
+
+impl FromStr for FederationTransport {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown federation transport: {}", s))
+    }
+}
+
+/// Federation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationConfig {
+    pub role: FederationRole,
+    pub transport: FederationTransport,
+    /// Region this hub runs in, tagged on every shipped row
+    pub region: String,
+    pub cluster: Option<String>,
+    /// Base URL of the global hub, for HTTP shipping
+    pub global_url: Option<String>,
+    /// API key presented to the global hub
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+    pub kafka_brokers: Vec<String>,
+    pub topic: String,
+    /// Consumer group of the global hub's Kafka receiver
+    pub group_id: String,
+    pub ship_interval_secs: u64,
+    /// Windows starting this far back are reshipped on every run
+    pub lookback_minutes: i64,
+    /// Rollup windows shipped
+    pub windows: Vec<TimeWindow>,
+    /// Rows per shipped batch
+    pub max_batch_rows: usize,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            role: FederationRole::default(),
+            transport: FederationTransport::default(),
+            region: "default".to_string(),
+            cluster: None,
+            global_url: None,
+            api_key: None,
+            kafka_brokers: vec!["localhost:9092".to_string()],
+            topic: "llm-federation-rollups".to_string(),
+            group_id: "llm-analytics-hub-federation".to_string(),
+            ship_interval_secs: 60,
+            lookback_minutes: 120,
+            windows: vec![TimeWindow::FiveMinutes, TimeWindow::OneHour],
+            max_batch_rows: 1000,
+        }
+    }
+}
+
+impl FederationConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let windows = match std::env::var("FEDERATION_WINDOWS") {
+            Ok(v) => v
+                .split(',')
+                .map(|w| parse_window(w.trim()))
+                .collect::<Result<Vec<_>>>()?,
+            Err(_) => defaults.windows.clone(),
+        };
+        Ok(Self {
+            role: std::env::var("FEDERATION_ROLE")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?
+                .unwrap_or(defaults.role),
+            transport: std::env::var("FEDERATION_TRANSPORT")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?
+                .unwrap_or(defaults.transport),
+            region: std::env::var("FEDERATION_REGION").unwrap_or(defaults.region),
+            cluster: std::env::var("FEDERATION_CLUSTER").ok(),
+            global_url: std::env::var("FEDERATION_GLOBAL_URL").ok(),
+            api_key: std::env::var("FEDERATION_API_KEY").ok(),
+            kafka_brokers: std::env::var("KAFKA_BROKERS")
+                .map(|v| v.split(',').map(|b| b.trim().to_string()).collect())
+                .unwrap_or(defaults.kafka_brokers),
+            topic: std::env::var("FEDERATION_TOPIC").unwrap_or(defaults.topic),
+            group_id: std::env::var("FEDERATION_GROUP_ID").unwrap_or(defaults.group_id),
+            ship_interval_secs: std::env::var("FEDERATION_SHIP_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.ship_interval_secs),
+            lookback_minutes: std::env::var("FEDERATION_LOOKBACK_MINUTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.lookback_minutes),
+            windows,
+            max_batch_rows: std::env::var("FEDERATION_MAX_BATCH_ROWS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_batch_rows),
+        })
+    }
+}
+
+// ========== Rollup Batches ==========
+
+/// Rollups shipped by one region in one request or record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupBatch {
+    pub batch_id: Uuid,
+    pub region: String,
+    #[serde(default)]
+    pub cluster: Option<String>,
+    pub shipped_at: DateTime<Utc>,
+    pub rows: Vec<AggregatedMetricRow>,
+}
+
+impl RollupBatch {
+    /// Batch of `rows` tagged with the shipping region and cluster
+    pub fn new(region: &str, cluster: Option<&str>, rows: Vec<AggregatedMetricRow>) -> Self {
+        Self {
+            batch_id: Uuid::new_v4(),
+            region: region.to_string(),
+            cluster: cluster.map(str::to_string),
+            shipped_at: Utc::now(),
+            rows: rows
+                .into_iter()
+                .map(|row| tag_region(row, region, cluster))
+                .collect(),
+        }
+    }
+
+    /// Reject batches whose rows do not belong to the batch's region
+    pub fn validate(&self) -> Result<()> {
+        if self.region.trim().is_empty() {
+            bail!("Rollup batch has no region");
+        }
+        for row in &self.rows {
+            if row.tags.get(REGION_TAG).and_then(|v| v.as_str()) != Some(self.region.as_str()) {
+                bail!(
+                    "Row for '{}' at {} is not tagged with region '{}'",
+                    row.metric_name,
+                    row.window_start,
+                    self.region
+                );
+            }
+            parse_window(&row.time_window)?;
+            if row.count < 0 {
+                bail!("Row for '{}' has a negative count", row.metric_name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add region and cluster tags to a row, replacing any it already has
+pub fn tag_region(
+    mut row: AggregatedMetricRow,
+    region: &str,
+    cluster: Option<&str>,
+) -> AggregatedMetricRow {
+    if !row.tags.is_object() {
+        row.tags = serde_json::json!({});
+    }
+    if let Some(tags) = row.tags.as_object_mut() {
+        tags.insert(REGION_TAG.to_string(), region.into());
+        match cluster {
+            Some(cluster) => tags.insert(CLUSTER_TAG.to_string(), cluster.into()),
+            None => tags.remove(CLUSTER_TAG),
+        };
+    }
+    row
+}
+
+// ========== Merging ==========
+
+/// Merge rows of one metric and window start into a single row.
+///
+/// Tags keep only the pairs every row shares. Percentiles come from the
+/// merged histograms when every row has one with the same buckets, and are
+/// otherwise the count-weighted mean of the rows' percentiles. `None` when
+/// `rows` is empty.
+pub fn merge_aggregates(rows: &[&AggregatedMetricRow]) -> Option<AggregatedMetricRow> {
+    let first = rows.first()?;
+    let count: i64 = rows.iter().map(|row| row.count).sum();
+    let sum: f64 = rows.iter().map(|row| row.sum).sum();
+    let weight = |row: &AggregatedMetricRow| row.count.max(0) as f64;
+    let total_weight: f64 = rows.iter().map(|row| weight(row)).sum();
+    let weighted = |value: fn(&AggregatedMetricRow) -> f64| -> f64 {
+        if total_weight == 0.0 {
+            return rows.iter().map(|row| value(row)).sum::<f64>() / rows.len() as f64;
+        }
+        rows.iter().map(|row| value(row) * weight(row)).sum::<f64>() / total_weight
+    };
+
+    let avg = if count > 0 {
+        sum / count as f64
+    } else {
+        weighted(|row| row.avg)
+    };
+    // Pool the variances around the merged mean
+    let stddev = rows
+        .iter()
+        .map(|row| row.stddev)
+        .collect::<Option<Vec<f64>>>()
+        .filter(|_| total_weight > 0.0)
+        .map(|deviations| {
+            let second_moment: f64 = rows
+                .iter()
+                .zip(&deviations)
+                .map(|(row, sd)| weight(row) * (sd * sd + row.avg * row.avg))
+                .sum::<f64>()
+                / total_weight;
+            (second_moment - avg * avg).max(0.0).sqrt()
+        });
+
+    let histogram = rows
+        .iter()
+        .map(|row| row.histogram.as_ref().map(|histogram| &histogram.0))
+        .collect::<Option<Vec<&Histogram>>>()
+        .and_then(Histogram::merged);
+    let percentile = |q: f64, value: fn(&AggregatedMetricRow) -> f64| {
+        histogram
+            .as_ref()
+            .and_then(|histogram| histogram.quantile(q))
+            .unwrap_or_else(|| weighted(value))
+    };
+
+    let tags = match &first.tags {
+        serde_json::Value::Object(tags) => serde_json::Value::Object(
+            tags.iter()
+                .filter(|(key, value)| {
+                    rows.iter()
+                        .all(|row| row.tags.get(key.as_str()) == Some(value))
+                })
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ),
+        other => other.clone(),
+    };
+
+    Some(AggregatedMetricRow {
+        metric_name: first.metric_name.clone(),
+        time_window: first.time_window.clone(),
+        window_start: first.window_start,
+        tags,
+        avg,
+        min: rows.iter().map(|row| row.min).fold(f64::INFINITY, f64::min),
+        max: rows
+            .iter()
+            .map(|row| row.max)
+            .fold(f64::NEG_INFINITY, f64::max),
+        p50: percentile(0.5, |row| row.p50),
+        p95: percentile(0.95, |row| row.p95),
+        p99: percentile(0.99, |row| row.p99),
+        stddev,
+        count,
+        sum,
+        histogram: histogram.map(Json),
+    })
+}
+
+/// Merge rows per window start, and per value of `group_by` when given,
+/// oldest first. Rows without the grouping tag are grouped under `unknown`.
+pub fn merge_by_window(
+    rows: &[AggregatedMetricRow],
+    group_by: Option<&str>,
+) -> Vec<AggregatedMetricRow> {
+    let mut groups: BTreeMap<(DateTime<Utc>, String), Vec<&AggregatedMetricRow>> = BTreeMap::new();
+    for row in rows {
+        let group = group_by
+            .map(|tag| {
+                row.tags
+                    .get(tag)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        groups
+            .entry((row.window_start, group))
+            .or_default()
+            .push(row);
+    }
+    groups
+        .values()
+        .filter_map(|rows| merge_aggregates(rows))
+        .collect()
+}
+
+// ========== Shipping ==========
+
+enum RollupSender {
+    Http {
+        http: reqwest::Client,
+        url: String,
+        api_key: Option<String>,
+    },
+    Kafka {
+        producer: FutureProducer,
+        topic: String,
+    },
+}
+
+impl RollupSender {
+    async fn send(&self, batch: &RollupBatch) -> Result<()> {
+        match self {
+            RollupSender::Http { http, url, api_key } => {
+                let mut request = http.post(url).json(batch);
+                if let Some(key) = api_key {
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                request
+                    .send()
+                    .await
+                    .context("Failed to post rollup batch")?
+                    .error_for_status()
+                    .context("Global hub rejected rollup batch")?;
+            }
+            RollupSender::Kafka { producer, topic } => {
+                let payload =
+                    serde_json::to_vec(batch).context("Failed to serialize rollup batch")?;
+                // Keyed by region so each region's batches stay in order
+                producer
+                    .send(
+                        FutureRecord::to(topic)
+                            .key(batch.region.as_str())
+                            .payload(&payload),
+                        std::time::Duration::from_secs(10),
+                    )
+                    .await
+                    .map_err(|(e, _)| anyhow::anyhow!("Failed to produce rollup batch: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ships this region's recent rollups to the global hub
+pub struct FederationShipper {
+    config: FederationConfig,
+    database: Arc<Database>,
+    sender: RollupSender,
+    runs: AtomicU64,
+    batches_shipped: AtomicU64,
+    rows_shipped: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl FederationShipper {
+    pub fn new(config: FederationConfig, database: Arc<Database>) -> Result<Self> {
+        let sender = match config.transport {
+            FederationTransport::Http => {
+                let base = config
+                    .global_url
+                    .as_deref()
+                    .context("HTTP federation needs FEDERATION_GLOBAL_URL")?;
+                RollupSender::Http {
+                    http: reqwest::Client::new(),
+                    url: format!("{}{}", base.trim_end_matches('/'), ROLLUPS_PATH),
+                    api_key: config.api_key.clone(),
+                }
+            }
+            FederationTransport::Kafka => RollupSender::Kafka {
+                producer: ClientConfig::new()
+                    .set("bootstrap.servers", config.kafka_brokers.join(","))
+                    .set("client.id", format!("federation-{}", config.region))
+                    .set("compression.type", "zstd")
+                    .set("message.max.bytes", "10485760")
+                    .set("acks", "all")
+                    .set("enable.idempotence", "true")
+                    .create()
+                    .context("Failed to create federation producer")?,
+                topic: config.topic.clone(),
+            },
+        };
+        Ok(Self {
+            config,
+            database,
+            sender,
+            runs: AtomicU64::new(0),
+            batches_shipped: AtomicU64::new(0),
+            rows_shipped: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        })
+    }
+
+    /// Ship every configured window starting within the lookback, returning rows shipped
+    pub async fn ship_once(&self, now: DateTime<Utc>) -> Result<usize> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let since = now - Duration::minutes(self.config.lookback_minutes.max(1));
+        let mut shipped = 0;
+        for window in &self.config.windows {
+            let rows = self.database.query_aggregates_since(*window, since).await?;
+            for chunk in rows.chunks(self.config.max_batch_rows.max(1)) {
+                let batch = RollupBatch::new(
+                    &self.config.region,
+                    self.config.cluster.as_deref(),
+                    chunk.to_vec(),
+                );
+                if let Err(e) = self.sender.send(&batch).await {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                    return Err(e);
+                }
+                self.batches_shipped.fetch_add(1, Ordering::Relaxed);
+                self.rows_shipped
+                    .fetch_add(batch.rows.len() as u64, Ordering::Relaxed);
+                shipped += batch.rows.len();
+            }
+        }
+        debug!(region = %self.config.region, rows = shipped, "Shipped rollups to global hub");
+        Ok(shipped)
+    }
+
+    /// Ship on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            info!(
+                region = %self.config.region,
+                transport = ?self.config.transport,
+                "Shipping rollups to the global hub"
+            );
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.ship_interval_secs.max(1),
+            ));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.ship_once(Utc::now()).await {
+                    warn!("Rollup shipping failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> FederationShipperStats {
+        FederationShipperStats {
+            runs: self.runs.load(Ordering::Relaxed),
+            batches_shipped: self.batches_shipped.load(Ordering::Relaxed),
+            rows_shipped: self.rows_shipped.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Shipper statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationShipperStats {
+    pub runs: u64,
+    pub batches_shipped: u64,
+    pub rows_shipped: u64,
+    pub failures: u64,
+}
+
+// ========== Receiving ==========
+
+/// Latest rollups received from one region and cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionStatus {
+    pub region: String,
+    pub cluster: Option<String>,
+    pub last_batch_id: Uuid,
+    pub last_shipped_at: DateTime<Utc>,
+    pub last_received_at: DateTime<Utc>,
+    pub batches: u64,
+    pub rows: u64,
+}
+
+/// Stores rollup batches shipped by local hubs on the global hub
+pub struct FederationReceiver {
+    database: Arc<Database>,
+    regions: RwLock<HashMap<(String, Option<String>), RegionStatus>>,
+    batches_received: AtomicU64,
+    rows_received: AtomicU64,
+    batches_rejected: AtomicU64,
+}
+
+impl FederationReceiver {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            regions: RwLock::new(HashMap::new()),
+            batches_received: AtomicU64::new(0),
+            rows_received: AtomicU64::new(0),
+            batches_rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Validate and store a batch, returning the rows stored
+    pub async fn receive(&self, batch: RollupBatch) -> Result<u64> {
+        if let Err(e) = batch.validate() {
+            self.batches_rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(e);
+        }
+        let stored = self.database.upsert_aggregate_rows(&batch.rows).await?;
+
+        self.batches_received.fetch_add(1, Ordering::Relaxed);
+        self.rows_received
+            .fetch_add(batch.rows.len() as u64, Ordering::Relaxed);
+        let now = Utc::now();
+        let mut regions = self.regions.write();
+        let status = regions
+            .entry((batch.region.clone(), batch.cluster.clone()))
+            .or_insert_with(|| RegionStatus {
+                region: batch.region.clone(),
+                cluster: batch.cluster.clone(),
+                last_batch_id: batch.batch_id,
+                last_shipped_at: batch.shipped_at,
+                last_received_at: now,
+                batches: 0,
+                rows: 0,
+            });
+        status.last_batch_id = batch.batch_id;
+        status.last_shipped_at = batch.shipped_at;
+        status.last_received_at = now;
+        status.batches += 1;
+        status.rows += batch.rows.len() as u64;
+        Ok(stored)
+    }
+
+    /// Regions and clusters heard from since startup, by region
+    pub fn regions(&self) -> Vec<RegionStatus> {
+        let mut regions: Vec<RegionStatus> = self.regions.read().values().cloned().collect();
+        regions.sort_by(|a, b| (&a.region, &a.cluster).cmp(&(&b.region, &b.cluster)));
+        regions
+    }
+
+    /// Consume rollup batches from Kafka until the task is aborted. Offsets
+    /// are committed once a batch is stored; undecodable records are skipped.
+    pub fn spawn_kafka(self: Arc<Self>, config: &FederationConfig) -> Result<JoinHandle<()>> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("group.id", &config.group_id)
+            .set("bootstrap.servers", config.kafka_brokers.join(","))
+            .set("enable.partition.eof", "false")
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest")
+            .set("max.partition.fetch.bytes", "10485760")
+            .create()
+            .context("Failed to create federation consumer")?;
+        consumer
+            .subscribe(&[config.topic.as_str()])
+            .context("Failed to subscribe to federation topic")?;
+        info!(topic = %config.topic, "Receiving federated rollups from Kafka");
+
+        Ok(tokio::spawn(async move {
+            loop {
+                let message = match consumer.recv().await {
+                    Ok(message) => message,
+                    Err(e) => {
+                        error!("Federation consumer error: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                        continue;
+                    }
+                };
+                let batch = message.payload().map(serde_json::from_slice::<RollupBatch>);
+                match batch {
+                    Some(Ok(batch)) => {
+                        let region = batch.region.clone();
+                        if let Err(e) = self.receive(batch).await {
+                            // Left uncommitted so the batch is redelivered
+                            warn!(%region, "Failed to store rollup batch: {}", e);
+                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                            continue;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        self.batches_rejected.fetch_add(1, Ordering::Relaxed);
+                        warn!("Skipping undecodable rollup batch: {}", e);
+                    }
+                    None => {}
+                }
+                if let Err(e) = consumer.commit_message(&message, CommitMode::Async) {
+                    warn!("Failed to commit federation offset: {}", e);
+                }
+            }
+        }))
+    }
+
+    pub fn get_stats(&self) -> FederationReceiverStats {
+        FederationReceiverStats {
+            regions: self.regions.read().len(),
+            batches_received: self.batches_received.load(Ordering::Relaxed),
+            rows_received: self.rows_received.load(Ordering::Relaxed),
+            batches_rejected: self.batches_rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Receiver statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationReceiverStats {
+    pub regions: usize,
+    pub batches_received: u64,
+    pub rows_received: u64,
+    pub batches_rejected: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(region: &str, count: i64, avg: f64, stddev: f64, values: &[f64]) -> AggregatedMetricRow {
+        let bounds = vec![10.0, 20.0, 30.0, 40.0];
+        AggregatedMetricRow {
+            metric_name: "latency_ms".to_string(),
+            time_window: "5m".to_string(),
+            window_start: DateTime::from_timestamp(1_700_000_100, 0).unwrap(),
+            tags: serde_json::json!({ "model": "gpt-4", "region": region }),
+            avg,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            p50: avg,
+            p95: avg,
+            p99: avg,
+            stddev: Some(stddev),
+            count,
+            sum: avg * count as f64,
+            histogram: Some(Json(Histogram::from_values(bounds, values))),
+        }
+    }
+
+    #[test]
+    fn test_batch_tags_and_validation() {
+        let untagged = AggregatedMetricRow {
+            tags: serde_json::json!({ "model": "gpt-4" }),
+            ..row("", 2, 15.0, 5.0, &[10.0, 20.0])
+        };
+        let batch = RollupBatch::new("us-east", Some("a"), vec![untagged.clone()]);
+        assert_eq!(
+            batch.rows[0].tags,
+            serde_json::json!({ "model": "gpt-4", "region": "us-east", "cluster": "a" })
+        );
+        assert!(batch.validate().is_ok());
+
+        let mut forged = batch.clone();
+        forged.region = "eu-west".to_string();
+        assert!(forged.validate().is_err());
+
+        let mut bad_window = batch;
+        bad_window.rows[0].time_window = "7m".to_string();
+        assert!(bad_window.validate().is_err());
+
+        assert_eq!(
+            "Global".parse::<FederationRole>().unwrap(),
+            FederationRole::Global
+        );
+        assert!("mesh".parse::<FederationTransport>().is_err());
+    }
+
+    #[test]
+    fn test_merge_across_regions() {
+        // Two values each: {10, 20} and {30, 40}
+        let us = row("us-east", 2, 15.0, 5.0, &[10.0, 20.0]);
+        let eu = row("eu-west", 2, 35.0, 5.0, &[30.0, 40.0]);
+
+        let merged = merge_aggregates(&[&us, &eu]).unwrap();
+        assert_eq!(merged.count, 4);
+        assert_eq!(merged.sum, 100.0);
+        assert_eq!(merged.avg, 25.0);
+        assert_eq!((merged.min, merged.max), (10.0, 40.0));
+        // Population deviation of {10, 20, 30, 40}
+        assert!((merged.stddev.unwrap() - 125f64.sqrt()).abs() < 1e-9);
+        assert_eq!(merged.p50, 20.0);
+        assert_eq!(merged.histogram.as_ref().unwrap().0.total(), 4);
+        // Only the tags both regions share survive
+        assert_eq!(merged.tags, serde_json::json!({ "model": "gpt-4" }));
+
+        let rows = vec![us.clone(), eu.clone()];
+        assert_eq!(merge_by_window(&rows, None).len(), 1);
+        let by_region = merge_by_window(&rows, Some(REGION_TAG));
+        assert_eq!(by_region.len(), 2);
+        assert_eq!(by_region[0].tags[REGION_TAG], "eu-west");
+        assert_eq!(by_region[1].avg, 15.0);
+    }
+}
@@ -0,0 +1,275 @@
+//! Feature Flags
+//!
+//! Periodically refreshes `FeatureFlags` from Config-Manager and answers typed
+//! checks against them. A flag is on when it is enabled, the current environment
+//! is in its `allowed_environments` (an empty list allows every environment),
+//! and the subject falls inside its `rollout_percentage`.
+//!
+//! Rollout buckets are derived from a hash of the flag name and subject key, so
+//! a given subject stays on the same side of the rollout as the percentage
+//! grows. Subsystem-level checks use the instance ID as the subject, rolling a
+//! flag out across replicas.
+
+use crate::adapters::config_manager::{ConfigManagerAdapter, FeatureFlag, FeatureFlags};
+use anyhow::Result;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+/// Top-K and distinct-count query endpoints
+pub const HEAVY_HITTER_ENDPOINTS: &str = "analytics.heavy_hitter_endpoints";
+
+/// Sampling of high-volume telemetry at ingestion
+pub const INGESTION_SAMPLING: &str = "ingestion.sampling";
+
+/// Feature flag service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagServiceConfig {
+    /// Seconds between refreshes from Config-Manager
+    pub refresh_interval_secs: u64,
+    /// Environment flags are scoped to
+    pub environment: String,
+    /// Subject key for subsystem-level rollout checks
+    pub instance_id: String,
+}
+
+impl Default for FlagServiceConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_secs: 60,
+            environment: crate::database::environment::default_environment(),
+            instance_id: std::env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string()),
+        }
+    }
+}
+
+impl FlagServiceConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            refresh_interval_secs: std::env::var("FEATURE_FLAG_REFRESH_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.refresh_interval_secs),
+            ..defaults
+        }
+    }
+}
+
+/// Rollout bucket of a subject for a flag, in `[0, 100)`
+pub fn rollout_bucket(flag: &str, key: &str) -> f64 {
+    let digest = Sha256::new()
+        .chain_update(flag.as_bytes())
+        .chain_update(b":")
+        .chain_update(key.as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 10_000) as f64 / 100.0
+}
+
+/// Whether a flag is on for a subject in an environment
+pub fn evaluate(flag: &FeatureFlag, environment: &str, key: &str) -> bool {
+    if !flag.enabled {
+        return false;
+    }
+    if !flag.allowed_environments.is_empty()
+        && !flag.allowed_environments.iter().any(|e| e == environment)
+    {
+        return false;
+    }
+    rollout_bucket(&flag.name, key) < flag.rollout_percentage
+}
+
+/// Feature flag service backed by Config-Manager
+pub struct FlagService {
+    config: FlagServiceConfig,
+    config_manager: Option<Arc<ConfigManagerAdapter>>,
+    flags: RwLock<HashMap<String, FeatureFlag>>,
+    defaults: HashMap<String, bool>,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+impl FlagService {
+    pub fn new(config_manager: Arc<ConfigManagerAdapter>, config: FlagServiceConfig) -> Self {
+        Self {
+            config_manager: Some(config_manager),
+            ..Self::detached(config)
+        }
+    }
+
+    /// Service that only serves defaults and flags set with `set_flags`
+    pub fn detached(config: FlagServiceConfig) -> Self {
+        Self {
+            config,
+            config_manager: None,
+            flags: RwLock::new(HashMap::new()),
+            defaults: HashMap::new(),
+            refreshes: AtomicU64::new(0),
+            refresh_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Value used while Config-Manager does not define the flag
+    pub fn with_default(mut self, name: &str, enabled: bool) -> Self {
+        self.defaults.insert(name.to_string(), enabled);
+        self
+    }
+
+    /// Replace the current flag set
+    pub fn set_flags(&self, flags: FeatureFlags) {
+        *self.flags.write() = flags
+            .flags
+            .into_values()
+            .map(|flag| (flag.name.clone(), flag))
+            .collect();
+    }
+
+    /// Fetch the latest flags from Config-Manager, returning how many were loaded
+    pub async fn refresh(&self) -> Result<usize> {
+        let Some(config_manager) = &self.config_manager else {
+            return Ok(self.flags.read().len());
+        };
+
+        let flags = match config_manager.fetch_feature_flags().await {
+            Ok(flags) => flags,
+            Err(e) => {
+                self.refresh_failures.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        self.set_flags(flags);
+        self.refreshes.fetch_add(1, Ordering::Relaxed);
+
+        let count = self.flags.read().len();
+        debug!(flags = count, "Refreshed feature flags");
+        Ok(count)
+    }
+
+    /// Refresh flags on the configured interval, keeping the last set on failure
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.refresh_interval_secs.max(1));
+        info!(
+            interval_secs = period.as_secs(),
+            environment = %self.config.environment,
+            "Starting feature flag refresh"
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh().await {
+                    error!("Feature flag refresh failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Whether a subsystem-level flag is on for this instance
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.is_enabled_for(name, &self.config.instance_id)
+    }
+
+    /// Whether a flag is on for a subject such as a tenant or request ID
+    pub fn is_enabled_for(&self, name: &str, key: &str) -> bool {
+        match self.flags.read().get(name) {
+            Some(flag) => evaluate(flag, &self.config.environment, key),
+            None => self.defaults.get(name).copied().unwrap_or(false),
+        }
+    }
+
+    /// Snapshot of the loaded flags
+    pub fn flags(&self) -> Vec<FeatureFlag> {
+        let mut flags: Vec<_> = self.flags.read().values().cloned().collect();
+        flags.sort_by(|a, b| a.name.cmp(&b.name));
+        flags
+    }
+
+    pub fn get_stats(&self) -> FlagServiceStats {
+        FlagServiceStats {
+            flags_loaded: self.flags.read().len(),
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+            refresh_failures: self.refresh_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Feature flag service statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagServiceStats {
+    pub flags_loaded: usize,
+    pub refreshes: u64,
+    pub refresh_failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn flag(name: &str, rollout: f64, environments: &[&str]) -> FeatureFlag {
+        FeatureFlag {
+            name: name.to_string(),
+            enabled: true,
+            description: String::new(),
+            rollout_percentage: rollout,
+            allowed_environments: environments.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    fn service(flags: Vec<FeatureFlag>) -> FlagService {
+        let service = FlagService::detached(FlagServiceConfig {
+            refresh_interval_secs: 60,
+            environment: "production".to_string(),
+            instance_id: "hub-0".to_string(),
+        });
+        service.set_flags(FeatureFlags {
+            config_id: "test".to_string(),
+            flags: flags.into_iter().map(|f| (f.name.clone(), f)).collect(),
+            last_updated: Utc::now(),
+        });
+        service
+    }
+
+    #[test]
+    fn test_environment_scoping() {
+        let service = service(vec![
+            flag("prod_only", 100.0, &["production"]),
+            flag("staging_only", 100.0, &["staging"]),
+            flag("everywhere", 100.0, &[]),
+        ]);
+        assert!(service.is_enabled("prod_only"));
+        assert!(!service.is_enabled("staging_only"));
+        assert!(service.is_enabled("everywhere"));
+    }
+
+    #[test]
+    fn test_rollout_percentage() {
+        let service = service(vec![flag("half", 50.0, &[]), flag("none", 0.0, &[])]);
+        let enabled = (0..1000)
+            .filter(|i| service.is_enabled_for("half", &format!("tenant-{}", i)))
+            .count();
+        assert!(enabled > 400 && enabled < 600, "enabled for {}", enabled);
+        assert!(!service.is_enabled_for("none", "tenant-1"));
+
+        // Stable per subject
+        let first = service.is_enabled_for("half", "tenant-42");
+        assert_eq!(service.is_enabled_for("half", "tenant-42"), first);
+    }
+
+    #[test]
+    fn test_disabled_and_defaults() {
+        let mut off = flag("off", 100.0, &[]);
+        off.enabled = false;
+        let service = service(vec![off]).with_default("unset", true);
+        assert!(!service.is_enabled("off"));
+        assert!(service.is_enabled("unset"));
+        assert!(!service.is_enabled("unknown"));
+    }
+}
@@ -0,0 +1,420 @@
+//! gRPC Ingestion
+//!
+//! Push-based ingestion for ecosystem modules that prefer gRPC over Kafka. The
+//! `IngestEvents` RPC accepts a client stream of event batches; each event is a
+//! JSON-encoded `AnalyticsEvent`, the same wire format used on the Kafka topic,
+//! and goes through the same validation and routing as the HTTP and Kafka paths.
+//!
+//! Clients authenticate per call with an API key sent in the `x-api-key`
+//! metadata entry. Accepted events are tagged with the authenticated client ID.
+
+pub mod proto {
+    tonic::include_proto!("llm_analytics_hub.ingestion.v1");
+}
+
+use crate::schemas::events::{validate_event, AnalyticsEvent};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use proto::event_ingestion_server::{EventIngestion, EventIngestionServer};
+use proto::{EventBatch, EventRejection, IngestSummary};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, info, warn};
+
+/// Metadata entry carrying the client API key
+pub const API_KEY_METADATA: &str = "x-api-key";
+
+/// Tag recording which client pushed an event
+pub const INGEST_CLIENT_TAG: &str = "ingest_client";
+
+/// Rejections returned in a summary before the rest are only counted
+const MAX_REJECTIONS: usize = 100;
+
+/// gRPC ingestion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcIngestionConfig {
+    pub port: u16,
+    /// Reject calls without a valid API key
+    pub require_auth: bool,
+    /// Largest accepted batch message in bytes
+    pub max_message_bytes: usize,
+}
+
+impl Default for GrpcIngestionConfig {
+    fn default() -> Self {
+        Self {
+            port: 50051,
+            require_auth: true,
+            max_message_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl GrpcIngestionConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            port: std::env::var("GRPC_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.port),
+            require_auth: std::env::var("GRPC_REQUIRE_AUTH")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(defaults.require_auth),
+            max_message_bytes: std::env::var("GRPC_MAX_MESSAGE_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_message_bytes),
+        }
+    }
+}
+
+// ========== API Keys ==========
+
+/// Client API keys, stored as SHA-256 digests
+#[derive(Debug, Default, Clone)]
+pub struct ApiKeyStore {
+    clients: HashMap<Vec<u8>, String>,
+}
+
+impl ApiKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `client_id:key` pairs separated by commas
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut store = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (client_id, key) = entry
+                .split_once(':')
+                .context("API key entries must be client_id:key")?;
+            if client_id.is_empty() || key.is_empty() {
+                anyhow::bail!("API key entries must be client_id:key");
+            }
+            store.insert(client_id, key);
+        }
+        Ok(store)
+    }
+
+    /// Load keys from GRPC_API_KEYS
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("GRPC_API_KEYS") {
+            Ok(spec) => Self::parse(&spec),
+            Err(_) => Ok(Self::new()),
+        }
+    }
+
+    pub fn insert(&mut self, client_id: &str, key: &str) {
+        self.clients.insert(digest(key), client_id.to_string());
+    }
+
+    /// Client ID owning a key
+    pub fn authenticate(&self, key: &str) -> Option<&str> {
+        self.clients.get(&digest(key)).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+}
+
+fn digest(key: &str) -> Vec<u8> {
+    Sha256::digest(key.as_bytes()).to_vec()
+}
+
+// ========== Service ==========
+
+/// Destination for validated events
+#[async_trait]
+pub trait EventRouter: Send + Sync {
+    /// Route an event, returning `false` if it was dropped by sampling
+    async fn route(&self, event: AnalyticsEvent) -> Result<bool>;
+}
+
+/// Outcome of ingesting one encoded event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventOutcome {
+    Accepted,
+    Sampled,
+    Rejected(String),
+}
+
+/// `EventIngestion` gRPC service
+pub struct IngestionService {
+    config: GrpcIngestionConfig,
+    router: Arc<dyn EventRouter>,
+    api_keys: Arc<ApiKeyStore>,
+    batches: AtomicU64,
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    sampled: AtomicU64,
+    auth_failures: AtomicU64,
+}
+
+impl IngestionService {
+    pub fn new(
+        config: GrpcIngestionConfig,
+        router: Arc<dyn EventRouter>,
+        api_keys: Arc<ApiKeyStore>,
+    ) -> Self {
+        if config.require_auth && api_keys.is_empty() {
+            warn!("gRPC ingestion requires auth but no API keys are configured");
+        }
+        Self {
+            config,
+            router,
+            api_keys,
+            batches: AtomicU64::new(0),
+            accepted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            sampled: AtomicU64::new(0),
+            auth_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Wrap the service in the generated tonic server
+    pub fn into_server(self) -> EventIngestionServer<Self> {
+        let max_message_bytes = self.config.max_message_bytes;
+        EventIngestionServer::new(self).max_decoding_message_size(max_message_bytes)
+    }
+
+    /// Resolve the calling client from request metadata
+    fn authenticate<T>(&self, request: &Request<T>) -> Result<Option<String>, Status> {
+        let key = request
+            .metadata()
+            .get(API_KEY_METADATA)
+            .and_then(|v| v.to_str().ok());
+
+        match key.and_then(|k| self.api_keys.authenticate(k)) {
+            Some(client_id) => Ok(Some(client_id.to_string())),
+            None if !self.config.require_auth => Ok(None),
+            None => {
+                self.auth_failures.fetch_add(1, Ordering::Relaxed);
+                Err(Status::unauthenticated("Missing or invalid API key"))
+            }
+        }
+    }
+
+    /// Decode, validate, and route one encoded event
+    pub async fn ingest_payload(&self, client_id: Option<&str>, payload: &[u8]) -> EventOutcome {
+        let mut event: AnalyticsEvent = match serde_json::from_slice(payload) {
+            Ok(event) => event,
+            Err(e) => return self.reject(format!("Invalid event: {}", e)),
+        };
+        if let Err(e) = validate_event(&event) {
+            return self.reject(e.to_string());
+        }
+        if let Some(client_id) = client_id {
+            event
+                .common
+                .tags
+                .insert(INGEST_CLIENT_TAG.to_string(), client_id.to_string());
+        }
+
+        match self.router.route(event).await {
+            Ok(true) => {
+                self.accepted.fetch_add(1, Ordering::Relaxed);
+                EventOutcome::Accepted
+            }
+            Ok(false) => {
+                self.sampled.fetch_add(1, Ordering::Relaxed);
+                EventOutcome::Sampled
+            }
+            Err(e) => self.reject(format!("Routing failed: {}", e)),
+        }
+    }
+
+    fn reject(&self, reason: String) -> EventOutcome {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        EventOutcome::Rejected(reason)
+    }
+
+    pub fn get_stats(&self) -> GrpcIngestionStats {
+        GrpcIngestionStats {
+            batches: self.batches.load(Ordering::Relaxed),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            sampled: self.sampled.load(Ordering::Relaxed),
+            auth_failures: self.auth_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl EventIngestion for IngestionService {
+    async fn ingest_events(
+        &self,
+        request: Request<Streaming<EventBatch>>,
+    ) -> Result<Response<IngestSummary>, Status> {
+        let client_id = self.authenticate(&request)?;
+        let mut stream = request.into_inner();
+        let mut summary = IngestSummary::default();
+
+        while let Some(batch) = stream.message().await? {
+            self.batches.fetch_add(1, Ordering::Relaxed);
+            summary.batches += 1;
+            debug!(batch_id = %batch.batch_id, events = batch.events.len(), "Received gRPC batch");
+
+            for (index, payload) in batch.events.iter().enumerate() {
+                match self.ingest_payload(client_id.as_deref(), payload).await {
+                    EventOutcome::Accepted => summary.accepted += 1,
+                    EventOutcome::Sampled => summary.sampled += 1,
+                    EventOutcome::Rejected(reason) => {
+                        summary.rejected += 1;
+                        if summary.rejections.len() < MAX_REJECTIONS {
+                            summary.rejections.push(EventRejection {
+                                batch_id: batch.batch_id.clone(),
+                                index: index as u32,
+                                reason,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        info!(
+            client = client_id.as_deref().unwrap_or("anonymous"),
+            batches = summary.batches,
+            accepted = summary.accepted,
+            rejected = summary.rejected,
+            "gRPC ingestion stream closed"
+        );
+        Ok(Response::new(summary))
+    }
+}
+
+/// gRPC ingestion statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcIngestionStats {
+    pub batches: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub sampled: u64,
+    pub auth_failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+        SCHEMA_VERSION,
+    };
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct CollectingRouter {
+        events: Mutex<Vec<AnalyticsEvent>>,
+    }
+
+    #[async_trait]
+    impl EventRouter for CollectingRouter {
+        async fn route(&self, event: AnalyticsEvent) -> Result<bool> {
+            self.events.lock().push(event);
+            Ok(true)
+        }
+    }
+
+    fn event(schema_version: &str) -> Vec<u8> {
+        let event = AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: schema_version.to_string(),
+                severity: Severity::Info,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        };
+        serde_json::to_vec(&event).unwrap()
+    }
+
+    #[test]
+    fn test_api_key_store() {
+        let store = ApiKeyStore::parse("observatory:s3cret, costops:other").unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.authenticate("s3cret"), Some("observatory"));
+        assert_eq!(store.authenticate("other"), Some("costops"));
+        assert_eq!(store.authenticate("wrong"), None);
+
+        assert!(ApiKeyStore::parse("missing-separator").is_err());
+        assert!(ApiKeyStore::parse("").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_payload_validates_and_tags() {
+        let router = Arc::new(CollectingRouter::default());
+        let service = IngestionService::new(
+            GrpcIngestionConfig::default(),
+            router.clone(),
+            Arc::new(ApiKeyStore::new()),
+        );
+
+        let outcome = service
+            .ingest_payload(Some("observatory"), &event(SCHEMA_VERSION))
+            .await;
+        assert_eq!(outcome, EventOutcome::Accepted);
+
+        let outcome = service.ingest_payload(None, &event("0.9.0")).await;
+        assert!(matches!(outcome, EventOutcome::Rejected(r) if r.contains("Schema version")));
+
+        let outcome = service.ingest_payload(None, b"not json").await;
+        assert!(matches!(outcome, EventOutcome::Rejected(_)));
+
+        let routed = router.events.lock();
+        assert_eq!(routed.len(), 1);
+        assert_eq!(
+            routed[0]
+                .common
+                .tags
+                .get(INGEST_CLIENT_TAG)
+                .map(String::as_str),
+            Some("observatory")
+        );
+
+        let stats = service.get_stats();
+        assert_eq!((stats.accepted, stats.rejected), (1, 2));
+    }
+
+    #[test]
+    fn test_authentication() {
+        let mut keys = ApiKeyStore::new();
+        keys.insert("observatory", "s3cret");
+        let service = IngestionService::new(
+            GrpcIngestionConfig::default(),
+            Arc::new(CollectingRouter::default()),
+            Arc::new(keys),
+        );
+
+        let mut request = Request::new(());
+        assert!(service.authenticate(&request).is_err());
+        request
+            .metadata_mut()
+            .insert(API_KEY_METADATA, "s3cret".parse().unwrap());
+        assert_eq!(
+            service.authenticate(&request).unwrap().as_deref(),
+            Some("observatory")
+        );
+        assert_eq!(service.get_stats().auth_failures, 1);
+    }
+}
@@ -0,0 +1,369 @@
+//! Operational Diagnostics
+//!
+//! Findings behind `llm-ops doctor`. Each check records what it saw and, when
+//! something is wrong, how to fix it; the report orders those fixes so the
+//! most urgent comes first and serializes to JSON for CI gates.
+
+use super::lag_monitor::{LagLevel, LagReport};
+use super::ComponentStatus;
+use crate::adapters::AdapterHealth;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Area a diagnostic check covers, in remediation order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckCategory {
+    Database,
+    Kafka,
+    Capacity,
+    Time,
+    Adapters,
+}
+
+/// Outcome of one diagnostic check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub category: CheckCategory,
+    pub status: ComponentStatus,
+    pub message: String,
+    /// How to fix a degraded or unhealthy result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl DiagnosticCheck {
+    pub fn healthy(
+        name: impl Into<String>,
+        category: CheckCategory,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            category,
+            status: ComponentStatus::Healthy,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    pub fn degraded(
+        name: impl Into<String>,
+        category: CheckCategory,
+        message: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self {
+            status: ComponentStatus::Degraded,
+            remediation: Some(remediation.into()),
+            ..Self::healthy(name, category, message)
+        }
+    }
+
+    pub fn unhealthy(
+        name: impl Into<String>,
+        category: CheckCategory,
+        message: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self {
+            status: ComponentStatus::Unhealthy,
+            remediation: Some(remediation.into()),
+            ..Self::healthy(name, category, message)
+        }
+    }
+}
+
+/// One entry of the prioritized fix list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remediation {
+    /// 1 is the most urgent
+    pub priority: usize,
+    pub check: String,
+    pub status: ComponentStatus,
+    pub action: String,
+}
+
+/// All diagnostic findings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    /// Worst status across all checks
+    pub status: ComponentStatus,
+    pub generated_at: DateTime<Utc>,
+    pub checks: Vec<DiagnosticCheck>,
+    pub remediations: Vec<Remediation>,
+}
+
+impl DoctorReport {
+    /// Build a report, ranking unhealthy checks before degraded ones and
+    /// then by category
+    pub fn from_checks(checks: Vec<DiagnosticCheck>) -> Self {
+        let status = checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(ComponentStatus::Healthy);
+
+        let mut failing: Vec<&DiagnosticCheck> = checks
+            .iter()
+            .filter(|c| c.status != ComponentStatus::Healthy)
+            .collect();
+        failing.sort_by_key(|c| (std::cmp::Reverse(c.status), c.category));
+
+        let remediations = failing
+            .into_iter()
+            .enumerate()
+            .map(|(i, check)| Remediation {
+                priority: i + 1,
+                check: check.name.clone(),
+                status: check.status,
+                action: check
+                    .remediation
+                    .clone()
+                    .unwrap_or_else(|| check.message.clone()),
+            })
+            .collect();
+
+        Self {
+            status,
+            generated_at: Utc::now(),
+            checks,
+            remediations,
+        }
+    }
+
+    /// Whether a CI gate should pass; `strict` also fails on degraded checks
+    pub fn passes(&self, strict: bool) -> bool {
+        match self.status {
+            ComponentStatus::Healthy => true,
+            ComponentStatus::Degraded => !strict,
+            ComponentStatus::Unhealthy => false,
+        }
+    }
+}
+
+/// Limits that turn measurements into findings
+#[derive(Debug, Clone)]
+pub struct DoctorThresholds {
+    /// Disk usage (percent) that degrades a volume
+    pub disk_warning_percent: f64,
+    /// Disk usage (percent) that makes a volume unhealthy
+    pub disk_critical_percent: f64,
+    /// Clock skew that degrades a host
+    pub skew_warning: chrono::Duration,
+    /// Clock skew that breaks token validation and time-bucketed queries
+    pub skew_critical: chrono::Duration,
+}
+
+impl Default for DoctorThresholds {
+    fn default() -> Self {
+        Self {
+            disk_warning_percent: 80.0,
+            disk_critical_percent: 90.0,
+            skew_warning: chrono::Duration::seconds(2),
+            skew_critical: chrono::Duration::seconds(30),
+        }
+    }
+}
+
+/// Reachability of an upstream adapter
+pub fn adapter_check(health: &AdapterHealth) -> DiagnosticCheck {
+    let name = format!("adapter:{}", health.adapter_name);
+    if health.is_healthy {
+        let message = match health.latency_ms {
+            Some(ms) => format!("Reachable in {}ms", ms),
+            None => "Reachable".to_string(),
+        };
+        return DiagnosticCheck::healthy(name, CheckCategory::Adapters, message);
+    }
+
+    let env_prefix = health.adapter_name.to_uppercase();
+    DiagnosticCheck::degraded(
+        name,
+        CheckCategory::Adapters,
+        health
+            .error_message
+            .clone()
+            .unwrap_or_else(|| "Unreachable".to_string()),
+        format!(
+            "Check that {}_ENDPOINT points at a running {} service",
+            env_prefix, health.adapter_name
+        ),
+    )
+}
+
+/// Whether every expected topic exists
+pub fn topics_check(expected: &[String], present: &[String]) -> DiagnosticCheck {
+    let missing: Vec<&str> = expected
+        .iter()
+        .filter(|t| !present.contains(t))
+        .map(String::as_str)
+        .collect();
+
+    if missing.is_empty() {
+        return DiagnosticCheck::healthy(
+            "kafka:topics",
+            CheckCategory::Kafka,
+            format!("All {} topics present", expected.len()),
+        );
+    }
+    DiagnosticCheck::unhealthy(
+        "kafka:topics",
+        CheckCategory::Kafka,
+        format!("Missing topics: {}", missing.join(", ")),
+        "Create the declared topics with `kafka-admin create-topics`",
+    )
+}
+
+/// Whether the database schema matches the migrations on disk
+pub fn migrations_check(pending: &[&str], drifted: &[&str]) -> DiagnosticCheck {
+    if !drifted.is_empty() {
+        return DiagnosticCheck::unhealthy(
+            "database:migrations",
+            CheckCategory::Database,
+            format!(
+                "Applied migrations modified on disk: {}",
+                drifted.join(", ")
+            ),
+            "Restore the original migration files; `db-migrate verify` lists the differences",
+        );
+    }
+    if !pending.is_empty() {
+        return DiagnosticCheck::unhealthy(
+            "database:migrations",
+            CheckCategory::Database,
+            format!("{} pending: {}", pending.len(), pending.join(", ")),
+            "Run `db-migrate migrate`",
+        );
+    }
+    DiagnosticCheck::healthy(
+        "database:migrations",
+        CheckCategory::Database,
+        "Schema is up to date",
+    )
+}
+
+/// Consumer lag from the hub's lag monitor
+pub fn lag_check(report: &LagReport) -> DiagnosticCheck {
+    let name = format!("kafka:lag:{}", report.group_id);
+    let message = format!(
+        "{} messages behind on {} ({:+.1}/s)",
+        report.total_lag, report.topic, report.growth_per_sec
+    );
+    let remediation = format!(
+        "Scale consumers to {} replicas (`llm-ops scale event-ingestion --recommended`)",
+        report.recommended_replicas
+    );
+
+    match report.level {
+        LagLevel::Normal => DiagnosticCheck::healthy(name, CheckCategory::Kafka, message),
+        LagLevel::Warning => {
+            DiagnosticCheck::degraded(name, CheckCategory::Kafka, message, remediation)
+        }
+        LagLevel::Critical => {
+            DiagnosticCheck::unhealthy(name, CheckCategory::Kafka, message, remediation)
+        }
+    }
+}
+
+/// Free space on a data volume
+pub fn disk_check(name: &str, used_percent: f64, thresholds: &DoctorThresholds) -> DiagnosticCheck {
+    let name = format!("disk:{}", name);
+    let message = format!("{:.0}% used", used_percent);
+    let remediation = "Expand the volume or tighten retention (`db-migrate policies`)";
+
+    if used_percent >= thresholds.disk_critical_percent {
+        DiagnosticCheck::unhealthy(name, CheckCategory::Capacity, message, remediation)
+    } else if used_percent >= thresholds.disk_warning_percent {
+        DiagnosticCheck::degraded(name, CheckCategory::Capacity, message, remediation)
+    } else {
+        DiagnosticCheck::healthy(name, CheckCategory::Capacity, message)
+    }
+}
+
+/// Clock difference between this machine and a server
+pub fn clock_skew_check(
+    name: &str,
+    skew: chrono::Duration,
+    thresholds: &DoctorThresholds,
+) -> DiagnosticCheck {
+    let name = format!("clock:{}", name);
+    let magnitude = skew.abs();
+    let message = format!(
+        "{:+.3}s from local clock",
+        skew.num_milliseconds() as f64 / 1000.0
+    );
+    let remediation = "Enable NTP (chrony/systemd-timesyncd) on the skewed host";
+
+    if magnitude >= thresholds.skew_critical {
+        DiagnosticCheck::unhealthy(name, CheckCategory::Time, message, remediation)
+    } else if magnitude >= thresholds.skew_warning {
+        DiagnosticCheck::degraded(name, CheckCategory::Time, message, remediation)
+    } else {
+        DiagnosticCheck::healthy(name, CheckCategory::Time, message)
+    }
+}
+
+/// Use% of the filesystem in `df -P` output
+pub fn parse_df_usage(output: &str) -> Option<f64> {
+    let line = output.lines().nth(1)?;
+    let capacity = line.split_whitespace().nth(4)?;
+    capacity.trim_end_matches('%').parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_prioritizes_remediations() {
+        let thresholds = DoctorThresholds::default();
+        let report = DoctorReport::from_checks(vec![
+            disk_check("kafka-0", 85.0, &thresholds),
+            DiagnosticCheck::healthy("adapter:registry", CheckCategory::Adapters, "Reachable"),
+            clock_skew_check("timescaledb", chrono::Duration::seconds(45), &thresholds),
+            migrations_check(&["016_create_alert_silences_table"], &[]),
+        ]);
+
+        assert_eq!(report.status, ComponentStatus::Unhealthy);
+        let order: Vec<&str> = report
+            .remediations
+            .iter()
+            .map(|r| r.check.as_str())
+            .collect();
+        assert_eq!(
+            order,
+            vec!["database:migrations", "clock:timescaledb", "disk:kafka-0"]
+        );
+        assert_eq!(report.remediations[0].priority, 1);
+        assert!(!report.passes(false));
+    }
+
+    #[test]
+    fn test_degraded_report_passes_unless_strict() {
+        let report = DoctorReport::from_checks(vec![disk_check(
+            "timescaledb-0",
+            82.0,
+            &DoctorThresholds::default(),
+        )]);
+        assert!(report.passes(false));
+        assert!(!report.passes(true));
+    }
+
+    #[test]
+    fn test_topics_check() {
+        let expected = vec!["llm-events".to_string(), "llm-alerts".to_string()];
+        let check = topics_check(&expected, &["llm-events".to_string()]);
+        assert_eq!(check.status, ComponentStatus::Unhealthy);
+        assert_eq!(check.message, "Missing topics: llm-alerts");
+    }
+
+    #[test]
+    fn test_parse_df_usage() {
+        let output = "Filesystem     1024-blocks     Used Available Capacity Mounted on\n\
+                      /dev/sdb          51475068 42210912   9247772      83% /var/lib/postgresql/data\n";
+        assert_eq!(parse_df_usage(output), Some(83.0));
+        assert_eq!(parse_df_usage("garbage"), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Kafka Consumer Lag Check
+//!
+//! Measures a consumer group's lag against partition high watermarks.
+
+use super::{ComponentCheck, ComponentStatus, DependencyCheck};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::{Offset, TopicPartitionList};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Consumer lag per partition and in total
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsumerLag {
+    pub group_id: String,
+    pub topic: String,
+    pub total_lag: u64,
+    pub partitions: BTreeMap<i32, u64>,
+}
+
+/// Readiness check reporting consumer lag for a group on one topic
+pub struct KafkaLagCheck {
+    consumer: Arc<BaseConsumer>,
+    group_id: String,
+    topic: String,
+    max_lag: u64,
+    timeout: Duration,
+}
+
+impl KafkaLagCheck {
+    /// Create a lag check for `group_id` on `topic`; lag above `max_lag` degrades readiness
+    pub fn new(brokers: &str, group_id: &str, topic: &str, max_lag: u64) -> Result<Self> {
+        let consumer: BaseConsumer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("group.id", group_id)
+            .set("enable.auto.commit", "false")
+            .create()
+            .context("Failed to create Kafka lag consumer")?;
+
+        Ok(Self {
+            consumer: Arc::new(consumer),
+            group_id: group_id.to_string(),
+            topic: topic.to_string(),
+            max_lag,
+            timeout: Duration::from_secs(3),
+        })
+    }
+
+    /// Query committed offsets and watermarks to compute lag
+    pub async fn lag(&self) -> Result<ConsumerLag> {
+        let consumer = self.consumer.clone();
+        let group_id = self.group_id.clone();
+        let topic = self.topic.clone();
+        let timeout = self.timeout;
+
+        tokio::task::spawn_blocking(move || {
+            let metadata = consumer
+                .fetch_metadata(Some(&topic), timeout)
+                .context("Failed to fetch Kafka metadata")?;
+            let partitions: Vec<i32> = metadata
+                .topics()
+                .iter()
+                .filter(|t| t.name() == topic)
+                .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+                .collect();
+
+            if partitions.is_empty() {
+                anyhow::bail!("Topic {} not found", topic);
+            }
+
+            let mut tpl = TopicPartitionList::new();
+            for partition in &partitions {
+                tpl.add_partition(&topic, *partition);
+            }
+            let committed = consumer
+                .committed_offsets(tpl, timeout)
+                .context("Failed to fetch committed offsets")?;
+
+            let mut lag = ConsumerLag {
+                group_id,
+                topic: topic.clone(),
+                ..Default::default()
+            };
+
+            for element in committed.elements() {
+                let (low, high) = consumer
+                    .fetch_watermarks(&topic, element.partition(), timeout)
+                    .context("Failed to fetch watermarks")?;
+                let position = match element.offset() {
+                    Offset::Offset(offset) => offset,
+                    // Nothing committed yet: the whole retained log is outstanding
+                    _ => low,
+                };
+                let partition_lag = (high - position).max(0) as u64;
+                lag.partitions.insert(element.partition(), partition_lag);
+                lag.total_lag += partition_lag;
+            }
+
+            Ok(lag)
+        })
+        .await
+        .context("Kafka lag task panicked")?
+    }
+}
+
+#[async_trait]
+impl DependencyCheck for KafkaLagCheck {
+    async fn check(&self) -> Vec<ComponentCheck> {
+        let start = Instant::now();
+        let check = match self.lag().await {
+            Ok(lag) => {
+                let status = if lag.total_lag > self.max_lag {
+                    ComponentStatus::Degraded
+                } else {
+                    ComponentStatus::Healthy
+                };
+                let check = ComponentCheck::new("kafka", status, true)
+                    .with_details(serde_json::to_value(&lag).unwrap_or_default());
+                if status == ComponentStatus::Degraded {
+                    check.with_message(format!(
+                        "Consumer lag {} exceeds threshold {}",
+                        lag.total_lag, self.max_lag
+                    ))
+                } else {
+                    check
+                }
+            }
+            Err(e) => ComponentCheck::new("kafka", ComponentStatus::Unhealthy, true)
+                .with_message(e.to_string()),
+        };
+        vec![check.with_latency(start.elapsed())]
+    }
+}
@@ -0,0 +1,440 @@
+//! Consumer Lag Monitor
+//!
+//! Periodically samples consumer group lag, exports it as Prometheus gauges,
+//! raises alert events when lag crosses the warning or critical threshold, and
+//! derives a recommended replica count for `llm-ops scale`.
+
+use super::kafka::{ConsumerLag, KafkaLagCheck};
+use super::{ComponentCheck, ComponentStatus, DependencyCheck};
+use crate::export::prometheus::HubMetrics;
+use crate::grpc::EventRouter;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Custom payload type for consumer lag alerts
+pub const CONSUMER_LAG_ALERT_TYPE: &str = "hub.consumer_lag";
+
+/// Replica scaling policy used to turn lag into a replica recommendation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingPolicy {
+    pub min_replicas: u32,
+    pub max_replicas: u32,
+    /// Sustained events per second a single consumer replica can process
+    pub events_per_replica_per_sec: f64,
+    /// How quickly outstanding lag should be worked off
+    pub target_drain_secs: u64,
+}
+
+impl Default for ScalingPolicy {
+    fn default() -> Self {
+        Self {
+            min_replicas: 1,
+            max_replicas: 12,
+            events_per_replica_per_sec: 5000.0,
+            target_drain_secs: 300,
+        }
+    }
+}
+
+impl ScalingPolicy {
+    /// Replicas needed to absorb lag growth and drain the backlog in time
+    ///
+    /// Consumers beyond the partition count would sit idle, so the
+    /// recommendation never exceeds it.
+    pub fn recommend(&self, lag: &ConsumerLag, growth_per_sec: f64) -> u32 {
+        let drain_rate = lag.total_lag as f64 / self.target_drain_secs.max(1) as f64;
+        let required_rate = drain_rate + growth_per_sec.max(0.0);
+        let needed = (required_rate / self.events_per_replica_per_sec.max(1.0)).ceil() as u32;
+
+        let mut ceiling = self.max_replicas.max(self.min_replicas);
+        if !lag.partitions.is_empty() {
+            ceiling = ceiling
+                .min(lag.partitions.len() as u32)
+                .max(self.min_replicas);
+        }
+        needed.clamp(self.min_replicas, ceiling)
+    }
+}
+
+/// Lag monitor configuration
+#[derive(Debug, Clone)]
+pub struct LagMonitorConfig {
+    pub interval_secs: u64,
+    pub warning_lag: u64,
+    pub critical_lag: u64,
+    pub scaling: ScalingPolicy,
+}
+
+impl Default for LagMonitorConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            warning_lag: 10_000,
+            critical_lag: 100_000,
+            scaling: ScalingPolicy::default(),
+        }
+    }
+}
+
+impl LagMonitorConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            interval_secs: env_or("LAG_MONITOR_INTERVAL_SECS").unwrap_or(defaults.interval_secs),
+            warning_lag: env_or("LAG_WARNING_THRESHOLD").unwrap_or(defaults.warning_lag),
+            critical_lag: env_or("LAG_CRITICAL_THRESHOLD")
+                .or_else(|| env_or("MAX_CONSUMER_LAG"))
+                .unwrap_or(defaults.critical_lag),
+            scaling: ScalingPolicy {
+                min_replicas: env_or("SCALE_MIN_REPLICAS").unwrap_or(defaults.scaling.min_replicas),
+                max_replicas: env_or("SCALE_MAX_REPLICAS").unwrap_or(defaults.scaling.max_replicas),
+                events_per_replica_per_sec: env_or("SCALE_EVENTS_PER_REPLICA")
+                    .unwrap_or(defaults.scaling.events_per_replica_per_sec),
+                target_drain_secs: env_or("SCALE_TARGET_DRAIN_SECS")
+                    .unwrap_or(defaults.scaling.target_drain_secs),
+            },
+        }
+    }
+
+    /// Classify a total lag against the configured thresholds
+    pub fn level(&self, total_lag: u64) -> LagLevel {
+        if total_lag >= self.critical_lag {
+            LagLevel::Critical
+        } else if total_lag >= self.warning_lag {
+            LagLevel::Warning
+        } else {
+            LagLevel::Normal
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Lag relative to the alert thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LagLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl LagLevel {
+    fn severity(&self) -> Severity {
+        match self {
+            Self::Normal => Severity::Info,
+            Self::Warning => Severity::Warning,
+            Self::Critical => Severity::Critical,
+        }
+    }
+}
+
+/// Latest lag sample with its scaling hint, served at `/health/lag`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LagReport {
+    pub group_id: String,
+    pub topic: String,
+    pub total_lag: u64,
+    pub partitions: BTreeMap<i32, u64>,
+    pub level: LagLevel,
+    /// Change in total lag per second since the previous sample
+    pub growth_per_sec: f64,
+    pub recommended_replicas: u32,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Background consumer lag sampler
+pub struct LagMonitor {
+    check: KafkaLagCheck,
+    config: LagMonitorConfig,
+    latest: RwLock<Option<(LagReport, Instant)>>,
+    alerts: Option<Arc<dyn EventRouter>>,
+    samples: AtomicU64,
+    sample_errors: AtomicU64,
+    alerts_raised: AtomicU64,
+}
+
+impl LagMonitor {
+    pub fn new(check: KafkaLagCheck, config: LagMonitorConfig) -> Self {
+        Self {
+            check,
+            config,
+            latest: RwLock::new(None),
+            alerts: None,
+            samples: AtomicU64::new(0),
+            sample_errors: AtomicU64::new(0),
+            alerts_raised: AtomicU64::new(0),
+        }
+    }
+
+    /// Publish lag alerts through the given router
+    pub fn with_alerts(mut self, router: Arc<dyn EventRouter>) -> Self {
+        self.alerts = Some(router);
+        self
+    }
+
+    /// Sample lag every `interval_secs` until the process exits
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(async move {
+            info!("Consumer lag monitor sampling every {:?}", interval);
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.sample().await {
+                    warn!("Consumer lag sample failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Take one lag sample, update metrics, and alert on level changes
+    pub async fn sample(&self) -> Result<LagReport> {
+        let lag = match self.check.lag().await {
+            Ok(lag) => lag,
+            Err(e) => {
+                self.sample_errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        self.samples.fetch_add(1, Ordering::Relaxed);
+
+        let now = Instant::now();
+        let previous = self.latest.read().clone();
+        let growth_per_sec = match &previous {
+            Some((prev, at)) => {
+                let elapsed = now.duration_since(*at).as_secs_f64();
+                if elapsed > 0.0 {
+                    (lag.total_lag as f64 - prev.total_lag as f64) / elapsed
+                } else {
+                    0.0
+                }
+            }
+            None => 0.0,
+        };
+
+        let report = LagReport {
+            level: self.config.level(lag.total_lag),
+            recommended_replicas: self.config.scaling.recommend(&lag, growth_per_sec),
+            growth_per_sec,
+            group_id: lag.group_id,
+            topic: lag.topic,
+            total_lag: lag.total_lag,
+            partitions: lag.partitions,
+            sampled_at: Utc::now(),
+        };
+
+        let metrics = HubMetrics::global();
+        metrics.set_consumer_lag(&report.group_id, &report.topic, &report.partitions);
+        metrics.set_recommended_replicas(
+            &report.group_id,
+            &report.topic,
+            report.recommended_replicas,
+        );
+
+        let previous_level = previous
+            .map(|(prev, _)| prev.level)
+            .unwrap_or(LagLevel::Normal);
+        if report.level != previous_level {
+            self.raise_alert(&report, previous_level);
+        }
+
+        *self.latest.write() = Some((report.clone(), now));
+        Ok(report)
+    }
+
+    /// Most recent lag sample, if any
+    pub fn latest(&self) -> Option<LagReport> {
+        self.latest
+            .read()
+            .as_ref()
+            .map(|(report, _)| report.clone())
+    }
+
+    /// Build the alert published when lag changes level
+    pub fn alert_event(&self, report: &LagReport, previous: LagLevel) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        tags.insert("consumer_group".to_string(), report.group_id.clone());
+        tags.insert("topic".to_string(), report.topic.clone());
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: report.level.severity(),
+                environment: crate::database::environment::default_environment(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: CONSUMER_LAG_ALERT_TYPE.to_string(),
+                data: serde_json::json!({
+                    "previous_level": previous,
+                    "level": report.level,
+                    "total_lag": report.total_lag,
+                    "warning_threshold": self.config.warning_lag,
+                    "critical_threshold": self.config.critical_lag,
+                    "growth_per_sec": report.growth_per_sec,
+                    "recommended_replicas": report.recommended_replicas,
+                }),
+            }),
+        }
+    }
+
+    fn raise_alert(&self, report: &LagReport, previous: LagLevel) {
+        if report.level > previous {
+            warn!(
+                "Consumer lag for {} on {} is {:?}: {} outstanding",
+                report.group_id, report.topic, report.level, report.total_lag
+            );
+        } else {
+            info!(
+                "Consumer lag for {} on {} recovered to {:?}",
+                report.group_id, report.topic, report.level
+            );
+        }
+
+        let Some(router) = self.alerts.clone() else {
+            return;
+        };
+        self.alerts_raised.fetch_add(1, Ordering::Relaxed);
+        let event = self.alert_event(report, previous);
+        tokio::spawn(async move {
+            if let Err(e) = router.route(event).await {
+                warn!("Failed to publish consumer lag alert: {}", e);
+            }
+        });
+    }
+
+    /// Get monitor statistics
+    pub fn get_stats(&self) -> LagMonitorStats {
+        LagMonitorStats {
+            samples: self.samples.load(Ordering::Relaxed),
+            sample_errors: self.sample_errors.load(Ordering::Relaxed),
+            alerts_raised: self.alerts_raised.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Lag monitor statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct LagMonitorStats {
+    pub samples: u64,
+    pub sample_errors: u64,
+    pub alerts_raised: u64,
+}
+
+#[async_trait]
+impl DependencyCheck for LagMonitor {
+    async fn check(&self) -> Vec<ComponentCheck> {
+        // Fall back to a direct query until the first sample lands
+        let Some(report) = self.latest() else {
+            return self.check.check().await;
+        };
+
+        let status = match report.level {
+            LagLevel::Critical => ComponentStatus::Degraded,
+            _ => ComponentStatus::Healthy,
+        };
+        let check = ComponentCheck::new("kafka", status, true)
+            .with_details(serde_json::to_value(&report).unwrap_or_default());
+        let check = if status == ComponentStatus::Degraded {
+            check.with_message(format!(
+                "Consumer lag {} exceeds threshold {}",
+                report.total_lag, self.config.critical_lag
+            ))
+        } else {
+            check
+        };
+        vec![check]
+    }
+}
+
+/// Router serving the latest lag sample at `/health/lag`
+pub fn lag_router<S>(monitor: Arc<LagMonitor>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/health/lag", get(lag_handler))
+        .with_state(monitor)
+}
+
+/// Lag handler; responds 503 until the first sample is taken
+pub async fn lag_handler(State(monitor): State<Arc<LagMonitor>>) -> impl IntoResponse {
+    match monitor.latest() {
+        Some(report) => (
+            StatusCode::OK,
+            Json(serde_json::to_value(report).unwrap_or_default()),
+        ),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "No consumer lag sample yet" })),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lag(total: u64, partitions: i32) -> ConsumerLag {
+        ConsumerLag {
+            group_id: "metrics-aggregation".to_string(),
+            topic: "llm-events".to_string(),
+            total_lag: total,
+            partitions: (0..partitions)
+                .map(|p| (p, total / partitions as u64))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_lag_levels() {
+        let config = LagMonitorConfig::default();
+        assert_eq!(config.level(0), LagLevel::Normal);
+        assert_eq!(config.level(10_000), LagLevel::Warning);
+        assert_eq!(config.level(250_000), LagLevel::Critical);
+    }
+
+    #[test]
+    fn test_recommendation_scales_with_backlog() {
+        let policy = ScalingPolicy::default();
+
+        // No backlog keeps the floor
+        assert_eq!(policy.recommend(&lag(0, 8), 0.0), 1);
+
+        // 3M events drained in 300s at 5k/s per replica needs 2 replicas
+        assert_eq!(policy.recommend(&lag(3_000_000, 8), 0.0), 2);
+
+        // Growing lag needs extra capacity on top of the drain
+        assert_eq!(policy.recommend(&lag(3_000_000, 8), 5_000.0), 3);
+    }
+
+    #[test]
+    fn test_recommendation_capped_by_partitions() {
+        let policy = ScalingPolicy::default();
+        assert_eq!(policy.recommend(&lag(1_000_000_000, 4), 0.0), 4);
+        assert_eq!(policy.recommend(&lag(1_000_000_000, 64), 0.0), 12);
+    }
+}
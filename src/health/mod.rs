@@ -0,0 +1,342 @@
+//! Health and Readiness Probes
+//!
+//! Combines adapter health, database connectivity, and Kafka consumer lag into a
+//! single readiness document served at `/health/live` and `/health/ready`.
+
+pub mod doctor;
+pub mod kafka;
+pub mod lag_monitor;
+
+pub use doctor::{CheckCategory, DiagnosticCheck, DoctorReport, DoctorThresholds};
+pub use kafka::KafkaLagCheck;
+pub use lag_monitor::{lag_router, LagMonitor, LagMonitorConfig, LagReport};
+
+use crate::adapters::AdapterManager;
+use crate::database::Database;
+use async_trait::async_trait;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Health of a single component or of the whole service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// Result of checking one dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentCheck {
+    pub name: String,
+    pub status: ComponentStatus,
+    /// Whether an unhealthy result makes the service not ready
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub details: serde_json::Value,
+}
+
+impl ComponentCheck {
+    pub fn new(name: impl Into<String>, status: ComponentStatus, critical: bool) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            critical,
+            latency_ms: None,
+            message: None,
+            details: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_millis() as u64);
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Liveness document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivenessReport {
+    pub status: ComponentStatus,
+    pub version: String,
+    pub uptime_seconds: u64,
+}
+
+/// Readiness document combining all dependency checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub status: ComponentStatus,
+    pub version: String,
+    pub uptime_seconds: u64,
+    pub checked_at: DateTime<Utc>,
+    pub components: Vec<ComponentCheck>,
+}
+
+impl ReadinessReport {
+    /// Build a report from component checks
+    ///
+    /// The service is ready unless a critical component is unhealthy; any other
+    /// failing component only degrades the overall status.
+    pub fn from_checks(components: Vec<ComponentCheck>, uptime: Duration) -> Self {
+        let ready = !components
+            .iter()
+            .any(|c| c.critical && c.status == ComponentStatus::Unhealthy);
+
+        let status = if !ready {
+            ComponentStatus::Unhealthy
+        } else if components
+            .iter()
+            .any(|c| c.status != ComponentStatus::Healthy)
+        {
+            ComponentStatus::Degraded
+        } else {
+            ComponentStatus::Healthy
+        };
+
+        Self {
+            ready,
+            status,
+            version: crate::VERSION.to_string(),
+            uptime_seconds: uptime.as_secs(),
+            checked_at: Utc::now(),
+            components,
+        }
+    }
+
+    /// Look up a component by name
+    pub fn component(&self, name: &str) -> Option<&ComponentCheck> {
+        self.components.iter().find(|c| c.name == name)
+    }
+}
+
+/// A dependency that contributes to readiness
+#[async_trait]
+pub trait DependencyCheck: Send + Sync {
+    /// Run the check, returning one or more component results
+    async fn check(&self) -> Vec<ComponentCheck>;
+}
+
+#[async_trait]
+impl DependencyCheck for AdapterManager {
+    async fn check(&self) -> Vec<ComponentCheck> {
+        self.health_check_all()
+            .await
+            .into_iter()
+            .map(|health| {
+                let status = if !health.is_healthy {
+                    ComponentStatus::Unhealthy
+                } else if health.is_degraded() {
+                    ComponentStatus::Degraded
+                } else {
+                    ComponentStatus::Healthy
+                };
+
+                let mut check = ComponentCheck::new(
+                    format!("adapter:{}", health.adapter_name),
+                    status,
+                    false,
+                );
+                check.latency_ms = health.latency_ms;
+                check.message = health.error_message.clone();
+                check.with_details(serde_json::json!({
+                    "circuit_state": health.circuit_state,
+                    "last_successful_fetch": health.last_successful_fetch,
+                }))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DependencyCheck for Database {
+    async fn check(&self) -> Vec<ComponentCheck> {
+        let start = Instant::now();
+        let check = match self.health_check().await {
+            Ok(health) => ComponentCheck::new("database", ComponentStatus::Healthy, true)
+                .with_details(serde_json::to_value(&health).unwrap_or_default()),
+            Err(e) => ComponentCheck::new("database", ComponentStatus::Unhealthy, true)
+                .with_message(e.to_string()),
+        };
+        vec![check.with_latency(start.elapsed())]
+    }
+}
+
+/// Aggregates dependency checks into liveness and readiness reports
+pub struct ReadinessProbe {
+    checks: Vec<Arc<dyn DependencyCheck>>,
+    started_at: Instant,
+    timeout: Duration,
+}
+
+impl Default for ReadinessProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadinessProbe {
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            started_at: Instant::now(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Add a dependency check
+    pub fn with_check(mut self, check: Arc<dyn DependencyCheck>) -> Self {
+        self.checks.push(check);
+        self
+    }
+
+    /// Set the per-check timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Time since the probe was created
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Liveness only reflects that the process is serving requests
+    pub fn liveness(&self) -> LivenessReport {
+        LivenessReport {
+            status: ComponentStatus::Healthy,
+            version: crate::VERSION.to_string(),
+            uptime_seconds: self.uptime().as_secs(),
+        }
+    }
+
+    /// Run all dependency checks concurrently
+    pub async fn readiness(&self) -> ReadinessReport {
+        let timeout = self.timeout;
+        let results = futures::future::join_all(self.checks.iter().map(|check| async move {
+            match tokio::time::timeout(timeout, check.check()).await {
+                Ok(components) => components,
+                Err(_) => vec![ComponentCheck::new(
+                    "dependency",
+                    ComponentStatus::Unhealthy,
+                    false,
+                )
+                .with_message(format!("Check timed out after {:?}", timeout))],
+            }
+        }))
+        .await;
+
+        ReadinessReport::from_checks(results.into_iter().flatten().collect(), self.uptime())
+    }
+}
+
+/// Router serving `/health/live` and `/health/ready`
+pub fn health_router<S>(probe: Arc<ReadinessProbe>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/health/live", get(live_handler))
+        .route("/health/ready", get(ready_handler))
+        .with_state(probe)
+}
+
+/// Liveness handler
+pub async fn live_handler(State(probe): State<Arc<ReadinessProbe>>) -> Json<LivenessReport> {
+    Json(probe.liveness())
+}
+
+/// Readiness handler; responds 503 when a critical dependency is down
+pub async fn ready_handler(State(probe): State<Arc<ReadinessProbe>>) -> impl IntoResponse {
+    let report = probe.readiness().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticCheck(ComponentCheck);
+
+    #[async_trait]
+    impl DependencyCheck for StaticCheck {
+        async fn check(&self) -> Vec<ComponentCheck> {
+            vec![self.0.clone()]
+        }
+    }
+
+    struct HangingCheck;
+
+    #[async_trait]
+    impl DependencyCheck for HangingCheck {
+        async fn check(&self) -> Vec<ComponentCheck> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Vec::new()
+        }
+    }
+
+    fn check(name: &str, status: ComponentStatus, critical: bool) -> Arc<dyn DependencyCheck> {
+        Arc::new(StaticCheck(ComponentCheck::new(name, status, critical)))
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_failure_degrades() {
+        let probe = ReadinessProbe::new()
+            .with_check(check("database", ComponentStatus::Healthy, true))
+            .with_check(check("adapter:costops", ComponentStatus::Unhealthy, false));
+
+        let report = probe.readiness().await;
+        assert!(report.ready);
+        assert_eq!(report.status, ComponentStatus::Degraded);
+        assert_eq!(report.components.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_critical_failure_is_not_ready() {
+        let probe = ReadinessProbe::new()
+            .with_check(check("database", ComponentStatus::Unhealthy, true))
+            .with_check(check("kafka", ComponentStatus::Healthy, true));
+
+        let report = probe.readiness().await;
+        assert!(!report.ready);
+        assert_eq!(report.status, ComponentStatus::Unhealthy);
+        assert_eq!(
+            report.component("database").unwrap().status,
+            ComponentStatus::Unhealthy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out() {
+        let probe = ReadinessProbe::new()
+            .with_timeout(Duration::from_millis(10))
+            .with_check(Arc::new(HangingCheck));
+
+        let report = probe.readiness().await;
+        assert!(report.ready);
+        assert_eq!(report.status, ComponentStatus::Degraded);
+    }
+}
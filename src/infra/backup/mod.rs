@@ -4,14 +4,17 @@
 //! - TimescaleDB database backups
 //! - S3 storage integration
 //! - Point-in-time recovery (PITR)
+//! - Streamed backups to S3, GCS, or Azure with manifests and rotation
 //! - Backup verification and integrity checks
 
 pub mod types;
 pub mod timescaledb;
 pub mod s3;
+pub mod store;
 pub mod verification;
 
 pub use types::*;
 pub use timescaledb::TimescaleBackupManager;
 pub use s3::S3BackupStorage;
-pub use verification::BackupVerifier;
+pub use store::{BackupDestination, BackupManifest, BackupStore, BackupUpload};
+pub use verification::{BackupVerifier, ScratchRestore};
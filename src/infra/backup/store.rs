@@ -0,0 +1,484 @@
+//! Object storage backup destinations
+//!
+//! Streams dumps to S3, GCS, Azure Blob Storage, or a local directory through
+//! multipart uploads, records a manifest (size, checksum, duration) next to
+//! each dump, and rotates old backups by count.
+//!
+//! Destinations are URLs: `s3://bucket/prefix`, `gs://bucket/prefix`,
+//! `az://container/prefix`, `file:///dir`, or a plain directory path.
+//! Credentials come from the provider's usual environment variables.
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::{MultipartId, ObjectStore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::{info, warn};
+
+const DUMP_SUFFIX: &str = ".dump";
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// A parsed backup destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupDestination {
+    S3 { bucket: String, prefix: String },
+    Gcs { bucket: String, prefix: String },
+    Azure { container: String, prefix: String },
+    Local(PathBuf),
+}
+
+impl BackupDestination {
+    pub fn parse(url: &str) -> Result<Self> {
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return Ok(BackupDestination::Local(PathBuf::from(url)));
+        };
+
+        let (bucket, prefix) = match rest.split_once('/') {
+            Some((bucket, prefix)) => (bucket, prefix.trim_matches('/')),
+            None => (rest, ""),
+        };
+        if scheme != "file" && bucket.is_empty() {
+            bail!("Backup destination '{}' has no bucket", url);
+        }
+        let (bucket, prefix) = (bucket.to_string(), prefix.to_string());
+
+        Ok(match scheme {
+            "s3" => BackupDestination::S3 { bucket, prefix },
+            "gs" => BackupDestination::Gcs { bucket, prefix },
+            "az" | "azure" => BackupDestination::Azure {
+                container: bucket,
+                prefix,
+            },
+            "file" => BackupDestination::Local(PathBuf::from(rest)),
+            _ => bail!("Unsupported backup destination scheme '{}'", scheme),
+        })
+    }
+
+    /// Whether `value` names a backup destination rather than a single file
+    pub fn is_url(value: &str) -> bool {
+        value.contains("://")
+    }
+}
+
+/// What was backed up, where, and how to check it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backup_id: String,
+    pub database: String,
+    /// Object key of the dump within the destination
+    pub object: String,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the dump
+    pub sha256: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub duration_secs: f64,
+    /// Last successful test restore
+    #[serde(default)]
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+/// Backups beyond the newest `keep`, given manifests sorted newest first
+pub fn backups_to_rotate(manifests: &[BackupManifest], keep: usize) -> Vec<&BackupManifest> {
+    manifests.iter().skip(keep).collect()
+}
+
+/// AsyncWrite adapter that hashes and counts everything written through it
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl<W> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Hex SHA-256 of everything written, and the wrapped writer
+    pub fn finish(self) -> (String, W) {
+        (hex_digest(self.hasher.finalize().as_slice()), self.inner)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for HashingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &poll {
+            this.hasher.update(&buf[..*n]);
+            this.bytes += *n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// An in-progress multipart upload of one dump
+pub struct BackupUpload {
+    backup_id: String,
+    database: String,
+    location: ObjectPath,
+    multipart_id: MultipartId,
+    writer: HashingWriter<Box<dyn AsyncWrite + Unpin + Send>>,
+    started_at: DateTime<Utc>,
+}
+
+impl BackupUpload {
+    pub fn backup_id(&self) -> &str {
+        &self.backup_id
+    }
+
+    /// Where to write the dump
+    pub fn writer(&mut self) -> &mut HashingWriter<Box<dyn AsyncWrite + Unpin + Send>> {
+        &mut self.writer
+    }
+}
+
+/// Backups kept under one destination
+pub struct BackupStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    url: String,
+}
+
+impl BackupStore {
+    /// Connect to a destination URL, reading credentials from the environment
+    pub fn open(url: &str) -> Result<Self> {
+        let (store, prefix): (Arc<dyn ObjectStore>, String) = match BackupDestination::parse(url)? {
+            BackupDestination::S3 { bucket, prefix } => (
+                Arc::new(
+                    AmazonS3Builder::from_env()
+                        .with_bucket_name(bucket)
+                        .build()
+                        .context("Failed to configure S3 backup storage")?,
+                ),
+                prefix,
+            ),
+            BackupDestination::Gcs { bucket, prefix } => (
+                Arc::new(
+                    GoogleCloudStorageBuilder::from_env()
+                        .with_bucket_name(bucket)
+                        .build()
+                        .context("Failed to configure GCS backup storage")?,
+                ),
+                prefix,
+            ),
+            BackupDestination::Azure { container, prefix } => (
+                Arc::new(
+                    MicrosoftAzureBuilder::from_env()
+                        .with_container_name(container)
+                        .build()
+                        .context("Failed to configure Azure backup storage")?,
+                ),
+                prefix,
+            ),
+            BackupDestination::Local(dir) => {
+                std::fs::create_dir_all(&dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+                let store = LocalFileSystem::new_with_prefix(&dir)
+                    .with_context(|| format!("Failed to open {}", dir.display()))?;
+                (Arc::new(store), String::new())
+            }
+        };
+
+        Ok(Self {
+            store,
+            prefix: ObjectPath::from(prefix),
+            url: url.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn dump_path(&self, backup_id: &str) -> ObjectPath {
+        self.prefix.child(format!("{}{}", backup_id, DUMP_SUFFIX))
+    }
+
+    fn manifest_path(&self, backup_id: &str) -> ObjectPath {
+        self.prefix
+            .child(format!("{}{}", backup_id, MANIFEST_SUFFIX))
+    }
+
+    /// Start a multipart upload for a new backup of `database`
+    pub async fn begin(&self, database: &str) -> Result<BackupUpload> {
+        let started_at = Utc::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let backup_id = format!(
+            "{}-{}-{}",
+            database,
+            started_at.format("%Y%m%dT%H%M%SZ"),
+            &suffix[..8]
+        );
+        let location = self.dump_path(&backup_id);
+        let (multipart_id, writer) = self
+            .store
+            .put_multipart(&location)
+            .await
+            .with_context(|| format!("Failed to start upload to {}/{}", self.url, location))?;
+
+        Ok(BackupUpload {
+            backup_id,
+            database: database.to_string(),
+            location,
+            multipart_id,
+            writer: HashingWriter::new(writer),
+            started_at,
+        })
+    }
+
+    /// Complete the upload and record its manifest
+    pub async fn finish(&self, mut upload: BackupUpload) -> Result<BackupManifest> {
+        if let Err(e) = upload.writer.shutdown().await {
+            self.abort(upload).await;
+            return Err(anyhow::Error::new(e).context("Failed to complete backup upload"));
+        }
+        if upload.writer.bytes_written() == 0 {
+            self.delete(&upload.backup_id).await?;
+            bail!("Backup {} is empty", upload.backup_id);
+        }
+
+        let completed_at = Utc::now();
+        let size_bytes = upload.writer.bytes_written();
+        let (sha256, _) = upload.writer.finish();
+        let manifest = BackupManifest {
+            backup_id: upload.backup_id,
+            database: upload.database,
+            object: upload.location.to_string(),
+            size_bytes,
+            sha256,
+            started_at: upload.started_at,
+            completed_at,
+            duration_secs: (completed_at - upload.started_at).num_milliseconds() as f64 / 1000.0,
+            verified_at: None,
+        };
+        self.write_manifest(&manifest).await?;
+
+        info!(
+            backup_id = %manifest.backup_id,
+            size_bytes = manifest.size_bytes,
+            "Backup uploaded to {}",
+            self.url
+        );
+        Ok(manifest)
+    }
+
+    /// Discard a failed upload
+    pub async fn abort(&self, upload: BackupUpload) {
+        if let Err(e) = self
+            .store
+            .abort_multipart(&upload.location, &upload.multipart_id)
+            .await
+        {
+            warn!("Failed to abort upload of {}: {}", upload.backup_id, e);
+        }
+    }
+
+    async fn write_manifest(&self, manifest: &BackupManifest) -> Result<()> {
+        let body = serde_json::to_vec_pretty(manifest)?;
+        self.store
+            .put(&self.manifest_path(&manifest.backup_id), Bytes::from(body))
+            .await
+            .context("Failed to write backup manifest")?;
+        Ok(())
+    }
+
+    /// Manifests of every backup at this destination, newest first
+    pub async fn list(&self) -> Result<Vec<BackupManifest>> {
+        let prefix = (!self.prefix.as_ref().is_empty()).then_some(&self.prefix);
+        let mut objects = self.store.list(prefix);
+
+        let mut manifests = Vec::new();
+        while let Some(object) = objects.next().await {
+            let object = object.context("Failed to list backups")?;
+            if !object.location.as_ref().ends_with(MANIFEST_SUFFIX) {
+                continue;
+            }
+            let body = self.store.get(&object.location).await?.bytes().await?;
+            match serde_json::from_slice::<BackupManifest>(&body) {
+                Ok(manifest) => manifests.push(manifest),
+                Err(e) => warn!("Skipping unreadable manifest {}: {}", object.location, e),
+            }
+        }
+
+        manifests.sort_by_key(|m| std::cmp::Reverse(m.started_at));
+        Ok(manifests)
+    }
+
+    /// The backup with the given ID, or the newest one
+    pub async fn find(&self, backup_id: Option<&str>) -> Result<BackupManifest> {
+        let manifests = self.list().await?;
+        let found = match backup_id {
+            Some(id) => manifests.into_iter().find(|m| m.backup_id == id),
+            None => manifests.into_iter().next(),
+        };
+        found.with_context(|| match backup_id {
+            Some(id) => format!("No backup {} at {}", id, self.url),
+            None => format!("No backups at {}", self.url),
+        })
+    }
+
+    /// Delete a backup's dump and manifest
+    pub async fn delete(&self, backup_id: &str) -> Result<()> {
+        for path in [self.dump_path(backup_id), self.manifest_path(backup_id)] {
+            match self.store.delete(&path).await {
+                Ok(()) | Err(object_store::Error::NotFound { .. }) => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to delete {}", path)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete all but the newest `keep` backups, returning the deleted IDs
+    pub async fn rotate(&self, keep: usize) -> Result<Vec<String>> {
+        let manifests = self.list().await?;
+        let mut deleted = Vec::new();
+        for manifest in backups_to_rotate(&manifests, keep) {
+            info!("Rotating out backup {}", manifest.backup_id);
+            self.delete(&manifest.backup_id).await?;
+            deleted.push(manifest.backup_id.clone());
+        }
+        Ok(deleted)
+    }
+
+    /// Download a dump, failing if its size or checksum differ from the manifest
+    pub async fn download(&self, manifest: &BackupManifest, local_path: &Path) -> Result<()> {
+        let location = ObjectPath::from(manifest.object.as_str());
+        let mut stream = self
+            .store
+            .get(&location)
+            .await
+            .with_context(|| format!("Failed to fetch {}", manifest.object))?
+            .into_stream();
+
+        let file = tokio::fs::File::create(local_path)
+            .await
+            .with_context(|| format!("Failed to create {}", local_path.display()))?;
+        let mut writer = HashingWriter::new(file);
+        while let Some(chunk) = stream.next().await {
+            writer
+                .write_all(&chunk.context("Failed to read backup")?)
+                .await?;
+        }
+        writer.flush().await?;
+
+        let size = writer.bytes_written();
+        let (sha256, _) = writer.finish();
+        if size != manifest.size_bytes || sha256 != manifest.sha256 {
+            bail!(
+                "Backup {} is corrupt: expected {} bytes with SHA-256 {}, got {} bytes with {}",
+                manifest.backup_id,
+                manifest.size_bytes,
+                manifest.sha256,
+                size,
+                sha256
+            );
+        }
+        Ok(())
+    }
+
+    /// Record a successful test restore in the backup's manifest
+    pub async fn mark_verified(&self, manifest: &mut BackupManifest) -> Result<()> {
+        manifest.verified_at = Some(Utc::now());
+        self.write_manifest(manifest).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_destination() {
+        assert_eq!(
+            BackupDestination::parse("s3://backups/prod/timescaledb/").unwrap(),
+            BackupDestination::S3 {
+                bucket: "backups".to_string(),
+                prefix: "prod/timescaledb".to_string()
+            }
+        );
+        assert_eq!(
+            BackupDestination::parse("az://dumps").unwrap(),
+            BackupDestination::Azure {
+                container: "dumps".to_string(),
+                prefix: String::new()
+            }
+        );
+        assert_eq!(
+            BackupDestination::parse("/var/backups").unwrap(),
+            BackupDestination::Local(PathBuf::from("/var/backups"))
+        );
+        assert!(BackupDestination::parse("ftp://host/dir").is_err());
+        assert!(BackupDestination::parse("gs:///prefix").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upload_rotate_and_download() {
+        let dir = std::env::temp_dir().join(format!("backup-store-{}", uuid::Uuid::new_v4()));
+        let store = BackupStore::open(dir.to_str().unwrap()).unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..3u8 {
+            let mut upload = store.begin("timescaledb").await.unwrap();
+            upload.writer().write_all(&[i; 1024]).await.unwrap();
+            ids.push(store.finish(upload).await.unwrap().backup_id);
+        }
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].backup_id, ids[2]);
+        assert_eq!(listed[0].size_bytes, 1024);
+
+        let local = dir.join("restored.dump");
+        let newest = store.find(None).await.unwrap();
+        store.download(&newest, &local).await.unwrap();
+        assert_eq!(std::fs::read(&local).unwrap(), vec![2u8; 1024]);
+
+        let mut corrupt = newest.clone();
+        corrupt.sha256 = "0".repeat(64);
+        assert!(store.download(&corrupt, &local).await.is_err());
+
+        assert_eq!(store.rotate(2).await.unwrap(), vec![ids[0].clone()]);
+        assert_eq!(store.list().await.unwrap().len(), 2);
+        assert!(store.find(Some(&ids[0])).await.is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,9 +1,12 @@
 //! Backup verification and integrity checking
 
 use anyhow::{Context, Result};
-use tracing::{debug, info};
+use sqlx::PgPool;
+use std::path::Path;
+use tracing::{debug, info, warn};
 
 use super::s3::S3BackupStorage;
+use super::store::{BackupManifest, BackupStore};
 use super::timescaledb::TimescaleBackupManager;
 use super::types::{BackupMetadata, RestoreConfig, VerificationCheck, VerificationResult};
 
@@ -367,3 +370,181 @@ pub struct BackupStatistics {
     /// Age of newest backup in days
     pub newest_backup_age_days: i64,
 }
+
+/// Test-restores a stored dump into a throwaway database
+pub struct ScratchRestore {
+    /// Connection URL for a role allowed to create and drop databases
+    admin_url: String,
+}
+
+impl ScratchRestore {
+    pub fn new(admin_url: impl Into<String>) -> Self {
+        Self {
+            admin_url: admin_url.into(),
+        }
+    }
+
+    /// Download the dump (checking its checksum), restore it into a scratch
+    /// database, check that tables came back, then drop the scratch database
+    pub async fn verify(
+        &self,
+        store: &BackupStore,
+        manifest: &BackupManifest,
+    ) -> Result<VerificationResult> {
+        info!("Test-restoring backup {}", manifest.backup_id);
+
+        let mut result = VerificationResult::new(&manifest.backup_id);
+        let dump = std::env::temp_dir().join(format!("{}.dump", manifest.backup_id));
+
+        if let Err(e) = store.download(manifest, &dump).await {
+            tokio::fs::remove_file(&dump).await.ok();
+            result.add_check(VerificationCheck::new("Checksum").fail(format!("{:#}", e)));
+            result.complete();
+            return Ok(result);
+        }
+        result.add_check(VerificationCheck::new("Checksum").pass(format!(
+            "{} bytes, SHA-256 {}",
+            manifest.size_bytes, manifest.sha256
+        )));
+
+        let scratch = scratch_database_name(&manifest.backup_id);
+        let admin = PgPool::connect(&self.admin_url)
+            .await
+            .context("Failed to connect to the scratch database server")?;
+        sqlx::query(&format!("CREATE DATABASE \"{}\"", scratch))
+            .execute(&admin)
+            .await
+            .with_context(|| format!("Failed to create scratch database {}", scratch))?;
+
+        let outcome = self.restore_into(&scratch, &dump, &mut result).await;
+
+        // Always clean up, even when the restore itself failed
+        let drop = format!("DROP DATABASE IF EXISTS \"{}\" WITH (FORCE)", scratch);
+        if let Err(e) = sqlx::query(&drop).execute(&admin).await {
+            warn!("Failed to drop scratch database {}: {}", scratch, e);
+        }
+        admin.close().await;
+        tokio::fs::remove_file(&dump).await.ok();
+
+        outcome?;
+        result.complete();
+
+        info!(
+            "Test restore of backup {}: {}",
+            manifest.backup_id,
+            if result.valid { "VALID" } else { "INVALID" }
+        );
+        Ok(result)
+    }
+
+    async fn restore_into(
+        &self,
+        scratch: &str,
+        dump: &Path,
+        result: &mut VerificationResult,
+    ) -> Result<()> {
+        let url = database_url(&self.admin_url, scratch)?;
+        let pool = PgPool::connect(&url)
+            .await
+            .context("Failed to connect to the scratch database")?;
+
+        // Hypertables only restore between TimescaleDB's pre/post restore hooks
+        let timescale = sqlx::query("CREATE EXTENSION IF NOT EXISTS timescaledb")
+            .execute(&pool)
+            .await
+            .is_ok();
+        if timescale {
+            sqlx::query("SELECT timescaledb_pre_restore()")
+                .execute(&pool)
+                .await?;
+        }
+
+        let start = std::time::Instant::now();
+        let output = tokio::process::Command::new("pg_restore")
+            .args(["--no-owner", "--no-privileges", "-d", &url])
+            .arg(dump)
+            .output()
+            .await
+            .context("Failed to run pg_restore")?;
+        let elapsed = start.elapsed().as_secs_f64();
+
+        if timescale {
+            sqlx::query("SELECT timescaledb_post_restore()")
+                .execute(&pool)
+                .await?;
+        }
+
+        if output.status.success() {
+            result.add_check(
+                VerificationCheck::new("Restore").pass(format!("Restored in {:.1}s", elapsed)),
+            );
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let tail: Vec<&str> = stderr.lines().rev().take(3).collect();
+            result.add_check(VerificationCheck::new("Restore").fail(format!(
+                "pg_restore exited with {}: {}",
+                output.status,
+                tail.into_iter().rev().collect::<Vec<_>>().join(" / ")
+            )));
+        }
+
+        let tables: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM information_schema.tables WHERE table_schema = 'public'",
+        )
+        .fetch_one(&pool)
+        .await
+        .context("Failed to count restored tables")?;
+        if tables > 0 {
+            result.add_check(
+                VerificationCheck::new("Tables").pass(format!("{} tables restored", tables)),
+            );
+        } else {
+            result.add_check(VerificationCheck::new("Tables").fail("No tables were restored"));
+        }
+
+        pool.close().await;
+        Ok(())
+    }
+}
+
+/// Postgres-safe scratch database name for a backup
+fn scratch_database_name(backup_id: &str) -> String {
+    let sanitized: String = backup_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // Identifiers are limited to 63 bytes
+    format!("verify_{}", sanitized).chars().take(63).collect()
+}
+
+/// `url` pointing at a different database on the same server
+fn database_url(url: &str, database: &str) -> Result<String> {
+    let mut url = reqwest::Url::parse(url).context("Invalid database URL")?;
+    url.set_path(&format!("/{}", database));
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_database_naming() {
+        assert_eq!(
+            scratch_database_name("timescaledb-20240301T020000Z-1a2b3c4d"),
+            "verify_timescaledb_20240301t020000z_1a2b3c4d"
+        );
+        let admin = "postgres://admin:pw@db:5432/postgres?sslmode=require";
+        let url = database_url(admin, "verify_x");
+        assert_eq!(
+            url.unwrap(),
+            "postgres://admin:pw@db:5432/verify_x?sslmode=require"
+        );
+    }
+}
@@ -1,17 +1,35 @@
 //! Kubernetes client wrapper
 
+use super::error::K8sError;
 use anyhow::{Context, Result};
 use k8s_openapi::api::apps::v1::{Deployment, StatefulSet};
 use k8s_openapi::api::core::v1::{Namespace, Pod, Service};
 use kube::{
-    api::{Api, DeleteParams, ListParams, Patch, PatchParams, PostParams},
+    api::{Api, AttachParams, DeleteParams, ListParams, Patch, PatchParams, PostParams},
     config::{Config, Kubeconfig, KubeConfigOptions},
     Client,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
 use tracing::{debug, info};
 
+/// Workload kinds that support the scale subresource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkloadKind {
+    Deployment,
+    StatefulSet,
+}
+
+impl WorkloadKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkloadKind::Deployment => "Deployment",
+            WorkloadKind::StatefulSet => "StatefulSet",
+        }
+    }
+}
+
 /// Kubernetes client wrapper
 #[derive(Clone)]
 pub struct K8sClient {
@@ -60,6 +78,35 @@ impl K8sClient {
         })
     }
 
+    /// Create a client from an explicit kubeconfig and/or context, falling
+    /// back to the in-cluster or default configuration when neither is given
+    pub async fn connect(
+        namespace: impl Into<String>,
+        kubeconfig: Option<PathBuf>,
+        context: Option<String>,
+    ) -> Result<Self> {
+        let namespace = namespace.into();
+        let result = match (kubeconfig, context) {
+            (Some(path), context) => Self::with_kubeconfig(namespace, path, context).await,
+            (None, Some(context)) => {
+                let options = KubeConfigOptions {
+                    context: Some(context),
+                    cluster: None,
+                    user: None,
+                };
+                match Config::from_kubeconfig(&options).await {
+                    Ok(config) => Client::try_from(config)
+                        .context("Failed to create client from config")
+                        .map(|client| Self { client, namespace }),
+                    Err(e) => Err(anyhow::Error::new(e).context("Failed to load kubeconfig")),
+                }
+            }
+            (None, None) => Self::new(namespace).await,
+        };
+
+        result.map_err(|e| K8sError::Unavailable(format!("{:#}", e)).into())
+    }
+
     /// Get the namespace
     pub fn namespace(&self) -> &str {
         &self.namespace
@@ -179,6 +226,30 @@ impl K8sClient {
         Ok(pod_list.items)
     }
 
+    /// List pods matching a label selector, across all namespaces when
+    /// `all_namespaces` is set
+    pub async fn list_pods_matching(
+        &self,
+        selector: Option<&str>,
+        all_namespaces: bool,
+    ) -> Result<Vec<Pod>> {
+        let pods: Api<Pod> = if all_namespaces {
+            Api::all(self.client.clone())
+        } else {
+            Api::namespaced(self.client.clone(), &self.namespace)
+        };
+        let mut params = ListParams::default();
+        if let Some(selector) = selector {
+            params = params.labels(selector);
+        }
+
+        let pod_list = pods
+            .list(&params)
+            .await
+            .map_err(|e| K8sError::from_kube("Pod", selector.unwrap_or("*"), e))?;
+        Ok(pod_list.items)
+    }
+
     /// Get a specific pod
     pub async fn get_pod(&self, name: &str) -> Result<Pod> {
         let pods: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
@@ -256,32 +327,70 @@ impl K8sClient {
             .unwrap_or_else(|| "Unknown".to_string())
     }
 
-    /// Execute a command in a pod
-    ///
-    /// Note: This is a simplified implementation that uses kubectl exec under the hood.
-    /// For production use, consider using the Kubernetes exec API directly.
+    /// Execute a shell command in a pod
     pub async fn exec_in_pod(&self, pod_name: &str, command: &str) -> Result<String> {
-        debug!("Executing in pod {}: {}", pod_name, command);
+        self.exec(pod_name, &["sh", "-c", command]).await
+    }
 
-        let output = tokio::process::Command::new("kubectl")
-            .arg("exec")
-            .arg("-n")
-            .arg(&self.namespace)
-            .arg(pod_name)
-            .arg("--")
-            .arg("sh")
-            .arg("-c")
-            .arg(command)
-            .output()
+    /// Execute a command in a pod through the exec API and return its stdout
+    pub async fn exec(&self, pod_name: &str, command: &[&str]) -> Result<String> {
+        let mut stdout = Vec::new();
+        self.exec_to_writer(pod_name, command, &mut stdout).await?;
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    }
+
+    /// Execute a command in a pod, streaming its stdout into `output`.
+    /// Returns the number of bytes written.
+    pub async fn exec_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        pod_name: &str,
+        command: &[&str],
+        output: &mut W,
+    ) -> Result<u64> {
+        debug!("Executing in pod {}: {:?}", pod_name, command);
+
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
+        let params = AttachParams::default().stdout(true).stderr(true);
+        let mut process = pods
+            .exec(pod_name, command.iter().copied(), &params)
             .await
-            .with_context(|| format!("Failed to execute command in pod {}", pod_name))?;
+            .map_err(|e| K8sError::from_kube("Pod", pod_name, e))?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Command failed in pod {}: {}", pod_name, stderr);
-        }
+        let status = process.take_status();
+        let mut stdout = process.stdout().context("Exec stream has no stdout")?;
+        let stderr = process.stderr();
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        let (written, stderr) =
+            tokio::join!(tokio::io::copy(&mut stdout, output), read_to_string(stderr));
+        let written =
+            written.with_context(|| format!("Failed to read output from pod {}", pod_name))?;
+
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        process
+            .join()
+            .await
+            .with_context(|| format!("Exec in pod {} did not complete", pod_name))?;
+
+        match status {
+            Some(status) if status.status.as_deref() != Some("Success") => {
+                let message = match stderr.trim() {
+                    "" => status
+                        .message
+                        .unwrap_or_else(|| "command failed".to_string()),
+                    stderr => stderr.to_string(),
+                };
+                Err(K8sError::ExecFailed {
+                    pod: pod_name.to_string(),
+                    command: command.join(" "),
+                    message,
+                }
+                .into())
+            }
+            _ => Ok(written),
+        }
     }
 
     /// Run a temporary pod to execute a command
@@ -347,6 +456,37 @@ impl K8sClient {
         Ok(deployment_name.to_string())
     }
 
+    /// Scale a deployment or statefulset through the scale subresource
+    pub async fn scale_workload(
+        &self,
+        kind: WorkloadKind,
+        name: &str,
+        replicas: i32,
+    ) -> Result<()> {
+        info!(
+            "Scaling {} '{}' to {} replicas",
+            kind.as_str(),
+            name,
+            replicas
+        );
+
+        let patch = serde_json::json!({ "spec": { "replicas": replicas } });
+        let params = PatchParams::default();
+        let result = match kind {
+            WorkloadKind::Deployment => {
+                let api: Api<Deployment> = Api::namespaced(self.client.clone(), &self.namespace);
+                api.patch_scale(name, &params, &Patch::Merge(&patch)).await
+            }
+            WorkloadKind::StatefulSet => {
+                let api: Api<StatefulSet> = Api::namespaced(self.client.clone(), &self.namespace);
+                api.patch_scale(name, &params, &Patch::Merge(&patch)).await
+            }
+        };
+
+        result.map_err(|e| K8sError::from_kube(kind.as_str(), name, e))?;
+        Ok(())
+    }
+
     /// Scale all deployments in the namespace
     pub async fn scale_all_deployments(&self, replicas: i32) -> Result<Vec<String>> {
         info!("Scaling all deployments to {} replicas", replicas);
@@ -404,6 +544,16 @@ impl K8sClient {
         }
     }
 
+    /// Version of the API server, which doubles as a connectivity check
+    pub async fn server_version(&self) -> Result<String> {
+        let info = self
+            .client
+            .apiserver_version()
+            .await
+            .map_err(|e| K8sError::from_kube("Cluster", "version", e))?;
+        Ok(info.git_version)
+    }
+
     /// Check if the Kubernetes cluster is accessible
     pub async fn is_accessible(&self) -> bool {
         let namespaces: Api<Namespace> = Api::all(self.client.clone());
@@ -453,6 +603,15 @@ impl K8sClient {
     }
 }
 
+async fn read_to_string<R: AsyncRead + Unpin>(reader: Option<R>) -> String {
+    let mut text = String::new();
+    if let Some(mut reader) = reader {
+        // Stderr is only used for error messages, so a read failure just loses detail
+        let _ = reader.read_to_string(&mut text).await;
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
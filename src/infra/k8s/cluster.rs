@@ -0,0 +1,488 @@
+//! Cluster access with kubectl fallback
+//!
+//! Operational commands (pod listing, exec, scaling) go through the
+//! Kubernetes API via [`K8sClient`]. When no API client can be configured, or
+//! the API server cannot be reached through it, the same operation is issued
+//! through the `kubectl` binary if one is installed.
+
+use super::client::{K8sClient, WorkloadKind};
+use super::error::K8sError;
+use anyhow::{Context, Result};
+use k8s_openapi::api::core::v1::Pod;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// Namespace the hub is deployed into
+pub const DEFAULT_NAMESPACE: &str = "llm-analytics-hub";
+
+/// Which cluster to talk to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterOptions {
+    pub namespace: String,
+    /// Kubeconfig file; the default loading rules apply when unset
+    pub kubeconfig: Option<PathBuf>,
+    /// Kubeconfig context; the current context is used when unset
+    pub context: Option<String>,
+}
+
+impl Default for ClusterOptions {
+    fn default() -> Self {
+        Self {
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            kubeconfig: None,
+            context: None,
+        }
+    }
+}
+
+impl ClusterOptions {
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    pub fn with_kubeconfig(mut self, kubeconfig: impl Into<PathBuf>) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// Cluster operations through the `kubectl` binary
+#[derive(Debug, Clone)]
+pub struct Kubectl {
+    options: ClusterOptions,
+}
+
+#[derive(Deserialize)]
+struct PodList {
+    items: Vec<Pod>,
+}
+
+impl Kubectl {
+    pub fn new(options: ClusterOptions) -> Self {
+        Self { options }
+    }
+
+    /// Whether a kubectl binary can be run
+    pub async fn is_installed() -> bool {
+        Command::new("kubectl")
+            .args(["version", "--client"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map_or(false, |status| status.success())
+    }
+
+    /// Arguments selecting the kubeconfig, context, and (optionally) namespace
+    pub fn base_args(&self, namespaced: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(kubeconfig) = &self.options.kubeconfig {
+            args.push("--kubeconfig".to_string());
+            args.push(kubeconfig.display().to_string());
+        }
+        if let Some(context) = &self.options.context {
+            args.push("--context".to_string());
+            args.push(context.clone());
+        }
+        if namespaced {
+            args.push("-n".to_string());
+            args.push(self.options.namespace.clone());
+        }
+        args
+    }
+
+    fn command(&self, namespaced: bool, args: &[&str]) -> Command {
+        let mut command = Command::new("kubectl");
+        command.args(self.base_args(namespaced)).args(args);
+        debug!("Running kubectl {:?}", args);
+        command
+    }
+
+    /// Run kubectl and return its stdout, classifying failures by `kind`/`name`
+    async fn output(
+        &self,
+        namespaced: bool,
+        args: &[&str],
+        kind: &str,
+        name: &str,
+    ) -> Result<Vec<u8>> {
+        let output = self
+            .command(namespaced, args)
+            .output()
+            .await
+            .context("Failed to execute kubectl")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(kubectl_error(kind, name, stderr.trim()).into());
+        }
+        Ok(output.stdout)
+    }
+
+    pub async fn list_pods(
+        &self,
+        selector: Option<&str>,
+        all_namespaces: bool,
+    ) -> Result<Vec<Pod>> {
+        let mut args = vec!["get", "pods", "-o", "json"];
+        if let Some(selector) = selector {
+            args.extend(["-l", selector]);
+        }
+        if all_namespaces {
+            args.push("-A");
+        }
+
+        let stdout = self
+            .output(!all_namespaces, &args, "Pod", selector.unwrap_or("*"))
+            .await?;
+        let list: PodList = serde_json::from_slice(&stdout).context("Invalid kubectl pod list")?;
+        Ok(list.items)
+    }
+
+    pub async fn exec(&self, pod: &str, command: &[&str]) -> Result<String> {
+        let mut args = vec!["exec", pod, "--"];
+        args.extend_from_slice(command);
+
+        let output = self
+            .command(true, &args)
+            .output()
+            .await
+            .context("Failed to execute kubectl")?;
+        if !output.status.success() {
+            return Err(K8sError::ExecFailed {
+                pod: pod.to_string(),
+                command: command.join(" "),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Execute a command in a pod, streaming its stdout into a local file
+    pub async fn exec_to_file(&self, pod: &str, command: &[&str], path: &Path) -> Result<u64> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut args = vec!["exec", pod, "--"];
+        args.extend_from_slice(command);
+
+        let output = self
+            .command(true, &args)
+            .stdout(Stdio::from(file))
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .context("Failed to execute kubectl")?;
+        if !output.status.success() {
+            return Err(K8sError::ExecFailed {
+                pod: pod.to_string(),
+                command: command.join(" "),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }
+            .into());
+        }
+        Ok(std::fs::metadata(path)?.len())
+    }
+
+    /// Execute a command in a pod, streaming its stdout into `output`
+    pub async fn exec_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        pod: &str,
+        command: &[&str],
+        output: &mut W,
+    ) -> Result<u64> {
+        let mut args = vec!["exec", pod, "--"];
+        args.extend_from_slice(command);
+
+        let mut child = self
+            .command(true, &args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute kubectl")?;
+        let mut stdout = child.stdout.take().context("kubectl stdout not captured")?;
+        let mut stderr = child.stderr.take().context("kubectl stderr not captured")?;
+
+        let mut message = String::new();
+        let (written, _) = tokio::join!(
+            tokio::io::copy(&mut stdout, output),
+            stderr.read_to_string(&mut message)
+        );
+        let written = written.with_context(|| format!("Failed to read output from pod {}", pod))?;
+
+        let status = child.wait().await.context("Failed to wait for kubectl")?;
+        if !status.success() {
+            return Err(K8sError::ExecFailed {
+                pod: pod.to_string(),
+                command: command.join(" "),
+                message: message.trim().to_string(),
+            }
+            .into());
+        }
+        Ok(written)
+    }
+
+    pub async fn scale(&self, kind: WorkloadKind, name: &str, replicas: i32) -> Result<()> {
+        let resource = format!("{}/{}", kind.as_str().to_lowercase(), name);
+        let replicas = format!("--replicas={}", replicas);
+        self.output(true, &["scale", &resource, &replicas], kind.as_str(), name)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn server_version(&self) -> Result<String> {
+        let stdout = self
+            .output(false, &["version", "-o", "json"], "Cluster", "version")
+            .await?;
+        let version: serde_json::Value =
+            serde_json::from_slice(&stdout).context("Invalid kubectl version output")?;
+        version["serverVersion"]["gitVersion"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                K8sError::Unavailable("kubectl reported no server version".to_string()).into()
+            })
+    }
+}
+
+/// Map kubectl's stderr onto a structured error
+fn kubectl_error(kind: &str, name: &str, stderr: &str) -> K8sError {
+    if stderr.contains("(NotFound)") {
+        K8sError::NotFound {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    } else if stderr.contains("Unable to connect to the server")
+        || stderr.contains("connection refused")
+    {
+        K8sError::Unavailable(stderr.to_string())
+    } else {
+        K8sError::Api {
+            operation: format!("{} {}", kind, name),
+            message: stderr.to_string(),
+        }
+    }
+}
+
+/// Kubernetes API access, falling back to kubectl when the API client is
+/// unavailable
+pub struct Cluster {
+    api: Option<K8sClient>,
+    kubectl: Option<Kubectl>,
+}
+
+impl Cluster {
+    /// Configure an API client and detect kubectl. Fails only when neither
+    /// can be used.
+    pub async fn connect(options: ClusterOptions) -> Result<Self> {
+        let api = K8sClient::connect(
+            options.namespace.clone(),
+            options.kubeconfig.clone(),
+            options.context.clone(),
+        )
+        .await;
+        let kubectl = Kubectl::is_installed().await.then(|| Kubectl::new(options));
+
+        match (api, kubectl) {
+            (Ok(client), kubectl) => Ok(Self {
+                api: Some(client),
+                kubectl,
+            }),
+            (Err(e), Some(kubectl)) => {
+                warn!("Kubernetes API client unavailable, using kubectl: {:#}", e);
+                Ok(Self {
+                    api: None,
+                    kubectl: Some(kubectl),
+                })
+            }
+            (Err(e), None) => {
+                Err(e.context("No Kubernetes API client and kubectl is not installed"))
+            }
+        }
+    }
+
+    /// The client operations are issued through
+    pub fn backend(&self) -> &'static str {
+        if self.api.is_some() {
+            "api"
+        } else {
+            "kubectl"
+        }
+    }
+
+    /// Whether a failed API call should be retried through kubectl
+    fn fallback(&self, err: &anyhow::Error) -> Option<&Kubectl> {
+        let unavailable = err
+            .downcast_ref::<K8sError>()
+            .map_or(false, K8sError::is_unavailable);
+        let kubectl = self.kubectl.as_ref().filter(|_| unavailable)?;
+        warn!(
+            "Kubernetes API call failed, retrying with kubectl: {:#}",
+            err
+        );
+        Some(kubectl)
+    }
+
+    fn kubectl(&self) -> Result<&Kubectl> {
+        self.kubectl.as_ref().context("kubectl is not installed")
+    }
+
+    pub async fn server_version(&self) -> Result<String> {
+        if let Some(client) = &self.api {
+            match client.server_version().await {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.server_version().await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.server_version().await
+    }
+
+    /// Pods matching a label selector, in the configured namespace or all of them
+    pub async fn pods(&self, selector: Option<&str>, all_namespaces: bool) -> Result<Vec<Pod>> {
+        if let Some(client) = &self.api {
+            match client.list_pods_matching(selector, all_namespaces).await {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.list_pods(selector, all_namespaces).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.list_pods(selector, all_namespaces).await
+    }
+
+    /// Run a command in a pod and return its stdout
+    pub async fn exec(&self, pod: &str, command: &[&str]) -> Result<String> {
+        if let Some(client) = &self.api {
+            match client.exec(pod, command).await {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.exec(pod, command).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.exec(pod, command).await
+    }
+
+    /// Run a command in a pod, streaming its stdout into a local file.
+    /// Returns the number of bytes written.
+    pub async fn exec_to_file(&self, pod: &str, command: &[&str], path: &Path) -> Result<u64> {
+        if let Some(client) = &self.api {
+            let result = async {
+                let mut file = tokio::fs::File::create(path)
+                    .await
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                let written = client.exec_to_writer(pod, command, &mut file).await?;
+                file.flush().await?;
+                Ok::<_, anyhow::Error>(written)
+            }
+            .await;
+            match result {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.exec_to_file(pod, command, path).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.exec_to_file(pod, command, path).await
+    }
+
+    /// Run a command in a pod, streaming its stdout into `output`.
+    /// Returns the number of bytes written.
+    pub async fn exec_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        pod: &str,
+        command: &[&str],
+        output: &mut W,
+    ) -> Result<u64> {
+        if let Some(client) = &self.api {
+            match client.exec_to_writer(pod, command, output).await {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.exec_to_writer(pod, command, output).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.exec_to_writer(pod, command, output).await
+    }
+
+    /// Scale a workload through the scale subresource
+    pub async fn scale(&self, kind: WorkloadKind, name: &str, replicas: i32) -> Result<()> {
+        if let Some(client) = &self.api {
+            match client.scale_workload(kind, name, replicas).await {
+                Err(e) => match self.fallback(&e) {
+                    Some(kubectl) => return kubectl.scale(kind, name, replicas).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+        }
+        self.kubectl()?.scale(kind, name, replicas).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kubectl_args() {
+        let kubectl = Kubectl::new(
+            ClusterOptions::default()
+                .with_kubeconfig("/tmp/kubeconfig")
+                .with_context("staging"),
+        );
+        assert_eq!(
+            kubectl.base_args(true),
+            vec![
+                "--kubeconfig",
+                "/tmp/kubeconfig",
+                "--context",
+                "staging",
+                "-n",
+                "llm-analytics-hub"
+            ]
+        );
+        assert!(Kubectl::new(ClusterOptions::default())
+            .base_args(false)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_kubectl_error_classification() {
+        let not_found = kubectl_error(
+            "Deployment",
+            "timescaledb",
+            r#"Error from server (NotFound): deployments.apps "timescaledb" not found"#,
+        );
+        assert_eq!(
+            not_found,
+            K8sError::NotFound {
+                kind: "Deployment".to_string(),
+                name: "timescaledb".to_string()
+            }
+        );
+
+        let unreachable = kubectl_error(
+            "Pod",
+            "*",
+            "Unable to connect to the server: dial tcp 10.0.0.1:443: i/o timeout",
+        );
+        assert!(unreachable.is_unavailable());
+    }
+}
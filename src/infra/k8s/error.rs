@@ -0,0 +1,106 @@
+//! Kubernetes operation errors
+//!
+//! Structured failures for cluster operations so callers can tell a missing
+//! resource or a failed command apart from a cluster that cannot be reached.
+//! Errors are returned through `anyhow` and recovered with `downcast_ref`.
+
+use std::fmt;
+
+/// Why a Kubernetes operation failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum K8sError {
+    /// No usable cluster configuration, or the API server is unreachable
+    Unavailable(String),
+    /// The named resource does not exist
+    NotFound { kind: String, name: String },
+    /// A command run in a pod did not succeed
+    ExecFailed {
+        pod: String,
+        command: String,
+        message: String,
+    },
+    /// The API server rejected a request
+    Api { operation: String, message: String },
+}
+
+impl K8sError {
+    /// Classify a kube-rs error raised while operating on `kind`/`name`
+    pub fn from_kube(kind: &str, name: &str, err: kube::Error) -> Self {
+        match err {
+            kube::Error::Api(response) if response.code == 404 => K8sError::NotFound {
+                kind: kind.to_string(),
+                name: name.to_string(),
+            },
+            kube::Error::Api(response) => K8sError::Api {
+                operation: format!("{} {}", kind, name),
+                message: response.message,
+            },
+            err @ (kube::Error::HyperError(_)
+            | kube::Error::Service(_)
+            | kube::Error::InferConfig(_)
+            | kube::Error::Auth(_)
+            | kube::Error::UpgradeConnection(_)) => K8sError::Unavailable(err.to_string()),
+            err => K8sError::Api {
+                operation: format!("{} {}", kind, name),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// Whether retrying through another client (e.g. kubectl) might succeed
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, K8sError::Unavailable(_))
+    }
+}
+
+impl fmt::Display for K8sError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            K8sError::Unavailable(message) => write!(f, "Kubernetes unavailable: {}", message),
+            K8sError::NotFound { kind, name } => write!(f, "{} '{}' not found", kind, name),
+            K8sError::ExecFailed {
+                pod,
+                command,
+                message,
+            } => write!(f, "'{}' failed in pod {}: {}", command, pod, message),
+            K8sError::Api { operation, message } => {
+                write!(f, "Kubernetes API error on {}: {}", operation, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for K8sError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kube::error::ErrorResponse;
+
+    fn api_error(code: u16, message: &str) -> kube::Error {
+        kube::Error::Api(ErrorResponse {
+            status: "Failure".to_string(),
+            message: message.to_string(),
+            reason: "Test".to_string(),
+            code,
+        })
+    }
+
+    #[test]
+    fn test_from_kube() {
+        assert_eq!(
+            K8sError::from_kube("Deployment", "api", api_error(404, "not found")),
+            K8sError::NotFound {
+                kind: "Deployment".to_string(),
+                name: "api".to_string()
+            }
+        );
+
+        let forbidden = K8sError::from_kube("Pod", "kafka-0", api_error(403, "forbidden"));
+        assert!(!forbidden.is_unavailable());
+        assert_eq!(
+            forbidden.to_string(),
+            "Kubernetes API error on Pod kafka-0: forbidden"
+        );
+    }
+}
@@ -3,11 +3,19 @@
 //! Provides type-safe Kubernetes operations using kube-rs.
 
 pub mod client;
+pub mod cluster;
 pub mod deployment;
+pub mod error;
 pub mod health;
 pub mod resources;
+pub mod rollout;
 
-pub use client::K8sClient;
+pub use client::{K8sClient, WorkloadKind};
+pub use cluster::{Cluster, ClusterOptions, Kubectl, DEFAULT_NAMESPACE};
 pub use deployment::{DeploymentManager, DeploymentOptions};
+pub use error::K8sError;
 pub use health::HealthChecker;
 pub use resources::ResourceManager;
+pub use rollout::{
+    RolloutConfig, RolloutController, RolloutOutcome, RolloutSlo, RolloutStrategy,
+};
@@ -0,0 +1,649 @@
+//! Progressive rollouts
+//!
+//! Blue/green and canary strategies for the hub's Deployments. Both stage the
+//! new image in a temporary `<name>-<track>` Deployment, judge it against
+//! error-rate and latency SLOs by probing the health API, and then either
+//! promote the image to the original Deployment or tear the temporary one down.
+//!
+//! Canary traffic is split by replica count: canary pods carry the stable
+//! pods' labels, so the Service balances across both. Blue/green points the
+//! Service (named like its Deployment) at the green pods for the analysis
+//! window and back at the original pods once they run the new image.
+
+use super::client::{K8sClient, WorkloadKind};
+use super::error::K8sError;
+use anyhow::{bail, Context, Result};
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::{PodSpec, Service};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::api::{DeleteParams, Patch, PatchParams};
+use kube::Api;
+use serde_json::json;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Pod label that tells temporary rollout pods apart from the original ones
+pub const TRACK_LABEL: &str = "rollout-track";
+
+/// Field manager used for server-side apply
+const FIELD_MANAGER: &str = "llm-ops";
+
+/// How a new image reaches the hub's Deployments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RolloutStrategy {
+    /// Update the Deployment in place and let Kubernetes roll it
+    #[default]
+    Rolling,
+    /// Stand up a full green copy, switch traffic to it, then promote
+    BlueGreen,
+    /// Shift a growing share of traffic to canary pods, then promote
+    Canary,
+}
+
+impl RolloutStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rolling => "rolling",
+            Self::BlueGreen => "blue-green",
+            Self::Canary => "canary",
+        }
+    }
+
+    /// Track label value and name suffix of the temporary Deployment
+    fn track(&self) -> &'static str {
+        match self {
+            Self::Rolling => "stable",
+            Self::BlueGreen => "green",
+            Self::Canary => "canary",
+        }
+    }
+}
+
+impl FromStr for RolloutStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "rolling" => Ok(Self::Rolling),
+            "blue-green" | "bluegreen" => Ok(Self::BlueGreen),
+            "canary" => Ok(Self::Canary),
+            other => bail!("Unknown rollout strategy '{}'", other),
+        }
+    }
+}
+
+/// Service level objectives a new version must hold while under analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct RolloutSlo {
+    /// Highest tolerated share of failed health probes (0.0-1.0)
+    pub max_error_rate: f64,
+    /// Highest tolerated 95th percentile probe latency
+    pub max_p95_latency: Duration,
+}
+
+impl Default for RolloutSlo {
+    fn default() -> Self {
+        Self {
+            max_error_rate: 0.01,
+            max_p95_latency: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Rollout configuration
+#[derive(Debug, Clone)]
+pub struct RolloutConfig {
+    pub strategy: RolloutStrategy,
+    /// Image tag applied to every container of the rolled-out Deployments
+    pub image_tag: String,
+    /// Canary traffic percentages, in order; promotion follows the last step
+    pub canary_steps: Vec<u32>,
+    /// How long each phase is observed
+    pub analysis_window: Duration,
+    /// Delay between health probes during analysis
+    pub probe_interval: Duration,
+    /// Health endpoint probed during analysis
+    pub health_url: String,
+    pub slo: RolloutSlo,
+    /// Seconds to wait for new pods to become ready
+    pub ready_timeout_secs: u64,
+}
+
+impl Default for RolloutConfig {
+    fn default() -> Self {
+        Self {
+            strategy: RolloutStrategy::default(),
+            image_tag: "latest".to_string(),
+            canary_steps: vec![10, 50],
+            analysis_window: Duration::from_secs(300),
+            probe_interval: Duration::from_secs(5),
+            health_url: "http://localhost:3000/health/ready".to_string(),
+            slo: RolloutSlo::default(),
+            ready_timeout_secs: 300,
+        }
+    }
+}
+
+impl RolloutConfig {
+    pub fn with_strategy(mut self, strategy: RolloutStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_image_tag(mut self, image_tag: impl Into<String>) -> Self {
+        self.image_tag = image_tag.into();
+        self
+    }
+
+    pub fn with_canary_steps(mut self, canary_steps: Vec<u32>) -> Self {
+        self.canary_steps = canary_steps;
+        self
+    }
+
+    pub fn with_analysis_window(mut self, analysis_window: Duration) -> Self {
+        self.analysis_window = analysis_window;
+        self
+    }
+
+    pub fn with_health_url(mut self, health_url: impl Into<String>) -> Self {
+        self.health_url = health_url.into();
+        self
+    }
+
+    pub fn with_slo(mut self, slo: RolloutSlo) -> Self {
+        self.slo = slo;
+        self
+    }
+}
+
+/// Outcome of one health probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeSample {
+    pub latency: Duration,
+    pub ok: bool,
+}
+
+/// Error rate and latency observed over an analysis window
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloReport {
+    pub samples: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub p95_latency: Duration,
+}
+
+impl SloReport {
+    pub fn from_samples(samples: &[ProbeSample]) -> Self {
+        let errors = samples.iter().filter(|s| !s.ok).count();
+        let error_rate = if samples.is_empty() {
+            0.0
+        } else {
+            errors as f64 / samples.len() as f64
+        };
+
+        let mut latencies: Vec<Duration> = samples.iter().map(|s| s.latency).collect();
+        latencies.sort();
+        let p95_latency = match latencies.len() {
+            0 => Duration::ZERO,
+            n => latencies[((n as f64 * 0.95).ceil() as usize).saturating_sub(1)],
+        };
+
+        Self {
+            samples: samples.len(),
+            errors,
+            error_rate,
+            p95_latency,
+        }
+    }
+
+    /// SLO breaches in this window; empty when it passed
+    pub fn violations(&self, slo: &RolloutSlo) -> Vec<String> {
+        if self.samples == 0 {
+            return vec!["no health probes completed".to_string()];
+        }
+
+        let mut violations = Vec::new();
+        if self.error_rate > slo.max_error_rate {
+            violations.push(format!(
+                "error rate {:.1}% exceeds {:.1}%",
+                self.error_rate * 100.0,
+                slo.max_error_rate * 100.0
+            ));
+        }
+        if self.p95_latency > slo.max_p95_latency {
+            violations.push(format!(
+                "p95 latency {}ms exceeds {}ms",
+                self.p95_latency.as_millis(),
+                slo.max_p95_latency.as_millis()
+            ));
+        }
+        violations
+    }
+}
+
+/// How a Deployment's rollout ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolloutOutcome {
+    /// The new image now runs in the original Deployment
+    Promoted,
+    /// The new image was withdrawn; the original Deployment is untouched
+    RolledBack { phase: String, reason: String },
+}
+
+/// Canary replicas that take roughly `percent` of traffic next to `stable`
+/// unchanged stable replicas
+pub fn canary_replicas(stable: i32, percent: u32) -> i32 {
+    let percent = percent.clamp(1, 99) as f64;
+    let replicas = (stable.max(1) as f64 * percent / (100.0 - percent)).ceil();
+    replicas.max(1.0) as i32
+}
+
+/// `image` with its tag (and any digest) replaced by `tag`
+pub fn with_image_tag(image: &str, tag: &str) -> String {
+    let image = image.split('@').next().unwrap_or(image);
+    let name_start = image.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let repository = match image[name_start..].find(':') {
+        Some(colon) => &image[..name_start + colon],
+        None => image,
+    };
+    format!("{}:{}", repository, tag)
+}
+
+/// Drives rollouts of the hub's Deployments
+pub struct RolloutController {
+    client: K8sClient,
+    config: RolloutConfig,
+    http: reqwest::Client,
+}
+
+impl RolloutController {
+    pub fn new(client: K8sClient, config: RolloutConfig) -> Result<Self> {
+        if config.strategy == RolloutStrategy::Canary {
+            if config.canary_steps.is_empty() {
+                bail!("Canary rollouts need at least one traffic step");
+            }
+            if let Some(step) = config.canary_steps.iter().find(|s| !(1..100).contains(*s)) {
+                bail!("Canary step {}% must be between 1 and 99", step);
+            }
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self {
+            client,
+            config,
+            http,
+        })
+    }
+
+    /// Roll the configured image out to Deployment `name`
+    pub async fn rollout(&self, name: &str) -> Result<RolloutOutcome> {
+        info!(
+            "Rolling out {}:{} ({})",
+            name,
+            self.config.image_tag,
+            self.config.strategy.as_str()
+        );
+
+        match self.config.strategy {
+            RolloutStrategy::Rolling => {
+                self.promote(name).await?;
+                Ok(RolloutOutcome::Promoted)
+            }
+            RolloutStrategy::BlueGreen => self.blue_green(name).await,
+            RolloutStrategy::Canary => self.canary(name).await,
+        }
+    }
+
+    async fn canary(&self, name: &str) -> Result<RolloutOutcome> {
+        let stable = self.get(name).await?;
+        let stable_replicas = stable.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+        let canary = self
+            .stage(
+                &stable,
+                canary_replicas(stable_replicas, self.config.canary_steps[0]),
+            )
+            .await?;
+
+        for &percent in &self.config.canary_steps {
+            let phase = format!("canary {}%", percent);
+            let replicas = canary_replicas(stable_replicas, percent);
+            info!(
+                "{}: {} canary next to {} stable replicas",
+                phase, replicas, stable_replicas
+            );
+
+            self.client
+                .scale_workload(WorkloadKind::Deployment, &canary, replicas)
+                .await?;
+            if let Some(reason) = self.check_phase(&canary).await? {
+                return self.roll_back(name, &canary, phase, reason).await;
+            }
+        }
+
+        self.promote(name).await?;
+        self.remove(&canary).await?;
+        Ok(RolloutOutcome::Promoted)
+    }
+
+    async fn blue_green(&self, name: &str) -> Result<RolloutOutcome> {
+        let blue = self.get(name).await?;
+        let replicas = blue.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+        let green = self.stage(&blue, replicas).await?;
+
+        if let Err(e) = self.wait_rolled_out(&green).await {
+            let reason = format!("{:#}", e);
+            return self
+                .roll_back(name, &green, "green".to_string(), reason)
+                .await;
+        }
+
+        self.route_to(name, Some(self.config.strategy.track()))
+            .await?;
+        if let Some(reason) = self.observe().await? {
+            self.route_to(name, None).await?;
+            return self
+                .roll_back(name, &green, "green".to_string(), reason)
+                .await;
+        }
+
+        // Keep serving from green until the original pods run the new image
+        self.promote(name).await?;
+        self.route_to(name, None).await?;
+        self.remove(&green).await?;
+        Ok(RolloutOutcome::Promoted)
+    }
+
+    /// Wait for the staged pods and watch the SLOs; `Some(reason)` on failure
+    async fn check_phase(&self, staged: &str) -> Result<Option<String>> {
+        if let Err(e) = self.wait_rolled_out(staged).await {
+            return Ok(Some(format!("{:#}", e)));
+        }
+        self.observe().await
+    }
+
+    /// Probe the health API for one analysis window
+    async fn observe(&self) -> Result<Option<String>> {
+        let deadline = Instant::now() + self.config.analysis_window;
+        let mut samples = Vec::new();
+
+        while Instant::now() < deadline {
+            let start = Instant::now();
+            let ok = match self.http.get(&self.config.health_url).send().await {
+                Ok(response) => response.status().is_success(),
+                Err(e) => {
+                    debug!("Health probe failed: {}", e);
+                    false
+                }
+            };
+            samples.push(ProbeSample {
+                latency: start.elapsed(),
+                ok,
+            });
+            tokio::time::sleep(self.config.probe_interval).await;
+        }
+
+        let report = SloReport::from_samples(&samples);
+        info!(
+            "Analysis: {} probes, {:.1}% errors, p95 {}ms",
+            report.samples,
+            report.error_rate * 100.0,
+            report.p95_latency.as_millis()
+        );
+
+        let violations = report.violations(&self.config.slo);
+        Ok(if violations.is_empty() {
+            None
+        } else {
+            Some(violations.join("; "))
+        })
+    }
+
+    async fn roll_back(
+        &self,
+        name: &str,
+        staged: &str,
+        phase: String,
+        reason: String,
+    ) -> Result<RolloutOutcome> {
+        warn!("Rolling back {} during {}: {}", name, phase, reason);
+        self.remove(staged).await?;
+        Ok(RolloutOutcome::RolledBack { phase, reason })
+    }
+
+    fn deployments(&self) -> Api<Deployment> {
+        Api::namespaced(self.client.client().clone(), self.client.namespace())
+    }
+
+    async fn get(&self, name: &str) -> Result<Deployment> {
+        Ok(self
+            .deployments()
+            .get(name)
+            .await
+            .map_err(|e| K8sError::from_kube("Deployment", name, e))?)
+    }
+
+    /// Create (or reset) the temporary Deployment running the new image
+    async fn stage(&self, original: &Deployment, replicas: i32) -> Result<String> {
+        let original_name = original.metadata.name.clone().unwrap_or_default();
+        let track = self.config.strategy.track();
+        let name = format!("{}-{}", original_name, track);
+
+        let mut spec = original
+            .spec
+            .clone()
+            .with_context(|| format!("Deployment {} has no spec", original_name))?;
+        spec.replicas = Some(replicas);
+        spec.selector
+            .match_labels
+            .get_or_insert_with(Default::default)
+            .insert(TRACK_LABEL.to_string(), track.to_string());
+        spec.template
+            .metadata
+            .get_or_insert_with(Default::default)
+            .labels
+            .get_or_insert_with(Default::default)
+            .insert(TRACK_LABEL.to_string(), track.to_string());
+        if let Some(pod_spec) = spec.template.spec.as_mut() {
+            set_image_tags(pod_spec, &self.config.image_tag);
+        }
+
+        let staged = Deployment {
+            metadata: ObjectMeta {
+                name: Some(name.clone()),
+                namespace: Some(self.client.namespace().to_string()),
+                labels: original.metadata.labels.clone(),
+                ..Default::default()
+            },
+            spec: Some(spec),
+            status: None,
+        };
+
+        // Server-side apply also resets a Deployment left behind by an aborted rollout
+        self.deployments()
+            .patch(
+                &name,
+                &PatchParams::apply(FIELD_MANAGER).force(),
+                &Patch::Apply(&staged),
+            )
+            .await
+            .map_err(|e| K8sError::from_kube("Deployment", &name, e))?;
+        info!("Staged {} with {} replicas", name, replicas);
+        Ok(name)
+    }
+
+    /// Move the original Deployment to the new image and wait for it
+    async fn promote(&self, name: &str) -> Result<()> {
+        let deployment = self.get(name).await?;
+        let containers: Vec<_> = deployment
+            .spec
+            .and_then(|s| s.template.spec)
+            .map(|s| s.containers)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|c| {
+                let image = with_image_tag(c.image.as_deref()?, &self.config.image_tag);
+                Some(json!({ "name": c.name, "image": image }))
+            })
+            .collect();
+
+        let patch = json!({ "spec": { "template": { "spec": { "containers": containers } } } });
+        self.deployments()
+            .patch(name, &PatchParams::default(), &Patch::Strategic(&patch))
+            .await
+            .map_err(|e| K8sError::from_kube("Deployment", name, e))?;
+
+        info!("Promoting {} to {}", name, self.config.image_tag);
+        self.wait_rolled_out(name).await
+    }
+
+    /// Point Service `name` at one track's pods, or at all of them with `None`
+    async fn route_to(&self, name: &str, track: Option<&str>) -> Result<()> {
+        let services: Api<Service> =
+            Api::namespaced(self.client.client().clone(), self.client.namespace());
+        // A null value removes the label from the selector
+        let patch = json!({ "spec": { "selector": { TRACK_LABEL: track } } });
+        services
+            .patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await
+            .map_err(|e| K8sError::from_kube("Service", name, e))?;
+        debug!("Service {} now selects track {:?}", name, track);
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str) -> Result<()> {
+        match self
+            .deployments()
+            .delete(name, &DeleteParams::default())
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => match K8sError::from_kube("Deployment", name, e) {
+                K8sError::NotFound { .. } => Ok(()),
+                err => Err(err.into()),
+            },
+        }
+    }
+
+    /// Wait until every replica of Deployment `name` runs its current spec
+    async fn wait_rolled_out(&self, name: &str) -> Result<()> {
+        let deadline = Instant::now() + Duration::from_secs(self.config.ready_timeout_secs);
+
+        loop {
+            let deployment = self.get(name).await?;
+            if is_rolled_out(&deployment) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "{} did not become ready within {}s",
+                    name,
+                    self.config.ready_timeout_secs
+                );
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+}
+
+fn set_image_tags(pod_spec: &mut PodSpec, tag: &str) {
+    for container in &mut pod_spec.containers {
+        if let Some(image) = &container.image {
+            container.image = Some(with_image_tag(image, tag));
+        }
+    }
+}
+
+/// Whether the controller has caught up with the spec and every replica is
+/// updated and ready
+fn is_rolled_out(deployment: &Deployment) -> bool {
+    let desired = deployment
+        .spec
+        .as_ref()
+        .and_then(|s| s.replicas)
+        .unwrap_or(1);
+    let Some(status) = &deployment.status else {
+        return false;
+    };
+
+    status.observed_generation >= deployment.metadata.generation
+        && status.updated_replicas.unwrap_or(0) == desired
+        && status.ready_replicas.unwrap_or(0) == desired
+        && status.replicas.unwrap_or(0) == desired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_image_tag() {
+        assert_eq!(
+            with_image_tag("ghcr.io/llm-analytics/api:0.1.0", "0.2.0"),
+            "ghcr.io/llm-analytics/api:0.2.0"
+        );
+        assert_eq!(
+            with_image_tag("registry:5000/hub/api", "0.2.0"),
+            "registry:5000/hub/api:0.2.0"
+        );
+        assert_eq!(
+            with_image_tag("ghcr.io/llm-analytics/api@sha256:abc", "0.2.0"),
+            "ghcr.io/llm-analytics/api:0.2.0"
+        );
+    }
+
+    #[test]
+    fn test_canary_replicas() {
+        assert_eq!(canary_replicas(9, 10), 1);
+        assert_eq!(canary_replicas(4, 50), 4);
+        assert_eq!(canary_replicas(3, 25), 1);
+        assert_eq!(canary_replicas(0, 10), 1);
+    }
+
+    #[test]
+    fn test_slo_report() {
+        let fast = ProbeSample {
+            latency: Duration::from_millis(20),
+            ok: true,
+        };
+        let mut samples = vec![fast; 19];
+        samples.push(ProbeSample {
+            latency: Duration::from_secs(2),
+            ok: false,
+        });
+
+        let report = SloReport::from_samples(&samples);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.p95_latency, Duration::from_millis(20));
+
+        let violations = report.violations(&RolloutSlo::default());
+        assert_eq!(violations, vec!["error rate 5.0% exceeds 1.0%".to_string()]);
+
+        assert!(SloReport::from_samples(&[fast])
+            .violations(&RolloutSlo::default())
+            .is_empty());
+        assert_eq!(
+            SloReport::from_samples(&[])
+                .violations(&RolloutSlo::default())
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_strategy_from_str() {
+        assert_eq!(
+            "blue-green".parse::<RolloutStrategy>().unwrap(),
+            RolloutStrategy::BlueGreen
+        );
+        assert_eq!(
+            "Canary".parse::<RolloutStrategy>().unwrap(),
+            RolloutStrategy::Canary
+        );
+        assert!("big-bang".parse::<RolloutStrategy>().is_err());
+    }
+}
@@ -1,11 +1,210 @@
 //! Kafka ACL management
+//!
+//! ACLs are applied with `kafka-acls.sh`, either inside a Kafka pod through
+//! [`AclManager`] or locally by `kafka-admin`. A declarative [`AclSpec`] lists
+//! the desired bindings and [`AclDrift`] compares it with the live listing.
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
 use tracing::{debug, info};
 
-use super::types::{AclConfig, AclOperation, AclResourceType};
+use super::types::{get_standard_acls, AclConfig, AclOperation, AclPermission, AclResourceType};
 use crate::infra::k8s::K8sClient;
 
+/// Declarative ACL spec, loaded from YAML (or JSON)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AclSpec {
+    pub acls: Vec<AclConfig>,
+}
+
+impl AclSpec {
+    /// Spec with the standard LLM Analytics bindings
+    pub fn standard() -> Self {
+        Self {
+            acls: get_standard_acls(),
+        }
+    }
+
+    /// Load a spec file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read ACL spec {}", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid ACL spec {}", path.display()))
+    }
+
+    /// Principals the spec manages
+    pub fn principals(&self) -> BTreeSet<&str> {
+        self.acls.iter().map(|acl| acl.principal.as_str()).collect()
+    }
+}
+
+/// Differences between the desired ACLs and those live on the cluster
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AclDrift {
+    /// Desired bindings missing from the cluster
+    pub missing: Vec<AclConfig>,
+    /// Live bindings for managed principals that the spec does not contain
+    pub unexpected: Vec<AclConfig>,
+}
+
+impl AclDrift {
+    /// Compare desired and live ACLs; principals outside the spec are ignored
+    pub fn detect(desired: &AclSpec, live: &[AclConfig]) -> Self {
+        let desired_set: BTreeSet<&AclConfig> = desired.acls.iter().collect();
+        let live_set: BTreeSet<&AclConfig> = live.iter().collect();
+        let principals = desired.principals();
+
+        Self {
+            missing: desired_set
+                .difference(&live_set)
+                .map(|acl| (*acl).clone())
+                .collect(),
+            unexpected: live_set
+                .difference(&desired_set)
+                .filter(|acl| principals.contains(acl.principal.as_str()))
+                .map(|acl| (*acl).clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// `kafka-acls.sh` arguments adding a binding
+///
+/// Resource names ending in `*` (other than the bare wildcard) are treated as
+/// prefixed patterns.
+pub fn acl_add_args(acl: &AclConfig, bootstrap_servers: &str) -> Vec<String> {
+    let mut args = vec![
+        "--bootstrap-server".to_string(),
+        bootstrap_servers.to_string(),
+        "--add".to_string(),
+        match acl.permission {
+            AclPermission::Allow => "--allow-principal".to_string(),
+            AclPermission::Deny => "--deny-principal".to_string(),
+        },
+        format!("User:{}", acl.principal),
+        "--operation".to_string(),
+        operation_name(&acl.operation).to_string(),
+    ];
+
+    let (name, prefixed) = match acl.resource_name.strip_suffix('*') {
+        Some(prefix) if !prefix.is_empty() => (prefix, true),
+        _ => (acl.resource_name.as_str(), false),
+    };
+    match acl.resource_type {
+        AclResourceType::Topic => args.extend(["--topic".to_string(), name.to_string()]),
+        AclResourceType::Group => args.extend(["--group".to_string(), name.to_string()]),
+        AclResourceType::Cluster => args.push("--cluster".to_string()),
+    }
+    if prefixed && !matches!(acl.resource_type, AclResourceType::Cluster) {
+        args.extend(["--resource-pattern-type".to_string(), "prefixed".to_string()]);
+    }
+
+    args.push("--force".to_string());
+    args
+}
+
+/// Parse the output of `kafka-acls.sh --list` into bindings
+///
+/// Listings look like:
+///
+/// ```text
+/// Current ACLs for resource `ResourcePattern(resourceType=TOPIC, name=llm-events, patternType=LITERAL)`:
+///     (principal=User:llm-sentinel, host=*, operation=WRITE, permissionType=ALLOW)
+/// ```
+pub fn parse_acl_listing(output: &str) -> Vec<AclConfig> {
+    let mut acls = Vec::new();
+    let mut resource: Option<(AclResourceType, String)> = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(pattern) = line.strip_prefix("Current ACLs for resource `ResourcePattern(") {
+            let fields = parse_fields(pattern.trim_end_matches(|c| c == ':' || c == '`' || c == ')'));
+            resource = parse_resource(&fields);
+        } else if let (Some(binding), Some((resource_type, name))) =
+            (line.strip_prefix('('), resource.as_ref())
+        {
+            let fields = parse_fields(binding.trim_end_matches(')'));
+            let principal = field(&fields, "principal");
+            let operation = field(&fields, "operation").and_then(parse_operation);
+            let permission = match field(&fields, "permissionType") {
+                Some("DENY") => AclPermission::Deny,
+                _ => AclPermission::Allow,
+            };
+            if let (Some(principal), Some(operation)) = (principal, operation) {
+                acls.push(AclConfig {
+                    principal: principal.strip_prefix("User:").unwrap_or(principal).to_string(),
+                    resource_type: resource_type.clone(),
+                    resource_name: name.clone(),
+                    operation,
+                    permission,
+                });
+            }
+        }
+    }
+
+    acls
+}
+
+fn parse_fields(s: &str) -> Vec<(&str, &str)> {
+    s.split(", ")
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect()
+}
+
+fn field<'a>(fields: &[(&'a str, &'a str)], key: &str) -> Option<&'a str> {
+    fields.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn parse_resource(fields: &[(&str, &str)]) -> Option<(AclResourceType, String)> {
+    let resource_type = match field(fields, "resourceType")? {
+        "TOPIC" => AclResourceType::Topic,
+        "GROUP" => AclResourceType::Group,
+        "CLUSTER" => AclResourceType::Cluster,
+        _ => return None,
+    };
+    let name = field(fields, "name")?;
+    let name = match (resource_type.clone(), field(fields, "patternType")) {
+        // The cluster resource is always named kafka-cluster
+        (AclResourceType::Cluster, _) => "kafka-cluster".to_string(),
+        (_, Some("PREFIXED")) => format!("{}*", name),
+        _ => name.to_string(),
+    };
+    Some((resource_type, name))
+}
+
+fn parse_operation(s: &str) -> Option<AclOperation> {
+    match s {
+        "READ" => Some(AclOperation::Read),
+        "WRITE" => Some(AclOperation::Write),
+        "DESCRIBE" => Some(AclOperation::Describe),
+        "CREATE" => Some(AclOperation::Create),
+        "DELETE" => Some(AclOperation::Delete),
+        "ALL" => Some(AclOperation::All),
+        _ => None,
+    }
+}
+
+/// `kafka-acls.sh` name of an operation
+pub fn operation_name(op: &AclOperation) -> &'static str {
+    match op {
+        AclOperation::Read => "READ",
+        AclOperation::Write => "WRITE",
+        AclOperation::Describe => "DESCRIBE",
+        AclOperation::Create => "CREATE",
+        AclOperation::Delete => "DELETE",
+        AclOperation::All => "ALL",
+    }
+}
+
 /// ACL manager for Kafka
 pub struct AclManager {
     k8s_client: K8sClient,
@@ -65,20 +264,9 @@ impl AclManager {
         // Find a Kafka pod to execute the command
         let kafka_pod = self.get_kafka_pod().await?;
 
-        let resource_flag = match acl.resource_type {
-            AclResourceType::Topic => format!("--topic {}", acl.resource_name),
-            AclResourceType::Group => format!("--group {}", acl.resource_name),
-            AclResourceType::Cluster => format!("--cluster"),
-        };
-
-        let operation = self.operation_str(&acl.operation);
-
         let command = format!(
-            "kafka-acls.sh --bootstrap-server {} --add --allow-principal User:{} --operation {} {} --force",
-            self.bootstrap_servers,
-            acl.principal,
-            operation,
-            resource_flag
+            "kafka-acls.sh {}",
+            acl_add_args(acl, &self.bootstrap_servers).join(" ")
         );
 
         self.k8s_client
@@ -109,6 +297,12 @@ impl AclManager {
         Ok(output)
     }
 
+    /// Compare the live ACLs with a desired spec
+    pub async fn detect_drift(&self, spec: &AclSpec) -> Result<AclDrift> {
+        let listing = self.list_acls().await?;
+        Ok(AclDrift::detect(spec, &parse_acl_listing(&listing)))
+    }
+
     /// Get a running Kafka pod
     async fn get_kafka_pod(&self) -> Result<String> {
         let pods = self.k8s_client.list_pods().await?;
@@ -139,13 +333,58 @@ impl AclManager {
 
     /// Convert operation to string
     fn operation_str(&self, op: &AclOperation) -> &str {
-        match op {
-            AclOperation::Read => "READ",
-            AclOperation::Write => "WRITE",
-            AclOperation::Describe => "DESCRIBE",
-            AclOperation::Create => "CREATE",
-            AclOperation::Delete => "DELETE",
-            AclOperation::All => "ALL",
-        }
+        operation_name(op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "\
+Current ACLs for resource `ResourcePattern(resourceType=TOPIC, name=llm-events, patternType=LITERAL)`:
+ \t(principal=User:llm-sentinel, host=*, operation=WRITE, permissionType=ALLOW)
+ \t(principal=User:llm-sentinel, host=*, operation=DELETE, permissionType=ALLOW)
+
+Current ACLs for resource `ResourcePattern(resourceType=TOPIC, name=llm-, patternType=PREFIXED)`:
+ \t(principal=User:llm-analytics-consumer, host=*, operation=DESCRIBE, permissionType=ALLOW)
+ \t(principal=User:other-team, host=*, operation=READ, permissionType=ALLOW)
+";
+
+    #[test]
+    fn test_parse_acl_listing() {
+        let acls = parse_acl_listing(LISTING);
+        assert_eq!(acls.len(), 4);
+        assert_eq!(
+            acls[0],
+            AclConfig::new("llm-sentinel", AclResourceType::Topic, "llm-events", AclOperation::Write)
+        );
+        assert_eq!(acls[2].resource_name, "llm-*");
+    }
+
+    #[test]
+    fn test_drift_ignores_unmanaged_principals() {
+        let spec = AclSpec {
+            acls: vec![
+                AclConfig::new("llm-sentinel", AclResourceType::Topic, "llm-events", AclOperation::Write),
+                AclConfig::new("llm-sentinel", AclResourceType::Topic, "llm-events", AclOperation::Describe),
+                AclConfig::new("llm-analytics-consumer", AclResourceType::Topic, "llm-*", AclOperation::Describe),
+            ],
+        };
+
+        let drift = AclDrift::detect(&spec, &parse_acl_listing(LISTING));
+        assert_eq!(drift.missing.len(), 1);
+        assert_eq!(drift.missing[0].operation, AclOperation::Describe);
+        assert_eq!(drift.unexpected.len(), 1);
+        assert_eq!(drift.unexpected[0].operation, AclOperation::Delete);
+        assert!(!drift.is_clean());
+    }
+
+    #[test]
+    fn test_prefixed_pattern_args() {
+        let acl = AclConfig::new("llm-analytics-producer", AclResourceType::Topic, "llm-*", AclOperation::Describe);
+        let args = acl_add_args(&acl, "kafka:9092");
+        assert!(args.windows(2).any(|w| w == ["--topic", "llm-"]));
+        assert!(args.windows(2).any(|w| w == ["--resource-pattern-type", "prefixed"]));
     }
 }
@@ -12,6 +12,6 @@ pub mod verification;
 pub mod types;
 
 pub use topics::TopicManager;
-pub use acls::AclManager;
+pub use acls::{AclDrift, AclManager, AclSpec};
 pub use verification::ClusterVerifier;
 pub use types::*;
@@ -169,7 +169,7 @@ pub fn get_llm_topic_configs() -> Vec<TopicConfig> {
 }
 
 /// ACL resource type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AclResourceType {
     /// Topic resource
     Topic,
@@ -180,7 +180,7 @@ pub enum AclResourceType {
 }
 
 /// ACL operation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AclOperation {
     /// Read operation
     Read,
@@ -197,16 +197,17 @@ pub enum AclOperation {
 }
 
 /// ACL permission
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AclPermission {
     /// Allow permission
+    #[default]
     Allow,
     /// Deny permission
     Deny,
 }
 
 /// ACL configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct AclConfig {
     /// Principal (User:username)
     pub principal: String,
@@ -221,6 +222,7 @@ pub struct AclConfig {
     pub operation: AclOperation,
 
     /// Permission
+    #[serde(default)]
     pub permission: AclPermission,
 }
 
@@ -260,6 +262,18 @@ pub fn get_standard_acls() -> Vec<AclConfig> {
     acls.push(AclConfig::new(producer, AclResourceType::Topic, "llm-*", AclOperation::Describe));
     acls.push(AclConfig::new(producer, AclResourceType::Cluster, "kafka-cluster", AclOperation::Create));
 
+    // Derived anomalies, rollups, and correlations published back by the hub
+    acls.push(AclConfig::new(producer, AclResourceType::Topic, "llm-aggregated-metrics", AclOperation::Write));
+
+    // Ecosystem services push their events into the hub's ingestion topic
+    let ecosystem_producers = vec![
+        "llm-observatory", "llm-sentinel", "llm-cost-ops", "llm-governance-dashboard",
+    ];
+    for service in ecosystem_producers {
+        acls.push(AclConfig::new(service, AclResourceType::Topic, "llm-events", AclOperation::Write));
+        acls.push(AclConfig::new(service, AclResourceType::Topic, "llm-events", AclOperation::Describe));
+    }
+
     // Consumer ACLs
     let consumer = "llm-analytics-consumer";
     let consumer_topics = vec![
@@ -52,6 +52,7 @@ pub mod models {
     //! Data models for metrics, time-series, correlation, and API responses
 
     pub mod metrics;
+    pub mod histogram;
     pub mod timeseries;
     pub mod correlation;
     pub mod api;
@@ -60,7 +61,26 @@ pub mod models {
 pub mod database;
 pub mod pipeline;
 pub mod analytics;
+pub mod alerting;
+pub mod archival;
+pub mod audit;
+pub mod auth;
 pub mod resilience;
+pub mod export;
+pub mod federation;
+pub mod flags;
+pub mod grpc;
+pub mod health;
+pub mod metering;
+pub mod otlp;
+pub mod replay;
+pub mod reporting;
+pub mod retention;
+pub mod slo;
+#[cfg(feature = "synthetic")]
+pub mod synthetic;
+pub mod tenancy;
+pub mod telemetry;
 
 // CLI and infrastructure modules
 pub mod cli;
@@ -94,7 +114,8 @@ pub use models::correlation::{
 };
 
 pub use models::api::{
-    ApiError, ApiResponse, PaginatedResponse, PaginationMetadata, QueryResult, StreamEvent,
+    ApiError, ApiResponse, CursorPage, CursorParams, PageCursor, PaginatedResponse,
+    PaginationMetadata, QueryResult, StreamEvent,
 };
 
 // Re-export ecosystem adapters
@@ -0,0 +1,499 @@
+//! Hub Usage Metering
+//!
+//! Tracks API calls, query compute time, storage footprint, and alert deliveries
+//! per tenant and API key, rolled up into monthly usage records for chargeback.
+//!
+//! Records are kept in memory and persisted by `UsageFlushJob`, which also
+//! samples each tenant's storage footprint. Closed periods are drained from
+//! memory once stored, and the current period is reloaded on startup.
+
+use crate::database::Database;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Request header carrying the tenant identifier
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Consumer of the hub, identified by tenant and optionally API key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Consumer {
+    pub tenant_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<String>,
+}
+
+impl Consumer {
+    /// Consumer identified only by tenant
+    pub fn tenant(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            api_key_id: None,
+        }
+    }
+
+    /// Consumer identified by tenant and API key
+    pub fn api_key(tenant_id: impl Into<String>, api_key_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            api_key_id: Some(api_key_id.into()),
+        }
+    }
+}
+
+/// Calendar month used as the metering period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UsagePeriod {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl UsagePeriod {
+    /// Period containing the given timestamp
+    pub fn containing(timestamp: DateTime<Utc>) -> Self {
+        Self {
+            year: timestamp.year(),
+            month: timestamp.month(),
+        }
+    }
+
+    /// Current period
+    pub fn current() -> Self {
+        Self::containing(Utc::now())
+    }
+
+    /// First instant of the period
+    pub fn start(&self) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(self.year, self.month, 1, 0, 0, 0)
+            .single()
+            .expect("valid month start")
+    }
+
+    /// First instant after the period
+    pub fn end(&self) -> DateTime<Utc> {
+        self.next().start()
+    }
+
+    /// Following period
+    pub fn next(&self) -> Self {
+        if self.month == 12 {
+            Self {
+                year: self.year + 1,
+                month: 1,
+            }
+        } else {
+            Self {
+                year: self.year,
+                month: self.month + 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for UsagePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+impl FromStr for UsagePeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, month) = s
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("Invalid usage period '{}', expected YYYY-MM", s))?;
+        let year: i32 = year.parse()?;
+        let month: u32 = month.parse()?;
+        if !(1..=12).contains(&month) {
+            anyhow::bail!("Invalid month in usage period '{}'", s);
+        }
+        Ok(Self { year, month })
+    }
+}
+
+/// Monthly usage record for one consumer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub consumer: Consumer,
+    pub period: UsagePeriod,
+    pub api_calls: u64,
+    pub api_calls_by_endpoint: HashMap<String, u64>,
+    pub queries: u64,
+    pub query_compute_ms: u64,
+    pub storage_bytes_latest: u64,
+    pub storage_bytes_peak: u64,
+    pub alert_deliveries: u64,
+    pub alert_deliveries_by_channel: HashMap<String, u64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UsageRecord {
+    fn empty(consumer: Consumer, period: UsagePeriod) -> Self {
+        Self {
+            consumer,
+            period,
+            api_calls: 0,
+            api_calls_by_endpoint: HashMap::new(),
+            queries: 0,
+            query_compute_ms: 0,
+            storage_bytes_latest: 0,
+            storage_bytes_peak: 0,
+            alert_deliveries: 0,
+            alert_deliveries_by_channel: HashMap::new(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// In-memory usage meter keyed by consumer and month
+#[derive(Clone, Default)]
+pub struct UsageMeter {
+    records: Arc<DashMap<(Consumer, UsagePeriod), UsageRecord>>,
+}
+
+impl UsageMeter {
+    /// Create a new usage meter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update<F>(&self, consumer: &Consumer, at: DateTime<Utc>, apply: F)
+    where
+        F: FnOnce(&mut UsageRecord),
+    {
+        let period = UsagePeriod::containing(at);
+        let mut record = self
+            .records
+            .entry((consumer.clone(), period))
+            .or_insert_with(|| UsageRecord::empty(consumer.clone(), period));
+        apply(&mut record);
+        record.updated_at = Utc::now();
+    }
+
+    /// Record an API call
+    pub fn record_api_call(&self, consumer: &Consumer, endpoint: &str) {
+        self.record_api_call_at(consumer, endpoint, Utc::now());
+    }
+
+    /// Record an API call at a specific time
+    pub fn record_api_call_at(&self, consumer: &Consumer, endpoint: &str, at: DateTime<Utc>) {
+        self.update(consumer, at, |r| {
+            r.api_calls += 1;
+            *r.api_calls_by_endpoint
+                .entry(endpoint.to_string())
+                .or_insert(0) += 1;
+        });
+    }
+
+    /// Record query compute time
+    pub fn record_query(&self, consumer: &Consumer, compute_ms: u64) {
+        self.record_query_at(consumer, compute_ms, Utc::now());
+    }
+
+    /// Record query compute time at a specific time
+    pub fn record_query_at(&self, consumer: &Consumer, compute_ms: u64, at: DateTime<Utc>) {
+        self.update(consumer, at, |r| {
+            r.queries += 1;
+            r.query_compute_ms += compute_ms;
+        });
+    }
+
+    /// Record a storage footprint sample
+    pub fn record_storage(&self, consumer: &Consumer, bytes: u64) {
+        self.record_storage_at(consumer, bytes, Utc::now());
+    }
+
+    /// Record a storage footprint sample at a specific time
+    pub fn record_storage_at(&self, consumer: &Consumer, bytes: u64, at: DateTime<Utc>) {
+        self.update(consumer, at, |r| {
+            r.storage_bytes_latest = bytes;
+            r.storage_bytes_peak = r.storage_bytes_peak.max(bytes);
+        });
+    }
+
+    /// Record an alert delivery
+    pub fn record_alert_delivery(&self, consumer: &Consumer, channel: &str) {
+        self.record_alert_delivery_at(consumer, channel, Utc::now());
+    }
+
+    /// Record an alert delivery at a specific time
+    pub fn record_alert_delivery_at(&self, consumer: &Consumer, channel: &str, at: DateTime<Utc>) {
+        self.update(consumer, at, |r| {
+            r.alert_deliveries += 1;
+            *r.alert_deliveries_by_channel
+                .entry(channel.to_string())
+                .or_insert(0) += 1;
+        });
+    }
+
+    /// Usage record for a consumer in a period
+    pub fn get_record(&self, consumer: &Consumer, period: UsagePeriod) -> Option<UsageRecord> {
+        self.records
+            .get(&(consumer.clone(), period))
+            .map(|r| r.value().clone())
+    }
+
+    /// All usage records for a period
+    pub fn records_for_period(&self, period: UsagePeriod) -> Vec<UsageRecord> {
+        let mut records: Vec<UsageRecord> = self
+            .records
+            .iter()
+            .filter(|entry| entry.key().1 == period)
+            .map(|entry| entry.value().clone())
+            .collect();
+        records.sort_by(|a, b| {
+            a.consumer
+                .tenant_id
+                .cmp(&b.consumer.tenant_id)
+                .then_with(|| a.consumer.api_key_id.cmp(&b.consumer.api_key_id))
+        });
+        records
+    }
+
+    /// Remove and return all records for periods before `period`
+    pub fn drain_before(&self, period: UsagePeriod) -> Vec<UsageRecord> {
+        let keys: Vec<_> = self
+            .records
+            .iter()
+            .filter(|entry| entry.key().1 < period)
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let drained: Vec<UsageRecord> = keys
+            .into_iter()
+            .filter_map(|key| self.records.remove(&key).map(|(_, record)| record))
+            .collect();
+
+        debug!(count = drained.len(), "Drained closed usage periods");
+        drained
+    }
+
+    /// Put records back, keeping any already held for the same consumer and period
+    pub fn restore(&self, records: Vec<UsageRecord>) {
+        for record in records {
+            self.records
+                .entry((record.consumer.clone(), record.period))
+                .or_insert(record);
+        }
+    }
+}
+
+/// Usage flush configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageFlushConfig {
+    /// Seconds between writes of usage records to the database
+    pub flush_interval_secs: u64,
+    /// Seconds between samples of each tenant's storage footprint
+    pub storage_interval_secs: u64,
+}
+
+impl Default for UsageFlushConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval_secs: 60,
+            storage_interval_secs: 3600,
+        }
+    }
+}
+
+impl UsageFlushConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            flush_interval_secs: std::env::var("USAGE_FLUSH_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.flush_interval_secs),
+            storage_interval_secs: std::env::var("USAGE_STORAGE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.storage_interval_secs),
+        }
+    }
+}
+
+/// Usage flush statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageFlushStats {
+    pub flushes: u64,
+    pub records_stored: u64,
+    pub storage_samples: u64,
+    pub failures: u64,
+}
+
+/// Persists metered usage and samples tenant storage in the background
+pub struct UsageFlushJob {
+    meter: UsageMeter,
+    database: Arc<Database>,
+    config: UsageFlushConfig,
+    flushes: AtomicU64,
+    records_stored: AtomicU64,
+    storage_samples: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl UsageFlushJob {
+    pub fn new(meter: UsageMeter, database: Arc<Database>, config: UsageFlushConfig) -> Self {
+        Self {
+            meter,
+            database,
+            config,
+            flushes: AtomicU64::new(0),
+            records_stored: AtomicU64::new(0),
+            storage_samples: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Load stored records of the period containing `now` into the meter
+    pub async fn load(&self, now: DateTime<Utc>) -> Result<usize> {
+        let records = self
+            .database
+            .query_usage_records(UsagePeriod::containing(now))
+            .await?;
+        let count = records.len();
+        self.meter.restore(records);
+        Ok(count)
+    }
+
+    /// Record each tenant's current storage footprint
+    pub async fn sample_storage(&self, now: DateTime<Utc>) -> Result<usize> {
+        let footprints = self.database.query_tenant_storage().await?;
+        for (tenant_id, bytes) in &footprints {
+            self.meter
+                .record_storage_at(&Consumer::tenant(tenant_id.as_str()), *bytes, now);
+        }
+        self.storage_samples.fetch_add(1, Ordering::Relaxed);
+        Ok(footprints.len())
+    }
+
+    /// Store the current period's records, then drain and store closed periods.
+    /// Closed records that fail to store are put back for the next flush.
+    pub async fn flush(&self, now: DateTime<Utc>) -> Result<usize> {
+        let period = UsagePeriod::containing(now);
+        let mut stored = 0;
+        for record in self.meter.records_for_period(period) {
+            self.database.store_usage_record(&record).await?;
+            stored += 1;
+        }
+
+        let closed = self.meter.drain_before(period);
+        for (i, record) in closed.iter().enumerate() {
+            if let Err(e) = self.database.store_usage_record(record).await {
+                self.meter.restore(closed[i..].to_vec());
+                return Err(e);
+            }
+            stored += 1;
+        }
+
+        self.flushes.fetch_add(1, Ordering::Relaxed);
+        self.records_stored
+            .fetch_add(stored as u64, Ordering::Relaxed);
+        debug!(records = stored, "Flushed usage records");
+        Ok(stored)
+    }
+
+    /// Flush and sample storage on their intervals until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut flush = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.flush_interval_secs.max(1),
+            ));
+            let mut storage = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.storage_interval_secs.max(1),
+            ));
+            loop {
+                tokio::select! {
+                    _ = flush.tick() => {
+                        if let Err(e) = self.flush(Utc::now()).await {
+                            self.failures.fetch_add(1, Ordering::Relaxed);
+                            warn!("Usage flush failed: {}", e);
+                        }
+                    }
+                    _ = storage.tick() => {
+                        match self.sample_storage(Utc::now()).await {
+                            Ok(tenants) => info!(tenants, "Sampled tenant storage"),
+                            Err(e) => {
+                                self.failures.fetch_add(1, Ordering::Relaxed);
+                                warn!("Tenant storage sample failed: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> UsageFlushStats {
+        UsageFlushStats {
+            flushes: self.flushes.load(Ordering::Relaxed),
+            records_stored: self.records_stored.load(Ordering::Relaxed),
+            storage_samples: self.storage_samples.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_period_boundaries() {
+        let period = UsagePeriod { year: 2024, month: 12 };
+        assert_eq!(period.to_string(), "2024-12");
+        assert_eq!(period.next(), UsagePeriod { year: 2025, month: 1 });
+        assert_eq!(period.end(), Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!("2024-12".parse::<UsagePeriod>().unwrap(), period);
+        assert!("2024-13".parse::<UsagePeriod>().is_err());
+    }
+
+    #[test]
+    fn test_meter_aggregates_per_consumer_and_month() {
+        let meter = UsageMeter::new();
+        let consumer = Consumer::api_key("team-a", "key-1");
+        let jan = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+        let feb = Utc.with_ymd_and_hms(2024, 2, 10, 0, 0, 0).unwrap();
+
+        meter.record_api_call_at(&consumer, "/api/v1/events", jan);
+        meter.record_api_call_at(&consumer, "/api/v1/events", jan);
+        meter.record_query_at(&consumer, 120, jan);
+        meter.record_storage_at(&consumer, 2048, jan);
+        meter.record_storage_at(&consumer, 1024, jan);
+        meter.record_alert_delivery_at(&consumer, "slack", jan);
+        meter.record_api_call_at(&consumer, "/api/v1/metrics", feb);
+
+        let record = meter
+            .get_record(&consumer, UsagePeriod::containing(jan))
+            .unwrap();
+        assert_eq!(record.api_calls, 2);
+        assert_eq!(record.api_calls_by_endpoint["/api/v1/events"], 2);
+        assert_eq!(record.query_compute_ms, 120);
+        assert_eq!(record.storage_bytes_latest, 1024);
+        assert_eq!(record.storage_bytes_peak, 2048);
+        assert_eq!(record.alert_deliveries_by_channel["slack"], 1);
+
+        assert_eq!(meter.records_for_period(UsagePeriod::containing(feb)).len(), 1);
+
+        let drained = meter.drain_before(UsagePeriod::containing(feb));
+        assert_eq!(drained.len(), 1);
+        assert!(meter.get_record(&consumer, UsagePeriod::containing(jan)).is_none());
+
+        // Restoring keeps records already held for the same consumer and period
+        meter.record_api_call_at(&consumer, "/api/v1/events", jan);
+        meter.restore(drained);
+        let record = meter
+            .get_record(&consumer, UsagePeriod::containing(jan))
+            .unwrap();
+        assert_eq!(record.api_calls, 1);
+    }
+}
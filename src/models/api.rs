@@ -143,6 +143,10 @@ pub struct PaginationMetadata {
     /// Links to related pages
     #[serde(skip_serializing_if = "Option::is_none")]
     pub links: Option<PaginationLinks>,
+
+    /// Opaque cursor for the next page (cursor pagination only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl PaginationMetadata {
@@ -156,6 +160,24 @@ impl PaginationMetadata {
             has_next: page < total_pages,
             has_previous: page > 1,
             links: None,
+            next_cursor: None,
+        }
+    }
+
+    /// Metadata for a cursor-paginated page
+    ///
+    /// Totals are not computed for cursor pages; counting a large hypertable
+    /// defeats the purpose of keyset pagination.
+    pub fn cursor(per_page: u32, has_previous: bool, next_cursor: Option<PageCursor>) -> Self {
+        Self {
+            page: 0,
+            per_page,
+            total_items: 0,
+            total_pages: 0,
+            has_next: next_cursor.is_some(),
+            has_previous,
+            links: None,
+            next_cursor: next_cursor.map(|c| c.encode()),
         }
     }
 
@@ -251,6 +273,107 @@ pub enum SortOrder {
     Desc,
 }
 
+/// Keyset position of the last row on a page
+///
+/// Rows are ordered by `(timestamp, id)` descending, so the next page starts
+/// strictly after this pair. Clients only ever see the encoded token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub timestamp: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl PageCursor {
+    pub fn new(timestamp: DateTime<Utc>, id: Uuid) -> Self {
+        Self { timestamp, id }
+    }
+
+    /// Encode as an opaque, URL-safe token
+    pub fn encode(&self) -> String {
+        format!(
+            "{:016x}{}",
+            self.timestamp.timestamp_micros() as u64,
+            self.id.simple()
+        )
+    }
+
+    /// Decode a token produced by [`PageCursor::encode`]
+    pub fn decode(token: &str) -> anyhow::Result<Self> {
+        if token.len() != 48 || !token.is_ascii() {
+            anyhow::bail!("Invalid page cursor");
+        }
+        let (micros, id) = token.split_at(16);
+        let micros = u64::from_str_radix(micros, 16)
+            .map_err(|_| anyhow::anyhow!("Invalid page cursor"))? as i64;
+        let timestamp = DateTime::<Utc>::from_timestamp_micros(micros)
+            .ok_or_else(|| anyhow::anyhow!("Invalid page cursor"))?;
+        let id = Uuid::parse_str(id).map_err(|_| anyhow::anyhow!("Invalid page cursor"))?;
+        Ok(Self { timestamp, id })
+    }
+}
+
+/// Cursor pagination parameters for requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorParams {
+    /// Token from the previous page's `next_cursor`; omit for the first page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+
+    /// Items per page
+    #[serde(default = "default_per_page")]
+    pub limit: u32,
+}
+
+impl Default for CursorParams {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            limit: default_per_page(),
+        }
+    }
+}
+
+impl CursorParams {
+    /// Decode the cursor, if any
+    pub fn page_cursor(&self) -> anyhow::Result<Option<PageCursor>> {
+        self.cursor.as_deref().map(PageCursor::decode).transpose()
+    }
+}
+
+/// One page of a keyset scan
+#[derive(Debug, Clone)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<PageCursor>,
+}
+
+impl<T> CursorPage<T> {
+    /// Build a page from up to `limit + 1` rows fetched in cursor order
+    ///
+    /// The extra row only signals that another page exists; it is dropped and the
+    /// last returned row becomes the cursor.
+    pub fn from_rows(mut rows: Vec<T>, limit: usize, key: impl Fn(&T) -> PageCursor) -> Self {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_more { rows.last().map(key) } else { None };
+        Self {
+            items: rows,
+            next_cursor,
+        }
+    }
+
+    /// Wrap the page in a paginated response
+    pub fn into_response(self, per_page: u32, has_previous: bool) -> PaginatedResponse<T> {
+        PaginatedResponse {
+            status: ResponseStatus::Success,
+            data: Some(self.items),
+            pagination: PaginationMetadata::cursor(per_page, has_previous, self.next_cursor),
+            error: None,
+            meta: ResponseMetadata::default(),
+        }
+    }
+}
+
 // ============================================================================
 // ERROR RESPONSES
 // ============================================================================
@@ -653,6 +776,46 @@ mod tests {
         assert!(pagination.has_previous);
     }
 
+    #[test]
+    fn test_page_cursor_round_trip() {
+        let cursor = PageCursor::new(
+            DateTime::parse_from_rfc3339("2025-03-01T12:30:45.123456Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            Uuid::new_v4(),
+        );
+        let token = cursor.encode();
+        assert_eq!(token.len(), 48);
+        assert_eq!(PageCursor::decode(&token).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_page_cursor_rejects_garbage() {
+        assert!(PageCursor::decode("").is_err());
+        assert!(PageCursor::decode("not-a-cursor").is_err());
+        assert!(PageCursor::decode(&"z".repeat(48)).is_err());
+    }
+
+    #[test]
+    fn test_cursor_page_from_rows() {
+        let base = Utc::now();
+        let rows: Vec<PageCursor> = (0..4)
+            .map(|i| PageCursor::new(base - chrono::Duration::seconds(i), Uuid::new_v4()))
+            .collect();
+
+        let page = CursorPage::from_rows(rows.clone(), 3, |c| *c);
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.next_cursor, Some(rows[2]));
+
+        let last = CursorPage::from_rows(rows[..2].to_vec(), 3, |c| *c);
+        assert!(last.next_cursor.is_none());
+
+        let response = page.into_response(3, false);
+        assert!(response.pagination.has_next);
+        let json = serde_json::to_value(&response.pagination).unwrap();
+        assert_eq!(json["next_cursor"], rows[2].encode());
+    }
+
     #[test]
     fn test_sse_message_format() {
         let msg = SseMessage::event("update".to_string(), "{\"status\":\"ok\"}".to_string())
@@ -0,0 +1,237 @@
+//! Histogram Models
+//!
+//! Fixed-bucket histograms stored alongside each aggregated window. Unlike
+//! the window's percentiles, histograms with the same bucket bounds can be
+//! added together, so percentiles over many windows or tag sets are computed
+//! from the merged buckets rather than approximated from per-window values,
+//! and the bucket counts feed heatmaps directly.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Most bucket bounds a layout may define
+pub const MAX_HISTOGRAM_BUCKETS: usize = 256;
+
+/// Bucket layout as configured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistogramLayout {
+    /// Explicit ascending upper bounds
+    Explicit { bounds: Vec<f64> },
+    /// `count` upper bounds starting at `start`, each `factor` times the last
+    Exponential {
+        start: f64,
+        factor: f64,
+        count: usize,
+    },
+}
+
+impl Default for HistogramLayout {
+    /// 1 to about 500k in powers of two, which suits millisecond latencies
+    /// and token counts alike
+    fn default() -> Self {
+        HistogramLayout::Exponential {
+            start: 1.0,
+            factor: 2.0,
+            count: 20,
+        }
+    }
+}
+
+impl HistogramLayout {
+    /// Upper bounds for this layout, validated to be finite and ascending
+    pub fn bounds(&self) -> Result<Vec<f64>> {
+        let bounds = match self {
+            HistogramLayout::Explicit { bounds } => bounds.clone(),
+            HistogramLayout::Exponential {
+                start,
+                factor,
+                count,
+            } => {
+                if *start <= 0.0 || *factor <= 1.0 {
+                    bail!(
+                        "Exponential histogram buckets need start > 0 and factor > 1, got start={} factor={}",
+                        start,
+                        factor
+                    );
+                }
+                // Counts past the limit are rejected below without building them all
+                (0..(*count).min(MAX_HISTOGRAM_BUCKETS + 1))
+                    .map(|i| start * factor.powi(i as i32))
+                    .collect()
+            }
+        };
+        if bounds.is_empty() || bounds.len() > MAX_HISTOGRAM_BUCKETS {
+            bail!(
+                "Histograms need between 1 and {} bucket bounds, got {}",
+                MAX_HISTOGRAM_BUCKETS,
+                bounds.len()
+            );
+        }
+        if bounds.iter().any(|b| !b.is_finite()) || bounds.windows(2).any(|w| w[0] >= w[1]) {
+            bail!("Histogram bucket bounds must be finite and strictly ascending");
+        }
+        Ok(bounds)
+    }
+}
+
+impl std::str::FromStr for HistogramLayout {
+    type Err = anyhow::Error;
+
+    /// `exp:<start>:<factor>:<count>` or a comma-separated list of upper bounds
+    fn from_str(s: &str) -> Result<Self> {
+        let buckets = match s.trim().strip_prefix("exp:") {
+            Some(spec) => {
+                let parts: Vec<&str> = spec.split(':').map(str::trim).collect();
+                let [start, factor, count] = parts.as_slice() else {
+                    bail!("Invalid exponential bucket spec '{}', expected exp:<start>:<factor>:<count>", s);
+                };
+                HistogramLayout::Exponential {
+                    start: start.parse().context("Invalid bucket start")?,
+                    factor: factor.parse().context("Invalid bucket factor")?,
+                    count: count.parse().context("Invalid bucket count")?,
+                }
+            }
+            None => HistogramLayout::Explicit {
+                bounds: s
+                    .split(',')
+                    .map(|b| {
+                        b.trim()
+                            .parse()
+                            .with_context(|| format!("Invalid bucket bound: {}", b))
+                    })
+                    .collect::<Result<_>>()?,
+            },
+        };
+        buckets.bounds()?;
+        Ok(buckets)
+    }
+}
+
+/// Counts of values per bucket.
+///
+/// `counts[i]` holds values in `(bounds[i - 1], bounds[i]]`; the final count
+/// holds values above the last bound.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub bounds: Vec<f64>,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Empty histogram over `bounds`
+    pub fn new(bounds: Vec<f64>) -> Self {
+        let counts = vec![0; bounds.len() + 1];
+        Self { bounds, counts }
+    }
+
+    /// Histogram of `values` over `bounds`
+    pub fn from_values(bounds: Vec<f64>, values: &[f64]) -> Self {
+        let mut histogram = Self::new(bounds);
+        for value in values {
+            histogram.record(*value);
+        }
+        histogram
+    }
+
+    pub fn record(&mut self, value: f64) {
+        let bucket = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[bucket] += 1;
+    }
+
+    /// Values recorded
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Add `other`'s counts into this histogram; the bounds must match
+    pub fn merge(&mut self, other: &Histogram) -> Result<()> {
+        if self.bounds != other.bounds || self.counts.len() != other.counts.len() {
+            bail!("Cannot merge histograms with different bucket bounds");
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        Ok(())
+    }
+
+    /// Sum of `histograms`, or `None` when there are none or their bounds differ
+    pub fn merged<'a>(histograms: impl IntoIterator<Item = &'a Histogram>) -> Option<Histogram> {
+        let mut histograms = histograms.into_iter();
+        let mut merged = histograms.next()?.clone();
+        for histogram in histograms {
+            merged.merge(histogram).ok()?;
+        }
+        Some(merged)
+    }
+
+    /// Estimated `q` quantile (0.0–1.0), interpolating linearly within the
+    /// bucket it falls in. Values in the overflow bucket report the last
+    /// bound; `None` when the histogram is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let rank = q.clamp(0.0, 1.0) * total as f64;
+        let mut cumulative = 0u64;
+        for (i, count) in self.counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let before = cumulative;
+            cumulative += count;
+            if (cumulative as f64) < rank {
+                continue;
+            }
+            let Some(upper) = self.bounds.get(i) else {
+                return self.bounds.last().copied();
+            };
+            // The first bucket starts at zero unless its bound is not positive
+            let lower = match i {
+                0 => upper.min(0.0),
+                _ => self.bounds[i - 1],
+            };
+            let fraction = (rank - before as f64) / *count as f64;
+            return Some(lower + (upper - lower) * fraction);
+        }
+        self.bounds.last().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_layouts_parse_and_validate() {
+        let exp: HistogramLayout = "exp:1:2:4".parse().unwrap();
+        assert_eq!(exp.bounds().unwrap(), vec![1.0, 2.0, 4.0, 8.0]);
+        let explicit: HistogramLayout = "5, 10,50".parse().unwrap();
+        assert_eq!(explicit.bounds().unwrap(), vec![5.0, 10.0, 50.0]);
+
+        assert!("10,5".parse::<HistogramLayout>().is_err());
+        assert!("exp:0:2:4".parse::<HistogramLayout>().is_err());
+        assert!("exp:1:2".parse::<HistogramLayout>().is_err());
+        assert_eq!(HistogramLayout::default().bounds().unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_merged_histograms_give_cross_window_quantiles() {
+        let bounds = vec![10.0, 20.0, 30.0, 40.0];
+        let fast = Histogram::from_values(bounds.clone(), &[5.0, 10.0, 15.0, 15.0]);
+        assert_eq!(fast.counts, vec![2, 2, 0, 0, 0]);
+        let slow = Histogram::from_values(bounds.clone(), &[35.0, 35.0, 35.0, 100.0]);
+
+        let merged = Histogram::merged([&fast, &slow]).unwrap();
+        assert_eq!(merged.total(), 8);
+        // Rank 4 of 8 closes the (10, 20] bucket
+        assert_eq!(merged.quantile(0.5), Some(20.0));
+        assert_eq!(merged.quantile(0.25), Some(10.0));
+        assert_eq!(merged.quantile(1.0), Some(40.0));
+
+        let other = Histogram::new(vec![1.0]);
+        assert!(Histogram::merged([&fast, &other]).is_none());
+        assert_eq!(other.quantile(0.5), None);
+    }
+}
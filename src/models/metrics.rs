@@ -2,6 +2,7 @@
 //!
 //! Time-window aggregations, statistical measures, and metric types for analytics.
 
+use super::histogram::Histogram;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -29,6 +30,18 @@ pub enum TimeWindow {
 }
 
 impl TimeWindow {
+    /// Every window, finest first
+    pub const ALL: [TimeWindow; 8] = [
+        TimeWindow::OneMinute,
+        TimeWindow::FiveMinutes,
+        TimeWindow::FifteenMinutes,
+        TimeWindow::OneHour,
+        TimeWindow::SixHours,
+        TimeWindow::OneDay,
+        TimeWindow::OneWeek,
+        TimeWindow::OneMonth,
+    ];
+
     /// Returns the duration in seconds
     pub fn to_seconds(&self) -> u64 {
         match self {
@@ -88,6 +101,10 @@ pub struct StatisticalMeasures {
 
     /// Sum of all values
     pub sum: f64,
+
+    /// Bucketed distribution, when histograms are enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Histogram>,
 }
 
 impl Default for StatisticalMeasures {
@@ -102,6 +119,38 @@ impl Default for StatisticalMeasures {
             stddev: None,
             count: 0,
             sum: 0.0,
+            histogram: None,
+        }
+    }
+}
+
+impl StatisticalMeasures {
+    /// Summarize a set of samples; empty input yields the default measures
+    pub fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let count = sorted.len();
+        let sum: f64 = sorted.iter().sum();
+        let avg = sum / count as f64;
+        let variance = sorted.iter().map(|v| (v - avg).powi(2)).sum::<f64>() / count as f64;
+        let percentile = |p: f64| sorted[((p / 100.0) * (count - 1) as f64) as usize];
+
+        Self {
+            avg,
+            min: sorted[0],
+            max: sorted[count - 1],
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            stddev: Some(variance.sqrt()),
+            count: count as u64,
+            sum,
+            histogram: None,
         }
     }
 }
@@ -404,6 +453,18 @@ mod tests {
         assert_eq!(TimeWindow::OneMinute.as_str(), "1m");
     }
 
+    #[test]
+    fn test_statistical_measures_from_values() {
+        let measures = StatisticalMeasures::from_values(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(measures.count, 4);
+        assert_eq!(measures.min, 1.0);
+        assert_eq!(measures.max, 4.0);
+        assert_eq!(measures.avg, 2.5);
+        assert_eq!(measures.p50, 2.0);
+
+        assert_eq!(StatisticalMeasures::from_values(&[]).count, 0);
+    }
+
     #[test]
     fn test_counter_metric_serialization() {
         let mut tags = HashMap::new();
@@ -451,6 +512,7 @@ mod tests {
                 stddev: Some(150.5),
                 count: 100,
                 sum: 45050.0,
+                histogram: None,
             },
             buckets,
             tags: HashMap::new(),
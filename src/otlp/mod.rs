@@ -0,0 +1,746 @@
+//! OTLP Ingestion Bridge
+//!
+//! Converts OpenTelemetry traces and metrics received over OTLP/HTTP (JSON
+//! encoding) into `TelemetryPayload` events, so services already instrumented
+//! with OpenTelemetry can feed the hub without a custom emitter.
+//!
+//! Spans become latency events, plus a token usage event when the span carries
+//! GenAI usage attributes. Spans are only converted when they name a model or
+//! are server spans, so internal spans don't flood the pipeline. Metrics are
+//! mapped by name: token usage, operation duration, and request counts; other
+//! metrics are skipped.
+
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, EventPayload, EventType, LatencyMetrics, Severity,
+    SourceModule, TelemetryPayload, ThroughputMetrics, TokenUsageMetrics, SCHEMA_VERSION,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Tag marking events that arrived through the OTLP bridge
+pub const OTLP_SOURCE_TAG: &str = "ingest_source";
+
+/// Span attributes naming the model, in order of preference
+const MODEL_ATTRIBUTES: &[&str] = &[
+    "gen_ai.response.model",
+    "gen_ai.request.model",
+    "llm.response.model",
+    "llm.request.model",
+    "llm.model",
+];
+
+const INPUT_TOKEN_ATTRIBUTES: &[&str] = &[
+    "gen_ai.usage.input_tokens",
+    "gen_ai.usage.prompt_tokens",
+    "llm.usage.prompt_tokens",
+];
+
+const OUTPUT_TOKEN_ATTRIBUTES: &[&str] = &[
+    "gen_ai.usage.output_tokens",
+    "gen_ai.usage.completion_tokens",
+    "llm.usage.completion_tokens",
+];
+
+/// Metrics carrying token counts, split by the `gen_ai.token.type` attribute
+const TOKEN_USAGE_METRICS: &[&str] = &["gen_ai.client.token.usage", "llm.usage.tokens"];
+
+/// Histograms of request duration in seconds
+const DURATION_METRICS: &[&str] = &[
+    "gen_ai.client.operation.duration",
+    "gen_ai.server.request.duration",
+    "llm.request.duration",
+];
+
+/// Cumulative request counters
+const REQUEST_COUNT_METRICS: &[&str] = &["gen_ai.client.requests", "llm.requests"];
+
+/// OTLP span kind for server spans
+const SPAN_KIND_SERVER: i32 = 2;
+
+/// OTLP status code for failed spans
+const STATUS_CODE_ERROR: i32 = 2;
+
+// ========== OTLP JSON Types ==========
+
+/// Integers are sent as JSON strings by the OTLP JSON encoding
+fn int_from_string_or_number<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Int {
+        Number(i64),
+        String(String),
+    }
+
+    match Option::<Int>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Int::Number(n)) => Ok(Some(n)),
+        Some(Int::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnyValue {
+    pub string_value: Option<String>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub int_value: Option<i64>,
+    pub double_value: Option<f64>,
+    pub bool_value: Option<bool>,
+}
+
+impl AnyValue {
+    fn as_f64(&self) -> Option<f64> {
+        self.double_value
+            .or(self.int_value.map(|v| v as f64))
+            .or_else(|| self.string_value.as_deref()?.parse().ok())
+    }
+
+    fn as_string(&self) -> Option<String> {
+        if let Some(s) = &self.string_value {
+            return Some(s.clone());
+        }
+        self.int_value
+            .map(|v| v.to_string())
+            .or(self.double_value.map(|v| v.to_string()))
+            .or(self.bool_value.map(|v| v.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyValue {
+    pub key: String,
+    #[serde(default)]
+    pub value: AnyValue,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Resource {
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTraceServiceRequest {
+    #[serde(default)]
+    pub resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSpans {
+    #[serde(default)]
+    pub resource: Resource,
+    #[serde(default)]
+    pub scope_spans: Vec<ScopeSpans>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScopeSpans {
+    #[serde(default)]
+    pub spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    #[serde(default)]
+    pub trace_id: String,
+    #[serde(default)]
+    pub span_id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub kind: i32,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub start_time_unix_nano: Option<i64>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub end_time_unix_nano: Option<i64>,
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+    #[serde(default)]
+    pub status: SpanStatus,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SpanStatus {
+    #[serde(default)]
+    pub code: i32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportMetricsServiceRequest {
+    #[serde(default)]
+    pub resource_metrics: Vec<ResourceMetrics>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceMetrics {
+    #[serde(default)]
+    pub resource: Resource,
+    #[serde(default)]
+    pub scope_metrics: Vec<ScopeMetrics>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScopeMetrics {
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Metric {
+    #[serde(default)]
+    pub name: String,
+    pub gauge: Option<NumberPoints>,
+    pub sum: Option<NumberPoints>,
+    pub histogram: Option<HistogramPoints>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberPoints {
+    #[serde(default)]
+    pub data_points: Vec<NumberDataPoint>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberDataPoint {
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub start_time_unix_nano: Option<i64>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub time_unix_nano: Option<i64>,
+    pub as_double: Option<f64>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub as_int: Option<i64>,
+}
+
+impl NumberDataPoint {
+    fn value(&self) -> Option<f64> {
+        self.as_double.or(self.as_int.map(|v| v as f64))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramPoints {
+    #[serde(default)]
+    pub data_points: Vec<HistogramDataPoint>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramDataPoint {
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub time_unix_nano: Option<i64>,
+    #[serde(default, deserialize_with = "int_from_string_or_number")]
+    pub count: Option<i64>,
+    pub sum: Option<f64>,
+}
+
+/// OTLP/HTTP export response body
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_success: Option<PartialSuccess>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialSuccess {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_spans: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_data_points: Option<u64>,
+    pub error_message: String,
+}
+
+// ========== Conversion ==========
+
+/// Events produced from one export request
+#[derive(Debug, Clone, Default)]
+pub struct Conversion {
+    pub events: Vec<AnalyticsEvent>,
+    /// Spans or data points that had no telemetry mapping
+    pub skipped: u64,
+}
+
+fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a AnyValue> {
+    attributes
+        .iter()
+        .find(|kv| kv.key == key)
+        .map(|kv| &kv.value)
+}
+
+fn first_string(attributes: &[KeyValue], keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| attribute(attributes, key)?.as_string())
+}
+
+fn first_number(attributes: &[KeyValue], keys: &[&str]) -> Option<f64> {
+    keys.iter()
+        .find_map(|key| attribute(attributes, key)?.as_f64())
+}
+
+fn timestamp_from_nanos(nanos: Option<i64>) -> DateTime<Utc> {
+    nanos
+        .filter(|n| *n > 0)
+        .map(|n| Utc.timestamp_nanos(n))
+        .unwrap_or_else(Utc::now)
+}
+
+/// Converts OTLP payloads into analytics events
+#[derive(Debug, Clone)]
+pub struct OtlpConverter {
+    /// Environment used when the resource has no `deployment.environment`
+    default_environment: String,
+}
+
+impl Default for OtlpConverter {
+    fn default() -> Self {
+        Self::new(crate::database::environment::default_environment())
+    }
+}
+
+impl OtlpConverter {
+    pub fn new(default_environment: impl Into<String>) -> Self {
+        Self {
+            default_environment: default_environment.into(),
+        }
+    }
+
+    fn event(
+        &self,
+        resource: &Resource,
+        timestamp: DateTime<Utc>,
+        severity: Severity,
+        correlation_id: Option<Uuid>,
+        payload: TelemetryPayload,
+    ) -> AnalyticsEvent {
+        let environment = attribute(&resource.attributes, "deployment.environment")
+            .and_then(AnyValue::as_string)
+            .unwrap_or_else(|| self.default_environment.clone());
+
+        let mut tags = HashMap::new();
+        tags.insert(OTLP_SOURCE_TAG.to_string(), "otlp".to_string());
+        if let Some(service) =
+            attribute(&resource.attributes, "service.name").and_then(AnyValue::as_string)
+        {
+            tags.insert("service".to_string(), service);
+        }
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity,
+                environment,
+                tags,
+            },
+            payload: EventPayload::Telemetry(payload),
+        }
+    }
+
+    /// Model or service a span or data point reports on
+    fn model_id(&self, resource: &Resource, attributes: &[KeyValue]) -> Option<String> {
+        first_string(attributes, MODEL_ATTRIBUTES)
+            .or_else(|| first_string(&resource.attributes, &["service.name"]))
+    }
+
+    /// Convert exported spans
+    pub fn convert_traces(&self, request: &ExportTraceServiceRequest) -> Conversion {
+        let mut conversion = Conversion::default();
+
+        for resource_spans in &request.resource_spans {
+            let resource = &resource_spans.resource;
+            for span in resource_spans.scope_spans.iter().flat_map(|s| &s.spans) {
+                let names_model = first_string(&span.attributes, MODEL_ATTRIBUTES).is_some();
+                let model_id = match self.model_id(resource, &span.attributes) {
+                    Some(model_id) if names_model || span.kind == SPAN_KIND_SERVER => model_id,
+                    _ => {
+                        conversion.skipped += 1;
+                        continue;
+                    }
+                };
+                let events = self.span_events(resource, span, model_id);
+                if events.is_empty() {
+                    conversion.skipped += 1;
+                }
+                conversion.events.extend(events);
+            }
+        }
+
+        conversion
+    }
+
+    /// Latency and token usage events for a span; empty without timestamps
+    fn span_events(
+        &self,
+        resource: &Resource,
+        span: &Span,
+        model_id: String,
+    ) -> Vec<AnalyticsEvent> {
+        let (Some(start), Some(end)) = (span.start_time_unix_nano, span.end_time_unix_nano) else {
+            return Vec::new();
+        };
+        let latency_ms = (end - start).max(0) as f64 / 1_000_000.0;
+        let timestamp = timestamp_from_nanos(Some(end));
+        let severity = if span.status.code == STATUS_CODE_ERROR {
+            Severity::Error
+        } else {
+            Severity::Info
+        };
+        // 32 hex digit trace IDs parse directly as UUIDs
+        let correlation_id = Uuid::parse_str(&span.trace_id).ok();
+        let request_id = first_string(&span.attributes, &["gen_ai.response.id"])
+            .unwrap_or_else(|| span.span_id.clone());
+
+        let input_tokens = first_number(&span.attributes, INPUT_TOKEN_ATTRIBUTES);
+        let output_tokens = first_number(&span.attributes, OUTPUT_TOKEN_ATTRIBUTES);
+        let ttft_ms = first_number(&span.attributes, &["gen_ai.server.time_to_first_token"])
+            .map(|secs| secs * 1000.0);
+        let tokens_per_second = output_tokens
+            .filter(|_| latency_ms > 0.0)
+            .map(|tokens| tokens / (latency_ms / 1000.0));
+
+        let mut events = vec![self.event(
+            resource,
+            timestamp,
+            severity.clone(),
+            correlation_id,
+            TelemetryPayload::Latency(LatencyMetrics {
+                model_id: model_id.clone(),
+                request_id: request_id.clone(),
+                total_latency_ms: latency_ms,
+                ttft_ms,
+                tokens_per_second,
+                breakdown: None,
+            }),
+        )];
+
+        if input_tokens.is_some() || output_tokens.is_some() {
+            let prompt_tokens = input_tokens.unwrap_or(0.0) as u32;
+            let completion_tokens = output_tokens.unwrap_or(0.0) as u32;
+            events.push(self.event(
+                resource,
+                timestamp,
+                severity,
+                correlation_id,
+                TelemetryPayload::TokenUsage(TokenUsageMetrics {
+                    model_id,
+                    request_id,
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                }),
+            ));
+        }
+
+        events
+    }
+
+    /// Convert exported metrics
+    pub fn convert_metrics(&self, request: &ExportMetricsServiceRequest) -> Conversion {
+        let mut conversion = Conversion::default();
+
+        for resource_metrics in &request.resource_metrics {
+            let resource = &resource_metrics.resource;
+            for metric in resource_metrics
+                .scope_metrics
+                .iter()
+                .flat_map(|s| &s.metrics)
+            {
+                let name = metric.name.as_str();
+                if TOKEN_USAGE_METRICS.contains(&name) {
+                    self.token_usage_points(resource, metric, &mut conversion);
+                } else if DURATION_METRICS.contains(&name) {
+                    self.duration_points(resource, metric, &mut conversion);
+                } else if REQUEST_COUNT_METRICS.contains(&name) {
+                    self.request_count_points(resource, metric, &mut conversion);
+                } else {
+                    conversion.skipped += 1;
+                }
+            }
+        }
+
+        conversion
+    }
+
+    fn token_usage_points(
+        &self,
+        resource: &Resource,
+        metric: &Metric,
+        conversion: &mut Conversion,
+    ) {
+        let points = metric
+            .histogram
+            .iter()
+            .flat_map(|h| &h.data_points)
+            .map(|p| (&p.attributes, p.time_unix_nano, p.sum));
+        let points = points.chain(
+            metric
+                .sum
+                .iter()
+                .chain(metric.gauge.iter())
+                .flat_map(|n| &n.data_points)
+                .map(|p| (&p.attributes, p.time_unix_nano, p.value())),
+        );
+
+        for (attributes, time, value) in points {
+            let (Some(tokens), Some(model_id)) = (value, self.model_id(resource, attributes))
+            else {
+                conversion.skipped += 1;
+                continue;
+            };
+            let tokens = tokens.max(0.0) as u32;
+            let (prompt_tokens, completion_tokens) =
+                match first_string(attributes, &["gen_ai.token.type"]).as_deref() {
+                    Some("input") | Some("prompt") => (tokens, 0),
+                    _ => (0, tokens),
+                };
+            conversion.events.push(self.event(
+                resource,
+                timestamp_from_nanos(time),
+                Severity::Info,
+                None,
+                TelemetryPayload::TokenUsage(TokenUsageMetrics {
+                    model_id,
+                    request_id: format!("otlp:{}", metric.name),
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: tokens,
+                }),
+            ));
+        }
+    }
+
+    fn duration_points(&self, resource: &Resource, metric: &Metric, conversion: &mut Conversion) {
+        for point in metric.histogram.iter().flat_map(|h| &h.data_points) {
+            let count = point.count.unwrap_or(0);
+            let (Some(sum), Some(model_id)) =
+                (point.sum, self.model_id(resource, &point.attributes))
+            else {
+                conversion.skipped += 1;
+                continue;
+            };
+            if count <= 0 {
+                conversion.skipped += 1;
+                continue;
+            }
+            conversion.events.push(self.event(
+                resource,
+                timestamp_from_nanos(point.time_unix_nano),
+                Severity::Info,
+                None,
+                TelemetryPayload::Latency(LatencyMetrics {
+                    model_id,
+                    request_id: format!("otlp:{}", metric.name),
+                    // Mean duration across the exported requests
+                    total_latency_ms: sum / count as f64 * 1000.0,
+                    ttft_ms: None,
+                    tokens_per_second: None,
+                    breakdown: None,
+                }),
+            ));
+        }
+    }
+
+    fn request_count_points(
+        &self,
+        resource: &Resource,
+        metric: &Metric,
+        conversion: &mut Conversion,
+    ) {
+        for point in metric.sum.iter().flat_map(|s| &s.data_points) {
+            let window_secs = match (point.start_time_unix_nano, point.time_unix_nano) {
+                (Some(start), Some(end)) if end > start => (end - start) as f64 / 1e9,
+                _ => 0.0,
+            };
+            let (Some(requests), Some(model_id)) =
+                (point.value(), self.model_id(resource, &point.attributes))
+            else {
+                conversion.skipped += 1;
+                continue;
+            };
+            if window_secs <= 0.0 {
+                conversion.skipped += 1;
+                continue;
+            }
+            conversion.events.push(self.event(
+                resource,
+                timestamp_from_nanos(point.time_unix_nano),
+                Severity::Info,
+                None,
+                TelemetryPayload::Throughput(ThroughputMetrics {
+                    model_id,
+                    requests_per_second: requests / window_secs,
+                    tokens_per_second: 0.0,
+                    concurrent_requests: 0,
+                    window_duration_seconds: window_secs.round() as u32,
+                }),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn traces() -> ExportTraceServiceRequest {
+        serde_json::from_value(json!({
+            "resourceSpans": [{
+                "resource": { "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "chat-api" } },
+                    { "key": "deployment.environment", "value": { "stringValue": "staging" } }
+                ]},
+                "scopeSpans": [{ "spans": [
+                    {
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "name": "chat gpt-4",
+                        "kind": 3,
+                        "startTimeUnixNano": "1700000000000000000",
+                        "endTimeUnixNano": "1700000001500000000",
+                        "attributes": [
+                            { "key": "gen_ai.request.model", "value": { "stringValue": "gpt-4" } },
+                            { "key": "gen_ai.usage.input_tokens", "value": { "intValue": "120" } },
+                            { "key": "gen_ai.usage.output_tokens", "value": { "intValue": 30 } }
+                        ],
+                        "status": { "code": 2 }
+                    },
+                    {
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "aaa19b7ec3c1b174",
+                        "name": "db.query",
+                        "kind": 1,
+                        "startTimeUnixNano": "1700000000000000000",
+                        "endTimeUnixNano": "1700000000100000000"
+                    }
+                ]}]
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_span_conversion() {
+        let conversion = OtlpConverter::new("production").convert_traces(&traces());
+        assert_eq!(conversion.skipped, 1);
+        assert_eq!(conversion.events.len(), 2);
+
+        let latency = &conversion.events[0];
+        assert_eq!(latency.common.environment, "staging");
+        assert_eq!(latency.common.severity, Severity::Error);
+        assert_eq!(
+            latency.common.correlation_id,
+            Some(Uuid::parse_str("5b8efff798038103d269b633813fc60c").unwrap())
+        );
+        match &latency.payload {
+            EventPayload::Telemetry(TelemetryPayload::Latency(l)) => {
+                assert_eq!(l.model_id, "gpt-4");
+                assert_eq!(l.total_latency_ms, 1500.0);
+                assert_eq!(l.tokens_per_second, Some(20.0));
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        match &conversion.events[1].payload {
+            EventPayload::Telemetry(TelemetryPayload::TokenUsage(t)) => {
+                assert_eq!(
+                    (t.prompt_tokens, t.completion_tokens, t.total_tokens),
+                    (120, 30, 150)
+                );
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_metric_conversion() {
+        let request: ExportMetricsServiceRequest = serde_json::from_value(json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "chat-api" } }
+                ]},
+                "scopeMetrics": [{ "metrics": [
+                    {
+                        "name": "gen_ai.client.token.usage",
+                        "histogram": { "dataPoints": [{
+                            "attributes": [
+                                { "key": "gen_ai.request.model", "value": { "stringValue": "gpt-4" } },
+                                { "key": "gen_ai.token.type", "value": { "stringValue": "input" } }
+                            ],
+                            "timeUnixNano": "1700000060000000000",
+                            "count": "10",
+                            "sum": 2000.0
+                        }]}
+                    },
+                    {
+                        "name": "gen_ai.client.operation.duration",
+                        "histogram": { "dataPoints": [{
+                            "timeUnixNano": "1700000060000000000",
+                            "count": "4",
+                            "sum": 2.0
+                        }]}
+                    },
+                    {
+                        "name": "llm.requests",
+                        "sum": { "dataPoints": [{
+                            "startTimeUnixNano": "1700000000000000000",
+                            "timeUnixNano": "1700000060000000000",
+                            "asInt": "120"
+                        }]}
+                    },
+                    { "name": "process.cpu.time", "sum": { "dataPoints": [] } }
+                ]}]
+            }]
+        }))
+        .unwrap();
+
+        let conversion = OtlpConverter::new("production").convert_metrics(&request);
+        assert_eq!(conversion.skipped, 1);
+        assert_eq!(conversion.events.len(), 3);
+
+        let payloads: Vec<_> = conversion.events.iter().map(|e| &e.payload).collect();
+        match payloads[0] {
+            EventPayload::Telemetry(TelemetryPayload::TokenUsage(t)) => {
+                assert_eq!((t.prompt_tokens, t.completion_tokens), (2000, 0));
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+        match payloads[1] {
+            EventPayload::Telemetry(TelemetryPayload::Latency(l)) => {
+                assert_eq!(l.model_id, "chat-api");
+                assert_eq!(l.total_latency_ms, 500.0);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+        match payloads[2] {
+            EventPayload::Telemetry(TelemetryPayload::Throughput(t)) => {
+                assert_eq!(t.requests_per_second, 2.0);
+                assert_eq!(t.window_duration_seconds, 60);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! High-performance distributed caching with Redis Cluster for metrics and query results.
 
+use crate::export::prometheus::HubMetrics;
 use crate::schemas::events::AnalyticsEvent;
 use anyhow::{Context, Result};
 use redis::aio::ConnectionManager;
@@ -59,6 +60,7 @@ impl CacheManager {
     pub async fn get_metric(&mut self, key: &str) -> Result<Option<f64>> {
         let conn = self.get_connection().await?;
         let value: Option<f64> = conn.get(key).await?;
+        HubMetrics::global().record_cache_lookup("metrics", value.is_some());
         Ok(value)
     }
 
@@ -90,6 +92,7 @@ impl CacheManager {
         let conn = self.get_connection().await?;
 
         let data: Option<String> = conn.get(&key).await?;
+        HubMetrics::global().record_cache_lookup("aggregates", data.is_some());
         match data {
             Some(json) => Ok(Some(serde_json::from_str(&json)?)),
             None => Ok(None),
@@ -0,0 +1,510 @@
+//! Cardinality Guard
+//!
+//! One badly chosen tag, such as a request id, turns every sample of a metric
+//! into a new series. `CardinalityGuard` tracks the distinct tag sets seen per
+//! metric ahead of aggregation, and once a metric exceeds `max_cardinality` it
+//! applies the configured strategy: drop the tag with the most distinct values,
+//! drop every unprotected tag, or reject samples of new series. Each time the
+//! guard intervenes it raises a `cardinality.exceeded` alert naming the metric
+//! and the culprit tag.
+
+use crate::database::environment::default_environment;
+use crate::export::prometheus::HubMetrics;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION, TENANT_TAG,
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Custom payload type of the alerts raised by the guard
+pub const CARDINALITY_ALERT_TYPE: &str = "cardinality.exceeded";
+
+/// What the guard does to a metric over its series limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardinalityStrategy {
+    /// Drop the unprotected tag with the most distinct values
+    #[default]
+    DropTopTag,
+    /// Drop every unprotected tag of the metric
+    DropAllTags,
+    /// Keep aggregating known series and drop samples of new ones
+    RejectNewSeries,
+}
+
+impl FromStr for CardinalityStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown cardinality strategy '{}'", s))
+    }
+}
+
+/// Cardinality guard configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardinalityConfig {
+    /// Distinct series allowed per metric
+    pub max_cardinality: u64,
+    pub strategy: CardinalityStrategy,
+    /// Tags never dropped; new series differing only in these are rejected instead
+    pub protected_tags: Vec<String>,
+}
+
+impl Default for CardinalityConfig {
+    fn default() -> Self {
+        Self {
+            max_cardinality: 10_000,
+            strategy: CardinalityStrategy::default(),
+            protected_tags: vec![TENANT_TAG.to_string()],
+        }
+    }
+}
+
+impl CardinalityConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            max_cardinality: std::env::var("MAX_CARDINALITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_cardinality),
+            strategy: match std::env::var("CARDINALITY_STRATEGY") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.strategy,
+            },
+            protected_tags: std::env::var("CARDINALITY_PROTECTED_TAGS")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or(defaults.protected_tags),
+        })
+    }
+
+    fn is_protected(&self, tag: &str) -> bool {
+        self.protected_tags.iter().any(|t| t == tag)
+    }
+}
+
+/// What the guard did when a metric crossed its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardinalityAction {
+    DroppedTag,
+    DroppedAllTags,
+    RejectedNewSeries,
+}
+
+/// Payload of a `cardinality.exceeded` alert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardinalityAlert {
+    pub metric_name: String,
+    pub action: CardinalityAction,
+    /// Tag with the most distinct values when the limit was hit
+    pub culprit_tag: Option<String>,
+    /// Distinct values seen for the culprit tag
+    pub distinct_values: usize,
+    pub series: usize,
+    pub max_cardinality: u64,
+    /// Every tag the guard now drops for the metric
+    pub dropped_tags: Vec<String>,
+}
+
+impl CardinalityAlert {
+    /// The alert as an analytics event, routable by the alert notifier
+    pub fn to_event(&self) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Warning,
+                environment: default_environment(),
+                tags: HashMap::from([("metric_name".to_string(), self.metric_name.clone())]),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: CARDINALITY_ALERT_TYPE.to_string(),
+                data: serde_json::to_value(self).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Series currently tracked for one metric
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricCardinality {
+    pub metric_name: String,
+    pub series: usize,
+    pub dropped_tags: Vec<String>,
+    pub drops_all_tags: bool,
+    /// New series are being rejected
+    pub limited: bool,
+}
+
+#[derive(Default)]
+struct SeriesState {
+    series: HashSet<u64>,
+    /// Hashes of the distinct values seen per tag
+    tag_values: HashMap<String, HashSet<u64>>,
+    dropped_tags: BTreeSet<String>,
+    drop_all: bool,
+    limited: bool,
+}
+
+impl SeriesState {
+    /// Tags remaining after the ones this metric drops
+    fn strip<'a>(
+        &self,
+        tags: &'a HashMap<String, String>,
+        config: &CardinalityConfig,
+    ) -> Cow<'a, HashMap<String, String>> {
+        let dropped = |key: &String| {
+            if self.drop_all {
+                !config.is_protected(key)
+            } else {
+                self.dropped_tags.contains(key)
+            }
+        };
+        if tags.keys().any(dropped) {
+            Cow::Owned(
+                tags.iter()
+                    .filter(|(key, _)| !dropped(key))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(tags)
+        }
+    }
+
+    fn observe(&mut self, series: u64, tags: &HashMap<String, String>) {
+        self.series.insert(series);
+        for (key, value) in tags {
+            self.tag_values
+                .entry(key.clone())
+                .or_default()
+                .insert(hash_of(value));
+        }
+    }
+
+    /// Unprotected tag with the most distinct values
+    fn culprit(&self, config: &CardinalityConfig) -> Option<(String, usize)> {
+        self.tag_values
+            .iter()
+            .filter(|(key, _)| !config.is_protected(key))
+            .map(|(key, values)| (key.clone(), values.len()))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+    }
+
+    /// Forget tracked series once the tag set changes; they are rebuilt from new samples
+    fn reset(&mut self) {
+        self.series.clear();
+        self.tag_values.clear();
+        self.limited = false;
+    }
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn series_hash(tags: &HashMap<String, String>) -> u64 {
+    let mut sorted: Vec<_> = tags.iter().collect();
+    sorted.sort();
+    hash_of(&sorted)
+}
+
+/// Per-metric series limit applied ahead of aggregation
+pub struct CardinalityGuard {
+    config: RwLock<CardinalityConfig>,
+    metrics: Mutex<HashMap<String, SeriesState>>,
+    alerts: Option<mpsc::Sender<AnalyticsEvent>>,
+    samples_rejected: AtomicU64,
+    tags_dropped: AtomicU64,
+    alerts_raised: AtomicU64,
+}
+
+impl CardinalityGuard {
+    pub fn new(config: CardinalityConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            metrics: Mutex::new(HashMap::new()),
+            alerts: None,
+            samples_rejected: AtomicU64::new(0),
+            tags_dropped: AtomicU64::new(0),
+            alerts_raised: AtomicU64::new(0),
+        }
+    }
+
+    /// Send raised alerts as `Alert` events to this channel
+    pub fn with_alert_sink(mut self, sink: mpsc::Sender<AnalyticsEvent>) -> Self {
+        self.alerts = Some(sink);
+        self
+    }
+
+    pub fn config(&self) -> CardinalityConfig {
+        self.config.read().clone()
+    }
+
+    /// Change the series limit. Metrics rejecting new series re-check against it.
+    pub fn set_max_cardinality(&self, max_cardinality: u64) {
+        let mut config = self.config.write();
+        if config.max_cardinality == max_cardinality {
+            return;
+        }
+        config.max_cardinality = max_cardinality;
+        for state in self.metrics.lock().values_mut() {
+            state.limited = false;
+        }
+    }
+
+    /// Tags a sample of `metric_name` is aggregated under, or `None` when the
+    /// sample is dropped
+    pub fn apply<'a>(
+        &self,
+        metric_name: &str,
+        tags: &'a HashMap<String, String>,
+    ) -> Option<Cow<'a, HashMap<String, String>>> {
+        let config = self.config.read();
+        let mut metrics = self.metrics.lock();
+        if !metrics.contains_key(metric_name) {
+            metrics.insert(metric_name.to_string(), SeriesState::default());
+        }
+        let state = metrics.get_mut(metric_name)?;
+
+        let stripped = state.strip(tags, &config);
+        let series = series_hash(&stripped);
+        if state.series.contains(&series) {
+            return Some(stripped);
+        }
+        if (state.series.len() as u64) < config.max_cardinality {
+            state.observe(series, &stripped);
+            return Some(stripped);
+        }
+
+        let culprit = state.culprit(&config);
+        let series_seen = state.series.len();
+        let action = match (config.strategy, &culprit) {
+            (CardinalityStrategy::DropTopTag, Some((tag, _))) => {
+                state.dropped_tags.insert(tag.clone());
+                CardinalityAction::DroppedTag
+            }
+            (CardinalityStrategy::DropAllTags, Some(_)) => {
+                state.drop_all = true;
+                CardinalityAction::DroppedAllTags
+            }
+            // Nothing left to drop
+            _ if state.limited => {
+                self.samples_rejected.fetch_add(1, Ordering::Relaxed);
+                HubMetrics::global().record_metric_dropped("cardinality_limit", 1);
+                return None;
+            }
+            _ => CardinalityAction::RejectedNewSeries,
+        };
+
+        let alert = CardinalityAlert {
+            metric_name: metric_name.to_string(),
+            action,
+            culprit_tag: culprit.as_ref().map(|(tag, _)| tag.clone()),
+            distinct_values: culprit.as_ref().map_or(0, |(_, distinct)| *distinct),
+            series: series_seen,
+            max_cardinality: config.max_cardinality,
+            dropped_tags: state.dropped_tags.iter().cloned().collect(),
+        };
+        let kept = if action == CardinalityAction::RejectedNewSeries {
+            state.limited = true;
+            self.samples_rejected.fetch_add(1, Ordering::Relaxed);
+            HubMetrics::global().record_metric_dropped("cardinality_limit", 1);
+            None
+        } else {
+            self.tags_dropped.fetch_add(1, Ordering::Relaxed);
+            state.reset();
+            let stripped = state.strip(tags, &config);
+            state.observe(series_hash(&stripped), &stripped);
+            Some(stripped)
+        };
+        drop(metrics);
+        drop(config);
+
+        self.raise(alert);
+        kept
+    }
+
+    fn raise(&self, alert: CardinalityAlert) {
+        warn!(
+            metric = %alert.metric_name,
+            action = ?alert.action,
+            culprit = ?alert.culprit_tag,
+            distinct_values = alert.distinct_values,
+            series = alert.series,
+            "Metric exceeded its series limit"
+        );
+        self.alerts_raised.fetch_add(1, Ordering::Relaxed);
+        if let Some(sink) = &self.alerts {
+            if let Err(e) = sink.try_send(alert.to_event()) {
+                warn!("Failed to queue cardinality alert: {}", e);
+            }
+        }
+    }
+
+    /// Metrics the guard has intervened on
+    pub fn limited_metrics(&self) -> Vec<MetricCardinality> {
+        let mut limited: Vec<MetricCardinality> = self
+            .metrics
+            .lock()
+            .iter()
+            .filter(|(_, state)| state.limited || state.drop_all || !state.dropped_tags.is_empty())
+            .map(|(metric_name, state)| MetricCardinality {
+                metric_name: metric_name.clone(),
+                series: state.series.len(),
+                dropped_tags: state.dropped_tags.iter().cloned().collect(),
+                drops_all_tags: state.drop_all,
+                limited: state.limited,
+            })
+            .collect();
+        limited.sort_by(|a, b| a.metric_name.cmp(&b.metric_name));
+        limited
+    }
+
+    pub fn get_stats(&self) -> CardinalityStats {
+        let metrics = self.metrics.lock();
+        CardinalityStats {
+            metrics_tracked: metrics.len(),
+            series_tracked: metrics.values().map(|state| state.series.len()).sum(),
+            samples_rejected: self.samples_rejected.load(Ordering::Relaxed),
+            tags_dropped: self.tags_dropped.load(Ordering::Relaxed),
+            alerts_raised: self.alerts_raised.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Cardinality guard statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardinalityStats {
+    pub metrics_tracked: usize,
+    pub series_tracked: usize,
+    pub samples_rejected: u64,
+    pub tags_dropped: u64,
+    pub alerts_raised: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn config(max_cardinality: u64, strategy: CardinalityStrategy) -> CardinalityConfig {
+        CardinalityConfig {
+            max_cardinality,
+            strategy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_drops_highest_cardinality_tag_and_alerts() {
+        let (sink, mut alerts) = mpsc::channel(8);
+        let guard =
+            CardinalityGuard::new(config(3, CardinalityStrategy::DropTopTag)).with_alert_sink(sink);
+
+        for i in 0..3 {
+            let sample = tags(&[("model", "gpt-4"), ("request_id", &i.to_string())]);
+            assert_eq!(guard.apply("latency_ms", &sample).unwrap().len(), 2);
+        }
+        let sample = tags(&[("model", "gpt-4"), ("request_id", "3")]);
+        let kept = guard.apply("latency_ms", &sample).unwrap();
+        assert_eq!(kept.into_owned(), tags(&[("model", "gpt-4")]));
+
+        // Later samples land in the reduced series
+        let sample = tags(&[("model", "claude"), ("request_id", "4")]);
+        assert_eq!(
+            guard.apply("latency_ms", &sample).unwrap().into_owned(),
+            tags(&[("model", "claude")])
+        );
+
+        let event = alerts.try_recv().unwrap();
+        assert_eq!(event.common.event_type, EventType::Alert);
+        let EventPayload::Custom(payload) = event.payload else {
+            panic!("expected a custom payload");
+        };
+        assert_eq!(payload.custom_type, CARDINALITY_ALERT_TYPE);
+        let alert: CardinalityAlert = serde_json::from_value(payload.data).unwrap();
+        assert_eq!(alert.action, CardinalityAction::DroppedTag);
+        assert_eq!(alert.culprit_tag.as_deref(), Some("request_id"));
+        assert_eq!(alert.distinct_values, 3);
+        assert!(alerts.try_recv().is_err());
+
+        let limited = guard.limited_metrics();
+        assert_eq!(limited[0].dropped_tags, vec!["request_id".to_string()]);
+        assert_eq!(guard.get_stats().series_tracked, 2);
+    }
+
+    #[test]
+    fn test_reject_new_series_keeps_known_series() {
+        let guard = CardinalityGuard::new(config(2, CardinalityStrategy::RejectNewSeries));
+        let a = tags(&[("model", "a")]);
+        let b = tags(&[("model", "b")]);
+        let c = tags(&[("model", "c")]);
+
+        assert!(guard.apply("tokens", &a).is_some());
+        assert!(guard.apply("tokens", &b).is_some());
+        assert!(guard.apply("tokens", &c).is_none());
+        assert!(guard.apply("tokens", &c).is_none());
+        assert!(guard.apply("tokens", &a).is_some());
+        // Other metrics have their own budget
+        assert!(guard.apply("cost_usd", &c).is_some());
+
+        let stats = guard.get_stats();
+        assert_eq!(stats.samples_rejected, 2);
+        assert_eq!(stats.alerts_raised, 1);
+
+        // Raising the limit admits new series again
+        guard.set_max_cardinality(3);
+        assert!(guard.apply("tokens", &c).is_some());
+        assert!(guard.limited_metrics().is_empty());
+    }
+
+    #[test]
+    fn test_protected_tags_are_never_dropped() {
+        let guard = CardinalityGuard::new(config(1, CardinalityStrategy::DropAllTags));
+        let first = tags(&[(TENANT_TAG, "acme"), ("user", "1")]);
+        let second = tags(&[(TENANT_TAG, "acme"), ("user", "2")]);
+
+        assert!(guard.apply("requests", &first).is_some());
+        assert_eq!(
+            guard.apply("requests", &second).unwrap().into_owned(),
+            tags(&[(TENANT_TAG, "acme")])
+        );
+        // Only the protected tenant tag differs, so the new series is rejected
+        let other_tenant = tags(&[(TENANT_TAG, "globex"), ("user", "3")]);
+        assert!(guard.apply("requests", &other_tenant).is_none());
+    }
+}
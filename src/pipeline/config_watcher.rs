@@ -0,0 +1,373 @@
+//! Configuration Watcher
+//!
+//! Polls Config-Manager for `AnalyticsParameters` and `RetentionSettings`, diffs
+//! each snapshot against the last one seen, and applies changes without a
+//! restart: analytics parameters are swapped into the shared configuration read
+//! by the aggregation, anomaly, and prediction engines, and sampling settings,
+//! metric filter rules, and the series limit are pushed to the ingestion sampler,
+//! metric filter, and cardinality guard. Every detected change is published as a
+//! `ConfigReload` lifecycle event listing the fields that changed.
+//!
+//! Retention settings are only reported here; `RetentionEnforcer` applies them
+//! on its own reconciliation schedule.
+
+use crate::adapters::config_manager::{
+    AnalyticsParameters, ConfigManagerAdapter, RetentionSettings,
+};
+use crate::analytics::SharedConfig;
+use crate::pipeline::cardinality::CardinalityGuard;
+use crate::pipeline::metric_filter::MetricFilter;
+use crate::pipeline::sampling::Sampler;
+use crate::pipeline::self_monitor::{LifecyclePhase, SelfMonitor};
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Fields that differ between every snapshot and carry no configuration
+const VOLATILE_FIELDS: &[&str] = &["config_id", "created_at", "version"];
+
+/// Configuration watcher settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigWatcherConfig {
+    /// Seconds between polls of Config-Manager
+    pub poll_interval_secs: u64,
+}
+
+impl Default for ConfigWatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 30,
+        }
+    }
+}
+
+impl ConfigWatcherConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            poll_interval_secs: std::env::var("CONFIG_WATCH_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.poll_interval_secs),
+        }
+    }
+}
+
+/// Configuration section a change belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    AnalyticsParameters,
+    RetentionSettings,
+}
+
+/// Single changed field, addressed by a dotted path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub section: ConfigSection,
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Changed leaves between two serialized configurations.
+///
+/// Objects are compared key by key; arrays and scalars are compared whole.
+pub fn diff_config(section: ConfigSection, old: &Value, new: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_value(section, "", old, new, &mut changes);
+    changes.retain(|c| !VOLATILE_FIELDS.contains(&c.path.as_str()));
+    changes
+}
+
+fn diff_value(
+    section: ConfigSection,
+    path: &str,
+    old: &Value,
+    new: &Value,
+    changes: &mut Vec<ConfigChange>,
+) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_value(
+                    section,
+                    &child,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        _ if old != new => changes.push(ConfigChange {
+            section,
+            path: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Watches Config-Manager and hot-reloads the active configuration
+pub struct ConfigWatcher {
+    config: ConfigWatcherConfig,
+    config_manager: Arc<ConfigManagerAdapter>,
+    self_monitor: Arc<SelfMonitor>,
+    analytics: Option<SharedConfig>,
+    sampler: Option<Arc<Sampler>>,
+    metric_filter: Option<Arc<MetricFilter>>,
+    cardinality: Option<Arc<CardinalityGuard>>,
+    last_parameters: Mutex<Option<Value>>,
+    last_retention: Mutex<Option<Value>>,
+    polls: AtomicU64,
+    reloads: AtomicU64,
+    poll_failures: AtomicU64,
+}
+
+impl ConfigWatcher {
+    pub fn new(
+        config: ConfigWatcherConfig,
+        config_manager: Arc<ConfigManagerAdapter>,
+        self_monitor: Arc<SelfMonitor>,
+    ) -> Self {
+        Self {
+            config,
+            config_manager,
+            self_monitor,
+            analytics: None,
+            sampler: None,
+            metric_filter: None,
+            cardinality: None,
+            last_parameters: Mutex::new(None),
+            last_retention: Mutex::new(None),
+            polls: AtomicU64::new(0),
+            reloads: AtomicU64::new(0),
+            poll_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Swap reloaded analytics parameters into the engines' shared configuration
+    pub fn with_analytics(mut self, config: SharedConfig) -> Self {
+        self.analytics = Some(config);
+        self
+    }
+
+    /// Push reloaded sampling settings to the ingestion sampler
+    pub fn with_sampler(mut self, sampler: Arc<Sampler>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Push reloaded allowlist/denylist rules to the metric filter
+    pub fn with_metric_filter(mut self, filter: Arc<MetricFilter>) -> Self {
+        self.metric_filter = Some(filter);
+        self
+    }
+
+    /// Push the reloaded series limit to the cardinality guard
+    pub fn with_cardinality_guard(mut self, guard: Arc<CardinalityGuard>) -> Self {
+        self.cardinality = Some(guard);
+        self
+    }
+
+    /// Fetch both configurations once and apply whatever changed.
+    ///
+    /// The first poll establishes the baseline and applies it without
+    /// reporting changes.
+    pub async fn poll_once(&self) -> Result<Vec<ConfigChange>> {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+
+        let (parameters, retention) = match futures::try_join!(
+            self.config_manager.fetch_analytics_parameters(),
+            self.config_manager.fetch_retention_settings(),
+        ) {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                self.poll_failures.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+
+        let mut changes = Vec::new();
+        if let Some(parameter_changes) = self.observe_parameters(&parameters)? {
+            self.apply_parameters(&parameters);
+            self.report(
+                ConfigSection::AnalyticsParameters,
+                &parameters.version,
+                &parameter_changes,
+            );
+            changes.extend(parameter_changes);
+        }
+        if let Some(retention_changes) = self.observe_retention(&retention)? {
+            self.report(
+                ConfigSection::RetentionSettings,
+                &retention.version,
+                &retention_changes,
+            );
+            changes.extend(retention_changes);
+        }
+
+        if !changes.is_empty() {
+            self.reloads.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(changes)
+    }
+
+    /// Poll on the configured interval
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.poll_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.poll_once().await {
+                    error!("Configuration poll failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Record a parameters snapshot; `None` when nothing changed since the last poll
+    fn observe_parameters(
+        &self,
+        parameters: &AnalyticsParameters,
+    ) -> Result<Option<Vec<ConfigChange>>> {
+        let snapshot = serde_json::to_value(parameters)?;
+        let mut last = self.last_parameters.lock();
+        let changes = match last.as_ref() {
+            // Apply the baseline so the engines start from Config-Manager values
+            None => Some(Vec::new()),
+            Some(previous) => {
+                let changes = diff_config(ConfigSection::AnalyticsParameters, previous, &snapshot);
+                (!changes.is_empty()).then_some(changes)
+            }
+        };
+        *last = Some(snapshot);
+        Ok(changes)
+    }
+
+    fn observe_retention(
+        &self,
+        retention: &RetentionSettings,
+    ) -> Result<Option<Vec<ConfigChange>>> {
+        let snapshot = serde_json::to_value(retention)?;
+        let mut last = self.last_retention.lock();
+        let changes = match last.as_ref() {
+            None => None,
+            Some(previous) => {
+                let changes = diff_config(ConfigSection::RetentionSettings, previous, &snapshot);
+                (!changes.is_empty()).then_some(changes)
+            }
+        };
+        *last = Some(snapshot);
+        Ok(changes)
+    }
+
+    fn apply_parameters(&self, parameters: &AnalyticsParameters) {
+        if let Some(analytics) = &self.analytics {
+            let updated = analytics.load().with_parameters(parameters);
+            analytics.store(updated);
+        }
+        if let Some(sampler) = &self.sampler {
+            sampler.update_config(parameters.sampling.clone());
+        }
+        if let Some(filter) = &self.metric_filter {
+            filter.update_config(parameters.metric_filters.clone());
+        }
+        if let Some(guard) = &self.cardinality {
+            guard.set_max_cardinality(parameters.aggregation.max_cardinality);
+        }
+    }
+
+    fn report(&self, section: ConfigSection, version: &str, changes: &[ConfigChange]) {
+        if changes.is_empty() {
+            return;
+        }
+        info!(
+            ?section,
+            version,
+            changed = changes.len(),
+            "Configuration reloaded"
+        );
+
+        let details = serde_json::json!({
+            "section": section,
+            "version": version,
+            "changes": changes,
+        });
+        if let Err(e) = self
+            .self_monitor
+            .report_lifecycle(LifecyclePhase::ConfigReload, details)
+        {
+            error!("Failed to report configuration reload: {}", e);
+        }
+    }
+
+    pub fn get_stats(&self) -> ConfigWatcherStats {
+        ConfigWatcherStats {
+            polls: self.polls.load(Ordering::Relaxed),
+            reloads: self.reloads.load(Ordering::Relaxed),
+            poll_failures: self.poll_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Configuration watcher statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigWatcherStats {
+    pub polls: u64,
+    pub reloads: u64,
+    pub poll_failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_reports_changed_leaves() {
+        let old = json!({
+            "config_id": "a",
+            "anomaly_detection": { "sensitivity": 3.0, "enabled": true },
+            "sampling": { "default_rate": 1.0 },
+        });
+        let new = json!({
+            "config_id": "b",
+            "anomaly_detection": { "sensitivity": 2.5, "enabled": true },
+            "sampling": { "default_rate": 1.0, "preserve_errors": true },
+        });
+
+        let changes = diff_config(ConfigSection::AnalyticsParameters, &old, &new);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["anomaly_detection.sensitivity", "sampling.preserve_errors"]
+        );
+        assert_eq!(changes[0].old, json!(3.0));
+        assert_eq!(changes[0].new, json!(2.5));
+        assert_eq!(changes[1].old, Value::Null);
+    }
+
+    #[test]
+    fn test_identical_configs_have_no_changes() {
+        let value = json!({ "policies": [{ "retention_days": 30 }], "version": "1" });
+        let mut other = value.clone();
+        other["version"] = json!("2");
+        assert!(diff_config(ConfigSection::RetentionSettings, &value, &other).is_empty());
+    }
+}
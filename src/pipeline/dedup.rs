@@ -0,0 +1,513 @@
+//! Ingestion Deduplication
+//!
+//! Upstream retries redeliver events that were already ingested. The dedup
+//! stage drops any event whose `event_id` was seen within a TTL, using a
+//! time-bounded seen-set held in process memory or, for deployments with
+//! several consumers, in Redis. Duplicates are counted per source module.
+//!
+//! Storage stays idempotent on `event_id` regardless; dropping duplicates here
+//! keeps them out of sampling, heavy-hitter tracking and the analytics engines.
+//! The stage fails open: if the seen-set is unavailable, events pass through.
+
+use crate::export::prometheus::HubMetrics;
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Where seen event IDs are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupBackend {
+    /// Per-process; duplicates delivered to another consumer are not caught
+    #[default]
+    Memory,
+    /// Shared by every consumer
+    Redis,
+}
+
+impl FromStr for DedupBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown dedup backend '{}'", s))
+    }
+}
+
+/// Dedup stage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupConfig {
+    pub backend: DedupBackend,
+    /// How long an event ID is remembered
+    pub ttl_secs: u64,
+    /// Most IDs the in-memory seen-set holds; the oldest are forgotten first
+    pub max_entries: usize,
+    pub redis_url: String,
+    /// Prefix for every key written to Redis
+    pub key_prefix: String,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            backend: DedupBackend::Memory,
+            ttl_secs: 3600,
+            max_entries: 1_000_000,
+            redis_url: "redis://localhost:6379".to_string(),
+            key_prefix: "hub".to_string(),
+        }
+    }
+}
+
+impl DedupConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            backend: match std::env::var("DEDUP_BACKEND") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.backend,
+            },
+            ttl_secs: std::env::var("DEDUP_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.ttl_secs),
+            max_entries: std::env::var("DEDUP_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_entries),
+            redis_url: std::env::var("REDIS_URL").unwrap_or(defaults.redis_url),
+            key_prefix: std::env::var("DEDUP_KEY_PREFIX").unwrap_or(defaults.key_prefix),
+        })
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::seconds(self.ttl_secs as i64)
+    }
+}
+
+/// Time-bounded set of seen event IDs
+#[async_trait]
+pub trait SeenSet: Send + Sync {
+    /// Record `ids` in order, returning for each whether it was unseen.
+    /// An ID repeated within `ids` is unseen only the first time.
+    async fn insert_new(&self, ids: &[Uuid]) -> Result<Vec<bool>>;
+
+    /// Forget `ids`, so redeliveries of events that were never stored pass
+    async fn forget(&self, ids: &[Uuid]) -> Result<()>;
+}
+
+#[derive(Default)]
+struct MemorySeenState {
+    expiry: HashMap<Uuid, DateTime<Utc>>,
+    /// Insertion order, for expiry and the size cap
+    order: VecDeque<(DateTime<Utc>, Uuid)>,
+}
+
+/// In-process seen-set
+pub struct MemorySeenSet {
+    ttl: Duration,
+    max_entries: usize,
+    state: Mutex<MemorySeenState>,
+}
+
+impl MemorySeenSet {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            state: Mutex::new(MemorySeenState::default()),
+        }
+    }
+
+    /// `insert_new` as of `now`
+    pub fn insert_new_at(&self, ids: &[Uuid], now: DateTime<Utc>) -> Vec<bool> {
+        let mut state = self.state.lock();
+
+        while let Some(&(expires_at, id)) = state.order.front() {
+            if expires_at > now && state.order.len() < self.max_entries {
+                break;
+            }
+            state.order.pop_front();
+            // The ID may have been re-inserted with a later expiry
+            if state.expiry.get(&id) == Some(&expires_at) {
+                state.expiry.remove(&id);
+            }
+        }
+
+        let expires_at = now + self.ttl;
+        ids.iter()
+            .map(|id| {
+                if matches!(state.expiry.get(id), Some(&seen_until) if seen_until > now) {
+                    return false;
+                }
+                state.expiry.insert(*id, expires_at);
+                state.order.push_back((expires_at, *id));
+                true
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().expiry.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl SeenSet for MemorySeenSet {
+    async fn insert_new(&self, ids: &[Uuid]) -> Result<Vec<bool>> {
+        Ok(self.insert_new_at(ids, Utc::now()))
+    }
+
+    async fn forget(&self, ids: &[Uuid]) -> Result<()> {
+        let mut state = self.state.lock();
+        for id in ids {
+            state.expiry.remove(id);
+        }
+        Ok(())
+    }
+}
+
+/// Seen-set shared through Redis, one `SET NX EX` key per event ID
+pub struct RedisSeenSet {
+    conn: ConnectionManager,
+    key_prefix: String,
+    ttl_secs: u64,
+}
+
+impl RedisSeenSet {
+    pub async fn connect(config: &DedupConfig) -> Result<Self> {
+        let client = redis::Client::open(config.redis_url.as_str())
+            .context("Failed to create Redis client")?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .context("Failed to connect dedup seen-set to Redis")?;
+
+        Ok(Self {
+            conn,
+            key_prefix: config.key_prefix.clone(),
+            ttl_secs: config.ttl_secs,
+        })
+    }
+
+    fn key(&self, id: &Uuid) -> String {
+        format!("{}:seen:{}", self.key_prefix, id)
+    }
+}
+
+#[async_trait]
+impl SeenSet for RedisSeenSet {
+    async fn insert_new(&self, ids: &[Uuid]) -> Result<Vec<bool>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for id in ids {
+            pipe.cmd("SET")
+                .arg(self.key(id))
+                .arg(1)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.ttl_secs);
+        }
+        let mut conn = self.conn.clone();
+        let replies: Vec<Option<String>> = pipe.query_async(&mut conn).await?;
+        Ok(replies.into_iter().map(|reply| reply.is_some()).collect())
+    }
+
+    async fn forget(&self, ids: &[Uuid]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let keys: Vec<String> = ids.iter().map(|id| self.key(id)).collect();
+        let mut conn = self.conn.clone();
+        redis::cmd("DEL")
+            .arg(keys)
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .context("Failed to forget seen event IDs")
+    }
+}
+
+/// Events checked and duplicates dropped for one source module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateCounts {
+    pub checked: u64,
+    pub duplicates: u64,
+}
+
+impl DuplicateCounts {
+    pub fn duplicate_rate(&self) -> f64 {
+        if self.checked == 0 {
+            0.0
+        } else {
+            self.duplicates as f64 / self.checked as f64
+        }
+    }
+}
+
+/// Dedup stage statistics
+#[derive(Debug, Clone)]
+pub struct DedupStats {
+    /// Per source module, sorted by module
+    pub by_module: Vec<(String, DuplicateCounts)>,
+    /// Batches passed through unchecked because the seen-set failed
+    pub seen_set_errors: u64,
+}
+
+impl DedupStats {
+    pub fn totals(&self) -> DuplicateCounts {
+        self.by_module
+            .iter()
+            .fold(DuplicateCounts::default(), |mut totals, (_, counts)| {
+                totals.checked += counts.checked;
+                totals.duplicates += counts.duplicates;
+                totals
+            })
+    }
+}
+
+/// Ingestion dedup stage
+pub struct Deduplicator {
+    seen: Arc<dyn SeenSet>,
+    counts: DashMap<&'static str, DuplicateCounts>,
+    seen_set_errors: AtomicU64,
+}
+
+impl Deduplicator {
+    pub fn new(seen: Arc<dyn SeenSet>) -> Self {
+        Self {
+            seen,
+            counts: DashMap::new(),
+            seen_set_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Build the stage with the configured seen-set
+    pub async fn from_config(config: &DedupConfig) -> Result<Self> {
+        let seen: Arc<dyn SeenSet> = match config.backend {
+            DedupBackend::Memory => Arc::new(MemorySeenSet::new(config.ttl(), config.max_entries)),
+            DedupBackend::Redis => Arc::new(RedisSeenSet::connect(config).await?),
+        };
+        info!(
+            backend = ?config.backend,
+            ttl_secs = config.ttl_secs,
+            "Ingestion dedup enabled"
+        );
+        Ok(Self::new(seen))
+    }
+
+    /// Drop events whose ID was already seen, returning how many were dropped
+    pub async fn dedup(&self, events: &mut Vec<AnalyticsEvent>) -> usize {
+        let Some(unseen) = self.check(events).await else {
+            return 0;
+        };
+        let dropped = unseen.iter().filter(|is_new| !**is_new).count();
+        let mut unseen = unseen.into_iter();
+        events.retain(|_| unseen.next().unwrap_or(true));
+        dropped
+    }
+
+    /// Whether a single event was already seen, recording it otherwise
+    pub async fn is_duplicate(&self, event: &AnalyticsEvent) -> bool {
+        matches!(
+            self.check(std::slice::from_ref(event)).await.as_deref(),
+            Some([false])
+        )
+    }
+
+    /// Record `events` in the seen-set and count duplicates, returning for each
+    /// event whether it is new, or nothing when the seen-set is unavailable
+    async fn check(&self, events: &[AnalyticsEvent]) -> Option<Vec<bool>> {
+        let ids: Vec<Uuid> = events.iter().map(|e| e.common.event_id).collect();
+        let unseen = match self.seen.insert_new(&ids).await {
+            Ok(unseen) if unseen.len() == events.len() => unseen,
+            Ok(_) | Err(_) => {
+                self.seen_set_errors.fetch_add(1, Ordering::Relaxed);
+                warn!("Dedup seen-set unavailable, passing batch through unchecked");
+                return None;
+            }
+        };
+
+        let mut duplicates: HashMap<&'static str, u64> = HashMap::new();
+        for (event, is_new) in events.iter().zip(&unseen) {
+            let module = event.common.source_module.as_str();
+            let mut counts = self.counts.entry(module).or_default();
+            counts.checked += 1;
+            if !is_new {
+                counts.duplicates += 1;
+                *duplicates.entry(module).or_insert(0) += 1;
+            }
+        }
+
+        let hub_metrics = HubMetrics::global();
+        for (module, n) in duplicates {
+            hub_metrics.record_duplicates(module, n);
+        }
+        Some(unseen)
+    }
+
+    /// Forget events that were let through but could not be stored, so their
+    /// redelivery is not dropped as a duplicate
+    pub async fn release(&self, events: &[AnalyticsEvent]) {
+        let ids: Vec<Uuid> = events.iter().map(|e| e.common.event_id).collect();
+        self.release_ids(&ids).await;
+    }
+
+    /// Forget event IDs that were let through but then rejected
+    pub async fn release_ids(&self, ids: &[Uuid]) {
+        if let Err(e) = self.seen.forget(ids).await {
+            self.seen_set_errors.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Failed to release {} event IDs from the dedup seen-set: {}",
+                ids.len(),
+                e
+            );
+        }
+    }
+
+    pub fn get_stats(&self) -> DedupStats {
+        let mut by_module: Vec<(String, DuplicateCounts)> = self
+            .counts
+            .iter()
+            .map(|entry| (entry.key().to_string(), *entry.value()))
+            .collect();
+        by_module.sort_by(|a, b| a.0.cmp(&b.0));
+        DedupStats {
+            by_module,
+            seen_set_errors: self.seen_set_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+    };
+
+    fn event(event_id: Uuid, source_module: SourceModule) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id,
+                timestamp: Utc::now(),
+                source_module,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Info,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_memory_seen_set_expires_and_caps() {
+        let seen = MemorySeenSet::new(Duration::seconds(60), 3);
+        let now = Utc::now();
+        let [a, b, c, d] = [
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        ];
+
+        assert_eq!(seen.insert_new_at(&[a, b, a], now), vec![true, true, false]);
+        assert_eq!(
+            seen.insert_new_at(&[a], now + Duration::seconds(30)),
+            vec![false]
+        );
+        assert_eq!(
+            seen.insert_new_at(&[a], now + Duration::seconds(61)),
+            vec![true]
+        );
+
+        // The cap forgets the oldest IDs first
+        seen.insert_new_at(&[c, d], now + Duration::seconds(62));
+        assert_eq!(seen.len(), 3);
+        assert_eq!(
+            seen.insert_new_at(&[b], now + Duration::seconds(63)),
+            vec![true]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dedup_drops_redeliveries_and_counts_per_module() {
+        let dedup = Deduplicator::new(Arc::new(MemorySeenSet::new(Duration::hours(1), 1000)));
+        let id = Uuid::new_v4();
+
+        let mut first = vec![
+            event(id, SourceModule::LlmSentinel),
+            event(Uuid::new_v4(), SourceModule::LlmObservatory),
+        ];
+        assert_eq!(dedup.dedup(&mut first).await, 0);
+        assert_eq!(first.len(), 2);
+
+        let mut retry = vec![
+            event(id, SourceModule::LlmSentinel),
+            event(Uuid::new_v4(), SourceModule::LlmSentinel),
+        ];
+        assert_eq!(dedup.dedup(&mut retry).await, 1);
+        assert_eq!(retry.len(), 1);
+        assert_ne!(retry[0].common.event_id, id);
+
+        let stats = dedup.get_stats();
+        let sentinel = stats
+            .by_module
+            .iter()
+            .find(|(module, _)| module == SourceModule::LlmSentinel.as_str())
+            .unwrap()
+            .1;
+        assert_eq!(sentinel.checked, 3);
+        assert_eq!(sentinel.duplicates, 1);
+        assert_eq!(stats.totals().checked, 4);
+    }
+
+    #[tokio::test]
+    async fn test_released_events_pass_again() {
+        let dedup = Deduplicator::new(Arc::new(MemorySeenSet::new(Duration::hours(1), 1000)));
+        let mut batch = vec![event(Uuid::new_v4(), SourceModule::LlmCostOps)];
+        dedup.dedup(&mut batch).await;
+
+        dedup.release(&batch).await;
+        let mut redelivered = batch.clone();
+        assert_eq!(dedup.dedup(&mut redelivered).await, 0);
+        assert_eq!(redelivered.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_single_event_retries_are_duplicates_until_released() {
+        let dedup = Deduplicator::new(Arc::new(MemorySeenSet::new(Duration::hours(1), 1000)));
+        let single = event(Uuid::new_v4(), SourceModule::LlmSentinel);
+
+        assert!(!dedup.is_duplicate(&single).await);
+        assert!(dedup.is_duplicate(&single).await);
+
+        // A rejected event is forgotten so the client's retry goes through
+        dedup.release_ids(&[single.common.event_id]).await;
+        assert!(!dedup.is_duplicate(&single).await);
+        assert_eq!(dedup.get_stats().totals().duplicates, 1);
+    }
+}
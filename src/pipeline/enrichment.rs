@@ -0,0 +1,433 @@
+//! Tag Normalization and Enrichment
+//!
+//! Modules tag events inconsistently (`env` vs `environment`, `model` vs
+//! `model_id`). This stage rewrites tag keys to their canonical names through a
+//! configurable alias mapping, drops tags whose values are unbounded (request
+//! and trace IDs), and derives tags the producers do not know:
+//!
+//! - `provider`, resolved from `model_id` through LLM-Registry and cached
+//! - `team`, from the LLM-CostOps consumer-to-team mapping, looked up by the
+//!   first consumer tag present (`consumer_id`, `user_id`, `application_id`)
+//!
+//! Tags already set by the producer are never overwritten. Registry lookups that
+//! fail are cached as misses and retried after the refresh interval.
+
+use crate::adapters::costops::CostOpsAdapter;
+use crate::adapters::registry::RegistryAdapter;
+use crate::analytics::clustering::DEFAULT_MODEL_TAG;
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Tag carrying the model's provider
+pub const PROVIDER_TAG: &str = "provider";
+
+/// Tag carrying the team an event is attributed to
+pub const TEAM_TAG: &str = "team";
+
+/// Provider reported by the registry when it does not know the model
+const UNKNOWN_PROVIDER: &str = "unknown";
+
+/// Tag enrichment configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagEnrichmentConfig {
+    /// Alias key to canonical key
+    pub key_aliases: HashMap<String, String>,
+    /// Keys dropped because their values are unbounded
+    pub dropped_keys: HashSet<String>,
+    /// Keys naming a CostOps consumer, in lookup order
+    pub consumer_keys: Vec<String>,
+    /// How long resolved providers are cached, and how often the team mapping is refreshed
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for TagEnrichmentConfig {
+    fn default() -> Self {
+        let key_aliases = [
+            ("env", "environment"),
+            ("model", DEFAULT_MODEL_TAG),
+            ("model_name", DEFAULT_MODEL_TAG),
+            ("tenant", "tenant_id"),
+            ("user", "user_id"),
+        ];
+        Self {
+            key_aliases: key_aliases
+                .into_iter()
+                .map(|(alias, key)| (alias.to_string(), key.to_string()))
+                .collect(),
+            // Session IDs are kept: unit economics divides cost per session
+            dropped_keys: ["request_id", "trace_id", "span_id"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            consumer_keys: vec![
+                "consumer_id".to_string(),
+                "user_id".to_string(),
+                "application_id".to_string(),
+            ],
+            refresh_interval_secs: 300,
+        }
+    }
+}
+
+impl TagEnrichmentConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let list = |v: String| -> Vec<String> {
+            v.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        };
+        Ok(Self {
+            key_aliases: match std::env::var("TAG_KEY_ALIASES") {
+                Ok(spec) => Self::parse_aliases(&spec)?,
+                Err(_) => defaults.key_aliases,
+            },
+            dropped_keys: std::env::var("TAG_DROPPED_KEYS")
+                .map(|v| list(v).into_iter().collect())
+                .unwrap_or(defaults.dropped_keys),
+            consumer_keys: std::env::var("TAG_CONSUMER_KEYS")
+                .map(list)
+                .unwrap_or(defaults.consumer_keys),
+            refresh_interval_secs: std::env::var("TAG_ENRICHMENT_REFRESH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.refresh_interval_secs),
+        })
+    }
+
+    /// Parse `alias=canonical` entries separated by commas
+    pub fn parse_aliases(spec: &str) -> Result<HashMap<String, String>> {
+        let mut aliases = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((alias, key)) = entry.split_once('=') else {
+                anyhow::bail!("Tag key aliases must be alias=canonical");
+            };
+            aliases.insert(alias.trim().to_lowercase(), key.trim().to_lowercase());
+        }
+        Ok(aliases)
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::seconds(self.refresh_interval_secs as i64)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedProvider {
+    provider: Option<String>,
+    resolved_at: DateTime<Utc>,
+}
+
+/// Tag normalization and enrichment stage
+pub struct TagEnricher {
+    config: TagEnrichmentConfig,
+    registry: Option<Arc<RegistryAdapter>>,
+    costops: Option<Arc<CostOpsAdapter>>,
+    providers: DashMap<String, CachedProvider>,
+    consumer_teams: RwLock<HashMap<String, String>>,
+    keys_renamed: AtomicU64,
+    tags_dropped: AtomicU64,
+    providers_added: AtomicU64,
+    teams_added: AtomicU64,
+    lookup_failures: AtomicU64,
+}
+
+impl TagEnricher {
+    /// Enricher that only normalizes and drops tags until adapters are attached
+    pub fn new(config: TagEnrichmentConfig) -> Self {
+        Self {
+            config,
+            registry: None,
+            costops: None,
+            providers: DashMap::new(),
+            consumer_teams: RwLock::new(HashMap::new()),
+            keys_renamed: AtomicU64::new(0),
+            tags_dropped: AtomicU64::new(0),
+            providers_added: AtomicU64::new(0),
+            teams_added: AtomicU64::new(0),
+            lookup_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Resolve `provider` from `model_id` through LLM-Registry
+    pub fn with_registry(mut self, registry: Arc<RegistryAdapter>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Resolve `team` from the LLM-CostOps consumer mapping
+    pub fn with_costops(mut self, costops: Arc<CostOpsAdapter>) -> Self {
+        self.costops = Some(costops);
+        self
+    }
+
+    /// Record a model's provider without asking the registry
+    pub fn set_provider(&self, model_id: &str, provider: &str) {
+        self.providers.insert(
+            model_id.to_string(),
+            CachedProvider {
+                provider: Some(provider.to_string()),
+                resolved_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Replace the consumer-to-team mapping
+    pub fn set_consumer_teams(&self, teams: HashMap<String, String>) {
+        *self.consumer_teams.write() = teams;
+    }
+
+    /// Fetch the consumer-to-team mapping from CostOps, returning how many were loaded
+    pub async fn refresh_consumer_teams(&self) -> Result<usize> {
+        let Some(costops) = &self.costops else {
+            return Ok(self.consumer_teams.read().len());
+        };
+        let teams = costops.fetch_consumer_teams().await?;
+        let count = teams.len();
+        self.set_consumer_teams(teams);
+        debug!(consumers = count, "Refreshed consumer team mapping");
+        Ok(count)
+    }
+
+    /// Refresh the team mapping on the configured interval, keeping the last one on failure
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.refresh_interval_secs.max(1));
+        info!(
+            interval_secs = period.as_secs(),
+            "Starting consumer team mapping refresh"
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh_consumer_teams().await {
+                    error!("Consumer team mapping refresh failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Normalize and enrich the tags of every event in a batch
+    pub async fn enrich(&self, events: &mut [AnalyticsEvent]) {
+        for event in events.iter_mut() {
+            self.normalize(event);
+        }
+        self.resolve_providers(events, Utc::now()).await;
+        for event in events.iter_mut() {
+            self.derive(event);
+        }
+    }
+
+    /// Rewrite aliased keys to their canonical names and drop unbounded tags
+    pub fn normalize(&self, event: &mut AnalyticsEvent) {
+        let tags = std::mem::take(&mut event.common.tags);
+        let mut normalized = HashMap::with_capacity(tags.len());
+        let mut aliased = Vec::new();
+
+        for (key, value) in tags {
+            let lowered = key.trim().to_lowercase();
+            if self.config.dropped_keys.contains(&lowered) {
+                self.tags_dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            match self.config.key_aliases.get(&lowered) {
+                Some(canonical) => aliased.push((canonical.clone(), value)),
+                None => {
+                    normalized.insert(lowered, value);
+                }
+            }
+        }
+        // A canonical key set by the producer wins over its aliases
+        for (key, value) in aliased {
+            self.keys_renamed.fetch_add(1, Ordering::Relaxed);
+            normalized.entry(key).or_insert(value);
+        }
+        event.common.tags = normalized;
+    }
+
+    /// Look up providers for models in the batch that are not cached or are stale
+    async fn resolve_providers(&self, events: &[AnalyticsEvent], now: DateTime<Utc>) {
+        let Some(registry) = &self.registry else {
+            return;
+        };
+        let stale: HashSet<&str> = events
+            .iter()
+            .filter(|event| !event.common.tags.contains_key(PROVIDER_TAG))
+            .filter_map(|event| event.common.tags.get(DEFAULT_MODEL_TAG))
+            .map(String::as_str)
+            .filter(|model_id| {
+                !matches!(self.providers.get(*model_id),
+                    Some(cached) if now - cached.resolved_at < self.config.refresh_interval())
+            })
+            .collect();
+
+        for model_id in stale {
+            let provider = match registry.fetch_model(model_id).await {
+                Ok(model) if model.provider != UNKNOWN_PROVIDER => Some(model.provider),
+                Ok(_) => None,
+                Err(e) => {
+                    self.lookup_failures.fetch_add(1, Ordering::Relaxed);
+                    warn!(model_id, "Failed to resolve model provider: {}", e);
+                    None
+                }
+            };
+            self.providers.insert(
+                model_id.to_string(),
+                CachedProvider {
+                    provider,
+                    resolved_at: now,
+                },
+            );
+        }
+    }
+
+    /// Add `provider` and `team` tags the producer did not set
+    fn derive(&self, event: &mut AnalyticsEvent) {
+        let tags = &mut event.common.tags;
+
+        if !tags.contains_key(PROVIDER_TAG) {
+            let provider = tags.get(DEFAULT_MODEL_TAG).and_then(|model_id| {
+                self.providers
+                    .get(model_id)
+                    .and_then(|cached| cached.provider.clone())
+            });
+            if let Some(provider) = provider {
+                tags.insert(PROVIDER_TAG.to_string(), provider);
+                self.providers_added.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        if !tags.contains_key(TEAM_TAG) {
+            let teams = self.consumer_teams.read();
+            let team = self
+                .config
+                .consumer_keys
+                .iter()
+                .find_map(|key| tags.get(key))
+                .and_then(|consumer| teams.get(consumer))
+                .cloned();
+            if let Some(team) = team {
+                tags.insert(TEAM_TAG.to_string(), team);
+                self.teams_added.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> TagEnrichmentStats {
+        TagEnrichmentStats {
+            keys_renamed: self.keys_renamed.load(Ordering::Relaxed),
+            tags_dropped: self.tags_dropped.load(Ordering::Relaxed),
+            providers_added: self.providers_added.load(Ordering::Relaxed),
+            teams_added: self.teams_added.load(Ordering::Relaxed),
+            lookup_failures: self.lookup_failures.load(Ordering::Relaxed),
+            models_cached: self.providers.len(),
+        }
+    }
+}
+
+/// Tag enrichment statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagEnrichmentStats {
+    pub keys_renamed: u64,
+    pub tags_dropped: u64,
+    pub providers_added: u64,
+    pub teams_added: u64,
+    pub lookup_failures: u64,
+    pub models_cached: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity, SourceModule,
+    };
+    use uuid::Uuid;
+
+    fn event(tags: &[(&str, &str)]) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Info,
+                environment: "test".to_string(),
+                tags: tags
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_normalize_renames_aliases_and_drops_unbounded_tags() {
+        let enricher = TagEnricher::new(TagEnrichmentConfig::default());
+        let mut e = event(&[
+            ("Env", "prod"),
+            ("model", "gpt-4"),
+            ("model_id", "gpt-4o"),
+            ("request_id", "req-123"),
+            ("region", "us-east-1"),
+        ]);
+        enricher.normalize(&mut e);
+
+        let tags = &e.common.tags;
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags["environment"], "prod");
+        // The producer's canonical key wins over its alias
+        assert_eq!(tags["model_id"], "gpt-4o");
+        assert_eq!(tags["region"], "us-east-1");
+
+        let stats = enricher.get_stats();
+        assert_eq!(stats.keys_renamed, 2);
+        assert_eq!(stats.tags_dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_enrich_derives_provider_and_team() {
+        let enricher = TagEnricher::new(TagEnrichmentConfig::default());
+        enricher.set_provider("gpt-4", "openai");
+        enricher.set_consumer_teams(HashMap::from([(
+            "svc-search".to_string(),
+            "search".to_string(),
+        )]));
+
+        let mut batch = vec![
+            event(&[("model", "gpt-4"), ("application_id", "svc-search")]),
+            event(&[("model_id", "gpt-4"), ("provider", "azure"), ("team", "ml")]),
+            event(&[("model_id", "claude-3")]),
+        ];
+        enricher.enrich(&mut batch).await;
+
+        assert_eq!(batch[0].common.tags[PROVIDER_TAG], "openai");
+        assert_eq!(batch[0].common.tags[TEAM_TAG], "search");
+        assert_eq!(batch[1].common.tags[PROVIDER_TAG], "azure");
+        assert_eq!(batch[1].common.tags[TEAM_TAG], "ml");
+        assert!(!batch[2].common.tags.contains_key(PROVIDER_TAG));
+        assert!(!batch[2].common.tags.contains_key(TEAM_TAG));
+
+        let stats = enricher.get_stats();
+        assert_eq!(stats.providers_added, 1);
+        assert_eq!(stats.teams_added, 1);
+    }
+}
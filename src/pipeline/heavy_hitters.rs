@@ -0,0 +1,568 @@
+//! Heavy Hitter Tracking
+//!
+//! Streaming top-K and cardinality sketches maintained on the ingestion path,
+//! so questions like "top 10 models by token usage in the last 15 minutes" or
+//! "distinct request IDs per hour" are answered from memory instead of scanning
+//! the events table.
+//!
+//! Events are folded into fixed-width time buckets, each holding a Space-Saving
+//! summary per top-K dimension and a HyperLogLog per distinct dimension. Queries
+//! merge the buckets that fall inside the requested window.
+
+use crate::schemas::events::{AnalyticsEvent, CostPayload, EventPayload, TelemetryPayload};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Heavy hitter tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeavyHitterConfig {
+    /// Width of each time bucket in seconds
+    pub bucket_secs: i64,
+    /// How far back queries can reach, in seconds
+    pub retention_secs: i64,
+    /// Counters kept per bucket and dimension by the Space-Saving summary
+    pub top_k_capacity: usize,
+    /// HyperLogLog precision (registers = 2^precision)
+    pub hll_precision: u8,
+}
+
+impl Default for HeavyHitterConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 60,
+            retention_secs: 24 * 3600,
+            top_k_capacity: 200,
+            hll_precision: 11,
+        }
+    }
+}
+
+impl HeavyHitterConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            bucket_secs: std::env::var("HEAVY_HITTER_BUCKET_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.bucket_secs),
+            retention_secs: std::env::var("HEAVY_HITTER_RETENTION_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.retention_secs),
+            top_k_capacity: std::env::var("HEAVY_HITTER_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.top_k_capacity),
+            ..defaults
+        }
+    }
+}
+
+/// Weighted dimension ranked by top-K queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopKDimension {
+    /// Models weighted by total tokens
+    ModelTokens,
+    /// Models weighted by request count
+    ModelRequests,
+    /// Models weighted by cost in USD
+    ModelCost,
+    /// Source modules weighted by event count
+    SourceModules,
+}
+
+impl FromStr for TopKDimension {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow::anyhow!("Unknown top-K dimension: {}", s))
+    }
+}
+
+/// Dimension counted by distinct queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistinctDimension {
+    RequestIds,
+    Models,
+}
+
+impl FromStr for DistinctDimension {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow::anyhow!("Unknown distinct dimension: {}", s))
+    }
+}
+
+/// Ranked key with its estimated weight.
+///
+/// The true weight lies in `[count - error, count]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeavyHitter {
+    pub key: String,
+    pub count: f64,
+    pub error: f64,
+}
+
+// ========== Space-Saving ==========
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    count: f64,
+    error: f64,
+}
+
+/// Space-Saving summary over weighted keys
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    counters: HashMap<String, Counter>,
+}
+
+impl SpaceSaving {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn offer(&mut self, key: &str, weight: f64) {
+        if let Some(counter) = self.counters.get_mut(key) {
+            counter.count += weight;
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            self.counters.insert(
+                key.to_string(),
+                Counter {
+                    count: weight,
+                    error: 0.0,
+                },
+            );
+            return;
+        }
+
+        // Replace the smallest counter; the newcomer inherits its count as error
+        let (min_key, min) = self
+            .counters
+            .iter()
+            .min_by(|a, b| a.1.count.total_cmp(&b.1.count))
+            .map(|(k, c)| (k.clone(), *c))
+            .expect("summary is at capacity");
+        self.counters.remove(&min_key);
+        self.counters.insert(
+            key.to_string(),
+            Counter {
+                count: min.count + weight,
+                error: min.count,
+            },
+        );
+    }
+
+    pub fn top(&self, k: usize) -> Vec<HeavyHitter> {
+        rank(
+            self.counters
+                .iter()
+                .map(|(key, c)| (key.clone(), *c))
+                .collect(),
+            k,
+        )
+    }
+}
+
+fn rank(counters: HashMap<String, Counter>, k: usize) -> Vec<HeavyHitter> {
+    let mut hitters: Vec<HeavyHitter> = counters
+        .into_iter()
+        .map(|(key, c)| HeavyHitter {
+            key,
+            count: c.count,
+            error: c.error,
+        })
+        .collect();
+    hitters.sort_by(|a, b| b.count.total_cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    hitters.truncate(k);
+    hitters
+}
+
+// ========== HyperLogLog ==========
+
+/// HyperLogLog distinct counter
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub fn new(precision: u8) -> Self {
+        let precision = precision.clamp(4, 16);
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    pub fn insert(&mut self, value: &str) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Fold another sketch of the same precision into this one
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Linear counting for small cardinalities
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+// ========== Windowed Tracker ==========
+
+struct Bucket {
+    index: i64,
+    top: HashMap<TopKDimension, SpaceSaving>,
+    distinct: HashMap<DistinctDimension, HyperLogLog>,
+}
+
+/// Top-K and distinct-count sketches over a sliding window of time buckets
+pub struct HeavyHitterTracker {
+    config: HeavyHitterConfig,
+    buckets: Mutex<VecDeque<Bucket>>,
+}
+
+impl HeavyHitterTracker {
+    pub fn new(config: HeavyHitterConfig) -> Self {
+        Self {
+            config: HeavyHitterConfig {
+                bucket_secs: config.bucket_secs.max(1),
+                ..config
+            },
+            buckets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn bucket_index(&self, at: DateTime<Utc>) -> i64 {
+        at.timestamp().div_euclid(self.config.bucket_secs)
+    }
+
+    fn retained_buckets(&self) -> i64 {
+        (self.config.retention_secs / self.config.bucket_secs).max(1)
+    }
+
+    /// Fold an event into the bucket of its timestamp
+    pub fn observe(&self, event: &AnalyticsEvent) {
+        let index = self.bucket_index(event.common.timestamp);
+        let mut buckets = self.buckets.lock();
+
+        let newest = buckets.back().map(|b| b.index).unwrap_or(index);
+        let oldest_kept = newest.max(index) - self.retained_buckets() + 1;
+        if index < oldest_kept {
+            // Too late to be queried
+            return;
+        }
+
+        while buckets.front().is_some_and(|b| b.index < oldest_kept) {
+            buckets.pop_front();
+        }
+
+        let position = match buckets.iter().rposition(|b| b.index <= index) {
+            Some(pos) if buckets[pos].index == index => pos,
+            found => {
+                let pos = found.map_or(0, |p| p + 1);
+                buckets.insert(
+                    pos,
+                    Bucket {
+                        index,
+                        top: HashMap::new(),
+                        distinct: HashMap::new(),
+                    },
+                );
+                pos
+            }
+        };
+        let bucket = &mut buckets[position];
+
+        let capacity = self.config.top_k_capacity;
+        for (dimension, key, weight) in top_k_samples(event) {
+            bucket
+                .top
+                .entry(dimension)
+                .or_insert_with(|| SpaceSaving::new(capacity))
+                .offer(key, weight);
+        }
+        let precision = self.config.hll_precision;
+        for (dimension, value) in distinct_samples(event) {
+            bucket
+                .distinct
+                .entry(dimension)
+                .or_insert_with(|| HyperLogLog::new(precision))
+                .insert(value);
+        }
+    }
+
+    /// Heaviest keys of a dimension over the window ending at `now`
+    pub fn top_k(
+        &self,
+        dimension: TopKDimension,
+        window: Duration,
+        k: usize,
+        now: DateTime<Utc>,
+    ) -> Vec<HeavyHitter> {
+        let from = self.bucket_index(now - window);
+        let to = self.bucket_index(now);
+        let buckets = self.buckets.lock();
+
+        let mut merged: HashMap<String, Counter> = HashMap::new();
+        for bucket in buckets.iter().filter(|b| b.index > from && b.index <= to) {
+            if let Some(summary) = bucket.top.get(&dimension) {
+                for (key, counter) in &summary.counters {
+                    let entry = merged.entry(key.clone()).or_default();
+                    entry.count += counter.count;
+                    entry.error += counter.error;
+                }
+            }
+        }
+
+        rank(merged, k)
+    }
+
+    /// Estimated distinct values of a dimension over the window ending at `now`
+    pub fn distinct(
+        &self,
+        dimension: DistinctDimension,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> u64 {
+        let from = self.bucket_index(now - window);
+        let to = self.bucket_index(now);
+        let buckets = self.buckets.lock();
+
+        let mut merged = HyperLogLog::new(self.config.hll_precision);
+        for bucket in buckets.iter().filter(|b| b.index > from && b.index <= to) {
+            if let Some(sketch) = bucket.distinct.get(&dimension) {
+                merged.merge(sketch);
+            }
+        }
+        merged.estimate()
+    }
+}
+
+impl Default for HeavyHitterTracker {
+    fn default() -> Self {
+        Self::new(HeavyHitterConfig::default())
+    }
+}
+
+fn top_k_samples(event: &AnalyticsEvent) -> Vec<(TopKDimension, &str, f64)> {
+    let mut samples = vec![(
+        TopKDimension::SourceModules,
+        event.common.source_module.as_str(),
+        1.0,
+    )];
+    match &event.payload {
+        EventPayload::Telemetry(TelemetryPayload::TokenUsage(usage)) => {
+            samples.push((
+                TopKDimension::ModelTokens,
+                usage.model_id.as_str(),
+                usage.total_tokens as f64,
+            ));
+        }
+        EventPayload::Telemetry(TelemetryPayload::Latency(latency)) => {
+            samples.push((TopKDimension::ModelRequests, latency.model_id.as_str(), 1.0));
+        }
+        EventPayload::Cost(CostPayload::TokenCost(cost)) => {
+            samples.push((
+                TopKDimension::ModelCost,
+                cost.model_id.as_str(),
+                cost.total_cost_usd,
+            ));
+        }
+        _ => {}
+    }
+    samples
+}
+
+fn distinct_samples(event: &AnalyticsEvent) -> Vec<(DistinctDimension, &str)> {
+    let (model_id, request_id) = match &event.payload {
+        EventPayload::Telemetry(TelemetryPayload::TokenUsage(usage)) => {
+            (usage.model_id.as_str(), usage.request_id.as_str())
+        }
+        EventPayload::Telemetry(TelemetryPayload::Latency(latency)) => {
+            (latency.model_id.as_str(), latency.request_id.as_str())
+        }
+        EventPayload::Cost(CostPayload::TokenCost(cost)) => {
+            (cost.model_id.as_str(), cost.request_id.as_str())
+        }
+        _ => return Vec::new(),
+    };
+    vec![
+        (DistinctDimension::Models, model_id),
+        (DistinctDimension::RequestIds, request_id),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, EventType, Severity, SourceModule, TokenUsageMetrics, SCHEMA_VERSION,
+    };
+    use uuid::Uuid;
+
+    fn token_event(model: &str, request: &str, tokens: u32, at: DateTime<Utc>) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: at,
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: "production".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Telemetry(TelemetryPayload::TokenUsage(TokenUsageMetrics {
+                model_id: model.to_string(),
+                request_id: request.to_string(),
+                prompt_tokens: tokens / 2,
+                completion_tokens: tokens - tokens / 2,
+                total_tokens: tokens,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_space_saving_keeps_heavy_hitters() {
+        let mut summary = SpaceSaving::new(3);
+        for _ in 0..100 {
+            summary.offer("gpt-4", 1.0);
+        }
+        for i in 0..50 {
+            summary.offer(&format!("noise-{}", i), 1.0);
+        }
+        for _ in 0..60 {
+            summary.offer("claude", 1.0);
+        }
+
+        let top = summary.top(2);
+        assert_eq!(top[0].key, "gpt-4");
+        assert_eq!(top[0].count, 100.0);
+        assert_eq!(top[1].key, "claude");
+        assert!(top[1].count - top[1].error <= 60.0);
+    }
+
+    #[test]
+    fn test_hyperloglog_estimate_within_error() {
+        let mut hll = HyperLogLog::new(11);
+        for i in 0..10_000 {
+            hll.insert(&format!("req-{}", i));
+        }
+        let estimate = hll.estimate() as f64;
+        assert!(
+            (estimate - 10_000.0).abs() / 10_000.0 < 0.1,
+            "estimate {}",
+            estimate
+        );
+
+        let mut small = HyperLogLog::new(11);
+        for i in 0..10 {
+            small.insert(&format!("req-{}", i % 5));
+        }
+        assert_eq!(small.estimate(), 5);
+    }
+
+    #[test]
+    fn test_top_models_by_tokens_in_window() {
+        let tracker = HeavyHitterTracker::default();
+        let now = Utc::now();
+
+        tracker.observe(&token_event(
+            "old-model",
+            "r0",
+            1_000_000,
+            now - Duration::minutes(30),
+        ));
+        tracker.observe(&token_event("gpt-4", "r1", 500, now - Duration::minutes(5)));
+        tracker.observe(&token_event("gpt-4", "r2", 700, now - Duration::minutes(1)));
+        tracker.observe(&token_event("claude", "r3", 900, now));
+
+        let top = tracker.top_k(TopKDimension::ModelTokens, Duration::minutes(15), 10, now);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].key, "gpt-4");
+        assert_eq!(top[0].count, 1200.0);
+        assert_eq!(top[1].key, "claude");
+
+        assert_eq!(
+            tracker.distinct(DistinctDimension::RequestIds, Duration::hours(1), now),
+            4
+        );
+        assert_eq!(
+            tracker.distinct(DistinctDimension::Models, Duration::minutes(15), now),
+            2
+        );
+    }
+
+    #[test]
+    fn test_buckets_expire_after_retention() {
+        let tracker = HeavyHitterTracker::new(HeavyHitterConfig {
+            bucket_secs: 60,
+            retention_secs: 600,
+            ..Default::default()
+        });
+        let now = Utc::now();
+
+        tracker.observe(&token_event("gpt-4", "r1", 10, now - Duration::minutes(30)));
+        tracker.observe(&token_event("gpt-4", "r2", 10, now));
+
+        let top = tracker.top_k(TopKDimension::ModelTokens, Duration::hours(1), 10, now);
+        assert_eq!(top[0].count, 10.0);
+    }
+
+    #[test]
+    fn test_dimension_parsing() {
+        assert_eq!(
+            "model_tokens".parse::<TopKDimension>().unwrap(),
+            TopKDimension::ModelTokens
+        );
+        assert_eq!(
+            "request_ids".parse::<DistinctDimension>().unwrap(),
+            DistinctDimension::RequestIds
+        );
+        assert!("nope".parse::<TopKDimension>().is_err());
+    }
+}
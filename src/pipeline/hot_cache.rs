@@ -0,0 +1,310 @@
+//! Hot Cache - Recent Aggregates and Detector State in Redis
+//!
+//! Keeps the most recent aggregate windows (15 minutes by default) in Redis so
+//! dashboard and SLO reads over that horizon are served without touching
+//! TimescaleDB. It also implements `SnapshotStore`, so anomaly detector
+//! snapshots can be kept in Redis instead of Postgres.
+//!
+//! Each metric/window pair is stored as a hash of rows keyed by
+//! `<window_start>|<tags>` plus a sorted-set index scored by window start,
+//! which makes re-aggregated windows overwrite in place and lets range reads
+//! and trimming work on scores.
+
+use crate::analytics::snapshot::{DetectorSnapshot, SnapshotStore};
+use crate::database::AggregatedMetricRow;
+use crate::export::prometheus::HubMetrics;
+use crate::models::metrics::TimeWindow;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+
+/// Hot cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotCacheConfig {
+    pub redis_url: String,
+    /// Prefix for every key written by the cache
+    pub key_prefix: String,
+    /// How far back aggregates are kept and served from Redis
+    pub horizon_secs: u64,
+}
+
+impl Default for HotCacheConfig {
+    fn default() -> Self {
+        Self {
+            redis_url: "redis://localhost:6379".to_string(),
+            key_prefix: "hub".to_string(),
+            horizon_secs: 900,
+        }
+    }
+}
+
+impl HotCacheConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            redis_url: std::env::var("REDIS_URL").unwrap_or(defaults.redis_url),
+            key_prefix: std::env::var("HOT_CACHE_PREFIX").unwrap_or(defaults.key_prefix),
+            horizon_secs: std::env::var("HOT_CACHE_HORIZON_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.horizon_secs),
+        }
+    }
+
+    fn horizon(&self) -> Duration {
+        Duration::seconds(self.horizon_secs as i64)
+    }
+}
+
+/// Whether `tags` contains every key/value of `filter` (JSONB `@>` on flat objects)
+pub fn tags_contain(tags: &serde_json::Value, filter: &serde_json::Value) -> bool {
+    match (tags.as_object(), filter.as_object()) {
+        (_, Some(filter)) if filter.is_empty() => true,
+        (Some(tags), Some(filter)) => filter.iter().all(|(k, v)| tags.get(k) == Some(v)),
+        _ => false,
+    }
+}
+
+/// Redis cache for recent aggregates and anomaly detector state
+pub struct HotCache {
+    conn: ConnectionManager,
+    config: HotCacheConfig,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    writes: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl HotCache {
+    /// Connect to Redis
+    pub async fn connect(config: HotCacheConfig) -> Result<Self> {
+        let client = redis::Client::open(config.redis_url.as_str())
+            .context("Failed to create Redis client")?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .context("Failed to connect hot cache to Redis")?;
+
+        info!(horizon_secs = config.horizon_secs, "Hot cache connected");
+
+        Ok(Self {
+            conn,
+            config,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        })
+    }
+
+    fn rows_key(&self, metric_name: &str, window: &str) -> String {
+        format!("{}:agg:{}:{}", self.config.key_prefix, metric_name, window)
+    }
+
+    fn index_key(&self, metric_name: &str, window: &str) -> String {
+        format!(
+            "{}:agg-idx:{}:{}",
+            self.config.key_prefix, metric_name, window
+        )
+    }
+
+    /// Set once when the cache starts receiving aggregates; reads earlier than
+    /// this would be incomplete and go to the database instead
+    fn warm_key(&self) -> String {
+        format!("{}:agg:warm-since", self.config.key_prefix)
+    }
+
+    fn snapshot_key(&self) -> String {
+        format!("{}:anomaly:snapshot", self.config.key_prefix)
+    }
+
+    // ========== Aggregates ==========
+
+    /// Write an aggregate window through to the cache and trim expired windows
+    pub async fn put_aggregate(&self, row: &AggregatedMetricRow) -> Result<()> {
+        let now = Utc::now();
+        if row.window_start < now - self.config.horizon() {
+            return Ok(());
+        }
+
+        let rows_key = self.rows_key(&row.metric_name, &row.time_window);
+        let index_key = self.index_key(&row.metric_name, &row.time_window);
+        let field = format!("{}|{}", row.window_start.timestamp_millis(), row.tags);
+        let cutoff = (now - self.config.horizon()).timestamp_millis();
+        let ttl = (self.config.horizon_secs * 2) as i64;
+
+        let mut conn = self.conn.clone();
+
+        // Fields falling out of the horizon are removed from both structures
+        let expired: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(&index_key)
+            .arg("-inf")
+            .arg(format!("({}", cutoff))
+            .query_async(&mut conn)
+            .await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("SET")
+            .arg(self.warm_key())
+            .arg(now.timestamp_millis())
+            .arg("NX")
+            .ignore()
+            .cmd("HSET")
+            .arg(&rows_key)
+            .arg(&field)
+            .arg(serde_json::to_string(row)?)
+            .ignore()
+            .cmd("ZADD")
+            .arg(&index_key)
+            .arg(row.window_start.timestamp_millis())
+            .arg(&field)
+            .ignore();
+        if !expired.is_empty() {
+            pipe.cmd("HDEL").arg(&rows_key).arg(&expired).ignore();
+            pipe.cmd("ZREMRANGEBYSCORE")
+                .arg(&index_key)
+                .arg("-inf")
+                .arg(format!("({}", cutoff))
+                .ignore();
+        }
+        pipe.cmd("EXPIRE").arg(&rows_key).arg(ttl).ignore();
+        pipe.cmd("EXPIRE").arg(&index_key).arg(ttl).ignore();
+
+        match pipe.query_async::<_, ()>(&mut conn).await {
+            Ok(()) => {
+                self.writes.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                Err(e).context("Failed to cache aggregate")
+            }
+        }
+    }
+
+    /// Whether a read starting at `start` lies entirely within the cached horizon
+    pub async fn covers(&self, start: DateTime<Utc>) -> Result<bool> {
+        if start < Utc::now() - self.config.horizon() {
+            return Ok(false);
+        }
+
+        let mut conn = self.conn.clone();
+        let warm_since: Option<i64> = redis::cmd("GET")
+            .arg(self.warm_key())
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(matches!(warm_since, Some(ms) if ms <= start.timestamp_millis()))
+    }
+
+    /// Cached aggregate windows in `[start, end)` whose tags contain `tags`,
+    /// oldest first. Returns `None` when the range is not covered by the cache.
+    pub async fn recent_aggregates(
+        &self,
+        metric_name: &str,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: Option<&serde_json::Value>,
+    ) -> Result<Option<Vec<AggregatedMetricRow>>> {
+        if !self.covers(start).await? {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            HubMetrics::global().record_cache_lookup("hot_aggregates", false);
+            return Ok(None);
+        }
+
+        let mut conn = self.conn.clone();
+        let fields: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(self.index_key(metric_name, window.as_str()))
+            .arg(start.timestamp_millis())
+            .arg(format!("({}", end.timestamp_millis()))
+            .query_async(&mut conn)
+            .await?;
+
+        let mut rows = Vec::with_capacity(fields.len());
+        if !fields.is_empty() {
+            let values: Vec<Option<String>> = redis::cmd("HMGET")
+                .arg(self.rows_key(metric_name, window.as_str()))
+                .arg(&fields)
+                .query_async(&mut conn)
+                .await?;
+
+            for json in values.into_iter().flatten() {
+                let row: AggregatedMetricRow = serde_json::from_str(&json)?;
+                if tags.map_or(true, |filter| tags_contain(&row.tags, filter)) {
+                    rows.push(row);
+                }
+            }
+        }
+
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        HubMetrics::global().record_cache_lookup("hot_aggregates", true);
+        Ok(Some(rows))
+    }
+
+    pub fn get_stats(&self) -> HotCacheStats {
+        HotCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for HotCache {
+    /// Keeps only the latest snapshot, expiring it after `retention`
+    async fn save(&self, snapshot: &DetectorSnapshot, retention: Duration) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::cmd("SET")
+            .arg(self.snapshot_key())
+            .arg(serde_json::to_string(snapshot)?)
+            .arg("EX")
+            .arg(retention.num_seconds().max(1))
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .context("Failed to save detector snapshot")
+    }
+
+    async fn load_latest(&self) -> Result<Option<serde_json::Value>> {
+        let mut conn = self.conn.clone();
+        let json: Option<String> = redis::cmd("GET")
+            .arg(self.snapshot_key())
+            .query_async(&mut conn)
+            .await
+            .context("Failed to load detector snapshot")?;
+
+        json.map(|j| serde_json::from_str(&j).context("Failed to parse detector snapshot"))
+            .transpose()
+    }
+}
+
+/// Hot cache statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub writes: u64,
+    pub errors: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tags_contain() {
+        let tags = json!({"tenant_id": "acme", "model": "gpt-4"});
+
+        assert!(tags_contain(&tags, &json!({})));
+        assert!(tags_contain(&tags, &json!({"tenant_id": "acme"})));
+        assert!(!tags_contain(&tags, &json!({"tenant_id": "globex"})));
+        assert!(!tags_contain(&json!({}), &json!({"tenant_id": "acme"})));
+    }
+}
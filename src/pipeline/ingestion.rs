@@ -2,20 +2,48 @@
 //!
 //! High-performance event ingestion from Kafka with support for 100k+ events/sec,
 //! including dead letter queue, metrics tracking, and automatic retry logic.
-
-use crate::database::Database;
-use crate::schemas::events::AnalyticsEvent;
+//!
+//! Offsets are committed manually, and only once the batch they cover has been
+//! persisted. Combined with idempotent inserts keyed on `event_id`, a restart
+//! replays at most the uncommitted batch without duplicating stored events.
+
+use crate::database::StorageBackend;
+use crate::export::prometheus::HubMetrics;
+use crate::pipeline::dedup::Deduplicator;
+use crate::pipeline::enrichment::TagEnricher;
+use crate::pipeline::heavy_hitters::HeavyHitterTracker;
+use crate::pipeline::quotas::ModuleQuotas;
+use crate::pipeline::sampling::Sampler;
+use crate::schemas::events::{validate_event, AnalyticsEvent};
 use anyhow::{Context, Result};
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
 use rdkafka::message::Message;
+use rdkafka::{Offset, TopicPartitionList};
 use rdkafka::producer::{FutureProducer, FutureRecord};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, error, info, instrument, warn};
 
+/// Wait before the first storage retry, doubled on each later one
+const INITIAL_STORAGE_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Longest wait between storage retries, however many are configured
+const MAX_STORAGE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Wait before the `attempt`th storage retry
+fn storage_backoff(attempt: u32) -> Duration {
+    let factor = 1u32
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    INITIAL_STORAGE_BACKOFF
+        .saturating_mul(factor)
+        .min(MAX_STORAGE_BACKOFF)
+}
+
 /// Event ingestion configuration
 #[derive(Debug, Clone)]
 pub struct IngestionConfig {
@@ -44,21 +72,32 @@ impl Default for IngestionConfig {
     }
 }
 
+/// Optional stages applied to each batch before it is stored
+#[derive(Clone, Default)]
+struct BatchStages {
+    dedup: Option<Arc<Deduplicator>>,
+    enricher: Option<Arc<TagEnricher>>,
+    heavy_hitters: Option<Arc<HeavyHitterTracker>>,
+    quotas: Option<Arc<ModuleQuotas>>,
+    sampler: Option<Arc<Sampler>>,
+}
+
 /// Event ingester with high-performance Kafka integration
 pub struct EventIngester {
     config: IngestionConfig,
     consumer: Arc<StreamConsumer>,
     producer: FutureProducer,
-    database: Arc<Database>,
+    database: Arc<dyn StorageBackend>,
     metrics: Arc<IngestionMetrics>,
     event_tx: mpsc::Sender<AnalyticsEvent>,
     event_rx: Option<mpsc::Receiver<AnalyticsEvent>>,
+    stages: BatchStages,
 }
 
 impl EventIngester {
     /// Create a new event ingester
     #[instrument(skip(config, database))]
-    pub async fn new(config: IngestionConfig, database: Arc<Database>) -> Result<Self> {
+    pub async fn new(config: IngestionConfig, database: Arc<dyn StorageBackend>) -> Result<Self> {
         info!("Initializing event ingester");
 
         // Configure Kafka consumer for high throughput
@@ -68,8 +107,7 @@ impl EventIngester {
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
             .set("heartbeat.interval.ms", "2000")
-            .set("enable.auto.commit", "true")
-            .set("auto.commit.interval.ms", "5000")
+            .set("enable.auto.commit", "false") // Committed after persistence
             .set("auto.offset.reset", "earliest")
             .set("compression.type", "snappy")
             .set("fetch.min.bytes", "1048576") // 1MB minimum fetch
@@ -105,9 +143,40 @@ impl EventIngester {
             metrics,
             event_tx,
             event_rx: Some(event_rx),
+            stages: BatchStages::default(),
         })
     }
 
+    /// Drop redelivered events before any other stage sees them
+    pub fn with_dedup(mut self, dedup: Arc<Deduplicator>) -> Self {
+        self.stages.dedup = Some(dedup);
+        self
+    }
+
+    /// Normalize and enrich event tags before they are tracked and stored
+    pub fn with_enricher(mut self, enricher: Arc<TagEnricher>) -> Self {
+        self.stages.enricher = Some(enricher);
+        self
+    }
+
+    /// Feed ingested events into a streaming top-K / cardinality tracker
+    pub fn with_heavy_hitters(mut self, tracker: Arc<HeavyHitterTracker>) -> Self {
+        self.stages.heavy_hitters = Some(tracker);
+        self
+    }
+
+    /// Throttle source modules that exceed their ingestion quota
+    pub fn with_quotas(mut self, quotas: Arc<ModuleQuotas>) -> Self {
+        self.stages.quotas = Some(quotas);
+        self
+    }
+
+    /// Sample events before they are stored and processed
+    pub fn with_sampler(mut self, sampler: Arc<Sampler>) -> Self {
+        self.stages.sampler = Some(sampler);
+        self
+    }
+
     /// Start consuming events from Kafka
     #[instrument(skip(self))]
     pub async fn start(&mut self) -> Result<()> {
@@ -124,15 +193,19 @@ impl EventIngester {
         let tx = self.event_tx.clone();
         let database = self.database.clone();
         let metrics = self.metrics.clone();
+        let stages = self.stages.clone();
         let batch_size = self.config.batch_size;
         let enable_dlq = self.config.enable_dlq;
         let dlq_topic = self.config.dlq_topic.clone();
 
+        let max_retries = self.config.max_retries;
+
         // Spawn consumer task
         tokio::spawn(async move {
             info!("Starting high-performance Kafka consumer");
 
             let mut batch = Vec::with_capacity(batch_size);
+            let mut pending = PendingOffsets::default();
             let mut last_flush = Instant::now();
             let flush_interval = Duration::from_millis(500);
 
@@ -140,36 +213,21 @@ impl EventIngester {
                 match consumer.recv().await {
                     Ok(message) => {
                         metrics.messages_received.fetch_add(1, Ordering::Relaxed);
+                        pending.track(message.topic(), message.partition(), message.offset());
 
                         if let Some(payload) = message.payload() {
-                            match serde_json::from_slice::<AnalyticsEvent>(payload) {
-                                Ok(event) => {
-                                    batch.push(event);
-
-                                    // Flush batch if full or timeout reached
-                                    let should_flush = batch.len() >= batch_size
-                                        || last_flush.elapsed() >= flush_interval;
-
-                                    if should_flush {
-                                        let events_to_process = std::mem::replace(
-                                            &mut batch,
-                                            Vec::with_capacity(batch_size)
-                                        );
-
-                                        // Process batch
-                                        Self::process_batch(
-                                            events_to_process,
-                                            &tx,
-                                            &database,
-                                            &metrics,
-                                        ).await;
-
-                                        last_flush = Instant::now();
-                                    }
-                                }
-                                Err(e) => {
+                            // Same decoding and validation as the HTTP and gRPC paths
+                            let decoded = serde_json::from_slice::<AnalyticsEvent>(payload)
+                                .map_err(|e| format!("Deserialization error: {}", e))
+                                .and_then(|event| match validate_event(&event) {
+                                    Ok(()) => Ok(event),
+                                    Err(e) => Err(format!("Validation error: {}", e)),
+                                });
+                            match decoded {
+                                Ok(event) => batch.push(event),
+                                Err(reason) => {
                                     metrics.deserialization_errors.fetch_add(1, Ordering::Relaxed);
-                                    warn!("Failed to deserialize event: {}", e);
+                                    warn!("Rejected event: {}", reason);
 
                                     // Send to DLQ if enabled
                                     if enable_dlq {
@@ -177,12 +235,42 @@ impl EventIngester {
                                             &producer,
                                             &dlq_topic,
                                             payload,
-                                            &format!("Deserialization error: {}", e),
+                                            &reason,
                                         ).await;
                                     }
                                 }
                             }
                         }
+
+                        // Flush batch if full or timeout reached
+                        let should_flush = batch.len() >= batch_size
+                            || last_flush.elapsed() >= flush_interval;
+
+                        if should_flush {
+                            let events_to_process = std::mem::replace(
+                                &mut batch,
+                                Vec::with_capacity(batch_size)
+                            );
+
+                            // Process batch, committing its offsets only once it is stored
+                            let persisted = Self::process_batch(
+                                events_to_process,
+                                &tx,
+                                &database,
+                                &metrics,
+                                &stages,
+                                max_retries,
+                            ).await;
+
+                            if persisted {
+                                Self::commit_offsets(&consumer, &pending, &metrics);
+                            } else {
+                                Self::rewind(&consumer, &pending);
+                            }
+                            pending.clear();
+
+                            last_flush = Instant::now();
+                        }
                     }
                     Err(e) => {
                         metrics.kafka_errors.fetch_add(1, Ordering::Relaxed);
@@ -197,25 +285,80 @@ impl EventIngester {
         Ok(())
     }
 
-    /// Process a batch of events
+    /// Process a batch of events, returning whether it was persisted
+    #[instrument(name = "pipeline.ingest_batch", skip_all, fields(batch_size = events.len()))]
     async fn process_batch(
-        events: Vec<AnalyticsEvent>,
+        mut events: Vec<AnalyticsEvent>,
         tx: &mpsc::Sender<AnalyticsEvent>,
-        database: &Arc<Database>,
+        database: &Arc<dyn StorageBackend>,
         metrics: &Arc<IngestionMetrics>,
-    ) {
+        stages: &BatchStages,
+        max_retries: u32,
+    ) -> bool {
         let start = Instant::now();
-        let count = events.len();
+        let hub_metrics = HubMetrics::global();
+
+        if let Some(dedup) = &stages.dedup {
+            let dropped = dedup.dedup(&mut events).await;
+            metrics
+                .duplicates_dropped
+                .fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+        if let Some(enricher) = &stages.enricher {
+            enricher.enrich(&mut events).await;
+        }
 
-        // Insert batch into database
-        match database.insert_events_batch(&events).await {
-            Ok(inserted) => {
-                metrics.events_stored.fetch_add(inserted, Ordering::Relaxed);
-                debug!("Stored batch of {} events", inserted);
+        let mut by_module: HashMap<&'static str, u64> = HashMap::new();
+        for event in &events {
+            *by_module.entry(event.common.source_module.as_str()).or_insert(0) += 1;
+            if let Some(tracker) = &stages.heavy_hitters {
+                tracker.observe(event);
             }
-            Err(e) => {
-                error!("Failed to store event batch: {}", e);
-                metrics.storage_errors.fetch_add(count as u64, Ordering::Relaxed);
+        }
+        for (module, n) in by_module {
+            hub_metrics.record_ingested(module, n);
+        }
+
+        // Heavy hitters see the full stream; storage only sees what survives
+        // quotas and sampling
+        if let Some(quotas) = &stages.quotas {
+            let throttled = quotas.throttle(&mut events);
+            metrics
+                .events_throttled
+                .fetch_add(throttled as u64, Ordering::Relaxed);
+        }
+        if let Some(sampler) = &stages.sampler {
+            events.retain_mut(|event| sampler.sample(event));
+        }
+        let count = events.len();
+
+        // Insert batch into database, retrying before giving the batch back to Kafka
+        let mut attempt = 0;
+        loop {
+            match database.insert_events_batch(&events).await {
+                Ok(inserted) => {
+                    metrics.events_stored.fetch_add(inserted, Ordering::Relaxed);
+                    metrics
+                        .duplicates_skipped
+                        .fetch_add(count as u64 - inserted, Ordering::Relaxed);
+                    debug!("Stored batch of {} events ({} already stored)", inserted, count as u64 - inserted);
+                    break;
+                }
+                Err(e) if attempt < max_retries => {
+                    attempt += 1;
+                    metrics.storage_retries.fetch_add(1, Ordering::Relaxed);
+                    warn!("Failed to store event batch (attempt {}/{}): {}", attempt, max_retries, e);
+                    tokio::time::sleep(storage_backoff(attempt)).await;
+                }
+                Err(e) => {
+                    error!("Failed to store event batch, leaving offsets uncommitted: {}", e);
+                    metrics.storage_errors.fetch_add(count as u64, Ordering::Relaxed);
+                    // The batch will be redelivered; it must not look like a duplicate then
+                    if let Some(dedup) = &stages.dedup {
+                        dedup.release(&events).await;
+                    }
+                    return false;
+                }
             }
         }
 
@@ -231,6 +374,49 @@ impl EventIngester {
 
         let duration = start.elapsed();
         metrics.record_batch_duration(duration);
+        hub_metrics.observe_processing("ingest_batch", duration);
+        hub_metrics.set_ingestion_rate(metrics.calculate_throughput());
+        true
+    }
+
+    /// Commit the offsets following every message of a persisted batch
+    fn commit_offsets(
+        consumer: &StreamConsumer,
+        pending: &PendingOffsets,
+        metrics: &IngestionMetrics,
+    ) {
+        if pending.is_empty() {
+            return;
+        }
+
+        let result = pending
+            .commit_list()
+            .and_then(|list| consumer.commit(&list, CommitMode::Async).map_err(Into::into));
+
+        match result {
+            Ok(()) => {
+                metrics.offset_commits.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                // The next successful commit covers these offsets as well
+                metrics.commit_errors.fetch_add(1, Ordering::Relaxed);
+                warn!("Failed to commit offsets: {}", e);
+            }
+        }
+    }
+
+    /// Seek back to the start of an unpersisted batch so it is consumed again
+    fn rewind(consumer: &StreamConsumer, pending: &PendingOffsets) {
+        for ((topic, partition), offset) in pending.rewind_offsets() {
+            if let Err(e) = consumer.seek(
+                &topic,
+                partition,
+                Offset::Offset(offset),
+                Duration::from_secs(5),
+            ) {
+                error!("Failed to rewind {} [{}] to {}: {}", topic, partition, offset, e);
+            }
+        }
     }
 
     /// Send failed event to dead letter queue
@@ -284,6 +470,11 @@ impl EventIngester {
         Ok(sent)
     }
 
+    /// Sender feeding events directly into the processing channel
+    pub fn sender(&self) -> mpsc::Sender<AnalyticsEvent> {
+        self.event_tx.clone()
+    }
+
     /// Get the event receiver channel
     pub fn take_receiver(&mut self) -> Option<mpsc::Receiver<AnalyticsEvent>> {
         self.event_rx.take()
@@ -318,6 +509,12 @@ pub struct IngestionMetrics {
     storage_errors: AtomicU64,
     processing_errors: AtomicU64,
     kafka_errors: AtomicU64,
+    storage_retries: AtomicU64,
+    duplicates_skipped: AtomicU64,
+    duplicates_dropped: AtomicU64,
+    events_throttled: AtomicU64,
+    offset_commits: AtomicU64,
+    commit_errors: AtomicU64,
     batch_durations: RwLock<Vec<Duration>>,
     start_time: Instant,
 }
@@ -333,6 +530,12 @@ impl IngestionMetrics {
             storage_errors: AtomicU64::new(0),
             processing_errors: AtomicU64::new(0),
             kafka_errors: AtomicU64::new(0),
+            storage_retries: AtomicU64::new(0),
+            duplicates_skipped: AtomicU64::new(0),
+            duplicates_dropped: AtomicU64::new(0),
+            events_throttled: AtomicU64::new(0),
+            offset_commits: AtomicU64::new(0),
+            commit_errors: AtomicU64::new(0),
             batch_durations: RwLock::new(Vec::new()),
             start_time: Instant::now(),
         }
@@ -367,6 +570,12 @@ impl IngestionMetrics {
             storage_errors: self.storage_errors.load(Ordering::Relaxed),
             processing_errors: self.processing_errors.load(Ordering::Relaxed),
             kafka_errors: self.kafka_errors.load(Ordering::Relaxed),
+            storage_retries: self.storage_retries.load(Ordering::Relaxed),
+            duplicates_skipped: self.duplicates_skipped.load(Ordering::Relaxed),
+            duplicates_dropped: self.duplicates_dropped.load(Ordering::Relaxed),
+            events_throttled: self.events_throttled.load(Ordering::Relaxed),
+            offset_commits: self.offset_commits.load(Ordering::Relaxed),
+            commit_errors: self.commit_errors.load(Ordering::Relaxed),
             avg_throughput: self.calculate_throughput(),
         }
     }
@@ -383,5 +592,99 @@ pub struct IngestionStats {
     pub storage_errors: u64,
     pub processing_errors: u64,
     pub kafka_errors: u64,
+    pub storage_retries: u64,
+    pub duplicates_skipped: u64,
+    /// Redelivered events dropped by the dedup stage before storage
+    pub duplicates_dropped: u64,
+    /// Events shed or sampled out by per-module quotas
+    pub events_throttled: u64,
+    pub offset_commits: u64,
+    pub commit_errors: u64,
     pub avg_throughput: f64,
 }
+
+/// Offsets consumed since the last commit, per topic partition
+#[derive(Debug, Default)]
+struct PendingOffsets {
+    // (topic, partition) -> (first offset, last offset)
+    ranges: HashMap<(String, i32), (i64, i64)>,
+}
+
+impl PendingOffsets {
+    fn track(&mut self, topic: &str, partition: i32, offset: i64) {
+        self.ranges
+            .entry((topic.to_string(), partition))
+            .and_modify(|(first, last)| {
+                *first = (*first).min(offset);
+                *last = (*last).max(offset);
+            })
+            .or_insert((offset, offset));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Kafka commits the offset of the next message to read
+    fn commit_list(&self) -> Result<TopicPartitionList> {
+        let mut list = TopicPartitionList::new();
+        for ((topic, partition), (_, last)) in &self.ranges {
+            list.add_partition_offset(topic, *partition, Offset::Offset(last + 1))
+                .context("Failed to build offset commit list")?;
+        }
+        Ok(list)
+    }
+
+    fn rewind_offsets(&self) -> Vec<((String, i32), i64)> {
+        self.ranges
+            .iter()
+            .map(|(tp, (first, _))| (tp.clone(), *first))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_offsets_commit_next_offset() {
+        let mut pending = PendingOffsets::default();
+        pending.track("events", 0, 10);
+        pending.track("events", 0, 12);
+        pending.track("events", 0, 11);
+        pending.track("events", 1, 4);
+
+        let list = pending.commit_list().unwrap();
+        assert_eq!(
+            list.find_partition("events", 0).unwrap().offset(),
+            Offset::Offset(13)
+        );
+        assert_eq!(
+            list.find_partition("events", 1).unwrap().offset(),
+            Offset::Offset(5)
+        );
+
+        let mut rewind = pending.rewind_offsets();
+        rewind.sort();
+        assert_eq!(
+            rewind,
+            vec![(("events".to_string(), 0), 10), (("events".to_string(), 1), 4)]
+        );
+
+        pending.clear();
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_storage_backoff_is_capped() {
+        assert_eq!(storage_backoff(1), Duration::from_millis(200));
+        assert_eq!(storage_backoff(3), Duration::from_millis(800));
+        assert_eq!(storage_backoff(40), MAX_STORAGE_BACKOFF);
+        assert_eq!(storage_backoff(u32::MAX), MAX_STORAGE_BACKOFF);
+    }
+}
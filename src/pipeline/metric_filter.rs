@@ -0,0 +1,336 @@
+//! Metric Allowlist and Denylist
+//!
+//! Not every upstream metric deserves storage. `MetricFilter` applies the
+//! `MetricFilterConfig` rules published by Config-Manager, or loaded from a
+//! local YAML file, to the metric series extracted from each event before they
+//! are aggregated: a series is dropped when it matches a deny rule, or when
+//! allow rules exist and it matches none of them. Dropped samples are counted
+//! per reason, and `preview` reports what a rule set would do to the series
+//! already stored before it is rolled out.
+
+use crate::adapters::config_manager::{ConfigManagerAdapter, MetricFilterConfig, MetricRule};
+use crate::database::MetricSeriesRow;
+use crate::export::prometheus::HubMetrics;
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+/// (including none) and `?` matches exactly one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried against
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` absorb one more character
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Whether a series matches every pattern of a rule
+pub fn rule_matches(rule: &MetricRule, metric_name: &str, tags: &HashMap<String, String>) -> bool {
+    rule.name
+        .as_deref()
+        .map_or(true, |pattern| glob_match(pattern, metric_name))
+        && rule.tags.iter().all(|(key, pattern)| {
+            tags.get(key)
+                .map_or(false, |value| glob_match(pattern, value))
+        })
+}
+
+/// What the rules do with a series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterDecision {
+    Keep,
+    /// Matched a deny rule
+    Denied,
+    /// Allow rules exist and none matched
+    NotAllowed,
+}
+
+impl FilterDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterDecision::Keep => "keep",
+            FilterDecision::Denied => "denied",
+            FilterDecision::NotAllowed => "not_allowed",
+        }
+    }
+}
+
+/// Decide whether a series is stored. Deny rules win over allow rules.
+pub fn decide(
+    config: &MetricFilterConfig,
+    metric_name: &str,
+    tags: &HashMap<String, String>,
+) -> FilterDecision {
+    if config
+        .deny
+        .iter()
+        .any(|rule| rule_matches(rule, metric_name, tags))
+    {
+        FilterDecision::Denied
+    } else if !config.allow.is_empty()
+        && !config
+            .allow
+            .iter()
+            .any(|rule| rule_matches(rule, metric_name, tags))
+    {
+        FilterDecision::NotAllowed
+    } else {
+        FilterDecision::Keep
+    }
+}
+
+/// Stored series a rule set would drop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewedSeries {
+    pub metric_name: String,
+    pub tags: serde_json::Value,
+    pub decision: FilterDecision,
+}
+
+/// Effect of a rule set on a list of series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricFilterPreview {
+    pub series_checked: usize,
+    pub kept: usize,
+    pub dropped: Vec<PreviewedSeries>,
+}
+
+/// Tag values of a stored series as strings
+fn tag_strings(tags: &serde_json::Value) -> HashMap<String, String> {
+    tags.as_object()
+        .map(|object| {
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Apply a rule set to known series without changing the active rules
+pub fn preview(config: &MetricFilterConfig, series: &[MetricSeriesRow]) -> MetricFilterPreview {
+    let dropped: Vec<PreviewedSeries> = series
+        .iter()
+        .filter_map(|row| {
+            let decision = decide(config, &row.metric_name, &tag_strings(&row.tags));
+            (decision != FilterDecision::Keep).then(|| PreviewedSeries {
+                metric_name: row.metric_name.clone(),
+                tags: row.tags.clone(),
+                decision,
+            })
+        })
+        .collect();
+    MetricFilterPreview {
+        series_checked: series.len(),
+        kept: series.len() - dropped.len(),
+        dropped,
+    }
+}
+
+/// Metric allowlist/denylist stage
+pub struct MetricFilter {
+    config: RwLock<MetricFilterConfig>,
+    samples_kept: AtomicU64,
+    samples_denied: AtomicU64,
+    samples_not_allowed: AtomicU64,
+}
+
+impl MetricFilter {
+    pub fn new(config: MetricFilterConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            samples_kept: AtomicU64::new(0),
+            samples_denied: AtomicU64::new(0),
+            samples_not_allowed: AtomicU64::new(0),
+        }
+    }
+
+    /// Active rules
+    pub fn config(&self) -> MetricFilterConfig {
+        self.config.read().clone()
+    }
+
+    /// Replace the active rules
+    pub fn update_config(&self, config: MetricFilterConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Replace the active rules with a YAML rule file, returning the number of rules
+    pub fn load_yaml(&self, yaml: &str) -> Result<usize> {
+        let config: MetricFilterConfig =
+            serde_yaml::from_str(yaml).context("Failed to parse metric filter rules")?;
+        let count = config.allow.len() + config.deny.len();
+        self.update_config(config);
+        Ok(count)
+    }
+
+    /// Pull the latest rules from Config-Manager
+    pub async fn refresh(&self, config_manager: &ConfigManagerAdapter) -> Result<()> {
+        let filters = config_manager
+            .fetch_analytics_parameters()
+            .await?
+            .metric_filters;
+        info!(
+            allow = filters.allow.len(),
+            deny = filters.deny.len(),
+            "Applied metric filter rules"
+        );
+        self.update_config(filters);
+        Ok(())
+    }
+
+    /// Decide for one series, counting the sample
+    pub fn check(&self, metric_name: &str, tags: &HashMap<String, String>) -> FilterDecision {
+        let decision = decide(&self.config.read(), metric_name, tags);
+        let counter = match decision {
+            FilterDecision::Keep => &self.samples_kept,
+            FilterDecision::Denied => &self.samples_denied,
+            FilterDecision::NotAllowed => &self.samples_not_allowed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if decision != FilterDecision::Keep {
+            HubMetrics::global().record_metric_dropped(decision.as_str(), 1);
+        }
+        decision
+    }
+
+    /// Drop the metrics of one event that the rules reject, returning how many were dropped
+    pub fn retain(
+        &self,
+        metrics: &mut Vec<(String, f64)>,
+        tags: &HashMap<String, String>,
+    ) -> usize {
+        let before = metrics.len();
+        metrics.retain(|(metric_name, _)| self.check(metric_name, tags) == FilterDecision::Keep);
+        before - metrics.len()
+    }
+
+    pub fn get_stats(&self) -> MetricFilterStats {
+        let config = self.config.read();
+        MetricFilterStats {
+            allow_rules: config.allow.len(),
+            deny_rules: config.deny.len(),
+            samples_kept: self.samples_kept.load(Ordering::Relaxed),
+            samples_denied: self.samples_denied.load(Ordering::Relaxed),
+            samples_not_allowed: self.samples_not_allowed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Metric filter statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricFilterStats {
+    pub allow_rules: usize,
+    pub deny_rules: usize,
+    pub samples_kept: u64,
+    pub samples_denied: u64,
+    pub samples_not_allowed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn rule(name: Option<&str>, tags: &[(&str, &str)]) -> MetricRule {
+        MetricRule {
+            name: name.map(str::to_string),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("tokens.*", "tokens.prompt"));
+        assert!(glob_match("*latency*", "total_latency_ms"));
+        assert!(glob_match("gpt-?", "gpt-4"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("tokens.*", "cost.tokens"));
+        assert!(!glob_match("gpt-?", "gpt-4o"));
+        assert!(!glob_match("a*b", "acbd"));
+    }
+
+    #[test]
+    fn test_deny_wins_and_allow_restricts() {
+        let config = MetricFilterConfig {
+            allow: vec![rule(Some("*_ms"), &[]), rule(None, &[("team", "search-*")])],
+            deny: vec![rule(Some("debug_*"), &[])],
+        };
+        let filter = MetricFilter::new(config.clone());
+        let tags = |team: &str| HashMap::from([("team".to_string(), team.to_string())]);
+
+        let mut metrics = vec![
+            ("total_latency_ms".to_string(), 1.0),
+            ("debug_latency_ms".to_string(), 1.0),
+            ("tokens_per_second".to_string(), 1.0),
+        ];
+        assert_eq!(filter.retain(&mut metrics, &tags("billing")), 2);
+        assert_eq!(metrics, vec![("total_latency_ms".to_string(), 1.0)]);
+        assert_eq!(
+            filter.check("tokens_per_second", &tags("search-api")),
+            FilterDecision::Keep
+        );
+
+        let stats = filter.get_stats();
+        assert_eq!(stats.samples_kept, 2);
+        assert_eq!(stats.samples_denied, 1);
+        assert_eq!(stats.samples_not_allowed, 1);
+
+        let series = |name: &str, tags: serde_json::Value| MetricSeriesRow {
+            metric_name: name.to_string(),
+            tags,
+            last_seen: Utc::now(),
+        };
+        let result = preview(
+            &config,
+            &[
+                series("total_latency_ms", json!({})),
+                series("debug_latency_ms", json!({ "team": "search-api" })),
+                series("cost_usd", json!({ "team": "billing" })),
+            ],
+        );
+        assert_eq!(result.series_checked, 3);
+        assert_eq!(result.kept, 1);
+        assert_eq!(result.dropped[0].decision, FilterDecision::Denied);
+        assert_eq!(result.dropped[1].decision, FilterDecision::NotAllowed);
+    }
+}
@@ -8,15 +8,39 @@ pub mod processing;
 pub mod storage;
 pub mod cache;
 pub mod stream;
+pub mod self_monitor;
+pub mod heavy_hitters;
+pub mod sampling;
+pub mod config_watcher;
+pub mod sink;
+pub mod hot_cache;
+pub mod dedup;
+pub mod quotas;
+pub mod enrichment;
+pub mod metric_filter;
+pub mod cardinality;
 
 pub use ingestion::EventIngester;
 pub use processing::EventProcessor;
 pub use storage::StorageManager;
 pub use cache::CacheManager;
 pub use stream::StreamManager;
-
+pub use self_monitor::{LifecyclePhase, SelfMetrics, SelfMonitor, SelfMonitorConfig};
+pub use heavy_hitters::{HeavyHitterConfig, HeavyHitterTracker};
+pub use sampling::Sampler;
+pub use config_watcher::{ConfigWatcher, ConfigWatcherConfig};
+pub use sink::{DeliveryReport, DerivedEventSink, PartitionKey, SinkConfig};
+pub use hot_cache::{HotCache, HotCacheConfig};
+pub use dedup::{DedupConfig, Deduplicator};
+pub use quotas::{ModuleQuotaConfig, ModuleQuotas, ThrottleAction};
+pub use enrichment::{TagEnricher, TagEnrichmentConfig};
+pub use metric_filter::{MetricFilter, MetricFilterPreview};
+pub use cardinality::{CardinalityConfig, CardinalityGuard, CardinalityStrategy};
+
+use crate::adapters::config_manager::ConfigManagerAdapter;
 use crate::schemas::events::AnalyticsEvent;
-use crate::database::Database;
+use crate::database::{connect_backend, Database, StorageConfig};
+use crate::export::prometheus::HubMetrics;
 use anyhow::Result;
 use async_trait::async_trait;
 use std::sync::Arc;
@@ -44,6 +68,18 @@ pub struct PipelineConfig {
 
     /// Enable compression
     pub enable_compression: bool,
+
+    /// Self-monitoring settings
+    pub self_monitor: SelfMonitorConfig,
+
+    /// Event store backend (TimescaleDB unless configured otherwise)
+    pub storage: StorageConfig,
+
+    /// Drop redelivered events by `event_id` before storage; disabled when `None`
+    pub dedup: Option<DedupConfig>,
+
+    /// Per-source-module ingestion quotas; disabled when `None`
+    pub module_quotas: Option<ModuleQuotaConfig>,
 }
 
 impl Default for PipelineConfig {
@@ -56,13 +92,16 @@ impl Default for PipelineConfig {
             num_workers: 4,
             buffer_size: 10000,
             enable_compression: true,
+            self_monitor: SelfMonitorConfig::default(),
+            storage: StorageConfig::default(),
+            dedup: None,
+            module_quotas: None,
         }
     }
 }
 
 /// Main pipeline orchestrator
 pub struct Pipeline {
-    #[allow(dead_code)]
     config: PipelineConfig,
     #[allow(dead_code)]
     database: Arc<Database>,
@@ -71,6 +110,7 @@ pub struct Pipeline {
     storage: StorageManager,
     cache: CacheManager,
     stream: StreamManager,
+    self_monitor: Arc<SelfMonitor>,
 }
 
 impl Pipeline {
@@ -91,11 +131,23 @@ impl Pipeline {
             dlq_topic: "llm-analytics-events-dlq".to_string(),
         };
 
-        let ingester = EventIngester::new(ingestion_config, database.clone()).await?;
+        let backend = connect_backend(&config.storage, database.clone()).await?;
+        let mut ingester = EventIngester::new(ingestion_config, backend).await?;
+        if let Some(dedup) = &config.dedup {
+            ingester = ingester.with_dedup(Arc::new(Deduplicator::from_config(dedup).await?));
+        }
         let processor = EventProcessor::new(&config).await?;
         let storage = StorageManager::new(&config).await?;
         let cache = CacheManager::new(&config).await?;
         let stream = StreamManager::new(&config).await?;
+        let self_monitor = Arc::new(SelfMonitor::new(
+            config.self_monitor.clone(),
+            ingester.sender(),
+        ));
+        if let Some(quotas) = &config.module_quotas {
+            let quotas = ModuleQuotas::new(quotas.clone()).with_self_monitor(self_monitor.clone());
+            ingester = ingester.with_quotas(Arc::new(quotas));
+        }
 
         Ok(Self {
             config,
@@ -105,9 +157,35 @@ impl Pipeline {
             storage,
             cache,
             stream,
+            self_monitor,
         })
     }
 
+    /// Normalize and enrich event tags during ingestion
+    pub fn with_tag_enricher(mut self, enricher: Arc<TagEnricher>) -> Self {
+        self.ingester = self.ingester.with_enricher(enricher);
+        self
+    }
+
+    /// Self-monitor publishing the hub's own events into this pipeline
+    pub fn self_monitor(&self) -> Arc<SelfMonitor> {
+        self.self_monitor.clone()
+    }
+
+    /// Configuration watcher reporting reloads through this pipeline's self-monitor
+    pub fn config_watcher(&self, config_manager: Arc<ConfigManagerAdapter>) -> ConfigWatcher {
+        ConfigWatcher::new(
+            ConfigWatcherConfig::from_env(),
+            config_manager,
+            self.self_monitor.clone(),
+        )
+    }
+
+    /// Publish current ingestion throughput as a self-monitoring event
+    pub fn report_self_metrics(&self) -> Result<()> {
+        self.self_monitor.report_ingestion(&self.ingester.get_stats())
+    }
+
     /// Start the pipeline
     pub async fn start(&mut self) -> Result<()> {
         tracing::info!("Starting LLM Analytics Hub pipeline");
@@ -117,12 +195,25 @@ impl Pipeline {
         self.cache.initialize().await?;
         self.stream.initialize().await?;
 
+        self.self_monitor.report_lifecycle(
+            LifecyclePhase::Startup,
+            serde_json::json!({ "num_workers": self.config.num_workers }),
+        )?;
+
         tracing::info!("Pipeline started successfully");
         Ok(())
     }
 
     /// Process a single event
+    #[tracing::instrument(
+        name = "pipeline.event",
+        skip(self, event),
+        fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+    )]
     pub async fn process_event(&mut self, event: AnalyticsEvent) -> Result<()> {
+        crate::telemetry::record_event_context(&event.common);
+        let start = std::time::Instant::now();
+
         // Process the event
         let processed = self.processor.process(event).await?;
 
@@ -135,17 +226,21 @@ impl Pipeline {
         // Publish to stream for real-time consumers
         self.stream.publish(&processed).await?;
 
+        HubMetrics::global().observe_processing("process_event", start.elapsed());
         Ok(())
     }
 
     /// Process a batch of events
+    #[tracing::instrument(name = "pipeline.batch", skip_all, fields(batch_size = events.len()))]
     pub async fn process_batch(&mut self, events: Vec<AnalyticsEvent>) -> Result<()> {
+        let start = std::time::Instant::now();
         let processed = self.processor.process_batch(events).await?;
 
         self.storage.store_batch(&processed).await?;
         self.cache.update_batch(&processed).await?;
         self.stream.publish_batch(&processed).await?;
 
+        HubMetrics::global().observe_processing("process_batch", start.elapsed());
         Ok(())
     }
 
@@ -153,6 +248,13 @@ impl Pipeline {
     pub async fn shutdown(&mut self) -> Result<()> {
         tracing::info!("Shutting down pipeline");
 
+        if let Err(e) = self
+            .self_monitor
+            .report_lifecycle(LifecyclePhase::Shutdown, serde_json::json!({}))
+        {
+            tracing::warn!("Failed to report shutdown event: {}", e);
+        }
+
         self.ingester.shutdown().await?;
         self.processor.shutdown().await?;
         self.storage.shutdown().await?;
@@ -3,10 +3,11 @@
 //! Core event processing logic including validation, enrichment, and transformation.
 
 use crate::schemas::events::AnalyticsEvent;
+use crate::telemetry::record_event_context;
 use anyhow::Result;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, instrument};
 
 use super::{HealthStatus, PipelineComponent, PipelineConfig};
 
@@ -27,7 +28,13 @@ impl EventProcessor {
     }
 
     /// Process a single event
+    #[instrument(
+        name = "pipeline.process",
+        skip(self, event),
+        fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+    )]
     pub async fn process(&self, mut event: AnalyticsEvent) -> Result<AnalyticsEvent> {
+        record_event_context(&event.common);
         debug!("Processing event: {}", event.common.event_id);
 
         // Validate event
@@ -0,0 +1,478 @@
+//! Per-Module Ingestion Quotas
+//!
+//! Every source module shares the ingestion pipeline, so one misbehaving
+//! producer (a runaway Observatory exporter, say) can crowd out the rest. Each
+//! module is given a sustained events-per-second quota enforced with a token
+//! bucket; events over quota are shed, or sampled down when the configured
+//! action is `sample`. The hub's own self-monitoring events are never
+//! throttled.
+//!
+//! When a module starts being throttled, and then at most once per report
+//! interval while it stays over quota, a `throttled` Lifecycle event is emitted
+//! through the self-monitor. Once the module has stayed within its quota for a
+//! full report interval, a `throttle_lifted` event follows.
+
+use crate::export::prometheus::HubMetrics;
+use crate::pipeline::sampling::keep_sampled;
+use crate::pipeline::self_monitor::{LifecyclePhase, SelfMonitor};
+use crate::schemas::events::{AnalyticsEvent, SourceModule};
+use crate::tenancy::TokenBucket;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+/// What happens to events over a module's quota
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleAction {
+    /// Drop every event over quota
+    Shed,
+    /// Keep events over quota at `overflow_sample_rate`
+    #[default]
+    Sample,
+}
+
+impl FromStr for ThrottleAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .with_context(|| format!("Unknown throttle action '{}'", s))
+    }
+}
+
+/// Per-module quota configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleQuotaConfig {
+    /// Sustained events per second for modules without an override; 0 is unlimited
+    pub default_rps: u32,
+    /// Seconds of sustained rate a module may burst above its quota
+    pub burst_secs: f64,
+    /// Quotas keyed by source module name (e.g. `llm-observatory`)
+    pub overrides: HashMap<String, u32>,
+    pub action: ThrottleAction,
+    /// Rate events over quota are kept at when sampling
+    pub overflow_sample_rate: f64,
+    /// Minimum interval between throttling events for one module
+    pub report_interval_secs: u64,
+}
+
+impl Default for ModuleQuotaConfig {
+    fn default() -> Self {
+        Self {
+            default_rps: 10_000,
+            burst_secs: 2.0,
+            overrides: HashMap::new(),
+            action: ThrottleAction::Sample,
+            overflow_sample_rate: 0.1,
+            report_interval_secs: 60,
+        }
+    }
+}
+
+impl ModuleQuotaConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let overrides = match std::env::var("MODULE_QUOTA_OVERRIDES") {
+            Ok(spec) => Self::parse_overrides(&spec)?,
+            Err(_) => defaults.overrides,
+        };
+        Ok(Self {
+            default_rps: std::env::var("MODULE_QUOTA_RPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.default_rps),
+            burst_secs: std::env::var("MODULE_QUOTA_BURST_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.burst_secs),
+            overrides,
+            action: match std::env::var("MODULE_QUOTA_ACTION") {
+                Ok(v) => v.parse()?,
+                Err(_) => defaults.action,
+            },
+            overflow_sample_rate: std::env::var("MODULE_QUOTA_SAMPLE_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.overflow_sample_rate),
+            report_interval_secs: std::env::var("MODULE_QUOTA_REPORT_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.report_interval_secs),
+        })
+    }
+
+    /// Parse `source_module:events_per_sec` entries separated by commas
+    pub fn parse_overrides(spec: &str) -> Result<HashMap<String, u32>> {
+        let mut overrides = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((module, rps)) = entry.split_once(':') else {
+                anyhow::bail!("Module quota overrides must be source_module:events_per_sec");
+            };
+            let module: SourceModule =
+                serde_json::from_value(serde_json::Value::String(module.to_string()))
+                    .with_context(|| format!("Unknown source module '{}'", module))?;
+            let rps = rps
+                .parse()
+                .with_context(|| format!("Invalid quota for module {}", module.as_str()))?;
+            overrides.insert(module.as_str().to_string(), rps);
+        }
+        Ok(overrides)
+    }
+
+    pub fn quota_for(&self, source_module: &str) -> u32 {
+        self.overrides
+            .get(source_module)
+            .copied()
+            .unwrap_or(self.default_rps)
+    }
+
+    fn report_interval(&self) -> Duration {
+        Duration::seconds(self.report_interval_secs as i64)
+    }
+}
+
+/// Throttling counts for one source module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleCounts {
+    /// Events that arrived while the module was over quota
+    pub over_quota: u64,
+    /// Events over quota that were shed or sampled out
+    pub dropped: u64,
+}
+
+/// Details carried by a throttling Lifecycle event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThrottleReport {
+    pub source_module: String,
+    pub quota_rps: u32,
+    pub action: ThrottleAction,
+    pub throttled_since: DateTime<Utc>,
+    /// Counts since the previous report for this module
+    pub since_last_report: ThrottleCounts,
+}
+
+#[derive(Debug)]
+struct ModuleState {
+    bucket: TokenBucket,
+    throttled_since: Option<DateTime<Utc>>,
+    last_over_quota: Option<DateTime<Utc>>,
+    last_report: Option<DateTime<Utc>>,
+    since_report: ThrottleCounts,
+    totals: ThrottleCounts,
+}
+
+/// Per-module ingestion throttle
+pub struct ModuleQuotas {
+    config: ModuleQuotaConfig,
+    modules: DashMap<&'static str, Mutex<ModuleState>>,
+    self_monitor: Option<Arc<SelfMonitor>>,
+}
+
+impl ModuleQuotas {
+    pub fn new(config: ModuleQuotaConfig) -> Self {
+        Self {
+            config,
+            modules: DashMap::new(),
+            self_monitor: None,
+        }
+    }
+
+    /// Report throttling as Lifecycle events through the self-monitor
+    pub fn with_self_monitor(mut self, self_monitor: Arc<SelfMonitor>) -> Self {
+        self.self_monitor = Some(self_monitor);
+        self
+    }
+
+    /// Drop the events over their module's quota, returning how many were dropped
+    pub fn throttle(&self, events: &mut Vec<AnalyticsEvent>) -> usize {
+        self.throttle_at(events, Utc::now())
+    }
+
+    /// `throttle` as of `now`
+    pub fn throttle_at(&self, events: &mut Vec<AnalyticsEvent>, now: DateTime<Utc>) -> usize {
+        let before = events.len();
+        let mut dropped: HashMap<&'static str, u64> = HashMap::new();
+        events.retain_mut(|event| {
+            let admitted = self.admit_at(event, now);
+            if !admitted {
+                *dropped
+                    .entry(event.common.source_module.as_str())
+                    .or_insert(0) += 1;
+            }
+            admitted
+        });
+
+        let hub_metrics = HubMetrics::global();
+        for (module, n) in dropped {
+            hub_metrics.record_throttled(module, n);
+        }
+        before - events.len()
+    }
+
+    /// Decide whether to keep one event, counting it as throttled when dropped
+    pub fn admit(&self, event: &mut AnalyticsEvent) -> bool {
+        let kept = self.admit_at(event, Utc::now());
+        if !kept {
+            HubMetrics::global().record_throttled(event.common.source_module.as_str(), 1);
+        }
+        kept
+    }
+
+    /// Decide whether to keep one event as of `now`
+    pub fn admit_at(&self, event: &mut AnalyticsEvent, now: DateTime<Utc>) -> bool {
+        if event.common.source_module == SourceModule::LlmAnalyticsHub {
+            return true;
+        }
+        let module = event.common.source_module.as_str();
+        let quota = self.config.quota_for(module);
+        if quota == 0 {
+            return true;
+        }
+        let rate = quota as f64;
+        let capacity = rate * self.config.burst_secs.max(1.0);
+
+        let entry = self.modules.entry(module).or_insert_with(|| {
+            Mutex::new(ModuleState {
+                bucket: TokenBucket::new(capacity, now),
+                throttled_since: None,
+                last_over_quota: None,
+                last_report: None,
+                since_report: ThrottleCounts::default(),
+                totals: ThrottleCounts::default(),
+            })
+        });
+        let mut state = entry.lock();
+
+        if state.bucket.try_take(1.0, rate, capacity, now) {
+            let quiet = !matches!(state.last_over_quota,
+                Some(last) if now - last < self.config.report_interval());
+            if quiet {
+                if let Some(throttled_since) = state.throttled_since.take() {
+                    let report = self.take_report(&mut state, module, quota, throttled_since, now);
+                    drop(state);
+                    drop(entry);
+                    self.report(LifecyclePhase::ThrottleLifted, report);
+                }
+            }
+            return true;
+        }
+
+        let kept = match self.config.action {
+            ThrottleAction::Shed => false,
+            ThrottleAction::Sample => keep_sampled(event, self.config.overflow_sample_rate),
+        };
+        state.last_over_quota = Some(now);
+        state.since_report.over_quota += 1;
+        state.totals.over_quota += 1;
+        if !kept {
+            state.since_report.dropped += 1;
+            state.totals.dropped += 1;
+        }
+
+        let due = match (state.throttled_since, state.last_report) {
+            (None, _) => true,
+            (Some(_), Some(last)) => now - last >= self.config.report_interval(),
+            (Some(_), None) => false,
+        };
+        if due {
+            let throttled_since = *state.throttled_since.get_or_insert(now);
+            let report = self.take_report(&mut state, module, quota, throttled_since, now);
+            drop(state);
+            drop(entry);
+            warn!(
+                source_module = module,
+                quota_rps = quota,
+                "Source module exceeded its ingestion quota"
+            );
+            self.report(LifecyclePhase::Throttled, report);
+        }
+        kept
+    }
+
+    fn take_report(
+        &self,
+        state: &mut ModuleState,
+        module: &str,
+        quota: u32,
+        throttled_since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> ThrottleReport {
+        state.last_report = Some(now);
+        ThrottleReport {
+            source_module: module.to_string(),
+            quota_rps: quota,
+            action: self.config.action,
+            throttled_since,
+            since_last_report: std::mem::take(&mut state.since_report),
+        }
+    }
+
+    fn report(&self, phase: LifecyclePhase, report: ThrottleReport) {
+        let Some(self_monitor) = &self.self_monitor else {
+            return;
+        };
+        let details = serde_json::to_value(&report).unwrap_or_default();
+        if let Err(e) = self_monitor.report_lifecycle(phase, details) {
+            warn!("Failed to report ingestion throttling: {}", e);
+        }
+    }
+
+    /// Modules currently over quota
+    pub fn throttled_modules(&self) -> Vec<String> {
+        let mut modules: Vec<String> = self
+            .modules
+            .iter()
+            .filter(|entry| entry.value().lock().throttled_since.is_some())
+            .map(|entry| entry.key().to_string())
+            .collect();
+        modules.sort();
+        modules
+    }
+
+    /// Throttling counts per source module, sorted by module
+    pub fn get_stats(&self) -> Vec<(String, ThrottleCounts)> {
+        let mut stats: Vec<(String, ThrottleCounts)> = self
+            .modules
+            .iter()
+            .map(|entry| (entry.key().to_string(), entry.value().lock().totals))
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::sampling::sampling_rate_of;
+    use crate::pipeline::self_monitor::SelfMonitorConfig;
+    use crate::schemas::events::{
+        CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    };
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    fn event(source_module: SourceModule) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity: Severity::Info,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    fn config(action: ThrottleAction) -> ModuleQuotaConfig {
+        ModuleQuotaConfig {
+            default_rps: 100,
+            burst_secs: 1.0,
+            overrides: ModuleQuotaConfig::parse_overrides("llm-observatory:10").unwrap(),
+            action,
+            overflow_sample_rate: 0.5,
+            report_interval_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_shed_only_affects_module_over_quota() {
+        let quotas = ModuleQuotas::new(config(ThrottleAction::Shed));
+        let now = Utc::now();
+
+        let mut batch: Vec<AnalyticsEvent> = (0..50)
+            .flat_map(|_| {
+                [
+                    event(SourceModule::LlmObservatory),
+                    event(SourceModule::LlmSentinel),
+                    event(SourceModule::LlmAnalyticsHub),
+                ]
+            })
+            .collect();
+        assert_eq!(quotas.throttle_at(&mut batch, now), 40);
+
+        let kept = |module: SourceModule| {
+            batch
+                .iter()
+                .filter(|e| e.common.source_module == module)
+                .count()
+        };
+        assert_eq!(kept(SourceModule::LlmObservatory), 10);
+        assert_eq!(kept(SourceModule::LlmSentinel), 50);
+        assert_eq!(kept(SourceModule::LlmAnalyticsHub), 50);
+        assert_eq!(quotas.throttled_modules(), vec!["llm-observatory"]);
+
+        // The bucket refills at the module's rate
+        let mut later = vec![event(SourceModule::LlmObservatory)];
+        assert_eq!(
+            quotas.throttle_at(&mut later, now + Duration::seconds(1)),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sampling_and_lifecycle_reports() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let monitor = Arc::new(SelfMonitor::new(SelfMonitorConfig::default(), tx));
+        let quotas = ModuleQuotas::new(config(ThrottleAction::Sample)).with_self_monitor(monitor);
+        let now = Utc::now();
+
+        let mut batch: Vec<AnalyticsEvent> = (0..1010)
+            .map(|_| event(SourceModule::LlmObservatory))
+            .collect();
+        let dropped = quotas.throttle_at(&mut batch, now);
+        assert!(dropped > 350 && dropped < 650, "dropped {}", dropped);
+        assert_eq!(
+            batch.iter().filter(|e| sampling_rate_of(e) == 0.5).count(),
+            1000 - dropped
+        );
+
+        let throttled = rx.try_recv().unwrap();
+        assert_eq!(throttled.common.event_type, EventType::Lifecycle);
+        assert_eq!(throttled.common.severity, Severity::Warning);
+        let EventPayload::Custom(payload) = &throttled.payload else {
+            panic!("expected a custom payload");
+        };
+        assert_eq!(payload.data["phase"], "throttled");
+        assert_eq!(payload.data["details"]["source_module"], "llm-observatory");
+        assert_eq!(
+            payload.data["details"]["since_last_report"]["over_quota"],
+            1
+        );
+        assert!(rx.try_recv().is_err());
+
+        // Within quota for a full report interval lifts the throttle
+        let mut quiet = vec![event(SourceModule::LlmObservatory)];
+        quotas.throttle_at(&mut quiet, now + Duration::seconds(61));
+        let lifted = rx.try_recv().unwrap();
+        let EventPayload::Custom(payload) = &lifted.payload else {
+            panic!("expected a custom payload");
+        };
+        assert_eq!(payload.data["phase"], "throttle_lifted");
+        assert_eq!(
+            payload.data["details"]["since_last_report"]["over_quota"],
+            999
+        );
+        assert!(quotas.throttled_modules().is_empty());
+
+        let stats = quotas.get_stats();
+        assert_eq!(stats[0].1.over_quota, 1000);
+        assert_eq!(stats[0].1.dropped, dropped as u64);
+    }
+}
@@ -0,0 +1,268 @@
+//! Ingestion Sampling
+//!
+//! Applies the `SamplingConfig` published by Config-Manager to incoming events.
+//! Telemetry is sampled at `default_rate`, dropping to `high_volume_rate` while
+//! the observed ingest rate exceeds `high_volume_threshold_rps`. Error and
+//! Critical events are always kept when `preserve_errors` is set, and events of
+//! other types are never sampled.
+//!
+//! Kept events carry their sampling rate in the `sampling_rate` tag so that
+//! downstream aggregates can report the effective rate they were built from.
+
+use crate::adapters::config_manager::{ConfigManagerAdapter, SamplingConfig};
+use crate::schemas::events::{AnalyticsEvent, EventType, Severity};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+
+/// Tag recording the rate an event was sampled at
+pub const SAMPLING_RATE_TAG: &str = "sampling_rate";
+
+const RANDOM_BITS_MASK: u64 = (1 << 62) - 1;
+
+/// Sampling rate recorded on an event, 1.0 if it was not sampled
+pub fn sampling_rate_of(event: &AnalyticsEvent) -> f64 {
+    sampling_rate_from_tags(&event.common.tags)
+}
+
+/// Sampling rate recorded in a tag set, 1.0 if absent or invalid
+pub fn sampling_rate_from_tags(tags: &HashMap<String, String>) -> f64 {
+    tags.get(SAMPLING_RATE_TAG)
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|rate| *rate > 0.0 && *rate <= 1.0)
+        .unwrap_or(1.0)
+}
+
+/// Keep an event with probability `rate`, recording the rate on kept events.
+///
+/// The decision is made on the event ID so every replica, and every stage that
+/// samples the same event, makes the same choice: an event sampled twice is
+/// kept at the lower of the two rates, which is the rate recorded.
+pub fn keep_sampled(event: &mut AnalyticsEvent, rate: f64) -> bool {
+    // The top two bits of the low half hold the UUID variant
+    let bits = event.common.event_id.as_u128() as u64 & RANDOM_BITS_MASK;
+    if bits as f64 / (RANDOM_BITS_MASK as f64 + 1.0) >= rate {
+        return false;
+    }
+    let recorded = rate.min(sampling_rate_of(event));
+    event
+        .common
+        .tags
+        .insert(SAMPLING_RATE_TAG.to_string(), recorded.to_string());
+    true
+}
+
+/// Counts events per wall-clock second
+#[derive(Debug, Default)]
+struct RateMeter {
+    second: i64,
+    current: u64,
+    previous: u64,
+}
+
+impl RateMeter {
+    /// Record one event and return the current rate estimate
+    fn record(&mut self, now: DateTime<Utc>) -> u64 {
+        let second = now.timestamp();
+        if second != self.second {
+            // Only carry the count over if the previous second was contiguous
+            self.previous = if second == self.second + 1 {
+                self.current
+            } else {
+                0
+            };
+            self.second = second;
+            self.current = 0;
+        }
+        self.current += 1;
+        self.previous.max(self.current)
+    }
+}
+
+/// Ingestion sampling stage
+pub struct Sampler {
+    config: RwLock<SamplingConfig>,
+    meter: Mutex<RateMeter>,
+    events_seen: AtomicU64,
+    events_kept: AtomicU64,
+    errors_preserved: AtomicU64,
+}
+
+impl Sampler {
+    pub fn new(config: SamplingConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            meter: Mutex::new(RateMeter::default()),
+            events_seen: AtomicU64::new(0),
+            events_kept: AtomicU64::new(0),
+            errors_preserved: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the active sampling configuration
+    pub fn update_config(&self, config: SamplingConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Pull the latest sampling configuration from Config-Manager
+    pub async fn refresh(&self, config_manager: &ConfigManagerAdapter) -> Result<()> {
+        let parameters = config_manager.fetch_analytics_parameters().await?;
+        let sampling = parameters.sampling;
+        info!(
+            enabled = sampling.enabled,
+            default_rate = sampling.default_rate,
+            high_volume_rate = sampling.high_volume_rate,
+            "Applied sampling configuration"
+        );
+        self.update_config(sampling);
+        Ok(())
+    }
+
+    /// Decide whether to keep an event, tagging it with its sampling rate
+    pub fn sample(&self, event: &mut AnalyticsEvent) -> bool {
+        self.sample_at(event, Utc::now())
+    }
+
+    /// Sampling decision as of `now`
+    pub fn sample_at(&self, event: &mut AnalyticsEvent, now: DateTime<Utc>) -> bool {
+        self.events_seen.fetch_add(1, Ordering::Relaxed);
+        let rps = self.meter.lock().record(now);
+        let config = self.config.read().clone();
+
+        let rate = if !config.enabled || event.common.event_type != EventType::Telemetry {
+            1.0
+        } else if config.preserve_errors && event.common.severity >= Severity::Error {
+            self.errors_preserved.fetch_add(1, Ordering::Relaxed);
+            1.0
+        } else if rps > config.high_volume_threshold_rps {
+            config.high_volume_rate
+        } else {
+            config.default_rate
+        }
+        .clamp(0.0, 1.0);
+
+        if rate < 1.0 && !keep_sampled(event, rate) {
+            return false;
+        }
+        self.events_kept.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    pub fn get_stats(&self) -> SamplerStats {
+        let events_seen = self.events_seen.load(Ordering::Relaxed);
+        let events_kept = self.events_kept.load(Ordering::Relaxed);
+        SamplerStats {
+            events_seen,
+            events_kept,
+            errors_preserved: self.errors_preserved.load(Ordering::Relaxed),
+            effective_rate: if events_seen == 0 {
+                1.0
+            } else {
+                events_kept as f64 / events_seen as f64
+            },
+        }
+    }
+}
+
+/// Sampler statistics
+#[derive(Debug, Clone)]
+pub struct SamplerStats {
+    pub events_seen: u64,
+    pub events_kept: u64,
+    pub errors_preserved: u64,
+    pub effective_rate: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, EventPayload, LatencyMetrics, SourceModule, TelemetryPayload,
+    };
+    use uuid::Uuid;
+
+    fn config(enabled: bool) -> SamplingConfig {
+        SamplingConfig {
+            enabled,
+            default_rate: 1.0,
+            high_volume_rate: 0.1,
+            high_volume_threshold_rps: 100,
+            preserve_errors: true,
+        }
+    }
+
+    fn event(severity: Severity) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Telemetry(TelemetryPayload::Latency(LatencyMetrics {
+                model_id: "gpt-4".to_string(),
+                request_id: "req".to_string(),
+                total_latency_ms: 100.0,
+                ttft_ms: None,
+                tokens_per_second: None,
+                breakdown: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_disabled_keeps_everything() {
+        let sampler = Sampler::new(config(false));
+        let now = Utc::now();
+        for _ in 0..500 {
+            let mut e = event(Severity::Info);
+            assert!(sampler.sample_at(&mut e, now));
+            assert!(!e.common.tags.contains_key(SAMPLING_RATE_TAG));
+        }
+    }
+
+    #[test]
+    fn test_high_volume_samples_but_preserves_errors() {
+        let sampler = Sampler::new(config(true));
+        let now = Utc::now();
+
+        let mut kept = 0;
+        for i in 0..2000 {
+            let mut e = event(Severity::Info);
+            if sampler.sample_at(&mut e, now) {
+                kept += 1;
+                let expected = if i < 100 { 1.0 } else { 0.1 };
+                assert_eq!(sampling_rate_of(&e), expected);
+            }
+        }
+        // First 100 pass at the default rate, the rest at roughly 10%
+        assert!(kept > 150 && kept < 400, "kept {}", kept);
+
+        for _ in 0..100 {
+            let mut e = event(Severity::Error);
+            assert!(sampler.sample_at(&mut e, now));
+            assert_eq!(sampling_rate_of(&e), 1.0);
+        }
+        assert_eq!(sampler.get_stats().errors_preserved, 100);
+    }
+
+    #[test]
+    fn test_rate_meter_resets_after_gap() {
+        let mut meter = RateMeter::default();
+        let now = Utc::now();
+        for _ in 0..50 {
+            meter.record(now);
+        }
+        assert_eq!(meter.record(now + chrono::Duration::seconds(1)), 51);
+        assert_eq!(meter.record(now + chrono::Duration::seconds(5)), 1);
+    }
+}
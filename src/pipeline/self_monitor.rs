@@ -0,0 +1,488 @@
+//! Self-Monitoring
+//!
+//! Publishes the hub's own lifecycle transitions and internal health metrics as
+//! `LlmAnalyticsHub` analytics events into the normal ingestion pipeline.
+//!
+//! Services report periodic samples by implementing `SelfMetrics` and
+//! spawning the monitor, which reports them every `interval_secs`.
+
+use crate::adapters::{AdapterHealth, AdapterManager};
+use crate::analytics::aggregation_engine::AggregationEngine;
+use crate::pipeline::ingestion::IngestionStats;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Custom payload type for lifecycle events
+pub const LIFECYCLE_EVENT_TYPE: &str = "hub.lifecycle";
+
+/// Custom payload type for ingestion throughput samples
+pub const INGESTION_THROUGHPUT_EVENT_TYPE: &str = "hub.ingestion_throughput";
+
+/// Custom payload type for aggregation lag samples
+pub const AGGREGATION_LAG_EVENT_TYPE: &str = "hub.aggregation_lag";
+
+/// Custom payload type for adapter health samples
+pub const ADAPTER_HEALTH_EVENT_TYPE: &str = "hub.adapter_health";
+
+/// Hub lifecycle phases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecyclePhase {
+    Startup,
+    Shutdown,
+    ConfigReload,
+    /// A source module exceeded its ingestion quota
+    Throttled,
+    /// A throttled source module is back within its quota
+    ThrottleLifted,
+}
+
+impl LifecyclePhase {
+    fn severity(&self) -> Severity {
+        match self {
+            LifecyclePhase::Throttled => Severity::Warning,
+            _ => Severity::Info,
+        }
+    }
+}
+
+/// Self-monitoring configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfMonitorConfig {
+    /// Whether self-monitoring events are emitted at all
+    pub enabled: bool,
+    /// Interval between periodic metric samples
+    pub interval_secs: u64,
+    /// Environment stamped on emitted events
+    pub environment: String,
+}
+
+impl Default for SelfMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl SelfMonitorConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("SELF_MONITOR_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(defaults.enabled),
+            interval_secs: std::env::var("SELF_MONITOR_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+            environment: defaults.environment,
+        }
+    }
+}
+
+/// Samples a service reports through its self-monitor on each interval
+#[async_trait]
+pub trait SelfMetrics: Send + Sync {
+    async fn report(&self, monitor: &SelfMonitor) -> Result<()>;
+}
+
+#[async_trait]
+impl SelfMetrics for AdapterManager {
+    async fn report(&self, monitor: &SelfMonitor) -> Result<()> {
+        monitor.report_adapter_health(&self.health_check_all().await)
+    }
+}
+
+#[async_trait]
+impl SelfMetrics for AggregationEngine {
+    async fn report(&self, monitor: &SelfMonitor) -> Result<()> {
+        match self.aggregation_lag().await {
+            Some(lag) => monitor.report_aggregation_lag(lag),
+            // Nothing has been aggregated yet
+            None => Ok(()),
+        }
+    }
+}
+
+/// Emits self-monitoring events into the pipeline
+pub struct SelfMonitor {
+    config: SelfMonitorConfig,
+    sink: mpsc::Sender<AnalyticsEvent>,
+    instance_id: Uuid,
+    started_at: Instant,
+    last_ingestion_sample: Mutex<Option<(Instant, u64)>>,
+    events_emitted: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl SelfMonitor {
+    /// Create a self-monitor publishing into the given pipeline channel
+    pub fn new(config: SelfMonitorConfig, sink: mpsc::Sender<AnalyticsEvent>) -> Self {
+        Self {
+            config,
+            sink,
+            instance_id: Uuid::new_v4(),
+            started_at: Instant::now(),
+            last_ingestion_sample: Mutex::new(None),
+            events_emitted: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Unique identifier of this hub instance
+    pub fn instance_id(&self) -> Uuid {
+        self.instance_id
+    }
+
+    /// Whether self-monitoring events are emitted
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    // ========== Event Builders ==========
+
+    fn build_event(
+        &self,
+        event_type: EventType,
+        severity: Severity,
+        custom_type: &str,
+        data: serde_json::Value,
+    ) -> AnalyticsEvent {
+        let mut tags = HashMap::new();
+        tags.insert("instance_id".to_string(), self.instance_id.to_string());
+        tags.insert("version".to_string(), crate::VERSION.to_string());
+
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity,
+                environment: self.config.environment.clone(),
+                tags,
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: custom_type.to_string(),
+                data,
+            }),
+        }
+    }
+
+    /// Build a lifecycle event
+    pub fn lifecycle_event(&self, phase: LifecyclePhase, details: serde_json::Value) -> AnalyticsEvent {
+        self.build_event(
+            EventType::Lifecycle,
+            phase.severity(),
+            LIFECYCLE_EVENT_TYPE,
+            serde_json::json!({
+                "phase": phase,
+                "uptime_seconds": self.started_at.elapsed().as_secs(),
+                "details": details,
+            }),
+        )
+    }
+
+    /// Build an ingestion throughput event from the ingester's counters
+    ///
+    /// The interval rate is measured against the previous sample taken by this monitor.
+    pub fn ingestion_event(&self, stats: &IngestionStats) -> AnalyticsEvent {
+        let now = Instant::now();
+        let interval_rate = {
+            let mut last = self
+                .last_ingestion_sample
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let rate = last.and_then(|(at, processed)| {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                (elapsed > 0.0)
+                    .then(|| stats.events_processed.saturating_sub(processed) as f64 / elapsed)
+            });
+            *last = Some((now, stats.events_processed));
+            rate
+        };
+
+        let errors = stats.deserialization_errors
+            + stats.storage_errors
+            + stats.processing_errors
+            + stats.kafka_errors;
+        let severity = if errors > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        };
+
+        self.build_event(
+            EventType::Telemetry,
+            severity,
+            INGESTION_THROUGHPUT_EVENT_TYPE,
+            serde_json::json!({
+                "events_processed": stats.events_processed,
+                "events_stored": stats.events_stored,
+                "events_per_second": interval_rate.unwrap_or(stats.avg_throughput),
+                "avg_events_per_second": stats.avg_throughput,
+                "errors": {
+                    "deserialization": stats.deserialization_errors,
+                    "storage": stats.storage_errors,
+                    "processing": stats.processing_errors,
+                    "kafka": stats.kafka_errors,
+                },
+            }),
+        )
+    }
+
+    /// Build an aggregation lag event
+    pub fn aggregation_lag_event(&self, lag: chrono::Duration) -> AnalyticsEvent {
+        let lag_ms = lag.num_milliseconds().max(0);
+        let severity = if lag_ms > self.config.interval_secs as i64 * 5_000 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        };
+
+        self.build_event(
+            EventType::Telemetry,
+            severity,
+            AGGREGATION_LAG_EVENT_TYPE,
+            serde_json::json!({ "lag_ms": lag_ms }),
+        )
+    }
+
+    /// Build an adapter health event
+    pub fn adapter_health_event(&self, health: &AdapterHealth) -> AnalyticsEvent {
+        let severity = if !health.is_healthy {
+            Severity::Error
+        } else if health.is_degraded() {
+            Severity::Warning
+        } else {
+            Severity::Info
+        };
+
+        self.build_event(
+            EventType::Telemetry,
+            severity,
+            ADAPTER_HEALTH_EVENT_TYPE,
+            serde_json::to_value(health).unwrap_or_default(),
+        )
+    }
+
+    // ========== Emission ==========
+
+    /// Emit an event into the pipeline without blocking ingestion
+    ///
+    /// Events are dropped (and counted) when the pipeline buffer is full.
+    pub fn emit(&self, event: AnalyticsEvent) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        match self.sink.try_send(event) {
+            Ok(()) => {
+                self.events_emitted.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("Pipeline buffer full, dropping self-monitoring event");
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                anyhow::bail!("Pipeline channel closed")
+            }
+        }
+    }
+
+    /// Report a lifecycle transition
+    pub fn report_lifecycle(&self, phase: LifecyclePhase, details: serde_json::Value) -> Result<()> {
+        debug!(?phase, "Reporting hub lifecycle event");
+        self.emit(self.lifecycle_event(phase, details))
+    }
+
+    /// Report ingestion throughput
+    pub fn report_ingestion(&self, stats: &IngestionStats) -> Result<()> {
+        self.emit(self.ingestion_event(stats))
+    }
+
+    /// Report aggregation lag
+    pub fn report_aggregation_lag(&self, lag: chrono::Duration) -> Result<()> {
+        self.emit(self.aggregation_lag_event(lag))
+    }
+
+    /// Report the health of each adapter
+    pub fn report_adapter_health(&self, health: &[AdapterHealth]) -> Result<()> {
+        for h in health {
+            self.emit(self.adapter_health_event(h))?;
+        }
+        Ok(())
+    }
+
+    /// Report `metrics` every `interval_secs` until the task is aborted
+    pub fn spawn(self: Arc<Self>, metrics: Arc<dyn SelfMetrics>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(async move {
+            if !self.config.enabled {
+                return;
+            }
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = metrics.report(&self).await {
+                    warn!("Failed to report self-monitoring metrics: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Get self-monitoring statistics
+    pub fn get_stats(&self) -> SelfMonitorStats {
+        SelfMonitorStats {
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Self-monitoring statistics snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfMonitorStats {
+    pub events_emitted: u64,
+    pub events_dropped: u64,
+    pub uptime_seconds: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(processed: u64, errors: u64) -> IngestionStats {
+        IngestionStats {
+            messages_received: processed,
+            messages_sent: 0,
+            events_processed: processed,
+            events_stored: processed,
+            deserialization_errors: errors,
+            storage_errors: 0,
+            processing_errors: 0,
+            kafka_errors: 0,
+            storage_retries: 0,
+            duplicates_skipped: 0,
+            duplicates_dropped: 0,
+            events_throttled: 0,
+            offset_commits: 0,
+            commit_errors: 0,
+            avg_throughput: 10.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_event_enters_pipeline() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let monitor = SelfMonitor::new(SelfMonitorConfig::default(), tx);
+
+        monitor
+            .report_lifecycle(LifecyclePhase::Startup, serde_json::json!({}))
+            .unwrap();
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.common.source_module, SourceModule::LlmAnalyticsHub);
+        assert_eq!(event.common.event_type, EventType::Lifecycle);
+        match event.payload {
+            EventPayload::Custom(payload) => {
+                assert_eq!(payload.custom_type, LIFECYCLE_EVENT_TYPE);
+                assert_eq!(payload.data["phase"], "startup");
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ingestion_errors_raise_severity() {
+        let (tx, _rx) = mpsc::channel(8);
+        let monitor = SelfMonitor::new(SelfMonitorConfig::default(), tx);
+
+        assert_eq!(monitor.ingestion_event(&stats(100, 0)).common.severity, Severity::Info);
+        assert_eq!(monitor.ingestion_event(&stats(200, 3)).common.severity, Severity::Warning);
+    }
+
+    #[tokio::test]
+    async fn test_full_buffer_drops_instead_of_blocking() {
+        let (tx, _rx) = mpsc::channel(1);
+        let monitor = SelfMonitor::new(SelfMonitorConfig::default(), tx);
+
+        let health = vec![
+            AdapterHealth::healthy("observatory", 5),
+            AdapterHealth::unhealthy("costops", "timeout"),
+        ];
+        monitor.report_adapter_health(&health).unwrap();
+
+        let stats = monitor.get_stats();
+        assert_eq!(stats.events_emitted, 1);
+        assert_eq!(stats.events_dropped, 1);
+    }
+
+    struct FixedLag;
+
+    #[async_trait]
+    impl SelfMetrics for FixedLag {
+        async fn report(&self, monitor: &SelfMonitor) -> Result<()> {
+            monitor.report_aggregation_lag(chrono::Duration::seconds(2))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawned_monitor_reports_samples() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let config = SelfMonitorConfig {
+            interval_secs: 1,
+            ..Default::default()
+        };
+        let task = Arc::new(SelfMonitor::new(config, tx)).spawn(Arc::new(FixedLag));
+
+        let event = rx.recv().await.unwrap();
+        task.abort();
+        match event.payload {
+            EventPayload::Custom(payload) => {
+                assert_eq!(payload.custom_type, AGGREGATION_LAG_EVENT_TYPE);
+                assert_eq!(payload.data["lag_ms"], 2000);
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_monitor_emits_nothing() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let config = SelfMonitorConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let monitor = SelfMonitor::new(config, tx);
+
+        monitor
+            .report_aggregation_lag(chrono::Duration::seconds(5))
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+}
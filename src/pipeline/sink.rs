@@ -0,0 +1,416 @@
+//! Derived Event Sink
+//!
+//! Publishes analytics produced by the hub back onto Kafka: detected anomalies
+//! to `llm-alerts`, rollup summaries to `llm-aggregated-metrics`, and
+//! correlation findings to `llm-analytics`. Production is idempotent and every
+//! send waits for its delivery report so callers learn the partition/offset.
+
+use crate::models::correlation::{AnomalyEvent, EventCorrelation};
+use crate::models::metrics::AggregatedMetric;
+use crate::schemas::events::TENANT_TAG;
+use anyhow::{Context, Result};
+use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Header naming the kind of derived event carried by a record
+pub const KIND_HEADER: &str = "derived-kind";
+
+/// How derived records are keyed (and therefore partitioned)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionKey {
+    /// Metric name for anomalies and aggregates, correlation id for findings
+    #[default]
+    Entity,
+    /// Tenant of the record, falling back to the entity key when untagged
+    Tenant,
+    /// Emitting source module, falling back to the entity key when unknown
+    SourceModule,
+    /// No key; records are spread by the partitioner
+    None,
+}
+
+impl FromStr for PartitionKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow::anyhow!("Unknown partition key strategy: {}", s))
+    }
+}
+
+/// Derived event sink configuration
+#[derive(Debug, Clone)]
+pub struct SinkConfig {
+    pub kafka_brokers: Vec<String>,
+    pub alerts_topic: String,
+    pub aggregates_topic: String,
+    pub analytics_topic: String,
+    pub client_id: String,
+    pub partition_key: PartitionKey,
+    /// How long a send may wait for its delivery report
+    pub delivery_timeout_ms: u64,
+}
+
+impl Default for SinkConfig {
+    fn default() -> Self {
+        Self {
+            kafka_brokers: vec!["localhost:9092".to_string()],
+            alerts_topic: "llm-alerts".to_string(),
+            aggregates_topic: "llm-aggregated-metrics".to_string(),
+            analytics_topic: "llm-analytics".to_string(),
+            client_id: "llm-analytics-hub-sink".to_string(),
+            partition_key: PartitionKey::default(),
+            delivery_timeout_ms: 5000,
+        }
+    }
+}
+
+impl SinkConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            kafka_brokers: std::env::var("KAFKA_BROKERS")
+                .map(|v| v.split(',').map(|b| b.trim().to_string()).collect())
+                .unwrap_or(defaults.kafka_brokers),
+            alerts_topic: std::env::var("SINK_ALERTS_TOPIC").unwrap_or(defaults.alerts_topic),
+            aggregates_topic: std::env::var("SINK_AGGREGATES_TOPIC")
+                .unwrap_or(defaults.aggregates_topic),
+            analytics_topic: std::env::var("SINK_ANALYTICS_TOPIC")
+                .unwrap_or(defaults.analytics_topic),
+            partition_key: std::env::var("SINK_PARTITION_KEY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.partition_key),
+            delivery_timeout_ms: std::env::var("SINK_DELIVERY_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.delivery_timeout_ms),
+            ..defaults
+        }
+    }
+}
+
+/// Kind of derived event, used for routing, headers and stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivedKind {
+    Anomaly,
+    Aggregate,
+    Correlation,
+}
+
+impl DerivedKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Anomaly => "anomaly",
+            Self::Aggregate => "aggregate",
+            Self::Correlation => "correlation",
+        }
+    }
+}
+
+/// Broker acknowledgement for a published record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeliveryReport {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// Publishes anomalies, aggregates and correlations to their Kafka topics
+pub struct DerivedEventSink {
+    config: SinkConfig,
+    producer: FutureProducer,
+    stats: SinkCounters,
+}
+
+impl DerivedEventSink {
+    /// Create a new sink with an idempotent producer
+    pub fn new(config: SinkConfig) -> Result<Self> {
+        info!(
+            "Initializing derived event sink (partition key: {:?})",
+            config.partition_key
+        );
+
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", config.kafka_brokers.join(","))
+            .set("client.id", &config.client_id)
+            .set("message.timeout.ms", config.delivery_timeout_ms.to_string())
+            .set("compression.type", "snappy")
+            .set("linger.ms", "20")
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .set("max.in.flight.requests.per.connection", "5")
+            .set("retries", "2147483647")
+            .create()
+            .context("Failed to create derived event producer")?;
+
+        Ok(Self {
+            config,
+            producer,
+            stats: SinkCounters::default(),
+        })
+    }
+
+    /// Publish a detected anomaly to the alerts topic
+    pub async fn publish_anomaly(&self, anomaly: &AnomalyEvent) -> Result<DeliveryReport> {
+        let key = anomaly_key(anomaly, self.config.partition_key);
+        let payload = serde_json::to_vec(anomaly).context("Failed to serialize anomaly")?;
+        self.send(DerivedKind::Anomaly, key, &payload).await
+    }
+
+    /// Publish a rollup summary to the aggregated metrics topic
+    pub async fn publish_aggregate(&self, metric: &AggregatedMetric) -> Result<DeliveryReport> {
+        let key = aggregate_key(metric, self.config.partition_key);
+        let payload = serde_json::to_vec(metric).context("Failed to serialize aggregate")?;
+        self.send(DerivedKind::Aggregate, key, &payload).await
+    }
+
+    /// Publish a correlation finding to the analytics topic
+    pub async fn publish_correlation(
+        &self,
+        correlation: &EventCorrelation,
+    ) -> Result<DeliveryReport> {
+        let key = correlation_key(correlation, self.config.partition_key);
+        let payload = serde_json::to_vec(correlation).context("Failed to serialize correlation")?;
+        self.send(DerivedKind::Correlation, key, &payload).await
+    }
+
+    /// Wait for all in-flight records to be acknowledged
+    pub fn flush(&self, timeout: Duration) -> Result<()> {
+        self.producer
+            .flush(timeout)
+            .map_err(|e| anyhow::anyhow!("Failed to flush derived event producer: {}", e))
+    }
+
+    fn topic_for(&self, kind: DerivedKind) -> &str {
+        match kind {
+            DerivedKind::Anomaly => &self.config.alerts_topic,
+            DerivedKind::Aggregate => &self.config.aggregates_topic,
+            DerivedKind::Correlation => &self.config.analytics_topic,
+        }
+    }
+
+    async fn send(
+        &self,
+        kind: DerivedKind,
+        key: Option<String>,
+        payload: &[u8],
+    ) -> Result<DeliveryReport> {
+        let topic = self.topic_for(kind);
+        let headers = OwnedHeaders::new().insert(Header {
+            key: KIND_HEADER,
+            value: Some(kind.as_str()),
+        });
+
+        let mut record = FutureRecord::to(topic).payload(payload).headers(headers);
+        if let Some(key) = key.as_deref() {
+            record = record.key(key);
+        }
+
+        let timeout = Duration::from_millis(self.config.delivery_timeout_ms);
+        match self.producer.send(record, timeout).await {
+            Ok((partition, offset)) => {
+                self.stats.delivered(kind);
+                debug!(
+                    "Delivered {} to {} [{}] @ {}",
+                    kind.as_str(),
+                    topic,
+                    partition,
+                    offset
+                );
+                Ok(DeliveryReport {
+                    topic: topic.to_string(),
+                    partition,
+                    offset,
+                })
+            }
+            Err((err, _)) => {
+                self.stats.failed(kind);
+                warn!("Failed to deliver {} to {}: {}", kind.as_str(), topic, err);
+                Err(anyhow::anyhow!(
+                    "Failed to deliver {} to {}: {}",
+                    kind.as_str(),
+                    topic,
+                    err
+                ))
+            }
+        }
+    }
+
+    /// Get sink statistics
+    pub fn get_stats(&self) -> SinkStats {
+        SinkStats {
+            anomalies_delivered: self.stats.anomalies_delivered.load(Ordering::Relaxed),
+            aggregates_delivered: self.stats.aggregates_delivered.load(Ordering::Relaxed),
+            correlations_delivered: self.stats.correlations_delivered.load(Ordering::Relaxed),
+            anomalies_failed: self.stats.anomalies_failed.load(Ordering::Relaxed),
+            aggregates_failed: self.stats.aggregates_failed.load(Ordering::Relaxed),
+            correlations_failed: self.stats.correlations_failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// ========== Partition Keys ==========
+
+fn anomaly_key(anomaly: &AnomalyEvent, strategy: PartitionKey) -> Option<String> {
+    match strategy {
+        PartitionKey::None => None,
+        PartitionKey::SourceModule => Some(source_module_key(&anomaly.source_module)),
+        // Anomalies carry no tenant tag, so they stay keyed by metric
+        PartitionKey::Entity | PartitionKey::Tenant => Some(anomaly.metric.clone()),
+    }
+}
+
+fn aggregate_key(metric: &AggregatedMetric, strategy: PartitionKey) -> Option<String> {
+    match strategy {
+        PartitionKey::None => None,
+        PartitionKey::Tenant => Some(
+            metric
+                .tags
+                .get(TENANT_TAG)
+                .cloned()
+                .unwrap_or_else(|| metric.name.clone()),
+        ),
+        PartitionKey::SourceModule => Some(
+            metric
+                .tags
+                .get("source_module")
+                .cloned()
+                .unwrap_or_else(|| metric.name.clone()),
+        ),
+        PartitionKey::Entity => Some(metric.name.clone()),
+    }
+}
+
+fn correlation_key(correlation: &EventCorrelation, strategy: PartitionKey) -> Option<String> {
+    let entity = correlation.correlation_id.0.to_string();
+    match strategy {
+        PartitionKey::None => None,
+        PartitionKey::Tenant => Some(
+            correlation
+                .metadata
+                .get(TENANT_TAG)
+                .cloned()
+                .unwrap_or(entity),
+        ),
+        PartitionKey::SourceModule => Some(
+            correlation
+                .events
+                .first()
+                .map(|e| source_module_key(&e.source_module))
+                .unwrap_or(entity),
+        ),
+        PartitionKey::Entity => Some(entity),
+    }
+}
+
+fn source_module_key<T: Serialize>(module: &T) -> String {
+    match serde_json::to_value(module) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => "unknown".to_string(),
+    }
+}
+
+// ========== Statistics ==========
+
+#[derive(Default)]
+struct SinkCounters {
+    anomalies_delivered: AtomicU64,
+    aggregates_delivered: AtomicU64,
+    correlations_delivered: AtomicU64,
+    anomalies_failed: AtomicU64,
+    aggregates_failed: AtomicU64,
+    correlations_failed: AtomicU64,
+}
+
+impl SinkCounters {
+    fn delivered(&self, kind: DerivedKind) {
+        match kind {
+            DerivedKind::Anomaly => &self.anomalies_delivered,
+            DerivedKind::Aggregate => &self.aggregates_delivered,
+            DerivedKind::Correlation => &self.correlations_delivered,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn failed(&self, kind: DerivedKind) {
+        match kind {
+            DerivedKind::Anomaly => &self.anomalies_failed,
+            DerivedKind::Aggregate => &self.aggregates_failed,
+            DerivedKind::Correlation => &self.correlations_failed,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Derived event sink statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkStats {
+    pub anomalies_delivered: u64,
+    pub aggregates_delivered: u64,
+    pub correlations_delivered: u64,
+    pub anomalies_failed: u64,
+    pub aggregates_failed: u64,
+    pub correlations_failed: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::metrics::{MetricValues, TimeWindow};
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn aggregate(tags: HashMap<String, String>) -> AggregatedMetric {
+        let now = Utc::now();
+        AggregatedMetric {
+            name: "llm.latency".to_string(),
+            window: TimeWindow::OneMinute,
+            window_start: now,
+            window_end: now,
+            values: MetricValues::Counter {
+                value: 10,
+                rate: 0.5,
+            },
+            tags,
+        }
+    }
+
+    #[test]
+    fn test_partition_key_parsing() {
+        assert_eq!(
+            "tenant".parse::<PartitionKey>().unwrap(),
+            PartitionKey::Tenant
+        );
+        assert_eq!(
+            "source_module".parse::<PartitionKey>().unwrap(),
+            PartitionKey::SourceModule
+        );
+        assert!("random".parse::<PartitionKey>().is_err());
+    }
+
+    #[test]
+    fn test_aggregate_key_falls_back_to_entity() {
+        let untagged = aggregate(HashMap::new());
+        assert_eq!(
+            aggregate_key(&untagged, PartitionKey::Tenant).as_deref(),
+            Some("llm.latency")
+        );
+
+        let mut tags = HashMap::new();
+        tags.insert(TENANT_TAG.to_string(), "acme".to_string());
+        let tagged = aggregate(tags);
+        assert_eq!(
+            aggregate_key(&tagged, PartitionKey::Tenant).as_deref(),
+            Some("acme")
+        );
+        assert_eq!(aggregate_key(&tagged, PartitionKey::None), None);
+    }
+}
@@ -3,10 +3,11 @@
 //! High-performance time-series storage with TimescaleDB (PostgreSQL extension).
 
 use crate::schemas::events::AnalyticsEvent;
+use crate::telemetry::record_event_context;
 use anyhow::{Context, Result};
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use sqlx::Row;
-use tracing::info;
+use tracing::{info, instrument};
 
 use super::{HealthStatus, PipelineComponent, PipelineConfig};
 
@@ -183,7 +184,13 @@ impl StorageManager {
     }
 
     /// Store a single event
+    #[instrument(
+        name = "pipeline.persist",
+        skip(self, event),
+        fields(event_id = %event.common.event_id, correlation_id = tracing::field::Empty)
+    )]
     pub async fn store_event(&self, event: &AnalyticsEvent) -> Result<()> {
+        record_event_context(&event.common);
         sqlx::query(
             r#"
             INSERT INTO analytics_events
@@ -211,6 +218,7 @@ impl StorageManager {
     }
 
     /// Store a batch of events
+    #[instrument(name = "pipeline.persist_batch", skip_all, fields(batch_size = events.len()))]
     pub async fn store_batch(&self, events: &[AnalyticsEvent]) -> Result<()> {
         let mut tx = self.pool.begin().await?;
 
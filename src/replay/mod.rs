@@ -0,0 +1,588 @@
+//! Historical Replay
+//!
+//! Reprocesses stored events through fresh analytics engines after a detector
+//! is added or an aggregation bug is fixed. Events are read from the events
+//! table (through any `StorageBackend`) or from archived partitions, replayed
+//! oldest first at a controlled rate, and every aggregate the replay produces
+//! is written through a `ShadowBackend` under a namespaced metric name so the
+//! production rollups are never overwritten.
+
+use crate::analytics::aggregation_engine::AggregationEngine;
+use crate::analytics::{AnalyticsConfig, AnomalyDetector, CorrelationEngine};
+use crate::archival::{decode_archive, ArchiveTable, ObjectStore};
+use crate::database::{AggregatedMetricRow, Database, EventCountRow, StorageBackend};
+use crate::models::metrics::{StatisticalMeasures, TimeWindow};
+use crate::retention::TablePolicy;
+use crate::schemas::events::AnalyticsEvent;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::{info, instrument, warn};
+
+/// Separator between the shadow namespace and the production metric name
+pub const NAMESPACE_SEPARATOR: char = ':';
+
+/// Most events read from the events table per slice
+const SLICE_LIMIT: i64 = 10_000;
+
+/// Metric name a replay stores `metric_name` under
+pub fn shadow_metric_name(namespace: &str, metric_name: &str) -> String {
+    format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, metric_name)
+}
+
+/// Replay settings
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    /// Replayed range, `[start, end)` by event time
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Events per second of wall-clock time; `None` replays as fast as possible
+    pub rate: Option<f64>,
+    /// Event-time span read from the events table per query
+    pub slice: Duration,
+    /// Prefix for every aggregate the replay writes, e.g. `replay-2025-01-31`
+    pub namespace: String,
+    /// Configuration of the replay's own analytics engines
+    pub analytics: AnalyticsConfig,
+}
+
+impl ReplayConfig {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>, namespace: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            rate: None,
+            slice: Duration::minutes(15),
+            namespace: namespace.into(),
+            analytics: AnalyticsConfig::default(),
+        }
+    }
+
+    pub fn with_rate(mut self, events_per_second: f64) -> Self {
+        self.rate = Some(events_per_second);
+        self
+    }
+
+    pub fn with_analytics(mut self, analytics: AnalyticsConfig) -> Self {
+        self.analytics = analytics;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.start >= self.end {
+            anyhow::bail!(
+                "Replay start {} must be before end {}",
+                self.start,
+                self.end
+            );
+        }
+        if self.slice <= Duration::zero() {
+            anyhow::bail!("Replay slice must be positive");
+        }
+        if matches!(self.rate, Some(rate) if rate.is_nan() || rate <= 0.0) {
+            anyhow::bail!("Replay rate must be positive");
+        }
+        if self.namespace.is_empty() || self.namespace.contains(NAMESPACE_SEPARATOR) {
+            anyhow::bail!(
+                "Replay namespace '{}' must be non-empty and must not contain '{}'",
+                self.namespace,
+                NAMESPACE_SEPARATOR
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Storage wrapper that confines aggregate writes to a namespace.
+///
+/// Aggregates are stored and read back under `shadow_metric_name`, so
+/// production queries by metric name never see them. Event reads pass through;
+/// event writes and retention are refused because a replay must not change the
+/// production event store.
+pub struct ShadowBackend {
+    inner: Arc<dyn StorageBackend>,
+    namespace: String,
+}
+
+impl ShadowBackend {
+    pub fn new(inner: Arc<dyn StorageBackend>, namespace: impl Into<String>) -> Self {
+        Self {
+            inner,
+            namespace: namespace.into(),
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+}
+
+#[async_trait]
+impl StorageBackend for ShadowBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn insert_events_batch(&self, _events: &[AnalyticsEvent]) -> Result<u64> {
+        anyhow::bail!("Shadow namespace {} does not accept events", self.namespace)
+    }
+
+    async fn store_aggregated_metric(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        window_start: DateTime<Utc>,
+        tags: &serde_json::Value,
+        measures: &StatisticalMeasures,
+    ) -> Result<()> {
+        self.inner
+            .store_aggregated_metric(
+                &shadow_metric_name(&self.namespace, metric_name),
+                time_window,
+                window_start,
+                tags,
+                measures,
+            )
+            .await
+    }
+
+    async fn query_aggregated_metrics(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        self.inner
+            .query_aggregated_metrics(
+                &shadow_metric_name(&self.namespace, metric_name),
+                time_window,
+                start,
+                end,
+            )
+            .await
+    }
+
+    async fn query_aggregated_metrics_tagged(
+        &self,
+        metric_name: &str,
+        time_window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tags: &serde_json::Value,
+    ) -> Result<Vec<AggregatedMetricRow>> {
+        self.inner
+            .query_aggregated_metrics_tagged(
+                &shadow_metric_name(&self.namespace, metric_name),
+                time_window,
+                start,
+                end,
+                tags,
+            )
+            .await
+    }
+
+    async fn query_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AnalyticsEvent>> {
+        self.inner.query_events(start, end, limit).await
+    }
+
+    async fn query_event_counts(
+        &self,
+        window: TimeWindow,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        self.inner.query_event_counts(window, start, end).await
+    }
+
+    async fn apply_retention(&self, _policies: &[TablePolicy]) -> Result<()> {
+        anyhow::bail!(
+            "Retention is managed by the production backend, not shadow namespace {}",
+            self.namespace
+        )
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+}
+
+/// Outcome of a replay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaySummary {
+    /// Events fed through the engines
+    pub replayed: u64,
+    /// Archived rows that were outside the range or could not be decoded
+    pub skipped: u64,
+    /// Slices that hit the per-query limit and may be missing events
+    pub truncated_slices: u64,
+    pub anomalies: u64,
+    /// Partial windows written when the replay finished
+    pub flushed_windows: usize,
+    /// Event time of the last replayed event
+    pub watermark: Option<DateTime<Utc>>,
+}
+
+/// Replays historical events through isolated analytics engines
+pub struct Replayer {
+    config: ReplayConfig,
+    aggregation: AggregationEngine,
+    correlation: CorrelationEngine,
+    anomaly: AnomalyDetector,
+}
+
+impl Replayer {
+    /// Replayer writing aggregates to `target` under the configured namespace
+    pub async fn new(target: Arc<dyn StorageBackend>, config: ReplayConfig) -> Result<Self> {
+        config.validate()?;
+        let shadow = Arc::new(ShadowBackend::new(target, config.namespace.clone()));
+        let anomaly = AnomalyDetector::new(Arc::new(config.analytics.clone())).await?;
+
+        Ok(Self {
+            aggregation: AggregationEngine::new(shadow),
+            correlation: CorrelationEngine::new(),
+            anomaly,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &ReplayConfig {
+        &self.config
+    }
+
+    /// Aggregation engine writing to the shadow namespace
+    pub fn aggregation(&self) -> &AggregationEngine {
+        &self.aggregation
+    }
+
+    /// Correlations rebuilt from the replayed events
+    pub fn correlation(&self) -> &CorrelationEngine {
+        &self.correlation
+    }
+
+    /// Anomaly detector trained only on replayed events
+    pub fn anomaly(&self) -> &AnomalyDetector {
+        &self.anomaly
+    }
+
+    /// Replay events read from the events table in slices of `config.slice`.
+    ///
+    /// A slice that returns `SLICE_LIMIT` events is halved and re-read until it
+    /// fits, down to one second; a one-second slice that still overflows is
+    /// replayed truncated and counted in `truncated_slices`.
+    #[instrument(skip(self, source), fields(namespace = %self.config.namespace, source = source.name()))]
+    pub async fn replay_events(&self, source: &dyn StorageBackend) -> Result<ReplaySummary> {
+        let mut summary = ReplaySummary::default();
+        let mut pacer = Pacer::new(self.config.rate);
+        let mut slice_start = self.config.start;
+
+        while slice_start < self.config.end {
+            let mut slice_end = (slice_start + self.config.slice).min(self.config.end);
+            let mut events = loop {
+                let events = source
+                    .query_events(slice_start, slice_end, Some(SLICE_LIMIT))
+                    .await?;
+                if (events.len() as i64) < SLICE_LIMIT
+                    || slice_end - slice_start <= Duration::seconds(1)
+                {
+                    break events;
+                }
+                slice_end = slice_start + (slice_end - slice_start) / 2;
+            };
+            if events.len() as i64 >= SLICE_LIMIT {
+                summary.truncated_slices += 1;
+                warn!(%slice_start, %slice_end, "Replay slice hit the query limit; events may be missing");
+            }
+
+            sort_by_event_time(&mut events);
+            self.replay_batch(&events, &mut pacer, &mut summary).await?;
+            slice_start = slice_end;
+        }
+
+        self.finish(summary).await
+    }
+
+    /// Replay the archived events partitions overlapping the configured range.
+    ///
+    /// Archives are read directly from object storage; nothing is restored into
+    /// the events table.
+    #[instrument(skip(self, database, store), fields(namespace = %self.config.namespace))]
+    pub async fn replay_archives(
+        &self,
+        database: &Database,
+        store: &dyn ObjectStore,
+    ) -> Result<ReplaySummary> {
+        let manifests = database
+            .query_archive_manifests(ArchiveTable::Events, self.config.start, self.config.end)
+            .await?;
+        let mut summary = ReplaySummary::default();
+        let mut pacer = Pacer::new(self.config.rate);
+
+        for manifest in manifests {
+            let body = store.get(&manifest.object_key).await?;
+            let rows = decode_archive(&body, &manifest.compression, &manifest.sha256)
+                .with_context(|| format!("Failed to read archive {}", manifest.object_uri))?;
+            let (mut events, skipped) =
+                events_from_archive_rows(&rows, self.config.start, self.config.end);
+            summary.skipped += skipped;
+
+            sort_by_event_time(&mut events);
+            self.replay_batch(&events, &mut pacer, &mut summary).await?;
+        }
+
+        self.finish(summary).await
+    }
+
+    /// Feed events, already in event-time order, through the engines
+    async fn replay_batch(
+        &self,
+        events: &[AnalyticsEvent],
+        pacer: &mut Pacer,
+        summary: &mut ReplaySummary,
+    ) -> Result<()> {
+        for event in events {
+            pacer.wait().await;
+
+            self.aggregation.process_event(event).await?;
+            if let Some(correlation_id) = event.common.correlation_id {
+                self.correlation
+                    .track_correlation(correlation_id, event.common.event_id);
+            }
+            for (metric_name, value) in self.aggregation.extract_metrics(event)? {
+                if self
+                    .anomaly
+                    .check_anomaly(&metric_name, value, event.common.timestamp)?
+                    .is_some()
+                {
+                    summary.anomalies += 1;
+                }
+            }
+
+            summary.replayed += 1;
+            summary.watermark = Some(event.common.timestamp);
+        }
+
+        if let Some(watermark) = summary.watermark {
+            info!(replayed = summary.replayed, %watermark, "Replay progress");
+        }
+        Ok(())
+    }
+
+    /// Flush windows still open at the end of the range
+    async fn finish(&self, mut summary: ReplaySummary) -> Result<ReplaySummary> {
+        summary.flushed_windows = self.aggregation.flush_all().await?;
+        info!(
+            namespace = %self.config.namespace,
+            replayed = summary.replayed,
+            skipped = summary.skipped,
+            anomalies = summary.anomalies,
+            "Replay finished"
+        );
+        Ok(summary)
+    }
+}
+
+/// Events stored in archived `events` rows that fall inside `[start, end)`.
+///
+/// Returns the events and the number of rows skipped, either because they are
+/// outside the range or because their payload no longer decodes.
+pub fn events_from_archive_rows(
+    rows: &[serde_json::Value],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (Vec<AnalyticsEvent>, u64) {
+    let mut skipped = 0;
+    let events = rows
+        .iter()
+        .filter_map(|row| {
+            let event = row
+                .get("payload")
+                .and_then(|payload| serde_json::from_value::<AnalyticsEvent>(payload.clone()).ok())
+                .filter(|event| event.common.timestamp >= start && event.common.timestamp < end);
+            if event.is_none() {
+                skipped += 1;
+            }
+            event
+        })
+        .collect();
+    (events, skipped)
+}
+
+fn sort_by_event_time(events: &mut [AnalyticsEvent]) {
+    events.sort_by_key(|event| (event.common.timestamp, event.common.event_id));
+}
+
+/// Spaces events evenly at a fixed rate of wall-clock time
+struct Pacer {
+    interval: Option<std::time::Duration>,
+    next: Instant,
+}
+
+impl Pacer {
+    fn new(rate: Option<f64>) -> Self {
+        Self {
+            interval: rate.map(|rate| std::time::Duration::from_secs_f64(1.0 / rate)),
+            next: Instant::now(),
+        }
+    }
+
+    async fn wait(&mut self) {
+        if let Some(interval) = self.interval {
+            tokio::time::sleep_until(self.next).await;
+            // Never bank idle time from slow queries into a burst
+            self.next = self.next.max(Instant::now()) + interval;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::environment::default_environment;
+    use crate::database::MemoryBackend;
+    use crate::schemas::events::{
+        CommonEventFields, EventPayload, EventType, LatencyMetrics, Severity, SourceModule,
+        TelemetryPayload, SCHEMA_VERSION,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn start() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn latency_event(timestamp: DateTime<Utc>, total_latency_ms: f64) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp,
+                source_module: SourceModule::LlmObservatory,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: default_environment(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Telemetry(TelemetryPayload::Latency(LatencyMetrics {
+                model_id: "gpt-4".to_string(),
+                request_id: "req".to_string(),
+                total_latency_ms,
+                ttft_ms: None,
+                tokens_per_second: None,
+                breakdown: None,
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_writes_only_to_shadow_namespace() {
+        let backend = Arc::new(MemoryBackend::new());
+        let events: Vec<_> = (0..30)
+            .map(|i| latency_event(start() + Duration::seconds(i * 10), 100.0 + i as f64))
+            .collect();
+        backend.insert_events_batch(&events).await.unwrap();
+
+        let end = start() + Duration::minutes(10);
+        let config = ReplayConfig {
+            slice: Duration::minutes(2),
+            ..ReplayConfig::new(start(), end, "fix-123")
+        };
+        let replayer = Replayer::new(backend.clone(), config).await.unwrap();
+        let summary = replayer.replay_events(backend.as_ref()).await.unwrap();
+
+        assert_eq!(summary.replayed, 30);
+        assert_eq!(summary.watermark, Some(events[29].common.timestamp));
+        assert!(summary.flushed_windows > 0);
+
+        let production = backend
+            .query_aggregated_metrics("total_latency_ms", TimeWindow::OneMinute, start(), end)
+            .await
+            .unwrap();
+        assert!(production.is_empty());
+
+        let shadow = backend
+            .query_aggregated_metrics(
+                &shadow_metric_name("fix-123", "total_latency_ms"),
+                TimeWindow::OneMinute,
+                start(),
+                end,
+            )
+            .await
+            .unwrap();
+        assert!(!shadow.is_empty());
+        let stats = replayer
+            .aggregation()
+            .get_aggregated_stats("total_latency_ms", TimeWindow::OneMinute, start(), end)
+            .await
+            .unwrap();
+        assert_eq!(stats.len(), shadow.len());
+    }
+
+    #[tokio::test]
+    async fn test_replay_is_rate_limited() {
+        let backend = Arc::new(MemoryBackend::new());
+        let events: Vec<_> = (0..5)
+            .map(|i| latency_event(start() + Duration::seconds(i), 100.0))
+            .collect();
+        backend.insert_events_batch(&events).await.unwrap();
+
+        let config =
+            ReplayConfig::new(start(), start() + Duration::minutes(1), "paced").with_rate(100.0);
+        let replayer = Replayer::new(backend.clone(), config).await.unwrap();
+        let began = std::time::Instant::now();
+        let summary = replayer.replay_events(backend.as_ref()).await.unwrap();
+
+        assert_eq!(summary.replayed, 5);
+        assert!(began.elapsed() >= std::time::Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_shadow_backend_refuses_event_writes() {
+        let shadow = ShadowBackend::new(Arc::new(MemoryBackend::new()), "ns");
+        assert!(shadow
+            .insert_events_batch(&[latency_event(start(), 1.0)])
+            .await
+            .is_err());
+        assert!(shadow.apply_retention(&[]).await.is_err());
+    }
+
+    #[test]
+    fn test_events_from_archive_rows() {
+        let inside = latency_event(start(), 1.0);
+        let outside = latency_event(start() - Duration::hours(1), 1.0);
+        let rows = vec![
+            json!({ "event_id": inside.common.event_id, "payload": inside }),
+            json!({ "event_id": outside.common.event_id, "payload": outside }),
+            json!({ "event_id": Uuid::new_v4(), "payload": { "unexpected": true } }),
+        ];
+
+        let (events, skipped) =
+            events_from_archive_rows(&rows, start(), start() + Duration::hours(1));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].common.event_id, inside.common.event_id);
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let end = start() + Duration::hours(1);
+        assert!(ReplayConfig::new(start(), end, "ok").validate().is_ok());
+        assert!(ReplayConfig::new(end, start(), "ok").validate().is_err());
+        assert!(ReplayConfig::new(start(), end, "").validate().is_err());
+        assert!(ReplayConfig::new(start(), end, "a:b").validate().is_err());
+        assert!(ReplayConfig::new(start(), end, "ok")
+            .with_rate(0.0)
+            .validate()
+            .is_err());
+    }
+}
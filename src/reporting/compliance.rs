@@ -0,0 +1,619 @@
+//! Compliance Reports
+//!
+//! Periodic SOC2/GDPR summaries compiled from governance compliance checks and
+//! Sentinel privacy events: pass rates by control, open findings, consent
+//! statistics, and data deletion SLA adherence.
+
+use crate::database::{Database, EnvironmentScope, EventFilter};
+use crate::export::tags::csv_field;
+use crate::schemas::events::{
+    AnalyticsEvent, ComplianceCheckEvent, ComplianceStatus, EventPayload, EventType,
+    GovernancePayload, PrivacyEvent, PrivacyOperation, SecurityPayload,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+/// Compliance report configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReportConfig {
+    /// Restrict the report to a single framework (e.g. "SOC2", "GDPR")
+    pub framework: Option<String>,
+    /// Days allowed between an erasure request and the deletion completing
+    pub deletion_sla_days: i64,
+}
+
+impl Default for ComplianceReportConfig {
+    fn default() -> Self {
+        Self {
+            framework: None,
+            deletion_sla_days: 30,
+        }
+    }
+}
+
+/// Check outcomes for a single control
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlSummary {
+    pub framework: String,
+    pub control_id: String,
+    pub checks: u64,
+    pub passed: u64,
+    pub failed: u64,
+    pub not_applicable: u64,
+    pub manual: u64,
+    /// Latest status of the control is a failure
+    pub open: bool,
+}
+
+impl ControlSummary {
+    /// Pass rate over checks with a pass/fail outcome
+    pub fn pass_rate(&self) -> Option<f64> {
+        let decided = self.passed + self.failed;
+        (decided > 0).then(|| self.passed as f64 / decided as f64 * 100.0)
+    }
+}
+
+/// Failing finding not yet superseded by a passing check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenFinding {
+    pub framework: String,
+    pub control_id: String,
+    pub check_id: String,
+    pub description: String,
+    pub evidence: Option<String>,
+    pub first_failed_at: DateTime<Utc>,
+    pub last_failed_at: DateTime<Utc>,
+}
+
+/// Consent coverage of privacy-relevant operations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsentStatistics {
+    pub privacy_events: u64,
+    pub with_consent: u64,
+    pub without_consent: u64,
+    pub data_subjects: u64,
+    pub by_operation: BTreeMap<String, u64>,
+}
+
+impl ConsentStatistics {
+    pub fn consent_rate(&self) -> Option<f64> {
+        (self.privacy_events > 0)
+            .then(|| self.with_consent as f64 / self.privacy_events as f64 * 100.0)
+    }
+}
+
+/// Adherence to the erasure deadline.
+///
+/// A request starts when a subject withdraws consent and completes at the
+/// subject's next data deletion.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeletionSlaStatistics {
+    pub sla_days: i64,
+    pub requests: u64,
+    pub completed_within_sla: u64,
+    pub completed_late: u64,
+    /// Still open and inside the deadline
+    pub pending: u64,
+    /// Still open and past the deadline
+    pub overdue: u64,
+    pub max_completion_hours: Option<f64>,
+}
+
+impl DeletionSlaStatistics {
+    pub fn adherence(&self) -> Option<f64> {
+        let due = self.completed_within_sla + self.completed_late + self.overdue;
+        (due > 0).then(|| self.completed_within_sla as f64 / due as f64 * 100.0)
+    }
+}
+
+/// Compliance report over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub framework: Option<String>,
+    pub compliance_checks: u64,
+    pub checks_passed: u64,
+    pub average_score: Option<f64>,
+    pub controls: Vec<ControlSummary>,
+    pub open_findings: Vec<OpenFinding>,
+    pub consent: ConsentStatistics,
+    pub deletion_sla: DeletionSlaStatistics,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DeletionRequest {
+    requested_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl ComplianceReport {
+    /// Compile a report from compliance check and privacy events in any order
+    pub fn from_events<'a>(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        events: impl IntoIterator<Item = &'a AnalyticsEvent>,
+        config: &ComplianceReportConfig,
+    ) -> Self {
+        let mut checks: Vec<(DateTime<Utc>, &ComplianceCheckEvent)> = Vec::new();
+        let mut privacy: Vec<(DateTime<Utc>, &PrivacyEvent)> = Vec::new();
+
+        for event in events {
+            let at = event.common.timestamp;
+            match &event.payload {
+                EventPayload::Governance(GovernancePayload::ComplianceCheck(check)) => {
+                    let in_scope = match &config.framework {
+                        Some(framework) => framework.eq_ignore_ascii_case(&check.framework),
+                        None => true,
+                    };
+                    if in_scope {
+                        checks.push((at, check));
+                    }
+                }
+                EventPayload::Security(SecurityPayload::Privacy(privacy_event)) => {
+                    privacy.push((at, privacy_event));
+                }
+                _ => {}
+            }
+        }
+        checks.sort_by_key(|(at, _)| *at);
+        privacy.sort_by_key(|(at, _)| *at);
+
+        let (controls, open_findings) = summarize_controls(&checks);
+        let scores: Vec<f64> = checks.iter().map(|(_, c)| c.score).collect();
+
+        Self {
+            start,
+            end,
+            generated_at: Utc::now(),
+            framework: config.framework.clone(),
+            compliance_checks: checks.len() as u64,
+            checks_passed: checks.iter().filter(|(_, c)| c.passed).count() as u64,
+            average_score: (!scores.is_empty())
+                .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+            controls,
+            open_findings,
+            consent: consent_statistics(&privacy),
+            deletion_sla: deletion_sla(&privacy, end, config.deletion_sla_days),
+        }
+    }
+
+    /// Render per-control pass rates as CSV
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "framework,control_id,checks,passed,failed,not_applicable,manual,pass_rate_percent,open\n",
+        );
+        for control in &self.controls {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{}",
+                csv_field(&control.framework),
+                csv_field(&control.control_id),
+                control.checks,
+                control.passed,
+                control.failed,
+                control.not_applicable,
+                control.manual,
+                control
+                    .pass_rate()
+                    .map(|r| format!("{:.2}", r))
+                    .unwrap_or_default(),
+                control.open
+            );
+        }
+        out
+    }
+
+    /// Render open findings as CSV
+    pub fn findings_to_csv(&self) -> String {
+        let mut out = String::from(
+            "framework,control_id,check_id,description,evidence,first_failed_at,last_failed_at\n",
+        );
+        for finding in &self.open_findings {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                csv_field(&finding.framework),
+                csv_field(&finding.control_id),
+                csv_field(&finding.check_id),
+                csv_field(&finding.description),
+                csv_field(finding.evidence.as_deref().unwrap_or("")),
+                finding.first_failed_at.to_rfc3339(),
+                finding.last_failed_at.to_rfc3339()
+            );
+        }
+        out
+    }
+}
+
+/// Roll chronologically ordered checks up per control.
+///
+/// Controls listed in a check without an explicit finding count as passing.
+fn summarize_controls(
+    checks: &[(DateTime<Utc>, &ComplianceCheckEvent)],
+) -> (Vec<ControlSummary>, Vec<OpenFinding>) {
+    let mut controls: BTreeMap<(String, String), ControlSummary> = BTreeMap::new();
+    let mut open: HashMap<(String, String), OpenFinding> = HashMap::new();
+
+    for (at, check) in checks {
+        let mut statuses: Vec<(&str, &ComplianceStatus)> = check
+            .findings
+            .iter()
+            .map(|f| (f.control_id.as_str(), &f.status))
+            .collect();
+        for control_id in &check.controls_checked {
+            if !check.findings.iter().any(|f| &f.control_id == control_id) {
+                statuses.push((control_id.as_str(), &ComplianceStatus::Pass));
+            }
+        }
+
+        for (control_id, status) in statuses {
+            let key = (check.framework.clone(), control_id.to_string());
+            let summary = controls
+                .entry(key.clone())
+                .or_insert_with(|| ControlSummary {
+                    framework: check.framework.clone(),
+                    control_id: control_id.to_string(),
+                    ..Default::default()
+                });
+            summary.checks += 1;
+            match status {
+                ComplianceStatus::Pass => {
+                    summary.passed += 1;
+                    open.remove(&key);
+                }
+                ComplianceStatus::Fail => {
+                    summary.failed += 1;
+                    let finding = check.findings.iter().find(|f| f.control_id == control_id);
+                    open.entry(key)
+                        .and_modify(|existing| existing.last_failed_at = *at)
+                        .or_insert_with(|| OpenFinding {
+                            framework: check.framework.clone(),
+                            control_id: control_id.to_string(),
+                            check_id: check.check_id.clone(),
+                            description: finding.map(|f| f.description.clone()).unwrap_or_default(),
+                            evidence: finding.and_then(|f| f.evidence.clone()),
+                            first_failed_at: *at,
+                            last_failed_at: *at,
+                        });
+                }
+                ComplianceStatus::NotApplicable => summary.not_applicable += 1,
+                ComplianceStatus::Manual => summary.manual += 1,
+            }
+        }
+    }
+
+    for (key, summary) in controls.iter_mut() {
+        summary.open = open.contains_key(key);
+    }
+
+    let mut open_findings: Vec<OpenFinding> = open.into_values().collect();
+    open_findings.sort_by(|a, b| {
+        a.first_failed_at
+            .cmp(&b.first_failed_at)
+            .then_with(|| a.control_id.cmp(&b.control_id))
+    });
+
+    (controls.into_values().collect(), open_findings)
+}
+
+fn operation_label(operation: &PrivacyOperation) -> String {
+    serde_json::to_value(operation)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", operation))
+}
+
+fn consent_statistics(privacy: &[(DateTime<Utc>, &PrivacyEvent)]) -> ConsentStatistics {
+    let mut stats = ConsentStatistics::default();
+    let mut subjects: HashSet<&str> = HashSet::new();
+
+    for (_, event) in privacy {
+        stats.privacy_events += 1;
+        if event.user_consent {
+            stats.with_consent += 1;
+        } else {
+            stats.without_consent += 1;
+        }
+        *stats
+            .by_operation
+            .entry(operation_label(&event.operation))
+            .or_insert(0) += 1;
+        subjects.extend(event.data_subjects.iter().map(String::as_str));
+    }
+    stats.data_subjects = subjects.len() as u64;
+    stats
+}
+
+fn deletion_sla(
+    privacy: &[(DateTime<Utc>, &PrivacyEvent)],
+    now: DateTime<Utc>,
+    sla_days: i64,
+) -> DeletionSlaStatistics {
+    let sla = Duration::days(sla_days);
+    let mut open: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut requests: Vec<DeletionRequest> = Vec::new();
+
+    for (at, event) in privacy {
+        for subject in &event.data_subjects {
+            match event.operation {
+                PrivacyOperation::ConsentUpdate if !event.user_consent => {
+                    open.entry(subject.as_str()).or_insert(*at);
+                }
+                PrivacyOperation::DataDeletion => {
+                    if let Some(requested_at) = open.remove(subject.as_str()) {
+                        requests.push(DeletionRequest {
+                            requested_at,
+                            completed_at: Some(*at),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    requests.extend(open.into_values().map(|requested_at| DeletionRequest {
+        requested_at,
+        completed_at: None,
+    }));
+
+    let mut stats = DeletionSlaStatistics {
+        sla_days,
+        requests: requests.len() as u64,
+        ..Default::default()
+    };
+    for request in requests {
+        match request.completed_at {
+            Some(completed_at) => {
+                let elapsed = completed_at - request.requested_at;
+                if elapsed <= sla {
+                    stats.completed_within_sla += 1;
+                } else {
+                    stats.completed_late += 1;
+                }
+                let hours = elapsed.num_seconds() as f64 / 3600.0;
+                stats.max_completion_hours =
+                    Some(stats.max_completion_hours.map_or(hours, |m| m.max(hours)));
+            }
+            None if now - request.requested_at > sla => stats.overdue += 1,
+            None => stats.pending += 1,
+        }
+    }
+    stats
+}
+
+/// Builds compliance reports from stored governance and security events
+pub struct ComplianceReporter {
+    database: Arc<Database>,
+    config: ComplianceReportConfig,
+}
+
+impl ComplianceReporter {
+    pub fn new(database: Arc<Database>, config: ComplianceReportConfig) -> Self {
+        Self { database, config }
+    }
+
+    /// Compile the report for `[start, end)`
+    #[instrument(skip(self))]
+    pub async fn generate(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<ComplianceReport> {
+        let filter = EventFilter::event_type(EventType::Governance)
+            .or(EventFilter::event_type(EventType::Security));
+        let mut events = Vec::new();
+        self.database
+            .scan_events(
+                start,
+                end,
+                Some(&filter),
+                &EnvironmentScope::Default,
+                |event| {
+                    if matches!(
+                        event.payload,
+                        EventPayload::Governance(GovernancePayload::ComplianceCheck(_))
+                            | EventPayload::Security(SecurityPayload::Privacy(_))
+                    ) {
+                        events.push(event.clone());
+                    }
+                },
+            )
+            .await?;
+
+        let report = ComplianceReport::from_events(start, end, &events, &self.config);
+        info!(
+            checks = report.compliance_checks,
+            open_findings = report.open_findings.len(),
+            "Compiled compliance report"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{
+        CommonEventFields, ComplianceFinding, Severity, SourceModule, SCHEMA_VERSION,
+    };
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn event(at: DateTime<Utc>, event_type: EventType, payload: EventPayload) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: at,
+                source_module: SourceModule::LlmGovernanceDashboard,
+                event_type,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: "production".to_string(),
+                tags: HashMap::new(),
+            },
+            payload,
+        }
+    }
+
+    fn check(
+        at: DateTime<Utc>,
+        framework: &str,
+        findings: Vec<(&str, ComplianceStatus)>,
+    ) -> AnalyticsEvent {
+        let passed = findings.iter().all(|(_, s)| *s != ComplianceStatus::Fail);
+        event(
+            at,
+            EventType::Governance,
+            EventPayload::Governance(GovernancePayload::ComplianceCheck(ComplianceCheckEvent {
+                check_id: format!("check-{}", at.timestamp()),
+                framework: framework.to_string(),
+                controls_checked: findings.iter().map(|(c, _)| c.to_string()).collect(),
+                passed,
+                findings: findings
+                    .into_iter()
+                    .map(|(control_id, status)| ComplianceFinding {
+                        control_id: control_id.to_string(),
+                        status,
+                        description: format!("{} finding", control_id),
+                        evidence: None,
+                    })
+                    .collect(),
+                score: if passed { 100.0 } else { 50.0 },
+            })),
+        )
+    }
+
+    fn privacy(
+        at: DateTime<Utc>,
+        operation: PrivacyOperation,
+        consent: bool,
+        subject: &str,
+    ) -> AnalyticsEvent {
+        event(
+            at,
+            EventType::Security,
+            EventPayload::Security(SecurityPayload::Privacy(PrivacyEvent {
+                data_type: "pii".to_string(),
+                operation,
+                user_consent: consent,
+                data_subjects: vec![subject.to_string()],
+                purpose: "support".to_string(),
+            })),
+        )
+    }
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_control_pass_rates_and_open_findings() {
+        let events = vec![
+            check(
+                day(3),
+                "SOC2",
+                vec![
+                    ("CC6.1", ComplianceStatus::Pass),
+                    ("CC7.2", ComplianceStatus::Fail),
+                ],
+            ),
+            check(
+                day(1),
+                "SOC2",
+                vec![
+                    ("CC6.1", ComplianceStatus::Fail),
+                    ("CC7.2", ComplianceStatus::Fail),
+                ],
+            ),
+            check(day(5), "GDPR", vec![("Art32", ComplianceStatus::Manual)]),
+        ];
+
+        let report = ComplianceReport::from_events(day(1), day(10), &events, &Default::default());
+
+        assert_eq!(report.compliance_checks, 3);
+        let cc61 = report
+            .controls
+            .iter()
+            .find(|c| c.control_id == "CC6.1")
+            .unwrap();
+        assert_eq!(cc61.pass_rate(), Some(50.0));
+        assert!(!cc61.open);
+
+        assert_eq!(report.open_findings.len(), 1);
+        assert_eq!(report.open_findings[0].control_id, "CC7.2");
+        assert_eq!(report.open_findings[0].first_failed_at, day(1));
+        assert_eq!(report.open_findings[0].last_failed_at, day(3));
+    }
+
+    #[test]
+    fn test_framework_filter() {
+        let events = vec![
+            check(day(1), "SOC2", vec![("CC6.1", ComplianceStatus::Pass)]),
+            check(day(2), "GDPR", vec![("Art32", ComplianceStatus::Fail)]),
+        ];
+        let config = ComplianceReportConfig {
+            framework: Some("gdpr".to_string()),
+            ..Default::default()
+        };
+
+        let report = ComplianceReport::from_events(day(1), day(10), &events, &config);
+
+        assert_eq!(report.compliance_checks, 1);
+        assert_eq!(report.controls.len(), 1);
+        assert_eq!(report.controls[0].framework, "GDPR");
+    }
+
+    #[test]
+    fn test_consent_and_deletion_sla() {
+        let events = vec![
+            privacy(day(1), PrivacyOperation::DataAccess, true, "alice"),
+            privacy(day(2), PrivacyOperation::ConsentUpdate, false, "alice"),
+            privacy(day(4), PrivacyOperation::DataDeletion, false, "alice"),
+            privacy(day(2), PrivacyOperation::ConsentUpdate, false, "bob"),
+            privacy(day(20), PrivacyOperation::DataDeletion, false, "bob"),
+            privacy(day(25), PrivacyOperation::ConsentUpdate, false, "carol"),
+        ];
+        let config = ComplianceReportConfig {
+            deletion_sla_days: 7,
+            ..Default::default()
+        };
+
+        let report = ComplianceReport::from_events(day(1), day(28), &events, &config);
+
+        assert_eq!(report.consent.privacy_events, 6);
+        assert_eq!(report.consent.with_consent, 1);
+        assert_eq!(report.consent.data_subjects, 3);
+        assert_eq!(report.consent.by_operation["consent_update"], 3);
+
+        let sla = &report.deletion_sla;
+        assert_eq!(sla.requests, 3);
+        assert_eq!(sla.completed_within_sla, 1);
+        assert_eq!(sla.completed_late, 1);
+        assert_eq!(sla.pending, 1);
+        assert_eq!(sla.adherence(), Some(50.0));
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let events = vec![check(
+            day(1),
+            "SOC2",
+            vec![("CC6,1", ComplianceStatus::Fail)],
+        )];
+        let report = ComplianceReport::from_events(day(1), day(2), &events, &Default::default());
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("framework,control_id"));
+        assert!(csv.contains("SOC2,\"CC6,1\",1,0,1,0,0,0.00,true"));
+        assert_eq!(report.findings_to_csv().lines().count(), 2);
+    }
+}
@@ -0,0 +1,310 @@
+//! Notification Rendering
+//!
+//! Renders report documents for delivery: Slack Block Kit payloads and HTML
+//! email bodies with inline styles, since mail clients drop stylesheets.
+//! Both keep within the limits of their targets by truncating long tables.
+
+use super::render::{escape_html, ReportDelta, ReportDocument, ReportTable};
+use serde_json::{json, Value};
+
+/// Rows of each table shown in a Slack message
+const SLACK_TABLE_ROWS: usize = 10;
+
+/// Slack limits on header text, section text, and fields per section
+const SLACK_HEADER_CHARS: usize = 150;
+const SLACK_TEXT_CHARS: usize = 3000;
+const SLACK_SECTION_FIELDS: usize = 10;
+
+/// Rows of each table shown in an email
+const EMAIL_TABLE_ROWS: usize = 50;
+
+const EMAIL_CELL_STYLE: &str = "border:1px solid #d0d7de;padding:4px 8px;text-align:left";
+const EMAIL_GOOD_COLOR: &str = "#1a7f37";
+const EMAIL_BAD_COLOR: &str = "#cf222e";
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn period(document: &ReportDocument) -> String {
+    format!(
+        "{} to {}",
+        document.period_start.format("%Y-%m-%d %H:%M UTC"),
+        document.period_end.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+/// Table as aligned monospace text, limited to `max_rows` rows
+fn text_table(table: &ReportTable, max_rows: usize) -> String {
+    let rows: Vec<&Vec<String>> = table.rows.iter().take(max_rows).collect();
+    let mut widths: Vec<usize> = table.columns.iter().map(|c| c.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate().take(widths.len()) {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| -> String {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(&table.columns)];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+fn delta_emoji(delta: &ReportDelta) -> &'static str {
+    match delta.improved() {
+        Some(true) => ":large_green_circle:",
+        Some(false) => ":red_circle:",
+        None => ":white_circle:",
+    }
+}
+
+/// Slack Block Kit blocks for a report
+pub fn slack_blocks(document: &ReportDocument) -> Value {
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": {
+                "type": "plain_text",
+                "text": truncate(&document.title, SLACK_HEADER_CHARS),
+            },
+        }),
+        json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": period(document) }],
+        }),
+    ];
+
+    let fields: Vec<Value> = document
+        .summary
+        .iter()
+        .map(|(label, value)| format!("*{}*\n{}", escape_mrkdwn(label), escape_mrkdwn(value)))
+        .chain(document.deltas.iter().map(|delta| {
+            format!(
+                "*{}*\n{} {}",
+                escape_mrkdwn(&delta.label),
+                delta_emoji(delta),
+                escape_mrkdwn(&delta.display())
+            )
+        }))
+        .map(|text| json!({ "type": "mrkdwn", "text": truncate(&text, 2000) }))
+        .collect();
+    for chunk in fields.chunks(SLACK_SECTION_FIELDS) {
+        blocks.push(json!({ "type": "section", "fields": chunk }));
+    }
+
+    for sparkline in &document.sparklines {
+        blocks.push(json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("*{}*  `{}`", escape_mrkdwn(&sparkline.label), sparkline.display()),
+            },
+        }));
+    }
+
+    for table in &document.tables {
+        blocks.push(json!({ "type": "divider" }));
+        let text = if table.rows.is_empty() {
+            format!("*{}*\n_None_", escape_mrkdwn(&table.title))
+        } else {
+            let body = truncate(
+                &escape_mrkdwn(&text_table(table, SLACK_TABLE_ROWS)),
+                SLACK_TEXT_CHARS.saturating_sub(table.title.len() + 16),
+            );
+            format!("*{}*\n```{}```", escape_mrkdwn(&table.title), body)
+        };
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text },
+        }));
+        if table.rows.len() > SLACK_TABLE_ROWS {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{
+                    "type": "mrkdwn",
+                    "text": format!("…and {} more rows", table.rows.len() - SLACK_TABLE_ROWS),
+                }],
+            }));
+        }
+    }
+
+    Value::Array(blocks)
+}
+
+/// Self-contained HTML email body for a report
+pub fn email_html(document: &ReportDocument) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title></head>\
+         <body style=\"font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;\
+         color:#1f2328;font-size:14px\">\
+         <h1 style=\"font-size:20px;margin:0 0 4px\">{title}</h1>\
+         <p style=\"color:#656d76;margin:0 0 16px\">{period}</p>",
+        title = escape_html(&document.title),
+        period = escape_html(&period(document)),
+    );
+
+    if !document.summary.is_empty() || !document.deltas.is_empty() {
+        out.push_str("<table style=\"border-collapse:collapse;margin-bottom:16px\">");
+        for (label, value) in &document.summary {
+            out.push_str(&format!(
+                "<tr><td style=\"padding:2px 16px 2px 0;color:#656d76\">{}</td>\
+                 <td style=\"padding:2px 0;font-weight:600\">{}</td></tr>",
+                escape_html(label),
+                escape_html(value)
+            ));
+        }
+        for delta in &document.deltas {
+            let color = match delta.improved() {
+                Some(true) => EMAIL_GOOD_COLOR,
+                Some(false) => EMAIL_BAD_COLOR,
+                None => "#656d76",
+            };
+            out.push_str(&format!(
+                "<tr><td style=\"padding:2px 16px 2px 0;color:#656d76\">{}</td>\
+                 <td style=\"padding:2px 0;font-weight:600\">{} {} \
+                 <span style=\"color:{}\">{}</span> \
+                 <span style=\"color:#656d76;font-weight:400\">vs {}</span></td></tr>",
+                escape_html(&delta.label),
+                escape_html(&format!("{:.2}", delta.current)),
+                escape_html(&delta.unit),
+                color,
+                escape_html(&delta.change_text()),
+                escape_html(format!("{:.2} {}", delta.baseline, delta.unit).trim_end()),
+            ));
+        }
+        out.push_str("</table>");
+    }
+
+    for sparkline in &document.sparklines {
+        out.push_str(&format!(
+            "<p style=\"margin:0 0 8px\"><span style=\"color:#656d76\">{}</span> \
+             <span style=\"font-family:monospace;font-size:16px\">{}</span></p>",
+            escape_html(&sparkline.label),
+            escape_html(&sparkline.display())
+        ));
+    }
+
+    for table in &document.tables {
+        out.push_str(&format!(
+            "<h2 style=\"font-size:16px;margin:20px 0 8px\">{}</h2>",
+            escape_html(&table.title)
+        ));
+        if table.rows.is_empty() {
+            out.push_str("<p style=\"color:#656d76\"><em>None</em></p>");
+            continue;
+        }
+        out.push_str("<table style=\"border-collapse:collapse\"><thead><tr>");
+        for column in &table.columns {
+            out.push_str(&format!(
+                "<th style=\"{};background:#f6f8fa\">{}</th>",
+                EMAIL_CELL_STYLE,
+                escape_html(column)
+            ));
+        }
+        out.push_str("</tr></thead><tbody>");
+        for row in table.rows.iter().take(EMAIL_TABLE_ROWS) {
+            out.push_str("<tr>");
+            for cell in row {
+                out.push_str(&format!(
+                    "<td style=\"{}\">{}</td>",
+                    EMAIL_CELL_STYLE,
+                    escape_html(cell)
+                ));
+            }
+            out.push_str("</tr>");
+        }
+        out.push_str("</tbody></table>");
+        if table.rows.len() > EMAIL_TABLE_ROWS {
+            out.push_str(&format!(
+                "<p style=\"color:#656d76\">…and {} more rows</p>",
+                table.rows.len() - EMAIL_TABLE_ROWS
+            ));
+        }
+    }
+
+    out.push_str("</body></html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporting::render::ReportSparkline;
+    use chrono::{Duration, Utc};
+
+    fn document() -> ReportDocument {
+        let end = Utc::now();
+        let mut table = ReportTable::new("Cost by model", &["Model", "Cost (USD)"]);
+        for i in 0..12 {
+            table.push_row(vec![format!("model-{}", i), format!("{}.00", i)]);
+        }
+        table.push_row(vec!["<b>".to_string(), "1.00".to_string()]);
+
+        ReportDocument::new("Cost summary", end - Duration::days(1), end)
+            .with_summary("Models", 13)
+            .with_delta(ReportDelta::new("Total cost", 12.0, 10.0).with_unit("USD"))
+            .with_sparkline(ReportSparkline::new(
+                "Cost per hour",
+                vec![0.0, 1.0, 2.0, 4.0],
+            ))
+            .with_table(table)
+    }
+
+    #[test]
+    fn test_slack_blocks() {
+        let blocks = slack_blocks(&document());
+        let blocks = blocks.as_array().unwrap();
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[0]["text"]["text"], "Cost summary");
+
+        let fields = blocks[2]["fields"].as_array().unwrap();
+        assert_eq!(fields[0]["text"], "*Models*\n13");
+        assert_eq!(
+            fields[1]["text"],
+            "*Total cost*\n:red_circle: 12.00 USD (▲ 20.0% vs 10.00 USD)"
+        );
+        assert!(blocks[3]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("`▁▃▅█ (0 – 4)`"));
+
+        let table = blocks[5]["text"]["text"].as_str().unwrap();
+        assert!(table.starts_with("*Cost by model*\n```Model    Cost (USD)\nmodel-0  0.00"));
+        assert!(!table.contains("model-10"));
+        assert_eq!(blocks[6]["elements"][0]["text"], "…and 3 more rows");
+    }
+
+    #[test]
+    fn test_email_html() {
+        let html = email_html(&document());
+        assert!(html.contains("<h1 style=\"font-size:20px;margin:0 0 4px\">Cost summary</h1>"));
+        assert!(html.contains(&format!(
+            "<span style=\"color:{}\">▲ 20.0%</span>",
+            EMAIL_BAD_COLOR
+        )));
+        assert!(html.contains("vs 10.00 USD"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(!html.contains("<b>"));
+        assert!(html.ends_with("</body></html>"));
+    }
+}
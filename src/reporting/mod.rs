@@ -0,0 +1,17 @@
+//! Reporting
+//!
+//! Report builders over analytics data produced by the hub.
+
+pub mod compliance;
+pub mod message;
+pub mod postmortem;
+pub mod render;
+pub mod scheduler;
+pub mod usage;
+
+pub use compliance::{ComplianceReport, ComplianceReportConfig, ComplianceReporter};
+pub use message::{email_html, slack_blocks};
+pub use postmortem::{PostmortemBundle, PostmortemConfig, PostmortemExporter};
+pub use render::{ReportDelta, ReportDocument, ReportFormat, ReportSparkline, ReportTable};
+pub use scheduler::{ReportDefinition, ReportKind, ReportScheduler};
+pub use usage::{TenantUsageSummary, UsageReport};
@@ -0,0 +1,486 @@
+//! Postmortem Bundles
+//!
+//! Everything a postmortem needs about one incident, gathered in one place:
+//! the incident and its timeline, events sharing its correlation IDs, metric
+//! windows around it, what the pre-incident trend forecast against what
+//! actually happened, the deploys and configuration changes reported during
+//! the period, and the topology context of the entities involved.
+
+use super::render::{ReportDocument, ReportTable};
+use crate::alerting::incidents::{Incident, SignalKind, TimelineEvent};
+use crate::analytics::topology::{EntityContext, TopologyStore};
+use crate::database::{AggregatedMetricRow, Database, EnvironmentScope, EventFilter};
+use crate::models::metrics::TimeWindow;
+use crate::schemas::events::{AnalyticsEvent, EventType};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// What a postmortem bundle covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostmortemConfig {
+    /// Minutes before the incident opened included as baseline
+    pub lead_minutes: i64,
+    /// Minutes after the incident's last signal or resolution included
+    pub trail_minutes: i64,
+    /// Aggregation window of metric snapshots
+    pub time_window: TimeWindow,
+    /// Smoothing factor of the baseline forecast
+    pub forecast_alpha: f64,
+    /// Related and change events included, each
+    pub max_events: i64,
+}
+
+impl Default for PostmortemConfig {
+    fn default() -> Self {
+        Self {
+            lead_minutes: 120,
+            trail_minutes: 30,
+            time_window: TimeWindow::FiveMinutes,
+            forecast_alpha: 0.3,
+            max_events: 500,
+        }
+    }
+}
+
+/// Aggregated windows of one metric around the incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSnapshot {
+    pub metric_name: String,
+    pub windows: Vec<AggregatedMetricRow>,
+}
+
+/// One window's actual mean against the baseline forecast
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub window_start: DateTime<Utc>,
+    pub actual: f64,
+    /// Actual over forecast, as a percentage difference
+    pub deviation_pct: Option<f64>,
+}
+
+/// What the pre-incident trend predicted for a metric against what happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastComparison {
+    pub metric_name: String,
+    /// Windows before the incident the forecast was fitted on
+    pub baseline_windows: usize,
+    /// Exponentially smoothed mean of the baseline windows
+    pub forecast: f64,
+    pub points: Vec<ForecastPoint>,
+}
+
+impl ForecastComparison {
+    /// Compare windows from `incident_start` on with a forecast smoothed over
+    /// the windows before it; `None` without windows on both sides
+    pub fn from_windows(
+        metric_name: &str,
+        windows: &[AggregatedMetricRow],
+        incident_start: DateTime<Utc>,
+        alpha: f64,
+    ) -> Option<Self> {
+        let mut windows: Vec<&AggregatedMetricRow> = windows.iter().collect();
+        windows.sort_by_key(|w| w.window_start);
+        let split = windows.partition_point(|w| w.window_start < incident_start);
+        let (baseline, during) = windows.split_at(split);
+        if baseline.is_empty() || during.is_empty() {
+            return None;
+        }
+
+        let alpha = alpha.clamp(0.0, 1.0);
+        let forecast = baseline[1..].iter().fold(baseline[0].avg, |smoothed, w| {
+            alpha * w.avg + (1.0 - alpha) * smoothed
+        });
+        let points = during
+            .iter()
+            .map(|w| ForecastPoint {
+                window_start: w.window_start,
+                actual: w.avg,
+                deviation_pct: (forecast != 0.0)
+                    .then(|| (w.avg - forecast) / forecast.abs() * 100.0),
+            })
+            .collect();
+
+        Some(Self {
+            metric_name: metric_name.to_string(),
+            baseline_windows: baseline.len(),
+            forecast,
+            points,
+        })
+    }
+
+    /// Largest absolute deviation from the forecast
+    pub fn peak_deviation_pct(&self) -> Option<f64> {
+        self.points
+            .iter()
+            .filter_map(|p| p.deviation_pct)
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+    }
+}
+
+/// Postmortem data for one incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostmortemBundle {
+    pub generated_at: DateTime<Utc>,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub incident: Incident,
+    /// Events sharing one of the incident's correlation IDs
+    pub related_events: Vec<AnalyticsEvent>,
+    pub metrics: Vec<MetricSnapshot>,
+    pub forecasts: Vec<ForecastComparison>,
+    /// Lifecycle (deploy) and governance (configuration, policy) events in
+    /// the incident's environment during the period
+    pub changes: Vec<AnalyticsEvent>,
+    /// Dependencies and blast radius of the entities involved
+    pub entities: Vec<EntityContext>,
+}
+
+impl PostmortemBundle {
+    /// Metrics the incident's anomalies were raised on
+    pub fn anomalous_metrics(incident: &Incident) -> BTreeSet<String> {
+        incident
+            .signals()
+            .filter(|s| s.kind == SignalKind::Anomaly)
+            .map(|s| s.summary.clone())
+            .collect()
+    }
+
+    /// Period covered for `incident`, ending no later than `now`
+    pub fn window(
+        incident: &Incident,
+        config: &PostmortemConfig,
+        now: DateTime<Utc>,
+    ) -> (DateTime<Utc>, DateTime<Utc>) {
+        let first_signal = incident
+            .signals()
+            .map(|s| s.at)
+            .min()
+            .unwrap_or(incident.opened_at);
+        let start = first_signal.min(incident.opened_at) - Duration::minutes(config.lead_minutes);
+        let last = incident
+            .resolved_at
+            .unwrap_or(incident.last_signal_at)
+            .max(incident.last_signal_at);
+        let end = (last + Duration::minutes(config.trail_minutes)).min(now);
+        (start, end.max(start))
+    }
+
+    /// Report document for the Markdown and HTML renderings
+    pub fn to_document(&self) -> ReportDocument {
+        let incident = &self.incident;
+        let mut document = ReportDocument::new(
+            &format!("Postmortem: {}", incident.title),
+            self.window_start,
+            self.window_end,
+        )
+        .with_summary("Incident", incident.incident_id)
+        .with_summary("Status", incident.status.as_str())
+        .with_summary(
+            "Severity",
+            serde_json::to_value(&incident.severity)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default(),
+        )
+        .with_summary("Environment", &incident.environment)
+        .with_summary("Opened", incident.opened_at.to_rfc3339())
+        .with_summary(
+            "Resolved",
+            incident
+                .resolved_at
+                .map_or_else(|| "-".to_string(), |at| at.to_rfc3339()),
+        )
+        .with_summary("Signals", incident.signal_count);
+        document.generated_at = self.generated_at;
+
+        let mut timeline = ReportTable::new("Timeline", &["Time", "Entry", "Detail"]);
+        for entry in &incident.timeline {
+            let (kind, detail) = match &entry.event {
+                TimelineEvent::Signal { signal } => (
+                    format!("{:?}", signal.kind).to_lowercase(),
+                    signal.summary.clone(),
+                ),
+                TimelineEvent::StatusChanged { status, by, note } => (
+                    status.as_str().to_string(),
+                    [by.as_deref(), note.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(": "),
+                ),
+                TimelineEvent::Note { by, text } => (
+                    "note".to_string(),
+                    match by {
+                        Some(by) => format!("{}: {}", by, text),
+                        None => text.clone(),
+                    },
+                ),
+            };
+            timeline.push_row(vec![entry.at.to_rfc3339(), kind, detail]);
+        }
+
+        let mut forecasts = ReportTable::new(
+            "Forecast vs actual",
+            &[
+                "Metric",
+                "Baseline windows",
+                "Forecast",
+                "Peak actual",
+                "Peak deviation %",
+            ],
+        );
+        for comparison in &self.forecasts {
+            let peak = comparison
+                .points
+                .iter()
+                .map(|p| p.actual)
+                .fold(f64::NEG_INFINITY, f64::max);
+            forecasts.push_row(vec![
+                comparison.metric_name.clone(),
+                comparison.baseline_windows.to_string(),
+                format!("{:.2}", comparison.forecast),
+                format!("{:.2}", peak),
+                comparison
+                    .peak_deviation_pct()
+                    .map_or_else(|| "-".to_string(), |d| format!("{:+.1}", d)),
+            ]);
+        }
+
+        let mut changes = ReportTable::new("Changes", &["Time", "Source", "Type", "Severity"]);
+        for event in &self.changes {
+            changes.push_row(event_row(event));
+        }
+        let mut related =
+            ReportTable::new("Related events", &["Time", "Source", "Type", "Severity"]);
+        for event in &self.related_events {
+            related.push_row(event_row(event));
+        }
+
+        let mut entities =
+            ReportTable::new("Entities", &["Entity", "Dependencies", "Affected", "Teams"]);
+        for context in &self.entities {
+            entities.push_row(vec![
+                context.entity.entity.to_string(),
+                context.dependencies.len().to_string(),
+                context.blast_radius.size().to_string(),
+                context.blast_radius.teams.join(", "),
+            ]);
+        }
+
+        document
+            .with_table(timeline)
+            .with_table(forecasts)
+            .with_table(changes)
+            .with_table(related)
+            .with_table(entities)
+    }
+}
+
+fn event_row(event: &AnalyticsEvent) -> Vec<String> {
+    let label = |value: serde_json::Result<serde_json::Value>| {
+        value
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default()
+    };
+    vec![
+        event.common.timestamp.to_rfc3339(),
+        label(serde_json::to_value(&event.common.source_module)),
+        label(serde_json::to_value(&event.common.event_type)),
+        label(serde_json::to_value(&event.common.severity)),
+    ]
+}
+
+/// Gathers postmortem bundles from storage and the topology
+pub struct PostmortemExporter {
+    database: Arc<Database>,
+    topology: Option<Arc<TopologyStore>>,
+    config: PostmortemConfig,
+}
+
+impl PostmortemExporter {
+    pub fn new(database: Arc<Database>, config: PostmortemConfig) -> Self {
+        Self {
+            database,
+            topology: None,
+            config,
+        }
+    }
+
+    pub fn with_topology(mut self, topology: Arc<TopologyStore>) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    #[instrument(skip(self, incident), fields(incident_id = %incident.incident_id))]
+    pub async fn export(&self, incident: &Incident) -> Result<PostmortemBundle> {
+        let now = Utc::now();
+        let (start, end) = PostmortemBundle::window(incident, &self.config, now);
+        let scope = EnvironmentScope::environment(&incident.environment);
+
+        let mut seen = HashSet::new();
+        let mut related_events = Vec::new();
+        for correlation_id in &incident.correlation_ids {
+            for event in self
+                .database
+                .query_events_by_correlation_scoped(*correlation_id, &scope)
+                .await?
+            {
+                if seen.insert(event.common.event_id) {
+                    related_events.push(event);
+                }
+            }
+        }
+        related_events.sort_by_key(|e| e.common.timestamp);
+        related_events.truncate(self.config.max_events.max(0) as usize);
+
+        let mut metrics = Vec::new();
+        let mut forecasts = Vec::new();
+        for metric_name in PostmortemBundle::anomalous_metrics(incident) {
+            let windows = match self
+                .database
+                .query_aggregated_metrics(&metric_name, self.config.time_window, start, end)
+                .await
+            {
+                Ok(windows) => windows,
+                Err(e) => {
+                    warn!("Skipping metric {} in postmortem: {}", metric_name, e);
+                    continue;
+                }
+            };
+            forecasts.extend(ForecastComparison::from_windows(
+                &metric_name,
+                &windows,
+                incident.opened_at,
+                self.config.forecast_alpha,
+            ));
+            metrics.push(MetricSnapshot {
+                metric_name,
+                windows,
+            });
+        }
+
+        let filter = EventFilter::event_type(EventType::Lifecycle)
+            .or(EventFilter::event_type(EventType::Governance));
+        let mut changes = self
+            .database
+            .search_events(&filter, start, end, Some(self.config.max_events), &scope)
+            .await?;
+        changes.sort_by_key(|e| e.common.timestamp);
+
+        let entities = match &self.topology {
+            Some(store) => {
+                let topology = store.current();
+                incident
+                    .entities
+                    .iter()
+                    .filter_map(|entity| topology.context(entity))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        info!(
+            related_events = related_events.len(),
+            metrics = metrics.len(),
+            changes = changes.len(),
+            "Compiled postmortem bundle"
+        );
+        Ok(PostmortemBundle {
+            generated_at: now,
+            window_start: start,
+            window_end: end,
+            incident: incident.clone(),
+            related_events,
+            metrics,
+            forecasts,
+            changes,
+            entities,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::incidents::IncidentSignal;
+    use crate::reporting::ReportFormat;
+    use crate::schemas::events::Severity;
+    use uuid::Uuid;
+
+    fn window(minute: i64, avg: f64) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: "latency_ms".to_string(),
+            time_window: "5m".to_string(),
+            window_start: DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+                + Duration::minutes(minute),
+            tags: serde_json::json!({}),
+            avg,
+            min: avg,
+            max: avg,
+            p50: avg,
+            p95: avg,
+            p99: avg,
+            stddev: None,
+            count: 1,
+            sum: avg,
+            histogram: None,
+        }
+    }
+
+    #[test]
+    fn test_bundle_compares_forecast_and_renders() {
+        let windows = vec![window(10, 110.0), window(0, 100.0), window(20, 300.0)];
+        let opened_at = windows[2].window_start;
+        let comparison =
+            ForecastComparison::from_windows("latency_ms", &windows, opened_at, 0.5).unwrap();
+        assert_eq!(comparison.baseline_windows, 2);
+        assert_eq!(comparison.forecast, 105.0);
+        assert_eq!(comparison.points.len(), 1);
+        assert!((comparison.peak_deviation_pct().unwrap() - 185.714).abs() < 0.01);
+        assert!(
+            ForecastComparison::from_windows("latency_ms", &windows[..2], opened_at, 0.5).is_none()
+        );
+
+        let mut incident = Incident::from_signal(IncidentSignal {
+            kind: SignalKind::Anomaly,
+            signal_id: Uuid::new_v4(),
+            at: opened_at,
+            summary: "latency_ms".to_string(),
+            severity: Severity::Error,
+            environment: "production".to_string(),
+            correlation_id: None,
+            entities: Vec::new(),
+        });
+        incident.add_note(
+            Some("oncall".to_string()),
+            "Rolled back".to_string(),
+            opened_at,
+        );
+        let config = PostmortemConfig::default();
+        let now = opened_at + Duration::minutes(10);
+        let (start, end) = PostmortemBundle::window(&incident, &config, now);
+        assert_eq!(start, opened_at - Duration::minutes(120));
+        assert_eq!(end, now);
+
+        let bundle = PostmortemBundle {
+            generated_at: now,
+            window_start: start,
+            window_end: end,
+            incident,
+            related_events: Vec::new(),
+            metrics: Vec::new(),
+            forecasts: vec![comparison],
+            changes: Vec::new(),
+            entities: Vec::new(),
+        };
+        let markdown = bundle.to_document().render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Postmortem: Anomaly in latency_ms"));
+        assert!(markdown.contains("| latency_ms | 2 | 105.00 | 300.00 | +185.7 |"));
+        assert!(markdown.contains("oncall: Rolled back"));
+    }
+}
@@ -0,0 +1,413 @@
+//! Report Rendering
+//!
+//! A format-neutral report document (summary lines, deltas, sparklines, and
+//! tables) and its JSON, Markdown, and HTML renderings.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Output format of a rendered report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Json,
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "application/json",
+            ReportFormat::Markdown => "text/markdown",
+            ReportFormat::Html => "text/html",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            other => anyhow::bail!("Unknown report format '{}'", other),
+        }
+    }
+}
+
+/// A titled table within a report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ReportTable {
+    pub fn new(title: &str, columns: &[&str]) -> Self {
+        Self {
+            title: title.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+}
+
+/// Sparkline glyphs from lowest to highest
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A headline figure compared against a baseline, e.g. the previous period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportDelta {
+    pub label: String,
+    pub current: f64,
+    pub baseline: f64,
+    /// Suffix shown after both figures, e.g. `USD` or `%`
+    #[serde(default)]
+    pub unit: String,
+    /// Whether an increase is an improvement; decides how deltas are colored
+    #[serde(default)]
+    pub higher_is_better: bool,
+}
+
+impl ReportDelta {
+    pub fn new(label: &str, current: f64, baseline: f64) -> Self {
+        Self {
+            label: label.to_string(),
+            current,
+            baseline,
+            unit: String::new(),
+            higher_is_better: false,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    pub fn higher_is_better(mut self) -> Self {
+        self.higher_is_better = true;
+        self
+    }
+
+    /// Relative change in percent, `None` against a zero baseline
+    pub fn change_pct(&self) -> Option<f64> {
+        (self.baseline != 0.0).then(|| (self.current - self.baseline) / self.baseline.abs() * 100.0)
+    }
+
+    /// `Some(true)` when the change is an improvement, `None` when unchanged
+    pub fn improved(&self) -> Option<bool> {
+        if self.current == self.baseline {
+            None
+        } else {
+            Some((self.current > self.baseline) == self.higher_is_better)
+        }
+    }
+
+    /// Arrow and relative change, e.g. `▲ 12.5%`
+    pub fn change_text(&self) -> String {
+        let arrow = if self.current > self.baseline {
+            '▲'
+        } else if self.current < self.baseline {
+            '▼'
+        } else {
+            '='
+        };
+        match self.change_pct() {
+            Some(pct) => format!("{} {:.1}%", arrow, pct.abs()),
+            None => format!("{} n/a", arrow),
+        }
+    }
+
+    /// Current value with its change, e.g. `12.00 USD (▲ 20.0% vs 10.00 USD)`
+    pub fn display(&self) -> String {
+        format!(
+            "{} ({} vs {})",
+            self.format_value(self.current),
+            self.change_text(),
+            self.format_value(self.baseline)
+        )
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        match self.unit.as_str() {
+            "" => format!("{:.2}", value),
+            "%" => format!("{:.2}%", value),
+            unit => format!("{:.2} {}", value, unit),
+        }
+    }
+}
+
+/// A labelled series drawn as a text sparkline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportSparkline {
+    pub label: String,
+    pub values: Vec<f64>,
+}
+
+impl ReportSparkline {
+    pub fn new(label: &str, values: Vec<f64>) -> Self {
+        Self {
+            label: label.to_string(),
+            values,
+        }
+    }
+
+    /// The series as block glyphs scaled between its minimum and maximum
+    pub fn render(&self) -> String {
+        let min = self.values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self
+            .values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let range = max - min;
+        self.values
+            .iter()
+            .map(|value| {
+                if range <= 0.0 {
+                    return SPARK_LEVELS[0];
+                }
+                let level = ((value - min) / range * (SPARK_LEVELS.len() - 1) as f64).round();
+                SPARK_LEVELS[level as usize]
+            })
+            .collect()
+    }
+
+    /// Sparkline followed by the range of the series, e.g. `▁▄█ (0 – 12)`
+    pub fn display(&self) -> String {
+        if self.values.is_empty() {
+            return "no data".to_string();
+        }
+        let min = self.values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self
+            .values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        format!("{} ({} – {})", self.render(), min, max)
+    }
+}
+
+/// A report ready to be rendered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportDocument {
+    pub title: String,
+    pub generated_at: DateTime<Utc>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Headline figures as label/value pairs
+    pub summary: Vec<(String, String)>,
+    #[serde(default)]
+    pub deltas: Vec<ReportDelta>,
+    #[serde(default)]
+    pub sparklines: Vec<ReportSparkline>,
+    pub tables: Vec<ReportTable>,
+}
+
+impl ReportDocument {
+    pub fn new(title: &str, period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Self {
+        Self {
+            title: title.to_string(),
+            generated_at: Utc::now(),
+            period_start,
+            period_end,
+            summary: Vec::new(),
+            deltas: Vec::new(),
+            sparklines: Vec::new(),
+            tables: Vec::new(),
+        }
+    }
+
+    pub fn with_summary(mut self, label: &str, value: impl ToString) -> Self {
+        self.summary.push((label.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_delta(mut self, delta: ReportDelta) -> Self {
+        self.deltas.push(delta);
+        self
+    }
+
+    pub fn with_sparkline(mut self, sparkline: ReportSparkline) -> Self {
+        self.sparklines.push(sparkline);
+        self
+    }
+
+    pub fn with_table(mut self, table: ReportTable) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\n_{} to {}_\n\n",
+            self.title,
+            self.period_start.to_rfc3339(),
+            self.period_end.to_rfc3339()
+        );
+
+        for (label, value) in &self.summary {
+            out.push_str(&format!("- **{}:** {}\n", label, value));
+        }
+        for delta in &self.deltas {
+            out.push_str(&format!("- **{}:** {}\n", delta.label, delta.display()));
+        }
+        for sparkline in &self.sparklines {
+            out.push_str(&format!(
+                "- **{}:** `{}`\n",
+                sparkline.label,
+                sparkline.display()
+            ));
+        }
+
+        for table in &self.tables {
+            out.push_str(&format!("\n## {}\n\n", table.title));
+            if table.rows.is_empty() {
+                out.push_str("_None_\n");
+                continue;
+            }
+            out.push_str(&format!("| {} |\n", table.columns.join(" | ")));
+            out.push_str(&format!("|{}\n", " --- |".repeat(table.columns.len())));
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<html><head><meta charset=\"utf-8\"><title>{title}</title></head><body>\
+             <h1>{title}</h1><p><em>{} to {}</em></p>",
+            self.period_start.to_rfc3339(),
+            self.period_end.to_rfc3339(),
+            title = escape_html(&self.title),
+        );
+
+        let headline: Vec<(&str, String)> = self
+            .summary
+            .iter()
+            .map(|(label, value)| (label.as_str(), value.clone()))
+            .chain(self.deltas.iter().map(|d| (d.label.as_str(), d.display())))
+            .chain(
+                self.sparklines
+                    .iter()
+                    .map(|s| (s.label.as_str(), s.display())),
+            )
+            .collect();
+        if !headline.is_empty() {
+            out.push_str("<ul>");
+            for (label, value) in headline {
+                out.push_str(&format!(
+                    "<li><strong>{}:</strong> {}</li>",
+                    escape_html(label),
+                    escape_html(&value)
+                ));
+            }
+            out.push_str("</ul>");
+        }
+
+        for table in &self.tables {
+            out.push_str(&format!("<h2>{}</h2>", escape_html(&table.title)));
+            if table.rows.is_empty() {
+                out.push_str("<p><em>None</em></p>");
+                continue;
+            }
+            out.push_str("<table><thead><tr>");
+            for column in &table.columns {
+                out.push_str(&format!("<th>{}</th>", escape_html(column)));
+            }
+            out.push_str("</tr></thead><tbody>");
+            for row in &table.rows {
+                out.push_str("<tr>");
+                for cell in row {
+                    out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                }
+                out.push_str("</tr>");
+            }
+            out.push_str("</tbody></table>");
+        }
+
+        out.push_str("</body></html>");
+        out
+    }
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn document() -> ReportDocument {
+        let end = Utc::now();
+        let mut table = ReportTable::new("Cost by model", &["Model", "Cost (USD)"]);
+        table.push_row(vec!["gpt-4".to_string(), "10.00".to_string()]);
+        table.push_row(vec!["<script>".to_string(), "a|b".to_string()]);
+
+        ReportDocument::new("Cost summary", end - Duration::days(1), end)
+            .with_summary("Total cost", "$10.00")
+            .with_table(table)
+            .with_table(ReportTable::new("Top consumers", &["Name"]))
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = document().render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Cost summary\n"));
+        assert!(markdown.contains("- **Total cost:** $10.00\n"));
+        assert!(markdown.contains("| Model | Cost (USD) |\n| --- | --- |\n| gpt-4 | 10.00 |\n"));
+        assert!(markdown.contains("a\\|b"));
+        assert!(markdown.contains("## Top consumers\n\n_None_\n"));
+    }
+
+    #[test]
+    fn test_render_html_and_json() {
+        let html = document().render(ReportFormat::Html);
+        assert!(html.contains("<h1>Cost summary</h1>"));
+        assert!(html.contains("<td>&lt;script&gt;</td>"));
+        assert!(!html.contains("<script>"));
+
+        let doc = document();
+        let parsed: ReportDocument = serde_json::from_str(&doc.render(ReportFormat::Json)).unwrap();
+        assert_eq!(parsed, doc);
+
+        assert_eq!(
+            "md".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Markdown
+        );
+        assert!("pdf".parse::<ReportFormat>().is_err());
+    }
+}
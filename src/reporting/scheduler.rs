@@ -0,0 +1,704 @@
+//! Scheduled Reports
+//!
+//! Runs configured analytics reports (cost summary, anomaly digest, SLO
+//! compliance) on cron schedules, renders them, and delivers them through the
+//! notification channels.
+//!
+//! Schedules accept standard five-field cron expressions as well as the
+//! six- and seven-field forms with seconds and years, evaluated in UTC.
+
+use super::render::{ReportDelta, ReportDocument, ReportFormat, ReportSparkline, ReportTable};
+use crate::adapters::costops::{CostOpsAdapter, CostSummary, CostSummaryQuery};
+use crate::alerting::digest::alert_type;
+use crate::alerting::{Notification, NotificationRouter};
+use crate::database::{Database, EnvironmentScope, EventFilter};
+use crate::schemas::events::{AnalyticsEvent, EventType, Severity};
+use crate::slo::{SloEngine, SloStatus};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Alerts listed individually in an anomaly digest
+const DIGEST_RECENT_ALERTS: usize = 20;
+
+/// Points in the alert-rate sparkline of an anomaly digest
+const DIGEST_SPARKLINE_POINTS: usize = 48;
+
+/// Nesting depth of payload fields listed in a single-alert report
+const ALERT_DETAIL_DEPTH: usize = 3;
+
+/// Report produced by a schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    CostSummary,
+    AnomalyDigest,
+    SloCompliance,
+}
+
+fn default_lookback_hours() -> i64 {
+    24
+}
+
+/// A report and when and where to deliver it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDefinition {
+    pub name: String,
+    pub kind: ReportKind,
+    /// Cron expression, evaluated in UTC
+    pub schedule: String,
+    #[serde(default)]
+    pub format: ReportFormat,
+    /// Notification channel names
+    pub channels: Vec<String>,
+    /// Period covered by the report, ending at the run time
+    #[serde(default = "default_lookback_hours")]
+    pub lookback_hours: i64,
+}
+
+/// Parse a cron expression, accepting the standard five-field form
+pub fn parse_schedule(expression: &str) -> Result<Schedule> {
+    let fields = expression.split_whitespace().count();
+    let normalized = match fields {
+        5 => format!("0 {}", expression.trim()),
+        6 | 7 => expression.trim().to_string(),
+        _ => bail!(
+            "Cron expression '{}' must have 5, 6, or 7 fields",
+            expression
+        ),
+    };
+    Schedule::from_str(&normalized)
+        .with_context(|| format!("Invalid cron expression '{}'", expression))
+}
+
+// ========== Report Builders ==========
+
+/// Cost summary from CostOps, compared against the previous period when given
+pub fn cost_summary_report(
+    summary: &CostSummary,
+    previous: Option<&CostSummary>,
+) -> ReportDocument {
+    let mut by_model = ReportTable::new("Cost by model", &["Model", "Cost (USD)"]);
+    for (model, cost) in sorted_by_value(&summary.breakdown.by_model) {
+        by_model.push_row(vec![model, format!("{:.2}", cost)]);
+    }
+    let mut by_team = ReportTable::new("Cost by team", &["Team", "Cost (USD)"]);
+    for (team, cost) in sorted_by_value(&summary.breakdown.by_team) {
+        by_team.push_row(vec![team, format!("{:.2}", cost)]);
+    }
+    let mut consumers = ReportTable::new("Top consumers", &["Name", "Cost (USD)", "Share"]);
+    for consumer in &summary.top_consumers {
+        consumers.push_row(vec![
+            consumer.name.clone(),
+            format!("{:.2}", consumer.cost_usd),
+            format!("{:.1}%", consumer.percentage),
+        ]);
+    }
+
+    let mut document =
+        ReportDocument::new("Cost summary", summary.period_start, summary.period_end);
+    document = match previous {
+        Some(previous) => document.with_delta(
+            ReportDelta::new(
+                "Total cost",
+                summary.total_cost_usd,
+                previous.total_cost_usd,
+            )
+            .with_unit(&summary.currency),
+        ),
+        None => document.with_summary(
+            "Total cost",
+            format!("{:.2} {}", summary.total_cost_usd, summary.currency),
+        ),
+    };
+    document
+        .with_table(by_model)
+        .with_table(by_team)
+        .with_table(consumers)
+}
+
+fn sorted_by_value(values: &HashMap<String, f64>) -> Vec<(String, f64)> {
+    let mut sorted: Vec<(String, f64)> = values.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted
+}
+
+/// Folds alert events into an anomaly digest
+#[derive(Debug, Default)]
+pub struct AnomalyDigestBuilder {
+    counts: BTreeMap<(String, Severity), u64>,
+    recent: Vec<AnalyticsEvent>,
+    /// Alerts per hour, keyed by the start of the hour
+    hourly: BTreeMap<DateTime<Utc>, u64>,
+    total: u64,
+}
+
+impl AnomalyDigestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, event: &AnalyticsEvent) {
+        *self
+            .counts
+            .entry((alert_type(event), event.common.severity.clone()))
+            .or_default() += 1;
+        self.total += 1;
+        if let Ok(hour) = event.common.timestamp.duration_trunc(Duration::hours(1)) {
+            *self.hourly.entry(hour).or_default() += 1;
+        }
+
+        self.recent.push(event.clone());
+        if self.recent.len() > DIGEST_RECENT_ALERTS * 2 {
+            self.trim_recent();
+        }
+    }
+
+    /// Alerts observed so far
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    fn trim_recent(&mut self) {
+        self.recent
+            .sort_by(|a, b| b.common.timestamp.cmp(&a.common.timestamp));
+        self.recent.truncate(DIGEST_RECENT_ALERTS);
+    }
+
+    /// Alert counts per hour over the period, merged into at most
+    /// `DIGEST_SPARKLINE_POINTS` points for long periods
+    fn rate_sparkline(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> ReportSparkline {
+        let Ok(first) = start.duration_trunc(Duration::hours(1)) else {
+            return ReportSparkline::new("Alerts per hour", Vec::new());
+        };
+        let hours = ((end - first).num_hours() + 1).max(1) as usize;
+        let per_point = (hours - 1) / DIGEST_SPARKLINE_POINTS + 1;
+        let label = match per_point {
+            1 => "Alerts per hour".to_string(),
+            n => format!("Alerts per {} hours", n),
+        };
+        let values = (0..hours)
+            .map(|i| {
+                let hour = first + Duration::hours(i as i64);
+                self.hourly.get(&hour).copied().unwrap_or(0)
+            })
+            .collect::<Vec<u64>>()
+            .chunks(per_point)
+            .map(|chunk| chunk.iter().sum::<u64>() as f64)
+            .collect();
+        ReportSparkline::new(&label, values)
+    }
+
+    pub fn build(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> ReportDocument {
+        self.trim_recent();
+        let rate = self.rate_sparkline(start, end);
+
+        let mut counts: Vec<((String, Severity), u64)> = self.counts.into_iter().collect();
+        counts.sort_by(|a, b| {
+            b.0 .1
+                .cmp(&a.0 .1)
+                .then_with(|| b.1.cmp(&a.1))
+                .then_with(|| a.0 .0.cmp(&b.0 .0))
+        });
+        let mut by_type = ReportTable::new("Alerts by type", &["Type", "Severity", "Count"]);
+        for ((kind, severity), count) in counts {
+            by_type.push_row(vec![
+                kind,
+                format!("{:?}", severity).to_lowercase(),
+                count.to_string(),
+            ]);
+        }
+
+        let mut recent = ReportTable::new("Most recent alerts", &["Time", "Severity", "Tags"]);
+        for event in &self.recent {
+            let mut tags: Vec<String> = event
+                .common
+                .tags
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect();
+            tags.sort();
+            recent.push_row(vec![
+                event.common.timestamp.to_rfc3339(),
+                format!("{:?}", event.common.severity).to_lowercase(),
+                tags.join(", "),
+            ]);
+        }
+
+        ReportDocument::new("Anomaly digest", start, end)
+            .with_summary("Alerts", self.total)
+            .with_sparkline(rate)
+            .with_table(by_type)
+            .with_table(recent)
+    }
+}
+
+/// A single alert as a report, for alerts paged immediately
+pub fn alert_report(event: &AnalyticsEvent) -> ReportDocument {
+    let mut tags: Vec<String> = event
+        .common
+        .tags
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    tags.sort();
+
+    let mut details = ReportTable::new("Details", &["Field", "Value"]);
+    if let Ok(serde_json::Value::Object(payload)) = serde_json::to_value(&event.payload) {
+        if let Some(data) = payload.get("data") {
+            flatten_fields("", data, 0, &mut details);
+        }
+    }
+
+    let at = event.common.timestamp;
+    ReportDocument::new(&alert_type(event), at, at)
+        .with_summary(
+            "Severity",
+            format!("{:?}", event.common.severity).to_lowercase(),
+        )
+        .with_summary("Source", format!("{:?}", event.common.source_module))
+        .with_summary("Environment", &event.common.environment)
+        .with_summary(
+            "Tags",
+            if tags.is_empty() {
+                "none".to_string()
+            } else {
+                tags.join(", ")
+            },
+        )
+        .with_table(details)
+}
+
+fn flatten_fields(prefix: &str, value: &serde_json::Value, depth: usize, table: &mut ReportTable) {
+    match value {
+        serde_json::Value::Object(fields) if depth < ALERT_DETAIL_DEPTH => {
+            for (key, value) in fields {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_fields(&path, value, depth + 1, table);
+            }
+        }
+        serde_json::Value::String(text) => table.push_row(vec![prefix.to_string(), text.clone()]),
+        serde_json::Value::Null => {}
+        other => table.push_row(vec![prefix.to_string(), other.to_string()]),
+    }
+}
+
+/// SLO compliance across every defined objective
+pub fn slo_compliance_report(
+    statuses: &[SloStatus],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ReportDocument {
+    let mut table = ReportTable::new(
+        "Objectives",
+        &["SLO", "Target", "Compliance", "Budget left", "Firing"],
+    );
+    let mut meeting = 0;
+    let mut breaches = Vec::new();
+    for status in statuses {
+        if status.meeting_target == Some(true) {
+            meeting += 1;
+        }
+        if let (Some(false), Some(compliance)) = (status.meeting_target, status.compliance) {
+            breaches.push(
+                ReportDelta::new(&status.objective.name, compliance, status.objective.target)
+                    .with_unit("%")
+                    .higher_is_better(),
+            );
+        }
+        table.push_row(vec![
+            status.objective.name.clone(),
+            format!("{}%", status.objective.target),
+            status
+                .compliance
+                .map_or_else(|| "n/a".to_string(), |c| format!("{:.3}%", c)),
+            status
+                .error_budget_remaining
+                .map_or_else(|| "n/a".to_string(), |b| format!("{:.1}%", b * 100.0)),
+            status.firing().count().to_string(),
+        ]);
+    }
+
+    let mut document = ReportDocument::new("SLO compliance", start, end).with_summary(
+        "Objectives meeting target",
+        format!("{} of {}", meeting, statuses.len()),
+    );
+    // Breached objectives show their compliance against the target
+    for breach in breaches {
+        document = document.with_delta(breach);
+    }
+    document.with_table(table)
+}
+
+// ========== Scheduler ==========
+
+/// Scheduler statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSchedulerStats {
+    pub reports_run: u64,
+    pub reports_failed: u64,
+    pub deliveries: u64,
+}
+
+/// Runs report definitions on their cron schedules
+pub struct ReportScheduler {
+    reports: Vec<(ReportDefinition, Schedule)>,
+    router: Arc<NotificationRouter>,
+    database: Option<Arc<Database>>,
+    costops: Option<Arc<CostOpsAdapter>>,
+    slo: Option<Arc<SloEngine>>,
+    reports_run: AtomicU64,
+    reports_failed: AtomicU64,
+    deliveries: AtomicU64,
+}
+
+impl ReportScheduler {
+    pub fn new(router: Arc<NotificationRouter>) -> Self {
+        Self {
+            reports: Vec::new(),
+            router,
+            database: None,
+            costops: None,
+            slo: None,
+            reports_run: AtomicU64::new(0),
+            reports_failed: AtomicU64::new(0),
+            deliveries: AtomicU64::new(0),
+        }
+    }
+
+    /// Source of alert events for anomaly digests
+    pub fn with_database(mut self, database: Arc<Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Source of cost summaries
+    pub fn with_costops(mut self, costops: Arc<CostOpsAdapter>) -> Self {
+        self.costops = Some(costops);
+        self
+    }
+
+    /// Source of SLO compliance
+    pub fn with_slo_engine(mut self, slo: Arc<SloEngine>) -> Self {
+        self.slo = Some(slo);
+        self
+    }
+
+    /// Add a report, validating its schedule
+    pub fn add(&mut self, definition: ReportDefinition) -> Result<()> {
+        let schedule = parse_schedule(&definition.schedule)
+            .with_context(|| format!("Report '{}'", definition.name))?;
+        let known = self.router.channel_names();
+        for channel in &definition.channels {
+            if !known.contains(channel) {
+                warn!(report = %definition.name, channel = %channel, "Report targets an unknown channel");
+            }
+        }
+        self.reports.push((definition, schedule));
+        Ok(())
+    }
+
+    /// Add reports from a YAML list
+    pub fn load_yaml(&mut self, yaml: &str) -> Result<usize> {
+        let definitions: Vec<ReportDefinition> =
+            serde_yaml::from_str(yaml).context("Failed to parse report schedules")?;
+        let count = definitions.len();
+        for definition in definitions {
+            self.add(definition)?;
+        }
+        Ok(count)
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &ReportDefinition> {
+        self.reports.iter().map(|(definition, _)| definition)
+    }
+
+    /// Earliest fire time of any report after `after`
+    pub fn next_due(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.reports
+            .iter()
+            .filter_map(|(_, schedule)| schedule.after(&after).next())
+            .min()
+    }
+
+    /// Reports with a fire time in `(after, now]`
+    pub fn due(&self, after: DateTime<Utc>, now: DateTime<Utc>) -> Vec<&ReportDefinition> {
+        self.reports
+            .iter()
+            .filter(|(_, schedule)| schedule.after(&after).next().map_or(false, |t| t <= now))
+            .map(|(definition, _)| definition)
+            .collect()
+    }
+
+    /// Build the report covering the lookback period ending at `now`
+    pub async fn build(
+        &self,
+        definition: &ReportDefinition,
+        now: DateTime<Utc>,
+    ) -> Result<ReportDocument> {
+        let lookback = Duration::hours(definition.lookback_hours.max(1));
+        let start = now - lookback;
+
+        match definition.kind {
+            ReportKind::CostSummary => {
+                let costops = self
+                    .costops
+                    .as_ref()
+                    .context("Cost summary reports need the CostOps adapter")?;
+                let summary = costops
+                    .fetch_cost_summary(CostSummaryQuery {
+                        start_time: Some(start),
+                        end_time: Some(now),
+                        ..Default::default()
+                    })
+                    .await?;
+                // The comparison is optional; the report still goes out without it
+                let previous = match costops
+                    .fetch_cost_summary(CostSummaryQuery {
+                        start_time: Some(start - lookback),
+                        end_time: Some(start),
+                        ..Default::default()
+                    })
+                    .await
+                {
+                    Ok(previous) => Some(previous),
+                    Err(e) => {
+                        warn!(report = %definition.name, "Previous cost period unavailable: {}", e);
+                        None
+                    }
+                };
+                Ok(cost_summary_report(&summary, previous.as_ref()))
+            }
+            ReportKind::AnomalyDigest => {
+                let database = self
+                    .database
+                    .as_ref()
+                    .context("Anomaly digests need a database")?;
+                let mut digest = AnomalyDigestBuilder::new();
+                database
+                    .scan_events(
+                        start,
+                        now,
+                        Some(&EventFilter::event_type(EventType::Alert)),
+                        &EnvironmentScope::Default,
+                        |event| digest.observe(event),
+                    )
+                    .await?;
+                Ok(digest.build(start, now))
+            }
+            ReportKind::SloCompliance => {
+                let slo = self
+                    .slo
+                    .as_ref()
+                    .context("SLO compliance reports need the SLO engine")?;
+                let mut statuses = Vec::new();
+                for objective in slo.list() {
+                    statuses.push(slo.evaluate(&objective, now).await?);
+                }
+                Ok(slo_compliance_report(&statuses, start, now))
+            }
+        }
+    }
+
+    /// Build, render, and deliver one report, returning the number of channels reached
+    pub async fn run(&self, definition: &ReportDefinition, now: DateTime<Utc>) -> Result<usize> {
+        let document = match self.build(definition, now).await {
+            Ok(document) => document,
+            Err(e) => {
+                self.reports_failed.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+        self.reports_run.fetch_add(1, Ordering::Relaxed);
+
+        let notification = Notification::new(
+            format!("{}: {}", definition.name, document.title),
+            document.render(definition.format),
+            Severity::Info,
+        )
+        .with_content_type(definition.format.content_type())
+        .with_tag("report", definition.name.clone())
+        .with_document(document);
+
+        let delivered = self
+            .router
+            .broadcast(&definition.channels, &notification)
+            .await;
+        self.deliveries
+            .fetch_add(delivered as u64, Ordering::Relaxed);
+        info!(report = %definition.name, delivered, "Scheduled report delivered");
+        Ok(delivered)
+    }
+
+    /// Run reports as they come due until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut last = Utc::now();
+            while let Some(next) = self.next_due(last) {
+                if let Ok(wait) = (next - Utc::now()).to_std() {
+                    tokio::time::sleep(wait).await;
+                }
+
+                let now = Utc::now();
+                for definition in self.due(last, now) {
+                    if let Err(e) = self.run(definition, now).await {
+                        warn!(report = %definition.name, "Scheduled report failed: {}", e);
+                    }
+                }
+                last = now;
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> ReportSchedulerStats {
+        ReportSchedulerStats {
+            reports_run: self.reports_run.load(Ordering::Relaxed),
+            reports_failed: self.reports_failed.load(Ordering::Relaxed),
+            deliveries: self.deliveries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::events::{CommonEventFields, CustomPayload, EventPayload, SourceModule};
+    use chrono::TimeZone;
+
+    fn alert(custom_type: &str, severity: Severity, minutes_ago: i64) -> AnalyticsEvent {
+        AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: uuid::Uuid::new_v4(),
+                timestamp: Utc::now() - Duration::minutes(minutes_ago),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Alert,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: "1.0.0".to_string(),
+                severity,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: custom_type.to_string(),
+                data: serde_json::json!({}),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let daily = parse_schedule("0 9 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        assert_eq!(
+            daily.after(&after).next(),
+            Some(Utc.with_ymd_and_hms(2024, 3, 2, 9, 0, 0).unwrap())
+        );
+
+        assert!(parse_schedule("0 0 * * * *").is_ok());
+        assert!(parse_schedule("* *").is_err());
+        assert!(parse_schedule("99 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_due_reports() {
+        let mut scheduler = ReportScheduler::new(Arc::new(NotificationRouter::new()));
+        let yaml = r#"
+- name: daily-cost
+  kind: cost_summary
+  schedule: "0 9 * * *"
+  channels: [ops]
+- name: hourly-anomalies
+  kind: anomaly_digest
+  schedule: "0 * * * *"
+  format: html
+  channels: [ops]
+  lookback_hours: 1
+"#;
+        assert_eq!(scheduler.load_yaml(yaml).unwrap(), 2);
+
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+        assert_eq!(scheduler.next_due(at(8, 30)), Some(at(9, 0)));
+
+        let names = |reports: Vec<&ReportDefinition>| -> Vec<String> {
+            reports.iter().map(|r| r.name.clone()).collect()
+        };
+        assert_eq!(
+            names(scheduler.due(at(8, 30), at(9, 0))),
+            vec!["daily-cost", "hourly-anomalies"]
+        );
+        assert_eq!(
+            names(scheduler.due(at(9, 0), at(9, 59))),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            names(scheduler.due(at(9, 0), at(10, 0))),
+            vec!["hourly-anomalies"]
+        );
+    }
+
+    #[test]
+    fn test_anomaly_digest() {
+        let mut digest = AnomalyDigestBuilder::new();
+        for minutes in 0..30 {
+            digest.observe(&alert("changepoint.detected", Severity::Warning, minutes));
+        }
+        digest.observe(&alert(
+            "provider.failover_recommendation",
+            Severity::Critical,
+            45,
+        ));
+
+        let now = Utc::now();
+        let report = digest.build(now - Duration::hours(1), now);
+        assert_eq!(
+            report.summary,
+            vec![("Alerts".to_string(), "31".to_string())]
+        );
+
+        let by_type = &report.tables[0];
+        assert_eq!(by_type.rows[0][0], "provider.failover_recommendation");
+        assert_eq!(
+            by_type.rows[1],
+            vec!["changepoint.detected", "warning", "30"]
+        );
+        assert_eq!(report.tables[1].rows.len(), DIGEST_RECENT_ALERTS);
+
+        let rate = &report.sparklines[0].values;
+        assert_eq!(rate.iter().sum::<f64>(), 31.0);
+        assert!(rate.len() <= 3);
+    }
+
+    #[test]
+    fn test_alert_report() {
+        let mut event = alert("changepoint.detected", Severity::Error, 0);
+        event
+            .common
+            .tags
+            .insert("model".to_string(), "gpt-4".to_string());
+        event.payload = EventPayload::Custom(CustomPayload {
+            custom_type: "changepoint.detected".to_string(),
+            data: serde_json::json!({ "metric": "latency_ms", "shift": { "before": 120.0 } }),
+        });
+
+        let report = alert_report(&event);
+        assert_eq!(report.title, "changepoint.detected");
+        assert!(report
+            .summary
+            .contains(&("Tags".to_string(), "model=gpt-4".to_string())));
+        let details = &report.tables[0].rows;
+        assert!(details.contains(&vec!["data.metric".to_string(), "latency_ms".to_string()]));
+        assert!(details.contains(&vec!["data.shift.before".to_string(), "120.0".to_string()]));
+    }
+}
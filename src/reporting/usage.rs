@@ -0,0 +1,132 @@
+//! Usage Reports
+//!
+//! Monthly chargeback reports built from hub usage metering records.
+
+use crate::export::tags::csv_field;
+use crate::metering::{UsagePeriod, UsageRecord};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Usage totals for a single tenant
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TenantUsageSummary {
+    pub tenant_id: String,
+    pub api_keys: usize,
+    pub api_calls: u64,
+    pub queries: u64,
+    pub query_compute_ms: u64,
+    pub storage_bytes_peak: u64,
+    pub alert_deliveries: u64,
+}
+
+impl TenantUsageSummary {
+    fn add(&mut self, record: &UsageRecord) {
+        if record.consumer.api_key_id.is_some() {
+            self.api_keys += 1;
+        }
+        self.api_calls += record.api_calls;
+        self.queries += record.queries;
+        self.query_compute_ms += record.query_compute_ms;
+        self.storage_bytes_peak += record.storage_bytes_peak;
+        self.alert_deliveries += record.alert_deliveries;
+    }
+}
+
+/// Monthly usage report across all consumers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub period: UsagePeriod,
+    pub generated_at: DateTime<Utc>,
+    pub tenants: Vec<TenantUsageSummary>,
+    pub totals: TenantUsageSummary,
+    pub records: Vec<UsageRecord>,
+}
+
+impl UsageReport {
+    /// Build a report from the usage records of a period
+    pub fn from_records(period: UsagePeriod, records: Vec<UsageRecord>) -> Self {
+        let mut by_tenant: BTreeMap<String, TenantUsageSummary> = BTreeMap::new();
+        let mut totals = TenantUsageSummary {
+            tenant_id: "*".to_string(),
+            ..Default::default()
+        };
+
+        for record in records.iter().filter(|r| r.period == period) {
+            by_tenant
+                .entry(record.consumer.tenant_id.clone())
+                .or_insert_with(|| TenantUsageSummary {
+                    tenant_id: record.consumer.tenant_id.clone(),
+                    ..Default::default()
+                })
+                .add(record);
+            totals.add(record);
+        }
+
+        Self {
+            period,
+            generated_at: Utc::now(),
+            tenants: by_tenant.into_values().collect(),
+            totals,
+            records: records.into_iter().filter(|r| r.period == period).collect(),
+        }
+    }
+
+    /// Get the summary for a tenant
+    pub fn tenant(&self, tenant_id: &str) -> Option<&TenantUsageSummary> {
+        self.tenants.iter().find(|t| t.tenant_id == tenant_id)
+    }
+
+    /// Render per-tenant summaries as CSV
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "period,tenant_id,api_keys,api_calls,queries,query_compute_ms,storage_bytes_peak,alert_deliveries\n",
+        );
+        for tenant in &self.tenants {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{}",
+                self.period,
+                csv_field(&tenant.tenant_id),
+                tenant.api_keys,
+                tenant.api_calls,
+                tenant.queries,
+                tenant.query_compute_ms,
+                tenant.storage_bytes_peak,
+                tenant.alert_deliveries
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metering::{Consumer, UsageMeter};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_usage_report_rolls_up_tenants() {
+        let meter = UsageMeter::new();
+        let at = Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap();
+        let period = UsagePeriod::containing(at);
+
+        meter.record_api_call_at(&Consumer::api_key("team-a", "k1"), "/api/v1/events", at);
+        meter.record_api_call_at(&Consumer::api_key("team-a", "k2"), "/api/v1/events", at);
+        meter.record_query_at(&Consumer::tenant("team,b"), 50, at);
+
+        let report = UsageReport::from_records(period, meter.records_for_period(period));
+
+        assert_eq!(report.tenants.len(), 2);
+        let team_a = report.tenant("team-a").unwrap();
+        assert_eq!(team_a.api_keys, 2);
+        assert_eq!(team_a.api_calls, 2);
+        assert_eq!(report.totals.query_compute_ms, 50);
+
+        let csv = report.to_csv();
+        assert!(csv.contains("2024-03,team-a,2,2,0,0,0,0"));
+        assert!(csv.contains("\"team,b\""));
+    }
+}
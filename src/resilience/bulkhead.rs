@@ -0,0 +1,171 @@
+//! Bulkhead Implementation
+//!
+//! Semaphore-based concurrency limiting so a slow downstream cannot exhaust the runtime.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{timeout, Duration};
+use tracing::warn;
+
+/// Concurrency limiter for a single downstream
+pub struct Bulkhead {
+    name: String,
+    semaphore: Arc<Semaphore>,
+    max_concurrent: usize,
+    queue_timeout: Duration,
+    metrics: Arc<BulkheadMetrics>,
+}
+
+#[derive(Default)]
+struct BulkheadMetrics {
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    timed_out: AtomicU64,
+}
+
+/// Permit held for the duration of a bulkhead-guarded call
+pub struct BulkheadPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Bulkhead {
+    /// Create a new bulkhead
+    ///
+    /// A `queue_timeout_ms` of zero rejects immediately when all slots are taken.
+    pub fn new(name: impl Into<String>, max_concurrent: usize, queue_timeout_ms: u64) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            name: name.into(),
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+            queue_timeout: Duration::from_millis(queue_timeout_ms),
+            metrics: Arc::new(BulkheadMetrics::default()),
+        }
+    }
+
+    /// Acquire a slot, waiting at most the configured queue timeout
+    pub async fn acquire(&self) -> Result<BulkheadPermit> {
+        let semaphore = self.semaphore.clone();
+
+        let permit = if self.queue_timeout.is_zero() {
+            match semaphore.try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+                    warn!(bulkhead = %self.name, "Bulkhead full, rejecting call");
+                    anyhow::bail!("Bulkhead {} is full", self.name);
+                }
+            }
+        } else {
+            match timeout(self.queue_timeout, semaphore.acquire_owned()).await {
+                Ok(Ok(permit)) => permit,
+                Ok(Err(_)) => anyhow::bail!("Bulkhead {} is closed", self.name),
+                Err(_) => {
+                    self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+                    self.metrics.timed_out.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        bulkhead = %self.name,
+                        timeout_ms = self.queue_timeout.as_millis() as u64,
+                        "Timed out waiting for bulkhead slot"
+                    );
+                    anyhow::bail!("Timed out waiting for bulkhead {}", self.name);
+                }
+            }
+        };
+
+        self.metrics.accepted.fetch_add(1, Ordering::Relaxed);
+        Ok(BulkheadPermit { _permit: permit })
+    }
+
+    /// Execute an operation inside the bulkhead
+    pub async fn execute<F, Fut, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let _permit = self.acquire().await?;
+        operation().await
+    }
+
+    /// Number of calls currently holding a slot
+    pub fn active(&self) -> usize {
+        self.max_concurrent - self.semaphore.available_permits()
+    }
+
+    /// Get bulkhead statistics
+    pub fn get_stats(&self) -> BulkheadStats {
+        BulkheadStats {
+            name: self.name.clone(),
+            max_concurrent: self.max_concurrent,
+            active: self.active(),
+            accepted: self.metrics.accepted.load(Ordering::Relaxed),
+            rejected: self.metrics.rejected.load(Ordering::Relaxed),
+            timed_out: self.metrics.timed_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Bulkhead statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkheadStats {
+    pub name: String,
+    pub max_concurrent: usize,
+    pub active: usize,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub timed_out: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bulkhead_rejects_when_full() {
+        let bulkhead = Bulkhead::new("test", 1, 0);
+
+        let permit = bulkhead.acquire().await.unwrap();
+        assert_eq!(bulkhead.active(), 1);
+        assert!(bulkhead.acquire().await.is_err());
+
+        drop(permit);
+        assert_eq!(bulkhead.active(), 0);
+        assert!(bulkhead.acquire().await.is_ok());
+
+        let stats = bulkhead.get_stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.timed_out, 0);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_queue_timeout() {
+        let bulkhead = Bulkhead::new("test", 1, 20);
+
+        let _permit = bulkhead.acquire().await.unwrap();
+        let result = bulkhead.execute(|| async { Ok(()) }).await;
+
+        assert!(result.is_err());
+        assert_eq!(bulkhead.get_stats().timed_out, 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_waits_for_slot() {
+        let bulkhead = Arc::new(Bulkhead::new("test", 1, 1000));
+
+        let permit = bulkhead.acquire().await.unwrap();
+        let waiter = {
+            let bulkhead = bulkhead.clone();
+            tokio::spawn(async move { bulkhead.execute(|| async { Ok(7) }).await })
+        };
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(permit);
+
+        assert_eq!(waiter.await.unwrap().unwrap(), 7);
+    }
+}
@@ -2,13 +2,15 @@
 //!
 //! Prevents cascading failures by breaking the circuit when error rate exceeds threshold.
 
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 use tracing::{info, warn};
 
 /// Circuit breaker states
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     /// Circuit is closed, operations allowed
     Closed,
@@ -120,7 +122,7 @@ impl CircuitBreaker {
 
     /// Get current circuit state
     pub async fn get_state(&self) -> CircuitState {
-        self.state.read().await.state.clone()
+        self.state.read().await.state
     }
 
     /// Reset circuit breaker to closed state
@@ -137,7 +139,7 @@ impl CircuitBreaker {
     pub async fn get_stats(&self) -> CircuitBreakerStats {
         let state = self.state.read().await;
         CircuitBreakerStats {
-            state: state.state.clone(),
+            state: state.state,
             failure_count: state.failure_count,
             success_count: state.success_count,
         }
@@ -152,6 +154,17 @@ pub struct CircuitBreakerStats {
     pub success_count: usize,
 }
 
+impl CircuitState {
+    /// Returns a stable lowercase label for logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,234 @@
+//! Fallback Implementation
+//!
+//! Serves a secondary, last-known-good, or static value when the primary operation fails.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Where a fallback-guarded value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackSource {
+    /// The primary operation succeeded
+    Primary,
+    /// The secondary operation succeeded
+    Secondary,
+    /// The last successful primary result was served
+    LastKnownGood,
+    /// The configured static value was served
+    Static,
+}
+
+/// Fallback combinator for a single operation's result type
+pub struct Fallback<T> {
+    static_value: Option<T>,
+    remember_last_good: bool,
+    max_staleness: Option<Duration>,
+    last_known_good: Arc<RwLock<Option<(T, DateTime<Utc>)>>>,
+}
+
+impl<T: Clone + Send + Sync> Fallback<T> {
+    /// Create a fallback with no alternatives configured
+    pub fn new() -> Self {
+        Self {
+            static_value: None,
+            remember_last_good: false,
+            max_staleness: None,
+            last_known_good: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Serve this value when nothing better is available
+    pub fn with_static(mut self, value: T) -> Self {
+        self.static_value = Some(value);
+        self
+    }
+
+    /// Remember the last successful primary result and serve it on failure
+    pub fn with_last_known_good(mut self) -> Self {
+        self.remember_last_good = true;
+        self
+    }
+
+    /// Refuse to serve a last-known-good value older than `max_staleness`
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = Some(max_staleness);
+        self
+    }
+
+    /// Execute the primary operation, falling back on failure
+    pub async fn execute<F, Fut>(&self, primary: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.execute_with_source(primary).await.map(|(value, _)| value)
+    }
+
+    /// Execute the primary operation, returning the value and where it came from
+    pub async fn execute_with_source<F, Fut>(&self, primary: F) -> Result<(T, FallbackSource)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match primary().await {
+            Ok(value) => {
+                self.remember(&value).await;
+                Ok((value, FallbackSource::Primary))
+            }
+            Err(e) => self.recover(e).await,
+        }
+    }
+
+    /// Execute the primary operation, trying a secondary operation before cached values
+    pub async fn execute_or_else<F, Fut, G, GFut>(
+        &self,
+        primary: F,
+        secondary: G,
+    ) -> Result<(T, FallbackSource)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        G: FnOnce() -> GFut,
+        GFut: Future<Output = Result<T>>,
+    {
+        match primary().await {
+            Ok(value) => {
+                self.remember(&value).await;
+                Ok((value, FallbackSource::Primary))
+            }
+            Err(primary_err) => {
+                warn!(error = %primary_err, "Primary operation failed, trying secondary");
+                match secondary().await {
+                    Ok(value) => Ok((value, FallbackSource::Secondary)),
+                    Err(secondary_err) => self
+                        .recover(primary_err.context(format!(
+                            "secondary operation also failed: {}",
+                            secondary_err
+                        )))
+                        .await,
+                }
+            }
+        }
+    }
+
+    /// Last successful primary result and when it was recorded
+    pub async fn last_known_good(&self) -> Option<(T, DateTime<Utc>)> {
+        self.last_known_good.read().await.clone()
+    }
+
+    /// Forget the last-known-good value
+    pub async fn clear(&self) {
+        *self.last_known_good.write().await = None;
+    }
+
+    async fn remember(&self, value: &T) {
+        if self.remember_last_good {
+            *self.last_known_good.write().await = Some((value.clone(), Utc::now()));
+        }
+    }
+
+    async fn recover(&self, error: anyhow::Error) -> Result<(T, FallbackSource)> {
+        if self.remember_last_good {
+            if let Some((value, recorded_at)) = self.last_known_good.read().await.clone() {
+                let fresh = self
+                    .max_staleness
+                    .map(|max| Utc::now() - recorded_at <= max)
+                    .unwrap_or(true);
+                if fresh {
+                    warn!(error = %error, %recorded_at, "Serving last-known-good value");
+                    return Ok((value, FallbackSource::LastKnownGood));
+                }
+            }
+        }
+
+        if let Some(value) = &self.static_value {
+            warn!(error = %error, "Serving static fallback value");
+            return Ok((value.clone(), FallbackSource::Static));
+        }
+
+        Err(error)
+    }
+}
+
+impl<T: Clone + Send + Sync> Default for Fallback<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_primary_success() {
+        let fallback = Fallback::new().with_static(0);
+        let (value, source) = fallback
+            .execute_with_source(|| async { Ok(5) })
+            .await
+            .unwrap();
+        assert_eq!(value, 5);
+        assert_eq!(source, FallbackSource::Primary);
+    }
+
+    #[tokio::test]
+    async fn test_last_known_good_served_on_failure() {
+        let fallback = Fallback::new().with_last_known_good().with_static(0);
+
+        fallback.execute(|| async { Ok(42) }).await.unwrap();
+
+        let (value, source) = fallback
+            .execute_with_source(|| async { anyhow::bail!("upstream down") })
+            .await
+            .unwrap();
+        assert_eq!(value, 42);
+        assert_eq!(source, FallbackSource::LastKnownGood);
+    }
+
+    #[tokio::test]
+    async fn test_stale_value_falls_through_to_static() {
+        let fallback = Fallback::new()
+            .with_last_known_good()
+            .with_max_staleness(Duration::zero())
+            .with_static(7);
+
+        fallback.execute(|| async { Ok(42) }).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        let (value, source) = fallback
+            .execute_with_source(|| async { anyhow::bail!("upstream down") })
+            .await
+            .unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(source, FallbackSource::Static);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_operation() {
+        let fallback: Fallback<i32> = Fallback::new();
+        let (value, source) = fallback
+            .execute_or_else(
+                || async { anyhow::bail!("primary down") },
+                || async { Ok(9) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(value, 9);
+        assert_eq!(source, FallbackSource::Secondary);
+    }
+
+    #[tokio::test]
+    async fn test_error_without_alternatives() {
+        let fallback: Fallback<i32> = Fallback::new();
+        let result = fallback
+            .execute(|| async { anyhow::bail!("primary down") })
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Resilience Guard
+//!
+//! Bundles a circuit breaker, bulkhead, and retry policy for a single downstream dependency.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+use super::bulkhead::{Bulkhead, BulkheadStats};
+use super::circuit_breaker::{CircuitBreaker, CircuitBreakerStats, CircuitState};
+use super::retry::RetryPolicy;
+use super::ResilienceConfig;
+use crate::export::prometheus::HubMetrics;
+
+/// Circuit breaker, bulkhead, and retry policy guarding calls to one downstream
+pub struct ResilienceGuard {
+    name: String,
+    circuit_breaker: CircuitBreaker,
+    bulkhead: Bulkhead,
+    retry_policy: RetryPolicy,
+}
+
+impl ResilienceGuard {
+    /// Create a new guard for the named downstream
+    pub fn new(name: impl Into<String>, config: &ResilienceConfig) -> Self {
+        let name = name.into();
+        Self {
+            bulkhead: Bulkhead::new(
+                name.clone(),
+                config.max_concurrent_calls,
+                config.queue_timeout_ms,
+            ),
+            name,
+            circuit_breaker: CircuitBreaker::new(config.failure_threshold, config.timeout_seconds),
+            retry_policy: RetryPolicy::new(
+                config.max_retries.max(1),
+                config.retry_delay_ms,
+                config.backoff_multiplier,
+            ),
+        }
+    }
+
+    /// Name of the guarded downstream
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Execute an operation through the circuit breaker and bulkhead with retry
+    ///
+    /// Bulkhead rejections are returned without counting against the circuit breaker.
+    pub async fn call<F, Fut, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if !self.circuit_breaker.is_available().await {
+            warn!(downstream = %self.name, "Rejecting call, circuit breaker is open");
+            anyhow::bail!("Circuit breaker is open for {}", self.name);
+        }
+
+        let _permit = self.bulkhead.acquire().await?;
+        let start = Instant::now();
+        let result = self.retry_policy.call(operation).await;
+
+        let metrics = HubMetrics::global();
+        metrics.observe_adapter_request(&self.name, result.is_ok(), start.elapsed());
+
+        match &result {
+            Ok(_) => self.circuit_breaker.record_success().await,
+            Err(e) => {
+                debug!(downstream = %self.name, error = %e, "Guarded call failed");
+                self.circuit_breaker.record_failure().await;
+            }
+        }
+        metrics.set_circuit_state(&self.name, self.circuit_breaker.get_state().await);
+
+        result
+    }
+
+    /// Current circuit state
+    pub async fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker.get_state().await
+    }
+
+    /// Circuit breaker statistics
+    pub async fn stats(&self) -> CircuitBreakerStats {
+        self.circuit_breaker.get_stats().await
+    }
+
+    /// Bulkhead statistics
+    pub fn bulkhead_stats(&self) -> BulkheadStats {
+        self.bulkhead.get_stats()
+    }
+
+    /// Underlying circuit breaker
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit_breaker
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast_config() -> ResilienceConfig {
+        ResilienceConfig {
+            failure_threshold: 2,
+            timeout_seconds: 60,
+            max_retries: 2,
+            retry_delay_ms: 1,
+            backoff_multiplier: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guard_retries_then_succeeds() {
+        let guard = ResilienceGuard::new("test", &fast_config());
+        let attempts = AtomicUsize::new(0);
+
+        let result = guard
+            .call(|| async {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    anyhow::bail!("transient")
+                }
+                Ok(42)
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(guard.circuit_state().await, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_guard_opens_circuit_and_rejects() {
+        let guard = ResilienceGuard::new("test", &fast_config());
+
+        for _ in 0..2 {
+            let result: Result<()> = guard.call(|| async { anyhow::bail!("down") }).await;
+            assert!(result.is_err());
+        }
+
+        assert_eq!(guard.circuit_state().await, CircuitState::Open);
+
+        let calls = AtomicUsize::new(0);
+        let result = guard
+            .call(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -1,17 +1,25 @@
 //! Resilience Patterns
 //!
-//! Circuit breaker, retry, and fallback patterns for fault tolerance.
+//! Circuit breaker, retry, bulkhead, and fallback patterns for fault tolerance.
 
+pub mod bulkhead;
 pub mod circuit_breaker;
+pub mod fallback;
+pub mod guard;
 pub mod retry;
 
-pub use circuit_breaker::CircuitBreaker;
+pub use bulkhead::{Bulkhead, BulkheadStats};
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use fallback::{Fallback, FallbackSource};
+pub use guard::ResilienceGuard;
 pub use retry::RetryPolicy;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 /// Resilience configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ResilienceConfig {
     /// Circuit breaker failure threshold
     pub failure_threshold: usize,
@@ -27,6 +35,12 @@ pub struct ResilienceConfig {
 
     /// Exponential backoff multiplier
     pub backoff_multiplier: f64,
+
+    /// Maximum concurrent calls admitted by the bulkhead
+    pub max_concurrent_calls: usize,
+
+    /// Maximum time to wait for a bulkhead slot (milliseconds, 0 = reject immediately)
+    pub queue_timeout_ms: u64,
 }
 
 impl Default for ResilienceConfig {
@@ -37,6 +51,40 @@ impl Default for ResilienceConfig {
             max_retries: 3,
             retry_delay_ms: 100,
             backoff_multiplier: 2.0,
+            max_concurrent_calls: 32,
+            queue_timeout_ms: 1000,
+        }
+    }
+}
+
+impl ResilienceConfig {
+    /// Load configuration from `{PREFIX}_*` environment variables, falling back to defaults
+    pub fn from_env(prefix: &str) -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok();
+
+        Self {
+            failure_threshold: var("CB_FAILURE_THRESHOLD")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.failure_threshold),
+            timeout_seconds: var("CB_TIMEOUT_SECS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_seconds),
+            max_retries: var("MAX_RETRIES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_retries),
+            retry_delay_ms: var("RETRY_DELAY_MS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.retry_delay_ms),
+            backoff_multiplier: var("BACKOFF_MULTIPLIER")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.backoff_multiplier),
+            max_concurrent_calls: var("MAX_CONCURRENT_CALLS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_concurrent_calls),
+            queue_timeout_ms: var("QUEUE_TIMEOUT_MS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.queue_timeout_ms),
         }
     }
 }
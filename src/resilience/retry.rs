@@ -30,6 +30,16 @@ impl RetryPolicy {
     where
         F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, E>> + Send>> + Send + Sync,
         E: std::fmt::Display,
+    {
+        self.call(operation).await
+    }
+
+    /// Execute an operation with retry, accepting any future (including borrowing ones)
+    pub async fn call<F, Fut, T, E>(&self, mut operation: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
     {
         let mut attempts = 0;
         let mut delay = self.initial_delay;
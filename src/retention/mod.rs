@@ -0,0 +1,591 @@
+//! Data Retention Enforcement
+//!
+//! Applies Config-Manager `RetentionSettings` to TimescaleDB. Each reconciliation
+//! translates retention policies into per-hypertable retention and compression
+//! policies, diffs them against the jobs registered in the database, applies the
+//! difference, and schedules archival of data that has aged past its archive
+//! threshold. Every policy change is recorded in the audit log.
+
+use crate::adapters::config_manager::{
+    ArchivalDestination, CompressionType, ConfigManagerAdapter, DataType, RetentionSettings,
+};
+use crate::audit::{self, AuditRecord};
+use crate::database::{Database, HypertablePolicyRow};
+use crate::schemas::events::{AnalyticsEvent, AuditTrailEvent};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, instrument, warn};
+
+/// Actor recorded on retention audit events
+pub const RETENTION_AUDIT_ACTOR: &str = "llm-analytics-hub/retention";
+
+/// Retention enforcement configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Seconds between reconciliations
+    pub reconcile_interval_secs: u64,
+    /// Plan changes without applying them
+    pub dry_run: bool,
+    /// Environment recorded on audit events
+    pub environment: String,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            reconcile_interval_secs: 3600,
+            dry_run: false,
+            environment: crate::database::environment::default_environment(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            reconcile_interval_secs: std::env::var("RETENTION_RECONCILE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.reconcile_interval_secs),
+            dry_run: std::env::var("RETENTION_DRY_RUN")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(defaults.dry_run),
+            ..defaults
+        }
+    }
+}
+
+/// Hypertable holding a data type, if the hub stores it
+pub fn hypertable_for(data_type: &DataType) -> Option<&'static str> {
+    match data_type {
+        DataType::RawEvents => Some("events"),
+        DataType::AggregatedMetrics => Some("aggregated_metrics"),
+        DataType::Alerts => Some("anomalies"),
+        DataType::Traces | DataType::Logs | DataType::Audits => None,
+    }
+}
+
+/// Retention and compression applied to one hypertable
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TablePolicy {
+    pub table: String,
+    pub retention_days: Option<u32>,
+    pub compress_after_days: Option<u32>,
+}
+
+/// Policy type managed on a hypertable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyKind {
+    Retention,
+    Compression,
+}
+
+/// Difference between the desired and registered policy of a hypertable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyChange {
+    pub table: String,
+    pub kind: PolicyKind,
+    pub current_days: Option<u32>,
+    pub desired_days: Option<u32>,
+}
+
+impl PolicyChange {
+    fn action(&self) -> String {
+        let kind = match self.kind {
+            PolicyKind::Retention => "retention",
+            PolicyKind::Compression => "compression",
+        };
+        let verb = match (self.current_days, self.desired_days) {
+            (None, Some(_)) => "add",
+            (Some(_), None) => "remove",
+            _ => "update",
+        };
+        format!("{}_policy.{}", kind, verb)
+    }
+}
+
+/// Request to export data older than a cutoff before it is dropped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivalJob {
+    pub policy_id: String,
+    pub table: String,
+    /// Rows older than this are eligible for archival
+    pub archive_before: DateTime<Utc>,
+    pub destination: ArchivalDestination,
+    pub compression: CompressionType,
+    pub encryption_enabled: bool,
+}
+
+/// Outcome of a reconciliation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    pub changes: Vec<PolicyChange>,
+    pub archival_jobs: Vec<ArchivalJob>,
+    pub applied: bool,
+}
+
+/// Collapse retention policies into one policy per hypertable.
+///
+/// When several policies target the same table the longest retention and the
+/// earliest compression win, so a reconciliation never drops data another
+/// policy still needs.
+pub fn desired_policies(settings: &RetentionSettings) -> Vec<TablePolicy> {
+    let mut tables: BTreeMap<&'static str, TablePolicy> = BTreeMap::new();
+
+    for policy in &settings.policies {
+        let Some(table) = hypertable_for(&policy.data_type) else {
+            debug!(policy = %policy.policy_id, "No hypertable for data type, skipping");
+            continue;
+        };
+        let retention_days = (policy.retention_days > 0).then_some(policy.retention_days);
+        let entry = tables.entry(table).or_insert_with(|| TablePolicy {
+            table: table.to_string(),
+            retention_days,
+            compress_after_days: policy.compress_after_days,
+        });
+
+        entry.retention_days = match (entry.retention_days, retention_days) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            // A policy without a limit keeps data indefinitely
+            _ => None,
+        };
+        entry.compress_after_days = match (entry.compress_after_days, policy.compress_after_days) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    tables.into_values().collect()
+}
+
+/// Rebuild the registered policies from TimescaleDB job rows
+pub fn actual_policies(rows: &[HypertablePolicyRow]) -> HashMap<String, TablePolicy> {
+    let mut tables: HashMap<String, TablePolicy> = HashMap::new();
+
+    for row in rows {
+        let days = row.after_secs.map(|secs| (secs / 86_400.0).round() as u32);
+        let entry = tables
+            .entry(row.hypertable_name.clone())
+            .or_insert_with(|| TablePolicy {
+                table: row.hypertable_name.clone(),
+                ..Default::default()
+            });
+        match row.proc_name.as_str() {
+            "policy_retention" => entry.retention_days = days,
+            "policy_compression" => entry.compress_after_days = days,
+            other => debug!(proc = other, "Ignoring unmanaged policy job"),
+        }
+    }
+
+    tables
+}
+
+/// Changes needed to bring the managed hypertables to their desired policies
+pub fn plan_changes(
+    desired: &[TablePolicy],
+    actual: &HashMap<String, TablePolicy>,
+) -> Vec<PolicyChange> {
+    let mut changes = Vec::new();
+
+    for policy in desired {
+        let current = actual.get(&policy.table).cloned().unwrap_or_default();
+        if current.retention_days != policy.retention_days {
+            changes.push(PolicyChange {
+                table: policy.table.clone(),
+                kind: PolicyKind::Retention,
+                current_days: current.retention_days,
+                desired_days: policy.retention_days,
+            });
+        }
+        if current.compress_after_days != policy.compress_after_days {
+            changes.push(PolicyChange {
+                table: policy.table.clone(),
+                kind: PolicyKind::Compression,
+                current_days: current.compress_after_days,
+                desired_days: policy.compress_after_days,
+            });
+        }
+    }
+
+    changes
+}
+
+/// Archival jobs due for policies with an archive threshold
+pub fn archival_jobs(settings: &RetentionSettings, now: DateTime<Utc>) -> Vec<ArchivalJob> {
+    if !settings.archival.enabled {
+        return Vec::new();
+    }
+
+    settings
+        .policies
+        .iter()
+        .filter_map(|policy| {
+            let table = hypertable_for(&policy.data_type)?;
+            let after_days = policy.archive_after_days?;
+            if policy.retention_days > 0 && after_days > policy.retention_days {
+                warn!(
+                    policy = %policy.policy_id,
+                    after_days,
+                    retention_days = policy.retention_days,
+                    "Archive threshold exceeds retention, data is dropped before it is archived"
+                );
+            }
+            Some(ArchivalJob {
+                policy_id: policy.policy_id.clone(),
+                table: table.to_string(),
+                archive_before: now - Duration::days(after_days as i64),
+                destination: settings.archival.destination.clone(),
+                compression: settings.archival.compression.clone(),
+                encryption_enabled: settings.archival.encryption_enabled,
+            })
+        })
+        .collect()
+}
+
+/// Build the audit trail event recording a policy change
+pub fn audit_event(
+    change: &PolicyChange,
+    settings_version: &str,
+    environment: &str,
+) -> AnalyticsEvent {
+    let mut changes = HashMap::new();
+    changes.insert("kind".to_string(), serde_json::json!(change.kind));
+    changes.insert(
+        "from_days".to_string(),
+        serde_json::json!(change.current_days),
+    );
+    changes.insert(
+        "to_days".to_string(),
+        serde_json::json!(change.desired_days),
+    );
+    changes.insert(
+        "settings_version".to_string(),
+        serde_json::json!(settings_version),
+    );
+
+    audit::audit_event(
+        AuditTrailEvent {
+            action: change.action(),
+            actor: RETENTION_AUDIT_ACTOR.to_string(),
+            resource_type: "hypertable".to_string(),
+            resource_id: change.table.clone(),
+            changes,
+            ip_address: None,
+            user_agent: None,
+        },
+        Utc::now(),
+        environment,
+    )
+}
+
+/// Reconciles retention settings against the database on a schedule
+pub struct RetentionEnforcer {
+    config_manager: Arc<ConfigManagerAdapter>,
+    database: Arc<Database>,
+    config: RetentionConfig,
+    audit_sink: Option<mpsc::Sender<AnalyticsEvent>>,
+    archival_sink: Option<mpsc::Sender<ArchivalJob>>,
+    reconciliations: AtomicU64,
+    policies_changed: AtomicU64,
+    archival_jobs_scheduled: AtomicU64,
+    events_dropped: AtomicU64,
+}
+
+impl RetentionEnforcer {
+    pub fn new(
+        config_manager: Arc<ConfigManagerAdapter>,
+        database: Arc<Database>,
+        config: RetentionConfig,
+    ) -> Self {
+        Self {
+            config_manager,
+            database,
+            config,
+            audit_sink: None,
+            archival_sink: None,
+            reconciliations: AtomicU64::new(0),
+            policies_changed: AtomicU64::new(0),
+            archival_jobs_scheduled: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Emit audit trail events for policy changes into the event pipeline
+    pub fn with_audit_sink(mut self, sink: mpsc::Sender<AnalyticsEvent>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Hand due archival jobs to an archiver
+    pub fn with_archival_sink(mut self, sink: mpsc::Sender<ArchivalJob>) -> Self {
+        self.archival_sink = Some(sink);
+        self
+    }
+
+    /// Fetch settings, apply policy differences, and schedule archival
+    #[instrument(skip(self))]
+    pub async fn reconcile_once(&self, now: DateTime<Utc>) -> Result<ReconcileReport> {
+        let settings = self.config_manager.fetch_retention_settings().await?;
+        let desired = desired_policies(&settings);
+        let actual = actual_policies(&self.database.query_hypertable_policies().await?);
+        let changes = plan_changes(&desired, &actual);
+        let jobs = archival_jobs(&settings, now);
+
+        self.reconciliations.fetch_add(1, Ordering::Relaxed);
+
+        if self.config.dry_run {
+            info!(
+                changes = changes.len(),
+                archival_jobs = jobs.len(),
+                "Retention dry run, not applying changes"
+            );
+            return Ok(ReconcileReport {
+                changes,
+                archival_jobs: jobs,
+                applied: false,
+            });
+        }
+
+        for change in &changes {
+            match change.kind {
+                PolicyKind::Retention => {
+                    self.database
+                        .set_retention_policy(&change.table, change.desired_days)
+                        .await?
+                }
+                PolicyKind::Compression => {
+                    self.database
+                        .set_compression_policy(&change.table, change.desired_days)
+                        .await?
+                }
+            }
+            self.policies_changed.fetch_add(1, Ordering::Relaxed);
+            info!(
+                table = %change.table,
+                kind = ?change.kind,
+                from = ?change.current_days,
+                to = ?change.desired_days,
+                "Applied retention policy change"
+            );
+
+            let event = audit_event(change, &settings.version, &self.config.environment);
+            if let Some(record) = AuditRecord::from_event(&event) {
+                if let Err(e) = self.database.store_audit_record(&record).await {
+                    warn!(table = %change.table, "Failed to store retention audit record: {}", e);
+                }
+            }
+            if let Some(sink) = &self.audit_sink {
+                if sink.try_send(event).is_err() {
+                    self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        if let Some(sink) = &self.archival_sink {
+            for job in &jobs {
+                if sink.try_send(job.clone()).is_err() {
+                    warn!(policy = %job.policy_id, "Archival queue full, job deferred to next run");
+                    continue;
+                }
+                self.archival_jobs_scheduled.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        Ok(ReconcileReport {
+            changes,
+            archival_jobs: jobs,
+            applied: true,
+        })
+    }
+
+    /// Reconcile on the configured interval until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.reconcile_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.reconcile_once(Utc::now()).await {
+                    error!("Retention reconciliation failed: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn get_stats(&self) -> RetentionStats {
+        RetentionStats {
+            reconciliations: self.reconciliations.load(Ordering::Relaxed),
+            policies_changed: self.policies_changed.load(Ordering::Relaxed),
+            archival_jobs_scheduled: self.archival_jobs_scheduled.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Retention enforcement statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionStats {
+    pub reconciliations: u64,
+    pub policies_changed: u64,
+    pub archival_jobs_scheduled: u64,
+    pub events_dropped: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::config_manager::{
+        ArchivalConfig, CompactionConfig, RetentionPolicy, StorageTier,
+    };
+    use crate::schemas::events::{EventPayload, GovernancePayload};
+
+    fn policy(
+        id: &str,
+        data_type: DataType,
+        retention_days: u32,
+        compress: Option<u32>,
+        archive: Option<u32>,
+    ) -> RetentionPolicy {
+        RetentionPolicy {
+            policy_id: id.to_string(),
+            name: id.to_string(),
+            data_type,
+            retention_days,
+            tier: StorageTier::Hot,
+            compress_after_days: compress,
+            archive_after_days: archive,
+        }
+    }
+
+    fn settings(policies: Vec<RetentionPolicy>, archival_enabled: bool) -> RetentionSettings {
+        RetentionSettings {
+            config_id: "cfg".to_string(),
+            version: "2".to_string(),
+            created_at: Utc::now(),
+            policies,
+            archival: ArchivalConfig {
+                enabled: archival_enabled,
+                destination: ArchivalDestination::S3 {
+                    bucket: "archive".to_string(),
+                    prefix: "hub/".to_string(),
+                },
+                compression: CompressionType::Zstd,
+                encryption_enabled: true,
+            },
+            compaction: CompactionConfig {
+                enabled: false,
+                schedule_cron: "0 2 * * *".to_string(),
+                target_file_size_mb: 256,
+                max_concurrent_jobs: 1,
+            },
+        }
+    }
+
+    fn row(table: &str, proc_name: &str, days: f64) -> HypertablePolicyRow {
+        HypertablePolicyRow {
+            hypertable_name: table.to_string(),
+            proc_name: proc_name.to_string(),
+            after_secs: Some(days * 86_400.0),
+        }
+    }
+
+    #[test]
+    fn test_desired_policies_merge_per_table() {
+        let s = settings(
+            vec![
+                policy("raw-short", DataType::RawEvents, 7, Some(3), None),
+                policy("raw-long", DataType::RawEvents, 30, Some(1), None),
+                policy("traces", DataType::Traces, 14, Some(3), None),
+            ],
+            false,
+        );
+
+        let desired = desired_policies(&s);
+
+        assert_eq!(
+            desired,
+            vec![TablePolicy {
+                table: "events".to_string(),
+                retention_days: Some(30),
+                compress_after_days: Some(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_changes_diffs_registered_jobs() {
+        let desired = vec![
+            TablePolicy {
+                table: "events".to_string(),
+                retention_days: Some(7),
+                compress_after_days: Some(7),
+            },
+            TablePolicy {
+                table: "anomalies".to_string(),
+                retention_days: Some(90),
+                compress_after_days: None,
+            },
+        ];
+        let actual = actual_policies(&[
+            row("events", "policy_retention", 30.0),
+            row("events", "policy_compression", 7.0),
+            row("anomalies", "policy_retention", 90.0),
+            row("anomalies", "policy_compression", 14.0),
+        ]);
+
+        let changes = plan_changes(&desired, &actual);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, PolicyKind::Retention);
+        assert_eq!(changes[0].current_days, Some(30));
+        assert_eq!(changes[0].desired_days, Some(7));
+        assert_eq!(changes[1].table, "anomalies");
+        assert_eq!(changes[1].desired_days, None);
+        assert_eq!(changes[1].action(), "compression_policy.remove");
+    }
+
+    #[test]
+    fn test_archival_jobs_only_when_enabled() {
+        let now = Utc::now();
+        let policies = vec![
+            policy("raw", DataType::RawEvents, 30, None, Some(7)),
+            policy("metrics", DataType::AggregatedMetrics, 90, None, None),
+        ];
+
+        assert!(archival_jobs(&settings(policies.clone(), false), now).is_empty());
+
+        let jobs = archival_jobs(&settings(policies, true), now);
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].table, "events");
+        assert_eq!(jobs[0].archive_before, now - Duration::days(7));
+    }
+
+    #[test]
+    fn test_audit_event_records_change() {
+        let change = PolicyChange {
+            table: "events".to_string(),
+            kind: PolicyKind::Retention,
+            current_days: None,
+            desired_days: Some(30),
+        };
+
+        let event = audit_event(&change, "2", "production");
+
+        match event.payload {
+            EventPayload::Governance(GovernancePayload::AuditTrail(audit)) => {
+                assert_eq!(audit.action, "retention_policy.add");
+                assert_eq!(audit.resource_id, "events");
+                assert_eq!(audit.changes["to_days"], serde_json::json!(30));
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+}
@@ -11,6 +11,9 @@ use uuid::Uuid;
 /// Schema version for event compatibility and migration
 pub const SCHEMA_VERSION: &str = "1.0.0";
 
+/// Tag identifying the tenant that owns an event
+pub const TENANT_TAG: &str = "tenant_id";
+
 /// Common fields present in all analytics events
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CommonEventFields {
@@ -50,6 +53,21 @@ pub struct CommonEventFields {
     pub tags: HashMap<String, String>,
 }
 
+impl CommonEventFields {
+    /// Tenant that owns the event, if any
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.tags
+            .get(TENANT_TAG)
+            .map(String::as_str)
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Assign the event to a tenant
+    pub fn set_tenant(&mut self, tenant_id: impl Into<String>) {
+        self.tags.insert(TENANT_TAG.to_string(), tenant_id.into());
+    }
+}
+
 fn default_schema_version() -> String {
     SCHEMA_VERSION.to_string()
 }
@@ -80,6 +98,21 @@ pub enum SourceModule {
     LlmAnalyticsHub,
 }
 
+impl SourceModule {
+    /// Serialized (kebab-case) module name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceModule::LlmObservatory => "llm-observatory",
+            SourceModule::LlmSentinel => "llm-sentinel",
+            SourceModule::LlmCostOps => "llm-cost-ops",
+            SourceModule::LlmGovernanceDashboard => "llm-governance-dashboard",
+            SourceModule::LlmRegistry => "llm-registry",
+            SourceModule::LlmPolicyEngine => "llm-policy-engine",
+            SourceModule::LlmAnalyticsHub => "llm-analytics-hub",
+        }
+    }
+}
+
 /// High-level event type classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -128,6 +161,21 @@ pub struct AnalyticsEvent {
     pub payload: EventPayload,
 }
 
+/// Validation shared by every ingestion path: HTTP, OTLP, gRPC, and Kafka
+pub fn validate_event(event: &AnalyticsEvent) -> anyhow::Result<()> {
+    if event.common.schema_version != SCHEMA_VERSION {
+        anyhow::bail!(
+            "Schema version mismatch: expected {}, got {}",
+            SCHEMA_VERSION,
+            event.common.schema_version
+        );
+    }
+    if event.common.timestamp.timestamp() <= 0 {
+        anyhow::bail!("Invalid timestamp");
+    }
+    Ok(())
+}
+
 /// Module-specific event payloads
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "payload_type", content = "data")]
@@ -1071,6 +1119,37 @@ mod tests {
         assert_eq!(event.common.parent_event_id, Some(parent_id));
     }
 
+    #[test]
+    fn test_validate_event() {
+        let mut event = AnalyticsEvent {
+            common: CommonEventFields {
+                event_id: Uuid::new_v4(),
+                timestamp: Utc::now(),
+                source_module: SourceModule::LlmAnalyticsHub,
+                event_type: EventType::Telemetry,
+                correlation_id: None,
+                parent_event_id: None,
+                schema_version: SCHEMA_VERSION.to_string(),
+                severity: Severity::Info,
+                environment: "test".to_string(),
+                tags: HashMap::new(),
+            },
+            payload: EventPayload::Custom(CustomPayload {
+                custom_type: "test".to_string(),
+                data: serde_json::json!({}),
+            }),
+        };
+        assert!(validate_event(&event).is_ok());
+
+        event.common.timestamp = DateTime::from_timestamp(0, 0).unwrap();
+        assert!(validate_event(&event).is_err());
+
+        event.common.timestamp = Utc::now();
+        event.common.schema_version = "0.9.0".to_string();
+        let error = validate_event(&event).unwrap_err().to_string();
+        assert!(error.contains("Schema version mismatch"));
+    }
+
     #[test]
     fn test_event_with_tags() {
         let mut tags = HashMap::new();
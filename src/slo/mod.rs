@@ -0,0 +1,564 @@
+//! Service Level Objectives
+//!
+//! User-defined objectives evaluated against aggregated metrics over rolling
+//! windows, with error budget accounting and multi-window burn-rate alerts.
+
+use crate::analytics::scorecard::{ERROR_RATE_METRIC, LATENCY_METRIC};
+use crate::database::{AggregatedMetricRow, Database};
+use crate::models::metrics::TimeWindow;
+use crate::schemas::events::{
+    AnalyticsEvent, CommonEventFields, CustomPayload, EventPayload, EventType, Severity,
+    SourceModule, SCHEMA_VERSION,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+/// Custom payload type for burn-rate alerts
+pub const SLO_BURN_RATE_EVENT_TYPE: &str = "slo.burn_rate";
+
+/// Latency percentile an objective is measured on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyPercentile {
+    P50,
+    P95,
+    P99,
+}
+
+impl LatencyPercentile {
+    fn value(&self, row: &AggregatedMetricRow) -> f64 {
+        match self {
+            LatencyPercentile::P50 => row.p50,
+            LatencyPercentile::P95 => row.p95,
+            LatencyPercentile::P99 => row.p99,
+        }
+    }
+}
+
+/// What an objective measures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SloIndicator {
+    /// Share of aggregation windows whose latency percentile stays under the threshold
+    Latency {
+        percentile: LatencyPercentile,
+        threshold_ms: f64,
+    },
+    /// Share of successful requests, derived from the error rate
+    Availability,
+}
+
+impl SloIndicator {
+    fn metric_name(&self) -> &'static str {
+        match self {
+            SloIndicator::Latency { .. } => LATENCY_METRIC,
+            SloIndicator::Availability => ERROR_RATE_METRIC,
+        }
+    }
+
+    /// Fraction of bad events in the given aggregation windows
+    ///
+    /// Windows are weighted by their sample count. Returns `None` without samples.
+    pub fn bad_fraction(&self, rows: &[AggregatedMetricRow]) -> Option<f64> {
+        let total: i64 = rows.iter().map(|r| r.count.max(0)).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let bad: f64 = match self {
+            SloIndicator::Latency {
+                percentile,
+                threshold_ms,
+            } => rows
+                .iter()
+                .filter(|r| percentile.value(r) > *threshold_ms)
+                .map(|r| r.count.max(0) as f64)
+                .sum(),
+            SloIndicator::Availability => rows
+                .iter()
+                .map(|r| (r.avg / 100.0).clamp(0.0, 1.0) * r.count.max(0) as f64)
+                .sum(),
+        };
+        Some(bad / total as f64)
+    }
+}
+
+/// A service level objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloObjective {
+    pub id: String,
+    pub name: String,
+    /// Model the objective applies to; all traffic when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    pub indicator: SloIndicator,
+    /// Target share of good events in percent (e.g. 99.9)
+    pub target: f64,
+    /// Rolling compliance window
+    #[serde(default = "default_window_days")]
+    pub window_days: i64,
+}
+
+fn default_window_days() -> i64 {
+    30
+}
+
+impl SloObjective {
+    pub fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            anyhow::bail!("SLO id must not be empty");
+        }
+        if !(self.target > 0.0 && self.target < 100.0) {
+            anyhow::bail!(
+                "SLO {} target must be between 0 and 100 (exclusive)",
+                self.id
+            );
+        }
+        if self.window_days <= 0 {
+            anyhow::bail!("SLO {} window must be at least one day", self.id);
+        }
+        if let SloIndicator::Latency { threshold_ms, .. } = self.indicator {
+            if threshold_ms <= 0.0 {
+                anyhow::bail!("SLO {} latency threshold must be positive", self.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fraction of events allowed to be bad
+    pub fn error_budget(&self) -> f64 {
+        1.0 - self.target / 100.0
+    }
+
+    fn tags(&self) -> serde_json::Value {
+        match &self.model_id {
+            Some(model_id) => serde_json::json!({ "model_id": model_id }),
+            None => serde_json::json!({}),
+        }
+    }
+}
+
+/// Multi-window burn-rate alert rule
+///
+/// Fires when both windows burn the error budget faster than `threshold` times the
+/// sustainable rate; the short window makes the alert reset quickly after recovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnRateRule {
+    pub long_window_secs: i64,
+    pub short_window_secs: i64,
+    pub threshold: f64,
+    pub severity: Severity,
+}
+
+impl BurnRateRule {
+    /// Standard page (2% of a 30-day budget in 1h) and ticket (5% in 6h) rules
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                long_window_secs: 3600,
+                short_window_secs: 300,
+                threshold: 14.4,
+                severity: Severity::Critical,
+            },
+            Self {
+                long_window_secs: 21600,
+                short_window_secs: 1800,
+                threshold: 6.0,
+                severity: Severity::Warning,
+            },
+        ]
+    }
+}
+
+/// Burn rates observed for one rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnRateStatus {
+    pub rule: BurnRateRule,
+    pub long_burn_rate: Option<f64>,
+    pub short_burn_rate: Option<f64>,
+    pub firing: bool,
+}
+
+/// Evaluated state of an objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub objective: SloObjective,
+    /// Share of good events over the rolling window, in percent
+    pub compliance: Option<f64>,
+    pub meeting_target: Option<bool>,
+    /// Fraction of the error budget left (negative once exhausted)
+    pub error_budget_remaining: Option<f64>,
+    pub burn_rates: Vec<BurnRateStatus>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+impl SloStatus {
+    /// Burn-rate rules currently firing
+    pub fn firing(&self) -> impl Iterator<Item = &BurnRateStatus> {
+        self.burn_rates.iter().filter(|b| b.firing)
+    }
+}
+
+/// Burn rate for a bad-event fraction: 1.0 spends the budget exactly over the SLO window
+pub fn burn_rate(bad_fraction: f64, objective: &SloObjective) -> f64 {
+    bad_fraction / objective.error_budget()
+}
+
+/// Aggregation window granular enough for a lookback
+fn window_for(lookback: Duration) -> TimeWindow {
+    if lookback <= Duration::hours(1) {
+        TimeWindow::OneMinute
+    } else if lookback <= Duration::hours(6) {
+        TimeWindow::FiveMinutes
+    } else {
+        TimeWindow::OneHour
+    }
+}
+
+/// Registers objectives and evaluates them against the metrics store
+pub struct SloEngine {
+    database: Arc<Database>,
+    objectives: DashMap<String, SloObjective>,
+    rules: Vec<BurnRateRule>,
+    alert_sink: Option<mpsc::Sender<AnalyticsEvent>>,
+    environment: String,
+    alerts_fired: AtomicU64,
+}
+
+impl SloEngine {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            objectives: DashMap::new(),
+            rules: BurnRateRule::defaults(),
+            alert_sink: None,
+            environment: crate::database::environment::default_environment(),
+            alerts_fired: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the burn-rate alert rules
+    pub fn with_rules(mut self, rules: Vec<BurnRateRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Publish burn-rate alerts into the given pipeline channel
+    pub fn with_alert_sink(mut self, sink: mpsc::Sender<AnalyticsEvent>) -> Self {
+        self.alert_sink = Some(sink);
+        self
+    }
+
+    // ========== Objectives ==========
+
+    /// Define or replace an objective
+    pub fn define(&self, objective: SloObjective) -> Result<()> {
+        objective.validate()?;
+        self.objectives.insert(objective.id.clone(), objective);
+        Ok(())
+    }
+
+    /// Load objectives from a YAML list
+    pub fn load_yaml(&self, yaml: &str) -> Result<usize> {
+        let objectives: Vec<SloObjective> =
+            serde_yaml::from_str(yaml).context("Failed to parse SLO definitions")?;
+        let count = objectives.len();
+        for objective in objectives {
+            self.define(objective)?;
+        }
+        Ok(count)
+    }
+
+    pub fn remove(&self, id: &str) -> Option<SloObjective> {
+        self.objectives.remove(id).map(|(_, o)| o)
+    }
+
+    pub fn get(&self, id: &str) -> Option<SloObjective> {
+        self.objectives.get(id).map(|o| o.clone())
+    }
+
+    /// All objectives, sorted by id
+    pub fn list(&self) -> Vec<SloObjective> {
+        let mut objectives: Vec<SloObjective> = self.objectives.iter().map(|o| o.clone()).collect();
+        objectives.sort_by(|a, b| a.id.cmp(&b.id));
+        objectives
+    }
+
+    // ========== Evaluation ==========
+
+    async fn bad_fraction(
+        &self,
+        objective: &SloObjective,
+        lookback: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Option<f64>> {
+        let rows = self
+            .database
+            .query_aggregated_metrics_tagged(
+                objective.indicator.metric_name(),
+                window_for(lookback),
+                now - lookback,
+                now,
+                &objective.tags(),
+            )
+            .await?;
+        Ok(objective.indicator.bad_fraction(&rows))
+    }
+
+    /// Evaluate one objective at `now`
+    #[instrument(skip(self, objective), fields(slo = %objective.id))]
+    pub async fn evaluate(
+        &self,
+        objective: &SloObjective,
+        now: DateTime<Utc>,
+    ) -> Result<SloStatus> {
+        let bad = self
+            .bad_fraction(objective, Duration::days(objective.window_days), now)
+            .await?;
+
+        let mut burn_rates = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let long = self
+                .bad_fraction(objective, Duration::seconds(rule.long_window_secs), now)
+                .await?
+                .map(|b| burn_rate(b, objective));
+            let short = self
+                .bad_fraction(objective, Duration::seconds(rule.short_window_secs), now)
+                .await?
+                .map(|b| burn_rate(b, objective));
+            let firing = matches!((long, short), (Some(l), Some(s)) if l >= rule.threshold && s >= rule.threshold);
+
+            burn_rates.push(BurnRateStatus {
+                rule: rule.clone(),
+                long_burn_rate: long,
+                short_burn_rate: short,
+                firing,
+            });
+        }
+
+        let compliance = bad.map(|b| (1.0 - b) * 100.0);
+        Ok(SloStatus {
+            objective: objective.clone(),
+            compliance,
+            meeting_target: compliance.map(|c| c >= objective.target),
+            error_budget_remaining: bad.map(|b| 1.0 - burn_rate(b, objective)),
+            burn_rates,
+            evaluated_at: now,
+        })
+    }
+
+    /// Evaluate every objective, raising alerts for firing burn-rate rules
+    pub async fn evaluate_all(&self) -> Vec<SloStatus> {
+        let now = Utc::now();
+        let mut statuses = Vec::new();
+
+        for objective in self.list() {
+            match self.evaluate(&objective, now).await {
+                Ok(status) => {
+                    for burn in status.firing() {
+                        info!(
+                            slo = %objective.id,
+                            long_burn_rate = ?burn.long_burn_rate,
+                            "SLO burn-rate alert firing"
+                        );
+                        self.raise(burn_rate_event(&status, burn, &self.environment));
+                    }
+                    statuses.push(status);
+                }
+                Err(e) => warn!(slo = %objective.id, error = %e, "Failed to evaluate SLO"),
+            }
+        }
+
+        statuses
+    }
+
+    fn raise(&self, event: AnalyticsEvent) {
+        let Some(sink) = &self.alert_sink else {
+            return;
+        };
+        match sink.try_send(event) {
+            Ok(()) => {
+                self.alerts_fired.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => warn!(error = %e, "Dropped SLO burn-rate alert"),
+        }
+    }
+
+    /// Number of burn-rate alerts published
+    pub fn alerts_fired(&self) -> u64 {
+        self.alerts_fired.load(Ordering::Relaxed)
+    }
+}
+
+/// Build the alert event for a firing burn-rate rule
+pub fn burn_rate_event(
+    status: &SloStatus,
+    burn: &BurnRateStatus,
+    environment: &str,
+) -> AnalyticsEvent {
+    let mut tags = HashMap::new();
+    tags.insert("slo_id".to_string(), status.objective.id.clone());
+    if let Some(model_id) = &status.objective.model_id {
+        tags.insert("model_id".to_string(), model_id.clone());
+    }
+
+    AnalyticsEvent {
+        common: CommonEventFields {
+            event_id: Uuid::new_v4(),
+            timestamp: status.evaluated_at,
+            source_module: SourceModule::LlmAnalyticsHub,
+            event_type: EventType::Alert,
+            correlation_id: None,
+            parent_event_id: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity: burn.rule.severity.clone(),
+            environment: environment.to_string(),
+            tags,
+        },
+        payload: EventPayload::Custom(CustomPayload {
+            custom_type: SLO_BURN_RATE_EVENT_TYPE.to_string(),
+            data: serde_json::json!({
+                "slo_id": status.objective.id,
+                "slo_name": status.objective.name,
+                "target": status.objective.target,
+                "compliance": status.compliance,
+                "error_budget_remaining": status.error_budget_remaining,
+                "long_window_secs": burn.rule.long_window_secs,
+                "short_window_secs": burn.rule.short_window_secs,
+                "threshold": burn.rule.threshold,
+                "long_burn_rate": burn.long_burn_rate,
+                "short_burn_rate": burn.short_burn_rate,
+            }),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(avg: f64, p95: f64, count: i64) -> AggregatedMetricRow {
+        AggregatedMetricRow {
+            metric_name: LATENCY_METRIC.to_string(),
+            time_window: "1h".to_string(),
+            window_start: Utc::now(),
+            tags: serde_json::json!({}),
+            avg,
+            min: avg,
+            max: p95,
+            p50: avg,
+            p95,
+            p99: p95,
+            stddev: None,
+            count,
+            sum: avg * count as f64,
+            histogram: None,
+        }
+    }
+
+    fn availability(target: f64) -> SloObjective {
+        SloObjective {
+            id: "gpt4-availability".to_string(),
+            name: "GPT-4 availability".to_string(),
+            model_id: Some("gpt-4".to_string()),
+            indicator: SloIndicator::Availability,
+            target,
+            window_days: 30,
+        }
+    }
+
+    #[test]
+    fn test_latency_bad_fraction_counts_slow_windows() {
+        let indicator = SloIndicator::Latency {
+            percentile: LatencyPercentile::P95,
+            threshold_ms: 2000.0,
+        };
+        let rows = [row(800.0, 1500.0, 90), row(1200.0, 2500.0, 10)];
+        assert_eq!(indicator.bad_fraction(&rows), Some(0.1));
+        assert_eq!(indicator.bad_fraction(&[]), None);
+    }
+
+    #[test]
+    fn test_availability_error_budget() {
+        let objective = availability(99.9);
+        // 0.05% errors uses half of a 0.1% budget
+        let bad = objective
+            .indicator
+            .bad_fraction(&[row(0.05, 0.05, 1000)])
+            .unwrap();
+        assert!((burn_rate(bad, &objective) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_objective_validation() {
+        assert!(availability(99.9).validate().is_ok());
+        assert!(availability(100.0).validate().is_err());
+        assert!(availability(0.0).validate().is_err());
+    }
+
+    #[test]
+    fn test_objectives_from_yaml() {
+        let yaml = r#"
+- id: gpt4-latency
+  name: GPT-4 p95 under 2s
+  model_id: gpt-4
+  indicator:
+    type: latency
+    percentile: p95
+    threshold_ms: 2000
+  target: 99.0
+- id: hub-availability
+  name: Availability
+  indicator:
+    type: availability
+  target: 99.9
+  window_days: 7
+"#;
+        let objectives: Vec<SloObjective> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(objectives.len(), 2);
+        assert_eq!(objectives[0].window_days, 30);
+        assert_eq!(
+            objectives[0].indicator,
+            SloIndicator::Latency {
+                percentile: LatencyPercentile::P95,
+                threshold_ms: 2000.0
+            }
+        );
+        assert!(objectives[1].model_id.is_none());
+    }
+
+    #[test]
+    fn test_burn_rate_event() {
+        let status = SloStatus {
+            objective: availability(99.9),
+            compliance: Some(99.0),
+            meeting_target: Some(false),
+            error_budget_remaining: Some(-9.0),
+            burn_rates: Vec::new(),
+            evaluated_at: Utc::now(),
+        };
+        let burn = BurnRateStatus {
+            rule: BurnRateRule::defaults().remove(0),
+            long_burn_rate: Some(20.0),
+            short_burn_rate: Some(18.0),
+            firing: true,
+        };
+
+        let event = burn_rate_event(&status, &burn, "production");
+        assert_eq!(event.common.severity, Severity::Critical);
+        assert_eq!(event.common.tags["slo_id"], "gpt4-availability");
+        let EventPayload::Custom(custom) = event.payload else {
+            panic!("expected custom payload");
+        };
+        assert_eq!(custom.custom_type, SLO_BURN_RATE_EVENT_TYPE);
+        assert_eq!(custom.data["long_burn_rate"], 20.0);
+    }
+}
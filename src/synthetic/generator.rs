@@ -0,0 +1,879 @@
+//! Synthetic Event Generator
+//!
+//! Deterministic event streams driven by a `ScenarioConfig`. Every random
+//! choice, including event and correlation IDs, comes from one seeded RNG, so
+//! the same seed and timestamps always yield the same events.
+//!
+//! Latencies and token counts are log-normal around per-model medians. A share
+//! of events start correlation chains (a request's latency, token usage and
+//! cost; a threat followed by auth denials, a policy violation and an audit
+//! entry) whose members share a correlation ID and link to their parent.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+use super::scenario::{ModelProfile, ScenarioConfig};
+use crate::schemas::events::{
+    AnalyticsEvent, ApiCostEvent, AuditTrailEvent, AuthAction, AuthEvent, BudgetAlertEvent,
+    BudgetAlertType, CommonEventFields, ComplianceCheckEvent, ComplianceFinding, ComplianceStatus,
+    ComplianceViolationEvent, CostPayload, CustomPayload, DataLineageEvent, DataOperation,
+    ErrorRateMetrics, EventPayload, EventType, GovernancePayload, LatencyBreakdown, LatencyMetrics,
+    MitigationStatus, ModelPerformanceMetrics, PolicyViolationEvent, PolicyViolationSeverity,
+    PrivacyEvent, PrivacyOperation, RemediationStatus, ResourceConsumptionEvent, ResourceType,
+    SecurityPayload, Severity, SourceModule, TelemetryPayload, ThreatEvent, ThreatLevel,
+    ThreatType, ThroughputMetrics, TokenCostEvent, TokenUsageMetrics, VulnerabilityEvent,
+    SCHEMA_VERSION,
+};
+
+/// Tag marking generated events so they can be told apart from real traffic
+pub const SYNTHETIC_TAG: &str = "synthetic";
+
+/// Seeded generator of `AnalyticsEvent`s
+pub struct SyntheticGenerator {
+    config: ScenarioConfig,
+    rng: StdRng,
+    /// Remaining members of the chain currently being emitted
+    pending: VecDeque<AnalyticsEvent>,
+}
+
+impl SyntheticGenerator {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    /// Next event stamped at `timestamp`. Chain members are returned on
+    /// subsequent calls, each a few milliseconds after its parent.
+    pub fn next_event(&mut self, timestamp: DateTime<Utc>) -> AnalyticsEvent {
+        if let Some(mut event) = self.pending.pop_front() {
+            event.common.timestamp = event.common.timestamp.max(timestamp);
+            return event;
+        }
+
+        if self.rng.gen_bool(self.config.chain_rate.clamp(0.0, 1.0)) {
+            let mut chain = if self.roll_family() == Family::Security {
+                self.threat_chain(timestamp)
+            } else {
+                self.request_chain(timestamp)
+            };
+            let first = chain.remove(0);
+            self.pending.extend(chain);
+            return first;
+        }
+
+        self.standalone(timestamp)
+    }
+
+    /// `count` consecutive events stamped at `timestamp`
+    pub fn batch(&mut self, count: usize, timestamp: DateTime<Utc>) -> Vec<AnalyticsEvent> {
+        (0..count).map(|_| self.next_event(timestamp)).collect()
+    }
+
+    /// Events spread over `[start, end)` at an average of `events_per_minute`
+    /// with exponentially distributed gaps. Chain members keep their own
+    /// offsets, so the stream is only roughly in time order.
+    pub fn history(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        events_per_minute: f64,
+    ) -> History<'_> {
+        History {
+            generator: self,
+            next: start,
+            end,
+            mean_gap_ms: 60_000.0 / events_per_minute.max(f64::MIN_POSITIVE),
+        }
+    }
+
+    // ---- sampling helpers ----
+
+    fn uuid(&mut self) -> Uuid {
+        uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid()
+    }
+
+    /// Standard normal sample (Box-Muller)
+    fn normal(&mut self) -> f64 {
+        let u1: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Log-normal sample with the given median, giving the long right tail
+    /// seen in request latencies and token counts
+    fn log_normal(&mut self, median: f64, sigma: f64) -> f64 {
+        median * (sigma * self.normal()).exp()
+    }
+
+    /// Scenario multiplier in effect at `timestamp`
+    fn multiplier(&self, timestamp: DateTime<Utc>, value: f64) -> f64 {
+        if self.config.incident_active(timestamp) {
+            value
+        } else {
+            1.0
+        }
+    }
+
+    fn pick_model(&mut self) -> ModelProfile {
+        let total: f64 = self.config.models.iter().map(|m| m.weight).sum();
+        let mut roll = self.rng.gen_range(0.0..total.max(f64::MIN_POSITIVE));
+        for model in &self.config.models {
+            if roll < model.weight {
+                return model.clone();
+            }
+            roll -= model.weight;
+        }
+        self.config
+            .models
+            .last()
+            .cloned()
+            .unwrap_or_else(|| ModelProfile::defaults().remove(0))
+    }
+
+    fn roll_family(&mut self) -> Family {
+        let mix = &self.config.mix;
+        let weights = [
+            (Family::Telemetry, mix.telemetry),
+            (Family::Security, mix.security),
+            (Family::Cost, mix.cost),
+            (Family::Governance, mix.governance),
+            (Family::Custom, mix.custom),
+        ];
+        let total: f64 = weights.iter().map(|(_, w)| w.max(0.0)).sum();
+        let mut roll = self.rng.gen_range(0.0..total.max(f64::MIN_POSITIVE));
+        for (family, weight) in weights {
+            if roll < weight.max(0.0) {
+                return family;
+            }
+            roll -= weight.max(0.0);
+        }
+        Family::Telemetry
+    }
+
+    fn common(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        source_module: SourceModule,
+        event_type: EventType,
+        model: &ModelProfile,
+    ) -> CommonEventFields {
+        let tenant = self.rng.gen_range(0..self.config.tenants.max(1));
+        let mut common = CommonEventFields {
+            event_id: self.uuid(),
+            timestamp,
+            source_module,
+            event_type,
+            correlation_id: None,
+            parent_event_id: None,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity: Severity::Info,
+            environment: self.config.environment.clone(),
+            tags: HashMap::from([
+                ("model".to_string(), model.model_id.clone()),
+                ("provider".to_string(), model.provider.clone()),
+                (SYNTHETIC_TAG.to_string(), "true".to_string()),
+            ]),
+        };
+        common.set_tenant(format!("tenant-{}", tenant));
+        common
+    }
+
+    fn event(common: CommonEventFields, payload: EventPayload) -> AnalyticsEvent {
+        AnalyticsEvent { common, payload }
+    }
+
+    /// Link `events` into a chain under one correlation ID, each a few
+    /// milliseconds after and pointing at the previous one
+    fn link(&mut self, mut events: Vec<AnalyticsEvent>) -> Vec<AnalyticsEvent> {
+        let correlation_id = self.uuid();
+        let tenant = events[0].common.tenant_id().map(str::to_string);
+        for i in 0..events.len() {
+            let (before, rest) = events.split_at_mut(i);
+            let event = &mut rest[0];
+            event.common.correlation_id = Some(correlation_id);
+            if let Some(tenant) = &tenant {
+                event.common.set_tenant(tenant.clone());
+            }
+            if let Some(parent) = before.last() {
+                event.common.parent_event_id = Some(parent.common.event_id);
+                event.common.timestamp =
+                    parent.common.timestamp + Duration::milliseconds(self.rng.gen_range(1..50));
+            }
+        }
+        events
+    }
+
+    // ---- standalone events ----
+
+    fn standalone(&mut self, timestamp: DateTime<Utc>) -> AnalyticsEvent {
+        let model = self.pick_model();
+        match self.roll_family() {
+            Family::Telemetry => match self.rng.gen_range(0..20) {
+                0..=9 => self.latency(timestamp, &model),
+                10..=14 => self.token_usage(timestamp, &model),
+                15..=16 => self.throughput(timestamp, &model),
+                17..=18 => self.error_rate(timestamp, &model),
+                _ => self.model_performance(timestamp, &model),
+            },
+            Family::Security => {
+                let threat_rate = (self.config.threat_rate
+                    * self.multiplier(timestamp, self.config.threat_multiplier))
+                .clamp(0.0, 1.0);
+                if self.rng.gen_bool(threat_rate) {
+                    return self.threat(timestamp, &model);
+                }
+                match self.rng.gen_range(0..10) {
+                    0..=5 => self.auth(timestamp, &model, AuthAction::Login, true),
+                    6..=7 => self.privacy(timestamp, &model),
+                    8 => self.vulnerability(timestamp, &model),
+                    _ => self.compliance_violation(timestamp, &model),
+                }
+            }
+            Family::Cost => match self.rng.gen_range(0..20) {
+                0..=13 => self.token_cost(timestamp, &model),
+                14..=16 => self.api_cost(timestamp, &model),
+                17..=18 => self.resource_consumption(timestamp, &model),
+                _ => self.budget_alert(timestamp, &model),
+            },
+            Family::Governance => match self.rng.gen_range(0..4) {
+                0 => self.audit_trail(timestamp, &model, "model.invoke"),
+                1 => self.compliance_check(timestamp, &model),
+                2 => self.data_lineage(timestamp, &model),
+                _ => self.policy_violation(timestamp, &model),
+            },
+            Family::Custom => self.custom(timestamp, &model),
+        }
+    }
+
+    // ---- chains ----
+
+    /// A model request: latency, token usage, then its cost
+    fn request_chain(&mut self, timestamp: DateTime<Utc>) -> Vec<AnalyticsEvent> {
+        let model = self.pick_model();
+        let (prompt_tokens, completion_tokens) = self.token_counts(timestamp);
+        let mut latency = self.latency(timestamp, &model);
+        let mut usage = self.token_usage(timestamp, &model);
+        let mut cost = self.token_cost(timestamp, &model);
+
+        let request_id = latency.common.event_id.to_string();
+        if let EventPayload::Telemetry(TelemetryPayload::Latency(metrics)) = &mut latency.payload {
+            metrics.request_id = request_id.clone();
+        }
+        if let EventPayload::Telemetry(TelemetryPayload::TokenUsage(metrics)) = &mut usage.payload {
+            metrics.request_id = request_id.clone();
+            metrics.prompt_tokens = prompt_tokens;
+            metrics.completion_tokens = completion_tokens;
+            metrics.total_tokens = prompt_tokens + completion_tokens;
+        }
+        if let EventPayload::Cost(CostPayload::TokenCost(event)) = &mut cost.payload {
+            event.request_id = request_id;
+            event.prompt_tokens = prompt_tokens;
+            event.completion_tokens = completion_tokens;
+            event.total_tokens = prompt_tokens + completion_tokens;
+            event.total_cost_usd = prompt_tokens as f64 * event.cost_per_prompt_token
+                + completion_tokens as f64 * event.cost_per_completion_token;
+        }
+
+        self.link(vec![latency, usage, cost])
+    }
+
+    /// A threat and its fallout: denied access, a policy violation, an audit entry
+    fn threat_chain(&mut self, timestamp: DateTime<Utc>) -> Vec<AnalyticsEvent> {
+        let model = self.pick_model();
+        let threat = self.threat(timestamp, &model);
+        let denied = self.auth(timestamp, &model, AuthAction::PermissionDenied, false);
+        let violation = self.policy_violation(timestamp, &model);
+        let audit = self.audit_trail(timestamp, &model, "access.blocked");
+        self.link(vec![threat, denied, violation, audit])
+    }
+
+    // ---- telemetry ----
+
+    fn latency(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let median =
+            model.median_latency_ms * self.multiplier(timestamp, self.config.latency_multiplier);
+        let total_latency_ms = self.log_normal(median, 0.5);
+        let ttft_ms = total_latency_ms * self.rng.gen_range(0.1..0.35);
+        let queue_time_ms = total_latency_ms * self.rng.gen_range(0.01..0.1);
+        let network_time_ms = total_latency_ms * self.rng.gen_range(0.02..0.08);
+        let completion_tokens = self.log_normal(250.0, 0.7).max(1.0);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            model,
+        );
+
+        Self::event(
+            common.clone(),
+            EventPayload::Telemetry(TelemetryPayload::Latency(LatencyMetrics {
+                model_id: model.model_id.clone(),
+                request_id: common.event_id.to_string(),
+                total_latency_ms,
+                ttft_ms: Some(ttft_ms),
+                tokens_per_second: Some(completion_tokens / (total_latency_ms / 1000.0)),
+                breakdown: Some(LatencyBreakdown {
+                    queue_time_ms,
+                    processing_time_ms: total_latency_ms - queue_time_ms - network_time_ms,
+                    network_time_ms,
+                    other_ms: 0.0,
+                }),
+            })),
+        )
+    }
+
+    fn token_counts(&mut self, timestamp: DateTime<Utc>) -> (u32, u32) {
+        let scale = self.multiplier(timestamp, self.config.token_multiplier);
+        let prompt = self.log_normal(800.0 * scale, 0.9).clamp(1.0, 128_000.0) as u32;
+        let completion = self.log_normal(250.0 * scale, 0.7).clamp(1.0, 16_000.0) as u32;
+        (prompt, completion)
+    }
+
+    fn token_usage(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let (prompt_tokens, completion_tokens) = self.token_counts(timestamp);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            model,
+        );
+
+        Self::event(
+            common.clone(),
+            EventPayload::Telemetry(TelemetryPayload::TokenUsage(TokenUsageMetrics {
+                model_id: model.model_id.clone(),
+                request_id: common.event_id.to_string(),
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            })),
+        )
+    }
+
+    fn throughput(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let requests_per_second = self.log_normal(40.0 * model.weight * 10.0, 0.3);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Telemetry(TelemetryPayload::Throughput(ThroughputMetrics {
+                model_id: model.model_id.clone(),
+                requests_per_second,
+                tokens_per_second: requests_per_second * self.log_normal(1000.0, 0.3),
+                concurrent_requests: (requests_per_second * model.median_latency_ms / 1000.0).ceil()
+                    as u32,
+                window_duration_seconds: 60,
+            })),
+        )
+    }
+
+    fn error_rate(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let total_requests = self.rng.gen_range(500..5_000u64);
+        let error_rate = (self.config.error_rate
+            * self.multiplier(timestamp, self.config.error_multiplier)
+            * self.log_normal(1.0, 0.4))
+        .min(1.0);
+        let failed_requests = (total_requests as f64 * error_rate).round() as u64;
+        let timeouts = failed_requests / 2;
+        let error_rate_percent = error_rate * 100.0;
+
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            model,
+        );
+        common.severity = if error_rate_percent >= 5.0 {
+            Severity::Error
+        } else if error_rate_percent >= 1.0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        };
+
+        Self::event(
+            common,
+            EventPayload::Telemetry(TelemetryPayload::ErrorRate(ErrorRateMetrics {
+                model_id: model.model_id.clone(),
+                total_requests,
+                failed_requests,
+                error_rate_percent,
+                error_breakdown: HashMap::from([
+                    ("timeout".to_string(), timeouts),
+                    ("rate_limited".to_string(), failed_requests - timeouts),
+                ]),
+                window_duration_seconds: 60,
+            })),
+        )
+    }
+
+    fn model_performance(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+    ) -> AnalyticsEvent {
+        let degradation = self.multiplier(timestamp, self.config.error_multiplier);
+        let quality = (self.rng.gen_range(0.80..0.97) / degradation.sqrt()).clamp(0.0, 1.0);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Telemetry(TelemetryPayload::ModelPerformance(
+                ModelPerformanceMetrics {
+                    model_id: model.model_id.clone(),
+                    accuracy: Some(quality),
+                    quality_score: Some(quality * 100.0),
+                    user_satisfaction: Some((quality * 5.0).min(5.0)),
+                    custom_metrics: HashMap::from([(
+                        "hallucination_rate".to_string(),
+                        (1.0 - quality) * 0.2,
+                    )]),
+                },
+            )),
+        )
+    }
+
+    // ---- security ----
+
+    fn threat(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let (threat_type, attack_vector) = match self.rng.gen_range(0..5) {
+            0 | 1 => (ThreatType::PromptInjection, "user_prompt"),
+            2 => (ThreatType::DataExfiltration, "model_output"),
+            3 => (ThreatType::DenialOfService, "api_gateway"),
+            _ => (ThreatType::UnauthorizedAccess, "api_key"),
+        };
+        let (threat_level, severity) = match self.rng.gen_range(0..100) {
+            0..=59 => (ThreatLevel::Low, Severity::Warning),
+            60..=89 => (ThreatLevel::Medium, Severity::Warning),
+            90..=97 => (ThreatLevel::High, Severity::Error),
+            _ => (ThreatLevel::Critical, Severity::Critical),
+        };
+        let mitigation_status = if self.rng.gen_bool(0.7) {
+            MitigationStatus::Blocked
+        } else {
+            MitigationStatus::Detected
+        };
+        let source_ip = format!("203.0.113.{}", self.rng.gen_range(1..255u8));
+
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmSentinel,
+            EventType::Security,
+            model,
+        );
+        common.severity = severity;
+
+        Self::event(
+            common.clone(),
+            EventPayload::Security(SecurityPayload::Threat(ThreatEvent {
+                threat_id: common.event_id.to_string(),
+                threat_type,
+                threat_level,
+                source_ip: Some(source_ip.clone()),
+                target_resource: model.model_id.clone(),
+                attack_vector: attack_vector.to_string(),
+                mitigation_status,
+                indicators_of_compromise: vec![format!("ip:{}", source_ip)],
+            })),
+        )
+    }
+
+    fn vulnerability(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let severity_score = self.rng.gen_range(2.0..9.8_f64);
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmSentinel,
+            EventType::Security,
+            model,
+        );
+        common.severity = if severity_score >= 7.0 {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        let cve = self.rng.gen_range(10_000..60_000u32);
+
+        Self::event(
+            common.clone(),
+            EventPayload::Security(SecurityPayload::Vulnerability(VulnerabilityEvent {
+                vulnerability_id: common.event_id.to_string(),
+                cve_id: Some(format!("CVE-2024-{}", cve)),
+                severity_score: (severity_score * 10.0).round() / 10.0,
+                affected_component: format!("{}-serving", model.provider),
+                description: "Dependency with a known vulnerability".to_string(),
+                remediation_status: RemediationStatus::Identified,
+            })),
+        )
+    }
+
+    fn compliance_violation(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+    ) -> AnalyticsEvent {
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmSentinel,
+            EventType::Security,
+            model,
+        );
+        common.severity = Severity::Warning;
+
+        Self::event(
+            common.clone(),
+            EventPayload::Security(SecurityPayload::ComplianceViolation(
+                ComplianceViolationEvent {
+                    violation_id: common.event_id.to_string(),
+                    regulation: "GDPR".to_string(),
+                    requirement: "Art. 5(1)(c) data minimisation".to_string(),
+                    violation_description: "Prompt contained unredacted personal data".to_string(),
+                    affected_data_types: vec!["email".to_string()],
+                    remediation_required: true,
+                },
+            )),
+        )
+    }
+
+    fn auth(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+        action: AuthAction,
+        success: bool,
+    ) -> AnalyticsEvent {
+        let user = self.rng.gen_range(0..500u32);
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmSentinel,
+            EventType::Security,
+            model,
+        );
+        if !success {
+            common.severity = Severity::Warning;
+        }
+
+        Self::event(
+            common,
+            EventPayload::Security(SecurityPayload::Auth(AuthEvent {
+                user_id: format!("user-{}", user),
+                action,
+                resource: model.model_id.clone(),
+                success,
+                failure_reason: (!success).then(|| "blocked by threat mitigation".to_string()),
+            })),
+        )
+    }
+
+    fn privacy(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let operation = match self.rng.gen_range(0..3) {
+            0 => PrivacyOperation::DataAccess,
+            1 => PrivacyOperation::DataCollection,
+            _ => PrivacyOperation::DataDeletion,
+        };
+        let subject = self.rng.gen_range(0..10_000u32);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmSentinel,
+            EventType::Security,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Security(SecurityPayload::Privacy(PrivacyEvent {
+                data_type: "conversation_history".to_string(),
+                operation,
+                user_consent: true,
+                data_subjects: vec![format!("subject-{}", subject)],
+                purpose: "service_delivery".to_string(),
+            })),
+        )
+    }
+
+    // ---- cost ----
+
+    fn token_cost(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let (prompt_tokens, completion_tokens) = self.token_counts(timestamp);
+        let common = self.common(timestamp, SourceModule::LlmCostOps, EventType::Cost, model);
+
+        Self::event(
+            common.clone(),
+            EventPayload::Cost(CostPayload::TokenCost(TokenCostEvent {
+                model_id: model.model_id.clone(),
+                request_id: common.event_id.to_string(),
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                cost_per_prompt_token: model.cost_per_prompt_token,
+                cost_per_completion_token: model.cost_per_completion_token,
+                total_cost_usd: prompt_tokens as f64 * model.cost_per_prompt_token
+                    + completion_tokens as f64 * model.cost_per_completion_token,
+                currency: "USD".to_string(),
+            })),
+        )
+    }
+
+    fn api_cost(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let request_count = self.rng.gen_range(100..10_000u64);
+        let cost_per_request = 0.002;
+        let common = self.common(timestamp, SourceModule::LlmCostOps, EventType::Cost, model);
+
+        Self::event(
+            common,
+            EventPayload::Cost(CostPayload::ApiCost(ApiCostEvent {
+                provider: model.provider.clone(),
+                api_endpoint: "/v1/chat/completions".to_string(),
+                request_count,
+                cost_per_request,
+                total_cost_usd: request_count as f64 * cost_per_request,
+                billing_period: timestamp.format("%Y-%m").to_string(),
+            })),
+        )
+    }
+
+    fn resource_consumption(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+    ) -> AnalyticsEvent {
+        let utilization_percent = self.rng.gen_range(20.0..95.0);
+        let gpu = self.rng.gen_range(0..8u32);
+        let common = self.common(timestamp, SourceModule::LlmCostOps, EventType::Cost, model);
+
+        Self::event(
+            common,
+            EventPayload::Cost(CostPayload::ResourceConsumption(ResourceConsumptionEvent {
+                resource_type: ResourceType::Gpu,
+                resource_id: format!("{}-gpu-{}", model.model_id, gpu),
+                quantity: 1.0,
+                unit: "gpu_hour".to_string(),
+                cost_usd: 2.5 * utilization_percent / 100.0,
+                utilization_percent,
+            })),
+        )
+    }
+
+    fn budget_alert(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let budget_limit_usd = 10_000.0;
+        let spend_ratio = self.rng.gen_range(0.8..1.0)
+            * self
+                .multiplier(timestamp, self.config.token_multiplier)
+                .sqrt();
+        let (alert_type, severity) = if spend_ratio >= 1.0 {
+            (BudgetAlertType::Exceeded, Severity::Critical)
+        } else if spend_ratio >= 0.9 {
+            (BudgetAlertType::Critical, Severity::Error)
+        } else {
+            (BudgetAlertType::Warning, Severity::Warning)
+        };
+        let mut common = self.common(timestamp, SourceModule::LlmCostOps, EventType::Cost, model);
+        common.severity = severity;
+        let tenant = common.tenant_id().unwrap_or_default().to_string();
+
+        Self::event(
+            common,
+            EventPayload::Cost(CostPayload::BudgetAlert(BudgetAlertEvent {
+                budget_id: format!("{}-monthly", tenant),
+                budget_name: format!("{} monthly LLM spend", tenant),
+                budget_limit_usd,
+                current_spend_usd: budget_limit_usd * spend_ratio,
+                threshold_percent: (spend_ratio * 100.0).floor().min(100.0),
+                alert_type,
+            })),
+        )
+    }
+
+    // ---- governance ----
+
+    fn policy_violation(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+    ) -> AnalyticsEvent {
+        let user = self.rng.gen_range(0..500u32);
+        let auto_remediated = self.rng.gen_bool(0.6);
+        let mut common = self.common(
+            timestamp,
+            SourceModule::LlmGovernanceDashboard,
+            EventType::Governance,
+            model,
+        );
+        common.severity = Severity::Warning;
+
+        Self::event(
+            common,
+            EventPayload::Governance(GovernancePayload::PolicyViolation(PolicyViolationEvent {
+                policy_id: "pii-output-filter".to_string(),
+                policy_name: "PII output filter".to_string(),
+                violation_description: "Response contained personal data".to_string(),
+                violated_rules: vec!["no-email-in-output".to_string()],
+                resource_id: model.model_id.clone(),
+                user_id: Some(format!("user-{}", user)),
+                severity: PolicyViolationSeverity::Medium,
+                auto_remediated,
+            })),
+        )
+    }
+
+    fn audit_trail(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+        action: &str,
+    ) -> AnalyticsEvent {
+        let user = self.rng.gen_range(0..500u32);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmGovernanceDashboard,
+            EventType::Audit,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Governance(GovernancePayload::AuditTrail(AuditTrailEvent {
+                action: action.to_string(),
+                actor: format!("user-{}", user),
+                resource_type: "model".to_string(),
+                resource_id: model.model_id.clone(),
+                changes: HashMap::new(),
+                ip_address: None,
+                user_agent: None,
+            })),
+        )
+    }
+
+    fn compliance_check(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        model: &ModelProfile,
+    ) -> AnalyticsEvent {
+        let passed = self.rng.gen_bool(0.9);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmGovernanceDashboard,
+            EventType::Governance,
+            model,
+        );
+
+        Self::event(
+            common.clone(),
+            EventPayload::Governance(GovernancePayload::ComplianceCheck(ComplianceCheckEvent {
+                check_id: common.event_id.to_string(),
+                framework: "SOC2".to_string(),
+                controls_checked: vec!["CC6.1".to_string(), "CC7.2".to_string()],
+                passed,
+                findings: vec![ComplianceFinding {
+                    control_id: "CC7.2".to_string(),
+                    status: if passed {
+                        ComplianceStatus::Pass
+                    } else {
+                        ComplianceStatus::Fail
+                    },
+                    description: "Anomalies in model traffic are monitored".to_string(),
+                    evidence: None,
+                }],
+                score: if passed { 100.0 } else { 50.0 },
+            })),
+        )
+    }
+
+    fn data_lineage(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmGovernanceDashboard,
+            EventType::Governance,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Governance(GovernancePayload::DataLineage(DataLineageEvent {
+                data_asset_id: format!("{}-finetune-set", model.model_id),
+                operation: DataOperation::Transform,
+                source: Some("raw_conversations".to_string()),
+                destination: Some("training_corpus".to_string()),
+                transformation: Some("pii_redaction".to_string()),
+                lineage_path: vec![
+                    "raw_conversations".to_string(),
+                    "pii_redaction".to_string(),
+                    "training_corpus".to_string(),
+                ],
+            })),
+        )
+    }
+
+    // ---- custom ----
+
+    fn custom(&mut self, timestamp: DateTime<Utc>, model: &ModelProfile) -> AnalyticsEvent {
+        let rating = self.rng.gen_range(1..=5u8);
+        let common = self.common(
+            timestamp,
+            SourceModule::LlmAnalyticsHub,
+            EventType::Telemetry,
+            model,
+        );
+
+        Self::event(
+            common,
+            EventPayload::Custom(CustomPayload {
+                custom_type: "user_feedback".to_string(),
+                data: serde_json::json!({ "model": model.model_id, "rating": rating }),
+            }),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Telemetry,
+    Security,
+    Cost,
+    Governance,
+    Custom,
+}
+
+/// Iterator over a generated time range, see `SyntheticGenerator::history`
+pub struct History<'a> {
+    generator: &'a mut SyntheticGenerator,
+    next: DateTime<Utc>,
+    end: DateTime<Utc>,
+    mean_gap_ms: f64,
+}
+
+impl Iterator for History<'_> {
+    type Item = AnalyticsEvent;
+
+    fn next(&mut self) -> Option<AnalyticsEvent> {
+        if self.next >= self.end {
+            return None;
+        }
+        let timestamp = self.next;
+        let event = self.generator.next_event(timestamp);
+
+        let u: f64 = self.generator.rng.gen_range(f64::EPSILON..1.0);
+        let gap_ms = -self.mean_gap_ms * u.ln();
+        self.next = timestamp + Duration::microseconds((gap_ms * 1000.0) as i64);
+        Some(event)
+    }
+}
@@ -0,0 +1,217 @@
+//! Scenario Configuration
+//!
+//! Knobs controlling what the synthetic generator produces: the model fleet,
+//! the mix of payload families, baseline error and threat rates, and an
+//! optional incident window during which those baselines are distorted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use crate::database::environment::default_environment;
+
+/// Model whose traffic the generator simulates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelProfile {
+    pub model_id: String,
+    pub provider: String,
+    /// Median request latency
+    pub median_latency_ms: f64,
+    pub cost_per_prompt_token: f64,
+    pub cost_per_completion_token: f64,
+    /// Relative share of traffic served by this model
+    pub weight: f64,
+}
+
+impl ModelProfile {
+    fn new(
+        model_id: &str,
+        provider: &str,
+        median_latency_ms: f64,
+        cost_per_prompt_token: f64,
+        cost_per_completion_token: f64,
+        weight: f64,
+    ) -> Self {
+        Self {
+            model_id: model_id.to_string(),
+            provider: provider.to_string(),
+            median_latency_ms,
+            cost_per_prompt_token,
+            cost_per_completion_token,
+            weight,
+        }
+    }
+
+    /// A small fleet with hosted and self-hosted models
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("gpt-4o", "openai", 900.0, 0.000_005, 0.000_015, 0.30),
+            Self::new(
+                "gpt-4o-mini",
+                "openai",
+                450.0,
+                0.000_000_15,
+                0.000_000_6,
+                0.30,
+            ),
+            Self::new(
+                "claude-3-5-sonnet",
+                "anthropic",
+                1100.0,
+                0.000_003,
+                0.000_015,
+                0.25,
+            ),
+            Self::new(
+                "llama-3-70b",
+                "self-hosted",
+                700.0,
+                0.000_000_9,
+                0.000_000_9,
+                0.15,
+            ),
+        ]
+    }
+}
+
+/// Relative weights of the payload families
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadMix {
+    pub telemetry: f64,
+    pub security: f64,
+    pub cost: f64,
+    pub governance: f64,
+    pub custom: f64,
+}
+
+impl Default for PayloadMix {
+    fn default() -> Self {
+        Self {
+            telemetry: 0.60,
+            security: 0.08,
+            cost: 0.25,
+            governance: 0.05,
+            custom: 0.02,
+        }
+    }
+}
+
+/// Full generator configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScenarioConfig {
+    /// Seed for every random choice, including event IDs
+    pub seed: u64,
+    pub environment: String,
+    /// Tenants events are spread across (`tenant-0` .. `tenant-N`)
+    pub tenants: u32,
+    pub models: Vec<ModelProfile>,
+    pub mix: PayloadMix,
+    /// Fraction of requests that fail
+    pub error_rate: f64,
+    /// Fraction of security events that are threats rather than routine auth
+    /// and privacy activity
+    pub threat_rate: f64,
+    /// Fraction of events that start a correlation chain instead of standing alone
+    pub chain_rate: f64,
+    /// When set, the multipliers below only apply to events inside this window
+    pub incident: Option<Range<DateTime<Utc>>>,
+    pub latency_multiplier: f64,
+    pub error_multiplier: f64,
+    pub token_multiplier: f64,
+    pub threat_multiplier: f64,
+}
+
+impl Default for ScenarioConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            environment: default_environment(),
+            tenants: 10,
+            models: ModelProfile::defaults(),
+            mix: PayloadMix::default(),
+            error_rate: 0.02,
+            threat_rate: 0.05,
+            chain_rate: 0.1,
+            incident: None,
+            latency_multiplier: 1.0,
+            error_multiplier: 1.0,
+            token_multiplier: 1.0,
+            threat_multiplier: 1.0,
+        }
+    }
+}
+
+impl ScenarioConfig {
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Confine the scenario's distortions to `window`
+    pub fn with_incident(mut self, window: Range<DateTime<Utc>>) -> Self {
+        self.incident = Some(window);
+        self
+    }
+
+    /// Whether the scenario multipliers apply at `timestamp`
+    pub fn incident_active(&self, timestamp: DateTime<Utc>) -> bool {
+        match &self.incident {
+            Some(window) => window.contains(&timestamp),
+            None => true,
+        }
+    }
+}
+
+/// Named presets for tests and demo environments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scenario {
+    /// Healthy baseline traffic
+    Steady,
+    /// Latency climbs and requests start failing
+    LatencyDegradation,
+    /// Token usage and spend balloon, tripping budget alerts
+    CostSpike,
+    /// A burst of threats with the auth and policy fallout that follows
+    SecurityIncident,
+}
+
+impl Scenario {
+    pub fn config(self) -> ScenarioConfig {
+        let base = ScenarioConfig::default();
+        match self {
+            Scenario::Steady => base,
+            Scenario::LatencyDegradation => ScenarioConfig {
+                latency_multiplier: 4.0,
+                error_multiplier: 5.0,
+                ..base
+            },
+            Scenario::CostSpike => ScenarioConfig {
+                token_multiplier: 3.0,
+                ..base
+            },
+            Scenario::SecurityIncident => ScenarioConfig {
+                mix: PayloadMix {
+                    security: 0.30,
+                    ..PayloadMix::default()
+                },
+                threat_multiplier: 8.0,
+                chain_rate: 0.25,
+                ..base
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Scenario {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        serde_json::from_value(serde_json::Value::String(
+            s.replace('-', "_").to_lowercase(),
+        ))
+        .map_err(|_| anyhow::anyhow!("Unknown scenario '{}'", s))
+    }
+}
@@ -0,0 +1,213 @@
+//! Distributed Tracing
+//!
+//! Tracing subscriber setup with optional OTLP export, and helpers that attach
+//! event correlation IDs to pipeline spans for end-to-end tracing across the ecosystem.
+
+use crate::schemas::events::CommonEventFields;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Event tag carrying an upstream W3C trace context
+pub const TRACEPARENT_TAG: &str = "traceparent";
+
+/// Tracing and OTLP export configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingConfig {
+    /// Service name reported to the collector
+    pub service_name: String,
+    /// OTLP gRPC collector endpoint; spans are only exported when set
+    pub otlp_endpoint: Option<String>,
+    /// Fraction of root traces to sample (0.0 - 1.0)
+    pub sample_ratio: f64,
+    /// Filter directives used when `RUST_LOG` is not set
+    pub default_filter: String,
+}
+
+impl TracingConfig {
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            otlp_endpoint: None,
+            sample_ratio: 1.0,
+            default_filter: "info".to_string(),
+        }
+    }
+
+    /// Read the standard `OTEL_*` environment variables
+    pub fn from_env(service_name: impl Into<String>) -> Self {
+        let defaults = Self::new(service_name);
+        Self {
+            service_name: std::env::var("OTEL_SERVICE_NAME").unwrap_or(defaults.service_name),
+            otlp_endpoint: std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            sample_ratio: std::env::var("OTEL_TRACES_SAMPLER_ARG")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.sample_ratio),
+            default_filter: defaults.default_filter,
+        }
+    }
+
+    /// Set the filter used when `RUST_LOG` is not set
+    pub fn with_default_filter(mut self, filter: impl Into<String>) -> Self {
+        self.default_filter = filter.into();
+        self
+    }
+}
+
+/// Flushes pending spans when dropped
+pub struct TracingGuard {
+    exporting: bool,
+}
+
+impl TracingGuard {
+    /// Whether spans are being exported to a collector
+    pub fn is_exporting(&self) -> bool {
+        self.exporting
+    }
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "telemetry")]
+        if self.exporting {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Install the global tracing subscriber, exporting spans over OTLP when configured
+///
+/// Keep the returned guard alive for the lifetime of the process.
+pub fn init_tracing(config: &TracingConfig) -> Result<TracingGuard> {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(&config.default_filter));
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "telemetry")]
+    {
+        let otel_layer = match &config.otlp_endpoint {
+            Some(endpoint) => Some(otlp::layer(config, endpoint)?),
+            None => None,
+        };
+        let exporting = otel_layer.is_some();
+        registry.with(otel_layer).try_init()?;
+
+        if let Some(endpoint) = &config.otlp_endpoint {
+            tracing::info!(%endpoint, service = %config.service_name, "Exporting traces over OTLP");
+        }
+        Ok(TracingGuard { exporting })
+    }
+
+    #[cfg(not(feature = "telemetry"))]
+    {
+        registry.try_init()?;
+        if config.otlp_endpoint.is_some() {
+            tracing::warn!("OTLP endpoint configured but the `telemetry` feature is disabled");
+        }
+        Ok(TracingGuard { exporting: false })
+    }
+}
+
+/// Record the event's correlation ID on the current span and link it to any upstream trace
+///
+/// The span must declare a `correlation_id` field (e.g. `correlation_id = tracing::field::Empty`).
+pub fn record_event_context(common: &CommonEventFields) {
+    let span = Span::current();
+    if let Some(correlation_id) = common.correlation_id {
+        span.record("correlation_id", tracing::field::display(correlation_id));
+    }
+
+    #[cfg(feature = "telemetry")]
+    otlp::link_upstream(&span, common);
+}
+
+#[cfg(feature = "telemetry")]
+mod otlp {
+    use super::{TracingConfig, TRACEPARENT_TAG};
+    use crate::schemas::events::CommonEventFields;
+    use anyhow::Result;
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{self, Sampler};
+    use opentelemetry_sdk::Resource;
+    use std::collections::HashMap;
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    /// Build the OpenTelemetry layer exporting to the given collector
+    pub(super) fn layer<S>(
+        config: &TracingConfig,
+        endpoint: &str,
+    ) -> Result<tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio.clamp(0.0, 1.0),
+        )));
+
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(
+                trace::config()
+                    .with_sampler(sampler)
+                    .with_resource(Resource::new(vec![
+                        KeyValue::new("service.name", config.service_name.clone()),
+                        KeyValue::new("service.version", crate::VERSION),
+                    ])),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+
+        Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+    }
+
+    /// Parent the span on the upstream trace carried in the event's tags
+    pub(super) fn link_upstream(span: &Span, common: &CommonEventFields) {
+        if !common.tags.contains_key(TRACEPARENT_TAG) {
+            return;
+        }
+
+        let carrier: HashMap<String, String> = common
+            .tags
+            .iter()
+            .filter(|(k, _)| k.as_str() == TRACEPARENT_TAG || k.as_str() == "tracestate")
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let context = opentelemetry::global::get_text_map_propagator(|p| p.extract(&carrier));
+        if context.span().span_context().is_valid() {
+            span.set_parent(context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracing_config_defaults() {
+        let config = TracingConfig::new("event-ingestion").with_default_filter("debug");
+        assert_eq!(config.service_name, "event-ingestion");
+        assert!(config.otlp_endpoint.is_none());
+        assert_eq!(config.sample_ratio, 1.0);
+        assert_eq!(config.default_filter, "debug");
+    }
+}
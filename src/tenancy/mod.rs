@@ -0,0 +1,507 @@
+//! Multi-Tenancy
+//!
+//! Events belong to the tenant named in their `tenant_id` tag. Each API request
+//! resolves a `TenantScope`: callers bound to a tenant (through their API key
+//! or token) only ever see and write that tenant's data, while unbound callers
+//! may pick a tenant with the `x-tenant-id` header or query across tenants.
+//!
+//! `TenantQuotas` enforces per-tenant ingestion rates with token buckets and
+//! caps concurrent queries per tenant with bulkheads. Events without a tenant
+//! are not subject to quotas.
+
+use crate::auth::Principal;
+use crate::database::EventFilter;
+use crate::resilience::bulkhead::{Bulkhead, BulkheadPermit};
+use crate::schemas::events::{AnalyticsEvent, TENANT_TAG};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+// ========== Tenant Scope ==========
+
+/// Tenant scope applied to a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum TenantScope {
+    /// A single tenant's data
+    Tenant { tenant_id: String },
+    /// Every tenant's data (unbound callers only)
+    AllTenants,
+}
+
+/// Reason a tenant scope could not be granted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantError {
+    /// A tenant-bound caller asked for another tenant
+    Mismatch { bound: String, requested: String },
+    /// A tenant-bound caller asked for data that spans tenants
+    CrossTenant { tenant_id: String },
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TenantError::Mismatch { bound, requested } => write!(
+                f,
+                "Caller is bound to tenant {} and cannot access tenant {}",
+                bound, requested
+            ),
+            TenantError::CrossTenant { tenant_id } => write!(
+                f,
+                "Data spans tenants and is not available to tenant {}",
+                tenant_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
+
+impl TenantScope {
+    pub fn tenant(tenant_id: impl Into<String>) -> Self {
+        Self::Tenant {
+            tenant_id: tenant_id.into(),
+        }
+    }
+
+    /// Resolve the scope of a request from its caller and requested tenant
+    pub fn resolve(
+        principal: Option<&Principal>,
+        requested: Option<&str>,
+    ) -> Result<Self, TenantError> {
+        let requested = requested.filter(|t| !t.is_empty());
+        match (principal.and_then(|p| p.tenant_id.as_deref()), requested) {
+            (Some(bound), Some(requested)) if bound != requested => Err(TenantError::Mismatch {
+                bound: bound.to_string(),
+                requested: requested.to_string(),
+            }),
+            (Some(bound), _) => Ok(Self::tenant(bound)),
+            (None, Some(requested)) => Ok(Self::tenant(requested)),
+            (None, None) => Ok(Self::AllTenants),
+        }
+    }
+
+    /// Tenant the scope is restricted to, `None` for all tenants
+    pub fn tenant_id(&self) -> Option<&str> {
+        match self {
+            Self::Tenant { tenant_id } => Some(tenant_id),
+            Self::AllTenants => None,
+        }
+    }
+
+    /// Whether data owned by `tenant_id` is visible in this scope
+    pub fn allows(&self, tenant_id: Option<&str>) -> bool {
+        match self {
+            Self::Tenant { tenant_id: scoped } => tenant_id == Some(scoped.as_str()),
+            Self::AllTenants => true,
+        }
+    }
+
+    /// Restrict an event filter to this scope
+    pub fn scope_filter(&self, filter: Option<EventFilter>) -> Option<EventFilter> {
+        let Some(tenant_id) = self.tenant_id() else {
+            return filter;
+        };
+        let tenant = EventFilter::tag_equals(TENANT_TAG, tenant_id);
+        Some(match filter {
+            Some(filter) => filter.and(tenant),
+            None => tenant,
+        })
+    }
+
+    /// Tags selecting this tenant's aggregated metrics, `None` for all tenants
+    pub fn aggregate_tags(&self) -> Option<serde_json::Value> {
+        let tenant_id = self.tenant_id()?;
+        let mut tags = serde_json::Map::new();
+        tags.insert(TENANT_TAG.to_string(), tenant_id.into());
+        Some(serde_json::Value::Object(tags))
+    }
+
+    /// Reject tenant-bound scopes for data that is not partitioned by tenant
+    pub fn require_all_tenants(&self) -> Result<(), TenantError> {
+        match self {
+            Self::Tenant { tenant_id } => Err(TenantError::CrossTenant {
+                tenant_id: tenant_id.clone(),
+            }),
+            Self::AllTenants => Ok(()),
+        }
+    }
+
+    /// Assign an ingested event to the scoped tenant.
+    ///
+    /// Events ingested across tenants keep whatever tenant they declare.
+    pub fn stamp(&self, event: &mut AnalyticsEvent) {
+        if let Self::Tenant { tenant_id } = self {
+            event.common.set_tenant(tenant_id.clone());
+        }
+    }
+}
+
+// ========== Quotas ==========
+
+/// Limits applied to a single tenant; zero means unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TenantQuota {
+    /// Sustained events per second
+    pub ingest_rps: u32,
+    /// Concurrent queries
+    pub max_concurrent_queries: usize,
+}
+
+/// Per-tenant quota configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantQuotaConfig {
+    /// Quota for tenants without an override
+    pub default_quota: TenantQuota,
+    /// Seconds of sustained rate a tenant may burst above its limit
+    pub burst_secs: f64,
+    /// Per-tenant overrides
+    pub overrides: HashMap<String, TenantQuota>,
+}
+
+impl Default for TenantQuotaConfig {
+    fn default() -> Self {
+        Self {
+            default_quota: TenantQuota {
+                ingest_rps: 1000,
+                max_concurrent_queries: 10,
+            },
+            burst_secs: 2.0,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl TenantQuotaConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let overrides = match std::env::var("TENANT_QUOTA_OVERRIDES") {
+            Ok(spec) => Self::parse_overrides(&spec)?,
+            Err(_) => defaults.overrides,
+        };
+        Ok(Self {
+            default_quota: TenantQuota {
+                ingest_rps: std::env::var("TENANT_INGEST_RPS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(defaults.default_quota.ingest_rps),
+                max_concurrent_queries: std::env::var("TENANT_MAX_CONCURRENT_QUERIES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(defaults.default_quota.max_concurrent_queries),
+            },
+            burst_secs: std::env::var("TENANT_INGEST_BURST_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.burst_secs),
+            overrides,
+        })
+    }
+
+    /// Parse `tenant_id:ingest_rps:max_concurrent_queries` entries separated by commas
+    pub fn parse_overrides(spec: &str) -> Result<HashMap<String, TenantQuota>> {
+        let mut overrides = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').collect();
+            let &[tenant_id, rps, queries] = parts.as_slice() else {
+                anyhow::bail!(
+                    "Quota overrides must be tenant_id:ingest_rps:max_concurrent_queries"
+                );
+            };
+            let quota = TenantQuota {
+                ingest_rps: rps
+                    .parse()
+                    .with_context(|| format!("Invalid ingest rate for tenant {}", tenant_id))?,
+                max_concurrent_queries: queries.parse().with_context(|| {
+                    format!("Invalid query concurrency for tenant {}", tenant_id)
+                })?,
+            };
+            overrides.insert(tenant_id.to_string(), quota);
+        }
+        Ok(overrides)
+    }
+
+    pub fn quota_for(&self, tenant_id: &str) -> TenantQuota {
+        self.overrides
+            .get(tenant_id)
+            .copied()
+            .unwrap_or(self.default_quota)
+    }
+}
+
+/// Quota a request exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaExceeded {
+    IngestRate { tenant_id: String, limit: u32 },
+    QueryConcurrency { tenant_id: String, limit: usize },
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaExceeded::IngestRate { tenant_id, limit } => write!(
+                f,
+                "Tenant {} exceeded its ingestion quota of {} events/s",
+                tenant_id, limit
+            ),
+            QuotaExceeded::QueryConcurrency { tenant_id, limit } => write!(
+                f,
+                "Tenant {} already has {} queries running",
+                tenant_id, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Token bucket refilled continuously at the tenant's rate
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    tokens: f64,
+    updated: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(capacity: f64, now: DateTime<Utc>) -> Self {
+        Self {
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    pub(crate) fn try_take(
+        &mut self,
+        count: f64,
+        rate: f64,
+        capacity: f64,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let elapsed = (now - self.updated).num_milliseconds().max(0) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.updated = now;
+        if self.tokens >= count {
+            self.tokens -= count;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Per-tenant ingestion and query quotas
+pub struct TenantQuotas {
+    config: TenantQuotaConfig,
+    buckets: DashMap<String, Mutex<TokenBucket>>,
+    query_slots: DashMap<String, Arc<Bulkhead>>,
+    ingest_throttled: AtomicU64,
+    queries_rejected: AtomicU64,
+}
+
+impl TenantQuotas {
+    pub fn new(config: TenantQuotaConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+            query_slots: DashMap::new(),
+            ingest_throttled: AtomicU64::new(0),
+            queries_rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Admit `events` events for a tenant against its ingestion rate
+    pub fn check_ingest(&self, tenant_id: &str, events: u32) -> Result<(), QuotaExceeded> {
+        self.check_ingest_at(tenant_id, events, Utc::now())
+    }
+
+    /// Ingestion admission as of `now`
+    pub fn check_ingest_at(
+        &self,
+        tenant_id: &str,
+        events: u32,
+        now: DateTime<Utc>,
+    ) -> Result<(), QuotaExceeded> {
+        let limit = self.config.quota_for(tenant_id).ingest_rps;
+        if limit == 0 {
+            return Ok(());
+        }
+        let rate = limit as f64;
+        let capacity = rate * self.config.burst_secs.max(1.0);
+
+        let admitted = self
+            .buckets
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| Mutex::new(TokenBucket::new(capacity, now)))
+            .lock()
+            .try_take(events as f64, rate, capacity, now);
+        if admitted {
+            Ok(())
+        } else {
+            self.ingest_throttled
+                .fetch_add(events as u64, Ordering::Relaxed);
+            Err(QuotaExceeded::IngestRate {
+                tenant_id: tenant_id.to_string(),
+                limit,
+            })
+        }
+    }
+
+    /// Take a query slot for a tenant, held until the permit is dropped.
+    ///
+    /// Returns `None` when the tenant's query concurrency is unlimited.
+    pub async fn acquire_query(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Option<BulkheadPermit>, QuotaExceeded> {
+        let limit = self.config.quota_for(tenant_id).max_concurrent_queries;
+        if limit == 0 {
+            return Ok(None);
+        }
+
+        let bulkhead = self
+            .query_slots
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| Arc::new(Bulkhead::new(format!("tenant:{}", tenant_id), limit, 0)))
+            .clone();
+        match bulkhead.acquire().await {
+            Ok(permit) => Ok(Some(permit)),
+            Err(_) => {
+                self.queries_rejected.fetch_add(1, Ordering::Relaxed);
+                warn!(tenant_id, limit, "Tenant query concurrency quota exceeded");
+                Err(QuotaExceeded::QueryConcurrency {
+                    tenant_id: tenant_id.to_string(),
+                    limit,
+                })
+            }
+        }
+    }
+
+    pub fn get_stats(&self) -> TenantQuotaStats {
+        TenantQuotaStats {
+            tenants_tracked: self.buckets.len().max(self.query_slots.len()),
+            ingest_throttled: self.ingest_throttled.load(Ordering::Relaxed),
+            queries_rejected: self.queries_rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tenant quota statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantQuotaStats {
+    pub tenants_tracked: usize,
+    pub ingest_throttled: u64,
+    pub queries_rejected: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthMethod, Scope};
+
+    fn principal(tenant_id: Option<&str>) -> Principal {
+        Principal {
+            subject: "client".to_string(),
+            scope: Scope::Read,
+            method: AuthMethod::ApiKey,
+            tenant_id: tenant_id.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_scope_resolution() {
+        let bound = principal(Some("team-a"));
+        assert_eq!(
+            TenantScope::resolve(Some(&bound), None),
+            Ok(TenantScope::tenant("team-a"))
+        );
+        assert_eq!(
+            TenantScope::resolve(Some(&bound), Some("team-a")),
+            Ok(TenantScope::tenant("team-a"))
+        );
+        assert!(matches!(
+            TenantScope::resolve(Some(&bound), Some("team-b")),
+            Err(TenantError::Mismatch { .. })
+        ));
+
+        let unbound = principal(None);
+        assert_eq!(
+            TenantScope::resolve(Some(&unbound), Some("team-b")),
+            Ok(TenantScope::tenant("team-b"))
+        );
+        assert_eq!(
+            TenantScope::resolve(Some(&unbound), None),
+            Ok(TenantScope::AllTenants)
+        );
+    }
+
+    #[test]
+    fn test_scope_filters() {
+        let scope = TenantScope::tenant("team-a");
+        assert!(scope.allows(Some("team-a")));
+        assert!(!scope.allows(Some("team-b")));
+        assert!(!scope.allows(None));
+        assert_eq!(
+            scope.scope_filter(None),
+            Some(EventFilter::tag_equals(TENANT_TAG, "team-a"))
+        );
+        assert!(scope.require_all_tenants().is_err());
+
+        assert_eq!(TenantScope::AllTenants.scope_filter(None), None);
+        assert!(TenantScope::AllTenants.allows(None));
+    }
+
+    #[test]
+    fn test_ingest_rate_quota() {
+        let mut config = TenantQuotaConfig {
+            burst_secs: 1.0,
+            ..TenantQuotaConfig::default()
+        };
+        config.overrides = TenantQuotaConfig::parse_overrides("small:10:1, unlimited:0:0").unwrap();
+        let quotas = TenantQuotas::new(config);
+        let now = Utc::now();
+
+        assert!(quotas.check_ingest_at("small", 10, now).is_ok());
+        assert!(quotas.check_ingest_at("small", 1, now).is_err());
+        // Refills at the sustained rate
+        let later = now + chrono::Duration::milliseconds(500);
+        assert!(quotas.check_ingest_at("small", 5, later).is_ok());
+        assert!(quotas.check_ingest_at("small", 1, later).is_err());
+
+        assert!(quotas.check_ingest_at("unlimited", 1_000_000, now).is_ok());
+        assert_eq!(quotas.get_stats().ingest_throttled, 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_concurrency_quota() {
+        let config = TenantQuotaConfig {
+            overrides: TenantQuotaConfig::parse_overrides("small:0:1").unwrap(),
+            ..TenantQuotaConfig::default()
+        };
+        let quotas = TenantQuotas::new(config);
+
+        let permit = quotas.acquire_query("small").await.unwrap();
+        assert!(permit.is_some());
+        assert!(matches!(
+            quotas.acquire_query("small").await,
+            Err(QuotaExceeded::QueryConcurrency { limit: 1, .. })
+        ));
+        // Other tenants are unaffected
+        assert!(quotas.acquire_query("other").await.is_ok());
+
+        drop(permit);
+        assert!(quotas.acquire_query("small").await.is_ok());
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(TenantQuotaConfig::parse_overrides("team:10").is_err());
+        assert!(TenantQuotaConfig::parse_overrides("team:fast:1").is_err());
+    }
+}
@@ -0,0 +1,875 @@
+//! Property-based Tests and Fuzzing for the AnalyticsEvent Schema
+//!
+//! Generates arbitrary events covering every `EventPayload` variant and checks
+//! that they survive JSON and MessagePack round trips unchanged. The decoder
+//! used by the Kafka and gRPC ingestion paths (`serde_json::from_slice` followed
+//! by `schemas::events::validate_event`, which the HTTP and OTLP paths also
+//! apply) is fuzzed with random bytes, random JSON documents and corrupted
+//! versions of valid events; it must reject bad input without panicking.
+
+use chrono::{DateTime, Utc};
+use llm_analytics_hub::schemas::events::*;
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// ============================================================================
+// STRATEGIES
+// ============================================================================
+
+/// Finite floats with short decimal representations, so the JSON text round
+/// trips to the same `f64`
+fn finite() -> impl Strategy<Value = f64> {
+    (-1_000_000_000i64..1_000_000_000i64).prop_map(|v| v as f64 / 1000.0)
+}
+
+fn non_negative() -> impl Strategy<Value = f64> {
+    (0i64..1_000_000_000i64).prop_map(|v| v as f64 / 1000.0)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9_\\-\\.:/ ]{0,24}"
+}
+
+fn texts() -> impl Strategy<Value = Vec<String>> {
+    vec(text(), 0..4)
+}
+
+fn uuid() -> impl Strategy<Value = Uuid> {
+    any::<u128>().prop_map(Uuid::from_u128)
+}
+
+fn timestamp() -> impl Strategy<Value = DateTime<Utc>> {
+    // 1970-01-01T00:00:01Z through 2100-01-01
+    (1i64..4_102_444_800i64, 0u32..1_000_000_000u32)
+        .prop_map(|(secs, nanos)| DateTime::from_timestamp(secs, nanos).unwrap())
+}
+
+fn source_module() -> impl Strategy<Value = SourceModule> {
+    prop_oneof![
+        Just(SourceModule::LlmObservatory),
+        Just(SourceModule::LlmSentinel),
+        Just(SourceModule::LlmCostOps),
+        Just(SourceModule::LlmGovernanceDashboard),
+        Just(SourceModule::LlmRegistry),
+        Just(SourceModule::LlmPolicyEngine),
+        Just(SourceModule::LlmAnalyticsHub),
+    ]
+}
+
+fn event_type() -> impl Strategy<Value = EventType> {
+    prop_oneof![
+        Just(EventType::Telemetry),
+        Just(EventType::Security),
+        Just(EventType::Cost),
+        Just(EventType::Governance),
+        Just(EventType::Lifecycle),
+        Just(EventType::Audit),
+        Just(EventType::Alert),
+    ]
+}
+
+fn severity() -> impl Strategy<Value = Severity> {
+    prop_oneof![
+        Just(Severity::Debug),
+        Just(Severity::Info),
+        Just(Severity::Warning),
+        Just(Severity::Error),
+        Just(Severity::Critical),
+    ]
+}
+
+prop_compose! {
+    fn common_fields()(
+        event_id in uuid(),
+        timestamp in timestamp(),
+        source_module in source_module(),
+        event_type in event_type(),
+        correlation_id in option::of(uuid()),
+        parent_event_id in option::of(uuid()),
+        severity in severity(),
+        environment in text(),
+        tags in hash_map(text(), text(), 0..4),
+    ) -> CommonEventFields {
+        CommonEventFields {
+            event_id,
+            timestamp,
+            source_module,
+            event_type,
+            correlation_id,
+            parent_event_id,
+            schema_version: SCHEMA_VERSION.to_string(),
+            severity,
+            environment,
+            tags,
+        }
+    }
+}
+
+// ---- telemetry ----
+
+prop_compose! {
+    fn latency_breakdown()(
+        queue_time_ms in non_negative(),
+        processing_time_ms in non_negative(),
+        network_time_ms in non_negative(),
+        other_ms in non_negative(),
+    ) -> LatencyBreakdown {
+        LatencyBreakdown { queue_time_ms, processing_time_ms, network_time_ms, other_ms }
+    }
+}
+
+prop_compose! {
+    fn latency()(
+        model_id in text(),
+        request_id in text(),
+        total_latency_ms in non_negative(),
+        ttft_ms in option::of(non_negative()),
+        tokens_per_second in option::of(non_negative()),
+        breakdown in option::of(latency_breakdown()),
+    ) -> TelemetryPayload {
+        TelemetryPayload::Latency(LatencyMetrics {
+            model_id,
+            request_id,
+            total_latency_ms,
+            ttft_ms,
+            tokens_per_second,
+            breakdown,
+        })
+    }
+}
+
+prop_compose! {
+    fn throughput()(
+        model_id in text(),
+        requests_per_second in non_negative(),
+        tokens_per_second in non_negative(),
+        concurrent_requests in any::<u32>(),
+        window_duration_seconds in any::<u32>(),
+    ) -> TelemetryPayload {
+        TelemetryPayload::Throughput(ThroughputMetrics {
+            model_id,
+            requests_per_second,
+            tokens_per_second,
+            concurrent_requests,
+            window_duration_seconds,
+        })
+    }
+}
+
+prop_compose! {
+    fn error_rate()(
+        model_id in text(),
+        total_requests in any::<u64>(),
+        failed_requests in any::<u64>(),
+        error_rate_percent in non_negative(),
+        error_breakdown in hash_map(text(), any::<u64>(), 0..4),
+        window_duration_seconds in any::<u32>(),
+    ) -> TelemetryPayload {
+        TelemetryPayload::ErrorRate(ErrorRateMetrics {
+            model_id,
+            total_requests,
+            failed_requests,
+            error_rate_percent,
+            error_breakdown,
+            window_duration_seconds,
+        })
+    }
+}
+
+prop_compose! {
+    fn token_usage()(
+        model_id in text(),
+        request_id in text(),
+        prompt_tokens in any::<u32>(),
+        completion_tokens in any::<u32>(),
+        total_tokens in any::<u32>(),
+    ) -> TelemetryPayload {
+        TelemetryPayload::TokenUsage(TokenUsageMetrics {
+            model_id,
+            request_id,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+        })
+    }
+}
+
+prop_compose! {
+    fn model_performance()(
+        model_id in text(),
+        accuracy in option::of(finite()),
+        quality_score in option::of(finite()),
+        user_satisfaction in option::of(finite()),
+        custom_metrics in hash_map(text(), finite(), 0..4),
+    ) -> TelemetryPayload {
+        TelemetryPayload::ModelPerformance(ModelPerformanceMetrics {
+            model_id,
+            accuracy,
+            quality_score,
+            user_satisfaction,
+            custom_metrics,
+        })
+    }
+}
+
+fn telemetry() -> impl Strategy<Value = EventPayload> {
+    prop_oneof![
+        latency(),
+        throughput(),
+        error_rate(),
+        token_usage(),
+        model_performance(),
+    ]
+    .prop_map(EventPayload::Telemetry)
+}
+
+// ---- security ----
+
+fn threat_type() -> impl Strategy<Value = ThreatType> {
+    prop_oneof![
+        Just(ThreatType::PromptInjection),
+        Just(ThreatType::DataExfiltration),
+        Just(ThreatType::ModelPoisoning),
+        Just(ThreatType::DenialOfService),
+        Just(ThreatType::UnauthorizedAccess),
+        Just(ThreatType::MaliciousInput),
+        text().prop_map(ThreatType::Other),
+    ]
+}
+
+fn threat_level() -> impl Strategy<Value = ThreatLevel> {
+    prop_oneof![
+        Just(ThreatLevel::Low),
+        Just(ThreatLevel::Medium),
+        Just(ThreatLevel::High),
+        Just(ThreatLevel::Critical),
+    ]
+}
+
+fn mitigation_status() -> impl Strategy<Value = MitigationStatus> {
+    prop_oneof![
+        Just(MitigationStatus::Detected),
+        Just(MitigationStatus::Blocked),
+        Just(MitigationStatus::Mitigated),
+        Just(MitigationStatus::Investigating),
+        Just(MitigationStatus::Resolved),
+    ]
+}
+
+prop_compose! {
+    fn threat()(
+        threat_id in text(),
+        threat_type in threat_type(),
+        threat_level in threat_level(),
+        source_ip in option::of(text()),
+        target_resource in text(),
+        attack_vector in text(),
+        mitigation_status in mitigation_status(),
+        indicators_of_compromise in texts(),
+    ) -> SecurityPayload {
+        SecurityPayload::Threat(ThreatEvent {
+            threat_id,
+            threat_type,
+            threat_level,
+            source_ip,
+            target_resource,
+            attack_vector,
+            mitigation_status,
+            indicators_of_compromise,
+        })
+    }
+}
+
+fn remediation_status() -> impl Strategy<Value = RemediationStatus> {
+    prop_oneof![
+        Just(RemediationStatus::Identified),
+        Just(RemediationStatus::PatchAvailable),
+        Just(RemediationStatus::Patching),
+        Just(RemediationStatus::Patched),
+        Just(RemediationStatus::Accepted),
+    ]
+}
+
+prop_compose! {
+    fn vulnerability()(
+        vulnerability_id in text(),
+        cve_id in option::of(text()),
+        severity_score in non_negative(),
+        affected_component in text(),
+        description in text(),
+        remediation_status in remediation_status(),
+    ) -> SecurityPayload {
+        SecurityPayload::Vulnerability(VulnerabilityEvent {
+            vulnerability_id,
+            cve_id,
+            severity_score,
+            affected_component,
+            description,
+            remediation_status,
+        })
+    }
+}
+
+prop_compose! {
+    fn compliance_violation()(
+        violation_id in text(),
+        regulation in text(),
+        requirement in text(),
+        violation_description in text(),
+        affected_data_types in texts(),
+        remediation_required in any::<bool>(),
+    ) -> SecurityPayload {
+        SecurityPayload::ComplianceViolation(ComplianceViolationEvent {
+            violation_id,
+            regulation,
+            requirement,
+            violation_description,
+            affected_data_types,
+            remediation_required,
+        })
+    }
+}
+
+fn auth_action() -> impl Strategy<Value = AuthAction> {
+    prop_oneof![
+        Just(AuthAction::Login),
+        Just(AuthAction::Logout),
+        Just(AuthAction::AccessAttempt),
+        Just(AuthAction::PermissionDenied),
+        Just(AuthAction::TokenGenerated),
+        Just(AuthAction::TokenRevoked),
+    ]
+}
+
+prop_compose! {
+    fn auth()(
+        user_id in text(),
+        action in auth_action(),
+        resource in text(),
+        success in any::<bool>(),
+        failure_reason in option::of(text()),
+    ) -> SecurityPayload {
+        SecurityPayload::Auth(AuthEvent { user_id, action, resource, success, failure_reason })
+    }
+}
+
+fn privacy_operation() -> impl Strategy<Value = PrivacyOperation> {
+    prop_oneof![
+        Just(PrivacyOperation::DataAccess),
+        Just(PrivacyOperation::DataCollection),
+        Just(PrivacyOperation::DataSharing),
+        Just(PrivacyOperation::DataDeletion),
+        Just(PrivacyOperation::ConsentUpdate),
+    ]
+}
+
+prop_compose! {
+    fn privacy()(
+        data_type in text(),
+        operation in privacy_operation(),
+        user_consent in any::<bool>(),
+        data_subjects in texts(),
+        purpose in text(),
+    ) -> SecurityPayload {
+        SecurityPayload::Privacy(PrivacyEvent {
+            data_type,
+            operation,
+            user_consent,
+            data_subjects,
+            purpose,
+        })
+    }
+}
+
+fn security() -> impl Strategy<Value = EventPayload> {
+    prop_oneof![
+        threat(),
+        vulnerability(),
+        compliance_violation(),
+        auth(),
+        privacy()
+    ]
+    .prop_map(EventPayload::Security)
+}
+
+// ---- cost ----
+
+prop_compose! {
+    fn token_cost()(
+        model_id in text(),
+        request_id in text(),
+        prompt_tokens in any::<u32>(),
+        completion_tokens in any::<u32>(),
+        total_tokens in any::<u32>(),
+        cost_per_prompt_token in non_negative(),
+        cost_per_completion_token in non_negative(),
+        total_cost_usd in non_negative(),
+        currency in text(),
+    ) -> CostPayload {
+        CostPayload::TokenCost(TokenCostEvent {
+            model_id,
+            request_id,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+            cost_per_prompt_token,
+            cost_per_completion_token,
+            total_cost_usd,
+            currency,
+        })
+    }
+}
+
+prop_compose! {
+    fn api_cost()(
+        provider in text(),
+        api_endpoint in text(),
+        request_count in any::<u64>(),
+        cost_per_request in non_negative(),
+        total_cost_usd in non_negative(),
+        billing_period in text(),
+    ) -> CostPayload {
+        CostPayload::ApiCost(ApiCostEvent {
+            provider,
+            api_endpoint,
+            request_count,
+            cost_per_request,
+            total_cost_usd,
+            billing_period,
+        })
+    }
+}
+
+fn resource_type() -> impl Strategy<Value = ResourceType> {
+    prop_oneof![
+        Just(ResourceType::Compute),
+        Just(ResourceType::Storage),
+        Just(ResourceType::Network),
+        Just(ResourceType::Memory),
+        Just(ResourceType::Gpu),
+        text().prop_map(ResourceType::Other),
+    ]
+}
+
+prop_compose! {
+    fn resource_consumption()(
+        resource_type in resource_type(),
+        resource_id in text(),
+        quantity in non_negative(),
+        unit in text(),
+        cost_usd in non_negative(),
+        utilization_percent in non_negative(),
+    ) -> CostPayload {
+        CostPayload::ResourceConsumption(ResourceConsumptionEvent {
+            resource_type,
+            resource_id,
+            quantity,
+            unit,
+            cost_usd,
+            utilization_percent,
+        })
+    }
+}
+
+fn budget_alert_type() -> impl Strategy<Value = BudgetAlertType> {
+    prop_oneof![
+        Just(BudgetAlertType::Warning),
+        Just(BudgetAlertType::Critical),
+        Just(BudgetAlertType::Exceeded),
+    ]
+}
+
+prop_compose! {
+    fn budget_alert()(
+        budget_id in text(),
+        budget_name in text(),
+        budget_limit_usd in non_negative(),
+        current_spend_usd in non_negative(),
+        threshold_percent in non_negative(),
+        alert_type in budget_alert_type(),
+    ) -> CostPayload {
+        CostPayload::BudgetAlert(BudgetAlertEvent {
+            budget_id,
+            budget_name,
+            budget_limit_usd,
+            current_spend_usd,
+            threshold_percent,
+            alert_type,
+        })
+    }
+}
+
+fn cost() -> impl Strategy<Value = EventPayload> {
+    prop_oneof![
+        token_cost(),
+        api_cost(),
+        resource_consumption(),
+        budget_alert()
+    ]
+    .prop_map(EventPayload::Cost)
+}
+
+// ---- governance ----
+
+fn policy_violation_severity() -> impl Strategy<Value = PolicyViolationSeverity> {
+    prop_oneof![
+        Just(PolicyViolationSeverity::Low),
+        Just(PolicyViolationSeverity::Medium),
+        Just(PolicyViolationSeverity::High),
+        Just(PolicyViolationSeverity::Critical),
+    ]
+}
+
+prop_compose! {
+    fn policy_violation()(
+        policy_id in text(),
+        policy_name in text(),
+        violation_description in text(),
+        violated_rules in texts(),
+        resource_id in text(),
+        user_id in option::of(text()),
+        severity in policy_violation_severity(),
+        auto_remediated in any::<bool>(),
+    ) -> GovernancePayload {
+        GovernancePayload::PolicyViolation(PolicyViolationEvent {
+            policy_id,
+            policy_name,
+            violation_description,
+            violated_rules,
+            resource_id,
+            user_id,
+            severity,
+            auto_remediated,
+        })
+    }
+}
+
+prop_compose! {
+    fn audit_trail()(
+        action in text(),
+        actor in text(),
+        resource_type in text(),
+        resource_id in text(),
+        changes in hash_map(text(), json_value(), 0..3),
+        ip_address in option::of(text()),
+        user_agent in option::of(text()),
+    ) -> GovernancePayload {
+        GovernancePayload::AuditTrail(AuditTrailEvent {
+            action,
+            actor,
+            resource_type,
+            resource_id,
+            changes,
+            ip_address,
+            user_agent,
+        })
+    }
+}
+
+fn compliance_status() -> impl Strategy<Value = ComplianceStatus> {
+    prop_oneof![
+        Just(ComplianceStatus::Pass),
+        Just(ComplianceStatus::Fail),
+        Just(ComplianceStatus::NotApplicable),
+        Just(ComplianceStatus::Manual),
+    ]
+}
+
+prop_compose! {
+    fn compliance_finding()(
+        control_id in text(),
+        status in compliance_status(),
+        description in text(),
+        evidence in option::of(text()),
+    ) -> ComplianceFinding {
+        ComplianceFinding { control_id, status, description, evidence }
+    }
+}
+
+prop_compose! {
+    fn compliance_check()(
+        check_id in text(),
+        framework in text(),
+        controls_checked in texts(),
+        passed in any::<bool>(),
+        findings in vec(compliance_finding(), 0..3),
+        score in non_negative(),
+    ) -> GovernancePayload {
+        GovernancePayload::ComplianceCheck(ComplianceCheckEvent {
+            check_id,
+            framework,
+            controls_checked,
+            passed,
+            findings,
+            score,
+        })
+    }
+}
+
+fn data_operation() -> impl Strategy<Value = DataOperation> {
+    prop_oneof![
+        Just(DataOperation::Create),
+        Just(DataOperation::Read),
+        Just(DataOperation::Update),
+        Just(DataOperation::Delete),
+        Just(DataOperation::Transform),
+        Just(DataOperation::Aggregate),
+    ]
+}
+
+prop_compose! {
+    fn data_lineage()(
+        data_asset_id in text(),
+        operation in data_operation(),
+        source in option::of(text()),
+        destination in option::of(text()),
+        transformation in option::of(text()),
+        lineage_path in texts(),
+    ) -> GovernancePayload {
+        GovernancePayload::DataLineage(DataLineageEvent {
+            data_asset_id,
+            operation,
+            source,
+            destination,
+            transformation,
+            lineage_path,
+        })
+    }
+}
+
+fn governance() -> impl Strategy<Value = EventPayload> {
+    prop_oneof![
+        policy_violation(),
+        audit_trail(),
+        compliance_check(),
+        data_lineage()
+    ]
+    .prop_map(EventPayload::Governance)
+}
+
+// ---- custom and arbitrary JSON ----
+
+/// Arbitrary JSON documents with short-decimal numbers
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        finite().prop_map(Value::from),
+        text().prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            hash_map(text(), inner, 0..4).prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn custom() -> impl Strategy<Value = EventPayload> {
+    (text(), json_value())
+        .prop_map(|(custom_type, data)| EventPayload::Custom(CustomPayload { custom_type, data }))
+}
+
+fn payload() -> impl Strategy<Value = EventPayload> {
+    prop_oneof![telemetry(), security(), cost(), governance(), custom()]
+}
+
+prop_compose! {
+    fn analytics_event()(common in common_fields(), payload in payload()) -> AnalyticsEvent {
+        AnalyticsEvent { common, payload }
+    }
+}
+
+/// Decode like the ingestion paths do
+fn ingest_decode(bytes: &[u8]) -> anyhow::Result<AnalyticsEvent> {
+    let event: AnalyticsEvent = serde_json::from_slice(bytes)?;
+    validate_event(&event)?;
+    Ok(event)
+}
+
+// ============================================================================
+// ROUND TRIP PROPERTIES
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn test_json_roundtrip(event in analytics_event()) {
+        let expected = serde_json::to_value(&event).unwrap();
+        let bytes = serde_json::to_vec(&event).unwrap();
+        let decoded: AnalyticsEvent = serde_json::from_slice(&bytes).unwrap();
+
+        prop_assert_eq!(&decoded.common, &event.common);
+        prop_assert_eq!(serde_json::to_value(&decoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_msgpack_roundtrip(event in analytics_event()) {
+        let expected = serde_json::to_value(&event).unwrap();
+        let bytes = rmp_serde::to_vec_named(&event).unwrap();
+        let decoded: AnalyticsEvent = rmp_serde::from_slice(&bytes).unwrap();
+
+        prop_assert_eq!(&decoded.common, &event.common);
+        prop_assert_eq!(serde_json::to_value(&decoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_generated_events_pass_ingestion(event in analytics_event()) {
+        let bytes = serde_json::to_vec(&event).unwrap();
+        let decoded = ingest_decode(&bytes);
+        prop_assert!(decoded.is_ok(), "rejected: {:?}", decoded.err());
+    }
+}
+
+// ============================================================================
+// INGESTION DECODER FUZZING
+// ============================================================================
+
+/// Ways to corrupt a valid event document
+#[derive(Debug, Clone)]
+enum Corruption {
+    RemoveField(usize),
+    ReplaceField(usize, Value),
+    RemovePayloadField(usize),
+    ReplacePayloadField(usize, Value),
+    Truncate(usize),
+}
+
+fn corruption() -> impl Strategy<Value = Corruption> {
+    prop_oneof![
+        any::<usize>().prop_map(Corruption::RemoveField),
+        (any::<usize>(), json_value()).prop_map(|(i, v)| Corruption::ReplaceField(i, v)),
+        any::<usize>().prop_map(Corruption::RemovePayloadField),
+        (any::<usize>(), json_value()).prop_map(|(i, v)| Corruption::ReplacePayloadField(i, v)),
+        any::<usize>().prop_map(Corruption::Truncate),
+    ]
+}
+
+/// Pick the `index`-th key (modulo the key count) of an object
+fn nth_key(object: &serde_json::Map<String, Value>, index: usize) -> Option<String> {
+    if object.is_empty() {
+        return None;
+    }
+    object.keys().nth(index % object.len()).cloned()
+}
+
+fn corrupt(event: &AnalyticsEvent, corruption: &Corruption) -> Vec<u8> {
+    let mut document = serde_json::to_value(event).unwrap();
+
+    if let Corruption::Truncate(at) = corruption {
+        let bytes = serde_json::to_vec(&document).unwrap();
+        return bytes[..at % bytes.len()].to_vec();
+    }
+
+    let target = match corruption {
+        Corruption::RemovePayloadField(_) | Corruption::ReplacePayloadField(..) => {
+            document.pointer_mut("/payload/data")
+        }
+        _ => Some(&mut document),
+    };
+    if let Some(Value::Object(object)) = target {
+        match corruption {
+            Corruption::RemoveField(i) | Corruption::RemovePayloadField(i) => {
+                if let Some(key) = nth_key(object, *i) {
+                    object.remove(&key);
+                }
+            }
+            Corruption::ReplaceField(i, value) | Corruption::ReplacePayloadField(i, value) => {
+                if let Some(key) = nth_key(object, *i) {
+                    object.insert(key, value.clone());
+                }
+            }
+            Corruption::Truncate(_) => unreachable!(),
+        }
+    }
+    serde_json::to_vec(&document).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn test_decoder_survives_arbitrary_bytes(bytes in vec(any::<u8>(), 0..512)) {
+        let _ = ingest_decode(&bytes);
+    }
+
+    #[test]
+    fn test_decoder_survives_arbitrary_json(document in json_value()) {
+        let bytes = serde_json::to_vec(&document).unwrap();
+        let _ = ingest_decode(&bytes);
+    }
+
+    #[test]
+    fn test_decoder_handles_corrupted_events(
+        event in analytics_event(),
+        corruption in corruption(),
+    ) {
+        let bytes = corrupt(&event, &corruption);
+
+        // Whatever the decoder accepts must re-encode to something it accepts again
+        if let Ok(decoded) = ingest_decode(&bytes) {
+            let reencoded = serde_json::to_vec(&decoded).unwrap();
+            let again = ingest_decode(&reencoded);
+            prop_assert!(again.is_ok(), "re-encoded event rejected: {:?}", again.err());
+        }
+    }
+
+    #[test]
+    fn test_decoder_rejects_foreign_schema_versions(
+        event in analytics_event(),
+        version in "[0-9]\\.[0-9]\\.[0-9]",
+    ) {
+        prop_assume!(version != SCHEMA_VERSION);
+        let mut document = serde_json::to_value(&event).unwrap();
+        document["schema_version"] = Value::String(version);
+
+        let bytes = serde_json::to_vec(&document).unwrap();
+        prop_assert!(ingest_decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_decoder_fills_optional_common_fields(event in analytics_event()) {
+        let mut document = serde_json::to_value(&event).unwrap();
+        let object = document.as_object_mut().unwrap();
+        for key in ["event_id", "schema_version", "tags", "correlation_id", "parent_event_id"] {
+            object.remove(key);
+        }
+
+        let decoded = ingest_decode(&serde_json::to_vec(&document).unwrap()).unwrap();
+        prop_assert_eq!(decoded.common.schema_version, SCHEMA_VERSION);
+        prop_assert!(decoded.common.tags.is_empty());
+        prop_assert!(decoded.common.correlation_id.is_none());
+        prop_assert_ne!(decoded.common.event_id, Uuid::nil());
+    }
+}
+
+#[test]
+fn test_every_payload_variant_is_generated() {
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    let mut runner = TestRunner::deterministic();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for _ in 0..2_000 {
+        let event = analytics_event().new_tree(&mut runner).unwrap().current();
+        let document = serde_json::to_value(&event).unwrap();
+        let family = document["payload"]["payload_type"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let kind = [
+            "telemetry_type",
+            "security_type",
+            "cost_type",
+            "governance_type",
+        ]
+        .iter()
+        .find_map(|tag| document["payload"]["data"][*tag].as_str())
+        .unwrap_or("custom");
+        *seen.entry(format!("{}/{}", family, kind)).or_default() += 1;
+    }
+
+    assert_eq!(seen.len(), 19, "variants generated: {:?}", seen.keys());
+}
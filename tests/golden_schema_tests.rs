@@ -0,0 +1,319 @@
+//! Golden-file Regression Tests for Serialized Schemas
+//!
+//! Serializes representative events, aggregates, API responses, and forecasts and
+//! compares them against the fixtures checked in under `tests/golden/`.
+//!
+//! To regenerate the fixtures after an intentional wire-format change, run:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --test golden_schema_tests
+//! ```
+//!
+//! and review the resulting diff before committing it.
+
+use llm_analytics_hub::adapters::costops::*;
+use llm_analytics_hub::analytics::prediction::PredictionPoint;
+use llm_analytics_hub::models::api::*;
+use llm_analytics_hub::models::metrics::*;
+use llm_analytics_hub::schemas::events::*;
+
+use chrono::{DateTime, TimeZone, Utc};
+use pretty_assertions::assert_eq;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Environment variable that switches the suite into regeneration mode
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+// ============================================================================
+// GOLDEN FILE HARNESS
+// ============================================================================
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.json", name))
+}
+
+fn update_requested() -> bool {
+    std::env::var(UPDATE_ENV)
+        .map(|v| !v.is_empty() && v != "0")
+        .unwrap_or(false)
+}
+
+/// Compare the serialized form of `value` against the named fixture.
+///
+/// The fixture is also deserialized back into `T` and re-serialized, so that
+/// checked-in payloads keep parsing even when only the reader side changes.
+fn assert_golden<T>(name: &str, value: &T)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let path = golden_path(name);
+    let actual = serde_json::to_value(value).expect("failed to serialize value");
+
+    if update_requested() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut pretty = serde_json::to_string_pretty(&actual).unwrap();
+        pretty.push('\n');
+        std::fs::write(&path, pretty).unwrap();
+        return;
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden file {} ({}); run with {}=1 to create it",
+            path.display(),
+            e,
+            UPDATE_ENV
+        )
+    });
+    let expected: serde_json::Value =
+        serde_json::from_str(&contents).expect("golden file is not valid JSON");
+
+    assert_eq!(
+        expected, actual,
+        "serialized output of '{}' differs from {}; if the change is intentional, rerun with {}=1",
+        name,
+        path.display(),
+        UPDATE_ENV
+    );
+
+    let parsed: T = serde_json::from_value(expected.clone())
+        .unwrap_or_else(|e| panic!("golden file '{}' no longer deserializes: {}", name, e));
+    let roundtrip = serde_json::to_value(&parsed).unwrap();
+    assert_eq!(expected, roundtrip, "golden file '{}' does not round-trip", name);
+}
+
+fn fixed_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap()
+}
+
+fn fixed_uuid(n: u128) -> Uuid {
+    Uuid::from_u128(n)
+}
+
+fn common_fields(
+    source_module: SourceModule,
+    event_type: EventType,
+    severity: Severity,
+) -> CommonEventFields {
+    let mut tags = HashMap::new();
+    tags.insert("region".to_string(), "us-east-1".to_string());
+
+    CommonEventFields {
+        event_id: fixed_uuid(1),
+        timestamp: fixed_time(),
+        source_module,
+        event_type,
+        correlation_id: Some(fixed_uuid(2)),
+        parent_event_id: None,
+        schema_version: SCHEMA_VERSION.to_string(),
+        severity,
+        environment: "production".to_string(),
+        tags,
+    }
+}
+
+fn sample_stats() -> StatisticalMeasures {
+    StatisticalMeasures {
+        avg: 450.5,
+        min: 50.0,
+        max: 980.0,
+        p50: 420.0,
+        p95: 850.0,
+        p99: 950.0,
+        stddev: Some(150.5),
+        count: 100,
+        sum: 45050.0,
+        histogram: None,
+    }
+}
+
+fn fixed_meta() -> ResponseMetadata {
+    ResponseMetadata {
+        request_id: fixed_uuid(3),
+        timestamp: fixed_time(),
+        api_version: "1.0.0".to_string(),
+        response_time_ms: Some(12),
+        extra: HashMap::new(),
+    }
+}
+
+// ============================================================================
+// EVENT FIXTURES
+// ============================================================================
+
+#[test]
+fn golden_telemetry_latency_event() {
+    let event = AnalyticsEvent {
+        common: common_fields(
+            SourceModule::LlmObservatory,
+            EventType::Telemetry,
+            Severity::Info,
+        ),
+        payload: EventPayload::Telemetry(TelemetryPayload::Latency(LatencyMetrics {
+            model_id: "gpt-4".to_string(),
+            request_id: "req-001".to_string(),
+            total_latency_ms: 1523.45,
+            ttft_ms: Some(234.5),
+            tokens_per_second: Some(45.2),
+            breakdown: Some(LatencyBreakdown {
+                queue_time_ms: 10.0,
+                processing_time_ms: 1400.0,
+                network_time_ms: 100.0,
+                other_ms: 13.45,
+            }),
+        })),
+    };
+
+    assert_golden("telemetry_latency_event", &event);
+}
+
+#[test]
+fn golden_cost_token_event() {
+    let event = AnalyticsEvent {
+        common: common_fields(SourceModule::LlmCostOps, EventType::Cost, Severity::Info),
+        payload: EventPayload::Cost(CostPayload::TokenCost(TokenCostEvent {
+            model_id: "claude-3-opus".to_string(),
+            request_id: "req-002".to_string(),
+            prompt_tokens: 1000,
+            completion_tokens: 500,
+            total_tokens: 1500,
+            cost_per_prompt_token: 0.000015,
+            cost_per_completion_token: 0.000075,
+            total_cost_usd: 0.0525,
+            currency: "USD".to_string(),
+        })),
+    };
+
+    assert_golden("cost_token_event", &event);
+}
+
+// ============================================================================
+// AGGREGATE FIXTURES
+// ============================================================================
+
+#[test]
+fn golden_aggregated_metric() {
+    let mut tags = HashMap::new();
+    tags.insert("model_id".to_string(), "gpt-4".to_string());
+
+    let metric = AggregatedMetric {
+        name: "request_latency_ms".to_string(),
+        window: TimeWindow::FiveMinutes,
+        window_start: fixed_time(),
+        window_end: Utc.with_ymd_and_hms(2024, 1, 15, 10, 35, 0).unwrap(),
+        values: MetricValues::Stats(sample_stats()),
+        tags,
+    };
+
+    assert_golden("aggregated_metric", &metric);
+}
+
+#[test]
+fn golden_histogram_metric() {
+    let histogram = MetricType::Histogram(HistogramMetric {
+        name: "request_latency_ms".to_string(),
+        stats: sample_stats(),
+        buckets: vec![
+            HistogramBucket {
+                upper_bound: 100.0,
+                count: 50,
+            },
+            HistogramBucket {
+                upper_bound: 500.0,
+                count: 30,
+            },
+            HistogramBucket {
+                upper_bound: 1000.0,
+                count: 20,
+            },
+        ],
+        tags: HashMap::new(),
+        timestamp: fixed_time(),
+    });
+
+    assert_golden("histogram_metric", &histogram);
+}
+
+// ============================================================================
+// API RESPONSE FIXTURES
+// ============================================================================
+
+#[test]
+fn golden_api_success_response() {
+    let response = ApiResponse::success(AggregatedMetric {
+        name: "requests_total".to_string(),
+        window: TimeWindow::OneHour,
+        window_start: fixed_time(),
+        window_end: Utc.with_ymd_and_hms(2024, 1, 15, 11, 30, 0).unwrap(),
+        values: MetricValues::Counter {
+            value: 12345,
+            rate: 3.43,
+        },
+        tags: HashMap::new(),
+    })
+    .with_meta(fixed_meta());
+
+    assert_golden("api_success_response", &response);
+}
+
+#[test]
+fn golden_api_error_response() {
+    let mut error = ApiError::bad_request("Invalid time range");
+    error.timestamp = fixed_time();
+
+    let response: ApiResponse<()> = ApiResponse::error(error).with_meta(fixed_meta());
+
+    assert_golden("api_error_response", &response);
+}
+
+// ============================================================================
+// FORECAST FIXTURES
+// ============================================================================
+
+#[test]
+fn golden_prediction_points() {
+    let points = vec![
+        PredictionPoint {
+            timestamp: fixed_time(),
+            value: 120.5,
+            confidence: 0.95,
+            lower_bound: 110.0,
+            upper_bound: 131.0,
+        },
+        PredictionPoint {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 15, 10, 31, 0).unwrap(),
+            value: 122.25,
+            confidence: 0.9,
+            lower_bound: 108.5,
+            upper_bound: 136.0,
+        },
+    ];
+
+    assert_golden("prediction_points", &points);
+}
+
+#[test]
+fn golden_cost_projection() {
+    let projection = CostProjection {
+        projection_id: "proj-001".to_string(),
+        generated_at: fixed_time(),
+        projection_period: ProjectionPeriod::Monthly,
+        projected_cost_usd: 15234.75,
+        confidence_interval: ConfidenceInterval {
+            lower_bound: 14000.0,
+            upper_bound: 16500.0,
+            confidence_level: 0.95,
+        },
+        trend: CostTrend::Increasing,
+        assumptions: vec!["Traffic grows 5% week over week".to_string()],
+    };
+
+    assert_golden("cost_projection", &projection);
+}